@@ -29,4 +29,8 @@ pub use basic_auth::{BasicAuthCredentials, BasicAuthError};
 pub mod salted_password;
 
 mod server_interceptor;
+pub(crate) use server_interceptor::unauthenticated;
 pub use server_interceptor::ServerAuthenticationInterceptor;
+
+mod scoped_service;
+pub use scoped_service::{RequiredScopeFn, ScopedAuthenticationService};
@@ -0,0 +1,197 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use subtle::{Choice, ConstantTimeEq};
+use tari_common_types::grpc_authentication::{GrpcScope, GrpcScopedToken};
+use tonic::{
+    body::BoxBody,
+    codegen::{
+        http::{self, header::AUTHORIZATION},
+        BoxFuture,
+        Service,
+    },
+    server::NamedService,
+    Status,
+};
+
+use crate::authentication::{server_interceptor::unauthenticated, ServerAuthenticationInterceptor};
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// Maps the name of a gRPC method to the scope a scoped token needs to call it, or `None` if the method requires full
+/// access
+pub type RequiredScopeFn = fn(&str) -> Option<GrpcScope>;
+
+/// Wraps a gRPC service so that requests can be authorized with scoped bearer tokens as well as the configured server
+/// authentication. A scoped token can only call the methods for which `required_scope` returns one of its scopes.
+#[derive(Clone)]
+pub struct ScopedAuthenticationService<S> {
+    inner: S,
+    authentication: ServerAuthenticationInterceptor,
+    tokens: Arc<Vec<GrpcScopedToken>>,
+    required_scope: RequiredScopeFn,
+}
+
+impl<S> ScopedAuthenticationService<S> {
+    pub fn new(
+        inner: S,
+        authentication: ServerAuthenticationInterceptor,
+        tokens: Vec<GrpcScopedToken>,
+        required_scope: RequiredScopeFn,
+    ) -> Self {
+        Self {
+            inner,
+            authentication,
+            tokens: Arc::new(tokens),
+            required_scope,
+        }
+    }
+
+    fn authorize<B>(&self, request: &http::Request<B>) -> Result<(), Status> {
+        let authorization = match request.headers().get(AUTHORIZATION) {
+            Some(value) => Some(value.to_str().map_err(unauthenticated)?),
+            None => None,
+        };
+        let bearer_token = match authorization.and_then(|header| header.strip_prefix(BEARER_PREFIX)) {
+            Some(token) => token,
+            None => return self.authentication.authenticate(authorization),
+        };
+
+        let scoped_token = self
+            .find_token(bearer_token.as_bytes())
+            .ok_or_else(|| unauthenticated("Invalid bearer token"))?;
+        let method = request.uri().path().rsplit('/').next().unwrap_or_default();
+        match (self.required_scope)(method) {
+            Some(scope) if scoped_token.has_scope(scope) => Ok(()),
+            _ => Err(Status::permission_denied(format!(
+                "Token is not authorized to call {}",
+                method
+            ))),
+        }
+    }
+
+    /// Find the configured token matching `token`, comparing against every configured token in constant time
+    fn find_token(&self, token: &[u8]) -> Option<&GrpcScopedToken> {
+        let mut found = None;
+        for scoped_token in self.tokens.iter() {
+            let is_match: Choice = scoped_token.token.reveal().ct_eq(token);
+            if bool::from(is_match) {
+                found = Some(scoped_token);
+            }
+        }
+        found
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for ScopedAuthenticationService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        match self.authorize(&request) {
+            Ok(()) => Box::pin(self.inner.call(request)),
+            Err(status) => {
+                let response = status.to_http();
+                Box::pin(async move { Ok(response) })
+            },
+        }
+    }
+}
+
+impl<S: NamedService> NamedService for ScopedAuthenticationService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::grpc_authentication::GrpcAuthentication;
+    use tari_utilities::SafePassword;
+    use tonic::Code;
+
+    use super::*;
+
+    fn required_scope(method: &str) -> Option<GrpcScope> {
+        match method {
+            "GetBalance" => Some(GrpcScope::ReadBalance),
+            "Transfer" => Some(GrpcScope::SendFunds),
+            _ => None,
+        }
+    }
+
+    fn request(method: &str, authorization: Option<&str>) -> http::Request<()> {
+        let mut builder = http::Request::builder().uri(format!("/tari.rpc.Wallet/{}", method));
+        if let Some(authorization) = authorization {
+            builder = builder.header(AUTHORIZATION, authorization);
+        }
+        builder.body(()).unwrap()
+    }
+
+    #[test]
+    fn it_only_allows_scoped_tokens_to_call_methods_in_scope() {
+        let auth = GrpcAuthentication::Basic {
+            username: "admin".to_string(),
+            password: SafePassword::from("secret"),
+        };
+        let service = ScopedAuthenticationService::new(
+            (),
+            ServerAuthenticationInterceptor::new(auth).unwrap(),
+            vec![GrpcScopedToken {
+                token: SafePassword::from("pos-terminal"),
+                scopes: vec![GrpcScope::ReadBalance],
+            }],
+            required_scope,
+        );
+
+        service
+            .authorize(&request("GetBalance", Some("Bearer pos-terminal")))
+            .unwrap();
+        let err = service
+            .authorize(&request("Transfer", Some("Bearer pos-terminal")))
+            .unwrap_err();
+        assert_eq!(err.code(), Code::PermissionDenied);
+        let err = service
+            .authorize(&request("CoinSplit", Some("Bearer pos-terminal")))
+            .unwrap_err();
+        assert_eq!(err.code(), Code::PermissionDenied);
+        let err = service
+            .authorize(&request("GetBalance", Some("Bearer pos-terminal-2")))
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+        let err = service.authorize(&request("GetBalance", None)).unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+    }
+}
@@ -50,14 +50,13 @@ impl ServerAuthenticationInterceptor {
 
     fn handle_basic_auth(
         &self,
-        req: Request<()>,
+        authorization: Option<&str>,
         valid_username: &str,
         valid_phc_password: &SafePassword,
-    ) -> Result<Request<()>, Status> {
-        match req.metadata().get(AUTHORIZATION.as_str()) {
-            Some(t) => {
+    ) -> Result<(), Status> {
+        match authorization {
+            Some(header) => {
                 // Parse the provided header
-                let header = t.to_str().map_err(unauthenticated)?;
                 let (header_username, header_password) =
                     BasicAuthCredentials::parse_header(header).map_err(unauthenticated)?;
 
@@ -69,22 +68,36 @@ impl ServerAuthenticationInterceptor {
                     .constant_time_validate(&header_username, &header_password)
                     .map_err(unauthenticated)?;
 
-                Ok(req)
+                Ok(())
             },
             _ => Err(unauthenticated("Missing authorization header")),
         }
     }
-}
 
-impl Interceptor for ServerAuthenticationInterceptor {
-    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+    /// Check the value of an authorization header, if any, against the configured authentication
+    pub(crate) fn authenticate(&self, authorization: Option<&str>) -> Result<(), Status> {
         match &self.auth {
-            GrpcAuthentication::None => Ok(request),
+            GrpcAuthentication::None => Ok(()),
             GrpcAuthentication::Basic {
                 username,
                 password: phc_password,
-            } => self.handle_basic_auth(request, username, phc_password),
+            } => self.handle_basic_auth(authorization, username, phc_password),
+        }
+    }
+}
+
+impl Interceptor for ServerAuthenticationInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        if let GrpcAuthentication::None = self.auth {
+            return Ok(request);
         }
+        let authorization = request
+            .metadata()
+            .get(AUTHORIZATION.as_str())
+            .map(|t| t.to_str().map_err(unauthenticated))
+            .transpose()?;
+        self.authenticate(authorization)?;
+        Ok(request)
     }
 }
 
@@ -97,7 +110,7 @@ impl Clone for ServerAuthenticationInterceptor {
 }
 
 /// Standard unauthenticated response
-fn unauthenticated<E: ToString>(err: E) -> Status {
+pub(crate) fn unauthenticated<E: ToString>(err: E) -> Status {
     warn!(target: LOG_TARGET, "GRPC authentication failed: {}", err.to_string());
     Status::unauthenticated("Auth failed")
 }
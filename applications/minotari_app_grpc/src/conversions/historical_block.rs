@@ -33,6 +33,7 @@ impl TryFrom<HistoricalBlock> for grpc::HistoricalBlock {
         Ok(Self {
             confirmations: hb.confirmations(),
             block: Some(hb.into_block().try_into().map_err(ChainStorageError::ConversionError)?),
+            cursor: Vec::new(),
         })
     }
 }
@@ -20,7 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use tari_comms::{connectivity::ConnectivityStatus, net_address::MultiaddrWithStats, peer_manager::Peer};
+use std::{convert::TryFrom, time::Duration};
+
+use tari_comms::{
+    bandwidth::{NetworkUsage, ServiceUsage},
+    connectivity::{ConnectivityStatus, PeerQualityMetrics},
+    net_address::MultiaddrWithStats,
+    peer_manager::{NodeId, OffenseEntry, Peer, PeerOffenseRecord},
+};
 use tari_utilities::ByteArray;
 
 use crate::tari_rpc as grpc;
@@ -82,6 +89,69 @@ impl From<MultiaddrWithStats> for grpc::Address {
     }
 }
 
+impl From<(NodeId, PeerQualityMetrics)> for grpc::ConnectedPeerMetrics {
+    fn from((node_id, metrics): (NodeId, PeerQualityMetrics)) -> Self {
+        let as_millis = |rtt: Option<Duration>| rtt.map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+        Self {
+            node_id: node_id.to_vec(),
+            rtt_ms: as_millis(metrics.smoothed_rtt).unwrap_or_default(),
+            last_rtt_ms: as_millis(metrics.last_rtt).unwrap_or_default(),
+            messages_sent: metrics.messages_sent,
+            messages_failed: metrics.messages_failed,
+            bytes_sent: metrics.bytes_sent,
+            bytes_received: metrics.bytes_received,
+        }
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+impl From<OffenseEntry> for grpc::PeerOffense {
+    fn from(entry: OffenseEntry) -> Self {
+        Self {
+            offense: entry.offense.to_string(),
+            reason: entry.reason,
+            score: entry.score,
+            committed_at: entry.committed_at.timestamp() as u64,
+        }
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+impl From<PeerOffenseRecord> for grpc::PeerOffenseRecord {
+    fn from(record: PeerOffenseRecord) -> Self {
+        Self {
+            node_id: record.node_id.to_vec(),
+            score: record.score,
+            num_bans: record.num_bans,
+            last_banned_at: record.last_banned_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
+            offenses: record.offenses.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ServiceUsage> for grpc::ServiceNetworkUsage {
+    fn from(usage: ServiceUsage) -> Self {
+        Self {
+            service: usage.service,
+            total_bytes_sent: usage.total_bytes_sent,
+            total_bytes_received: usage.total_bytes_received,
+            period_bytes_sent: usage.period_bytes_sent,
+            period_bytes_received: usage.period_bytes_received,
+            quota: usage.quota.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<NetworkUsage> for grpc::NetworkUsageResponse {
+    fn from(usage: NetworkUsage) -> Self {
+        Self {
+            accounting_period: usage.period.as_secs(),
+            period_elapsed: usage.period_elapsed.as_secs(),
+            services: usage.services.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl From<ConnectivityStatus> for grpc::ConnectivityStatus {
     fn from(status: ConnectivityStatus) -> Self {
         use ConnectivityStatus::{Degraded, Initializing, Offline, Online};
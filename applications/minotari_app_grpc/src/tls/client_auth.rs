@@ -0,0 +1,184 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use sha2::{Digest, Sha256};
+use tari_common_types::grpc_authentication::GrpcClientCertificate;
+use tari_utilities::hex::to_hex;
+use tonic::{
+    body::BoxBody,
+    codegen::{http, BoxFuture, Service},
+    server::NamedService,
+    transport::{
+        server::{TcpConnectInfo, TlsConnectInfo},
+        Certificate,
+        Identity,
+        ServerTlsConfig,
+    },
+    Status,
+};
+
+use crate::authentication::unauthenticated;
+
+/// Returns the TLS configuration of a gRPC server. If a client CA certificate is given, clients must present a
+/// certificate signed by the CA.
+pub fn server_tls_config(identity: Identity, client_ca: Option<Certificate>) -> ServerTlsConfig {
+    let tls_config = ServerTlsConfig::new().identity(identity);
+    match client_ca {
+        Some(client_ca) => tls_config.client_ca_root(client_ca),
+        None => tls_config,
+    }
+}
+
+/// Restricts the gRPC methods that each TLS client certificate may call. If no client certificates are configured,
+/// all requests are passed on.
+#[derive(Clone)]
+pub struct ClientCertificateService<S> {
+    inner: S,
+    certificates: Arc<Vec<(String, GrpcClientCertificate)>>,
+}
+
+impl<S> ClientCertificateService<S> {
+    pub fn new(inner: S, certificates: Vec<GrpcClientCertificate>) -> Self {
+        Self {
+            inner,
+            certificates: Arc::new(
+                certificates
+                    .into_iter()
+                    .map(|cert| (cert.normalized_fingerprint(), cert))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn authorize<B>(&self, request: &http::Request<B>) -> Result<(), Status> {
+        if self.certificates.is_empty() {
+            return Ok(());
+        }
+        let peer_cert = request
+            .extensions()
+            .get::<TlsConnectInfo<TcpConnectInfo>>()
+            .and_then(|info| info.peer_certs())
+            .and_then(|certs| certs.first().cloned())
+            .ok_or_else(|| unauthenticated("Missing TLS client certificate"))?;
+        let fingerprint = to_hex(&Sha256::digest(peer_cert.get_ref()));
+        let (_, certificate) = self
+            .certificates
+            .iter()
+            .find(|(f, _)| *f == fingerprint)
+            .ok_or_else(|| unauthenticated(format!("Unknown TLS client certificate {}", fingerprint)))?;
+
+        let method = request.uri().path().rsplit('/').next().unwrap_or_default();
+        let method = to_snake_case(method);
+        if certificate.is_method_allowed(&method) {
+            Ok(())
+        } else {
+            Err(Status::permission_denied(format!(
+                "Client certificate is not authorized to call {}",
+                method
+            )))
+        }
+    }
+}
+
+/// Converts a gRPC method name e.g. `GetBalance` to the snake case name used in configuration e.g. `get_balance`
+fn to_snake_case(method: &str) -> String {
+    let mut snake_case = String::with_capacity(method.len() + 4);
+    for (i, c) in method.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake_case.push('_');
+            }
+            snake_case.push(c.to_ascii_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+    }
+    snake_case
+}
+
+impl<S, B> Service<http::Request<B>> for ClientCertificateService<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    type Response = S::Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        match self.authorize(&request) {
+            Ok(()) => Box::pin(self.inner.call(request)),
+            Err(status) => {
+                let response = status.to_http();
+                Box::pin(async move { Ok(response) })
+            },
+        }
+    }
+}
+
+impl<S: NamedService> NamedService for ClientCertificateService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod test {
+    use tonic::Code;
+
+    use super::*;
+
+    #[test]
+    fn it_converts_method_names_to_snake_case() {
+        assert_eq!(to_snake_case("GetBalance"), "get_balance");
+        assert_eq!(to_snake_case("Identify"), "identify");
+        assert_eq!(
+            to_snake_case("GetNewBlockTemplateWithCoinbases"),
+            "get_new_block_template_with_coinbases"
+        );
+    }
+
+    #[test]
+    fn it_rejects_requests_without_a_client_certificate() {
+        let request = http::Request::builder()
+            .uri("/tari.rpc.BaseNode/GetTipInfo")
+            .body(())
+            .unwrap();
+        ClientCertificateService::new((), vec![]).authorize(&request).unwrap();
+
+        let service = ClientCertificateService::new((), vec![GrpcClientCertificate {
+            fingerprint: "AB:CD".to_string(),
+            allow_methods: vec!["get_tip_info".to_string()],
+        }]);
+        let err = service.authorize(&request).unwrap_err();
+        assert_eq!(err.code(), Code::Unauthenticated);
+        assert_eq!(service.certificates[0].0, "abcd");
+    }
+}
@@ -22,7 +22,7 @@
 
 use std::path::PathBuf;
 
-use tonic::transport::Identity;
+use tonic::transport::{Certificate, Identity};
 
 use crate::tls::error::GrpcTlsError;
 
@@ -37,3 +37,13 @@ pub async fn read_identity(config_dir: PathBuf) -> Result<Identity, GrpcTlsError
 
     Ok(Identity::from_pem(cert, key))
 }
+
+/// Reads the CA certificate that TLS client certificates must be signed by
+pub async fn read_client_ca(config_dir: PathBuf) -> Result<Certificate, GrpcTlsError> {
+    let ca_file = config_dir.join("client_ca.pem");
+    let ca = tokio::fs::read(&ca_file)
+        .await
+        .map_err(|e| GrpcTlsError::FileReadError(format!("Could not load the file `{:?}`: {}", ca_file, e)))?;
+
+    Ok(Certificate::from_pem(ca))
+}
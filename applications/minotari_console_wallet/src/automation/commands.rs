@@ -36,6 +36,7 @@ use futures::FutureExt;
 use log::*;
 use minotari_app_grpc::tls::certs::{generate_self_signed_certs, print_warning, write_cert_to_disk};
 use minotari_wallet::{
+    account::models::DEFAULT_ACCOUNT_INDEX,
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
     transaction_service::{
@@ -63,6 +64,8 @@ use tari_comms::{
 };
 use tari_comms_dht::{envelope::NodeDestination, DhtDiscoveryRequester};
 use tari_core::transactions::{
+    key_manager::TransactionKeyManagerInterface,
+    offline_signing::{PaymentDetails, SignedTransaction, TransactionSigningRequest},
     tari_amount::{uT, MicroMinotari, Minotari},
     transaction_components::{OutputFeatures, TransactionOutput, WalletOutput},
 };
@@ -95,6 +98,11 @@ pub enum WalletCommand {
     ExportUtxos,
     ExportTx,
     ImportTx,
+    ExportTransactionHistory,
+    ImportTransactionHistory,
+    CreateUnsigned,
+    SignOffline,
+    BroadcastSigned,
     ExportSpentUtxos,
     CountUtxos,
     SetBaseNode,
@@ -177,7 +185,7 @@ pub async fn finalise_sha_atomic_swap(
     message: String,
 ) -> Result<TxId, CommandError> {
     let (tx_id, _fee, amount, tx) = output_service
-        .create_claim_sha_atomic_swap_transaction(output_hash, pre_image, fee_per_gram)
+        .create_claim_sha_atomic_swap_transaction(output_hash, pre_image, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
         .await?;
     transaction_service
         .submit_transaction(tx_id, tx, amount, message)
@@ -194,7 +202,7 @@ pub async fn claim_htlc_refund(
     message: String,
 ) -> Result<TxId, CommandError> {
     let (tx_id, _fee, amount, tx) = output_service
-        .create_htlc_refund_transaction(output_hash, fee_per_gram)
+        .create_htlc_refund_transaction(output_hash, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
         .await?;
     transaction_service
         .submit_transaction(tx_id, tx, amount, message)
@@ -241,6 +249,8 @@ pub async fn send_one_sided(
             OutputFeatures::default(),
             fee_per_gram * uT,
             message,
+            Vec::new(),
+            Vec::new(),
         )
         .await
         .map_err(CommandError::TransactionServiceError)
@@ -262,11 +272,60 @@ pub async fn send_one_sided_to_stealth_address(
             OutputFeatures::default(),
             fee_per_gram * uT,
             message,
+            Vec::new(),
+            Vec::new(),
         )
         .await
         .map_err(CommandError::TransactionServiceError)
 }
 
+/// Builds an unsigned one-sided transaction and writes it to a file to be carried to an offline signer. The selected
+/// inputs stay encumbered until the signed transaction is broadcast or the transaction is cancelled.
+pub async fn create_unsigned_transaction(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    fee_per_gram: u64,
+    amount: MicroMinotari,
+    dest_address: TariAddress,
+    message: String,
+    output_file: &Path,
+) -> Result<TxId, CommandError> {
+    let request = wallet_transaction_service
+        .prepare_one_sided_transaction_for_signing(
+            dest_address,
+            amount,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            fee_per_gram * uT,
+            message,
+            Vec::new(),
+        )
+        .await?;
+    fs::write(output_file, request.to_json()?)?;
+    Ok(request.payment.tx_id)
+}
+
+/// Signs an unsigned transaction read from a file and writes the signed transaction to another file. This does not
+/// need a connection to the network, so it can be run on an air-gapped wallet that holds the same seed.
+pub async fn sign_offline<KM: TransactionKeyManagerInterface>(
+    key_manager: &KM,
+    input_file: &Path,
+    output_file: &Path,
+) -> Result<PaymentDetails, CommandError> {
+    let request = TransactionSigningRequest::from_json(&fs::read_to_string(input_file)?)?;
+    let signed = request.sign(key_manager).await?;
+    fs::write(output_file, signed.to_json()?)?;
+    Ok(signed.payment)
+}
+
+/// Records and broadcasts a transaction signed by an offline signer
+pub async fn broadcast_signed_transaction(
+    mut wallet_transaction_service: TransactionServiceHandle,
+    input_file: &Path,
+) -> Result<TxId, CommandError> {
+    let signed = SignedTransaction::from_json(&fs::read_to_string(input_file)?)?;
+    Ok(wallet_transaction_service.submit_signed_transaction(signed).await?)
+}
+
 pub async fn coin_split(
     amount_per_split: MicroMinotari,
     num_splits: usize,
@@ -276,7 +335,13 @@ pub async fn coin_split(
     transaction_service: &mut TransactionServiceHandle,
 ) -> Result<TxId, CommandError> {
     let (tx_id, tx, amount) = output_service
-        .create_coin_split(vec![], amount_per_split, num_splits, fee_per_gram)
+        .create_coin_split(
+            vec![],
+            amount_per_split,
+            num_splits,
+            fee_per_gram,
+            DEFAULT_ACCOUNT_INDEX,
+        )
         .await?;
     transaction_service
         .submit_transaction(tx_id, tx, amount, message)
@@ -633,7 +698,7 @@ pub async fn monitor_transactions(
 pub async fn command_runner(
     config: &WalletConfig,
     commands: Vec<CliCommands>,
-    wallet: WalletSqlite,
+    mut wallet: WalletSqlite,
 ) -> Result<(), CommandError> {
     let wait_stage = config.command_send_wait_stage;
 
@@ -849,6 +914,68 @@ pub async fn command_runner(
                     Err(e) => eprintln!("ImportTx error! {}", e),
                 };
             },
+            ExportTransactionHistory(args) => match wallet.export_transaction_history(args.format).await {
+                Ok(data) => {
+                    if let Some(file) = args.output_file {
+                        if let Err(e) = fs::write(file, data) {
+                            eprintln!("ExportTransactionHistory error! {}", e);
+                        }
+                    } else {
+                        println!("{}", data);
+                    }
+                },
+                Err(e) => eprintln!("ExportTransactionHistory error! {}", e),
+            },
+            ImportTransactionHistory(args) => match fs::read_to_string(args.input_file) {
+                Ok(data) => match wallet.import_transaction_history(&data, args.format).await {
+                    Ok(import) => {
+                        println!("Restored {} transaction messages", import.num_messages_restored);
+                        println!("Restored {} contacts", import.num_contacts_restored);
+                    },
+                    Err(e) => eprintln!("ImportTransactionHistory error! {}", e),
+                },
+                Err(e) => eprintln!("ImportTransactionHistory error! {}", e),
+            },
+            CreateUnsigned(args) => {
+                match create_unsigned_transaction(
+                    transaction_service.clone(),
+                    config.fee_per_gram,
+                    args.amount,
+                    args.destination,
+                    args.message,
+                    &args.output_file,
+                )
+                .await
+                {
+                    Ok(tx_id) => println!(
+                        "Unsigned transaction {} written to {}",
+                        tx_id,
+                        args.output_file.display()
+                    ),
+                    Err(e) => eprintln!("CreateUnsigned error! {}", e),
+                }
+            },
+            SignOffline(args) => {
+                match sign_offline(&wallet.key_manager_service, &args.input_file, &args.output_file).await {
+                    Ok(payment) => {
+                        println!("Signed transaction {}", payment.tx_id);
+                        println!("Destination: {}", payment.destination);
+                        println!("Amount: {}", payment.amount);
+                        println!("Fee: {}", payment.fee);
+                        println!("Signed transaction written to {}", args.output_file.display());
+                    },
+                    Err(e) => eprintln!("SignOffline error! {}", e),
+                }
+            },
+            BroadcastSigned(args) => {
+                match broadcast_signed_transaction(transaction_service.clone(), &args.input_file).await {
+                    Ok(tx_id) => {
+                        debug!(target: LOG_TARGET, "broadcast-signed concluded with tx_id {}", tx_id);
+                        tx_ids.push(tx_id);
+                    },
+                    Err(e) => eprintln!("BroadcastSigned error! {}", e),
+                }
+            },
             ExportSpentUtxos(args) => match output_service.get_spent_outputs().await {
                 Ok(utxos) => {
                     let utxos: Vec<(WalletOutput, Commitment)> =
@@ -1049,6 +1176,7 @@ pub async fn command_runner(
                 },
                 Err(err) => eprintln!("Error generating certificates: {}", err),
             },
+            Db(_) => eprintln!("Database commands must be run on their own, before the wallet is started."),
         }
     }
 
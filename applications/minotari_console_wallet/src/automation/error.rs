@@ -34,7 +34,11 @@ use minotari_wallet::{
 };
 use tari_common::exit_codes::{ExitCode, ExitError};
 use tari_common_types::types::FixedHashSizeError;
-use tari_core::transactions::{tari_amount::MicroMinotariError, transaction_components::TransactionError};
+use tari_core::transactions::{
+    offline_signing::OfflineSigningError,
+    tari_amount::MicroMinotariError,
+    transaction_components::TransactionError,
+};
 use tari_key_manager::key_manager_service::KeyManagerServiceError;
 use tari_utilities::{hex::HexError, ByteArrayError};
 use thiserror::Error;
@@ -59,6 +63,8 @@ pub enum CommandError {
     OutputManagerError(#[from] OutputManagerError),
     #[error("Key manager error: `{0}`")]
     KeyManagerError(#[from] KeyManagerServiceError),
+    #[error("Offline signing error: `{0}`")]
+    OfflineSigningError(#[from] OfflineSigningError),
     #[error("Tokio join error `{0}`")]
     Join(#[from] JoinError),
     #[error("Config error `{0}`")]
@@ -29,6 +29,7 @@ use std::{
 use chrono::{DateTime, Utc};
 use clap::{Args, Parser, Subcommand};
 use minotari_app_utilities::{common_cli_args::CommonCliArgs, utilities::UniPublicKey};
+use minotari_wallet::transaction_service::history::TransactionHistoryFormat;
 use tari_common::configuration::{ConfigOverrideProvider, Network};
 use tari_common_types::tari_address::TariAddress;
 use tari_comms::multiaddr::Multiaddr;
@@ -125,6 +126,11 @@ pub enum CliCommands {
     ExportUtxos(ExportUtxosArgs),
     ExportTx(ExportTxArgs),
     ImportTx(ImportTxArgs),
+    ExportTransactionHistory(ExportTransactionHistoryArgs),
+    ImportTransactionHistory(ImportTransactionHistoryArgs),
+    CreateUnsigned(CreateUnsignedArgs),
+    SignOffline(SignOfflineArgs),
+    BroadcastSigned(BroadcastSignedArgs),
     ExportSpentUtxos(ExportUtxosArgs),
     CountUtxos,
     SetBaseNode(SetBaseNodeArgs),
@@ -136,6 +142,7 @@ pub enum CliCommands {
     RevalidateWalletDb,
     RegisterValidatorNode(RegisterValidatorNodeArgs),
     CreateTlsCerts,
+    Db(DbArgs),
 }
 
 #[derive(Debug, Args, Clone)]
@@ -256,6 +263,70 @@ pub struct ImportTxArgs {
     pub input_file: PathBuf,
 }
 
+#[derive(Debug, Args, Clone)]
+pub struct ExportTransactionHistoryArgs {
+    #[clap(short, long, default_value = "csv")]
+    pub format: TransactionHistoryFormat,
+    #[clap(short, long)]
+    pub output_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct ImportTransactionHistoryArgs {
+    #[clap(short, long, default_value = "csv")]
+    pub format: TransactionHistoryFormat,
+    #[clap(short, long)]
+    pub input_file: PathBuf,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CreateUnsignedArgs {
+    pub amount: MicroMinotari,
+    pub destination: TariAddress,
+    #[clap(short, long, default_value = "<No message>")]
+    pub message: String,
+    #[clap(short, long)]
+    pub output_file: PathBuf,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SignOfflineArgs {
+    #[clap(short, long)]
+    pub input_file: PathBuf,
+    #[clap(short, long)]
+    pub output_file: PathBuf,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct BroadcastSignedArgs {
+    #[clap(short, long)]
+    pub input_file: PathBuf,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct DbArgs {
+    #[clap(subcommand)]
+    pub command: DbCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum DbCommand {
+    /// Rotate the database encryption key and change the passphrase, then exit. Run it again with the same existing
+    /// passphrase if it is interrupted. The wallet must not be running, since the rekey needs exclusive access to the
+    /// database.
+    Rekey(DbRekeyArgs),
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct DbRekeyArgs {
+    /// The number of rows re-encrypted per database transaction
+    #[clap(long, default_value_t = 1000)]
+    pub batch_size: usize,
+    /// Keep the existing passphrase and only rotate the encryption key
+    #[clap(long)]
+    pub keep_passphrase: bool,
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct SetBaseNodeArgs {
     pub public_key: UniPublicKey,
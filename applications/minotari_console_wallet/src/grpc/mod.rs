@@ -9,6 +9,7 @@ use minotari_wallet::transaction_service::storage::models::{
     InboundTransaction,
     OutboundTransaction,
 };
+use tari_common_types::grpc_authentication::GrpcScope;
 
 pub use self::wallet_grpc_server::*;
 
@@ -18,6 +19,26 @@ pub enum TransactionWrapper {
     Inbound(Box<InboundTransaction>),
 }
 
+/// The scope a scoped token needs to call a wallet gRPC method. Methods not listed here can only be called with full
+/// access.
+pub fn required_scope(method: &str) -> Option<GrpcScope> {
+    match method {
+        "GetVersion" |
+        "Identify" |
+        "GetBalance" |
+        "GetUnspentAmounts" |
+        "GetTransactionInfo" |
+        "GetTransactionByPaymentRef" |
+        "GetCompletedTransactions" |
+        "CreateBalanceProof" |
+        "StreamTransactionEvents" |
+        "StreamContactStatus" => Some(GrpcScope::ReadBalance),
+        "GetAddress" | "GetSubaddress" | "CreatePaymentRequest" => Some(GrpcScope::CreateInvoice),
+        "Transfer" | "SendToMany" | "PayPaymentRequest" => Some(GrpcScope::SendFunds),
+        _ => None,
+    }
+}
+
 pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -> TransactionEvent {
     match source {
         TransactionWrapper::Completed(completed) => TransactionEvent {
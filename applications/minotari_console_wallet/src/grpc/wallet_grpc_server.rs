@@ -20,18 +20,27 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::convert::{TryFrom, TryInto};
+use std::{
+    convert::{TryFrom, TryInto},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use futures::{
     channel::mpsc::{self, Sender},
     future,
+    Future,
     SinkExt,
 };
 use log::*;
 use minotari_app_grpc::tari_rpc::{
     self,
+    create_covenant_request,
     payment_recipient::PaymentType,
     wallet_server,
+    AcceptAtomicSwapRequest,
+    AtomicSwapResponse,
+    ChatMessage,
     CheckConnectivityResponse,
     ClaimHtlcRefundRequest,
     ClaimHtlcRefundResponse,
@@ -40,33 +49,64 @@ use minotari_app_grpc::tari_rpc::{
     CoinSplitRequest,
     CoinSplitResponse,
     CommitmentSignature,
+    ContactStatusRequest,
+    ContactStatusResponse,
+    CreateAccountRequest,
+    CreateAccountResponse,
     CreateBurnTransactionRequest,
     CreateBurnTransactionResponse,
+    CreateCovenantRequest,
+    CreateCovenantResponse,
     CreateTemplateRegistrationRequest,
     CreateTemplateRegistrationResponse,
+    GetAccountsRequest,
+    GetAccountsResponse,
     GetAddressResponse,
+    GetAtomicSwapsRequest,
+    GetAtomicSwapsResponse,
     GetBalanceRequest,
     GetBalanceResponse,
+    GetChatMessagesRequest,
+    GetChatMessagesResponse,
     GetCompletedTransactionsRequest,
     GetCompletedTransactionsResponse,
     GetConnectivityRequest,
     GetIdentityRequest,
     GetIdentityResponse,
+    GetTransactionByPaymentRefRequest,
+    GetTransactionByPaymentRefResponse,
     GetTransactionInfoRequest,
     GetTransactionInfoResponse,
     GetUnspentAmountsResponse,
     GetVersionRequest,
     GetVersionResponse,
+    ImportUtxoResult,
     ImportUtxosRequest,
     ImportUtxosResponse,
+    InitiateAtomicSwapRequest,
+    MarkChatMessageReadRequest,
+    MarkChatMessageReadResponse,
+    PreviewCoinSelectionRequest,
+    PreviewCoinSelectionResponse,
+    RedeemAtomicSwapRequest,
+    RefundAtomicSwapRequest,
     RegisterValidatorNodeRequest,
     RegisterValidatorNodeResponse,
     RevalidateRequest,
     RevalidateResponse,
+    ScanWithViewKeyRequest,
+    ScanWithViewKeyResponse,
+    SelectUtxosRequest,
+    SelectUtxosResponse,
+    SendChatMessageRequest,
+    SendChatMessageResponse,
     SendShaAtomicSwapRequest,
     SendShaAtomicSwapResponse,
     SetBaseNodeRequest,
     SetBaseNodeResponse,
+    SetUtxoSpendingPriorityRequest,
+    SetUtxoSpendingPriorityResponse,
+    SpendableUtxo,
     TransactionDirection,
     TransactionEvent,
     TransactionEventRequest,
@@ -80,23 +120,53 @@ use minotari_app_grpc::tari_rpc::{
     ValidateResponse,
 };
 use minotari_wallet::{
+    account::{
+        error::AccountError,
+        models::{WalletAccount, DEFAULT_ACCOUNT_INDEX, DEFAULT_ACCOUNT_NAME},
+        AccountManager,
+    },
+    atomic_swap::{
+        error::AtomicSwapError,
+        models::{AtomicSwap, CounterpartyChain, SwapRole},
+        AtomicSwapManager,
+    },
     connectivity_service::{OnlineStatus, WalletConnectivityInterface},
-    error::WalletStorageError,
-    output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
+    error::{WalletError, WalletStorageError},
+    output_manager_service::{
+        error::OutputManagerError,
+        handle::OutputManagerHandle,
+        service::Balance,
+        storage::models::SpendingPriority,
+        BalanceBreakdown,
+        UtxoSelectionCriteria,
+        UtxoSelectionMode,
+        UtxoSelectionOrdering,
+    },
+    storage::sqlite_db::wallet::WalletSqliteDatabase,
     transaction_service::{
-        handle::TransactionServiceHandle,
+        handle::{BatchRecipient, BatchRecipientStatus, TransactionServiceHandle},
+        history::TransactionHistoryFormat,
+        payment_request::PaymentRequest,
         storage::models::{self, WalletTransaction},
     },
+    utxo_scanner_service::view_key_scanner::{ViewKeyScannedOutput, ViewKeyScanner},
+    WalletKeyManagerSqlite,
     WalletSqlite,
 };
 use tari_common_types::{
     tari_address::TariAddress,
     transaction::TxId,
-    types::{BlockHash, PublicKey, Signature},
+    types::{BlockHash, Commitment, FixedHash, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::{multiaddr::Multiaddr, types::CommsPublicKey, CommsNode};
+use tari_contacts::contacts_service::{
+    handle::ContactsLivenessEvent,
+    service::ContactOnlineStatus,
+    types::{Direction, Message, MessageBuilder},
+};
 use tari_core::{
     consensus::{ConsensusBuilderError, ConsensusConstants, ConsensusManager},
+    covenants::CovenantBuilder,
     transactions::{
         tari_amount::{MicroMinotari, T},
         transaction_components::{
@@ -154,6 +224,47 @@ impl WalletGrpcServer {
         self.wallet.output_manager_service.clone()
     }
 
+    fn get_atomic_swap_manager(&self) -> AtomicSwapManager<WalletSqliteDatabase> {
+        let wallet_address = TariAddress::new(
+            self.wallet.comms.node_identity().public_key().clone(),
+            self.wallet.network.as_network(),
+        );
+        AtomicSwapManager::new(
+            self.wallet.db.clone(),
+            self.get_transaction_service(),
+            self.get_output_manager_service(),
+            wallet_address,
+        )
+    }
+
+    /// Returns a task that keeps the state of the atomic swaps up to date as new blocks are detected
+    pub fn atomic_swap_updates(&self) -> impl Future<Output = ()> {
+        self.get_atomic_swap_manager()
+            .run_updates(self.wallet.base_node_service.get_event_stream())
+    }
+
+    fn get_chain_tip_height(&self) -> Result<u64, Status> {
+        self.wallet
+            .db
+            .get_chain_metadata()
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map(|m| m.best_block_height())
+            .ok_or_else(|| Status::unavailable("The chain tip is not known yet"))
+    }
+
+    fn get_account_manager(&self) -> AccountManager<WalletSqliteDatabase, WalletKeyManagerSqlite> {
+        let wallet_address = TariAddress::new(
+            self.wallet.comms.node_identity().public_key().clone(),
+            self.wallet.network.as_network(),
+        );
+        AccountManager::new(
+            self.wallet.db.clone(),
+            self.get_output_manager_service(),
+            self.wallet.key_manager_service.clone(),
+            wallet_address,
+        )
+    }
+
     fn comms(&self) -> &CommsNode {
         &self.wallet.comms
     }
@@ -174,6 +285,7 @@ impl WalletGrpcServer {
 #[tonic::async_trait]
 impl wallet_server::Wallet for WalletGrpcServer {
     type GetCompletedTransactionsStream = mpsc::Receiver<Result<GetCompletedTransactionsResponse, Status>>;
+    type StreamContactStatusStream = mpsc::Receiver<Result<ContactStatusResponse, Status>>;
     type StreamTransactionEventsStream = mpsc::Receiver<Result<TransactionEventResponse, Status>>;
 
     async fn get_version(&self, _: Request<GetVersionRequest>) -> Result<Response<GetVersionResponse>, Status> {
@@ -234,6 +346,21 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
+    async fn get_subaddress(
+        &self,
+        request: Request<tari_rpc::GetSubaddressRequest>,
+    ) -> Result<Response<GetAddressResponse>, Status> {
+        let index = request.into_inner().index;
+        let address = self
+            .get_output_manager_service()
+            .get_subaddress(index)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(GetAddressResponse {
+            address: address.to_bytes().to_vec(),
+        }))
+    }
+
     async fn set_base_node(
         &self,
         request: Request<SetBaseNodeRequest>,
@@ -257,17 +384,112 @@ impl wallet_server::Wallet for WalletGrpcServer {
         Ok(Response::new(SetBaseNodeResponse {}))
     }
 
-    async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+    async fn get_balance(&self, request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        let message = request.into_inner();
+        if !message.account.is_empty() {
+            let mut manager = self.get_account_manager();
+            let index = manager
+                .resolve_account(&message.account)
+                .map_err(account_error_to_status)?;
+            let balance = manager.get_balance(index).await.map_err(account_error_to_status)?;
+            let breakdown = manager
+                .get_balance_breakdown(index)
+                .await
+                .map_err(account_error_to_status)?;
+            return Ok(Response::new(convert_balance_with_breakdown(
+                balance,
+                &breakdown,
+                message.spendable_at_height,
+                message.min_confirmations,
+            )));
+        }
+
         let mut output_service = self.get_output_manager_service();
         let balance = match output_service.get_balance().await {
             Ok(b) => b,
             Err(e) => return Err(Status::not_found(format!("GetBalance error! {}", e))),
         };
-        Ok(Response::new(GetBalanceResponse {
-            available_balance: balance.available_balance.0,
-            pending_incoming_balance: balance.pending_incoming_balance.0,
-            pending_outgoing_balance: balance.pending_outgoing_balance.0,
-            timelocked_balance: balance.time_locked_balance.unwrap_or_default().0,
+        let breakdown = match output_service.get_balance_breakdown(None).await {
+            Ok(b) => b,
+            Err(e) => return Err(Status::not_found(format!("GetBalance error! {}", e))),
+        };
+        Ok(Response::new(convert_balance_with_breakdown(
+            balance,
+            &breakdown,
+            message.spendable_at_height,
+            message.min_confirmations,
+        )))
+    }
+
+    async fn create_account(
+        &self,
+        request: Request<CreateAccountRequest>,
+    ) -> Result<Response<CreateAccountResponse>, Status> {
+        let message = request.into_inner();
+        let mut manager = self.get_account_manager();
+        let account = manager
+            .create_account(message.name)
+            .await
+            .map_err(account_error_to_status)?;
+        let account = convert_account(&mut manager, account.index, account.name)
+            .await
+            .map_err(account_error_to_status)?;
+
+        Ok(Response::new(CreateAccountResponse { account: Some(account) }))
+    }
+
+    async fn get_accounts(
+        &self,
+        _request: Request<GetAccountsRequest>,
+    ) -> Result<Response<GetAccountsResponse>, Status> {
+        let mut manager = self.get_account_manager();
+        let mut accounts = vec![
+            convert_account(&mut manager, DEFAULT_ACCOUNT_INDEX, DEFAULT_ACCOUNT_NAME.to_string())
+                .await
+                .map_err(account_error_to_status)?,
+        ];
+        for WalletAccount { index, name, .. } in manager.get_accounts().map_err(account_error_to_status)? {
+            accounts.push(
+                convert_account(&mut manager, index, name)
+                    .await
+                    .map_err(account_error_to_status)?,
+            );
+        }
+
+        Ok(Response::new(GetAccountsResponse { accounts }))
+    }
+
+    async fn scan_with_view_key(
+        &self,
+        request: Request<ScanWithViewKeyRequest>,
+    ) -> Result<Response<ScanWithViewKeyResponse>, Status> {
+        let message = request.into_inner();
+        let view_key = PrivateKey::from_canonical_bytes(&message.view_key)
+            .map_err(|_| Status::invalid_argument("View key is malformed"))?;
+        let scan_key = PrivateKey::from_canonical_bytes(&message.scan_key)
+            .map_err(|_| Status::invalid_argument("Scan key is malformed"))?;
+        let scanner = ViewKeyScanner::new(view_key, scan_key, self.wallet.factories.clone());
+
+        let mut connectivity = self.wallet.wallet_connectivity.clone();
+        let mut client = connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or_else(|| Status::unavailable("Wallet connectivity has shut down"))?;
+        let report = scanner.scan(&mut client, message.start_height).await.map_err(|e| {
+            warn!(target: LOG_TARGET, "View key scan failed: {}", e);
+            Status::internal(e.to_string())
+        })?;
+
+        Ok(Response::new(ScanWithViewKeyResponse {
+            balance: report.balance().as_u64(),
+            total_received: report.total_received().as_u64(),
+            outputs: report
+                .outputs
+                .into_iter()
+                .map(convert_view_key_scanned_output)
+                .collect(),
+            tip_height: report.tip_height,
+            num_scanned: report.num_scanned,
         }))
     }
 
@@ -289,6 +511,103 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
+    async fn select_utxos(
+        &self,
+        request: Request<SelectUtxosRequest>,
+    ) -> Result<Response<SelectUtxosResponse>, Status> {
+        let message = request.into_inner();
+        let selection_criteria = UtxoSelectionCriteria {
+            mode: if message.include_locked {
+                UtxoSelectionMode::ListingOnly
+            } else {
+                UtxoSelectionMode::Safe
+            },
+            min_dust: message.min_value,
+            ..Default::default()
+        };
+
+        let mut output_service = self.get_output_manager_service();
+        let outputs = output_service
+            .get_spendable_outputs(selection_criteria)
+            .await
+            .map_err(|e| Status::internal(format!("SelectUtxos error! {}", e)))?;
+
+        let utxos = outputs
+            .into_iter()
+            .map(|o| SpendableUtxo {
+                commitment: o.commitment.to_vec(),
+                value: o.wallet_output.value.as_u64(),
+                maturity: o.wallet_output.features.maturity,
+                script_lock_height: o.wallet_output.script_lock_height,
+                spending_priority: i32::from(o.spending_priority),
+                mined_height: o.mined_height.unwrap_or_default(),
+                features: Some(o.wallet_output.features.into()),
+            })
+            .collect();
+
+        Ok(Response::new(SelectUtxosResponse { utxos }))
+    }
+
+    async fn set_utxo_spending_priority(
+        &self,
+        request: Request<SetUtxoSpendingPriorityRequest>,
+    ) -> Result<Response<SetUtxoSpendingPriorityResponse>, Status> {
+        let message = request.into_inner();
+        let spending_priority = u32::try_from(message.spending_priority)
+            .map_err(|e| e.to_string())
+            .and_then(SpendingPriority::try_from)
+            .map_err(Status::invalid_argument)?;
+        if spending_priority == SpendingPriority::HtlcSpendAsap {
+            return Err(Status::invalid_argument(
+                "Spending priority must be normal, pinned or frozen",
+            ));
+        }
+        let commitments = message
+            .commitments
+            .iter()
+            .map(|c| Commitment::from_canonical_bytes(c))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Status::invalid_argument("Malformed output commitment"))?;
+
+        let mut output_service = self.get_output_manager_service();
+        output_service
+            .set_outputs_spending_priority(commitments, spending_priority)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(SetUtxoSpendingPriorityResponse {}))
+    }
+
+    async fn preview_coin_selection(
+        &self,
+        request: Request<PreviewCoinSelectionRequest>,
+    ) -> Result<Response<PreviewCoinSelectionResponse>, Status> {
+        let message = request.into_inner();
+        let ordering = u32::try_from(message.coin_selection_strategy)
+            .map_err(|e| e.to_string())
+            .and_then(UtxoSelectionOrdering::try_from)
+            .map_err(Status::invalid_argument)?;
+        let selection_criteria = UtxoSelectionCriteria {
+            ordering,
+            ..Default::default()
+        };
+
+        let mut output_service = self.get_output_manager_service();
+        let report = output_service
+            .preview_coin_selection(message.amount.into(), selection_criteria, message.fee_per_gram.into())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(PreviewCoinSelectionResponse {
+            coin_selection_strategy: i32::from(report.strategy),
+            num_inputs: report.num_inputs as u64,
+            total_value: report.total_value.as_u64(),
+            fee: report.fee.as_u64(),
+            change: report.change.as_u64(),
+            requires_change_output: report.requires_change_output,
+        }))
+    }
+
     async fn revalidate_all_transactions(
         &self,
         _request: Request<RevalidateRequest>,
@@ -333,13 +652,21 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .ok_or_else(|| Status::internal("Request is malformed".to_string()))?;
         let address = TariAddress::from_hex(&message.address)
             .map_err(|_| Status::internal("Destination address is malformed".to_string()))?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
+        let selection_criteria = UtxoSelectionCriteria {
+            account,
+            ..Default::default()
+        };
 
         let mut transaction_service = self.get_transaction_service();
         let response = match transaction_service
             .send_sha_atomic_swap_transaction(
                 address.clone(),
                 message.amount.into(),
-                UtxoSelectionCriteria::default(),
+                selection_criteria,
                 message.fee_per_gram.into(),
                 message.message,
             )
@@ -388,11 +715,15 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map_err(|_| Status::internal("pre_image is malformed".to_string()))?;
         let output = BlockHash::from_hex(&message.output)
             .map_err(|_| Status::internal("Output hash is malformed".to_string()))?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
         debug!(target: LOG_TARGET, "Trying to claim HTLC with hash {}", output.to_hex());
         let mut transaction_service = self.get_transaction_service();
         let mut output_manager_service = self.get_output_manager_service();
         let response = match output_manager_service
-            .create_claim_sha_atomic_swap_transaction(output, pre_image, message.fee_per_gram.into())
+            .create_claim_sha_atomic_swap_transaction(output, pre_image, message.fee_per_gram.into(), account)
             .await
         {
             Ok((tx_id, _fee, amount, tx)) => {
@@ -442,12 +773,16 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let message = request.into_inner();
         let output = BlockHash::from_hex(&message.output_hash)
             .map_err(|_| Status::internal("Output hash is malformed".to_string()))?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
 
         let mut transaction_service = self.get_transaction_service();
         let mut output_manager_service = self.get_output_manager_service();
         debug!(target: LOG_TARGET, "Trying to claim HTLC with hash {}", output.to_hex());
         let response = match output_manager_service
-            .create_htlc_refund_transaction(output, message.fee_per_gram.into())
+            .create_htlc_refund_transaction(output, message.fee_per_gram.into(), account)
             .await
         {
             Ok((tx_id, _fee, amount, tx)) => {
@@ -485,8 +820,141 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
+    async fn initiate_atomic_swap(
+        &self,
+        request: Request<InitiateAtomicSwapRequest>,
+    ) -> Result<Response<AtomicSwapResponse>, Status> {
+        let message = request.into_inner();
+        let address = TariAddress::from_hex(&message.address)
+            .map_err(|_| Status::invalid_argument("Destination address is malformed"))?;
+        let counterparty_chain = CounterpartyChain::try_from(message.counterparty_chain)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
+        let swap = self
+            .get_atomic_swap_manager()
+            .initiate(
+                address,
+                message.amount.into(),
+                message.fee_per_gram.into(),
+                counterparty_chain,
+                message.counterparty_address,
+                message.message,
+                account,
+            )
+            .await
+            .map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to initiate atomic swap: {}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(AtomicSwapResponse {
+            swap: Some(convert_atomic_swap(swap)),
+        }))
+    }
+
+    async fn accept_atomic_swap(
+        &self,
+        request: Request<AcceptAtomicSwapRequest>,
+    ) -> Result<Response<AtomicSwapResponse>, Status> {
+        let message = request.into_inner();
+        let output_hash = FixedHash::try_from(message.output_hash)
+            .map_err(|_| Status::invalid_argument("Output hash is malformed"))?;
+        let hash_lock =
+            FixedHash::try_from(message.hash_lock).map_err(|_| Status::invalid_argument("Hash lock is malformed"))?;
+        let counterparty_chain = CounterpartyChain::try_from(message.counterparty_chain)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let tip_height = self.get_chain_tip_height()?;
+        let swap = self
+            .get_atomic_swap_manager()
+            .accept(
+                output_hash,
+                hash_lock,
+                message.amount.into(),
+                message.timeout_height,
+                counterparty_chain,
+                message.counterparty_address,
+                tip_height,
+            )
+            .await
+            .map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to accept atomic swap: {}", e);
+                atomic_swap_error_to_status(e)
+            })?;
+
+        Ok(Response::new(AtomicSwapResponse {
+            swap: Some(convert_atomic_swap(swap)),
+        }))
+    }
+
+    async fn redeem_atomic_swap(
+        &self,
+        request: Request<RedeemAtomicSwapRequest>,
+    ) -> Result<Response<AtomicSwapResponse>, Status> {
+        let message = request.into_inner();
+        let pre_image = PublicKey::from_canonical_bytes(&message.pre_image)
+            .map_err(|_| Status::invalid_argument("Pre-image is malformed"))?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
+        let swap = self
+            .get_atomic_swap_manager()
+            .redeem(message.swap_id.into(), pre_image, message.fee_per_gram.into(), account)
+            .await
+            .map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to redeem atomic swap {}: {}", message.swap_id, e);
+                atomic_swap_error_to_status(e)
+            })?;
+
+        Ok(Response::new(AtomicSwapResponse {
+            swap: Some(convert_atomic_swap(swap)),
+        }))
+    }
+
+    async fn refund_atomic_swap(
+        &self,
+        request: Request<RefundAtomicSwapRequest>,
+    ) -> Result<Response<AtomicSwapResponse>, Status> {
+        let message = request.into_inner();
+        let tip_height = self.get_chain_tip_height()?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
+        let swap = self
+            .get_atomic_swap_manager()
+            .refund(message.swap_id.into(), message.fee_per_gram.into(), tip_height, account)
+            .await
+            .map_err(|e| {
+                warn!(target: LOG_TARGET, "Failed to refund atomic swap {}: {}", message.swap_id, e);
+                atomic_swap_error_to_status(e)
+            })?;
+
+        Ok(Response::new(AtomicSwapResponse {
+            swap: Some(convert_atomic_swap(swap)),
+        }))
+    }
+
+    async fn get_atomic_swaps(
+        &self,
+        _request: Request<GetAtomicSwapsRequest>,
+    ) -> Result<Response<GetAtomicSwapsResponse>, Status> {
+        let swaps = self
+            .get_atomic_swap_manager()
+            .get_swaps()
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetAtomicSwapsResponse {
+            swaps: swaps.into_iter().map(convert_atomic_swap).collect(),
+        }))
+    }
+
     async fn transfer(&self, request: Request<TransferRequest>) -> Result<Response<TransferResponse>, Status> {
         let message = request.into_inner();
+        let account_manager = self.get_account_manager();
         let recipients = message
             .recipients
             .into_iter()
@@ -494,6 +962,40 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map(|(idx, dest)| -> Result<_, String> {
                 let address = TariAddress::from_hex(&dest.address)
                     .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                if !dest.payment_reference.is_empty() && dest.payment_type == PaymentType::StandardMimblewimble as i32 {
+                    return Err(format!(
+                        "Payment reference at index {} is only supported for one-sided payments",
+                        idx
+                    ));
+                }
+                if !dest.memo.is_empty() && dest.payment_type == PaymentType::StandardMimblewimble as i32 {
+                    return Err(format!(
+                        "Memo at index {} is only supported for one-sided payments",
+                        idx
+                    ));
+                }
+                let account = account_manager
+                    .resolve_account(&dest.account)
+                    .map_err(|e| format!("Account at index {} is invalid: {}", idx, e))?;
+                let mut selection_criteria = if dest.input_commitments.is_empty() {
+                    let ordering = u32::try_from(dest.coin_selection_strategy)
+                        .map_err(|e| e.to_string())
+                        .and_then(UtxoSelectionOrdering::try_from)
+                        .map_err(|e| format!("Coin selection strategy at index {} is invalid: {}", idx, e))?;
+                    UtxoSelectionCriteria {
+                        ordering,
+                        ..Default::default()
+                    }
+                } else {
+                    let commitments = dest
+                        .input_commitments
+                        .iter()
+                        .map(|c| Commitment::from_canonical_bytes(c))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| format!("Input commitments at index {} are malformed", idx))?;
+                    UtxoSelectionCriteria::specific(commitments)
+                };
+                selection_criteria.account = account;
                 Ok((
                     dest.address,
                     address,
@@ -501,13 +1003,27 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     dest.fee_per_gram,
                     dest.message,
                     dest.payment_type,
+                    dest.payment_reference,
+                    dest.memo,
+                    selection_criteria,
                 ))
             })
             .collect::<Result<Vec<_>, _>>()
             .map_err(Status::invalid_argument)?;
 
         let mut transfers = Vec::new();
-        for (hex_address, address, amount, fee_per_gram, message, payment_type) in recipients {
+        for (
+            hex_address,
+            address,
+            amount,
+            fee_per_gram,
+            message,
+            payment_type,
+            payment_reference,
+            memo,
+            selection_criteria,
+        ) in recipients
+        {
             let mut transaction_service = self.get_transaction_service();
             transfers.push(async move {
                 (
@@ -517,7 +1033,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .send_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
@@ -528,10 +1044,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .send_one_sided_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
+                                payment_reference,
+                                memo,
                             )
                             .await
                     } else {
@@ -539,10 +1057,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .send_one_sided_to_stealth_address_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
+                                payment_reference,
+                                memo,
                             )
                             .await
                     },
@@ -579,6 +1099,82 @@ impl wallet_server::Wallet for WalletGrpcServer {
         Ok(Response::new(TransferResponse { results }))
     }
 
+    async fn send_to_many(
+        &self,
+        request: Request<tari_rpc::SendToManyRequest>,
+    ) -> Result<Response<tari_rpc::SendToManyResponse>, Status> {
+        let message = request.into_inner();
+        let recipients = message
+            .recipients
+            .iter()
+            .enumerate()
+            .map(|(idx, dest)| -> Result<_, String> {
+                let destination = TariAddress::from_hex(&dest.address)
+                    .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                if !dest.payment_reference.is_empty() && !dest.one_sided {
+                    return Err(format!(
+                        "Payment reference at index {} is only supported for one-sided payments",
+                        idx
+                    ));
+                }
+                Ok(BatchRecipient {
+                    destination,
+                    amount: dest.amount.into(),
+                    one_sided: dest.one_sided,
+                    payment_reference: dest.payment_reference.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Status::invalid_argument)?;
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
+        let selection_criteria = UtxoSelectionCriteria {
+            account,
+            ..Default::default()
+        };
+        let mut transaction_service = self.get_transaction_service();
+
+        let statuses = transaction_service
+            .send_to_many(
+                recipients,
+                selection_criteria,
+                message.fee_per_gram.into(),
+                message.message,
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        let results = message
+            .recipients
+            .into_iter()
+            .zip(statuses)
+            .map(|(dest, status)| match status {
+                BatchRecipientStatus::Sent(tx_id) => TransferResult {
+                    address: dest.address,
+                    transaction_id: tx_id.into(),
+                    is_success: true,
+                    failure_message: Default::default(),
+                },
+                BatchRecipientStatus::Failed(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to send to address `{}` in batch: {}", dest.address, err
+                    );
+                    TransferResult {
+                        address: dest.address,
+                        transaction_id: Default::default(),
+                        is_success: false,
+                        failure_message: err,
+                    }
+                },
+            })
+            .collect();
+
+        Ok(Response::new(tari_rpc::SendToManyResponse { results }))
+    }
+
     async fn create_burn_transaction(
         &self,
         request: Request<CreateBurnTransactionRequest>,
@@ -664,6 +1260,37 @@ impl wallet_server::Wallet for WalletGrpcServer {
         Ok(Response::new(GetTransactionInfoResponse { transactions }))
     }
 
+    async fn get_transaction_by_payment_ref(
+        &self,
+        request: Request<GetTransactionByPaymentRefRequest>,
+    ) -> Result<Response<GetTransactionByPaymentRefResponse>, Status> {
+        let message = request.into_inner();
+        if message.payment_reference.is_empty() {
+            return Err(Status::invalid_argument("Payment reference must not be empty"));
+        }
+
+        let transactions = self
+            .get_transaction_service()
+            .get_transactions_by_payment_reference(message.payment_reference)
+            .await
+            .map_err(|err| Status::unknown(err.to_string()))?;
+
+        let wallet_pk = self.wallet.comms.node_identity_ref().public_key();
+        let wallet_network = self.wallet.network.as_network();
+        let wallet_address = TariAddress::new(wallet_pk.clone(), wallet_network);
+        let transactions = transactions
+            .into_values()
+            .map(|tx| {
+                convert_wallet_transaction_into_transaction_info(
+                    models::WalletTransaction::Completed(tx),
+                    &wallet_address,
+                )
+            })
+            .collect();
+
+        Ok(Response::new(GetTransactionByPaymentRefResponse { transactions }))
+    }
+
     async fn stream_transaction_events(
         &self,
         _request: tonic::Request<TransactionEventRequest>,
@@ -730,19 +1357,154 @@ impl wallet_server::Wallet for WalletGrpcServer {
         Ok(Response::new(receiver))
     }
 
+    async fn stream_contact_status(
+        &self,
+        _request: Request<ContactStatusRequest>,
+    ) -> Result<Response<Self::StreamContactStatusStream>, Status> {
+        let mut contacts_service = self.wallet.contacts_service.clone();
+        // Subscribe before reading the current state, so that no update in between is missed
+        let mut liveness_events = contacts_service.get_contacts_liveness_event_stream();
+        let contacts = contacts_service
+            .get_contacts()
+            .await
+            .map_err(|e| Status::internal(format!("Could not fetch contacts: {}", e)))?;
+        let mut current_status = Vec::with_capacity(contacts.len());
+        for contact in contacts {
+            let online_status = contacts_service
+                .get_contact_online_status(contact.clone())
+                .await
+                .map_err(|e| Status::internal(format!("Could not fetch contact status: {}", e)))?;
+            current_status.push(convert_contact_status(
+                &contact.address,
+                contact.alias,
+                online_status,
+                contact.last_seen,
+                contact.latency,
+            ));
+        }
+
+        let (mut sender, receiver) = mpsc::channel(100);
+        task::spawn(async move {
+            for response in current_status {
+                if sender.send(Ok(response)).await.is_err() {
+                    return;
+                }
+            }
+            loop {
+                match liveness_events.recv().await {
+                    Ok(event) => {
+                        let data = match &*event {
+                            ContactsLivenessEvent::StatusUpdated(data) => data,
+                            ContactsLivenessEvent::NetworkSilence => continue,
+                        };
+                        // The contact may have been removed since it was pinged
+                        let alias = match contacts_service.get_contact(data.address().clone()).await {
+                            Ok(contact) => contact.alias,
+                            Err(_) => continue,
+                        };
+                        let response = convert_contact_status(
+                            data.address(),
+                            alias,
+                            data.online_status(),
+                            data.last_ping_pong_received(),
+                            data.latency(),
+                        );
+                        if sender.send(Ok(response)).await.is_err() {
+                            debug!(target: LOG_TARGET, "Contact status stream closed by the client");
+                            break;
+                        }
+                    },
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Missed {} from Contacts liveness events", n);
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        Ok(Response::new(receiver))
+    }
+
+    async fn send_chat_message(
+        &self,
+        request: Request<SendChatMessageRequest>,
+    ) -> Result<Response<SendChatMessageResponse>, Status> {
+        let message = request.into_inner();
+        let address = TariAddress::from_hex(&message.address)
+            .map_err(|_| Status::invalid_argument("Destination address is malformed".to_string()))?;
+        let mut chat_message = MessageBuilder::new().address(address).build();
+        chat_message.body = message.body;
+        let message_id = chat_message.message_id.clone();
+
+        let mut contacts_service = self.wallet.contacts_service.clone();
+        contacts_service
+            .send_message(chat_message)
+            .await
+            .map_err(|e| Status::internal(format!("Could not send chat message: {}", e)))?;
+
+        Ok(Response::new(SendChatMessageResponse { message_id }))
+    }
+
+    async fn get_chat_messages(
+        &self,
+        request: Request<GetChatMessagesRequest>,
+    ) -> Result<Response<GetChatMessagesResponse>, Status> {
+        let message = request.into_inner();
+        let address = TariAddress::from_hex(&message.address)
+            .map_err(|_| Status::invalid_argument("Address is malformed".to_string()))?;
+
+        let mut contacts_service = self.wallet.contacts_service.clone();
+        let messages = contacts_service
+            .get_messages(address, message.limit, message.page)
+            .await
+            .map_err(|e| Status::internal(format!("Could not fetch chat messages: {}", e)))?;
+
+        Ok(Response::new(GetChatMessagesResponse {
+            messages: messages.into_iter().map(convert_chat_message).collect(),
+        }))
+    }
+
+    async fn mark_chat_message_read(
+        &self,
+        request: Request<MarkChatMessageReadRequest>,
+    ) -> Result<Response<MarkChatMessageReadResponse>, Status> {
+        let message = request.into_inner();
+        let address = TariAddress::from_hex(&message.address)
+            .map_err(|_| Status::invalid_argument("Address is malformed".to_string()))?;
+
+        let mut contacts_service = self.wallet.contacts_service.clone();
+        contacts_service
+            .send_read_confirmation(address, message.message_id)
+            .await
+            .map_err(|e| Status::internal(format!("Could not send read receipt: {}", e)))?;
+
+        Ok(Response::new(MarkChatMessageReadResponse {}))
+    }
+
     async fn get_completed_transactions(
         &self,
-        _request: Request<GetCompletedTransactionsRequest>,
+        request: Request<GetCompletedTransactionsRequest>,
     ) -> Result<Response<Self::GetCompletedTransactionsStream>, Status> {
         debug!(
             target: LOG_TARGET,
             "GetAllCompletedTransactions: Incoming GRPC request"
         );
+        let message = request.into_inner();
         let mut transaction_service = self.get_transaction_service();
-        let transactions = transaction_service
+        let mut transactions = transaction_service
             .get_completed_transactions()
             .await
             .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+        if !message.account.is_empty() {
+            let mut manager = self.get_account_manager();
+            let index = manager
+                .resolve_account(&message.account)
+                .map_err(account_error_to_status)?;
+            let tx_ids = manager
+                .get_transaction_ids(index)
+                .await
+                .map_err(account_error_to_status)?;
+            transactions.retain(|tx_id, _| tx_ids.contains(tx_id));
+        }
         debug!(
             target: LOG_TARGET,
             "GetAllCompletedTransactions: Found {} completed transactions",
@@ -770,6 +1532,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .get_signature()
                             .to_vec(),
                         message: txn.message.clone(),
+                        payment_reference: txn.payment_reference.clone().unwrap_or_default(),
+                        memo: txn.memo.clone().unwrap_or_default(),
                     }),
                 };
                 match sender.send(Ok(response)).await {
@@ -801,6 +1565,10 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
     async fn coin_split(&self, request: Request<CoinSplitRequest>) -> Result<Response<CoinSplitResponse>, Status> {
         let message = request.into_inner();
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
 
         let mut wallet = self.wallet.clone();
 
@@ -811,6 +1579,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
                 usize::try_from(message.split_count)
                     .map_err(|_| Status::internal("Count not convert u64 to usize".to_string()))?,
                 MicroMinotari::from(message.fee_per_gram),
+                account,
                 message.message,
             )
             .await
@@ -833,23 +1602,32 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map(UnblindedOutput::try_from)
             .collect::<Result<Vec<_>, _>>()
             .map_err(Status::invalid_argument)?;
-        let mut tx_ids = Vec::new();
-
-        for o in &unblinded_outputs {
-            tx_ids.push(
-                wallet
-                    .import_unblinded_output_as_non_rewindable(
-                        o.clone(),
-                        TariAddress::default(),
-                        "Imported via gRPC".to_string(),
-                    )
-                    .await
-                    .map_err(|e| Status::internal(format!("{:?}", e)))?
-                    .into(),
-            );
-        }
 
-        Ok(Response::new(ImportUtxosResponse { tx_ids }))
+        let results = wallet
+            .import_unblinded_outputs_as_non_rewindable(
+                unblinded_outputs,
+                TariAddress::default(),
+                "Imported via gRPC".to_string(),
+            )
+            .await
+            .map_err(|e| Status::internal(format!("{:?}", e)))?
+            .into_iter()
+            .map(|result| match result {
+                Ok(tx_id) => ImportUtxoResult {
+                    is_success: true,
+                    tx_id: tx_id.into(),
+                    failure_message: String::new(),
+                },
+                Err(e) => ImportUtxoResult {
+                    is_success: false,
+                    tx_id: 0,
+                    failure_message: e.to_string(),
+                },
+            })
+            .collect::<Vec<_>>();
+        let tx_ids = results.iter().filter(|r| r.is_success).map(|r| r.tx_id).collect();
+
+        Ok(Response::new(ImportUtxosResponse { tx_ids, results }))
     }
 
     async fn get_network_status(
@@ -890,6 +1668,11 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .await
             .map_err(|err| Status::internal(err.to_string()))?;
 
+        let peer_metrics = connectivity
+            .get_peer_quality_metrics()
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?;
+
         let mut peers = Vec::with_capacity(connected_peers.len());
         for conn in connected_peers {
             peers.push(
@@ -903,6 +1686,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
         let resp = tari_rpc::ListConnectedPeersResponse {
             connected_peers: peers.into_iter().map(Into::into).collect(),
+            peer_metrics: peer_metrics.into_iter().map(Into::into).collect(),
         };
 
         Ok(Response::new(resp))
@@ -935,6 +1719,194 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }
     }
 
+    async fn bump_fee(
+        &self,
+        request: Request<tari_rpc::BumpFeeRequest>,
+    ) -> Result<Response<tari_rpc::BumpFeeResponse>, Status> {
+        let message = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming gRPC request to Bump Fee (TxId: {}, fee per gram: {})", message.tx_id, message.fee_per_gram,
+        );
+        let mut transaction_service = self.get_transaction_service();
+
+        match transaction_service
+            .bump_fee(message.tx_id.into(), message.fee_per_gram.into())
+            .await
+        {
+            Ok(replacement_tx_id) => Ok(Response::new(tari_rpc::BumpFeeResponse {
+                is_success: true,
+                failure_message: "".to_string(),
+                replacement_tx_id: replacement_tx_id.into(),
+            })),
+            Err(e) => Ok(Response::new(tari_rpc::BumpFeeResponse {
+                is_success: false,
+                failure_message: e.to_string(),
+                replacement_tx_id: 0,
+            })),
+        }
+    }
+
+    async fn cancel_pending(
+        &self,
+        request: Request<tari_rpc::CancelPendingRequest>,
+    ) -> Result<Response<tari_rpc::CancelPendingResponse>, Status> {
+        let message = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming gRPC request to Cancel Pending (TxId: {}, fee per gram: {})", message.tx_id, message.fee_per_gram,
+        );
+        let mut transaction_service = self.get_transaction_service();
+
+        match transaction_service
+            .cancel_pending(message.tx_id.into(), message.fee_per_gram.into())
+            .await
+        {
+            Ok(replacement_tx_id) => Ok(Response::new(tari_rpc::CancelPendingResponse {
+                is_success: true,
+                failure_message: "".to_string(),
+                replacement_tx_id: replacement_tx_id.into(),
+            })),
+            Err(e) => Ok(Response::new(tari_rpc::CancelPendingResponse {
+                is_success: false,
+                failure_message: e.to_string(),
+                replacement_tx_id: 0,
+            })),
+        }
+    }
+
+    async fn create_payment_request(
+        &self,
+        request: Request<tari_rpc::CreatePaymentRequestRequest>,
+    ) -> Result<Response<tari_rpc::CreatePaymentRequestResponse>, Status> {
+        let message = request.into_inner();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Status::internal(e.to_string()))?
+            .as_secs();
+        let expiry = now
+            .checked_add(message.expires_in_seconds)
+            .ok_or_else(|| Status::invalid_argument("expires_in_seconds is too large"))?;
+        let mut transaction_service = self.get_transaction_service();
+
+        let payment_request = transaction_service
+            .create_payment_request(message.amount.into(), message.memo, expiry)
+            .await
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        Ok(Response::new(tari_rpc::CreatePaymentRequestResponse {
+            payment_request: payment_request.to_base58(),
+            expiry,
+        }))
+    }
+
+    async fn pay_payment_request(
+        &self,
+        request: Request<tari_rpc::PayPaymentRequestRequest>,
+    ) -> Result<Response<tari_rpc::PayPaymentRequestResponse>, Status> {
+        let message = request.into_inner();
+        let payment_request = PaymentRequest::from_base58(&message.payment_request)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        debug!(
+            target: LOG_TARGET,
+            "Incoming gRPC request to pay {} to {} for '{}'",
+            payment_request.amount,
+            payment_request.recipient,
+            payment_request.memo
+        );
+        let account = self
+            .get_account_manager()
+            .resolve_account(&message.account)
+            .map_err(account_error_to_status)?;
+        let selection_criteria = UtxoSelectionCriteria {
+            account,
+            ..Default::default()
+        };
+        let mut transaction_service = self.get_transaction_service();
+
+        match transaction_service
+            .pay_payment_request(payment_request, selection_criteria, message.fee_per_gram.into())
+            .await
+        {
+            Ok(tx_id) => Ok(Response::new(tari_rpc::PayPaymentRequestResponse {
+                tx_id: tx_id.into(),
+                is_success: true,
+                failure_message: "".to_string(),
+            })),
+            Err(e) => Ok(Response::new(tari_rpc::PayPaymentRequestResponse {
+                tx_id: 0,
+                is_success: false,
+                failure_message: e.to_string(),
+            })),
+        }
+    }
+
+    async fn export_transaction_history(
+        &self,
+        request: Request<tari_rpc::ExportTransactionHistoryRequest>,
+    ) -> Result<Response<tari_rpc::ExportTransactionHistoryResponse>, Status> {
+        let message = request.into_inner();
+        let format = TransactionHistoryFormat::from_str(&message.format)
+            .map_err(|_| Status::invalid_argument(format!("Unsupported format '{}'", message.format)))?;
+        let mut wallet = self.wallet.clone();
+
+        let data = wallet
+            .export_transaction_history(format)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(tari_rpc::ExportTransactionHistoryResponse { data }))
+    }
+
+    async fn import_transaction_history(
+        &self,
+        request: Request<tari_rpc::ImportTransactionHistoryRequest>,
+    ) -> Result<Response<tari_rpc::ImportTransactionHistoryResponse>, Status> {
+        let message = request.into_inner();
+        let format = TransactionHistoryFormat::from_str(&message.format)
+            .map_err(|_| Status::invalid_argument(format!("Unsupported format '{}'", message.format)))?;
+        let mut wallet = self.wallet.clone();
+
+        let import = wallet
+            .import_transaction_history(&message.data, format)
+            .await
+            .map_err(|e| match e {
+                WalletError::TransactionHistoryError(e) => Status::invalid_argument(e.to_string()),
+                e => Status::internal(e.to_string()),
+            })?;
+        Ok(Response::new(tari_rpc::ImportTransactionHistoryResponse {
+            num_messages_restored: import.num_messages_restored as u64,
+            num_contacts_restored: import.num_contacts_restored as u64,
+        }))
+    }
+
+    async fn create_balance_proof(
+        &self,
+        request: Request<tari_rpc::CreateBalanceProofRequest>,
+    ) -> Result<Response<tari_rpc::CreateBalanceProofResponse>, Status> {
+        let message = request.into_inner();
+        let commitments = message
+            .commitments
+            .iter()
+            .map(|c| Commitment::from_canonical_bytes(c))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Status::invalid_argument("Malformed output commitment"))?;
+
+        let proof = self
+            .get_output_manager_service()
+            .create_balance_proof(commitments, message.message)
+            .await
+            .map_err(|e| match e {
+                OutputManagerError::InvalidArgument(e) => Status::invalid_argument(e),
+                e => Status::internal(e.to_string()),
+            })?;
+        Ok(Response::new(tari_rpc::CreateBalanceProofResponse {
+            commitments: proof.commitments.iter().map(|c| c.to_vec()).collect(),
+            total: proof.total.as_u64(),
+            message: proof.message,
+            signature: Some(proof.signature.into()),
+        }))
+    }
+
     async fn create_template_registration(
         &self,
         request: Request<CreateTemplateRegistrationRequest>,
@@ -992,6 +1964,36 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
+    async fn create_covenant(
+        &self,
+        request: Request<CreateCovenantRequest>,
+    ) -> Result<Response<CreateCovenantResponse>, Status> {
+        let template = request
+            .into_inner()
+            .template
+            .ok_or_else(|| Status::invalid_argument("Covenant template is missing"))?;
+        let builder = match template {
+            create_covenant_request::Template::TimeLockedVault(vault) => {
+                CovenantBuilder::time_locked_vault(vault.unlock_height)
+            },
+            create_covenant_request::Template::OutputTypeRestriction(restriction) => {
+                let output_type = u8::try_from(restriction.output_type)
+                    .ok()
+                    .and_then(OutputType::from_byte)
+                    .ok_or_else(|| Status::invalid_argument("Invalid output type"))?;
+                CovenantBuilder::output_type_restriction(output_type)
+            },
+            create_covenant_request::Template::PinnedToAddress(pinned) => {
+                let address = TariAddress::from_hex(&pinned.address)
+                    .map_err(|_| Status::invalid_argument("Address is malformed"))?;
+                CovenantBuilder::pinned_to_address(&address)
+            },
+        };
+        let covenant = borsh::to_vec(&builder.build()).map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(CreateCovenantResponse { covenant }))
+    }
+
     async fn register_validator_node(
         &self,
         request: Request<RegisterValidatorNodeRequest>,
@@ -1076,6 +2078,44 @@ async fn handle_pending_outbound(
     }
 }
 
+fn convert_contact_status(
+    address: &TariAddress,
+    alias: String,
+    online_status: ContactOnlineStatus,
+    last_seen: Option<chrono::NaiveDateTime>,
+    latency: Option<u32>,
+) -> ContactStatusResponse {
+    let (online_status, banned_reason) = match online_status {
+        ContactOnlineStatus::Online => (tari_rpc::ContactOnlineStatus::Online, String::new()),
+        ContactOnlineStatus::Offline => (tari_rpc::ContactOnlineStatus::Offline, String::new()),
+        ContactOnlineStatus::NeverSeen => (tari_rpc::ContactOnlineStatus::NeverSeen, String::new()),
+        ContactOnlineStatus::Banned(reason) => (tari_rpc::ContactOnlineStatus::Banned, reason),
+    };
+    ContactStatusResponse {
+        address: address.to_bytes().to_vec(),
+        alias,
+        online_status: online_status.into(),
+        last_seen: last_seen
+            .and_then(|time| u64::try_from(time.timestamp()).ok())
+            .unwrap_or_default(),
+        latency: latency.unwrap_or_default(),
+        banned_reason,
+    }
+}
+
+fn convert_chat_message(message: Message) -> ChatMessage {
+    ChatMessage {
+        message_id: message.message_id,
+        address: message.address.to_bytes().to_vec(),
+        body: message.body,
+        is_outbound: message.direction == Direction::Outbound,
+        sent_at: message.sent_at,
+        stored_at: message.stored_at,
+        delivered_at: message.delivery_confirmation_at.unwrap_or_default(),
+        read_at: message.read_confirmation_at.unwrap_or_default(),
+    }
+}
+
 fn simple_event(event: &str) -> TransactionEvent {
     TransactionEvent {
         event: event.to_string(),
@@ -1107,6 +2147,8 @@ fn convert_wallet_transaction_into_transaction_info(
             excess_sig: Default::default(),
             timestamp: tx.timestamp.timestamp() as u64,
             message: tx.message,
+            payment_reference: vec![],
+            memo: vec![],
         },
         PendingOutbound(tx) => TransactionInfo {
             tx_id: tx.tx_id.into(),
@@ -1120,6 +2162,8 @@ fn convert_wallet_transaction_into_transaction_info(
             excess_sig: Default::default(),
             timestamp: tx.timestamp.timestamp() as u64,
             message: tx.message,
+            payment_reference: vec![],
+            memo: vec![],
         },
         Completed(tx) => TransactionInfo {
             tx_id: tx.tx_id.into(),
@@ -1137,6 +2181,126 @@ fn convert_wallet_transaction_into_transaction_info(
                 .map(|s| s.get_signature().to_vec())
                 .unwrap_or_default(),
             message: tx.message,
+            payment_reference: tx.payment_reference.unwrap_or_default(),
+            memo: tx.memo.unwrap_or_default(),
+        },
+    }
+}
+
+fn convert_atomic_swap(swap: AtomicSwap) -> tari_rpc::AtomicSwap {
+    tari_rpc::AtomicSwap {
+        swap_id: swap.swap_id.as_u64(),
+        is_initiator: swap.role == SwapRole::Initiator,
+        counterparty_chain: swap.counterparty_chain as i32,
+        counterparty_address: swap.counterparty_address,
+        amount: swap.amount.as_u64(),
+        hash_lock: swap.hash_lock.to_vec(),
+        pre_image: swap.pre_image.map(|p| p.to_vec()).unwrap_or_default(),
+        output_hash: swap.output_hash.to_vec(),
+        timeout_height: swap.timeout_height,
+        status: swap.status as i32,
+        funding_tx_id: swap.funding_tx_id.map(|id| id.as_u64()).unwrap_or_default(),
+        redeem_tx_id: swap.redeem_tx_id.map(|id| id.as_u64()).unwrap_or_default(),
+        refund_tx_id: swap.refund_tx_id.map(|id| id.as_u64()).unwrap_or_default(),
+    }
+}
+
+fn convert_balance(balance: Balance) -> GetBalanceResponse {
+    GetBalanceResponse {
+        available_balance: balance.available_balance.0,
+        pending_incoming_balance: balance.pending_incoming_balance.0,
+        pending_outgoing_balance: balance.pending_outgoing_balance.0,
+        timelocked_balance: balance.time_locked_balance.unwrap_or_default().0,
+        ..Default::default()
+    }
+}
+
+fn convert_balance_with_breakdown(
+    balance: Balance,
+    breakdown: &BalanceBreakdown,
+    spendable_at_height: u64,
+    min_confirmations: u64,
+) -> GetBalanceResponse {
+    GetBalanceResponse {
+        unconfirmed_balance: breakdown.unconfirmed_balance().as_u64(),
+        balance_by_confirmations: breakdown
+            .balance_by_confirmations()
+            .into_iter()
+            .map(|(confirmations, balance)| tari_rpc::BalanceAtDepth {
+                confirmations,
+                balance: balance.as_u64(),
+            })
+            .collect(),
+        immature_balance: breakdown
+            .immature_balance_by_height()
+            .into_iter()
+            .map(|(height, balance)| tari_rpc::ImmatureBalance {
+                height,
+                balance: balance.as_u64(),
+            })
+            .collect(),
+        spendable_at_height: if spendable_at_height == 0 {
+            0
+        } else {
+            breakdown
+                .spendable_at_height(spendable_at_height, min_confirmations)
+                .as_u64()
         },
+        tip_height: breakdown.tip_height,
+        ..convert_balance(balance)
+    }
+}
+
+async fn convert_account(
+    manager: &mut AccountManager<WalletSqliteDatabase, WalletKeyManagerSqlite>,
+    index: u64,
+    name: String,
+) -> Result<tari_rpc::WalletAccount, AccountError> {
+    let address = manager.get_account_address(index).await?;
+    let balance = manager.get_balance(index).await?;
+    Ok(tari_rpc::WalletAccount {
+        index,
+        name,
+        address: address.to_bytes().to_vec(),
+        balance: Some(convert_balance(balance)),
+    })
+}
+
+fn convert_view_key_scanned_output(output: ViewKeyScannedOutput) -> tari_rpc::ViewKeyScannedOutput {
+    tari_rpc::ViewKeyScannedOutput {
+        output_hash: output.output_hash.to_vec(),
+        commitment: output.commitment.to_vec(),
+        value: output.value.as_u64(),
+        source: output.source.to_string(),
+        payment_reference: output.payment_reference,
+        mined_height: output.mined_height,
+        spent_height: output.spent_height.unwrap_or_default(),
+    }
+}
+
+fn account_error_to_status(error: AccountError) -> Status {
+    match error {
+        AccountError::AccountNotFound(_) => Status::not_found(error.to_string()),
+        AccountError::AccountExists(_) => Status::already_exists(error.to_string()),
+        AccountError::InvalidAccountName(_) => Status::invalid_argument(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
+
+fn atomic_swap_error_to_status(error: AtomicSwapError) -> Status {
+    match error {
+        AtomicSwapError::SwapNotFound(_) => Status::not_found(error.to_string()),
+        AtomicSwapError::InvalidTransition { .. } |
+        AtomicSwapError::PreImageMismatch(_) |
+        AtomicSwapError::TimeoutNotReached { .. } |
+        AtomicSwapError::NotInitiator(_) |
+        AtomicSwapError::NotParticipant(_) => Status::failed_precondition(error.to_string()),
+        AtomicSwapError::InvalidHtlcScript |
+        AtomicSwapError::NotRecipient(_) |
+        AtomicSwapError::HashLockMismatch(_) |
+        AtomicSwapError::AmountMismatch { .. } |
+        AtomicSwapError::TimeoutMismatch { .. } |
+        AtomicSwapError::TimeoutTooSoon { .. } => Status::invalid_argument(error.to_string()),
+        _ => Status::internal(error.to_string()),
     }
 }
@@ -33,7 +33,8 @@ use minotari_wallet::{
     output_manager_service::storage::database::OutputManagerDatabase,
     storage::{
         database::{WalletBackend, WalletDatabase},
-        sqlite_utilities::initialize_sqlite_database_backends,
+        sqlite_db::wallet::WalletSqliteDatabase,
+        sqlite_utilities::{initialize_sqlite_database_backends, run_migration_and_create_sqlite_connection},
     },
     wallet::{derive_comms_secret_key, read_or_create_master_seed, read_or_create_wallet_type},
     Wallet,
@@ -66,7 +67,7 @@ use tari_utilities::{hex::Hex, ByteArray, SafePassword};
 use zxcvbn::zxcvbn;
 
 use crate::{
-    cli::Cli,
+    cli::{Cli, DbRekeyArgs},
     utils::db::{get_custom_base_node_peer_from_db, set_custom_base_node_peer_in_db},
     wallet_modes::{PeerConfig, WalletMode},
     ApplicationConfig,
@@ -267,6 +268,47 @@ pub async fn change_password(
     })
 }
 
+/// Rotates the database encryption key, and unless `keep_passphrase` is set also the passphrase, then exits.
+/// This opens the database directly rather than starting the wallet, since running services hold their own copy of
+/// the current key.
+pub fn rekey_database(config: &ApplicationConfig, existing: SafePassword, args: &DbRekeyArgs) -> Result<(), ExitError> {
+    let db_path = &config.wallet.db_file;
+    if !db_path.exists() {
+        return Err(ExitError::new(
+            ExitCode::WalletError,
+            "There is no wallet database to rekey.",
+        ));
+    }
+    let connection = run_migration_and_create_sqlite_connection(db_path, config.wallet.db_connection_pool_size)
+        .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?;
+    let db = WalletDatabase::new(
+        WalletSqliteDatabase::new(connection, existing.clone()).map_err(|e| match e {
+            WalletStorageError::InvalidPassphrase => {
+                ExitError::new(ExitCode::IncorrectOrEmptyPassword, "Your database was not rekeyed.")
+            },
+            e => ExitError::new(ExitCode::DatabaseError, e),
+        })?,
+    );
+
+    let new = if args.keep_passphrase {
+        existing.clone()
+    } else {
+        get_new_passphrase("New wallet passphrase: ", "Confirm new passphrase: ")?
+    };
+
+    let summary = db.rekey(&existing, &new, args.batch_size).map_err(|e| {
+        ExitError::new(
+            ExitCode::DatabaseError,
+            format!("Your database was not fully rekeyed: {}", e),
+        )
+    })?;
+    println!(
+        "Database rekeyed: {} values re-encrypted, {} already rekeyed, {} legacy plaintext values encrypted.",
+        summary.rekeyed, summary.already_rekeyed, summary.migrated_plaintext
+    );
+    Ok(())
+}
+
 /// Populates the PeerConfig struct from:
 /// 1. The custom peer in the wallet config if it exists
 /// 2. The custom peer in the wallet db if it exists
@@ -816,14 +858,14 @@ pub fn prompt_wallet_type(
     wallet_config: &WalletConfig,
     non_interactive: bool,
 ) -> Option<WalletType> {
-    if non_interactive {
-        return Some(WalletType::Software);
-    }
-
     if wallet_config.wallet_type.is_some() {
         return wallet_config.wallet_type;
     }
 
+    if non_interactive {
+        return Some(WalletType::Software);
+    }
+
     match boot_mode {
         WalletBoot::New => {
             #[cfg(not(feature = "ledger"))]
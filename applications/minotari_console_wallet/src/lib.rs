@@ -39,6 +39,9 @@ pub use cli::{
     Cli,
     CliCommands,
     CoinSplitArgs,
+    DbArgs,
+    DbCommand,
+    DbRekeyArgs,
     DiscoverPeerArgs,
     ExportUtxosArgs,
     MakeItRainArgs,
@@ -46,10 +49,19 @@ pub use cli::{
     SetBaseNodeArgs,
     WhoisArgs,
 };
-use init::{change_password, get_base_node_peer_config, init_wallet, start_wallet, tari_splash_screen, WalletBoot};
+use init::{
+    change_password,
+    get_base_node_peer_config,
+    init_wallet,
+    rekey_database,
+    start_wallet,
+    tari_splash_screen,
+    WalletBoot,
+};
 use log::*;
 use minotari_app_utilities::{common_cli_args::CommonCliArgs, consts};
 use minotari_wallet::transaction_service::config::TransactionRoutingMechanism;
+use notifier::WebhookNotifier;
 use recovery::{get_seed_from_seed_words, prompt_private_key_from_seed_words};
 use tari_common::{
     configuration::bootstrap::ApplicationType,
@@ -146,6 +158,15 @@ pub fn run_wallet_with_cli(
         ));
     }
 
+    if let Some(CliCommands::Db(ref db_args)) = cli.command2 {
+        match db_args.command {
+            DbCommand::Rekey(ref args) => {
+                info!(target: LOG_TARGET, "Database rekey requested.");
+                return rekey_database(config, password, args);
+            },
+        }
+    }
+
     // Run our own Tor instance, if configured
     // This is currently only possible on linux/macos
     #[cfg(all(unix, feature = "libtor"))]
@@ -219,6 +240,14 @@ pub fn run_wallet_with_cli(
 
     let handle = runtime.handle().clone();
 
+    if matches!(wallet_mode, WalletMode::Tui | WalletMode::Grpc) {
+        let webhook_notifier = WebhookNotifier::new(&config.wallet, wallet.transaction_service.clone())
+            .map_err(|e| ExitError::new(ExitCode::ConfigError, e))?;
+        if let Some(webhook_notifier) = webhook_notifier {
+            handle.spawn(webhook_notifier.run(shutdown.to_signal()));
+        }
+    }
+
     let result = match wallet_mode {
         WalletMode::Tui => tui_mode(handle, &config.wallet, &base_node_config, wallet.clone()),
         WalletMode::Grpc => grpc_mode(handle, &config.wallet, wallet.clone()),
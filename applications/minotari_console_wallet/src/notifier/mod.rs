@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod webhook;
+
 use std::{
     io::Error,
     path::PathBuf,
@@ -39,6 +41,8 @@ use minotari_wallet::{
 use tari_common_types::transaction::TxId;
 use tari_utilities::hex::Hex;
 use tokio::{runtime::Handle, sync::broadcast::Sender};
+
+pub use self::webhook::WebhookNotifier;
 pub const LOG_TARGET: &str = "wallet::notifier";
 pub const RECEIVED: &str = "received";
 pub const SENT: &str = "sent";
@@ -0,0 +1,310 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    cmp::min,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use hmac::{Hmac, Mac};
+use log::*;
+use minotari_wallet::{
+    transaction_service::{
+        handle::{TransactionEvent, TransactionServiceHandle},
+        storage::models::{TxCancellationReason, WalletTransaction},
+    },
+    WalletConfig,
+};
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tari_common_types::transaction::TxId;
+use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::Hex;
+use tokio::{sync::broadcast::error::RecvError, time::sleep};
+
+use crate::notifier::{CANCELLED, MINED, RECEIVED};
+
+const LOG_TARGET: &str = "wallet::notifier::webhook";
+pub const REORGED_OUT: &str = "reorged_out";
+pub const VALIDATION_COMPLETE: &str = "validation_complete";
+/// The header carrying the hex encoded HMAC-SHA256 of the request body
+pub const SIGNATURE_HEADER: &str = "X-Tari-Signature";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// POSTs signed JSON payloads describing transaction events to the configured webhook URLs, so that merchants do not
+/// have to poll the wallet for changes.
+pub struct WebhookNotifier {
+    client: Client,
+    urls: Vec<Url>,
+    secret: Vec<u8>,
+    max_retries: usize,
+    transaction_service: TransactionServiceHandle,
+}
+
+impl WebhookNotifier {
+    /// Returns `None` if no webhook URLs are configured. Fails if URLs are configured without a webhook secret, as
+    /// anyone could then forge the payload signatures.
+    pub fn new(config: &WalletConfig, transaction_service: TransactionServiceHandle) -> Result<Option<Self>, String> {
+        let urls = config
+            .webhook_urls
+            .iter()
+            .filter_map(|url| match Url::parse(url) {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    error!(target: LOG_TARGET, "Ignoring invalid webhook URL '{}': {}", url, e);
+                    None
+                },
+            })
+            .collect::<Vec<_>>();
+        if urls.is_empty() {
+            return Ok(None);
+        }
+        let secret = webhook_secret(config)?;
+
+        Ok(Some(Self {
+            client: Client::new(),
+            urls,
+            secret,
+            max_retries: config.webhook_max_retries,
+            transaction_service,
+        }))
+    }
+
+    pub async fn run(mut self, mut shutdown_signal: ShutdownSignal) {
+        let mut event_stream = self.transaction_service.get_event_stream();
+        info!(
+            target: LOG_TARGET,
+            "Webhook notifier started for {} URL(s)",
+            self.urls.len()
+        );
+        loop {
+            tokio::select! {
+                result = event_stream.recv() => match result {
+                    Ok(event) => {
+                        if let Some(payload) = self.payload_for_event(&event).await {
+                            self.notify(payload);
+                        }
+                    },
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Webhook notifier missed {} transaction events", n);
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown_signal.wait() => break,
+            }
+        }
+        info!(target: LOG_TARGET, "Webhook notifier shutting down");
+    }
+
+    async fn payload_for_event(&mut self, event: &TransactionEvent) -> Option<Value> {
+        match event {
+            TransactionEvent::ReceivedFinalizedTransaction(tx_id) | TransactionEvent::TransactionImported(tx_id) => {
+                self.transaction_payload(RECEIVED, *tx_id, None).await
+            },
+            TransactionEvent::TransactionMined { tx_id, .. } |
+            TransactionEvent::DetectedTransactionConfirmed { tx_id, .. } => {
+                self.transaction_payload(MINED, *tx_id, None).await
+            },
+            TransactionEvent::TransactionCancelled(tx_id, reason) => {
+                self.transaction_payload(CANCELLED, *tx_id, Some(reason)).await
+            },
+            TransactionEvent::TransactionReorgedOut(tx_id) => self.transaction_payload(REORGED_OUT, *tx_id, None).await,
+            TransactionEvent::TransactionValidationCompleted(operation_id) => Some(json!({
+                "event": VALIDATION_COMPLETE,
+                "timestamp": unix_timestamp(),
+                "operation_id": operation_id.as_u64(),
+            })),
+            _ => None,
+        }
+    }
+
+    async fn transaction_payload(
+        &mut self,
+        event: &str,
+        tx_id: TxId,
+        cancellation_reason: Option<&TxCancellationReason>,
+    ) -> Option<Value> {
+        let transaction = match self.transaction_service.get_any_transaction(tx_id).await {
+            Ok(Some(WalletTransaction::Completed(tx))) => json!({
+                "source_address": tx.source_address.to_hex(),
+                "destination_address": tx.destination_address.to_hex(),
+                "amount": tx.amount.as_u64(),
+                "fee": tx.fee.as_u64(),
+                "status": tx.status.to_string(),
+                "direction": tx.direction.to_string(),
+                "message": tx.message,
+                "mined_height": tx.mined_height,
+                "mined_in_block": tx.mined_in_block.map(|hash| hash.to_hex()),
+                "excess": tx.transaction.body.kernels().first().map(|kernel| kernel.excess.to_hex()),
+            }),
+            Ok(Some(WalletTransaction::PendingInbound(tx))) => json!({
+                "source_address": tx.source_address.to_hex(),
+                "amount": tx.amount.as_u64(),
+                "status": tx.status.to_string(),
+                "direction": "inbound",
+                "message": tx.message,
+            }),
+            Ok(Some(WalletTransaction::PendingOutbound(tx))) => json!({
+                "destination_address": tx.destination_address.to_hex(),
+                "amount": tx.amount.as_u64(),
+                "fee": tx.fee.as_u64(),
+                "status": tx.status.to_string(),
+                "direction": "outbound",
+                "message": tx.message,
+            }),
+            Ok(None) => {
+                warn!(target: LOG_TARGET, "Transaction not found for webhook tx_id: {}", tx_id);
+                return None;
+            },
+            Err(e) => {
+                error!(target: LOG_TARGET, "Transaction service error: {}", e);
+                return None;
+            },
+        };
+
+        Some(json!({
+            "event": event,
+            "timestamp": unix_timestamp(),
+            "tx_id": tx_id.as_u64(),
+            "cancellation_reason": cancellation_reason.map(|reason| reason.to_string()),
+            "transaction": transaction,
+        }))
+    }
+
+    /// Deliver the payload to every configured URL in the background, so that a slow endpoint does not hold up the
+    /// processing of further events
+    fn notify(&self, payload: Value) {
+        let body = payload.to_string();
+        let signature = sign_payload(&self.secret, body.as_bytes());
+        for url in &self.urls {
+            tokio::spawn(deliver(
+                self.client.clone(),
+                url.clone(),
+                body.clone(),
+                signature.clone(),
+                self.max_retries,
+            ));
+        }
+    }
+}
+
+/// POST the body to the URL, retrying with exponential backoff until it is accepted or `max_retries` is exhausted
+async fn deliver(client: Client, url: Url, body: String, signature: String, max_retries: usize) {
+    let mut retry_delay = INITIAL_RETRY_DELAY;
+    for attempt in 0..=max_retries {
+        let result = client
+            .post(url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature.as_str())
+            .timeout(REQUEST_TIMEOUT)
+            .body(body.clone())
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => {
+                debug!(target: LOG_TARGET, "Webhook delivered to {}", url);
+                return;
+            },
+            Ok(response) => warn!(
+                target: LOG_TARGET,
+                "Webhook {} responded with status {} (attempt {} of {})",
+                url,
+                response.status(),
+                attempt + 1,
+                max_retries + 1
+            ),
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Webhook delivery to {} failed: {} (attempt {} of {})",
+                url,
+                e,
+                attempt + 1,
+                max_retries + 1
+            ),
+        }
+        if attempt < max_retries {
+            sleep(retry_delay).await;
+            retry_delay = min(retry_delay * 2, MAX_RETRY_DELAY);
+        }
+    }
+    error!(
+        target: LOG_TARGET,
+        "Dropping webhook notification to {} after {} attempts",
+        url,
+        max_retries + 1
+    );
+}
+
+/// The configured webhook secret, which must not be empty
+fn webhook_secret(config: &WalletConfig) -> Result<Vec<u8>, String> {
+    match config.webhook_secret.as_ref().map(|secret| secret.reveal().to_vec()) {
+        Some(secret) if !secret.is_empty() => Ok(secret),
+        _ => Err(
+            "Webhook URLs are configured but `webhook_secret` is not set, refusing to send unsigned webhook \
+             notifications"
+                .to_string(),
+        ),
+    }
+}
+
+/// The hex encoded HMAC-SHA256 of the payload, keyed with the webhook secret
+fn sign_payload(secret: &[u8], payload: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac.finalize().into_bytes().to_vec().to_hex()
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use tari_utilities::SafePassword;
+
+    use super::*;
+
+    #[test]
+    fn it_signs_payloads_with_hmac_sha256() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn it_requires_a_webhook_secret() {
+        let mut config = WalletConfig::default();
+        assert!(webhook_secret(&config).is_err());
+        config.webhook_secret = Some(SafePassword::from(""));
+        assert!(webhook_secret(&config).is_err());
+        config.webhook_secret = Some(SafePassword::from("secret"));
+        assert_eq!(webhook_secret(&config).unwrap(), b"secret".to_vec());
+    }
+}
@@ -94,6 +94,7 @@ pub async fn wallet_recovery(
     wallet: &WalletSqlite,
     base_node_config: &PeerConfig,
     retry_limit: usize,
+    subaddress_lookahead: u64,
 ) -> Result<(), ExitError> {
     println!("\nPress Ctrl-C to stop the recovery process\n");
     // We dont care about the shutdown signal here, so we just create one
@@ -122,6 +123,7 @@ pub async fn wallet_recovery(
         .with_peers(peer_public_keys)
         // Do not make this a small number as wallet recovery needs to be resilient
         .with_retry_limit(retry_limit)
+        .with_subaddress_lookahead(subaddress_lookahead)
         .build_with_wallet(wallet, shutdown_signal);
 
     let mut event_stream = recovery_task.get_event_receiver();
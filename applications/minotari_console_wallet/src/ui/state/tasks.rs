@@ -151,6 +151,8 @@ pub async fn send_one_sided_transaction_task(
             output_features,
             fee_per_gram,
             message,
+            Vec::new(),
+            Vec::new(),
         )
         .await
     {
@@ -205,6 +207,8 @@ pub async fn send_one_sided_to_stealth_address_transaction(
             output_features,
             fee_per_gram,
             message,
+            Vec::new(),
+            Vec::new(),
         )
         .await
     {
@@ -238,8 +238,13 @@ impl WalletEventMonitor {
                     match result {
                         Ok(msg) => {
                             trace!(target: LOG_TARGET, "Output Manager Service Callback Handler event {:?}", msg);
-                            if let OutputManagerEvent::TxoValidationSuccess(_) = &*msg {
-                                self.trigger_balance_refresh();
+                            match &*msg {
+                                OutputManagerEvent::TxoValidationSuccess(_) => self.trigger_balance_refresh(),
+                                OutputManagerEvent::UtxoConsolidationSubmitted(tx_id) => {
+                                    self.trigger_balance_refresh();
+                                    self.add_notification(format!("UTXO consolidation submitted - TxId: {}", tx_id)).await;
+                                },
+                                _ => (),
                             }
                         },
                         Err(broadcast::error::RecvError::Lagged(n)) => {
@@ -26,20 +26,27 @@ use std::{fs, io::Stdout, path::PathBuf};
 
 use clap::Parser;
 use log::*;
-use minotari_app_grpc::{authentication::ServerAuthenticationInterceptor, tls::identity::read_identity};
+use minotari_app_grpc::{
+    authentication::{ScopedAuthenticationService, ServerAuthenticationInterceptor},
+    tls::{
+        client_auth::{server_tls_config, ClientCertificateService},
+        error::GrpcTlsError,
+        identity::{read_client_ca, read_identity},
+    },
+};
 use minotari_wallet::{WalletConfig, WalletSqlite};
 use rand::{rngs::OsRng, seq::SliceRandom};
 use tari_common::exit_codes::{ExitCode, ExitError};
-use tari_common_types::grpc_authentication::GrpcAuthentication;
+use tari_common_types::grpc_authentication::{GrpcAuthentication, GrpcClientCertificate, GrpcScopedToken};
 use tari_comms::{multiaddr::Multiaddr, peer_manager::Peer, utils::multiaddr::multiaddr_to_socketaddr};
 use tokio::{runtime::Handle, sync::broadcast};
-use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic::transport::{Server, ServerTlsConfig};
 use tui::backend::CrosstermBackend;
 
 use crate::{
     automation::commands::command_runner,
     cli::{Cli, CliCommands},
-    grpc::WalletGrpcServer,
+    grpc::{required_scope, WalletGrpcServer},
     notifier::Notifier,
     recovery::wallet_recovery,
     ui,
@@ -276,24 +283,16 @@ pub fn tui_mode(
                 exit_code: ExitCode::UnknownError,
                 details: Some(e.to_string()),
             })?;
-
-            let mut tls_identity = None;
-            if config.grpc_tls_enabled {
-                match handle
-                    .block_on(read_identity(config.config_dir.clone()))
-                    .map(Some)
-                    .map_err(|e| ExitError::new(ExitCode::TlsConfigurationError, e.to_string()))
-                {
-                    Ok(identity) => tls_identity = identity,
-                    Err(e) => return Err(e),
-                }
-            }
+            handle.spawn(grpc.atomic_swap_updates());
+            let (tls_config, client_certificates) = read_grpc_tls_config(&handle, config)?;
 
             handle.spawn(run_grpc(
                 grpc,
                 address,
                 config.grpc_authentication.clone(),
-                tls_identity,
+                config.grpc_scoped_tokens.clone(),
+                tls_config,
+                client_certificates,
                 wallet.clone(),
             ));
         }
@@ -366,6 +365,7 @@ pub fn recovery_mode(
         &wallet,
         base_node_config,
         wallet_config.recovery_retry_limit,
+        wallet_config.subaddress_recovery_lookahead,
     )) {
         Ok(_) => println!("Wallet recovered!"),
         Err(e) => {
@@ -404,22 +404,21 @@ pub fn grpc_mode(handle: Handle, config: &WalletConfig, wallet: WalletSqlite) ->
                 exit_code: ExitCode::UnknownError,
                 details: Some(e.to_string()),
             })?;
+            handle.spawn(grpc.atomic_swap_updates());
             let auth = config.grpc_authentication.clone();
-
-            let mut tls_identity = None;
-            if config.grpc_tls_enabled {
-                match handle
-                    .block_on(read_identity(config.config_dir.clone()))
-                    .map(Some)
-                    .map_err(|e| ExitError::new(ExitCode::TlsConfigurationError, e.to_string()))
-                {
-                    Ok(identity) => tls_identity = identity,
-                    Err(e) => return Err(e),
-                }
-            }
+            let scoped_tokens = config.grpc_scoped_tokens.clone();
+            let (tls_config, client_certificates) = read_grpc_tls_config(&handle, config)?;
 
             handle
-                .block_on(run_grpc(grpc, address, auth, tls_identity, wallet))
+                .block_on(run_grpc(
+                    grpc,
+                    address,
+                    auth,
+                    scoped_tokens,
+                    tls_config,
+                    client_certificates,
+                    wallet,
+                ))
                 .map_err(|e| ExitError::new(ExitCode::GrpcError, e))?;
         }
         #[cfg(not(feature = "grpc"))]
@@ -434,11 +433,37 @@ pub fn grpc_mode(handle: Handle, config: &WalletConfig, wallet: WalletSqlite) ->
     Ok(())
 }
 
+/// Reads the gRPC server TLS configuration and the TLS client certificates that may call the server
+fn read_grpc_tls_config(
+    handle: &Handle,
+    config: &WalletConfig,
+) -> Result<(Option<ServerTlsConfig>, Vec<GrpcClientCertificate>), ExitError> {
+    if !config.grpc_tls_enabled {
+        return Ok((None, Vec::new()));
+    }
+    let tls_error = |e: GrpcTlsError| ExitError::new(ExitCode::TlsConfigurationError, e.to_string());
+    let identity = handle
+        .block_on(read_identity(config.config_dir.clone()))
+        .map_err(tls_error)?;
+    if !config.grpc_tls_client_auth_enabled {
+        return Ok((Some(server_tls_config(identity, None)), Vec::new()));
+    }
+    let client_ca = handle
+        .block_on(read_client_ca(config.config_dir.clone()))
+        .map_err(tls_error)?;
+    Ok((
+        Some(server_tls_config(identity, Some(client_ca))),
+        config.grpc_tls_client_certificates.clone(),
+    ))
+}
+
 async fn run_grpc(
     grpc: WalletGrpcServer,
     grpc_listener_addr: Multiaddr,
     auth_config: GrpcAuthentication,
-    tls_identity: Option<Identity>,
+    scoped_tokens: Vec<GrpcScopedToken>,
+    tls_config: Option<ServerTlsConfig>,
+    client_certificates: Vec<GrpcClientCertificate>,
     wallet: WalletSqlite,
 ) -> Result<(), String> {
     // Do not remove this println!
@@ -449,12 +474,18 @@ async fn run_grpc(
     let address = multiaddr_to_socketaddr(&grpc_listener_addr).map_err(|e| e.to_string())?;
     let auth = ServerAuthenticationInterceptor::new(auth_config)
         .ok_or("Unable to prepare server gRPC authentication".to_string())?;
-    let service = minotari_app_grpc::tari_rpc::wallet_server::WalletServer::with_interceptor(grpc, auth);
+    let service = ClientCertificateService::new(
+        ScopedAuthenticationService::new(
+            minotari_app_grpc::tari_rpc::wallet_server::WalletServer::new(grpc),
+            auth,
+            scoped_tokens,
+            required_scope,
+        ),
+        client_certificates,
+    );
 
-    let mut server_builder = if let Some(identity) = tls_identity {
-        Server::builder()
-            .tls_config(ServerTlsConfig::new().identity(identity))
-            .map_err(|e| e.to_string())?
+    let mut server_builder = if let Some(tls_config) = tls_config {
+        Server::builder().tls_config(tls_config).map_err(|e| e.to_string())?
     } else {
         Server::builder()
     };
@@ -541,6 +572,11 @@ mod test {
                         import_tx = true
                     }
                 },
+                CliCommands::ExportTransactionHistory(_) => {},
+                CliCommands::ImportTransactionHistory(_) => {},
+                CliCommands::CreateUnsigned(_) => {},
+                CliCommands::SignOffline(_) => {},
+                CliCommands::BroadcastSigned(_) => {},
                 CliCommands::ExportSpentUtxos(_) => {},
                 CliCommands::CountUtxos => {},
                 CliCommands::SetBaseNode(_) => {},
@@ -552,6 +588,7 @@ mod test {
                 CliCommands::RevalidateWalletDb => {},
                 CliCommands::RegisterValidatorNode(_) => {},
                 CliCommands::CreateTlsCerts => {},
+                CliCommands::Db(_) => {},
             }
         }
         assert!(
@@ -352,6 +352,7 @@ pub mod test {
             target_difficulty: 600000,
             total_fees: 100,
             algo: Some(grpc::PowAlgo { pow_algo: 0 }),
+            coinbase_extra_max_length: 64,
         };
         let new_block_template = grpc::NewBlockTemplate::default();
         let btdb = BlockTemplateDataBuilder::new()
@@ -420,6 +421,7 @@ pub mod test {
             target_difficulty: 600000,
             total_fees: 100,
             algo: Some(grpc::PowAlgo { pow_algo: 0 }),
+            coinbase_extra_max_length: 64,
         };
         let btdb = BlockTemplateDataBuilder::new()
             .monero_seed(FixedByteArray::new())
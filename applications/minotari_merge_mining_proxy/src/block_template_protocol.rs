@@ -272,6 +272,7 @@ impl BlockTemplateProtocol<'_> {
             miner_data,
             new_block_template: template,
             initial_sync_achieved,
+            template_version: _,
         } = self
             .base_node_client
             .get_new_block_template(grpc::NewBlockTemplateRequest {
@@ -279,6 +280,7 @@ impl BlockTemplateProtocol<'_> {
                     pow_algo: grpc::pow_algo::PowAlgos::Randomx.into(),
                 }),
                 max_weight: 0,
+                template_version: 0,
             })
             .await
             .map_err(|status| MmProxyError::GrpcRequestError {
@@ -20,12 +20,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use minotari_wallet_grpc_client::GrpcAuthentication;
 use serde::{Deserialize, Serialize};
 use tari_common::{
-    configuration::{Network, StringList},
+    configuration::{serializers, Network, StringList},
     SubConfigPath,
 };
 use tari_common_types::tari_address::TariAddress;
@@ -56,6 +59,12 @@ pub struct MergeMiningProxyConfig {
     pub monerod_password: String,
     /// If authentication is being used for curl
     pub monerod_use_auth: bool,
+    /// The HTTP authentication scheme used when `monerod_use_auth` is set. Monerod started with `--rpc-login` requires
+    /// digest authentication.
+    pub monerod_auth_type: MonerodAuthType,
+    /// Path to a PEM encoded CA certificate that is trusted in addition to the system roots when connecting to monerod
+    /// over TLS, e.g. for a remote node with a self-signed certificate
+    pub monerod_tls_ca_cert_filename: Option<PathBuf>,
     /// The Minotari base node's GRPC address
     pub base_node_grpc_address: Option<Multiaddr>,
     /// GRPC authentication for base node
@@ -94,6 +103,21 @@ pub struct MergeMiningProxyConfig {
     pub stealth_payment: bool,
     /// Range proof type - revealed_value or bullet_proof_plus: (default = revealed_value)
     pub range_proof_type: RangeProofType,
+    /// Address of the Stratum listener that RandomX miners (e.g. XMRig) can connect to directly. The Stratum server is
+    /// disabled if this is not set.
+    pub stratum_listener_address: Option<Multiaddr>,
+    /// The Monero wallet address that the Monero block reward is paid to when mining via the Stratum server
+    pub stratum_monero_wallet_address: String,
+    /// The share difficulty assigned to a new Stratum connection
+    pub stratum_initial_difficulty: u64,
+    /// The minimum share difficulty that variable difficulty will assign to a Stratum connection
+    pub stratum_min_difficulty: u64,
+    /// The average time between shares that variable difficulty aims for on each Stratum connection
+    #[serde(with = "serializers::seconds")]
+    pub stratum_target_share_time: Duration,
+    /// How often a new block template is requested for Stratum jobs
+    #[serde(with = "serializers::seconds")]
+    pub stratum_job_refresh_interval: Duration,
 }
 
 impl Default for MergeMiningProxyConfig {
@@ -106,6 +130,8 @@ impl Default for MergeMiningProxyConfig {
             monerod_username: String::new(),
             monerod_password: String::new(),
             monerod_use_auth: false,
+            monerod_auth_type: MonerodAuthType::default(),
+            monerod_tls_ca_cert_filename: None,
             base_node_grpc_address: None,
             base_node_grpc_authentication: GrpcAuthentication::default(),
             base_node_grpc_tls_domain_name: None,
@@ -121,6 +147,12 @@ impl Default for MergeMiningProxyConfig {
             wallet_payment_address: TariAddress::default().to_hex(),
             stealth_payment: true,
             range_proof_type: RangeProofType::RevealedValue,
+            stratum_listener_address: None,
+            stratum_monero_wallet_address: String::new(),
+            stratum_initial_difficulty: 10_000,
+            stratum_min_difficulty: 1_000,
+            stratum_target_share_time: Duration::from_secs(15),
+            stratum_job_refresh_interval: Duration::from_secs(5),
         }
     }
 }
@@ -133,6 +165,15 @@ impl MergeMiningProxyConfig {
     }
 }
 
+/// The HTTP authentication scheme used for monerod requests
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonerodAuthType {
+    #[default]
+    Basic,
+    Digest,
+}
+
 impl SubConfigPath for MergeMiningProxyConfig {
     fn main_key_prefix() -> &'static str {
         "merge_mining_proxy"
@@ -146,7 +187,7 @@ mod test {
     use tari_common::DefaultConfigLoader;
     use tari_comms::multiaddr::Multiaddr;
 
-    use crate::config::MergeMiningProxyConfig;
+    use crate::config::{MergeMiningProxyConfig, MonerodAuthType};
 
     fn get_config(override_from: &str) -> config::Config {
         let s = r#"
@@ -162,6 +203,7 @@ mod test {
               submit_to_origin = false
               monerod_url = [ "http://network.b.org" ]
               monerod_password = "password_stagenet"
+              monerod_auth_type = "digest"
               base_node_grpc_address = "/dns4/base_node_b/tcp/8080"
             "#;
 
@@ -181,6 +223,7 @@ mod test {
         assert!(!config.submit_to_origin);
         assert_eq!(config.monerod_username.as_str(), "cmot");
         assert_eq!(config.monerod_password.as_str(), "password_stagenet");
+        assert_eq!(config.monerod_auth_type, MonerodAuthType::Digest);
         assert_eq!(
             config.base_node_grpc_address,
             Some(Multiaddr::from_str("/dns4/base_node_b/tcp/8080").unwrap())
@@ -192,6 +235,7 @@ mod test {
         assert!(config.submit_to_origin);
         assert_eq!(config.monerod_username.as_str(), "cmot");
         assert_eq!(config.monerod_password.as_str(), "password_igor");
+        assert_eq!(config.monerod_auth_type, MonerodAuthType::Basic);
         assert_eq!(
             config.base_node_grpc_address,
             Some(Multiaddr::from_str("/dns4/base_node_a/tcp/8080").unwrap())
@@ -117,6 +117,8 @@ pub enum MmProxyError {
     UnexpectedMissingData(String),
     #[error("Failed to get block template: {0}")]
     FailedToGetBlockTemplate(String),
+    #[error("Stratum error: {0}")]
+    StratumError(String),
 }
 
 impl From<tonic::Status> for MmProxyError {
@@ -32,8 +32,10 @@ mod config;
 mod error;
 mod proxy;
 mod run_merge_miner;
+mod stratum;
 use run_merge_miner::start_merge_miner;
 mod monero_fail;
+mod monerod_auth;
 
 pub async fn merge_miner(cli: Cli) -> Result<(), anyhow::Error> {
     start_merge_miner(cli).await
@@ -29,8 +29,10 @@ mod common;
 mod config;
 mod error;
 mod monero_fail;
+mod monerod_auth;
 mod proxy;
 mod run_merge_miner;
+mod stratum;
 
 #[cfg(test)]
 mod test;
@@ -0,0 +1,264 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! HTTP digest access authentication (RFC 7616, MD5 with `qop=auth`) as used by monerod's `--rpc-login`.
+
+use std::sync::Mutex;
+
+use md5::{Digest, Md5};
+use reqwest::{
+    header::{HeaderMap, WWW_AUTHENTICATE},
+    Method,
+    Url,
+};
+
+/// A digest challenge sent by the server in a `WWW-Authenticate` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub qop_auth: bool,
+}
+
+impl DigestChallenge {
+    /// Parse a `WWW-Authenticate` header value. Returns `None` if it is not a digest challenge that can be answered,
+    /// i.e. one that uses an algorithm other than MD5 or does not offer `qop=auth` when a qop is given.
+    pub fn parse(header: &str) -> Option<Self> {
+        let header = header.trim();
+        let (scheme, params) = header.split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+
+        let mut realm = None;
+        let mut nonce = None;
+        let mut opaque = None;
+        let mut qop = None;
+        for (key, value) in split_params(params) {
+            match key.to_ascii_lowercase().as_str() {
+                "realm" => realm = Some(value),
+                "nonce" => nonce = Some(value),
+                "opaque" => opaque = Some(value),
+                "qop" => qop = Some(value),
+                "algorithm" => {
+                    if !value.eq_ignore_ascii_case("md5") {
+                        return None;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        let qop_auth = match qop {
+            Some(qop) => {
+                if !qop.split(',').any(|q| q.trim().eq_ignore_ascii_case("auth")) {
+                    return None;
+                }
+                true
+            },
+            None => false,
+        };
+
+        Some(Self {
+            realm: realm?,
+            nonce: nonce?,
+            opaque,
+            qop_auth,
+        })
+    }
+
+    /// Find the first digest challenge that can be answered in the given response headers. Monerod sends one
+    /// challenge per supported algorithm.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(Self::parse)
+    }
+}
+
+/// Splits the comma separated `key=value` parameters of a challenge, allowing commas inside quoted values
+fn split_params(params: &str) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut in_quotes = false;
+    for c in params.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '=' if !in_value && !in_quotes => in_value = true,
+            ',' if !in_quotes => {
+                if in_value {
+                    result.push((key.trim().to_string(), value.trim().to_string()));
+                }
+                key.clear();
+                value.clear();
+                in_value = false;
+            },
+            c if in_value => value.push(c),
+            c => key.push(c),
+        }
+    }
+    if in_value {
+        result.push((key.trim().to_string(), value.trim().to_string()));
+    }
+    result
+}
+
+/// Digest authentication state for a monerod connection. The last challenge is cached so that subsequent requests can
+/// be authenticated up front instead of first receiving a `401 Unauthorized`.
+#[derive(Debug)]
+pub struct MonerodDigestAuth {
+    username: String,
+    password: String,
+    state: Mutex<Option<(DigestChallenge, u32)>>,
+}
+
+impl MonerodDigestAuth {
+    pub fn new(username: String, password: String) -> Self {
+        Self {
+            username,
+            password,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Store a new challenge from a `401 Unauthorized` response, resetting the nonce count. Returns false if the
+    /// response did not contain a challenge that can be answered.
+    pub fn update_challenge(&self, headers: &HeaderMap) -> bool {
+        match DigestChallenge::from_headers(headers) {
+            Some(challenge) => {
+                *self.state.lock().expect("Lock should not be poisoned") = Some((challenge, 0));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Returns the `Authorization` header value for a request, or `None` if no challenge has been received yet
+    pub fn authorization(&self, method: &Method, url: &Url) -> Option<String> {
+        let mut state = self.state.lock().expect("Lock should not be poisoned");
+        let (challenge, nonce_count) = state.as_mut()?;
+        *nonce_count += 1;
+        let cnonce = format!("{:016x}", rand::random::<u64>());
+        let uri = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        Some(self.authorization_with_cnonce(challenge, *nonce_count, &cnonce, method, &uri))
+    }
+
+    fn authorization_with_cnonce(
+        &self,
+        challenge: &DigestChallenge,
+        nonce_count: u32,
+        cnonce: &str,
+        method: &Method,
+        uri: &str,
+    ) -> String {
+        let ha1 = md5_hex(&format!("{}:{}:{}", self.username, challenge.realm, self.password));
+        let ha2 = md5_hex(&format!("{}:{}", method.as_str(), uri));
+        let nc = format!("{:08x}", nonce_count);
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm=MD5"#,
+            self.username, challenge.realm, challenge.nonce, uri
+        );
+        if challenge.qop_auth {
+            let response = md5_hex(&format!("{}:{}:{}:{}:auth:{}", ha1, challenge.nonce, nc, cnonce, ha2));
+            header.push_str(&format!(
+                r#", qop=auth, nc={}, cnonce="{}", response="{}""#,
+                nc, cnonce, response
+            ));
+        } else {
+            let response = md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, ha2));
+            header.push_str(&format!(r#", response="{}""#, response));
+        }
+        if let Some(opaque) = &challenge.opaque {
+            header.push_str(&format!(r#", opaque="{}""#, opaque));
+        }
+        header
+    }
+}
+
+fn md5_hex(data: &str) -> String {
+    hex::encode(Md5::digest(data.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_parses_a_monerod_challenge() {
+        let challenge =
+            DigestChallenge::parse(r#"Digest qop="auth,auth-int", realm="monero-rpc", nonce="abc,def", stale=false"#)
+                .unwrap();
+        assert_eq!(challenge, DigestChallenge {
+            realm: "monero-rpc".to_string(),
+            nonce: "abc,def".to_string(),
+            opaque: None,
+            qop_auth: true,
+        });
+
+        assert!(DigestChallenge::parse(r#"Digest realm="r", nonce="n", algorithm=MD5-sess"#).is_none());
+        assert!(DigestChallenge::parse(r#"Basic realm="r""#).is_none());
+    }
+
+    #[test]
+    fn it_computes_the_rfc_2617_response() {
+        // Example from RFC 2617 section 3.5
+        let auth = MonerodDigestAuth::new("Mufasa".to_string(), "Circle Of Life".to_string());
+        let challenge = DigestChallenge {
+            realm: "testrealm@host.com".to_string(),
+            nonce: "dcd98b7102dd2f0e8b11d0f600bfb0c093".to_string(),
+            opaque: Some("5ccc069c403ebaf9f0171e9517f40e41".to_string()),
+            qop_auth: true,
+        };
+        let header = auth.authorization_with_cnonce(&challenge, 1, "0a4f113b", &Method::GET, "/dir/index.html");
+        assert!(header.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(header.contains("nc=00000001"));
+        assert!(header.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+    }
+
+    #[test]
+    fn it_increments_the_nonce_count() {
+        let auth = MonerodDigestAuth::new("user".to_string(), "pass".to_string());
+        let url = Url::parse("http://localhost:18081/json_rpc").unwrap();
+        assert!(auth.authorization(&Method::POST, &url).is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            WWW_AUTHENTICATE,
+            r#"Digest qop="auth", realm="monero-rpc", nonce="n""#.parse().unwrap(),
+        );
+        assert!(auth.update_challenge(&headers));
+        assert!(auth.authorization(&Method::POST, &url).unwrap().contains("nc=00000001"));
+        assert!(auth.authorization(&Method::POST, &url).unwrap().contains("nc=00000002"));
+        assert!(auth
+            .authorization(&Method::POST, &url)
+            .unwrap()
+            .contains(r#"uri="/json_rpc""#));
+    }
+}
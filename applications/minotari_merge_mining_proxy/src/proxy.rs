@@ -41,12 +41,17 @@ use json::json;
 use jsonrpc::error::StandardError;
 use minotari_app_utilities::parse_miner_input::BaseNodeGrpcClient;
 use minotari_node_grpc_client::grpc;
-use reqwest::{ResponseBuilderExt, Url};
+use reqwest::{header::AUTHORIZATION, RequestBuilder, ResponseBuilderExt, Url};
 use serde_json as json;
 use tari_common_types::tari_address::TariAddress;
 use tari_core::{
     consensus::ConsensusManager,
-    proof_of_work::{monero_rx, monero_rx::FixedByteArray, randomx_difficulty, randomx_factory::RandomXFactory},
+    proof_of_work::{
+        monero_rx,
+        monero_rx::{FixedByteArray, RandomXPreWarmer},
+        randomx_difficulty,
+        randomx_factory::RandomXFactory,
+    },
 };
 use tari_utilities::hex::Hex;
 use tokio::time::timeout;
@@ -56,8 +61,9 @@ use crate::{
     block_template_data::BlockTemplateRepository,
     block_template_protocol::{BlockTemplateProtocol, MoneroMiningData},
     common::{json_rpc, monero_rpc::CoreRpcErrorCode, proxy, proxy::convert_json_to_hyper_json_response},
-    config::MergeMiningProxyConfig,
+    config::{MergeMiningProxyConfig, MonerodAuthType},
     error::MmProxyError,
+    monerod_auth::MonerodDigestAuth,
 };
 
 const LOG_TARGET: &str = "minotari_mm_proxy::proxy";
@@ -82,15 +88,24 @@ impl MergeMiningProxyService {
     ) -> Result<Self, MmProxyError> {
         debug!(target: LOG_TARGET, "Config: {:?}", config);
         let consensus_manager = ConsensusManager::builder(config.network).build()?;
+        let monerod_digest_auth = (config.monerod_use_auth && config.monerod_auth_type == MonerodAuthType::Digest)
+            .then(|| {
+                Arc::new(MonerodDigestAuth::new(
+                    config.monerod_username.clone(),
+                    config.monerod_password.clone(),
+                ))
+            });
         Ok(Self {
             inner: InnerService {
                 config: Arc::new(config),
                 block_templates,
                 http_client,
+                monerod_digest_auth,
                 base_node_client,
                 initial_sync_achieved: Arc::new(AtomicBool::new(false)),
                 current_monerod_server: Arc::new(RwLock::new(None)),
                 last_assigned_monerod_server: Arc::new(RwLock::new(None)),
+                randomx_pre_warmer: RandomXPreWarmer::spawn(randomx_factory.clone()),
                 randomx_factory,
                 consensus_manager,
                 wallet_payment_address,
@@ -159,11 +174,13 @@ struct InnerService {
     config: Arc<MergeMiningProxyConfig>,
     block_templates: BlockTemplateRepository,
     http_client: reqwest::Client,
+    monerod_digest_auth: Option<Arc<MonerodDigestAuth>>,
     base_node_client: BaseNodeGrpcClient,
     initial_sync_achieved: Arc<AtomicBool>,
     current_monerod_server: Arc<RwLock<Option<String>>>,
     last_assigned_monerod_server: Arc<RwLock<Option<String>>>,
     randomx_factory: RandomXFactory,
+    randomx_pre_warmer: RandomXPreWarmer,
     consensus_manager: ConsensusManager,
     wallet_payment_address: TariAddress,
 }
@@ -243,7 +260,8 @@ impl InnerService {
         };
 
         for param in params.iter().filter_map(|p| p.as_str()) {
-            let monero_block = monero_rx::deserialize_monero_block_from_hex(param)?;
+            let monero_block =
+                monero_rx::deserialize_monero_block_from_hex_strict(param, &monero_rx::MoneroDecodeLimits::default())?;
             debug!(target: LOG_TARGET, "Monero block: {}", monero_block);
             let hash = monero_rx::extract_aux_merkle_root_from_block(&monero_block)?.ok_or_else(|| {
                 MmProxyError::MissingDataError("Could not find Minotari header in coinbase".to_string())
@@ -457,8 +475,9 @@ impl InnerService {
         )
         .await?;
 
-        let seed_hash = FixedByteArray::from_hex(&monerod_resp["result"]["seed_hash"].to_string().replace('\"', ""))
+        let seed_hash = json::from_value::<FixedByteArray>(monerod_resp["result"]["seed_hash"].clone())
             .map_err(|err| MmProxyError::InvalidMonerodResponse(format!("seed hash hex is invalid: {}", err)))?;
+        self.pre_warm_next_seed_hash(&monerod_resp);
         let blocktemplate_blob = monerod_resp["result"]["blocktemplate_blob"]
             .to_string()
             .replace('\"', "");
@@ -508,6 +527,33 @@ impl InnerService {
         Ok(proxy::into_response(parts, &monerod_resp))
     }
 
+    /// monerod includes the next RandomX seed hash in the block template when the seed hash is about to change, so
+    /// that the VM for the next epoch can be initialized before the first block of that epoch is submitted.
+    fn pre_warm_next_seed_hash(&self, monerod_resp: &json::Value) {
+        let Some(next_seed_hash) = monerod_resp["result"]["next_seed_hash"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+        else {
+            return;
+        };
+        if monerod_resp["result"]["seed_hash"].as_str() == Some(next_seed_hash) {
+            return;
+        }
+        let Ok(next_seed_hash) = next_seed_hash.parse::<FixedByteArray>() else {
+            warn!(target: LOG_TARGET, "monerod returned an invalid next seed hash: {}", next_seed_hash);
+            return;
+        };
+        if let Some(height) = monerod_resp["result"]["height"].as_u64() {
+            debug!(
+                target: LOG_TARGET,
+                "RandomX seed hash changes in {} block(s), pre-warming VM for seed hash {}",
+                monero_rx::blocks_until_seed_change(height),
+                next_seed_hash
+            );
+        }
+        self.randomx_pre_warmer.pre_warm(next_seed_hash.as_slice());
+    }
+
     async fn handle_get_block_header_by_hash(
         &self,
         request: Request<json::Value>,
@@ -655,7 +701,7 @@ impl InnerService {
         for next_url in iter {
             let uri = format!("{}{}", next_url, uri.path()).parse::<Url>()?;
             debug!(target: LOG_TARGET, "Trying to connect to Monerod server at: {}", uri.as_str());
-            match timeout(Duration::from_secs(10), reqwest::get(uri.clone())).await {
+            match timeout(Duration::from_secs(10), self.http_client.get(uri.clone()).send()).await {
                 Ok(_) => {
                     let mut lock = self.current_monerod_server.write().expect("Write lock should not fail");
                     *lock = Some(next_url.to_string());
@@ -702,7 +748,7 @@ impl InnerService {
             .request(request.method().clone(), monerod_uri.clone())
             .headers(headers);
 
-        if self.config.monerod_use_auth {
+        if self.config.monerod_use_auth && self.monerod_digest_auth.is_none() {
             // Use HTTP basic auth. This is the only reason we are using `reqwest` over the standard hyper client.
             builder = builder.basic_auth(&self.config.monerod_username, Some(&self.config.monerod_password));
         }
@@ -761,12 +807,10 @@ impl InnerService {
 
             convert_json_to_hyper_json_response(accept_response, StatusCode::OK, monerod_uri.clone()).await?
         } else {
-            let resp = builder
-                // This is a cheap clone of the request body
-                .body(body)
-                .send()
-                .await
-                .map_err(MmProxyError::MonerodRequestFailed)?;
+            // This is a cheap clone of the request body
+            let resp = self
+                .send_to_monerod(builder.body(body), request.method(), &monerod_uri)
+                .await?;
             convert_reqwest_response_to_hyper_json_response(resp).await?
         };
 
@@ -786,6 +830,44 @@ impl InnerService {
         Ok((request, json_response))
     }
 
+    /// Send a request to monerod. With digest auth, the request is sent with the cached challenge (if any) and is
+    /// retried once with a fresh challenge if monerod responds with `401 Unauthorized`.
+    async fn send_to_monerod(
+        &self,
+        builder: RequestBuilder,
+        method: &Method,
+        monerod_uri: &Url,
+    ) -> Result<reqwest::Response, MmProxyError> {
+        let Some(digest_auth) = self.monerod_digest_auth.as_ref() else {
+            return builder.send().await.map_err(MmProxyError::MonerodRequestFailed);
+        };
+        let retry = builder.try_clone();
+        let builder = match digest_auth.authorization(method, monerod_uri) {
+            Some(authorization) => builder.header(AUTHORIZATION, authorization),
+            None => builder,
+        };
+        let resp = builder.send().await.map_err(MmProxyError::MonerodRequestFailed)?;
+        if resp.status() != StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+        let Some(retry) = retry else {
+            return Ok(resp);
+        };
+        if !digest_auth.update_challenge(resp.headers()) {
+            warn!(target: LOG_TARGET, "Monerod rejected the request without a usable digest auth challenge");
+            return Ok(resp);
+        }
+        let authorization = digest_auth
+            .authorization(method, monerod_uri)
+            .ok_or_else(|| MmProxyError::MissingDataError("Digest auth challenge".to_string()))?;
+        debug!(target: LOG_TARGET, "Retrying monerod request with a new digest auth challenge");
+        retry
+            .header(AUTHORIZATION, authorization)
+            .send()
+            .await
+            .map_err(MmProxyError::MonerodRequestFailed)
+    }
+
     async fn get_proxy_response(
         &self,
         request: Request<Bytes>,
@@ -946,6 +1028,7 @@ fn try_into_json_block_header(header: grpc::BlockHeaderResponse) -> Result<json:
         confirmations,
         difficulty,
         num_transactions,
+        ..
     } = header;
     let header = header.ok_or_else(|| {
         MmProxyError::UnexpectedTariBaseNodeResponse(
@@ -46,6 +46,7 @@ use crate::{
     error::MmProxyError,
     monero_fail::get_monerod_info,
     proxy::MergeMiningProxyService,
+    stratum::StratumServer,
     Cli,
 };
 
@@ -64,12 +65,18 @@ pub async fn start_merge_miner(cli: Cli) -> Result<(), anyhow::Error> {
     }
 
     info!(target: LOG_TARGET, "Configuration: {:?}", config);
-    let client = reqwest::Client::builder()
+    let mut client_builder = reqwest::Client::builder()
         .connect_timeout(Duration::from_secs(5))
         .timeout(Duration::from_secs(10))
-        .pool_max_idle_per_host(25)
-        .build()
-        .map_err(MmProxyError::ReqwestError)?;
+        .pool_max_idle_per_host(25);
+    if let Some(ca_cert_filename) = &config.monerod_tls_ca_cert_filename {
+        let pem = tokio::fs::read(config.config_dir.join(ca_cert_filename))
+            .await
+            .map_err(|e| MmProxyError::TlsConnectionError(e.to_string()))?;
+        let ca = reqwest::Certificate::from_pem(&pem).map_err(|e| MmProxyError::TlsConnectionError(e.to_string()))?;
+        client_builder = client_builder.add_root_certificate(ca);
+    }
+    let client = client_builder.build().map_err(MmProxyError::ReqwestError)?;
 
     let wallet_payment_address = wallet_payment_address(config.wallet_payment_address.clone(), config.network)?;
     let mut base_node_client = match connect_base_node(&config).await {
@@ -97,14 +104,31 @@ pub async fn start_merge_miner(cli: Cli) -> Result<(), anyhow::Error> {
 
     let listen_addr = multiaddr_to_socketaddr(&config.listener_address)?;
     let randomx_factory = RandomXFactory::new(config.max_randomx_vms);
+    let stratum_config = config.clone();
     let randomx_service = MergeMiningProxyService::new(
         config,
         client,
         base_node_client,
         BlockTemplateRepository::new(),
-        randomx_factory,
+        randomx_factory.clone(),
         wallet_payment_address,
     )?;
+    if let Some(stratum_address) = &stratum_config.stratum_listener_address {
+        if stratum_config.stratum_monero_wallet_address.is_empty() {
+            return Err(MmProxyError::MissingDataError(
+                "'stratum_monero_wallet_address' must be set to enable the Stratum server".to_string(),
+            )
+            .into());
+        }
+        let stratum_addr = multiaddr_to_socketaddr(stratum_address)?;
+        let stratum_server = StratumServer::new(stratum_config, randomx_service.clone(), randomx_factory);
+        tokio::spawn(async move {
+            if let Err(err) = stratum_server.run(stratum_addr).await {
+                error!(target: LOG_TARGET, "Stratum server failed: {}", err);
+                println!("Stratum server failed: {}", err);
+            }
+        });
+    }
     let service = make_service_fn(|_conn| future::ready(Result::<_, Infallible>::Ok(randomx_service.clone())));
 
     match Server::try_bind(&listen_addr) {
@@ -133,6 +157,7 @@ async fn verify_base_node_responses(node_conn: &mut BaseNodeGrpcClient) -> Resul
             pow_algo: grpc::pow_algo::PowAlgos::Randomx.into(),
         }),
         max_weight: 0,
+        template_version: 0,
     })
     .await
     {
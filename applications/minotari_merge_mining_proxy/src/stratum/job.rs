@@ -0,0 +1,200 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use monero::{
+    blockdata::transaction::{ExtraField, SubField},
+    consensus,
+};
+use serde_json as json;
+use tari_core::proof_of_work::monero_rx::{self, MergeMineError};
+
+use crate::error::MmProxyError;
+
+/// The size of the nonce field at the end of the serialized Monero block header
+const NONCE_SIZE: usize = 4;
+
+/// A merged Tari and Monero block template that the work for every Stratum connection is derived from
+#[derive(Debug, Clone)]
+pub struct JobTemplate {
+    block: monero::Block,
+    pub height: u64,
+    pub seed_hash: String,
+    /// The lower of the Monero and Tari difficulties, a share that meets this difficulty is submitted as a block
+    pub network_difficulty: u64,
+    pub tari_height: u64,
+}
+
+impl JobTemplate {
+    /// Create a template from the merge mining proxy's `get_block_template` response
+    pub fn from_block_template_response(response: &json::Value) -> Result<Self, MmProxyError> {
+        if !response["error"].is_null() {
+            return Err(MmProxyError::FailedToGetBlockTemplate(response["error"].to_string()));
+        }
+        let result = &response["result"];
+        let blob = result["blocktemplate_blob"]
+            .as_str()
+            .ok_or_else(|| MmProxyError::InvalidMonerodResponse("missing `blocktemplate_blob`".to_string()))?;
+        let block = monero_rx::deserialize_monero_block_from_hex(blob)?;
+        let height = result["height"]
+            .as_u64()
+            .ok_or_else(|| MmProxyError::InvalidMonerodResponse("missing `height`".to_string()))?;
+        let seed_hash = result["seed_hash"]
+            .as_str()
+            .ok_or_else(|| MmProxyError::InvalidMonerodResponse("missing `seed_hash`".to_string()))?
+            .to_string();
+        let network_difficulty = result["difficulty"]
+            .as_u64()
+            .ok_or_else(|| MmProxyError::InvalidMonerodResponse("missing `difficulty`".to_string()))?;
+        let tari_height = result["_aux"]["chains"]
+            .as_array()
+            .and_then(|chains| chains.iter().find_map(|c| c["height"].as_u64()))
+            .unwrap_or_default();
+        Ok(Self {
+            block,
+            height,
+            seed_hash,
+            network_difficulty,
+            tari_height,
+        })
+    }
+
+    /// Returns true if this template builds on a different Monero or Tari tip to `other`, in which case the work of
+    /// `other` is stale.
+    pub fn is_new_tip(&self, other: &JobTemplate) -> bool {
+        self.block.header.prev_id != other.block.header.prev_id || self.tari_height != other.tari_height
+    }
+
+    /// Create the work for a single connection. The connection's extra nonce is written into the coinbase extra field
+    /// so that every connection searches a different nonce space.
+    pub fn create_work(&self, extra_nonce: u64) -> Result<MinerWork, MmProxyError> {
+        let mut block = self.block.clone();
+        let mut extra_field = ExtraField::try_parse(&block.miner_tx.prefix.extra)
+            .map_err(|_| MergeMineError::DeserializeError("Invalid extra field".to_string()))?;
+        let extra_nonce = extra_nonce.to_le_bytes();
+        // Use the space reserved by monerod if there is any, otherwise add a nonce after the merge mining tag
+        let reserved = extra_field.0.iter_mut().find_map(|field| match field {
+            SubField::Nonce(nonce) if nonce.len() >= extra_nonce.len() => Some(nonce),
+            _ => None,
+        });
+        match reserved {
+            Some(nonce) => nonce[..extra_nonce.len()].copy_from_slice(&extra_nonce),
+            None => {
+                let index = extra_field.0.len().min(1);
+                extra_field.0.insert(index, SubField::Nonce(extra_nonce.to_vec()));
+            },
+        }
+        block.miner_tx.prefix.extra = extra_field.into();
+
+        let hashing_blob = hex::decode(monero_rx::create_blockhashing_blob_from_block(&block)?)?;
+        let nonce_offset = consensus::serialize(&block.header).len() - NONCE_SIZE;
+        Ok(MinerWork {
+            block,
+            hashing_blob,
+            nonce_offset,
+        })
+    }
+}
+
+/// The work assigned to a single Stratum connection
+#[derive(Debug, Clone)]
+pub struct MinerWork {
+    block: monero::Block,
+    hashing_blob: Vec<u8>,
+    nonce_offset: usize,
+}
+
+impl MinerWork {
+    /// The blob that is sent to the miner
+    pub fn hashing_blob(&self) -> &[u8] {
+        &self.hashing_blob
+    }
+
+    /// The hashing blob with the nonce found by the miner, as hashed by RandomX
+    pub fn hashing_blob_with_nonce(&self, nonce: [u8; NONCE_SIZE]) -> Vec<u8> {
+        let mut blob = self.hashing_blob.clone();
+        blob[self.nonce_offset..self.nonce_offset + NONCE_SIZE].copy_from_slice(&nonce);
+        blob
+    }
+
+    /// The hex encoded Monero block with the nonce found by the miner, as submitted to the proxy
+    pub fn block_blob_with_nonce(&self, nonce: [u8; NONCE_SIZE]) -> Result<String, MmProxyError> {
+        let mut block = self.block.clone();
+        block.header.nonce = u32::from_le_bytes(nonce);
+        Ok(monero_rx::serialize_monero_block_to_hex(&block)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    // A Monero block with only the miner tx, which has an 8 byte nonce in its extra field
+    const BLOCK_BLOB: &str = "0c0c94debaf805beb3489c722a285c092a32e7c6893abfc7d069699c8326fc3445a749c5276b6200000000029b892201ffdf882201b699d4c8b1ec020223df524af2a2ef5f870adb6e1ceb03a475c39f8b9ef76aa50b46ddd2a18349402b012839bfa19b7524ec7488917714c216ca254b38ed0424ca65ae828a7c006aeaf10208f5316a7f6b99cca60000";
+
+    fn template() -> JobTemplate {
+        JobTemplate::from_block_template_response(&json!({
+            "result": {
+                "blocktemplate_blob": BLOCK_BLOB,
+                "height": 100,
+                "seed_hash": "00".repeat(32),
+                "difficulty": 1000,
+                "_aux": { "chains": [{ "id": "xtr", "height": 5 }] },
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_parses_the_block_template_response() {
+        let template = template();
+        assert_eq!(template.height, 100);
+        assert_eq!(template.network_difficulty, 1000);
+        assert_eq!(template.tari_height, 5);
+        assert!(!template.is_new_tip(&template));
+
+        let err = JobTemplate::from_block_template_response(&json!({"error": {"code": -9}})).unwrap_err();
+        assert!(matches!(err, MmProxyError::FailedToGetBlockTemplate(_)));
+    }
+
+    #[test]
+    fn it_gives_each_connection_different_work() {
+        let template = template();
+        let work1 = template.create_work(1).unwrap();
+        let work2 = template.create_work(2).unwrap();
+        assert_ne!(work1.hashing_blob(), work2.hashing_blob());
+        assert_eq!(work1.hashing_blob().len(), work2.hashing_blob().len());
+    }
+
+    #[test]
+    fn it_inserts_the_nonce_into_the_block_and_hashing_blob() {
+        let work = template().create_work(1).unwrap();
+        let nonce = [1, 2, 3, 4];
+        let block = monero_rx::deserialize_monero_block_from_hex(work.block_blob_with_nonce(nonce).unwrap()).unwrap();
+        assert_eq!(block.header.nonce, 0x0403_0201);
+        assert_eq!(
+            hex::decode(monero_rx::create_blockhashing_blob_from_block(&block).unwrap()).unwrap(),
+            work.hashing_blob_with_nonce(nonce)
+        );
+    }
+}
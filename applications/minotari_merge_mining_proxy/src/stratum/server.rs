@@ -0,0 +1,459 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use hyper::{header::CONTENT_TYPE, service::Service, Body, Request};
+use json::json;
+use log::*;
+use serde_json as json;
+use tari_core::proof_of_work::{monero_rx::MergeMineError, randomx_factory::RandomXFactory, Difficulty};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{tcp::OwnedReadHalf, TcpListener, TcpStream},
+    sync::{mpsc, watch},
+    time,
+};
+
+use crate::{
+    common::json_rpc,
+    config::MergeMiningProxyConfig,
+    error::MmProxyError,
+    proxy::MergeMiningProxyService,
+    stratum::{
+        job::{JobTemplate, MinerWork},
+        vardiff::{difficulty_to_target_hex, VarDiff},
+    },
+};
+
+const LOG_TARGET: &str = "minotari_mm_proxy::stratum";
+/// Stratum messages are small, anything longer than this is not a valid message
+const MAX_LINE_LENGTH: usize = 16 * 1024;
+/// The number of recent jobs per connection that shares are accepted for
+const MAX_JOBS_PER_CONNECTION: usize = 4;
+/// A template is replaced after this long even if the tip has not changed, so that new transactions are mined
+const MAX_TEMPLATE_AGE: Duration = Duration::from_secs(60);
+/// The number of bytes monerod reserves in the coinbase extra field for the extra nonce of each connection
+const EXTRA_NONCE_RESERVE_SIZE: u64 = 8;
+const STRATUM_ALGO: &str = "rx/0";
+const STRATUM_ERROR_CODE: i32 = -1;
+
+type TemplateReceiver = watch::Receiver<Option<Arc<JobTemplate>>>;
+
+/// A Stratum v1 server for RandomX miners. Jobs are derived from the merged Tari and Monero block template of the merge
+/// mining proxy, shares are validated locally against a per-connection variable difficulty and shares that meet the
+/// network difficulty are submitted to the proxy as blocks.
+#[derive(Clone)]
+pub struct StratumServer {
+    config: Arc<MergeMiningProxyConfig>,
+    proxy: MergeMiningProxyService,
+    randomx_factory: RandomXFactory,
+    next_extra_nonce: Arc<AtomicU64>,
+}
+
+impl StratumServer {
+    pub fn new(
+        config: MergeMiningProxyConfig,
+        proxy: MergeMiningProxyService,
+        randomx_factory: RandomXFactory,
+    ) -> Self {
+        Self {
+            config: Arc::new(config),
+            proxy,
+            randomx_factory,
+            next_extra_nonce: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    pub async fn run(self, listen_addr: SocketAddr) -> Result<(), MmProxyError> {
+        let listener = TcpListener::bind(listen_addr).await?;
+        info!(target: LOG_TARGET, "Stratum server listening on {}", listen_addr);
+        println!("Stratum server listening on {}...", listen_addr);
+
+        let (template_tx, template_rx) = watch::channel(None);
+        tokio::spawn(self.clone().refresh_templates(template_tx));
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Failed to accept Stratum connection: {}", err);
+                    continue;
+                },
+            };
+            debug!(target: LOG_TARGET, "New Stratum connection from {}", peer);
+            let extra_nonce = self.next_extra_nonce.fetch_add(1, Ordering::Relaxed);
+            let connection = StratumConnection::new(self.clone(), template_rx.clone(), peer, extra_nonce);
+            tokio::spawn(connection.run(stream));
+        }
+    }
+
+    async fn refresh_templates(self, template_tx: watch::Sender<Option<Arc<JobTemplate>>>) {
+        let mut interval = time::interval(self.config.stratum_job_refresh_interval);
+        let mut last_update = Instant::now();
+        loop {
+            interval.tick().await;
+            let template = match self.get_block_template().await {
+                Ok(template) => template,
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Failed to get a block template for Stratum jobs: {}", err);
+                    continue;
+                },
+            };
+            let is_new_tip = template_tx
+                .borrow()
+                .as_ref()
+                .map_or(true, |current| template.is_new_tip(current));
+            if is_new_tip || last_update.elapsed() >= MAX_TEMPLATE_AGE {
+                debug!(
+                    target: LOG_TARGET,
+                    "New Stratum job template for Monero height {}, Tari height {} (new tip: {})",
+                    template.height,
+                    template.tari_height,
+                    is_new_tip
+                );
+                last_update = Instant::now();
+                template_tx.send_replace(Some(Arc::new(template)));
+            }
+        }
+    }
+
+    async fn get_block_template(&self) -> Result<JobTemplate, MmProxyError> {
+        let response = self
+            .call_proxy(
+                "get_block_template",
+                json!({
+                    "wallet_address": self.config.stratum_monero_wallet_address,
+                    "reserve_size": EXTRA_NONCE_RESERVE_SIZE,
+                }),
+            )
+            .await?;
+        JobTemplate::from_block_template_response(&response)
+    }
+
+    async fn submit_block(&self, block_blob: String) -> Result<(), MmProxyError> {
+        let response = self.call_proxy("submit_block", json!([block_blob])).await?;
+        if response["error"].is_null() {
+            info!(target: LOG_TARGET, "Block submitted by Stratum miner was accepted: {}", response);
+            Ok(())
+        } else {
+            Err(MmProxyError::StratumError(format!(
+                "Block submitted by Stratum miner was not accepted: {}",
+                response["error"]
+            )))
+        }
+    }
+
+    /// Send a JSON-RPC request through the merge mining proxy, exactly as if it had been sent by an HTTP client
+    async fn call_proxy(&self, method: &str, params: json::Value) -> Result<json::Value, MmProxyError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": method,
+            "params": params,
+        });
+        let request = Request::post("/json_rpc")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))?;
+        let response = self.proxy.clone().call(request).await?;
+        let bytes = hyper::body::to_bytes(response.into_body()).await?;
+        Ok(json::from_slice(&bytes)?)
+    }
+}
+
+/// A job that has been sent to a Stratum connection
+struct ConnectionJob {
+    job_id: String,
+    template: Arc<JobTemplate>,
+    work: MinerWork,
+    difficulty: u64,
+    submitted_nonces: HashSet<[u8; 4]>,
+}
+
+struct StratumConnection {
+    server: StratumServer,
+    templates: TemplateReceiver,
+    peer: SocketAddr,
+    session_id: String,
+    extra_nonce: u64,
+    vardiff: VarDiff,
+    jobs: VecDeque<ConnectionJob>,
+    next_job_id: u64,
+    logged_in: bool,
+}
+
+impl StratumConnection {
+    fn new(server: StratumServer, templates: TemplateReceiver, peer: SocketAddr, extra_nonce: u64) -> Self {
+        let vardiff = VarDiff::new(
+            server.config.stratum_initial_difficulty,
+            server.config.stratum_min_difficulty,
+            server.config.stratum_target_share_time,
+            Instant::now(),
+        );
+        Self {
+            server,
+            templates,
+            peer,
+            session_id: format!("{:016x}", rand::random::<u64>()),
+            extra_nonce,
+            vardiff,
+            jobs: VecDeque::with_capacity(MAX_JOBS_PER_CONNECTION),
+            next_job_id: 0,
+            logged_in: false,
+        }
+    }
+
+    async fn run(mut self, stream: TcpStream) {
+        let (reader, mut writer) = stream.into_split();
+        let (line_tx, mut line_rx) = mpsc::channel(16);
+        tokio::spawn(read_lines(reader, line_tx, self.peer));
+        let mut retarget_interval = time::interval(self.server.config.stratum_target_share_time);
+
+        loop {
+            let messages = tokio::select! {
+                line = line_rx.recv() => match line {
+                    Some(line) => self.handle_line(&line).await,
+                    None => break,
+                },
+                changed = self.templates.changed(), if self.logged_in => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    self.job_notification()
+                },
+                _ = retarget_interval.tick(), if self.logged_in => {
+                    match self.vardiff.retarget(Instant::now()) {
+                        Some(_) => self.job_notification(),
+                        None => vec![],
+                    }
+                },
+            };
+            for message in messages {
+                let mut message = message.to_string();
+                message.push('\n');
+                if let Err(err) = writer.write_all(message.as_bytes()).await {
+                    debug!(target: LOG_TARGET, "Failed to write to Stratum connection {}: {}", self.peer, err);
+                    return;
+                }
+            }
+        }
+        debug!(target: LOG_TARGET, "Stratum connection {} closed", self.peer);
+    }
+
+    async fn handle_line(&mut self, line: &str) -> Vec<json::Value> {
+        let request = match json::from_str::<json::Value>(line) {
+            Ok(request) => request,
+            Err(err) => {
+                debug!(target: LOG_TARGET, "Invalid Stratum message from {}: {}", self.peer, err);
+                return vec![stratum_error(None, "Invalid JSON")];
+            },
+        };
+        let id = request["id"].as_i64();
+        match request["method"].as_str().unwrap_or_default() {
+            "login" => self.handle_login(id, &request["params"]),
+            "getjob" => match self.next_job() {
+                Ok(Some(job)) => vec![json_rpc::success_response(id, job)],
+                Ok(None) => vec![stratum_error(id, "No job available")],
+                Err(err) => vec![stratum_error(id, &err.to_string())],
+            },
+            "submit" => {
+                let (response, new_difficulty) = match self.handle_submit(&request["params"]).await {
+                    Ok(new_difficulty) => (
+                        json_rpc::success_response(id, json!({ "status": "OK" })),
+                        new_difficulty,
+                    ),
+                    Err(err) => (stratum_error(id, &err.to_string()), None),
+                };
+                let mut messages = vec![response];
+                if new_difficulty.is_some() {
+                    messages.extend(self.job_notification());
+                }
+                messages
+            },
+            "keepalived" => vec![json_rpc::success_response(id, json!({ "status": "KEEPALIVED" }))],
+            method => vec![stratum_error(id, &format!("Unknown method `{}`", method))],
+        }
+    }
+
+    fn handle_login(&mut self, id: Option<i64>, params: &json::Value) -> Vec<json::Value> {
+        debug!(
+            target: LOG_TARGET,
+            "Stratum login from {} (login: {}, agent: {})",
+            self.peer,
+            params["login"].as_str().unwrap_or_default(),
+            params["agent"].as_str().unwrap_or_default()
+        );
+        match self.next_job() {
+            Ok(Some(job)) => {
+                self.logged_in = true;
+                vec![json_rpc::success_response(
+                    id,
+                    json!({
+                        "id": self.session_id,
+                        "job": job,
+                        "extensions": ["algo", "keepalive"],
+                        "status": "OK",
+                    }),
+                )]
+            },
+            Ok(None) => vec![stratum_error(
+                id,
+                "No job available, the merge mining proxy does not have a block template yet",
+            )],
+            Err(err) => vec![stratum_error(id, &err.to_string())],
+        }
+    }
+
+    /// Validate a share, submitting it as a block if it meets the network difficulty. Returns the new share
+    /// difficulty if it was adjusted.
+    async fn handle_submit(&mut self, params: &json::Value) -> Result<Option<u64>, MmProxyError> {
+        let job_id = params["job_id"].as_str().unwrap_or_default();
+        let nonce = params["nonce"]
+            .as_str()
+            .and_then(|nonce| hex::decode(nonce).ok())
+            .and_then(|nonce| <[u8; 4]>::try_from(nonce).ok())
+            .ok_or_else(|| MmProxyError::StratumError("Invalid nonce".to_string()))?;
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|job| job.job_id == job_id)
+            .ok_or_else(|| MmProxyError::StratumError("Invalid job id".to_string()))?;
+        if !job.submitted_nonces.insert(nonce) {
+            return Err(MmProxyError::StratumError("Duplicate share".to_string()));
+        }
+        let template = job.template.clone();
+        let work = job.work.clone();
+        let share_difficulty = job.difficulty;
+
+        let blob = work.hashing_blob_with_nonce(nonce);
+        let seed_hash = hex::decode(&template.seed_hash)?;
+        let randomx_factory = self.server.randomx_factory.clone();
+        let hash = tokio::task::spawn_blocking(move || -> Result<Vec<u8>, MmProxyError> {
+            let vm = randomx_factory.create(&seed_hash).map_err(MergeMineError::from)?;
+            Ok(vm.calculate_hash(&blob).map_err(MergeMineError::from)?)
+        })
+        .await
+        .map_err(|err| MmProxyError::StratumError(err.to_string()))??;
+        let achieved = Difficulty::little_endian_difficulty(&hash)?.as_u64();
+        if achieved < share_difficulty {
+            return Err(MmProxyError::StratumError("Low difficulty share".to_string()));
+        }
+
+        if achieved >= template.network_difficulty {
+            info!(
+                target: LOG_TARGET,
+                "Stratum miner {} found a block at Monero height {} (difficulty {})", self.peer, template.height, achieved
+            );
+            if let Err(err) = self.server.submit_block(work.block_blob_with_nonce(nonce)?).await {
+                warn!(target: LOG_TARGET, "{}", err);
+            }
+        }
+        Ok(self.vardiff.record_share(Instant::now()))
+    }
+
+    /// A `job` notification with new work for the current template, or nothing if there is no template
+    fn job_notification(&mut self) -> Vec<json::Value> {
+        match self.next_job() {
+            Ok(Some(job)) => vec![json!({
+                "jsonrpc": "2.0",
+                "method": "job",
+                "params": job,
+            })],
+            Ok(None) => vec![],
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Failed to create a Stratum job for {}: {}", self.peer, err);
+                vec![]
+            },
+        }
+    }
+
+    fn next_job(&mut self) -> Result<Option<json::Value>, MmProxyError> {
+        let Some(template) = self.templates.borrow_and_update().clone() else {
+            return Ok(None);
+        };
+        // Shares for work on a previous tip can no longer produce a block
+        if self.jobs.back().map_or(false, |job| template.is_new_tip(&job.template)) {
+            self.jobs.clear();
+        }
+        self.vardiff.set_max_difficulty(template.network_difficulty);
+        let work = template.create_work(self.extra_nonce)?;
+        self.next_job_id += 1;
+        let job = ConnectionJob {
+            job_id: format!("{:x}", self.next_job_id),
+            difficulty: self.vardiff.difficulty(),
+            template,
+            work,
+            submitted_nonces: HashSet::new(),
+        };
+        let json = json!({
+            "blob": hex::encode(job.work.hashing_blob()),
+            "job_id": job.job_id,
+            "target": difficulty_to_target_hex(job.difficulty),
+            "height": job.template.height,
+            "seed_hash": job.template.seed_hash,
+            "algo": STRATUM_ALGO,
+        });
+        if self.jobs.len() >= MAX_JOBS_PER_CONNECTION {
+            self.jobs.pop_front();
+        }
+        self.jobs.push_back(job);
+        Ok(Some(json))
+    }
+}
+
+/// Read newline delimited messages from the connection until it is closed or a message is too long
+async fn read_lines(reader: OwnedReadHalf, line_tx: mpsc::Sender<String>, peer: SocketAddr) {
+    let mut reader = BufReader::new(reader);
+    loop {
+        let mut line = String::new();
+        match (&mut reader).take(MAX_LINE_LENGTH as u64).read_line(&mut line).await {
+            Ok(0) => break,
+            Ok(_) if !line.ends_with('\n') && line.len() >= MAX_LINE_LENGTH => {
+                warn!(target: LOG_TARGET, "Stratum message from {} is too long, disconnecting", peer);
+                break;
+            },
+            Ok(_) => {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if line_tx.send(line).await.is_err() {
+                    break;
+                }
+            },
+            Err(err) => {
+                debug!(target: LOG_TARGET, "Failed to read from Stratum connection {}: {}", peer, err);
+                break;
+            },
+        }
+    }
+}
+
+fn stratum_error(id: Option<i64>, message: &str) -> json::Value {
+    json_rpc::error_response(id, STRATUM_ERROR_CODE, message, None)
+}
@@ -0,0 +1,163 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::{Duration, Instant};
+
+/// The number of target share times between difficulty adjustments
+const RETARGET_INTERVAL_SHARES: u32 = 4;
+/// The largest factor by which the difficulty is changed in a single adjustment
+const MAX_ADJUSTMENT_FACTOR: u64 = 4;
+
+/// Per-connection variable share difficulty. The difficulty is adjusted so that a miner submits a share every
+/// `target_share_time` on average.
+#[derive(Debug, Clone)]
+pub struct VarDiff {
+    difficulty: u64,
+    min_difficulty: u64,
+    max_difficulty: u64,
+    target_share_time: Duration,
+    last_retarget: Instant,
+    shares_since_retarget: u32,
+}
+
+impl VarDiff {
+    pub fn new(initial_difficulty: u64, min_difficulty: u64, target_share_time: Duration, now: Instant) -> Self {
+        let min_difficulty = min_difficulty.max(1);
+        Self {
+            difficulty: initial_difficulty.max(min_difficulty),
+            min_difficulty,
+            max_difficulty: u64::MAX,
+            target_share_time,
+            last_retarget: now,
+            shares_since_retarget: 0,
+        }
+    }
+
+    pub fn difficulty(&self) -> u64 {
+        self.difficulty
+    }
+
+    /// Limit the share difficulty to the network difficulty of the current job, there is no point in asking for
+    /// shares that are harder than a block. Returns the new difficulty if it changed.
+    pub fn set_max_difficulty(&mut self, max_difficulty: u64) -> Option<u64> {
+        self.max_difficulty = max_difficulty.max(self.min_difficulty);
+        self.clamp_and_set(self.difficulty)
+    }
+
+    /// Record an accepted share. Returns the new difficulty if the difficulty was adjusted.
+    pub fn record_share(&mut self, now: Instant) -> Option<u64> {
+        self.shares_since_retarget += 1;
+        self.retarget(now)
+    }
+
+    /// Adjust the difficulty if enough time has passed since the last adjustment. This is also called when no shares
+    /// are being submitted, so that the difficulty is lowered for miners that cannot find shares at the current
+    /// difficulty. Returns the new difficulty if the difficulty was adjusted.
+    pub fn retarget(&mut self, now: Instant) -> Option<u64> {
+        let elapsed = now.saturating_duration_since(self.last_retarget);
+        let interval = self.target_share_time * RETARGET_INTERVAL_SHARES;
+        if elapsed < interval && self.shares_since_retarget < RETARGET_INTERVAL_SHARES {
+            return None;
+        }
+
+        let elapsed_millis = elapsed.as_millis().max(1);
+        let target_millis = self.target_share_time.as_millis().max(1) * u128::from(self.shares_since_retarget.max(1));
+        let adjusted = if self.shares_since_retarget == 0 {
+            // No shares at all in the interval, the miner is too slow for the current difficulty
+            u128::from(self.difficulty / MAX_ADJUSTMENT_FACTOR)
+        } else {
+            u128::from(self.difficulty) * target_millis / elapsed_millis
+        };
+        let lower = u128::from(self.difficulty / MAX_ADJUSTMENT_FACTOR);
+        let upper = u128::from(self.difficulty.saturating_mul(MAX_ADJUSTMENT_FACTOR));
+        let adjusted = u64::try_from(adjusted.clamp(lower, upper)).unwrap_or(u64::MAX);
+
+        self.last_retarget = now;
+        self.shares_since_retarget = 0;
+        self.clamp_and_set(adjusted)
+    }
+
+    fn clamp_and_set(&mut self, difficulty: u64) -> Option<u64> {
+        let difficulty = difficulty.clamp(self.min_difficulty, self.max_difficulty);
+        if difficulty == self.difficulty {
+            return None;
+        }
+        self.difficulty = difficulty;
+        Some(difficulty)
+    }
+}
+
+/// Encode a share difficulty as a Monero Stratum target. A 4 byte target is used where possible as it is supported by
+/// all miners, an 8 byte target is used for difficulties that cannot be represented in 4 bytes.
+pub fn difficulty_to_target_hex(difficulty: u64) -> String {
+    let difficulty = difficulty.max(1);
+    if difficulty <= u64::from(u32::MAX) {
+        let target = u32::try_from(u64::from(u32::MAX) / difficulty).unwrap_or(u32::MAX);
+        hex::encode(target.to_le_bytes())
+    } else {
+        hex::encode((u64::MAX / difficulty).to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_increases_the_difficulty_for_fast_miners() {
+        let start = Instant::now();
+        let mut vardiff = VarDiff::new(1000, 100, Duration::from_secs(10), start);
+        assert_eq!(vardiff.record_share(start + Duration::from_secs(1)), None);
+        assert_eq!(vardiff.record_share(start + Duration::from_secs(2)), None);
+        assert_eq!(vardiff.record_share(start + Duration::from_secs(3)), None);
+        // 4 shares in 4 seconds with a 10 second target, capped at 4x
+        assert_eq!(vardiff.record_share(start + Duration::from_secs(4)), Some(4000));
+        assert_eq!(vardiff.difficulty(), 4000);
+    }
+
+    #[test]
+    fn it_decreases_the_difficulty_for_slow_miners() {
+        let start = Instant::now();
+        let mut vardiff = VarDiff::new(1000, 100, Duration::from_secs(10), start);
+        assert_eq!(vardiff.record_share(start + Duration::from_secs(60)), Some(250));
+        // No shares for a full interval
+        assert_eq!(vardiff.retarget(start + Duration::from_secs(99)), None);
+        assert_eq!(vardiff.retarget(start + Duration::from_secs(100)), Some(100));
+        assert_eq!(vardiff.retarget(start + Duration::from_secs(200)), None);
+    }
+
+    #[test]
+    fn it_limits_the_difficulty_to_the_network_difficulty() {
+        let start = Instant::now();
+        let mut vardiff = VarDiff::new(1000, 100, Duration::from_secs(10), start);
+        assert_eq!(vardiff.set_max_difficulty(500), Some(500));
+        assert_eq!(vardiff.set_max_difficulty(5000), None);
+        assert_eq!(vardiff.difficulty(), 500);
+    }
+
+    #[test]
+    fn it_encodes_targets() {
+        assert_eq!(difficulty_to_target_hex(1), "ffffffff");
+        assert_eq!(difficulty_to_target_hex(0x10000), "ffff0000");
+        assert_eq!(difficulty_to_target_hex(u64::from(u32::MAX) + 1), "ffffffff00000000");
+    }
+}
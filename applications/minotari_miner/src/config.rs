@@ -44,7 +44,7 @@ use serde::{Deserialize, Serialize};
 use tari_common::{configuration::Network, SubConfigPath};
 use tari_common_types::{grpc_authentication::GrpcAuthentication, tari_address::TariAddress};
 use tari_comms::multiaddr::Multiaddr;
-use tari_core::transactions::transaction_components::RangeProofType;
+use tari_core::transactions::{transaction_components::RangeProofType, CoinbaseExtra};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
@@ -67,6 +67,9 @@ pub struct MinerConfig {
     /// Will check tip with node every N seconds and restart mining if height already taken and option
     /// `mine_on_tip_only` is set to true
     pub validate_tip_timeout_sec: u64,
+    /// The next block template is prepared in the background whenever the base node's tip changes, and also every N
+    /// seconds so that newly arrived mempool transactions are included
+    pub template_refresh_interval_sec: u64,
     /// Stratum Mode configuration - mining pool address
     pub stratum_mining_pool_address: String,
     /// Stratum Mode configuration - mining wallet address/public key
@@ -77,6 +80,11 @@ pub struct MinerConfig {
     /// Note that this data is publicly readable, but it is suggested you populate it so that
     /// pool dominance can be seen before any one party has more than 51%.
     pub coinbase_extra: String,
+    /// Tag the coinbase with the name of the mining pool. If a pool tag or miner id is set, a structured payload that
+    /// holds the pool tag, miner id and `coinbase_extra` is stored in the coinbase instead of `coinbase_extra` alone.
+    pub coinbase_extra_pool_tag: String,
+    /// Tag the coinbase with the identity of the miner, see `coinbase_extra_pool_tag`
+    pub coinbase_extra_miner_id: String,
     /// Selected network
     pub network: Network,
     /// Base node reconnect timeout after any gRPC or miner error
@@ -89,6 +97,25 @@ pub struct MinerConfig {
     pub stealth_payment: bool,
     /// Range proof type - revealed_value or bullet_proof_plus: (default = revealed_value)
     pub range_proof_type: RangeProofType,
+    /// The OpenCL GPU devices to mine with in addition to the CPU mining threads. Requires the miner to be built with
+    /// the `gpu` feature.
+    pub gpu_devices: Vec<GpuDeviceConfig>,
+}
+
+/// Mining configuration for a single GPU device
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct GpuDeviceConfig {
+    /// The index of the device, as listed by `--list-gpu-devices`
+    pub device: usize,
+    /// Each kernel launch hashes 2^intensity nonces. Higher values give a higher hash rate at the cost of a less
+    /// responsive device (default = 22)
+    #[serde(default = "default_gpu_intensity")]
+    pub intensity: u32,
+}
+
+fn default_gpu_intensity() -> u32 {
+    22
 }
 
 /// The proof of work data structure that is included in the block header. For the Minotari miner only `Sha3x` is
@@ -116,16 +143,20 @@ impl Default for MinerConfig {
             mine_on_tip_only: true,
             proof_of_work_algo: ProofOfWork::Sha3x,
             validate_tip_timeout_sec: 30,
+            template_refresh_interval_sec: 30,
             stratum_mining_pool_address: String::new(),
             stratum_mining_wallet_address: String::new(),
             mining_worker_name: String::new(),
             coinbase_extra: "minotari_miner".to_string(),
+            coinbase_extra_pool_tag: String::new(),
+            coinbase_extra_miner_id: String::new(),
             network: Default::default(),
             wait_timeout_on_error: 10,
             config_dir: PathBuf::from("config/miner"),
             wallet_payment_address: TariAddress::default().to_hex(),
             stealth_payment: true,
             range_proof_type: RangeProofType::RevealedValue,
+            gpu_devices: vec![],
         }
     }
 }
@@ -137,7 +168,11 @@ impl MinerConfig {
                 pow_algo: PowAlgos::Sha3x.into(),
             }),
         };
-        NewBlockTemplateRequest { algo, max_weight: 0 }
+        NewBlockTemplateRequest {
+            algo,
+            max_weight: 0,
+            template_version: 0,
+        }
     }
 
     pub fn wait_timeout(&self) -> Duration {
@@ -148,6 +183,25 @@ impl MinerConfig {
         Duration::from_secs(self.validate_tip_timeout_sec)
     }
 
+    pub fn template_refresh_interval(&self) -> Duration {
+        Duration::from_secs(self.template_refresh_interval_sec.max(1))
+    }
+
+    /// The structured coinbase extra payload, if a pool tag or miner id is configured
+    pub fn structured_coinbase_extra(&self) -> Option<CoinbaseExtra> {
+        if self.coinbase_extra_pool_tag.is_empty() && self.coinbase_extra_miner_id.is_empty() {
+            return None;
+        }
+        let mut extra = CoinbaseExtra::new().with_data(self.coinbase_extra.as_bytes().to_vec());
+        if !self.coinbase_extra_pool_tag.is_empty() {
+            extra = extra.with_pool_tag(self.coinbase_extra_pool_tag.clone());
+        }
+        if !self.coinbase_extra_miner_id.is_empty() {
+            extra = extra.with_miner_id(self.coinbase_extra_miner_id.clone());
+        }
+        Some(extra)
+    }
+
     pub fn set_base_path<P: AsRef<Path>>(&mut self, base_path: P) {
         if !self.config_dir.is_absolute() {
             self.config_dir = base_path.as_ref().join(self.config_dir.as_path());
@@ -162,7 +216,7 @@ mod test {
     use tari_common::DefaultConfigLoader;
     use tari_comms::multiaddr::Multiaddr;
 
-    use crate::config::MinerConfig;
+    use crate::config::{GpuDeviceConfig, MinerConfig};
 
     #[test]
     fn miner_configuration() {
@@ -171,6 +225,7 @@ mod test {
 num_mining_threads=2
 base_node_grpc_address = "/dns4/my_base_node/tcp/1234"
 mine_on_tip_only = false
+gpu_devices = [{ device = 0 }, { device = 1, intensity = 24 }]
 "#;
         let mut cfg: config::Config = config::Config::default();
         #[allow(deprecated)]
@@ -183,5 +238,15 @@ mine_on_tip_only = false
             Some(Multiaddr::from_str("/dns4/my_base_node/tcp/1234").unwrap())
         );
         assert!(!config.mine_on_tip_only);
+        assert_eq!(config.gpu_devices, vec![
+            GpuDeviceConfig {
+                device: 0,
+                intensity: 22
+            },
+            GpuDeviceConfig {
+                device: 1,
+                intensity: 24
+            }
+        ]);
     }
 }
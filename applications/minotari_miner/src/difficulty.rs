@@ -25,7 +25,7 @@ use std::convert::TryInto;
 use minotari_app_grpc::tari_rpc::BlockHeader as grpc_header;
 use tari_core::{
     blocks::BlockHeader,
-    proof_of_work::{sha3x_difficulty, DifficultyError},
+    proof_of_work::{DifficultyError, Sha3xHasher},
 };
 use tari_utilities::epoch_time::EpochTime;
 
@@ -37,6 +37,7 @@ pub type Difficulty = u64;
 pub struct BlockHeaderSha3 {
     pub header: BlockHeader,
     pub hashes: u64,
+    hasher: Sha3xHasher,
 }
 
 impl BlockHeaderSha3 {
@@ -44,7 +45,11 @@ impl BlockHeaderSha3 {
     #[allow(clippy::cast_sign_loss)]
     pub fn new(header: grpc_header) -> Result<Self, MinerError> {
         let header: BlockHeader = header.try_into().map_err(MinerError::BlockHeader)?;
-        Ok(Self { header, hashes: 0 })
+        Ok(Self {
+            header,
+            hashes: 0,
+            hasher: Sha3xHasher::new(),
+        })
     }
 
     /// This function will update the timestamp of the header, but only if the new timestamp is greater than the current
@@ -70,7 +75,7 @@ impl BlockHeaderSha3 {
     #[inline]
     pub fn difficulty(&mut self) -> Result<Difficulty, DifficultyError> {
         self.hashes = self.hashes.saturating_add(1);
-        Ok(sha3x_difficulty(&self.header)?.as_u64())
+        Ok(self.hasher.difficulty(&self.header)?.as_u64())
     }
 
     #[allow(clippy::cast_possible_wrap)]
@@ -57,6 +57,11 @@ pub enum MinerError {
     ParseInputError(#[from] ParseInputError),
     #[error("Base node not responding to gRPC requests: {0}")]
     BaseNodeNotResponding(String),
+    #[error("The block template cache stopped unexpectedly")]
+    TemplateCacheStopped,
+    #[cfg(feature = "gpu")]
+    #[error("GPU error: {0}")]
+    GpuError(String),
 }
 
 pub fn err_empty(name: &str) -> MinerError {
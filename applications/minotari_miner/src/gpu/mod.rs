@@ -0,0 +1,358 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! OpenCL backend for the Sha3x nonce search. Each configured device is driven by its own thread, which reports to the
+//! [Miner](crate::miner::Miner) in the same way as a CPU mining thread, so that found blocks and shares follow the same
+//! submission path.
+
+use std::{
+    convert::TryFrom,
+    panic::panic_any,
+    ptr,
+    task::Waker,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use crossbeam::channel::{Sender, TrySendError};
+use log::*;
+use minotari_app_grpc::tari_rpc::BlockHeader;
+use opencl3::{
+    command_queue::CommandQueue,
+    context::Context,
+    device::{get_all_devices, Device, CL_DEVICE_TYPE_GPU},
+    kernel::{ExecuteKernel, Kernel},
+    memory::{Buffer, CL_MEM_READ_ONLY, CL_MEM_WRITE_ONLY},
+    program::Program,
+    types::{cl_ulong, CL_BLOCKING},
+};
+
+use crate::{config::GpuDeviceConfig, difficulty::BlockHeaderSha3, errors::MinerError, miner::MiningReport};
+
+pub const LOG_TARGET: &str = "minotari::miner::gpu";
+
+const KERNEL_SOURCE: &str = include_str!("sha3x.cl");
+const KERNEL_NAME: &str = "sha3x";
+
+/// The Keccak rate of Sha3-256 in bytes
+const SHA3_256_RATE: usize = 136;
+const RATE_LANES: usize = SHA3_256_RATE / 8;
+
+const MIN_INTENSITY: u32 = 8;
+const MAX_INTENSITY: u32 = 32;
+
+// How often a GPU mining thread reports its hash rate
+const REPORTING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An OpenCL device that can be used for mining
+#[derive(Debug, Clone)]
+pub struct GpuDevice {
+    pub index: usize,
+    pub name: String,
+    pub vendor: String,
+    pub compute_units: u32,
+    pub global_mem_size: u64,
+}
+
+/// Lists the GPU devices of all the OpenCL platforms. The index of a device is used to select it in the
+/// `gpu_devices` config.
+pub fn list_devices() -> Result<Vec<GpuDevice>, MinerError> {
+    let ids = get_all_devices(CL_DEVICE_TYPE_GPU).map_err(|e| MinerError::GpuError(e.to_string()))?;
+    ids.into_iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let device = Device::new(id);
+            Ok(GpuDevice {
+                index,
+                name: device.name().map_err(|e| MinerError::GpuError(e.to_string()))?,
+                vendor: device.vendor().map_err(|e| MinerError::GpuError(e.to_string()))?,
+                compute_units: device
+                    .max_compute_units()
+                    .map_err(|e| MinerError::GpuError(e.to_string()))?,
+                global_mem_size: device
+                    .global_mem_size()
+                    .map_err(|e| MinerError::GpuError(e.to_string()))?,
+            })
+        })
+        .collect()
+}
+
+/// The compiled Sha3x kernel and buffers for a single device
+struct GpuSha3x {
+    queue: CommandQueue,
+    kernel: Kernel,
+    block: Buffer<cl_ulong>,
+    output: Buffer<cl_ulong>,
+    batch_size: u64,
+    // The context must outlive the queue, kernel and buffers
+    _context: Context,
+}
+
+impl GpuSha3x {
+    fn new(config: &GpuDeviceConfig) -> Result<Self, MinerError> {
+        let ids = get_all_devices(CL_DEVICE_TYPE_GPU).map_err(|e| MinerError::GpuError(e.to_string()))?;
+        let id = ids
+            .get(config.device)
+            .copied()
+            .ok_or_else(|| MinerError::GpuError(format!("GPU device {} does not exist", config.device)))?;
+        let device = Device::new(id);
+        let context = Context::from_device(&device).map_err(|e| MinerError::GpuError(e.to_string()))?;
+        let queue = CommandQueue::create_default_with_properties(&context, 0, 0)
+            .map_err(|e| MinerError::GpuError(e.to_string()))?;
+        let program =
+            Program::create_and_build_from_source(&context, KERNEL_SOURCE, "").map_err(MinerError::GpuError)?;
+        let kernel = Kernel::create(&program, KERNEL_NAME).map_err(|e| MinerError::GpuError(e.to_string()))?;
+        // Safety: the buffers are created without a host pointer
+        let block = unsafe { Buffer::<cl_ulong>::create(&context, CL_MEM_READ_ONLY, RATE_LANES, ptr::null_mut()) }
+            .map_err(|e| MinerError::GpuError(e.to_string()))?;
+        let output = unsafe { Buffer::<cl_ulong>::create(&context, CL_MEM_WRITE_ONLY, 2, ptr::null_mut()) }
+            .map_err(|e| MinerError::GpuError(e.to_string()))?;
+        Ok(Self {
+            queue,
+            kernel,
+            block,
+            output,
+            batch_size: 1u64 << config.intensity.clamp(MIN_INTENSITY, MAX_INTENSITY),
+            _context: context,
+        })
+    }
+
+    fn set_block(&mut self, lanes: &[u64; RATE_LANES]) -> Result<(), MinerError> {
+        // Safety: the buffer holds exactly `RATE_LANES` values
+        unsafe {
+            self.queue
+                .enqueue_write_buffer(&mut self.block, CL_BLOCKING, 0, lanes, &[])
+        }
+        .map_err(|e| MinerError::GpuError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Hashes `batch_size` nonces starting at `nonce_start`, returning a nonce with a hash that is below the target
+    /// in its first eight bytes, if one was found
+    fn search(&mut self, nonce_start: u64, target: u64) -> Result<Option<u64>, MinerError> {
+        let mut output = [0u64; 2];
+        // Safety: the buffers are the sizes the kernel expects and the kernel arguments match its signature
+        unsafe {
+            self.queue
+                .enqueue_write_buffer(&mut self.output, CL_BLOCKING, 0, &output, &[])
+                .map_err(|e| MinerError::GpuError(e.to_string()))?;
+            ExecuteKernel::new(&self.kernel)
+                .set_arg(&self.block)
+                .set_arg(&nonce_start)
+                .set_arg(&target)
+                .set_arg(&self.output)
+                .set_global_work_size(usize::try_from(self.batch_size).unwrap_or(usize::MAX))
+                .enqueue_nd_range(&self.queue)
+                .map_err(|e| MinerError::GpuError(e.to_string()))?
+                .wait()
+                .map_err(|e| MinerError::GpuError(e.to_string()))?;
+            self.queue
+                .enqueue_read_buffer(&self.output, CL_BLOCKING, 0, &mut output, &[])
+                .map_err(|e| MinerError::GpuError(e.to_string()))?;
+        }
+        Ok((output[0] != 0).then_some(output[1]))
+    }
+}
+
+/// Builds the padded first Sha3-256 block of the Sha3x hash as little endian Keccak lanes. The first lane is the nonce,
+/// which is set by the kernel.
+fn first_block_lanes(hasher: &BlockHeaderSha3) -> Result<[u64; RATE_LANES], MinerError> {
+    let header = &hasher.header;
+    let mut block = [0u8; SHA3_256_RATE];
+    let mining_hash = header.mining_hash();
+    let pow_data = &header.pow.pow_data;
+    let len = 8 + mining_hash.len() + 1 + pow_data.len();
+    if len >= SHA3_256_RATE {
+        return Err(MinerError::GpuError(format!(
+            "The PoW data is too large to hash on the GPU ({} bytes)",
+            pow_data.len()
+        )));
+    }
+    block[8..40].copy_from_slice(mining_hash.as_slice());
+    block[40] = header.pow.pow_algo as u8;
+    block[41..len].copy_from_slice(pow_data);
+    block[len] ^= 0x06;
+    block[SHA3_256_RATE - 1] ^= 0x80;
+
+    let mut lanes = [0u64; RATE_LANES];
+    for (lane, bytes) in lanes.iter_mut().zip(block.chunks_exact(8)) {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(bytes);
+        *lane = u64::from_le_bytes(buf);
+    }
+    Ok(lanes)
+}
+
+/// The largest value of the first eight bytes of a big endian hash that can meet the target difficulty. The GPU only
+/// compares these bytes, so every hash meeting the difficulty is a candidate, but candidates must be checked with the
+/// full difficulty calculation.
+fn candidate_target(target_difficulty: u64) -> u64 {
+    u64::MAX / target_difficulty.max(1)
+}
+
+fn gpu_failure(miner: usize, err: MinerError) -> ! {
+    let err = format!("GPU miner {} failed: {}", miner, err);
+    error!(target: LOG_TARGET, "{}", err);
+    panic_any(err);
+}
+
+/// Mines on a single GPU device. Starts with a random nonce and searches batches of nonces until it finds a header
+/// hash that meets the desired target.
+pub fn mining_task(
+    config: GpuDeviceConfig,
+    header: BlockHeader,
+    target_difficulty: u64,
+    sender: Sender<MiningReport>,
+    waker: Waker,
+    miner: usize,
+    share_mode: bool,
+) {
+    let start = Instant::now();
+    let mut gpu = GpuSha3x::new(&config).unwrap_or_else(|e| gpu_failure(miner, e));
+    let mut hasher = BlockHeaderSha3::new(header).unwrap_or_else(|e| gpu_failure(miner, e));
+    hasher.random_nonce();
+    gpu.set_block(&first_block_lanes(&hasher).unwrap_or_else(|e| gpu_failure(miner, e)))
+        .unwrap_or_else(|e| gpu_failure(miner, e));
+    let target = candidate_target(target_difficulty);
+    let mut hashes = 0u64;
+    let mut last_report = Instant::now();
+    trace!(
+        target: LOG_TARGET,
+        "GPU mining thread {} started on device {} with batch size {}",
+        miner,
+        config.device,
+        gpu.batch_size
+    );
+    loop {
+        let nonce_start = hasher.header.nonce;
+        let candidate = gpu
+            .search(nonce_start, target)
+            .unwrap_or_else(|e| gpu_failure(miner, e));
+        hashes = hashes.saturating_add(gpu.batch_size);
+        let mut difficulty = 0;
+        if let Some(nonce) = candidate {
+            hasher.header.nonce = nonce;
+            difficulty = hasher
+                .difficulty()
+                .unwrap_or_else(|e| gpu_failure(miner, MinerError::GpuError(e.to_string())));
+        }
+        if difficulty >= target_difficulty && candidate.is_some() {
+            debug!(
+                target: LOG_TARGET,
+                "GPU miner {} found nonce {} with matching difficulty {}", miner, hasher.header.nonce, difficulty
+            );
+            if let Err(err) = sender.try_send(MiningReport {
+                miner,
+                difficulty,
+                hashes,
+                elapsed: start.elapsed(),
+                height: hasher.height(),
+                last_nonce: hasher.header.nonce,
+                header: Some(hasher.create_header()),
+                target_difficulty,
+            }) {
+                error!(target: LOG_TARGET, "GPU miner {} failed to send report: {}", miner, err);
+            }
+            if share_mode {
+                waker.wake_by_ref();
+            } else {
+                waker.wake();
+                trace!(target: LOG_TARGET, "GPU mining thread {} stopped", miner);
+                return;
+            }
+        }
+        hasher.header.nonce = nonce_start.wrapping_add(gpu.batch_size);
+
+        if last_report.elapsed() >= REPORTING_INTERVAL {
+            last_report = Instant::now();
+            let res = sender.try_send(MiningReport {
+                miner,
+                difficulty,
+                hashes,
+                elapsed: start.elapsed(),
+                header: None,
+                last_nonce: hasher.header.nonce,
+                height: hasher.height(),
+                target_difficulty,
+            });
+            waker.wake_by_ref();
+            trace!(target: LOG_TARGET, "Reporting from GPU miner {} result {:?}", miner, res);
+            if let Err(TrySendError::Disconnected(_)) = res {
+                info!(target: LOG_TARGET, "GPU mining thread {} disconnected", miner);
+                return;
+            }
+            if !share_mode {
+                let timestamp = hasher.header.timestamp;
+                #[allow(clippy::cast_sign_loss)]
+                hasher.set_forward_timestamp(Utc::now().timestamp() as u64);
+                if hasher.header.timestamp != timestamp {
+                    gpu.set_block(&first_block_lanes(&hasher).unwrap_or_else(|e| gpu_failure(miner, e)))
+                        .unwrap_or_else(|e| gpu_failure(miner, e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_core::proof_of_work::Difficulty;
+
+    use super::*;
+    use crate::difficulty::test::get_header;
+
+    #[test]
+    fn it_pads_the_first_block() {
+        let (header, core_header) = get_header();
+        let hasher = BlockHeaderSha3::new(header).unwrap();
+        let lanes = first_block_lanes(&hasher).unwrap();
+        assert_eq!(lanes[0], 0);
+        let mining_hash = core_header.mining_hash();
+        assert_eq!(lanes[1].to_le_bytes(), mining_hash.as_slice()[..8]);
+        // The PoW algo byte is followed by the Sha3 padding byte, and the rate ends with the final padding bit
+        assert_eq!(lanes[5], u64::from(core_header.pow.pow_algo as u8) | 0x0600);
+        assert_eq!(lanes[RATE_LANES - 1], 0x8000_0000_0000_0000);
+    }
+
+    #[test]
+    fn it_rejects_pow_data_larger_than_a_block() {
+        let (mut header, _) = get_header();
+        header.pow.as_mut().unwrap().pow_data = vec![0u8; SHA3_256_RATE];
+        let hasher = BlockHeaderSha3::new(header).unwrap();
+        assert!(first_block_lanes(&hasher).is_err());
+    }
+
+    #[test]
+    fn it_calculates_a_tight_candidate_target() {
+        for target_difficulty in [1u64, 2, 1_000, 123_456_789, u64::MAX] {
+            let target = candidate_target(target_difficulty);
+            // The hash with the largest first eight bytes that is still a candidate
+            let mut hash = [0u8; 32];
+            hash[..8].copy_from_slice(&target.to_be_bytes());
+            assert!(Difficulty::big_endian_difficulty(&hash).unwrap().as_u64() >= target_difficulty);
+            if let Some(excluded) = target.checked_add(1) {
+                hash[..8].copy_from_slice(&excluded.to_be_bytes());
+                assert!(Difficulty::big_endian_difficulty(&hash).unwrap().as_u64() < target_difficulty);
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2024. The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+// Sha3x nonce search. Each work item hashes a single nonce: a triple Sha3-256 of the nonce, mining hash and PoW bytes.
+// The host prepares the padded first Keccak block (at most one rate block of 136 bytes) with the nonce lane left empty.
+
+__constant ulong RC[24] = {
+    0x0000000000000001UL, 0x0000000000008082UL, 0x800000000000808AUL, 0x8000000080008000UL,
+    0x000000000000808BUL, 0x0000000080000001UL, 0x8000000080008081UL, 0x8000000000008009UL,
+    0x000000000000008AUL, 0x0000000000000088UL, 0x0000000080008009UL, 0x000000008000000AUL,
+    0x000000008000808BUL, 0x800000000000008BUL, 0x8000000000008089UL, 0x8000000000008003UL,
+    0x8000000000008002UL, 0x8000000000000080UL, 0x000000000000800AUL, 0x800000008000000AUL,
+    0x8000000080008081UL, 0x8000000000008080UL, 0x0000000080000001UL, 0x8000000080008008UL,
+};
+
+// Rotation offsets indexed by lane (x + 5 * y)
+__constant uint ROTATIONS[25] = {
+    0,  1,  62, 28, 27,
+    36, 44, 6,  55, 20,
+    3,  10, 43, 25, 39,
+    41, 45, 15, 21, 8,
+    18, 2,  61, 56, 14,
+};
+
+static void keccak_f1600(ulong *a) {
+    ulong c[5];
+    ulong b[25];
+    for (int round = 0; round < 24; round++) {
+        // Theta
+        for (int x = 0; x < 5; x++) {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for (int x = 0; x < 5; x++) {
+            ulong d = c[(x + 4) % 5] ^ rotate(c[(x + 1) % 5], (ulong)1);
+            for (int y = 0; y < 25; y += 5) {
+                a[y + x] ^= d;
+            }
+        }
+        // Rho and pi
+        for (int x = 0; x < 5; x++) {
+            for (int y = 0; y < 5; y++) {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotate(a[x + 5 * y], (ulong)ROTATIONS[x + 5 * y]);
+            }
+        }
+        // Chi
+        for (int y = 0; y < 25; y += 5) {
+            for (int x = 0; x < 5; x++) {
+                a[y + x] = b[y + x] ^ (~b[y + (x + 1) % 5] & b[y + (x + 2) % 5]);
+            }
+        }
+        // Iota
+        a[0] ^= RC[round];
+    }
+}
+
+__kernel void sha3x(__constant ulong *block, ulong nonce_start, ulong target, __global ulong *output) {
+    ulong nonce = nonce_start + get_global_id(0);
+    ulong state[25];
+    state[0] = nonce;
+    for (int i = 1; i < 17; i++) {
+        state[i] = block[i];
+    }
+    for (int i = 17; i < 25; i++) {
+        state[i] = 0;
+    }
+    keccak_f1600(state);
+
+    // Hash the 32 byte digest of the previous round twice more. The digest is already in the first four lanes.
+    for (int round = 0; round < 2; round++) {
+        for (int i = 4; i < 25; i++) {
+            state[i] = 0;
+        }
+        state[4] = 0x06UL;
+        state[16] = 0x8000000000000000UL;
+        keccak_f1600(state);
+    }
+
+    // The difficulty is calculated from the big endian hash, so the first eight bytes decide whether the nonce is a
+    // candidate. Candidates are checked on the host.
+    if (as_ulong(as_uchar8(state[0]).s76543210) <= target) {
+        output[0] = 1;
+        output[1] = nonce;
+    }
+}
@@ -31,8 +31,11 @@ use run_miner::start_miner;
 mod config;
 mod difficulty;
 mod errors;
+#[cfg(feature = "gpu")]
+mod gpu;
 mod miner;
 mod stratum;
+mod template_cache;
 
 pub async fn run_miner(cli: Cli) -> Result<(), ExitError> {
     start_miner(cli).await
@@ -38,9 +38,12 @@ mod cli;
 mod config;
 mod difficulty;
 mod errors;
+#[cfg(feature = "gpu")]
+mod gpu;
 mod miner;
 mod run_miner;
 mod stratum;
+mod template_cache;
 
 /// Application entry point
 #[tokio::main]
@@ -36,6 +36,7 @@ use minotari_app_grpc::tari_rpc::BlockHeader;
 use thread::JoinHandle;
 
 use super::difficulty::BlockHeaderSha3;
+use crate::config::GpuDeviceConfig;
 
 pub const LOG_TARGET: &str = "minotari::miner::standalone";
 
@@ -69,6 +70,7 @@ pub struct Miner {
     header: BlockHeader,
     target_difficulty: u64,
     share_mode: bool,
+    gpu_devices: Vec<GpuDeviceConfig>,
 }
 
 impl Miner {
@@ -80,6 +82,22 @@ impl Miner {
             num_threads,
             target_difficulty,
             share_mode,
+            gpu_devices: vec![],
+        }
+    }
+
+    /// Also mine on the given GPU devices. Each device is driven by its own thread, numbered after the CPU mining
+    /// threads. GPU devices are ignored if the miner was built without the `gpu` feature.
+    pub fn with_gpu_devices(mut self, gpu_devices: Vec<GpuDeviceConfig>) -> Self {
+        self.gpu_devices = gpu_devices;
+        self
+    }
+
+    fn num_workers(&self) -> usize {
+        if cfg!(feature = "gpu") {
+            self.num_threads + self.gpu_devices.len()
+        } else {
+            self.num_threads
         }
     }
 
@@ -111,7 +129,26 @@ impl Miner {
                 (handle, rx)
             });
 
-        let (threads, channels) = miners.unzip();
+        #[cfg_attr(not(feature = "gpu"), allow(unused_mut))]
+        let (mut threads, mut channels): (Vec<_>, Vec<_>) = miners.unzip();
+
+        #[cfg(feature = "gpu")]
+        for (i, device) in self.gpu_devices.iter().enumerate() {
+            let miner = self.num_threads + i;
+            let (tx, rx) = bounded(1);
+            let device = device.clone();
+            let header = self.header.clone();
+            let waker = ctx.waker().clone();
+            let difficulty = self.target_difficulty;
+            let share_mode = self.share_mode;
+            let handle = thread::Builder::new()
+                .name(format!("gpu-miner-{}", device.device))
+                .spawn(move || crate::gpu::mining_task(device, header, difficulty, tx, waker, miner, share_mode))
+                .expect("Failed to create GPU mining thread");
+            threads.push(handle);
+            channels.push(rx);
+        }
+
         self.threads = threads;
         self.channels = channels;
     }
@@ -123,14 +160,16 @@ impl Stream for Miner {
     fn poll_next(mut self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         trace!(target: LOG_TARGET, "Polling Miner");
         // First poll would start all the threads passing async context waker
-        if self.threads.is_empty() && self.num_threads > 0 {
+        if self.threads.is_empty() && self.num_workers() > 0 {
             debug!(
                 target: LOG_TARGET,
-                "Starting {} mining threads for target difficulty {}", self.num_threads, self.target_difficulty
+                "Starting {} mining threads for target difficulty {}",
+                self.num_workers(),
+                self.target_difficulty
             );
             self.start_threads(ctx);
             return Poll::Pending;
-        } else if self.num_threads == 0 {
+        } else if self.num_workers() == 0 {
             error!(target: LOG_TARGET, "Cannot mine: no mining threads");
             return Poll::Ready(None);
         } else if self.channels.is_empty() {
@@ -26,7 +26,7 @@ use futures::stream::StreamExt;
 use log::*;
 use minotari_app_grpc::{
     authentication::ClientAuthenticationInterceptor,
-    tari_rpc::{base_node_client::BaseNodeClient, TransactionOutput as GrpcTransactionOutput},
+    tari_rpc::base_node_client::BaseNodeClient,
     tls::protocol_string,
 };
 use minotari_app_utilities::parse_miner_input::{
@@ -40,15 +40,10 @@ use tari_common::{
     load_configuration,
     DefaultConfigLoader,
 };
-use tari_common_types::tari_address::TariAddress;
 use tari_core::{
     blocks::BlockHeader,
     consensus::ConsensusManager,
-    transactions::{
-        generate_coinbase,
-        key_manager::{create_memory_db_key_manager, MemoryDbKeyManager},
-        tari_amount::MicroMinotari,
-    },
+    transactions::key_manager::create_memory_db_key_manager,
 };
 use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_utilities::hex::Hex;
@@ -58,9 +53,10 @@ use tonic::transport::{Certificate, ClientTlsConfig, Endpoint};
 use crate::{
     cli::Cli,
     config::MinerConfig,
-    errors::{err_empty, MinerError},
+    errors::MinerError,
     miner::{Miner, MiningReport},
     stratum::stratum_controller::controller::Controller,
+    template_cache::TemplateCache,
 };
 
 pub const LOG_TARGET: &str = "minotari::miner::main";
@@ -68,12 +64,21 @@ pub const LOG_TARGET_FILE: &str = "minotari::logging::miner::main";
 
 #[allow(clippy::too_many_lines)]
 pub async fn start_miner(cli: Cli) -> Result<(), ExitError> {
+    if cli.list_gpu_devices {
+        return list_gpu_devices();
+    }
     let config_path = cli.common.config_path();
     let cfg = load_configuration(config_path.as_path(), true, cli.non_interactive_mode, &cli)?;
     let mut config = MinerConfig::load_from(&cfg).expect("Failed to load config");
     config.set_base_path(cli.common.get_base_path());
 
     debug!(target: LOG_TARGET_FILE, "{:?}", config);
+    if !cfg!(feature = "gpu") && !config.gpu_devices.is_empty() {
+        warn!(
+            target: LOG_TARGET,
+            "GPU devices are configured, but the miner was built without the `gpu` feature. Only the CPU will be used."
+        );
+    }
     let key_manager = create_memory_db_key_manager();
     let wallet_payment_address = wallet_payment_address(config.wallet_payment_address.clone(), config.network)
         .map_err(|err| {
@@ -99,10 +104,12 @@ pub async fn start_miner(cli: Cli) -> Result<(), ExitError> {
         if !config.mining_worker_name.is_empty() {
             miner_address += &format!("{}{}", ".", config.mining_worker_name);
         }
-        let mut mc = Controller::new(config.num_mining_threads).unwrap_or_else(|e| {
-            debug!(target: LOG_TARGET_FILE, "Error loading mining controller: {}", e);
-            panic!("Error loading mining controller: {}", e);
-        });
+        let mut mc = Controller::new(config.num_mining_threads)
+            .unwrap_or_else(|e| {
+                debug!(target: LOG_TARGET_FILE, "Error loading mining controller: {}", e);
+                panic!("Error loading mining controller: {}", e);
+            })
+            .with_gpu_devices(config.gpu_devices.clone());
         let cc = crate::stratum::controller::Controller::new(&url, Some(miner_address), None, None, mc.tx.clone())
             .unwrap_or_else(|e| {
                 debug!(
@@ -135,26 +142,24 @@ pub async fn start_miner(cli: Cli) -> Result<(), ExitError> {
                 let msg = "Could not connect to the base node. \nAre the base node's gRPC mining methods allowed in \
                            its 'config.toml'? Please ensure these methods are enabled in:\n  \
                            'grpc_server_allow_methods': \"get_new_block_template\", \"get_tip_info\", \
-                           \"get_new_block\", \"submit_block\"";
+                           \"get_new_block\", \"submit_block\" and optionally \"subscribe_blocks\"";
                 println!("{}", msg);
                 println!();
                 return Err(ExitError::new(ExitCode::GrpcError, e.to_string()));
             }
         }
 
+        let mut template_cache = TemplateCache::spawn(
+            node_conn.clone(),
+            &config,
+            key_manager.clone(),
+            wallet_payment_address.clone(),
+            consensus_manager.clone(),
+        );
         let mut blocks_found: u64 = 0;
         loop {
             debug!(target: LOG_TARGET, "Starting new mining cycle");
-            match mining_cycle(
-                &mut node_conn,
-                &config,
-                &cli,
-                &key_manager,
-                &wallet_payment_address,
-                &consensus_manager,
-            )
-            .await
-            {
+            match mining_cycle(&mut node_conn, &mut template_cache, &config, &cli).await {
                 err @ Err(MinerError::GrpcConnection(_)) | err @ Err(MinerError::GrpcStatus(_)) => {
                     // Any GRPC error we will try to reconnect with a standard delay
                     error!(target: LOG_TARGET, "Connection error: {:?}", err);
@@ -163,6 +168,13 @@ pub async fn start_miner(cli: Cli) -> Result<(), ExitError> {
                         sleep(config.wait_timeout()).await;
                         match connect(&config).await {
                             Ok(nc) => {
+                                template_cache = TemplateCache::spawn(
+                                    nc.clone(),
+                                    &config,
+                                    key_manager.clone(),
+                                    wallet_payment_address.clone(),
+                                    consensus_manager.clone(),
+                                );
                                 node_conn = nc;
                                 break;
                             },
@@ -185,6 +197,10 @@ pub async fn start_miner(cli: Cli) -> Result<(), ExitError> {
                         target: LOG_TARGET,
                         "Height {} already mined by other node. Restarting ...", h
                     );
+                    // The cached block is for the height that was lost, wait for the block on the new tip
+                    if let Err(err) = template_cache.changed().await {
+                        error!(target: LOG_TARGET, "Error: {:?}", err);
+                    }
                 },
                 Err(err) => {
                     error!(target: LOG_TARGET, "Error: {:?}", err);
@@ -206,6 +222,33 @@ pub async fn start_miner(cli: Cli) -> Result<(), ExitError> {
     }
 }
 
+#[cfg(feature = "gpu")]
+fn list_gpu_devices() -> Result<(), ExitError> {
+    let devices = crate::gpu::list_devices().map_err(|e| ExitError::new(ExitCode::UnknownError, e.to_string()))?;
+    if devices.is_empty() {
+        println!("No GPU devices found");
+    }
+    for device in devices {
+        println!(
+            "{}: {} ({}), {} compute units, {} MiB",
+            device.index,
+            device.name,
+            device.vendor,
+            device.compute_units,
+            device.global_mem_size / 1024 / 1024
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "gpu"))]
+fn list_gpu_devices() -> Result<(), ExitError> {
+    Err(ExitError::new(
+        ExitCode::ConfigError,
+        "The miner was built without GPU support, rebuild it with the `gpu` feature",
+    ))
+}
+
 async fn connect(config: &MinerConfig) -> Result<BaseNodeGrpcClient, MinerError> {
     let node_conn = match connect_base_node(config).await {
         Ok(client) => client,
@@ -269,26 +312,12 @@ async fn verify_base_node_responses(
 #[allow(clippy::too_many_lines)]
 async fn mining_cycle(
     node_conn: &mut BaseNodeGrpcClient,
+    template_cache: &mut TemplateCache,
     config: &MinerConfig,
     cli: &Cli,
-    key_manager: &MemoryDbKeyManager,
-    wallet_payment_address: &TariAddress,
-    consensus_manager: &ConsensusManager,
 ) -> Result<bool, MinerError> {
-    debug!(target: LOG_TARGET, "Getting new block template");
-    let template_response = node_conn
-        .get_new_block_template(config.pow_algo_request())
-        .await?
-        .into_inner();
-    let mut block_template = template_response
-        .new_block_template
-        .clone()
-        .ok_or_else(|| err_empty("new_block_template"))?;
-    let height = block_template
-        .header
-        .as_ref()
-        .ok_or_else(|| err_empty("header"))?
-        .height;
+    let prepared = template_cache.next_block().await?;
+    let height = prepared.height;
 
     if config.mine_on_tip_only {
         debug!(
@@ -298,45 +327,27 @@ async fn mining_cycle(
         validate_tip(node_conn, height, cli.mine_until_height).await?;
     }
 
-    debug!(target: LOG_TARGET, "Getting coinbase");
-    let miner_data = template_response.miner_data.ok_or_else(|| err_empty("miner_data"))?;
-    let fee = MicroMinotari::from(miner_data.total_fees);
-    let reward = MicroMinotari::from(miner_data.reward);
-    let (coinbase_output, coinbase_kernel) = generate_coinbase(
-        fee,
-        reward,
-        height,
-        config.coinbase_extra.as_bytes(),
-        key_manager,
-        wallet_payment_address,
-        config.stealth_payment,
-        consensus_manager.consensus_constants(height),
-        config.range_proof_type,
-    )
-    .await
-    .map_err(|e| MinerError::CoinbaseError(e.to_string()))?;
-    debug!(target: LOG_TARGET, "Coinbase kernel: {}", coinbase_kernel);
-    debug!(target: LOG_TARGET, "Coinbase output: {}", coinbase_output);
-
-    let body = block_template
-        .body
-        .as_mut()
-        .ok_or_else(|| err_empty("new_block_template.body"))?;
-    let grpc_output = GrpcTransactionOutput::try_from(coinbase_output.clone()).map_err(MinerError::Conversion)?;
-    body.outputs.push(grpc_output);
-    body.kernels.push(coinbase_kernel.into());
-    let target_difficulty = miner_data.target_difficulty;
-
-    debug!(target: LOG_TARGET, "Asking base node to assemble the MMR roots");
-    let block_result = node_conn.get_new_block(block_template).await?.into_inner();
-    let block = block_result.block.ok_or_else(|| err_empty("block"))?;
-    let header = block.clone().header.ok_or_else(|| err_empty("block.header"))?;
+    let block = prepared.block.clone();
+    let header = prepared.header.clone();
+    let target_difficulty = prepared.target_difficulty;
 
     debug!(target: LOG_TARGET, "Initializing miner");
-    let mut reports = Miner::init_mining(header.clone(), target_difficulty, config.num_mining_threads, false);
+    let mut reports = Miner::init_mining(header.clone(), target_difficulty, config.num_mining_threads, false)
+        .with_gpu_devices(config.gpu_devices.clone());
     let mut reporting_timeout = Instant::now();
     let mut block_submitted = false;
-    while let Some(report) = reports.next().await {
+    loop {
+        let report = tokio::select! {
+            report = reports.next() => match report {
+                Some(report) => report,
+                None => break,
+            },
+            changed = template_cache.changed() => {
+                changed?;
+                debug!(target: LOG_TARGET, "A new block template is available, restarting the miner");
+                break;
+            },
+        };
         if let Some(header) = report.header.clone() {
             let mut submit = true;
             if let Some(min_diff) = cli.miner_min_diff {
@@ -29,6 +29,7 @@ use minotari_app_grpc::tari_rpc::BlockHeader;
 use tari_utilities::hex::Hex;
 
 use crate::{
+    config::GpuDeviceConfig,
     miner::Miner,
     run_miner::display_report,
     stratum::{error::Error, stratum_types as types},
@@ -48,6 +49,7 @@ pub struct Controller {
     current_header: Option<BlockHeader>,
     keep_alive_time: SystemTime,
     num_mining_threads: usize,
+    gpu_devices: Vec<GpuDeviceConfig>,
 }
 
 impl Controller {
@@ -64,9 +66,15 @@ impl Controller {
             current_header: None,
             keep_alive_time: SystemTime::now(),
             num_mining_threads,
+            gpu_devices: vec![],
         })
     }
 
+    pub fn with_gpu_devices(mut self, gpu_devices: Vec<GpuDeviceConfig>) -> Self {
+        self.gpu_devices = gpu_devices;
+        self
+    }
+
     pub fn set_client_tx(&mut self, client_tx: mpsc::Sender<types::client_message::ClientMessage>) {
         self.client_tx = Some(client_tx);
     }
@@ -90,12 +98,15 @@ impl Controller {
                                     if let Some(acive_miner) = miner.as_mut() {
                                         acive_miner.kill_threads();
                                     }
-                                    miner = Some(Miner::init_mining(
-                                        header,
-                                        self.current_difficulty_target,
-                                        self.num_mining_threads,
-                                        true,
-                                    ));
+                                    miner = Some(
+                                        Miner::init_mining(
+                                            header,
+                                            self.current_difficulty_target,
+                                            self.num_mining_threads,
+                                            true,
+                                        )
+                                        .with_gpu_devices(self.gpu_devices.clone()),
+                                    );
                                 } else {
                                     continue;
                                 }
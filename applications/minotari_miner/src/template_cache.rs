@@ -0,0 +1,296 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A warm cache of the next block to mine. A background task prepares a new block (the block template with the
+//! coinbase added and the MMR roots assembled) as soon as the base node reports a new chain tip, so that the miner can
+//! switch to the new tip without waiting for the base node round trips.
+
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+use log::*;
+use minotari_app_grpc::tari_rpc::{
+    Block,
+    BlockHeader,
+    Empty,
+    NewBlockTemplateRequest,
+    TransactionOutput as GrpcTransactionOutput,
+};
+use minotari_app_utilities::parse_miner_input::BaseNodeGrpcClient;
+use tari_common_types::tari_address::TariAddress;
+use tari_core::{
+    consensus::ConsensusManager,
+    transactions::{
+        generate_coinbase,
+        key_manager::MemoryDbKeyManager,
+        tari_amount::MicroMinotari,
+        transaction_components::RangeProofType,
+        CoinbaseExtra,
+    },
+};
+use tokio::{
+    sync::watch,
+    time::{interval, sleep, MissedTickBehavior},
+};
+
+use crate::{
+    config::MinerConfig,
+    errors::{err_empty, MinerError},
+};
+
+pub const LOG_TARGET: &str = "minotari::miner::template_cache";
+
+/// A block that is ready to be mined
+#[derive(Debug, Clone)]
+pub struct PreparedBlock {
+    pub block: Block,
+    pub header: BlockHeader,
+    pub height: u64,
+    pub target_difficulty: u64,
+}
+
+/// The receiving end of the template cache. The prefetch task stops once the cache is dropped.
+pub struct TemplateCache {
+    receiver: watch::Receiver<Option<Arc<PreparedBlock>>>,
+}
+
+impl TemplateCache {
+    /// Start the prefetch task for the base node connection
+    pub fn spawn(
+        node_conn: BaseNodeGrpcClient,
+        config: &MinerConfig,
+        key_manager: MemoryDbKeyManager,
+        wallet_payment_address: TariAddress,
+        consensus_manager: ConsensusManager,
+    ) -> Self {
+        let (sender, receiver) = watch::channel(None);
+        let prefetcher = TemplatePrefetcher {
+            node_conn,
+            template_request: config.pow_algo_request(),
+            coinbase_extra: config.coinbase_extra.as_bytes().to_vec(),
+            structured_coinbase_extra: config.structured_coinbase_extra(),
+            stealth_payment: config.stealth_payment,
+            range_proof_type: config.range_proof_type,
+            key_manager,
+            wallet_payment_address,
+            consensus_manager,
+            refresh_interval: config.template_refresh_interval(),
+            retry_interval: config.wait_timeout(),
+            sender,
+        };
+        tokio::spawn(prefetcher.run());
+        Self { receiver }
+    }
+
+    /// Returns the latest prepared block, waiting for one if none has been prepared yet. The block is marked as seen
+    /// by [changed](Self::changed).
+    pub async fn next_block(&mut self) -> Result<Arc<PreparedBlock>, MinerError> {
+        loop {
+            if let Some(block) = self.receiver.borrow_and_update().clone() {
+                return Ok(block);
+            }
+            self.changed().await?;
+        }
+    }
+
+    /// Resolves once a newer block than the one returned by [next_block](Self::next_block) has been prepared
+    pub async fn changed(&mut self) -> Result<(), MinerError> {
+        self.receiver
+            .changed()
+            .await
+            .map_err(|_| MinerError::TemplateCacheStopped)
+    }
+}
+
+struct TemplatePrefetcher {
+    node_conn: BaseNodeGrpcClient,
+    template_request: NewBlockTemplateRequest,
+    coinbase_extra: Vec<u8>,
+    structured_coinbase_extra: Option<CoinbaseExtra>,
+    stealth_payment: bool,
+    range_proof_type: RangeProofType,
+    key_manager: MemoryDbKeyManager,
+    wallet_payment_address: TariAddress,
+    consensus_manager: ConsensusManager,
+    refresh_interval: Duration,
+    retry_interval: Duration,
+    sender: watch::Sender<Option<Arc<PreparedBlock>>>,
+}
+
+impl TemplatePrefetcher {
+    async fn run(mut self) {
+        while !self.sender.is_closed() {
+            match self.node_conn.subscribe_blocks(Empty {}).await {
+                Ok(stream) => {
+                    debug!(target: LOG_TARGET, "Subscribed to base node tip changes");
+                    self.prefetch_on_tip_changes(stream.into_inner()).await;
+                },
+                Err(status) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Could not subscribe to base node tip changes ({}), polling the tip instead", status
+                    );
+                    self.prefetch_on_tip_poll().await;
+                },
+            }
+            sleep(self.retry_interval).await;
+        }
+    }
+
+    /// Prepare a new block every time the base node's tip changes. The block is also refreshed periodically so that
+    /// new transactions are included. Returns when the stream ends or fails.
+    async fn prefetch_on_tip_changes(
+        &mut self,
+        mut stream: tonic::Streaming<minotari_app_grpc::tari_rpc::SubscribeBlocksResponse>,
+    ) {
+        let mut refresh = interval(self.refresh_interval);
+        refresh.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately, the stream sends the current tip when subscribing
+        refresh.tick().await;
+        loop {
+            tokio::select! {
+                message = stream.message() => match message {
+                    Ok(Some(tip)) => {
+                        debug!(target: LOG_TARGET, "Base node tip changed to height {}", tip.tip_height);
+                        refresh.reset();
+                        self.prefetch().await;
+                    },
+                    Ok(None) => return,
+                    Err(status) => {
+                        warn!(target: LOG_TARGET, "Base node tip stream failed: {}", status);
+                        return;
+                    },
+                },
+                _ = refresh.tick() => self.prefetch().await,
+                _ = self.sender.closed() => return,
+            }
+        }
+    }
+
+    /// Fallback for base nodes that do not allow `subscribe_blocks`. Returns when the tip cannot be fetched.
+    async fn prefetch_on_tip_poll(&mut self) {
+        let mut poll = interval(self.retry_interval.min(self.refresh_interval));
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_tip = None;
+        let mut refresh = interval(self.refresh_interval);
+        refresh.tick().await;
+        loop {
+            tokio::select! {
+                _ = poll.tick() => {
+                    let tip = match self.node_conn.get_tip_info(Empty {}).await {
+                        Ok(tip) => tip.into_inner().metadata.map(|m| m.best_block_hash),
+                        Err(status) => {
+                            warn!(target: LOG_TARGET, "Could not get the base node tip: {}", status);
+                            return;
+                        },
+                    };
+                    if tip != last_tip {
+                        last_tip = tip;
+                        refresh.reset();
+                        self.prefetch().await;
+                    }
+                },
+                _ = refresh.tick() => self.prefetch().await,
+                _ = self.sender.closed() => return,
+            }
+        }
+    }
+
+    async fn prefetch(&mut self) {
+        match self.prepare_block().await {
+            Ok(block) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Prepared block #{} with target difficulty {}", block.height, block.target_difficulty
+                );
+                self.sender.send_replace(Some(Arc::new(block)));
+            },
+            Err(err) => warn!(target: LOG_TARGET, "Could not prepare a new block: {}", err),
+        }
+    }
+
+    async fn prepare_block(&mut self) -> Result<PreparedBlock, MinerError> {
+        debug!(target: LOG_TARGET, "Getting new block template");
+        let template_response = self
+            .node_conn
+            .get_new_block_template(self.template_request.clone())
+            .await?
+            .into_inner();
+        let mut block_template = template_response
+            .new_block_template
+            .ok_or_else(|| err_empty("new_block_template"))?;
+        let height = block_template
+            .header
+            .as_ref()
+            .ok_or_else(|| err_empty("header"))?
+            .height;
+
+        debug!(target: LOG_TARGET, "Getting coinbase");
+        let miner_data = template_response.miner_data.ok_or_else(|| err_empty("miner_data"))?;
+        let fee = MicroMinotari::from(miner_data.total_fees);
+        let reward = MicroMinotari::from(miner_data.reward);
+        // Older base nodes do not report the maximum size, in which case the size is checked when the coinbase is built
+        let max_extra_length = match miner_data.coinbase_extra_max_length {
+            0 => u32::MAX,
+            max => u32::try_from(max).unwrap_or(u32::MAX),
+        };
+        let coinbase_extra = match &self.structured_coinbase_extra {
+            Some(extra) => extra
+                .to_bytes(max_extra_length)
+                .map_err(|e| MinerError::CoinbaseError(e.to_string()))?,
+            None => self.coinbase_extra.clone(),
+        };
+        let (coinbase_output, coinbase_kernel) = generate_coinbase(
+            fee,
+            reward,
+            height,
+            &coinbase_extra,
+            &self.key_manager,
+            &self.wallet_payment_address,
+            self.stealth_payment,
+            self.consensus_manager.consensus_constants(height),
+            self.range_proof_type,
+        )
+        .await
+        .map_err(|e| MinerError::CoinbaseError(e.to_string()))?;
+        debug!(target: LOG_TARGET, "Coinbase kernel: {}", coinbase_kernel);
+        debug!(target: LOG_TARGET, "Coinbase output: {}", coinbase_output);
+
+        let body = block_template
+            .body
+            .as_mut()
+            .ok_or_else(|| err_empty("new_block_template.body"))?;
+        let grpc_output = GrpcTransactionOutput::try_from(coinbase_output.clone()).map_err(MinerError::Conversion)?;
+        body.outputs.push(grpc_output);
+        body.kernels.push(coinbase_kernel.into());
+
+        debug!(target: LOG_TARGET, "Asking base node to assemble the MMR roots");
+        let block_result = self.node_conn.get_new_block(block_template).await?.into_inner();
+        let block = block_result.block.ok_or_else(|| err_empty("block"))?;
+        let header = block.clone().header.ok_or_else(|| err_empty("block.header"))?;
+        Ok(PreparedBlock {
+            block,
+            header,
+            height,
+            target_difficulty: miner_data.target_difficulty,
+        })
+    }
+}
@@ -132,19 +132,21 @@ where B: BlockchainBackend + 'static
                     .expect("Unable to parse application version. Not valid semver"),
                 self.app_config.auto_update.clone(),
             ))
-            .add_initializer(BaseNodeServiceInitializer::new(
-                peer_message_subscriptions.clone(),
-                self.db.clone().into(),
-                self.mempool.clone(),
-                self.rules.clone(),
-                base_node_config.messaging_request_timeout,
-                self.randomx_factory.clone(),
-                base_node_config.state_machine.clone(),
-            ))
-            .add_initializer(MempoolServiceInitializer::new(
-                self.mempool.clone(),
-                peer_message_subscriptions.clone(),
-            ))
+            .add_initializer(
+                BaseNodeServiceInitializer::new(
+                    peer_message_subscriptions.clone(),
+                    self.db.clone().into(),
+                    self.mempool.clone(),
+                    self.rules.clone(),
+                    base_node_config.messaging_request_timeout,
+                    self.randomx_factory.clone(),
+                )
+                .with_compact_block_relay(base_node_config.compact_block_relay),
+            )
+            .add_initializer(
+                MempoolServiceInitializer::new(self.mempool.clone(), peer_message_subscriptions.clone())
+                    .with_persistence(base_node_config.mempool.persistence.clone()),
+            )
             .add_initializer(mempool_sync)
             .add_initializer(LivenessInitializer::new(
                 LivenessConfig {
@@ -189,9 +191,7 @@ where B: BlockchainBackend + 'static
                     return;
                 }
                 let address = result.unwrap();
-                if !node_id.public_addresses().contains(&address) {
-                    node_id.add_public_address(address);
-                }
+                node_id.replace_onion_address(address);
                 if let Err(e) = identity_management::save_as_json(&node_id_path, &*node_id) {
                     error!(target: LOG_TARGET, "Failed to save node identity identity{:?}", e);
                 }
@@ -31,7 +31,15 @@ use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, Com
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
-    chain_storage::{create_lmdb_database, BlockchainDatabase, ChainStorageError, LMDBDatabase, Validators},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        create_lmdb_database,
+        run_lmdb_maintenance,
+        BlockchainDatabase,
+        ChainStorageError,
+        LMDBDatabase,
+        Validators,
+    },
     consensus::ConsensusManager,
     mempool::{service::LocalMempoolService, Mempool},
     proof_of_work::randomx_factory::RandomXFactory,
@@ -46,7 +54,7 @@ use tari_core::{
 use tari_p2p::{auto_update::SoftwareUpdaterHandle, services::liveness::LivenessHandle};
 use tari_service_framework::ServiceHandles;
 use tari_shutdown::ShutdownSignal;
-use tokio::sync::watch;
+use tokio::{sync::watch, task};
 
 use crate::{bootstrap::BaseNodeBootstrapper, ApplicationConfig, DatabaseType};
 
@@ -62,6 +70,7 @@ pub struct BaseNodeContext {
     base_node_comms: CommsNode,
     base_node_dht: Dht,
     base_node_handles: ServiceHandles,
+    mempool: Mempool,
 }
 
 impl BaseNodeContext {
@@ -73,6 +82,19 @@ impl BaseNodeContext {
 
         self.base_node_comms.wait_until_shutdown().await;
         info!(target: LOG_TARGET, "Communications stack has shutdown");
+
+        let persistence = &self.config.base_node.mempool.persistence;
+        if persistence.enabled {
+            match self.mempool.save_snapshot(persistence.snapshot_file.clone()).await {
+                Ok(num_saved) => info!(
+                    target: LOG_TARGET,
+                    "Saved {} mempool transaction(s) to {}",
+                    num_saved,
+                    persistence.snapshot_file.display()
+                ),
+                Err(e) => warn!(target: LOG_TARGET, "Could not save the mempool: {}", e),
+            }
+        }
     }
 
     /// Return the node config
@@ -95,6 +117,11 @@ impl BaseNodeContext {
         &self.base_node_comms
     }
 
+    /// Returns the Mempool, which can be used to subscribe to mempool events
+    pub fn mempool(&self) -> Mempool {
+        self.mempool.clone()
+    }
+
     /// Returns the liveness service handle
     pub fn liveness(&self) -> LivenessHandle {
         self.base_node_handles.expect_handle()
@@ -238,6 +265,12 @@ async fn build_node_context(
         }
     })?;
 
+    task::spawn(run_lmdb_maintenance(
+        AsyncBlockchainDb::from(blockchain_db.clone()),
+        app_config.base_node.lmdb_maintenance,
+        interrupt_signal.clone(),
+    ));
+
     let mempool_validator = TransactionFullValidator::new(
         factories.clone(),
         app_config.base_node.bypass_range_proof_verification,
@@ -257,7 +290,7 @@ async fn build_node_context(
         app_config: &app_config,
         node_identity: base_node_identity,
         db: blockchain_db.clone(),
-        mempool,
+        mempool: mempool.clone(),
         rules: rules.clone(),
         factories: factories.clone(),
         randomx_factory,
@@ -276,5 +309,6 @@ async fn build_node_context(
         base_node_comms,
         base_node_dht,
         base_node_handles,
+        mempool,
     })
 }
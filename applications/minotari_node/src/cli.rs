@@ -37,6 +37,12 @@ pub struct Cli {
     /// This will rebuild the db, adding block for block in
     #[clap(long, alias = "rebuild_db")]
     pub rebuild_db: bool,
+    /// Check the db for missing or inconsistent chain data and exit
+    #[clap(long, alias = "check_db")]
+    pub check_db: bool,
+    /// Check the db and repair any inconsistencies, truncating the chain back to the last consistent height if needed
+    #[clap(long, alias = "repair_db")]
+    pub repair_db: bool,
     /// Run in non-interactive mode, with no UI.
     #[clap(short, long, alias = "non-interactive", env = "TARI_NON_INTERACTIVE")]
     pub non_interactive_mode: bool,
@@ -0,0 +1,81 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use clap::Parser;
+use tari_core::consensus::conformance::ConformanceVectors;
+
+use super::{CommandContext, HandleCommand};
+
+/// Checks that this node produces the canonical consensus encodings. Use `--export` to write the vectors to a file
+/// and `--vectors` to check them against a file exported by another version.
+#[derive(Debug, Parser)]
+pub struct Args {
+    /// Check the consensus encodings against the vectors in this file
+    #[clap(long)]
+    vectors: Option<PathBuf>,
+    /// Write the consensus encoding vectors of this node to this file
+    #[clap(long)]
+    export: Option<PathBuf>,
+}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, args: Args) -> Result<(), Error> {
+        self.check_consensus_encoding(args)
+    }
+}
+
+impl CommandContext {
+    /// Function to process the check-consensus-encoding command
+    pub fn check_consensus_encoding(&self, args: Args) -> Result<(), Error> {
+        let vectors = ConformanceVectors::generate()?;
+        println!("Consensus encoding vectors ({}):", vectors.network);
+        for vector in &vectors.vectors {
+            println!("  {}", vector);
+        }
+
+        if let Some(path) = args.export {
+            fs::write(&path, serde_json::to_string_pretty(&vectors)?)?;
+            println!("Vectors written to {}", path.display());
+        }
+
+        if let Some(path) = args.vectors {
+            let expected: ConformanceVectors = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            if let Err(errors) = expected.check() {
+                for error in &errors {
+                    println!("  {}", error);
+                }
+                return Err(anyhow!(
+                    "{} consensus encoding vector(s) in {} do not match",
+                    errors.len(),
+                    path.display()
+                ));
+            }
+            println!("All {} vectors in {} match", expected.vectors.len(), path.display());
+        }
+        Ok(())
+    }
+}
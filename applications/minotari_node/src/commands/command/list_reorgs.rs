@@ -47,7 +47,7 @@ impl CommandContext {
         if self.config.base_node.storage.track_reorgs {
             let reorgs = self.blockchain_db.inner().fetch_all_reorgs()?;
             let mut table = Table::new();
-            table.set_titles(vec!["#", "New Tip", "Prev Tip", "Depth", "Timestamp"]);
+            table.set_titles(vec!["#", "New Tip", "Prev Tip", "Depth", "Reinstated Txs", "Timestamp"]);
 
             for (i, reorg) in reorgs.iter().enumerate() {
                 table.add_row(row![
@@ -55,6 +55,7 @@ impl CommandContext {
                     format!("#{} ({})", reorg.new_height, reorg.new_hash.to_hex()),
                     format!("#{} ({})", reorg.prev_height, reorg.prev_hash.to_hex()),
                     format!("{} added, {} removed", reorg.num_blocks_added, reorg.num_blocks_removed),
+                    reorg.reinstated_transactions.len(),
                     reorg.local_time
                 ]);
             }
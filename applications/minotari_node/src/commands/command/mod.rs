@@ -23,6 +23,7 @@
 mod add_peer;
 mod ban_peer;
 mod block_timing;
+mod check_consensus_encoding;
 mod check_db;
 mod check_for_updates;
 mod create_tls_certs;
@@ -46,10 +47,12 @@ mod list_validator_nodes;
 mod period_stats;
 mod ping_peer;
 mod quit;
+mod reconstruct_mmrs;
 mod reset_offline_peers;
 mod rewind_blockchain;
 mod search_kernel;
 mod search_utxo;
+mod set_pruning_horizon;
 mod status;
 mod unban_all_peers;
 mod version;
@@ -76,13 +79,16 @@ use tari_comms_dht::{DhtDiscoveryRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface},
     blocks::ChainHeader,
-    chain_storage::{async_db::AsyncBlockchainDb, LMDBDatabase},
+    chain_storage::{async_db::AsyncBlockchainDb, LMDBDatabase, MmrReconstructionProgress},
     consensus::ConsensusManager,
     mempool::service::LocalMempoolService,
 };
 use tari_p2p::{auto_update::SoftwareUpdaterHandle, services::liveness::LivenessHandle};
 use tari_shutdown::Shutdown;
-use tokio::{sync::watch, time};
+use tokio::{
+    sync::{broadcast, watch},
+    time,
+};
 pub use watch_command::WatchCommand;
 
 use crate::{
@@ -111,6 +117,8 @@ pub enum Command {
     PingPeer(ping_peer::Args),
     ResetOfflinePeers(reset_offline_peers::Args),
     RewindBlockchain(rewind_blockchain::Args),
+    SetPruningHorizon(set_pruning_horizon::Args),
+    ReconstructMmrs(reconstruct_mmrs::Args),
     AddPeer(add_peer::ArgsAddPeer),
     BanPeer(ban_peer::ArgsBan),
     UnbanPeer(ban_peer::ArgsUnban),
@@ -119,6 +127,7 @@ pub enum Command {
     ListConnections(list_connections::Args),
     ListHeaders(list_headers::Args),
     CheckDb(check_db::Args),
+    CheckConsensusEncoding(check_consensus_encoding::Args),
     PeriodStats(period_stats::Args),
     HeaderStats(header_stats::Args),
     BlockTiming(block_timing::Args),
@@ -166,6 +175,8 @@ pub struct CommandContext {
     state_machine_info: watch::Receiver<StatusInfo>,
     pub software_updater: SoftwareUpdaterHandle,
     last_time_full: Instant,
+    mmr_reconstruction: Option<broadcast::Receiver<MmrReconstructionProgress>>,
+    last_mmr_reconstruction_progress: Option<MmrReconstructionProgress>,
     pub shutdown: Shutdown,
 }
 
@@ -186,6 +197,8 @@ impl CommandContext {
             state_machine_info: ctx.get_state_machine_info_channel(),
             software_updater: ctx.software_updater(),
             last_time_full: Instant::now(),
+            mmr_reconstruction: None,
+            last_mmr_reconstruction_progress: None,
             shutdown,
         }
     }
@@ -210,6 +223,7 @@ impl CommandContext {
                 Command::DialPeer(_) |
                 Command::PingPeer(_) |
                 Command::DiscoverPeer(_) |
+                Command::ReconstructMmrs(_) |
                 Command::ListPeers(_) |
                 Command::ListBannedPeers(_) |
                 Command::ListConnections(_) |
@@ -231,10 +245,14 @@ impl CommandContext {
                 Command::Watch(_) |
                 Command::ListValidatorNodes(_) |
                 Command::CreateTlsCerts(_) |
+                Command::CheckConsensusEncoding(_) |
                 Command::Quit(_) |
                 Command::Exit(_) => 30,
                 // These commands involve intense blockchain db operations and needs a lot of time to complete
-                Command::CheckDb(_) | Command::PeriodStats(_) | Command::RewindBlockchain(_) => 600,
+                Command::CheckDb(_) |
+                Command::PeriodStats(_) |
+                Command::RewindBlockchain(_) |
+                Command::SetPruningHorizon(_) => 600,
             };
             let fut = self.handle_command(args.command);
             if let Err(e) = time::timeout(Duration::from_secs(time_out), fut).await? {
@@ -276,9 +294,12 @@ impl HandleCommand<Command> for CommandContext {
             Command::UnbanPeer(args) => self.handle_command(args).await,
             Command::ResetOfflinePeers(args) => self.handle_command(args).await,
             Command::RewindBlockchain(args) => self.handle_command(args).await,
+            Command::SetPruningHorizon(args) => self.handle_command(args).await,
+            Command::ReconstructMmrs(args) => self.handle_command(args).await,
             Command::UnbanAllPeers(args) => self.handle_command(args).await,
             Command::ListHeaders(args) => self.handle_command(args).await,
             Command::CheckDb(args) => self.handle_command(args).await,
+            Command::CheckConsensusEncoding(args) => self.handle_command(args).await,
             Command::PeriodStats(args) => self.handle_command(args).await,
             Command::HeaderStats(args) => self.handle_command(args).await,
             Command::BlockTiming(args) => self.handle_command(args).await,
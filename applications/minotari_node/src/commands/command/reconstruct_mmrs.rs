@@ -0,0 +1,67 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+use tokio::{sync::broadcast, task};
+
+use super::{CommandContext, HandleCommand};
+use crate::LOG_TARGET;
+
+/// Rebuilds the kernel MMR and output SMT from the kernels and outputs in the blockchain database. This runs in the
+/// background and its progress is shown in the status line.
+#[derive(Debug, Parser)]
+pub struct Args {}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, _: Args) -> Result<(), Error> {
+        self.reconstruct_mmrs().await
+    }
+}
+
+impl CommandContext {
+    pub async fn reconstruct_mmrs(&mut self) -> Result<(), Error> {
+        if self.mmr_reconstruction.is_some() {
+            println!("MMR reconstruction is already in progress");
+            return Ok(());
+        }
+        let (tx, rx) = broadcast::channel(100);
+        self.mmr_reconstruction = Some(rx);
+        let db = self.blockchain_db.clone();
+        task::spawn(async move {
+            println!("MMR reconstruction started");
+            match db.reconstruct_mmrs(Some(tx)).await {
+                Ok(result) => println!(
+                    "MMR reconstruction completed at height {}: {} kernels and {} unspent outputs",
+                    result.height, result.kernel_mmr_size, result.output_smt_size
+                ),
+                Err(err) => {
+                    log::error!(target: LOG_TARGET, "MMR reconstruction failed: {}", err);
+                    println!("MMR reconstruction failed: {}", err);
+                },
+            }
+        });
+        Ok(())
+    }
+}
@@ -28,7 +28,8 @@ use chrono::{DateTime, NaiveDateTime, Utc};
 use clap::Parser;
 use minotari_app_utilities::consts;
 use tari_comms::connection_manager::LivenessStatus;
-use tokio::time;
+use tari_core::chain_storage::MmrReconstructionProgress;
+use tokio::{sync::broadcast::error::TryRecvError, time};
 
 use super::{CommandContext, HandleCommand};
 use crate::commands::status_line::{StatusLine, StatusLineOutput};
@@ -62,6 +63,9 @@ impl CommandContext {
         status_line.add_field("", format!("v{}", consts::APP_VERSION_NUMBER));
         status_line.add_field("", self.config.network());
         status_line.add_field("State", self.state_machine_info.borrow().state_info.short_desc());
+        if let Some(progress) = self.poll_mmr_reconstruction_progress() {
+            status_line.add_field("MMR rebuild", progress);
+        }
 
         let metadata = self.node_service.get_metadata().await?;
         let height = metadata.best_block_height();
@@ -160,4 +164,22 @@ impl CommandContext {
         };
         Ok(())
     }
+
+    /// Returns the latest progress of a running MMR reconstruction, or None if no reconstruction is running
+    fn poll_mmr_reconstruction_progress(&mut self) -> Option<MmrReconstructionProgress> {
+        let events = self.mmr_reconstruction.as_mut()?;
+        loop {
+            match events.try_recv() {
+                Ok(progress) => self.last_mmr_reconstruction_progress = Some(progress),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Closed) => {
+                    self.mmr_reconstruction = None;
+                    self.last_mmr_reconstruction_progress = None;
+                    break;
+                },
+            }
+        }
+        self.last_mmr_reconstruction_progress
+    }
 }
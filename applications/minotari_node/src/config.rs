@@ -35,11 +35,11 @@ use tari_common::{
     DefaultConfigLoader,
     SubConfigPath,
 };
-use tari_common_types::grpc_authentication::GrpcAuthentication;
+use tari_common_types::grpc_authentication::{GrpcAuthentication, GrpcClientCertificate};
 use tari_comms::multiaddr::Multiaddr;
 use tari_core::{
     base_node::BaseNodeStateMachineConfig,
-    chain_storage::BlockchainDatabaseConfig,
+    chain_storage::{BlockchainDatabaseConfig, LmdbMaintenanceConfig},
     mempool::MempoolConfig,
 };
 use tari_p2p::{auto_update::AutoUpdateConfig, P2pConfig, PeerSeedsConfig};
@@ -95,6 +95,11 @@ pub struct BaseNodeConfig {
     pub grpc_authentication: GrpcAuthentication,
     /// GRPC tls enabled
     pub grpc_tls_enabled: bool,
+    /// Require GRPC TLS clients to present a certificate signed by the CA in `client_ca.pem` in the config directory
+    pub grpc_tls_client_auth_enabled: bool,
+    /// The TLS client certificates that may call the GRPC server, with the methods each may call. Only used if client
+    /// certificate authentication is enabled. If empty, any certificate signed by the client CA is accepted.
+    pub grpc_tls_client_certificates: Vec<GrpcClientCertificate>,
     /// Enable mining on the base node, overriding other settings regarding mining
     pub mining_enabled: bool,
     /// Enable second layer specific grpc methods.
@@ -110,6 +115,8 @@ pub struct BaseNodeConfig {
     pub db_type: DatabaseType,
     /// The lmdb config settings
     pub lmdb: LMDBConfig,
+    /// The lmdb maintenance task settings
+    pub lmdb_maintenance: LmdbMaintenanceConfig,
     /// The relative path to store persistent data
     pub data_dir: PathBuf,
     /// The relative path to the config directory
@@ -143,6 +150,9 @@ pub struct BaseNodeConfig {
     pub state_machine: BaseNodeStateMachineConfig,
     /// Obscure GRPC error responses
     pub report_grpc_error: bool,
+    /// Propagate blocks as compact blocks, identifying their transactions by short IDs. Peers that do not support
+    /// compact blocks cannot reconstruct these, so this should only be enabled once the network has upgraded.
+    pub compact_block_relay: bool,
 }
 
 impl Default for BaseNodeConfig {
@@ -160,6 +170,8 @@ impl Default for BaseNodeConfig {
             grpc_server_allow_methods: vec![GrpcMethod::GetVersion],
             grpc_authentication: GrpcAuthentication::default(),
             grpc_tls_enabled: false,
+            grpc_tls_client_auth_enabled: false,
+            grpc_tls_client_certificates: vec![],
             mining_enabled: false,
             second_layer_grpc_enabled: false,
             identity_file: PathBuf::from("config/base_node_id.json"),
@@ -168,6 +180,7 @@ impl Default for BaseNodeConfig {
             p2p,
             db_type: DatabaseType::Lmdb,
             lmdb: Default::default(),
+            lmdb_maintenance: Default::default(),
             data_dir: PathBuf::from("data/base_node"),
             config_dir: PathBuf::from("config/base_node"),
             lmdb_path: PathBuf::from("db"),
@@ -182,6 +195,7 @@ impl Default for BaseNodeConfig {
             metadata_auto_ping_interval: Duration::from_secs(30),
             state_machine: Default::default(),
             report_grpc_error: false,
+            compact_block_relay: false,
         }
     }
 }
@@ -209,6 +223,10 @@ impl BaseNodeConfig {
         if !self.lmdb_path.is_absolute() {
             self.lmdb_path = self.data_dir.join(self.lmdb_path.as_path());
         }
+        if !self.mempool.persistence.snapshot_file.is_absolute() {
+            self.mempool.persistence.snapshot_file =
+                self.data_dir.join(self.mempool.persistence.snapshot_file.as_path());
+        }
         self.p2p.set_base_path(base_path);
     }
 }
@@ -254,11 +272,26 @@ pub enum GrpcMethod {
     Identify,
     GetNetworkStatus,
     ListConnectedPeers,
+    ListPeerOffenses,
+    GetNetworkUsage,
     GetMempoolStats,
     GetActiveValidatorNodes,
     GetShardKey,
+    GetValidatorNodeChanges,
     GetTemplateRegistrations,
+    GetTemplateRegistration,
     GetSideChainUtxos,
+    SubscribeBlocks,
+    GetSupportedTemplateVersions,
+    EstimateFeePerGram,
+    GetMempoolDependencyGraph,
+    CompactDatabase,
+    GetReorgHistory,
+    SearchPaymentReferences,
+    SearchOneSidedScriptKey,
+    SetSyncBandwidthLimit,
+    SubscribeSyncState,
+    SubscribeMempool,
 }
 
 impl fmt::Display for GrpcMethod {
@@ -23,6 +23,8 @@
 use std::{
     cmp,
     convert::{TryFrom, TryInto},
+    ops::RangeInclusive,
+    sync::Arc,
 };
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -35,52 +37,61 @@ use minotari_app_grpc::{
 };
 use minotari_app_utilities::consts;
 use tari_common_types::{
+    epoch::VnEpoch,
     tari_address::TariAddress,
     types::{Commitment, FixedHash, PublicKey, Signature},
 };
 use tari_comms::{Bytes, CommsNode};
 use tari_core::{
     base_node::{
-        comms_interface::CommsInterfaceError,
-        state_machine_service::states::StateInfo,
+        comms_interface::{BlockEvent, CommsInterfaceError},
+        state_machine_service::states::{StateInfo, StatusInfo},
+        sync::{HorizonSyncStatus, SyncPeer},
         LocalNodeCommsInterface,
         StateMachineHandle,
     },
-    blocks::{Block, BlockHeader, NewBlockTemplate},
-    chain_storage::ChainStorageError,
+    blocks::{Block, BlockHeader, ChainBlock, ChainHeader, HistoricalBlock, NewBlockTemplate},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        BlockAddResult,
+        ChainStorageError,
+        LMDBDatabase,
+        OutputMinedInfo,
+        ValidatorNodeChangeKind,
+    },
     consensus::{emission::Emission, ConsensusManager, NetworkConsensus},
     iterators::NonOverlappingIntegerPairIter,
-    mempool::{service::LocalMempoolService, TxStorageResponse},
-    proof_of_work::PowAlgorithm,
+    mempool::{service::LocalMempoolService, DependencyRelation, Mempool, MempoolEvent, TxStorageResponse},
+    payment_reference::generate_payment_reference,
+    proof_of_work::{DifficultyStatsWindow, PowAlgorithm, PowRegistry},
     transactions::{
-        generate_coinbase_with_wallet_output,
-        key_manager::{
-            create_memory_db_key_manager,
-            TariKeyId,
-            TransactionKeyManagerBranch,
-            TransactionKeyManagerInterface,
-            TxoStage,
-        },
-        transaction_components::{
-            KernelBuilder,
-            RangeProofType,
-            Transaction,
-            TransactionKernel,
-            TransactionKernelVersion,
-        },
+        generate_split_coinbase,
+        key_manager::create_memory_db_key_manager,
+        tari_amount::MicroMinotari,
+        transaction_components::{RangeProofType, Transaction},
+        CoinbaseRecipient,
     },
 };
-use tari_key_manager::key_manager_service::KeyManagerInterface;
 use tari_p2p::{auto_update::SoftwareUpdaterHandle, services::liveness::LivenessHandle};
 use tari_utilities::{hex::Hex, message_format::MessageFormat, ByteArray};
-use tokio::task;
+use tokio::{sync::broadcast, task};
 use tonic::{Request, Response, Status};
 
 use crate::{
     builder::BaseNodeContext,
     config::GrpcMethod,
     grpc::{
-        blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
+        blocks::{
+            block_fees,
+            block_heights,
+            block_size,
+            height_range,
+            pow_algo_filter,
+            BlockCursor,
+            BlockQuery,
+            GET_BLOCKS_MAX_HEIGHTS,
+            GET_BLOCKS_PAGE_SIZE,
+        },
         hash_rate::HashRateMovingAverage,
         helpers::{mean, median},
     },
@@ -105,16 +116,32 @@ const LIST_HEADERS_PAGE_SIZE: usize = 10;
 const LIST_HEADERS_DEFAULT_NUM_HEADERS: u64 = 10;
 
 const BLOCK_TIMING_MAX_BLOCKS: u64 = 10_000;
+// The maximum number of reorgs that are returned by GetReorgHistory, and the number returned if no limit is given.
+const GET_REORG_HISTORY_MAX_LIMIT: u64 = 1_000;
+const GET_REORG_HISTORY_DEFAULT_LIMIT: u64 = 100;
+// The maximum number of payment references that can be searched for in a single SearchPaymentReferences request
+const SEARCH_PAYMENT_REFERENCES_MAX: usize = 1_000;
+// The maximum number of epochs that can be requested in a single GetValidatorNodeChanges request
+const GET_VALIDATOR_NODE_CHANGES_MAX_EPOCHS: u64 = 1_000;
+// The number of tip change events that are buffered for a SubscribeBlocks client. Tips that change while the buffer is
+// full are skipped, the client is sent the latest tip once there is space.
+const SUBSCRIBE_BLOCKS_BUFFER_SIZE: usize = 10;
+const SUBSCRIBE_SYNC_STATE_BUFFER_SIZE: usize = 10;
+// The number of mempool events that are buffered for a SubscribeMempool client before the client starts missing events
+const SUBSCRIBE_MEMPOOL_BUFFER_SIZE: usize = 100;
 
 pub struct BaseNodeGrpcServer {
     node_service: LocalNodeCommsInterface,
     mempool_service: LocalMempoolService,
+    mempool: Mempool,
     network: NetworkConsensus,
     state_machine_handle: StateMachineHandle,
     consensus_rules: ConsensusManager,
+    pow_registry: PowRegistry,
     software_updater: SoftwareUpdaterHandle,
     comms: CommsNode,
     liveness: LivenessHandle,
+    blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
     report_grpc_error: bool,
     config: BaseNodeConfig,
 }
@@ -124,12 +151,15 @@ impl BaseNodeGrpcServer {
         Self {
             node_service: ctx.local_node(),
             mempool_service: ctx.local_mempool(),
+            mempool: ctx.mempool(),
             network: ctx.network().into(),
             state_machine_handle: ctx.state_machine(),
             consensus_rules: ctx.consensus_rules().clone(),
+            pow_registry: PowRegistry::from_consensus(ctx.consensus_rules()),
             software_updater: ctx.software_updater(),
             comms: ctx.base_node_comms().clone(),
             liveness: ctx.liveness(),
+            blockchain_db: ctx.blockchain_db().into(),
             report_grpc_error: ctx.get_report_grpc_error(),
             config,
         }
@@ -139,6 +169,53 @@ impl BaseNodeGrpcServer {
         self.report_grpc_error
     }
 
+    /// The hash of the block template header that the miner commits to, as defined by its PoW algorithm
+    fn template_mining_hash(&self, header: &BlockHeader, report_error_flag: bool) -> Result<Vec<u8>, Status> {
+        let pow_algo = self
+            .pow_registry
+            .get(header.pow_algo(), header.height)
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e.to_string())))?;
+        Ok(pow_algo.mining_hash(header).to_vec())
+    }
+
+    fn coinbase_extra_max_length(&self, height: u64) -> u64 {
+        self.consensus_rules
+            .consensus_constants(height)
+            .coinbase_output_features_extra_max_length()
+            .into()
+    }
+
+    /// The template version that is served for a block at the given height. Template versions are the block header
+    /// version plus one, so that a client can request 0 to accept whatever version is served.
+    fn current_template_version(&self, height: u64) -> u32 {
+        u32::from(self.consensus_rules.consensus_constants(height).blockchain_version()) + 1
+    }
+
+    /// The template versions of the blocks that are accepted at the given height, in ascending order
+    fn supported_template_versions(&self, height: u64) -> Vec<u32> {
+        self.consensus_rules
+            .consensus_constants(height)
+            .valid_blockchain_version_range()
+            .clone()
+            .map(|version| u32::from(version) + 1)
+            .collect()
+    }
+
+    /// Checks that the template version requested by a client is the version that is served at the given height
+    fn check_template_version(&self, requested: u32, height: u64, report_error_flag: bool) -> Result<u32, Status> {
+        let current = self.current_template_version(height);
+        if requested != 0 && requested != current {
+            return Err(obscure_error_if_true(
+                report_error_flag,
+                Status::failed_precondition(format!(
+                    "Template version {} was requested but this node serves template version {} at height {}",
+                    requested, current, height
+                )),
+            ));
+        }
+        Ok(current)
+    }
+
     fn is_method_enabled(&self, grpc_method: GrpcMethod) -> bool {
         let mining_method = [
             GrpcMethod::GetVersion,
@@ -150,6 +227,8 @@ impl BaseNodeGrpcServer {
             GrpcMethod::SubmitBlock,
             GrpcMethod::SubmitBlockBlob,
             GrpcMethod::GetTipInfo,
+            GrpcMethod::SubscribeBlocks,
+            GrpcMethod::GetSupportedTemplateVersions,
         ];
 
         let second_layer_methods = [
@@ -182,6 +261,16 @@ impl BaseNodeGrpcServer {
         }
         Ok(())
     }
+
+    fn check_output_indexes_enabled(&self) -> Result<(), Status> {
+        if !self.config.storage.enable_output_indexes {
+            return Err(Status::failed_precondition(
+                "Output indexes are not enabled. Set `enable_output_indexes` in the `[base_node.storage]` config to \
+                 enable them",
+            ));
+        }
+        Ok(())
+    }
 }
 
 pub fn obscure_error_if_true(report: bool, status: Status) -> Status {
@@ -193,6 +282,189 @@ pub fn obscure_error_if_true(report: bool, status: Status) -> Status {
     }
 }
 
+/// Converts outputs that were found using the output indexes into a gRPC response
+fn indexed_outputs_response(outputs: Vec<OutputMinedInfo>) -> Result<tari_rpc::SearchIndexedOutputsResponse, String> {
+    let outputs = outputs
+        .into_iter()
+        .map(|info| {
+            let payment_reference = generate_payment_reference(&info.header_hash, &info.output.hash());
+            Ok(tari_rpc::IndexedOutput {
+                output: Some(tari_rpc::TransactionOutput::try_from(info.output)?),
+                payment_reference: payment_reference.to_vec(),
+                header_hash: info.header_hash.to_vec(),
+                mined_height: info.mined_height,
+                mined_timestamp: info.mined_timestamp,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(tari_rpc::SearchIndexedOutputsResponse { outputs })
+}
+
+/// Converts the coinbases of a gRPC request to the recipients of a split coinbase, using each coinbase's value as its
+/// share
+fn sync_state_event(status: &StatusInfo) -> tari_rpc::SyncStateEvent {
+    use tari_rpc::NodeState;
+
+    let (state, local_height, tip_height, sync_peer) = match &status.state_info {
+        StateInfo::StartUp => (NodeState::Starting, 0, 0, None),
+        StateInfo::Connecting(sync_peer) => (NodeState::Connecting, 0, 0, Some(sync_peer)),
+        StateInfo::HeaderSync(None) => (NodeState::HeaderSync, 0, 0, None),
+        StateInfo::HeaderSync(Some(info)) => (
+            NodeState::HeaderSync,
+            info.local_height,
+            info.tip_height,
+            Some(&info.sync_peer),
+        ),
+        StateInfo::HorizonSync(info) => match &info.status {
+            HorizonSyncStatus::Kernels {
+                current,
+                total,
+                sync_peer,
+            } |
+            HorizonSyncStatus::Outputs {
+                current,
+                total,
+                sync_peer,
+            } => (NodeState::HorizonSync, *current, *total, Some(sync_peer)),
+            HorizonSyncStatus::Starting | HorizonSyncStatus::Finalizing => (NodeState::HorizonSync, 0, 0, None),
+        },
+        StateInfo::BlockSync(info) => (
+            NodeState::BlockSync,
+            info.local_height,
+            info.tip_height,
+            Some(&info.sync_peer),
+        ),
+        StateInfo::Listening(_) => (NodeState::Listening, 0, 0, None),
+        StateInfo::SyncFailed(_) => (NodeState::SyncFailed, 0, 0, None),
+    };
+
+    let progress_percentage = if tip_height == 0 {
+        0.0
+    } else {
+        (local_height.min(tip_height) as f64 / tip_height as f64) * 100.0
+    };
+
+    tari_rpc::SyncStateEvent {
+        state: state.into(),
+        description: status.state_info.short_desc(),
+        progress_percentage,
+        local_height,
+        tip_height,
+        sync_peer: sync_peer.map(sync_state_peer),
+        is_synced: status.state_info.is_synced(),
+        bootstrapped: status.bootstrapped,
+    }
+}
+
+fn block_hashes(blocks: &[Arc<ChainBlock>]) -> Vec<Vec<u8>> {
+    blocks.iter().map(|block| block.hash().to_vec()).collect()
+}
+
+fn mempool_event(event: &MempoolEvent, missed_events: u64) -> Result<tari_rpc::MempoolEvent, String> {
+    let (event_type, transactions) = match event {
+        MempoolEvent::TransactionAdded(tx) => (tari_rpc::MempoolEventType::Added, vec![tx.clone()]),
+        MempoolEvent::OrphansAccepted(txs) => (tari_rpc::MempoolEventType::Added, txs.clone()),
+        MempoolEvent::TransactionsRemoved(txs) => (tari_rpc::MempoolEventType::Removed, txs.clone()),
+        MempoolEvent::TransactionsExpired(txs) => (tari_rpc::MempoolEventType::Expired, txs.clone()),
+        MempoolEvent::TransactionsReorged(txs) => (tari_rpc::MempoolEventType::Reorged, txs.clone()),
+    };
+    Ok(tari_rpc::MempoolEvent {
+        event_type: event_type.into(),
+        transactions: transactions
+            .into_iter()
+            .map(tari_rpc::Transaction::try_from)
+            .collect::<Result<_, _>>()?,
+        missed_events,
+    })
+}
+
+fn header_response(
+    block: HistoricalBlock,
+    consensus_rules: &ConsensusManager,
+) -> Result<tari_rpc::BlockHeaderResponse, String> {
+    let cursor = BlockCursor::new(block.header().height, *block.hash()).to_bytes();
+    let (block, acc_data, confirmations) = block.dissolve();
+    let total_block_reward = consensus_rules
+        .calculate_coinbase_and_fees(block.header.height, block.body.kernels())
+        .map_err(|e| e.to_string())?;
+    Ok(tari_rpc::BlockHeaderResponse {
+        difficulty: acc_data.achieved_difficulty.into(),
+        num_transactions: block.body.kernels().len() as u32,
+        confirmations,
+        header: Some(block.header.into()),
+        reward: total_block_reward.into(),
+        cursor,
+    })
+}
+
+fn header_only_response(header: ChainHeader, tip: u64) -> tari_rpc::BlockHeaderResponse {
+    let cursor = BlockCursor::new(header.height(), *header.hash()).to_bytes();
+    let (header, acc_data) = header.into_parts();
+    tari_rpc::BlockHeaderResponse {
+        difficulty: acc_data.achieved_difficulty.into(),
+        num_transactions: 0,
+        confirmations: tip.saturating_sub(header.height).saturating_add(1),
+        header: Some(header.into()),
+        reward: 0,
+        cursor,
+    }
+}
+
+fn historical_block_response(block: HistoricalBlock) -> Result<tari_rpc::HistoricalBlock, String> {
+    let cursor = BlockCursor::new(block.header().height, *block.hash()).to_bytes();
+    let mut response = tari_rpc::HistoricalBlock::try_from(block).map_err(|e| e.to_string())?;
+    response.cursor = cursor;
+    Ok(response)
+}
+
+fn header_only_block(header: ChainHeader, tip: u64) -> tari_rpc::HistoricalBlock {
+    let cursor = BlockCursor::new(header.height(), *header.hash()).to_bytes();
+    tari_rpc::HistoricalBlock {
+        confirmations: tip.saturating_sub(header.height()).saturating_add(1),
+        block: Some(tari_rpc::Block {
+            header: Some(header.into_header().into()),
+            body: None,
+        }),
+        cursor,
+    }
+}
+
+fn sync_state_peer(sync_peer: &SyncPeer) -> tari_rpc::SyncStatePeer {
+    tari_rpc::SyncStatePeer {
+        node_id: sync_peer.node_id().to_string().into_bytes(),
+        latency_ms: sync_peer
+            .latency()
+            .map(|latency| u64::try_from(latency.as_millis()).unwrap_or(u64::MAX))
+            .unwrap_or_default(),
+        claimed_tip_height: sync_peer.claimed_chain_metadata().best_block_height(),
+    }
+}
+
+fn coinbase_recipients(
+    coinbases: Vec<tari_rpc::NewBlockCoinbase>,
+    report_error_flag: bool,
+) -> Result<Vec<CoinbaseRecipient>, Status> {
+    coinbases
+        .into_iter()
+        .map(|coinbase| {
+            let address = TariAddress::from_hex(&coinbase.address)
+                .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e.to_string())))?;
+            let range_proof_type = if coinbase.revealed_value_proof {
+                RangeProofType::RevealedValue
+            } else {
+                RangeProofType::BulletProofPlus
+            };
+            Ok(CoinbaseRecipient {
+                address,
+                share: coinbase.value,
+                stealth_payment: coinbase.stealth_payment,
+                range_proof_type,
+                extra: coinbase.coinbase_extra,
+            })
+        })
+        .collect()
+}
+
 pub async fn get_heights(
     request: &tari_rpc::HeightRequest,
     handler: LocalNodeCommsInterface,
@@ -215,6 +487,9 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     type ListHeadersStream = mpsc::Receiver<Result<tari_rpc::BlockHeaderResponse, Status>>;
     type SearchKernelsStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
     type SearchUtxosStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
+    type SubscribeBlocksStream = mpsc::Receiver<Result<tari_rpc::SubscribeBlocksResponse, Status>>;
+    type SubscribeMempoolStream = mpsc::Receiver<Result<tari_rpc::MempoolEvent, Status>>;
+    type SubscribeSyncStateStream = mpsc::Receiver<Result<tari_rpc::SyncStateEvent, Status>>;
 
     #[allow(clippy::too_many_lines)]
     async fn get_network_difficulty(
@@ -262,6 +537,13 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             HashRateMovingAverage::new(PowAlgorithm::Sha3x, self.consensus_rules.clone());
         let mut randomx_hash_rate_moving_average =
             HashRateMovingAverage::new(PowAlgorithm::RandomX, self.consensus_rules.clone());
+        // Statistics are calculated over the same number of blocks of each algorithm as the difficulty adjustment
+        let stats_window_size = self
+            .consensus_rules
+            .consensus_constants(start_height)
+            .difficulty_block_window()
+            .saturating_mul(2);
+        let mut stats_window = DifficultyStatsWindow::new(usize::try_from(stats_window_size).unwrap_or(usize::MAX));
 
         let page_iter =
             NonOverlappingIntegerPairIter::new(start_height, end_height.saturating_add(1), GET_DIFFICULTY_PAGE_SIZE)
@@ -303,6 +585,8 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                         PowAlgorithm::Sha3x => &mut sha3x_hash_rate_moving_average,
                     };
                     current_hash_rate_moving_average.add(current_height, current_difficulty);
+                    stats_window.add(pow_algo, current_timestamp, current_difficulty);
+                    let stats = stats_window.stats();
 
                     let sha3x_estimated_hash_rate = sha3x_hash_rate_moving_average.average();
                     let randomx_estimated_hash_rate = randomx_hash_rate_moving_average.average();
@@ -316,6 +600,10 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                         height: current_height,
                         timestamp: current_timestamp.as_u64(),
                         pow_algo: pow_algo.as_u64(),
+                        sha3x_share: stats.sha3x.share,
+                        randomx_share: stats.randomx.share,
+                        sha3x_solve_time_variance: stats.sha3x.solve_time_variance,
+                        randomx_solve_time_variance: stats.randomx.solve_time_variance,
                     };
 
                     if let Err(err) = tx.send(Ok(difficulty)).await {
@@ -422,144 +710,52 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         };
 
         let sorting: Sorting = request.sorting();
+        let is_reversed = sorting == Sorting::Desc;
         let num_headers = match request.num_headers {
             0 => LIST_HEADERS_DEFAULT_NUM_HEADERS,
             _ => request.num_headers,
         };
-
-        let num_headers = cmp::min(num_headers, LIST_HEADERS_MAX_NUM_HEADERS);
-        let (mut tx, rx) = mpsc::channel(LIST_HEADERS_PAGE_SIZE);
-
-        let from_height = cmp::min(request.from_height, tip);
-
-        let (header_range, is_reversed) = if from_height == 0 {
-            match sorting {
-                Sorting::Desc => {
-                    let from = match tip.overflowing_sub(num_headers) {
-                        (_, true) => 0,
-                        (res, false) => res + 1,
-                    };
-                    (from..=tip, true)
-                },
-                Sorting::Asc => (0..=num_headers.saturating_sub(1), false),
-            }
-        } else {
-            match sorting {
-                Sorting::Desc => {
-                    let from = match from_height.overflowing_sub(num_headers) {
-                        (_, true) => 0,
-                        (res, false) => res + 1,
-                    };
-                    (from..=from_height, true)
-                },
-                Sorting::Asc => {
-                    let to = from_height.saturating_add(num_headers).saturating_sub(1);
-                    (from_height..=to, false)
-                },
-            }
+        let query = BlockQuery {
+            pow_algo: pow_algo_filter(request.pow_algo.clone())
+                .map_err(|e| obscure_error_if_true(report_error_flag, e))?,
+            header_only: request.field_mask() == tari_rpc::BlockFieldMask::HeaderOnly,
+            limit: cmp::min(num_headers, LIST_HEADERS_MAX_NUM_HEADERS),
+            descending: is_reversed,
         };
-        let consensus_rules = self.consensus_rules.clone();
+        let (tx, rx) = mpsc::channel(LIST_HEADERS_PAGE_SIZE);
+        let header_range = match list_headers_range(&mut handler, &request, tip, is_reversed)
+            .await
+            .map_err(|e| obscure_error_if_true(report_error_flag, e))?
+        {
+            Some(header_range) => header_range,
+            None => return Ok(Response::new(rx)),
+        };
+
         let page_iter = NonOverlappingIntegerPairIter::new(
             *header_range.start(),
             header_range.end().saturating_add(1),
             LIST_HEADERS_PAGE_SIZE,
         )
         .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e)))?;
-        task::spawn(async move {
-            debug!(
-                target: LOG_TARGET,
-                "Starting base node request {}-{}",
-                header_range.start(),
-                header_range.end()
-            );
-            let page_iter = if is_reversed {
-                Either::Left(page_iter.rev())
-            } else {
-                Either::Right(page_iter)
-            };
-            for (start, end) in page_iter {
-                debug!(target: LOG_TARGET, "Page: {}-{}", start, end);
-                let result_data = match handler.get_blocks(start..=end, true).await {
-                    Err(err) => {
-                        warn!(target: LOG_TARGET, "Internal base node service error: {}", err);
-                        return;
-                    },
-                    Ok(data) => {
-                        if is_reversed {
-                            data.into_iter()
-                                .map(|chain_block| {
-                                    let (block, acc_data, confirmations) = chain_block.dissolve();
-                                    match consensus_rules
-                                        .calculate_coinbase_and_fees(block.header.height, block.body.kernels())
-                                    {
-                                        Ok(total_block_reward) => Ok(tari_rpc::BlockHeaderResponse {
-                                            difficulty: acc_data.achieved_difficulty.into(),
-                                            num_transactions: block.body.kernels().len() as u32,
-                                            confirmations,
-                                            header: Some(block.header.into()),
-                                            reward: total_block_reward.into(),
-                                        }),
-                                        Err(e) => {
-                                            Err(obscure_error_if_true(report_error_flag, Status::internal(e))
-                                                .to_string())
-                                        },
-                                    }
-                                })
-                                .rev()
-                                .collect::<Result<Vec<_>, String>>()
-                        } else {
-                            data.into_iter()
-                                .map(|chain_block| {
-                                    let (block, acc_data, confirmations) = chain_block.dissolve();
-                                    match consensus_rules
-                                        .calculate_coinbase_and_fees(block.header.height, block.body.kernels())
-                                    {
-                                        Ok(total_block_reward) => Ok(tari_rpc::BlockHeaderResponse {
-                                            difficulty: acc_data.achieved_difficulty.into(),
-                                            num_transactions: block.body.kernels().len() as u32,
-                                            confirmations,
-                                            header: Some(block.header.into()),
-                                            reward: total_block_reward.into(),
-                                        }),
-                                        Err(e) => {
-                                            Err(obscure_error_if_true(report_error_flag, Status::internal(e))
-                                                .to_string())
-                                        },
-                                    }
-                                })
-                                .collect::<Result<Vec<_>, String>>()
-                        }
-                    },
-                };
-
-                match result_data {
-                    Err(e) => {
-                        error!(target: LOG_TARGET, "No result headers transmitted due to error: {}", e)
-                    },
-                    Ok(result_data) => {
-                        let result_size = result_data.len();
-                        debug!(target: LOG_TARGET, "Result headers: {}", result_size);
-
-                        for response in result_data {
-                            // header wont be none here as we just filled it in above
-                            debug!(
-                                target: LOG_TARGET,
-                                "Sending block header: {}",
-                                response.header.as_ref().map( | h| h.height).unwrap_or(0)
-                            );
-                            if tx.send(Ok(response)).await.is_err() {
-                                // Sender has closed i.e the connection has dropped/request was abandoned
-                                warn!(
-                                    target: LOG_TARGET,
-                                    "[list_headers] GRPC request cancelled while sending response"
-                                );
-                                return;
-                            }
-                        }
-                    },
-                }
-            }
-        });
+        debug!(
+            target: LOG_TARGET,
+            "Starting base node request {}-{}",
+            header_range.start(),
+            header_range.end()
+        );
+        let page_iter = if is_reversed {
+            Either::Left(page_iter.rev())
+        } else {
+            Either::Right(page_iter)
+        };
+        task::spawn(stream_headers(
+            handler,
+            self.consensus_rules.clone(),
+            page_iter,
+            query,
+            tip,
+            tx,
+        ));
 
         debug!(target: LOG_TARGET, "Sending ListHeaders response stream to client");
         Ok(Response::new(rx))
@@ -606,6 +802,8 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
             })?;
 
+        let template_version =
+            self.check_template_version(request.template_version, new_template.header.height, report_error_flag)?;
         let status_watch = self.state_machine_handle.get_status_info_watch();
         let pow = algo as i32;
         let response = tari_rpc::NewBlockTemplateResponse {
@@ -614,6 +812,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 target_difficulty: new_template.target_difficulty.as_u64(),
                 total_fees: new_template.total_fees.into(),
                 algo: Some(tari_rpc::PowAlgo { pow_algo: pow }),
+                coinbase_extra_max_length: self.coinbase_extra_max_length(new_template.header.height),
             }),
             new_block_template: Some(
                 new_template
@@ -621,6 +820,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                     .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e)))?,
             ),
             initial_sync_achieved: status_watch.borrow().bootstrapped,
+            template_version,
         };
 
         debug!(target: LOG_TARGET, "Sending GetNewBlockTemplate response to client");
@@ -693,10 +893,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             .to_vec();
         // construct response
         let block_hash = new_block.hash().to_vec();
-        let mining_hash = match new_block.header.pow.pow_algo {
-            PowAlgorithm::Sha3x => new_block.header.mining_hash().to_vec(),
-            PowAlgorithm::RandomX => new_block.header.merge_mining_hash().to_vec(),
-        };
+        let mining_hash = self.template_mining_hash(&new_block.header, report_error_flag)?;
         let block: Option<tari_rpc::Block> = Some(
             new_block
                 .try_into()
@@ -718,6 +915,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             target_difficulty: new_template.target_difficulty.as_u64(),
             total_fees: fees.as_u64(),
             algo: Some(tari_rpc::PowAlgo { pow_algo: pow }),
+            coinbase_extra_max_length: self.coinbase_extra_max_length(new_template.header.height),
         };
 
         let response = tari_rpc::GetNewBlockResult {
@@ -775,6 +973,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 );
                 obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
             })?;
+        self.check_template_version(request.template_version, new_template.header.height, report_error_flag)?;
 
         let pow = algo as i32;
 
@@ -783,11 +982,12 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             target_difficulty: new_template.target_difficulty.as_u64(),
             total_fees: new_template.total_fees.into(),
             algo: Some(tari_rpc::PowAlgo { pow_algo: pow }),
+            coinbase_extra_max_length: self.coinbase_extra_max_length(new_template.header.height),
         };
 
-        let mut coinbases: Vec<tari_rpc::NewBlockCoinbase> = request.coinbases;
+        let recipients = coinbase_recipients(request.coinbases, report_error_flag)?;
 
-        // let validate the coinbase amounts;
+        // The coinbase values of the request are shares of the reward plus fees
         let reward = self
             .consensus_rules
             .calculate_coinbase_and_fees(new_template.header.height, new_template.body.kernels())
@@ -796,97 +996,23 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                     report_error_flag,
                     Status::internal("Could not calculate the amount of fees in the block".to_string()),
                 )
-            })?
-            .as_u64();
-        let mut total_shares = 0u64;
-        for coinbase in &coinbases {
-            total_shares += coinbase.value;
-        }
-        let mut remainder = reward - ((reward / total_shares) * total_shares);
-        for coinbase in &mut coinbases {
-            coinbase.value *= reward / total_shares;
-            if remainder > 0 {
-                coinbase.value += 1;
-                remainder -= 1;
-            }
-        }
-
+            })?;
         let key_manager = create_memory_db_key_manager();
         let height = new_template.header.height;
-        // The script key is not used in the Diffie-Hellmann protocol, so we assign default.
-        let script_key_id = TariKeyId::default();
-
-        let mut total_excess = Commitment::default();
-        let mut total_nonce = PublicKey::default();
-        let mut private_keys = Vec::new();
-        let mut kernel_message = [0; 32];
-        let mut last_kernel = Default::default();
-        for coinbase in coinbases {
-            let address = TariAddress::from_hex(&coinbase.address)
-                .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
-            let range_proof_type = if coinbase.revealed_value_proof {
-                RangeProofType::RevealedValue
-            } else {
-                RangeProofType::BulletProofPlus
-            };
-            let (_, coinbase_output, coinbase_kernel, wallet_output) = generate_coinbase_with_wallet_output(
-                0.into(),
-                coinbase.value.into(),
-                height,
-                &coinbase.coinbase_extra,
-                &key_manager,
-                &script_key_id,
-                &address,
-                coinbase.stealth_payment,
-                self.consensus_rules.consensus_constants(height),
-                range_proof_type,
-            )
-            .await
-            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
-            new_template.body.add_output(coinbase_output);
-            let (new_private_nonce, pub_nonce) = key_manager
-                .get_next_key(TransactionKeyManagerBranch::KernelNonce.get_branch_key())
-                .await
-                .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
-            total_nonce = &total_nonce + &pub_nonce;
-            total_excess = &total_excess + &coinbase_kernel.excess;
-            private_keys.push((wallet_output.spending_key_id, new_private_nonce));
-            kernel_message = TransactionKernel::build_kernel_signature_message(
-                &TransactionKernelVersion::get_current_version(),
-                coinbase_kernel.fee,
-                coinbase_kernel.lock_height,
-                &coinbase_kernel.features,
-                &None,
-            );
-            last_kernel = coinbase_kernel;
-        }
-        let mut kernel_signature = Signature::default();
-        for (spending_key_id, nonce) in private_keys {
-            kernel_signature = &kernel_signature +
-                &key_manager
-                    .get_partial_txo_kernel_signature(
-                        &spending_key_id,
-                        &nonce,
-                        &total_nonce,
-                        total_excess.as_public_key(),
-                        &TransactionKernelVersion::get_current_version(),
-                        &kernel_message,
-                        &last_kernel.features,
-                        TxoStage::Output,
-                    )
-                    .await
-                    .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
+        let (coinbase_outputs, coinbase_kernel) = generate_split_coinbase(
+            MicroMinotari::zero(),
+            reward,
+            height,
+            &recipients,
+            &key_manager,
+            self.consensus_rules.consensus_constants(height),
+        )
+        .await
+        .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e.to_string())))?;
+        for output in coinbase_outputs {
+            new_template.body.add_output(output);
         }
-        let kernel_new = KernelBuilder::new()
-            .with_fee(0.into())
-            .with_features(last_kernel.features)
-            .with_lock_height(last_kernel.lock_height)
-            .with_excess(&total_excess)
-            .with_signature(kernel_signature)
-            .build()
-            .unwrap();
-
-        new_template.body.add_kernel(kernel_new);
+        new_template.body.add_kernel(coinbase_kernel);
         new_template.body.sort();
 
         let new_block = match handler.get_new_block(new_template).await {
@@ -931,10 +1057,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             .to_vec();
         // construct response
         let block_hash = new_block.hash().to_vec();
-        let mining_hash = match new_block.header.pow.pow_algo {
-            PowAlgorithm::Sha3x => new_block.header.mining_hash().to_vec(),
-            PowAlgorithm::RandomX => new_block.header.merge_mining_hash().to_vec(),
-        };
+        let mining_hash = self.template_mining_hash(&new_block.header, report_error_flag)?;
         let block: Option<tari_rpc::Block> = Some(
             new_block
                 .try_into()
@@ -978,7 +1101,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                     Status::invalid_argument(format!("Malformed block template provided: {}", s)),
                 )
             })?;
-        let coinbases: Vec<tari_rpc::NewBlockCoinbase> = request.coinbases;
+        let recipients = coinbase_recipients(request.coinbases, report_error_flag)?;
 
         let mut handler = self.node_service.clone();
 
@@ -992,93 +1115,33 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                     Status::internal("Could not calculate the amount of fees in the block".to_string()),
                 )
             })?;
-        let mut amount = 0u64;
-        for coinbase in &coinbases {
-            amount += coinbase.value;
-        }
-
+        let amount = recipients
+            .iter()
+            .try_fold(0u64, |total, r| total.checked_add(r.share))
+            .unwrap_or(u64::MAX);
         if amount != reward.as_u64() {
             return Err(obscure_error_if_true(
                 report_error_flag,
                 Status::invalid_argument("Malformed coinbase amounts".to_string()),
             ));
         }
+        // The coinbase values add up to the reward, so splitting the reward by value gives each coinbase its value
         let key_manager = create_memory_db_key_manager();
         let height = block_template.header.height;
-        // The script key is not used in the Diffie-Hellmann protocol, so we assign default.
-        let script_key_id = TariKeyId::default();
-
-        let mut total_excess = Commitment::default();
-        let mut total_nonce = PublicKey::default();
-        let mut private_keys = Vec::new();
-        let mut kernel_message = [0; 32];
-        let mut last_kernel = Default::default();
-        for coinbase in coinbases {
-            let address = TariAddress::from_hex(&coinbase.address)
-                .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
-            let range_proof_type = if coinbase.revealed_value_proof {
-                RangeProofType::RevealedValue
-            } else {
-                RangeProofType::BulletProofPlus
-            };
-            let (_, coinbase_output, coinbase_kernel, wallet_output) = generate_coinbase_with_wallet_output(
-                0.into(),
-                coinbase.value.into(),
-                height,
-                &coinbase.coinbase_extra,
-                &key_manager,
-                &script_key_id,
-                &address,
-                coinbase.stealth_payment,
-                self.consensus_rules.consensus_constants(height),
-                range_proof_type,
-            )
-            .await
-            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
-            block_template.body.add_output(coinbase_output);
-            let (new_private_nonce, pub_nonce) = key_manager
-                .get_next_key(TransactionKeyManagerBranch::KernelNonce.get_branch_key())
-                .await
-                .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
-            total_nonce = &total_nonce + &pub_nonce;
-            total_excess = &total_excess + &coinbase_kernel.excess;
-            private_keys.push((wallet_output.spending_key_id, new_private_nonce));
-            kernel_message = TransactionKernel::build_kernel_signature_message(
-                &TransactionKernelVersion::get_current_version(),
-                coinbase_kernel.fee,
-                coinbase_kernel.lock_height,
-                &coinbase_kernel.features,
-                &None,
-            );
-            last_kernel = coinbase_kernel;
-        }
-        let mut kernel_signature = Signature::default();
-        for (spending_key_id, nonce) in private_keys {
-            kernel_signature = &kernel_signature +
-                &key_manager
-                    .get_partial_txo_kernel_signature(
-                        &spending_key_id,
-                        &nonce,
-                        &total_nonce,
-                        total_excess.as_public_key(),
-                        &TransactionKernelVersion::get_current_version(),
-                        &kernel_message,
-                        &last_kernel.features,
-                        TxoStage::Output,
-                    )
-                    .await
-                    .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
+        let (coinbase_outputs, coinbase_kernel) = generate_split_coinbase(
+            MicroMinotari::zero(),
+            reward,
+            height,
+            &recipients,
+            &key_manager,
+            self.consensus_rules.consensus_constants(height),
+        )
+        .await
+        .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e.to_string())))?;
+        for output in coinbase_outputs {
+            block_template.body.add_output(output);
         }
-        let kernel_new = KernelBuilder::new()
-            .with_fee(0.into())
-            .with_features(last_kernel.features)
-            .with_lock_height(last_kernel.lock_height)
-            .with_excess(&total_excess)
-            .with_signature(kernel_signature)
-            .build()
-            .unwrap();
-
-        block_template.body.add_kernel(kernel_new);
+        block_template.body.add_kernel(coinbase_kernel);
         block_template.body.sort();
 
         let new_block = match handler.get_new_block(block_template).await {
@@ -1130,10 +1193,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             .to_vec();
         // construct response
         let block_hash = new_block.hash().to_vec();
-        let mining_hash = match new_block.header.pow.pow_algo {
-            PowAlgorithm::Sha3x => new_block.header.mining_hash().to_vec(),
-            PowAlgorithm::RandomX => new_block.header.merge_mining_hash().to_vec(),
-        };
+        let mining_hash = self.template_mining_hash(&new_block.header, report_error_flag)?;
         let block: Option<tari_rpc::Block> = Some(
             new_block
                 .try_into()
@@ -1156,6 +1216,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             target_difficulty: new_template.target_difficulty.as_u64(),
             total_fees: fees.as_u64(),
             algo: Some(tari_rpc::PowAlgo { pow_algo: pow }),
+            coinbase_extra_max_length: self.coinbase_extra_max_length(new_template.header.height),
         };
 
         let response = tari_rpc::GetNewBlockResult {
@@ -1211,10 +1272,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         };
         // construct response
         let block_hash = new_block.hash().to_vec();
-        let mining_hash = match new_block.header.pow.pow_algo {
-            PowAlgorithm::Sha3x => new_block.header.mining_hash().to_vec(),
-            PowAlgorithm::RandomX => new_block.header.merge_mining_hash().to_vec(),
-        };
+        let mining_hash = self.template_mining_hash(&new_block.header, report_error_flag)?;
         let gen_hash = handler
             .get_header(0)
             .await
@@ -1370,6 +1428,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredConsensus |
             TxStorageResponse::NotStoredFeeTooLow |
+            TxStorageResponse::NotStoredExpired |
             TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResponse {
                 result: tari_rpc::SubmitTransactionResult::Rejected.into(),
             },
@@ -1453,6 +1512,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             TxStorageResponse::NotStoredConsensus |
             TxStorageResponse::NotStoredOrphan |
             TxStorageResponse::NotStoredFeeTooLow |
+            TxStorageResponse::NotStoredExpired |
             TxStorageResponse::NotStoredTimeLocked |
             TxStorageResponse::NotStoredAlreadyMined => tari_rpc::TransactionStateResponse {
                 result: tari_rpc::TransactionLocation::NotStored.into(),
@@ -1508,61 +1568,47 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         let request = request.into_inner();
         debug!(
             target: LOG_TARGET,
-            "Incoming GRPC request for GetBlocks: {:?}", request.heights
+            "Incoming GRPC request for GetBlocks: heights: {:?}, height_range: {:?}",
+            request.heights,
+            request.height_range
         );
 
-        let mut heights = request.heights;
-        if heights.is_empty() {
-            return Err(obscure_error_if_true(
-                report_error_flag,
-                Status::invalid_argument("heights cannot be empty"),
-            ));
-        }
-
-        heights.truncate(GET_BLOCKS_MAX_HEIGHTS);
-        heights.sort_unstable();
-        // unreachable panic: `heights` is not empty
-        let start = *heights.first().expect("unreachable");
-        let end = *heights.last().expect("unreachable");
-
+        let query = BlockQuery {
+            pow_algo: pow_algo_filter(request.pow_algo.clone())
+                .map_err(|e| obscure_error_if_true(report_error_flag, e))?,
+            header_only: request.field_mask() == tari_rpc::BlockFieldMask::HeaderOnly,
+            limit: match request.limit {
+                0 => GET_BLOCKS_MAX_HEIGHTS as u64,
+                limit => cmp::min(limit, GET_BLOCKS_MAX_HEIGHTS as u64),
+            },
+            descending: false,
+        };
         let mut handler = self.node_service.clone();
-        let (mut tx, rx) = mpsc::channel(GET_BLOCKS_PAGE_SIZE);
-        let page_iter = NonOverlappingIntegerPairIter::new(start, end.saturating_add(1), GET_BLOCKS_PAGE_SIZE)
-            .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e)))?;
-        task::spawn(async move {
-            for (start, end) in page_iter {
-                let blocks = match handler.get_blocks(start..=end, false).await {
-                    Err(err) => {
-                        warn!(
-                            target: LOG_TARGET,
-                            "Error communicating with local base node: {:?}", err,
-                        );
-                        return;
-                    },
-                    Ok(data) => data.into_iter().filter(|b| heights.contains(&b.header().height)),
-                };
+        let tip = handler
+            .get_metadata()
+            .await
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?
+            .best_block_height();
 
-                for block in blocks {
-                    debug!(
-                        target: LOG_TARGET,
-                        "GetBlock GRPC sending block #{}",
-                        block.header().height
-                    );
-                    let result = block.try_into().map_err(|err| {
-                        obscure_error_if_true(
-                            report_error_flag,
-                            Status::internal(format!("Could not provide block: {}", err)),
-                        )
-                    });
-                    if tx.send(result).await.is_err() {
-                        warn!(
-                            target: LOG_TARGET,
-                            "[get_blocks] Request was cancelled while sending a response"
-                        );
-                    }
-                }
-            }
-        });
+        let (tx, rx) = mpsc::channel(GET_BLOCKS_PAGE_SIZE);
+        let (range, heights) = get_blocks_range(&mut handler, request, tip)
+            .await
+            .map_err(|e| obscure_error_if_true(report_error_flag, e))?;
+        if range.is_empty() {
+            return Ok(Response::new(rx));
+        }
+        let page_iter =
+            NonOverlappingIntegerPairIter::new(*range.start(), range.end().saturating_add(1), GET_BLOCKS_PAGE_SIZE)
+                .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e)))?;
+        task::spawn(stream_blocks(
+            handler,
+            page_iter,
+            heights,
+            query,
+            tip,
+            tx,
+            report_error_flag,
+        ));
 
         debug!(target: LOG_TARGET, "Sending GetBlocks response stream to client");
         Ok(Response::new(rx))
@@ -2014,7 +2060,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     ) -> Result<Response<tari_rpc::SyncInfoResponse>, Status> {
         self.check_method_enabled(GrpcMethod::GetSyncInfo)?;
         debug!(target: LOG_TARGET, "Incoming GRPC request for BN sync data");
-        let response = self
+        let mut response = self
             .state_machine_handle
             .get_status_info_watch()
             .borrow()
@@ -2026,9 +2072,21 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                     tip_height: info.tip_height,
                     local_height: info.local_height,
                     peer_node_id: vec![node_ids],
+                    ..Default::default()
                 }
             })
             .unwrap_or_default();
+        let bandwidth_limiter = self.state_machine_handle.bandwidth_limiter();
+        response.max_bandwidth_bytes_per_second = bandwidth_limiter.max_bytes_per_second().unwrap_or_default();
+        response.peer_bandwidth = bandwidth_limiter
+            .peer_bandwidth()
+            .into_iter()
+            .map(|peer| tari_rpc::SyncPeerBandwidth {
+                peer_node_id: peer.node_id.to_string().into_bytes(),
+                bytes_received: peer.bytes_received,
+                bytes_per_second: peer.bytes_per_second,
+            })
+            .collect();
 
         debug!(target: LOG_TARGET, "Sending SyncData response to client");
         Ok(Response::new(response))
@@ -2138,6 +2196,11 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             .await
             .map_err(|err| obscure_error_if_true(report_error_flag, Status::internal(err.to_string())))?;
 
+        let peer_metrics = connectivity
+            .get_peer_quality_metrics()
+            .await
+            .map_err(|err| obscure_error_if_true(report_error_flag, Status::internal(err.to_string())))?;
+
         let mut peers = Vec::with_capacity(connected_peers.len());
         for peer in connected_peers {
             peers.push(
@@ -2156,11 +2219,32 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
 
         let resp = tari_rpc::ListConnectedPeersResponse {
             connected_peers: peers.into_iter().map(Into::into).collect(),
+            peer_metrics: peer_metrics.into_iter().map(Into::into).collect(),
         };
 
         Ok(Response::new(resp))
     }
 
+    async fn list_peer_offenses(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListPeerOffensesResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::ListPeerOffenses)?;
+        let records = self.comms.peer_manager().offense_records().await;
+
+        Ok(Response::new(tari_rpc::ListPeerOffensesResponse {
+            peers: records.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_network_usage(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::NetworkUsageResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetNetworkUsage)?;
+        Ok(Response::new(self.comms.bandwidth_meter().usage().into()))
+    }
+
     async fn get_mempool_stats(
         &self,
         _: Request<tari_rpc::Empty>,
@@ -2178,32 +2262,300 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             unconfirmed_txs: mempool_stats.unconfirmed_txs,
             reorg_txs: mempool_stats.reorg_txs,
             unconfirmed_weight: mempool_stats.unconfirmed_weight,
+            dependent_txs: mempool_stats.dependent_txs,
+            max_package_size: mempool_stats.max_package_size,
+            min_package_fee_per_gram: mempool_stats.min_package_fee_per_gram,
         };
 
         Ok(Response::new(response))
     }
 
-    async fn get_shard_key(
+    async fn estimate_fee_per_gram(
         &self,
-        request: Request<tari_rpc::GetShardKeyRequest>,
-    ) -> Result<Response<tari_rpc::GetShardKeyResponse>, Status> {
-        self.check_method_enabled(GrpcMethod::GetShardKey)?;
-        let request = request.into_inner();
+        request: Request<tari_rpc::EstimateFeePerGramRequest>,
+    ) -> Result<Response<tari_rpc::EstimateFeePerGramResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::EstimateFeePerGram)?;
         let report_error_flag = self.report_error_flag();
+        let request = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for EstimateFeePerGram");
+        let target_blocks = usize::try_from(request.target_blocks)
+            .ok()
+            .filter(|target_blocks| *target_blocks > 0)
+            .ok_or_else(|| {
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::invalid_argument("target_blocks must be greater than 0"),
+                )
+            })?;
+
         let mut handler = self.node_service.clone();
-        let public_key = PublicKey::from_canonical_bytes(&request.public_key)
-            .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e.to_string())))?;
+        let metadata = handler
+            .get_metadata()
+            .await
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
+        let mut mempool_handle = self.mempool_service.clone();
+        let fee_per_gram = mempool_handle
+            .estimate_fee_per_gram(target_blocks, metadata.best_block_height())
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error estimating fee per gram: {}", e);
+                obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+            })?;
 
-        let shard_key = handler.get_shard_key(request.height, public_key).await.map_err(|e| {
-            error!(target: LOG_TARGET, "Error {}", e);
-            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
-        })?;
-        if let Some(shard_key) = shard_key {
-            Ok(Response::new(tari_rpc::GetShardKeyResponse {
-                shard_key: shard_key.to_vec(),
-                found: true,
-            }))
-        } else {
+        Ok(Response::new(tari_rpc::EstimateFeePerGramResponse {
+            fee_per_gram: fee_per_gram.as_u64(),
+            tip_height: metadata.best_block_height(),
+        }))
+    }
+
+    async fn get_mempool_dependency_graph(
+        &self,
+        request: Request<tari_rpc::GetMempoolDependencyGraphRequest>,
+    ) -> Result<Response<tari_rpc::GetMempoolDependencyGraphResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetMempoolDependencyGraph)?;
+        let report_error_flag = self.report_error_flag();
+        let request = request.into_inner();
+        let excess_sig: Signature = request
+            .excess_sig
+            .ok_or_else(|| {
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::invalid_argument("excess_sig not provided".to_string()),
+                )
+            })?
+            .try_into()
+            .map_err(|e| {
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::invalid_argument(format!("excess_sig could not be converted '{}'", e)),
+                )
+            })?;
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetMempoolDependencyGraph");
+
+        let mut mempool_handle = self.mempool_service.clone();
+        let graph = mempool_handle.get_dependency_graph(excess_sig).await.map_err(|e| {
+            error!(target: LOG_TARGET, "Error getting mempool dependency graph: {}", e);
+            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+        })?;
+        let graph = match graph {
+            Some(graph) => graph,
+            None => return Ok(Response::new(tari_rpc::GetMempoolDependencyGraphResponse::default())),
+        };
+        let nodes = graph
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let relation = match node.relation {
+                    DependencyRelation::Root => tari_rpc::DependencyRelation::Root,
+                    DependencyRelation::Ancestor => tari_rpc::DependencyRelation::Ancestor,
+                    DependencyRelation::Descendant => tari_rpc::DependencyRelation::Descendant,
+                };
+                tari_rpc::DependencyGraphNode {
+                    excess_sig: Some(node.excess_sig.into()),
+                    relation: relation.into(),
+                    fee: node.fee.as_u64(),
+                    weight: node.weight,
+                    fee_per_gram: node.fee_per_gram,
+                    parents: node.parents.into_iter().map(|p| p as u64).collect(),
+                }
+            })
+            .collect();
+
+        Ok(Response::new(tari_rpc::GetMempoolDependencyGraphResponse {
+            found: true,
+            nodes,
+            package_fee_per_gram: graph.package_fee_per_gram,
+        }))
+    }
+
+    async fn compact_database(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::CompactDatabaseResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::CompactDatabase)?;
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for CompactDatabase");
+
+        let result = self.blockchain_db.compact().await.map_err(|e| {
+            error!(target: LOG_TARGET, "Error compacting the database: {}", e);
+            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+        })?;
+
+        Ok(Response::new(tari_rpc::CompactDatabaseResponse {
+            path: result.path.to_string_lossy().to_string(),
+            original_size_bytes: result.original_size_bytes,
+            compacted_size_bytes: result.compacted_size_bytes,
+        }))
+    }
+
+    async fn set_sync_bandwidth_limit(
+        &self,
+        request: Request<tari_rpc::SetSyncBandwidthLimitRequest>,
+    ) -> Result<Response<tari_rpc::SetSyncBandwidthLimitResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::SetSyncBandwidthLimit)?;
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for SetSyncBandwidthLimit ({} bytes/s)", request.max_bytes_per_second
+        );
+
+        let previous = self
+            .state_machine_handle
+            .bandwidth_limiter()
+            .set_max_bytes_per_second(request.max_bytes_per_second);
+        info!(
+            target: LOG_TARGET,
+            "Block sync bandwidth limit changed from {} to {} bytes/s (0 = unlimited)",
+            previous.unwrap_or_default(),
+            request.max_bytes_per_second
+        );
+
+        Ok(Response::new(tari_rpc::SetSyncBandwidthLimitResponse {
+            previous_max_bytes_per_second: previous.unwrap_or_default(),
+        }))
+    }
+
+    async fn get_reorg_history(
+        &self,
+        request: Request<tari_rpc::GetReorgHistoryRequest>,
+    ) -> Result<Response<tari_rpc::GetReorgHistoryResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetReorgHistory)?;
+        let request = request.into_inner();
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetReorgHistory");
+
+        if !self.config.storage.track_reorgs {
+            return Ok(Response::new(tari_rpc::GetReorgHistoryResponse {
+                tracking_enabled: false,
+                reorgs: vec![],
+            }));
+        }
+        let limit = match request.limit {
+            0 => GET_REORG_HISTORY_DEFAULT_LIMIT,
+            limit => cmp::min(limit, GET_REORG_HISTORY_MAX_LIMIT),
+        };
+        let reorgs = self
+            .blockchain_db
+            .fetch_reorg_history(usize::try_from(limit).unwrap_or(usize::MAX))
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error fetching the reorg history: {}", e);
+                obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+            })?;
+
+        Ok(Response::new(tari_rpc::GetReorgHistoryResponse {
+            tracking_enabled: true,
+            reorgs: reorgs
+                .into_iter()
+                .map(|reorg| tari_rpc::ReorgRecord {
+                    new_height: reorg.new_height,
+                    new_hash: reorg.new_hash.to_vec(),
+                    prev_height: reorg.prev_height,
+                    prev_hash: reorg.prev_hash.to_vec(),
+                    added_blocks: reorg.added_blocks.iter().map(|h| h.to_vec()).collect(),
+                    removed_blocks: reorg.removed_blocks.iter().map(|h| h.to_vec()).collect(),
+                    reinstated_transactions: reorg.reinstated_transactions.iter().map(Into::into).collect(),
+                    timestamp: reorg.local_time.timestamp(),
+                })
+                .collect(),
+        }))
+    }
+
+    async fn search_payment_references(
+        &self,
+        request: Request<tari_rpc::SearchPaymentReferencesRequest>,
+    ) -> Result<Response<tari_rpc::SearchIndexedOutputsResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::SearchPaymentReferences)?;
+        let request = request.into_inner();
+        let report_error_flag = self.report_error_flag();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for SearchPaymentReferences ({} payment references)",
+            request.payment_references.len()
+        );
+
+        self.check_output_indexes_enabled()?;
+        if request.payment_references.len() > SEARCH_PAYMENT_REFERENCES_MAX {
+            return Err(obscure_error_if_true(
+                report_error_flag,
+                Status::invalid_argument(format!(
+                    "At most {} payment references can be searched for at a time",
+                    SEARCH_PAYMENT_REFERENCES_MAX
+                )),
+            ));
+        }
+        let payrefs = request
+            .payment_references
+            .into_iter()
+            .map(FixedHash::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::invalid_argument(format!("Invalid payment reference '{}'", e)),
+                )
+            })?;
+        let outputs = self.blockchain_db.fetch_outputs_by_payref(payrefs).await.map_err(|e| {
+            error!(target: LOG_TARGET, "Error searching payment references: {}", e);
+            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+        })?;
+
+        indexed_outputs_response(outputs)
+            .map(Response::new)
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e)))
+    }
+
+    async fn search_one_sided_script_key(
+        &self,
+        request: Request<tari_rpc::SearchOneSidedScriptKeyRequest>,
+    ) -> Result<Response<tari_rpc::SearchIndexedOutputsResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::SearchOneSidedScriptKey)?;
+        let request = request.into_inner();
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for SearchOneSidedScriptKey");
+
+        self.check_output_indexes_enabled()?;
+        let script_key = PublicKey::from_canonical_bytes(&request.script_key).map_err(|e| {
+            obscure_error_if_true(
+                report_error_flag,
+                Status::invalid_argument(format!("Invalid script key '{}'", e)),
+            )
+        })?;
+        let outputs = self
+            .blockchain_db
+            .fetch_outputs_by_script_key(script_key)
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error searching one-sided script key: {}", e);
+                obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+            })?;
+
+        indexed_outputs_response(outputs)
+            .map(Response::new)
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e)))
+    }
+
+    async fn get_shard_key(
+        &self,
+        request: Request<tari_rpc::GetShardKeyRequest>,
+    ) -> Result<Response<tari_rpc::GetShardKeyResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetShardKey)?;
+        let request = request.into_inner();
+        let report_error_flag = self.report_error_flag();
+        let mut handler = self.node_service.clone();
+        let public_key = PublicKey::from_canonical_bytes(&request.public_key)
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::invalid_argument(e.to_string())))?;
+
+        let shard_key = handler.get_shard_key(request.height, public_key).await.map_err(|e| {
+            error!(target: LOG_TARGET, "Error {}", e);
+            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+        })?;
+        if let Some(shard_key) = shard_key {
+            Ok(Response::new(tari_rpc::GetShardKeyResponse {
+                shard_key: shard_key.to_vec(),
+                found: true,
+            }))
+        } else {
             Ok(Response::new(tari_rpc::GetShardKeyResponse {
                 shard_key: vec![],
                 found: false,
@@ -2211,6 +2563,53 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         }
     }
 
+    async fn get_validator_node_changes(
+        &self,
+        request: Request<tari_rpc::GetValidatorNodeChangesRequest>,
+    ) -> Result<Response<tari_rpc::GetValidatorNodeChangesResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetValidatorNodeChanges)?;
+        let request = request.into_inner();
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetValidatorNodeChanges");
+
+        if request.end_epoch < request.start_epoch {
+            return Err(Status::invalid_argument("end_epoch must not be less than start_epoch"));
+        }
+        if request.end_epoch - request.start_epoch >= GET_VALIDATOR_NODE_CHANGES_MAX_EPOCHS {
+            return Err(Status::invalid_argument(format!(
+                "At most {} epochs can be requested at a time",
+                GET_VALIDATOR_NODE_CHANGES_MAX_EPOCHS
+            )));
+        }
+
+        let changes = self
+            .blockchain_db
+            .fetch_validator_node_changes(VnEpoch(request.start_epoch), VnEpoch(request.end_epoch))
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error fetching validator node changes: {}", e);
+                obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+            })?;
+
+        Ok(Response::new(tari_rpc::GetValidatorNodeChangesResponse {
+            changes: changes
+                .into_iter()
+                .map(|change| {
+                    let state = match change.kind {
+                        ValidatorNodeChangeKind::Added => tari_rpc::ValidatorNodeChangeState::Added,
+                        ValidatorNodeChangeKind::Removed => tari_rpc::ValidatorNodeChangeState::Removed,
+                    };
+                    tari_rpc::ValidatorNodeChange {
+                        epoch: change.epoch.as_u64(),
+                        public_key: change.public_key.to_vec(),
+                        shard_key: change.shard_key.to_vec(),
+                        state: state.into(),
+                    }
+                })
+                .collect(),
+        }))
+    }
+
     async fn get_active_validator_nodes(
         &self,
         request: Request<tari_rpc::GetActiveValidatorNodesRequest>,
@@ -2253,6 +2652,43 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(rx))
     }
 
+    async fn get_template_registration(
+        &self,
+        request: Request<tari_rpc::GetTemplateRegistrationRequest>,
+    ) -> Result<Response<tari_rpc::GetTemplateRegistrationResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetTemplateRegistration)?;
+        let request = request.into_inner();
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetTemplateRegistration");
+
+        let template_address = FixedHash::try_from(request.template_address).map_err(|e| {
+            obscure_error_if_true(
+                report_error_flag,
+                Status::invalid_argument(format!("Invalid template_address '{}'", e)),
+            )
+        })?;
+        let output = self
+            .blockchain_db
+            .fetch_output(template_address)
+            .await
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error fetching template registration: {}", e);
+                obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+            })?
+            .map(|info| info.output);
+        let registration = output
+            .as_ref()
+            .and_then(|output| output.features.code_template_registration())
+            .ok_or_else(|| {
+                obscure_error_if_true(report_error_flag, Status::not_found("Template registration not found"))
+            })?;
+
+        Ok(Response::new(tari_rpc::GetTemplateRegistrationResponse {
+            utxo_hash: template_address.to_vec(),
+            registration: Some(registration.clone().into()),
+        }))
+    }
+
     async fn get_template_registrations(
         &self,
         request: Request<tari_rpc::GetTemplateRegistrationsRequest>,
@@ -2464,6 +2900,162 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         );
         Ok(Response::new(rx))
     }
+
+    async fn subscribe_blocks(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<Self::SubscribeBlocksStream>, Status> {
+        self.check_method_enabled(GrpcMethod::SubscribeBlocks)?;
+        debug!(target: LOG_TARGET, "Incoming GRPC request for SubscribeBlocks");
+        let mut node_service = self.node_service.clone();
+        // Subscribe before fetching the current tip so that no tip changes are missed
+        let mut block_events = node_service.get_block_event_stream();
+        let (mut tx, rx) = mpsc::channel(SUBSCRIBE_BLOCKS_BUFFER_SIZE);
+
+        task::spawn(async move {
+            let mut last_tip_hash = None;
+            let mut reorg = None;
+            loop {
+                let metadata = match node_service.get_metadata().await {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        warn!(target: LOG_TARGET, "Base node service error: {}", e);
+                        return;
+                    },
+                };
+                if last_tip_hash.as_ref() != Some(metadata.best_block_hash()) || reorg.is_some() {
+                    last_tip_hash = Some(*metadata.best_block_hash());
+                    let mut resp = tari_rpc::SubscribeBlocksResponse {
+                        tip_height: metadata.best_block_height(),
+                        tip_hash: metadata.best_block_hash().to_vec(),
+                        ..Default::default()
+                    };
+                    if let Some((removed, added)) = reorg.take() {
+                        resp.set_event_type(tari_rpc::BlockEventType::Reorg);
+                        resp.removed_block_hashes = removed;
+                        resp.added_block_hashes = added;
+                    }
+                    if tx.send(Ok(resp)).await.is_err() {
+                        debug!(target: LOG_TARGET, "[subscribe_blocks] Client has disconnected");
+                        return;
+                    }
+                }
+
+                // Wait for the next event that may have changed the chain tip
+                reorg = loop {
+                    match block_events.recv().await {
+                        Ok(event) => match &*event {
+                            BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }) => {
+                                break Some((block_hashes(removed), block_hashes(added)));
+                            },
+                            BlockEvent::BlockSyncRewind(removed) => break Some((block_hashes(removed), Vec::new())),
+                            BlockEvent::ValidBlockAdded(_, BlockAddResult::Ok(_)) |
+                            BlockEvent::BlockSyncComplete(_, _) => break None,
+                            _ => {},
+                        },
+                        // Events were missed, check the tip in case it changed
+                        Err(broadcast::error::RecvError::Lagged(_)) => break None,
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    }
+                };
+            }
+        });
+        Ok(Response::new(rx))
+    }
+
+    async fn subscribe_sync_state(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<Self::SubscribeSyncStateStream>, Status> {
+        self.check_method_enabled(GrpcMethod::SubscribeSyncState)?;
+        debug!(target: LOG_TARGET, "Incoming GRPC request for SubscribeSyncState");
+        let mut status_watch = self.state_machine_handle.get_status_info_watch();
+        let (mut tx, rx) = mpsc::channel(SUBSCRIBE_SYNC_STATE_BUFFER_SIZE);
+
+        task::spawn(async move {
+            let mut last_event = None;
+            loop {
+                // The watch only holds the latest status, so a slow client skips intermediate progress updates
+                let event = sync_state_event(&status_watch.borrow_and_update());
+                if last_event.as_ref() != Some(&event) {
+                    if tx.send(Ok(event.clone())).await.is_err() {
+                        debug!(target: LOG_TARGET, "[subscribe_sync_state] Client has disconnected");
+                        return;
+                    }
+                    last_event = Some(event);
+                }
+
+                if status_watch.changed().await.is_err() {
+                    debug!(target: LOG_TARGET, "[subscribe_sync_state] State machine has shut down");
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(rx))
+    }
+
+    async fn subscribe_mempool(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<Self::SubscribeMempoolStream>, Status> {
+        self.check_method_enabled(GrpcMethod::SubscribeMempool)?;
+        debug!(target: LOG_TARGET, "Incoming GRPC request for SubscribeMempool");
+        let mut mempool_events = self.mempool.subscribe_events();
+        let (mut tx, rx) = mpsc::channel(SUBSCRIBE_MEMPOOL_BUFFER_SIZE);
+
+        task::spawn(async move {
+            let mut missed_events = 0;
+            loop {
+                let event = match mempool_events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        debug!(target: LOG_TARGET, "[subscribe_mempool] Client missed {} mempool event(s)", n);
+                        missed_events += n;
+                        continue;
+                    },
+                    Err(broadcast::error::RecvError::Closed) => {
+                        debug!(target: LOG_TARGET, "[subscribe_mempool] Mempool has shut down");
+                        return;
+                    },
+                };
+                let resp = match mempool_event(&event, missed_events) {
+                    Ok(resp) => resp,
+                    Err(e) => {
+                        warn!(target: LOG_TARGET, "[subscribe_mempool] Error converting mempool event: {}", e);
+                        missed_events += 1;
+                        continue;
+                    },
+                };
+                if tx.send(Ok(resp)).await.is_err() {
+                    debug!(target: LOG_TARGET, "[subscribe_mempool] Client has disconnected");
+                    return;
+                }
+                missed_events = 0;
+            }
+        });
+        Ok(Response::new(rx))
+    }
+
+    async fn get_supported_template_versions(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::GetSupportedTemplateVersionsResponse>, Status> {
+        self.check_method_enabled(GrpcMethod::GetSupportedTemplateVersions)?;
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetSupportedTemplateVersions");
+        let mut handler = self.node_service.clone();
+        let metadata = handler
+            .get_metadata()
+            .await
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?;
+        let height = metadata.best_block_height().saturating_add(1);
+
+        Ok(Response::new(tari_rpc::GetSupportedTemplateVersionsResponse {
+            height,
+            current_template_version: self.current_template_version(height),
+            supported_template_versions: self.supported_template_versions(height),
+        }))
+    }
 }
 
 enum BlockGroupType {
@@ -2534,3 +3126,224 @@ async fn get_block_group(
         calc_type: calc_type_response,
     }))
 }
+
+/// Returns the range of heights that a ListHeaders request scans, or None if there are no heights to scan
+async fn list_headers_range(
+    handler: &mut LocalNodeCommsInterface,
+    request: &tari_rpc::ListHeadersRequest,
+    tip: u64,
+    is_reversed: bool,
+) -> Result<Option<RangeInclusive<u64>>, Status> {
+    let bounds = match &request.height_range {
+        Some(range) => height_range(range, tip)?,
+        None => 0..=tip,
+    };
+    // The height of the first header to return, or None if a cursor is at the start of the chain already
+    let from_height = match BlockCursor::from_bytes(&request.cursor)? {
+        Some(cursor) => {
+            cursor.check_main_chain(handler).await?;
+            if is_reversed {
+                cursor.height().checked_sub(1)
+            } else {
+                Some(cursor.height().saturating_add(1))
+            }
+        },
+        None if request.from_height == 0 && is_reversed => Some(tip),
+        None => Some(cmp::min(request.from_height, tip)),
+    };
+    let header_range = from_height.map(|from_height| {
+        if is_reversed {
+            *bounds.start()..=cmp::min(from_height, *bounds.end())
+        } else {
+            cmp::max(from_height, *bounds.start())..=*bounds.end()
+        }
+    });
+    Ok(header_range.filter(|range| !range.is_empty()))
+}
+
+/// Returns the range of heights that a GetBlocks request scans, and the requested heights if the request is not for a
+/// height range
+async fn get_blocks_range(
+    handler: &mut LocalNodeCommsInterface,
+    request: tari_rpc::GetBlocksRequest,
+    tip: u64,
+) -> Result<(RangeInclusive<u64>, Option<Vec<u64>>), Status> {
+    let mut heights = request.heights;
+    let (range, heights) = match &request.height_range {
+        Some(_) if !heights.is_empty() => {
+            return Err(Status::invalid_argument(
+                "heights and height_range cannot both be provided",
+            ));
+        },
+        Some(range) => (height_range(range, tip)?, None),
+        None if heights.is_empty() => {
+            return Err(Status::invalid_argument(
+                "Either heights or height_range must be provided",
+            ));
+        },
+        None => {
+            heights.truncate(GET_BLOCKS_MAX_HEIGHTS);
+            heights.sort_unstable();
+            // unreachable panic: `heights` is not empty
+            let start = *heights.first().expect("unreachable");
+            let end = *heights.last().expect("unreachable");
+            (start..=cmp::min(end, tip), Some(heights))
+        },
+    };
+    match BlockCursor::from_bytes(&request.cursor)? {
+        Some(cursor) => {
+            cursor.check_main_chain(handler).await?;
+            let start = cmp::max(*range.start(), cursor.height().saturating_add(1));
+            Ok((start..=*range.end(), heights))
+        },
+        None => Ok((range, heights)),
+    }
+}
+
+/// Streams the headers in the given pages of heights that match the query to a ListHeaders client
+async fn stream_headers<I: Iterator<Item = (u64, u64)>>(
+    mut handler: LocalNodeCommsInterface,
+    consensus_rules: ConsensusManager,
+    pages: I,
+    query: BlockQuery,
+    tip: u64,
+    mut tx: mpsc::Sender<Result<tari_rpc::BlockHeaderResponse, Status>>,
+) {
+    let mut num_sent = 0;
+    for (start, end) in pages {
+        debug!(target: LOG_TARGET, "Page: {}-{}", start, end);
+        let result_data = if query.header_only {
+            handler.get_headers(start..=end).await.map(|headers| {
+                headers
+                    .into_iter()
+                    .filter(|header| query.matches(header.header()))
+                    .map(|header| Ok(header_only_response(header, tip)))
+                    .collect::<Result<Vec<_>, String>>()
+            })
+        } else {
+            handler.get_blocks(start..=end, true).await.map(|blocks| {
+                blocks
+                    .into_iter()
+                    .filter(|block| query.matches(block.header()))
+                    .map(|block| header_response(block, &consensus_rules))
+                    .collect::<Result<Vec<_>, String>>()
+            })
+        };
+
+        let mut result_data = match result_data {
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Internal base node service error: {}", err);
+                return;
+            },
+            Ok(Err(e)) => {
+                error!(target: LOG_TARGET, "No result headers transmitted due to error: {}", e);
+                continue;
+            },
+            Ok(Ok(result_data)) => result_data,
+        };
+        debug!(target: LOG_TARGET, "Result headers: {}", result_data.len());
+        if query.descending {
+            result_data.reverse();
+        }
+
+        for response in result_data {
+            debug!(
+                target: LOG_TARGET,
+                "Sending block header: {}",
+                response.header.as_ref().map(|h| h.height).unwrap_or(0)
+            );
+            if tx.send(Ok(response)).await.is_err() {
+                // Sender has closed i.e the connection has dropped/request was abandoned
+                warn!(
+                    target: LOG_TARGET,
+                    "[list_headers] GRPC request cancelled while sending response"
+                );
+                return;
+            }
+            num_sent += 1;
+            if num_sent >= query.limit {
+                return;
+            }
+        }
+    }
+}
+
+/// Streams the blocks in the given pages of heights that match the query to a GetBlocks client. The headers are
+/// fetched first, so that only the bodies of matching blocks are read.
+async fn stream_blocks<I: Iterator<Item = (u64, u64)>>(
+    mut handler: LocalNodeCommsInterface,
+    pages: I,
+    heights: Option<Vec<u64>>,
+    query: BlockQuery,
+    tip: u64,
+    mut tx: mpsc::Sender<Result<tari_rpc::HistoricalBlock, Status>>,
+    report_error_flag: bool,
+) {
+    let mut num_sent = 0;
+    for (start, end) in pages {
+        let headers = match handler.get_headers(start..=end).await {
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Error communicating with local base node: {:?}", err,
+                );
+                return;
+            },
+            Ok(data) => data
+                .into_iter()
+                .filter(|header| {
+                    heights
+                        .as_ref()
+                        .map_or(true, |heights| heights.contains(&header.height()))
+                })
+                .filter(|header| query.matches(header.header()))
+                .collect::<Vec<_>>(),
+        };
+
+        let (first, last) = match (headers.first(), headers.last()) {
+            (Some(first), Some(last)) => (first.height(), last.height()),
+            _ => continue,
+        };
+        let blocks = if query.header_only {
+            headers
+                .into_iter()
+                .map(|header| Ok(header_only_block(header, tip)))
+                .collect::<Vec<_>>()
+        } else {
+            match handler.get_blocks(first..=last, false).await {
+                Err(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error communicating with local base node: {:?}", err,
+                    );
+                    return;
+                },
+                Ok(data) => data
+                    .into_iter()
+                    .filter(|block| headers.iter().any(|header| header.hash() == block.hash()))
+                    .map(historical_block_response)
+                    .collect(),
+            }
+        };
+
+        for block in blocks {
+            let result = block.map_err(|err| {
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::internal(format!("Could not provide block: {}", err)),
+                )
+            });
+            if tx.send(result).await.is_err() {
+                warn!(
+                    target: LOG_TARGET,
+                    "[get_blocks] Request was cancelled while sending a response"
+                );
+                return;
+            }
+            num_sent += 1;
+            if num_sent >= query.limit {
+                return;
+            }
+        }
+    }
+}
@@ -20,9 +20,19 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::cmp;
+use std::{
+    cmp,
+    convert::{TryFrom, TryInto},
+    ops::RangeInclusive,
+};
 
-use tari_core::{base_node::LocalNodeCommsInterface, blocks::HistoricalBlock};
+use minotari_app_grpc::tari_rpc;
+use tari_common_types::types::FixedHash;
+use tari_core::{
+    base_node::LocalNodeCommsInterface,
+    blocks::{BlockHeader, HistoricalBlock},
+    proof_of_work::PowAlgorithm,
+};
 use tonic::Status;
 
 // The maximum number of blocks that can be requested at a time. These will be streamed to the
@@ -34,6 +44,8 @@ pub const GET_BLOCKS_MAX_HEIGHTS: usize = 1000;
 // requests to the base node, but if you'd like to stream directly, this can be set to 1.
 pub const GET_BLOCKS_PAGE_SIZE: usize = 10;
 
+const BLOCK_CURSOR_SIZE: usize = 8 + FixedHash::byte_size();
+
 /// Magic number for input and output sizes
 pub const BLOCK_INPUT_SIZE: u64 = 4;
 pub const BLOCK_OUTPUT_SIZE: u64 = 13;
@@ -82,3 +94,131 @@ pub fn block_fees(block: &HistoricalBlock) -> u64 {
         .iter()
         .sum::<u64>()
 }
+
+/// The position in the main chain after which a paginated GetBlocks or ListHeaders query continues. Clients receive it
+/// as opaque bytes. It includes the block hash so that a cursor that was invalidated by a reorg is rejected, instead of
+/// silently skipping or repeating blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockCursor {
+    height: u64,
+    hash: FixedHash,
+}
+
+impl BlockCursor {
+    pub fn new(height: u64, hash: FixedHash) -> Self {
+        Self { height, hash }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BLOCK_CURSOR_SIZE);
+        bytes.extend_from_slice(&self.height.to_be_bytes());
+        bytes.extend_from_slice(self.hash.as_slice());
+        bytes
+    }
+
+    /// Parses the cursor sent by a client, returning None if the client did not send a cursor
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<Self>, Status> {
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        if bytes.len() != BLOCK_CURSOR_SIZE {
+            return Err(Status::invalid_argument("Invalid cursor"));
+        }
+        let (height, hash) = bytes.split_at(8);
+        let height = height
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| Status::invalid_argument("Invalid cursor"))?;
+        let hash = FixedHash::try_from(hash).map_err(|_| Status::invalid_argument("Invalid cursor"))?;
+        Ok(Some(Self { height, hash }))
+    }
+
+    /// Checks that the block of the cursor is still in the main chain
+    pub async fn check_main_chain(&self, handler: &mut LocalNodeCommsInterface) -> Result<(), Status> {
+        let header = handler
+            .get_header(self.height)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        match header {
+            Some(header) if *header.hash() == self.hash => Ok(()),
+            _ => Err(Status::failed_precondition(
+                "The block of the cursor is no longer in the main chain, the query must be restarted",
+            )),
+        }
+    }
+}
+
+/// Returns the heights of a requested height range, limited to the chain tip. An end height of 0 means the tip.
+pub fn height_range(range: &tari_rpc::HeightRange, tip: u64) -> Result<RangeInclusive<u64>, Status> {
+    let end = match range.end_height {
+        0 => tip,
+        end if end < range.start_height => {
+            return Err(Status::invalid_argument("Start height was greater than end height"));
+        },
+        end => cmp::min(end, tip),
+    };
+    Ok(range.start_height..=end)
+}
+
+/// Parses the PoW algorithm filter of a query
+pub fn pow_algo_filter(pow_algo: Option<tari_rpc::PowAlgo>) -> Result<Option<PowAlgorithm>, Status> {
+    pow_algo
+        .map(|algo| {
+            u64::try_from(algo.pow_algo)
+                .map_err(|e| e.to_string())
+                .and_then(PowAlgorithm::try_from)
+                .map_err(|e| Status::invalid_argument(format!("Invalid PoW algo '{}'", e)))
+        })
+        .transpose()
+}
+
+/// The filters and field mask of a GetBlocks or ListHeaders query
+#[derive(Debug, Clone, Copy)]
+pub struct BlockQuery {
+    /// Only return blocks mined with this PoW algorithm
+    pub pow_algo: Option<PowAlgorithm>,
+    /// Only return block headers, without reading the block bodies
+    pub header_only: bool,
+    /// The maximum number of blocks or headers that are returned
+    pub limit: u64,
+    /// Return blocks from the highest to the lowest height
+    pub descending: bool,
+}
+
+impl BlockQuery {
+    /// Returns true if the block with this header matches the filters of the query
+    pub fn matches(&self, header: &BlockHeader) -> bool {
+        self.pow_algo.map_or(true, |algo| header.pow_algo() == algo)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_encodes_and_decodes_block_cursors() {
+        let cursor = BlockCursor::new(1234, FixedHash::from([7u8; 32]));
+        let decoded = BlockCursor::from_bytes(&cursor.to_bytes()).unwrap();
+        assert_eq!(decoded, Some(cursor));
+        assert_eq!(BlockCursor::from_bytes(&[]).unwrap(), None);
+        assert!(BlockCursor::from_bytes(&cursor.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn it_limits_height_ranges_to_the_tip() {
+        let range = |start_height, end_height| tari_rpc::HeightRange {
+            start_height,
+            end_height,
+        };
+        assert_eq!(height_range(&range(5, 0), 100).unwrap(), 5..=100);
+        assert_eq!(height_range(&range(5, 200), 100).unwrap(), 5..=100);
+        assert_eq!(height_range(&range(5, 10), 100).unwrap(), 5..=10);
+        assert!(height_range(&range(200, 0), 100).unwrap().is_empty());
+        assert!(height_range(&range(10, 5), 100).is_err());
+    }
+}
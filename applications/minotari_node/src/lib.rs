@@ -42,17 +42,24 @@ use std::{process, sync::Arc};
 use commands::{cli_loop::CliLoop, command::CommandContext};
 use futures::FutureExt;
 use log::*;
-use minotari_app_grpc::{authentication::ServerAuthenticationInterceptor, tls::identity::read_identity};
+use minotari_app_grpc::{
+    authentication::ServerAuthenticationInterceptor,
+    tls::{
+        client_auth::{server_tls_config, ClientCertificateService},
+        error::GrpcTlsError,
+        identity::{read_client_ca, read_identity},
+    },
+};
 use minotari_app_utilities::common_cli_args::CommonCliArgs;
 use tari_common::{
     configuration::bootstrap::{grpc_default_port, ApplicationType},
     exit_codes::{ExitCode, ExitError},
 };
-use tari_common_types::grpc_authentication::GrpcAuthentication;
+use tari_common_types::grpc_authentication::{GrpcAuthentication, GrpcClientCertificate};
 use tari_comms::{multiaddr::Multiaddr, utils::multiaddr::multiaddr_to_socketaddr, NodeIdentity};
 use tari_shutdown::{Shutdown, ShutdownSignal};
 use tokio::task;
-use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic::transport::{Server, ServerTlsConfig};
 
 use crate::cli::Cli;
 pub use crate::config::{ApplicationConfig, BaseNodeConfig, DatabaseType};
@@ -83,6 +90,8 @@ pub async fn run_base_node(
         },
         init: true,
         rebuild_db: false,
+        check_db: false,
+        repair_db: false,
         non_interactive_mode: true,
         watch: None,
         profile_with_tokio_console: false,
@@ -126,6 +135,13 @@ pub async fn run_base_node_with_cli(
         return Ok(());
     };
 
+    if cli.check_db || cli.repair_db {
+        info!(target: LOG_TARGET, "Checking the database integrity");
+        recovery::run_integrity_check(&config.base_node, cli.repair_db)
+            .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?;
+        return Ok(());
+    }
+
     // Build, node, build!
     let ctx = builder::configure_and_initialize_node(config.clone(), node_identity, shutdown.to_signal()).await?;
 
@@ -139,14 +155,15 @@ pub async fn run_base_node_with_cli(
             grpc::base_node_grpc_server::BaseNodeGrpcServer::from_base_node_context(&ctx, config.base_node.clone());
         let auth = config.base_node.grpc_authentication.clone();
 
-        let mut tls_identity = None;
-        if config.base_node.grpc_tls_enabled {
-            tls_identity = read_identity(config.base_node.config_dir.clone())
-                .await
-                .map(Some)
-                .map_err(|e| ExitError::new(ExitCode::TlsConfigurationError, e.to_string()))?;
-        }
-        task::spawn(run_grpc(grpc, grpc_address, auth, tls_identity, shutdown.to_signal()));
+        let (tls_config, client_certificates) = read_grpc_tls_config(&config.base_node).await?;
+        task::spawn(run_grpc(
+            grpc,
+            grpc_address,
+            auth,
+            tls_config,
+            client_certificates,
+            shutdown.to_signal(),
+        ));
     }
 
     // Run, node, run!
@@ -176,12 +193,32 @@ pub async fn run_base_node_with_cli(
     Ok(())
 }
 
+/// Reads the gRPC server TLS configuration and the TLS client certificates that may call the server
+async fn read_grpc_tls_config(
+    config: &BaseNodeConfig,
+) -> Result<(Option<ServerTlsConfig>, Vec<GrpcClientCertificate>), ExitError> {
+    if !config.grpc_tls_enabled {
+        return Ok((None, Vec::new()));
+    }
+    let tls_error = |e: GrpcTlsError| ExitError::new(ExitCode::TlsConfigurationError, e.to_string());
+    let identity = read_identity(config.config_dir.clone()).await.map_err(tls_error)?;
+    if !config.grpc_tls_client_auth_enabled {
+        return Ok((Some(server_tls_config(identity, None)), Vec::new()));
+    }
+    let client_ca = read_client_ca(config.config_dir.clone()).await.map_err(tls_error)?;
+    Ok((
+        Some(server_tls_config(identity, Some(client_ca))),
+        config.grpc_tls_client_certificates.clone(),
+    ))
+}
+
 /// Runs the gRPC server
 async fn run_grpc(
     grpc: grpc::base_node_grpc_server::BaseNodeGrpcServer,
     grpc_address: Multiaddr,
     auth_config: GrpcAuthentication,
-    tls_identity: Option<Identity>,
+    tls_config: Option<ServerTlsConfig>,
+    client_certificates: Vec<GrpcClientCertificate>,
     interrupt_signal: ShutdownSignal,
 ) -> Result<(), anyhow::Error> {
     info!(target: LOG_TARGET, "Starting GRPC on {}", grpc_address);
@@ -189,10 +226,13 @@ async fn run_grpc(
     let grpc_address = multiaddr_to_socketaddr(&grpc_address)?;
     let auth = ServerAuthenticationInterceptor::new(auth_config)
         .ok_or(anyhow::anyhow!("Unable to prepare server gRPC authentication"))?;
-    let service = minotari_app_grpc::tari_rpc::base_node_server::BaseNodeServer::with_interceptor(grpc, auth);
+    let service = ClientCertificateService::new(
+        minotari_app_grpc::tari_rpc::base_node_server::BaseNodeServer::with_interceptor(grpc, auth),
+        client_certificates,
+    );
 
-    let mut server_builder = if let Some(identity) = tls_identity {
-        Server::builder().tls_config(ServerTlsConfig::new().identity(identity))?
+    let mut server_builder = if let Some(tls_config) = tls_config {
+        Server::builder().tls_config(tls_config)?
     } else {
         Server::builder()
     };
@@ -127,6 +127,52 @@ pub async fn run_recovery(node_config: &BaseNodeConfig) -> Result<(), anyhow::Er
     })
 }
 
+/// Checks the database for missing or inconsistent chain data, optionally repairing it, and prints the report
+pub fn run_integrity_check(node_config: &BaseNodeConfig, repair: bool) -> Result<(), anyhow::Error> {
+    let rules = ConsensusManager::builder(node_config.network).build().map_err(|e| {
+        error!(target: LOG_TARGET, "Error configuring consensus manager: {}", e);
+        anyhow!("Could not configure consensus manager: {}", e)
+    })?;
+    let backend = match &node_config.db_type {
+        DatabaseType::Lmdb => create_lmdb_database(&node_config.lmdb_path, node_config.lmdb.clone(), rules.clone())
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Error opening db: {}", e);
+                anyhow!("Could not open DB: {}", e)
+            })?,
+    };
+    // The blocks in the db are not validated again, so mock validators are used
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let difficulty_calculator = DifficultyCalculator::new(rules.clone(), Default::default());
+    let db = BlockchainDatabase::new(backend, rules, validators, node_config.storage, difficulty_calculator)?;
+
+    println!("Checking the database integrity, this may take a while...");
+    let report = db.check_integrity()?;
+    println!("{}", report);
+    if report.is_consistent() {
+        return Ok(());
+    }
+    if !repair {
+        return Err(anyhow!(
+            "Found {} database issue(s), run with --repair-db to repair the database",
+            report.issues.len()
+        ));
+    }
+
+    println!("Repairing the database...");
+    let report = db.repair_integrity()?;
+    println!("{}", report);
+    if !report.is_consistent() {
+        return Err(anyhow!(
+            "The database could not be repaired, run with --rebuild-db or resync"
+        ));
+    }
+    Ok(())
+}
+
 // Function to handle the recovery attempt of the db
 async fn do_recovery<D: BlockchainBackend + 'static>(
     db: AsyncBlockchainDb<D>,
@@ -42,6 +42,7 @@ pub trait Encryptable<C> {
     const KNOWN_ONESIDED_PAYMENT_SCRIPT: &'static [u8] = b"KNOWN_ONESIDED_PAYMENT_SCRIPT";
     const CLIENT_KEY_VALUE: &'static [u8] = b"CLIENT_KEY_VALUE";
     const BURNT_PROOF: &'static [u8] = b"BURNT_PROOF";
+    const ATOMIC_SWAP: &'static [u8] = b"ATOMIC_SWAP";
 
     fn domain(&self, field_name: &'static str) -> Vec<u8>;
     fn encrypt(self, cipher: &C) -> Result<Self, String>
@@ -107,6 +108,103 @@ pub fn encrypt_bytes_integral_nonce(
     Ok(ciphertext_integral_nonce)
 }
 
+/// The result of moving a single encrypted value from one cipher to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RekeyOutcome {
+    /// The value was decrypted with the old cipher and encrypted with the new one
+    Rekeyed,
+    /// The value already authenticates under the new cipher and was left untouched
+    AlreadyRekeyed,
+    /// The value authenticated under neither cipher and was encrypted as legacy plaintext
+    MigratedPlaintext,
+}
+
+/// How a rekey treats a value that authenticates under neither the old nor the new cipher
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyPlaintext {
+    /// The value is corrupt or encrypted under an unknown key, and the rekey fails
+    Reject,
+    /// The column is known to hold values written before the database was encrypted, so the value is treated as
+    /// plaintext and encrypted under the new cipher
+    Migrate,
+}
+
+/// Running totals of the values touched during a rekey
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RekeySummary {
+    pub rekeyed: usize,
+    pub already_rekeyed: usize,
+    pub migrated_plaintext: usize,
+}
+
+impl RekeySummary {
+    pub fn record(&mut self, outcome: RekeyOutcome) {
+        match outcome {
+            RekeyOutcome::Rekeyed => self.rekeyed += 1,
+            RekeyOutcome::AlreadyRekeyed => self.already_rekeyed += 1,
+            RekeyOutcome::MigratedPlaintext => self.migrated_plaintext += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: RekeySummary) {
+        self.rekeyed += other.rekeyed;
+        self.already_rekeyed += other.already_rekeyed;
+        self.migrated_plaintext += other.migrated_plaintext;
+    }
+
+    /// The number of values that had to be written back
+    pub fn num_updated(&self) -> usize {
+        self.rekeyed + self.migrated_plaintext
+    }
+}
+
+/// Move an encryptable value from `old` to `new`.
+/// Values that already decrypt under `new` are returned as-is, which allows an interrupted rekey to be resumed. Values
+/// that decrypt under neither cipher are an error, unless `legacy_plaintext` allows them to be encrypted under `new` as
+/// legacy plaintext, in which case `old` must have been authenticated before calling this.
+pub fn rekey_encryptable<T: Encryptable<XChaCha20Poly1305> + Clone>(
+    value: T,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+    legacy_plaintext: LegacyPlaintext,
+) -> Result<(T, RekeyOutcome), String> {
+    if let Ok(decrypted) = value.clone().decrypt(old) {
+        return Ok((decrypted.encrypt(new)?, RekeyOutcome::Rekeyed));
+    }
+    if value.clone().decrypt(new).is_ok() {
+        return Ok((value, RekeyOutcome::AlreadyRekeyed));
+    }
+    check_legacy_plaintext(legacy_plaintext)?;
+    Ok((value.encrypt(new)?, RekeyOutcome::MigratedPlaintext))
+}
+
+/// Move raw ciphertext bound to `domain` from `old` to `new`, with the same semantics as [rekey_encryptable]
+pub fn rekey_bytes_integral_nonce(
+    ciphertext: &[u8],
+    domain: &[u8],
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+    legacy_plaintext: LegacyPlaintext,
+) -> Result<(Vec<u8>, RekeyOutcome), String> {
+    if let Ok(plaintext) = decrypt_bytes_integral_nonce(old, domain.to_vec(), ciphertext) {
+        let ciphertext = encrypt_bytes_integral_nonce(new, domain.to_vec(), Hidden::hide(plaintext))?;
+        return Ok((ciphertext, RekeyOutcome::Rekeyed));
+    }
+    if decrypt_bytes_integral_nonce(new, domain.to_vec(), ciphertext).is_ok() {
+        return Ok((ciphertext.to_vec(), RekeyOutcome::AlreadyRekeyed));
+    }
+    check_legacy_plaintext(legacy_plaintext)?;
+    let ciphertext = encrypt_bytes_integral_nonce(new, domain.to_vec(), Hidden::hide(ciphertext.to_vec()))?;
+    Ok((ciphertext, RekeyOutcome::MigratedPlaintext))
+}
+
+fn check_legacy_plaintext(legacy_plaintext: LegacyPlaintext) -> Result<(), String> {
+    match legacy_plaintext {
+        LegacyPlaintext::Migrate => Ok(()),
+        LegacyPlaintext::Reject => Err("Value cannot be decrypted with either the existing or the new key".to_string()),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::mem::size_of;
@@ -186,4 +284,43 @@ mod test {
         )
         .is_err());
     }
+
+    #[test]
+    fn test_rekey_bytes() {
+        let mut old_key = [0u8; size_of::<Key>()];
+        OsRng.fill_bytes(&mut old_key);
+        let old = XChaCha20Poly1305::new(Key::from_slice(&old_key));
+        let mut new_key = [0u8; size_of::<Key>()];
+        OsRng.fill_bytes(&mut new_key);
+        let new = XChaCha20Poly1305::new(Key::from_slice(&new_key));
+        let plaintext = b"The quick brown fox was annoying".to_vec();
+
+        // Values under the old key are moved to the new key
+        let ciphertext =
+            encrypt_bytes_integral_nonce(&old, b"domain".to_vec(), Hidden::hide(plaintext.clone())).unwrap();
+        let (rekeyed, outcome) =
+            rekey_bytes_integral_nonce(&ciphertext, b"domain", &old, &new, LegacyPlaintext::Reject).unwrap();
+        assert_eq!(outcome, RekeyOutcome::Rekeyed);
+        assert_eq!(
+            decrypt_bytes_integral_nonce(&new, b"domain".to_vec(), &rekeyed).unwrap(),
+            plaintext
+        );
+
+        // Running it again is a no-op
+        let (again, outcome) =
+            rekey_bytes_integral_nonce(&rekeyed, b"domain", &old, &new, LegacyPlaintext::Reject).unwrap();
+        assert_eq!(outcome, RekeyOutcome::AlreadyRekeyed);
+        assert_eq!(again, rekeyed);
+
+        // Plaintext is rejected unless the column may hold legacy plaintext, in which case it is encrypted under the
+        // new key
+        assert!(rekey_bytes_integral_nonce(&plaintext, b"domain", &old, &new, LegacyPlaintext::Reject).is_err());
+        let (migrated, outcome) =
+            rekey_bytes_integral_nonce(&plaintext, b"domain", &old, &new, LegacyPlaintext::Migrate).unwrap();
+        assert_eq!(outcome, RekeyOutcome::MigratedPlaintext);
+        assert_eq!(
+            decrypt_bytes_integral_nonce(&new, b"domain".to_vec(), &migrated).unwrap(),
+            plaintext
+        );
+    }
 }
@@ -44,6 +44,60 @@ impl GrpcAuthentication {
     }
 }
 
+/// A permission that can be granted to a [GrpcScopedToken]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GrpcScope {
+    /// Query balances, addresses and transaction history
+    ReadBalance,
+    /// Create payment requests to this wallet
+    CreateInvoice,
+    /// Send funds from this wallet
+    SendFunds,
+}
+
+/// A bearer token that only grants access to the gRPC methods covered by its scopes, e.g. for a point-of-sale terminal
+/// that needs to read the balance and create invoices but must not be able to spend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcScopedToken {
+    #[serde(deserialize_with = "deserialize_safe_password")]
+    pub token: SafePassword,
+    pub scopes: Vec<GrpcScope>,
+}
+
+impl GrpcScopedToken {
+    pub fn has_scope(&self, scope: GrpcScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+/// A TLS client certificate that is allowed to call the gRPC server when client certificate authentication is enabled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcClientCertificate {
+    /// The hex encoded SHA-256 fingerprint of the DER encoded certificate. Colon separators, as printed by `openssl
+    /// x509 -fingerprint -sha256`, are allowed.
+    pub fingerprint: String,
+    /// The snake case names of the gRPC methods that the certificate may call, e.g. "get_balance". An empty list
+    /// allows all methods.
+    #[serde(default)]
+    pub allow_methods: Vec<String>,
+}
+
+impl GrpcClientCertificate {
+    /// Returns the fingerprint as lowercase hex without separators
+    pub fn normalized_fingerprint(&self) -> String {
+        self.fingerprint
+            .chars()
+            .filter(|c| *c != ':')
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+
+    pub fn is_method_allowed(&self, method: &str) -> bool {
+        self.allow_methods.is_empty() || self.allow_methods.iter().any(|m| m == method)
+    }
+}
+
 fn deserialize_safe_password<'de, D>(deserializer: D) -> Result<SafePassword, D::Error>
 where D: serde::Deserializer<'de> {
     let password: String = Deserialize::deserialize(deserializer)?;
@@ -39,11 +39,16 @@ use crate::{
 };
 
 const INTERNAL_SIZE: usize = 33; // number of bytes used for the internal representation
+/// Set in the network byte of a subaddress. No node listens on the public key of a subaddress, so it can only receive
+/// one-sided payments.
+const SUBADDRESS_FLAG: u8 = 0x80;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct TariAddress {
     network: Network,
     public_key: PublicKey,
+    #[serde(default)]
+    is_subaddress: bool,
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -61,7 +66,20 @@ pub enum TariAddressError {
 impl TariAddress {
     /// Creates a new Tari Address from the provided public key and network while using the current version
     pub fn new(public_key: PublicKey, network: Network) -> Self {
-        TariAddress { network, public_key }
+        TariAddress {
+            network,
+            public_key,
+            is_subaddress: false,
+        }
+    }
+
+    /// Creates a subaddress from its public key, which the wallet derives from its view key and the subaddress index
+    pub fn new_subaddress(public_key: PublicKey, network: Network) -> Self {
+        TariAddress {
+            network,
+            public_key,
+            is_subaddress: true,
+        }
     }
 
     /// helper function to convert emojis to u8
@@ -99,10 +117,7 @@ impl TariAddress {
 
     /// Construct an Tari Address from a public key
     pub fn from_public_key(public_key: &PublicKey, network: Network) -> Self {
-        Self {
-            network,
-            public_key: public_key.clone(),
-        }
+        Self::new(public_key.clone(), network)
     }
 
     /// Gets the network from the Tari Address
@@ -110,6 +125,11 @@ impl TariAddress {
         self.network
     }
 
+    /// Whether this is a subaddress, which can only receive one-sided payments
+    pub fn is_subaddress(&self) -> bool {
+        self.is_subaddress
+    }
+
     /// Convert Tari Address to an emoji string with checksum
     pub fn to_emoji_string(&self) -> String {
         // Convert the public key to bytes and compute the checksum
@@ -130,6 +150,12 @@ impl TariAddress {
         }
         let mut fixed_data = bytes.to_vec();
         fixed_data[32] ^= network.as_byte();
+        let mut is_subaddress = false;
+        if validate_checksum(&fixed_data).is_err() {
+            // A subaddress carries the subaddress flag in addition to the network
+            fixed_data[32] ^= SUBADDRESS_FLAG;
+            is_subaddress = true;
+        }
         // Assert the checksum is valid
         if validate_checksum(&fixed_data).is_err() {
             return Err(TariAddressError::InvalidNetworkOrChecksum);
@@ -139,6 +165,7 @@ impl TariAddress {
         Ok(TariAddress {
             public_key: key,
             network,
+            is_subaddress,
         })
     }
 
@@ -149,14 +176,17 @@ impl TariAddress {
             return Err(TariAddressError::InvalidSize);
         }
         let checksum = compute_checksum(&bytes[0..32].to_vec());
+        let network_byte = checksum ^ bytes[32];
+        let is_subaddress = network_byte & SUBADDRESS_FLAG != 0;
         // if the network is a valid network number, we can assume that the checksum as valid
-        let network =
-            Network::try_from(checksum ^ bytes[32]).map_err(|_| TariAddressError::InvalidNetworkOrChecksum)?;
+        let network = Network::try_from(network_byte & !SUBADDRESS_FLAG)
+            .map_err(|_| TariAddressError::InvalidNetworkOrChecksum)?;
         let key =
             PublicKey::from_canonical_bytes(&bytes[0..32]).map_err(|_| TariAddressError::CannotRecoverPublicKey)?;
         Ok(TariAddress {
             public_key: key,
             network,
+            is_subaddress,
         })
     }
 
@@ -165,7 +195,11 @@ impl TariAddress {
         let mut buf = [0u8; INTERNAL_SIZE];
         buf[0..32].copy_from_slice(self.public_key.as_bytes());
         let checksum = compute_checksum(&buf[0..32].to_vec());
-        buf[32] = self.network.as_byte() ^ checksum;
+        let mut network_byte = self.network.as_byte();
+        if self.is_subaddress {
+            network_byte |= SUBADDRESS_FLAG;
+        }
+        buf[32] = network_byte ^ checksum;
         buf
     }
 
@@ -325,6 +359,33 @@ mod test {
         );
     }
 
+    #[test]
+    /// Test subaddress encoding
+    fn subaddress_encoding() {
+        let mut rng = rand::thread_rng();
+        let public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut rng));
+
+        let address = TariAddress::from_public_key(&public_key, Network::Esmeralda);
+        let subaddress = TariAddress::new_subaddress(public_key, Network::Esmeralda);
+        assert!(subaddress.is_subaddress());
+        assert_ne!(subaddress.to_bytes(), address.to_bytes());
+
+        assert_eq!(TariAddress::from_bytes(&subaddress.to_bytes()), Ok(subaddress.clone()));
+        assert_eq!(
+            TariAddress::from_bytes_with_network(&subaddress.to_bytes(), Network::Esmeralda),
+            Ok(subaddress.clone())
+        );
+        assert_eq!(
+            TariAddress::from_emoji_string(&subaddress.to_emoji_string()),
+            Ok(subaddress.clone())
+        );
+        assert_eq!(
+            TariAddress::from_bytes_with_network(&subaddress.to_bytes(), Network::Igor),
+            Err(TariAddressError::InvalidNetworkOrChecksum)
+        );
+        assert!(!TariAddress::from_bytes(&address.to_bytes()).unwrap().is_subaddress());
+    }
+
     #[test]
     /// Test invalid public key
     fn invalid_public_key() {
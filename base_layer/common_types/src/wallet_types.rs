@@ -32,6 +32,9 @@ use strum_macros::EnumString;
 pub enum WalletType {
     Software,
     Ledger(usize),
+    /// Detects outputs and builds transactions, but can't produce script signatures. Transactions are exported as
+    /// signing requests for an external signer that holds the same seed.
+    WatchOnly,
 }
 
 impl Display for WalletType {
@@ -39,6 +42,7 @@ impl Display for WalletType {
         match self {
             WalletType::Software => write!(f, "Software"),
             WalletType::Ledger(account) => write!(f, "Ledger({account})"),
+            WalletType::WatchOnly => write!(f, "WatchOnly"),
         }
     }
 }
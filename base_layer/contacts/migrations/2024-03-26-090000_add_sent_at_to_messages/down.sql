@@ -0,0 +1 @@
+ALTER TABLE messages drop sent_at;
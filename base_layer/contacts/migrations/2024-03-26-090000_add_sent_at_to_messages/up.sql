@@ -0,0 +1,2 @@
+ALTER TABLE messages ADD sent_at TIMESTAMP NULL;
+UPDATE messages SET sent_at = stored_at;
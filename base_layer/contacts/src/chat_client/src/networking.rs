@@ -124,9 +124,7 @@ pub async fn start(
             }
             let address = result.unwrap();
             trace!(target: LOG_TARGET, "resave the chat tor identity {:?}", identity);
-            if !node_id.public_addresses().contains(&address) {
-                node_id.add_public_address(address);
-            }
+            node_id.replace_onion_address(address);
         };
         spawn_comms_using_transport(comms, p2p_config.transport.clone(), after_comms).await?
     } else {
@@ -36,7 +36,12 @@ use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityRequester},
     types::CommsPublicKey,
 };
-use tari_comms_dht::{domain_message::OutboundDomainMessage, outbound::OutboundEncryption, Dht};
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    envelope::SafMessageClass,
+    outbound::OutboundEncryption,
+    Dht,
+};
 use tari_p2p::{
     comms_connector::SubscriptionFactory,
     domain_message::DomainMessage,
@@ -299,9 +304,10 @@ where T: ContactsBackend + 'static
                 Ok(result.map(ContactsServiceResponse::Messages)?)
             },
             ContactsServiceRequest::SendMessage(address, mut message) => {
+                message.stored_at = Utc::now().naive_utc().timestamp() as u64;
+                message.sent_at = message.stored_at;
                 let ob_message = OutboundDomainMessage::from(MessageDispatch::Message(message.clone()));
 
-                message.stored_at = Utc::now().naive_utc().timestamp() as u64;
                 match self.db.save_message(message) {
                     Ok(_) => {
                         if let Err(e) = self.deliver_message(address.clone(), ob_message).await {
@@ -581,9 +587,16 @@ where T: ContactsBackend + 'static
         message: Message,
         source_public_key: CommsPublicKey,
     ) -> Result<(), ContactsServiceError> {
+        let stored_at = EpochTime::now().as_u64();
+        // The sender's clock is not trusted to place a message after the time it was received
+        let sent_at = match message.sent_at {
+            0 => stored_at,
+            sent_at => sent_at.min(stored_at),
+        };
         let our_message = Message {
             address: TariAddress::from_public_key(&source_public_key, message.address.network()),
-            stored_at: EpochTime::now().as_u64(),
+            stored_at,
+            sent_at,
             ..message
         };
         trace!(target: LOG_TARGET, "Handling chat message {:?}", our_message);
@@ -681,7 +694,12 @@ where T: ContactsBackend + 'static
                 info!(target: LOG_TARGET, "Chat message being sent via closest broadcast");
                 let mut comms_outbound = self.dht.outbound_requester();
                 comms_outbound
-                    .closest_broadcast(address.public_key().clone(), encryption, vec![], message)
+                    .closest_broadcast(
+                        address.public_key().clone(),
+                        encryption,
+                        vec![],
+                        message.with_saf_class(SafMessageClass::Chat),
+                    )
                     .await?;
             },
         };
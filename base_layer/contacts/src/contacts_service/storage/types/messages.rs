@@ -47,6 +47,7 @@ pub struct MessagesSqlInsert {
     pub metadata: Vec<u8>,
     pub stored_at: NaiveDateTime,
     pub direction: i32,
+    pub sent_at: Option<NaiveDateTime>,
 }
 
 #[derive(Clone, Debug, Queryable, PartialEq, Eq, QueryableByName)]
@@ -61,6 +62,7 @@ pub struct MessagesSql {
     pub delivery_confirmation_at: Option<NaiveDateTime>,
     pub read_confirmation_at: Option<NaiveDateTime>,
     pub direction: i32,
+    pub sent_at: Option<NaiveDateTime>,
 }
 #[derive(Clone, Debug, AsChangeset, PartialEq, Eq)]
 #[diesel(table_name = messages)]
@@ -90,7 +92,7 @@ impl MessagesSql {
     ) -> Result<Vec<MessagesSql>, ContactsServiceStorageError> {
         Ok(messages::table
             .filter(messages::address.eq(address))
-            .order(messages::stored_at.desc())
+            .order((messages::sent_at.desc(), messages::stored_at.desc()))
             .offset(limit * page)
             .limit(limit)
             .load::<MessagesSql>(conn)?)
@@ -146,8 +148,9 @@ impl TryFrom<MessagesSql> for Message {
             )
             .ok_or(ContactsServiceStorageError::ConversionError)?,
             stored_at: o.stored_at.timestamp() as u64,
-            delivery_confirmation_at: Some(o.stored_at.timestamp() as u64),
-            read_confirmation_at: Some(o.stored_at.timestamp() as u64),
+            sent_at: o.sent_at.unwrap_or(o.stored_at).timestamp() as u64,
+            delivery_confirmation_at: o.delivery_confirmation_at.map(|t| t.timestamp() as u64),
+            read_confirmation_at: o.read_confirmation_at.map(|t| t.timestamp() as u64),
             body: o.body,
             metadata,
             message_id: o.message_id,
@@ -170,6 +173,7 @@ impl TryFrom<Message> for MessagesSqlInsert {
             metadata: metadata.into_bytes().to_vec(),
             stored_at: NaiveDateTime::from_timestamp_opt(o.stored_at as i64, 0).unwrap(),
             direction: i32::from(o.direction.as_byte()),
+            sent_at: NaiveDateTime::from_timestamp_opt(o.sent_at as i64, 0),
         })
     }
 }
@@ -39,6 +39,7 @@ pub struct Message {
     pub address: TariAddress,
     pub direction: Direction,
     pub stored_at: u64,
+    pub sent_at: u64,
     pub delivery_confirmation_at: Option<u64>,
     pub read_confirmation_at: Option<u64>,
     pub message_id: Vec<u8>,
@@ -90,6 +91,7 @@ impl TryFrom<proto::Message> for Message {
             // A Message from a proto::Message will always be an inbound message
             direction: Direction::Inbound,
             message_id: message.message_id,
+            sent_at: message.sent_at,
             ..Message::default()
         })
     }
@@ -107,6 +109,7 @@ impl From<Message> for proto::Message {
             address: message.address.to_bytes().to_vec(),
             direction: i32::from(message.direction.as_byte()),
             message_id: message.message_id,
+            sent_at: message.sent_at,
         }
     }
 }
@@ -21,5 +21,6 @@ diesel::table! {
         delivery_confirmation_at -> Nullable<Timestamp>,
         read_confirmation_at -> Nullable<Timestamp>,
         direction -> Integer,
+        sent_at -> Nullable<Timestamp>,
     }
 }
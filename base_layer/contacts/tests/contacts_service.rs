@@ -97,6 +97,7 @@ pub fn setup_contacts_service<T: ContactsBackend + 'static>(
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
         listener_liveness_check_interval: None,
+        ban_policy: Default::default(),
     };
     let peer_message_subscription_factory = Arc::new(subscription_factory);
     let shutdown = Shutdown::new();
@@ -305,3 +306,43 @@ pub fn test_message_pagination() {
         assert_eq!(0, messages.len());
     });
 }
+
+#[test]
+pub fn test_messages_are_ordered_by_send_time() {
+    with_temp_dir(|dir_path| {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_path = format!("{}/{}", dir_path.to_str().unwrap(), db_name);
+        let url: DbConnectionUrl = db_path.try_into().unwrap();
+
+        let db = DbConnection::connect_url(&url).unwrap();
+        let contacts_db = ContactsDatabase::new(ContactsServiceSqliteDatabase::init(db));
+
+        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let address = TariAddress::new(public_key, Network::default());
+
+        // Messages delivered late by store-and-forward arrive out of order
+        for (sent_at, stored_at) in [(1_700_000_030, 1_700_000_100), (1_700_000_010, 1_700_000_200)] {
+            let mut message = MessageBuilder::new()
+                .message(format!("Sent at {}", sent_at))
+                .address(address.clone())
+                .build();
+            message.sent_at = sent_at;
+            message.stored_at = stored_at;
+            contacts_db.save_message(message).expect("Message to be saved");
+        }
+
+        let messages = contacts_db.get_messages(address.clone(), 10, 0).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].sent_at, 1_700_000_030);
+        assert_eq!(messages[1].sent_at, 1_700_000_010);
+        assert!(messages[0].delivery_confirmation_at.is_none());
+        assert!(messages[0].read_confirmation_at.is_none());
+
+        contacts_db
+            .confirm_message(messages[1].message_id.clone(), Some(1_700_000_300), None)
+            .unwrap();
+        let messages = contacts_db.get_messages(address, 10, 0).unwrap();
+        assert_eq!(messages[1].delivery_confirmation_at, Some(1_700_000_300));
+        assert!(messages[1].read_confirmation_at.is_none());
+    });
+}
@@ -26,7 +26,7 @@ use log::*;
 use prost::Message;
 use tari_common::log_if_error;
 use tari_common_types::chain_metadata::ChainMetadata;
-use tari_comms::{connectivity::ConnectivityRequester, message::MessageExt, BAN_DURATION_LONG};
+use tari_comms::{connectivity::ConnectivityRequester, message::MessageExt, peer_manager::PeerOffense};
 use tari_p2p::services::liveness::{LivenessEvent, LivenessHandle, MetadataKey, PingPongEvent};
 use tokio::sync::broadcast;
 
@@ -103,8 +103,8 @@ impl ChainMetadataService {
                            if let ChainMetadataSyncError::ReceivedInvalidChainMetadata(node_id,reason) = e {
                                log_if_error!(
                                  level: info,
-                                 target: LOG_TARGET, "Failed to ban node '{}'",
-                                 self.connectivity.ban_peer_until(node_id, BAN_DURATION_LONG, reason).await);                                           }
+                                 target: LOG_TARGET, "Failed to report offense for node '{}'",
+                                 self.connectivity.report_offense(node_id, PeerOffense::InvalidMessage, reason).await);                                           }
                         }
                     }
 
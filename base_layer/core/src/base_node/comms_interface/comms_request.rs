@@ -29,7 +29,11 @@ use serde::{Deserialize, Serialize};
 use tari_common_types::types::{BlockHash, Commitment, HashOutput, PrivateKey, PublicKey, Signature};
 use tari_utilities::hex::Hex;
 
-use crate::{blocks::NewBlockTemplate, chain_storage::MmrTree, proof_of_work::PowAlgorithm};
+use crate::{
+    blocks::{NewBlockTemplate, ShortIdKey},
+    chain_storage::MmrTree,
+    proof_of_work::PowAlgorithm,
+};
 
 /// A container for the parameters required for a FetchMmrState request.
 #[derive(Debug, Serialize, Deserialize)]
@@ -56,6 +60,7 @@ pub enum NodeCommsRequest {
     GetBlockFromAllChains(HashOutput),
     FetchKernelByExcessSig(Signature),
     FetchMempoolTransactionsByExcessSigs { excess_sigs: Vec<PrivateKey> },
+    FetchMempoolTransactionsByShortIds { key: ShortIdKey, short_ids: Vec<u64> },
     FetchValidatorNodesKeys { height: u64 },
     GetShardKey { height: u64, public_key: PublicKey },
     FetchTemplateRegistrations { start_height: u64, end_height: u64 },
@@ -98,6 +103,14 @@ impl Display for NodeCommsRequest {
             FetchMempoolTransactionsByExcessSigs { .. } => {
                 write!(f, "FetchMempoolTransactionsByExcessSigs")
             },
+            FetchMempoolTransactionsByShortIds { key, short_ids } => {
+                write!(
+                    f,
+                    "FetchMempoolTransactionsByShortIds ({}, n={})",
+                    key.block_hash,
+                    short_ids.len()
+                )
+            },
             FetchValidatorNodesKeys { height } => {
                 write!(f, "FetchValidatorNodesKeys ({})", height)
             },
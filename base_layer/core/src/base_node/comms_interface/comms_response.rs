@@ -58,6 +58,7 @@ pub enum NodeCommsResponse {
     TargetDifficulty(Difficulty),
     MmrNodes(Vec<HashOutput>, Vec<u8>),
     FetchMempoolTransactionsByExcessSigsResponse(FetchMempoolTransactionsResponse),
+    FetchMempoolTransactionsByShortIdsResponse(FetchMempoolTransactionsByShortIdsResponse),
     FetchValidatorNodesKeysResponse(Vec<(PublicKey, [u8; 32])>),
     GetShardKeyResponse(Option<[u8; 32]>),
     FetchTemplateRegistrationsResponse(Vec<TemplateRegistrationEntry>),
@@ -95,6 +96,12 @@ impl Display for NodeCommsResponse {
                 resp.transactions.len(),
                 resp.not_found.len()
             ),
+            FetchMempoolTransactionsByShortIdsResponse(resp) => write!(
+                f,
+                "FetchMempoolTransactionsByShortIdsResponse({} transaction(s), {} not found)",
+                resp.transactions.len(),
+                resp.not_found.len()
+            ),
             FetchValidatorNodesKeysResponse(_) => write!(f, "FetchValidatorNodesKeysResponse"),
             GetShardKeyResponse(_) => write!(f, "GetShardKeyResponse"),
             FetchTemplateRegistrationsResponse(_) => write!(f, "FetchTemplateRegistrationsResponse"),
@@ -108,3 +115,10 @@ pub struct FetchMempoolTransactionsResponse {
     pub transactions: Vec<Arc<Transaction>>,
     pub not_found: Vec<PrivateKey>,
 }
+
+/// Container struct for mempool transaction responses to a request by compact block short IDs
+#[derive(Debug, Clone)]
+pub struct FetchMempoolTransactionsByShortIdsResponse {
+    pub transactions: Vec<Arc<Transaction>>,
+    pub not_found: Vec<u64>,
+}
@@ -33,6 +33,7 @@ use crate::{
     mempool::MempoolError,
     proof_of_work::{monero_rx::MergeMineError, DifficultyError},
     transactions::transaction_components::TransactionError,
+    validation::ValidationError,
 };
 
 #[derive(Debug, Error)]
@@ -77,6 +78,8 @@ pub enum CommsInterfaceError {
     DifficultyError(#[from] DifficultyError),
     #[error("Transaction error: {0}")]
     TransactionError(#[from] TransactionError),
+    #[error("Validation error: {0}")]
+    ValidationError(#[from] ValidationError),
 }
 
 impl CommsInterfaceError {
@@ -99,6 +102,7 @@ impl CommsInterfaceError {
             CommsInterfaceError::MempoolError(e) => e.get_ban_reason(),
             CommsInterfaceError::ChainStorageError(e) => e.get_ban_reason(),
             CommsInterfaceError::MergeMineError(e) => e.get_ban_reason(),
+            CommsInterfaceError::ValidationError(e) => e.get_ban_reason(),
             CommsInterfaceError::NoBootstrapNodesConfigured |
             CommsInterfaceError::OutboundMessageError(_) |
             CommsInterfaceError::BroadcastFailed |
@@ -26,7 +26,7 @@ use std::{cmp::max, collections::HashSet, sync::Arc, time::Instant};
 
 use log::*;
 use strum_macros::Display;
-use tari_common_types::types::{BlockHash, FixedHash, HashOutput};
+use tari_common_types::types::{BlockHash, FixedHash, HashOutput, PrivateKey};
 use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId};
 use tari_utilities::hex::Hex;
 use tokio::sync::RwLock;
@@ -37,24 +37,27 @@ use crate::{
     base_node::comms_interface::{
         error::CommsInterfaceError,
         local_interface::BlockEventSender,
+        FetchMempoolTransactionsByShortIdsResponse,
         FetchMempoolTransactionsResponse,
         NodeCommsRequest,
         NodeCommsResponse,
         OutboundNodeCommsInterface,
     },
-    blocks::{Block, BlockBuilder, BlockHeader, BlockHeaderValidationError, ChainBlock, NewBlock, NewBlockTemplate},
+    blocks::{
+        Block,
+        BlockBuilder,
+        BlockHeader,
+        BlockHeaderValidationError,
+        ChainBlock,
+        NewBlock,
+        NewBlockTemplate,
+        ShortIdKey,
+    },
     chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainStorageError},
     consensus::{ConsensusConstants, ConsensusManager},
     mempool::Mempool,
-    proof_of_work::{
-        randomx_difficulty,
-        randomx_factory::RandomXFactory,
-        sha3x_difficulty,
-        Difficulty,
-        PowAlgorithm,
-        PowError,
-    },
-    transactions::aggregated_body::AggregateBody,
+    proof_of_work::{randomx_factory::RandomXFactory, Difficulty, PowAlgorithm, PowContext, PowError, PowRegistry},
+    transactions::{aggregated_body::AggregateBody, transaction_components::Transaction},
     validation::{helpers, ValidationError},
 };
 
@@ -75,6 +78,14 @@ pub enum BlockEvent {
     AddBlockErrored {
         block: Arc<Block>,
     },
+    /// A block would have caused a reorg deeper than the maximum allowed reorg depth and was rejected
+    DeepReorgDetected {
+        block: Arc<Block>,
+        source_peer: Option<NodeId>,
+        fork_height: u64,
+        depth: u64,
+        max_depth: u64,
+    },
     BlockSyncComplete(Arc<ChainBlock>, u64),
     BlockSyncRewind(Vec<Arc<ChainBlock>>),
 }
@@ -89,6 +100,8 @@ pub struct InboundNodeCommsHandlers<B> {
     outbound_nci: OutboundNodeCommsInterface,
     connectivity: ConnectivityRequester,
     randomx_factory: RandomXFactory,
+    pow_registry: PowRegistry,
+    compact_block_relay: bool,
 }
 
 impl<B> InboundNodeCommsHandlers<B>
@@ -104,6 +117,7 @@ where B: BlockchainBackend + 'static
         connectivity: ConnectivityRequester,
         randomx_factory: RandomXFactory,
     ) -> Self {
+        let pow_registry = PowRegistry::from_consensus(&consensus_manager);
         Self {
             block_event_sender,
             blockchain_db,
@@ -113,9 +127,18 @@ where B: BlockchainBackend + 'static
             outbound_nci,
             connectivity,
             randomx_factory,
+            pow_registry,
+            compact_block_relay: false,
         }
     }
 
+    /// Propagate blocks as compact blocks, identifying their transactions by short IDs rather than by kernel excess
+    /// signatures. Blocks received in either form are always accepted.
+    pub fn with_compact_block_relay(mut self, enabled: bool) -> Self {
+        self.compact_block_relay = enabled;
+        self
+    }
+
     /// Handle inbound node comms requests from remote nodes and local services.
     #[allow(clippy::too_many_lines)]
     pub async fn handle_request(&self, request: NodeCommsRequest) -> Result<NodeCommsResponse, CommsInterfaceError> {
@@ -396,6 +419,15 @@ where B: BlockchainBackend + 'static
                     },
                 ))
             },
+            NodeCommsRequest::FetchMempoolTransactionsByShortIds { key, short_ids } => {
+                let (transactions, not_found) = self.mempool.retrieve_by_short_ids(key, short_ids).await?;
+                Ok(NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse(
+                    FetchMempoolTransactionsByShortIdsResponse {
+                        transactions,
+                        not_found,
+                    },
+                ))
+            },
             NodeCommsRequest::FetchValidatorNodesKeys { height } => {
                 let active_validator_nodes = self.blockchain_db.fetch_active_validator_nodes(height).await?;
                 Ok(NodeCommsResponse::FetchValidatorNodesKeysResponse(
@@ -531,15 +563,16 @@ where B: BlockchainBackend + 'static
                 .fetch_chain_header(header.height().saturating_sub(1))
                 .await?;
         }
-        let achieved = match new_block.header.pow_algo() {
-            PowAlgorithm::RandomX => randomx_difficulty(
-                &new_block.header,
-                &self.randomx_factory,
-                &gen_hash,
-                &self.consensus_manager,
-            )?,
-            PowAlgorithm::Sha3x => sha3x_difficulty(&new_block.header)?,
+        let pow_context = PowContext {
+            consensus: &self.consensus_manager,
+            randomx_factory: &self.randomx_factory,
+            genesis_hash: gen_hash,
         };
+        let achieved = self
+            .pow_registry
+            .get(new_block.header.pow_algo(), new_block.header.height)
+            .map_err(BlockHeaderValidationError::ProofOfWorkError)?
+            .achieved_difficulty(&new_block.header, &pow_context)?;
         if achieved < min_difficulty {
             return Err(CommsInterfaceError::InvalidBlockHeader(
                 BlockHeaderValidationError::ProofOfWorkError(PowError::AchievedDifficultyBelowMin),
@@ -586,7 +619,6 @@ where B: BlockchainBackend + 'static
         Ok(())
     }
 
-    #[allow(clippy::too_many_lines)]
     async fn reconcile_block(
         &mut self,
         source_peer: NodeId,
@@ -597,10 +629,12 @@ where B: BlockchainBackend + 'static
             coinbase_kernel,
             coinbase_output,
             kernel_excess_sigs: excess_sigs,
+            kernel_short_ids: short_ids,
+            short_id_salt,
         } = new_block;
         // If the block is empty, we dont have to ask for the block, as we already have the full block available
         // to us.
-        if excess_sigs.is_empty() {
+        if excess_sigs.is_empty() && short_ids.is_empty() {
             let block = BlockBuilder::new(header.version)
                 .with_coinbase_utxo(coinbase_output, coinbase_kernel)
                 .with_header(header)
@@ -625,83 +659,40 @@ where B: BlockchainBackend + 'static
             );
             #[allow(clippy::cast_possible_wrap)]
             #[cfg(feature = "metrics")]
-            metrics::compact_block_tx_misses(header.height).set(excess_sigs.len() as i64);
+            metrics::compact_block_tx_misses(header.height).set((excess_sigs.len() + short_ids.len()) as i64);
             let block = self.request_full_block_from_peer(source_peer, block_hash).await?;
             return Ok(block);
         }
 
         // We know that the block is neither and orphan or a coinbase, so lets ask our mempool for the transactions
-        let (known_transactions, missing_excess_sigs) = self.mempool.retrieve_by_excess_sigs(excess_sigs).await?;
-        let known_transactions = known_transactions.into_iter().map(|tx| (*tx).clone()).collect();
-
-        #[allow(clippy::cast_possible_wrap)]
-        #[cfg(feature = "metrics")]
-        metrics::compact_block_tx_misses(header.height).set(missing_excess_sigs.len() as i64);
-
-        let mut builder = BlockBuilder::new(header.version)
-            .with_coinbase_utxo(coinbase_output, coinbase_kernel)
-            .with_transactions(known_transactions);
-
-        if missing_excess_sigs.is_empty() {
-            debug!(
-                target: LOG_TARGET,
-                "All transactions for block #{} ({}) found in mempool",
-                header.height,
-                block_hash.to_hex()
-            );
+        let transactions = if short_ids.is_empty() {
+            self.fetch_transactions_by_excess_sigs(&source_peer, &header, excess_sigs)
+                .await?
         } else {
-            debug!(
-                target: LOG_TARGET,
-                "Requesting {} unknown transaction(s) from peer '{}'.",
-                missing_excess_sigs.len(),
-                source_peer
-            );
-
-            let FetchMempoolTransactionsResponse {
-                transactions,
-                not_found,
-            } = self
-                .outbound_nci
-                .request_transactions_by_excess_sig(source_peer.clone(), missing_excess_sigs)
-                .await?;
-
-            // Add returned transactions to unconfirmed pool
-            if !transactions.is_empty() {
-                self.mempool.insert_all(transactions.clone()).await?;
-            }
-
-            if !not_found.is_empty() {
-                warn!(
-                    target: LOG_TARGET,
-                    "Peer {} was not able to return all transactions for block #{} ({}). {} transaction(s) not found. \
-                     Requesting full block.",
-                    source_peer,
-                    header.height,
-                    block_hash.to_hex(),
-                    not_found.len()
-                );
-
+            let key = ShortIdKey::new(block_hash, short_id_salt);
+            self.fetch_transactions_by_short_ids(&source_peer, &header, key, short_ids)
+                .await?
+        };
+        let transactions = match transactions {
+            Some(transactions) => transactions,
+            None => {
                 #[cfg(feature = "metrics")]
                 metrics::compact_block_full_misses(header.height).inc();
                 let block = self.request_full_block_from_peer(source_peer, block_hash).await?;
                 return Ok(block);
-            }
-
-            builder = builder.with_transactions(
-                transactions
-                    .into_iter()
-                    .map(|tx| Arc::try_unwrap(tx).unwrap_or_else(|tx| (*tx).clone()))
-                    .collect(),
-            );
-        }
+            },
+        };
 
         // NB: Add the header last because `with_transactions` etc updates the current header, but we have the final one
         // already
-        builder = builder.with_header(header.clone());
-        let block = builder.build();
+        let block = BlockBuilder::new(header.version)
+            .with_coinbase_utxo(coinbase_output, coinbase_kernel)
+            .with_transactions(transactions)
+            .with_header(header.clone())
+            .build();
 
         // Perform a sanity check on the reconstructed block, if the MMR roots don't match then it's possible one or
-        // more transactions in our mempool had the same excess/signature for a *different* transaction.
+        // more transactions in our mempool had the same excess/signature (or short ID) for a *different* transaction.
         // This is extremely unlikely, but still possible. In case of a mismatch, request the full block from the peer.
         let (block, mmr_roots) = match self.blockchain_db.calculate_mmr_roots(block).await {
             Err(_) => {
@@ -728,6 +719,131 @@ where B: BlockchainBackend + 'static
         Ok(block)
     }
 
+    /// Collect the transactions of a block from the mempool by kernel excess signature, requesting any that are missing
+    /// from the peer that sent the block. Returns `None` if the peer could not provide all the missing transactions.
+    async fn fetch_transactions_by_excess_sigs(
+        &mut self,
+        source_peer: &NodeId,
+        header: &BlockHeader,
+        excess_sigs: Vec<PrivateKey>,
+    ) -> Result<Option<Vec<Transaction>>, CommsInterfaceError> {
+        let (known_transactions, missing_excess_sigs) = self.mempool.retrieve_by_excess_sigs(excess_sigs).await?;
+        let transactions = known_transactions
+            .into_iter()
+            .map(|tx| (*tx).clone())
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::cast_possible_wrap)]
+        #[cfg(feature = "metrics")]
+        metrics::compact_block_tx_misses(header.height).set(missing_excess_sigs.len() as i64);
+
+        if missing_excess_sigs.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "All transactions for block #{} ({}) found in mempool",
+                header.height,
+                header.hash().to_hex()
+            );
+            return Ok(Some(transactions));
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Requesting {} unknown transaction(s) from peer '{}'.",
+            missing_excess_sigs.len(),
+            source_peer
+        );
+        let FetchMempoolTransactionsResponse {
+            transactions: fetched,
+            not_found,
+        } = self
+            .outbound_nci
+            .request_transactions_by_excess_sig(source_peer.clone(), missing_excess_sigs)
+            .await?;
+        self.add_fetched_transactions(source_peer, header, transactions, fetched, not_found.len())
+            .await
+    }
+
+    /// Collect the transactions of a compact block from the mempool by short ID, requesting any that are missing from
+    /// the peer that sent the block. Returns `None` if the peer could not provide all the missing transactions.
+    async fn fetch_transactions_by_short_ids(
+        &mut self,
+        source_peer: &NodeId,
+        header: &BlockHeader,
+        key: ShortIdKey,
+        short_ids: Vec<u64>,
+    ) -> Result<Option<Vec<Transaction>>, CommsInterfaceError> {
+        let (known_transactions, missing_short_ids) = self.mempool.retrieve_by_short_ids(key, short_ids).await?;
+        let transactions = known_transactions
+            .into_iter()
+            .map(|tx| (*tx).clone())
+            .collect::<Vec<_>>();
+
+        #[allow(clippy::cast_possible_wrap)]
+        #[cfg(feature = "metrics")]
+        metrics::compact_block_tx_misses(header.height).set(missing_short_ids.len() as i64);
+
+        if missing_short_ids.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "All transactions for compact block #{} ({}) found in mempool",
+                header.height,
+                key.block_hash.to_hex()
+            );
+            return Ok(Some(transactions));
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Requesting {} unknown transaction(s) by short ID from peer '{}'.",
+            missing_short_ids.len(),
+            source_peer
+        );
+        let FetchMempoolTransactionsByShortIdsResponse {
+            transactions: fetched,
+            not_found,
+        } = self
+            .outbound_nci
+            .request_transactions_by_short_ids(source_peer.clone(), key, missing_short_ids)
+            .await?;
+        self.add_fetched_transactions(source_peer, header, transactions, fetched, not_found.len())
+            .await
+    }
+
+    async fn add_fetched_transactions(
+        &mut self,
+        source_peer: &NodeId,
+        header: &BlockHeader,
+        mut transactions: Vec<Transaction>,
+        fetched: Vec<Arc<Transaction>>,
+        num_not_found: usize,
+    ) -> Result<Option<Vec<Transaction>>, CommsInterfaceError> {
+        // Add returned transactions to unconfirmed pool
+        if !fetched.is_empty() {
+            self.mempool.insert_all(fetched.clone()).await?;
+        }
+
+        if num_not_found > 0 {
+            warn!(
+                target: LOG_TARGET,
+                "Peer {} was not able to return all transactions for block #{} ({}). {} transaction(s) not found. \
+                 Requesting full block.",
+                source_peer,
+                header.height,
+                header.hash().to_hex(),
+                num_not_found
+            );
+            return Ok(None);
+        }
+
+        transactions.extend(
+            fetched
+                .into_iter()
+                .map(|tx| Arc::try_unwrap(tx).unwrap_or_else(|tx| (*tx).clone())),
+        );
+        Ok(Some(transactions))
+    }
+
     async fn request_full_block_from_peer(
         &mut self,
         source_peer: NodeId,
@@ -820,7 +936,10 @@ where B: BlockchainBackend + 'static
                         block_hash.to_hex()
                     );
                     let exclude_peers = source_peer.into_iter().collect();
-                    let new_block_msg = NewBlock::from(&*block);
+                    let mut new_block_msg = NewBlock::from(&*block);
+                    if self.compact_block_relay {
+                        new_block_msg = new_block_msg.into_compact(rand::random());
+                    }
                     if let Err(e) = self.outbound_nci.propagate_block(new_block_msg, exclude_peers).await {
                         warn!(
                             target: LOG_TARGET,
@@ -851,6 +970,37 @@ where B: BlockchainBackend + 'static
                 Err(e.into())
             },
 
+            Err(
+                e @ ChainStorageError::ReorgDepthExceeded {
+                    fork_height,
+                    depth,
+                    max_depth,
+                },
+            ) => {
+                #[cfg(feature = "metrics")]
+                metrics::rejected_blocks(block.header.height, &block.hash()).inc();
+
+                warn!(
+                    target: LOG_TARGET,
+                    "Block #{} ({}) from peer {} was rejected: {}",
+                    block_height,
+                    block_hash.to_hex(),
+                    source_peer
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| "<local request>".to_string()),
+                    e
+                );
+                self.publish_block_event(BlockEvent::DeepReorgDetected {
+                    block,
+                    source_peer,
+                    fork_height,
+                    depth,
+                    max_depth,
+                });
+                Err(e.into())
+            },
+
             Err(e) => {
                 #[cfg(feature = "metrics")]
                 metrics::rejected_blocks(block.header.height, &block.hash()).inc();
@@ -1000,6 +1150,7 @@ impl<B> Clone for InboundNodeCommsHandlers<B> {
             outbound_nci: self.outbound_nci.clone(),
             connectivity: self.connectivity.clone(),
             randomx_factory: self.randomx_factory.clone(),
+            compact_block_relay: self.compact_block_relay,
         }
     }
 }
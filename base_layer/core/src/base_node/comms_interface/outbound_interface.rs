@@ -28,11 +28,12 @@ use tokio::sync::mpsc::UnboundedSender;
 use crate::{
     base_node::comms_interface::{
         error::CommsInterfaceError,
+        FetchMempoolTransactionsByShortIdsResponse,
         FetchMempoolTransactionsResponse,
         NodeCommsRequest,
         NodeCommsResponse,
     },
-    blocks::{Block, NewBlock},
+    blocks::{Block, NewBlock, ShortIdKey},
 };
 
 /// The OutboundNodeCommsInterface provides an interface to request information from remove nodes.
@@ -94,6 +95,27 @@ impl OutboundNodeCommsInterface {
         }
     }
 
+    /// Fetch the transactions corresponding to the short IDs of a compact block from the given peer `NodeId`.
+    pub async fn request_transactions_by_short_ids(
+        &mut self,
+        node_id: NodeId,
+        key: ShortIdKey,
+        short_ids: Vec<u64>,
+    ) -> Result<FetchMempoolTransactionsByShortIdsResponse, CommsInterfaceError> {
+        if let NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse(resp) = self
+            .request_sender
+            .call((
+                NodeCommsRequest::FetchMempoolTransactionsByShortIds { key, short_ids },
+                Some(node_id),
+            ))
+            .await??
+        {
+            Ok(resp)
+        } else {
+            Err(CommsInterfaceError::UnexpectedApiResponse)
+        }
+    }
+
     /// Transmit a block to remote base nodes, excluding the provided peers.
     pub async fn propagate_block(
         &self,
@@ -27,6 +27,7 @@ use tari_utilities::ByteArray;
 
 use crate::{
     base_node::comms_interface::NodeCommsRequest,
+    blocks::ShortIdKey,
     proto::{base_node as proto, base_node::base_node_service_request::Request as ProtoNodeCommsRequest},
 };
 
@@ -35,7 +36,11 @@ impl TryInto<NodeCommsRequest> for ProtoNodeCommsRequest {
     type Error = String;
 
     fn try_into(self) -> Result<NodeCommsRequest, Self::Error> {
-        use ProtoNodeCommsRequest::{FetchMempoolTransactionsByExcessSigs, GetBlockFromAllChains};
+        use ProtoNodeCommsRequest::{
+            FetchMempoolTransactionsByExcessSigs,
+            FetchMempoolTransactionsByShortIds,
+            GetBlockFromAllChains,
+        };
         let request = match self {
             GetBlockFromAllChains(req) => {
                 NodeCommsRequest::GetBlockFromAllChains(req.hash.try_into().map_err(|_| "Malformed hash".to_string())?)
@@ -51,6 +56,13 @@ impl TryInto<NodeCommsRequest> for ProtoNodeCommsRequest {
 
                 NodeCommsRequest::FetchMempoolTransactionsByExcessSigs { excess_sigs }
             },
+            FetchMempoolTransactionsByShortIds(req) => NodeCommsRequest::FetchMempoolTransactionsByShortIds {
+                key: ShortIdKey::new(
+                    req.block_hash.try_into().map_err(|_| "Malformed hash".to_string())?,
+                    req.salt,
+                ),
+                short_ids: req.short_ids,
+            },
         };
         Ok(request)
     }
@@ -60,7 +72,11 @@ impl TryFrom<NodeCommsRequest> for ProtoNodeCommsRequest {
     type Error = String;
 
     fn try_from(request: NodeCommsRequest) -> Result<Self, Self::Error> {
-        use NodeCommsRequest::{FetchMempoolTransactionsByExcessSigs, GetBlockFromAllChains};
+        use NodeCommsRequest::{
+            FetchMempoolTransactionsByExcessSigs,
+            FetchMempoolTransactionsByShortIds,
+            GetBlockFromAllChains,
+        };
         match request {
             GetBlockFromAllChains(hash) => Ok(ProtoNodeCommsRequest::GetBlockFromAllChains(
                 proto::GetBlockFromAllChainsRequest { hash: hash.to_vec() },
@@ -70,6 +86,13 @@ impl TryFrom<NodeCommsRequest> for ProtoNodeCommsRequest {
                     excess_sigs: excess_sigs.into_iter().map(|sig| sig.to_vec()).collect(),
                 }),
             ),
+            FetchMempoolTransactionsByShortIds { key, short_ids } => Ok(
+                ProtoNodeCommsRequest::FetchMempoolTransactionsByShortIds(proto::ShortIds {
+                    block_hash: key.block_hash.to_vec(),
+                    salt: key.salt,
+                    short_ids,
+                }),
+            ),
             e => Err(format!("{} request is not supported", e)),
         }
     }
@@ -31,7 +31,11 @@ use tari_utilities::{convert::try_convert_all, ByteArray};
 
 pub use crate::proto::base_node::base_node_service_response::Response as ProtoNodeCommsResponse;
 use crate::{
-    base_node::comms_interface::{FetchMempoolTransactionsResponse, NodeCommsResponse},
+    base_node::comms_interface::{
+        FetchMempoolTransactionsByShortIdsResponse,
+        FetchMempoolTransactionsResponse,
+        NodeCommsResponse,
+    },
     blocks::{Block, BlockHeader, HistoricalBlock},
     proto,
 };
@@ -40,7 +44,12 @@ impl TryInto<NodeCommsResponse> for ProtoNodeCommsResponse {
     type Error = String;
 
     fn try_into(self) -> Result<NodeCommsResponse, Self::Error> {
-        use ProtoNodeCommsResponse::{BlockResponse, FetchMempoolTransactionsByExcessSigsResponse, HistoricalBlocks};
+        use ProtoNodeCommsResponse::{
+            BlockResponse,
+            FetchMempoolTransactionsByExcessSigsResponse,
+            FetchMempoolTransactionsByShortIdsResponse,
+            HistoricalBlocks,
+        };
         let response = match self {
             BlockResponse(block) => NodeCommsResponse::Block(Box::new(block.try_into()?)),
             HistoricalBlocks(blocks) => {
@@ -67,6 +76,19 @@ impl TryInto<NodeCommsResponse> for ProtoNodeCommsResponse {
                     },
                 )
             },
+            FetchMempoolTransactionsByShortIdsResponse(response) => {
+                let transactions = response
+                    .transactions
+                    .into_iter()
+                    .map(|tx| tx.try_into().map(Arc::new))
+                    .collect::<Result<_, _>>()?;
+                NodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse(
+                    self::FetchMempoolTransactionsByShortIdsResponse {
+                        transactions,
+                        not_found: response.not_found,
+                    },
+                )
+            },
         };
 
         Ok(response)
@@ -77,7 +99,11 @@ impl TryFrom<NodeCommsResponse> for ProtoNodeCommsResponse {
     type Error = String;
 
     fn try_from(response: NodeCommsResponse) -> Result<Self, Self::Error> {
-        use NodeCommsResponse::{FetchMempoolTransactionsByExcessSigsResponse, HistoricalBlocks};
+        use NodeCommsResponse::{
+            FetchMempoolTransactionsByExcessSigsResponse,
+            FetchMempoolTransactionsByShortIdsResponse,
+            HistoricalBlocks,
+        };
         match response {
             NodeCommsResponse::Block(block) => Ok(ProtoNodeCommsResponse::BlockResponse((*block).try_into()?)),
             HistoricalBlocks(historical_blocks) => {
@@ -103,6 +129,19 @@ impl TryFrom<NodeCommsResponse> for ProtoNodeCommsResponse {
                     },
                 ))
             },
+            FetchMempoolTransactionsByShortIdsResponse(resp) => {
+                let transactions = resp
+                    .transactions
+                    .into_iter()
+                    .map(|tx| tx.try_into())
+                    .collect::<Result<_, _>>()?;
+                Ok(ProtoNodeCommsResponse::FetchMempoolTransactionsByShortIdsResponse(
+                    proto::base_node::FetchMempoolTransactionsByShortIdsResponse {
+                        transactions,
+                        not_found: resp.not_found,
+                    },
+                ))
+            },
             // This would only occur if a programming error sent out the unsupported response
             resp => Err(format!("Response not supported {:?}", resp)),
         }
@@ -150,6 +150,8 @@ pub enum TxLocation {
     NotStored,
     InMempool,
     Mined,
+    /// The transaction was removed from the mempool because it was not mined before the mempool's expiry
+    Expired,
 }
 
 impl Display for TxLocation {
@@ -158,6 +160,7 @@ impl Display for TxLocation {
             TxLocation::NotStored => "Not Stored",
             TxLocation::InMempool => "In Mempool",
             TxLocation::Mined => "Mined",
+            TxLocation::Expired => "Expired",
         };
         fmt.write_str(response)
     }
@@ -167,23 +170,25 @@ impl TryFrom<proto::TxLocation> for TxLocation {
     type Error = String;
 
     fn try_from(tx_location: proto::TxLocation) -> Result<Self, Self::Error> {
-        use proto::TxLocation::{InMempool, Mined, None, NotStored};
+        use proto::TxLocation::{Expired, InMempool, Mined, None, NotStored};
         Ok(match tx_location {
             None => return Err("TxLocation not provided".to_string()),
             NotStored => TxLocation::NotStored,
             InMempool => TxLocation::InMempool,
             Mined => TxLocation::Mined,
+            Expired => TxLocation::Expired,
         })
     }
 }
 
 impl From<TxLocation> for proto::TxLocation {
     fn from(resp: TxLocation) -> Self {
-        use TxLocation::{InMempool, Mined, NotStored};
+        use TxLocation::{Expired, InMempool, Mined, NotStored};
         match resp {
             NotStored => proto::TxLocation::NotStored,
             InMempool => proto::TxLocation::InMempool,
             Mined => proto::TxLocation::Mined,
+            Expired => proto::TxLocation::Expired,
         }
     }
 }
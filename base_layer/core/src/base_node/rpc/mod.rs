@@ -20,6 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod scan_tag_filter;
 #[cfg(feature = "base_node")]
 mod service;
 #[cfg(feature = "base_node")]
@@ -41,6 +42,8 @@ use crate::{
     proto,
     proto::{
         base_node::{
+            EstimateFeePerGramRequest,
+            EstimateFeePerGramResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
@@ -48,6 +51,7 @@ use crate::{
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures,
+            SyncCandidateUtxosByBlockRequest,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
@@ -119,6 +123,19 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
         &self,
         request: Request<GetMempoolFeePerGramStatsRequest>,
     ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus>;
+
+    #[rpc(method = 13)]
+    async fn estimate_fee_per_gram(
+        &self,
+        request: Request<EstimateFeePerGramRequest>,
+    ) -> Result<Response<EstimateFeePerGramResponse>, RpcStatus>;
+
+    /// As `sync_utxos_by_block`, but only streams the outputs that match the request's scan tag filter
+    #[rpc(method = 14)]
+    async fn sync_candidate_utxos_by_block(
+        &self,
+        request: Request<SyncCandidateUtxosByBlockRequest>,
+    ) -> Result<Streaming<SyncUtxosByBlockResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
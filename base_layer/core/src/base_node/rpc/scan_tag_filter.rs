@@ -0,0 +1,219 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use tari_common_types::types::PublicKey;
+use tari_crypto::{hash_domain, hashing::DomainSeparatedHasher};
+use tari_utilities::ByteArray;
+
+use crate::{
+    one_sided::one_sided_scan_tag,
+    proto::base_node as proto,
+    transactions::transaction_components::TransactionOutput,
+};
+
+hash_domain!(
+    ScanTagFilterDomain,
+    "com.tari.base_layer.core.base_node.scan_tag_filter",
+    1
+);
+
+/// The largest filter a base node will accept
+pub const MAX_SCAN_TAG_FILTER_BYTES: usize = 64 * 1024;
+/// The most hash functions a base node will evaluate per output
+pub const MAX_SCAN_TAG_FILTER_HASHES: u32 = 16;
+// Ten bits per tag with seven hash functions gives a false positive rate of just under 1%
+const BITS_PER_TAG: usize = 10;
+const NUM_HASHES: u32 = 7;
+const MIN_FILTER_BYTES: usize = 8;
+
+/// A bloom filter over the scan tags of the one-sided outputs a wallet is looking for. A wallet sends this to a base
+/// node so that only candidate outputs are returned instead of every output on chain. False positives are expected
+/// and are discarded by the wallet when it fails to decrypt them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanTagFilter {
+    bits: Vec<u8>,
+    num_hashes: u32,
+}
+
+impl ScanTagFilter {
+    /// Creates an empty filter sized for `num_tags` scan tags
+    pub fn with_capacity(num_tags: usize) -> Self {
+        let num_bytes = num_tags
+            .saturating_mul(BITS_PER_TAG)
+            .div_ceil(8)
+            .clamp(MIN_FILTER_BYTES, MAX_SCAN_TAG_FILTER_BYTES);
+        Self {
+            bits: vec![0u8; num_bytes],
+            num_hashes: NUM_HASHES,
+        }
+    }
+
+    pub fn insert(&mut self, scan_tag: &PublicKey) {
+        for bit in self.bit_indexes(scan_tag) {
+            self.bits[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    pub fn contains(&self, scan_tag: &PublicKey) -> bool {
+        self.bit_indexes(scan_tag)
+            .iter()
+            .all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Returns true if the output is a candidate for the wallet that built this filter. Outputs without a scan tag are
+    /// candidates only if `include_untagged` is set.
+    pub fn is_candidate(&self, output: &TransactionOutput, include_untagged: bool) -> bool {
+        match one_sided_scan_tag(&output.script) {
+            Some(scan_tag) => self.contains(scan_tag),
+            None => include_untagged,
+        }
+    }
+
+    // Double hashing (Kirsch-Mitzenmacher) over a single domain separated digest of the scan tag
+    #[allow(clippy::cast_possible_truncation)]
+    fn bit_indexes(&self, scan_tag: &PublicKey) -> Vec<usize> {
+        let digest = DomainSeparatedHasher::<Blake2b<U32>, ScanTagFilterDomain>::new()
+            .chain(scan_tag.as_bytes())
+            .finalize();
+        let digest = digest.as_ref();
+        let mut h1 = [0u8; 8];
+        h1.copy_from_slice(&digest[..8]);
+        let mut h2 = [0u8; 8];
+        h2.copy_from_slice(&digest[8..16]);
+        let (h1, h2) = (u64::from_le_bytes(h1), u64::from_le_bytes(h2));
+        let num_bits = self.bits.len() as u64 * 8;
+        (0..u64::from(self.num_hashes))
+            // The index is less than the number of bits in the filter so it fits in a usize
+            .map(|i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+            .collect()
+    }
+}
+
+impl TryFrom<proto::ScanTagFilter> for ScanTagFilter {
+    type Error = String;
+
+    fn try_from(filter: proto::ScanTagFilter) -> Result<Self, Self::Error> {
+        if filter.bits.is_empty() || filter.bits.len() > MAX_SCAN_TAG_FILTER_BYTES {
+            return Err(format!(
+                "Scan tag filter must be between 1 and {} bytes, got {}",
+                MAX_SCAN_TAG_FILTER_BYTES,
+                filter.bits.len()
+            ));
+        }
+        if filter.num_hashes == 0 || filter.num_hashes > MAX_SCAN_TAG_FILTER_HASHES {
+            return Err(format!(
+                "Scan tag filter must use between 1 and {} hash functions, got {}",
+                MAX_SCAN_TAG_FILTER_HASHES, filter.num_hashes
+            ));
+        }
+        Ok(Self {
+            bits: filter.bits,
+            num_hashes: filter.num_hashes,
+        })
+    }
+}
+
+impl From<ScanTagFilter> for proto::ScanTagFilter {
+    fn from(filter: ScanTagFilter) -> Self {
+        Self {
+            bits: filter.bits,
+            num_hashes: filter.num_hashes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+    use tari_script::{one_sided_payment_script, stealth_payment_script};
+
+    use super::*;
+
+    fn random_public_key() -> PublicKey {
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng))
+    }
+
+    #[test]
+    fn it_matches_inserted_scan_tags() {
+        let tags = (0..100).map(|_| random_public_key()).collect::<Vec<_>>();
+        let mut filter = ScanTagFilter::with_capacity(tags.len());
+        for tag in &tags {
+            filter.insert(tag);
+        }
+        assert!(tags.iter().all(|tag| filter.contains(tag)));
+
+        let false_positives = (0..1000).filter(|_| filter.contains(&random_public_key())).count();
+        assert!(false_positives < 50, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn it_selects_candidate_outputs() {
+        let tag = random_public_key();
+        let mut filter = ScanTagFilter::with_capacity(1);
+        filter.insert(&tag);
+
+        let one_sided = TransactionOutput {
+            script: one_sided_payment_script(&tag),
+            ..Default::default()
+        };
+        assert!(filter.is_candidate(&one_sided, false));
+
+        let other = TransactionOutput {
+            script: one_sided_payment_script(&random_public_key()),
+            ..Default::default()
+        };
+        assert!(!filter.is_candidate(&other, true));
+
+        let stealth = TransactionOutput {
+            script: stealth_payment_script(&random_public_key(), &random_public_key()),
+            ..Default::default()
+        };
+        assert!(!filter.is_candidate(&stealth, false));
+        assert!(filter.is_candidate(&stealth, true));
+        assert!(filter.is_candidate(&TransactionOutput::default(), true));
+    }
+
+    #[test]
+    fn it_rejects_oversized_filters() {
+        let filter = proto::ScanTagFilter {
+            bits: vec![0u8; MAX_SCAN_TAG_FILTER_BYTES + 1],
+            num_hashes: NUM_HASHES,
+        };
+        assert!(ScanTagFilter::try_from(filter).is_err());
+        let filter = proto::ScanTagFilter {
+            bits: vec![0u8; 8],
+            num_hashes: MAX_SCAN_TAG_FILTER_HASHES + 1,
+        };
+        assert!(ScanTagFilter::try_from(filter).is_err());
+        let filter = ScanTagFilter::with_capacity(10);
+        assert_eq!(
+            ScanTagFilter::try_from(proto::ScanTagFilter::from(filter.clone())),
+            Ok(filter)
+        );
+    }
+}
@@ -31,7 +31,7 @@ use tokio::sync::mpsc;
 
 use crate::{
     base_node::{
-        rpc::{sync_utxos_by_block_task::SyncUtxosByBlockTask, BaseNodeWalletService},
+        rpc::{scan_tag_filter::ScanTagFilter, sync_utxos_by_block_task::SyncUtxosByBlockTask, BaseNodeWalletService},
         state_machine_service::states::StateInfo,
         StateMachineHandle,
     },
@@ -40,6 +40,8 @@ use crate::{
     proto,
     proto::{
         base_node::{
+            EstimateFeePerGramRequest,
+            EstimateFeePerGramResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
@@ -48,6 +50,7 @@ use crate::{
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures as SignaturesProto,
+            SyncCandidateUtxosByBlockRequest,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
@@ -169,6 +172,14 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletRpcService<B> {
                 best_block_height: chain_metadata.best_block_height(),
                 mined_timestamp: 0,
             },
+            TxStorageResponse::NotStoredExpired => TxQueryResponse {
+                location: TxLocation::Expired as i32,
+                best_block_hash: vec![],
+                confirmations: 0,
+                is_synced,
+                best_block_height: chain_metadata.best_block_height(),
+                mined_timestamp: 0,
+            },
         };
         Ok(mempool_response)
     }
@@ -219,7 +230,9 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
                 rejection_reason: TxSubmissionRejectionReason::TimeLocked.into(),
                 is_synced,
             },
-            TxStorageResponse::NotStoredConsensus | TxStorageResponse::NotStored => TxSubmissionResponse {
+            TxStorageResponse::NotStoredConsensus |
+            TxStorageResponse::NotStored |
+            TxStorageResponse::NotStoredExpired => TxSubmissionResponse {
                 accepted: false,
                 rejection_reason: TxSubmissionRejectionReason::ValidationFailed.into(),
                 is_synced,
@@ -652,6 +665,39 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
         Ok(Streaming::new(rx))
     }
 
+    async fn sync_candidate_utxos_by_block(
+        &self,
+        request: Request<SyncCandidateUtxosByBlockRequest>,
+    ) -> Result<Streaming<SyncUtxosByBlockResponse>, RpcStatus> {
+        let peer = request.context().peer_node_id().clone();
+        let req = request.into_message();
+        let filter = req
+            .filter
+            .ok_or_else(|| RpcStatus::bad_request("Scan tag filter is required"))
+            .and_then(|filter| ScanTagFilter::try_from(filter).map_err(|e| RpcStatus::bad_request(&e)))?;
+        debug!(
+            target: LOG_TARGET,
+            "Received sync_candidate_utxos_by_block request from {} from header {} to {} ",
+            peer,
+            req.start_header_hash.to_hex(),
+            req.end_header_hash.to_hex(),
+        );
+
+        const BATCH_SIZE: usize = 5;
+        let (tx, rx) = mpsc::channel(BATCH_SIZE);
+        let task = SyncUtxosByBlockTask::new(self.db()).with_filter(filter, req.include_untagged);
+        task.run(
+            SyncUtxosByBlockRequest {
+                start_header_hash: req.start_header_hash,
+                end_header_hash: req.end_header_hash,
+            },
+            tx,
+        )
+        .await?;
+
+        Ok(Streaming::new(rx))
+    }
+
     async fn get_mempool_fee_per_gram_stats(
         &self,
         request: Request<GetMempoolFeePerGramStatsRequest>,
@@ -677,4 +723,30 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
 
         Ok(Response::new(stats.into()))
     }
+
+    async fn estimate_fee_per_gram(
+        &self,
+        request: Request<EstimateFeePerGramRequest>,
+    ) -> Result<Response<EstimateFeePerGramResponse>, RpcStatus> {
+        let req = request.into_message();
+        let target_blocks = usize::try_from(req.target_blocks)
+            .ok()
+            .filter(|target_blocks| *target_blocks > 0)
+            .ok_or_else(|| RpcStatus::bad_request("target_blocks must be greater than 0"))?;
+
+        let metadata = self
+            .db
+            .get_chain_metadata()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let fee_per_gram = self
+            .mempool()
+            .estimate_fee_per_gram(target_blocks, metadata.best_block_height())
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+
+        Ok(Response::new(EstimateFeePerGramResponse {
+            fee_per_gram: fee_per_gram.as_u64(),
+        }))
+    }
 }
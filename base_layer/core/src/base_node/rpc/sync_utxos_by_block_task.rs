@@ -28,23 +28,44 @@ use tari_utilities::hex::Hex;
 use tokio::{sync::mpsc, task};
 
 use crate::{
+    base_node::rpc::scan_tag_filter::ScanTagFilter,
     blocks::BlockHeader,
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
     proto,
     proto::base_node::{SyncUtxosByBlockRequest, SyncUtxosByBlockResponse},
+    transactions::transaction_components::TransactionOutput,
 };
 
 const LOG_TARGET: &str = "c::base_node::sync_rpc::sync_utxo_by_block_task";
 
 pub(crate) struct SyncUtxosByBlockTask<B> {
     db: AsyncBlockchainDb<B>,
+    filter: Option<ScanTagFilter>,
+    include_untagged: bool,
 }
 
 impl<B> SyncUtxosByBlockTask<B>
 where B: BlockchainBackend + 'static
 {
     pub(crate) fn new(db: AsyncBlockchainDb<B>) -> Self {
-        Self { db }
+        Self {
+            db,
+            filter: None,
+            include_untagged: true,
+        }
+    }
+
+    /// Only stream the outputs that are candidates for the given scan tag filter
+    pub(crate) fn with_filter(mut self, filter: ScanTagFilter, include_untagged: bool) -> Self {
+        self.filter = Some(filter);
+        self.include_untagged = include_untagged;
+        self
+    }
+
+    fn is_candidate(&self, output: &TransactionOutput) -> bool {
+        self.filter
+            .as_ref()
+            .map_or(true, |filter| filter.is_candidate(output, self.include_untagged))
     }
 
     pub(crate) async fn run(
@@ -129,7 +150,9 @@ where B: BlockchainBackend + 'static
                 .rpc_status_internal_error(LOG_TARGET)?;
             let outputs = outputs_with_statuses
                 .into_iter()
-                .map(|(output, _spent)| output.try_into())
+                .map(|(output, _spent)| output)
+                .filter(|output| self.is_candidate(output))
+                .map(|output| output.try_into())
                 .collect::<Result<Vec<proto::types::TransactionOutput>, String>>()
                 .map_err(|err| RpcStatus::general(&err))?;
 
@@ -69,7 +69,7 @@ pub struct BaseNodeServiceInitializer<T> {
     consensus_manager: ConsensusManager,
     service_request_timeout: Duration,
     randomx_factory: RandomXFactory,
-    base_node_config: BaseNodeStateMachineConfig,
+    compact_block_relay: bool,
 }
 
 impl<T> BaseNodeServiceInitializer<T>
@@ -83,7 +83,6 @@ where T: BlockchainBackend
         consensus_manager: ConsensusManager,
         service_request_timeout: Duration,
         randomx_factory: RandomXFactory,
-        base_node_config: BaseNodeStateMachineConfig,
     ) -> Self {
         Self {
             inbound_message_subscription_factory,
@@ -92,10 +91,16 @@ where T: BlockchainBackend
             consensus_manager,
             service_request_timeout,
             randomx_factory,
-            base_node_config,
+            compact_block_relay: false,
         }
     }
 
+    /// Propagate blocks as compact blocks that identify their transactions by short IDs
+    pub fn with_compact_block_relay(mut self, enabled: bool) -> Self {
+        self.compact_block_relay = enabled;
+        self
+    }
+
     /// Get a stream for inbound Base Node request messages
     fn inbound_request_stream(
         &self,
@@ -184,7 +189,7 @@ where T: BlockchainBackend + 'static
         let mempool = self.mempool.clone();
         let consensus_manager = self.consensus_manager.clone();
         let randomx_factory = self.randomx_factory.clone();
-        let config = self.base_node_config.clone();
+        let compact_block_relay = self.compact_block_relay;
 
         context.spawn_when_ready(move |handles| async move {
             let dht = handles.expect_handle::<Dht>();
@@ -201,7 +206,8 @@ where T: BlockchainBackend + 'static
                 outbound_nci.clone(),
                 connectivity.clone(),
                 randomx_factory,
-            );
+            )
+            .with_compact_block_relay(compact_block_relay);
 
             let streams = BaseNodeStreams {
                 outbound_request_stream,
@@ -218,7 +224,6 @@ where T: BlockchainBackend + 'static
                 service_request_timeout,
                 state_machine,
                 connectivity,
-                config,
             )
             .start(streams);
             futures::pin_mut!(service);
@@ -25,12 +25,16 @@ use std::sync::Arc;
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::{broadcast, watch};
 
-use crate::base_node::state_machine_service::states::{StateEvent, StatusInfo};
+use crate::base_node::{
+    state_machine_service::states::{StateEvent, StatusInfo},
+    sync::SyncBandwidthLimiter,
+};
 
 #[derive(Clone)]
 pub struct StateMachineHandle {
     state_change_event_subscriber: broadcast::Sender<Arc<StateEvent>>,
     status_event_receiver: watch::Receiver<StatusInfo>,
+    bandwidth_limiter: SyncBandwidthLimiter,
     shutdown_signal: ShutdownSignal,
 }
 
@@ -38,11 +42,13 @@ impl StateMachineHandle {
     pub fn new(
         state_change_event_subscriber: broadcast::Sender<Arc<StateEvent>>,
         status_event_receiver: watch::Receiver<StatusInfo>,
+        bandwidth_limiter: SyncBandwidthLimiter,
         shutdown_signal: ShutdownSignal,
     ) -> Self {
         Self {
             state_change_event_subscriber,
             status_event_receiver,
+            bandwidth_limiter,
             shutdown_signal,
         }
     }
@@ -61,6 +67,12 @@ impl StateMachineHandle {
         self.status_event_receiver.clone()
     }
 
+    /// Returns the limiter for block sync bandwidth, which can be used to adjust the limit while the node is running
+    /// and to inspect the data received from each sync peer.
+    pub fn bandwidth_limiter(&self) -> &SyncBandwidthLimiter {
+        &self.bandwidth_limiter
+    }
+
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
     }
@@ -35,7 +35,7 @@ use crate::{
             state_machine::{BaseNodeStateMachine, BaseNodeStateMachineConfig},
             states::StatusInfo,
         },
-        sync::SyncValidators,
+        sync::{SyncBandwidthLimiter, SyncValidators},
         LocalNodeCommsInterface,
     },
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
@@ -85,10 +85,12 @@ where B: BlockchainBackend + 'static
         debug!(target: LOG_TARGET, "Initializing Base Node State Machine Service");
         let (state_event_publisher, _) = broadcast::channel(500);
         let (status_event_sender, status_event_receiver) = watch::channel(StatusInfo::new());
+        let bandwidth_limiter = SyncBandwidthLimiter::new(self.config.blockchain_sync_config.max_block_sync_bandwidth);
 
         let handle = StateMachineHandle::new(
             state_event_publisher.clone(),
             status_event_receiver,
+            bandwidth_limiter.clone(),
             context.get_shutdown_signal(),
         );
         context.register_handle(handle);
@@ -125,7 +127,8 @@ where B: BlockchainBackend + 'static
                 randomx_factory,
                 rules,
                 handles.get_shutdown_signal(),
-            );
+            )
+            .with_bandwidth_limiter(bandwidth_limiter);
 
             node.run().await;
             info!(target: LOG_TARGET, "Base Node State Machine Service has shut down");
@@ -38,7 +38,7 @@ use crate::{
             states,
             states::{BaseNodeState, HeaderSyncState, StateEvent, StateInfo, StatusInfo, SyncStatus},
         },
-        sync::{BlockchainSyncConfig, SyncValidators},
+        sync::{BlockchainSyncConfig, SyncBandwidthLimiter, SyncPeerScorer, SyncValidators},
     },
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
     consensus::ConsensusManager,
@@ -87,6 +87,8 @@ pub struct BaseNodeStateMachine<B: BlockchainBackend> {
     pub(super) local_node_interface: LocalNodeCommsInterface,
     pub(super) connectivity: ConnectivityRequester,
     pub(super) peer_manager: Arc<PeerManager>,
+    pub(super) sync_peer_scorer: SyncPeerScorer,
+    pub(super) bandwidth_limiter: SyncBandwidthLimiter,
     pub(super) metadata_event_stream: broadcast::Receiver<Arc<ChainMetadataEvent>>,
     pub(super) config: BaseNodeStateMachineConfig,
     pub(super) info: StateInfo,
@@ -120,6 +122,8 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             db,
             local_node_interface,
             connectivity,
+            sync_peer_scorer: SyncPeerScorer::new(peer_manager.clone()),
+            bandwidth_limiter: SyncBandwidthLimiter::new(config.blockchain_sync_config.max_block_sync_bandwidth),
             peer_manager,
             metadata_event_stream,
             config,
@@ -134,6 +138,13 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
         }
     }
 
+    /// Share the given block sync bandwidth limiter with the state machine, so that the limit can be adjusted from
+    /// outside of the state machine.
+    pub fn with_bandwidth_limiter(mut self, bandwidth_limiter: SyncBandwidthLimiter) -> Self {
+        self.bandwidth_limiter = bandwidth_limiter;
+        self
+    }
+
     /// Describe the Finite State Machine for the base node. This function describes _every possible_ state
     /// transition for the node given its current state and an event that gets triggered.
     pub fn transition(&self, state: BaseNodeState, event: StateEvent) -> BaseNodeState {
@@ -51,10 +51,13 @@ impl BlockSync {
         &mut self,
         shared: &mut BaseNodeStateMachine<B>,
     ) -> StateEvent {
+        shared.bandwidth_limiter.clear_peer_bandwidth();
         let mut synchronizer = BlockSynchronizer::new(
             shared.config.blockchain_sync_config.clone(),
             shared.db.clone(),
             shared.connectivity.clone(),
+            shared.sync_peer_scorer.clone(),
+            shared.bandwidth_limiter.clone(),
             &mut self.sync_peers,
             shared.sync_validators.block_body.clone(),
         );
@@ -107,11 +107,15 @@ impl HeaderSyncState {
             Err(e) => return StateEvent::FatalError(format!("{}", e)),
         }
 
+        // Attempt header sync with the best scoring peers first
+        shared.sync_peer_scorer.sort_sync_peers(&mut self.sync_peers).await;
+
         let mut synchronizer = HeaderSynchronizer::new(
             shared.config.blockchain_sync_config.clone(),
             shared.db.clone(),
             shared.consensus_rules.clone(),
             shared.connectivity.clone(),
+            shared.sync_peer_scorer.clone(),
             &mut self.sync_peers,
             shared.randomx_factory.clone(),
             &self.local_metadata,
@@ -58,12 +58,13 @@ impl HorizonStateSync {
         };
 
         let sync_peers = &mut self.sync_peers;
-        // Order sync peers according to accumulated difficulty
+        // Order sync peers by their sync score, falling back to accumulated difficulty for peers with equal scores
         sync_peers.sort_by(|a, b| {
             b.claimed_chain_metadata()
                 .accumulated_difficulty()
                 .cmp(&a.claimed_chain_metadata().accumulated_difficulty())
         });
+        shared.sync_peer_scorer.sort_sync_peers(sync_peers).await;
 
         // Target horizon sync height based on the last header we have synced
         let last_header = match shared.db.fetch_last_header().await {
@@ -95,6 +96,7 @@ impl HorizonStateSync {
             config,
             db,
             connectivity,
+            shared.sync_peer_scorer.clone(),
             rules,
             sync_peers,
             target_horizon_sync_height,
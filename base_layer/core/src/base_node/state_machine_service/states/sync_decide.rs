@@ -65,7 +65,7 @@ impl DecideNextSync {
             // Filter sync peers that claim to be able to provide blocks up until our pruned height
             debug!(target: LOG_TARGET, "Local metadata: {}", local_metadata);
             let mut sync_peers = self.sync_peers.clone();
-            let sync_peers = sync_peers
+            let mut sync_peers = sync_peers
                 .drain(..)
                 .filter(|sync_peer| {
                     let remote_metadata = sync_peer.claimed_chain_metadata();
@@ -85,6 +85,7 @@ impl DecideNextSync {
                     "Unable to find any appropriate sync peers for horizon sync, trying for block sync"
                 );
             } else {
+                shared.sync_peer_scorer.sort_sync_peers(&mut sync_peers).await;
                 debug!(
                     target: LOG_TARGET,
                     "Proceeding to horizon sync with {} sync peer(s) with a best latency of {:.2?}",
@@ -98,7 +99,7 @@ impl DecideNextSync {
         // This is not a pruned node or horizon sync is not possible, try for block sync
 
         // Filter sync peers that are able to provide full blocks from our current tip
-        let sync_peers = self
+        let mut sync_peers = self
             .sync_peers
             .drain(..)
             .filter(|sync_peer| {
@@ -111,6 +112,7 @@ impl DecideNextSync {
             warn!(target: LOG_TARGET, "Unable to find any appropriate sync peers for block sync");
             return Continue;
         }
+        shared.sync_peer_scorer.sort_sync_peers(&mut sync_peers).await;
 
         debug!(
             target: LOG_TARGET,
@@ -20,16 +20,18 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::time::Duration;
-
 use log::*;
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, PeerOffense},
+};
 
-use crate::base_node::BlockchainSyncConfig;
+use crate::{base_node::BlockchainSyncConfig, common::BanReason};
 
 const LOG_TARGET: &str = "c::bn::sync";
 
-// Sync peers are banned if there exists a ban reason for the error and the peer is not on the allow list for sync.
+// Offenses are reported for sync peers if there exists a ban reason for the error and the peer is not on the allow list
+// for sync. The peer manager's ban policy decides whether the peer is banned.
 
 pub struct PeerBanManager {
     config: BlockchainSyncConfig,
@@ -41,25 +43,39 @@ impl PeerBanManager {
         Self { config, connectivity }
     }
 
-    pub async fn ban_peer_if_required(&mut self, node_id: &NodeId, ban_reason: String, ban_duration: Duration) {
+    /// Reports the offense for the given ban reason, unless the peer is on the allow list for sync. `misbehaviour` is
+    /// the offense reported for long ban reasons.
+    pub async fn report_offense_if_required(
+        &mut self,
+        node_id: &NodeId,
+        ban_reason: BanReason,
+        misbehaviour: PeerOffense,
+    ) {
         if self.config.forced_sync_peers.contains(node_id) {
             debug!(
                 target: LOG_TARGET,
-                "Not banning peer that is on the allow list for sync. Ban reason = {}", ban_reason
+                "Not reporting offense for peer that is on the allow list for sync. Ban reason = {}", ban_reason.reason
             );
             return;
         }
-        debug!(target: LOG_TARGET, "Sync peer {} removed from the sync peer list because {}", node_id, ban_reason);
+        debug!(
+            target: LOG_TARGET,
+            "Sync peer {} removed from the sync peer list because {}", node_id, ban_reason.reason
+        );
 
+        let offense = ban_reason.offense(misbehaviour);
         match self
             .connectivity
-            .ban_peer_until(node_id.clone(), ban_duration, ban_reason.clone())
+            .report_offense(node_id.clone(), offense, ban_reason.reason.clone())
             .await
         {
             Ok(_) => {
-                warn!(target: LOG_TARGET, "Banned sync peer {} for {:?} because {}", node_id, ban_duration, ban_reason)
+                warn!(
+                    target: LOG_TARGET,
+                    "Reported offense '{}' for sync peer {} because {}", offense, node_id, ban_reason.reason
+                )
             },
-            Err(err) => error!(target: LOG_TARGET, "Failed to ban sync peer {}: {}", node_id, err),
+            Err(err) => error!(target: LOG_TARGET, "Failed to report offense for sync peer {}: {}", node_id, err),
         }
     }
 }
@@ -0,0 +1,210 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tari_comms::peer_manager::NodeId;
+use tokio::{sync::Mutex as AsyncMutex, time};
+
+/// The amount of unused bandwidth that may be used in a single burst after a quiet period
+const MAX_BURST: Duration = Duration::from_secs(1);
+
+/// Limits the rate at which block sync data is consumed from sync peers and keeps per-peer download accounting.
+///
+/// Callers wait in the order that they requested bandwidth, so concurrent downloads from several peers are served
+/// fairly. Data that is not consumed stays in the RPC stream, which applies back pressure to the sending peer. The
+/// limit may be changed at any time and applies to the next request.
+#[derive(Debug, Clone)]
+pub struct SyncBandwidthLimiter {
+    inner: Arc<LimiterInner>,
+}
+
+#[derive(Debug)]
+struct LimiterInner {
+    max_bytes_per_second: AtomicU64,
+    /// The time at which all bandwidth granted so far has been used up at the current limit
+    next_free: AsyncMutex<Option<Instant>>,
+    peers: Mutex<HashMap<NodeId, PeerAccount>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PeerAccount {
+    bytes_received: u64,
+    first_received: Instant,
+    last_received: Instant,
+}
+
+/// The amount of block sync data received from a sync peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncPeerBandwidth {
+    pub node_id: NodeId,
+    pub bytes_received: u64,
+    /// The average download rate from the peer since the first data was received
+    pub bytes_per_second: u64,
+}
+
+impl SyncBandwidthLimiter {
+    /// Creates a limiter that allows `max_bytes_per_second`, where 0 means unlimited.
+    pub fn new(max_bytes_per_second: u64) -> Self {
+        Self {
+            inner: Arc::new(LimiterInner {
+                max_bytes_per_second: AtomicU64::new(max_bytes_per_second),
+                next_free: AsyncMutex::new(None),
+                peers: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Returns the current limit in bytes per second, or None if the bandwidth is not limited.
+    pub fn max_bytes_per_second(&self) -> Option<u64> {
+        Some(self.inner.max_bytes_per_second.load(Ordering::Relaxed)).filter(|limit| *limit > 0)
+    }
+
+    /// Sets the limit in bytes per second, where 0 removes the limit. Returns the previous limit.
+    pub fn set_max_bytes_per_second(&self, max_bytes_per_second: u64) -> Option<u64> {
+        Some(
+            self.inner
+                .max_bytes_per_second
+                .swap(max_bytes_per_second, Ordering::Relaxed),
+        )
+        .filter(|limit| *limit > 0)
+    }
+
+    /// Accounts for `num_bytes` received from the peer and waits until the bandwidth limit allows them to be consumed.
+    pub async fn consume(&self, node_id: &NodeId, num_bytes: u64) {
+        self.record_received(node_id, num_bytes);
+
+        // Holding the lock while waiting queues the other callers behind this one
+        let mut next_free = self.inner.next_free.lock().await;
+        let now = Instant::now();
+        let max_bytes_per_second = match self.max_bytes_per_second() {
+            Some(limit) => limit,
+            None => {
+                *next_free = None;
+                return;
+            },
+        };
+
+        let burst_start = now.checked_sub(MAX_BURST).unwrap_or(now);
+        let start = next_free.filter(|t| *t > burst_start).unwrap_or(burst_start);
+        let free_at = start + transfer_time(num_bytes, max_bytes_per_second);
+        *next_free = Some(free_at);
+        if free_at > now {
+            time::sleep_until(free_at.into()).await;
+        }
+    }
+
+    /// Returns the data received from each peer since the accounting was last cleared, most data first.
+    pub fn peer_bandwidth(&self) -> Vec<SyncPeerBandwidth> {
+        let peers = self.inner.peers.lock().expect("bandwidth accounting lock poisoned");
+        let mut bandwidth = peers
+            .iter()
+            .map(|(node_id, account)| {
+                let elapsed = account.last_received.duration_since(account.first_received);
+                let millis = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+                SyncPeerBandwidth {
+                    node_id: node_id.clone(),
+                    bytes_received: account.bytes_received,
+                    bytes_per_second: account.bytes_received.saturating_mul(1000) / millis.max(1),
+                }
+            })
+            .collect::<Vec<_>>();
+        bandwidth.sort_by(|a, b| b.bytes_received.cmp(&a.bytes_received));
+        bandwidth
+    }
+
+    /// Clears the per-peer accounting, typically at the start of a new sync.
+    pub fn clear_peer_bandwidth(&self) {
+        self.inner
+            .peers
+            .lock()
+            .expect("bandwidth accounting lock poisoned")
+            .clear();
+    }
+
+    fn record_received(&self, node_id: &NodeId, num_bytes: u64) {
+        let now = Instant::now();
+        let mut peers = self.inner.peers.lock().expect("bandwidth accounting lock poisoned");
+        let account = peers.entry(node_id.clone()).or_insert(PeerAccount {
+            bytes_received: 0,
+            first_received: now,
+            last_received: now,
+        });
+        account.bytes_received = account.bytes_received.saturating_add(num_bytes);
+        account.last_received = now;
+    }
+}
+
+impl Default for SyncBandwidthLimiter {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+fn transfer_time(num_bytes: u64, bytes_per_second: u64) -> Duration {
+    let nanos = u128::from(num_bytes) * 1_000_000_000 / u128::from(bytes_per_second.max(1));
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_calculates_transfer_time() {
+        assert_eq!(transfer_time(1_000, 1_000), Duration::from_secs(1));
+        assert_eq!(transfer_time(500, 1_000), Duration::from_millis(500));
+        assert_eq!(transfer_time(0, 1_000), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn it_limits_the_consumption_rate() {
+        let limiter = SyncBandwidthLimiter::new(100_000);
+        let node_id = NodeId::default();
+        let start = Instant::now();
+        // The first second of data is allowed as a burst
+        limiter.consume(&node_id, 100_000).await;
+        limiter.consume(&node_id, 20_000).await;
+        assert!(start.elapsed() >= Duration::from_millis(150));
+
+        assert_eq!(limiter.set_max_bytes_per_second(0), Some(100_000));
+        assert_eq!(limiter.max_bytes_per_second(), None);
+        let start = Instant::now();
+        limiter.consume(&node_id, 1_000_000).await;
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        let bandwidth = limiter.peer_bandwidth();
+        assert_eq!(bandwidth.len(), 1);
+        assert_eq!(bandwidth[0].bytes_received, 1_120_000);
+        limiter.clear_peer_bandwidth();
+        assert!(limiter.peer_bandwidth().is_empty());
+    }
+}
@@ -28,19 +28,25 @@ use std::{
 
 use futures::StreamExt;
 use log::*;
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, protocol::rpc::RpcClient, PeerConnection};
+use prost::Message;
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, PeerOffense},
+    protocol::rpc::RpcClient,
+    PeerConnection,
+};
 use tari_utilities::hex::Hex;
 use tokio::task;
 
 use super::error::BlockSyncError;
 use crate::{
     base_node::{
-        sync::{ban::PeerBanManager, hooks::Hooks, rpc, SyncPeer},
+        sync::{ban::PeerBanManager, hooks::Hooks, rpc, SyncBandwidthLimiter, SyncPeer, SyncPeerScorer},
         BlockchainSyncConfig,
     },
     blocks::{Block, ChainBlock},
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
-    common::{rolling_avg::RollingAverageTime, BanPeriod},
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError},
+    common::rolling_avg::RollingAverageTime,
     proto::base_node::SyncBlocksRequest,
     transactions::aggregated_body::AggregateBody,
     validation::{BlockBodyValidator, ValidationError},
@@ -50,6 +56,9 @@ const LOG_TARGET: &str = "c::bn::block_sync";
 
 const MAX_LATENCY_INCREASES: usize = 5;
 
+/// The maximum number of blocks whose internal consistency is validated together
+const BLOCK_VALIDATION_BATCH_SIZE: usize = 20;
+
 pub struct BlockSynchronizer<'a, B> {
     config: BlockchainSyncConfig,
     db: AsyncBlockchainDb<B>,
@@ -58,6 +67,8 @@ pub struct BlockSynchronizer<'a, B> {
     block_validator: Arc<dyn BlockBodyValidator<B>>,
     hooks: Hooks,
     peer_ban_manager: PeerBanManager,
+    peer_scorer: SyncPeerScorer,
+    bandwidth_limiter: SyncBandwidthLimiter,
 }
 
 impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
@@ -65,6 +76,8 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
         config: BlockchainSyncConfig,
         db: AsyncBlockchainDb<B>,
         connectivity: ConnectivityRequester,
+        peer_scorer: SyncPeerScorer,
+        bandwidth_limiter: SyncBandwidthLimiter,
         sync_peers: &'a mut Vec<SyncPeer>,
         block_validator: Arc<dyn BlockBodyValidator<B>>,
     ) -> Self {
@@ -77,6 +90,8 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
             block_validator,
             hooks: Default::default(),
             peer_ban_manager,
+            peer_scorer,
+            bandwidth_limiter,
         }
     }
 
@@ -153,6 +168,7 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
                         target: LOG_TARGET,
                         "Failed to connect to sync peer `{}`: {}", node_id, e
                     );
+                    self.peer_scorer.record_failure(&node_id).await;
                     self.remove_sync_peer(&node_id);
                     continue;
                 },
@@ -170,6 +186,7 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
                         target: LOG_TARGET,
                         "Failed to obtain RPC connection from sync peer `{}`: {}", node_id, e
                     );
+                    self.peer_scorer.record_failure(&node_id).await;
                     self.remove_sync_peer(&node_id);
                     continue;
                 },
@@ -183,19 +200,23 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
                 target: LOG_TARGET,
                 "Attempting to synchronize blocks with `{}` latency: {:.2?}", node_id, latency
             );
-            match self.synchronize_blocks(sync_peer, client, max_latency).await {
-                Ok(_) => return Ok(()),
+            match self.synchronize_blocks(sync_peer.clone(), client, max_latency).await {
+                Ok(_) => {
+                    self.peer_scorer.record_success(&sync_peer).await;
+                    return Ok(());
+                },
                 Err(err) => {
                     warn!(target: LOG_TARGET, "{}", err);
+                    if let BlockSyncError::PeerDidNotSupplyAllClaimedBlocks(_) = err {
+                        self.peer_scorer.record_inaccurate_difficulty_claim(&node_id).await;
+                    } else {
+                        self.peer_scorer.record_failure(&node_id).await;
+                    }
                     let ban_reason = BlockSyncError::get_ban_reason(&err);
                     if let Some(reason) = ban_reason {
-                        let duration = match reason.ban_duration {
-                            BanPeriod::Short => self.config.short_ban_period,
-                            BanPeriod::Long => self.config.ban_period,
-                        };
                         warn!(target: LOG_TARGET, "{}", err);
                         self.peer_ban_manager
-                            .ban_peer_if_required(&node_id, reason.reason, duration)
+                            .report_offense_if_required(&node_id, reason, PeerOffense::InvalidBlock)
                             .await;
                     }
                     if let BlockSyncError::MaxLatencyExceeded { .. } = err {
@@ -262,158 +283,204 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
             end_hash: tip_hash.to_vec(),
         };
 
+        let sync_timer = Instant::now();
+        let mut bytes_received = 0u64;
         let mut block_stream = client.sync_blocks(request).await?;
         let mut prev_hash = best_full_block_hash;
         let mut current_block = None;
         let mut last_sync_timer = Instant::now();
         let mut avg_latency = RollingAverageTime::new(20);
-        while let Some(block_result) = block_stream.next().await {
-            let latency = last_sync_timer.elapsed();
-            avg_latency.add_sample(latency);
-            let block_body_response = block_result?;
-
-            let header = self
-                .db
-                .fetch_chain_header_by_block_hash(block_body_response.hash.clone().try_into()?)
-                .await?
-                .ok_or_else(|| {
-                    BlockSyncError::UnknownHeaderHash(format!(
-                        "Peer sent hash ({}) for block header we do not have",
-                        block_body_response.hash.to_hex()
-                    ))
-                })?;
-
-            let current_height = header.height();
-            let header_hash = *header.hash();
-            let timestamp = header.timestamp();
-
-            if header.header().prev_hash != prev_hash {
-                return Err(BlockSyncError::BlockWithoutParent {
-                    expected: prev_hash.to_hex(),
-                    got: header.header().prev_hash.to_hex(),
-                });
+        let mut stream_ended = false;
+        while !stream_ended {
+            // Receive a batch of blocks so that their internal consistency can be validated concurrently
+            let mut blocks = Vec::with_capacity(BLOCK_VALIDATION_BATCH_SIZE);
+            let mut block_data = Vec::with_capacity(BLOCK_VALIDATION_BATCH_SIZE);
+            while blocks.len() < BLOCK_VALIDATION_BATCH_SIZE {
+                let block_result = match block_stream.next().await {
+                    Some(block_result) => block_result,
+                    None => {
+                        stream_ended = true;
+                        break;
+                    },
+                };
+                let latency = last_sync_timer.elapsed();
+                avg_latency.add_sample(latency);
+                let block_body_response = block_result?;
+                let block_size = block_body_response.encoded_len() as u64;
+                bytes_received = bytes_received.saturating_add(block_size);
+                // Waiting for bandwidth happens after the latency sample is taken so that throttling is not mistaken
+                // for a slow peer
+                self.bandwidth_limiter.consume(sync_peer.node_id(), block_size).await;
+
+                let header = self
+                    .db
+                    .fetch_chain_header_by_block_hash(block_body_response.hash.clone().try_into()?)
+                    .await?
+                    .ok_or_else(|| {
+                        BlockSyncError::UnknownHeaderHash(format!(
+                            "Peer sent hash ({}) for block header we do not have",
+                            block_body_response.hash.to_hex()
+                        ))
+                    })?;
+
+                if header.header().prev_hash != prev_hash {
+                    return Err(BlockSyncError::BlockWithoutParent {
+                        expected: prev_hash.to_hex(),
+                        got: header.header().prev_hash.to_hex(),
+                    });
+                }
+                prev_hash = *header.hash();
+
+                let body = block_body_response
+                    .body
+                    .map(AggregateBody::try_from)
+                    .ok_or_else(|| BlockSyncError::InvalidBlockBody("Peer sent empty block".to_string()))?
+                    .map_err(BlockSyncError::InvalidBlockBody)?;
+
+                debug!(
+                    target: LOG_TARGET,
+                    "Received block body #{} (PoW = {}, {}, latency: {:.2?})",
+                    header.height(),
+                    header.header().pow_algo(),
+                    body.to_counts_string(),
+                    latency
+                );
+
+                let (header, header_accum_data) = header.into_parts();
+                blocks.push(Block::new(header, body));
+                block_data.push((header_accum_data, latency));
+                last_sync_timer = Instant::now();
+            }
+            if blocks.is_empty() {
+                break;
             }
-
-            prev_hash = header_hash;
-
-            let body = block_body_response
-                .body
-                .map(AggregateBody::try_from)
-                .ok_or_else(|| BlockSyncError::InvalidBlockBody("Peer sent empty block".to_string()))?
-                .map_err(BlockSyncError::InvalidBlockBody)?;
-
-            debug!(
-                target: LOG_TARGET,
-                "Validating block body #{} (PoW = {}, {}, latency: {:.2?})",
-                current_height,
-                header.header().pow_algo(),
-                body.to_counts_string(),
-                latency
-            );
 
             let timer = Instant::now();
-            let (header, header_accum_data) = header.into_parts();
-            let block = Block::new(header, body);
-
-            // Validate the block inside a tokio task
-            let task_block = block.clone();
-            let db = self.db.inner().clone();
-            let validator = self.block_validator.clone();
-            let res = task::spawn_blocking(move || {
-                let txn = db.db_read_access()?;
-                validator.validate_body(&*txn, &task_block)
-            })
-            .await?;
-
-            let block = match res {
-                Ok(block) => block,
-                Err(err @ ValidationError::BadBlockFound { .. }) | Err(err @ ValidationError::FatalStorageError(_)) => {
-                    return Err(err.into());
-                },
-                Err(err) => {
-                    // Add to bad blocks
-                    if let Err(err) = self
-                        .db
-                        .write_transaction()
-                        .delete_orphan(header_hash)
-                        .insert_bad_block(header_hash, current_height, err.to_string())
-                        .commit()
-                        .await
-                    {
-                        error!(target: LOG_TARGET, "Failed to insert bad block: {}", err);
-                    }
-                    return Err(err.into());
-                },
-            };
-
-            let block = ChainBlock::try_construct(Arc::new(block), header_accum_data)
-                .map(Arc::new)
-                .ok_or(BlockSyncError::FailedToConstructChainBlock)?;
-
+            let (blocks, results) = self.validate_internal_consistency(blocks).await?;
             debug!(
                 target: LOG_TARGET,
-                "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
-                timer.elapsed(),
-                block.header().height,
-                block.header().pow_algo(),
-                block.block().body.to_counts_string(),
-            );
-            trace!(
-                target: LOG_TARGET,
-                "{}",block
+                "Validated the internal consistency of {} block(s) in {:.0?}",
+                blocks.len(),
+                timer.elapsed()
             );
 
-            let timer = Instant::now();
-            self.db
-                .write_transaction()
-                .delete_orphan(header_hash)
-                .insert_tip_block_body(block.clone())
-                .set_best_block(
+            for ((block, (header_accum_data, latency)), internal_result) in
+                blocks.into_iter().zip(block_data).zip(results)
+            {
+                let timer = Instant::now();
+                let current_height = block.header.height;
+                let header_hash = block.hash();
+                let timestamp = block.header.timestamp.as_u64();
+
+                // Validate the rest of the block against the chain inside a tokio task
+                let db = self.db.inner().clone();
+                let validator = self.block_validator.clone();
+                let res = match internal_result {
+                    Ok(()) => {
+                        task::spawn_blocking(move || {
+                            let txn = db.db_read_access()?;
+                            validator.validate_chain_linked_body(&*txn, &block)
+                        })
+                        .await?
+                    },
+                    Err(err) => Err(err),
+                };
+
+                let block = match res {
+                    Ok(block) => block,
+                    Err(err @ ValidationError::BadBlockFound { .. }) |
+                    Err(err @ ValidationError::FatalStorageError(_)) => {
+                        return Err(err.into());
+                    },
+                    Err(err) => {
+                        // Add to bad blocks
+                        if let Err(err) = self
+                            .db
+                            .write_transaction()
+                            .delete_orphan(header_hash)
+                            .insert_bad_block(header_hash, current_height, err.to_string())
+                            .commit()
+                            .await
+                        {
+                            error!(target: LOG_TARGET, "Failed to insert bad block: {}", err);
+                        }
+                        return Err(err.into());
+                    },
+                };
+
+                let block = ChainBlock::try_construct(Arc::new(block), header_accum_data)
+                    .map(Arc::new)
+                    .ok_or(BlockSyncError::FailedToConstructChainBlock)?;
+
+                debug!(
+                    target: LOG_TARGET,
+                    "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
+                    timer.elapsed(),
+                    block.header().height,
+                    block.header().pow_algo(),
+                    block.block().body.to_counts_string(),
+                );
+                trace!(
+                    target: LOG_TARGET,
+                    "{}",block
+                );
+
+                let timer = Instant::now();
+                self.db
+                    .write_transaction()
+                    .delete_orphan(header_hash)
+                    .insert_tip_block_body(block.clone())
+                    .set_best_block(
+                        block.height(),
+                        header_hash,
+                        block.accumulated_data().total_accumulated_difficulty,
+                        block.header().prev_hash,
+                        timestamp,
+                    )
+                    .commit()
+                    .await?;
+
+                // Average time between receiving blocks from the peer - used to detect a slow sync peer
+                let last_avg_latency = avg_latency.calculate_average_with_min_samples(5);
+                if let Some(latency) = last_avg_latency {
+                    sync_peer.set_latency(latency);
+                }
+                // Includes time to add block to database, used to show blocks/s on status line
+                sync_peer.add_sample(latency.saturating_add(timer.elapsed()));
+                self.hooks
+                    .call_on_progress_block_hooks(block.clone(), tip_height, &sync_peer);
+
+                debug!(
+                    target: LOG_TARGET,
+                    "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}, latency: {:.2?}",
                     block.height(),
-                    header_hash,
-                    block.accumulated_data().total_accumulated_difficulty,
-                    block.header().prev_hash,
-                    timestamp,
-                )
-                .commit()
-                .await?;
-
-            // Average time between receiving blocks from the peer - used to detect a slow sync peer
-            let last_avg_latency = avg_latency.calculate_average_with_min_samples(5);
-            if let Some(latency) = last_avg_latency {
-                sync_peer.set_latency(latency);
-            }
-            // Includes time to add block to database, used to show blocks/s on status line
-            sync_peer.add_sample(last_sync_timer.elapsed());
-            self.hooks
-                .call_on_progress_block_hooks(block.clone(), tip_height, &sync_peer);
-
-            debug!(
-                target: LOG_TARGET,
-                "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}, latency: {:.2?}",
-                block.height(),
-                timer.elapsed(),
-                block
-                    .accumulated_data()
-                    .total_accumulated_difficulty,
-                block.accumulated_data().accumulated_randomx_difficulty,
-                block.accumulated_data().accumulated_sha3x_difficulty,
-                latency
-            );
-            if let Some(avg_latency) = last_avg_latency {
-                if avg_latency > max_latency {
-                    return Err(BlockSyncError::MaxLatencyExceeded {
-                        peer: sync_peer.node_id().clone(),
-                        latency: avg_latency,
-                        max_latency,
-                    });
+                    timer.elapsed(),
+                    block
+                        .accumulated_data()
+                        .total_accumulated_difficulty,
+                    block.accumulated_data().accumulated_randomx_difficulty,
+                    block.accumulated_data().accumulated_sha3x_difficulty,
+                    latency
+                );
+                if let Some(avg_latency) = last_avg_latency {
+                    if avg_latency > max_latency {
+                        return Err(BlockSyncError::MaxLatencyExceeded {
+                            peer: sync_peer.node_id().clone(),
+                            latency: avg_latency,
+                            max_latency,
+                        });
+                    }
                 }
-            }
 
-            current_block = Some(block);
+                current_block = Some(block);
+            }
             last_sync_timer = Instant::now();
         }
 
+        self.peer_scorer
+            .record_bandwidth(sync_peer.node_id(), bytes_received, sync_timer.elapsed())
+            .await;
+
         let accumulated_difficulty = self.db.get_chain_metadata().await?.accumulated_difficulty();
         if accumulated_difficulty < sync_peer.claimed_chain_metadata().accumulated_difficulty() {
             return Err(BlockSyncError::PeerDidNotSupplyAllClaimedBlocks(format!(
@@ -432,6 +499,23 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
         Ok(())
     }
 
+    /// Validates the internal consistency of a batch of consecutive blocks concurrently, returning the blocks along
+    /// with a result for each of them in the same order
+    async fn validate_internal_consistency(
+        &self,
+        blocks: Vec<Block>,
+    ) -> Result<(Vec<Block>, Vec<Result<(), ValidationError>>), BlockSyncError> {
+        let db = self.db.inner().clone();
+        let validator = self.block_validator.clone();
+        let res = task::spawn_blocking(move || {
+            let txn = db.db_read_access()?;
+            let results = validator.validate_internal_consistency_batch(&*txn, &blocks);
+            Ok::<_, ChainStorageError>((blocks, results))
+        })
+        .await??;
+        Ok(res)
+    }
+
     // Sync peers are also removed from the list of sync peers if the ban duration is longer than the short ban period.
     fn remove_sync_peer(&mut self, node_id: &NodeId) {
         if let Some(pos) = self.sync_peers.iter().position(|p| p.node_id() == node_id) {
@@ -26,6 +26,8 @@ use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
 use tari_comms::peer_manager::NodeId;
 
+use crate::{chain_storage::WriteBatchConfig, validation::header::HeaderCheckpoint};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct BlockchainSyncConfig {
@@ -37,12 +39,6 @@ pub struct BlockchainSyncConfig {
     /// If all sync peers exceed latency, increase allowed latency by this value
     #[serde(with = "serializers::seconds")]
     pub max_latency_increase: Duration,
-    /// Longer ban period for potentially malicious infractions (protocol violations etc.)
-    #[serde(with = "serializers::seconds")]
-    pub ban_period: Duration,
-    /// Short ban period for infractions that are likely not malicious (slow to respond, spotty connections etc)
-    #[serde(with = "serializers::seconds")]
-    pub short_ban_period: Duration,
     /// An allowlist of sync peers from which to sync. No other peers will be selected for sync. If empty, sync peers
     /// are chosen based on their advertised chain metadata.
     pub forced_sync_peers: Vec<NodeId>,
@@ -51,6 +47,20 @@ pub struct BlockchainSyncConfig {
     /// The RPC deadline to set on sync clients. If this deadline is reached, a new sync peer will be selected for
     /// sync.
     pub rpc_deadline: Duration,
+    /// Trusted header hashes at fixed heights. Headers below a checkpoint skip proof of work validation during header
+    /// sync and are only stored once the header at the checkpoint height is received with the trusted hash.
+    #[serde(default)]
+    pub header_checkpoints: Vec<HeaderCheckpoint>,
+    /// Flush thresholds for writing synced headers to the database. Headers are grouped into large database
+    /// transactions, which is much faster than committing each header on its own.
+    pub header_write_batch: WriteBatchConfig,
+    /// The maximum number of peers that headers are downloaded from concurrently during header sync. Disjoint header
+    /// ranges are requested from each peer and validated in order. A value of 1 disables parallel header sync.
+    pub max_parallel_header_sync_peers: usize,
+    /// The number of headers requested from a peer in a single range when syncing headers in parallel
+    pub parallel_header_sync_range_size: u64,
+    /// The maximum rate in bytes per second at which block bodies are downloaded during block sync. 0 is unlimited.
+    pub max_block_sync_bandwidth: u64,
 }
 
 impl Default for BlockchainSyncConfig {
@@ -58,11 +68,14 @@ impl Default for BlockchainSyncConfig {
         Self {
             initial_max_sync_latency: Duration::from_secs(15),
             max_latency_increase: Duration::from_secs(2),
-            ban_period: Duration::from_secs(60 * 60 * 2), // 2 hours
-            short_ban_period: Duration::from_secs(240),   // 4 mins
             forced_sync_peers: Default::default(),
             validation_concurrency: 6,
             rpc_deadline: Duration::from_secs(15),
+            header_checkpoints: Vec::new(),
+            header_write_batch: WriteBatchConfig::default(),
+            max_parallel_header_sync_peers: 4,
+            parallel_header_sync_range_size: 1_000,
+            max_block_sync_bandwidth: 0,
         }
     }
 }
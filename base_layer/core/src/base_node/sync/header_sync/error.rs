@@ -97,6 +97,15 @@ pub enum BlockHeaderSyncError {
     },
     #[error("All sync peers exceeded max allowed latency")]
     AllSyncPeersExceedLatency,
+    #[error("Peer {peer} sent headers that diverge from the sync chain at height {height}")]
+    PeerSentDivergentChain { peer: NodeId, height: u64 },
+    #[error("Peer claimed a chain tip at height {claimed_height}, below the last checkpoint at {checkpoint_height}")]
+    PeerBelowLastCheckpoint {
+        claimed_height: u64,
+        checkpoint_height: u64,
+    },
+    #[error("Peer stopped sending headers at height {height} before reaching the checkpoint at {checkpoint_height}")]
+    CheckpointNotReached { height: u64, checkpoint_height: u64 },
 }
 
 impl BlockHeaderSyncError {
@@ -109,6 +118,7 @@ impl BlockHeaderSyncError {
             BlockHeaderSyncError::AllSyncPeersExceedLatency |
             BlockHeaderSyncError::ConnectivityError(_) |
             BlockHeaderSyncError::NotInSync |
+            BlockHeaderSyncError::PeerBelowLastCheckpoint { .. } |
             BlockHeaderSyncError::PeerNotFound => None,
             BlockHeaderSyncError::ChainStorageError(e) => e.get_ban_reason(),
 
@@ -130,7 +140,9 @@ impl BlockHeaderSyncError {
             err @ BlockHeaderSyncError::ChainLinkBroken { .. } |
             err @ BlockHeaderSyncError::BlockError(_) |
             err @ BlockHeaderSyncError::PeerSentInaccurateChainMetadata { .. } |
-            err @ BlockHeaderSyncError::PeerSentTooManyHeaders(_) => Some(BanReason {
+            err @ BlockHeaderSyncError::PeerSentTooManyHeaders(_) |
+            err @ BlockHeaderSyncError::PeerSentDivergentChain { .. } |
+            err @ BlockHeaderSyncError::CheckpointNotReached { .. } => Some(BanReason {
                 reason: format!("{}", err),
                 ban_duration: BanPeriod::Long,
             }),
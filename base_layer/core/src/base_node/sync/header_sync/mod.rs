@@ -25,5 +25,7 @@ pub use error::BlockHeaderSyncError;
 
 mod validator;
 
+mod parallel;
+
 mod synchronizer;
 pub use synchronizer::{AttemptSyncResult, HeaderSyncStatus, HeaderSynchronizer};
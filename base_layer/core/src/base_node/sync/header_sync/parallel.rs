@@ -0,0 +1,156 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::VecDeque, convert::TryFrom};
+
+use futures::StreamExt;
+use tari_common_types::types::HashOutput;
+use tari_comms::peer_manager::NodeId;
+use tari_utilities::hex::Hex;
+
+use super::BlockHeaderSyncError;
+use crate::{base_node::sync::rpc, blocks::BlockHeader, proto::base_node::SyncHeadersRequest};
+
+/// An inclusive range of header heights that is downloaded from a single peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct HeaderRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl HeaderRange {
+    pub fn num_headers(&self) -> u64 {
+        self.end.saturating_sub(self.start).saturating_add(1)
+    }
+}
+
+/// Splits the heights `start..=end` into consecutive ranges of at most `range_size` headers.
+pub(super) fn split_header_ranges(start: u64, end: u64, range_size: u64) -> VecDeque<HeaderRange> {
+    let range_size = range_size.max(1);
+    let mut ranges = VecDeque::new();
+    let mut range_start = start;
+    while range_start <= end {
+        let range_end = range_start.saturating_add(range_size - 1).min(end);
+        ranges.push_back(HeaderRange {
+            start: range_start,
+            end: range_end,
+        });
+        if range_end == u64::MAX {
+            break;
+        }
+        range_start = range_end + 1;
+    }
+    ranges
+}
+
+/// A peer that downloads header ranges during a parallel header sync.
+pub(super) struct RangePeer {
+    pub node_id: NodeId,
+    pub client: rpc::BaseNodeSyncRpcClient,
+    /// True if this is the peer the sync is being performed against
+    pub is_primary: bool,
+}
+
+/// The outcome of downloading a header range from a peer.
+pub(super) struct RangeDownload {
+    pub peer: RangePeer,
+    pub range: HeaderRange,
+    pub result: Result<Vec<BlockHeader>, BlockHeaderSyncError>,
+}
+
+/// Downloads the headers in `range` from the peer, starting after the header with hash `anchor_hash` at height
+/// `range.start - 1`. The returned headers are checked to be the requested heights and to link to the anchor, but are
+/// not otherwise validated.
+pub(super) async fn download_header_range(
+    mut peer: RangePeer,
+    range: HeaderRange,
+    anchor_hash: HashOutput,
+) -> RangeDownload {
+    let result = fetch_headers(&mut peer.client, range, anchor_hash).await;
+    RangeDownload { peer, range, result }
+}
+
+async fn fetch_headers(
+    client: &mut rpc::BaseNodeSyncRpcClient,
+    range: HeaderRange,
+    anchor_hash: HashOutput,
+) -> Result<Vec<BlockHeader>, BlockHeaderSyncError> {
+    let request = SyncHeadersRequest {
+        start_hash: anchor_hash.to_vec(),
+        count: range.num_headers(),
+    };
+    let mut stream = client.sync_headers(request).await?;
+    let mut headers = Vec::new();
+    let mut prev_hash = anchor_hash;
+    while let Some(header) = stream.next().await {
+        let header = BlockHeader::try_from(header?).map_err(BlockHeaderSyncError::ReceivedInvalidHeader)?;
+        let expected = range.start.saturating_add(headers.len() as u64);
+        if header.height != expected || header.height > range.end {
+            return Err(BlockHeaderSyncError::InvalidBlockHeight {
+                expected,
+                actual: header.height,
+            });
+        }
+        if header.prev_hash != prev_hash {
+            return Err(BlockHeaderSyncError::ChainLinkBroken {
+                height: header.height,
+                actual: header.prev_hash.to_hex(),
+                expected: prev_hash.to_hex(),
+            });
+        }
+        prev_hash = header.hash();
+        headers.push(header);
+    }
+
+    if (headers.len() as u64) < range.num_headers() {
+        return Err(BlockHeaderSyncError::InvalidProtocolResponse(format!(
+            "Peer sent {} header(s) for range {}-{} but {} were requested",
+            headers.len(),
+            range.start,
+            range.end,
+            range.num_headers()
+        )));
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_splits_heights_into_ranges() {
+        let ranges = split_header_ranges(11, 35, 10);
+        assert_eq!(ranges, vec![
+            HeaderRange { start: 11, end: 20 },
+            HeaderRange { start: 21, end: 30 },
+            HeaderRange { start: 31, end: 35 },
+        ]);
+        assert_eq!(ranges.iter().map(HeaderRange::num_headers).sum::<u64>(), 25);
+    }
+
+    #[test]
+    fn it_returns_no_ranges_for_an_empty_height_range() {
+        assert!(split_header_ranges(10, 9, 10).is_empty());
+        assert_eq!(split_header_ranges(5, 5, 0), vec![HeaderRange { start: 5, end: 5 }]);
+    }
+}
@@ -20,24 +20,29 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use std::{
+    collections::{BTreeMap, HashSet},
     convert::TryFrom,
     sync::Arc,
     time::{Duration, Instant},
 };
 
-use futures::StreamExt;
+use futures::{stream::FuturesUnordered, StreamExt};
 use log::*;
 use primitive_types::U256;
 use tari_common_types::{chain_metadata::ChainMetadata, types::HashOutput};
 use tari_comms::{
     connectivity::ConnectivityRequester,
-    peer_manager::NodeId,
+    peer_manager::{NodeId, PeerOffense},
     protocol::rpc::{RpcClient, RpcError},
     PeerConnection,
 };
 use tari_utilities::hex::Hex;
 
-use super::{validator::BlockHeaderSyncValidator, BlockHeaderSyncError};
+use super::{
+    parallel::{self, HeaderRange, RangeDownload, RangePeer},
+    validator::BlockHeaderSyncValidator,
+    BlockHeaderSyncError,
+};
 use crate::{
     base_node::sync::{
         ban::PeerBanManager,
@@ -46,16 +51,24 @@ use crate::{
         rpc,
         BlockchainSyncConfig,
         SyncPeer,
+        SyncPeerScorer,
     },
     blocks::{BlockHeader, ChainBlock, ChainHeader},
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError},
-    common::{rolling_avg::RollingAverageTime, BanPeriod},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        BatchedDbWriter,
+        BlockchainBackend,
+        ChainStorageError,
+        DbTransaction,
+    },
+    common::rolling_avg::RollingAverageTime,
     consensus::ConsensusManager,
-    proof_of_work::randomx_factory::RandomXFactory,
+    proof_of_work::{monero_rx::RandomXPreWarmer, randomx_factory::RandomXFactory},
     proto::{
         base_node::{FindChainSplitRequest, SyncHeadersRequest},
         core::BlockHeader as ProtoBlockHeader,
     },
+    validation::header::HeaderCheckpoints,
 };
 
 const LOG_TARGET: &str = "c::bn::header_sync";
@@ -66,11 +79,13 @@ pub struct HeaderSynchronizer<'a, B> {
     config: BlockchainSyncConfig,
     db: AsyncBlockchainDb<B>,
     header_validator: BlockHeaderSyncValidator<B>,
+    randomx_pre_warmer: RandomXPreWarmer,
     connectivity: ConnectivityRequester,
     sync_peers: &'a mut Vec<SyncPeer>,
     hooks: Hooks,
     local_cached_metadata: &'a ChainMetadata,
     peer_ban_manager: PeerBanManager,
+    peer_scorer: SyncPeerScorer,
 }
 
 impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
@@ -79,20 +94,25 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         db: AsyncBlockchainDb<B>,
         consensus_rules: ConsensusManager,
         connectivity: ConnectivityRequester,
+        peer_scorer: SyncPeerScorer,
         sync_peers: &'a mut Vec<SyncPeer>,
         randomx_factory: RandomXFactory,
         local_metadata: &'a ChainMetadata,
     ) -> Self {
         let peer_ban_manager = PeerBanManager::new(config.clone(), connectivity.clone());
+        let checkpoints = HeaderCheckpoints::new(config.header_checkpoints.iter().copied());
         Self {
+            randomx_pre_warmer: RandomXPreWarmer::spawn(randomx_factory.clone()),
+            header_validator: BlockHeaderSyncValidator::new(db.clone(), consensus_rules, randomx_factory)
+                .with_checkpoints(checkpoints),
             config,
-            header_validator: BlockHeaderSyncValidator::new(db.clone(), consensus_rules, randomx_factory),
             db,
             connectivity,
             sync_peers,
             hooks: Default::default(),
             local_cached_metadata: local_metadata,
             peer_ban_manager,
+            peer_scorer,
         }
     }
 
@@ -154,17 +174,21 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         let mut latency_counter = 0usize;
         for node_id in sync_peer_node_ids {
             match self.connect_and_attempt_sync(&node_id, max_latency).await {
-                Ok((peer, sync_result)) => return Ok((peer, sync_result)),
+                Ok((peer, sync_result)) => {
+                    self.peer_scorer.record_success(&peer).await;
+                    return Ok((peer, sync_result));
+                },
                 Err(err) => {
+                    if let BlockHeaderSyncError::PeerSentInaccurateChainMetadata { .. } = err {
+                        self.peer_scorer.record_inaccurate_difficulty_claim(&node_id).await;
+                    } else {
+                        self.peer_scorer.record_failure(&node_id).await;
+                    }
                     let ban_reason = BlockHeaderSyncError::get_ban_reason(&err);
                     if let Some(reason) = ban_reason {
                         warn!(target: LOG_TARGET, "{}", err);
-                        let duration = match reason.ban_duration {
-                            BanPeriod::Short => self.config.short_ban_period,
-                            BanPeriod::Long => self.config.ban_period,
-                        };
                         self.peer_ban_manager
-                            .ban_peer_if_required(&node_id, reason.reason, duration)
+                            .report_offense_if_required(&node_id, reason, PeerOffense::InvalidBlock)
                             .await;
                     }
                     if let BlockHeaderSyncError::MaxLatencyExceeded { .. } = err {
@@ -254,6 +278,17 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             latency.unwrap_or_default().as_millis()
         );
 
+        // Headers below the last checkpoint are only trusted once the checkpoint is reached, which this peer cannot do
+        if let Some(checkpoint_height) = self.header_validator.last_checkpoint_height() {
+            let claimed_height = sync_peer.claimed_chain_metadata().best_block_height();
+            if claimed_height < checkpoint_height {
+                return Err(BlockHeaderSyncError::PeerBelowLastCheckpoint {
+                    claimed_height,
+                    checkpoint_height,
+                });
+            }
+        }
+
         // Fetch best local data at the beginning of the sync process
         let best_block_metadata = self.db.get_chain_metadata().await?;
         let best_header = self.db.fetch_last_chain_header().await?;
@@ -510,6 +545,7 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         self.header_validator
             .initialize_state(&chain_split_result.chain_split_hash)
             .await?;
+        self.randomx_pre_warmer.pre_warm_headers(&headers);
         for header in headers {
             debug!(
                 target: LOG_TARGET,
@@ -544,6 +580,8 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             split_hash.to_hex()
         );
 
+        // Syncing to a chain that forks deeper than the local reorg depth limit is refused before anything is removed
+        self.db.check_reorg_depth(split_hash).await?;
         let blocks = self.db.rewind_to_hash(split_hash).await?;
         debug!(
             target: LOG_TARGET,
@@ -562,19 +600,16 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         max_latency: Duration,
     ) -> Result<(), BlockHeaderSyncError> {
         info!(target: LOG_TARGET, "Starting header sync from peer {}", sync_peer);
-        const COMMIT_EVERY_N_HEADERS: usize = 1000;
 
         let mut has_switched_to_new_chain = false;
-        let pending_len = self.header_validator.valid_headers().len();
+        let pending_len = self.header_validator.num_pending_headers();
 
-        // Find the hash to start syncing the rest of the headers.
-        // The expectation cannot fail because there has been at least one valid header returned (checked in
-        // determine_sync_status)
-        let (start_header_height, start_header_hash, total_accumulated_difficulty) = self
+        // There has been at least one valid header returned (checked in determine_sync_status), but it may still be
+        // waiting for a checkpoint
+        let total_accumulated_difficulty = self
             .header_validator
             .current_valid_chain_tip_header()
-            .map(|h| (h.height(), *h.hash(), h.accumulated_data().total_accumulated_difficulty))
-            .expect("synchronize_headers: expected there to be a valid tip header but it was None");
+            .map(|h| h.accumulated_data().total_accumulated_difficulty);
 
         // If we already have a stronger chain at this point, switch over to it.
         // just in case we happen to be exactly HEADER_SYNC_INITIAL_MAX_HEADERS headers behind.
@@ -593,12 +628,13 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         if pending_len < HEADER_SYNC_INITIAL_MAX_HEADERS {
             // Peer returned less than the max number of requested headers. This indicates that we have all the
             // available headers from the peer.
+            self.check_checkpoint_reached()?;
             if !has_better_pow {
                 // Because the pow is less or equal than the current chain the peer had to have lied about their pow
                 debug!(target: LOG_TARGET, "No further headers to download");
                 return Err(BlockHeaderSyncError::PeerSentInaccurateChainMetadata {
                     claimed: sync_peer.claimed_chain_metadata().accumulated_difficulty(),
-                    actual: Some(total_accumulated_difficulty),
+                    actual: total_accumulated_difficulty,
                     local: split_info
                         .best_block_header
                         .accumulated_data()
@@ -610,14 +646,31 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             return Ok(());
         }
 
+        let mut last_total_accumulated_difficulty = U256::zero();
+        let mut writer = BatchedDbWriter::new(self.db.clone(), self.config.header_write_batch);
+        if let Some(accumulated_difficulty) = self
+            .synchronize_headers_in_parallel(
+                &sync_peer,
+                client,
+                &split_info,
+                &mut has_switched_to_new_chain,
+                &mut writer,
+            )
+            .await?
+        {
+            last_total_accumulated_difficulty = accumulated_difficulty;
+        }
+
+        // Stream the rest of the headers from the sync peer, starting at the last validated header
+        let start_header = self.header_validator.last_validated_header();
         debug!(
             target: LOG_TARGET,
             "Download remaining headers starting from header #{} from peer `{}`",
-            start_header_height,
+            start_header.height,
             sync_peer.node_id()
         );
         let request = SyncHeadersRequest {
-            start_hash: start_header_hash.to_vec(),
+            start_hash: start_header.hash().to_vec(),
             // To the tip!
             count: 0,
         };
@@ -631,7 +684,6 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
 
         let mut last_sync_timer = Instant::now();
 
-        let mut last_total_accumulated_difficulty = U256::zero();
         let mut avg_latency = RollingAverageTime::new(20);
         let mut prev_height: Option<u64> = None;
         while let Some(header) = header_stream.next().await {
@@ -678,21 +730,8 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             }
             let current_height = header.height;
             last_total_accumulated_difficulty = self.header_validator.validate(header).await?;
-
-            if has_switched_to_new_chain {
-                // If we've switched to the new chain, we simply commit every COMMIT_EVERY_N_HEADERS headers
-                if self.header_validator.valid_headers().len() >= COMMIT_EVERY_N_HEADERS {
-                    self.commit_pending_headers().await?;
-                }
-            } else {
-                // The remote chain has not (yet) been accepted.
-                // We check the tip difficulties, switching over to the new chain if a higher accumulated difficulty is
-                // achieved.
-                if self.pending_chain_has_higher_pow(&split_info.best_block_header) {
-                    self.switch_to_pending_chain(&split_info).await?;
-                    has_switched_to_new_chain = true;
-                }
-            }
+            self.store_validated_headers(&split_info, &mut has_switched_to_new_chain, &mut writer)
+                .await?;
 
             sync_peer.set_latency(latency);
             sync_peer.add_sample(last_sync_timer.elapsed());
@@ -716,6 +755,7 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             last_sync_timer = Instant::now();
             prev_height = Some(current_height);
         }
+        self.check_checkpoint_reached()?;
 
         if !has_switched_to_new_chain {
             if sync_peer.claimed_chain_metadata().accumulated_difficulty() <
@@ -751,10 +791,8 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
             }
         }
 
-        // Commit the last blocks that don't fit into the COMMIT_EVENT_N_HEADERS blocks
-        if !self.header_validator.valid_headers().is_empty() {
-            self.commit_pending_headers().await?;
-        }
+        // Commit the last headers in the batch
+        writer.flush().await?;
 
         let claimed_total_accumulated_diff = sync_peer.claimed_chain_metadata().accumulated_difficulty();
         // This rule is strict: if the peer advertised a higher PoW than they were able to provide (without
@@ -773,6 +811,277 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         Ok(())
     }
 
+    /// Downloads the bulk of the remaining headers as disjoint ranges from other sync peers concurrently, validating
+    /// the ranges in height order as they arrive. The sync peer supplies the hashes that each range must start from
+    /// and end with, so a peer serving a divergent chain is detected before its headers are validated. That peer is
+    /// banned and its range is requested again from another peer, or from the sync peer once no others remain.
+    ///
+    /// The last range up to the sync peer's tip is left to be streamed from the sync peer. Returns the accumulated
+    /// difficulty of the last validated header, or None if no other sync peers were available.
+    #[allow(clippy::too_many_lines)]
+    async fn synchronize_headers_in_parallel(
+        &mut self,
+        sync_peer: &SyncPeer,
+        client: &mut rpc::BaseNodeSyncRpcClient,
+        split_info: &ChainSplitInfo,
+        has_switched_to_new_chain: &mut bool,
+        writer: &mut BatchedDbWriter<B>,
+    ) -> Result<Option<U256>, BlockHeaderSyncError> {
+        let range_size = self.config.parallel_header_sync_range_size.max(1);
+        let start_height = self.header_validator.last_validated_header().height.saturating_add(1);
+        let end_height = sync_peer
+            .claimed_chain_metadata()
+            .best_block_height()
+            .saturating_sub(range_size);
+        if self.config.max_parallel_header_sync_peers < 2 ||
+            end_height < start_height.saturating_add(range_size.saturating_mul(2))
+        {
+            return Ok(None);
+        }
+
+        let mut idle_peers = self.connect_header_range_peers(sync_peer.node_id(), end_height).await;
+        if idle_peers.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "No other sync peers available, downloading headers from peer `{}` only",
+                sync_peer.node_id()
+            );
+            return Ok(None);
+        }
+        info!(
+            target: LOG_TARGET,
+            "Downloading headers #{} to #{} in parallel from {} peer(s) (sync peer `{}`)",
+            start_height,
+            end_height,
+            idle_peers.len(),
+            sync_peer.node_id()
+        );
+
+        let mut pending_ranges = parallel::split_header_ranges(start_height, end_height, range_size);
+        let mut in_flight = FuturesUnordered::new();
+        let mut completed = BTreeMap::new();
+        let mut dropped_peers = HashSet::new();
+        let mut is_using_sync_peer = false;
+        let mut next_height = start_height;
+        let mut last_total_accumulated_difficulty = None;
+
+        'sync: loop {
+            // Validate the downloaded ranges that follow on from the last validated header
+            while let Some((node_id, is_primary, headers)) = completed.remove(&next_height) {
+                let range_end = headers.last().map(|h: &BlockHeader| h.height).unwrap_or(next_height);
+                if dropped_peers.contains(&node_id) {
+                    pending_ranges.push_front(HeaderRange {
+                        start: next_height,
+                        end: range_end,
+                    });
+                    continue;
+                }
+                self.randomx_pre_warmer.pre_warm_headers(&headers);
+                for header in headers {
+                    let height = header.height;
+                    match self.header_validator.validate(header).await {
+                        Ok(accumulated_difficulty) => {
+                            last_total_accumulated_difficulty = Some(accumulated_difficulty);
+                        },
+                        Err(err) if is_primary => return Err(err),
+                        Err(err) => {
+                            self.drop_header_range_peer(&node_id, &err).await;
+                            idle_peers.retain(|p| p.node_id != node_id);
+                            dropped_peers.insert(node_id);
+                            pending_ranges.push_front(HeaderRange {
+                                start: height,
+                                end: range_end,
+                            });
+                            continue 'sync;
+                        },
+                    }
+                    self.store_validated_headers(split_info, has_switched_to_new_chain, writer)
+                        .await?;
+                    next_height = height.saturating_add(1);
+                }
+                self.hooks.call_on_progress_header_hooks(
+                    range_end,
+                    sync_peer.claimed_chain_metadata().best_block_height(),
+                    sync_peer,
+                );
+            }
+            if next_height > end_height {
+                break;
+            }
+
+            // Fall back to the sync peer once all other peers have been dropped
+            if idle_peers.is_empty() && in_flight.is_empty() && !is_using_sync_peer {
+                debug!(
+                    target: LOG_TARGET,
+                    "All other peers were dropped, downloading remaining header ranges from peer `{}`",
+                    sync_peer.node_id()
+                );
+                idle_peers.push(RangePeer {
+                    node_id: sync_peer.node_id().clone(),
+                    client: client.clone(),
+                    is_primary: true,
+                });
+                is_using_sync_peer = true;
+            }
+
+            while !pending_ranges.is_empty() && !idle_peers.is_empty() {
+                let range = pending_ranges.pop_front().expect("pending_ranges is not empty");
+                let peer = idle_peers.pop().expect("idle_peers is not empty");
+                let anchor_hash = self
+                    .fetch_sync_peer_header_hash(client, range.start.saturating_sub(1))
+                    .await?;
+                trace!(
+                    target: LOG_TARGET,
+                    "Requesting headers #{} to #{} from peer `{}`",
+                    range.start,
+                    range.end,
+                    peer.node_id
+                );
+                in_flight.push(parallel::download_header_range(peer, range, anchor_hash));
+            }
+
+            let RangeDownload { peer, range, result } = match in_flight.next().await {
+                Some(download) => download,
+                // Any headers that were not validated are streamed from the sync peer
+                None => break,
+            };
+            if dropped_peers.contains(&peer.node_id) {
+                pending_ranges.push_front(range);
+                continue;
+            }
+            match result {
+                Ok(headers) => {
+                    if !peer.is_primary {
+                        let expected_hash = self.fetch_sync_peer_header_hash(client, range.end).await?;
+                        if headers.last().map(|h| h.hash()) != Some(expected_hash) {
+                            let err = BlockHeaderSyncError::PeerSentDivergentChain {
+                                peer: peer.node_id.clone(),
+                                height: range.end,
+                            };
+                            self.drop_header_range_peer(&peer.node_id, &err).await;
+                            dropped_peers.insert(peer.node_id);
+                            pending_ranges.push_front(range);
+                            continue;
+                        }
+                    }
+                    completed.insert(range.start, (peer.node_id.clone(), peer.is_primary, headers));
+                    idle_peers.push(peer);
+                },
+                Err(err) if peer.is_primary => return Err(err),
+                Err(err) => {
+                    self.drop_header_range_peer(&peer.node_id, &err).await;
+                    dropped_peers.insert(peer.node_id);
+                    pending_ranges.push_front(range);
+                },
+            }
+        }
+
+        Ok(last_total_accumulated_difficulty)
+    }
+
+    /// Connects to the sync peers, other than the given sync peer, that claim to have headers up to `min_height`.
+    async fn connect_header_range_peers(&self, sync_peer: &NodeId, min_height: u64) -> Vec<RangePeer> {
+        let node_ids = self
+            .sync_peers
+            .iter()
+            .filter(|p| p.node_id() != sync_peer && p.claimed_chain_metadata().best_block_height() >= min_height)
+            .map(|p| p.node_id().clone())
+            .take(self.config.max_parallel_header_sync_peers.saturating_sub(1))
+            .collect::<Vec<_>>();
+
+        let mut peers = Vec::with_capacity(node_ids.len());
+        for node_id in node_ids {
+            let config = RpcClient::builder()
+                .with_deadline(self.config.rpc_deadline)
+                .with_deadline_grace_period(Duration::from_secs(5));
+            let client = match self.dial_sync_peer(&node_id).await {
+                Ok(mut conn) => conn
+                    .connect_rpc_using_builder::<rpc::BaseNodeSyncRpcClient>(config)
+                    .await
+                    .map_err(BlockHeaderSyncError::from),
+                Err(err) => Err(err),
+            };
+            match client {
+                Ok(client) => peers.push(RangePeer {
+                    node_id,
+                    client,
+                    is_primary: false,
+                }),
+                Err(err) => debug!(
+                    target: LOG_TARGET,
+                    "Unable to connect to peer `{}` for parallel header sync: {}", node_id, err
+                ),
+            }
+        }
+        peers
+    }
+
+    async fn fetch_sync_peer_header_hash(
+        &self,
+        client: &mut rpc::BaseNodeSyncRpcClient,
+        height: u64,
+    ) -> Result<HashOutput, BlockHeaderSyncError> {
+        let header = client.get_header_by_height(height).await?;
+        let header = BlockHeader::try_from(header).map_err(BlockHeaderSyncError::ReceivedInvalidHeader)?;
+        if header.height != height {
+            return Err(BlockHeaderSyncError::InvalidBlockHeight {
+                expected: height,
+                actual: header.height,
+            });
+        }
+        Ok(header.hash())
+    }
+
+    /// Bans a peer that failed to provide a valid header range, if required, and removes it from the sync peers.
+    async fn drop_header_range_peer(&mut self, node_id: &NodeId, err: &BlockHeaderSyncError) {
+        warn!(
+            target: LOG_TARGET,
+            "Dropping peer `{}` from parallel header sync: {}", node_id, err
+        );
+        if let Some(reason) = err.get_ban_reason() {
+            self.peer_ban_manager
+                .report_offense_if_required(node_id, reason, PeerOffense::InvalidBlock)
+                .await;
+        }
+        self.remove_sync_peer(node_id);
+    }
+
+    /// Checks that no headers whose proof of work was skipped are left waiting for a checkpoint once the peer has sent
+    /// all of its headers
+    fn check_checkpoint_reached(&self) -> Result<(), BlockHeaderSyncError> {
+        if !self.header_validator.has_unverified_headers() {
+            return Ok(());
+        }
+        Err(BlockHeaderSyncError::CheckpointNotReached {
+            height: self.header_validator.last_validated_header().height,
+            checkpoint_height: self.header_validator.last_checkpoint_height().unwrap_or_default(),
+        })
+    }
+
+    /// Writes the validated headers to the database if the remote chain has been accepted, otherwise switches over to
+    /// the remote chain once it has a higher accumulated difficulty than the local chain.
+    async fn store_validated_headers(
+        &mut self,
+        split_info: &ChainSplitInfo,
+        has_switched_to_new_chain: &mut bool,
+        writer: &mut BatchedDbWriter<B>,
+    ) -> Result<(), BlockHeaderSyncError> {
+        if *has_switched_to_new_chain {
+            // If we've switched to the new chain, we simply write each header to the batch, which is committed once
+            // its flush thresholds are reached
+            self.write_pending_headers(writer).await?;
+        } else {
+            // The remote chain has not (yet) been accepted.
+            // We check the tip difficulties, switching over to the new chain if a higher accumulated difficulty is
+            // achieved.
+            if self.pending_chain_has_higher_pow(&split_info.best_block_header) {
+                self.switch_to_pending_chain(split_info).await?;
+                *has_switched_to_new_chain = true;
+            }
+        }
+        Ok(())
+    }
+
     async fn commit_pending_headers(&mut self) -> Result<ChainHeader, BlockHeaderSyncError> {
         let chain_headers = self.header_validator.take_valid_headers();
         let num_headers = chain_headers.len();
@@ -797,6 +1106,15 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         Ok(new_tip)
     }
 
+    async fn write_pending_headers(&mut self, writer: &mut BatchedDbWriter<B>) -> Result<(), BlockHeaderSyncError> {
+        let mut txn = DbTransaction::new();
+        for chain_header in self.header_validator.take_valid_headers() {
+            txn.insert_chain_header(chain_header);
+        }
+        writer.write(txn).await?;
+        Ok(())
+    }
+
     fn pending_chain_has_higher_pow(&self, current_tip: &ChainHeader) -> bool {
         let chain_headers = self.header_validator.valid_headers();
         if chain_headers.is_empty() {
@@ -828,7 +1146,7 @@ impl<'a, B: BlockchainBackend + 'static> HeaderSynchronizer<'a, B> {
         // 1. Headers have been validated
         // 2. The forked chain has a higher PoW than the local chain
         //
-        // After this we commit headers every `n` blocks
+        // After this we commit headers in batches
         self.commit_pending_headers().await?;
 
         Ok(())
@@ -33,7 +33,12 @@ use crate::{
     common::rolling_vec::RollingVec,
     consensus::ConsensusManager,
     proof_of_work::{randomx_factory::RandomXFactory, PowAlgorithm},
-    validation::{header::HeaderFullValidator, DifficultyCalculator, HeaderChainLinkedValidator, ValidationError},
+    validation::{
+        header::{HeaderCheckpoints, HeaderFullValidator},
+        DifficultyCalculator,
+        HeaderChainLinkedValidator,
+        ValidationError,
+    },
 };
 
 const LOG_TARGET: &str = "c::bn::header_sync";
@@ -44,6 +49,7 @@ pub struct BlockHeaderSyncValidator<B> {
     state: Option<State>,
     consensus_rules: ConsensusManager,
     validator: HeaderFullValidator,
+    checkpoints: HeaderCheckpoints,
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +60,9 @@ struct State {
     previous_accum: BlockHeaderAccumulatedData,
     previous_header: BlockHeader,
     valid_headers: Vec<ChainHeader>,
+    /// The number of headers at the end of `valid_headers` whose proof of work was not checked and that are not yet
+    /// tied to a checkpoint
+    unverified_headers: usize,
 }
 
 impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
@@ -65,9 +74,22 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             state: None,
             consensus_rules,
             validator,
+            checkpoints: HeaderCheckpoints::default(),
         }
     }
 
+    /// Skip the proof of work checks for headers below the last of the given checkpoints. These headers are held back
+    /// from [Self::valid_headers] until a header with a checkpoint hash is validated on top of them.
+    pub fn with_checkpoints(mut self, checkpoints: HeaderCheckpoints) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    /// The height of the highest checkpoint, if any
+    pub fn last_checkpoint_height(&self) -> Option<u64> {
+        self.checkpoints.last_checkpoint_height()
+    }
+
     #[allow(clippy::ptr_arg)]
     pub async fn initialize_state(&mut self, start_hash: &HashOutput) -> Result<(), BlockHeaderSyncError> {
         let start_header = self
@@ -101,6 +123,7 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             previous_header: start_header,
             // One large allocation is usually better even if it is not always used.
             valid_headers: Vec::with_capacity(HEADER_SYNC_INITIAL_MAX_HEADERS),
+            unverified_headers: 0,
         });
 
         Ok(())
@@ -119,15 +142,29 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
             constants.max_pow_difficulty(header.pow_algo()),
         );
 
+        let is_checkpoint = self.checkpoints.get(header.height).is_some();
+        let skip_pow = self.checkpoints.is_below_last_checkpoint(header.height);
         let result = {
             let txn = self.db.inner().db_read_access()?;
-            self.validator.validate(
-                &*txn,
-                &header,
-                &state.previous_header,
-                &state.timestamps,
-                Some(target_difficulty),
-            )
+            self.checkpoints.check(header.height, &header.hash()).and_then(|_| {
+                if skip_pow {
+                    self.validator.validate_without_pow(
+                        &*txn,
+                        &header,
+                        &state.previous_header,
+                        &state.timestamps,
+                        target_difficulty,
+                    )
+                } else {
+                    self.validator.validate(
+                        &*txn,
+                        &header,
+                        &state.previous_header,
+                        &state.timestamps,
+                        Some(target_difficulty),
+                    )
+                }
+            })
         };
         let achieved_target = match result {
             Ok(achieved_target) => achieved_target,
@@ -185,26 +222,67 @@ impl<B: BlockchainBackend + 'static> BlockHeaderSyncValidator<B> {
 
         state.previous_accum = chain_header.accumulated_data().clone();
         state.valid_headers.push(chain_header);
+        // The previous hash of every header has been checked, so a header with a checkpoint hash vouches for all the
+        // headers before it
+        if is_checkpoint {
+            state.unverified_headers = 0;
+        } else if skip_pow {
+            state.unverified_headers += 1;
+        }
 
         Ok(total_accumulated_difficulty)
     }
 
-    /// Drains and returns all the headers that were validated.
+    /// Returns the last header that was validated, or the header at the chain split if no headers have been validated
+    /// yet.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if initialize_state was not called prior to calling this function
+    pub fn last_validated_header(&self) -> &BlockHeader {
+        &self.state().previous_header
+    }
+
+    /// Drains and returns all the headers that were validated, except for headers that are still waiting for a
+    /// checkpoint.
     ///
     /// ## Panics
     ///
     /// Panics if initialize_state was not called prior to calling this function
     pub fn take_valid_headers(&mut self) -> Vec<ChainHeader> {
-        self.state_mut().valid_headers.drain(..).collect::<Vec<_>>()
+        let state = self.state_mut();
+        let num_verified = state.valid_headers.len() - state.unverified_headers;
+        state.valid_headers.drain(..num_verified).collect::<Vec<_>>()
     }
 
-    /// Returns a slice containing the current valid headers
+    /// Returns a slice containing the current valid headers, except for headers that are still waiting for a
+    /// checkpoint.
     ///
     /// ## Panics
     ///
     /// Panics if initialize_state was not called prior to calling this function
     pub fn valid_headers(&self) -> &[ChainHeader] {
-        &self.state().valid_headers
+        let state = self.state();
+        &state.valid_headers[..state.valid_headers.len() - state.unverified_headers]
+    }
+
+    /// Returns the number of headers that were validated and not yet taken, including headers that are still waiting
+    /// for a checkpoint.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if initialize_state was not called prior to calling this function
+    pub fn num_pending_headers(&self) -> usize {
+        self.state().valid_headers.len()
+    }
+
+    /// Returns true if headers were validated without their proof of work and have not been tied to a checkpoint.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if initialize_state was not called prior to calling this function
+    pub fn has_unverified_headers(&self) -> bool {
+        self.state().unverified_headers > 0
     }
 
     pub fn compare_chains(&self, our_header: &ChainHeader, their_header: &ChainHeader) -> Ordering {
@@ -315,8 +393,13 @@ mod test {
     }
 
     mod validate {
+        use tari_common_types::types::FixedHash;
+
         use super::*;
-        use crate::{blocks::BlockHeaderValidationError, validation::ValidationError};
+        use crate::{
+            blocks::BlockHeaderValidationError,
+            validation::{header::HeaderCheckpoint, ValidationError},
+        };
 
         #[tokio::test]
         async fn it_passes_if_headers_are_valid() {
@@ -332,6 +415,44 @@ mod test {
             assert_eq!(validator.valid_headers().len(), 2);
         }
 
+        #[tokio::test]
+        async fn it_holds_back_headers_below_a_checkpoint_until_it_is_reached() {
+            let (validator, _, tip) = setup_with_headers(1).await;
+            let header2 = BlockHeader::from_previous(tip.header());
+            let header3 = BlockHeader::from_previous(&header2);
+            let mut validator = validator.with_checkpoints(HeaderCheckpoints::new([HeaderCheckpoint {
+                height: 3,
+                hash: header3.hash(),
+            }]));
+            validator.initialize_state(tip.hash()).await.unwrap();
+
+            validator.validate(header2).await.unwrap();
+            assert!(validator.has_unverified_headers());
+            assert!(validator.valid_headers().is_empty());
+            assert!(validator.take_valid_headers().is_empty());
+            assert_eq!(validator.num_pending_headers(), 1);
+
+            validator.validate(header3).await.unwrap();
+            assert!(!validator.has_unverified_headers());
+            assert_eq!(validator.take_valid_headers().len(), 2);
+        }
+
+        #[tokio::test]
+        async fn it_fails_if_a_header_does_not_match_the_checkpoint() {
+            let (validator, _, tip) = setup_with_headers(1).await;
+            let header2 = BlockHeader::from_previous(tip.header());
+            let mut validator = validator.with_checkpoints(HeaderCheckpoints::new([HeaderCheckpoint {
+                height: 2,
+                hash: FixedHash::from([1u8; 32]),
+            }]));
+            validator.initialize_state(tip.hash()).await.unwrap();
+
+            let err = validator.validate(header2).await.unwrap_err();
+            unpack_enum!(BlockHeaderSyncError::ValidationFailed(val_err) = err);
+            unpack_enum!(ValidationError::CheckpointMismatch { height, .. } = val_err);
+            assert_eq!(height, 2);
+        }
+
         #[tokio::test]
         async fn it_fails_if_height_is_not_serial() {
             let (mut validator, _, tip) = setup_with_headers(12).await;
@@ -30,7 +30,12 @@ use std::{
 use futures::StreamExt;
 use log::*;
 use tari_common_types::types::{Commitment, FixedHash, RangeProofService};
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, protocol::rpc::RpcClient, PeerConnection};
+use tari_comms::{
+    connectivity::ConnectivityRequester,
+    peer_manager::{NodeId, PeerOffense},
+    protocol::rpc::{RpcClient, RpcError, RpcStatusCode, Streaming},
+    PeerConnection,
+};
 use tari_crypto::commitment::HomomorphicCommitment;
 use tari_mmr::sparse_merkle_tree::{DeleteResult, NodeKey, ValueHash};
 use tari_utilities::{hex::Hex, ByteArray};
@@ -46,12 +51,26 @@ use crate::{
         rpc::BaseNodeSyncRpcClient,
         BlockchainSyncConfig,
         SyncPeer,
+        SyncPeerScorer,
     },
     blocks::{BlockHeader, ChainHeader, UpdateBlockAccumulatedData},
-    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError, MmrTree},
-    common::{rolling_avg::RollingAverageTime, BanPeriod},
+    chain_storage::{
+        async_db::AsyncBlockchainDb,
+        BlockBalanceSums,
+        BlockchainBackend,
+        ChainStorageError,
+        HorizonSyncCheckpoint,
+        MmrTree,
+    },
+    common::rolling_avg::RollingAverageTime,
     consensus::ConsensusManager,
-    proto::base_node::{sync_utxos_response::Txo, SyncKernelsRequest, SyncUtxosRequest, SyncUtxosResponse},
+    proto::base_node::{
+        sync_utxos_response::Txo,
+        SyncKernelsRequest,
+        SyncUtxosRequest,
+        SyncUtxosResponse,
+        SyncUtxosResumeRequest,
+    },
     transactions::transaction_components::{
         transaction_output::batch_verify_range_proofs,
         OutputType,
@@ -67,6 +86,16 @@ const LOG_TARGET: &str = "c::bn::state_machine_service::states::horizon_state_sy
 
 const MAX_LATENCY_INCREASES: usize = 5;
 
+/// The minimum number of TXOs that are received between horizon sync checkpoints
+const OUTPUT_SYNC_CHECKPOINT_INTERVAL: u64 = 10_000;
+
+/// The change to the UTXO sum made by a completed output sync, relative to the local tip the sync started from
+struct UtxoSumDelta {
+    from_height: u64,
+    added: Commitment,
+    spent: Commitment,
+}
+
 pub struct HorizonStateSynchronization<'a, B> {
     config: BlockchainSyncConfig,
     db: AsyncBlockchainDb<B>,
@@ -81,6 +110,9 @@ pub struct HorizonStateSynchronization<'a, B> {
     final_state_validator: Arc<dyn FinalHorizonStateValidation<B>>,
     max_latency: Duration,
     peer_ban_manager: PeerBanManager,
+    peer_scorer: SyncPeerScorer,
+    utxo_sum_delta: Option<UtxoSumDelta>,
+    output_checkpoint_height: Option<u64>,
 }
 
 impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
@@ -89,6 +121,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         config: BlockchainSyncConfig,
         db: AsyncBlockchainDb<B>,
         connectivity: ConnectivityRequester,
+        peer_scorer: SyncPeerScorer,
         rules: ConsensusManager,
         sync_peers: &'a mut Vec<SyncPeer>,
         horizon_sync_height: u64,
@@ -110,6 +143,9 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
             hooks: Hooks::default(),
             final_state_validator,
             peer_ban_manager,
+            peer_scorer,
+            utxo_sum_delta: None,
+            output_checkpoint_height: None,
         }
     }
 
@@ -180,19 +216,21 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         let mut latency_counter = 0usize;
         for node_id in sync_peer_node_ids {
             match self.connect_and_attempt_sync(&node_id, to_header).await {
-                Ok(_) => return Ok(()),
+                Ok(_) => {
+                    if let Some(sync_peer) = self.sync_peers.iter().find(|p| p.node_id() == &node_id) {
+                        self.peer_scorer.record_success(sync_peer).await;
+                    }
+                    return Ok(());
+                },
                 // Try another peer
                 Err(err) => {
+                    self.peer_scorer.record_failure(&node_id).await;
                     let ban_reason = HorizonSyncError::get_ban_reason(&err);
 
                     if let Some(reason) = ban_reason {
-                        let duration = match reason.ban_duration {
-                            BanPeriod::Short => self.config.short_ban_period,
-                            BanPeriod::Long => self.config.ban_period,
-                        };
                         warn!(target: LOG_TARGET, "{}", err);
                         self.peer_ban_manager
-                            .ban_peer_if_required(&node_id, reason.reason, duration)
+                            .report_offense_if_required(&node_id, reason, PeerOffense::BadHorizonData)
                             .await;
                     }
                     if let HorizonSyncError::MaxLatencyExceeded { .. } = err {
@@ -301,24 +339,27 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         match self.synchronize_outputs(sync_peer, client, to_header).await {
             Ok(_) => Ok(()),
             Err(err) => {
-                // We need to clean up the outputs
-                let _ = self.clean_up_failed_output_sync(to_header).await;
+                if let HorizonSyncError::InvalidMrRoot { .. } = err {
+                    // The received TXOs do not produce the expected output SMT, so none of them can be resumed from
+                    self.discard_output_sync_checkpoint().await;
+                }
+                // We need to clean up the outputs that were received after the last checkpoint
+                if let Ok(tip_header) = self.db.fetch_tip_header().await {
+                    let down_to_height = self.output_checkpoint_height.unwrap_or_else(|| tip_header.height());
+                    self.clean_up_failed_output_sync(to_header, down_to_height).await;
+                }
                 Err(err)
             },
         }
     }
 
-    /// We clean up a failed output sync attempt and ignore any errors that occur during the clean up process.
-    async fn clean_up_failed_output_sync(&mut self, to_header: &BlockHeader) {
-        let tip_header = if let Ok(header) = self.db.fetch_tip_header().await {
-            header
-        } else {
-            return;
-        };
+    /// We clean up a failed output sync attempt by removing the outputs in the blocks from `from_header` down to (but
+    /// not including) `down_to_height`, and ignore any errors that occur during the clean up process.
+    async fn clean_up_failed_output_sync(&mut self, from_header: &BlockHeader, down_to_height: u64) {
         let db = self.db().clone();
         let mut txn = db.write_transaction();
-        let mut current_header = to_header.clone();
-        loop {
+        let mut current_header = from_header.clone();
+        while current_header.height > down_to_height {
             if let Ok(outputs) = self.db.fetch_outputs_in_block(current_header.hash()).await {
                 for (count, output) in (1..=outputs.len()).zip(outputs.iter()) {
                     // Note: We do not need to clean up the SMT as it was not saved in the database yet, however, we
@@ -367,10 +408,21 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 );
                 break;
             }
-            if &current_header.hash() == tip_header.hash() {
-                debug!(target: LOG_TARGET, "Finished cleaning up failed output sync");
-                break;
-            }
+        }
+        debug!(target: LOG_TARGET, "Finished cleaning up failed output sync");
+    }
+
+    /// Removes the persisted output sync checkpoint, ignoring any errors
+    async fn discard_output_sync_checkpoint(&mut self) {
+        self.output_checkpoint_height = None;
+        if let Err(e) = self
+            .db()
+            .write_transaction()
+            .clear_horizon_sync_checkpoint()
+            .commit()
+            .await
+        {
+            warn!(target: LOG_TARGET, "Could not clear the horizon sync checkpoint: {}", e);
         }
     }
 
@@ -567,6 +619,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         to_header: &BlockHeader,
     ) -> Result<(), HorizonSyncError> {
         info!(target: LOG_TARGET, "Starting output sync from peer {}", sync_peer);
+        self.utxo_sum_delta = None;
         let db = self.db().clone();
         let tip_header = db.fetch_tip_header().await?;
 
@@ -607,22 +660,25 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
             latency.unwrap_or_default().as_millis(),
         );
 
-        let start_chain_header = db.fetch_chain_header(tip_header.height() + 1).await?;
-        let req = SyncUtxosRequest {
-            start_header_hash: start_chain_header.hash().to_vec(),
-            end_header_hash: to_header.hash().to_vec(),
-        };
-        let mut output_stream = client.sync_utxos(req).await?;
+        let kernel_mmr_position = db.fetch_mmr_size(MmrTree::Kernel).await?;
+        let (mut output_stream, checkpoint) = self
+            .start_output_stream(client, tip_header.height(), to_header, kernel_mmr_position)
+            .await?;
 
         let mut txn = db.write_transaction();
-        let mut utxo_counter = 0u64;
-        let mut stxo_counter = 0u64;
         let timer = Instant::now();
-        let mut output_smt = db.fetch_tip_smt().await?;
+        let (mut output_smt, mut progress) = match checkpoint {
+            Some(checkpoint) => (self.rebuild_output_smt_at_checkpoint(&checkpoint).await?, checkpoint),
+            None => (
+                db.fetch_tip_smt().await?,
+                HorizonSyncCheckpoint::new(to_header.hash(), tip_header.height(), kernel_mmr_position),
+            ),
+        };
+        let mut txos_at_last_checkpoint = progress.txo_count();
+        let mut last_block = None;
         let mut last_sync_timer = Instant::now();
         let mut avg_latency = RollingAverageTime::new(20);
 
-        let mut inputs_to_delete = Vec::new();
         while let Some(response) = output_stream.next().await {
             let latency = last_sync_timer.elapsed();
             avg_latency.add_sample(latency);
@@ -638,15 +694,27 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                     HorizonSyncError::IncorrectResponse("Peer sent mined header we do not know of".into())
                 })?;
 
+            // TXOs are streamed in block order, so the previous block is complete once a TXO for the next one arrives
+            if let Some((block_hash, block_height)) = last_block {
+                if block_hash != output_header_hash {
+                    progress.height = block_height;
+                    if progress.txo_count() >= txos_at_last_checkpoint + OUTPUT_SYNC_CHECKPOINT_INTERVAL {
+                        self.write_output_sync_checkpoint(&progress).await?;
+                        txos_at_last_checkpoint = progress.txo_count();
+                    }
+                }
+            }
+            last_block = Some((output_header_hash, current_header.height));
+
             let proto_output = res
                 .txo
                 .ok_or_else(|| HorizonSyncError::IncorrectResponse("Peer sent no transaction output data".into()))?;
             match proto_output {
                 Txo::Output(output) => {
-                    utxo_counter += 1;
+                    progress.utxo_count += 1;
                     // Increase the estimate number of outputs to be downloaded (for display purposes only).
-                    if utxo_counter >= self.num_outputs {
-                        self.num_outputs = utxo_counter + u64::from(current_header.hash() != to_header.hash());
+                    if progress.utxo_count >= self.num_outputs {
+                        self.num_outputs = progress.utxo_count + u64::from(current_header.hash() != to_header.hash());
                     }
 
                     let constants = self.rules.consensus_constants(current_header.height).clone();
@@ -656,7 +724,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                             target: LOG_TARGET,
                             "UTXO `{}` received from sync peer ({} of {})",
                             output.hash(),
-                            utxo_counter,
+                            progress.utxo_count,
                             self.num_outputs,
                         );
                         helpers::check_tari_script_byte_size(&output.script, constants.max_script_byte_size())?;
@@ -672,6 +740,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                             );
                             return Err(e.into());
                         }
+                        progress.added_sum = &output.commitment + &progress.added_sum;
                         txn.insert_output_via_horizon_sync(
                             output,
                             current_header.hash(),
@@ -684,7 +753,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                     }
                 },
                 Txo::Commitment(commitment_bytes) => {
-                    stxo_counter += 1;
+                    progress.stxo_count += 1;
 
                     let commitment = Commitment::from_canonical_bytes(commitment_bytes.as_slice())?;
                     match self
@@ -697,7 +766,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                                 target: LOG_TARGET,
                                 "STXO hash `{}` received from sync peer ({})",
                                 output_hash,
-                                stxo_counter,
+                                progress.stxo_count,
                             );
                             let smt_key = NodeKey::try_from(commitment_bytes.as_slice())?;
                             match output_smt.delete(&smt_key)? {
@@ -715,7 +784,8 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                             };
                             // This will only be committed once the SMT has been verified due to rewind difficulties if
                             // we need to abort the sync
-                            inputs_to_delete.push((output_hash, commitment));
+                            progress.spent_sum = &commitment + &progress.spent_sum;
+                            progress.spent_outputs.push((output_hash, commitment));
                         },
                         None => {
                             return Err(HorizonSyncError::IncorrectResponse(
@@ -726,9 +796,9 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 },
             }
 
-            if utxo_counter % 100 == 0 {
+            if progress.utxo_count % 100 == 0 {
                 let info = HorizonSyncInfo::new(vec![sync_peer.node_id().clone()], HorizonSyncStatus::Outputs {
-                    current: utxo_counter,
+                    current: progress.utxo_count,
                     total: self.num_outputs,
                     sync_peer: sync_peer.clone(),
                 });
@@ -759,6 +829,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         HorizonStateSynchronization::<B>::check_output_smt_root_hash(&mut output_smt, to_header)?;
 
         // Commit in chunks to avoid locking the database for too long
+        let inputs_to_delete = std::mem::take(&mut progress.spent_outputs);
         let inputs_to_delete_len = inputs_to_delete.len();
         for (count, (output_hash, commitment)) in (1..=inputs_to_delete_len).zip(inputs_to_delete.into_iter()) {
             txn.prune_output_from_all_dbs(output_hash, commitment, OutputType::default());
@@ -768,16 +839,165 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         }
         // This has a very low probability of failure
         db.set_tip_smt(output_smt).await?;
+        self.discard_output_sync_checkpoint().await;
+        self.utxo_sum_delta = Some(UtxoSumDelta {
+            from_height: tip_header.height(),
+            added: progress.added_sum,
+            spent: progress.spent_sum,
+        });
         debug!(
             target: LOG_TARGET,
             "Finished syncing TXOs: {} unspent and {} spent downloaded in {:.2?}",
-            utxo_counter,
-            stxo_counter,
+            progress.utxo_count,
+            progress.stxo_count,
             timer.elapsed()
         );
         Ok(())
     }
 
+    /// Requests the TXO stream from the sync peer. If a checkpoint was persisted by an earlier attempt at this sync,
+    /// the stream is resumed from the block after the checkpoint and the checkpoint is returned.
+    async fn start_output_stream(
+        &mut self,
+        client: &mut rpc::BaseNodeSyncRpcClient,
+        tip_height: u64,
+        to_header: &BlockHeader,
+        kernel_mmr_position: u64,
+    ) -> Result<(Streaming<SyncUtxosResponse>, Option<HorizonSyncCheckpoint>), HorizonSyncError> {
+        let db = self.db().clone();
+        let start_chain_header = db.fetch_chain_header(tip_height + 1).await?;
+        if let Some(checkpoint) = self
+            .fetch_resumable_checkpoint(tip_height, to_header, kernel_mmr_position)
+            .await?
+        {
+            let resume_chain_header = db.fetch_chain_header(checkpoint.height + 1).await?;
+            let req = SyncUtxosResumeRequest {
+                start_header_hash: start_chain_header.hash().to_vec(),
+                end_header_hash: to_header.hash().to_vec(),
+                resume_header_hash: resume_chain_header.hash().to_vec(),
+            };
+            match client.sync_utxos_resume(req).await {
+                Ok(stream) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Resuming output sync from height {} ({} TXOs already received)",
+                        resume_chain_header.height(),
+                        checkpoint.txo_count()
+                    );
+                    self.output_checkpoint_height = Some(checkpoint.height);
+                    return Ok((stream, Some(checkpoint)));
+                },
+                Err(RpcError::RequestFailed(status)) if status.as_status_code() == RpcStatusCode::UnsupportedMethod => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Sync peer does not support resuming the output sync, restarting it from height {}",
+                        start_chain_header.height()
+                    );
+                    self.discard_output_sync_checkpoint().await;
+                    self.clean_up_failed_output_sync(to_header, tip_height).await;
+                },
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let req = SyncUtxosRequest {
+            start_header_hash: start_chain_header.hash().to_vec(),
+            end_header_hash: to_header.hash().to_vec(),
+        };
+        Ok((client.sync_utxos(req).await?, None))
+    }
+
+    /// Returns the persisted checkpoint if it was written by an output sync to `to_header` from the current local tip.
+    /// Outputs received after the checkpoint are removed, as the node may have stopped before it could clean them up.
+    /// A checkpoint for a different sync is discarded along with the outputs that it covered.
+    async fn fetch_resumable_checkpoint(
+        &mut self,
+        tip_height: u64,
+        to_header: &BlockHeader,
+        kernel_mmr_position: u64,
+    ) -> Result<Option<HorizonSyncCheckpoint>, HorizonSyncError> {
+        let checkpoint = match self.db().fetch_horizon_sync_checkpoint().await? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        if checkpoint.is_resumable_for(&to_header.hash(), tip_height, kernel_mmr_position) &&
+            checkpoint.height < to_header.height
+        {
+            self.clean_up_failed_output_sync(to_header, checkpoint.height).await;
+            return Ok(Some(checkpoint));
+        }
+
+        debug!(
+            target: LOG_TARGET,
+            "Discarding horizon sync checkpoint at height {} for a different sync", checkpoint.height
+        );
+        if checkpoint.start_height == tip_height {
+            if let Some(header) = self.db().fetch_header(checkpoint.height).await? {
+                self.clean_up_failed_output_sync(&header, tip_height).await;
+            }
+        }
+        self.discard_output_sync_checkpoint().await;
+        Ok(None)
+    }
+
+    /// Rebuilds the output SMT that the output sync had built when the checkpoint was written, from the tip SMT and the
+    /// outputs that were committed for the blocks up to the checkpoint
+    async fn rebuild_output_smt_at_checkpoint(
+        &self,
+        checkpoint: &HorizonSyncCheckpoint,
+    ) -> Result<OutputSmt, HorizonSyncError> {
+        let db = self.db().inner().clone();
+        let start_height = checkpoint.start_height + 1;
+        let end_height = checkpoint.height;
+        let spent_commitments = checkpoint
+            .spent_outputs
+            .iter()
+            .map(|(_, commitment)| commitment.clone())
+            .collect::<Vec<_>>();
+        task::spawn_blocking(move || {
+            let mut output_smt = db.fetch_tip_smt()?;
+            for height in start_height..=end_height {
+                let header = db.fetch_chain_header(height)?;
+                for output in db.fetch_outputs_in_block(*header.hash())? {
+                    if output.is_burned() {
+                        continue;
+                    }
+                    let smt_key = NodeKey::try_from(output.commitment.as_bytes())?;
+                    let smt_node = ValueHash::try_from(output.smt_hash(height).as_slice())?;
+                    output_smt.insert(smt_key, smt_node)?;
+                }
+            }
+            for commitment in spent_commitments {
+                let smt_key = NodeKey::try_from(commitment.as_bytes())?;
+                if let DeleteResult::KeyNotFound = output_smt.delete(&smt_key)? {
+                    return Err(HorizonSyncError::ChainStorageError(ChainStorageError::UnspendableInput));
+                }
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Rebuilt the output SMT at the horizon sync checkpoint at height {}", end_height
+            );
+            Ok(output_smt)
+        })
+        .await?
+    }
+
+    async fn write_output_sync_checkpoint(&mut self, progress: &HorizonSyncCheckpoint) -> Result<(), HorizonSyncError> {
+        self.db()
+            .write_transaction()
+            .set_horizon_sync_checkpoint(progress.clone())
+            .commit()
+            .await?;
+        self.output_checkpoint_height = Some(progress.height);
+        debug!(
+            target: LOG_TARGET,
+            "Horizon sync checkpoint written at height {} ({} TXOs received)",
+            progress.height,
+            progress.txo_count()
+        );
+        Ok(())
+    }
+
     // Helper function to check the output SMT root hash against the expected root hash.
     fn check_output_smt_root_hash(output_smt: &mut OutputSmt, header: &BlockHeader) -> Result<(), HorizonSyncError> {
         let root = FixedHash::try_from(output_smt.hash().as_slice())?;
@@ -836,6 +1056,10 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                 header.timestamp(),
             )
             .set_pruned_height(header.height())
+            .set_block_balance_sums(
+                header.height(),
+                BlockBalanceSums::new(calc_kernel_sum.clone(), calc_utxo_sum.clone(), calc_burned_sum),
+            )
             .set_horizon_data(calc_kernel_sum, calc_utxo_sum)
             .commit()
             .await?;
@@ -843,11 +1067,15 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         Ok(())
     }
 
-    /// (UTXO sum, Kernel sum)
+    /// (UTXO sum, Kernel sum, Burned sum)
     async fn calculate_commitment_sums(
         &mut self,
         header: &ChainHeader,
     ) -> Result<(Commitment, Commitment, Commitment), HorizonSyncError> {
+        if let Some(sums) = self.calculate_commitment_sums_from_cache(header).await? {
+            return Ok(sums);
+        }
+
         let mut utxo_sum = HomomorphicCommitment::default();
         let mut kernel_sum = HomomorphicCommitment::default();
         let mut burned_sum = HomomorphicCommitment::default();
@@ -913,6 +1141,55 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
         .await?
     }
 
+    /// Calculates the sums from the balance sums cached at the local tip that the output sync started from, the
+    /// change that the output sync made to the UTXO set and the kernels of the blocks since. This only reads the
+    /// kernels of the synced blocks instead of every kernel and output since the genesis block. Returns None if the
+    /// sums are not cached at the local tip.
+    async fn calculate_commitment_sums_from_cache(
+        &self,
+        header: &ChainHeader,
+    ) -> Result<Option<(Commitment, Commitment, Commitment)>, HorizonSyncError> {
+        let delta = match self.utxo_sum_delta.as_ref() {
+            Some(delta) => delta,
+            None => return Ok(None),
+        };
+        let mut sums = match self.db().fetch_block_balance_sums(delta.from_height).await? {
+            Some(sums) => sums,
+            None => {
+                debug!(
+                    target: LOG_TARGET,
+                    "No balance sums cached at height {}, summing all kernels and outputs", delta.from_height
+                );
+                return Ok(None);
+            },
+        };
+        sums.add_unspent_commitment(&delta.added);
+        sums.spend_commitment(&delta.spent);
+
+        let from_height = delta.from_height + 1;
+        let height = header.height();
+        let db = self.db().inner().clone();
+        task::spawn_blocking(move || {
+            for h in from_height..=height {
+                let curr_header = db.fetch_chain_header(h)?;
+                for kernel in db.fetch_kernels_in_block(*curr_header.hash())? {
+                    sums.add_kernel(&kernel)?;
+                }
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Calculated balance sums from the sums cached at height {}",
+                from_height - 1
+            );
+            Ok(Some((
+                sums.utxo_sum().clone(),
+                sums.kernel_sum().clone(),
+                sums.burned_sum().clone(),
+            )))
+        })
+        .await?
+    }
+
     // Sync peers are also removed from the list of sync peers if the ban duration is longer than the short ban period.
     fn remove_sync_peer(&mut self, node_id: &NodeId) {
         if let Some(pos) = self.sync_peers.iter().position(|p| p.node_id() == node_id) {
@@ -23,6 +23,11 @@
 #[cfg(feature = "base_node")]
 pub mod ban;
 
+#[cfg(feature = "base_node")]
+mod bandwidth;
+#[cfg(feature = "base_node")]
+pub use bandwidth::{SyncBandwidthLimiter, SyncPeerBandwidth};
+
 #[cfg(feature = "base_node")]
 mod config;
 #[cfg(feature = "base_node")]
@@ -49,6 +54,11 @@ mod hooks;
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub mod rpc;
 
+#[cfg(feature = "base_node")]
+mod peer_scorer;
+#[cfg(feature = "base_node")]
+pub use peer_scorer::{SyncPeerScore, SyncPeerScorer, SYNC_PEER_SCORE_METADATA_KEY};
+
 #[cfg(feature = "base_node")]
 mod sync_peer;
 #[cfg(feature = "base_node")]
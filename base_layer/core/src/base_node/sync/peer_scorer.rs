@@ -0,0 +1,292 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{cmp::Reverse, collections::HashMap, convert::TryFrom, sync::Arc, time::Duration};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use tari_comms::peer_manager::{NodeId, PeerManager};
+use tari_utilities::epoch_time::EpochTime;
+
+use crate::base_node::sync::SyncPeer;
+
+const LOG_TARGET: &str = "c::bn::sync::peer_scorer";
+
+/// The peer database metadata key that sync peer scores are stored under. Key 1 holds the peer's claimed chain
+/// metadata.
+pub const SYNC_PEER_SCORE_METADATA_KEY: u8 = 2;
+
+/// Failure counts are halved for every period that passes without the score being updated, so that a peer is not
+/// penalised forever for a bad sync.
+const FAILURE_DECAY_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+const BASE_SCORE: i64 = 1_000;
+const MAX_LATENCY_PENALTY: i64 = 500;
+const MAX_BANDWIDTH_BONUS: i64 = 500;
+const MAX_SUCCESS_BONUS: i64 = 200;
+const SUCCESS_BONUS: i64 = 20;
+const FAILURE_PENALTY: i64 = 100;
+const INACCURATE_DIFFICULTY_PENALTY: i64 = 400;
+
+/// The sync history of a peer, used to rank sync peers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncPeerScore {
+    /// Moving average of the peer's RPC latency in milliseconds
+    pub avg_latency_ms: Option<u64>,
+    /// Moving average of the rate at which the peer delivered sync data in bytes per second
+    pub avg_bytes_per_second: Option<u64>,
+    /// The number of syncs that completed successfully with this peer
+    pub num_successful_syncs: u64,
+    /// The number of syncs that failed with this peer
+    pub num_failed_syncs: u64,
+    /// The number of syncs in which the peer delivered less accumulated difficulty than it claimed
+    pub num_inaccurate_difficulty_claims: u64,
+    /// The unix timestamp at which the score was last updated
+    pub last_updated: u64,
+}
+
+impl SyncPeerScore {
+    /// Returns the score used to rank this peer against other sync peers. Higher is better.
+    pub fn score(&self) -> i64 {
+        let latency_penalty = self
+            .avg_latency_ms
+            .map(|ms| i64::try_from(ms / 10).unwrap_or(i64::MAX).min(MAX_LATENCY_PENALTY))
+            .unwrap_or(MAX_LATENCY_PENALTY / 2);
+        let bandwidth_bonus = self
+            .avg_bytes_per_second
+            .map(|bps| i64::try_from(bps / 10_000).unwrap_or(i64::MAX).min(MAX_BANDWIDTH_BONUS))
+            .unwrap_or_default();
+        let success_bonus = i64::try_from(self.num_successful_syncs)
+            .unwrap_or(i64::MAX)
+            .saturating_mul(SUCCESS_BONUS)
+            .min(MAX_SUCCESS_BONUS);
+        let failure_penalty = i64::try_from(self.num_failed_syncs)
+            .unwrap_or(i64::MAX)
+            .saturating_mul(FAILURE_PENALTY);
+        let inaccurate_difficulty_penalty = i64::try_from(self.num_inaccurate_difficulty_claims)
+            .unwrap_or(i64::MAX)
+            .saturating_mul(INACCURATE_DIFFICULTY_PENALTY);
+
+        BASE_SCORE
+            .saturating_sub(latency_penalty)
+            .saturating_add(bandwidth_bonus)
+            .saturating_add(success_bonus)
+            .saturating_sub(failure_penalty)
+            .saturating_sub(inaccurate_difficulty_penalty)
+    }
+
+    pub fn add_latency_sample(&mut self, latency: Duration) -> &mut Self {
+        let sample = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        self.avg_latency_ms = Some(moving_average(self.avg_latency_ms, sample));
+        self
+    }
+
+    pub fn add_bandwidth_sample(&mut self, num_bytes: u64, elapsed: Duration) -> &mut Self {
+        let millis = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        if num_bytes > 0 && millis > 0 {
+            let sample = num_bytes.saturating_mul(1000) / millis;
+            self.avg_bytes_per_second = Some(moving_average(self.avg_bytes_per_second, sample));
+        }
+        self
+    }
+
+    /// Halves the failure counts for every full decay period between the last update and `now`.
+    fn apply_decay(&mut self, now: u64) -> &mut Self {
+        let periods = now.saturating_sub(self.last_updated) / FAILURE_DECAY_PERIOD.as_secs();
+        if periods > 0 {
+            let shift = u32::try_from(periods).unwrap_or(u32::MAX).min(63);
+            self.num_failed_syncs >>= shift;
+            self.num_inaccurate_difficulty_claims >>= shift;
+        }
+        self
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bincode::deserialize(bytes).ok()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        // Serializing a struct of integers cannot fail
+        bincode::serialize(self).unwrap_or_default()
+    }
+}
+
+/// Weights the new sample at 30%
+fn moving_average(current: Option<u64>, sample: u64) -> u64 {
+    match current {
+        Some(current) => current.saturating_mul(7).saturating_add(sample.saturating_mul(3)) / 10,
+        None => sample,
+    }
+}
+
+/// Tracks the sync history of peers in the peer database and orders sync peers by their score, so that sync is
+/// attempted with the most reliable and fastest peers first.
+#[derive(Clone)]
+pub struct SyncPeerScorer {
+    peer_manager: Arc<PeerManager>,
+}
+
+impl SyncPeerScorer {
+    pub fn new(peer_manager: Arc<PeerManager>) -> Self {
+        Self { peer_manager }
+    }
+
+    /// Returns the stored score for the peer, or the default score if the peer has no sync history.
+    pub async fn get_score(&self, node_id: &NodeId) -> SyncPeerScore {
+        let mut score = match self.peer_manager.find_by_node_id(node_id).await {
+            Ok(Some(peer)) => peer
+                .get_metadata(SYNC_PEER_SCORE_METADATA_KEY)
+                .and_then(|bytes| SyncPeerScore::from_bytes(bytes))
+                .unwrap_or_default(),
+            Ok(None) => SyncPeerScore::default(),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Failed to load sync score for peer `{}`: {}", node_id, err);
+                SyncPeerScore::default()
+            },
+        };
+        score.apply_decay(EpochTime::now().as_u64());
+        score
+    }
+
+    /// Records a completed sync with the peer.
+    pub async fn record_success(&self, sync_peer: &SyncPeer) {
+        self.update_score(sync_peer.node_id(), |score| {
+            if let Some(latency) = sync_peer.latency() {
+                score.add_latency_sample(latency);
+            }
+            score.num_successful_syncs = score.num_successful_syncs.saturating_add(1);
+        })
+        .await;
+    }
+
+    /// Records that `num_bytes` of sync data were received from the peer in `elapsed` time.
+    pub async fn record_bandwidth(&self, node_id: &NodeId, num_bytes: u64, elapsed: Duration) {
+        self.update_score(node_id, |score| {
+            score.add_bandwidth_sample(num_bytes, elapsed);
+        })
+        .await;
+    }
+
+    /// Records a failed sync attempt with the peer.
+    pub async fn record_failure(&self, node_id: &NodeId) {
+        self.update_score(node_id, |score| {
+            score.num_failed_syncs = score.num_failed_syncs.saturating_add(1);
+        })
+        .await;
+    }
+
+    /// Records that the peer delivered less accumulated difficulty than it claimed to have.
+    pub async fn record_inaccurate_difficulty_claim(&self, node_id: &NodeId) {
+        self.update_score(node_id, |score| {
+            score.num_failed_syncs = score.num_failed_syncs.saturating_add(1);
+            score.num_inaccurate_difficulty_claims = score.num_inaccurate_difficulty_claims.saturating_add(1);
+        })
+        .await;
+    }
+
+    /// Orders the sync peers from the highest to the lowest score. Peers with equal scores keep their relative order.
+    pub async fn sort_sync_peers(&self, sync_peers: &mut [SyncPeer]) {
+        let mut scores = HashMap::with_capacity(sync_peers.len());
+        for sync_peer in sync_peers.iter() {
+            let mut score = self.get_score(sync_peer.node_id()).await;
+            // Use the latency measured for this sync round for peers without any sync history
+            if score.avg_latency_ms.is_none() {
+                if let Some(latency) = sync_peer.latency() {
+                    score.add_latency_sample(latency);
+                }
+            }
+            scores.insert(sync_peer.node_id().clone(), score.score());
+        }
+        sync_peers.sort_by_key(|p| Reverse(scores.get(p.node_id()).copied().unwrap_or(BASE_SCORE)));
+        debug!(
+            target: LOG_TARGET,
+            "Sync peers ordered by score: {}",
+            sync_peers
+                .iter()
+                .map(|p| format!("{} ({})", p.node_id(), scores.get(p.node_id()).copied().unwrap_or(BASE_SCORE)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    async fn update_score<F>(&self, node_id: &NodeId, update: F)
+    where F: FnOnce(&mut SyncPeerScore) {
+        let mut score = self.get_score(node_id).await;
+        update(&mut score);
+        score.last_updated = EpochTime::now().as_u64();
+        // If this fails, it's not the end of the world, the peer is simply ranked on its previous score
+        if let Err(err) = self
+            .peer_manager
+            .set_peer_metadata(node_id, SYNC_PEER_SCORE_METADATA_KEY, score.to_bytes())
+            .await
+        {
+            warn!(target: LOG_TARGET, "Failed to store sync score for peer `{}`: {}", node_id, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_ranks_reliable_peers_higher() {
+        let mut fast = SyncPeerScore::default();
+        fast.add_latency_sample(Duration::from_millis(50))
+            .add_bandwidth_sample(5_000_000, Duration::from_secs(1));
+        let mut slow = SyncPeerScore::default();
+        slow.add_latency_sample(Duration::from_millis(2_000))
+            .add_bandwidth_sample(50_000, Duration::from_secs(1));
+        assert!(fast.score() > slow.score());
+
+        let mut unreliable = fast.clone();
+        unreliable.num_failed_syncs = 3;
+        assert!(unreliable.score() < fast.score());
+
+        let mut dishonest = fast.clone();
+        dishonest.num_inaccurate_difficulty_claims = 1;
+        assert!(dishonest.score() < unreliable.score());
+    }
+
+    #[test]
+    fn it_decays_failures_over_time() {
+        let mut score = SyncPeerScore {
+            num_failed_syncs: 8,
+            num_inaccurate_difficulty_claims: 2,
+            last_updated: 1_000,
+            ..Default::default()
+        };
+        score.apply_decay(1_000 + FAILURE_DECAY_PERIOD.as_secs() - 1);
+        assert_eq!(score.num_failed_syncs, 8);
+        score.apply_decay(1_000 + 2 * FAILURE_DECAY_PERIOD.as_secs());
+        assert_eq!(score.num_failed_syncs, 2);
+        assert_eq!(score.num_inaccurate_difficulty_claims, 0);
+    }
+
+    #[test]
+    fn it_serializes_to_peer_metadata() {
+        let mut score = SyncPeerScore::default();
+        score.add_latency_sample(Duration::from_millis(120));
+        score.num_successful_syncs = 4;
+        assert_eq!(SyncPeerScore::from_bytes(&score.to_bytes()), Some(score));
+    }
+}
@@ -49,6 +49,7 @@ use crate::{
         SyncKernelsRequest,
         SyncUtxosRequest,
         SyncUtxosResponse,
+        SyncUtxosResumeRequest,
     },
 };
 
@@ -92,6 +93,12 @@ pub trait BaseNodeSyncService: Send + Sync + 'static {
 
     #[rpc(method = 8)]
     async fn sync_utxos(&self, request: Request<SyncUtxosRequest>) -> Result<Streaming<SyncUtxosResponse>, RpcStatus>;
+
+    #[rpc(method = 9)]
+    async fn sync_utxos_resume(
+        &self,
+        request: Request<SyncUtxosResumeRequest>,
+    ) -> Result<Streaming<SyncUtxosResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
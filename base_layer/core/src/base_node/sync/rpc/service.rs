@@ -62,6 +62,7 @@ use crate::{
         SyncKernelsRequest,
         SyncUtxosRequest,
         SyncUtxosResponse,
+        SyncUtxosResumeRequest,
     },
 };
 
@@ -613,4 +614,27 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
 
         Ok(Streaming::new(rx))
     }
+
+    async fn sync_utxos_resume(
+        &self,
+        request: Request<SyncUtxosResumeRequest>,
+    ) -> Result<Streaming<SyncUtxosResponse>, RpcStatus> {
+        let req = request.message();
+        let peer_node_id = request.context().peer_node_id();
+        debug!(
+            target: LOG_TARGET,
+            "Received sync_utxos_resume-{} request from header {} to {}, resuming at {}",
+            peer_node_id,
+            req.start_header_hash.to_hex(),
+            req.end_header_hash.to_hex(),
+            req.resume_header_hash.to_hex(),
+        );
+
+        let session_token = self.try_add_exclusive_session(peer_node_id.clone()).await?;
+        let (tx, rx) = mpsc::channel(200);
+        let task = SyncUtxosTask::new(self.db(), session_token);
+        task.run_resume(request, tx).await?;
+
+        Ok(Streaming::new(rx))
+    }
 }
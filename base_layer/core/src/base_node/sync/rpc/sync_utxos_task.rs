@@ -41,7 +41,7 @@ use crate::{
     blocks::BlockHeader,
     chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
     proto,
-    proto::base_node::{sync_utxos_response::Txo, SyncUtxosRequest, SyncUtxosResponse},
+    proto::base_node::{sync_utxos_response::Txo, SyncUtxosRequest, SyncUtxosResponse, SyncUtxosResumeRequest},
 };
 
 const LOG_TARGET: &str = "c::base_node::sync_rpc::sync_utxo_task";
@@ -61,38 +61,55 @@ where B: BlockchainBackend + 'static
     pub(crate) async fn run(
         self,
         request: Request<SyncUtxosRequest>,
-        mut tx: mpsc::Sender<Result<SyncUtxosResponse, RpcStatus>>,
+        tx: mpsc::Sender<Result<SyncUtxosResponse, RpcStatus>>,
     ) -> Result<(), RpcStatus> {
         let msg = request.into_message();
-        let start_hash = msg
-            .start_header_hash
-            .clone()
-            .try_into()
-            .rpc_status_bad_request("Invalid header hash")?;
-
-        let start_header = self
-            .db
-            .fetch_header_by_block_hash(start_hash)
-            .await
-            .rpc_status_internal_error(LOG_TARGET)?
-            .ok_or_else(|| RpcStatus::not_found("Start header hash was not found"))?;
+        let start_header = self.fetch_header(msg.start_header_hash, "Start").await?;
+        let end_header = self.fetch_header(msg.end_header_hash, "End").await?;
+        self.spawn_stream(tx, start_header.clone(), start_header, end_header)
+    }
 
-        let end_hash = msg
-            .end_header_hash
-            .clone()
-            .try_into()
-            .rpc_status_bad_request("Invalid header hash")?;
+    /// Continues an interrupted UTXO sync from `resume_header_hash`. Spent outputs are still filtered against the
+    /// tranche that started at `start_header_hash`, so the peer receives exactly the TXOs that the original stream
+    /// would have sent from the resume block onwards.
+    pub(crate) async fn run_resume(
+        self,
+        request: Request<SyncUtxosResumeRequest>,
+        tx: mpsc::Sender<Result<SyncUtxosResponse, RpcStatus>>,
+    ) -> Result<(), RpcStatus> {
+        let msg = request.into_message();
+        let start_header = self.fetch_header(msg.start_header_hash, "Start").await?;
+        let resume_header = self.fetch_header(msg.resume_header_hash, "Resume").await?;
+        let end_header = self.fetch_header(msg.end_header_hash, "End").await?;
+        if resume_header.height < start_header.height {
+            return Err(RpcStatus::bad_request(&format!(
+                "Resume header height({}) cannot be less than the start header height({})",
+                resume_header.height, start_header.height
+            )));
+        }
+        self.spawn_stream(tx, start_header, resume_header, end_header)
+    }
 
-        let end_header = self
-            .db
-            .fetch_header_by_block_hash(end_hash)
+    async fn fetch_header(&self, hash: Vec<u8>, name: &str) -> Result<BlockHeader, RpcStatus> {
+        let hash = hash.try_into().rpc_status_bad_request("Invalid header hash")?;
+        self.db
+            .fetch_header_by_block_hash(hash)
             .await
             .rpc_status_internal_error(LOG_TARGET)?
-            .ok_or_else(|| RpcStatus::not_found("End header hash was not found"))?;
-        if start_header.height > end_header.height {
+            .ok_or_else(|| RpcStatus::not_found(&format!("{} header hash was not found", name)))
+    }
+
+    fn spawn_stream(
+        self,
+        mut tx: mpsc::Sender<Result<SyncUtxosResponse, RpcStatus>>,
+        start_header: BlockHeader,
+        resume_header: BlockHeader,
+        end_header: BlockHeader,
+    ) -> Result<(), RpcStatus> {
+        if resume_header.height > end_header.height {
             return Err(RpcStatus::bad_request(&format!(
                 "Start header height({}) cannot be greater than the end header height({})",
-                start_header.height, end_header.height
+                resume_header.height, end_header.height
             )));
         }
 
@@ -101,7 +118,10 @@ where B: BlockchainBackend + 'static
                 target: LOG_TARGET,
                 "Starting UTXO stream for peer '{}'", self.peer_node_id
             );
-            if let Err(err) = self.start_streaming(&mut tx, start_header, end_header).await {
+            if let Err(err) = self
+                .start_streaming(&mut tx, start_header, resume_header, end_header)
+                .await
+            {
                 debug!(
                     target: LOG_TARGET,
                     "UTXO stream errored for peer '{}': {}", self.peer_node_id, err
@@ -123,6 +143,7 @@ where B: BlockchainBackend + 'static
     async fn start_streaming(
         &self,
         tx: &mut mpsc::Sender<Result<SyncUtxosResponse, RpcStatus>>,
+        start_header: BlockHeader,
         mut current_header: BlockHeader,
         end_header: BlockHeader,
     ) -> Result<(), RpcStatus> {
@@ -141,7 +162,7 @@ where B: BlockchainBackend + 'static
             .get_chain_metadata()
             .await
             .rpc_status_internal_error(LOG_TARGET)?;
-        if current_header.height == 1 && metadata.is_pruned_node() {
+        if start_header.height == 1 && start_header == current_header && metadata.is_pruned_node() {
             let genesis_block = self.db.fetch_genesis_block();
             for output in genesis_block.block().body.outputs() {
                 let output_hash = output.hash();
@@ -165,7 +186,6 @@ where B: BlockchainBackend + 'static
             }
         }
 
-        let start_header = current_header.clone();
         loop {
             let timer = Instant::now();
             let current_header_hash = current_header.hash();
@@ -21,7 +21,10 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use futures::StreamExt;
-use tari_comms::protocol::rpc::{mock::RpcRequestMock, RpcStatusCode};
+use tari_comms::{
+    peer_manager::NodeId,
+    protocol::rpc::{mock::RpcRequestMock, RpcStatusCode},
+};
 use tari_service_framework::reply_channel;
 use tari_test_utils::{streams::convert_mpsc_to_stream, unpack_enum};
 use tempfile::{tempdir, TempDir};
@@ -31,7 +34,7 @@ use super::BaseNodeSyncRpcService;
 use crate::{
     base_node::{BaseNodeSyncService, LocalNodeCommsInterface},
     chain_storage::BlockchainDatabase,
-    proto::base_node::{SyncBlocksRequest, SyncUtxosRequest},
+    proto::base_node::{SyncBlocksRequest, SyncUtxosRequest, SyncUtxosResumeRequest},
     test_helpers::{
         blockchain::{create_main_chain, create_new_blockchain, TempDatabase},
         create_peer_manager,
@@ -145,4 +148,59 @@ mod sync_utxos {
         let err = service.sync_utxos(req).await.unwrap_err();
         unpack_enum!(RpcStatusCode::NotFound = err.as_status_code());
     }
+
+    #[tokio::test]
+    async fn it_returns_bad_request_if_resume_is_before_start() {
+        let (service, db, rpc_request_mock, _tmp) = setup();
+        let (_, chain) = create_main_chain(&db, block_specs!(["A->GB"], ["B->A"])).await;
+        let msg = SyncUtxosResumeRequest {
+            start_header_hash: chain.get("B").unwrap().hash().to_vec(),
+            end_header_hash: chain.get("B").unwrap().hash().to_vec(),
+            resume_header_hash: chain.get("A").unwrap().hash().to_vec(),
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let err = service.sync_utxos_resume(req).await.unwrap_err();
+        unpack_enum!(RpcStatusCode::BadRequest = err.as_status_code());
+    }
+
+    #[tokio::test]
+    async fn it_resumes_streaming_from_the_resume_header() {
+        let (service, db, rpc_request_mock, _tmp) = setup();
+        let (_, chain) = create_main_chain(&db, block_specs!(["A->GB"], ["B->A"], ["C->B"])).await;
+        let start_hash = chain.get("A").unwrap().hash().to_vec();
+        let resume_hash = chain.get("B").unwrap().hash().to_vec();
+        let end_hash = chain.get("C").unwrap().hash().to_vec();
+
+        let msg = SyncUtxosRequest {
+            start_header_hash: start_hash.clone(),
+            end_header_hash: end_hash.clone(),
+        };
+        let req = rpc_request_mock.request_with_context(Default::default(), msg);
+        let mut streaming = service.sync_utxos(req).await.unwrap().into_inner();
+        let txos = convert_mpsc_to_stream(&mut streaming)
+            .map(|txo| txo.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        let msg = SyncUtxosResumeRequest {
+            start_header_hash: start_hash.clone(),
+            end_header_hash: end_hash,
+            resume_header_hash: resume_hash,
+        };
+        // A different peer, as the first session may not have been released yet
+        let peer = NodeId::from_public_key(&Default::default());
+        let req = rpc_request_mock.request_with_context(peer, msg);
+        let mut streaming = service.sync_utxos_resume(req).await.unwrap().into_inner();
+        let resumed_txos = convert_mpsc_to_stream(&mut streaming)
+            .map(|txo| txo.unwrap())
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(!resumed_txos.is_empty());
+        let expected = txos
+            .into_iter()
+            .filter(|txo| txo.mined_header != start_hash)
+            .collect::<Vec<_>>();
+        assert_eq!(resumed_txos, expected);
+    }
 }
@@ -35,7 +35,7 @@ use tari_common_types::types::{FixedHash, PrivateKey};
 use thiserror::Error;
 
 use crate::{
-    blocks::BlockHeader,
+    blocks::{BlockHeader, ShortIdKey},
     consensus::ConsensusConstants,
     proof_of_work::ProofOfWork,
     transactions::{
@@ -97,6 +97,7 @@ impl Block {
     /// 1. There is exactly ONE coinbase output
     /// 2. The output's maturity is correctly set
     /// 3. The amount is correct.
+    /// 4. The `coinbase_extra` field is within the consensus maximum size.
     pub fn check_coinbase_output(
         &self,
         reward: MicroMinotari,
@@ -109,6 +110,8 @@ impl Block {
             factories,
             self.header.height,
         )?;
+        self.body
+            .check_coinbase_extra_size(consensus_constants.coinbase_output_features_extra_max_length())?;
         Ok(())
     }
 
@@ -252,6 +255,35 @@ pub struct NewBlock {
     pub coinbase_output: TransactionOutput,
     /// The scalar `s` component of the kernel excess signatures of the transactions contained in the block.
     pub kernel_excess_sigs: Vec<PrivateKey>,
+    /// The short IDs of the kernels of the transactions contained in the block. A compact block sends these instead of
+    /// the kernel excess signatures.
+    pub kernel_short_ids: Vec<u64>,
+    /// The salt used to derive the kernel short IDs
+    pub short_id_salt: u64,
+}
+
+impl NewBlock {
+    /// Replace the kernel excess signatures with short IDs derived from the block hash and the given salt. A short ID
+    /// is 6 bytes rather than the 32 bytes of an excess signature scalar.
+    pub fn into_compact(mut self, salt: u64) -> Self {
+        let key = ShortIdKey::new(self.header.hash(), salt);
+        self.kernel_short_ids = self
+            .kernel_excess_sigs
+            .drain(..)
+            .map(|sig| key.short_id(&sig))
+            .collect();
+        self.short_id_salt = salt;
+        self
+    }
+
+    /// Returns true if the transactions in this block are identified by short IDs
+    pub fn is_compact(&self) -> bool {
+        !self.kernel_short_ids.is_empty()
+    }
+
+    pub fn short_id_key(&self) -> ShortIdKey {
+        ShortIdKey::new(self.header.hash(), self.short_id_salt)
+    }
 }
 
 impl From<&Block> for NewBlock {
@@ -282,6 +314,8 @@ impl From<&Block> for NewBlock {
                 .filter(|k| !k.features.contains(KernelFeatures::COINBASE_KERNEL))
                 .map(|kernel| kernel.excess_sig.get_signature().clone())
                 .collect(),
+            kernel_short_ids: Vec::new(),
+            short_id_salt: 0,
         }
     }
 }
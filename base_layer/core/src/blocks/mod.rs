@@ -39,6 +39,9 @@ pub use error::BlockError;
 mod block;
 pub use block::{Block, BlockBuilder, BlockValidationError, NewBlock};
 
+mod short_id;
+pub use short_id::{ShortIdKey, SHORT_ID_LENGTH};
+
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 mod block_header;
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
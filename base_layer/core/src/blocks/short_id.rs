@@ -0,0 +1,78 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{BlockHash, PrivateKey};
+
+use crate::{blocks::BlocksHashDomain, consensus::DomainSeparatedConsensusHasher};
+
+/// The number of bytes of a kernel excess signature hash that are kept in a short transaction ID
+pub const SHORT_ID_LENGTH: usize = 6;
+
+/// The key used to derive the short transaction IDs of a compact block. Short IDs are derived from the block hash and
+/// a random salt chosen by the sending node, so that an attacker cannot precompute transactions whose short IDs
+/// collide across every block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShortIdKey {
+    pub block_hash: BlockHash,
+    pub salt: u64,
+}
+
+impl ShortIdKey {
+    pub fn new(block_hash: BlockHash, salt: u64) -> Self {
+        Self { block_hash, salt }
+    }
+
+    /// Returns the short ID of the transaction kernel with the given excess signature scalar. Only the lowest
+    /// [SHORT_ID_LENGTH] bytes of the returned value are set.
+    pub fn short_id(&self, excess_sig: &PrivateKey) -> u64 {
+        let hash = DomainSeparatedConsensusHasher::<BlocksHashDomain, Blake2b<U32>>::new("short_transaction_id")
+            .chain(&self.block_hash)
+            .chain(&self.salt)
+            .chain(excess_sig)
+            .finalize();
+        let mut bytes = [0u8; 8];
+        bytes[..SHORT_ID_LENGTH].copy_from_slice(&hash[..SHORT_ID_LENGTH]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn it_derives_short_ids_from_the_block_hash_and_salt() {
+        let sig = PrivateKey::random(&mut OsRng);
+        let key = ShortIdKey::new(BlockHash::zero(), 1);
+        let short_id = key.short_id(&sig);
+        assert!(short_id < 1 << (SHORT_ID_LENGTH * 8));
+        assert_eq!(key.short_id(&sig), short_id);
+        assert_ne!(ShortIdKey::new(BlockHash::zero(), 2).short_id(&sig), short_id);
+        assert_ne!(ShortIdKey::new(BlockHash::from([1u8; 32]), 1).short_id(&sig), short_id);
+    }
+}
@@ -20,10 +20,12 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::collections::HashSet;
+
 use serde::{Deserialize, Serialize};
 use tari_common_types::{
     epoch::VnEpoch,
-    types::{Commitment, PublicKey},
+    types::{Commitment, FixedHash, PublicKey},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -34,3 +36,120 @@ pub struct ValidatorNodeEntry {
     pub public_key: PublicKey,
     pub commitment: Commitment,
 }
+
+/// The active validator node set and its merkle root, recorded when the first block of an epoch is added to the chain.
+/// Snapshots are retained after the registrations they were built from have been pruned.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidatorNodeEpochSnapshot {
+    pub epoch: VnEpoch,
+    /// The height of the first block in the epoch
+    pub height: u64,
+    pub merkle_root: FixedHash,
+    pub validator_nodes: Vec<(PublicKey, [u8; 32])>,
+}
+
+impl ValidatorNodeEpochSnapshot {
+    /// Returns the validator nodes that joined or left the set since the `previous` snapshot. If there is no previous
+    /// snapshot, every validator node in this snapshot is reported as added.
+    // Public key does not mutate once compressed and will always produce the same hash
+    #[allow(clippy::mutable_key_type)]
+    pub fn changes_since(&self, previous: Option<&ValidatorNodeEpochSnapshot>) -> Vec<ValidatorNodeChange> {
+        let previous_nodes = previous
+            .map(|p| p.validator_nodes.iter().collect::<HashSet<_>>())
+            .unwrap_or_default();
+        let current_nodes = self.validator_nodes.iter().collect::<HashSet<_>>();
+
+        let removed = previous
+            .into_iter()
+            .flat_map(|p| p.validator_nodes.iter())
+            .filter(|node| !current_nodes.contains(node))
+            .map(|(public_key, shard_key)| ValidatorNodeChange {
+                epoch: self.epoch,
+                public_key: public_key.clone(),
+                shard_key: *shard_key,
+                kind: ValidatorNodeChangeKind::Removed,
+            });
+        let added = self
+            .validator_nodes
+            .iter()
+            .filter(|node| !previous_nodes.contains(node))
+            .map(|(public_key, shard_key)| ValidatorNodeChange {
+                epoch: self.epoch,
+                public_key: public_key.clone(),
+                shard_key: *shard_key,
+                kind: ValidatorNodeChangeKind::Added,
+            });
+        removed.chain(added).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidatorNodeChangeKind {
+    Added,
+    Removed,
+}
+
+/// A validator node that joined or left the active set at the start of `epoch`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorNodeChange {
+    pub epoch: VnEpoch,
+    pub public_key: PublicKey,
+    pub shard_key: [u8; 32],
+    pub kind: ValidatorNodeChangeKind,
+}
+
+#[cfg(test)]
+mod test {
+    use tari_utilities::ByteArray;
+
+    use super::*;
+    use crate::test_helpers::{make_hash, new_public_key};
+
+    fn snapshot(epoch: u64, validator_nodes: Vec<(PublicKey, [u8; 32])>) -> ValidatorNodeEpochSnapshot {
+        ValidatorNodeEpochSnapshot {
+            epoch: VnEpoch(epoch),
+            height: epoch * 10,
+            merkle_root: FixedHash::zero(),
+            validator_nodes,
+        }
+    }
+
+    fn new_node() -> (PublicKey, [u8; 32]) {
+        let public_key = new_public_key();
+        let shard_key = make_hash(public_key.as_bytes());
+        (public_key, shard_key)
+    }
+
+    #[test]
+    fn it_reports_all_nodes_as_added_without_a_previous_snapshot() {
+        let nodes = vec![new_node(), new_node()];
+        let changes = snapshot(1, nodes.clone()).changes_since(None);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|c| c.kind == ValidatorNodeChangeKind::Added));
+        assert_eq!(changes[0].public_key, nodes[0].0);
+        assert_eq!(changes[1].public_key, nodes[1].0);
+    }
+
+    #[test]
+    fn it_reports_added_and_removed_nodes() {
+        let (kept, left, joined) = (new_node(), new_node(), new_node());
+        let previous = snapshot(1, vec![kept.clone(), left.clone()]);
+        let current = snapshot(2, vec![kept, joined.clone()]);
+
+        let changes = current.changes_since(Some(&previous));
+        assert_eq!(changes, vec![
+            ValidatorNodeChange {
+                epoch: VnEpoch(2),
+                public_key: left.0,
+                shard_key: left.1,
+                kind: ValidatorNodeChangeKind::Removed,
+            },
+            ValidatorNodeChange {
+                epoch: VnEpoch(2),
+                public_key: joined.0,
+                shard_key: joined.1,
+                kind: ValidatorNodeChangeKind::Added,
+            },
+        ]);
+    }
+}
@@ -26,9 +26,11 @@ use primitive_types::U256;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
-    types::{BlockHash, Commitment, HashOutput, PublicKey, Signature},
+    epoch::VnEpoch,
+    types::{BlockHash, Commitment, FixedHash, HashOutput, PublicKey, Signature},
 };
 use tari_utilities::epoch_time::EpochTime;
+use tokio::sync::broadcast;
 
 use super::TemplateRegistrationEntry;
 use crate::{
@@ -43,10 +45,12 @@ use crate::{
         NewBlockTemplate,
         UpdateBlockAccumulatedData,
     },
+    burn_proof::BurnProof,
     chain_storage::{
         blockchain_database::MmrRoots,
         utxo_mined_info::{InputMinedInfo, OutputMinedInfo},
         BlockAddResult,
+        BlockBalanceSums,
         BlockchainBackend,
         BlockchainDatabase,
         ChainStorageError,
@@ -54,10 +58,19 @@ use crate::{
         DbTotalSizeStats,
         DbTransaction,
         HorizonData,
+        HorizonSyncCheckpoint,
+        LmdbCompactionResult,
+        MapUtilization,
+        MmrReconstructionProgress,
+        MmrReconstructionResult,
         MmrTree,
+        Reorg,
         TargetDifficulties,
+        ValidatorNodeChange,
+        ValidatorNodeEpochSnapshot,
     },
     common::rolling_vec::RollingVec,
+    output_inclusion_proof::OutputInclusionProof,
     proof_of_work::{PowAlgorithm, TargetDifficultyWindow},
     transactions::transaction_components::{OutputType, TransactionInput, TransactionKernel, TransactionOutput},
     OutputSmt,
@@ -157,6 +170,10 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_horizon_data() -> HorizonData, "fetch_horizon_data");
 
+    make_async_fn!(fetch_horizon_sync_checkpoint() -> Option<HorizonSyncCheckpoint>, "fetch_horizon_sync_checkpoint");
+
+    make_async_fn!(fetch_block_balance_sums(height: u64) -> Option<BlockBalanceSums>, "fetch_block_balance_sums");
+
     //---------------------------------- TXO --------------------------------------------//
 
     make_async_fn!(fetch_output(output_hash: HashOutput) -> Option<OutputMinedInfo>, "fetch_output");
@@ -165,6 +182,12 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_unspent_output_hash_by_commitment(commitment: Commitment) -> Option<HashOutput>, "fetch_unspent_output_by_commitment");
 
+    make_async_fn!(output_indexes_enabled() -> bool, "output_indexes_enabled");
+
+    make_async_fn!(fetch_outputs_by_payref(payrefs: Vec<FixedHash>) -> Vec<OutputMinedInfo>, "fetch_outputs_by_payref");
+
+    make_async_fn!(fetch_outputs_by_script_key(script_key: PublicKey) -> Vec<OutputMinedInfo>, "fetch_outputs_by_script_key");
+
     make_async_fn!(fetch_outputs_with_spend_status_at_tip(hashes: Vec<HashOutput>) -> Vec<Option<(TransactionOutput, bool)>>, "fetch_outputs_with_spend_status_at_tip");
 
     make_async_fn!(fetch_outputs_mined_info(hashes: Vec<HashOutput>) -> Vec<Option<OutputMinedInfo>>, "fetch_outputs_mined_info");
@@ -224,6 +247,10 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_tip_smt() -> OutputSmt, "fetch_tip_smt");
 
+    make_async_fn!(fetch_output_inclusion_proof(output_hash: HashOutput, height: u64) -> OutputInclusionProof, "fetch_output_inclusion_proof");
+
+    make_async_fn!(fetch_burn_proof(burn_commitment: Commitment) -> BurnProof, "fetch_burn_proof");
+
     make_async_fn!(set_tip_smt(smt: OutputSmt) -> (), "set_tip_smt");
 
     make_async_fn!(insert_valid_headers(headers: Vec<ChainHeader>) -> (), "insert_valid_headers");
@@ -263,10 +290,14 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(prune_to_height(height: u64) -> (), "prune_to_height");
 
+    make_async_fn!(prune_to_horizon(new_horizon: u64) -> (), "prune_to_horizon");
+
     make_async_fn!(rewind_to_height(height: u64) -> Vec<Arc<ChainBlock>>, "rewind_to_height");
 
     make_async_fn!(rewind_to_hash(hash: BlockHash) -> Vec<Arc<ChainBlock>>, "rewind_to_hash");
 
+    make_async_fn!(check_reorg_depth(fork_hash: BlockHash) -> (), "check_reorg_depth");
+
     make_async_fn!(fetch_block_timestamps(start_hash: HashOutput) -> RollingVec<EpochTime>, "fetch_block_timestamps");
 
     make_async_fn!(fetch_target_difficulty_for_next_block(pow_algo: PowAlgorithm, current_block_hash: HashOutput) -> TargetDifficultyWindow, "fetch_target_difficulty");
@@ -279,10 +310,24 @@ impl<B: BlockchainBackend + 'static> AsyncBlockchainDb<B> {
 
     make_async_fn!(fetch_total_size_stats() -> DbTotalSizeStats, "fetch_total_size_stats");
 
+    make_async_fn!(fetch_map_utilization() -> MapUtilization, "fetch_map_utilization");
+
+    make_async_fn!(grow_map_if_required(max_utilization_percentage: u8) -> bool, "grow_map_if_required");
+
+    make_async_fn!(compact() -> LmdbCompactionResult, "compact");
+
+    make_async_fn!(fetch_reorg_history(limit: usize) -> Vec<Reorg>, "fetch_reorg_history");
+
+    make_async_fn!(reconstruct_mmrs(progress: Option<broadcast::Sender<MmrReconstructionProgress>>) -> MmrReconstructionResult, "reconstruct_mmrs");
+
     make_async_fn!(fetch_active_validator_nodes(height: u64) -> Vec<(PublicKey, [u8;32])>, "fetch_active_validator_nodes");
 
     make_async_fn!(get_shard_key(height:u64, public_key: PublicKey) -> Option<[u8;32]>, "get_shard_key");
 
+    make_async_fn!(fetch_validator_node_epoch_snapshot(epoch: VnEpoch) -> Option<ValidatorNodeEpochSnapshot>, "fetch_validator_node_epoch_snapshot");
+
+    make_async_fn!(fetch_validator_node_changes(start_epoch: VnEpoch, end_epoch: VnEpoch) -> Vec<ValidatorNodeChange>, "fetch_validator_node_changes");
+
     make_async_fn!(fetch_template_registrations<T: RangeBounds<u64>>(range: T) -> Vec<TemplateRegistrationEntry>, "fetch_template_registrations");
 
     make_async_fn!(swap_to_highest_pow_chain() -> (), "swap to highest proof-of-work chain");
@@ -341,6 +386,21 @@ impl<'a, B: BlockchainBackend + 'static> AsyncDbTransaction<'a, B> {
         self
     }
 
+    pub fn set_block_balance_sums(&mut self, height: u64, sums: BlockBalanceSums) -> &mut Self {
+        self.transaction.set_block_balance_sums(height, sums);
+        self
+    }
+
+    pub fn set_horizon_sync_checkpoint(&mut self, checkpoint: HorizonSyncCheckpoint) -> &mut Self {
+        self.transaction.set_horizon_sync_checkpoint(checkpoint);
+        self
+    }
+
+    pub fn clear_horizon_sync_checkpoint(&mut self) -> &mut Self {
+        self.transaction.clear_horizon_sync_checkpoint();
+        self
+    }
+
     pub fn insert_kernel_via_horizon_sync(
         &mut self,
         kernel: TransactionKernel,
@@ -443,4 +503,21 @@ mod tests {
         obj.fetch_total_size_stats().await.unwrap();
         let _trans = obj.write_transaction();
     }
+
+    #[tokio::test]
+    async fn it_grows_and_compacts_the_database() {
+        let obj = AsyncBlockchainDb::sample();
+        let utilization = obj.fetch_map_utilization().await.unwrap();
+        assert!(utilization.used_bytes > 0);
+        assert!(!obj.grow_map_if_required(100).await.unwrap());
+        assert!(obj.grow_map_if_required(1).await.unwrap());
+        let grown = obj.fetch_map_utilization().await.unwrap();
+        assert!(grown.map_size_bytes > utilization.map_size_bytes);
+        assert!(!grown.exceeds(1));
+
+        let result = obj.compact().await.unwrap();
+        assert!(result.path.join("data.mdb").exists());
+        assert!(result.compacted_size_bytes > 0);
+        assert!(result.compacted_size_bytes <= result.original_size_bytes);
+    }
 }
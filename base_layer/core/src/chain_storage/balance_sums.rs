@@ -0,0 +1,109 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::Commitment;
+
+use crate::{
+    chain_storage::ChainStorageError,
+    transactions::transaction_components::{TransactionKernel, TransactionOutput},
+};
+
+/// The cumulative kernel excess, unspent output commitment and burned commitment sums of the chain up to and including
+/// a block. These are the sums checked by the
+/// [ChainBalanceValidator](crate::validation::ChainBalanceValidator), and caching them per block allows the chain
+/// balance at a height to be checked without summing every kernel and output since the genesis block.
+#[derive(Clone, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct BlockBalanceSums {
+    kernel_sum: Commitment,
+    utxo_sum: Commitment,
+    burned_sum: Commitment,
+}
+
+impl BlockBalanceSums {
+    pub fn new(kernel_sum: Commitment, utxo_sum: Commitment, burned_sum: Commitment) -> Self {
+        Self {
+            kernel_sum,
+            utxo_sum,
+            burned_sum,
+        }
+    }
+
+    pub fn kernel_sum(&self) -> &Commitment {
+        &self.kernel_sum
+    }
+
+    pub fn utxo_sum(&self) -> &Commitment {
+        &self.utxo_sum
+    }
+
+    pub fn burned_sum(&self) -> &Commitment {
+        &self.burned_sum
+    }
+
+    pub fn add_kernel(&mut self, kernel: &TransactionKernel) -> Result<(), ChainStorageError> {
+        self.kernel_sum = &self.kernel_sum + &kernel.excess;
+        if kernel.is_burned() {
+            self.burned_sum = kernel.get_burn_commitment()? + &self.burned_sum;
+        }
+        Ok(())
+    }
+
+    /// Adds a new output to the UTXO sum. Burned outputs are not part of the UTXO set and are ignored, since they are
+    /// accounted for by the burn commitment of their kernel.
+    pub fn add_output(&mut self, output: &TransactionOutput) {
+        if !output.is_burned() {
+            self.add_unspent_commitment(&output.commitment);
+        }
+    }
+
+    pub fn add_unspent_commitment(&mut self, commitment: &Commitment) {
+        self.utxo_sum = commitment + &self.utxo_sum;
+    }
+
+    /// Removes the commitment of a spent output from the UTXO sum
+    pub fn spend_commitment(&mut self, commitment: &Commitment) {
+        self.utxo_sum = &self.utxo_sum - commitment;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::commitment::HomomorphicCommitmentFactory;
+
+    use super::*;
+    use crate::transactions::CryptoFactories;
+
+    #[test]
+    fn it_removes_spent_commitments_from_the_utxo_sum() {
+        let factories = CryptoFactories::default();
+        let a = factories.commitment.commit_value(&Default::default(), 10);
+        let b = factories.commitment.commit_value(&Default::default(), 20);
+        let mut sums = BlockBalanceSums::default();
+        sums.add_unspent_commitment(&a);
+        sums.add_unspent_commitment(&b);
+        sums.spend_commitment(&a);
+        assert_eq!(sums.utxo_sum(), &(&Commitment::default() + &b));
+        sums.spend_commitment(&b);
+        assert_eq!(sums.utxo_sum(), &Commitment::default());
+    }
+}
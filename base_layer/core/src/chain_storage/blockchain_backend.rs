@@ -3,13 +3,16 @@
 
 use tari_common_types::{
     chain_metadata::ChainMetadata,
-    types::{Commitment, HashOutput, PublicKey, Signature},
+    epoch::VnEpoch,
+    types::{Commitment, FixedHash, HashOutput, PublicKey, Signature},
 };
+use tari_utilities::epoch_time::EpochTime;
 
 use super::TemplateRegistrationEntry;
 use crate::{
     blocks::{Block, BlockAccumulatedData, BlockHeader, BlockHeaderAccumulatedData, ChainBlock, ChainHeader},
     chain_storage::{
+        BlockBalanceSums,
         ChainStorageError,
         DbBasicStats,
         DbKey,
@@ -17,11 +20,16 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
+        HorizonSyncCheckpoint,
         InputMinedInfo,
+        LmdbCompactionResult,
+        MapUtilization,
         MmrTree,
         OutputMinedInfo,
         Reorg,
+        ValidatorNodeEpochSnapshot,
     },
+    proof_of_work::{Difficulty, PowAlgorithm},
     transactions::transaction_components::{TransactionInput, TransactionKernel, TransactionOutput},
     OutputSmt,
 };
@@ -87,6 +95,13 @@ pub trait BlockchainBackend: Send + Sync {
         excess_sig: &Signature,
     ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>;
 
+    /// Fetch the burn kernel committing to this burned output commitment and returns a `TransactionKernel` and the
+    /// hash of the block that it is in
+    fn fetch_kernel_by_burn_commitment(
+        &self,
+        burn_commitment: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError>;
+
     /// Fetch all UTXOs and spends in the block
     fn fetch_outputs_in_block_with_spend_state(
         &self,
@@ -100,6 +115,17 @@ pub trait BlockchainBackend: Send + Sync {
     /// Fetch a specific input. Returns the input
     fn fetch_input(&self, output_hash: &HashOutput) -> Result<Option<InputMinedInfo>, ChainStorageError>;
 
+    /// Returns true if the payment reference and one-sided script key output indexes are maintained
+    fn output_indexes_enabled(&self) -> bool;
+
+    /// Fetch the outputs with the given payment references. Payment references that are not found are skipped. An
+    /// error is returned if the output indexes are not enabled.
+    fn fetch_outputs_by_payref(&self, payrefs: &[FixedHash]) -> Result<Vec<OutputMinedInfo>, ChainStorageError>;
+
+    /// Fetch the spent and unspent outputs with a one-sided or stealth one-sided script that pays to the given script
+    /// public key. An error is returned if the output indexes are not enabled.
+    fn fetch_outputs_by_script_key(&self, script_key: &PublicKey) -> Result<Vec<OutputMinedInfo>, ChainStorageError>;
+
     /// Returns the unspent TransactionOutput output that matches the given commitment if it exists in the current UTXO
     /// set, otherwise None is returned.
     fn fetch_unspent_output_hash_by_commitment(
@@ -155,14 +181,36 @@ pub trait BlockchainBackend: Send + Sync {
     /// This gets the monero seed_height. This will return 0, if the seed is unkown
     fn fetch_monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError>;
 
+    /// Fetches the persisted (timestamp, target difficulty) samples, oldest first, that make up the target difficulty
+    /// window of the given PoW algorithm for the block following `tip_hash`. Returns None if no window has been
+    /// persisted for `tip_hash`.
+    fn fetch_target_difficulty_window(
+        &self,
+        pow_algo: PowAlgorithm,
+        tip_hash: &HashOutput,
+    ) -> Result<Option<Vec<(EpochTime, Difficulty)>>, ChainStorageError>;
+
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError>;
 
+    /// Fetches the checkpoint of an interrupted horizon sync, if there is one
+    fn fetch_horizon_sync_checkpoint(&self) -> Result<Option<HorizonSyncCheckpoint>, ChainStorageError>;
+
+    /// Fetches the cumulative balance sums of the chain at the given height, if they have been cached
+    fn fetch_block_balance_sums(&self, height: u64) -> Result<Option<BlockBalanceSums>, ChainStorageError>;
+
     /// Returns basic database stats for each internal database, such as number of entries and page sizes. This call may
     /// not apply to every database implementation.
     fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError>;
     /// Returns total size information about each internal database. This call may be very slow and will obtain a read
     /// lock for the duration.
     fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError>;
+    /// Returns the utilization of the database memory map. This call may not apply to every database implementation.
+    fn fetch_map_utilization(&self) -> Result<MapUtilization, ChainStorageError>;
+    /// Grows the database memory map if more than `max_utilization_percentage` of it is in use. Returns true if the
+    /// map was grown.
+    fn grow_map_if_required(&mut self, max_utilization_percentage: u8) -> Result<bool, ChainStorageError>;
+    /// Writes a compacted copy of the database to a new file. The copy replaces the database when it is next opened.
+    fn compact(&self) -> Result<LmdbCompactionResult, ChainStorageError>;
 
     /// Check if a block hash is in the bad block list
     fn bad_block_exists(&self, block_hash: HashOutput) -> Result<(bool, String), ChainStorageError>;
@@ -175,6 +223,11 @@ pub trait BlockchainBackend: Send + Sync {
     fn fetch_active_validator_nodes(&self, height: u64) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError>;
     /// Returns the shard key for the validator node if valid at the given height.
     fn get_shard_key(&self, height: u64, public_key: PublicKey) -> Result<Option<[u8; 32]>, ChainStorageError>;
+    /// Returns the validator node set snapshot recorded at the start of the given epoch, if any.
+    fn fetch_validator_node_epoch_snapshot(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Option<ValidatorNodeEpochSnapshot>, ChainStorageError>;
     /// Returns all template registrations within (inclusive) the given height range.
     fn fetch_template_registrations(
         &self,
@@ -28,6 +28,7 @@ use std::{
     mem,
     ops::{Bound, RangeBounds},
     sync::{atomic, atomic::AtomicBool, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    thread,
     time::Instant,
 };
 
@@ -38,6 +39,7 @@ use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 use tari_common_types::{
     chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
     types::{BlockHash, Commitment, FixedHash, HashOutput, PublicKey, Signature},
 };
 use tari_hashing::TransactionHashDomain;
@@ -63,24 +65,32 @@ use crate::{
     },
     chain_storage::{
         consts::{
+            BLOCKCHAIN_DATABASE_MAX_REORG_HISTORY,
             BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY,
             BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
+            BLOCKCHAIN_DATABASE_PRUNING_BATCH_SIZE,
             BLOCKCHAIN_DATABASE_PRUNING_HORIZON,
         },
         db_transaction::{DbKey, DbTransaction, DbValue},
         error::ChainStorageError,
         utxo_mined_info::OutputMinedInfo,
         BlockAddResult,
+        BlockBalanceSums,
         BlockchainBackend,
         DbBasicStats,
         DbTotalSizeStats,
         HorizonData,
+        HorizonSyncCheckpoint,
         InputMinedInfo,
+        LmdbCompactionResult,
+        MapUtilization,
         MmrTree,
         Optional,
         OrNotFound,
         Reorg,
         TargetDifficulties,
+        ValidatorNodeChange,
+        ValidatorNodeEpochSnapshot,
     },
     common::{rolling_vec::RollingVec, BanPeriod},
     consensus::{
@@ -89,7 +99,7 @@ use crate::{
         ConsensusManager,
         DomainSeparatedConsensusHasher,
     },
-    proof_of_work::{monero_rx::MoneroPowData, PowAlgorithm, TargetDifficultyWindow},
+    proof_of_work::{monero_rx::MoneroPowData, Difficulty, DifficultyAlgorithm, PowAlgorithm, TargetDifficultyWindow},
     transactions::transaction_components::{TransactionInput, TransactionKernel, TransactionOutput},
     validation::{
         helpers::calc_median_timestamp,
@@ -115,7 +125,14 @@ pub struct BlockchainDatabaseConfig {
     pub pruning_horizon: u64,
     pub pruning_interval: u64,
     pub track_reorgs: bool,
+    /// The maximum number of reorgs that are kept when `track_reorgs` is set. The oldest reorgs are deleted first.
+    pub max_reorg_history: usize,
     pub cleanup_orphans_at_startup: bool,
+    /// Maintain indexes of the outputs by payment reference and by one-sided script public key
+    pub enable_output_indexes: bool,
+    /// The maximum number of blocks that a reorg may remove from the main chain. Deeper reorgs are rejected and the
+    /// fork is marked as bad. This is a local policy, not a consensus rule. `None` allows reorgs of any depth.
+    pub max_reorg_depth: Option<u64>,
 }
 
 impl Default for BlockchainDatabaseConfig {
@@ -125,7 +142,10 @@ impl Default for BlockchainDatabaseConfig {
             pruning_horizon: BLOCKCHAIN_DATABASE_PRUNING_HORIZON,
             pruning_interval: BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
             track_reorgs: false,
+            max_reorg_history: BLOCKCHAIN_DATABASE_MAX_REORG_HISTORY,
             cleanup_orphans_at_startup: false,
+            enable_output_indexes: false,
+            max_reorg_depth: None,
         }
     }
 }
@@ -292,10 +312,19 @@ where B: BlockchainBackend
             blockchain_db.store_pruning_horizon(config.pruning_horizon)?;
         }
 
-        if !config.track_reorgs {
+        if config.track_reorgs {
+            let mut txn = DbTransaction::new();
+            txn.prune_reorgs(config.max_reorg_history);
+            blockchain_db.write(txn)?;
+        } else {
             blockchain_db.clear_all_reorgs()?;
         }
 
+        let mut txn = DbTransaction::new();
+        txn.set_output_indexes(config.enable_output_indexes);
+        txn.set_max_reorg_depth(config.max_reorg_depth);
+        blockchain_db.write(txn)?;
+
         Ok(blockchain_db)
     }
 
@@ -406,6 +435,29 @@ where B: BlockchainBackend
         db.fetch_unspent_output_hash_by_commitment(&commitment)
     }
 
+    /// Returns true if the payment reference and one-sided script key output indexes are enabled
+    pub fn output_indexes_enabled(&self) -> Result<bool, ChainStorageError> {
+        let db = self.db_read_access()?;
+        Ok(db.output_indexes_enabled())
+    }
+
+    /// Returns the outputs with the given payment references, skipping payment references that are not found. The
+    /// output indexes must be enabled.
+    pub fn fetch_outputs_by_payref(&self, payrefs: Vec<FixedHash>) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_outputs_by_payref(&payrefs)
+    }
+
+    /// Returns the outputs with a one-sided or stealth one-sided script that pays to the given public key. The output
+    /// indexes must be enabled.
+    pub fn fetch_outputs_by_script_key(
+        &self,
+        script_key: PublicKey,
+    ) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_outputs_by_script_key(&script_key)
+    }
+
     /// Return a list of matching utxos, with each being `None` if not found. If found, the transaction
     /// output, and a boolean indicating if the UTXO was spent as of the current tip.
     pub fn fetch_outputs_with_spend_status_at_tip(
@@ -777,8 +829,17 @@ where B: BlockchainBackend
     ) -> Result<TargetDifficulties, ChainStorageError> {
         let db = self.db_read_access()?;
         let mut current_header = db.fetch_chain_header_in_all_chains(&current_block_hash)?;
-        let mut targets = TargetDifficulties::new(&self.consensus_manager, current_header.height().saturating_add(1))
+        let next_height = current_header.height().saturating_add(1);
+        let mut targets = TargetDifficulties::new(&self.consensus_manager, next_height)
             .map_err(ChainStorageError::UnexpectedResult)?;
+        targets.set_chain_tip(current_header.header());
+        for pow_algo in [PowAlgorithm::Sha3x, PowAlgorithm::RandomX] {
+            if let Some(difficulty) =
+                fetch_asert_anchor_difficulty(&*db, &self.consensus_manager, pow_algo, next_height)?
+            {
+                targets.set_anchor_difficulty(pow_algo, difficulty);
+            }
+        }
         // Add start header since we have it on hand
         targets.add_front(
             current_header.header(),
@@ -1008,7 +1069,7 @@ where B: BlockchainBackend
                 db.fetch_chain_metadata()?.best_block_height()
             );
             // If blocks were added and the node is in pruned mode, perform pruning
-            prune_database_if_needed(&mut *db, self.config.pruning_horizon, self.config.pruning_interval)?;
+            prune_database_if_needed(&mut *db, self.config.pruning_interval)?;
         }
 
         // Clean up orphan pool
@@ -1062,6 +1123,60 @@ where B: BlockchainBackend
         prune_to_height(&mut *db, height)
     }
 
+    /// Changes the pruning horizon at runtime and prunes the database to the new horizon, converting an archival node
+    /// to a pruned node if required. The database is pruned in batches of at most
+    /// [BLOCKCHAIN_DATABASE_PRUNING_BATCH_SIZE] blocks and the write lock is released between batches, so blocks can
+    /// still be validated and added while a large range is pruned. Pruned data cannot be restored, so a node that has
+    /// been pruned cannot be converted back to an archival node.
+    ///
+    /// The pruning horizon in the node configuration replaces this value when the node is restarted.
+    pub fn prune_to_horizon(&self, new_horizon: u64) -> Result<(), ChainStorageError> {
+        let metadata = self.get_chain_metadata()?;
+        if new_horizon == 0 && metadata.pruned_height() > 0 {
+            return Err(ChainStorageError::InvalidArguments {
+                func: "prune_to_horizon",
+                arg: "new_horizon",
+                message: format!(
+                    "The database has been pruned to height {} and cannot be converted to an archival node",
+                    metadata.pruned_height()
+                ),
+            });
+        }
+        if new_horizon != metadata.pruning_horizon() {
+            info!(
+                target: LOG_TARGET,
+                "Changing pruning horizon from {} to {}",
+                metadata.pruning_horizon(),
+                new_horizon
+            );
+            self.store_pruning_horizon(new_horizon)?;
+        }
+        if new_horizon == 0 {
+            return Ok(());
+        }
+
+        let timer = Instant::now();
+        loop {
+            let pruned_height = {
+                let mut db = self.db_write_access()?;
+                prune_batch_to_horizon(&mut *db, new_horizon)?
+            };
+            match pruned_height {
+                Some(height) => debug!(target: LOG_TARGET, "Pruned database to height {}", height),
+                None => break,
+            }
+            // Give writers waiting on the lock a chance to acquire it before the next batch
+            thread::yield_now();
+        }
+        info!(
+            target: LOG_TARGET,
+            "Pruned database to horizon {} in {:.2?}",
+            new_horizon,
+            timer.elapsed()
+        );
+        Ok(())
+    }
+
     /// Fetch a block from the blockchain database.
     ///
     /// # Returns
@@ -1193,6 +1308,13 @@ where B: BlockchainBackend
         rewind_to_hash(&mut *db, hash)
     }
 
+    /// Returns an error if rewinding the main chain to the given fork would exceed the configured maximum reorg depth.
+    /// Sync checks this before rewinding to the chain split with a sync peer.
+    pub fn check_reorg_depth(&self, fork_hash: BlockHash) -> Result<(), ChainStorageError> {
+        let db = self.db_read_access()?;
+        check_reorg_depth(&*db, fork_hash, self.config.max_reorg_depth)
+    }
+
     /// This method will compare all chain tips the node currently knows about. This includes
     /// all tips in the orphan pool and the main active chain. It will swap the main active
     /// chain to the highest pow chain
@@ -1215,6 +1337,19 @@ where B: BlockchainBackend
         Ok(db.fetch_horizon_data()?.unwrap_or_default())
     }
 
+    /// Returns the checkpoint of an interrupted horizon sync, if there is one
+    pub fn fetch_horizon_sync_checkpoint(&self) -> Result<Option<HorizonSyncCheckpoint>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_horizon_sync_checkpoint()
+    }
+
+    /// Returns the cumulative balance sums of the chain at the given height, or None if they have not been cached for
+    /// that height
+    pub fn fetch_block_balance_sums(&self, height: u64) -> Result<Option<BlockBalanceSums>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_block_balance_sums(height)
+    }
+
     pub fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
         let lock = self.db_read_access()?;
         lock.get_stats()
@@ -1227,11 +1362,40 @@ where B: BlockchainBackend
         lock.fetch_total_size_stats()
     }
 
+    pub fn fetch_map_utilization(&self) -> Result<MapUtilization, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_map_utilization()
+    }
+
+    /// Grows the database memory map if more than `max_utilization_percentage` of it is in use. This obtains a write
+    /// lock so that the map is never resized while a transaction is active.
+    pub fn grow_map_if_required(&self, max_utilization_percentage: u8) -> Result<bool, ChainStorageError> {
+        let mut db = self.db_write_access()?;
+        db.grow_map_if_required(max_utilization_percentage)
+    }
+
+    /// Writes a compacted copy of the database to a new file, which replaces the database when the node is restarted.
+    /// This call may be very slow and will obtain a read lock for the duration.
+    pub fn compact(&self) -> Result<LmdbCompactionResult, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.compact()
+    }
+
     pub fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError> {
         let db = self.db_read_access()?;
         db.fetch_all_reorgs()
     }
 
+    /// Returns up to `limit` of the most recent reorgs, most recent first. Reorgs are only recorded when
+    /// `track_reorgs` is set.
+    pub fn fetch_reorg_history(&self, limit: usize) -> Result<Vec<Reorg>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let mut reorgs = db.fetch_all_reorgs()?;
+        reorgs.reverse();
+        reorgs.truncate(limit);
+        Ok(reorgs)
+    }
+
     pub fn clear_all_reorgs(&self) -> Result<(), ChainStorageError> {
         let mut db = self.db_write_access()?;
         let mut txn = DbTransaction::new();
@@ -1244,6 +1408,37 @@ where B: BlockchainBackend
         db.fetch_active_validator_nodes(height)
     }
 
+    pub fn fetch_validator_node_epoch_snapshot(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Option<ValidatorNodeEpochSnapshot>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        db.fetch_validator_node_epoch_snapshot(epoch)
+    }
+
+    /// Returns the validator nodes that joined or left the active set at the start of each epoch in the inclusive
+    /// range. Epochs without a snapshot are skipped. If there is no snapshot for the epoch before `start_epoch`, all
+    /// validator nodes in the first available snapshot are reported as added.
+    pub fn fetch_validator_node_changes(
+        &self,
+        start_epoch: VnEpoch,
+        end_epoch: VnEpoch,
+    ) -> Result<Vec<ValidatorNodeChange>, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let mut previous = match start_epoch.as_u64().checked_sub(1) {
+            Some(epoch) => db.fetch_validator_node_epoch_snapshot(VnEpoch(epoch))?,
+            None => None,
+        };
+        let mut changes = Vec::new();
+        for epoch in start_epoch.as_u64()..=end_epoch.as_u64() {
+            if let Some(snapshot) = db.fetch_validator_node_epoch_snapshot(VnEpoch(epoch))? {
+                changes.extend(snapshot.changes_since(previous.as_ref()));
+                previous = Some(snapshot);
+            }
+        }
+        Ok(changes)
+    }
+
     pub fn fetch_template_registrations<T: RangeBounds<u64>>(
         &self,
         range: T,
@@ -1557,9 +1752,24 @@ pub fn fetch_target_difficulty_for_next_block<T: BlockchainBackend>(
 ) -> Result<TargetDifficultyWindow, ChainStorageError> {
     // The block may be in the chained orphan pool or in the main chain
     let mut header = db.fetch_chain_header_in_all_chains(current_block_hash)?;
+    let next_height = header.height() + 1;
     let mut target_difficulties = consensus_manager
-        .new_target_difficulty(pow_algo, header.height() + 1)
+        .new_target_difficulty(pow_algo, next_height)
         .map_err(ChainStorageError::UnexpectedResult)?;
+    target_difficulties.set_chain_tip(header.height(), header.header().timestamp());
+    if let Some(difficulty) = fetch_asert_anchor_difficulty(db, consensus_manager, pow_algo, next_height)? {
+        target_difficulties.set_anchor_difficulty(difficulty);
+    }
+    // Use the persisted window if it ends at this block, which is the case for the header chain tip
+    if let Some(samples) = db.fetch_target_difficulty_window(pow_algo, current_block_hash)? {
+        let mut persisted = target_difficulties.clone();
+        for (timestamp, difficulty) in samples {
+            persisted.add_back(timestamp, difficulty);
+        }
+        if persisted.is_full() {
+            return Ok(persisted);
+        }
+    }
     if header.header().pow.pow_algo == pow_algo {
         target_difficulties.add_front(header.header().timestamp(), header.accumulated_data().target_difficulty);
     }
@@ -1576,6 +1786,32 @@ pub fn fetch_target_difficulty_for_next_block<T: BlockchainBackend>(
     Ok(target_difficulties)
 }
 
+/// Fetches the target difficulty of the ASERT anchor for the given algorithm if ASERT is effective at `height`, which
+/// is the target difficulty of the last block of the algorithm at or below the anchor height
+fn fetch_asert_anchor_difficulty<T: BlockchainBackend>(
+    db: &T,
+    consensus_manager: &ConsensusManager,
+    pow_algo: PowAlgorithm,
+    height: u64,
+) -> Result<Option<Difficulty>, ChainStorageError> {
+    let constants = consensus_manager.consensus_constants(height);
+    let anchor_height = match constants.difficulty_algorithm() {
+        DifficultyAlgorithm::Lwma => return Ok(None),
+        DifficultyAlgorithm::Asert { anchor_height, .. } => anchor_height,
+    };
+    let mut header = db.fetch_chain_header_by_height(anchor_height)?;
+    loop {
+        if header.header().pow.pow_algo == pow_algo {
+            return Ok(Some(header.accumulated_data().target_difficulty));
+        }
+        if header.height() == 0 {
+            // The algorithm has not mined a block before the anchor
+            return Ok(Some(constants.min_pow_difficulty(pow_algo)));
+        }
+        header = db.fetch_chain_header_in_all_chains(&header.header().prev_hash)?;
+    }
+}
+
 fn fetch_block<T: BlockchainBackend>(db: &T, height: u64, compact: bool) -> Result<HistoricalBlock, ChainStorageError> {
     let mark = Instant::now();
     let (tip_height, _is_pruned) = check_for_valid_height(db, height)?;
@@ -1889,7 +2125,21 @@ fn reorganize_chain<T: BlockchainBackend>(
     fork_hash: HashOutput,
     new_chain_from_fork: &VecDeque<Arc<ChainBlock>>,
     consensus: &ConsensusManager,
+    max_reorg_depth: Option<u64>,
 ) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
+    if let Err(e) = check_reorg_depth(backend, fork_hash, max_reorg_depth) {
+        // Remove the fork from the orphan pool, otherwise it is selected as the strongest orphan chain again on every
+        // block add
+        let mut txn = DbTransaction::new();
+        if let Some(block) = new_chain_from_fork.front() {
+            txn.insert_bad_block(*block.hash(), block.height(), e.to_string());
+        }
+        for block in new_chain_from_fork {
+            txn.delete_orphan(*block.hash());
+        }
+        backend.write(txn)?;
+        return Err(e);
+    }
     let removed_blocks = rewind_to_hash(backend, fork_hash)?;
     debug!(
         target: LOG_TARGET,
@@ -1948,6 +2198,46 @@ fn reorganize_chain<T: BlockchainBackend>(
     Ok(removed_blocks)
 }
 
+/// Rejects a reorg to the given fork if it would remove more blocks from the main chain than the configured maximum
+/// reorg depth allows.
+fn check_reorg_depth<T: BlockchainBackend>(
+    backend: &T,
+    fork_hash: HashOutput,
+    max_reorg_depth: Option<u64>,
+) -> Result<(), ChainStorageError> {
+    let max_depth = match max_reorg_depth {
+        Some(max_depth) => max_depth,
+        None => return Ok(()),
+    };
+    let tip_height = backend.fetch_chain_metadata()?.best_block_height();
+    let fork_height = fetch_header_by_block_hash(backend, fork_hash)?
+        .ok_or_else(|| ChainStorageError::ValueNotFound {
+            entity: "BlockHeader",
+            field: "block_hash",
+            value: fork_hash.to_hex(),
+        })?
+        .height;
+    let depth = tip_height.saturating_sub(fork_height);
+    if depth > max_depth {
+        error!(
+            target: LOG_TARGET,
+            "Rejecting reorg to fork {} at height {}: {} block(s) would be removed from tip #{}, but the maximum \
+             allowed reorg depth is {}",
+            fork_hash,
+            fork_height,
+            depth,
+            tip_height,
+            max_depth
+        );
+        return Err(ChainStorageError::ReorgDepthExceeded {
+            fork_height,
+            depth,
+            max_depth,
+        });
+    }
+    Ok(())
+}
+
 fn swap_to_highest_pow_chain<T: BlockchainBackend>(
     db: &mut T,
     config: &BlockchainDatabaseConfig,
@@ -2010,7 +2300,14 @@ fn swap_to_highest_pow_chain<T: BlockchainBackend>(
         .prev_hash;
 
     let num_added_blocks = reorg_chain.len();
-    let removed_blocks = reorganize_chain(db, block_validator, fork_hash, &reorg_chain, consensus)?;
+    let removed_blocks = reorganize_chain(
+        db,
+        block_validator,
+        fork_hash,
+        &reorg_chain,
+        consensus,
+        config.max_reorg_depth,
+    )?;
     let num_removed_blocks = removed_blocks.len();
 
     // reorg is required when any blocks are removed or more than one are added
@@ -2018,7 +2315,8 @@ fn swap_to_highest_pow_chain<T: BlockchainBackend>(
     if num_removed_blocks > 0 || num_added_blocks > 1 {
         if config.track_reorgs {
             let mut txn = DbTransaction::new();
-            txn.insert_reorg(Reorg::from_reorged_blocks(&reorg_chain, &removed_blocks));
+            txn.insert_reorg(Reorg::from_reorged_blocks(&reorg_chain, &removed_blocks))
+                .prune_reorgs(config.max_reorg_history);
             if let Err(e) = db.write(txn) {
                 error!(target: LOG_TARGET, "Failed to track reorg: {}", e);
             }
@@ -2368,16 +2666,14 @@ fn cleanup_orphans<T: BlockchainBackend>(db: &mut T, orphan_storage_capacity: us
     db.delete_oldest_orphans(horizon_height, orphan_storage_capacity)
 }
 
-fn prune_database_if_needed<T: BlockchainBackend>(
-    db: &mut T,
-    pruning_horizon: u64,
-    pruning_interval: u64,
-) -> Result<(), ChainStorageError> {
+fn prune_database_if_needed<T: BlockchainBackend>(db: &mut T, pruning_interval: u64) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     if !metadata.is_pruned_node() {
         return Ok(());
     }
 
+    // The stored horizon is used rather than the configured one, since it may have been changed at runtime
+    let pruning_horizon = metadata.pruning_horizon();
     let prune_to_height_target = metadata.best_block_height().saturating_sub(pruning_horizon);
     debug!(
         target: LOG_TARGET,
@@ -2389,12 +2685,33 @@ fn prune_database_if_needed<T: BlockchainBackend>(
         pruning_interval,
     );
     if metadata.pruned_height() < prune_to_height_target.saturating_sub(pruning_interval) {
-        prune_to_height(db, prune_to_height_target)?;
+        // Limit the time the write lock is held for a single block. Any remaining blocks are pruned when the next
+        // blocks are added.
+        prune_batch_to_horizon(db, pruning_horizon)?;
     }
 
     Ok(())
 }
 
+/// Prunes at most [BLOCKCHAIN_DATABASE_PRUNING_BATCH_SIZE] blocks towards the given pruning horizon. Returns the new
+/// pruned height, or None if the database is already pruned to the horizon.
+fn prune_batch_to_horizon<T: BlockchainBackend>(
+    db: &mut T,
+    pruning_horizon: u64,
+) -> Result<Option<u64>, ChainStorageError> {
+    let metadata = db.fetch_chain_metadata()?;
+    let target_height = metadata.best_block_height().saturating_sub(pruning_horizon);
+    if target_height <= metadata.pruned_height() {
+        return Ok(None);
+    }
+    let batch_height = cmp::min(
+        target_height,
+        metadata.pruned_height() + BLOCKCHAIN_DATABASE_PRUNING_BATCH_SIZE,
+    );
+    prune_to_height(db, batch_height)?;
+    Ok(Some(batch_height))
+}
+
 fn prune_to_height<T: BlockchainBackend>(db: &mut T, target_horizon_height: u64) -> Result<(), ChainStorageError> {
     let metadata = db.fetch_chain_metadata()?;
     let last_pruned = metadata.pruned_height();
@@ -2561,6 +2878,32 @@ mod test {
         }
     }
 
+    #[tokio::test]
+    async fn it_persists_the_target_difficulty_window_of_the_tip() {
+        let db = create_new_blockchain();
+        let (_, chain) = create_main_chain(&db, &[("A->GB", 1, 120), ("B->A", 1, 120), ("C->B", 1, 120)]).await;
+        let tip = chain.get("C").unwrap();
+        let pow_algo = tip.header().pow_algo();
+        let expected = (0..=tip.height())
+            .map(|height| db.fetch_chain_header(height).unwrap())
+            .filter(|header| header.header().pow_algo() == pow_algo)
+            .map(|header| (header.header().timestamp(), header.accumulated_data().target_difficulty))
+            .collect::<Vec<_>>();
+
+        let access = db.db_read_access().unwrap();
+        let samples = access
+            .fetch_target_difficulty_window(pow_algo, tip.hash())
+            .unwrap()
+            .unwrap();
+        assert_eq!(samples, expected);
+        // Only the window for the tip is persisted
+        let block_b = chain.get("B").unwrap();
+        assert!(access
+            .fetch_target_difficulty_window(pow_algo, block_b.hash())
+            .unwrap()
+            .is_none());
+    }
+
     mod get_orphan_link_main_chain {
         use super::*;
 
@@ -3233,6 +3576,74 @@ mod test {
         check_whole_chain(&mut access);
     }
 
+    #[tokio::test]
+    async fn test_handle_possible_reorg_records_reorg_history() {
+        let db = create_new_blockchain();
+        let mut smt = db.fetch_tip_smt().unwrap();
+        let (_, mainchain) = create_main_chain(&db, &[("A->GB", 1, 120), ("B->A", 1, 120), ("C->B", 1, 120)]).await;
+
+        let mock_validator = MockValidator::new(true);
+        let chain_strength_comparer = strongest_chain().by_sha3x_difficulty().build();
+        let config = BlockchainDatabaseConfig {
+            track_reorgs: true,
+            max_reorg_history: 1,
+            ..Default::default()
+        };
+
+        let mut a_block = mainchain.get("A").unwrap().block().clone();
+        let fork_block = mainchain.get("A").unwrap().clone();
+        update_block_and_smt(&mut a_block, &mut smt);
+        let (_, reorg_chain) = create_chained_blocks(
+            &[("B2->GB", 1, 120), ("C2->B2", 1, 120), ("D2->C2", 1, 120)],
+            fork_block,
+            &mut smt,
+        )
+        .await;
+
+        {
+            let mut access = db.db_write_access().unwrap();
+            for name in ["D2", "C2", "B2"] {
+                let result = handle_possible_reorg(
+                    &mut *access,
+                    &config,
+                    &db.consensus_manager,
+                    &mock_validator,
+                    &mock_validator,
+                    &*chain_strength_comparer,
+                    reorg_chain.get(name).unwrap().to_arc_block(),
+                )
+                .unwrap();
+                if name == "B2" {
+                    result.assert_reorg(3, 2);
+                } else {
+                    result.assert_orphaned();
+                }
+            }
+        }
+
+        let history = db.fetch_reorg_history(10).unwrap();
+        assert_eq!(history.len(), 1);
+        let reorg = &history[0];
+        assert_eq!(reorg.num_blocks_added, 3);
+        assert_eq!(reorg.num_blocks_removed, 2);
+        let added = ["B2", "C2", "D2"].map(|name| *reorg_chain.get(name).unwrap().hash());
+        assert_eq!(reorg.added_blocks, added.to_vec());
+        let removed = ["C", "B"].map(|name| *mainchain.get(name).unwrap().hash());
+        assert_eq!(reorg.removed_blocks, removed.to_vec());
+        // The blocks only contain coinbase transactions
+        assert!(reorg.reinstated_transactions.is_empty());
+
+        // Only the most recent reorg is kept
+        let mut newer_reorg = reorg.clone();
+        newer_reorg.local_time += chrono::Duration::seconds(1);
+        newer_reorg.num_blocks_added = 1;
+        let mut txn = DbTransaction::new();
+        txn.insert_reorg(newer_reorg.clone()).prune_reorgs(1);
+        db.write(txn).unwrap();
+        assert_eq!(db.fetch_reorg_history(10).unwrap(), vec![newer_reorg]);
+        assert!(db.fetch_reorg_history(0).unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_handle_possible_reorg_case7_fail_reorg() {
         let db = create_new_blockchain();
@@ -3285,6 +3696,88 @@ mod test {
         check_whole_chain(&mut access);
     }
 
+    #[tokio::test]
+    async fn test_handle_possible_reorg_rejects_reorg_deeper_than_max_depth() {
+        let db = create_new_blockchain();
+        let (_, mainchain) = create_main_chain(&db, &[
+            ("A->GB", 1, 120),
+            ("B->A", 1, 120),
+            ("C->B", 1, 120),
+            ("D->C", 1, 120),
+        ])
+        .await;
+
+        let mock_validator = MockValidator::new(true);
+        let chain_strength_comparer = strongest_chain().by_sha3x_difficulty().build();
+        let config = BlockchainDatabaseConfig {
+            max_reorg_depth: Some(2),
+            ..Default::default()
+        };
+        let mut smt = db.fetch_tip_smt().unwrap();
+        let fork_block = mainchain.get("A").unwrap().clone();
+        let (_, reorg_chain) = create_chained_blocks(
+            &[
+                ("B2->GB", 1, 120),
+                ("C2->B2", 1, 120),
+                ("D2->C2", 1, 120),
+                ("E2->D2", 1, 120),
+            ],
+            fork_block,
+            &mut smt,
+        )
+        .await;
+
+        let mut access = db.db_write_access().unwrap();
+        for name in ["E2", "D2", "C2"] {
+            let result = handle_possible_reorg(
+                &mut *access,
+                &config,
+                &db.consensus_manager,
+                &mock_validator,
+                &mock_validator,
+                &*chain_strength_comparer,
+                reorg_chain.get(name).unwrap().to_arc_block(),
+            )
+            .unwrap();
+            result.assert_orphaned();
+        }
+
+        let err = handle_possible_reorg(
+            &mut *access,
+            &config,
+            &db.consensus_manager,
+            &mock_validator,
+            &mock_validator,
+            &*chain_strength_comparer,
+            reorg_chain.get("B2").unwrap().to_arc_block(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ChainStorageError::ReorgDepthExceeded {
+            fork_height: 1,
+            depth: 3,
+            max_depth: 2
+        }));
+
+        // The main chain is untouched
+        let tip = access.fetch_last_header().unwrap();
+        assert_eq!(&tip, mainchain.get("D").unwrap().header());
+        check_whole_chain(&mut access);
+
+        // The fork is marked as bad and is no longer selected from the orphan pool
+        let (is_bad, _) = access.bad_block_exists(*reorg_chain.get("B2").unwrap().hash()).unwrap();
+        assert!(is_bad);
+        assert!(access.fetch_strongest_orphan_chain_tips().unwrap().is_empty());
+        let result = swap_to_highest_pow_chain(
+            &mut *access,
+            &config,
+            &mock_validator,
+            &*chain_strength_comparer,
+            &db.consensus_manager,
+        )
+        .unwrap();
+        result.assert_orphaned();
+    }
+
     #[tokio::test]
     async fn test_handle_possible_reorg_target_difficulty_is_correct_case_1() {
         let (result, _blocks) = test_case_handle_possible_reorg(&[
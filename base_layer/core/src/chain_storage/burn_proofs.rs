@@ -0,0 +1,89 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use log::*;
+use tari_common_types::types::Commitment;
+use tari_mmr::{common::LeafIndex, MerkleProof};
+use tari_utilities::hex::Hex;
+
+use crate::{
+    burn_proof::BurnProof,
+    chain_storage::{BlockchainBackend, BlockchainDatabase, ChainStorageError},
+    KernelMmr,
+};
+
+const LOG_TARGET: &str = "c::cs::burn_proofs";
+
+impl<B: BlockchainBackend> BlockchainDatabase<B> {
+    /// Produces a proof that the output with the given commitment was burned, which can be verified against the header
+    /// of the block it was burned in with [BurnProof::verify]. Only the peaks of the kernel MMR are stored, so the MMR
+    /// is rebuilt from the kernels of every block up to and including the block containing the burn, which takes
+    /// longer the higher that block is.
+    pub fn fetch_burn_proof(&self, burn_commitment: Commitment) -> Result<BurnProof, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let (kernel, header_hash) =
+            db.fetch_kernel_by_burn_commitment(&burn_commitment)?
+                .ok_or_else(|| ChainStorageError::ValueNotFound {
+                    entity: "Burn kernel",
+                    field: "burn_commitment",
+                    value: burn_commitment.to_hex(),
+                })?;
+        let header = db.fetch_chain_header_in_all_chains(&header_hash)?.into_header();
+
+        let kernel_hash = kernel.hash();
+        let mut kernel_mmr = KernelMmr::new(Vec::new());
+        let mut leaf_index = None;
+        for height in 0..=header.height {
+            let block_hash = *db.fetch_chain_header_by_height(height)?.hash();
+            for block_kernel in db.fetch_kernels_in_block(&block_hash)? {
+                let hash = block_kernel.hash();
+                if height == header.height && hash == kernel_hash {
+                    leaf_index = Some(kernel_mmr.get_leaf_count()?);
+                }
+                kernel_mmr.push(hash.to_vec())?;
+            }
+        }
+        let leaf_index = leaf_index.ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+            function: "fetch_burn_proof",
+            details: format!(
+                "Burn kernel {} was not found in block {} at height {}",
+                kernel_hash, header_hash, header.height
+            ),
+        })?;
+        debug!(
+            target: LOG_TARGET,
+            "Rebuilt the kernel MMR with {} kernels up to height {}",
+            kernel_mmr.get_leaf_count()?,
+            header.height
+        );
+
+        let merkle_proof = MerkleProof::for_leaf_node(&kernel_mmr, LeafIndex(leaf_index))?;
+        Ok(BurnProof {
+            header,
+            kernel,
+            leaf_index: u64::try_from(leaf_index).map_err(|_| ChainStorageError::OutOfRange)?,
+            merkle_proof,
+        })
+    }
+}
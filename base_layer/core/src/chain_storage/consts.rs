@@ -26,3 +26,9 @@ pub const BLOCKCHAIN_DATABASE_ORPHAN_STORAGE_CAPACITY: usize = 720;
 pub const BLOCKCHAIN_DATABASE_PRUNING_HORIZON: u64 = 0;
 /// The chain height interval used to determine when a pruned node should perform pruning.
 pub const BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL: u64 = 50;
+/// The maximum number of blocks that are pruned while the database write lock is held.
+pub const BLOCKCHAIN_DATABASE_PRUNING_BATCH_SIZE: u64 = 500;
+/// The number of blocks whose MMR leaves are hashed in parallel when the MMRs are reconstructed.
+pub const BLOCKCHAIN_DATABASE_MMR_RECONSTRUCTION_CHUNK_SIZE: u64 = 1000;
+/// The maximum number of reorgs that are kept in the reorg history.
+pub const BLOCKCHAIN_DATABASE_MAX_REORG_HISTORY: usize = 1000;
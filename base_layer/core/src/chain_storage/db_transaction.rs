@@ -32,7 +32,7 @@ use tari_utilities::hex::Hex;
 
 use crate::{
     blocks::{Block, BlockHeader, BlockHeaderAccumulatedData, ChainBlock, ChainHeader, UpdateBlockAccumulatedData},
-    chain_storage::{error::ChainStorageError, HorizonData, Reorg},
+    chain_storage::{error::ChainStorageError, BlockBalanceSums, HorizonData, HorizonSyncCheckpoint, Reorg},
     transactions::transaction_components::{OutputType, TransactionKernel, TransactionOutput},
     OutputSmt,
 };
@@ -80,6 +80,12 @@ impl DbTransaction {
         self
     }
 
+    /// Delete a block body that is stored above the chain tip, without updating the tip output SMT
+    pub fn delete_dangling_block_body(&mut self, height: u64) -> &mut Self {
+        self.operations.push(WriteOperation::DeleteDanglingBlockBody(height));
+        self
+    }
+
     /// Delete a block
     pub fn delete_tip_block(&mut self, block_hash: HashOutput) -> &mut Self {
         self.operations.push(WriteOperation::DeleteTipBlock(block_hash));
@@ -259,10 +265,47 @@ impl DbTransaction {
         self
     }
 
+    /// Stores the progress of a horizon sync, replacing any previous checkpoint
+    pub fn set_horizon_sync_checkpoint(&mut self, checkpoint: HorizonSyncCheckpoint) -> &mut Self {
+        self.operations.push(WriteOperation::SetHorizonSyncCheckpoint {
+            checkpoint: Some(checkpoint),
+        });
+        self
+    }
+
+    /// Removes the horizon sync checkpoint, if there is one
+    pub fn clear_horizon_sync_checkpoint(&mut self) -> &mut Self {
+        self.operations
+            .push(WriteOperation::SetHorizonSyncCheckpoint { checkpoint: None });
+        self
+    }
+
+    /// Stores the cumulative balance sums of the chain at the given height. The sums of the blocks that follow are
+    /// calculated from these as the blocks are added.
+    pub fn set_block_balance_sums(&mut self, height: u64, sums: BlockBalanceSums) -> &mut Self {
+        self.operations
+            .push(WriteOperation::SetBlockBalanceSums { height, sums });
+        self
+    }
+
     pub(crate) fn operations(&self) -> &[WriteOperation] {
         &self.operations
     }
 
+    /// Moves the operations of `other` onto the end of this transaction, so that both are committed atomically
+    pub fn append(&mut self, mut other: DbTransaction) -> &mut Self {
+        self.operations.append(&mut other.operations);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
     /// This will store the seed key with the height. This is called when a block is accepted into the main chain.
     /// This will only update the hieght of the seed, if its lower then currently stored.
     pub fn insert_monero_seed_height(&mut self, monero_seed: Vec<u8>, height: u64) {
@@ -280,6 +323,26 @@ impl DbTransaction {
         self
     }
 
+    /// Deletes the oldest reorgs so that at most `max_entries` reorgs are kept
+    pub fn prune_reorgs(&mut self, max_entries: usize) -> &mut Self {
+        self.operations.push(WriteOperation::PruneReorgs { max_entries });
+        self
+    }
+
+    /// Enables or disables the payment reference and one-sided script key output indexes. Enabling the indexes builds
+    /// them from the outputs that are already stored, disabling them deletes them.
+    pub fn set_output_indexes(&mut self, enabled: bool) -> &mut Self {
+        self.operations.push(WriteOperation::SetOutputIndexes { enabled });
+        self
+    }
+
+    /// Sets the locally configured maximum reorg depth. This is not persisted.
+    pub fn set_max_reorg_depth(&mut self, max_reorg_depth: Option<u64>) -> &mut Self {
+        self.operations
+            .push(WriteOperation::SetMaxReorgDepth { max_reorg_depth });
+        self
+    }
+
     pub fn insert_tip_smt(&mut self, smt: OutputSmt) -> &mut Self {
         self.operations.push(WriteOperation::InsertTipSmt { smt });
         self
@@ -316,6 +379,7 @@ pub enum WriteOperation {
     DeleteHeader(u64),
     DeleteOrphan(HashOutput),
     DeleteTipBlock(HashOutput),
+    DeleteDanglingBlockBody(u64),
     DeleteOrphanChainTip(HashOutput),
     InsertOrphanChainTip(HashOutput, U256),
     InsertMoneroSeedHeight(Vec<u8>, u64),
@@ -352,10 +416,26 @@ pub enum WriteOperation {
     SetHorizonData {
         horizon_data: HorizonData,
     },
+    SetHorizonSyncCheckpoint {
+        checkpoint: Option<HorizonSyncCheckpoint>,
+    },
+    SetBlockBalanceSums {
+        height: u64,
+        sums: BlockBalanceSums,
+    },
     InsertReorg {
         reorg: Reorg,
     },
     ClearAllReorgs,
+    PruneReorgs {
+        max_entries: usize,
+    },
+    SetOutputIndexes {
+        enabled: bool,
+    },
+    SetMaxReorgDepth {
+        max_reorg_depth: Option<u64>,
+    },
     InsertTipSmt {
         smt: OutputSmt,
     },
@@ -409,6 +489,7 @@ impl fmt::Display for WriteOperation {
                 write!(f, "InsertOrphanChainTip({}, {})", hash, total_accumulated_difficulty)
             },
             DeleteTipBlock(hash) => write!(f, "DeleteTipBlock({})", hash),
+            DeleteDanglingBlockBody(height) => write!(f, "DeleteDanglingBlockBody({})", height),
             InsertMoneroSeedHeight(data, height) => {
                 write!(f, "Insert Monero seed string {} for height: {}", data.to_hex(), height)
             },
@@ -452,8 +533,16 @@ impl fmt::Display for WriteOperation {
                 write!(f, "Insert bad block #{} {} for {}", height, hash, reason)
             },
             SetHorizonData { .. } => write!(f, "Set horizon data"),
+            SetHorizonSyncCheckpoint {
+                checkpoint: Some(checkpoint),
+            } => write!(f, "Set horizon sync checkpoint at height {}", checkpoint.height),
+            SetHorizonSyncCheckpoint { checkpoint: None } => write!(f, "Clear horizon sync checkpoint"),
+            SetBlockBalanceSums { height, .. } => write!(f, "Set block balance sums at height {}", height),
             InsertReorg { .. } => write!(f, "Insert reorg"),
             ClearAllReorgs => write!(f, "Clear all reorgs"),
+            PruneReorgs { max_entries } => write!(f, "Prune reorgs to {} entries", max_entries),
+            SetOutputIndexes { enabled } => write!(f, "Set output indexes enabled to {}", enabled),
+            SetMaxReorgDepth { max_reorg_depth } => write!(f, "Set max reorg depth to {:?}", max_reorg_depth),
             InsertTipSmt { smt: output_smt } => {
                 write!(
                     f,
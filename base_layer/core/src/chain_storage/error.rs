@@ -141,6 +141,19 @@ pub enum ChainStorageError {
     SMTError(#[from] SMTError),
     #[error("Invalid ChainMetaData: {0}")]
     InvalidChainMetaData(#[from] ChainMetaDataError),
+    #[error("Invalid UTXO snapshot: {0}")]
+    InvalidUtxoSnapshot(String),
+    #[error("Remote blockchain backend error: {0}")]
+    RemoteBackendError(String),
+    #[error(
+        "Reorg from fork height {fork_height} would remove {depth} block(s), which exceeds the maximum allowed reorg \
+         depth of {max_depth}"
+    )]
+    ReorgDepthExceeded {
+        fork_height: u64,
+        depth: u64,
+        max_depth: u64,
+    },
 }
 
 impl ChainStorageError {
@@ -193,6 +206,9 @@ impl ChainStorageError {
             _err @ ChainStorageError::CompositeKeyLengthExceeded |
             _err @ ChainStorageError::FromKeyBytesFailed(_) |
             _err @ ChainStorageError::InvalidChainMetaData(_) |
+            _err @ ChainStorageError::InvalidUtxoSnapshot(_) |
+            _err @ ChainStorageError::RemoteBackendError(_) |
+            _err @ ChainStorageError::ReorgDepthExceeded { .. } |
             _err @ ChainStorageError::OutOfRange => None,
         }
     }
@@ -0,0 +1,83 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, HashOutput};
+
+/// The progress of an interrupted horizon sync, persisted after each verified chunk of TXOs so that the output sync
+/// can be resumed from the block following [HorizonSyncCheckpoint::height] instead of starting again from the local
+/// tip.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HorizonSyncCheckpoint {
+    /// The hash of the header that the horizon sync is synchronizing to
+    pub target_header_hash: HashOutput,
+    /// The height of the local tip that the output sync started from
+    pub start_height: u64,
+    /// The height of the last block whose TXOs have all been received and committed
+    pub height: u64,
+    /// The size of the kernel MMR when the checkpoint was written
+    pub kernel_mmr_position: u64,
+    /// The outputs spent in the synced blocks. These are only pruned once the output SMT has been verified at the
+    /// target header.
+    pub spent_outputs: Vec<(HashOutput, Commitment)>,
+    /// The sum of the commitments of the unspent outputs received so far
+    pub added_sum: Commitment,
+    /// The sum of the commitments of the spent outputs received so far
+    pub spent_sum: Commitment,
+    pub utxo_count: u64,
+    pub stxo_count: u64,
+}
+
+impl HorizonSyncCheckpoint {
+    /// Creates the checkpoint of an output sync to `target_header_hash` that has not received any TXOs yet
+    pub fn new(target_header_hash: HashOutput, start_height: u64, kernel_mmr_position: u64) -> Self {
+        Self {
+            target_header_hash,
+            start_height,
+            height: start_height,
+            kernel_mmr_position,
+            spent_outputs: Vec::new(),
+            added_sum: Commitment::default(),
+            spent_sum: Commitment::default(),
+            utxo_count: 0,
+            stxo_count: 0,
+        }
+    }
+
+    /// Returns true if this checkpoint was written by an output sync to `target_header_hash` that started from the
+    /// local tip at `start_height` with `kernel_mmr_position` kernels synced
+    pub fn is_resumable_for(
+        &self,
+        target_header_hash: &HashOutput,
+        start_height: u64,
+        kernel_mmr_position: u64,
+    ) -> bool {
+        self.target_header_hash == *target_header_hash &&
+            self.start_height == start_height &&
+            self.kernel_mmr_position == kernel_mmr_position
+    }
+
+    /// The number of TXOs that have been received
+    pub fn txo_count(&self) -> u64 {
+        self.utxo_count + self.stxo_count
+    }
+}
@@ -0,0 +1,393 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Error, Formatter},
+};
+
+use log::*;
+use tari_common_types::types::FixedHash;
+
+use crate::{
+    blocks::ChainHeader,
+    chain_storage::{BlockchainBackend, BlockchainDatabase, ChainStorageError, DbTransaction, Optional},
+    PrunedKernelMmr,
+};
+
+const LOG_TARGET: &str = "c::cs::integrity";
+
+/// An inconsistency found by [BlockchainDatabase::check_integrity]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// There is no header on the main chain at a height below the chain tip
+    MissingHeader { height: u64 },
+    /// There is a header on the main chain at a height below the chain tip, but no block body
+    MissingBlockBody { height: u64 },
+    /// A block body is stored above the chain tip
+    DanglingBlockBody { height: u64 },
+    /// The number of leaves in the stored kernel MMR does not match the header
+    KernelMmrSizeMismatch { height: u64, expected: u64, actual: u64 },
+    /// The root of the stored kernel MMR does not match the header
+    KernelMmrRootMismatch {
+        height: u64,
+        expected: FixedHash,
+        actual: FixedHash,
+    },
+    /// The output SMT stored at the tip does not match the tip header
+    OutputSmtMismatch {
+        height: u64,
+        expected_root: FixedHash,
+        actual_root: FixedHash,
+    },
+    /// The chain metadata points at a block that is not the main chain header at the tip height
+    StaleTipMetadata {
+        height: u64,
+        expected_hash: FixedHash,
+        actual_hash: FixedHash,
+    },
+}
+
+impl IntegrityIssue {
+    pub fn height(&self) -> u64 {
+        match self {
+            IntegrityIssue::MissingHeader { height } |
+            IntegrityIssue::MissingBlockBody { height } |
+            IntegrityIssue::DanglingBlockBody { height } |
+            IntegrityIssue::KernelMmrSizeMismatch { height, .. } |
+            IntegrityIssue::KernelMmrRootMismatch { height, .. } |
+            IntegrityIssue::OutputSmtMismatch { height, .. } |
+            IntegrityIssue::StaleTipMetadata { height, .. } => *height,
+        }
+    }
+
+    /// Returns true if the chain data at this height is lost or corrupt, i.e. it cannot be fixed without removing the
+    /// block at this height and every block above it
+    pub fn requires_truncation(&self) -> bool {
+        matches!(
+            self,
+            IntegrityIssue::MissingHeader { .. } |
+                IntegrityIssue::MissingBlockBody { .. } |
+                IntegrityIssue::KernelMmrSizeMismatch { .. } |
+                IntegrityIssue::KernelMmrRootMismatch { .. }
+        )
+    }
+}
+
+impl Display for IntegrityIssue {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            IntegrityIssue::MissingHeader { height } => write!(f, "Missing header at height {}", height),
+            IntegrityIssue::MissingBlockBody { height } => write!(f, "Missing block body at height {}", height),
+            IntegrityIssue::DanglingBlockBody { height } => {
+                write!(f, "Block body at height {} is above the chain tip", height)
+            },
+            IntegrityIssue::KernelMmrSizeMismatch {
+                height,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Kernel MMR at height {} has {} leaves but the header expects {}",
+                height, actual, expected
+            ),
+            IntegrityIssue::KernelMmrRootMismatch {
+                height,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Kernel MMR root at height {} is {} but the header expects {}",
+                height, actual, expected
+            ),
+            IntegrityIssue::OutputSmtMismatch {
+                height,
+                expected_root,
+                actual_root,
+            } => write!(
+                f,
+                "Output SMT root at height {} is {} but the header expects {}",
+                height, actual_root, expected_root
+            ),
+            IntegrityIssue::StaleTipMetadata {
+                height,
+                expected_hash,
+                actual_hash,
+            } => write!(
+                f,
+                "Chain metadata tip at height {} is {} but the header at that height is {}",
+                height, actual_hash, expected_hash
+            ),
+        }
+    }
+}
+
+/// The result of [BlockchainDatabase::check_integrity]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The best block height in the chain metadata when the check was run
+    pub tip_height: u64,
+    /// The highest height up to which the chain data is complete, or None if the genesis block is not intact
+    pub last_consistent_height: Option<u64>,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn has_issue<F: Fn(&IntegrityIssue) -> bool>(&self, predicate: F) -> bool {
+        self.issues.iter().any(predicate)
+    }
+}
+
+impl Display for IntegrityReport {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        if self.is_consistent() {
+            return write!(f, "Database is consistent up to tip height {}", self.tip_height);
+        }
+        write!(
+            f,
+            "Found {} issue(s) up to tip height {}, last consistent height: ",
+            self.issues.len(),
+            self.tip_height
+        )?;
+        match self.last_consistent_height {
+            Some(height) => writeln!(f, "{}", height)?,
+            None => writeln!(f, "none")?,
+        }
+        for issue in &self.issues {
+            writeln!(f, "- {}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B: BlockchainBackend> BlockchainDatabase<B> {
+    /// Scans the main chain for data that is missing or does not match the header chain. Every height up to the tip in
+    /// the chain metadata must have a header and a block body whose kernel MMR matches the header, the metadata must
+    /// point at the header at the tip height, no block bodies may be stored above the tip and the tip output SMT must
+    /// match the tip header. The database is read one block at a time, so other users are not blocked for the
+    /// duration of the check.
+    pub fn check_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        let metadata = self.get_chain_metadata()?;
+        let tip_height = metadata.best_block_height();
+        let mut issues = Vec::new();
+
+        let mut tip_header = None;
+        for height in 0..=tip_height {
+            let header = match self.fetch_chain_header(height).optional()? {
+                Some(header) => header,
+                None => {
+                    issues.push(IntegrityIssue::MissingHeader { height });
+                    continue;
+                },
+            };
+            if let Some(issue) = self.check_block_body(&header)? {
+                issues.push(issue);
+            }
+            if height == tip_height {
+                tip_header = Some(header);
+            }
+        }
+
+        if let Some(header) = &tip_header {
+            if header.hash() != metadata.best_block_hash() {
+                issues.push(IntegrityIssue::StaleTipMetadata {
+                    height: tip_height,
+                    expected_hash: *header.hash(),
+                    actual_hash: *metadata.best_block_hash(),
+                });
+            }
+            let output_smt = self.fetch_tip_smt()?;
+            let actual_root = FixedHash::try_from(output_smt.hash().as_slice())?;
+            if actual_root != header.header().output_mr {
+                issues.push(IntegrityIssue::OutputSmtMismatch {
+                    height: tip_height,
+                    expected_root: header.header().output_mr,
+                    actual_root,
+                });
+            }
+        }
+
+        let mut height = tip_height + 1;
+        while self
+            .fetch_block_accumulated_data_by_height(height)
+            .optional()?
+            .is_some()
+        {
+            issues.push(IntegrityIssue::DanglingBlockBody { height });
+            height += 1;
+        }
+
+        let last_consistent_height = match issues
+            .iter()
+            .filter(|issue| issue.requires_truncation())
+            .map(|issue| issue.height())
+            .min()
+        {
+            Some(height) => height.checked_sub(1),
+            None => Some(tip_height),
+        };
+        let report = IntegrityReport {
+            tip_height,
+            last_consistent_height,
+            issues,
+        };
+        if report.is_consistent() {
+            debug!(target: LOG_TARGET, "{}", report);
+        } else {
+            warn!(target: LOG_TARGET, "{}", report);
+        }
+        Ok(report)
+    }
+
+    fn check_block_body(&self, header: &ChainHeader) -> Result<Option<IntegrityIssue>, ChainStorageError> {
+        let height = header.height();
+        let accumulated_data = match self.fetch_block_accumulated_data_by_height(height).optional()? {
+            Some(accumulated_data) => accumulated_data,
+            None => return Ok(Some(IntegrityIssue::MissingBlockBody { height })),
+        };
+        let kernel_mmr = PrunedKernelMmr::new(accumulated_data.dissolve());
+        let actual = kernel_mmr.get_leaf_count()? as u64;
+        if actual != header.header().kernel_mmr_size {
+            return Ok(Some(IntegrityIssue::KernelMmrSizeMismatch {
+                height,
+                expected: header.header().kernel_mmr_size,
+                actual,
+            }));
+        }
+        let actual = FixedHash::try_from(kernel_mmr.get_merkle_root()?)?;
+        if actual != header.header().kernel_mr {
+            return Ok(Some(IntegrityIssue::KernelMmrRootMismatch {
+                height,
+                expected: header.header().kernel_mr,
+                actual,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Checks the database and repairs the issues that were found, returning a report of the database after the
+    /// repair. Stale tip metadata is pointed back at the main chain header and dangling block bodies are removed. The
+    /// kernel MMRs and tip output SMT are rebuilt from the stored kernels and outputs if they do not match the headers,
+    /// and if that fails (or blocks are missing) the chain is truncated back to the last consistent height. An error is
+    /// returned if the genesis block is not intact or the blocks above the last consistent height cannot be removed.
+    pub fn repair_integrity(&self) -> Result<IntegrityReport, ChainStorageError> {
+        let report = self.check_integrity()?;
+        if report.is_consistent() {
+            return Ok(report);
+        }
+        let last_consistent_height = report.last_consistent_height.ok_or_else(|| {
+            ChainStorageError::CriticalError(
+                "The genesis block is not intact, the database must be deleted and resynced".to_string(),
+            )
+        })?;
+
+        if report.has_issue(|issue| matches!(issue, IntegrityIssue::StaleTipMetadata { .. })) {
+            self.reset_tip_metadata(report.tip_height)?;
+        }
+        if report.has_issue(|issue| matches!(issue, IntegrityIssue::DanglingBlockBody { .. })) {
+            self.delete_dangling_block_bodies(&report)?;
+        }
+
+        // Dangling block bodies may have left their outputs in the tip output SMT, so it is rebuilt after removing them
+        let needs_rebuild = report.has_issue(|issue| {
+            matches!(
+                issue,
+                IntegrityIssue::KernelMmrSizeMismatch { .. } |
+                    IntegrityIssue::KernelMmrRootMismatch { .. } |
+                    IntegrityIssue::OutputSmtMismatch { .. } |
+                    IntegrityIssue::DanglingBlockBody { .. }
+            )
+        });
+        let rebuilt = needs_rebuild &&
+            !report.has_issue(|issue| {
+                matches!(
+                    issue,
+                    IntegrityIssue::MissingHeader { .. } | IntegrityIssue::MissingBlockBody { .. }
+                )
+            }) &&
+            match self.reconstruct_mmrs(None) {
+                Ok(result) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Rebuilt the kernel MMR and output SMT up to height {}", result.height
+                    );
+                    true
+                },
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Could not rebuild the MMRs: {}", err);
+                    false
+                },
+            };
+
+        if !rebuilt && last_consistent_height < self.get_height()? {
+            warn!(
+                target: LOG_TARGET,
+                "Truncating the chain back to the last consistent height {}", last_consistent_height
+            );
+            self.rewind_to_height(last_consistent_height)?;
+        }
+
+        self.check_integrity()
+    }
+
+    /// Points the chain metadata back at the main chain header at `height`
+    fn reset_tip_metadata(&self, height: u64) -> Result<(), ChainStorageError> {
+        let metadata = self.get_chain_metadata()?;
+        let header = self.fetch_chain_header(height)?;
+        warn!(
+            target: LOG_TARGET,
+            "Resetting the chain metadata tip from {} to {} at height {}",
+            metadata.best_block_hash(),
+            header.hash(),
+            height
+        );
+        let mut txn = DbTransaction::new();
+        txn.set_best_block(
+            header.height(),
+            *header.hash(),
+            header.accumulated_data().total_accumulated_difficulty,
+            *metadata.best_block_hash(),
+            header.timestamp(),
+        );
+        self.write(txn)
+    }
+
+    /// Removes the block bodies above the chain tip, starting with the highest
+    fn delete_dangling_block_bodies(&self, report: &IntegrityReport) -> Result<(), ChainStorageError> {
+        let mut heights = report
+            .issues
+            .iter()
+            .filter(|issue| matches!(issue, IntegrityIssue::DanglingBlockBody { .. }))
+            .map(|issue| issue.height())
+            .collect::<Vec<_>>();
+        heights.sort_unstable_by(|a, b| b.cmp(a));
+        let mut txn = DbTransaction::new();
+        for height in heights {
+            txn.delete_dangling_block_body(height);
+        }
+        self.write(txn)
+    }
+}
@@ -20,10 +20,21 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{cmp::max, convert::TryFrom, fmt, fs, fs::File, ops::Deref, path::Path, sync::Arc, time::Instant};
+use std::{
+    cmp::max,
+    convert::TryFrom,
+    fmt,
+    fs,
+    fs::File,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
 
 use fs2::FileExt;
 use lmdb_zero::{
+    copy,
     open,
     traits::AsLmdbBytes,
     ConstTransaction,
@@ -43,6 +54,7 @@ use tari_common_types::{
 use tari_mmr::sparse_merkle_tree::{DeleteResult, NodeKey, ValueHash};
 use tari_storage::lmdb_store::{db, LMDBBuilder, LMDBConfig, LMDBStore, BYTES_PER_MB};
 use tari_utilities::{
+    epoch_time::EpochTime,
     hex::{to_hex, Hex},
     ByteArray,
 };
@@ -59,6 +71,7 @@ use crate::{
         UpdateBlockAccumulatedData,
     },
     chain_storage::{
+        calculate_validator_node_mr,
         db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation},
         error::{ChainStorageError, OrNotFound},
         lmdb_db::{
@@ -82,7 +95,11 @@ use crate::{
                 lmdb_len,
                 lmdb_replace,
             },
+            maintenance::{COMPACTED_DIR_NAME, COMPACTING_DIR_NAME},
             validator_node_store::ValidatorNodeStore,
+            LmdbCompactionResult,
+            MapUtilization,
+            TargetDifficultyWindowRowData,
             TransactionInputRowData,
             TransactionInputRowDataRef,
             TransactionKernelRowData,
@@ -90,18 +107,23 @@ use crate::{
         },
         stats::DbTotalSizeStats,
         utxo_mined_info::OutputMinedInfo,
+        BlockBalanceSums,
         BlockchainBackend,
         ChainTipData,
         DbBasicStats,
         DbSize,
         HorizonData,
+        HorizonSyncCheckpoint,
         InputMinedInfo,
         MmrTree,
         Reorg,
         TemplateRegistrationEntry,
         ValidatorNodeEntry,
+        ValidatorNodeEpochSnapshot,
     },
+    common::{one_sided::one_sided_script_public_key, payment_reference::generate_payment_reference},
     consensus::{ConsensusConstants, ConsensusManager},
+    proof_of_work::{Difficulty, PowAlgorithm},
     transactions::{
         aggregated_body::AggregateBody,
         transaction_components::{
@@ -133,6 +155,7 @@ const LMDB_DB_KERNELS: &str = "kernels";
 const LMDB_DB_KERNEL_EXCESS_INDEX: &str = "kernel_excess_index";
 const LMDB_DB_KERNEL_EXCESS_SIG_INDEX: &str = "kernel_excess_sig_index";
 const LMDB_DB_KERNEL_MMR_SIZE_INDEX: &str = "kernel_mmr_size_index";
+const LMDB_DB_BURN_COMMITMENT_INDEX: &str = "burn_commitment_index";
 const LMDB_DB_DELETED_TXO_HASH_TO_HEADER_INDEX: &str = "deleted_txo_hash_to_header_index";
 const LMDB_DB_UTXO_COMMITMENT_INDEX: &str = "utxo_commitment_index";
 const LMDB_DB_UNIQUE_ID_INDEX: &str = "unique_id_index";
@@ -146,8 +169,20 @@ const LMDB_DB_BAD_BLOCK_LIST: &str = "bad_blocks";
 const LMDB_DB_REORGS: &str = "reorgs";
 const LMDB_DB_VALIDATOR_NODES: &str = "validator_nodes";
 const LMDB_DB_VALIDATOR_NODES_MAPPING: &str = "validator_nodes_mapping";
+const LMDB_DB_VALIDATOR_NODE_EPOCH_SNAPSHOTS: &str = "validator_node_epoch_snapshots";
 const LMDB_DB_TEMPLATE_REGISTRATIONS: &str = "template_registrations";
 const LMDB_DB_TIP_UTXO_SMT: &str = "tip_utxo_smt";
+const LMDB_DB_TARGET_DIFFICULTY_WINDOW: &str = "target_difficulty_window";
+const LMDB_DB_PAYREF_INDEX: &str = "payref_index";
+const LMDB_DB_SCRIPT_KEY_INDEX: &str = "script_key_index";
+const LMDB_DB_BLOCK_BALANCE_SUMS: &str = "block_balance_sums";
+
+const LMDB_DATA_FILE: &str = "data.mdb";
+
+/// The number of blocks that expired validator node registrations are kept for beyond the validity period, so that they
+/// are still available if a reorg rewinds the chain past the start of the period. A larger configured max reorg depth
+/// extends this.
+const VALIDATOR_NODE_PRUNE_REORG_MARGIN: u64 = 1000;
 
 /// HeaderHash(32), mmr_pos(8), hash(32)
 type KernelKey = CompositeKey<72>;
@@ -164,6 +199,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(
     fs::create_dir_all(&path)?;
 
     let file_lock = acquire_exclusive_file_lock(path.as_ref())?;
+    apply_pending_compaction(path.as_ref())?;
 
     let lmdb_store = LMDBBuilder::new()
         .set_path(path)
@@ -183,6 +219,7 @@ pub fn create_lmdb_database<P: AsRef<Path>>(
         .add_database(LMDB_DB_KERNEL_EXCESS_INDEX, flags)
         .add_database(LMDB_DB_KERNEL_EXCESS_SIG_INDEX, flags)
         .add_database(LMDB_DB_KERNEL_MMR_SIZE_INDEX, flags)
+        .add_database(LMDB_DB_BURN_COMMITMENT_INDEX, flags)
         .add_database(LMDB_DB_UTXO_COMMITMENT_INDEX, flags)
         .add_database(LMDB_DB_UNIQUE_ID_INDEX, flags)
         .add_database(LMDB_DB_CONTRACT_ID_INDEX, flags)
@@ -196,8 +233,13 @@ pub fn create_lmdb_database<P: AsRef<Path>>(
         .add_database(LMDB_DB_REORGS, flags | db::INTEGERKEY)
         .add_database(LMDB_DB_VALIDATOR_NODES, flags)
         .add_database(LMDB_DB_VALIDATOR_NODES_MAPPING, flags)
+        .add_database(LMDB_DB_VALIDATOR_NODE_EPOCH_SNAPSHOTS, flags | db::INTEGERKEY)
         .add_database(LMDB_DB_TEMPLATE_REGISTRATIONS, flags | db::DUPSORT)
         .add_database(LMDB_DB_TIP_UTXO_SMT, flags)
+        .add_database(LMDB_DB_TARGET_DIFFICULTY_WINDOW, flags)
+        .add_database(LMDB_DB_PAYREF_INDEX, flags)
+        .add_database(LMDB_DB_SCRIPT_KEY_INDEX, flags | db::DUPSORT)
+        .add_database(LMDB_DB_BLOCK_BALANCE_SUMS, flags | db::INTEGERKEY)
         .build()
         .map_err(|err| ChainStorageError::CriticalError(format!("Could not create LMDB store:{}", err)))?;
     debug!(target: LOG_TARGET, "LMDB database creation successful");
@@ -231,6 +273,8 @@ pub struct LMDBDatabase {
     kernel_excess_sig_index: DatabaseRef,
     /// Maps kernel_mmr_size -> height
     kernel_mmr_size_index: DatabaseRef,
+    /// Maps burn_commitment -> <block_hash, mmr_pos, kernel_hash>
+    burn_commitment_index: DatabaseRef,
     /// Maps commitment -> output_hash
     utxo_commitment_index: DatabaseRef,
     /// Maps unique_id -> output_hash
@@ -260,8 +304,21 @@ pub struct LMDBDatabase {
     tip_utxo_smt: DatabaseRef,
     /// Maps <Epoch, VN Public Key> -> VN Shard Key
     validator_nodes_mapping: DatabaseRef,
+    /// Maps Epoch -> ValidatorNodeEpochSnapshot
+    validator_node_epoch_snapshots: DatabaseRef,
     /// Maps CodeTemplateRegistration <block_height, hash> -> TemplateRegistration
     template_registrations: DatabaseRef,
+    /// Maps pow_algo -> TargetDifficultyWindowRowData for the header chain tip
+    target_difficulty_window_db: DatabaseRef,
+    /// Maps payment_reference -> output_hash, only populated when the output indexes are enabled
+    payref_index: DatabaseRef,
+    /// Maps one-sided script public key -> output_hash, only populated when the output indexes are enabled
+    script_key_index: DatabaseRef,
+    /// Maps height -> BlockBalanceSums
+    block_balance_sums_db: DatabaseRef,
+    output_indexes_enabled: bool,
+    /// Local node policy, used to decide when expired validator node registrations can be pruned
+    max_reorg_depth: Option<u64>,
     _file_lock: Arc<File>,
     consensus_manager: ConsensusManager,
 }
@@ -274,7 +331,7 @@ impl LMDBDatabase {
     ) -> Result<Self, ChainStorageError> {
         let env = store.env();
 
-        let db = Self {
+        let mut db = Self {
             metadata_db: get_database(store, LMDB_DB_METADATA)?,
             headers_db: get_database(store, LMDB_DB_HEADERS)?,
             header_accumulated_data_db: get_database(store, LMDB_DB_HEADER_ACCUMULATED_DATA)?,
@@ -287,6 +344,7 @@ impl LMDBDatabase {
             kernel_excess_index: get_database(store, LMDB_DB_KERNEL_EXCESS_INDEX)?,
             kernel_excess_sig_index: get_database(store, LMDB_DB_KERNEL_EXCESS_SIG_INDEX)?,
             kernel_mmr_size_index: get_database(store, LMDB_DB_KERNEL_MMR_SIZE_INDEX)?,
+            burn_commitment_index: get_database(store, LMDB_DB_BURN_COMMITMENT_INDEX)?,
             utxo_commitment_index: get_database(store, LMDB_DB_UTXO_COMMITMENT_INDEX)?,
             unique_id_index: get_database(store, LMDB_DB_UNIQUE_ID_INDEX)?,
             contract_index: get_database(store, LMDB_DB_CONTRACT_ID_INDEX)?,
@@ -300,8 +358,15 @@ impl LMDBDatabase {
             reorgs: get_database(store, LMDB_DB_REORGS)?,
             validator_nodes: get_database(store, LMDB_DB_VALIDATOR_NODES)?,
             validator_nodes_mapping: get_database(store, LMDB_DB_VALIDATOR_NODES_MAPPING)?,
+            validator_node_epoch_snapshots: get_database(store, LMDB_DB_VALIDATOR_NODE_EPOCH_SNAPSHOTS)?,
             tip_utxo_smt: get_database(store, LMDB_DB_TIP_UTXO_SMT)?,
             template_registrations: get_database(store, LMDB_DB_TEMPLATE_REGISTRATIONS)?,
+            target_difficulty_window_db: get_database(store, LMDB_DB_TARGET_DIFFICULTY_WINDOW)?,
+            payref_index: get_database(store, LMDB_DB_PAYREF_INDEX)?,
+            script_key_index: get_database(store, LMDB_DB_SCRIPT_KEY_INDEX)?,
+            block_balance_sums_db: get_database(store, LMDB_DB_BLOCK_BALANCE_SUMS)?,
+            output_indexes_enabled: false,
+            max_reorg_depth: None,
             env,
             env_config: store.env_config(),
             _file_lock: Arc::new(file_lock),
@@ -309,6 +374,7 @@ impl LMDBDatabase {
         };
 
         run_migrations(&db)?;
+        db.output_indexes_enabled = db.fetch_output_indexes_enabled()?;
 
         Ok(db)
     }
@@ -331,6 +397,7 @@ impl LMDBDatabase {
         use WriteOperation::*;
 
         let number_of_operations = txn.operations().len();
+        let mut output_indexes_enabled = None;
         let write_txn = self.write_transaction()?;
         for (i, op) in txn.operations().iter().enumerate() {
             trace!(target: LOG_TARGET, "[apply_db_transaction] WriteOperation: {} ({} of {})", op, i + 1, number_of_operations);
@@ -386,6 +453,9 @@ impl LMDBDatabase {
                 DeleteTipBlock(hash) => {
                     self.delete_tip_block_body(&write_txn, hash)?;
                 },
+                DeleteDanglingBlockBody(height) => {
+                    self.delete_dangling_block_body(&write_txn, *height)?;
+                },
                 InsertMoneroSeedHeight(data, height) => {
                     self.insert_monero_seed_height(&write_txn, data, *height)?;
                 },
@@ -481,26 +551,62 @@ impl LMDBDatabase {
                         &MetadataValue::HorizonData(horizon_data.clone()),
                     )?;
                 },
+                SetHorizonSyncCheckpoint { checkpoint } => match checkpoint {
+                    Some(checkpoint) => {
+                        self.set_metadata(
+                            &write_txn,
+                            MetadataKey::HorizonSyncCheckpoint,
+                            &MetadataValue::HorizonSyncCheckpoint(checkpoint.clone()),
+                        )?;
+                    },
+                    None => {
+                        let key = MetadataKey::HorizonSyncCheckpoint.as_u32();
+                        if lmdb_exists(&write_txn, &self.metadata_db, &key)? {
+                            lmdb_delete(&write_txn, &self.metadata_db, &key, LMDB_DB_METADATA)?;
+                        }
+                    },
+                },
+                SetBlockBalanceSums { height, sums } => {
+                    lmdb_replace(&write_txn, &self.block_balance_sums_db, height, sums, None)?;
+                },
                 InsertBadBlock { hash, height, reason } => {
                     self.insert_bad_block_and_cleanup(&write_txn, hash, *height, reason.to_string())?;
                 },
                 InsertReorg { reorg } => {
-                    lmdb_replace(&write_txn, &self.reorgs, &reorg.local_time.timestamp(), &reorg, None)?;
+                    // Keyed on the time in nanoseconds so that reorgs in the same second do not replace each other
+                    let key = reorg
+                        .local_time
+                        .timestamp_nanos_opt()
+                        .unwrap_or_else(|| reorg.local_time.timestamp());
+                    lmdb_replace(&write_txn, &self.reorgs, &key, &reorg, None)?;
                 },
                 ClearAllReorgs => {
                     lmdb_clear(&write_txn, &self.reorgs)?;
                 },
+                PruneReorgs { max_entries } => {
+                    self.prune_reorgs(&write_txn, *max_entries)?;
+                },
+                SetOutputIndexes { enabled } => {
+                    self.set_output_indexes(&write_txn, *enabled)?;
+                    output_indexes_enabled = Some(*enabled);
+                },
+                SetMaxReorgDepth { max_reorg_depth } => {
+                    self.max_reorg_depth = *max_reorg_depth;
+                },
                 InsertTipSmt { smt } => {
                     self.insert_tip_smt(&write_txn, smt)?;
                 },
             }
         }
         write_txn.commit()?;
+        if let Some(enabled) = output_indexes_enabled {
+            self.output_indexes_enabled = enabled;
+        }
 
         Ok(())
     }
 
-    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 27] {
+    fn all_dbs(&self) -> [(&'static str, &DatabaseRef); 33] {
         [
             (LMDB_DB_METADATA, &self.metadata_db),
             (LMDB_DB_HEADERS, &self.headers_db),
@@ -514,6 +620,7 @@ impl LMDBDatabase {
             (LMDB_DB_KERNEL_EXCESS_INDEX, &self.kernel_excess_index),
             (LMDB_DB_KERNEL_EXCESS_SIG_INDEX, &self.kernel_excess_sig_index),
             (LMDB_DB_KERNEL_MMR_SIZE_INDEX, &self.kernel_mmr_size_index),
+            (LMDB_DB_BURN_COMMITMENT_INDEX, &self.burn_commitment_index),
             (LMDB_DB_UTXO_COMMITMENT_INDEX, &self.utxo_commitment_index),
             (LMDB_DB_CONTRACT_ID_INDEX, &self.contract_index),
             (LMDB_DB_UNIQUE_ID_INDEX, &self.unique_id_index),
@@ -534,7 +641,15 @@ impl LMDBDatabase {
             (LMDB_DB_VALIDATOR_NODES, &self.validator_nodes),
             (LMDB_DB_TIP_UTXO_SMT, &self.tip_utxo_smt),
             (LMDB_DB_VALIDATOR_NODES_MAPPING, &self.validator_nodes_mapping),
+            (
+                LMDB_DB_VALIDATOR_NODE_EPOCH_SNAPSHOTS,
+                &self.validator_node_epoch_snapshots,
+            ),
             (LMDB_DB_TEMPLATE_REGISTRATIONS, &self.template_registrations),
+            (LMDB_DB_TARGET_DIFFICULTY_WINDOW, &self.target_difficulty_window_db),
+            (LMDB_DB_PAYREF_INDEX, &self.payref_index),
+            (LMDB_DB_SCRIPT_KEY_INDEX, &self.script_key_index),
+            (LMDB_DB_BLOCK_BALANCE_SUMS, &self.block_balance_sums_db),
         ]
     }
 
@@ -580,10 +695,132 @@ impl LMDBDatabase {
             },
             LMDB_DB_UTXOS,
         )?;
+        if self.output_indexes_enabled {
+            self.insert_output_index_entries(
+                txn,
+                header_hash,
+                &output_hash,
+                one_sided_script_public_key(&output.script),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn insert_output_index_entries(
+        &self,
+        txn: &WriteTransaction<'_>,
+        header_hash: &HashOutput,
+        output_hash: &HashOutput,
+        script_key: Option<&PublicKey>,
+    ) -> Result<(), ChainStorageError> {
+        let payref = generate_payment_reference(header_hash, output_hash);
+        lmdb_insert(
+            txn,
+            &self.payref_index,
+            payref.as_slice(),
+            output_hash,
+            LMDB_DB_PAYREF_INDEX,
+        )?;
+        if let Some(script_key) = script_key {
+            lmdb_insert_dup(txn, &self.script_key_index, script_key.as_bytes(), output_hash)?;
+        }
+        Ok(())
+    }
+
+    fn delete_output_index_entries(
+        &self,
+        txn: &WriteTransaction<'_>,
+        header_hash: &HashOutput,
+        output_hash: &HashOutput,
+        script_key: Option<&PublicKey>,
+    ) -> Result<(), ChainStorageError> {
+        let payref = generate_payment_reference(header_hash, output_hash);
+        lmdb_delete(txn, &self.payref_index, payref.as_slice(), LMDB_DB_PAYREF_INDEX)?;
+        if let Some(script_key) = script_key {
+            lmdb_delete_key_value(txn, &self.script_key_index, script_key.as_bytes(), output_hash)?;
+        }
+        Ok(())
+    }
 
+    /// Deletes the output index entries of the output stored under `output_key` in the utxos db, if the output indexes
+    /// are enabled. This must be called before the output is deleted.
+    fn prune_output_index_entries(
+        &self,
+        txn: &WriteTransaction<'_>,
+        output_key: &CompositeKey<68>,
+    ) -> Result<(), ChainStorageError> {
+        if !self.output_indexes_enabled {
+            return Ok(());
+        }
+        if let Some(row) = lmdb_get::<_, TransactionOutputRowData>(txn, &self.utxos_db, output_key)? {
+            self.delete_output_index_entries(
+                txn,
+                &row.header_hash,
+                &row.hash,
+                one_sided_script_public_key(&row.output.script),
+            )?;
+        }
         Ok(())
     }
 
+    /// Builds the output indexes from the outputs that are already stored when they are enabled and deletes them when
+    /// they are disabled. Nothing is done if the indexes are already in the requested state.
+    fn set_output_indexes(&self, txn: &WriteTransaction<'_>, enabled: bool) -> Result<(), ChainStorageError> {
+        if enabled == self.output_indexes_enabled {
+            return Ok(());
+        }
+        let timer = Instant::now();
+        if enabled {
+            let outputs = lmdb_filter_map_values(txn, &self.utxos_db, |row: TransactionOutputRowData| {
+                Some((
+                    row.header_hash,
+                    row.hash,
+                    one_sided_script_public_key(&row.output.script).cloned(),
+                ))
+            })?;
+            for (header_hash, output_hash, script_key) in &outputs {
+                self.insert_output_index_entries(txn, header_hash, output_hash, script_key.as_ref())?;
+            }
+            info!(
+                target: LOG_TARGET,
+                "Built output indexes for {} output(s) in {:.2?}",
+                outputs.len(),
+                timer.elapsed()
+            );
+        } else {
+            let num_deleted = lmdb_clear(txn, &self.payref_index)? + lmdb_clear(txn, &self.script_key_index)?;
+            info!(
+                target: LOG_TARGET,
+                "Deleted {} output index entries in {:.2?}",
+                num_deleted,
+                timer.elapsed()
+            );
+        }
+        self.set_metadata(
+            txn,
+            MetadataKey::OutputIndexesEnabled,
+            &MetadataValue::OutputIndexesEnabled(enabled),
+        )
+    }
+
+    fn fetch_output_indexes_enabled(&self) -> Result<bool, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let k = MetadataKey::OutputIndexesEnabled;
+        let val = lmdb_get::<_, MetadataValue>(&txn, &self.metadata_db, &k.as_u32())?;
+        Ok(matches!(val, Some(MetadataValue::OutputIndexesEnabled(true))))
+    }
+
+    fn check_output_indexes_enabled(&self) -> Result<(), ChainStorageError> {
+        if self.output_indexes_enabled {
+            Ok(())
+        } else {
+            Err(ChainStorageError::InvalidOperation(
+                "The output indexes are not enabled".to_string(),
+            ))
+        }
+    }
+
     fn insert_kernel(
         &self,
         txn: &WriteTransaction<'_>,
@@ -617,6 +854,16 @@ impl LMDBDatabase {
             "kernel_excess_sig_index",
         )?;
 
+        if let Some(ref burn_commitment) = kernel.burn_commitment {
+            lmdb_insert(
+                txn,
+                &self.burn_commitment_index,
+                burn_commitment.as_bytes(),
+                &(*header_hash, mmr_position, hash),
+                "burn_commitment_index",
+            )?;
+        }
+
         lmdb_insert(
             txn,
             &self.kernels_db,
@@ -829,9 +1076,81 @@ impl LMDBDatabase {
             &header.height,
             "kernel_mmr_size_index",
         )?;
+        self.update_target_difficulty_windows(txn, header, accum_data)?;
         Ok(())
     }
 
+    /// Adds the newly inserted header to the persisted target difficulty windows so that the windows for the next block
+    /// can be loaded without walking back through the header chain. If the persisted windows do not end at the
+    /// previous header (e.g. after a reorg or on a database that predates the table) they are rebuilt from the headers.
+    fn update_target_difficulty_windows(
+        &self,
+        txn: &WriteTransaction<'_>,
+        header: &BlockHeader,
+        accum_data: &BlockHeaderAccumulatedData,
+    ) -> Result<(), ChainStorageError> {
+        let block_window = self
+            .consensus_manager
+            .consensus_constants(header.height.saturating_add(1))
+            .difficulty_block_window();
+        let window_size = usize::try_from(block_window).unwrap_or(usize::MAX);
+        for pow_algo in [PowAlgorithm::Sha3x, PowAlgorithm::RandomX] {
+            let key = [pow_algo as u8];
+            let row =
+                match lmdb_get::<_, TargetDifficultyWindowRowData>(txn, &self.target_difficulty_window_db, &key[..])? {
+                    Some(mut row) if row.tip_hash == header.prev_hash => {
+                        if header.pow_algo() == pow_algo {
+                            row.samples
+                                .push((header.timestamp.as_u64(), accum_data.target_difficulty));
+                            let excess = row.samples.len().saturating_sub(window_size);
+                            row.samples.drain(..excess);
+                        }
+                        row.tip_hash = accum_data.hash;
+                        row
+                    },
+                    _ => self.build_target_difficulty_window(txn, pow_algo, header.height, window_size)?,
+                };
+            lmdb_replace(txn, &self.target_difficulty_window_db, &key[..], &row, None)?;
+        }
+        Ok(())
+    }
+
+    fn build_target_difficulty_window(
+        &self,
+        txn: &WriteTransaction<'_>,
+        pow_algo: PowAlgorithm,
+        tip_height: u64,
+        window_size: usize,
+    ) -> Result<TargetDifficultyWindowRowData, ChainStorageError> {
+        let mut tip_hash = None;
+        let mut samples = Vec::with_capacity(window_size);
+        let mut height = tip_height;
+        loop {
+            let header = lmdb_get::<_, BlockHeader>(txn, &self.headers_db, &height).or_not_found(
+                "BlockHeader",
+                "height",
+                height.to_string(),
+            )?;
+            let accum_data = lmdb_get::<_, BlockHeaderAccumulatedData>(txn, &self.header_accumulated_data_db, &height)
+                .or_not_found("BlockHeaderAccumulatedData", "height", height.to_string())?;
+            if tip_hash.is_none() {
+                tip_hash = Some(accum_data.hash);
+            }
+            if header.pow_algo() == pow_algo {
+                samples.push((header.timestamp.as_u64(), accum_data.target_difficulty));
+            }
+            if height == 0 || samples.len() >= window_size {
+                break;
+            }
+            height -= 1;
+        }
+        samples.reverse();
+        Ok(TargetDifficultyWindowRowData {
+            tip_hash: tip_hash.unwrap_or_default(),
+            samples,
+        })
+    }
+
     fn delete_header(&self, txn: &WriteTransaction<'_>, height: u64) -> Result<(), ChainStorageError> {
         if self.fetch_block_accumulated_data(txn, height)?.is_some() {
             return Err(ChainStorageError::InvalidOperation(format!(
@@ -913,9 +1232,18 @@ impl LMDBDatabase {
             &height,
             "block_accumulated_data_db",
         )?;
+        if lmdb_exists(write_txn, &self.block_balance_sums_db, &height)? {
+            lmdb_delete(
+                write_txn,
+                &self.block_balance_sums_db,
+                &height,
+                LMDB_DB_BLOCK_BALANCE_SUMS,
+            )?;
+        }
+        self.delete_validator_node_epoch_snapshot(write_txn, height)?;
         let mut smt = self.fetch_tip_smt()?;
 
-        self.delete_block_inputs_outputs(write_txn, block_hash.as_slice(), &mut smt)?;
+        self.delete_block_inputs_outputs(write_txn, block_hash.as_slice(), Some(&mut smt))?;
 
         let new_tip_header = self.fetch_chain_header_by_height(prev_height)?;
         let root = FixedHash::try_from(smt.hash().as_slice())?;
@@ -937,11 +1265,51 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    /// Deletes a block body that was left above the chain tip. The tip output SMT does not contain the outputs of such
+    /// a block, so it is not updated. If there is no header at `height` the block hash is unknown and only the block
+    /// accumulated data can be removed.
+    fn delete_dangling_block_body(
+        &self,
+        write_txn: &WriteTransaction<'_>,
+        height: u64,
+    ) -> Result<(), ChainStorageError> {
+        let metadata = fetch_metadata(write_txn, &self.metadata_db)?;
+        if height <= metadata.best_block_height() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Attempted to delete the block body at height {} which is not above the chain tip",
+                height
+            )));
+        }
+        debug!(target: LOG_TARGET, "Deleting dangling block body at height {}", height);
+        lmdb_delete(
+            write_txn,
+            &self.block_accumulated_data_db,
+            &height,
+            "block_accumulated_data_db",
+        )?;
+        if lmdb_exists(write_txn, &self.block_balance_sums_db, &height)? {
+            lmdb_delete(
+                write_txn,
+                &self.block_balance_sums_db,
+                &height,
+                LMDB_DB_BLOCK_BALANCE_SUMS,
+            )?;
+        }
+        self.delete_validator_node_epoch_snapshot(write_txn, height)?;
+        let header: Option<BlockHeader> = lmdb_get(write_txn, &self.headers_db, &height)?;
+        if let Some(header) = header {
+            let block_hash = header.hash();
+            self.delete_block_inputs_outputs(write_txn, block_hash.as_slice(), None)?;
+            self.delete_block_kernels(write_txn, block_hash.as_slice())?;
+        }
+        Ok(())
+    }
+
     fn delete_block_inputs_outputs(
         &self,
         txn: &WriteTransaction<'_>,
         block_hash: &[u8],
-        output_smt: &mut OutputSmt,
+        mut output_smt: Option<&mut OutputSmt>,
     ) -> Result<(), ChainStorageError> {
         let output_rows = lmdb_delete_keys_starting_with::<TransactionOutputRowData>(txn, &self.utxos_db, block_hash)?;
         debug!(target: LOG_TARGET, "Deleted {} outputs...", output_rows.len());
@@ -956,6 +1324,14 @@ impl LMDBDatabase {
                 utxo.hash.as_slice(),
                 "txos_hash_to_index_db",
             )?;
+            if self.output_indexes_enabled {
+                self.delete_output_index_entries(
+                    txn,
+                    &utxo.header_hash,
+                    &utxo.hash,
+                    one_sided_script_public_key(&utxo.output.script),
+                )?;
+            }
 
             let output_hash = utxo.output.hash();
             // if an output was already spent in the block, it was never created as unspent, so dont delete it as it
@@ -967,18 +1343,20 @@ impl LMDBDatabase {
             if utxo.output.is_burned() {
                 continue;
             }
-            let smt_key = NodeKey::try_from(utxo.output.commitment.as_bytes())?;
-            match output_smt.delete(&smt_key)? {
-                DeleteResult::Deleted(_value_hash) => {},
-                DeleteResult::KeyNotFound => {
-                    error!(
-                        target: LOG_TARGET,
-                        "Could not find input({}) in SMT",
-                        utxo.output.commitment.to_hex(),
-                    );
-                    return Err(ChainStorageError::UnspendableInput);
-                },
-            };
+            if let Some(output_smt) = output_smt.as_mut() {
+                let smt_key = NodeKey::try_from(utxo.output.commitment.as_bytes())?;
+                match output_smt.delete(&smt_key)? {
+                    DeleteResult::Deleted(_value_hash) => {},
+                    DeleteResult::KeyNotFound => {
+                        error!(
+                            target: LOG_TARGET,
+                            "Could not find input({}) in SMT",
+                            utxo.output.commitment.to_hex(),
+                        );
+                        return Err(ChainStorageError::UnspendableInput);
+                    },
+                };
+            }
             lmdb_delete(
                 txn,
                 &self.utxo_commitment_index,
@@ -1028,15 +1406,17 @@ impl LMDBDatabase {
                 rp_hash,
                 utxo_mined_info.output.minimum_value_promise,
             );
-            let smt_key = NodeKey::try_from(input.commitment()?.as_bytes())?;
-            let smt_node = ValueHash::try_from(input.smt_hash(utxo_mined_info.mined_height).as_slice())?;
-            if let Err(e) = output_smt.insert(smt_key, smt_node) {
-                error!(
-                    target: LOG_TARGET,
-                    "Output commitment({}) already in SMT",
-                    input.commitment()?.to_hex(),
-                );
-                return Err(e.into());
+            if let Some(output_smt) = output_smt.as_mut() {
+                let smt_key = NodeKey::try_from(input.commitment()?.as_bytes())?;
+                let smt_node = ValueHash::try_from(input.smt_hash(utxo_mined_info.mined_height).as_slice())?;
+                if let Err(e) = output_smt.insert(smt_key, smt_node) {
+                    error!(
+                        target: LOG_TARGET,
+                        "Output commitment({}) already in SMT",
+                        input.commitment()?.to_hex(),
+                    );
+                    return Err(e.into());
+                }
             }
 
             trace!(target: LOG_TARGET, "Input moved to UTXO set: {}", input);
@@ -1080,6 +1460,14 @@ impl LMDBDatabase {
                 excess_sig_key.as_slice(),
                 "kernel_excess_sig_index",
             )?;
+            if let Some(ref burn_commitment) = kernel.kernel.burn_commitment {
+                lmdb_delete(
+                    txn,
+                    &self.burn_commitment_index,
+                    burn_commitment.as_bytes(),
+                    "burn_commitment_index",
+                )?;
+            }
         }
         Ok(())
     }
@@ -1204,6 +1592,8 @@ impl LMDBDatabase {
             )));
         }
 
+        self.insert_validator_node_epoch_snapshot(txn, header)?;
+
         let (inputs, outputs, kernels) = body.dissolve();
 
         let data = if header.height == 0 {
@@ -1217,6 +1607,13 @@ impl LMDBDatabase {
                 })?
         };
 
+        // The balance sums are only cached if they are known for the previous block, e.g. they are not known for a
+        // database that was created before the sums were cached
+        let mut balance_sums = if header.height == 0 {
+            Some(BlockBalanceSums::default())
+        } else {
+            self.fetch_block_balance_sums(txn, header.height - 1)?
+        };
         let mut total_kernel_sum = Commitment::default();
         let BlockAccumulatedData {
             kernels: pruned_kernel_set,
@@ -1227,6 +1624,9 @@ impl LMDBDatabase {
 
         for kernel in kernels {
             total_kernel_sum = &total_kernel_sum + &kernel.excess;
+            if let Some(sums) = balance_sums.as_mut() {
+                sums.add_kernel(&kernel)?;
+            }
             let pos =
                 u64::try_from(kernel_mmr.push(kernel.hash().to_vec())?).map_err(|_| ChainStorageError::OutOfRange)?;
             trace!(
@@ -1288,6 +1688,9 @@ impl LMDBDatabase {
 
                 self.insert_template_registration(txn, &record)?;
             }
+            if let Some(sums) = balance_sums.as_mut() {
+                sums.add_output(&output);
+            }
             self.insert_output(txn, &block_hash, header.height, header.timestamp().as_u64(), &output)?;
         }
 
@@ -1307,6 +1710,10 @@ impl LMDBDatabase {
                 },
             };
 
+            if let Some(sums) = balance_sums.as_mut() {
+                sums.spend_commitment(input_with_output_data.commitment()?);
+            }
+
             let features = input_with_output_data.features()?;
             if let Some(vn_reg) = features
                 .sidechain_feature
@@ -1339,6 +1746,9 @@ impl LMDBDatabase {
             header.height,
             &BlockAccumulatedData::new(kernel_mmr.get_pruned_hash_set()?, total_kernel_sum),
         )?;
+        if let Some(sums) = balance_sums {
+            lmdb_replace(txn, &self.block_balance_sums_db, &header.height, &sums, None)?;
+        }
         self.insert_tip_smt(txn, &output_smt)?;
 
         Ok(())
@@ -1390,6 +1800,76 @@ impl LMDBDatabase {
         Ok(())
     }
 
+    /// Records the active validator node set when the first block of an epoch is inserted. This must be called
+    /// before the block's own registrations are inserted so that the snapshot matches the validator node merkle root
+    /// committed to in the header.
+    fn insert_validator_node_epoch_snapshot(
+        &self,
+        txn: &WriteTransaction<'_>,
+        header: &BlockHeader,
+    ) -> Result<(), ChainStorageError> {
+        let constants = self.get_consensus_constants(header.height);
+        if header.height % constants.epoch_length() != 0 {
+            return Ok(());
+        }
+        let epoch = constants.block_height_to_epoch(header.height);
+        let start_height = epoch
+            .saturating_sub(constants.validator_node_validity_period_epochs())
+            .as_u64() *
+            constants.epoch_length();
+        let store = self.validator_node_store(txn);
+        let validator_nodes = store.get_vn_set(start_height, header.height)?;
+        let snapshot = ValidatorNodeEpochSnapshot {
+            epoch,
+            height: header.height,
+            merkle_root: FixedHash::try_from(calculate_validator_node_mr(&validator_nodes))?,
+            validator_nodes,
+        };
+        lmdb_replace(
+            txn,
+            &self.validator_node_epoch_snapshots,
+            &epoch.as_u64(),
+            &snapshot,
+            None,
+        )?;
+
+        // Registrations that fall outside of the validity period can only become active again if the chain is
+        // rewound past the start of the period, which a reorg beyond the prune margin cannot do.
+        let reorg_margin = self.max_reorg_depth.map_or(VALIDATOR_NODE_PRUNE_REORG_MARGIN, |depth| {
+            depth.max(VALIDATOR_NODE_PRUNE_REORG_MARGIN)
+        });
+        let prune_height = start_height.saturating_sub(reorg_margin.saturating_add(constants.epoch_length()));
+        let num_pruned = store.prune_registrations_before(prune_height)?;
+        if num_pruned > 0 {
+            debug!(
+                target: LOG_TARGET,
+                "Pruned {} expired validator node registration(s) below height {}", num_pruned, prune_height
+            );
+        }
+        Ok(())
+    }
+
+    fn delete_validator_node_epoch_snapshot(
+        &self,
+        txn: &WriteTransaction<'_>,
+        height: u64,
+    ) -> Result<(), ChainStorageError> {
+        let constants = self.get_consensus_constants(height);
+        if height % constants.epoch_length() != 0 {
+            return Ok(());
+        }
+        let epoch = constants.block_height_to_epoch(height).as_u64();
+        if lmdb_exists(txn, &self.validator_node_epoch_snapshots, &epoch)? {
+            lmdb_delete(
+                txn,
+                &self.validator_node_epoch_snapshots,
+                &epoch,
+                LMDB_DB_VALIDATOR_NODE_EPOCH_SNAPSHOTS,
+            )?;
+        }
+        Ok(())
+    }
+
     #[allow(clippy::ptr_arg)]
     fn insert_block_accumulated_data(
         &self,
@@ -1406,6 +1886,24 @@ impl LMDBDatabase {
         )
     }
 
+    /// Deletes the oldest reorgs until at most `max_entries` remain. Reorgs are keyed on the time they occurred, so
+    /// the oldest reorgs are first in the table.
+    fn prune_reorgs(&self, txn: &WriteTransaction<'_>, max_entries: usize) -> Result<(), ChainStorageError> {
+        let mut num_to_delete = lmdb_len(txn, &self.reorgs)?.saturating_sub(max_entries);
+        if num_to_delete == 0 {
+            return Ok(());
+        }
+        let num_deleted = lmdb_delete_each_where::<[u8], Reorg, _>(txn, &self.reorgs, |_, _| {
+            if num_to_delete == 0 {
+                return None;
+            }
+            num_to_delete -= 1;
+            Some(true)
+        })?;
+        debug!(target: LOG_TARGET, "Pruned {} reorgs from the reorg history", num_deleted);
+        Ok(())
+    }
+
     fn insert_tip_smt(&self, txn: &WriteTransaction<'_>, smt: &OutputSmt) -> Result<(), ChainStorageError> {
         let start = Instant::now();
         let k = MetadataKey::TipSmt;
@@ -1533,7 +2031,9 @@ impl LMDBDatabase {
                 buffer.copy_from_slice(&key_bytes[0..32]);
                 let key = OutputKey::new(&FixedHash::from(buffer), &input.output_hash())?;
                 debug!(target: LOG_TARGET, "Pruning output from 'utxos_db': key '{}'", key.0);
-                lmdb_delete(write_txn, &self.utxos_db, &key.convert_to_comp_key(), LMDB_DB_UTXOS)?;
+                let key = key.convert_to_comp_key();
+                self.prune_output_index_entries(write_txn, &key)?;
+                lmdb_delete(write_txn, &self.utxos_db, &key, LMDB_DB_UTXOS)?;
             };
             // From 'txos_hash_to_index_db::utxos_db'
             debug!(
@@ -1582,7 +2082,9 @@ impl LMDBDatabase {
                 buffer.copy_from_slice(&key_bytes[0..32]);
                 let key = OutputKey::new(&FixedHash::from(buffer), output_hash)?;
                 debug!(target: LOG_TARGET, "Pruning output from 'utxos_db': key '{}'", key.0);
-                lmdb_delete(write_txn, &self.utxos_db, &key.convert_to_comp_key(), LMDB_DB_UTXOS)?;
+                let key = key.convert_to_comp_key();
+                self.prune_output_index_entries(write_txn, &key)?;
+                lmdb_delete(write_txn, &self.utxos_db, &key, LMDB_DB_UTXOS)?;
             },
             None => return Err(ChainStorageError::InvalidOperation("Output key not found".to_string())),
         }
@@ -1615,6 +2117,14 @@ impl LMDBDatabase {
         lmdb_get(txn, &self.block_accumulated_data_db, &height).map_err(Into::into)
     }
 
+    fn fetch_block_balance_sums(
+        &self,
+        txn: &ConstTransaction<'_>,
+        height: u64,
+    ) -> Result<Option<BlockBalanceSums>, ChainStorageError> {
+        lmdb_get(txn, &self.block_balance_sums_db, &height).map_err(Into::into)
+    }
+
     #[allow(clippy::ptr_arg)]
     fn fetch_height_from_hash(
         &self,
@@ -1794,6 +2304,30 @@ fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, ChainStorageError
     Ok(file)
 }
 
+/// Replaces the data file with the compacted copy written by [LMDBDatabase::compact], if there is one. Any blocks that
+/// were added after the copy was made are lost and will be synced again. This must only be called while holding the
+/// database file lock and before the environment is opened.
+fn apply_pending_compaction(path: &Path) -> Result<(), ChainStorageError> {
+    // An incomplete copy is left behind if the node was stopped during compaction
+    let compacting_dir = path.join(COMPACTING_DIR_NAME);
+    if compacting_dir.exists() {
+        fs::remove_dir_all(&compacting_dir)?;
+    }
+    let compacted_dir = path.join(COMPACTED_DIR_NAME);
+    let compacted_file = compacted_dir.join(LMDB_DATA_FILE);
+    if !compacted_file.exists() {
+        return Ok(());
+    }
+    info!(
+        target: LOG_TARGET,
+        "Replacing the database at {:?} with the compacted copy",
+        path
+    );
+    fs::rename(&compacted_file, path.join(LMDB_DATA_FILE))?;
+    fs::remove_dir_all(&compacted_dir)?;
+    Ok(())
+}
+
 impl BlockchainBackend for LMDBDatabase {
     fn write(&mut self, txn: DbTransaction) -> Result<(), ChainStorageError> {
         if txn.operations().is_empty() {
@@ -2084,6 +2618,26 @@ impl BlockchainBackend for LMDBDatabase {
         }
     }
 
+    fn fetch_kernel_by_burn_commitment(
+        &self,
+        burn_commitment: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        if let Some((header_hash, mmr_position, hash)) =
+            lmdb_get::<_, (HashOutput, u64, HashOutput)>(&txn, &self.burn_commitment_index, burn_commitment.as_bytes())?
+        {
+            let key = KernelKey::try_from_parts(&[
+                header_hash.as_slice(),
+                mmr_position.to_be_bytes().as_slice(),
+                hash.as_slice(),
+            ])?;
+            Ok(lmdb_get(&txn, &self.kernels_db, &key)?
+                .map(|kernel: TransactionKernelRowData| (kernel.kernel, header_hash)))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn fetch_outputs_in_block_with_spend_state(
         &self,
         previous_header_hash: &HashOutput,
@@ -2139,6 +2693,37 @@ impl BlockchainBackend for LMDBDatabase {
         self.fetch_input_in_txn(&txn, output_hash.as_slice())
     }
 
+    fn output_indexes_enabled(&self) -> bool {
+        self.output_indexes_enabled
+    }
+
+    fn fetch_outputs_by_payref(&self, payrefs: &[FixedHash]) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        self.check_output_indexes_enabled()?;
+        let txn = self.read_transaction()?;
+        let mut outputs = Vec::with_capacity(payrefs.len());
+        for payref in payrefs {
+            if let Some(output_hash) = lmdb_get::<_, HashOutput>(&txn, &self.payref_index, payref.as_slice())? {
+                if let Some(output) = self.fetch_output_in_txn(&txn, output_hash.as_slice())? {
+                    outputs.push(output);
+                }
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn fetch_outputs_by_script_key(&self, script_key: &PublicKey) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        self.check_output_indexes_enabled()?;
+        let txn = self.read_transaction()?;
+        let output_hashes = lmdb_get_multiple::<_, HashOutput>(&txn, &self.script_key_index, script_key.as_bytes())?;
+        let mut outputs = Vec::with_capacity(output_hashes.len());
+        for output_hash in output_hashes {
+            if let Some(output) = self.fetch_output_in_txn(&txn, output_hash.as_slice())? {
+                outputs.push(output);
+            }
+        }
+        Ok(outputs)
+    }
+
     fn fetch_unspent_output_hash_by_commitment(
         &self,
         commitment: &Commitment,
@@ -2437,11 +3022,40 @@ impl BlockchainBackend for LMDBDatabase {
         Ok(lmdb_get(&txn, &self.monero_seed_height_db, seed)?.unwrap_or(0))
     }
 
+    fn fetch_target_difficulty_window(
+        &self,
+        pow_algo: PowAlgorithm,
+        tip_hash: &HashOutput,
+    ) -> Result<Option<Vec<(EpochTime, Difficulty)>>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        let row = lmdb_get::<_, TargetDifficultyWindowRowData>(
+            &txn,
+            &self.target_difficulty_window_db,
+            &[pow_algo as u8][..],
+        )?;
+        Ok(row.filter(|row| row.tip_hash == *tip_hash).map(|row| {
+            row.samples
+                .into_iter()
+                .map(|(timestamp, difficulty)| (EpochTime::from(timestamp), difficulty))
+                .collect()
+        }))
+    }
+
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
         let txn = self.read_transaction()?;
         Ok(Some(fetch_horizon_data(&txn, &self.metadata_db)?))
     }
 
+    fn fetch_horizon_sync_checkpoint(&self) -> Result<Option<HorizonSyncCheckpoint>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        fetch_horizon_sync_checkpoint(&txn, &self.metadata_db)
+    }
+
+    fn fetch_block_balance_sums(&self, height: u64) -> Result<Option<BlockBalanceSums>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        self.fetch_block_balance_sums(&txn, height)
+    }
+
     fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
         let global = self.env.stat()?;
         let env_info = self.env.info()?;
@@ -2470,6 +3084,83 @@ impl BlockchainBackend for LMDBDatabase {
             .collect()
     }
 
+    fn fetch_map_utilization(&self) -> Result<MapUtilization, ChainStorageError> {
+        let env_info = self.env.info()?;
+        let stat = self.env.stat()?;
+        Ok(MapUtilization::new(
+            env_info.mapsize as u64,
+            u64::from(stat.psize),
+            env_info.last_pgno as u64,
+        ))
+    }
+
+    fn grow_map_if_required(&mut self, max_utilization_percentage: u8) -> Result<bool, ChainStorageError> {
+        let utilization = self.fetch_map_utilization()?;
+        if !utilization.exceeds(max_utilization_percentage) {
+            return Ok(false);
+        }
+        let required_growth =
+            usize::try_from(utilization.required_growth_bytes(max_utilization_percentage)).unwrap_or(usize::MAX);
+        debug!(
+            target: LOG_TARGET,
+            "LMDB map is {}% utilized, growing it by at least {} MB",
+            utilization.utilization_percentage(),
+            max(required_growth, self.env_config.grow_size_bytes()) / BYTES_PER_MB
+        );
+        // SAFETY: `LmdbDatabase` is wrapped in an exclusive write lock in BlockchainDatabase and this function requires
+        // mutable access, so we know there are no other threads taking out LMDB transactions when this is called.
+        unsafe {
+            LMDBStore::resize(
+                &self.env,
+                &self.env_config,
+                Some(required_growth.saturating_sub(self.env_config.grow_size_bytes())),
+            )?;
+        }
+        Ok(true)
+    }
+
+    fn compact(&self) -> Result<LmdbCompactionResult, ChainStorageError> {
+        let db_path = PathBuf::from(
+            self.env
+                .path()?
+                .to_str()
+                .map_err(|e| ChainStorageError::CriticalError(format!("Invalid database path: {}", e)))?,
+        );
+        let compacting_dir = db_path.join(COMPACTING_DIR_NAME);
+        let compacted_dir = db_path.join(COMPACTED_DIR_NAME);
+        for dir in [&compacting_dir, &compacted_dir] {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+        fs::create_dir_all(&compacting_dir)?;
+
+        let timer = Instant::now();
+        let compacting_dir_str = compacting_dir
+            .to_str()
+            .ok_or_else(|| ChainStorageError::CriticalError("Invalid compaction path".to_string()))?;
+        // The copy is made in a read transaction, so it is a consistent snapshot of the database and does not block
+        // writers
+        self.env.copy(compacting_dir_str, copy::COMPACT)?;
+        // Only a complete copy is moved to the directory that is swapped in on startup
+        fs::rename(&compacting_dir, &compacted_dir)?;
+
+        let original_size_bytes = fs::metadata(db_path.join(LMDB_DATA_FILE))?.len();
+        let compacted_size_bytes = fs::metadata(compacted_dir.join(LMDB_DATA_FILE))?.len();
+        info!(
+            target: LOG_TARGET,
+            "Compacted database from {} MB to {} MB in {:.2?}. The compacted database will be used after a restart.",
+            original_size_bytes / BYTES_PER_MB as u64,
+            compacted_size_bytes / BYTES_PER_MB as u64,
+            timer.elapsed()
+        );
+        Ok(LmdbCompactionResult {
+            path: compacted_dir,
+            original_size_bytes,
+            compacted_size_bytes,
+        })
+    }
+
     fn bad_block_exists(&self, block_hash: HashOutput) -> Result<(bool, String), ChainStorageError> {
         let txn = self.read_transaction()?;
         // We do this to ensure backwards compatibility on older exising dbs that did not store a reason
@@ -2549,6 +3240,14 @@ impl BlockchainBackend for LMDBDatabase {
         Ok(maybe_shard_id)
     }
 
+    fn fetch_validator_node_epoch_snapshot(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Option<ValidatorNodeEpochSnapshot>, ChainStorageError> {
+        let txn = self.read_transaction()?;
+        lmdb_get(&txn, &self.validator_node_epoch_snapshots, &epoch.as_u64())
+    }
+
     fn fetch_template_registrations(
         &self,
         start_height: u64,
@@ -2635,6 +3334,23 @@ fn fetch_horizon_data(txn: &ConstTransaction<'_>, db: &Database) -> Result<Horiz
         }),
     }
 }
+/// Fetches the horizon sync checkpoint from the provided metadata db.
+fn fetch_horizon_sync_checkpoint(
+    txn: &ConstTransaction<'_>,
+    db: &Database,
+) -> Result<Option<HorizonSyncCheckpoint>, ChainStorageError> {
+    let k = MetadataKey::HorizonSyncCheckpoint;
+    let val: Option<MetadataValue> = lmdb_get(txn, db, &k.as_u32())?;
+    match val {
+        Some(MetadataValue::HorizonSyncCheckpoint(checkpoint)) => Ok(Some(checkpoint)),
+        None => Ok(None),
+        Some(k) => Err(ChainStorageError::DataInconsistencyDetected {
+            function: "fetch_horizon_sync_checkpoint",
+            details: format!("Received incorrect value {:?} for key horizon sync checkpoint", k),
+        }),
+    }
+}
+
 // Fetches the best block hash from the provided metadata db.
 fn fetch_best_block(txn: &ConstTransaction<'_>, db: &Database) -> Result<BlockHash, ChainStorageError> {
     let k = MetadataKey::BestBlock;
@@ -2705,6 +3421,8 @@ enum MetadataKey {
     BestBlockTimestamp,
     MigrationVersion,
     TipSmt,
+    OutputIndexesEnabled,
+    HorizonSyncCheckpoint,
 }
 
 impl MetadataKey {
@@ -2726,6 +3444,8 @@ impl fmt::Display for MetadataKey {
             MetadataKey::BestBlockTimestamp => write!(f, "Chain tip block timestamp"),
             MetadataKey::MigrationVersion => write!(f, "Migration version"),
             MetadataKey::TipSmt => write!(f, "Chain tip Sparse Merkle Tree version"),
+            MetadataKey::OutputIndexesEnabled => write!(f, "Output indexes enabled"),
+            MetadataKey::HorizonSyncCheckpoint => write!(f, "Horizon sync checkpoint"),
         }
     }
 }
@@ -2741,6 +3461,8 @@ enum MetadataValue {
     HorizonData(HorizonData),
     BestBlockTimestamp(u64),
     MigrationVersion(u64),
+    OutputIndexesEnabled(bool),
+    HorizonSyncCheckpoint(HorizonSyncCheckpoint),
 }
 
 impl fmt::Display for MetadataValue {
@@ -2754,12 +3476,16 @@ impl fmt::Display for MetadataValue {
             MetadataValue::HorizonData(_) => write!(f, "Horizon data"),
             MetadataValue::BestBlockTimestamp(timestamp) => write!(f, "Chain tip block timestamp is {}", timestamp),
             MetadataValue::MigrationVersion(n) => write!(f, "Migration version {}", n),
+            MetadataValue::OutputIndexesEnabled(enabled) => write!(f, "Output indexes enabled is {}", enabled),
+            MetadataValue::HorizonSyncCheckpoint(checkpoint) => {
+                write!(f, "Horizon sync checkpoint at height {}", checkpoint.height)
+            },
         }
     }
 }
 
 fn run_migrations(db: &LMDBDatabase) -> Result<(), ChainStorageError> {
-    const MIGRATION_VERSION: u64 = 1;
+    const MIGRATION_VERSION: u64 = 2;
     let txn = db.read_transaction()?;
 
     let k = MetadataKey::MigrationVersion;
@@ -2776,6 +3502,13 @@ fn run_migrations(db: &LMDBDatabase) -> Result<(), ChainStorageError> {
 
     if n < MIGRATION_VERSION {
         // Add migrations here
+        if n < 2 {
+            // Reorgs recorded before v2 do not include the added and removed blocks and cannot be read
+            let txn = db.write_transaction()?;
+            let num_deleted = lmdb_clear(&txn, &db.reorgs)?;
+            txn.commit()?;
+            info!(target: LOG_TARGET, "Cleared {} reorgs recorded in the old format", num_deleted);
+        }
         info!(target: LOG_TARGET, "Migrated database to version {}", MIGRATION_VERSION);
         let txn = db.write_transaction()?;
         lmdb_replace(
@@ -2790,3 +3523,47 @@ fn run_migrations(db: &LMDBDatabase) -> Result<(), ChainStorageError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+    use crate::test_helpers::{blockchain::TempDatabase, create_consensus_constants};
+
+    #[test]
+    fn it_prunes_expired_validator_node_registrations_by_default() {
+        let db = TempDatabase::new();
+        assert!(db.max_reorg_depth.is_none());
+        let constants = create_consensus_constants(0);
+        let epoch_length = constants.epoch_length();
+        let validity_period = constants.validator_node_validity_period_epochs().as_u64() * epoch_length;
+        // An epoch boundary at which a registration at height 1 is beyond the validity period and the prune margin
+        let horizon = validity_period + VALIDATOR_NODE_PRUNE_REORG_MARGIN;
+        let height = (horizon / epoch_length + 3) * epoch_length;
+
+        let (_, expired) = PublicKey::random_keypair(&mut OsRng);
+        let (_, active) = PublicKey::random_keypair(&mut OsRng);
+        let txn = db.write_transaction().unwrap();
+        let store = db.validator_node_store(&txn);
+        for (registered_height, public_key) in [(1, &expired), (height - 1, &active)] {
+            store
+                .insert(registered_height, &ValidatorNodeEntry {
+                    public_key: public_key.clone(),
+                    commitment: Commitment::from_public_key(public_key),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        db.insert_validator_node_epoch_snapshot(&txn, &BlockHeader {
+            height,
+            ..BlockHeader::new(0)
+        })
+        .unwrap();
+        let store = db.validator_node_store(&txn);
+        assert!(store.get_shard_key(0, height, &expired).unwrap().is_none());
+        assert!(store.get_shard_key(0, height, &active).unwrap().is_some());
+    }
+}
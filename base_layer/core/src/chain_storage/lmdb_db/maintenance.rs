@@ -0,0 +1,200 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, path::PathBuf, time::Duration};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use tari_shutdown::ShutdownSignal;
+use tokio::time;
+
+use crate::chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend};
+
+const LOG_TARGET: &str = "c::cs::lmdb_db::maintenance";
+
+/// The name of the directory, within the database directory, that a compacted copy of the database is written to
+pub(crate) const COMPACTING_DIR_NAME: &str = "compacting";
+/// The name of the directory, within the database directory, that holds a complete compacted copy of the database.
+/// The copy replaces the database the next time it is opened.
+pub(crate) const COMPACTED_DIR_NAME: &str = "compacted";
+
+/// Configuration for the LMDB maintenance task
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LmdbMaintenanceConfig {
+    /// Set to false to disable the maintenance task. Default: true
+    pub enabled: bool,
+    /// The time between map utilization checks. Default: 60 seconds
+    #[serde(with = "serializers::seconds")]
+    pub check_interval: Duration,
+    /// The memory map is grown when more than this percentage of it is in use. Default: 90
+    pub max_map_utilization_percentage: u8,
+}
+
+impl Default for LmdbMaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval: Duration::from_secs(60),
+            max_map_utilization_percentage: 90,
+        }
+    }
+}
+
+/// The utilization of the LMDB memory map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapUtilization {
+    /// The size of the memory map in bytes
+    pub map_size_bytes: u64,
+    /// The number of bytes of the memory map that have been used, i.e. the size of the data file
+    pub used_bytes: u64,
+    /// The database page size in bytes
+    pub page_size: u64,
+    /// The number of pages in the memory map that have not been used yet
+    pub free_pages: u64,
+}
+
+impl MapUtilization {
+    pub fn new(map_size_bytes: u64, page_size: u64, last_page_number: u64) -> Self {
+        let used_bytes = page_size.saturating_mul(last_page_number).min(map_size_bytes);
+        Self {
+            map_size_bytes,
+            used_bytes,
+            page_size,
+            free_pages: (map_size_bytes - used_bytes).checked_div(page_size).unwrap_or(0),
+        }
+    }
+
+    pub fn free_bytes(&self) -> u64 {
+        self.map_size_bytes - self.used_bytes
+    }
+
+    /// The percentage of the memory map that is in use
+    pub fn utilization_percentage(&self) -> u8 {
+        if self.map_size_bytes == 0 {
+            return 100;
+        }
+        let pct = u128::from(self.used_bytes) * 100 / u128::from(self.map_size_bytes);
+        u8::try_from(pct).unwrap_or(100)
+    }
+
+    pub fn exceeds(&self, max_utilization_percentage: u8) -> bool {
+        self.utilization_percentage() >= max_utilization_percentage
+    }
+
+    /// The number of bytes the memory map must grow by so that no more than `max_utilization_percentage` of it is in
+    /// use.
+    pub fn required_growth_bytes(&self, max_utilization_percentage: u8) -> u64 {
+        if !self.exceeds(max_utilization_percentage) {
+            return 0;
+        }
+        let max_pct = u128::from(max_utilization_percentage.clamp(1, 99));
+        let target = u128::from(self.used_bytes) * 100 / max_pct + 1;
+        u64::try_from(target)
+            .unwrap_or(u64::MAX)
+            .saturating_sub(self.map_size_bytes)
+    }
+}
+
+/// The result of compacting the database
+#[derive(Debug, Clone)]
+pub struct LmdbCompactionResult {
+    /// The directory containing the compacted database file
+    pub path: PathBuf,
+    /// The size of the data file before compaction
+    pub original_size_bytes: u64,
+    /// The size of the compacted data file
+    pub compacted_size_bytes: u64,
+}
+
+/// Periodically checks the utilization of the LMDB memory map and grows the map before writes fail because the map is
+/// full. Runs until the shutdown signal is triggered.
+pub async fn run_lmdb_maintenance<B: BlockchainBackend + 'static>(
+    db: AsyncBlockchainDb<B>,
+    config: LmdbMaintenanceConfig,
+    mut shutdown: ShutdownSignal,
+) {
+    if !config.enabled {
+        debug!(target: LOG_TARGET, "LMDB maintenance is disabled");
+        return;
+    }
+    let mut interval = time::interval(config.check_interval);
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.wait() => break,
+        }
+
+        match db.fetch_map_utilization().await {
+            Ok(utilization) => trace!(
+                target: LOG_TARGET,
+                "LMDB map utilization: {}% ({} of {} bytes used, {} free pages)",
+                utilization.utilization_percentage(),
+                utilization.used_bytes,
+                utilization.map_size_bytes,
+                utilization.free_pages
+            ),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Failed to fetch LMDB map utilization: {}", err);
+                continue;
+            },
+        }
+        match db.grow_map_if_required(config.max_map_utilization_percentage).await {
+            Ok(true) => info!(target: LOG_TARGET, "LMDB memory map grown by the maintenance task"),
+            Ok(false) => {},
+            Err(err) => warn!(target: LOG_TARGET, "Failed to grow the LMDB memory map: {}", err),
+        }
+    }
+    debug!(target: LOG_TARGET, "LMDB maintenance task has shut down");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_calculates_utilization() {
+        let utilization = MapUtilization::new(1000 * 4096, 4096, 950);
+        assert_eq!(utilization.used_bytes, 950 * 4096);
+        assert_eq!(utilization.free_pages, 50);
+        assert_eq!(utilization.free_bytes(), 50 * 4096);
+        assert_eq!(utilization.utilization_percentage(), 95);
+        assert!(utilization.exceeds(90));
+        assert!(!utilization.exceeds(96));
+
+        let empty = MapUtilization::new(0, 4096, 0);
+        assert_eq!(empty.utilization_percentage(), 100);
+        assert_eq!(empty.free_pages, 0);
+    }
+
+    #[test]
+    fn it_calculates_the_required_growth() {
+        let utilization = MapUtilization::new(1000, 1, 950);
+        assert_eq!(utilization.required_growth_bytes(96), 0);
+        let growth = utilization.required_growth_bytes(90);
+        let grown = MapUtilization::new(1000 + growth, 1, 950);
+        assert!(!grown.exceeds(90));
+        assert_eq!(grown.utilization_percentage(), 89);
+    }
+}
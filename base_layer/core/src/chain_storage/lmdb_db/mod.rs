@@ -21,11 +21,15 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
+pub use maintenance::{run_lmdb_maintenance, LmdbCompactionResult, LmdbMaintenanceConfig, MapUtilization};
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::HashOutput;
 use tari_crypto::hash_domain;
 
-use crate::transactions::transaction_components::{TransactionInput, TransactionKernel, TransactionOutput};
+use crate::{
+    proof_of_work::Difficulty,
+    transactions::transaction_components::{TransactionInput, TransactionKernel, TransactionOutput},
+};
 
 mod composite_key;
 pub(crate) mod cursors;
@@ -33,6 +37,7 @@ pub(crate) mod helpers;
 mod lmdb;
 #[allow(clippy::module_inception)]
 mod lmdb_db;
+mod maintenance;
 mod validator_node_store;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -74,4 +79,12 @@ pub(crate) struct TransactionKernelRowData {
     pub hash: HashOutput,
 }
 
+/// The (timestamp, target difficulty) samples of a single PoW algorithm, oldest first, in the chain that ends at
+/// `tip_hash`.
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct TargetDifficultyWindowRowData {
+    pub tip_hash: HashOutput,
+    pub samples: Vec<(u64, Difficulty)>,
+}
+
 hash_domain!(CoreChainStorageHashDomain, "com.tari.base_layer.core.lmdb_db", 1);
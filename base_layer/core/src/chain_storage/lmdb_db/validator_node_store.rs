@@ -113,6 +113,28 @@ impl ValidatorNodeStore<'_, WriteTransaction<'_>> {
         )?;
         Ok(())
     }
+
+    /// Deletes all validator node registrations made below `height`. Returns the number of registrations deleted.
+    pub fn prune_registrations_before(&self, height: u64) -> Result<usize, ChainStorageError> {
+        let mut expired = Vec::new();
+        {
+            let mut cursor = self.db_read_cursor()?;
+            let mut next = cursor.seek_range::<ValidatorNodeStoreKey>(&0u64.to_be_bytes())?;
+            while let Some((key, vn)) = next {
+                let registered_height = u64::from_key_bytes(&key[0..8])?;
+                if registered_height >= height {
+                    break;
+                }
+                expired.push((registered_height, vn));
+                next = cursor.next::<ValidatorNodeStoreKey>()?;
+            }
+        }
+
+        for (registered_height, vn) in &expired {
+            self.delete(*registered_height, &vn.public_key, &vn.commitment)?;
+        }
+        Ok(expired.len())
+    }
 }
 
 impl<'a, Txn: Deref<Target = ConstTransaction<'a>>> ValidatorNodeStore<'a, Txn> {
@@ -350,6 +372,27 @@ mod tests {
         }
     }
 
+    mod prune_registrations_before {
+        use super::*;
+
+        #[test]
+        fn it_deletes_registrations_below_the_height() {
+            let db = TempLmdbDatabase::with_dbs(DBS);
+            let txn = db.write_transaction();
+            let store = create_store(&db, &txn);
+            let expired = insert_n_vns(&store, 1, 2);
+            let remaining = insert_n_vns(&store, 3, 3);
+
+            let num_pruned = store.prune_registrations_before(3).unwrap();
+            assert_eq!(num_pruned, 2);
+            assert_eq!(store.get_vn_set(0, 10).unwrap(), remaining);
+            assert!(store.get_shard_key(0, 10, &expired[0].0).unwrap().is_none());
+            assert!(store.get_shard_key(0, 10, &expired[1].0).unwrap().is_none());
+
+            assert_eq!(store.prune_registrations_before(3).unwrap(), 0);
+        }
+    }
+
     mod get_shard_key {
         use super::*;
 
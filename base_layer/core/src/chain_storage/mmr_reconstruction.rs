@@ -0,0 +1,310 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    cmp,
+    convert::TryFrom,
+    fmt::{Display, Error, Formatter},
+    time::Instant,
+};
+
+use log::*;
+use rayon::prelude::*;
+use tari_common_types::types::{Commitment, FixedHash};
+use tari_mmr::{
+    pruned_hashset::PrunedHashSet,
+    sparse_merkle_tree::{NodeKey, ValueHash},
+};
+use tari_utilities::ByteArray;
+use tokio::sync::broadcast;
+
+use crate::{
+    blocks::{ChainHeader, UpdateBlockAccumulatedData},
+    chain_storage::{
+        consts::BLOCKCHAIN_DATABASE_MMR_RECONSTRUCTION_CHUNK_SIZE,
+        BlockchainBackend,
+        BlockchainDatabase,
+        ChainStorageError,
+        DbTransaction,
+    },
+    OutputSmt,
+    PrunedKernelMmr,
+};
+
+const LOG_TARGET: &str = "c::cs::mmr_reconstruction";
+
+/// The tree that is being rebuilt by [BlockchainDatabase::reconstruct_mmrs]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MmrReconstructionStage {
+    Kernels,
+    Outputs,
+}
+
+impl Display for MmrReconstructionStage {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            MmrReconstructionStage::Kernels => f.write_str("kernels"),
+            MmrReconstructionStage::Outputs => f.write_str("outputs"),
+        }
+    }
+}
+
+/// Emitted by [BlockchainDatabase::reconstruct_mmrs] each time a chunk of blocks has been added to a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MmrReconstructionProgress {
+    pub stage: MmrReconstructionStage,
+    /// The height of the last block that has been added to the tree
+    pub height: u64,
+    pub tip_height: u64,
+}
+
+impl MmrReconstructionProgress {
+    pub fn percentage(&self) -> u64 {
+        if self.tip_height == 0 {
+            return 100;
+        }
+        self.height.saturating_mul(100) / self.tip_height
+    }
+}
+
+impl Display for MmrReconstructionProgress {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(
+            f,
+            "{} {}/{} ({}%)",
+            self.stage,
+            self.height,
+            self.tip_height,
+            self.percentage()
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MmrReconstructionResult {
+    pub height: u64,
+    pub block_hash: FixedHash,
+    pub kernel_mmr_size: u64,
+    pub output_smt_size: u64,
+}
+
+/// The kernel MMR leaves of a single block, hashed on a worker thread
+struct BlockKernelLeaves {
+    header: ChainHeader,
+    leaves: Vec<Vec<u8>>,
+    kernel_sum: Commitment,
+}
+
+struct ProgressNotifier {
+    sender: Option<broadcast::Sender<MmrReconstructionProgress>>,
+    tip_height: u64,
+}
+
+impl ProgressNotifier {
+    fn notify(&self, stage: MmrReconstructionStage, height: u64) {
+        if let Some(sender) = &self.sender {
+            // An error only means that nobody is listening
+            let _size = sender.send(MmrReconstructionProgress {
+                stage,
+                height,
+                tip_height: self.tip_height,
+            });
+        }
+    }
+}
+
+impl<B: BlockchainBackend> BlockchainDatabase<B> {
+    /// Rebuilds the pruned kernel MMR stored with each block and the output SMT at the chain tip from the kernels and
+    /// outputs in the database. Blocks are processed in chunks: the leaves of each block in a chunk are read and
+    /// hashed in parallel, then merged into the trees in block order. Every rebuilt kernel MMR root is checked against
+    /// its header and the output SMT root is checked against the tip header, so nothing is written for a block whose
+    /// data does not match the header chain. A progress event is sent on `progress` after each chunk.
+    pub fn reconstruct_mmrs(
+        &self,
+        progress: Option<broadcast::Sender<MmrReconstructionProgress>>,
+    ) -> Result<MmrReconstructionResult, ChainStorageError> {
+        let timer = Instant::now();
+        let metadata = self.get_chain_metadata()?;
+        let tip_header = self.fetch_chain_header(metadata.best_block_height())?;
+        let notifier = ProgressNotifier {
+            sender: progress,
+            tip_height: tip_header.height(),
+        };
+
+        let kernel_mmr_size = self.reconstruct_kernel_mmr(tip_header.height(), &notifier)?;
+        let output_smt = self.reconstruct_output_smt(&tip_header, &notifier)?;
+
+        // The blocks are read a chunk at a time so that other database users are not blocked for the duration of the
+        // reconstruction, so make sure that the tip did not change under us before replacing the tip SMT.
+        if self.get_chain_metadata()?.best_block_hash() != tip_header.hash() {
+            return Err(ChainStorageError::InvalidOperation(
+                "The chain tip changed while reconstructing the MMRs".to_string(),
+            ));
+        }
+        let result = MmrReconstructionResult {
+            height: tip_header.height(),
+            block_hash: *tip_header.hash(),
+            kernel_mmr_size,
+            output_smt_size: output_smt.size(),
+        };
+        let mut txn = DbTransaction::new();
+        txn.insert_tip_smt(output_smt);
+        self.write(txn)?;
+        info!(
+            target: LOG_TARGET,
+            "Reconstructed the kernel MMR ({} leaves) and output SMT ({} leaves) up to height {} in {:.2?}",
+            result.kernel_mmr_size,
+            result.output_smt_size,
+            result.height,
+            timer.elapsed()
+        );
+        Ok(result)
+    }
+
+    fn reconstruct_kernel_mmr(&self, tip_height: u64, notifier: &ProgressNotifier) -> Result<u64, ChainStorageError> {
+        let mut kernel_mmr = PrunedKernelMmr::new(PrunedHashSet::default());
+        let mut start = 0;
+        while start <= tip_height {
+            let end = cmp::min(
+                start + BLOCKCHAIN_DATABASE_MMR_RECONSTRUCTION_CHUNK_SIZE - 1,
+                tip_height,
+            );
+            let heights = (start..=end).collect::<Vec<_>>();
+            let blocks = heights
+                .par_iter()
+                .map(|height| self.fetch_block_kernel_leaves(*height))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut txn = DbTransaction::new();
+            for block in blocks {
+                for leaf in block.leaves {
+                    kernel_mmr.push(leaf)?;
+                }
+                let kernel_mr = FixedHash::try_from(kernel_mmr.get_merkle_root()?)?;
+                if kernel_mr != block.header.header().kernel_mr {
+                    return Err(ChainStorageError::DataInconsistencyDetected {
+                        function: "reconstruct_mmrs",
+                        details: format!(
+                            "Kernel MMR root mismatch at height {}: expected {} but got {}",
+                            block.header.height(),
+                            block.header.header().kernel_mr,
+                            kernel_mr
+                        ),
+                    });
+                }
+                txn.update_block_accumulated_data(*block.header.hash(), UpdateBlockAccumulatedData {
+                    kernel_hash_set: Some(kernel_mmr.get_pruned_hash_set()?),
+                    kernel_sum: Some(block.kernel_sum),
+                });
+            }
+            self.write(txn)?;
+            debug!(
+                target: LOG_TARGET,
+                "Reconstructed the kernel MMR up to height {} of {}", end, tip_height
+            );
+            notifier.notify(MmrReconstructionStage::Kernels, end);
+            start = end + 1;
+        }
+        Ok(kernel_mmr.get_leaf_count()? as u64)
+    }
+
+    fn fetch_block_kernel_leaves(&self, height: u64) -> Result<BlockKernelLeaves, ChainStorageError> {
+        let header = self.fetch_chain_header(height)?;
+        let kernels = self.fetch_kernels_in_block(*header.hash())?;
+        let mut kernel_sum = Commitment::default();
+        let mut leaves = Vec::with_capacity(kernels.len());
+        for kernel in &kernels {
+            kernel_sum = &kernel_sum + &kernel.excess;
+            leaves.push(kernel.hash().to_vec());
+        }
+        Ok(BlockKernelLeaves {
+            header,
+            leaves,
+            kernel_sum,
+        })
+    }
+
+    fn reconstruct_output_smt(
+        &self,
+        tip_header: &ChainHeader,
+        notifier: &ProgressNotifier,
+    ) -> Result<OutputSmt, ChainStorageError> {
+        let mut output_smt = OutputSmt::new();
+        let mut start = 0;
+        while start <= tip_header.height() {
+            let end = cmp::min(
+                start + BLOCKCHAIN_DATABASE_MMR_RECONSTRUCTION_CHUNK_SIZE - 1,
+                tip_header.height(),
+            );
+            let heights = (start..=end).collect::<Vec<_>>();
+            let blocks = heights
+                .par_iter()
+                .map(|height| self.fetch_block_output_leaves(*height, *tip_header.hash()))
+                .collect::<Result<Vec<_>, _>>()?;
+            for leaves in blocks {
+                for (key, value) in leaves {
+                    output_smt.insert(key, value)?;
+                }
+            }
+            debug!(
+                target: LOG_TARGET,
+                "Reconstructed the output SMT up to height {} of {}",
+                end,
+                tip_header.height()
+            );
+            notifier.notify(MmrReconstructionStage::Outputs, end);
+            start = end + 1;
+        }
+
+        let output_mr = FixedHash::try_from(output_smt.hash().as_slice())?;
+        if output_mr != tip_header.header().output_mr {
+            return Err(ChainStorageError::DataInconsistencyDetected {
+                function: "reconstruct_mmrs",
+                details: format!(
+                    "Output SMT root mismatch at height {}: expected {} but got {}",
+                    tip_header.height(),
+                    tip_header.header().output_mr,
+                    output_mr
+                ),
+            });
+        }
+        Ok(output_smt)
+    }
+
+    /// Returns the SMT leaves of the outputs in the block at `height` that are unspent at the tip
+    fn fetch_block_output_leaves(
+        &self,
+        height: u64,
+        tip_hash: FixedHash,
+    ) -> Result<Vec<(NodeKey, ValueHash)>, ChainStorageError> {
+        let header_hash = *self.fetch_chain_header(height)?.hash();
+        self.fetch_outputs_in_block_with_spend_state(header_hash, Some(tip_hash))?
+            .into_iter()
+            .filter(|(output, spent)| !spent && !output.is_burned())
+            .map(|(output, _)| {
+                let smt_key = NodeKey::try_from(output.commitment.as_bytes())?;
+                let smt_node = ValueHash::try_from(output.smt_hash(height).as_slice())?;
+                Ok((smt_key, smt_node))
+            })
+            .collect()
+    }
+}
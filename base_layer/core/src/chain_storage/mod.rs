@@ -67,11 +67,28 @@ pub use error::{ChainStorageError, Optional, OrNotFound};
 mod horizon_data;
 pub use horizon_data::HorizonData;
 
+mod horizon_sync_checkpoint;
+pub use horizon_sync_checkpoint::HorizonSyncCheckpoint;
+
+mod balance_sums;
+pub use balance_sums::BlockBalanceSums;
+
 mod reorg;
 pub use reorg::Reorg;
 
+mod remote_db;
+pub use remote_db::RemoteBlockchainBackend;
+
 mod lmdb_db;
-pub use lmdb_db::{create_lmdb_database, create_recovery_lmdb_database, LMDBDatabase};
+pub use lmdb_db::{
+    create_lmdb_database,
+    create_recovery_lmdb_database,
+    run_lmdb_maintenance,
+    LMDBDatabase,
+    LmdbCompactionResult,
+    LmdbMaintenanceConfig,
+    MapUtilization,
+};
 
 mod stats;
 pub use stats::{DbBasicStats, DbSize, DbStat, DbTotalSizeStats};
@@ -82,12 +99,33 @@ pub use target_difficulties::TargetDifficulties;
 pub use utxo_mined_info::*;
 
 mod active_validator_node;
-pub use active_validator_node::ValidatorNodeEntry;
+pub use active_validator_node::{
+    ValidatorNodeChange,
+    ValidatorNodeChangeKind,
+    ValidatorNodeEntry,
+    ValidatorNodeEpochSnapshot,
+};
 use tari_common_types::types::HashOutput;
 
 mod template_registation;
 pub use template_registation::TemplateRegistrationEntry;
 
+mod write_batch;
+pub use write_batch::{BatchedDbWriter, WriteBatchConfig};
+
+mod integrity;
+pub use integrity::{IntegrityIssue, IntegrityReport};
+
+mod mmr_reconstruction;
+pub use mmr_reconstruction::{MmrReconstructionProgress, MmrReconstructionResult, MmrReconstructionStage};
+
+mod burn_proofs;
+
+mod output_proofs;
+
+mod utxo_snapshot;
+pub use utxo_snapshot::{read_utxo_snapshot_header, UtxoSnapshotHeader, UTXO_SNAPSHOT_VERSION};
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
 pub struct ChainTipData {
     pub hash: HashOutput,
@@ -0,0 +1,140 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use log::*;
+use tari_common_types::types::HashOutput;
+use tari_mmr::sparse_merkle_tree::{InclusionProof, NodeKey, ValueHash};
+use tari_utilities::ByteArray;
+
+use crate::{
+    chain_storage::{BlockchainBackend, BlockchainDatabase, ChainStorageError},
+    output_inclusion_proof::OutputInclusionProof,
+    OutputSmt,
+};
+
+const LOG_TARGET: &str = "c::cs::output_proofs";
+
+impl<B: BlockchainBackend> BlockchainDatabase<B> {
+    /// Produces a proof that the output was unspent at the given height, which can be verified against the header at
+    /// that height with [OutputInclusionProof::verify]. Only the output tree of the tip is stored, so the tree at the
+    /// given height is rebuilt by undoing the blocks above it. This is only possible for heights that have not been
+    /// pruned, and takes longer the further the height is below the tip.
+    pub fn fetch_output_inclusion_proof(
+        &self,
+        output_hash: HashOutput,
+        height: u64,
+    ) -> Result<OutputInclusionProof, ChainStorageError> {
+        let db = self.db_read_access()?;
+        let metadata = db.fetch_chain_metadata()?;
+        if height > metadata.best_block_height() || height < metadata.pruned_height() {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Cannot produce an output proof at height {}, the available heights are {} to {}",
+                height,
+                metadata.pruned_height(),
+                metadata.best_block_height()
+            )));
+        }
+
+        let output_info = db
+            .fetch_output(&output_hash)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "Output",
+                field: "hash",
+                value: output_hash.to_string(),
+            })?;
+        if output_info.mined_height > height {
+            return Err(ChainStorageError::InvalidOperation(format!(
+                "Output {} was mined at height {}, after height {}",
+                output_hash, output_info.mined_height, height
+            )));
+        }
+        if let Some(input_info) = db.fetch_input(&output_hash)? {
+            if input_info.spent_height <= height {
+                return Err(ChainStorageError::InvalidOperation(format!(
+                    "Output {} was spent at height {}, at or before height {}",
+                    output_hash, input_info.spent_height, height
+                )));
+            }
+        }
+
+        let mut output_smt = db.fetch_tip_smt()?;
+        for h in (height + 1..=metadata.best_block_height()).rev() {
+            let header_hash = *db.fetch_chain_header_by_height(h)?.hash();
+            undo_block_in_smt(&*db, &header_hash, &mut output_smt)?;
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Rebuilt the output tree at height {} from the tip at height {}",
+            height,
+            metadata.best_block_height()
+        );
+
+        let header = db.fetch_chain_header_by_height(height)?.into_header();
+        let key = NodeKey::try_from(output_info.output.commitment.as_bytes())?;
+        let value = ValueHash::try_from(output_info.output.smt_hash(output_info.mined_height).as_slice())?;
+        let proof = InclusionProof::from_tree(&output_smt, &key, &value)?;
+        Ok(OutputInclusionProof {
+            header,
+            output_hash,
+            mined_height: output_info.mined_height,
+            siblings: proof.siblings().to_vec(),
+        })
+    }
+}
+
+/// Removes the outputs created in the block from the output tree and restores the outputs it spent, the same way the
+/// tree is updated when the block is rewound.
+fn undo_block_in_smt<B: BlockchainBackend>(
+    db: &B,
+    header_hash: &HashOutput,
+    output_smt: &mut OutputSmt,
+) -> Result<(), ChainStorageError> {
+    let outputs = db.fetch_outputs_in_block(header_hash)?;
+    let inputs = db.fetch_inputs_in_block(header_hash)?;
+    let spent_hashes = inputs.iter().map(|i| i.output_hash()).collect::<Vec<_>>();
+
+    for output in &outputs {
+        // Outputs spent in the same block and burned outputs were never added to the tree
+        if output.is_burned() || spent_hashes.contains(&output.hash()) {
+            continue;
+        }
+        output_smt.delete(&NodeKey::try_from(output.commitment.as_bytes())?)?;
+    }
+    for output_hash in spent_hashes {
+        if outputs.iter().any(|o| o.hash() == output_hash) {
+            continue;
+        }
+        let spent = db
+            .fetch_output(&output_hash)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "Output",
+                field: "hash",
+                value: output_hash.to_string(),
+            })?;
+        let key = NodeKey::try_from(spent.output.commitment.as_bytes())?;
+        let value = ValueHash::try_from(spent.output.smt_hash(spent.mined_height).as_slice())?;
+        output_smt.insert(key, value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,484 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, future::Future};
+
+use futures::StreamExt;
+use log::*;
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
+    types::{Commitment, FixedHash, HashOutput, PublicKey, Signature},
+};
+use tari_comms::protocol::rpc::RpcError;
+use tari_utilities::{epoch_time::EpochTime, hex::Hex};
+use tokio::{runtime::Handle, task};
+
+use crate::{
+    base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient},
+    blocks::{Block, BlockAccumulatedData, BlockHeader, BlockHeaderAccumulatedData, ChainBlock, ChainHeader},
+    chain_storage::{
+        BlockBalanceSums,
+        BlockchainBackend,
+        ChainStorageError,
+        DbBasicStats,
+        DbKey,
+        DbTotalSizeStats,
+        DbTransaction,
+        DbValue,
+        HorizonData,
+        HorizonSyncCheckpoint,
+        InputMinedInfo,
+        LmdbCompactionResult,
+        MapUtilization,
+        MmrTree,
+        OutputMinedInfo,
+        Reorg,
+        TemplateRegistrationEntry,
+        ValidatorNodeEpochSnapshot,
+    },
+    proof_of_work::{Difficulty, PowAlgorithm},
+    proto::base_node::{FindChainSplitRequest, SyncBlocksRequest, UtxoQueryRequest},
+    transactions::{
+        aggregated_body::AggregateBody,
+        transaction_components::{TransactionInput, TransactionKernel, TransactionOutput},
+    },
+    OutputSmt,
+};
+
+const LOG_TARGET: &str = "c::cs::remote_db";
+
+/// A read-only [BlockchainBackend] that proxies reads to a remote base node over the base node sync and wallet RPC
+/// protocols. This allows lightweight services, such as block explorers, to use the
+/// [BlockchainDatabase](super::BlockchainDatabase) query API without keeping their own copy of the blockchain.
+///
+/// Headers, block bodies, outputs and the chain metadata are read from the remote node. Writes, and reads of data that
+/// the RPC protocols do not provide (accumulated data, orphans, indexes and database statistics), return an error.
+/// Every call blocks on an RPC request, so the backend must be used from a multi-threaded tokio runtime.
+#[derive(Clone)]
+pub struct RemoteBlockchainBackend {
+    sync_client: BaseNodeSyncRpcClient,
+    wallet_client: BaseNodeWalletRpcClient,
+    runtime: Handle,
+}
+
+impl RemoteBlockchainBackend {
+    /// Create a backend that reads from the base node that the given RPC clients are connected to. This must be called
+    /// from within a tokio runtime.
+    pub fn new(sync_client: BaseNodeSyncRpcClient, wallet_client: BaseNodeWalletRpcClient) -> Self {
+        Self {
+            sync_client,
+            wallet_client,
+            runtime: Handle::current(),
+        }
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        task::block_in_place(|| self.runtime.block_on(future))
+    }
+
+    fn fetch_header_by_height(&self, height: u64) -> Result<Option<BlockHeader>, ChainStorageError> {
+        let mut client = self.sync_client.clone();
+        match self.block_on(client.get_header_by_height(height)) {
+            Ok(header) => BlockHeader::try_from(header)
+                .map(Some)
+                .map_err(ChainStorageError::ConversionError),
+            Err(RpcError::RequestFailed(status)) if status.is_not_found() => Ok(None),
+            Err(err) => Err(remote_error(err)),
+        }
+    }
+
+    fn fetch_header_by_hash(&self, hash: &HashOutput) -> Result<Option<BlockHeader>, ChainStorageError> {
+        // The RPC protocols cannot fetch a header by hash, so the height is found using the header that follows it
+        let mut client = self.sync_client.clone();
+        let request = FindChainSplitRequest {
+            block_hashes: vec![hash.to_vec()],
+            header_count: 1,
+        };
+        let response = match self.block_on(client.find_chain_split(request)) {
+            Ok(response) => response,
+            Err(RpcError::RequestFailed(status)) if status.is_not_found() => return Ok(None),
+            Err(err) => return Err(remote_error(err)),
+        };
+        let height = match response.headers.into_iter().next() {
+            Some(next_header) => {
+                let next_header = BlockHeader::try_from(next_header).map_err(ChainStorageError::ConversionError)?;
+                next_header.height.saturating_sub(1)
+            },
+            None => self.fetch_chain_metadata()?.best_block_height(),
+        };
+        Ok(self
+            .fetch_header_by_height(height)?
+            .filter(|header| header.hash() == *hash))
+    }
+
+    fn fetch_block_body(&self, header_hash: &HashOutput) -> Result<AggregateBody, ChainStorageError> {
+        let header = self
+            .fetch_header_by_hash(header_hash)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHeader",
+                field: "hash",
+                value: header_hash.to_hex(),
+            })?;
+        let mut client = self.sync_client.clone();
+        let request = SyncBlocksRequest {
+            start_hash: header.prev_hash.to_vec(),
+            end_hash: header_hash.to_vec(),
+        };
+        let mut stream = self.block_on(client.sync_blocks(request)).map_err(remote_error)?;
+        let response = self
+            .block_on(stream.next())
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "Block",
+                field: "hash",
+                value: header_hash.to_hex(),
+            })?
+            .map_err(remote_error)?;
+        if response.hash != header_hash.as_slice() {
+            return Err(ChainStorageError::UnexpectedResult(format!(
+                "Remote node returned block {} instead of block {}",
+                response.hash.to_hex(),
+                header_hash.to_hex()
+            )));
+        }
+        response
+            .body
+            .map(AggregateBody::try_from)
+            .ok_or_else(|| ChainStorageError::UnexpectedResult("Remote node returned an empty block".to_string()))?
+            .map_err(ChainStorageError::ConversionError)
+    }
+}
+
+fn remote_error(err: RpcError) -> ChainStorageError {
+    debug!(target: LOG_TARGET, "Remote blockchain backend request failed: {}", err);
+    ChainStorageError::RemoteBackendError(err.to_string())
+}
+
+fn unsupported<T>(operation: &'static str) -> Result<T, ChainStorageError> {
+    Err(ChainStorageError::InvalidOperation(format!(
+        "`{}` is not supported by the remote blockchain backend",
+        operation
+    )))
+}
+
+fn read_only<T>() -> Result<T, ChainStorageError> {
+    Err(ChainStorageError::InvalidOperation(
+        "The remote blockchain backend is read-only".to_string(),
+    ))
+}
+
+impl BlockchainBackend for RemoteBlockchainBackend {
+    fn write(&mut self, _tx: DbTransaction) -> Result<(), ChainStorageError> {
+        read_only()
+    }
+
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ChainStorageError> {
+        match key {
+            DbKey::HeaderHeight(height) => Ok(self
+                .fetch_header_by_height(*height)?
+                .map(|header| DbValue::HeaderHeight(Box::new(header)))),
+            DbKey::HeaderHash(hash) => Ok(self
+                .fetch_header_by_hash(hash)?
+                .map(|header| DbValue::HeaderHash(Box::new(header)))),
+            DbKey::OrphanBlock(_) => unsupported("fetch orphan block"),
+        }
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, ChainStorageError> {
+        match key {
+            DbKey::HeaderHeight(height) => Ok(*height <= self.fetch_chain_metadata()?.best_block_height()),
+            DbKey::HeaderHash(hash) => Ok(self.fetch_header_by_hash(hash)?.is_some()),
+            DbKey::OrphanBlock(_) => unsupported("contains orphan block"),
+        }
+    }
+
+    fn fetch_chain_header_by_height(&self, _height: u64) -> Result<ChainHeader, ChainStorageError> {
+        unsupported("fetch_chain_header_by_height")
+    }
+
+    fn fetch_header_accumulated_data(
+        &self,
+        _hash: &HashOutput,
+    ) -> Result<Option<BlockHeaderAccumulatedData>, ChainStorageError> {
+        unsupported("fetch_header_accumulated_data")
+    }
+
+    fn fetch_chain_header_in_all_chains(&self, _hash: &HashOutput) -> Result<ChainHeader, ChainStorageError> {
+        unsupported("fetch_chain_header_in_all_chains")
+    }
+
+    fn fetch_header_containing_kernel_mmr(&self, _mmr_position: u64) -> Result<ChainHeader, ChainStorageError> {
+        unsupported("fetch_header_containing_kernel_mmr")
+    }
+
+    fn is_empty(&self) -> Result<bool, ChainStorageError> {
+        // A base node always has at least the genesis block
+        self.fetch_chain_metadata().map(|_| false)
+    }
+
+    fn fetch_block_accumulated_data(
+        &self,
+        _header_hash: &HashOutput,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        unsupported("fetch_block_accumulated_data")
+    }
+
+    fn fetch_block_accumulated_data_by_height(
+        &self,
+        _height: u64,
+    ) -> Result<Option<BlockAccumulatedData>, ChainStorageError> {
+        unsupported("fetch_block_accumulated_data_by_height")
+    }
+
+    fn fetch_kernels_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionKernel>, ChainStorageError> {
+        let (_, _, kernels) = self.fetch_block_body(header_hash)?.dissolve();
+        Ok(kernels)
+    }
+
+    fn fetch_kernel_by_excess_sig(
+        &self,
+        _excess_sig: &Signature,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        unsupported("fetch_kernel_by_excess_sig")
+    }
+
+    fn fetch_kernel_by_burn_commitment(
+        &self,
+        _burn_commitment: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        unsupported("fetch_kernel_by_burn_commitment")
+    }
+
+    fn fetch_outputs_in_block_with_spend_state(
+        &self,
+        _header_hash: &HashOutput,
+        _spend_status_at_header: Option<HashOutput>,
+    ) -> Result<Vec<(TransactionOutput, bool)>, ChainStorageError> {
+        unsupported("fetch_outputs_in_block_with_spend_state")
+    }
+
+    fn fetch_output(&self, output_hash: &HashOutput) -> Result<Option<OutputMinedInfo>, ChainStorageError> {
+        let mut client = self.wallet_client.clone();
+        let request = UtxoQueryRequest {
+            output_hashes: vec![output_hash.to_vec()],
+        };
+        let response = self.block_on(client.utxo_query(request)).map_err(remote_error)?;
+        let utxo = match response.responses.into_iter().next() {
+            Some(utxo) => utxo,
+            None => return Ok(None),
+        };
+        let output = utxo
+            .output
+            .map(TransactionOutput::try_from)
+            .ok_or_else(|| ChainStorageError::UnexpectedResult("Remote node returned an empty output".to_string()))?
+            .map_err(ChainStorageError::ConversionError)?;
+        Ok(Some(OutputMinedInfo {
+            output,
+            mined_height: utxo.mined_at_height,
+            header_hash: FixedHash::try_from(utxo.mined_in_block)?,
+            mined_timestamp: utxo.mined_timestamp,
+        }))
+    }
+
+    fn fetch_input(&self, _output_hash: &HashOutput) -> Result<Option<InputMinedInfo>, ChainStorageError> {
+        unsupported("fetch_input")
+    }
+
+    fn output_indexes_enabled(&self) -> bool {
+        false
+    }
+
+    fn fetch_outputs_by_payref(&self, _payrefs: &[FixedHash]) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        unsupported("fetch_outputs_by_payref")
+    }
+
+    fn fetch_outputs_by_script_key(&self, _script_key: &PublicKey) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        unsupported("fetch_outputs_by_script_key")
+    }
+
+    fn fetch_unspent_output_hash_by_commitment(
+        &self,
+        _commitment: &Commitment,
+    ) -> Result<Option<HashOutput>, ChainStorageError> {
+        unsupported("fetch_unspent_output_hash_by_commitment")
+    }
+
+    fn fetch_outputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionOutput>, ChainStorageError> {
+        let (_, outputs, _) = self.fetch_block_body(header_hash)?.dissolve();
+        Ok(outputs)
+    }
+
+    /// The inputs are returned as they are sent by the remote node, which may be in compact form (without the data of
+    /// the output that they spend).
+    fn fetch_inputs_in_block(&self, header_hash: &HashOutput) -> Result<Vec<TransactionInput>, ChainStorageError> {
+        let (inputs, _, _) = self.fetch_block_body(header_hash)?.dissolve();
+        Ok(inputs)
+    }
+
+    fn fetch_mmr_size(&self, _tree: MmrTree) -> Result<u64, ChainStorageError> {
+        unsupported("fetch_mmr_size")
+    }
+
+    fn orphan_count(&self) -> Result<usize, ChainStorageError> {
+        unsupported("orphan_count")
+    }
+
+    fn fetch_last_header(&self) -> Result<BlockHeader, ChainStorageError> {
+        let height = self.fetch_chain_metadata()?.best_block_height();
+        self.fetch_header_by_height(height)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockHeader",
+                field: "height",
+                value: height.to_string(),
+            })
+    }
+
+    fn clear_all_pending_headers(&self) -> Result<usize, ChainStorageError> {
+        read_only()
+    }
+
+    fn fetch_last_chain_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        unsupported("fetch_last_chain_header")
+    }
+
+    fn fetch_tip_header(&self) -> Result<ChainHeader, ChainStorageError> {
+        unsupported("fetch_tip_header")
+    }
+
+    fn fetch_chain_metadata(&self) -> Result<ChainMetadata, ChainStorageError> {
+        let mut client = self.sync_client.clone();
+        let metadata = self.block_on(client.get_chain_metadata()).map_err(remote_error)?;
+        ChainMetadata::try_from(metadata).map_err(ChainStorageError::ConversionError)
+    }
+
+    fn utxo_count(&self) -> Result<usize, ChainStorageError> {
+        unsupported("utxo_count")
+    }
+
+    fn kernel_count(&self) -> Result<usize, ChainStorageError> {
+        unsupported("kernel_count")
+    }
+
+    fn fetch_orphan_chain_tip_by_hash(&self, _hash: &HashOutput) -> Result<Option<ChainHeader>, ChainStorageError> {
+        unsupported("fetch_orphan_chain_tip_by_hash")
+    }
+
+    fn fetch_strongest_orphan_chain_tips(&self) -> Result<Vec<ChainHeader>, ChainStorageError> {
+        unsupported("fetch_strongest_orphan_chain_tips")
+    }
+
+    fn fetch_orphan_children_of(&self, _hash: HashOutput) -> Result<Vec<Block>, ChainStorageError> {
+        unsupported("fetch_orphan_children_of")
+    }
+
+    fn fetch_orphan_chain_block(&self, _hash: HashOutput) -> Result<Option<ChainBlock>, ChainStorageError> {
+        unsupported("fetch_orphan_chain_block")
+    }
+
+    fn delete_oldest_orphans(
+        &mut self,
+        _horizon_height: u64,
+        _orphan_storage_capacity: usize,
+    ) -> Result<(), ChainStorageError> {
+        read_only()
+    }
+
+    fn fetch_monero_seed_first_seen_height(&self, _seed: &[u8]) -> Result<u64, ChainStorageError> {
+        unsupported("fetch_monero_seed_first_seen_height")
+    }
+
+    fn fetch_target_difficulty_window(
+        &self,
+        _pow_algo: PowAlgorithm,
+        _tip_hash: &HashOutput,
+    ) -> Result<Option<Vec<(EpochTime, Difficulty)>>, ChainStorageError> {
+        unsupported("fetch_target_difficulty_window")
+    }
+
+    fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
+        unsupported("fetch_horizon_data")
+    }
+
+    fn fetch_horizon_sync_checkpoint(&self) -> Result<Option<HorizonSyncCheckpoint>, ChainStorageError> {
+        unsupported("fetch_horizon_sync_checkpoint")
+    }
+
+    fn fetch_block_balance_sums(&self, _height: u64) -> Result<Option<BlockBalanceSums>, ChainStorageError> {
+        unsupported("fetch_block_balance_sums")
+    }
+
+    fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
+        unsupported("get_stats")
+    }
+
+    fn fetch_total_size_stats(&self) -> Result<DbTotalSizeStats, ChainStorageError> {
+        unsupported("fetch_total_size_stats")
+    }
+
+    fn fetch_map_utilization(&self) -> Result<MapUtilization, ChainStorageError> {
+        unsupported("fetch_map_utilization")
+    }
+
+    fn grow_map_if_required(&mut self, _max_utilization_percentage: u8) -> Result<bool, ChainStorageError> {
+        // There is no local memory map to grow
+        Ok(false)
+    }
+
+    fn compact(&self) -> Result<LmdbCompactionResult, ChainStorageError> {
+        read_only()
+    }
+
+    fn bad_block_exists(&self, _block_hash: HashOutput) -> Result<(bool, String), ChainStorageError> {
+        unsupported("bad_block_exists")
+    }
+
+    fn fetch_all_reorgs(&self) -> Result<Vec<Reorg>, ChainStorageError> {
+        unsupported("fetch_all_reorgs")
+    }
+
+    fn fetch_active_validator_nodes(&self, _height: u64) -> Result<Vec<(PublicKey, [u8; 32])>, ChainStorageError> {
+        unsupported("fetch_active_validator_nodes")
+    }
+
+    fn get_shard_key(&self, _height: u64, _public_key: PublicKey) -> Result<Option<[u8; 32]>, ChainStorageError> {
+        unsupported("get_shard_key")
+    }
+
+    fn fetch_validator_node_epoch_snapshot(
+        &self,
+        _epoch: VnEpoch,
+    ) -> Result<Option<ValidatorNodeEpochSnapshot>, ChainStorageError> {
+        unsupported("fetch_validator_node_epoch_snapshot")
+    }
+
+    fn fetch_template_registrations(
+        &self,
+        _start_height: u64,
+        _end_height: u64,
+    ) -> Result<Vec<TemplateRegistrationEntry>, ChainStorageError> {
+        unsupported("fetch_template_registrations")
+    }
+
+    fn fetch_tip_smt(&self) -> Result<OutputSmt, ChainStorageError> {
+        unsupported("fetch_tip_smt")
+    }
+}
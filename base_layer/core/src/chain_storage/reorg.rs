@@ -20,14 +20,18 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{collections::VecDeque, sync::Arc};
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+};
 
 use chrono::{NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use tari_common_types::types::HashOutput;
+use tari_common_types::types::{HashOutput, Signature};
 
 use crate::blocks::ChainBlock;
 
+/// A record of a chain reorganization. The most recent reorgs are kept in the database when `track_reorgs` is set.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Reorg {
     pub new_height: u64,
@@ -37,6 +41,13 @@ pub struct Reorg {
     pub num_blocks_added: u64,
     pub num_blocks_removed: u64,
     pub local_time: NaiveDateTime,
+    /// The hashes of the blocks that were added to the main chain, in the order they were added
+    pub added_blocks: Vec<HashOutput>,
+    /// The hashes of the blocks that were removed from the main chain, in the order they were removed
+    pub removed_blocks: Vec<HashOutput>,
+    /// The kernel excess signatures of the non-coinbase transactions in the removed blocks that are not in any of the
+    /// added blocks. These transactions are returned to the mempool.
+    pub reinstated_transactions: Vec<Signature>,
 }
 
 impl Reorg {
@@ -50,6 +61,23 @@ impl Reorg {
             num_blocks_added: added.len() as u64,
             num_blocks_removed: removed.len() as u64,
             local_time: Utc::now().naive_local(),
+            added_blocks: added.iter().map(|b| *b.hash()).collect(),
+            removed_blocks: removed.iter().map(|b| *b.hash()).collect(),
+            reinstated_transactions: reinstated_transactions(added, removed),
         }
     }
 }
+
+fn reinstated_transactions(added: &VecDeque<Arc<ChainBlock>>, removed: &[Arc<ChainBlock>]) -> Vec<Signature> {
+    let added_kernels = added
+        .iter()
+        .flat_map(|b| b.block().body.kernels())
+        .map(|k| k.excess_sig.get_signature())
+        .collect::<HashSet<_>>();
+    removed
+        .iter()
+        .flat_map(|b| b.block().body.kernels())
+        .filter(|k| !k.is_coinbase() && !added_kernels.contains(k.excess_sig.get_signature()))
+        .map(|k| k.excess_sig.clone())
+        .collect()
+}
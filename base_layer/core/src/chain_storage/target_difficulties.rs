@@ -23,7 +23,7 @@
 use crate::{
     blocks::BlockHeader,
     consensus::ConsensusManager,
-    proof_of_work::{Difficulty, PowAlgorithm, TargetDifficultyWindow},
+    proof_of_work::{Difficulty, DifficultyStats, DifficultyStatsWindow, PowAlgorithm, TargetDifficultyWindow},
 };
 
 #[derive(Debug, Clone)]
@@ -40,9 +40,11 @@ impl TargetDifficulties {
         })
     }
 
+    /// Appends the header to the window of its algorithm, and makes it the chain tip that the next block builds on
     pub fn add_back(&mut self, header: &BlockHeader, target_difficulty: Difficulty) {
         self.get_mut(header.pow_algo())
             .add_back(header.timestamp(), target_difficulty);
+        self.set_chain_tip(header);
     }
 
     pub fn add_front(&mut self, header: &BlockHeader, target_difficulty: Difficulty) {
@@ -50,6 +52,16 @@ impl TargetDifficulties {
             .add_front(header.timestamp(), target_difficulty);
     }
 
+    /// Sets the header that the next block builds on for both algorithms
+    pub fn set_chain_tip(&mut self, header: &BlockHeader) {
+        self.sha3x.set_chain_tip(header.height, header.timestamp());
+        self.randomx.set_chain_tip(header.height, header.timestamp());
+    }
+
+    pub fn set_anchor_difficulty(&mut self, algo: PowAlgorithm, difficulty: Difficulty) {
+        self.get_mut(algo).set_anchor_difficulty(difficulty);
+    }
+
     pub fn is_algo_full(&self, algo: PowAlgorithm) -> bool {
         self.get(algo).is_full()
     }
@@ -58,6 +70,28 @@ impl TargetDifficulties {
         self.sha3x.is_full() && self.randomx.is_full()
     }
 
+    /// Returns per-algorithm statistics (estimated hash rate, solve time variance and share of blocks) over the most
+    /// recent `window` blocks held in the target difficulty windows.
+    pub fn stats(&self, window: usize) -> DifficultyStats {
+        let mut blocks = self
+            .sha3x
+            .samples()
+            .map(|(timestamp, difficulty)| (PowAlgorithm::Sha3x, *timestamp, *difficulty))
+            .chain(
+                self.randomx
+                    .samples()
+                    .map(|(timestamp, difficulty)| (PowAlgorithm::RandomX, *timestamp, *difficulty)),
+            )
+            .collect::<Vec<_>>();
+        // The windows are each in chain order, merge them by timestamp
+        blocks.sort_by_key(|(_, timestamp, _)| timestamp.as_u64());
+        let mut stats_window = DifficultyStatsWindow::new(window);
+        for (pow_algo, timestamp, difficulty) in blocks {
+            stats_window.add(pow_algo, timestamp, difficulty);
+        }
+        stats_window.stats()
+    }
+
     pub fn get(&self, algo: PowAlgorithm) -> &TargetDifficultyWindow {
         use PowAlgorithm::{RandomX, Sha3x};
         match algo {
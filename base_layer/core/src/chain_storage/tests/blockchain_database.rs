@@ -653,3 +653,627 @@ mod validator_node_merkle_root {
         assert_eq!(tip.header().validator_node_mr, merkle_root);
     }
 }
+
+mod utxo_snapshot {
+    use super::*;
+    use crate::transactions::key_manager::create_memory_db_key_manager;
+
+    fn setup_with_headers_of(db: &BlockchainDatabase<TempDatabase>) -> BlockchainDatabase<TempDatabase> {
+        let new_db = setup();
+        let tip_height = db.get_height().unwrap();
+        let headers = (1..=tip_height).map(|h| db.fetch_chain_header(h).unwrap()).collect();
+        new_db.insert_valid_headers(headers).unwrap();
+        new_db
+    }
+
+    #[tokio::test]
+    async fn it_imports_an_exported_snapshot() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(5, &db, &key_manager).await;
+        let mut snapshot = Vec::new();
+        let header = db.export_utxo_snapshot(3, &mut snapshot).unwrap();
+        assert_eq!(header.height, 3);
+        assert_eq!(header.block_hash, blocks[2].hash());
+
+        let new_db = setup_with_headers_of(&db);
+        let imported = new_db.import_utxo_snapshot(&mut snapshot.as_slice()).unwrap();
+        assert_eq!(imported, header);
+        let metadata = new_db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.best_block_height(), 3);
+        assert_eq!(*metadata.best_block_hash(), blocks[2].hash());
+        assert_eq!(metadata.pruned_height(), 3);
+        assert_eq!(
+            new_db.fetch_tip_smt().unwrap().hash().as_slice(),
+            blocks[2].header.output_mr.as_slice()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_rejects_an_export_above_the_tip() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(2, &db, &key_manager).await;
+        let err = db.export_utxo_snapshot(3, &mut Vec::new()).unwrap_err();
+        assert!(matches!(err, ChainStorageError::InvalidArguments { .. }));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_a_snapshot_without_the_header_chain() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(2, &db, &key_manager).await;
+        let mut snapshot = Vec::new();
+        db.export_utxo_snapshot(2, &mut snapshot).unwrap();
+
+        let new_db = setup();
+        let err = new_db.import_utxo_snapshot(&mut snapshot.as_slice()).unwrap_err();
+        assert!(matches!(err, ChainStorageError::InvalidUtxoSnapshot(_)));
+    }
+
+    #[tokio::test]
+    async fn it_leaves_the_chain_unchanged_if_the_import_fails() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(3, &db, &key_manager).await;
+        let mut snapshot = Vec::new();
+        db.export_utxo_snapshot(3, &mut snapshot).unwrap();
+        snapshot.truncate(snapshot.len() - 10);
+
+        let new_db = setup_with_headers_of(&db);
+        let utxo_count = new_db.utxo_count().unwrap();
+        new_db.import_utxo_snapshot(&mut snapshot.as_slice()).unwrap_err();
+        assert_eq!(new_db.get_height().unwrap(), 0);
+        assert_eq!(new_db.utxo_count().unwrap(), utxo_count);
+    }
+}
+
+mod prune_to_horizon {
+    use super::*;
+    use crate::transactions::key_manager::create_memory_db_key_manager;
+
+    #[tokio::test]
+    async fn it_converts_an_archival_node_to_a_pruned_node() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(10, &db, &key_manager).await;
+        assert!(db.get_chain_metadata().unwrap().is_archival_node());
+
+        db.prune_to_horizon(3).unwrap();
+        let metadata = db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.pruning_horizon(), 3);
+        assert_eq!(metadata.pruned_height(), 7);
+
+        // Increasing the horizon does not prune any further
+        db.prune_to_horizon(5).unwrap();
+        let metadata = db.get_chain_metadata().unwrap();
+        assert_eq!(metadata.pruning_horizon(), 5);
+        assert_eq!(metadata.pruned_height(), 7);
+    }
+
+    #[tokio::test]
+    async fn it_does_not_convert_a_pruned_node_to_an_archival_node() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(5, &db, &key_manager).await;
+        db.prune_to_horizon(2).unwrap();
+        let err = db.prune_to_horizon(0).unwrap_err();
+        assert!(matches!(err, ChainStorageError::InvalidArguments { .. }));
+        assert_eq!(db.get_chain_metadata().unwrap().pruning_horizon(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_allows_an_unpruned_node_to_become_archival() {
+        let db = setup();
+        db.prune_to_horizon(100).unwrap();
+        assert!(db.get_chain_metadata().unwrap().is_pruned_node());
+        db.prune_to_horizon(0).unwrap();
+        assert!(db.get_chain_metadata().unwrap().is_archival_node());
+    }
+}
+
+mod reconstruct_mmrs {
+    use tari_mmr::pruned_hashset::PrunedHashSet;
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::{
+        blocks::UpdateBlockAccumulatedData,
+        chain_storage::{DbTransaction, MmrReconstructionStage},
+        transactions::key_manager::create_memory_db_key_manager,
+        PrunedKernelMmr,
+    };
+
+    fn kernel_mr_at(db: &BlockchainDatabase<TempDatabase>, height: u64) -> Vec<u8> {
+        let kernels = db.fetch_block_accumulated_data_by_height(height).unwrap().dissolve();
+        PrunedKernelMmr::new(kernels).get_merkle_root().unwrap().to_vec()
+    }
+
+    #[tokio::test]
+    async fn it_rebuilds_corrupted_kernel_hash_sets() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(5, &db, &key_manager).await;
+        let expected_kernel_mr = blocks[2].header.kernel_mr.to_vec();
+        assert_eq!(kernel_mr_at(&db, 3), expected_kernel_mr);
+
+        let mut txn = DbTransaction::new();
+        txn.update_block_accumulated_data(blocks[2].hash(), UpdateBlockAccumulatedData {
+            kernel_hash_set: Some(PrunedHashSet::default()),
+            ..Default::default()
+        });
+        db.write(txn).unwrap();
+        assert_ne!(kernel_mr_at(&db, 3), expected_kernel_mr);
+
+        let (tx, mut rx) = broadcast::channel(10);
+        let result = db.reconstruct_mmrs(Some(tx)).unwrap();
+        assert_eq!(result.height, 5);
+        assert_eq!(result.block_hash, blocks[4].hash());
+        assert_eq!(kernel_mr_at(&db, 3), expected_kernel_mr);
+
+        let kernels = rx.recv().await.unwrap();
+        assert_eq!(kernels.stage, MmrReconstructionStage::Kernels);
+        assert_eq!(kernels.height, 5);
+        let outputs = rx.recv().await.unwrap();
+        assert_eq!(outputs.stage, MmrReconstructionStage::Outputs);
+        assert_eq!(outputs.percentage(), 100);
+        assert!(rx.recv().await.is_err());
+
+        // The chain can be extended on top of the rebuilt trees
+        add_many_chained_blocks(1, &db, &key_manager).await;
+    }
+
+    #[tokio::test]
+    async fn it_rebuilds_without_a_progress_listener() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(2, &db, &key_manager).await;
+        let tip_smt_size = db.fetch_tip_smt().unwrap().size();
+
+        let result = db.reconstruct_mmrs(None).unwrap();
+        assert_eq!(result.height, 2);
+        assert_eq!(result.output_smt_size, tip_smt_size);
+    }
+}
+
+mod output_indexes {
+    use tari_common_types::types::{FixedHash, PublicKey};
+
+    use super::*;
+    use crate::{
+        chain_storage::DbTransaction,
+        one_sided::one_sided_script_public_key,
+        payment_reference::generate_payment_reference,
+        transactions::key_manager::create_memory_db_key_manager,
+    };
+
+    fn set_output_indexes(db: &BlockchainDatabase<TempDatabase>, enabled: bool) {
+        let mut txn = DbTransaction::new();
+        txn.set_output_indexes(enabled);
+        db.write(txn).unwrap();
+    }
+
+    fn payref_of(block: &Block) -> FixedHash {
+        generate_payment_reference(&block.hash(), &block.body.outputs()[0].hash())
+    }
+
+    fn script_key_of(block: &Block) -> PublicKey {
+        one_sided_script_public_key(&block.body.outputs()[0].script)
+            .unwrap()
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn it_errors_if_the_indexes_are_not_enabled() {
+        let db = setup();
+        assert!(!db.output_indexes_enabled().unwrap());
+        let err = db.fetch_outputs_by_payref(vec![FixedHash::zero()]).unwrap_err();
+        assert!(matches!(err, ChainStorageError::InvalidOperation(_)));
+    }
+
+    #[tokio::test]
+    async fn it_indexes_existing_and_new_outputs() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(2, &db, &key_manager).await;
+        set_output_indexes(&db, true);
+        assert!(db.output_indexes_enabled().unwrap());
+        let (new_blocks, _) = add_many_chained_blocks(1, &db, &key_manager).await;
+
+        for block in [&blocks[0], &blocks[1], &new_blocks[0]] {
+            let found = db.fetch_outputs_by_payref(vec![payref_of(block)]).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].output.hash(), block.body.outputs()[0].hash());
+            assert_eq!(found[0].header_hash, block.hash());
+        }
+        assert!(db.fetch_outputs_by_payref(vec![FixedHash::zero()]).unwrap().is_empty());
+
+        // The coinbases of blocks added together pay to the same address
+        let found = db.fetch_outputs_by_script_key(script_key_of(&blocks[0])).unwrap();
+        assert_eq!(found.len(), 2);
+        let found = db.fetch_outputs_by_script_key(script_key_of(&new_blocks[0])).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].header_hash, new_blocks[0].hash());
+    }
+
+    #[tokio::test]
+    async fn it_removes_rewound_outputs_from_the_indexes() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        set_output_indexes(&db, true);
+        let (blocks, _) = add_many_chained_blocks(2, &db, &key_manager).await;
+
+        db.rewind_to_height(1).unwrap();
+        assert_eq!(
+            db.fetch_outputs_by_payref(vec![payref_of(&blocks[0])]).unwrap().len(),
+            1
+        );
+        assert!(db
+            .fetch_outputs_by_payref(vec![payref_of(&blocks[1])])
+            .unwrap()
+            .is_empty());
+        let found = db.fetch_outputs_by_script_key(script_key_of(&blocks[1])).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].header_hash, blocks[0].hash());
+
+        set_output_indexes(&db, false);
+        assert!(!db.output_indexes_enabled().unwrap());
+        assert!(db.fetch_outputs_by_payref(vec![payref_of(&blocks[0])]).is_err());
+    }
+}
+
+mod block_balance_sums {
+    use tari_common_types::types::Commitment;
+
+    use super::*;
+    use crate::{
+        transactions::{key_manager::create_memory_db_key_manager, CryptoFactories},
+        validation::ChainBalanceValidator,
+    };
+
+    fn sum_chain_to(db: &BlockchainDatabase<TempDatabase>, height: u64) -> (Commitment, Commitment) {
+        let mut kernel_sum = Commitment::default();
+        let mut utxo_sum = Commitment::default();
+        for h in 0..=height {
+            let block = db.fetch_block(h, false).unwrap().into_block();
+            for kernel in block.body.kernels() {
+                kernel_sum = &kernel.excess + &kernel_sum;
+            }
+            for output in block.body.outputs().iter().filter(|o| !o.is_burned()) {
+                utxo_sum = &output.commitment + &utxo_sum;
+            }
+            for input in block.body.inputs() {
+                utxo_sum = &utxo_sum - input.commitment().unwrap();
+            }
+        }
+        (kernel_sum, utxo_sum)
+    }
+
+    #[tokio::test]
+    async fn it_caches_the_cumulative_sums_of_each_block() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, outputs) = add_many_chained_blocks(1, &db, &key_manager).await;
+        let (txns, _) = schema_to_transaction(
+            &[txn_schema!(from: vec![outputs[0].clone()], to: vec![50 * T])],
+            &key_manager,
+        )
+        .await;
+        let (script_key_id, wallet_payment_address) = default_coinbase_entities(&key_manager).await;
+        let (block, _) = create_next_block(
+            &db,
+            &blocks[0],
+            txns,
+            &key_manager,
+            &script_key_id,
+            &wallet_payment_address,
+        )
+        .await;
+        db.add_block(block).unwrap().assert_added();
+
+        for height in 0..=2 {
+            let sums = db.fetch_block_balance_sums(height).unwrap().unwrap();
+            let (kernel_sum, utxo_sum) = sum_chain_to(&db, height);
+            assert_eq!(sums.kernel_sum(), &kernel_sum);
+            assert_eq!(sums.utxo_sum(), &utxo_sum);
+        }
+
+        let validator = ChainBalanceValidator::new(db.rules().clone(), CryptoFactories::default());
+        validator.validate_cached(&*db.db_read_access().unwrap(), 2).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_removes_the_sums_of_rewound_blocks() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(2, &db, &key_manager).await;
+        assert!(db.fetch_block_balance_sums(2).unwrap().is_some());
+
+        db.rewind_to_height(1).unwrap();
+        assert!(db.fetch_block_balance_sums(1).unwrap().is_some());
+        assert!(db.fetch_block_balance_sums(2).unwrap().is_none());
+    }
+}
+
+mod output_inclusion_proof {
+    use super::*;
+    use crate::{
+        output_inclusion_proof::OutputInclusionProofError,
+        transactions::key_manager::create_memory_db_key_manager,
+    };
+
+    #[tokio::test]
+    async fn it_proves_an_output_at_the_tip_and_below_it() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(3, &db, &key_manager).await;
+        let output = blocks[0].body.outputs()[0].clone();
+
+        for height in 1..=3 {
+            let proof = db.fetch_output_inclusion_proof(output.hash(), height).unwrap();
+            assert_eq!(proof.height(), height);
+            proof.verify(&output, &blocks[height as usize - 1].hash()).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn it_rejects_proofs_for_other_headers_and_outputs() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(2, &db, &key_manager).await;
+        let output = blocks[0].body.outputs()[0].clone();
+        let proof = db.fetch_output_inclusion_proof(output.hash(), 1).unwrap();
+
+        assert!(matches!(
+            proof.verify(&output, &blocks[1].hash()),
+            Err(OutputInclusionProofError::HeaderMismatch { .. })
+        ));
+        assert!(matches!(
+            proof.verify(&blocks[1].body.outputs()[0], &blocks[0].hash()),
+            Err(OutputInclusionProofError::OutputMismatch { .. })
+        ));
+        let mut tampered = proof.clone();
+        tampered.mined_height = 0;
+        assert!(matches!(
+            tampered.verify(&output, &blocks[0].hash()),
+            Err(OutputInclusionProofError::InvalidProof)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_errors_for_an_output_mined_after_the_height() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(2, &db, &key_manager).await;
+        let output = &blocks[1].body.outputs()[0];
+        let err = db.fetch_output_inclusion_proof(output.hash(), 1).unwrap_err();
+        assert!(matches!(err, ChainStorageError::InvalidOperation(_)));
+        let err = db.fetch_output_inclusion_proof(output.hash(), 3).unwrap_err();
+        assert!(matches!(err, ChainStorageError::InvalidOperation(_)));
+    }
+}
+
+mod burn_proof {
+    use tari_script::script;
+
+    use super::*;
+    use crate::{
+        burn_proof::BurnProofError,
+        covenants::Covenant,
+        transactions::{
+            key_manager::create_memory_db_key_manager,
+            test_helpers::{create_test_kernel, create_utxo},
+            transaction_components::{KernelFeatures, OutputFeatures, TransactionOutput},
+        },
+    };
+
+    async fn add_block_with_burn(
+        db: &BlockchainDatabase<TempDatabase>,
+        key_manager: &MemoryDbKeyManager,
+    ) -> (Arc<Block>, TransactionOutput) {
+        let last_header = db.fetch_last_header().unwrap();
+        let prev_block = db.fetch_block(last_header.height, true).unwrap().into_block();
+        let (script_key_id, wallet_payment_address) = default_coinbase_entities(key_manager).await;
+        let (mut block, _) = create_block(
+            db.rules(),
+            &prev_block,
+            BlockSpec::new().finish(),
+            key_manager,
+            &script_key_id,
+            &wallet_payment_address,
+            None,
+        )
+        .await;
+
+        let claim_public_key = crate::test_helpers::new_public_key();
+        let (burned_output, _, _) = create_utxo(
+            100.into(),
+            key_manager,
+            &OutputFeatures::create_burn_confidential_output(claim_public_key),
+            &script!(Nop),
+            &Covenant::default(),
+            0.into(),
+        )
+        .await;
+        let mut kernel = create_test_kernel(0.into(), 0, KernelFeatures::create_burn());
+        kernel.burn_commitment = Some(burned_output.commitment.clone());
+        block.body.add_output(burned_output.clone());
+        block.body.add_kernel(kernel);
+        block.body.sort();
+
+        let block = Arc::new(apply_mmr_to_block(db, block));
+        db.add_block(block.clone()).unwrap().assert_added();
+        (block, burned_output)
+    }
+
+    #[tokio::test]
+    async fn it_proves_a_burned_output() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(2, &db, &key_manager).await;
+        let (block, burned_output) = add_block_with_burn(&db, &key_manager).await;
+        add_many_chained_blocks(1, &db, &key_manager).await;
+
+        let proof = db.fetch_burn_proof(burned_output.commitment.clone()).unwrap();
+        assert_eq!(proof.height(), 3);
+        proof.verify(&burned_output.commitment, &block.hash()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_rejects_proofs_for_other_headers_and_commitments() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(1, &db, &key_manager).await;
+        let (block, burned_output) = add_block_with_burn(&db, &key_manager).await;
+        let proof = db.fetch_burn_proof(burned_output.commitment.clone()).unwrap();
+
+        assert!(matches!(
+            proof.verify(&burned_output.commitment, &blocks[0].hash()),
+            Err(BurnProofError::HeaderMismatch { .. })
+        ));
+        let coinbase = block.body.outputs().iter().find(|o| !o.is_burned()).unwrap();
+        assert!(matches!(
+            proof.verify(&coinbase.commitment, &block.hash()),
+            Err(BurnProofError::CommitmentMismatch { .. })
+        ));
+        let mut tampered = proof.clone();
+        tampered.leaf_index = (tampered.leaf_index + 1) % block.header.kernel_mmr_size;
+        assert!(matches!(
+            tampered.verify(&burned_output.commitment, &block.hash()),
+            Err(BurnProofError::InvalidProof(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_errors_for_an_unknown_commitment() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(1, &db, &key_manager).await;
+        let err = db
+            .fetch_burn_proof(blocks[0].body.outputs()[0].commitment.clone())
+            .unwrap_err();
+        assert!(matches!(err, ChainStorageError::ValueNotFound { .. }));
+    }
+}
+
+mod integrity {
+    use tari_mmr::pruned_hashset::PrunedHashSet;
+
+    use super::*;
+    use crate::{
+        blocks::UpdateBlockAccumulatedData,
+        chain_storage::{DbTransaction, IntegrityIssue},
+        transactions::key_manager::create_memory_db_key_manager,
+        OutputSmt,
+    };
+
+    #[tokio::test]
+    async fn it_reports_a_consistent_chain() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        add_many_chained_blocks(3, &db, &key_manager).await;
+
+        let report = db.check_integrity().unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(report.tip_height, 3);
+        assert_eq!(report.last_consistent_height, Some(3));
+    }
+
+    #[tokio::test]
+    async fn it_repairs_corrupted_mmrs() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(5, &db, &key_manager).await;
+
+        let mut txn = DbTransaction::new();
+        txn.update_block_accumulated_data(blocks[2].hash(), UpdateBlockAccumulatedData {
+            kernel_hash_set: Some(PrunedHashSet::default()),
+            ..Default::default()
+        });
+        txn.insert_tip_smt(OutputSmt::new());
+        db.write(txn).unwrap();
+
+        let report = db.check_integrity().unwrap();
+        assert_eq!(report.last_consistent_height, Some(2));
+        assert!(matches!(report.issues[0], IntegrityIssue::KernelMmrSizeMismatch {
+            height: 3,
+            actual: 0,
+            ..
+        }));
+        assert!(matches!(report.issues[1], IntegrityIssue::OutputSmtMismatch {
+            height: 5,
+            ..
+        }));
+
+        let report = db.repair_integrity().unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(db.get_height().unwrap(), 5);
+    }
+
+    #[tokio::test]
+    async fn it_repairs_stale_tip_metadata() {
+        let db = setup();
+        let key_manager = create_memory_db_key_manager();
+        let (blocks, _) = add_many_chained_blocks(3, &db, &key_manager).await;
+
+        let tip = db.fetch_chain_header(3).unwrap();
+        let mut txn = DbTransaction::new();
+        txn.set_best_block(
+            3,
+            blocks[1].hash(),
+            tip.accumulated_data().total_accumulated_difficulty,
+            *tip.hash(),
+            tip.timestamp(),
+        );
+        db.write(txn).unwrap();
+
+        let report = db.check_integrity().unwrap();
+        assert_eq!(report.issues, vec![IntegrityIssue::StaleTipMetadata {
+            height: 3,
+            expected_hash: *tip.hash(),
+            actual_hash: blocks[1].hash(),
+        }]);
+        assert_eq!(report.last_consistent_height, Some(3));
+
+        let report = db.repair_integrity().unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(db.get_chain_metadata().unwrap().best_block_hash(), tip.hash());
+    }
+}
+
+mod horizon_sync_checkpoint {
+    use tari_common_types::types::{Commitment, FixedHash};
+
+    use super::*;
+    use crate::chain_storage::{DbTransaction, HorizonSyncCheckpoint};
+
+    #[test]
+    fn it_stores_and_clears_the_checkpoint() {
+        let db = setup();
+        assert!(db.fetch_horizon_sync_checkpoint().unwrap().is_none());
+
+        let mut checkpoint = HorizonSyncCheckpoint::new(FixedHash::zero(), 0, 10);
+        checkpoint.height = 5;
+        checkpoint.utxo_count = 3;
+        checkpoint
+            .spent_outputs
+            .push((FixedHash::zero(), Commitment::default()));
+        let mut txn = DbTransaction::new();
+        txn.set_horizon_sync_checkpoint(checkpoint.clone());
+        db.write(txn).unwrap();
+        let stored = db.fetch_horizon_sync_checkpoint().unwrap().unwrap();
+        assert_eq!(stored, checkpoint);
+        assert!(stored.is_resumable_for(&FixedHash::zero(), 0, 10));
+        assert!(!stored.is_resumable_for(&FixedHash::zero(), 1, 10));
+        assert_eq!(stored.txo_count(), 3);
+
+        let mut txn = DbTransaction::new();
+        txn.clear_horizon_sync_checkpoint();
+        db.write(txn).unwrap();
+        assert!(db.fetch_horizon_sync_checkpoint().unwrap().is_none());
+        // Clearing a missing checkpoint is not an error
+        let mut txn = DbTransaction::new();
+        txn.clear_horizon_sync_checkpoint();
+        db.write(txn).unwrap();
+    }
+}
@@ -0,0 +1,413 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    io::{Read, Write},
+    mem,
+    time::Instant,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use log::*;
+use tari_common_types::types::{Commitment, FixedHash};
+use tari_mmr::{
+    pruned_hashset::PrunedHashSet,
+    sparse_merkle_tree::{NodeKey, ValueHash},
+};
+use tari_utilities::{hex::Hex, ByteArray};
+
+use crate::{
+    blocks::{ChainHeader, UpdateBlockAccumulatedData},
+    chain_storage::{BlockBalanceSums, BlockchainBackend, BlockchainDatabase, ChainStorageError, DbTransaction},
+    transactions::{
+        transaction_components::{TransactionKernel, TransactionOutput},
+        CryptoFactories,
+    },
+    validation::{ChainBalanceValidator, FinalHorizonStateValidation},
+    OutputSmt,
+    PrunedKernelMmr,
+};
+
+const LOG_TARGET: &str = "c::cs::utxo_snapshot";
+
+/// The version of the snapshot format written by [BlockchainDatabase::export_utxo_snapshot]
+pub const UTXO_SNAPSHOT_VERSION: u8 = 1;
+/// The number of write operations that are committed together when importing a snapshot
+const IMPORT_BATCH_SIZE: usize = 1_000;
+
+/// The first record in a UTXO snapshot. It identifies the block the snapshot was taken at and the roots the snapshot
+/// contents are verified against.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct UtxoSnapshotHeader {
+    pub version: u8,
+    pub height: u64,
+    pub block_hash: FixedHash,
+    pub timestamp: u64,
+    pub output_mr: FixedHash,
+    pub output_smt_size: u64,
+    pub kernel_mr: FixedHash,
+    pub kernel_mmr_size: u64,
+}
+
+/// The kernels of a block and the outputs of the block that are unspent at the snapshot height. A snapshot contains one
+/// of these records for every block from the genesis block up to and including the snapshot height.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+struct UtxoSnapshotBlock {
+    height: u64,
+    header_hash: FixedHash,
+    kernels: Vec<TransactionKernel>,
+    outputs: Vec<TransactionOutput>,
+}
+
+/// Running totals of an import, used to verify the snapshot once every block has been read
+struct ImportState {
+    kernel_mmr: PrunedKernelMmr,
+    output_smt: OutputSmt,
+    utxo_sum: Commitment,
+    kernel_sum: Commitment,
+    burned_sum: Commitment,
+    /// Outputs in blocks that were already in the database that are spent at the snapshot height
+    spent_local_outputs: Vec<TransactionOutput>,
+    /// The height of the last block whose kernels and outputs were written to the database
+    last_written_height: Option<u64>,
+}
+
+impl<B: BlockchainBackend> BlockchainDatabase<B> {
+    /// Writes a snapshot of the UTXO set at the given height to `writer`. The snapshot contains every kernel and every
+    /// output that is unspent at that height, together with the header roots they are verified against on import.
+    /// The height must not be below the pruned height, because outputs that were spent after the snapshot height may
+    /// already have been pruned.
+    pub fn export_utxo_snapshot<W: Write>(
+        &self,
+        height: u64,
+        writer: &mut W,
+    ) -> Result<UtxoSnapshotHeader, ChainStorageError> {
+        let metadata = self.get_chain_metadata()?;
+        if height > metadata.best_block_height() || height < metadata.pruned_height() {
+            return Err(ChainStorageError::InvalidArguments {
+                func: "export_utxo_snapshot",
+                arg: "height",
+                message: format!(
+                    "Snapshot height {} must be between the pruned height {} and the tip height {}",
+                    height,
+                    metadata.pruned_height(),
+                    metadata.best_block_height()
+                ),
+            });
+        }
+        let timer = Instant::now();
+        let chain_header = self.fetch_chain_header(height)?;
+        let snapshot_header = UtxoSnapshotHeader {
+            version: UTXO_SNAPSHOT_VERSION,
+            height,
+            block_hash: *chain_header.hash(),
+            timestamp: chain_header.timestamp(),
+            output_mr: chain_header.header().output_mr,
+            output_smt_size: chain_header.header().output_smt_size,
+            kernel_mr: chain_header.header().kernel_mr,
+            kernel_mmr_size: chain_header.header().kernel_mmr_size,
+        };
+        snapshot_header.serialize(writer)?;
+
+        let mut num_outputs = 0usize;
+        for h in 0..=height {
+            let header_hash = *self.fetch_chain_header(h)?.hash();
+            let outputs = self
+                .fetch_outputs_in_block_with_spend_state(header_hash, Some(snapshot_header.block_hash))?
+                .into_iter()
+                .filter(|(output, spent)| !spent && !output.is_burned())
+                .map(|(output, _)| output)
+                .collect::<Vec<_>>();
+            num_outputs += outputs.len();
+            UtxoSnapshotBlock {
+                height: h,
+                header_hash,
+                kernels: self.fetch_kernels_in_block(header_hash)?,
+                outputs,
+            }
+            .serialize(writer)?;
+        }
+        writer.flush()?;
+
+        // The blocks are read one at a time so that other database users are not blocked for the duration of the
+        // export, so make sure that a reorg did not change the chain under us.
+        if *self.fetch_chain_header(height)?.hash() != snapshot_header.block_hash {
+            return Err(ChainStorageError::InvalidOperation(
+                "A reorg occurred while exporting the UTXO snapshot".to_string(),
+            ));
+        }
+        info!(
+            target: LOG_TARGET,
+            "Exported UTXO snapshot at height {} ({} unspent outputs) in {:.2?}",
+            height,
+            num_outputs,
+            timer.elapsed()
+        );
+        Ok(snapshot_header)
+    }
+
+    /// Imports a snapshot written by [export_utxo_snapshot](Self::export_utxo_snapshot), making the snapshot block the
+    /// tip of a chain that is pruned at that height. The headers up to the snapshot height must already have been
+    /// synced. The kernels and outputs in the snapshot are verified against the kernel MMR and output SMT roots of the
+    /// local headers, and the chain balance is checked, before the chain tip is moved.
+    pub fn import_utxo_snapshot<R: Read>(&self, reader: &mut R) -> Result<UtxoSnapshotHeader, ChainStorageError> {
+        let timer = Instant::now();
+        let snapshot_header = UtxoSnapshotHeader::deserialize_reader(reader)?;
+        if snapshot_header.version != UTXO_SNAPSHOT_VERSION {
+            return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                "Unsupported snapshot version {}",
+                snapshot_header.version
+            )));
+        }
+        let metadata = self.get_chain_metadata()?;
+        if snapshot_header.height <= metadata.best_block_height() {
+            return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                "The chain tip ({}) is already at or past the snapshot height ({})",
+                metadata.best_block_height(),
+                snapshot_header.height
+            )));
+        }
+        let chain_header = self
+            .fetch_chain_header_by_block_hash(snapshot_header.block_hash)?
+            .ok_or_else(|| {
+                ChainStorageError::InvalidUtxoSnapshot(format!(
+                    "Snapshot block {} is not in the header chain. Headers must be synced before importing a snapshot.",
+                    snapshot_header.block_hash
+                ))
+            })?;
+        check_snapshot_header(&snapshot_header, &chain_header)?;
+
+        let mut state = ImportState {
+            kernel_mmr: PrunedKernelMmr::new(PrunedHashSet::default()),
+            output_smt: OutputSmt::new(),
+            utxo_sum: Commitment::default(),
+            kernel_sum: Commitment::default(),
+            burned_sum: Commitment::default(),
+            spent_local_outputs: Vec::new(),
+            last_written_height: None,
+        };
+        let result = self
+            .import_snapshot_blocks(reader, &snapshot_header, metadata.best_block_height(), &mut state)
+            .and_then(|_| self.verify_imported_snapshot(&chain_header, &mut state));
+        if let Err(err) = result {
+            warn!(target: LOG_TARGET, "UTXO snapshot import failed: {}", err);
+            if let Some(last_written_height) = state.last_written_height {
+                self.remove_imported_outputs(metadata.best_block_height() + 1, last_written_height)?;
+            }
+            return Err(err);
+        }
+
+        let mut txn = DbTransaction::new();
+        for output in state.spent_local_outputs {
+            txn.prune_output_from_all_dbs(output.hash(), output.commitment, output.features.output_type);
+        }
+        txn.insert_tip_smt(state.output_smt)
+            .set_best_block(
+                chain_header.height(),
+                *chain_header.hash(),
+                chain_header.accumulated_data().total_accumulated_difficulty,
+                *metadata.best_block_hash(),
+                chain_header.timestamp(),
+            )
+            .set_pruned_height(chain_header.height())
+            .set_block_balance_sums(
+                chain_header.height(),
+                BlockBalanceSums::new(state.kernel_sum.clone(), state.utxo_sum.clone(), state.burned_sum),
+            )
+            .set_horizon_data(state.kernel_sum, state.utxo_sum);
+        self.write(txn)?;
+        info!(
+            target: LOG_TARGET,
+            "Imported UTXO snapshot at height {} in {:.2?}",
+            snapshot_header.height,
+            timer.elapsed()
+        );
+        Ok(snapshot_header)
+    }
+
+    fn import_snapshot_blocks<R: Read>(
+        &self,
+        reader: &mut R,
+        snapshot_header: &UtxoSnapshotHeader,
+        local_tip_height: u64,
+        state: &mut ImportState,
+    ) -> Result<(), ChainStorageError> {
+        let mut txn = DbTransaction::new();
+        for height in 0..=snapshot_header.height {
+            let block = UtxoSnapshotBlock::deserialize_reader(reader)?;
+            let chain_header = self.fetch_chain_header(height)?;
+            if block.height != height || block.header_hash != *chain_header.hash() {
+                return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                    "Expected block {} at height {} but got block {} at height {}",
+                    chain_header.hash(),
+                    height,
+                    block.header_hash,
+                    block.height
+                )));
+            }
+
+            let mut mmr_position = state.kernel_mmr.get_leaf_count()? as u64;
+            for kernel in &block.kernels {
+                state.kernel_mmr.push(kernel.hash().to_vec())?;
+                state.kernel_sum = &kernel.excess + &state.kernel_sum;
+                if kernel.is_burned() {
+                    state.burned_sum = kernel.get_burn_commitment()? + &state.burned_sum;
+                }
+            }
+            let kernel_mr = FixedHash::try_from(state.kernel_mmr.get_merkle_root()?)?;
+            if kernel_mr != chain_header.header().kernel_mr {
+                return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                    "Kernel MMR root mismatch at height {}: expected {} but got {}",
+                    height,
+                    chain_header.header().kernel_mr,
+                    kernel_mr
+                )));
+            }
+
+            for output in &block.outputs {
+                if output.is_burned() {
+                    return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                        "Burned output {} is not part of the UTXO set",
+                        output.hash()
+                    )));
+                }
+                let smt_key = NodeKey::try_from(output.commitment.as_bytes())?;
+                let smt_node = ValueHash::try_from(output.smt_hash(height).as_slice())?;
+                state.output_smt.insert(smt_key, smt_node)?;
+                state.utxo_sum = &output.commitment + &state.utxo_sum;
+            }
+
+            if height <= local_tip_height {
+                // The block body is already in the database, so any of its outputs that are not in the snapshot were
+                // spent before the snapshot height
+                let unspent = block.outputs.iter().map(|o| o.hash()).collect::<HashSet<_>>();
+                let local_outputs = self.fetch_outputs_in_block(block.header_hash)?;
+                let num_local_unspent = local_outputs
+                    .iter()
+                    .filter(|o| !o.is_burned() && unspent.contains(&o.hash()))
+                    .count();
+                if num_local_unspent != unspent.len() {
+                    return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                        "Snapshot contains outputs in block {} that are not in the local block",
+                        height
+                    )));
+                }
+                state.spent_local_outputs.extend(
+                    local_outputs
+                        .into_iter()
+                        .filter(|o| !o.is_burned() && !unspent.contains(&o.hash())),
+                );
+                continue;
+            }
+
+            for kernel in block.kernels {
+                txn.insert_kernel(kernel, block.header_hash, mmr_position);
+                mmr_position += 1;
+            }
+            for output in block.outputs {
+                txn.insert_utxo(output, block.header_hash, height, chain_header.timestamp());
+            }
+            txn.update_block_accumulated_data(block.header_hash, UpdateBlockAccumulatedData {
+                kernel_hash_set: Some(state.kernel_mmr.get_pruned_hash_set()?),
+                ..Default::default()
+            });
+            if txn.len() >= IMPORT_BATCH_SIZE || height == snapshot_header.height {
+                self.write(mem::take(&mut txn))?;
+                state.last_written_height = Some(height);
+            }
+            if height % 1000 == 0 {
+                debug!(
+                    target: LOG_TARGET,
+                    "Imported UTXO snapshot up to height {} of {}", height, snapshot_header.height
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn verify_imported_snapshot(
+        &self,
+        chain_header: &ChainHeader,
+        state: &mut ImportState,
+    ) -> Result<(), ChainStorageError> {
+        let output_mr = FixedHash::try_from(state.output_smt.hash().as_slice())?;
+        if output_mr != chain_header.header().output_mr {
+            return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+                "Output SMT root mismatch: expected {} but got {}",
+                chain_header.header().output_mr,
+                output_mr
+            )));
+        }
+        let validator = ChainBalanceValidator::<B>::new(self.rules().clone(), CryptoFactories::default());
+        let db = self.db_read_access()?;
+        validator.validate(
+            &*db,
+            chain_header.height(),
+            &state.utxo_sum,
+            &state.kernel_sum,
+            &state.burned_sum,
+        )?;
+        Ok(())
+    }
+
+    /// Removes the outputs written by a failed import. The kernels are left in place, as is done when horizon sync
+    /// fails, since they are verified against the header chain.
+    fn remove_imported_outputs(&self, from_height: u64, to_height: u64) -> Result<(), ChainStorageError> {
+        for height in from_height..=to_height {
+            let header_hash = *self.fetch_chain_header(height)?.hash();
+            let mut txn = DbTransaction::new();
+            for output in self.fetch_outputs_in_block(header_hash)? {
+                txn.prune_output_from_all_dbs(output.hash(), output.commitment, output.features.output_type);
+            }
+            self.write(txn)?;
+        }
+        Ok(())
+    }
+}
+
+fn check_snapshot_header(
+    snapshot_header: &UtxoSnapshotHeader,
+    chain_header: &ChainHeader,
+) -> Result<(), ChainStorageError> {
+    let header = chain_header.header();
+    let matches = chain_header.height() == snapshot_header.height &&
+        header.output_mr == snapshot_header.output_mr &&
+        header.output_smt_size == snapshot_header.output_smt_size &&
+        header.kernel_mr == snapshot_header.kernel_mr &&
+        header.kernel_mmr_size == snapshot_header.kernel_mmr_size;
+    if !matches {
+        return Err(ChainStorageError::InvalidUtxoSnapshot(format!(
+            "Snapshot header does not match the local header at height {} ({})",
+            chain_header.height(),
+            chain_header.hash().to_hex()
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the header of a UTXO snapshot, which identifies the block the snapshot was taken at, without reading the rest
+/// of the snapshot
+pub fn read_utxo_snapshot_header<R: Read>(reader: &mut R) -> Result<UtxoSnapshotHeader, ChainStorageError> {
+    Ok(UtxoSnapshotHeader::deserialize_reader(reader)?)
+}
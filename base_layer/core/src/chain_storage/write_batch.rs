@@ -0,0 +1,192 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    mem,
+    time::{Duration, Instant},
+};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+use crate::chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend, ChainStorageError, DbTransaction};
+
+const LOG_TARGET: &str = "c::cs::write_batch";
+
+/// Flush thresholds for a [BatchedDbWriter]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WriteBatchConfig {
+    /// The number of pending write operations at which the batch is committed. Default: 5_000
+    pub max_operations: usize,
+    /// The maximum time a write operation is held in the batch. The batch is committed by the first write after this
+    /// time has elapsed. Default: 10 seconds
+    #[serde(with = "serializers::seconds")]
+    pub max_delay: Duration,
+}
+
+impl Default for WriteBatchConfig {
+    fn default() -> Self {
+        Self {
+            max_operations: 5_000,
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Groups the operations of many small database transactions into a single database transaction (group commit).
+/// Each LMDB write transaction is synced to disk when it is committed, so committing many small transactions (e.g. one
+/// per header during header sync) is dominated by disk syncs, particularly on HDDs.
+///
+/// Operations are not visible to readers until the batch is committed, and a batch is committed atomically, so if a
+/// commit fails none of the operations in the batch are applied. Callers must call [flush](Self::flush) once they are
+/// done writing; pending operations are discarded when the writer is dropped.
+pub struct BatchedDbWriter<B> {
+    db: AsyncBlockchainDb<B>,
+    config: WriteBatchConfig,
+    pending: DbTransaction,
+    pending_since: Option<Instant>,
+}
+
+impl<B: BlockchainBackend + 'static> BatchedDbWriter<B> {
+    pub fn new(db: AsyncBlockchainDb<B>, config: WriteBatchConfig) -> Self {
+        Self {
+            db,
+            config,
+            pending: DbTransaction::new(),
+            pending_since: None,
+        }
+    }
+
+    /// Adds the operations of the transaction to the batch, committing the batch if a flush threshold has been
+    /// reached. Returns true if the batch was committed.
+    pub async fn write(&mut self, txn: DbTransaction) -> Result<bool, ChainStorageError> {
+        if txn.is_empty() {
+            return Ok(false);
+        }
+        self.pending_since.get_or_insert_with(Instant::now);
+        self.pending.append(txn);
+        if !self.is_flush_required() {
+            return Ok(false);
+        }
+        self.flush().await?;
+        Ok(true)
+    }
+
+    /// Commits all pending operations in a single database transaction, returning the number of operations committed
+    pub async fn flush(&mut self) -> Result<usize, ChainStorageError> {
+        if self.pending.is_empty() {
+            return Ok(0);
+        }
+        let txn = mem::take(&mut self.pending);
+        self.pending_since = None;
+        let num_operations = txn.len();
+        let timer = Instant::now();
+        self.db.write(txn).await?;
+        debug!(
+            target: LOG_TARGET,
+            "Committed a batch of {} operation(s) in {:.2?}",
+            num_operations,
+            timer.elapsed()
+        );
+        Ok(num_operations)
+    }
+
+    /// Returns true if a flush threshold has been reached
+    pub fn is_flush_required(&self) -> bool {
+        self.pending.len() >= self.config.max_operations ||
+            self.pending_since
+                .map_or(false, |since| since.elapsed() >= self.config.max_delay)
+    }
+
+    /// The number of operations that have not been committed yet
+    pub fn pending_operations(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<B> Drop for BatchedDbWriter<B> {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            warn!(
+                target: LOG_TARGET,
+                "Batched writer dropped with {} uncommitted operation(s)",
+                self.pending.len()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::FixedHash;
+
+    use super::*;
+    use crate::test_helpers::blockchain::create_new_blockchain;
+
+    #[tokio::test]
+    async fn it_commits_when_a_threshold_is_reached() {
+        let db = AsyncBlockchainDb::new(create_new_blockchain());
+        let config = WriteBatchConfig {
+            max_operations: 3,
+            max_delay: Duration::from_secs(60),
+        };
+        let mut writer = BatchedDbWriter::new(db.clone(), config);
+        let hashes = (1u8..=4).map(|i| FixedHash::from([i; 32])).collect::<Vec<_>>();
+        for (height, hash) in hashes.iter().enumerate().take(2) {
+            let mut txn = DbTransaction::new();
+            txn.insert_bad_block(*hash, height as u64, "bad".to_string());
+            assert!(!writer.write(txn).await.unwrap());
+        }
+        assert_eq!(writer.pending_operations(), 2);
+        assert!(!db.bad_block_exists(hashes[0]).await.unwrap().0);
+
+        let mut txn = DbTransaction::new();
+        txn.insert_bad_block(hashes[2], 2, "bad".to_string());
+        assert!(writer.write(txn).await.unwrap());
+        assert_eq!(writer.pending_operations(), 0);
+        for hash in &hashes[..3] {
+            assert!(db.bad_block_exists(*hash).await.unwrap().0);
+        }
+
+        let mut txn = DbTransaction::new();
+        txn.insert_bad_block(hashes[3], 3, "bad".to_string());
+        assert!(!writer.write(txn).await.unwrap());
+        assert_eq!(writer.flush().await.unwrap(), 1);
+        assert!(db.bad_block_exists(hashes[3]).await.unwrap().0);
+    }
+
+    #[tokio::test]
+    async fn it_commits_when_the_max_delay_has_elapsed() {
+        let db = AsyncBlockchainDb::new(create_new_blockchain());
+        let config = WriteBatchConfig {
+            max_operations: 100,
+            max_delay: Duration::from_secs(0),
+        };
+        let mut writer = BatchedDbWriter::new(db.clone(), config);
+        let mut txn = DbTransaction::new();
+        txn.insert_bad_block(FixedHash::from([1u8; 32]), 1, "bad".to_string());
+        assert!(writer.write(txn).await.unwrap());
+        assert!(db.bad_block_exists(FixedHash::from([1u8; 32])).await.unwrap().0);
+    }
+}
@@ -0,0 +1,212 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::HashSet;
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, CommitmentFactory, PrivateKey, PublicKey, Signature};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    hash_domain,
+    hashing::DomainSeparatedHasher,
+    keys::PublicKey as PublicKeyT,
+};
+use tari_utilities::{hex::Hex, ByteArray};
+use thiserror::Error;
+
+use crate::transactions::tari_amount::MicroMinotari;
+
+hash_domain!(BalanceProofHashDomain, "com.tari.base_layer.core.balance_proof", 0);
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum BalanceProofError {
+    #[error("The proof does not contain any commitments")]
+    NoCommitments,
+    #[error("Commitment {0} is included more than once")]
+    DuplicateCommitment(String),
+    #[error("The signature does not prove that the commitments add up to {0}")]
+    InvalidSignature(MicroMinotari),
+    #[error("Could not sign the proof: {0}")]
+    SigningError(String),
+}
+
+/// A proof of reserves over a set of unspent output commitments. Each commitment is `k_i.G + v_i.H`, so subtracting
+/// `total.H` from their sum leaves `(sum k_i).G` exactly when the values add up to `total`. The signature is a Schnorr
+/// signature with that excess as the public key, proving knowledge of the summed blinding factors without revealing
+/// any individual value or spend key. The claimed total is only meaningful if the verifier also checks that every
+/// commitment is in the current unspent set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceProof {
+    /// The commitments of the outputs the reserves are held in
+    pub commitments: Vec<Commitment>,
+    /// The total value held by the commitments
+    pub total: MicroMinotari,
+    /// A free-form statement bound to the proof, such as the verifier's challenge or the date
+    pub message: String,
+    /// The signature with the summed blinding factors
+    pub signature: Signature,
+}
+
+impl BalanceProof {
+    /// Creates a proof that `commitments` add up to `total`, where `blinding_factor_sum` is the sum of the blinding
+    /// factors (spend keys) of all of the commitments.
+    pub fn create(
+        blinding_factor_sum: &PrivateKey,
+        commitments: Vec<Commitment>,
+        total: MicroMinotari,
+        message: String,
+    ) -> Result<Self, BalanceProofError> {
+        let excess = PublicKey::from_secret_key(blinding_factor_sum);
+        let (secret_nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+        let challenge = Self::construct_challenge(&excess, &public_nonce, &commitments, total, &message);
+        let signature = Signature::sign_raw_uniform(blinding_factor_sum, secret_nonce, &challenge)
+            .map_err(|e| BalanceProofError::SigningError(e.to_string()))?;
+        Ok(Self {
+            commitments,
+            total,
+            message,
+            signature,
+        })
+    }
+
+    /// Verifies that the commitments in the proof add up to the claimed total and that the prover knows their
+    /// blinding factors.
+    pub fn verify(&self, factory: &CommitmentFactory) -> Result<(), BalanceProofError> {
+        if self.commitments.is_empty() {
+            return Err(BalanceProofError::NoCommitments);
+        }
+        let mut seen = HashSet::with_capacity(self.commitments.len());
+        for commitment in &self.commitments {
+            if !seen.insert(commitment.as_bytes()) {
+                return Err(BalanceProofError::DuplicateCommitment(commitment.to_hex()));
+            }
+        }
+
+        let sum = self
+            .commitments
+            .iter()
+            .fold(Commitment::default(), |sum, commitment| &sum + commitment);
+        let excess = &sum - &factory.commit_value(&PrivateKey::default(), self.total.as_u64());
+        let challenge = Self::construct_challenge(
+            excess.as_public_key(),
+            self.signature.get_public_nonce(),
+            &self.commitments,
+            self.total,
+            &self.message,
+        );
+        if self.signature.verify_raw_uniform(excess.as_public_key(), &challenge) {
+            Ok(())
+        } else {
+            Err(BalanceProofError::InvalidSignature(self.total))
+        }
+    }
+
+    fn construct_challenge(
+        excess: &PublicKey,
+        public_nonce: &PublicKey,
+        commitments: &[Commitment],
+        total: MicroMinotari,
+        message: &str,
+    ) -> [u8; 64] {
+        let mut hasher = DomainSeparatedHasher::<Blake2b<U64>, BalanceProofHashDomain>::new_with_label("challenge")
+            .chain(excess.as_bytes())
+            .chain(public_nonce.as_bytes());
+        for commitment in commitments {
+            hasher = hasher.chain(commitment.as_bytes());
+        }
+        let hasher = hasher.chain(total.as_u64().to_le_bytes()).chain(message.as_bytes());
+        digest::Digest::finalize(hasher).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::SecretKey;
+
+    use super::*;
+
+    fn commitments(values: &[u64], factory: &CommitmentFactory) -> (PrivateKey, Vec<Commitment>) {
+        let mut blinding_factor_sum = PrivateKey::default();
+        let mut commitments = Vec::new();
+        for value in values {
+            let k = PrivateKey::random(&mut OsRng);
+            commitments.push(factory.commit_value(&k, *value));
+            blinding_factor_sum = &blinding_factor_sum + &k;
+        }
+        (blinding_factor_sum, commitments)
+    }
+
+    #[test]
+    fn it_verifies_the_total() {
+        let factory = CommitmentFactory::default();
+        let (k, commitments) = commitments(&[100, 2_000, 30_000], &factory);
+
+        let proof = BalanceProof::create(
+            &k,
+            commitments.clone(),
+            MicroMinotari::from(32_100),
+            "reserves".to_string(),
+        )
+        .unwrap();
+        proof.verify(&factory).unwrap();
+
+        let mut inflated = proof.clone();
+        inflated.total = MicroMinotari::from(32_101);
+        assert_eq!(
+            inflated.verify(&factory),
+            Err(BalanceProofError::InvalidSignature(MicroMinotari::from(32_101)))
+        );
+
+        let mut reworded = proof.clone();
+        reworded.message = "other reserves".to_string();
+        assert!(reworded.verify(&factory).is_err());
+
+        // The right total without the blinding factors proves nothing
+        let forged = BalanceProof::create(
+            &PrivateKey::random(&mut OsRng),
+            commitments,
+            MicroMinotari::from(32_100),
+            "reserves".to_string(),
+        )
+        .unwrap();
+        assert!(forged.verify(&factory).is_err());
+    }
+
+    #[test]
+    fn it_rejects_duplicates() {
+        let factory = CommitmentFactory::default();
+        let (k, mut commitments) = commitments(&[100], &factory);
+        commitments.push(commitments[0].clone());
+        let proof = BalanceProof::create(&(&k + &k), commitments, MicroMinotari::from(200), String::new()).unwrap();
+        assert!(matches!(
+            proof.verify(&factory),
+            Err(BalanceProofError::DuplicateCommitment(_))
+        ));
+
+        let empty =
+            BalanceProof::create(&PrivateKey::default(), vec![], MicroMinotari::from(0), String::new()).unwrap();
+        assert_eq!(empty.verify(&factory), Err(BalanceProofError::NoCommitments));
+    }
+}
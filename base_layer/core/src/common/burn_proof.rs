@@ -0,0 +1,107 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, FixedHash};
+use tari_mmr::{common::LeafIndex, MerkleProof};
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+
+use crate::{blocks::BlockHeader, transactions::transaction_components::TransactionKernel, KernelMmrHasherBlake256};
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum BurnProofError {
+    #[error("The proof is for header {actual} but header {expected} was expected")]
+    HeaderMismatch { expected: FixedHash, actual: FixedHash },
+    #[error("The kernel is not a burn kernel")]
+    NotABurnKernel,
+    #[error("The kernel burns commitment {actual} but commitment {expected} was given")]
+    CommitmentMismatch { expected: String, actual: String },
+    #[error("Kernel leaf index {leaf_index} is not in the kernel MMR of size {mmr_size}")]
+    LeafIndexOutOfRange { leaf_index: u64, mmr_size: u64 },
+    #[error("The proof does not match the kernel root of the header: {0}")]
+    InvalidProof(String),
+}
+
+/// A proof that an output was burned in a block. The burn kernel commits to the burned output commitment, and the
+/// proof is a membership proof of the kernel in the kernel MMR, which is committed to by the `kernel_mr` of the block
+/// header. A second layer that trusts the header hash can verify the proof and mint the burned value, paid to the claim
+/// public key of the burned output, without access to the blockchain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BurnProof {
+    /// The header of the block the burn was mined in
+    pub header: BlockHeader,
+    /// The burn kernel committing to the burned output
+    pub kernel: TransactionKernel,
+    /// The index of the kernel in the kernel MMR
+    pub leaf_index: u64,
+    /// The merkle proof of the kernel against the `kernel_mr` of the header
+    pub merkle_proof: MerkleProof,
+}
+
+impl BurnProof {
+    /// The height of the block the burn was mined in
+    pub fn height(&self) -> u64 {
+        self.header.height
+    }
+
+    /// Verifies that the output with `burn_commitment` was burned in the block with hash `trusted_header_hash`.
+    pub fn verify(&self, burn_commitment: &Commitment, trusted_header_hash: &FixedHash) -> Result<(), BurnProofError> {
+        let header_hash = self.header.hash();
+        if header_hash != *trusted_header_hash {
+            return Err(BurnProofError::HeaderMismatch {
+                expected: *trusted_header_hash,
+                actual: header_hash,
+            });
+        }
+        if !self.kernel.is_burned() {
+            return Err(BurnProofError::NotABurnKernel);
+        }
+        let kernel_commitment = self
+            .kernel
+            .get_burn_commitment()
+            .map_err(|_| BurnProofError::NotABurnKernel)?;
+        if kernel_commitment != burn_commitment {
+            return Err(BurnProofError::CommitmentMismatch {
+                expected: burn_commitment.to_hex(),
+                actual: kernel_commitment.to_hex(),
+            });
+        }
+        if self.leaf_index >= self.header.kernel_mmr_size {
+            return Err(BurnProofError::LeafIndexOutOfRange {
+                leaf_index: self.leaf_index,
+                mmr_size: self.header.kernel_mmr_size,
+            });
+        }
+
+        let leaf_index = usize::try_from(self.leaf_index).map_err(|e| BurnProofError::InvalidProof(e.to_string()))?;
+        self.merkle_proof
+            .verify_leaf::<KernelMmrHasherBlake256>(
+                self.header.kernel_mr.as_slice(),
+                self.kernel.hash().as_slice(),
+                LeafIndex(leaf_index),
+            )
+            .map_err(|e| BurnProofError::InvalidProof(e.to_string()))
+    }
+}
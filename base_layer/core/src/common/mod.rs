@@ -22,14 +22,21 @@
 
 use blake2::Blake2b;
 use digest::consts::U64;
+use tari_comms::peer_manager::PeerOffense;
 use tari_hashing::ConfidentialOutputHashDomain;
 
 use crate::consensus::DomainSeparatedConsensusHasher;
 
+pub mod balance_proof;
 pub mod borsh;
+#[cfg(feature = "base_node")]
+pub mod burn_proof;
 pub mod byte_counter;
 pub mod limited_reader;
 pub mod one_sided;
+#[cfg(feature = "base_node")]
+pub mod output_inclusion_proof;
+pub mod payment_reference;
 
 #[cfg(feature = "base_node")]
 pub mod rolling_avg;
@@ -65,4 +72,13 @@ impl BanReason {
     pub fn ban_duration(&self) -> BanPeriod {
         self.ban_duration
     }
+
+    /// The offense to report to the peer ban policy. Short bans are for infractions that are likely not malicious and
+    /// are reported as [PeerOffense::Unresponsive], otherwise the given `misbehaviour` is returned.
+    pub fn offense(&self, misbehaviour: PeerOffense) -> PeerOffense {
+        match self.ban_duration {
+            BanPeriod::Short => PeerOffense::Unresponsive,
+            BanPeriod::Long => misbehaviour,
+        }
+    }
 }
@@ -32,7 +32,8 @@ use tari_crypto::{
     keys::{PublicKey as PKtrait, SecretKey as SKtrait},
 };
 use tari_hashing::WalletOutputEncryptionKeysDomain;
-use tari_utilities::byte_array::ByteArrayError;
+use tari_script::{Opcode, TariScript};
+use tari_utilities::{byte_array::ByteArrayError, ByteArray};
 
 hash_domain!(
     WalletOutputRewindKeysDomain,
@@ -89,3 +90,38 @@ pub fn stealth_address_script_spending_key(
             .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size"),
     ) + destination_public_key
 }
+
+/// The offset of a subaddress private key from the wallet's view key. The subaddress at `index` of a wallet with view
+/// key `k` has private key `k + H(k, index)`, so only the holder of the view key can link subaddresses to the wallet or
+/// to each other.
+pub fn subaddress_key_offset(view_private_key: &PrivateKey, index: u64) -> PrivateKey {
+    PrivateKey::from_uniform_bytes(
+        WalletHasher::new_with_label("subaddress")
+            .chain(view_private_key.as_bytes())
+            .chain(index.to_le_bytes())
+            .finalize()
+            .as_ref(),
+    )
+    .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size")
+}
+
+/// Returns the script public key of a one-sided or stealth one-sided payment script, or None if the script is not a
+/// one-sided payment script. For one-sided payments this is the recipient's public key, for stealth payments it is the
+/// one-time stealth spending key.
+pub fn one_sided_script_public_key(script: &TariScript) -> Option<&PublicKey> {
+    match script.as_slice() {
+        [Opcode::PushPubKey(public_key)] | [Opcode::PushPubKey(_), Opcode::Drop, Opcode::PushPubKey(public_key)] => {
+            Some(&**public_key)
+        },
+        _ => None,
+    }
+}
+
+/// Returns the scan tag of a one-sided payment script, being the recipient's public key. Stealth one-sided payments use
+/// a fresh script key per output, so they carry no scan tag and can only be found by trial decryption.
+pub fn one_sided_scan_tag(script: &TariScript) -> Option<&PublicKey> {
+    match script.as_slice() {
+        [Opcode::PushPubKey(public_key)] => Some(&**public_key),
+        _ => None,
+    }
+}
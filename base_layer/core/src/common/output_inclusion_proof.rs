@@ -0,0 +1,107 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{FixedHash, HashOutput};
+use tari_mmr::sparse_merkle_tree::{InclusionProof, NodeHash, NodeKey, ValueHash};
+use tari_utilities::ByteArray;
+use thiserror::Error;
+
+use crate::{blocks::BlockHeader, transactions::transaction_components::TransactionOutput, OutputSmtHasherBlake256};
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum OutputInclusionProofError {
+    #[error("The proof is for header {actual} but header {expected} was expected")]
+    HeaderMismatch { expected: FixedHash, actual: FixedHash },
+    #[error("The proof is for output {actual} but output {expected} was given")]
+    OutputMismatch { expected: HashOutput, actual: HashOutput },
+    #[error("The output was mined at height {mined_height}, after the proof height {height}")]
+    MinedAfterProofHeight { mined_height: u64, height: u64 },
+    #[error("Invalid proof data: {0}")]
+    InvalidData(String),
+    #[error("The proof does not match the output root of the header")]
+    InvalidProof,
+}
+
+/// A proof that an output was in the unspent output set at a block height. The proof is a membership proof in the
+/// output sparse merkle tree, which is committed to by the `output_mr` of the block header at that height, so anyone
+/// that trusts the header hash can verify the proof without access to the blockchain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputInclusionProof {
+    /// The header of the block at the height the proof is for
+    pub header: BlockHeader,
+    pub output_hash: HashOutput,
+    /// The height at which the output was mined, which is part of the value committed to in the tree
+    pub mined_height: u64,
+    /// The sibling hashes along the path to the output's leaf in the output sparse merkle tree
+    pub siblings: Vec<NodeHash>,
+}
+
+impl OutputInclusionProof {
+    /// The height of the block the proof is for
+    pub fn height(&self) -> u64 {
+        self.header.height
+    }
+
+    /// Verifies that `output` was unspent at the height of the proof in the chain that contains the block with hash
+    /// `trusted_header_hash`.
+    pub fn verify(
+        &self,
+        output: &TransactionOutput,
+        trusted_header_hash: &FixedHash,
+    ) -> Result<(), OutputInclusionProofError> {
+        let header_hash = self.header.hash();
+        if header_hash != *trusted_header_hash {
+            return Err(OutputInclusionProofError::HeaderMismatch {
+                expected: *trusted_header_hash,
+                actual: header_hash,
+            });
+        }
+        let output_hash = output.hash();
+        if output_hash != self.output_hash {
+            return Err(OutputInclusionProofError::OutputMismatch {
+                expected: output_hash,
+                actual: self.output_hash,
+            });
+        }
+        if self.mined_height > self.header.height {
+            return Err(OutputInclusionProofError::MinedAfterProofHeight {
+                mined_height: self.mined_height,
+                height: self.header.height,
+            });
+        }
+
+        let key = NodeKey::try_from(output.commitment.as_bytes())
+            .map_err(|e| OutputInclusionProofError::InvalidData(e.to_string()))?;
+        let value = ValueHash::try_from(output.smt_hash(self.mined_height).as_slice())
+            .map_err(|e| OutputInclusionProofError::InvalidData(e.to_string()))?;
+        let root = NodeHash::try_from(self.header.output_mr.as_slice())
+            .map_err(|e| OutputInclusionProofError::InvalidData(e.to_string()))?;
+        let proof = InclusionProof::<OutputSmtHasherBlake256>::new(self.siblings.clone());
+        if !proof.validate(&key, &value, &root) {
+            return Err(OutputInclusionProofError::InvalidProof);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use tari_common_types::types::{BlockHash, FixedHash, HashOutput};
+use tari_crypto::hash_domain;
+
+use crate::consensus::DomainSeparatedConsensusHasher;
+
+hash_domain!(
+    PaymentReferenceHashDomain,
+    "com.tari.base_layer.core.payment_reference",
+    1
+);
+
+/// Generate the payment reference of an output. A payment reference identifies an output mined in a specific block and
+/// can be shared by the sender and receiver of a payment without revealing any of the output's keys.
+pub fn generate_payment_reference(block_hash: &BlockHash, output_hash: &HashOutput) -> FixedHash {
+    DomainSeparatedConsensusHasher::<PaymentReferenceHashDomain, Blake2b<U32>>::new("payment_reference")
+        .chain(block_hash)
+        .chain(output_hash)
+        .finalize()
+        .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_is_unique_per_block_and_output() {
+        let block_hash = FixedHash::from([1u8; 32]);
+        let output_hash = FixedHash::from([2u8; 32]);
+        let payref = generate_payment_reference(&block_hash, &output_hash);
+        assert_eq!(payref, generate_payment_reference(&block_hash, &output_hash));
+        assert_ne!(payref, generate_payment_reference(&output_hash, &block_hash));
+        assert_ne!(
+            payref,
+            generate_payment_reference(&FixedHash::from([3u8; 32]), &output_hash)
+        );
+    }
+}
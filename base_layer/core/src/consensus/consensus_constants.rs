@@ -22,6 +22,7 @@
 
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     ops::{Add, RangeInclusive},
 };
 
@@ -34,7 +35,7 @@ use tari_utilities::epoch_time::EpochTime;
 use crate::{
     borsh::SerializedSize,
     consensus::network::NetworkConsensus,
-    proof_of_work::{Difficulty, PowAlgorithm},
+    proof_of_work::{Difficulty, DifficultyAlgorithm, PowAlgorithm},
     transactions::{
         tari_amount::{uT, MicroMinotari},
         transaction_components::{
@@ -69,6 +70,8 @@ pub struct ConsensusConstants {
     /// When doing difficulty adjustments and FTL calculations this is the amount of blocks we look at
     /// <https://github.com/zawy12/difficulty-algorithms/issues/14>
     difficulty_block_window: u64,
+    /// The difficulty adjustment algorithm used to calculate the target difficulty from the difficulty block window
+    difficulty_algorithm: DifficultyAlgorithm,
     /// Maximum transaction weight used for the construction of new blocks.
     max_block_transaction_weight: u64,
     /// This is how many blocks we use to count towards the median timestamp to ensure the block chain timestamp moves
@@ -111,6 +114,13 @@ pub struct ConsensusConstants {
     coinbase_output_features_extra_max_length: u32,
     /// Maximum number of token elements permitted in covenants
     max_covenant_length: u32,
+    /// Maximum length of the binary and repository URLs of a code template registration. The code template
+    /// registration rules are only enforced in epochs that set this limit.
+    max_template_registration_url_length: Option<usize>,
+    /// Maximum size in bytes of the payment reference carried in the encrypted data of an output
+    max_payment_reference_size: usize,
+    /// Maximum size in bytes of the memo carried in the encrypted data of an output
+    max_memo_size: usize,
     /// Epoch duration in blocks
     vn_epoch_length: u64,
     /// The number of Epochs that a validator node registration is valid
@@ -225,6 +235,11 @@ impl ConsensusConstants {
         self.difficulty_block_window
     }
 
+    /// The difficulty adjustment algorithm used for blocks from the effective height of these constants
+    pub fn difficulty_algorithm(&self) -> DifficultyAlgorithm {
+        self.difficulty_algorithm
+    }
+
     /// Maximum transaction weight used for the construction of new blocks.
     pub fn max_block_transaction_weight(&self) -> u64 {
         self.max_block_transaction_weight
@@ -255,6 +270,11 @@ impl ConsensusConstants {
         self.proof_of_work.len() as u64
     }
 
+    /// Whether blocks may be mined with the given PoW algorithm while these constants are effective
+    pub fn is_pow_algo_enabled(&self, pow_algo: PowAlgorithm) -> bool {
+        self.proof_of_work.contains_key(&pow_algo)
+    }
+
     /// The target time used by the difficulty adjustment algorithms, their target time is the target block interval /
     /// algo block percentage
     pub fn pow_target_block_interval(&self, pow_algo: PowAlgorithm) -> u64 {
@@ -264,6 +284,15 @@ impl ConsensusConstants {
         }
     }
 
+    /// The target block interval of the chain across all enabled PoW algorithms, which is the harmonic combination of
+    /// their target times
+    pub fn target_block_interval(&self) -> u64 {
+        let target_times = self.proof_of_work.values().map(|v| u128::from(v.target_time.max(1)));
+        let product = target_times.clone().product::<u128>();
+        let rate = target_times.map(|t| product / t).sum::<u128>();
+        u64::try_from(product.checked_div(rate).unwrap_or(0)).unwrap_or(u64::MAX)
+    }
+
     /// This is how many blocks we use to count towards the median timestamp to ensure the block chain moves forward.
     pub fn median_timestamp_count(&self) -> usize {
         self.median_timestamp_count
@@ -334,6 +363,22 @@ impl ConsensusConstants {
         self.max_covenant_length
     }
 
+    /// The maximum length of the binary and repository URLs of a code template registration, or `None` if the code
+    /// template registration rules are not active
+    pub fn max_template_registration_url_length(&self) -> Option<usize> {
+        self.max_template_registration_url_length
+    }
+
+    /// The maximum size in bytes of the payment reference carried in the encrypted data of an output
+    pub fn max_payment_reference_size(&self) -> usize {
+        self.max_payment_reference_size
+    }
+
+    /// The maximum size in bytes of the memo carried in the encrypted data of an output
+    pub fn max_memo_size(&self) -> usize {
+        self.max_memo_size
+    }
+
     pub fn validator_node_validity_period_epochs(&self) -> VnEpoch {
         self.vn_validity_period_epochs
     }
@@ -377,14 +422,17 @@ impl ConsensusConstants {
             max_difficulty: Difficulty::min(),
             target_time: 240,
         });
-        let (input_version_range, output_version_range, kernel_version_range) = version_zero();
-        let consensus_constants = vec![ConsensusConstants {
+        let (input_version_range, mut output_version_range, kernel_version_range) = version_zero();
+        // The V1 script opcodes (threshold multisig and hash preimage verification) are active from genesis on localnet
+        output_version_range.opcode = OpcodeVersion::V0..=OpcodeVersion::V1;
+        let mut consensus_constants = vec![ConsensusConstants {
             effective_from_height: 0,
             coinbase_min_maturity: 2,
             blockchain_version: 0,
             valid_blockchain_version_range: 0..=0,
             future_time_limit: 540,
             difficulty_block_window,
+            difficulty_algorithm: DifficultyAlgorithm::Lwma,
             max_block_transaction_weight: 19500,
             median_timestamp_count: 11,
             emission_initial: 18_462_816_327 * uT,
@@ -403,6 +451,9 @@ impl ConsensusConstants {
             permitted_output_types: OutputType::all(),
             permitted_range_proof_types: Self::all_range_proof_types(),
             max_covenant_length: 100,
+            max_template_registration_url_length: None,
+            max_payment_reference_size: 64,
+            max_memo_size: 128,
             vn_epoch_length: 10,
             vn_validity_period_epochs: VnEpoch(100),
             vn_registration_min_deposit_amount: MicroMinotari(0),
@@ -410,8 +461,13 @@ impl ConsensusConstants {
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
         }];
+        // The code template registration rules are enforced from this epoch onwards
+        let mut template_registration_rules = consensus_constants[0].clone();
+        template_registration_rules.effective_from_height = 1_000;
+        template_registration_rules.max_template_registration_url_length = Some(255);
+        consensus_constants.push(template_registration_rules);
         #[cfg(any(test, debug_assertions))]
-        assert_hybrid_pow_constants(&consensus_constants, &[120], &[50], &[50]);
+        assert_hybrid_pow_constants(&consensus_constants, &[120, 120], &[50, 50], &[50, 50]);
         consensus_constants
     }
 
@@ -446,6 +502,7 @@ impl ConsensusConstants {
             valid_blockchain_version_range: 0..=0,
             future_time_limit,
             difficulty_block_window,
+            difficulty_algorithm: DifficultyAlgorithm::Lwma,
             // 65536 =  target_block_size / bytes_per_gram =  (1024*1024) / 16
             // adj. + 95% = 127,795 - this effectively targets ~2Mb blocks closely matching the previous 19500
             // weightings
@@ -468,6 +525,9 @@ impl ConsensusConstants {
             permitted_output_types: OutputType::all(),
             permitted_range_proof_types: Self::all_range_proof_types(),
             max_covenant_length: 100,
+            max_template_registration_url_length: None,
+            max_payment_reference_size: 64,
+            max_memo_size: 128,
             vn_epoch_length: 10,
             vn_validity_period_epochs: VnEpoch(3),
             vn_registration_min_deposit_amount: MicroMinotari(0),
@@ -506,6 +566,7 @@ impl ConsensusConstants {
             valid_blockchain_version_range: 0..=0,
             future_time_limit: 540,
             difficulty_block_window: 90,
+            difficulty_algorithm: DifficultyAlgorithm::Lwma,
             max_block_transaction_weight: 127_795,
             median_timestamp_count: 11,
             emission_initial: ESMERALDA_INITIAL_EMISSION,
@@ -524,6 +585,9 @@ impl ConsensusConstants {
             permitted_output_types: Self::current_permitted_output_types(),
             permitted_range_proof_types: Self::current_permitted_range_proof_types(),
             max_covenant_length: 0,
+            max_template_registration_url_length: None,
+            max_payment_reference_size: 64,
+            max_memo_size: 128,
             vn_epoch_length: 60,
             vn_validity_period_epochs: VnEpoch(100),
             vn_registration_min_deposit_amount: MicroMinotari(0),
@@ -562,6 +626,7 @@ impl ConsensusConstants {
             valid_blockchain_version_range: 0..=0,
             future_time_limit: 540,
             difficulty_block_window: 90,
+            difficulty_algorithm: DifficultyAlgorithm::Lwma,
             max_block_transaction_weight: 127_795,
             median_timestamp_count: 11,
             emission_initial: INITIAL_EMISSION,
@@ -580,6 +645,9 @@ impl ConsensusConstants {
             permitted_output_types: Self::current_permitted_output_types(),
             permitted_range_proof_types: Self::current_permitted_range_proof_types(),
             max_covenant_length: 0,
+            max_template_registration_url_length: None,
+            max_payment_reference_size: 64,
+            max_memo_size: 128,
             vn_epoch_length: 60,
             vn_validity_period_epochs: VnEpoch(100),
             vn_registration_min_deposit_amount: MicroMinotari(0),
@@ -612,6 +680,7 @@ impl ConsensusConstants {
             valid_blockchain_version_range: 0..=0,
             future_time_limit: 540,
             difficulty_block_window: 90,
+            difficulty_algorithm: DifficultyAlgorithm::Lwma,
             max_block_transaction_weight: 127_795,
             median_timestamp_count: 11,
             emission_initial: INITIAL_EMISSION,
@@ -630,6 +699,9 @@ impl ConsensusConstants {
             permitted_output_types: Self::current_permitted_output_types(),
             permitted_range_proof_types: Self::current_permitted_range_proof_types(),
             max_covenant_length: 0,
+            max_template_registration_url_length: None,
+            max_payment_reference_size: 64,
+            max_memo_size: 128,
             vn_epoch_length: 60,
             vn_validity_period_epochs: VnEpoch(100),
             vn_registration_min_deposit_amount: MicroMinotari(0),
@@ -664,6 +736,7 @@ impl ConsensusConstants {
             valid_blockchain_version_range: 0..=0,
             future_time_limit: 540,
             difficulty_block_window,
+            difficulty_algorithm: DifficultyAlgorithm::Lwma,
             max_block_transaction_weight: 19500,
             median_timestamp_count: 11,
             emission_initial: 10_000_000.into(),
@@ -682,6 +755,9 @@ impl ConsensusConstants {
             permitted_output_types: Self::current_permitted_output_types(),
             permitted_range_proof_types: Self::current_permitted_range_proof_types(),
             max_covenant_length: 0,
+            max_template_registration_url_length: None,
+            max_payment_reference_size: 64,
+            max_memo_size: 128,
             vn_epoch_length: 60,
             vn_validity_period_epochs: VnEpoch(100),
             vn_registration_min_deposit_amount: MicroMinotari(0),
@@ -826,6 +902,16 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_max_payment_reference_size(mut self, byte_size: usize) -> Self {
+        self.consensus.max_payment_reference_size = byte_size;
+        self
+    }
+
+    pub fn with_max_memo_size(mut self, byte_size: usize) -> Self {
+        self.consensus.max_memo_size = byte_size;
+        self
+    }
+
     pub fn with_max_block_transaction_weight(mut self, weight: u64) -> Self {
         self.consensus.max_block_transaction_weight = weight;
         self
@@ -873,6 +959,21 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_effective_from_height(mut self, height: u64) -> Self {
+        self.consensus.effective_from_height = height;
+        self
+    }
+
+    pub fn with_opcode_version_range(mut self, range: RangeInclusive<OpcodeVersion>) -> Self {
+        self.consensus.output_version_range.opcode = range;
+        self
+    }
+
+    pub fn with_difficulty_algorithm(mut self, algorithm: DifficultyAlgorithm) -> Self {
+        self.consensus.difficulty_algorithm = algorithm;
+        self
+    }
+
     pub fn with_blockchain_version(mut self, version: u16) -> Self {
         self.consensus.blockchain_version = version;
         self
@@ -887,11 +988,18 @@ impl ConsensusConstantsBuilder {
 mod test {
     use std::convert::TryFrom;
 
+    use tari_common::configuration::Network;
+    use tari_script::OpcodeVersion;
+    use tari_utilities::epoch_time::EpochTime;
+
     use crate::{
         consensus::{
             emission::{Emission, EmissionSchedule},
             ConsensusConstants,
+            ConsensusConstantsBuilder,
+            ConsensusManager,
         },
+        proof_of_work::{Difficulty, DifficultyAlgorithm, PowAlgorithm},
         transactions::{
             tari_amount::{uT, MicroMinotari, T},
             transaction_components::{OutputType, RangeProofType},
@@ -1158,4 +1266,98 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn all_networks_use_lwma() {
+        let constants = [
+            ConsensusConstants::localnet(),
+            ConsensusConstants::igor(),
+            ConsensusConstants::esmeralda(),
+            ConsensusConstants::stagenet(),
+            ConsensusConstants::nextnet(),
+            ConsensusConstants::mainnet(),
+        ];
+        for c in constants.iter().flatten() {
+            assert_eq!(c.difficulty_algorithm(), DifficultyAlgorithm::Lwma);
+        }
+    }
+
+    #[test]
+    fn difficulty_algorithm_is_selected_by_activation_height() {
+        let asert = DifficultyAlgorithm::Asert {
+            half_life: 3600,
+            anchor_height: 90,
+            anchor_timestamp: 10_000,
+        };
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(ConsensusConstantsBuilder::new(Network::LocalNet).build())
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_effective_from_height(100)
+                    .with_difficulty_algorithm(asert)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            rules.consensus_constants(99).difficulty_algorithm(),
+            DifficultyAlgorithm::Lwma
+        );
+        assert_eq!(rules.consensus_constants(100).difficulty_algorithm(), asert);
+        assert_eq!(rules.consensus_constants(u64::MAX).difficulty_algorithm(), asert);
+
+        let mut lwma_window = rules.new_target_difficulty(PowAlgorithm::Sha3x, 99).unwrap();
+        let mut asert_window = rules.new_target_difficulty(PowAlgorithm::Sha3x, 100).unwrap();
+        let difficulty = Difficulty::from_u64(1_000).unwrap();
+        for window in [&mut lwma_window, &mut asert_window] {
+            // The Sha3x blocks are found twice as fast as their target time, but the chain as a whole is on schedule
+            for i in 0..=90 {
+                window.add_back(EpochTime::from(i * 120), difficulty);
+            }
+            window.set_anchor_difficulty(difficulty);
+            window.set_chain_tip(99, EpochTime::from(11_080));
+        }
+        let min = Difficulty::min();
+        let max = Difficulty::max();
+        assert_eq!(lwma_window.calculate(min, max), Difficulty::from_u64(2_000).unwrap());
+        assert_eq!(asert_window.calculate(min, max), difficulty);
+    }
+
+    #[test]
+    fn opcode_version_is_selected_by_activation_height() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_opcode_version_range(OpcodeVersion::V0..=OpcodeVersion::V0)
+                    .build(),
+            )
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_effective_from_height(100)
+                    .with_opcode_version_range(OpcodeVersion::V0..=OpcodeVersion::V1)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let opcode_range = |height| rules.consensus_constants(height).output_version_range().opcode.clone();
+        assert!(!opcode_range(99).contains(&OpcodeVersion::V1));
+        assert!(opcode_range(100).contains(&OpcodeVersion::V1));
+        assert!(opcode_range(100).contains(&OpcodeVersion::V0));
+    }
+
+    #[test]
+    fn v1_opcodes_are_only_active_on_localnet() {
+        let localnet = ConsensusConstants::localnet();
+        assert!(localnet[0].output_version_range().opcode.contains(&OpcodeVersion::V1));
+        let constants = [
+            ConsensusConstants::igor(),
+            ConsensusConstants::esmeralda(),
+            ConsensusConstants::stagenet(),
+            ConsensusConstants::nextnet(),
+            ConsensusConstants::mainnet(),
+        ];
+        for c in constants.iter().flatten() {
+            assert!(!c.output_version_range().opcode.contains(&OpcodeVersion::V1));
+        }
+    }
 }
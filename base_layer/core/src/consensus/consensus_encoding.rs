@@ -21,6 +21,8 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod bytes;
+#[cfg(feature = "base_node")]
+pub mod conformance;
 mod hashing;
 mod string;
 
@@ -0,0 +1,423 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Canonical consensus encoding test vectors.
+//!
+//! A fixed, deterministic instance of every consensus struct is Borsh encoded and hashed. The resulting vectors can
+//! be exported by one version of the software and checked by another, so that changes to the consensus encoding
+//! (which would split the network) are caught before they are released.
+
+use std::fmt::{Display, Formatter};
+
+use blake2::Blake2b;
+use borsh::{BorshDeserialize, BorshSerialize};
+use digest::consts::U32;
+use monero::{blockdata::transaction::RawExtraField, VarInt};
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_common_types::types::{
+    ComAndPubSignature,
+    Commitment,
+    CommitmentFactory,
+    FixedHash,
+    PrivateKey,
+    PublicKey,
+    RangeProof,
+    Signature,
+};
+use tari_crypto::{commitment::HomomorphicCommitmentFactory, hash_domain, keys::PublicKey as PublicKeyTrait};
+use tari_script::{script, ExecutionStack};
+use tari_utilities::{hex::Hex, ByteArray};
+use thiserror::Error;
+use tiny_keccak::Keccak;
+
+use crate::{
+    blocks::{Block, BlockHeader},
+    consensus::DomainSeparatedConsensusHasher,
+    covenants::CovenantBuilder,
+    proof_of_work::{
+        monero_rx::{FixedByteArray, MerkleProof, MoneroPowData},
+        PowAlgorithm,
+        ProofOfWork,
+    },
+    transactions::{
+        aggregated_body::AggregateBody,
+        tari_amount::MicroMinotari,
+        transaction_components::{
+            EncryptedData,
+            KernelFeatures,
+            OutputFeatures,
+            OutputFeaturesVersion,
+            OutputType,
+            RangeProofType,
+            TransactionInput,
+            TransactionInputVersion,
+            TransactionKernel,
+            TransactionKernelVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
+    },
+};
+
+hash_domain!(
+    ConformanceHashDomain,
+    "com.tari.base_layer.core.consensus.consensus_encoding.conformance",
+    0
+);
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum ConformanceError {
+    #[error("Failed to encode `{name}`: {details}")]
+    EncodingFailed { name: String, details: String },
+    #[error("`{name}` does not re-encode to the same bytes after decoding")]
+    RoundTripFailed { name: String },
+    #[error("The vectors were created for network {expected}, but the current network is {actual}")]
+    NetworkMismatch { expected: Network, actual: Network },
+    #[error("No vector is generated for `{0}`")]
+    MissingVector(String),
+    #[error("The encoding of `{name}` changed: expected {expected}, got {actual}")]
+    EncodingMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("The hash of `{name}` changed: expected {expected}, got {actual}")]
+    HashMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// The canonical Borsh encoding and hash of a consensus struct
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConformanceVector {
+    /// The name of the consensus struct
+    pub name: String,
+    /// The hex encoded Borsh encoding
+    pub encoding: String,
+    /// The hex encoded consensus hash of the struct. Structs without a consensus hash use a domain-separated hash of
+    /// their encoding.
+    pub hash: String,
+}
+
+impl Display for ConformanceVector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({} bytes)", self.name, self.hash, self.encoding.len() / 2)
+    }
+}
+
+/// A set of conformance vectors. Consensus hashes are domain separated by network, so the vectors are only valid for
+/// the network they were created for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConformanceVectors {
+    pub network: Network,
+    pub vectors: Vec<ConformanceVector>,
+}
+
+impl ConformanceVectors {
+    /// Generates the vectors of the running software for the current network. Every struct is decoded and re-encoded
+    /// to check that its encoding round-trips.
+    pub fn generate() -> Result<Self, ConformanceError> {
+        let header = sample_header();
+        let monero_pow_data = sample_monero_pow_data();
+        let mut randomx_header = header.clone();
+        randomx_header.pow = ProofOfWork {
+            pow_algo: PowAlgorithm::RandomX,
+            pow_data: borsh::to_vec(&monero_pow_data).map_err(|e| ConformanceError::EncodingFailed {
+                name: "MoneroPowData".to_string(),
+                details: e.to_string(),
+            })?,
+        };
+        let features = sample_output_features();
+        let output = sample_output(features.clone());
+        let input = sample_input(&output);
+        let kernel = sample_kernel();
+        let block = Block::new(
+            header.clone(),
+            AggregateBody::new(vec![input.clone()], vec![output.clone()], vec![kernel.clone()]),
+        );
+
+        let vectors = vec![
+            encode("BlockHeader", &header, header.hash())?,
+            encode("BlockHeader(RandomX)", &randomx_header, randomx_header.hash())?,
+            encode(
+                "FixedByteArray",
+                &monero_pow_data.randomx_key,
+                hash_encoding(&monero_pow_data.randomx_key),
+            )?,
+            encode("MoneroPowData", &monero_pow_data, hash_encoding(&monero_pow_data))?,
+            encode("OutputFeatures", &features, hash_encoding(&features))?,
+            encode("TransactionOutput", &output, output.hash())?,
+            encode("TransactionInput", &input, input.canonical_hash())?,
+            encode("TransactionKernel", &kernel, kernel.hash())?,
+            encode("Block", &block, block.hash())?,
+        ];
+
+        Ok(Self {
+            network: Network::get_current_or_user_setting_or_default(),
+            vectors,
+        })
+    }
+
+    /// Checks that the running software produces exactly these vectors, returning all differences.
+    pub fn check(&self) -> Result<(), Vec<ConformanceError>> {
+        let current = Self::generate().map_err(|e| vec![e])?;
+        if current.network != self.network {
+            return Err(vec![ConformanceError::NetworkMismatch {
+                expected: self.network,
+                actual: current.network,
+            }]);
+        }
+        let errors = self
+            .vectors
+            .iter()
+            .filter_map(|expected| {
+                let actual = match current.vectors.iter().find(|v| v.name == expected.name) {
+                    Some(v) => v,
+                    None => return Some(ConformanceError::MissingVector(expected.name.clone())),
+                };
+                if actual.encoding != expected.encoding {
+                    return Some(ConformanceError::EncodingMismatch {
+                        name: expected.name.clone(),
+                        expected: expected.encoding.clone(),
+                        actual: actual.encoding.clone(),
+                    });
+                }
+                if actual.hash != expected.hash {
+                    return Some(ConformanceError::HashMismatch {
+                        name: expected.name.clone(),
+                        expected: expected.hash.clone(),
+                        actual: actual.hash.clone(),
+                    });
+                }
+                None
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn encode<T: BorshSerialize + BorshDeserialize>(
+    name: &str,
+    value: &T,
+    hash: FixedHash,
+) -> Result<ConformanceVector, ConformanceError> {
+    let encoding_failed = |e: std::io::Error| ConformanceError::EncodingFailed {
+        name: name.to_string(),
+        details: e.to_string(),
+    };
+    let encoding = borsh::to_vec(value).map_err(encoding_failed)?;
+    let decoded = T::deserialize(&mut encoding.as_slice()).map_err(encoding_failed)?;
+    if borsh::to_vec(&decoded).map_err(encoding_failed)? != encoding {
+        return Err(ConformanceError::RoundTripFailed { name: name.to_string() });
+    }
+    Ok(ConformanceVector {
+        name: name.to_string(),
+        encoding: encoding.to_hex(),
+        hash: hash.to_hex(),
+    })
+}
+
+fn hash_encoding<T: BorshSerialize>(value: &T) -> FixedHash {
+    DomainSeparatedConsensusHasher::<ConformanceHashDomain, Blake2b<U32>>::new("conformance")
+        .chain(value)
+        .finalize()
+        .into()
+}
+
+fn key(n: u64) -> PrivateKey {
+    PrivateKey::from(n)
+}
+
+fn public_key(n: u64) -> PublicKey {
+    PublicKey::from_secret_key(&key(n))
+}
+
+fn commitment(mask: u64, value: u64) -> Commitment {
+    CommitmentFactory::default().commit(&key(mask), &key(value))
+}
+
+fn com_and_pub_signature(n: u64) -> ComAndPubSignature {
+    ComAndPubSignature::new(
+        commitment(n, n + 1),
+        public_key(n + 2),
+        key(n + 3),
+        key(n + 4),
+        key(n + 5),
+    )
+}
+
+fn sample_header() -> BlockHeader {
+    BlockHeader {
+        version: 1,
+        height: 1234,
+        prev_hash: FixedHash::from([1u8; 32]),
+        timestamp: 1_700_000_000.into(),
+        input_mr: FixedHash::from([2u8; 32]),
+        output_mr: FixedHash::from([3u8; 32]),
+        output_smt_size: 5678,
+        kernel_mr: FixedHash::from([4u8; 32]),
+        kernel_mmr_size: 910,
+        total_kernel_offset: key(5),
+        total_script_offset: key(6),
+        validator_node_mr: FixedHash::from([7u8; 32]),
+        validator_node_size: 11,
+        pow: ProofOfWork {
+            pow_algo: PowAlgorithm::Sha3x,
+            pow_data: vec![],
+        },
+        nonce: 12,
+    }
+}
+
+fn sample_monero_pow_data() -> MoneroPowData {
+    MoneroPowData {
+        header: monero::BlockHeader {
+            major_version: VarInt(16),
+            minor_version: VarInt(16),
+            timestamp: VarInt(1_700_000_000),
+            prev_id: monero::Hash::new([8u8; 32]),
+            nonce: 0,
+        },
+        randomx_key: FixedByteArray::from_canonical_bytes(&[9u8; 32]).expect("32 bytes fit into a FixedByteArray"),
+        transaction_count: 3,
+        merkle_root: monero::Hash::new([10u8; 32]),
+        coinbase_merkle_proof: MerkleProof::default(),
+        coinbase_tx_hasher: Keccak::v256(),
+        coinbase_tx_extra: RawExtraField(vec![1, 2, 3]),
+        aux_chain_merkle_proof: MerkleProof::default(),
+    }
+}
+
+fn sample_output_features() -> OutputFeatures {
+    OutputFeatures {
+        version: OutputFeaturesVersion::V0,
+        output_type: OutputType::Standard,
+        maturity: 42,
+        coinbase_extra: vec![],
+        sidechain_feature: None,
+        range_proof_type: RangeProofType::BulletProofPlus,
+    }
+}
+
+fn sample_output(features: OutputFeatures) -> TransactionOutput {
+    TransactionOutput::new(
+        TransactionOutputVersion::V0,
+        features,
+        commitment(13, 100),
+        Some(RangeProof::from_canonical_bytes(&[14u8; 32]).expect("RangeProof accepts any bytes")),
+        script!(PushPubKey(Box::new(public_key(15)))),
+        public_key(16),
+        com_and_pub_signature(17),
+        CovenantBuilder::time_locked_vault(100).build(),
+        EncryptedData::default(),
+        MicroMinotari(100),
+    )
+}
+
+fn sample_input(spent: &TransactionOutput) -> TransactionInput {
+    TransactionInput::new_with_output_data(
+        TransactionInputVersion::V0,
+        spent.features.clone(),
+        spent.commitment.clone(),
+        spent.script.clone(),
+        ExecutionStack::new(vec![]),
+        com_and_pub_signature(23),
+        spent.sender_offset_public_key.clone(),
+        spent.covenant.clone(),
+        spent.encrypted_data.clone(),
+        spent.metadata_signature.clone(),
+        FixedHash::from([24u8; 32]),
+        spent.minimum_value_promise,
+    )
+}
+
+fn sample_kernel() -> TransactionKernel {
+    TransactionKernel::new(
+        TransactionKernelVersion::V0,
+        KernelFeatures::empty(),
+        MicroMinotari(25),
+        26,
+        commitment(27, 0),
+        Signature::new(public_key(28), key(29)),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_generates_deterministic_vectors() {
+        let vectors = ConformanceVectors::generate().unwrap();
+        assert_eq!(vectors, ConformanceVectors::generate().unwrap());
+        assert_eq!(vectors.vectors.len(), 9);
+        vectors.check().unwrap();
+    }
+
+    #[test]
+    fn it_round_trips_through_json() {
+        let vectors = ConformanceVectors::generate().unwrap();
+        let json = serde_json::to_string(&vectors).unwrap();
+        let decoded: ConformanceVectors = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, vectors);
+        decoded.check().unwrap();
+    }
+
+    #[test]
+    fn it_reports_changed_vectors() {
+        let mut vectors = ConformanceVectors::generate().unwrap();
+        vectors.vectors[0].hash = FixedHash::zero().to_hex();
+        vectors.vectors[1].encoding = "00".to_string();
+        vectors.vectors.push(ConformanceVector {
+            name: "Unknown".to_string(),
+            encoding: String::new(),
+            hash: String::new(),
+        });
+        let errors = vectors.check().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], ConformanceError::HashMismatch { name, .. } if name == "BlockHeader"));
+        assert!(
+            matches!(&errors[1], ConformanceError::EncodingMismatch { name, .. } if name == "BlockHeader(RandomX)")
+        );
+        assert_eq!(errors[2], ConformanceError::MissingVector("Unknown".to_string()));
+    }
+
+    #[test]
+    fn it_rejects_vectors_for_another_network() {
+        let mut vectors = ConformanceVectors::generate().unwrap();
+        vectors.network = if vectors.network == Network::MainNet {
+            Network::LocalNet
+        } else {
+            Network::MainNet
+        };
+        let errors = vectors.check().unwrap_err();
+        assert!(matches!(errors[0], ConformanceError::NetworkMismatch { .. }));
+    }
+}
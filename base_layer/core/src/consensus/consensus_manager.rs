@@ -29,8 +29,7 @@ use thiserror::Error;
 use crate::{
     blocks::ChainBlock,
     consensus::chain_strength_comparer::{strongest_chain, ChainStrengthComparer},
-    proof_of_work::PowAlgorithm,
-    proof_of_work::TargetDifficultyWindow,
+    proof_of_work::{DifficultyAlgorithm, TargetDifficultyWindow},
 };
 use crate::{
     consensus::{
@@ -38,7 +37,7 @@ use crate::{
         ConsensusConstants,
         NetworkConsensus,
     },
-    proof_of_work::DifficultyAdjustmentError,
+    proof_of_work::{DifficultyAdjustmentError, PowAlgorithm},
     transactions::{tari_amount::MicroMinotari, transaction_components::TransactionKernel},
 };
 
@@ -110,6 +109,15 @@ impl ConsensusManager {
         constants
     }
 
+    /// The height of the first consensus constants that enable the given PoW algorithm, or None if it is never enabled
+    pub fn pow_algo_activation_height(&self, pow_algo: PowAlgorithm) -> Option<u64> {
+        self.inner
+            .consensus_constants
+            .iter()
+            .find(|c| c.is_pow_algo_enabled(pow_algo))
+            .map(|c| c.effective_from_height())
+    }
+
     /// Create a new TargetDifficulty for the given proof of work using constants that are effective from the given
     /// height
     #[cfg(feature = "base_node")]
@@ -125,7 +133,12 @@ impl ConsensusManager {
         let block_window_u =
             usize::try_from(block_window).map_err(|e| format!("difficulty block window exceeds usize::MAX: {}", e))?;
 
-        TargetDifficultyWindow::new(block_window_u, constants.pow_target_block_interval(pow_algo))
+        let target_time = match constants.difficulty_algorithm() {
+            DifficultyAlgorithm::Lwma => constants.pow_target_block_interval(pow_algo),
+            // ASERT schedules the chain as a whole from its anchor rather than the blocks of a single algorithm
+            DifficultyAlgorithm::Asert { .. } => constants.target_block_interval(),
+        };
+        TargetDifficultyWindow::with_algorithm(constants.difficulty_algorithm(), block_window_u, target_time)
     }
 
     /// Creates a total_coinbase offset containing all fees for the validation from the height and kernel set
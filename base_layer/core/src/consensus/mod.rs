@@ -30,6 +30,8 @@ mod consensus_manager;
 pub use consensus_manager::{ConsensusBuilderError, ConsensusManager, ConsensusManagerBuilder, ConsensusManagerError};
 
 mod consensus_encoding;
+#[cfg(feature = "base_node")]
+pub use consensus_encoding::conformance;
 pub use consensus_encoding::{DomainSeparatedConsensusHasher, MaxSizeBytes, MaxSizeString};
 mod network;
 pub use network::NetworkConsensus;
@@ -0,0 +1,239 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::{
+    tari_address::TariAddress,
+    types::{FixedHash, PublicKey},
+};
+use tari_script::{one_sided_payment_script, TariScript};
+
+use crate::{
+    covenants::{fields::OutputField, token::CovenantToken, Covenant},
+    transactions::transaction_components::OutputType,
+};
+
+/// A fluent builder for covenants. Each constructor produces a single filter expression, which can be combined with
+/// [CovenantBuilder::and], [CovenantBuilder::or], [CovenantBuilder::xor] and [CovenantBuilder::not] before calling
+/// [CovenantBuilder::build]. The builder takes care of emitting the tokens in the prefix order the covenant
+/// interpreter expects, so callers never have to assemble a token stream by hand.
+///
+/// ```rust,ignore
+/// // Before height 42, this may only be spent into an output with the same script and covenant
+/// let covenant = CovenantBuilder::time_locked_vault(42).build();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CovenantBuilder {
+    tokens: Vec<CovenantToken>,
+}
+
+impl CovenantBuilder {
+    fn filter(filter: CovenantToken, args: Vec<CovenantToken>) -> Self {
+        let mut tokens = Vec::with_capacity(args.len() + 1);
+        tokens.push(filter);
+        tokens.extend(args);
+        Self { tokens }
+    }
+
+    fn combine(filter: CovenantToken, left: Self, right: Self) -> Self {
+        let mut tokens = Vec::with_capacity(left.tokens.len() + right.tokens.len() + 1);
+        tokens.push(filter);
+        tokens.extend(left.tokens);
+        tokens.extend(right.tokens);
+        Self { tokens }
+    }
+
+    /// Matches every output
+    pub fn identity() -> Self {
+        Self::filter(CovenantToken::identity(), vec![])
+    }
+
+    /// Matches every output at or after the given block height, and no output before it
+    pub fn absolute_height(height: u64) -> Self {
+        Self::filter(CovenantToken::absolute_height(), vec![CovenantToken::uint(height)])
+    }
+
+    /// Matches the output with the given hash
+    pub fn output_hash_eq(hash: FixedHash) -> Self {
+        Self::filter(CovenantToken::output_hash_eq(), vec![CovenantToken::hash(hash)])
+    }
+
+    /// Matches outputs of the given output type
+    pub fn output_type_eq(output_type: OutputType) -> Self {
+        Self::filter(CovenantToken::field_eq(), vec![
+            CovenantToken::field(OutputField::features_output_type()),
+            CovenantToken::output_type(output_type),
+        ])
+    }
+
+    /// Matches outputs with the given script
+    pub fn script_eq(script: TariScript) -> Self {
+        Self::filter(CovenantToken::field_eq(), vec![
+            CovenantToken::field(OutputField::script()),
+            CovenantToken::script(script),
+        ])
+    }
+
+    /// Matches outputs with the given sender offset public key
+    pub fn sender_offset_public_key_eq(public_key: PublicKey) -> Self {
+        Self::filter(CovenantToken::field_eq(), vec![
+            CovenantToken::field(OutputField::sender_offset_public_key()),
+            CovenantToken::public_key(public_key),
+        ])
+    }
+
+    /// Matches outputs with the given maturity
+    pub fn maturity_eq(maturity: u64) -> Self {
+        Self::filter(CovenantToken::field_eq(), vec![
+            CovenantToken::field(OutputField::features_maturity()),
+            CovenantToken::uint(maturity),
+        ])
+    }
+
+    /// Matches outputs in which the given fields are the same as in the input being spent
+    pub fn fields_preserved(fields: Vec<OutputField>) -> Self {
+        Self::filter(CovenantToken::fields_preserved(), vec![CovenantToken::fields(fields)])
+    }
+
+    /// Matches outputs matched by both this and the other expression
+    pub fn and(self, other: Self) -> Self {
+        Self::combine(CovenantToken::and(), self, other)
+    }
+
+    /// Matches outputs matched by either this or the other expression
+    pub fn or(self, other: Self) -> Self {
+        Self::combine(CovenantToken::or(), self, other)
+    }
+
+    /// Matches outputs matched by exactly one of this and the other expression
+    pub fn xor(self, other: Self) -> Self {
+        Self::combine(CovenantToken::xor(), self, other)
+    }
+
+    /// Matches outputs not matched by this expression
+    pub fn not(self) -> Self {
+        let mut tokens = Vec::with_capacity(self.tokens.len() + 1);
+        tokens.push(CovenantToken::not());
+        tokens.extend(self.tokens);
+        Self { tokens }
+    }
+
+    /// Until `unlock_height`, the value may only be spent into an output that keeps the script and covenant of the
+    /// input, i.e. it stays in the vault. From `unlock_height` onwards it can be spent freely.
+    pub fn time_locked_vault(unlock_height: u64) -> Self {
+        Self::absolute_height(unlock_height).or(Self::fields_preserved(vec![
+            OutputField::script(),
+            OutputField::covenant(),
+        ]))
+    }
+
+    /// The value may only be spent into an output of the given output type
+    pub fn output_type_restriction(output_type: OutputType) -> Self {
+        Self::output_type_eq(output_type)
+    }
+
+    /// The value may only be spent into a one-sided payment to the given address
+    pub fn pinned_to_address(address: &TariAddress) -> Self {
+        Self::script_eq(one_sided_payment_script(address.public_key()))
+    }
+
+    /// Consumes the builder and returns the covenant
+    pub fn build(self) -> Covenant {
+        self.tokens.into_iter().collect()
+    }
+}
+
+impl From<CovenantBuilder> for Covenant {
+    fn from(builder: CovenantBuilder) -> Self {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+    use tari_common_types::types::PublicKey;
+    use tari_script::script;
+
+    use super::*;
+    use crate::{
+        covenant,
+        covenants::{
+            test::{create_input, create_outputs},
+            CovenantError,
+        },
+        test_helpers::new_public_key,
+        transactions::key_manager::create_memory_db_key_manager,
+    };
+
+    #[test]
+    fn it_emits_the_same_tokens_as_the_macro() {
+        let covenant = CovenantBuilder::absolute_height(42)
+            .or(CovenantBuilder::maturity_eq(8).and(CovenantBuilder::identity().not()))
+            .build();
+        assert_eq!(
+            covenant,
+            covenant!(or(
+                absolute_height(@uint(42),),
+                and(field_eq(@field::features_maturity, @uint(8),), not(identity()))
+            ))
+        );
+
+        let covenant = CovenantBuilder::output_type_restriction(OutputType::Burn).build();
+        assert_eq!(
+            covenant,
+            covenant!(field_eq(@field::features_output_type, @output_type(Burn)))
+        );
+    }
+
+    #[tokio::test]
+    async fn it_keeps_the_value_in_the_vault_until_the_unlock_height() {
+        let key_manager = create_memory_db_key_manager();
+        let covenant = CovenantBuilder::time_locked_vault(100).build();
+        let input = create_input(&key_manager).await;
+        let mut outputs = create_outputs(2, Default::default(), &key_manager).await;
+        for output in &mut outputs {
+            output.script = script!(Nop Nop);
+        }
+
+        let err = covenant.execute(99, &input, &outputs[..1]).unwrap_err();
+        assert!(matches!(err, CovenantError::NoMatchingOutputs));
+        outputs[0].script = input.script().unwrap().clone();
+        outputs[0].covenant = input.covenant().unwrap().clone();
+        assert_eq!(covenant.execute(99, &input, &outputs).unwrap(), 1);
+        assert_eq!(covenant.execute(100, &input, &outputs).unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_pins_the_value_to_an_address() {
+        let key_manager = create_memory_db_key_manager();
+        let address = TariAddress::new(new_public_key(), Network::LocalNet);
+        let covenant = CovenantBuilder::pinned_to_address(&address).build();
+        let input = create_input(&key_manager).await;
+        let mut outputs = create_outputs(3, Default::default(), &key_manager).await;
+        assert!(covenant.execute(0, &input, &outputs).is_err());
+
+        outputs[1].script = one_sided_payment_script(address.public_key());
+        assert_eq!(covenant.execute(0, &input, &outputs).unwrap(), 1);
+        outputs[1].script = one_sided_payment_script(&PublicKey::default());
+        assert!(covenant.execute(0, &input, &outputs).is_err());
+    }
+}
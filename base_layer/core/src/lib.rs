@@ -50,7 +50,9 @@ pub mod mempool;
 pub mod transactions;
 
 mod common;
-pub use common::{borsh, one_sided, ConfidentialOutputHasher};
+pub use common::{balance_proof, borsh, one_sided, payment_reference, ConfidentialOutputHasher};
+#[cfg(feature = "base_node")]
+pub use common::{burn_proof, output_inclusion_proof};
 
 #[cfg(feature = "base_node")]
 mod domain_hashing {
@@ -23,7 +23,14 @@
 use serde::{Deserialize, Serialize};
 use tari_common::SubConfigPath;
 
-use crate::mempool::{reorg_pool::ReorgPoolConfig, unconfirmed_pool::UnconfirmedPoolConfig};
+use crate::mempool::{
+    orphan_pool::OrphanPoolConfig,
+    priority::PriorityLanesConfig,
+    reorg_pool::ReorgPoolConfig,
+    unconfirmed_pool::UnconfirmedPoolConfig,
+    FeeEstimatorConfig,
+    MempoolPersistenceConfig,
+};
 
 /// Configuration for the Mempool.
 #[derive(Clone, Deserialize, Serialize, Default, Debug)]
@@ -33,6 +40,11 @@ pub struct MempoolConfig {
     pub unconfirmed_pool: UnconfirmedPoolConfig,
     pub reorg_pool: ReorgPoolConfig,
     pub service: MempoolServiceConfig,
+    pub fee_estimator: FeeEstimatorConfig,
+    pub replace_by_fee: ReplaceByFeeConfig,
+    pub persistence: MempoolPersistenceConfig,
+    pub priority_lanes: PriorityLanesConfig,
+    pub orphan_pool: OrphanPoolConfig,
 }
 
 impl SubConfigPath for MempoolConfig {
@@ -52,6 +64,9 @@ pub struct MempoolServiceConfig {
     pub initial_sync_max_transactions: usize,
     /// The maximum number of blocks added via sync or re-org to triggering a sync
     pub block_sync_trigger: usize,
+    /// The maximum number of short transaction IDs sent in a single inventory message during a mempool sync.
+    /// Default: 1_000
+    pub sync_page_size: usize,
 }
 
 impl Default for MempoolServiceConfig {
@@ -60,6 +75,31 @@ impl Default for MempoolServiceConfig {
             initial_sync_num_peers: 2,
             initial_sync_max_transactions: 10_000,
             block_sync_trigger: 5,
+            sync_page_size: 1_000,
+        }
+    }
+}
+
+/// Configuration for replacing unconfirmed transactions in the mempool with conflicting transactions that pay a higher
+/// fee. This is a local relay policy: it only changes which of a set of conflicting transactions this node keeps and
+/// propagates, and never affects the validity of a block.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ReplaceByFeeConfig {
+    /// If true, a transaction that spends an input of one or more unconfirmed transactions will replace them (and any
+    /// unconfirmed transactions that depend on them) if it pays a sufficiently higher fee. If false, conflicting
+    /// transactions are kept side by side and the first one to be mined wins. Default: false
+    pub enabled: bool,
+    /// The minimum percentage by which both the total fee and the fee per gram of a replacement transaction must
+    /// exceed those of the transactions it replaces. Default: 10
+    pub min_fee_bump_percentage: u64,
+}
+
+impl Default for ReplaceByFeeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_fee_bump_percentage: 10,
         }
     }
 }
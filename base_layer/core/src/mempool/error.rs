@@ -26,7 +26,7 @@ use tokio::task::JoinError;
 
 use crate::{
     common::{BanPeriod, BanReason},
-    mempool::unconfirmed_pool::UnconfirmedPoolError,
+    mempool::{unconfirmed_pool::UnconfirmedPoolError, MempoolPersistenceError},
     transactions::transaction_components::TransactionError,
 };
 
@@ -48,6 +48,8 @@ pub enum MempoolError {
     InternalError(String),
     #[error("Mempool indexes out of sync: transaction exists in txs_by_signature but not in tx_by_key")]
     IndexOutOfSync,
+    #[error("Mempool persistence error: {0}")]
+    PersistenceError(#[from] MempoolPersistenceError),
 }
 impl MempoolError {
     pub fn get_ban_reason(&self) -> Option<BanReason> {
@@ -64,7 +66,8 @@ impl MempoolError {
             _err @ MempoolError::RwLockPoisonError |
             _err @ MempoolError::BlockingTaskError(_) |
             _err @ MempoolError::InternalError(_) |
-            _err @ MempoolError::IndexOutOfSync => None,
+            _err @ MempoolError::IndexOutOfSync |
+            _err @ MempoolError::PersistenceError(_) => None,
         }
     }
 }
@@ -0,0 +1,303 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::transactions::tari_amount::MicroMinotari;
+
+/// The confidence (in per mille) that a transaction paying the estimated fee per gram would have been mined within the
+/// target number of blocks, going by the recently mined blocks
+const TARGET_CONFIDENCE_PER_MILLE: u64 = 950;
+/// Estimates for targets larger than this are the same as for this target
+const MAX_TARGET_BLOCKS: usize = 100;
+
+/// Configuration for the [FeeEstimator]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct FeeEstimatorConfig {
+    /// The number of recently mined blocks that fee estimates are based on
+    pub block_window: usize,
+    /// A block is considered full if its weight is at least this percentage of the maximum block weight. Only
+    /// transactions mined in full blocks had to compete on fees.
+    pub full_block_percentage: u64,
+    /// The lowest fee per gram that is ever estimated
+    pub min_fee_per_gram: u64,
+}
+
+impl Default for FeeEstimatorConfig {
+    fn default() -> Self {
+        Self {
+            block_window: 30,
+            full_block_percentage: 90,
+            min_fee_per_gram: 5,
+        }
+    }
+}
+
+/// Estimates the fee per gram a transaction needs to pay to be mined within a target number of blocks.
+///
+/// The estimate is the larger of two values:
+/// - the fee per gram needed to outbid enough of the pending transactions that the transaction fits into the target
+///   number of blocks, taken from a histogram of the weight of the pending transactions by fee per gram, and
+/// - the fee per gram that would have been mined within the target number of blocks most of the time, taken from a
+///   rolling histogram of the lowest fee per gram that was mined in each recent full block.
+///
+/// Fees per gram are grouped into buckets that are roughly 25% apart, so estimates are rounded up to the top of a
+/// bucket.
+#[derive(Debug, Clone)]
+pub struct FeeEstimator {
+    config: FeeEstimatorConfig,
+    /// The height and the lowest mined fee per gram of each recently mined block, oldest first. Blocks that were not
+    /// full have a fee per gram of 0.
+    recent_blocks: VecDeque<(u64, u64)>,
+    /// The number of recent blocks per fee per gram bucket
+    mined_histogram: BTreeMap<u64, u64>,
+}
+
+impl FeeEstimator {
+    pub fn new(config: FeeEstimatorConfig) -> Self {
+        Self {
+            config,
+            recent_blocks: VecDeque::with_capacity(config.block_window),
+            mined_histogram: BTreeMap::new(),
+        }
+    }
+
+    /// Record a mined block. `mined_fees_per_gram` are the fees per gram of the transactions in the block that were
+    /// known to the mempool. A full block in which none of the transactions were known is not recorded.
+    pub fn record_block<I>(&mut self, height: u64, block_weight: u64, max_block_weight: u64, mined_fees_per_gram: I)
+    where I: IntoIterator<Item = u64> {
+        let is_full = u128::from(block_weight) * 100 >=
+            u128::from(max_block_weight) * u128::from(self.config.full_block_percentage);
+        let fee_per_gram = if is_full {
+            match mined_fees_per_gram.into_iter().min() {
+                Some(fee_per_gram) => fee_per_gram,
+                None => return,
+            }
+        } else {
+            0
+        };
+
+        self.remove_blocks_from_height(height);
+        if self.config.block_window == 0 {
+            return;
+        }
+        while self.recent_blocks.len() >= self.config.block_window {
+            if let Some((_, evicted)) = self.recent_blocks.pop_front() {
+                self.remove_from_histogram(evicted);
+            }
+        }
+        self.recent_blocks.push_back((height, fee_per_gram));
+        *self.mined_histogram.entry(bucket_floor(fee_per_gram)).or_default() += 1;
+    }
+
+    /// Forget the blocks at or above the given height, e.g. because they were reorged out
+    pub fn remove_blocks_from_height(&mut self, height: u64) {
+        while let Some((block_height, fee_per_gram)) = self.recent_blocks.back().copied() {
+            if block_height < height {
+                break;
+            }
+            self.recent_blocks.pop_back();
+            self.remove_from_histogram(fee_per_gram);
+        }
+    }
+
+    /// The number of recent blocks that estimates are based on
+    pub fn num_recent_blocks(&self) -> usize {
+        self.recent_blocks.len()
+    }
+
+    /// Estimate the fee per gram needed to be mined within `target_blocks` blocks, given the fee per gram and weight
+    /// of each pending transaction and the maximum weight of the transactions in a block.
+    pub fn estimate_fee_per_gram<I>(&self, target_blocks: usize, pending: I, max_block_weight: u64) -> MicroMinotari
+    where I: IntoIterator<Item = (u64, u64)> {
+        let target_blocks = target_blocks.clamp(1, MAX_TARGET_BLOCKS);
+        let pending = pending_fee_per_gram(target_blocks, pending, max_block_weight);
+        let mined = self.mined_fee_per_gram(target_blocks);
+        pending.max(mined).max(self.config.min_fee_per_gram).into()
+    }
+
+    /// The lowest fee per gram that would have been mined within `target_blocks` blocks with the target confidence.
+    /// If a fee clears a fraction `q` of blocks, it is mined within `n` blocks with a probability of `1 - (1 - q)^n`.
+    fn mined_fee_per_gram(&self, target_blocks: usize) -> u64 {
+        let num_blocks = self.recent_blocks.len() as u64;
+        if num_blocks == 0 {
+            return 0;
+        }
+        let required_per_mille = (0..=1000u64)
+            .find(|cleared| {
+                let mut missed = 1000u64;
+                for _ in 0..target_blocks {
+                    missed = missed * (1000 - cleared) / 1000;
+                }
+                1000 - missed >= TARGET_CONFIDENCE_PER_MILLE
+            })
+            .unwrap_or(1000);
+        let required_blocks = (required_per_mille * num_blocks).div_ceil(1000);
+
+        let mut cleared_blocks = 0u64;
+        for (floor, count) in &self.mined_histogram {
+            cleared_blocks += count;
+            if cleared_blocks >= required_blocks {
+                return bucket_ceiling(*floor);
+            }
+        }
+        self.mined_histogram
+            .keys()
+            .next_back()
+            .map_or(0, |floor| bucket_ceiling(*floor))
+    }
+
+    fn remove_from_histogram(&mut self, fee_per_gram: u64) {
+        let floor = bucket_floor(fee_per_gram);
+        if let Some(count) = self.mined_histogram.get_mut(&floor) {
+            *count -= 1;
+            if *count == 0 {
+                self.mined_histogram.remove(&floor);
+            }
+        }
+    }
+}
+
+/// The fee per gram needed to outbid enough pending transactions to fit into `target_blocks` blocks, or 0 if all the
+/// pending transactions fit.
+fn pending_fee_per_gram<I>(target_blocks: usize, pending: I, max_block_weight: u64) -> u64
+where I: IntoIterator<Item = (u64, u64)> {
+    let mut histogram = BTreeMap::<u64, u64>::new();
+    for (fee_per_gram, weight) in pending {
+        *histogram.entry(bucket_floor(fee_per_gram)).or_default() += weight;
+    }
+    let capacity = max_block_weight.saturating_mul(target_blocks as u64);
+    let mut total_weight = 0u64;
+    for (floor, weight) in histogram.iter().rev() {
+        total_weight = total_weight.saturating_add(*weight);
+        if total_weight >= capacity {
+            return bucket_ceiling(*floor).saturating_add(1);
+        }
+    }
+    0
+}
+
+/// The lowest fee per gram in the bucket of the given fee per gram. Fees below 16 each have their own bucket, larger
+/// fees keep their 3 most significant bits, giving 4 buckets per power of two.
+fn bucket_floor(fee_per_gram: u64) -> u64 {
+    if fee_per_gram < 16 {
+        return fee_per_gram;
+    }
+    let shift = 61 - fee_per_gram.leading_zeros();
+    (fee_per_gram >> shift) << shift
+}
+
+/// The highest fee per gram in the bucket with the given floor
+fn bucket_ceiling(floor: u64) -> u64 {
+    if floor < 16 {
+        return floor;
+    }
+    let shift = 61 - floor.leading_zeros();
+    floor.saturating_add((1 << shift) - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAX_BLOCK_WEIGHT: u64 = 1000;
+
+    #[test]
+    fn it_groups_fees_into_buckets() {
+        assert_eq!(bucket_floor(7), 7);
+        assert_eq!(bucket_ceiling(7), 7);
+        assert_eq!(bucket_floor(16), 16);
+        assert_eq!(bucket_ceiling(16), 19);
+        assert_eq!(bucket_floor(19), 16);
+        assert_eq!(bucket_floor(20), 20);
+        assert_eq!(bucket_floor(1000), 896);
+        assert_eq!(bucket_ceiling(896), 1023);
+        assert_eq!(bucket_ceiling(bucket_floor(u64::MAX)), u64::MAX);
+    }
+
+    #[test]
+    fn it_estimates_the_minimum_without_data() {
+        let estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        assert_eq!(
+            estimator.estimate_fee_per_gram(1, vec![], MAX_BLOCK_WEIGHT),
+            MicroMinotari::from(5)
+        );
+        // Pending transactions that fit into the next block do not raise the estimate
+        assert_eq!(
+            estimator.estimate_fee_per_gram(1, vec![(100, 400), (50, 400)], MAX_BLOCK_WEIGHT),
+            MicroMinotari::from(5)
+        );
+    }
+
+    #[test]
+    fn it_outbids_pending_transactions() {
+        let estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        let pending = vec![(100, 600), (50, 600), (30, 600), (10, 600)];
+        // 100 and 50 fill the next block
+        assert_eq!(
+            estimator.estimate_fee_per_gram(1, pending.clone(), MAX_BLOCK_WEIGHT),
+            56.into()
+        );
+        // 100, 50, 30 and 10 fill less than 3 blocks
+        assert_eq!(estimator.estimate_fee_per_gram(3, pending, MAX_BLOCK_WEIGHT), 5.into());
+    }
+
+    #[test]
+    fn it_uses_recently_mined_blocks() {
+        let mut estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        for height in 1..=10 {
+            let lowest_mined = if height % 2 == 0 { 100 } else { 10 };
+            estimator.record_block(height, 950, MAX_BLOCK_WEIGHT, vec![lowest_mined, 200]);
+        }
+        // Only paying 100 would have been mined in the next block most of the time
+        assert_eq!(estimator.estimate_fee_per_gram(1, vec![], MAX_BLOCK_WEIGHT), 111.into());
+        // Paying 10 is mined within 5 blocks most of the time
+        assert_eq!(estimator.estimate_fee_per_gram(5, vec![], MAX_BLOCK_WEIGHT), 10.into());
+
+        // Blocks that are not full could be entered with any fee
+        for height in 11..=40 {
+            estimator.record_block(height, 100, MAX_BLOCK_WEIGHT, vec![200]);
+        }
+        assert_eq!(estimator.num_recent_blocks(), 30);
+        assert_eq!(estimator.estimate_fee_per_gram(1, vec![], MAX_BLOCK_WEIGHT), 5.into());
+    }
+
+    #[test]
+    fn it_forgets_reorged_blocks() {
+        let mut estimator = FeeEstimator::new(FeeEstimatorConfig::default());
+        estimator.record_block(1, 100, MAX_BLOCK_WEIGHT, vec![]);
+        estimator.record_block(2, 1000, MAX_BLOCK_WEIGHT, vec![100]);
+        estimator.record_block(3, 1000, MAX_BLOCK_WEIGHT, vec![100]);
+        assert_eq!(estimator.estimate_fee_per_gram(1, vec![], MAX_BLOCK_WEIGHT), 111.into());
+
+        estimator.remove_blocks_from_height(2);
+        assert_eq!(estimator.num_recent_blocks(), 1);
+        assert_eq!(estimator.estimate_fee_per_gram(1, vec![], MAX_BLOCK_WEIGHT), 5.into());
+        // A block at an existing height replaces it
+        estimator.record_block(1, 1000, MAX_BLOCK_WEIGHT, vec![100]);
+        assert_eq!(estimator.num_recent_blocks(), 1);
+        assert_eq!(estimator.estimate_fee_per_gram(1, vec![], MAX_BLOCK_WEIGHT), 111.into());
+    }
+}
@@ -20,29 +20,40 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::{Arc, RwLock};
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
 
 use log::debug;
 use tari_common_types::types::{PrivateKey, Signature};
-use tokio::task;
+use tokio::{sync::broadcast, task};
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortIdKey},
     consensus::ConsensusManager,
     mempool::{
         error::MempoolError,
         mempool_storage::MempoolStorage,
+        persistence::{load_snapshot, save_snapshot},
         FeePerGramStat,
         MempoolConfig,
+        MempoolEvent,
+        MempoolEventReceiver,
+        MempoolEventSender,
         StateResponse,
         StatsResponse,
+        TransactionDependencyGraph,
         TxStorageResponse,
     },
-    transactions::transaction_components::Transaction,
+    transactions::{tari_amount::MicroMinotari, transaction_components::Transaction},
     validation::TransactionValidator,
 };
 
 pub const LOG_TARGET: &str = "c::mp::mempool";
+/// The number of events buffered for each event subscriber. Every transaction added to the unconfirmed pool publishes
+/// an event, so this allows for bursts of incoming transactions before slow subscribers start lagging.
+const EVENT_CHANNEL_SIZE: usize = 1000;
 
 /// The Mempool consists of an Unconfirmed Transaction Pool, Pending Pool, Orphan Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions that have not yet been included in a block, and
@@ -50,44 +61,81 @@ pub const LOG_TARGET: &str = "c::mp::mempool";
 #[derive(Clone)]
 pub struct Mempool {
     pool_storage: Arc<RwLock<MempoolStorage>>,
+    event_publisher: MempoolEventSender,
 }
 
 impl Mempool {
     /// Create a new Mempool with an UnconfirmedPool and ReOrgPool.
     pub fn new(config: MempoolConfig, rules: ConsensusManager, validator: Box<dyn TransactionValidator>) -> Self {
+        let (event_publisher, _) = broadcast::channel(EVENT_CHANNEL_SIZE);
         Self {
             pool_storage: Arc::new(RwLock::new(MempoolStorage::new(config, rules, validator))),
+            event_publisher,
         }
     }
 
-    /// Insert an unconfirmed transaction into the Mempool.
+    /// Subscribe to events published by the mempool, such as transactions expiring from the unconfirmed pool.
+    pub fn subscribe_events(&self) -> MempoolEventReceiver {
+        self.event_publisher.subscribe()
+    }
+
+    /// Insert an unconfirmed transaction into the Mempool. A [MempoolEvent::TransactionAdded] event is published if the
+    /// transaction was added to the unconfirmed pool.
     pub async fn insert(&self, tx: Arc<Transaction>) -> Result<TxStorageResponse, MempoolError> {
-        self.with_write_access(|storage| {
-            storage
-                .insert(tx)
-                .map_err(|e| MempoolError::InternalError(e.to_string()))
-        })
-        .await
+        let added = tx.clone();
+        let response = self
+            .with_write_access(|storage| {
+                storage
+                    .insert(tx)
+                    .map_err(|e| MempoolError::InternalError(e.to_string()))
+            })
+            .await?;
+        if response == TxStorageResponse::UnconfirmedPool {
+            self.publish_event(MempoolEvent::TransactionAdded(added));
+        }
+        Ok(response)
     }
 
     /// Inserts all transactions into the mempool.
     pub async fn insert_all(&self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
-        self.with_write_access(|storage| {
-            for tx in transactions {
-                storage
-                    .insert(tx)
-                    .map_err(|e| MempoolError::InternalError(e.to_string()))?;
-            }
+        let added = self
+            .with_write_access(|storage| {
+                let mut added = Vec::new();
+                for tx in transactions {
+                    let response = storage
+                        .insert(tx.clone())
+                        .map_err(|e| MempoolError::InternalError(e.to_string()))?;
+                    if response == TxStorageResponse::UnconfirmedPool {
+                        added.push(tx);
+                    }
+                }
 
-            Ok(())
-        })
-        .await
+                Ok(added)
+            })
+            .await?;
+        for tx in added {
+            self.publish_event(MempoolEvent::TransactionAdded(tx));
+        }
+        Ok(())
     }
 
-    /// Update the Mempool based on the received published block.
+    /// Update the Mempool based on the received published block. Transactions that have been in the unconfirmed pool
+    /// for longer than the configured expiry are removed and published as a [MempoolEvent::TransactionsExpired] event.
     pub async fn process_published_block(&self, published_block: Arc<Block>) -> Result<(), MempoolError> {
-        self.with_write_access(move |storage| storage.process_published_block(&published_block))
-            .await
+        let (removed, expired) = self
+            .with_write_access(move |storage| {
+                let removed = storage.process_published_block(&published_block)?;
+                let expired = storage.remove_expired_transactions()?;
+                Ok((removed, expired))
+            })
+            .await?;
+        if !removed.is_empty() {
+            self.publish_event(MempoolEvent::TransactionsRemoved(removed));
+        }
+        if !expired.is_empty() {
+            self.publish_event(MempoolEvent::TransactionsExpired(expired));
+        }
+        Ok(())
     }
 
     /// Update the Mempool by clearing transactions for a block that failed to validate.
@@ -103,8 +151,13 @@ impl Mempool {
         removed_blocks: Vec<Arc<Block>>,
         new_blocks: Vec<Arc<Block>>,
     ) -> Result<(), MempoolError> {
-        self.with_write_access(move |storage| storage.process_reorg(&removed_blocks, &new_blocks))
-            .await
+        let reorged = self
+            .with_write_access(move |storage| storage.process_reorg(&removed_blocks, &new_blocks))
+            .await?;
+        if !reorged.is_empty() {
+            self.publish_event(MempoolEvent::TransactionsReorged(reorged));
+        }
+        Ok(())
     }
 
     /// After a sync event, we can move all orphan transactions to the unconfirmed pool after validation
@@ -157,6 +210,17 @@ impl Mempool {
             .await
     }
 
+    /// Retrieve the transactions for the short IDs of a compact block, returning the short IDs that could not be
+    /// resolved.
+    pub async fn retrieve_by_short_ids(
+        &self,
+        key: ShortIdKey,
+        short_ids: Vec<u64>,
+    ) -> Result<(Vec<Arc<Transaction>>, Vec<u64>), MempoolError> {
+        self.with_read_access(move |storage| storage.retrieve_by_short_ids(&key, &short_ids))
+            .await
+    }
+
     /// Check if the specified excess signature is found in the Mempool.
     pub async fn has_tx_with_excess_sig(&self, excess_sig: Signature) -> Result<TxStorageResponse, MempoolError> {
         self.with_read_access(move |storage| Ok(storage.has_tx_with_excess_sig(&excess_sig)))
@@ -168,6 +232,16 @@ impl Mempool {
         self.with_read_access(move |storage| storage.has_transaction(&tx)).await
     }
 
+    /// Returns the unconfirmed ancestors and descendants of the unconfirmed transaction with the given excess
+    /// signature, or None if the transaction is not in the unconfirmed pool.
+    pub async fn get_dependency_graph(
+        &self,
+        excess_sig: Signature,
+    ) -> Result<Option<TransactionDependencyGraph>, MempoolError> {
+        self.with_read_access(move |storage| storage.get_dependency_graph(&excess_sig))
+            .await
+    }
+
     /// Gathers and returns the stats of the Mempool.
     pub async fn stats(&self) -> Result<StatsResponse, MempoolError> {
         self.with_read_access(|storage| storage.stats().map_err(|e| MempoolError::InternalError(e.to_string())))
@@ -188,6 +262,43 @@ impl Mempool {
             .await
     }
 
+    /// Estimates the fee per gram needed for a transaction to be mined within `target_blocks` blocks.
+    pub async fn estimate_fee_per_gram(
+        &self,
+        target_blocks: usize,
+        tip_height: u64,
+    ) -> Result<MicroMinotari, MempoolError> {
+        self.with_read_access(move |storage| storage.estimate_fee_per_gram(target_blocks, tip_height))
+            .await
+    }
+
+    /// Save the unconfirmed pool to the given snapshot file, returning the number of transactions saved.
+    pub async fn save_snapshot(&self, path: PathBuf) -> Result<usize, MempoolError> {
+        self.with_read_access(move |storage| {
+            let transactions = storage.snapshot();
+            save_snapshot(&path, &transactions)?;
+            Ok(transactions.len())
+        })
+        .await
+    }
+
+    /// Revalidate the transactions in the given snapshot file and insert the valid transactions into the mempool,
+    /// returning the number of transactions that were stored.
+    pub async fn load_snapshot(&self, path: PathBuf) -> Result<usize, MempoolError> {
+        let transactions = task::spawn_blocking(move || load_snapshot(&path)).await??;
+        self.with_write_access(move |storage| {
+            let mut num_stored = 0;
+            for tx in transactions {
+                if storage.insert(tx)?.is_stored() {
+                    num_stored += 1;
+                }
+            }
+            debug!(target: LOG_TARGET, "{} transaction(s) from the mempool snapshot were stored", num_stored);
+            Ok(num_stored)
+        })
+        .await
+    }
+
     async fn with_read_access<F, T>(&self, callback: F) -> Result<T, MempoolError>
     where
         F: FnOnce(&MempoolStorage) -> Result<T, MempoolError> + Send + 'static,
@@ -207,10 +318,20 @@ impl Mempool {
         T: Send + 'static,
     {
         let storage = self.pool_storage.clone();
-        task::spawn_blocking(move || {
+        let (result, accepted_orphans) = task::spawn_blocking(move || {
             let mut lock = storage.write().map_err(|_| MempoolError::RwLockPoisonError)?;
-            callback(&mut lock)
+            let result = callback(&mut lock);
+            Ok::<_, MempoolError>((result, lock.take_accepted_orphans()))
         })
-        .await?
+        .await??;
+        if !accepted_orphans.is_empty() {
+            self.publish_event(MempoolEvent::OrphansAccepted(accepted_orphans));
+        }
+        result
+    }
+
+    fn publish_event(&self, event: MempoolEvent) {
+        // Sending only fails if there are no subscribers
+        let _size = self.event_publisher.send(Arc::new(event));
     }
 }
@@ -20,26 +20,35 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
 use log::*;
-use tari_common_types::types::{PrivateKey, Signature};
+use tari_common_types::types::{HashOutput, PrivateKey, Signature};
 use tari_utilities::hex::Hex;
 
 use crate::{
-    blocks::Block,
+    blocks::{Block, ShortIdKey},
     consensus::ConsensusManager,
     mempool::{
         error::MempoolError,
+        orphan_pool::OrphanPool,
         reorg_pool::ReorgPool,
         unconfirmed_pool::{RetrieveResults, TransactionKey, UnconfirmedPool, UnconfirmedPoolError},
+        FeeEstimator,
         FeePerGramStat,
         MempoolConfig,
+        ReplaceByFeeConfig,
         StateResponse,
         StatsResponse,
+        TransactionDependencyGraph,
         TxStorageResponse,
     },
     transactions::{
+        tari_amount::MicroMinotari,
         transaction_components::{Transaction, TransactionError},
         weight::TransactionWeight,
     },
@@ -48,12 +57,23 @@ use crate::{
 
 pub const LOG_TARGET: &str = "c::mp::mempool_storage";
 
+/// The number of blocks for which the excess signatures of expired transactions are remembered, so that queries for an
+/// expired transaction report it as expired rather than unknown
+const EXPIRED_TX_RETENTION_BLOCKS: u64 = 720;
+
 /// The Mempool consists of an Unconfirmed Transaction Pool and Reorg Pool and is responsible
 /// for managing and maintaining all unconfirmed transactions have not yet been included in a block, and transactions
 /// that have recently been included in a block.
 pub struct MempoolStorage {
     pub(crate) unconfirmed_pool: UnconfirmedPool,
     reorg_pool: ReorgPool,
+    orphan_pool: OrphanPool,
+    // Orphan transactions that were added to the unconfirmed pool since they were last taken, to be propagated
+    accepted_orphans: Vec<Arc<Transaction>>,
+    fee_estimator: FeeEstimator,
+    replace_by_fee: ReplaceByFeeConfig,
+    // Excess signatures of expired transactions, mapped to the height at which they expired
+    expired_txs: HashMap<PrivateKey, u64>,
     validator: Box<dyn TransactionValidator>,
     rules: ConsensusManager,
     last_seen_height: u64,
@@ -63,16 +83,78 @@ impl MempoolStorage {
     /// Create a new Mempool with an UnconfirmedPool and ReOrgPool.
     pub fn new(config: MempoolConfig, rules: ConsensusManager, validator: Box<dyn TransactionValidator>) -> Self {
         Self {
-            unconfirmed_pool: UnconfirmedPool::new(config.unconfirmed_pool),
+            unconfirmed_pool: UnconfirmedPool::new(config.unconfirmed_pool).with_priority_lanes(config.priority_lanes),
             reorg_pool: ReorgPool::new(config.reorg_pool),
+            orphan_pool: OrphanPool::new(config.orphan_pool),
+            accepted_orphans: Vec::new(),
+            fee_estimator: FeeEstimator::new(config.fee_estimator),
+            replace_by_fee: config.replace_by_fee,
+            expired_txs: HashMap::new(),
             validator,
             rules,
             last_seen_height: 0,
         }
     }
 
-    /// Insert an unconfirmed transaction into the Mempool.
+    /// Insert an unconfirmed transaction into the Mempool. If replace-by-fee is enabled, a transaction that spends the
+    /// inputs of unconfirmed transactions replaces them if it pays a high enough fee.
     pub fn insert(&mut self, tx: Arc<Transaction>) -> Result<TxStorageResponse, UnconfirmedPoolError> {
+        self.insert_and_retry_orphans(tx, self.replace_by_fee.enabled)
+    }
+
+    /// Inserts the transaction and, if it was added to the unconfirmed pool, retries the orphan transactions that spend
+    /// its outputs
+    fn insert_and_retry_orphans(
+        &mut self,
+        tx: Arc<Transaction>,
+        allow_replacement: bool,
+    ) -> Result<TxStorageResponse, UnconfirmedPoolError> {
+        let outputs = output_hashes(&tx);
+        let response = self.insert_transaction(tx, allow_replacement)?;
+        if response == TxStorageResponse::UnconfirmedPool {
+            self.retry_orphans(outputs)?;
+        }
+        Ok(response)
+    }
+
+    /// Validates the orphan transactions that spend any of the given outputs again, and then the orphans that spend the
+    /// outputs of any that are added to the unconfirmed pool
+    fn retry_orphans(&mut self, mut outputs: Vec<HashOutput>) -> Result<(), UnconfirmedPoolError> {
+        while !outputs.is_empty() {
+            let candidates = self.orphan_pool.take_spenders_of(&outputs);
+            outputs = Vec::new();
+            for tx in candidates {
+                let tx_outputs = output_hashes(&tx);
+                // Orphans that are still missing a parent are added back to the orphan pool
+                if self.insert_transaction(tx.clone(), self.replace_by_fee.enabled)? ==
+                    TxStorageResponse::UnconfirmedPool
+                {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Orphan transaction {} added to the unconfirmed pool",
+                        tx.first_kernel_excess_sig()
+                            .map(|sig| sig.get_signature().to_hex())
+                            .unwrap_or_else(|| "None?!".into())
+                    );
+                    outputs.extend(tx_outputs);
+                    self.accepted_orphans.push(tx);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the orphan transactions that were added to the unconfirmed pool since this was last called
+    pub fn take_accepted_orphans(&mut self) -> Vec<Arc<Transaction>> {
+        std::mem::take(&mut self.accepted_orphans)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn insert_transaction(
+        &mut self,
+        tx: Arc<Transaction>,
+        allow_replacement: bool,
+    ) -> Result<TxStorageResponse, UnconfirmedPoolError> {
         let tx_id = tx
             .body
             .kernels()
@@ -93,6 +175,27 @@ impl MempoolStorage {
             debug!(target: LOG_TARGET, "Tx: ({}) fee too low, rejecting",tx_id);
             return Ok(TxStorageResponse::NotStoredFeeTooLow);
         }
+        if tx
+            .first_kernel_excess_sig()
+            .map_or(false, |sig| self.orphan_pool.contains(sig))
+        {
+            debug!(target: LOG_TARGET, "Tx: ({}) is already in the orphan pool", tx_id);
+            return Ok(TxStorageResponse::NotStoredOrphan);
+        }
+        let replaced = if allow_replacement {
+            match self.find_replaced_transactions(&tx, tx_fee)? {
+                Some(replaced) => replaced,
+                None => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Tx: ({}) conflicts with unconfirmed transactions and does not pay enough to replace them", tx_id
+                    );
+                    return Ok(TxStorageResponse::NotStoredFeeTooLow);
+                },
+            }
+        } else {
+            Vec::new()
+        };
         match self.validator.validate(&tx) {
             Ok(()) => {
                 debug!(
@@ -104,6 +207,7 @@ impl MempoolStorage {
                 let timer = Instant::now();
                 let weight = self.get_transaction_weighting();
                 self.unconfirmed_pool.insert(tx, None, &weight)?;
+                self.remove_replaced_transactions(&tx_id, &replaced)?;
                 debug!(
                     target: LOG_TARGET,
                     "Transaction {} inserted in {:.2?}",
@@ -113,12 +217,22 @@ impl MempoolStorage {
                 Ok(TxStorageResponse::UnconfirmedPool)
             },
             Err(ValidationError::UnknownInputs(dependent_outputs)) => {
-                if self.unconfirmed_pool.contains_all_outputs(&dependent_outputs) {
+                if self.unconfirmed_pool.is_created_by_any(&dependent_outputs, &replaced) {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Tx: ({}) spends an output of a transaction it would replace, rejecting", tx_id
+                    );
+                    Ok(TxStorageResponse::NotStoredConsensus)
+                } else if self.unconfirmed_pool.contains_all_outputs(&dependent_outputs) {
                     let weight = self.get_transaction_weighting();
                     self.unconfirmed_pool.insert(tx, Some(dependent_outputs), &weight)?;
+                    self.remove_replaced_transactions(&tx_id, &replaced)?;
                     Ok(TxStorageResponse::UnconfirmedPool)
                 } else {
                     warn!(target: LOG_TARGET, "Validation failed due to unknown inputs");
+                    if self.orphan_pool.insert(tx, dependent_outputs) {
+                        debug!(target: LOG_TARGET, "Tx: ({}) added to the orphan pool", tx_id);
+                    }
                     Ok(TxStorageResponse::NotStoredOrphan)
                 }
             },
@@ -149,6 +263,70 @@ impl MempoolStorage {
         }
     }
 
+    /// Returns the keys of the unconfirmed transactions that the given transaction would replace: the transactions that
+    /// spend any of its inputs, and all the transactions that depend on them. Returns `None` if the transaction
+    /// conflicts with unconfirmed transactions but does not exceed both their total fee and their fee per gram by the
+    /// configured fee bump percentage. An evicted transaction that is later received again from a peer is therefore
+    /// rejected, so that conflicting transactions cannot repeatedly replace each other.
+    fn find_replaced_transactions(
+        &self,
+        tx: &Transaction,
+        tx_fee: MicroMinotari,
+    ) -> Result<Option<Vec<TransactionKey>>, UnconfirmedPoolError> {
+        if tx
+            .body
+            .kernels()
+            .iter()
+            .all(|k| self.unconfirmed_pool.has_tx_with_excess_sig(&k.excess_sig))
+        {
+            // The transaction is already in the pool
+            return Ok(Some(Vec::new()));
+        }
+        let conflicts = self.unconfirmed_pool.conflicting_transactions(tx);
+        if conflicts.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+        let replaced = self.unconfirmed_pool.with_descendants(&conflicts);
+        let (replaced_fee, replaced_weight) = self.unconfirmed_pool.total_fee_and_weight(&replaced)?;
+        let tx_weight = tx.calculate_weight(&self.get_transaction_weighting())?;
+
+        let bump = u128::from(100 + self.replace_by_fee.min_fee_bump_percentage);
+        let tx_fee = u128::from(tx_fee.as_u64());
+        let replaced_fee = u128::from(replaced_fee.as_u64());
+        let pays_higher_fee = tx_fee * 100 >= replaced_fee * bump;
+        // tx_fee / tx_weight >= (replaced_fee / replaced_weight) * bump / 100
+        let pays_higher_fee_per_gram =
+            tx_fee * u128::from(replaced_weight) * 100 >= replaced_fee * u128::from(tx_weight) * bump;
+        if pays_higher_fee && pays_higher_fee_per_gram {
+            Ok(Some(replaced))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn remove_replaced_transactions(
+        &mut self,
+        tx_id: &str,
+        replaced: &[TransactionKey],
+    ) -> Result<(), UnconfirmedPoolError> {
+        for key in replaced {
+            if let Some(removed) = self.unconfirmed_pool.remove_transaction(*key)? {
+                debug!(
+                    target: LOG_TARGET,
+                    "Transaction {} replaced by {}",
+                    removed
+                        .body
+                        .kernels()
+                        .first()
+                        .map(|k| k.excess_sig.get_signature().to_hex())
+                        .unwrap_or_else(|| "None?!".into()),
+                    tx_id
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn get_transaction_weighting(&self) -> TransactionWeight {
         *self
             .rules
@@ -173,16 +351,37 @@ impl MempoolStorage {
         Ok(())
     }
 
-    // Insert a set of new transactions into the UTxPool.
+    // Insert a set of new transactions into the UTxPool. These are transactions that were previously accepted (or
+    // mined) and are being revalidated, so they never replace other transactions.
     fn insert_txs(&mut self, txs: Vec<Arc<Transaction>>) -> Result<(), UnconfirmedPoolError> {
         for tx in txs {
-            self.insert(tx)?;
+            self.insert_and_retry_orphans(tx, false)?;
         }
         Ok(())
     }
 
-    /// Update the Mempool based on the received published block.
-    pub fn process_published_block(&mut self, published_block: &Block) -> Result<(), MempoolError> {
+    /// Record the fees per gram of the transactions in a mined block with the fee estimator. This must be called
+    /// before the transactions of the block are removed from the unconfirmed pool.
+    fn record_block_fees(&mut self, block: &Block) {
+        let constants = self.rules.consensus_constants(block.header.height);
+        let block_weight = match block.body.calculate_weight(constants.transaction_weight_params()) {
+            Ok(weight) => weight,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not calculate the weight of block #{}: {}", block.header.height, e);
+                return;
+            },
+        };
+        self.fee_estimator.record_block(
+            block.header.height,
+            block_weight,
+            constants.max_block_transaction_weight(),
+            self.unconfirmed_pool.fees_per_gram_in_block(block),
+        );
+    }
+
+    /// Update the Mempool based on the received published block, returning the transactions that were removed from the
+    /// unconfirmed pool because they were mined or double spent by the block.
+    pub fn process_published_block(&mut self, published_block: &Block) -> Result<Vec<Arc<Transaction>>, MempoolError> {
         debug!(
             target: LOG_TARGET,
             "Mempool processing new block: #{} ({}) {}",
@@ -190,6 +389,7 @@ impl MempoolStorage {
             published_block.header.hash().to_hex(),
             published_block.body.to_counts_string()
         );
+        self.record_block_fees(published_block);
         let timer = Instant::now();
         // Move published txs to ReOrgPool and discard double spends
         let removed_transactions = self
@@ -206,7 +406,7 @@ impl MempoolStorage {
         );
         let timer = Instant::now();
         self.reorg_pool
-            .insert_all(published_block.header.height, removed_transactions);
+            .insert_all(published_block.header.height, removed_transactions.clone());
         debug!(
             target: LOG_TARGET,
             "Transactions added to reorg pool in {:.2?} for block #{} ({}) {}",
@@ -215,6 +415,17 @@ impl MempoolStorage {
             published_block.header.hash().to_hex(),
             published_block.body.to_counts_string()
         );
+        self.orphan_pool.remove_published(published_block);
+        let expired_orphans = self.orphan_pool.remove_expired(Instant::now());
+        if expired_orphans > 0 {
+            debug!(
+                target: LOG_TARGET,
+                "{} orphan transaction(s) expired, {} remaining",
+                expired_orphans,
+                self.orphan_pool.len()
+            );
+        }
+        self.retry_orphans(block_output_hashes(published_block))?;
         let timer = Instant::now();
         self.unconfirmed_pool.compact();
         self.reorg_pool.compact();
@@ -225,7 +436,35 @@ impl MempoolStorage {
             Ok(stats) => debug!(target: LOG_TARGET, "{}", stats),
             Err(e) => warn!(target: LOG_TARGET, "error to obtain stats: {}", e),
         }
-        Ok(())
+        Ok(removed_transactions)
+    }
+
+    /// Remove the transactions that have been in the unconfirmed pool for longer than the configured expiry, together
+    /// with the transactions that depend on them, returning the removed transactions.
+    pub fn remove_expired_transactions(&mut self) -> Result<Vec<Arc<Transaction>>, MempoolError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let expired = self.unconfirmed_pool.remove_expired_transactions(now)?;
+        let height = self.last_seen_height;
+        for tx in &expired {
+            for kernel in tx.body.kernels() {
+                self.expired_txs
+                    .insert(kernel.excess_sig.get_signature().clone(), height);
+            }
+        }
+        self.expired_txs
+            .retain(|_, expired_at| expired_at.saturating_add(EXPIRED_TX_RETENTION_BLOCKS) >= height);
+        if !expired.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "{} transaction(s) expired from the unconfirmed pool at height {}",
+                expired.len(),
+                height
+            );
+        }
+        Ok(expired)
     }
 
     pub fn clear_transactions_for_failed_block(&mut self, failed_block: &Block) -> Result<(), MempoolError> {
@@ -248,13 +487,19 @@ impl MempoolStorage {
     }
 
     /// In the event of a ReOrg, resubmit all ReOrged transactions into the Mempool and process each newly introduced
-    /// block from the latest longest chain.
+    /// block from the latest longest chain. Returns the transactions of the removed blocks that were resubmitted.
     pub fn process_reorg(
         &mut self,
         removed_blocks: &[Arc<Block>],
         new_blocks: &[Arc<Block>],
-    ) -> Result<(), MempoolError> {
+    ) -> Result<Vec<Arc<Transaction>>, MempoolError> {
         debug!(target: LOG_TARGET, "Mempool processing reorg");
+        if let Some(height) = removed_blocks.iter().map(|block| block.header.height).min() {
+            self.fee_estimator.remove_blocks_from_height(height);
+        }
+        for block in new_blocks {
+            self.record_block_fees(block);
+        }
 
         // Clear out all transactions from the unconfirmed pool and re-submit them to the unconfirmed mempool for
         // validation. This is important as invalid transactions that have not been mined yet may remain in the mempool
@@ -264,11 +509,15 @@ impl MempoolStorage {
         self.insert_txs(removed_txs)
             .map_err(|e| MempoolError::InternalError(e.to_string()))?;
         // Remove re-orged transactions from reorg  pool and re-submit them to the unconfirmed mempool
-        let removed_txs = self
+        let reorged_txs = self
             .reorg_pool
             .remove_reorged_txs_and_discard_double_spends(removed_blocks, new_blocks);
-        self.insert_txs(removed_txs)
+        self.insert_txs(reorged_txs.clone())
             .map_err(|e| MempoolError::InternalError(e.to_string()))?;
+        for block in new_blocks {
+            self.orphan_pool.remove_published(block);
+            self.retry_orphans(block_output_hashes(block))?;
+        }
         if let Some(height) = new_blocks
             .last()
             .or_else(|| removed_blocks.first())
@@ -276,7 +525,7 @@ impl MempoolStorage {
         {
             self.last_seen_height = height;
         }
-        Ok(())
+        Ok(reorged_txs)
     }
 
     /// After a sync event, we need to try to add in all the transaction form the reorg pool.
@@ -323,12 +572,46 @@ impl MempoolStorage {
         }
     }
 
+    /// Retrieve the transactions for the short IDs of a compact block. Short IDs that are not found, or that match the
+    /// kernels of more than one transaction, are returned as missing.
+    pub fn retrieve_by_short_ids(
+        &self,
+        key: &ShortIdKey,
+        short_ids: &[u64],
+    ) -> Result<(Vec<Arc<Transaction>>, Vec<u64>), MempoolError> {
+        // None marks a short ID collision
+        let mut sigs_by_short_id = HashMap::<u64, Option<&PrivateKey>>::new();
+        for sig in self.unconfirmed_pool.excess_sigs().chain(self.reorg_pool.excess_sigs()) {
+            sigs_by_short_id
+                .entry(key.short_id(sig))
+                .and_modify(|entry| {
+                    if *entry != Some(sig) {
+                        *entry = None;
+                    }
+                })
+                .or_insert(Some(sig));
+        }
+
+        let mut excess_sigs = Vec::with_capacity(short_ids.len());
+        let mut missing = Vec::new();
+        for short_id in short_ids {
+            match sigs_by_short_id.get(short_id) {
+                Some(Some(sig)) => excess_sigs.push((*sig).clone()),
+                _ => missing.push(*short_id),
+            }
+        }
+        let (transactions, _) = self.retrieve_by_excess_sigs(&excess_sigs)?;
+        Ok((transactions, missing))
+    }
+
     /// Check if the specified excess signature is found in the Mempool.
     pub fn has_tx_with_excess_sig(&self, excess_sig: &Signature) -> TxStorageResponse {
         if self.unconfirmed_pool.has_tx_with_excess_sig(excess_sig) {
             TxStorageResponse::UnconfirmedPool
         } else if self.reorg_pool.has_tx_with_excess_sig(excess_sig) {
             TxStorageResponse::ReorgPool
+        } else if self.expired_txs.contains_key(excess_sig.get_signature()) {
+            TxStorageResponse::NotStoredExpired
         } else {
             TxStorageResponse::NotStored
         }
@@ -369,13 +652,26 @@ impl MempoolStorage {
     /// Gathers and returns the stats of the Mempool.
     pub fn stats(&self) -> Result<StatsResponse, TransactionError> {
         let weighting = self.get_transaction_weighting();
+        let package_stats = self.unconfirmed_pool.package_stats();
         Ok(StatsResponse {
             unconfirmed_txs: self.unconfirmed_pool.len() as u64,
             reorg_txs: self.reorg_pool.len() as u64,
             unconfirmed_weight: self.unconfirmed_pool.calculate_weight(&weighting)?,
+            dependent_txs: package_stats.dependent_txs,
+            max_package_size: package_stats.max_package_size,
+            min_package_fee_per_gram: package_stats.min_package_fee_per_gram,
         })
     }
 
+    /// Returns the unconfirmed ancestors and descendants of the unconfirmed transaction with the given excess
+    /// signature, or None if the transaction is not in the unconfirmed pool.
+    pub fn get_dependency_graph(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<TransactionDependencyGraph>, MempoolError> {
+        Ok(self.unconfirmed_pool.dependency_graph(excess_sig)?)
+    }
+
     /// Gathers and returns a breakdown of all the transaction in the Mempool.
     pub fn state(&self) -> StateResponse {
         let unconfirmed_pool = self.unconfirmed_pool.snapshot();
@@ -400,4 +696,26 @@ impl MempoolStorage {
         let stats = self.unconfirmed_pool.get_fee_per_gram_stats(count, target_weight)?;
         Ok(stats)
     }
+
+    /// Estimate the fee per gram needed for a transaction to be mined within `target_blocks` blocks
+    pub fn estimate_fee_per_gram(&self, target_blocks: usize, tip_height: u64) -> Result<MicroMinotari, MempoolError> {
+        let max_block_weight = self
+            .rules
+            .consensus_constants(tip_height)
+            .max_block_weight_excluding_coinbase()
+            .map_err(|e| MempoolError::InternalError(e.to_string()))?;
+        Ok(self.fee_estimator.estimate_fee_per_gram(
+            target_blocks,
+            self.unconfirmed_pool.fee_per_gram_and_weights(),
+            max_block_weight,
+        ))
+    }
+}
+
+fn output_hashes(tx: &Transaction) -> Vec<HashOutput> {
+    tx.body.outputs().iter().map(|output| output.hash()).collect()
+}
+
+fn block_output_hashes(block: &Block) -> Vec<HashOutput> {
+    block.body.outputs().iter().map(|output| output.hash()).collect()
 }
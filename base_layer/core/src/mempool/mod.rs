@@ -28,11 +28,17 @@ mod config;
 #[cfg(feature = "base_node")]
 mod error;
 #[cfg(feature = "base_node")]
+mod fee_estimator;
+#[cfg(feature = "base_node")]
 #[allow(clippy::module_inception)]
 mod mempool;
 #[cfg(feature = "base_node")]
 mod mempool_storage;
 #[cfg(feature = "base_node")]
+mod orphan_pool;
+#[cfg(feature = "base_node")]
+mod persistence;
+#[cfg(feature = "base_node")]
 mod priority;
 #[cfg(feature = "base_node")]
 mod reorg_pool;
@@ -53,10 +59,18 @@ mod unconfirmed_pool;
 #[cfg(feature = "base_node")]
 pub use error::MempoolError;
 #[cfg(feature = "base_node")]
+pub use fee_estimator::{FeeEstimator, FeeEstimatorConfig};
+#[cfg(feature = "base_node")]
 pub use mempool::Mempool;
+#[cfg(feature = "base_node")]
+pub use orphan_pool::OrphanPoolConfig;
+#[cfg(feature = "base_node")]
+pub use persistence::{load_snapshot, save_snapshot, MempoolPersistenceConfig, MempoolPersistenceError};
+#[cfg(feature = "base_node")]
+pub use priority::{PriorityLane, PriorityLanesConfig};
 
 #[cfg(feature = "base_node")]
-pub use self::config::{MempoolConfig, MempoolServiceConfig};
+pub use self::config::{MempoolConfig, MempoolServiceConfig, ReplaceByFeeConfig};
 
 #[cfg(any(feature = "base_node", feature = "mempool_proto"))]
 pub mod proto;
@@ -75,6 +89,7 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "base_node")]
 pub use sync_protocol::MempoolSyncInitializer;
 use tari_common_types::types::Signature;
+use tokio::sync::broadcast;
 
 use crate::{
     proto::base_node as base_node_proto,
@@ -86,6 +101,13 @@ pub struct StatsResponse {
     pub unconfirmed_txs: u64,
     pub reorg_txs: u64,
     pub unconfirmed_weight: u64,
+    /// The number of unconfirmed transactions that spend the outputs of other unconfirmed transactions
+    pub dependent_txs: u64,
+    /// The largest number of transactions in a package, i.e. a transaction and all of its unconfirmed ancestors
+    pub max_package_size: u64,
+    /// The lowest package fee per gram in the unconfirmed pool. Packages below this fee per gram are evicted first
+    /// when the pool is full.
+    pub min_package_fee_per_gram: u64,
 }
 
 impl Display for StatsResponse {
@@ -98,6 +120,26 @@ impl Display for StatsResponse {
     }
 }
 
+/// Events published by the [Mempool]
+#[derive(Clone, Debug)]
+pub enum MempoolEvent {
+    /// Transactions that were removed from the unconfirmed pool because they were not mined before the configured
+    /// expiry
+    TransactionsExpired(Vec<Arc<Transaction>>),
+    /// Orphan transactions that were added to the unconfirmed pool once the transactions or blocks creating the
+    /// outputs they spend arrived
+    OrphansAccepted(Vec<Arc<Transaction>>),
+    /// A transaction that was added to the unconfirmed pool
+    TransactionAdded(Arc<Transaction>),
+    /// Transactions that were removed from the unconfirmed pool because they were mined or double spent by a block
+    TransactionsRemoved(Vec<Arc<Transaction>>),
+    /// Transactions from blocks that were removed by a chain reorg, which were resubmitted to the mempool
+    TransactionsReorged(Vec<Arc<Transaction>>),
+}
+
+pub type MempoolEventSender = broadcast::Sender<Arc<MempoolEvent>>;
+pub type MempoolEventReceiver = broadcast::Receiver<Arc<MempoolEvent>>;
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StateResponse {
     pub unconfirmed_pool: Vec<Arc<Transaction>>,
@@ -115,6 +157,7 @@ pub enum TxStorageResponse {
     NotStored,
     NotStoredAlreadyMined,
     NotStoredFeeTooLow,
+    NotStoredExpired,
 }
 
 impl TxStorageResponse {
@@ -135,11 +178,61 @@ impl Display for TxStorageResponse {
             TxStorageResponse::NotStored => "Not stored",
             TxStorageResponse::NotStoredAlreadyMined => "Not stored tx already mined",
             TxStorageResponse::NotStoredFeeTooLow => "Not stored tx fee is below the minimum accepted by this mempool",
+            TxStorageResponse::NotStoredExpired => "Not stored tx expired from the mempool",
         };
         fmt.write_str(storage)
     }
 }
 
+/// How a transaction in a [TransactionDependencyGraph] relates to the transaction the graph was requested for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DependencyRelation {
+    /// The transaction the graph was requested for
+    Root,
+    /// An unconfirmed transaction that the root transaction (indirectly) spends the outputs of, which must be mined
+    /// first
+    Ancestor,
+    /// An unconfirmed transaction that (indirectly) spends the outputs of the root transaction
+    Descendant,
+}
+
+/// A transaction in a [TransactionDependencyGraph]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DependencyGraphNode {
+    pub excess_sig: Signature,
+    pub relation: DependencyRelation,
+    pub fee: MicroMinotari,
+    pub weight: u64,
+    pub fee_per_gram: u64,
+    /// The indices, in the graph nodes, of the transactions whose outputs this transaction spends
+    pub parents: Vec<usize>,
+}
+
+/// The unconfirmed ancestors and descendants of a transaction in the unconfirmed pool. The nodes are ordered so that
+/// every transaction comes after its parents, which is an order in which the transactions can be mined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionDependencyGraph {
+    pub nodes: Vec<DependencyGraphNode>,
+    /// The fee per gram of the root transaction together with all of its unconfirmed ancestors
+    pub package_fee_per_gram: u64,
+}
+
+impl TransactionDependencyGraph {
+    pub fn root(&self) -> Option<&DependencyGraphNode> {
+        self.nodes.iter().find(|n| n.relation == DependencyRelation::Root)
+    }
+
+    pub fn ancestors(&self) -> impl Iterator<Item = &DependencyGraphNode> + '_ {
+        self.nodes.iter().filter(|n| n.relation == DependencyRelation::Ancestor)
+    }
+
+    pub fn descendants(&self) -> impl Iterator<Item = &DependencyGraphNode> + '_ {
+        self.nodes
+            .iter()
+            .filter(|n| n.relation == DependencyRelation::Descendant)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FeePerGramStat {
     pub order: u64,
@@ -0,0 +1,256 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use tari_common_types::types::{HashOutput, PrivateKey, Signature};
+
+use crate::{blocks::Block, transactions::transaction_components::Transaction};
+
+/// Configuration for the [OrphanPool]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct OrphanPoolConfig {
+    /// The maximum number of orphan transactions that are kept. The oldest orphan is dropped to make space for a new
+    /// one. Zero disables the orphan pool.
+    pub storage_capacity: usize,
+    /// Orphan transactions whose parents have not arrived within this time are dropped
+    #[serde(with = "serializers::seconds")]
+    pub expiry: Duration,
+}
+
+impl Default for OrphanPoolConfig {
+    fn default() -> Self {
+        Self {
+            storage_capacity: 1_000,
+            expiry: Duration::from_secs(20 * 60),
+        }
+    }
+}
+
+struct OrphanTransaction {
+    transaction: Arc<Transaction>,
+    missing_outputs: Vec<HashOutput>,
+    inserted_at: Instant,
+    insert_order: u64,
+}
+
+/// Holds transactions that spend outputs that are not yet known to this node, usually because their parent
+/// transaction is still propagating. An orphan is handed back for validation when a transaction or block that creates
+/// one of the outputs it spends arrives.
+pub struct OrphanPool {
+    config: OrphanPoolConfig,
+    txs_by_signature: HashMap<PrivateKey, OrphanTransaction>,
+    txs_by_missing_output: HashMap<HashOutput, Vec<PrivateKey>>,
+    insert_order: BTreeMap<u64, PrivateKey>,
+    insert_counter: u64,
+}
+
+impl OrphanPool {
+    pub fn new(config: OrphanPoolConfig) -> Self {
+        Self {
+            config,
+            txs_by_signature: HashMap::new(),
+            txs_by_missing_output: HashMap::new(),
+            insert_order: BTreeMap::new(),
+            insert_counter: 0,
+        }
+    }
+
+    /// Add an orphan transaction that spends the given unknown outputs, dropping the oldest orphan if the pool is
+    /// full. Returns false if the transaction was not added.
+    pub fn insert(&mut self, transaction: Arc<Transaction>, missing_outputs: Vec<HashOutput>) -> bool {
+        if self.config.storage_capacity == 0 || missing_outputs.is_empty() {
+            return false;
+        }
+        let key = match transaction.first_kernel_excess_sig() {
+            Some(sig) => sig.get_signature().clone(),
+            None => return false,
+        };
+        if self.txs_by_signature.contains_key(&key) {
+            return true;
+        }
+        while self.txs_by_signature.len() >= self.config.storage_capacity {
+            match self.insert_order.values().next().cloned() {
+                Some(oldest) => {
+                    self.remove(&oldest);
+                },
+                None => break,
+            }
+        }
+
+        for output in &missing_outputs {
+            self.txs_by_missing_output
+                .entry(output.clone())
+                .or_default()
+                .push(key.clone());
+        }
+        self.insert_counter += 1;
+        self.insert_order.insert(self.insert_counter, key.clone());
+        self.txs_by_signature.insert(key, OrphanTransaction {
+            transaction,
+            missing_outputs,
+            inserted_at: Instant::now(),
+            insert_order: self.insert_counter,
+        });
+        true
+    }
+
+    /// Returns true if the transaction with the given excess signature is in the orphan pool
+    pub fn contains(&self, excess_sig: &Signature) -> bool {
+        self.txs_by_signature.contains_key(excess_sig.get_signature())
+    }
+
+    /// Removes and returns the orphans that spend any of the given outputs, oldest first
+    pub fn take_spenders_of(&mut self, outputs: &[HashOutput]) -> Vec<Arc<Transaction>> {
+        let mut keys = outputs
+            .iter()
+            .filter_map(|output| self.txs_by_missing_output.get(output))
+            .flatten()
+            .filter_map(|key| {
+                self.txs_by_signature
+                    .get(key)
+                    .map(|orphan| (orphan.insert_order, key.clone()))
+            })
+            .collect::<Vec<_>>();
+        keys.sort_unstable_by_key(|(order, _)| *order);
+        keys.dedup_by_key(|(order, _)| *order);
+        keys.into_iter().filter_map(|(_, key)| self.remove(&key)).collect()
+    }
+
+    /// Remove the orphans that are included in the given block
+    pub fn remove_published(&mut self, block: &Block) {
+        for kernel in block.body.kernels() {
+            self.remove(kernel.excess_sig.get_signature());
+        }
+    }
+
+    /// Remove the orphans that were added more than the configured expiry before `now`, returning the number of
+    /// orphans removed
+    pub fn remove_expired(&mut self, now: Instant) -> usize {
+        let expired = self
+            .txs_by_signature
+            .iter()
+            .filter(|(_, orphan)| now.saturating_duration_since(orphan.inserted_at) >= self.config.expiry)
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in &expired {
+            self.remove(key);
+        }
+        expired.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.txs_by_signature.len()
+    }
+
+    fn remove(&mut self, key: &PrivateKey) -> Option<Arc<Transaction>> {
+        let orphan = self.txs_by_signature.remove(key)?;
+        self.insert_order.remove(&orphan.insert_order);
+        for output in &orphan.missing_outputs {
+            if let Some(keys) = self.txs_by_missing_output.get_mut(output) {
+                keys.retain(|k| k != key);
+                if keys.is_empty() {
+                    self.txs_by_missing_output.remove(output);
+                }
+            }
+        }
+        Some(orphan.transaction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        transactions::{key_manager::create_memory_db_key_manager, tari_amount::MicroMinotari},
+        tx,
+    };
+
+    fn hash(n: u8) -> HashOutput {
+        HashOutput::from([n; 32])
+    }
+
+    #[tokio::test]
+    async fn it_returns_orphans_when_a_parent_arrives() {
+        let key_manager = create_memory_db_key_manager();
+        let mut orphan_pool = OrphanPool::new(OrphanPoolConfig::default());
+        let mut txs = Vec::new();
+        for _ in 0..3 {
+            let tx = tx!(MicroMinotari(10_000), fee: MicroMinotari(5), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0;
+            txs.push(Arc::new(tx));
+        }
+        assert!(orphan_pool.insert(txs[0].clone(), vec![hash(1), hash(2)]));
+        assert!(orphan_pool.insert(txs[1].clone(), vec![hash(2)]));
+        assert!(orphan_pool.insert(txs[2].clone(), vec![hash(3)]));
+        assert!(!orphan_pool.insert(txs[2].clone(), vec![]));
+        assert_eq!(orphan_pool.len(), 3);
+
+        assert!(orphan_pool.take_spenders_of(&[hash(4)]).is_empty());
+        let taken = orphan_pool.take_spenders_of(&[hash(2), hash(1)]);
+        assert_eq!(taken, vec![txs[0].clone(), txs[1].clone()]);
+        assert_eq!(orphan_pool.len(), 1);
+        assert!(!orphan_pool.contains(txs[0].first_kernel_excess_sig().unwrap()));
+        assert!(orphan_pool.contains(txs[2].first_kernel_excess_sig().unwrap()));
+        assert!(orphan_pool.take_spenders_of(&[hash(1)]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_limits_the_size_and_age_of_orphans() {
+        let key_manager = create_memory_db_key_manager();
+        let mut orphan_pool = OrphanPool::new(OrphanPoolConfig {
+            storage_capacity: 2,
+            expiry: Duration::from_secs(60),
+        });
+        let mut txs = Vec::new();
+        for _ in 0..3 {
+            let tx = tx!(MicroMinotari(10_000), fee: MicroMinotari(5), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0;
+            txs.push(Arc::new(tx));
+        }
+        for (tx, n) in txs.iter().zip(1u8..) {
+            assert!(orphan_pool.insert(tx.clone(), vec![hash(n)]));
+        }
+        // The oldest orphan was dropped
+        assert_eq!(orphan_pool.len(), 2);
+        assert!(!orphan_pool.contains(txs[0].first_kernel_excess_sig().unwrap()));
+
+        assert_eq!(orphan_pool.remove_expired(Instant::now()), 0);
+        assert_eq!(orphan_pool.remove_expired(Instant::now() + Duration::from_secs(60)), 2);
+        assert_eq!(orphan_pool.len(), 0);
+
+        let mut disabled = OrphanPool::new(OrphanPoolConfig {
+            storage_capacity: 0,
+            ..Default::default()
+        });
+        assert!(!disabled.insert(txs[0].clone(), vec![hash(1)]));
+    }
+}
@@ -0,0 +1,230 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    fs,
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::*;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use thiserror::Error;
+
+use crate::{
+    mempool::{proto::mempool::MempoolSnapshot, Mempool},
+    proto,
+    transactions::transaction_components::Transaction,
+};
+
+const LOG_TARGET: &str = "c::mp::persistence";
+
+/// The version of the snapshot file format written by this node
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Configuration for saving the unconfirmed pool to disk so that it survives a restart of the node
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MempoolPersistenceConfig {
+    /// If true, the unconfirmed pool is saved to disk periodically and on shutdown, and reloaded on startup
+    pub enabled: bool,
+    /// The path of the snapshot file. A relative path is relative to the base node data directory.
+    pub snapshot_file: PathBuf,
+    /// The interval at which the unconfirmed pool is saved to disk
+    #[serde(with = "serializers::seconds")]
+    pub snapshot_interval: Duration,
+}
+
+impl Default for MempoolPersistenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            snapshot_file: PathBuf::from("mempool_snapshot.bin"),
+            snapshot_interval: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MempoolPersistenceError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Could not decode the mempool snapshot: {0}")]
+    DecodeError(#[from] prost::DecodeError),
+    #[error("Unsupported mempool snapshot version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Could not convert a transaction: {0}")]
+    ConversionError(String),
+}
+
+/// Write the given transactions to the snapshot file. The snapshot is written to a temporary file that then replaces
+/// the existing snapshot, so a crash while writing never leaves a partially written snapshot behind.
+pub fn save_snapshot(path: &Path, transactions: &[Arc<Transaction>]) -> Result<(), MempoolPersistenceError> {
+    let snapshot = MempoolSnapshot {
+        version: SNAPSHOT_VERSION,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        transactions: transactions
+            .iter()
+            .cloned()
+            .map(proto::types::Transaction::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(MempoolPersistenceError::ConversionError)?,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, snapshot.encode_to_vec())?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Read the transactions from the snapshot file. Returns no transactions if the snapshot file does not exist.
+/// Transactions that cannot be converted are skipped; the caller is responsible for validating the transactions.
+pub fn load_snapshot(path: &Path) -> Result<Vec<Arc<Transaction>>, MempoolPersistenceError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let snapshot = MempoolSnapshot::decode(bytes.as_slice())?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(MempoolPersistenceError::UnsupportedVersion(snapshot.version));
+    }
+    let transactions = snapshot
+        .transactions
+        .into_iter()
+        .filter_map(|tx| match Transaction::try_from(tx) {
+            Ok(tx) => Some(Arc::new(tx)),
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Skipping malformed transaction in mempool snapshot: {}", e);
+                None
+            },
+        })
+        .collect();
+    Ok(transactions)
+}
+
+/// Reloads the mempool from the snapshot file and then saves the mempool to the snapshot file at the configured
+/// interval. This runs until the node is shut down; the final snapshot is taken by the caller once the node has shut
+/// down.
+pub(crate) async fn run_mempool_snapshots(mempool: Mempool, config: MempoolPersistenceConfig) {
+    match mempool.load_snapshot(config.snapshot_file.clone()).await {
+        Ok(num_stored) => info!(
+            target: LOG_TARGET,
+            "Reloaded {} transaction(s) into the mempool from {}",
+            num_stored,
+            config.snapshot_file.display()
+        ),
+        Err(e) => warn!(
+            target: LOG_TARGET,
+            "Could not reload the mempool from {}: {}",
+            config.snapshot_file.display(),
+            e
+        ),
+    }
+
+    let mut interval = tokio::time::interval(config.snapshot_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes immediately
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match mempool.save_snapshot(config.snapshot_file.clone()).await {
+            Ok(num_saved) => debug!(
+                target: LOG_TARGET,
+                "Saved {} mempool transaction(s) to {}",
+                num_saved,
+                config.snapshot_file.display()
+            ),
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Could not save the mempool to {}: {}",
+                config.snapshot_file.display(),
+                e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        transactions::{key_manager::create_memory_db_key_manager, tari_amount::MicroMinotari},
+        tx,
+    };
+
+    #[tokio::test]
+    async fn it_saves_and_loads_a_snapshot() {
+        let key_manager = create_memory_db_key_manager();
+        let (tx1, _, _) = tx!(MicroMinotari(5_000), fee: MicroMinotari(10), inputs: 1, outputs: 1, &key_manager)
+            .expect("Failed to get tx");
+        let (tx2, _, _) = tx!(MicroMinotari(10_000), fee: MicroMinotari(20), inputs: 2, outputs: 2, &key_manager)
+            .expect("Failed to get tx");
+        let transactions = vec![Arc::new(tx1), Arc::new(tx2)];
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("mempool").join("snapshot.bin");
+        save_snapshot(&path, &transactions).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+        assert_eq!(load_snapshot(&path).unwrap(), transactions);
+
+        // A new snapshot replaces the previous one
+        save_snapshot(&path, &transactions[1..]).unwrap();
+        assert_eq!(load_snapshot(&path).unwrap(), transactions[1..].to_vec());
+    }
+
+    #[test]
+    fn it_loads_nothing_if_there_is_no_snapshot() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let transactions = load_snapshot(&temp_dir.path().join("snapshot.bin")).unwrap();
+        assert!(transactions.is_empty());
+    }
+
+    #[test]
+    fn it_rejects_an_unsupported_version() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("snapshot.bin");
+        let snapshot = MempoolSnapshot {
+            version: SNAPSHOT_VERSION + 1,
+            timestamp: 0,
+            transactions: vec![],
+        };
+        fs::write(&path, snapshot.encode_to_vec()).unwrap();
+        assert!(matches!(
+            load_snapshot(&path),
+            Err(MempoolPersistenceError::UnsupportedVersion(v)) if v == SNAPSHOT_VERSION + 1
+        ));
+
+        fs::write(&path, b"not a snapshot").unwrap();
+        assert!(load_snapshot(&path).is_err());
+    }
+}
@@ -22,3 +22,6 @@
 
 mod prioritized_transaction;
 pub use prioritized_transaction::{FeePriority, PrioritizedTransaction};
+
+mod priority_lanes;
+pub use priority_lanes::{PriorityLane, PriorityLanes, PriorityLanesConfig};
@@ -29,9 +29,12 @@ use std::{
 use tari_common_types::types::{HashOutput, PrivateKey, PublicKey};
 use tari_utilities::{hex::Hex, ByteArray};
 
-use crate::transactions::{
-    transaction_components::{Transaction, TransactionError},
-    weight::TransactionWeight,
+use crate::{
+    mempool::priority::PriorityLane,
+    transactions::{
+        transaction_components::{Transaction, TransactionError},
+        weight::TransactionWeight,
+    },
 };
 
 /// Create a unique unspent transaction priority based on the transaction fee, maturity of the oldest input UTXO and the
@@ -49,6 +52,12 @@ impl FeePriority {
             .saturating_mul(1000)
             .checked_div(weight)
             .ok_or(TransactionError::ZeroWeight)?;
+        Ok(Self::from_fee_per_byte(transaction, fee_per_byte, insert_epoch))
+    }
+
+    /// Create the priority of a transaction from a given fee per byte, which may differ from the fee per byte that
+    /// the transaction pays when it has been boosted.
+    pub fn from_fee_per_byte(transaction: &Transaction, fee_per_byte: u64, insert_epoch: u64) -> Self {
         // Big-endian used here, the MSB is in the starting index. The ordering for Vec<u8> is taken from elements left
         // to right and the unconfirmed pool expects the lowest priority to be sorted lowest to highest in the
         // BTreeMap
@@ -71,7 +80,7 @@ impl FeePriority {
             );
         priority[16..48].copy_from_slice(agg_sig.as_bytes());
         priority[48..80].copy_from_slice(agg_nonce.as_bytes());
-        Ok(Self(priority))
+        Self(priority)
     }
 }
 
@@ -84,6 +93,11 @@ pub struct PrioritizedTransaction {
     pub fee_per_byte: u64,
     pub weight: u64,
     pub dependent_output_hashes: Vec<HashOutput>,
+    /// The unix timestamp (in seconds) at which the transaction was added to the pool
+    pub insert_epoch: u64,
+    pub lane: PriorityLane,
+    /// The percentage by which the fee per byte is boosted when the transaction is prioritised
+    pub boost: u64,
 }
 
 impl PrioritizedTransaction {
@@ -98,6 +112,7 @@ impl PrioritizedTransaction {
             Ok(n) => n.as_secs(),
             Err(_) => 0,
         };
+        let lane = PriorityLane::for_transaction(&transaction);
         Ok(Self {
             key,
             priority: FeePriority::new(&transaction, insert_epoch, weight)?,
@@ -111,8 +126,28 @@ impl PrioritizedTransaction {
             weight,
             transaction,
             dependent_output_hashes: dependent_outputs.unwrap_or_default(),
+            insert_epoch,
+            lane,
+            boost: 0,
         })
     }
+
+    /// The fee per byte used to prioritise the transaction, including any priority lane boost
+    pub fn effective_fee_per_byte(&self) -> u64 {
+        self.boost_fee_per_byte(self.fee_per_byte)
+    }
+
+    /// Applies the priority lane boost of this transaction to the given fee per byte
+    pub fn boost_fee_per_byte(&self, fee_per_byte: u64) -> u64 {
+        fee_per_byte.saturating_mul(self.boost.saturating_add(100)) / 100
+    }
+
+    /// Sets the priority lane boost percentage, recalculating the priority of the transaction
+    pub fn set_boost(&mut self, boost: u64) {
+        self.boost = boost;
+        self.priority =
+            FeePriority::from_fee_per_byte(&self.transaction, self.effective_fee_per_byte(), self.insert_epoch);
+    }
 }
 
 impl Display for PrioritizedTransaction {
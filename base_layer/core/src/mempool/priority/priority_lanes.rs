@@ -0,0 +1,195 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::transactions::transaction_components::{OutputType, Transaction};
+
+/// The largest boost, as a percentage of the fee per gram, that a priority lane can be configured with. Larger
+/// configured values are capped to this.
+pub const MAX_PRIORITY_LANE_BOOST_PERCENTAGE: u64 = 400;
+
+/// The lane that a transaction is prioritised in. Transactions in the validator node registration and burn lanes have
+/// their fee per gram boosted when they are ordered and selected for a block template, so that time-sensitive
+/// registrations are not crowded out during fee spikes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriorityLane {
+    Standard,
+    ValidatorNodeRegistration,
+    Burn,
+}
+
+impl PriorityLane {
+    /// Classifies a transaction by its outputs. A transaction with a validator node registration output is placed in
+    /// the validator node registration lane, even if it also contains a burn output.
+    pub fn for_transaction(transaction: &Transaction) -> Self {
+        let outputs = transaction.body.outputs();
+        if outputs
+            .iter()
+            .any(|o| o.features.output_type == OutputType::ValidatorNodeRegistration)
+        {
+            return PriorityLane::ValidatorNodeRegistration;
+        }
+        if outputs.iter().any(|o| o.features.output_type == OutputType::Burn) {
+            return PriorityLane::Burn;
+        }
+        PriorityLane::Standard
+    }
+}
+
+/// Configuration for the mempool priority lanes. This is a local block template policy and does not affect consensus.
+#[derive(Clone, Copy, Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PriorityLanesConfig {
+    /// If true, transactions with validator node registration or burn outputs have their fee per gram boosted when
+    /// they are prioritised. Default: false
+    pub enabled: bool,
+    /// The percentage by which the fee per gram of a validator node registration transaction is boosted. Default: 100
+    pub validator_node_registration_boost: u64,
+    /// The percentage by which the fee per gram of a burn transaction is boosted. Default: 50
+    pub burn_boost: u64,
+    /// The maximum number of boosted transactions in each lane. Transactions that arrive once a lane is full are
+    /// prioritised by their fee alone. Default: 100
+    pub max_transactions_per_lane: usize,
+    /// The minimum fee per gram a transaction must pay before it is boosted. Default: 5
+    pub min_fee_per_gram: u64,
+}
+
+impl Default for PriorityLanesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            validator_node_registration_boost: 100,
+            burn_boost: 50,
+            max_transactions_per_lane: 100,
+            min_fee_per_gram: 5,
+        }
+    }
+}
+
+/// Applies the [PriorityLanesConfig] policy and keeps track of the number of boosted transactions in each lane.
+#[derive(Debug, Clone, Default)]
+pub struct PriorityLanes {
+    config: PriorityLanesConfig,
+    boosted_counts: HashMap<PriorityLane, usize>,
+}
+
+impl PriorityLanes {
+    pub fn new(config: PriorityLanesConfig) -> Self {
+        Self {
+            config,
+            boosted_counts: HashMap::new(),
+        }
+    }
+
+    /// Returns the boost percentage that a new transaction in the given lane, paying the given fee per gram, would
+    /// receive. Zero is returned if lanes are disabled, the lane is full or the fee is too low.
+    pub fn boost_for(&self, lane: PriorityLane, fee_per_gram: u64) -> u64 {
+        if !self.config.enabled || fee_per_gram < self.config.min_fee_per_gram {
+            return 0;
+        }
+        let boost = match lane {
+            PriorityLane::Standard => return 0,
+            PriorityLane::ValidatorNodeRegistration => self.config.validator_node_registration_boost,
+            PriorityLane::Burn => self.config.burn_boost,
+        };
+        if self.boosted_count(lane) >= self.config.max_transactions_per_lane {
+            return 0;
+        }
+        boost.min(MAX_PRIORITY_LANE_BOOST_PERCENTAGE)
+    }
+
+    /// The number of boosted transactions currently in the lane
+    pub fn boosted_count(&self, lane: PriorityLane) -> usize {
+        self.boosted_counts.get(&lane).copied().unwrap_or(0)
+    }
+
+    /// Records that a boosted transaction was added to the lane
+    pub fn add_boosted(&mut self, lane: PriorityLane) {
+        *self.boosted_counts.entry(lane).or_insert(0) += 1;
+    }
+
+    /// Records that a boosted transaction was removed from the lane
+    pub fn remove_boosted(&mut self, lane: PriorityLane) {
+        if let Some(count) = self.boosted_counts.get_mut(&lane) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.boosted_counts.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn enabled_config() -> PriorityLanesConfig {
+        PriorityLanesConfig {
+            enabled: true,
+            max_transactions_per_lane: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_only_boosts_when_enabled() {
+        let lanes = PriorityLanes::new(PriorityLanesConfig::default());
+        assert_eq!(lanes.boost_for(PriorityLane::ValidatorNodeRegistration, 10), 0);
+
+        let lanes = PriorityLanes::new(enabled_config());
+        assert_eq!(lanes.boost_for(PriorityLane::ValidatorNodeRegistration, 10), 100);
+        assert_eq!(lanes.boost_for(PriorityLane::Burn, 10), 50);
+        assert_eq!(lanes.boost_for(PriorityLane::Standard, 10), 0);
+    }
+
+    #[test]
+    fn it_limits_boosted_transactions() {
+        let mut lanes = PriorityLanes::new(PriorityLanesConfig {
+            validator_node_registration_boost: 10_000,
+            ..enabled_config()
+        });
+        // Fee too low to qualify
+        assert_eq!(lanes.boost_for(PriorityLane::ValidatorNodeRegistration, 4), 0);
+        // Boost is capped
+        assert_eq!(
+            lanes.boost_for(PriorityLane::ValidatorNodeRegistration, 5),
+            MAX_PRIORITY_LANE_BOOST_PERCENTAGE
+        );
+
+        lanes.add_boosted(PriorityLane::ValidatorNodeRegistration);
+        lanes.add_boosted(PriorityLane::ValidatorNodeRegistration);
+        assert_eq!(lanes.boost_for(PriorityLane::ValidatorNodeRegistration, 5), 0);
+        // Other lanes are not affected
+        assert_eq!(lanes.boost_for(PriorityLane::Burn, 5), 50);
+
+        lanes.remove_boosted(PriorityLane::ValidatorNodeRegistration);
+        assert_eq!(lanes.boosted_count(PriorityLane::ValidatorNodeRegistration), 1);
+        assert_eq!(
+            lanes.boost_for(PriorityLane::ValidatorNodeRegistration, 5),
+            MAX_PRIORITY_LANE_BOOST_PERCENTAGE
+        );
+    }
+}
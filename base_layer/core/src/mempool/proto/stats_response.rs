@@ -32,6 +32,9 @@ impl TryFrom<ProtoStatsResponse> for StatsResponse {
             unconfirmed_txs: stats.unconfirmed_txs,
             reorg_txs: stats.reorg_txs,
             unconfirmed_weight: stats.unconfirmed_weight,
+            dependent_txs: stats.dependent_txs,
+            max_package_size: stats.max_package_size,
+            min_package_fee_per_gram: stats.min_package_fee_per_gram,
         })
     }
 }
@@ -42,6 +45,9 @@ impl From<StatsResponse> for ProtoStatsResponse {
             unconfirmed_txs: stats.unconfirmed_txs,
             reorg_txs: stats.reorg_txs,
             unconfirmed_weight: stats.unconfirmed_weight,
+            dependent_txs: stats.dependent_txs,
+            max_package_size: stats.max_package_size,
+            min_package_fee_per_gram: stats.min_package_fee_per_gram,
         }
     }
 }
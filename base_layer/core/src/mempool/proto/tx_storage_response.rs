@@ -52,6 +52,7 @@ impl From<TxStorageResponse> for proto::TxStorageResponse {
             NotStoredConsensus => proto::TxStorageResponse::NotStored,
             NotStoredAlreadyMined => proto::TxStorageResponse::NotStored,
             NotStoredFeeTooLow => proto::TxStorageResponse::NotStored,
+            NotStoredExpired => proto::TxStorageResponse::NotStored,
         }
     }
 }
@@ -144,6 +144,11 @@ impl ReorgPool {
         result
     }
 
+    /// Returns the excess signature scalars of all the kernels in the pool
+    pub fn excess_sigs(&self) -> impl Iterator<Item = &PrivateKey> + '_ {
+        self.txs_by_signature.keys()
+    }
+
     pub fn retrieve_by_excess_sigs(
         &self,
         excess_sigs: &[PrivateKey],
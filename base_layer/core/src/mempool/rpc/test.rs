@@ -52,6 +52,9 @@ mod get_stats {
 
             reorg_txs: 5,
             unconfirmed_weight: 6,
+            dependent_txs: 1,
+            max_package_size: 2,
+            min_package_fee_per_gram: 5,
         };
         mempool.set_get_stats_response(expected_stats.clone()).await;
 
@@ -30,9 +30,10 @@ use crate::{
         MempoolServiceError,
         StateResponse,
         StatsResponse,
+        TransactionDependencyGraph,
         TxStorageResponse,
     },
-    transactions::transaction_components::Transaction,
+    transactions::{tari_amount::MicroMinotari, transaction_components::Transaction},
 };
 
 #[derive(Clone)]
@@ -97,4 +98,36 @@ impl MempoolHandle {
             _ => panic!("Incorrect response"),
         }
     }
+
+    pub async fn estimate_fee_per_gram(
+        &mut self,
+        target_blocks: usize,
+        tip_height: u64,
+    ) -> Result<MicroMinotari, MempoolServiceError> {
+        match self
+            .inner
+            .call(MempoolRequest::EstimateFeePerGram {
+                target_blocks,
+                tip_height,
+            })
+            .await??
+        {
+            MempoolResponse::FeePerGramEstimate { fee_per_gram } => Ok(fee_per_gram),
+            _ => panic!("Incorrect response"),
+        }
+    }
+
+    pub async fn get_dependency_graph(
+        &mut self,
+        excess_sig: Signature,
+    ) -> Result<Option<TransactionDependencyGraph>, MempoolServiceError> {
+        match self
+            .inner
+            .call(MempoolRequest::GetDependencyGraph(excess_sig))
+            .await??
+        {
+            MempoolResponse::DependencyGraph(graph) => Ok(graph),
+            _ => panic!("Incorrect response"),
+        }
+    }
 }
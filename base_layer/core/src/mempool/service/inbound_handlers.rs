@@ -34,6 +34,7 @@ use crate::{
     mempool::{
         service::{MempoolRequest, MempoolResponse, MempoolServiceError, OutboundMempoolServiceInterface},
         Mempool,
+        MempoolEvent,
         TxStorageResponse,
     },
     transactions::transaction_components::Transaction,
@@ -61,7 +62,15 @@ impl MempoolInboundHandlers {
     /// Handle inbound Mempool service requests from remote nodes and local services.
     pub async fn handle_request(&mut self, request: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
         debug!(target: LOG_TARGET, "Handling remote request: {}", request);
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            EstimateFeePerGram,
+            GetDependencyGraph,
+            GetFeePerGramStats,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+        };
         match request {
             GetStats => Ok(MempoolResponse::Stats(self.mempool.stats().await?)),
             GetState => Ok(MempoolResponse::State(self.mempool.state().await?)),
@@ -85,6 +94,16 @@ impl MempoolInboundHandlers {
                 let stats = self.mempool.get_fee_per_gram_stats(count, tip_height).await?;
                 Ok(MempoolResponse::FeePerGramStats { response: stats })
             },
+            EstimateFeePerGram {
+                target_blocks,
+                tip_height,
+            } => {
+                let fee_per_gram = self.mempool.estimate_fee_per_gram(target_blocks, tip_height).await?;
+                Ok(MempoolResponse::FeePerGramEstimate { fee_per_gram })
+            },
+            GetDependencyGraph(excess_sig) => Ok(MempoolResponse::DependencyGraph(
+                self.mempool.get_dependency_graph(excess_sig).await?,
+            )),
         }
     }
 
@@ -175,7 +194,13 @@ impl MempoolInboundHandlers {
 
     /// Handle inbound block events from the local base node service.
     pub async fn handle_block_event(&mut self, block_event: &BlockEvent) -> Result<(), MempoolServiceError> {
-        use BlockEvent::{AddBlockValidationFailed, BlockSyncComplete, BlockSyncRewind, ValidBlockAdded};
+        use BlockEvent::{
+            AddBlockValidationFailed,
+            BlockSyncComplete,
+            BlockSyncRewind,
+            DeepReorgDetected,
+            ValidBlockAdded,
+        };
         match block_event {
             ValidBlockAdded(block, BlockAddResult::Ok(_)) => {
                 self.mempool.process_published_block(block.clone()).await?;
@@ -205,10 +230,37 @@ impl MempoolInboundHandlers {
                 }
             },
             AddBlockErrored { .. } => {},
+            DeepReorgDetected { .. } => {},
         }
 
         self.update_pool_size_metrics().await;
 
         Ok(())
     }
+
+    /// Handle inbound mempool events. Orphan transactions that were accepted once their parents arrived are
+    /// propagated to the network, as they were not propagated when they were first received.
+    pub async fn handle_mempool_event(&mut self, mempool_event: &MempoolEvent) -> Result<(), MempoolServiceError> {
+        match mempool_event {
+            MempoolEvent::OrphansAccepted(txs) => {
+                for tx in txs {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Propagate accepted orphan transaction ({}) to network.",
+                        tx.first_kernel_excess_sig()
+                            .map(|s| s.get_signature().to_hex())
+                            .unwrap_or_else(|| "No kernels!".to_string()),
+                    );
+                    self.outbound_service.propagate_tx(tx.clone(), vec![]).await?;
+                }
+                self.update_pool_size_metrics().await;
+            },
+            MempoolEvent::TransactionsExpired(_) |
+            MempoolEvent::TransactionAdded(_) |
+            MempoolEvent::TransactionsRemoved(_) |
+            MempoolEvent::TransactionsReorged(_) => {},
+        }
+
+        Ok(())
+    }
 }
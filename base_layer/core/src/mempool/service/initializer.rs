@@ -43,6 +43,7 @@ use crate::{
     base_node::comms_interface::LocalNodeCommsInterface,
     mempool::{
         mempool::Mempool,
+        persistence::run_mempool_snapshots,
         service::{
             inbound_handlers::MempoolInboundHandlers,
             local_service::LocalMempoolService,
@@ -50,6 +51,7 @@ use crate::{
             service::{MempoolService, MempoolStreams},
             MempoolHandle,
         },
+        MempoolPersistenceConfig,
     },
     proto,
     transactions::transaction_components::Transaction,
@@ -62,6 +64,7 @@ const SUBSCRIPTION_LABEL: &str = "Mempool";
 pub struct MempoolServiceInitializer {
     mempool: Mempool,
     inbound_message_subscription_factory: Arc<SubscriptionFactory>,
+    persistence: Option<MempoolPersistenceConfig>,
 }
 
 impl MempoolServiceInitializer {
@@ -70,9 +73,17 @@ impl MempoolServiceInitializer {
         Self {
             mempool,
             inbound_message_subscription_factory,
+            persistence: None,
         }
     }
 
+    /// Reload the mempool from the snapshot file on startup and save it to the snapshot file periodically, if
+    /// persistence is enabled in the given config.
+    pub fn with_persistence(mut self, config: MempoolPersistenceConfig) -> Self {
+        self.persistence = Some(config).filter(|c| c.enabled);
+        self
+    }
+
     /// Create a stream of 'New Transaction` messages
     fn inbound_transaction_stream(&self) -> impl Stream<Item = DomainMessage<Transaction>> {
         self.inbound_message_subscription_factory
@@ -127,12 +138,20 @@ impl ServiceInitializer for MempoolServiceInitializer {
         let (local_request_sender_service, local_request_stream) = reply_channel::unbounded();
         let outbound_mp_interface = OutboundMempoolServiceInterface::new(outbound_tx_sender);
         let local_mp_interface = LocalMempoolService::new(local_request_sender_service);
+        let mempool_event_stream = self.mempool.subscribe_events();
         let inbound_handlers = MempoolInboundHandlers::new(self.mempool.clone(), outbound_mp_interface.clone());
 
         // Register handle to OutboundMempoolServiceInterface before waiting for handles to be ready
         context.register_handle(outbound_mp_interface);
         context.register_handle(local_mp_interface);
 
+        if let Some(config) = self.persistence.take() {
+            let mempool = self.mempool.clone();
+            context
+                .clone()
+                .spawn_until_shutdown(move |_| run_mempool_snapshots(mempool, config));
+        }
+
         context.spawn_until_shutdown(move |handles| {
             let outbound_message_service = handles.expect_handle::<Dht>().outbound_requester();
             let base_node = handles.expect_handle::<LocalNodeCommsInterface>();
@@ -142,6 +161,7 @@ impl ServiceInitializer for MempoolServiceInitializer {
                 inbound_transaction_stream,
                 local_request_stream,
                 block_event_stream: base_node.get_block_event_stream(),
+                mempool_event_stream,
                 request_receiver,
             };
             debug!(target: LOG_TARGET, "Mempool service started");
@@ -28,9 +28,10 @@ use crate::{
         service::{MempoolRequest, MempoolResponse, MempoolServiceError},
         StateResponse,
         StatsResponse,
+        TransactionDependencyGraph,
         TxStorageResponse,
     },
-    transactions::transaction_components::Transaction,
+    transactions::{tari_amount::MicroMinotari, transaction_components::Transaction},
 };
 
 pub type LocalMempoolRequester = SenderService<MempoolRequest, Result<MempoolResponse, MempoolServiceError>>;
@@ -100,6 +101,41 @@ impl LocalMempoolService {
             _ => Err(MempoolServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns a future that resolves to the estimated fee per gram needed to be mined within `target_blocks` blocks
+    pub async fn estimate_fee_per_gram(
+        &mut self,
+        target_blocks: usize,
+        tip_height: u64,
+    ) -> Result<MicroMinotari, MempoolServiceError> {
+        match self
+            .request_sender
+            .call(MempoolRequest::EstimateFeePerGram {
+                target_blocks,
+                tip_height,
+            })
+            .await??
+        {
+            MempoolResponse::FeePerGramEstimate { fee_per_gram } => Ok(fee_per_gram),
+            _ => Err(MempoolServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns a future that resolves to the unconfirmed ancestors and descendants of the transaction with the given
+    /// excess signature, or None if the transaction is not in the unconfirmed pool
+    pub async fn get_dependency_graph(
+        &mut self,
+        excess_sig: Signature,
+    ) -> Result<Option<TransactionDependencyGraph>, MempoolServiceError> {
+        match self
+            .request_sender
+            .call(MempoolRequest::GetDependencyGraph(excess_sig))
+            .await??
+        {
+            MempoolResponse::DependencyGraph(graph) => Ok(graph),
+            _ => Err(MempoolServiceError::UnexpectedApiResponse),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -121,6 +157,9 @@ mod test {
             unconfirmed_txs: 3,
             reorg_txs: 4,
             unconfirmed_weight: 1000,
+            dependent_txs: 1,
+            max_package_size: 2,
+            min_package_fee_per_gram: 5,
         }
     }
 
@@ -37,6 +37,8 @@ pub enum MempoolRequest {
     GetTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
     GetFeePerGramStats { count: usize, tip_height: u64 },
+    EstimateFeePerGram { target_blocks: usize, tip_height: u64 },
+    GetDependencyGraph(Signature),
 }
 
 impl Display for MempoolRequest {
@@ -57,6 +59,19 @@ impl Display for MempoolRequest {
             MempoolRequest::GetFeePerGramStats { count, tip_height } => {
                 write!(f, "GetFeePerGramStats(count: {}, tip_height: {})", *count, *tip_height)
             },
+            MempoolRequest::EstimateFeePerGram {
+                target_blocks,
+                tip_height,
+            } => {
+                write!(
+                    f,
+                    "EstimateFeePerGram(target_blocks: {}, tip_height: {})",
+                    *target_blocks, *tip_height
+                )
+            },
+            MempoolRequest::GetDependencyGraph(sig) => {
+                write!(f, "GetDependencyGraph ({})", sig.get_signature().to_hex())
+            },
         }
     }
 }
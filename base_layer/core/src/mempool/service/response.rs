@@ -24,7 +24,8 @@ use std::{fmt, fmt::Formatter};
 
 use crate::{
     common::waiting_requests::RequestKey,
-    mempool::{FeePerGramStat, StateResponse, StatsResponse, TxStorageResponse},
+    mempool::{FeePerGramStat, StateResponse, StatsResponse, TransactionDependencyGraph, TxStorageResponse},
+    transactions::tari_amount::MicroMinotari,
 };
 
 /// API Response enum for Mempool responses.
@@ -34,16 +35,24 @@ pub enum MempoolResponse {
     State(StateResponse),
     TxStorage(TxStorageResponse),
     FeePerGramStats { response: Vec<FeePerGramStat> },
+    FeePerGramEstimate { fee_per_gram: MicroMinotari },
+    DependencyGraph(Option<TransactionDependencyGraph>),
 }
 
 impl fmt::Display for MempoolResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        use MempoolResponse::{FeePerGramStats, State, Stats, TxStorage};
+        use MempoolResponse::{DependencyGraph, FeePerGramEstimate, FeePerGramStats, State, Stats, TxStorage};
         match &self {
             Stats(_) => write!(f, "Stats"),
             State(_) => write!(f, "State"),
             TxStorage(_) => write!(f, "TxStorage"),
             FeePerGramStats { response } => write!(f, "FeePerGramStats({} item(s))", response.len()),
+            FeePerGramEstimate { fee_per_gram } => write!(f, "FeePerGramEstimate({})", fee_per_gram),
+            DependencyGraph(graph) => write!(
+                f,
+                "DependencyGraph({} transaction(s))",
+                graph.as_ref().map_or(0, |g| g.nodes.len())
+            ),
         }
     }
 }
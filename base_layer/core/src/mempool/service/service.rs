@@ -37,11 +37,15 @@ use tokio::{sync::mpsc, task};
 
 use crate::{
     base_node::comms_interface::{BlockEvent, BlockEventReceiver},
-    mempool::service::{
-        error::MempoolServiceError,
-        inbound_handlers::MempoolInboundHandlers,
-        MempoolRequest,
-        MempoolResponse,
+    mempool::{
+        service::{
+            error::MempoolServiceError,
+            inbound_handlers::MempoolInboundHandlers,
+            MempoolRequest,
+            MempoolResponse,
+        },
+        MempoolEvent,
+        MempoolEventReceiver,
     },
     proto,
     transactions::transaction_components::Transaction,
@@ -55,6 +59,7 @@ pub struct MempoolStreams<STxIn, SLocalReq> {
     pub inbound_transaction_stream: STxIn,
     pub local_request_stream: SLocalReq,
     pub block_event_stream: BlockEventReceiver,
+    pub mempool_event_stream: MempoolEventReceiver,
     pub request_receiver: reply_channel::TryReceiver<MempoolRequest, MempoolResponse, MempoolServiceError>,
 }
 
@@ -87,6 +92,7 @@ impl MempoolService {
         let local_request_stream = streams.local_request_stream.fuse();
         pin_mut!(local_request_stream);
         let mut block_event_stream = streams.block_event_stream;
+        let mut mempool_event_stream = streams.mempool_event_stream;
         let mut request_receiver = streams.request_receiver;
 
         loop {
@@ -119,6 +125,13 @@ impl MempoolService {
                     }
                 },
 
+                // Events from the local mempool
+                mempool_event = mempool_event_stream.recv() => {
+                    if let Ok(mempool_event) = mempool_event {
+                        self.spawn_handle_mempool_event(mempool_event);
+                    }
+                },
+
 
                 else => {
                     info!(target: LOG_TARGET, "Mempool service shutting down");
@@ -163,6 +176,16 @@ impl MempoolService {
         });
     }
 
+    fn spawn_handle_mempool_event(&self, mempool_event: Arc<MempoolEvent>) {
+        let mut inbound_handlers = self.inbound_handlers.clone();
+        task::spawn(async move {
+            let result = inbound_handlers.handle_mempool_event(&mempool_event).await;
+            if let Err(e) = result {
+                error!(target: LOG_TARGET, "Failed to handle mempool event: {}", e);
+            }
+        });
+    }
+
     fn handle_incoming_tx(&self, domain_transaction_msg: DomainMessage<Transaction>) {
         let DomainMessage::<_> { source_peer, inner, .. } = domain_transaction_msg;
 
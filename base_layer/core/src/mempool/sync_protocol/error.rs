@@ -45,4 +45,6 @@ pub enum MempoolProtocolError {
     SendTimeout,
     #[error("Receive timeout occurred")]
     RecvTimeout,
+    #[error("Peer `{peer}` sent more than the maximum of {max} transaction short ID(s)")]
+    TooManyShortIds { peer: NodeId, max: usize },
 }
@@ -30,11 +30,16 @@
 //! ## Protocol Flow
 //!
 //! Alice initiates (initiator) the connection to Bob (responder).
-//! As the initiator, Alice MUST send a transaction inventory
+//! Transactions are identified by short IDs, derived from their kernel excess signatures and a random salt chosen by
+//! Alice. Lists of short IDs are sent in pages of at most `MempoolServiceConfig::sync_page_size` items, and
+//! transactions are streamed one message at a time, so no message exceeds the frame size limit regardless of the size
+//! of either mempool.
+//!
+//! As the initiator, Alice MUST send a transaction inventory of short IDs, setting `is_last_page` on the final page
 //! Bob SHOULD respond with any transactions known to him, excluding the transactions in the inventory
 //! Bob MUST send a complete message (An empty `TransactionItem` or 1 byte in protobuf)
-//! Bob MUST send indexes of inventory items that are not known to him
-//! Alice SHOULD return the Transactions relating to those indexes
+//! Bob MUST send the short IDs of inventory items that are not known to him, setting `is_last_page` on the final page
+//! Alice SHOULD return the Transactions relating to those short IDs
 //! Alice SHOULD close the stream immediately after sending
 //!
 //!
@@ -43,7 +48,10 @@
 //!  | Alice |                    | Bob |
 //!  +-------+                    +-----+
 //!  |                                |
-//!  | Txn Inventory                  |
+//!  | Txn Inventory (page 1)         |
+//!  |------------------------------->|
+//!  |             ...paging...       |
+//!  | Txn Inventory (last page)      |
 //!  |------------------------------->|
 //!  |                                |
 //!  |      TransactionItem(tx_b1)    |
@@ -51,7 +59,7 @@
 //!  |             ...streaming...    |
 //!  |      TransactionItem(empty)    |
 //!  |<-------------------------------|
-//!  |  Inventory missing txn indexes |
+//!  |  Missing txn short IDs (pages) |
 //!  |<-------------------------------|
 //!  |                                |
 //!  | TransactionItem(tx_a1)         |
@@ -64,8 +72,8 @@
 //! ```
 
 use std::{
+    collections::HashSet,
     convert::TryFrom,
-    iter,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc,
@@ -74,10 +82,11 @@ use std::{
 };
 
 use error::MempoolProtocolError;
-use futures::{stream, SinkExt, Stream, StreamExt};
+use futures::{SinkExt, StreamExt};
 pub use initializer::MempoolSyncInitializer;
 use log::*;
-use prost::Message;
+use rand::{rngs::OsRng, RngCore};
+use tari_common_types::types::BlockHash;
 use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityRequester, ConnectivitySelection},
     framing,
@@ -88,7 +97,7 @@ use tari_comms::{
     Bytes,
     PeerConnection,
 };
-use tari_utilities::{hex::Hex, ByteArray};
+use tari_utilities::hex::Hex;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     sync::Semaphore,
@@ -100,6 +109,7 @@ use tokio::{
 use crate::mempool::metrics;
 use crate::{
     base_node::comms_interface::{BlockEvent, BlockEventReceiver},
+    blocks::ShortIdKey,
     chain_storage::BlockAddResult,
     mempool::{proto, Mempool, MempoolServiceConfig},
     proto as shared_proto,
@@ -115,7 +125,7 @@ mod initializer;
 const MAX_FRAME_SIZE: usize = 3 * 1024 * 1024; // 3 MiB
 const LOG_TARGET: &str = "c::mempool::sync_protocol";
 
-pub static MEMPOOL_SYNC_PROTOCOL: Bytes = Bytes::from_static(b"t/mempool-sync/1");
+pub static MEMPOOL_SYNC_PROTOCOL: Bytes = Bytes::from_static(b"t/mempool-sync/2");
 
 pub struct MempoolSyncProtocol<TSubstream> {
     config: MempoolServiceConfig,
@@ -366,49 +376,52 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             self.peer_node_id.short_str()
         );
 
+        let key = inventory_key(OsRng.next_u64());
         let transactions = self.mempool.snapshot().await?;
-        let items = transactions
-            .iter()
-            .take(self.config.initial_sync_max_transactions)
-            .filter_map(|txn| txn.first_kernel_excess_sig())
-            .map(|excess| excess.get_signature().to_vec())
-            .collect();
-        let inventory = proto::TransactionInventory { items };
+        let inventory = short_id_inventory(&key, transactions, self.config.initial_sync_max_transactions);
+        let short_ids = inventory.iter().map(|(short_id, _)| *short_id).collect::<Vec<_>>();
 
         // Send an inventory of items currently in this node's mempool
         debug!(
             target: LOG_TARGET,
             "Sending transaction inventory containing {} item(s) to peer `{}`",
-            inventory.items.len(),
+            short_ids.len(),
             self.peer_node_id.short_str()
         );
-
-        self.write_message(inventory).await?;
+        for (page, is_last_page) in paginate(&short_ids, self.config.sync_page_size) {
+            self.write_message(proto::TransactionInventory {
+                salt: key.salt,
+                short_ids: page.to_vec(),
+                is_last_page,
+            })
+            .await?;
+        }
 
         self.read_and_insert_transactions_until_complete().await?;
 
-        let missing_items: proto::InventoryIndexes = self.read_message().await?;
+        let missing_short_ids = self.read_missing_transactions(short_ids.len()).await?;
         debug!(
             target: LOG_TARGET,
-            "Received {} missing transaction index(es) from peer `{}`",
-            missing_items.indexes.len(),
+            "Received {} missing transaction short ID(s) from peer `{}`",
+            missing_short_ids.len(),
             self.peer_node_id.short_str(),
         );
-        let missing_txns = missing_items
-            .indexes
-            .iter()
-            .filter_map(|idx| transactions.get(*idx as usize).cloned())
+        // Transactions are sent in the order of the inventory so that parents are sent before their children
+        let missing_txns = inventory
+            .into_iter()
+            .filter(|(short_id, _)| missing_short_ids.contains(short_id))
+            .map(|(_, txn)| txn)
             .collect::<Vec<_>>();
         debug!(
             target: LOG_TARGET,
             "Sending {} missing transaction(s) to peer `{}`",
-            missing_items.indexes.len(),
+            missing_txns.len(),
             self.peer_node_id.short_str(),
         );
 
-        // If we don't have any transactions at the given indexes we still need to send back an empty if they requested
-        // at least one index
-        if !missing_items.indexes.is_empty() {
+        // If we don't have any transactions for the given short IDs we still need to send back an empty if they
+        // requested at least one short ID
+        if !missing_short_ids.is_empty() {
             self.write_transactions(missing_txns).await?;
         }
 
@@ -443,36 +456,24 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
             self.peer_node_id.short_str()
         );
 
-        let inventory: proto::TransactionInventory = self.read_message().await?;
+        let (salt, peer_short_ids) = self.read_inventory().await?;
 
         debug!(
             target: LOG_TARGET,
             "Received inventory from peer `{}` containing {} item(s)",
             self.peer_node_id.short_str(),
-            inventory.items.len()
+            peer_short_ids.len()
         );
 
+        let key = inventory_key(salt);
         let transactions = self.mempool.snapshot().await?;
-
-        let mut duplicate_inventory_items = Vec::new();
-        let (transactions, _) = transactions.into_iter().partition::<Vec<_>, _>(|transaction| {
-            let excess_sig = transaction
-                .first_kernel_excess_sig()
-                .expect("transaction stored in mempool did not have any kernels");
-
-            let has_item = inventory
-                .items
-                .iter()
-                .position(|bytes| bytes.as_slice() == excess_sig.get_signature().as_bytes());
-
-            match has_item {
-                Some(pos) => {
-                    duplicate_inventory_items.push(pos);
-                    false
-                },
-                None => true,
-            }
-        });
+        let inventory = short_id_inventory(&key, transactions, usize::MAX);
+        let known_short_ids = inventory.iter().map(|(short_id, _)| *short_id).collect::<HashSet<_>>();
+        let transactions = inventory
+            .into_iter()
+            .filter(|(short_id, _)| !peer_short_ids.contains(short_id))
+            .map(|(_, txn)| txn)
+            .collect::<Vec<_>>();
 
         debug!(
             target: LOG_TARGET,
@@ -483,32 +484,24 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
 
         self.write_transactions(transactions).await?;
 
-        // Generate an index list of inventory indexes that this node does not have
-        #[allow(clippy::cast_possible_truncation)]
-        let missing_items = inventory
-            .items
-            .into_iter()
-            .enumerate()
-            .filter_map(|(i, _)| {
-                if duplicate_inventory_items.contains(&i) {
-                    None
-                } else {
-                    Some(i as u32)
-                }
-            })
-            .collect::<Vec<_>>();
+        // Request the inventory items that this node does not have
+        let missing_short_ids = peer_short_ids.difference(&known_short_ids).copied().collect::<Vec<_>>();
         debug!(
             target: LOG_TARGET,
-            "Requesting {} missing transaction index(es) from peer `{}`",
-            missing_items.len(),
+            "Requesting {} missing transaction(s) from peer `{}`",
+            missing_short_ids.len(),
             self.peer_node_id.short_str(),
         );
 
-        let missing_items = proto::InventoryIndexes { indexes: missing_items };
-        let num_missing_items = missing_items.indexes.len();
-        self.write_message(missing_items).await?;
+        for (page, is_last_page) in paginate(&missing_short_ids, self.config.sync_page_size) {
+            self.write_message(proto::MissingTransactions {
+                short_ids: page.to_vec(),
+                is_last_page,
+            })
+            .await?;
+        }
 
-        if num_missing_items > 0 {
+        if !missing_short_ids.is_empty() {
             debug!(target: LOG_TARGET, "Waiting for missing transactions");
             self.read_and_insert_transactions_until_complete().await?;
         }
@@ -516,17 +509,49 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         Ok(())
     }
 
-    async fn read_and_insert_transactions_until_complete(&mut self) -> Result<(), MempoolProtocolError> {
-        let mut num_recv = 0;
-        while let Some(result) = self.framed.next().await {
-            let bytes = result?;
-            let item = proto::TransactionItem::decode(&mut bytes.freeze()).map_err(|err| {
-                MempoolProtocolError::DecodeFailed {
-                    source: err,
+    /// Reads inventory pages until the last page, returning the salt of the inventory and its short IDs
+    async fn read_inventory(&mut self) -> Result<(u64, HashSet<u64>), MempoolProtocolError> {
+        let mut salt = None;
+        let mut short_ids = HashSet::new();
+        loop {
+            let page: proto::TransactionInventory = self.read_message().await?;
+            let inventory_salt = *salt.get_or_insert(page.salt);
+            short_ids.extend(page.short_ids);
+            if short_ids.len() > self.config.initial_sync_max_transactions {
+                return Err(MempoolProtocolError::TooManyShortIds {
                     peer: self.peer_node_id.clone(),
-                }
-            })?;
+                    max: self.config.initial_sync_max_transactions,
+                });
+            }
+            if page.is_last_page {
+                return Ok((inventory_salt, short_ids));
+            }
+        }
+    }
+
+    /// Reads pages of missing transaction short IDs until the last page. The peer may not request more transactions
+    /// than were in the inventory.
+    async fn read_missing_transactions(&mut self, max: usize) -> Result<HashSet<u64>, MempoolProtocolError> {
+        let mut short_ids = HashSet::new();
+        loop {
+            let page: proto::MissingTransactions = self.read_message().await?;
+            short_ids.extend(page.short_ids);
+            if short_ids.len() > max {
+                return Err(MempoolProtocolError::TooManyShortIds {
+                    peer: self.peer_node_id.clone(),
+                    max,
+                });
+            }
+            if page.is_last_page {
+                return Ok(short_ids);
+            }
+        }
+    }
 
+    async fn read_and_insert_transactions_until_complete(&mut self) -> Result<(), MempoolProtocolError> {
+        let mut num_recv = 0;
+        loop {
+            let item: proto::TransactionItem = self.read_message().await?;
             match item.transaction {
                 Some(txn) => {
                     self.validate_and_insert_transaction(txn).await?;
@@ -604,24 +629,20 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
     }
 
     async fn write_transactions(&mut self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolProtocolError> {
-        let txns = transactions.into_iter().take(self.config.initial_sync_max_transactions)
-            .filter_map(|txn| {
-                match shared_proto::types::Transaction::try_from(txn) {
-                    Ok(txn) =>   Some(proto::TransactionItem {
-                        transaction: Some(txn),
-                    }),
-                    Err(e) => {
-                        warn!(target: LOG_TARGET, "Could not convert transaction: {}", e);
-                        None
-                    }
-                }
-            })
-            // Write an empty `TransactionItem` to indicate we're done
-            .chain(iter::once(proto::TransactionItem::empty()));
-
-        self.write_messages(stream::iter(txns)).await?;
+        // Transactions are converted and written one at a time, so that a slow peer applies backpressure and a peer
+        // that stops reading causes a send timeout
+        for txn in transactions.into_iter().take(self.config.initial_sync_max_transactions) {
+            match shared_proto::types::Transaction::try_from(txn) {
+                Ok(txn) => {
+                    self.write_message(proto::TransactionItem { transaction: Some(txn) })
+                        .await?
+                },
+                Err(e) => warn!(target: LOG_TARGET, "Could not convert transaction: {}", e),
+            }
+        }
 
-        Ok(())
+        // Write an empty `TransactionItem` to indicate we're done
+        self.write_message(proto::TransactionItem::empty()).await
     }
 
     async fn read_message<T: prost::Message + Default>(&mut self) -> Result<T, MempoolProtocolError> {
@@ -636,16 +657,6 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         })
     }
 
-    async fn write_messages<S, T>(&mut self, stream: S) -> Result<(), MempoolProtocolError>
-    where
-        S: Stream<Item = T> + Unpin,
-        T: prost::Message,
-    {
-        let mut s = stream.map(|m| Bytes::from(m.to_encoded_bytes())).map(Ok);
-        self.framed.send_all(&mut s).await?;
-        Ok(())
-    }
-
     async fn write_message<T: prost::Message>(&mut self, message: T) -> Result<(), MempoolProtocolError> {
         time::timeout(
             Duration::from_secs(10),
@@ -656,3 +667,40 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         Ok(())
     }
 }
+
+/// Mempool inventories are not tied to a block, so their short IDs are derived from the salt alone
+fn inventory_key(salt: u64) -> ShortIdKey {
+    ShortIdKey::new(BlockHash::zero(), salt)
+}
+
+/// Returns the short ID of each of the given transactions, up to `max` transactions, in the order given. A short ID
+/// collision between the transactions of two peers only means that one of the transactions is not synced.
+fn short_id_inventory(
+    key: &ShortIdKey,
+    transactions: Vec<Arc<Transaction>>,
+    max: usize,
+) -> Vec<(u64, Arc<Transaction>)> {
+    transactions
+        .into_iter()
+        .filter_map(|txn| {
+            let short_id = key.short_id(txn.first_kernel_excess_sig()?.get_signature());
+            Some((short_id, txn))
+        })
+        .take(max)
+        .collect()
+}
+
+/// Splits the short IDs into pages of at most `page_size` items, returning each page and whether it is the last page.
+/// An empty list of short IDs results in a single, empty, last page.
+fn paginate(short_ids: &[u64], page_size: usize) -> Vec<(&[u64], bool)> {
+    if short_ids.is_empty() {
+        return vec![(short_ids, true)];
+    }
+    let page_size = page_size.max(1);
+    let num_pages = short_ids.len().div_ceil(page_size);
+    short_ids
+        .chunks(page_size)
+        .enumerate()
+        .map(|(i, page)| (page, i + 1 == num_pages))
+        .collect()
+}
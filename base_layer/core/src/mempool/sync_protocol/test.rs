@@ -48,8 +48,16 @@ use crate::{
     consensus::ConsensusManager,
     mempool::{
         proto,
-        sync_protocol::{MempoolPeerProtocol, MempoolSyncProtocol, MAX_FRAME_SIZE, MEMPOOL_SYNC_PROTOCOL},
+        sync_protocol::{
+            inventory_key,
+            paginate,
+            MempoolPeerProtocol,
+            MempoolSyncProtocol,
+            MAX_FRAME_SIZE,
+            MEMPOOL_SYNC_PROTOCOL,
+        },
         Mempool,
+        MempoolServiceConfig,
     },
     transactions::{
         key_manager::create_memory_db_key_manager,
@@ -245,6 +253,40 @@ async fn responder() {
     // this.
 }
 
+#[tokio::test]
+async fn synchronise_in_pages() {
+    let (protocol_notif, _, _, transactions1) = setup(3).await;
+
+    let node1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let node2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    let (sock_in, sock_out) = MemorySocket::new_pair();
+    protocol_notif
+        .send(ProtocolNotification::new(
+            MEMPOOL_SYNC_PROTOCOL.clone(),
+            ProtocolEvent::NewInboundSubstream(node1.node_id().clone(), sock_in),
+        ))
+        .await
+        .unwrap();
+
+    let (mempool2, transactions2) = new_mempool_with_transactions(4).await;
+    mempool2.insert(Arc::new(transactions1[0].clone())).await.unwrap();
+    let framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
+    let config = MempoolServiceConfig {
+        sync_page_size: 2,
+        ..Default::default()
+    };
+    MempoolPeerProtocol::new(config, framed, node2.node_id().clone(), mempool2.clone())
+        .start_initiator()
+        .await
+        .unwrap();
+
+    let transactions = get_snapshot(&mempool2).await;
+    assert_eq!(transactions.len(), 7);
+    assert!(transactions1.iter().all(|txn| transactions.contains(txn)));
+    assert!(transactions2.iter().all(|txn| transactions.contains(txn)));
+}
+
 #[tokio::test]
 async fn initiator_messages() {
     let (protocol_notif, _, _, transactions1) = setup(2).await;
@@ -263,21 +305,35 @@ async fn initiator_messages() {
     let mut transactions = create_transactions(2).await;
     transactions.push(transactions1[0].clone());
     let mut framed = framing::canonical(sock_out, MAX_FRAME_SIZE);
-    // As the initiator, send an inventory
-    let inventory = proto::TransactionInventory {
-        items: transactions
-            .iter()
-            .map(|tx| tx.first_kernel_excess_sig().unwrap().get_signature().to_vec())
-            .collect(),
+    let key = inventory_key(123);
+    let short_ids = transactions
+        .iter()
+        .map(|tx| key.short_id(tx.first_kernel_excess_sig().unwrap().get_signature()))
+        .collect::<Vec<_>>();
+    // As the initiator, send an inventory in two pages
+    let page = proto::TransactionInventory {
+        salt: key.salt,
+        short_ids: short_ids[..2].to_vec(),
+        is_last_page: false,
+    };
+    write_message(&mut framed, page).await;
+    let page = proto::TransactionInventory {
+        salt: key.salt,
+        short_ids: short_ids[2..].to_vec(),
+        is_last_page: true,
     };
-    write_message(&mut framed, inventory).await;
-    // Expect 1 transaction, a "stop message" and indexes for missing transactions
+    write_message(&mut framed, page).await;
+    // Expect 1 transaction, a "stop message" and the short IDs of the missing transactions
     let transaction: proto::TransactionItem = read_message(&mut framed).await;
     assert!(transaction.transaction.is_some());
     let stop: proto::TransactionItem = read_message(&mut framed).await;
     assert!(stop.transaction.is_none());
-    let indexes: proto::InventoryIndexes = read_message(&mut framed).await;
-    assert_eq!(indexes.indexes, [0, 1]);
+    let mut missing: proto::MissingTransactions = read_message(&mut framed).await;
+    assert!(missing.is_last_page);
+    missing.short_ids.sort_unstable();
+    let mut expected = short_ids[..2].to_vec();
+    expected.sort_unstable();
+    assert_eq!(missing.short_ids, expected);
 }
 
 #[tokio::test]
@@ -297,13 +353,22 @@ async fn responder_messages() {
 
     // Expect an inventory
     let inventory: proto::TransactionInventory = read_message(&mut framed).await;
-    assert_eq!(inventory.items.len(), 1);
+    assert_eq!(inventory.short_ids.len(), 1);
+    assert!(inventory.is_last_page);
     // Send no transactions back
     let nothing = proto::TransactionItem::empty();
     write_message(&mut framed, nothing).await;
-    // Send transaction indexes back
-    let indexes = proto::InventoryIndexes { indexes: vec![0] };
-    write_message(&mut framed, indexes).await;
+    // Request the transaction in the second of two pages
+    let page = proto::MissingTransactions {
+        short_ids: vec![],
+        is_last_page: false,
+    };
+    write_message(&mut framed, page).await;
+    let page = proto::MissingTransactions {
+        short_ids: inventory.short_ids,
+        is_last_page: true,
+    };
+    write_message(&mut framed, page).await;
     // Expect a single transaction back and a stop message
     let transaction: proto::TransactionItem = read_message(&mut framed).await;
     assert_eq!(
@@ -329,6 +394,20 @@ async fn responder_messages() {
     assert!(framed.next().await.is_none());
 }
 
+#[test]
+fn it_paginates_short_ids() {
+    let pages = paginate(&[1, 2, 3, 4, 5], 2);
+    assert_eq!(pages, vec![
+        (&[1, 2][..], false),
+        (&[3, 4][..], false),
+        (&[5][..], true)
+    ]);
+    let pages = paginate(&[1, 2], 2);
+    assert_eq!(pages, vec![(&[1, 2][..], true)]);
+    let pages = paginate(&[], 2);
+    assert_eq!(pages, vec![(&[][..], true)]);
+}
+
 async fn get_snapshot(mempool: &Mempool) -> Vec<Transaction> {
     mempool
         .snapshot()
@@ -61,6 +61,9 @@ impl Default for MempoolMockState {
                 unconfirmed_txs: 0,
                 reorg_txs: 0,
                 unconfirmed_weight: 0,
+                dependent_txs: 0,
+                max_package_size: 0,
+                min_package_fee_per_gram: 0,
             })),
             get_state: Arc::new(Mutex::new(StateResponse {
                 unconfirmed_pool: vec![],
@@ -124,7 +127,15 @@ impl MempoolServiceMock {
     }
 
     async fn handle_request(&self, req: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            EstimateFeePerGram,
+            GetDependencyGraph,
+            GetFeePerGramStats,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+        };
 
         self.state.inc_call_count();
         match req {
@@ -136,7 +147,7 @@ impl MempoolServiceMock {
             SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
                 self.state.submit_transaction.lock().await.clone(),
             )),
-            GetFeePerGramStats { .. } => {
+            GetFeePerGramStats { .. } | EstimateFeePerGram { .. } | GetDependencyGraph(_) => {
                 unimplemented!()
             },
         }
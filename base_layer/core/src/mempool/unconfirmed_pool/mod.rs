@@ -21,11 +21,13 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod error;
+mod packages;
 #[allow(clippy::module_inception)]
 mod unconfirmed_pool;
 
 // Public re-exports
 pub use error::UnconfirmedPoolError;
+pub use packages::PackageStats;
 use tari_crypto::hash_domain;
 pub use unconfirmed_pool::{RetrieveResults, TransactionKey, UnconfirmedPool, UnconfirmedPoolConfig};
 
@@ -0,0 +1,246 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::mempool::{priority::FeePriority, unconfirmed_pool::TransactionKey};
+
+/// The total fee and weight of a set of transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackageTotals {
+    pub count: usize,
+    pub fee: u64,
+    pub weight: u64,
+}
+
+impl PackageTotals {
+    fn new(fee: u64, weight: u64) -> Self {
+        Self { count: 1, fee, weight }
+    }
+
+    /// The fee per 1000 grams of the package, using the same scale as the `fee_per_byte` of a prioritized transaction
+    pub fn fee_rate(&self) -> u64 {
+        self.fee.saturating_mul(1000).checked_div(self.weight).unwrap_or(0)
+    }
+
+    fn add(&mut self, other: PackageTotals) {
+        self.count += other.count;
+        self.fee = self.fee.saturating_add(other.fee);
+        self.weight = self.weight.saturating_add(other.weight);
+    }
+
+    fn sub(&mut self, other: PackageTotals) {
+        self.count = self.count.saturating_sub(other.count);
+        self.fee = self.fee.saturating_sub(other.fee);
+        self.weight = self.weight.saturating_sub(other.weight);
+    }
+}
+
+/// Summary of the dependencies between the transactions in the unconfirmed pool
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackageStats {
+    /// The number of transactions that spend the outputs of other unconfirmed transactions
+    pub dependent_txs: u64,
+    /// The largest number of transactions in a package, i.e. a transaction and all of its unconfirmed ancestors
+    pub max_package_size: u64,
+    /// The lowest package fee per gram of the transactions that would be evicted first when the pool is full
+    pub min_package_fee_per_gram: u64,
+}
+
+struct PackageEntry {
+    own: PackageTotals,
+    priority: FeePriority,
+    ancestors: HashSet<TransactionKey>,
+    descendants: HashSet<TransactionKey>,
+    // The totals of the transaction and all of its descendants
+    descendant_totals: PackageTotals,
+}
+
+impl PackageEntry {
+    /// Evicting a transaction also evicts its descendants, so a transaction is only as cheap to evict as the better
+    /// of its own fee rate and the fee rate of the package formed with its descendants. This keeps a low fee parent
+    /// in the pool while a high fee child is paying for it.
+    fn eviction_score(&self) -> (u64, FeePriority) {
+        (
+            self.own.fee_rate().max(self.descendant_totals.fee_rate()),
+            self.priority.clone(),
+        )
+    }
+}
+
+/// Tracks the in-pool ancestors and descendants of every transaction in the unconfirmed pool, so that transactions
+/// can be evicted as packages ordered by package fee rate rather than individually, which would strand the high fee
+/// children of low fee parents.
+#[derive(Default)]
+pub struct TransactionPackages {
+    entries: HashMap<TransactionKey, PackageEntry>,
+    by_eviction_score: BTreeMap<(u64, FeePriority), TransactionKey>,
+}
+
+impl TransactionPackages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track a new transaction that spends the outputs of the given (in-pool) parent transactions
+    pub fn insert(
+        &mut self,
+        key: TransactionKey,
+        fee: u64,
+        weight: u64,
+        priority: FeePriority,
+        parents: &[TransactionKey],
+    ) {
+        let own = PackageTotals::new(fee, weight);
+        let ancestors = self.collect_ancestors(parents);
+        for ancestor in &ancestors {
+            self.update_entry(*ancestor, |entry| {
+                entry.descendants.insert(key);
+                entry.descendant_totals.add(own);
+            });
+        }
+        let entry = PackageEntry {
+            own,
+            priority,
+            ancestors,
+            descendants: HashSet::new(),
+            descendant_totals: own,
+        };
+        self.by_eviction_score.insert(entry.eviction_score(), key);
+        self.entries.insert(key, entry);
+    }
+
+    /// Stop tracking a transaction. Its descendants are not removed, they no longer count it as an ancestor.
+    pub fn remove(&mut self, key: TransactionKey) {
+        let entry = match self.entries.remove(&key) {
+            Some(entry) => entry,
+            None => return,
+        };
+        self.by_eviction_score.remove(&entry.eviction_score());
+        for ancestor in &entry.ancestors {
+            self.update_entry(*ancestor, |a| {
+                a.descendants.remove(&key);
+                a.descendant_totals.sub(entry.own);
+            });
+        }
+        for descendant in &entry.descendants {
+            if let Some(d) = self.entries.get_mut(descendant) {
+                d.ancestors.remove(&key);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.by_eviction_score.clear();
+    }
+
+    /// Returns the package fee rate and key of the transaction that should be evicted first
+    pub fn lowest_eviction_score(&self) -> Option<(u64, TransactionKey)> {
+        self.by_eviction_score
+            .iter()
+            .next()
+            .map(|((fee_rate, _), key)| (*fee_rate, *key))
+    }
+
+    /// Returns the transaction and all of its in-pool descendants
+    pub fn with_descendants(&self, key: TransactionKey) -> Vec<TransactionKey> {
+        let mut keys = vec![key];
+        if let Some(entry) = self.entries.get(&key) {
+            keys.extend(entry.descendants.iter().copied());
+        }
+        keys
+    }
+
+    /// Returns the in-pool ancestors of a transaction
+    pub fn ancestors(&self, key: TransactionKey) -> Vec<TransactionKey> {
+        self.entries
+            .get(&key)
+            .map(|entry| entry.ancestors.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the in-pool descendants of a transaction
+    pub fn descendants(&self, key: TransactionKey) -> Vec<TransactionKey> {
+        self.entries
+            .get(&key)
+            .map(|entry| entry.descendants.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of in-pool ancestors of a transaction
+    pub fn ancestor_count(&self, key: TransactionKey) -> usize {
+        self.entries.get(&key).map_or(0, |entry| entry.ancestors.len())
+    }
+
+    /// Returns the totals of a transaction with the given fee and weight together with all of its in-pool ancestors,
+    /// given its parents
+    pub fn ancestor_totals(&self, fee: u64, weight: u64, parents: &[TransactionKey]) -> PackageTotals {
+        let mut totals = PackageTotals::new(fee, weight);
+        for ancestor in self.collect_ancestors(parents) {
+            if let Some(entry) = self.entries.get(&ancestor) {
+                totals.add(entry.own);
+            }
+        }
+        totals
+    }
+
+    /// Returns true if any of the given keys is one of the given parents or one of their ancestors
+    pub fn contains_any_ancestor(&self, parents: &[TransactionKey], keys: &[TransactionKey]) -> bool {
+        let ancestors = self.collect_ancestors(parents);
+        keys.iter().any(|k| ancestors.contains(k))
+    }
+
+    pub fn stats(&self) -> PackageStats {
+        let dependent_txs = self.entries.values().filter(|e| !e.ancestors.is_empty()).count();
+        let max_package_size = self.entries.values().map(|e| e.ancestors.len() + 1).max().unwrap_or(0);
+        PackageStats {
+            dependent_txs: dependent_txs as u64,
+            max_package_size: max_package_size as u64,
+            min_package_fee_per_gram: self.lowest_eviction_score().map_or(0, |(r, _)| r / 1000),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn collect_ancestors(&self, parents: &[TransactionKey]) -> HashSet<TransactionKey> {
+        let mut ancestors = HashSet::new();
+        for parent in parents {
+            if let Some(entry) = self.entries.get(parent) {
+                ancestors.insert(*parent);
+                ancestors.extend(entry.ancestors.iter().copied());
+            }
+        }
+        ancestors
+    }
+
+    fn update_entry<F: FnOnce(&mut PackageEntry)>(&mut self, key: TransactionKey, f: F) {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.by_eviction_score.remove(&entry.eviction_score());
+            f(entry);
+            self.by_eviction_score.insert(entry.eviction_score(), key);
+        }
+    }
+}
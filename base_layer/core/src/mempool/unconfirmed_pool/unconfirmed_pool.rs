@@ -23,21 +23,29 @@
 use std::{
     collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
     sync::Arc,
+    time::Duration,
 };
 
 use log::*;
 use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
 use tari_common_types::types::{FixedHash, HashOutput, PrivateKey, Signature};
 use tokio::time::Instant;
 
 use crate::{
     blocks::Block,
     mempool::{
-        priority::{FeePriority, PrioritizedTransaction},
+        priority::{FeePriority, PrioritizedTransaction, PriorityLanes, PriorityLanesConfig},
         shrink_hashmap::shrink_hashmap,
-        unconfirmed_pool::UnconfirmedPoolError,
+        unconfirmed_pool::{
+            packages::{PackageStats, TransactionPackages},
+            UnconfirmedPoolError,
+        },
+        DependencyGraphNode,
+        DependencyRelation,
         FeePerGramStat,
         MempoolError,
+        TransactionDependencyGraph,
     },
     transactions::{
         tari_amount::MicroMinotari,
@@ -61,6 +69,10 @@ pub struct UnconfirmedPoolConfig {
     pub weight_tx_skip_count: usize,
     /// The minimum fee accepted by this mempool
     pub min_fee: u64,
+    /// Transactions that have been in the pool for longer than this are removed, together with the transactions that
+    /// depend on them. Zero disables expiry.
+    #[serde(with = "serializers::seconds")]
+    pub expiry: Duration,
 }
 
 impl Default for UnconfirmedPoolConfig {
@@ -69,6 +81,7 @@ impl Default for UnconfirmedPoolConfig {
             storage_capacity: 40_000,
             weight_tx_skip_count: 20,
             min_fee: 0,
+            expiry: Duration::from_secs(3 * 24 * 60 * 60),
         }
     }
 }
@@ -88,7 +101,12 @@ pub struct UnconfirmedPool {
     txs_by_signature: HashMap<PrivateKey, Vec<TransactionKey>>,
     tx_by_priority: BTreeMap<FeePriority, TransactionKey>,
     txs_by_output: HashMap<HashOutput, Vec<TransactionKey>>,
+    txs_by_input: HashMap<HashOutput, Vec<TransactionKey>>,
     txs_by_unique_id: HashMap<[u8; 32], Vec<TransactionKey>>,
+    packages: TransactionPackages,
+    priority_lanes: PriorityLanes,
+    // The insert times of drained transactions, so that revalidated transactions keep their original insert time
+    drained_insert_epochs: HashMap<PrivateKey, u64>,
 }
 
 // helper class to reduce type complexity
@@ -110,13 +128,24 @@ impl UnconfirmedPool {
             txs_by_signature: HashMap::new(),
             tx_by_priority: BTreeMap::new(),
             txs_by_output: HashMap::new(),
+            txs_by_input: HashMap::new(),
             txs_by_unique_id: HashMap::new(),
+            packages: TransactionPackages::new(),
+            priority_lanes: PriorityLanes::default(),
+            drained_insert_epochs: HashMap::new(),
         }
     }
 
+    /// Boost the priority of validator node registration and burn transactions according to the given policy
+    pub fn with_priority_lanes(mut self, config: PriorityLanesConfig) -> Self {
+        self.priority_lanes = PriorityLanes::new(config);
+        self
+    }
+
     /// Insert a new transaction into the UnconfirmedPool. Low priority transactions will be removed to make space for
-    /// higher priority transactions. The lowest priority transactions will be removed when the maximum capacity is
-    /// reached and the new transaction has a higher priority than the currently stored lowest priority transaction.
+    /// higher priority transactions. When the maximum capacity is reached, the package (a transaction and its
+    /// unconfirmed descendants) with the lowest package fee rate is removed if the new transaction, together with its
+    /// unconfirmed ancestors, pays a higher fee rate.
     pub fn insert(
         &mut self,
         tx: Arc<Transaction>,
@@ -133,27 +162,71 @@ impl UnconfirmedPool {
         }
 
         let new_key = self.get_next_key();
-        let prioritized_tx = PrioritizedTransaction::new(new_key, transaction_weighting, tx, dependent_outputs)?;
+        let mut prioritized_tx = PrioritizedTransaction::new(new_key, transaction_weighting, tx, dependent_outputs)?;
+        if let Some(insert_epoch) = prioritized_tx
+            .transaction
+            .first_kernel_excess_sig()
+            .and_then(|sig| self.drained_insert_epochs.remove(sig.get_signature()))
+        {
+            prioritized_tx.insert_epoch = insert_epoch;
+        }
+        let boost = self
+            .priority_lanes
+            .boost_for(prioritized_tx.lane, prioritized_tx.fee_per_byte / 1000);
+        if boost > 0 {
+            prioritized_tx.set_boost(boost);
+        }
+        let fee = prioritized_tx.transaction.body.get_total_fee()?.as_u64();
+        let parents = self.parent_transactions(&prioritized_tx.transaction);
         if self.tx_by_key.len() >= self.config.storage_capacity {
-            if prioritized_tx.priority < *self.lowest_priority()? {
-                return Ok(());
+            let package_fee_rate = self
+                .packages
+                .ancestor_totals(fee, prioritized_tx.weight, &parents)
+                .fee_rate();
+            while self.tx_by_key.len() >= self.config.storage_capacity {
+                let (lowest_fee_rate, lowest_key) = self
+                    .packages
+                    .lowest_eviction_score()
+                    .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+                if package_fee_rate <= lowest_fee_rate {
+                    return Ok(());
+                }
+                let evicted = self.packages.with_descendants(lowest_key);
+                if self.packages.contains_any_ancestor(&parents, &evicted) {
+                    return Ok(());
+                }
+                for key in evicted {
+                    self.remove_transaction(key)?;
+                }
             }
-            self.remove_lowest_priority_tx()?;
         }
 
         self.tx_by_priority.insert(prioritized_tx.priority.clone(), new_key);
         for output in prioritized_tx.transaction.body.outputs() {
             self.txs_by_output.entry(output.hash()).or_default().push(new_key);
         }
+        for input in prioritized_tx.transaction.body.inputs() {
+            self.txs_by_input.entry(input.output_hash()).or_default().push(new_key);
+        }
         for kernel in prioritized_tx.transaction.body.kernels() {
             let sig = kernel.excess_sig.get_signature();
             self.txs_by_signature.entry(sig.clone()).or_default().push(new_key);
         }
+        self.packages.insert(
+            new_key,
+            fee,
+            prioritized_tx.weight,
+            prioritized_tx.priority.clone(),
+            &parents,
+        );
 
         debug!(
             target: LOG_TARGET,
             "Inserted transaction {} into unconfirmed pool:", prioritized_tx
         );
+        if prioritized_tx.boost > 0 {
+            self.priority_lanes.add_boosted(prioritized_tx.lane);
+        }
         self.tx_by_key.insert(new_key, prioritized_tx);
 
         Ok(())
@@ -231,7 +304,7 @@ impl UnconfirmedPool {
                 &mut potentional_to_add,
                 &mut depended_on,
                 &mut recompute,
-                prioritized_transaction.fee_per_byte,
+                prioritized_transaction.effective_fee_per_byte(),
             )?;
             if curr_skip_count >= self.config.weight_tx_skip_count {
                 break;
@@ -266,7 +339,8 @@ impl UnconfirmedPool {
                             .or_insert_with(|| vec![tx_key]);
                     }
                 }
-                let fee_per_byte = total_transaction_fees.saturating_mul(1000) / total_transaction_weight;
+                let fee_per_byte = prioritized_transaction
+                    .boost_fee_per_byte(total_transaction_fees.saturating_mul(1000) / total_transaction_weight);
                 complete_transaction_branch.insert(
                     *tx_key,
                     (
@@ -334,7 +408,11 @@ impl UnconfirmedPool {
                 let (_, total_transaction_weight, total_transaction_fees) = complete_transaction_branch
                     .get(&tx_key)
                     .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
-                let fee_per_byte = total_transaction_fees.saturating_mul(1000) / *total_transaction_weight;
+                let fee_per_byte = self
+                    .tx_by_key
+                    .get(&tx_key)
+                    .ok_or(UnconfirmedPoolError::StorageOutofSync)?
+                    .boost_fee_per_byte(total_transaction_fees.saturating_mul(1000) / *total_transaction_weight);
                 potentional_to_add.push((fee_per_byte, tx_key));
                 continue;
             }
@@ -413,6 +491,11 @@ impl UnconfirmedPool {
         Ok(())
     }
 
+    /// Returns the excess signature scalars of all the kernels in the pool
+    pub fn excess_sigs(&self) -> impl Iterator<Item = &PrivateKey> + '_ {
+        self.txs_by_signature.keys()
+    }
+
     pub fn retrieve_by_excess_sigs(
         &self,
         excess_sigs: &[PrivateKey],
@@ -541,18 +624,43 @@ impl UnconfirmedPool {
         false
     }
 
-    fn lowest_priority(&self) -> Result<&FeePriority, UnconfirmedPoolError> {
-        self.tx_by_priority
-            .keys()
-            .next()
-            .ok_or(UnconfirmedPoolError::StorageOutofSync)
+    /// Returns the keys of the transactions in the pool that create the outputs spent by the given transaction
+    fn parent_transactions(&self, tx: &Transaction) -> Vec<TransactionKey> {
+        let mut keys = tx
+            .body
+            .inputs()
+            .iter()
+            .filter_map(|input| self.txs_by_output.get(&input.output_hash()))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
     }
 
-    fn remove_lowest_priority_tx(&mut self) -> Result<(), UnconfirmedPoolError> {
-        if let Some(tx_key) = self.tx_by_priority.values().next().copied() {
-            self.remove_transaction(tx_key)?;
+    /// Remove the transactions that were inserted more than the configured expiry before `now` (a unix timestamp in
+    /// seconds), together with the transactions that depend on them, returning the removed transactions.
+    pub fn remove_expired_transactions(&mut self, now: u64) -> Result<Vec<Arc<Transaction>>, UnconfirmedPoolError> {
+        let expiry = self.config.expiry.as_secs();
+        if expiry == 0 {
+            return Ok(Vec::new());
         }
-        Ok(())
+        let expired = self
+            .tx_by_key
+            .iter()
+            .filter(|(_, tx)| tx.insert_epoch.saturating_add(expiry) <= now)
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        let mut removed = Vec::new();
+        for key in expired {
+            for key in self.packages.with_descendants(key) {
+                if let Some(tx) = self.remove_transaction(key)? {
+                    removed.push(tx);
+                }
+            }
+        }
+        Ok(removed)
     }
 
     /// Remove all current mempool transactions from the UnconfirmedPoolStorage, returning that which have been removed
@@ -560,6 +668,18 @@ impl UnconfirmedPool {
         self.txs_by_signature.clear();
         self.tx_by_priority.clear();
         self.txs_by_output.clear();
+        self.txs_by_input.clear();
+        self.packages.clear();
+        self.priority_lanes.clear();
+        self.drained_insert_epochs = self
+            .tx_by_key
+            .values()
+            .filter_map(|tx| {
+                tx.transaction
+                    .first_kernel_excess_sig()
+                    .map(|sig| (sig.get_signature().clone(), tx.insert_epoch))
+            })
+            .collect();
         self.tx_by_key.drain().map(|(_, val)| val.transaction).collect()
     }
 
@@ -700,6 +820,10 @@ impl UnconfirmedPool {
         };
 
         self.tx_by_priority.remove(&prioritized_transaction.priority);
+        self.packages.remove(tx_key);
+        if prioritized_transaction.boost > 0 {
+            self.priority_lanes.remove_boosted(prioritized_transaction.lane);
+        }
 
         for kernel in prioritized_transaction.transaction.body.kernels() {
             let sig = kernel.excess_sig.get_signature();
@@ -727,6 +851,18 @@ impl UnconfirmedPool {
             }
         }
 
+        for input in prioritized_transaction.transaction.body.inputs() {
+            let input_hash = input.output_hash();
+            if let Some(keys) = self.txs_by_input.get_mut(&input_hash) {
+                if let Some(pos) = keys.iter().position(|k| *k == tx_key) {
+                    keys.remove(pos);
+                }
+                if keys.is_empty() {
+                    self.txs_by_input.remove(&input_hash);
+                }
+            }
+        }
+
         trace!(
             target: LOG_TARGET,
             "Deleted transaction: {}",
@@ -814,10 +950,172 @@ impl UnconfirmedPool {
         Ok(stats)
     }
 
+    /// Returns statistics about the packages of dependent transactions in the pool
+    pub fn package_stats(&self) -> PackageStats {
+        self.packages.stats()
+    }
+
+    /// Returns the unconfirmed ancestors and descendants of the transaction with the given excess signature, or None
+    /// if the transaction is not in the pool
+    pub fn dependency_graph(
+        &self,
+        excess_sig: &Signature,
+    ) -> Result<Option<TransactionDependencyGraph>, UnconfirmedPoolError> {
+        let root_key = match self
+            .txs_by_signature
+            .get(excess_sig.get_signature())
+            .and_then(|keys| keys.first())
+        {
+            Some(key) => *key,
+            None => return Ok(None),
+        };
+        let ancestors = self.packages.ancestors(root_key);
+        let descendants = self.packages.descendants(root_key);
+        let mut keys = ancestors
+            .iter()
+            .chain(Some(&root_key))
+            .chain(descendants.iter())
+            .copied()
+            .collect::<Vec<_>>();
+        // A transaction has more in-pool ancestors than each of its parents, so this orders parents before children
+        keys.sort_by_key(|key| (self.packages.ancestor_count(*key), *key));
+        let positions = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (*key, i))
+            .collect::<HashMap<_, _>>();
+
+        let mut nodes = Vec::with_capacity(keys.len());
+        for key in &keys {
+            let tx = self.tx_by_key.get(key).ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+            let relation = if *key == root_key {
+                DependencyRelation::Root
+            } else if ancestors.contains(key) {
+                DependencyRelation::Ancestor
+            } else {
+                DependencyRelation::Descendant
+            };
+            nodes.push(DependencyGraphNode {
+                excess_sig: tx
+                    .transaction
+                    .first_kernel_excess_sig()
+                    .cloned()
+                    .ok_or(UnconfirmedPoolError::StorageOutofSync)?,
+                relation,
+                fee: tx.transaction.body.get_total_fee()?,
+                weight: tx.weight,
+                fee_per_gram: tx.fee_per_byte / 1000,
+                parents: self
+                    .parent_transactions(&tx.transaction)
+                    .iter()
+                    .filter_map(|parent| positions.get(parent).copied())
+                    .collect(),
+            });
+        }
+
+        let root = self
+            .tx_by_key
+            .get(&root_key)
+            .ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+        let package_fee_per_gram = self
+            .packages
+            .ancestor_totals(
+                root.transaction.body.get_total_fee()?.as_u64(),
+                root.weight,
+                &self.parent_transactions(&root.transaction),
+            )
+            .fee_rate() /
+            1000;
+        Ok(Some(TransactionDependencyGraph {
+            nodes,
+            package_fee_per_gram,
+        }))
+    }
+
+    /// Returns the fee per gram and weight of each transaction in the pool
+    pub fn fee_per_gram_and_weights(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.tx_by_key.values().map(|tx| (tx.fee_per_byte / 1000, tx.weight))
+    }
+
+    /// Returns the fee per gram of each transaction in the pool that has a kernel in the given block
+    pub fn fees_per_gram_in_block(&self, block: &Block) -> Vec<u64> {
+        block
+            .body
+            .kernels()
+            .iter()
+            .filter_map(|kernel| self.txs_by_signature.get(kernel.excess_sig.get_signature()))
+            .flatten()
+            .filter_map(|key| self.tx_by_key.get(key))
+            .map(|tx| tx.fee_per_byte / 1000)
+            .collect()
+    }
+
+    /// Returns the keys of the transactions in the pool that spend any of the inputs of the given transaction. A
+    /// transaction that is already in the pool is returned as conflicting with itself.
+    pub fn conflicting_transactions(&self, tx: &Transaction) -> Vec<TransactionKey> {
+        let mut keys = tx
+            .body
+            .inputs()
+            .iter()
+            .filter_map(|input| self.txs_by_input.get(&input.output_hash()))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    /// Returns the given transaction keys together with the keys of all transactions in the pool that (directly or
+    /// indirectly) spend their outputs. These are the transactions that become invalid if the given transactions are
+    /// removed.
+    pub fn with_descendants(&self, keys: &[TransactionKey]) -> Vec<TransactionKey> {
+        let mut result = keys.iter().copied().collect::<HashSet<_>>();
+        let mut pending = keys.to_vec();
+        while let Some(key) = pending.pop() {
+            let tx = match self.tx_by_key.get(&key) {
+                Some(tx) => tx,
+                None => continue,
+            };
+            for output in tx.transaction.body.outputs() {
+                for child in self.txs_by_input.get(&output.hash()).into_iter().flatten() {
+                    if result.insert(*child) {
+                        pending.push(*child);
+                    }
+                }
+            }
+        }
+        let mut result = result.into_iter().collect::<Vec<_>>();
+        result.sort_unstable();
+        result
+    }
+
+    /// Returns true if any of the given outputs is created by one of the given transactions
+    pub fn is_created_by_any(&self, outputs: &[HashOutput], keys: &[TransactionKey]) -> bool {
+        outputs
+            .iter()
+            .filter_map(|output| self.txs_by_output.get(output))
+            .flatten()
+            .any(|key| keys.contains(key))
+    }
+
+    /// Returns the total fee and total weight of the given transactions
+    pub fn total_fee_and_weight(&self, keys: &[TransactionKey]) -> Result<(MicroMinotari, u64), UnconfirmedPoolError> {
+        let mut total_fee = 0u64;
+        let mut total_weight = 0u64;
+        for key in keys {
+            let tx = self.tx_by_key.get(key).ok_or(UnconfirmedPoolError::StorageOutofSync)?;
+            total_fee = total_fee.saturating_add(tx.transaction.body.get_total_fee()?.as_u64());
+            total_weight = total_weight.saturating_add(tx.weight);
+        }
+        Ok((MicroMinotari(total_fee), total_weight))
+    }
+
     /// Returns false if there are any inconsistencies in the internal mempool state, otherwise true
     #[cfg(test)]
     fn check_data_consistency(&self) -> bool {
         self.tx_by_priority.len() == self.tx_by_key.len() &&
+            self.packages.len() == self.tx_by_key.len() &&
             self.tx_by_priority
                 .values()
                 .all(|tx_key| self.tx_by_key.contains_key(tx_key)) &&
@@ -827,6 +1125,9 @@ impl UnconfirmedPool {
             self.txs_by_output
                 .values()
                 .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key))) &&
+            self.txs_by_input
+                .values()
+                .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key))) &&
             self.txs_by_unique_id
                 .values()
                 .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key)))
@@ -844,7 +1145,9 @@ impl UnconfirmedPool {
         let (old, new) = shrink_hashmap(&mut self.tx_by_key);
         shrink_hashmap(&mut self.txs_by_signature);
         shrink_hashmap(&mut self.txs_by_output);
+        shrink_hashmap(&mut self.txs_by_input);
         shrink_hashmap(&mut self.txs_by_unique_id);
+        self.drained_insert_epochs = HashMap::new();
 
         if old > new {
             debug!(
@@ -873,11 +1176,13 @@ mod test {
             fee::Fee,
             key_manager::create_memory_db_key_manager,
             tari_amount::MicroMinotari,
-            test_helpers::{TestParams, UtxoTestParams},
+            test_helpers::{spend_utxos, TestParams, UtxoTestParams},
+            transaction_components::OutputFeatures,
             weight::TransactionWeight,
             SenderTransactionProtocol,
         },
         tx,
+        txn_schema,
     };
 
     #[tokio::test]
@@ -942,6 +1247,7 @@ mod test {
             storage_capacity: 4,
             weight_tx_skip_count: 3,
             min_fee: 0,
+            ..Default::default()
         });
 
         let tx_weight = TransactionWeight::latest();
@@ -972,6 +1278,118 @@ mod test {
         assert!(unconfirmed_pool.check_data_consistency());
     }
 
+    #[tokio::test]
+    async fn test_evicts_by_package_fee_rate() {
+        let key_manager = create_memory_db_key_manager();
+        let (parent, _, parent_outputs) =
+            tx!(MicroMinotari(10_000), fee: MicroMinotari(5), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx");
+        let schema = txn_schema!(from: vec![parent_outputs[0].clone()], to: vec![], fee: MicroMinotari(100), lock: 0, features: OutputFeatures::default());
+        let (child, _) = spend_utxos(schema, &key_manager).await;
+        let (tx1, _, _) = tx!(MicroMinotari(10_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+            .expect("Failed to get tx");
+        let (tx2, _, _) = tx!(MicroMinotari(10_000), fee: MicroMinotari(30), inputs: 1, outputs: 1, &key_manager)
+            .expect("Failed to get tx");
+        let (tx3, _, _) = tx!(MicroMinotari(10_000), fee: MicroMinotari(10), inputs: 1, outputs: 1, &key_manager)
+            .expect("Failed to get tx");
+        let parent = Arc::new(parent);
+        let child = Arc::new(child);
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 3,
+            weight_tx_skip_count: 3,
+            min_fee: 0,
+            ..Default::default()
+        });
+        let tx_weight = TransactionWeight::latest();
+        unconfirmed_pool
+            .insert_many(vec![parent.clone(), child.clone(), Arc::new(tx1.clone())], &tx_weight)
+            .expect("Failed to insert many");
+        let stats = unconfirmed_pool.package_stats();
+        assert_eq!(stats.dependent_txs, 1);
+        assert_eq!(stats.max_package_size, 2);
+        // tx1 pays the lowest package fee per gram
+        assert!((19..=21).contains(&stats.min_package_fee_per_gram));
+
+        // The parent pays the lowest fee, but the child pays for it, so tx1 is evicted instead
+        unconfirmed_pool
+            .insert(Arc::new(tx2.clone()), None, &tx_weight)
+            .expect("Failed to insert");
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&child.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx1.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx2.body.kernels()[0].excess_sig));
+
+        // A transaction that pays less than every package is not accepted
+        unconfirmed_pool
+            .insert(Arc::new(tx3.clone()), None, &tx_weight)
+            .expect("Failed to insert");
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+        assert_eq!(unconfirmed_pool.len(), 3);
+
+        // Once the child is gone, the parent is evicted on its own fee rate
+        let child_key = unconfirmed_pool.conflicting_transactions(&child)[0];
+        unconfirmed_pool.remove_transaction(child_key).unwrap();
+        assert_eq!(unconfirmed_pool.package_stats().dependent_txs, 0);
+        unconfirmed_pool
+            .insert(Arc::new(tx3.clone()), None, &tx_weight)
+            .expect("Failed to insert");
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&tx3.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
+    #[tokio::test]
+    async fn test_dependency_graph() {
+        let key_manager = create_memory_db_key_manager();
+        let (parent, _, parent_outputs) =
+            tx!(MicroMinotari(10_000), fee: MicroMinotari(5), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx");
+        let schema = txn_schema!(from: vec![parent_outputs[0].clone()], to: vec![], fee: MicroMinotari(20), lock: 0, features: OutputFeatures::default());
+        let (child, child_outputs) = spend_utxos(schema, &key_manager).await;
+        let schema = txn_schema!(from: vec![child_outputs[0].clone()], to: vec![], fee: MicroMinotari(30), lock: 0, features: OutputFeatures::default());
+        let (grandchild, _) = spend_utxos(schema, &key_manager).await;
+        let (unrelated, _, _) = tx!(MicroMinotari(10_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+            .expect("Failed to get tx");
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        let tx_weight = TransactionWeight::latest();
+        unconfirmed_pool
+            .insert_many(
+                [parent.clone(), unrelated, child.clone(), grandchild.clone()]
+                    .into_iter()
+                    .map(Arc::new),
+                &tx_weight,
+            )
+            .expect("Failed to insert many");
+
+        let excess_sig = |tx: &Transaction| tx.first_kernel_excess_sig().unwrap().clone();
+        let graph = unconfirmed_pool.dependency_graph(&excess_sig(&child)).unwrap().unwrap();
+        let sigs = graph.nodes.iter().map(|n| n.excess_sig.clone()).collect::<Vec<_>>();
+        assert_eq!(sigs, vec![
+            excess_sig(&parent),
+            excess_sig(&child),
+            excess_sig(&grandchild)
+        ]);
+        assert_eq!(graph.root().unwrap().excess_sig, excess_sig(&child));
+        assert_eq!(graph.ancestors().count(), 1);
+        assert_eq!(graph.descendants().count(), 1);
+        assert_eq!(graph.nodes[0].parents, Vec::<usize>::new());
+        assert_eq!(graph.nodes[1].parents, vec![0]);
+        assert_eq!(graph.nodes[2].parents, vec![1]);
+        // The child pays for its parent
+        assert!(graph.package_fee_per_gram > graph.nodes[0].fee_per_gram);
+        assert!(graph.package_fee_per_gram < graph.nodes[1].fee_per_gram);
+
+        let (not_in_pool, _, _) =
+            tx!(MicroMinotari(10_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx");
+        assert!(unconfirmed_pool
+            .dependency_graph(&excess_sig(&not_in_pool))
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_double_spend_inputs() {
         let key_manager = create_memory_db_key_manager();
@@ -1040,6 +1458,7 @@ mod test {
             storage_capacity: 4,
             weight_tx_skip_count: 3,
             min_fee: 0,
+            ..Default::default()
         });
 
         let tx_weight = TransactionWeight::latest();
@@ -1057,6 +1476,15 @@ mod test {
         // Whether tx2 or tx3 is selected is non-deterministic
         assert!(results.retrieved_transactions.contains(&tx2) ^ results.retrieved_transactions.contains(&tx3));
         assert_eq!(results.retrieved_transactions.len(), 2);
+
+        // tx2 and tx3 spend the same input
+        assert_eq!(unconfirmed_pool.conflicting_transactions(&tx1).len(), 1);
+        let conflicts = unconfirmed_pool.conflicting_transactions(&tx3);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(unconfirmed_pool.with_descendants(&conflicts), conflicts);
+        unconfirmed_pool.remove_transaction(conflicts[0]).unwrap();
+        assert_eq!(unconfirmed_pool.conflicting_transactions(&tx3), vec![conflicts[1]]);
+        assert!(unconfirmed_pool.check_data_consistency());
     }
 
     #[tokio::test]
@@ -1100,6 +1528,7 @@ mod test {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
             min_fee: 0,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_many(
@@ -1131,6 +1560,117 @@ mod test {
         assert!(unconfirmed_pool.check_data_consistency());
     }
 
+    #[tokio::test]
+    async fn test_remove_expired_transactions() {
+        let key_manager = create_memory_db_key_manager();
+        let tx1 = Arc::new(
+            tx!(MicroMinotari(10_000), fee: MicroMinotari(50), inputs:2, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let tx2 = Arc::new(
+            tx!(MicroMinotari(10_000), fee: MicroMinotari(20), inputs:3, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+
+        let tx_weight = TransactionWeight::latest();
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            expiry: Duration::from_secs(60),
+            ..Default::default()
+        });
+        unconfirmed_pool
+            .insert_many(vec![tx1.clone(), tx2.clone()], &tx_weight)
+            .expect("Failed to insert many");
+        let insert_epochs = unconfirmed_pool
+            .tx_by_key
+            .values()
+            .map(|tx| tx.insert_epoch)
+            .collect::<Vec<_>>();
+        let first_inserted = *insert_epochs.iter().min().unwrap();
+        let last_inserted = *insert_epochs.iter().max().unwrap();
+
+        let expired = unconfirmed_pool
+            .remove_expired_transactions(first_inserted + 59)
+            .unwrap();
+        assert!(expired.is_empty());
+        assert_eq!(unconfirmed_pool.len(), 2);
+
+        // Revalidated transactions keep their original insert time
+        let drained = unconfirmed_pool.drain_all_mempool_transactions();
+        unconfirmed_pool
+            .insert_many(drained, &tx_weight)
+            .expect("Failed to insert many");
+        let expired = unconfirmed_pool
+            .remove_expired_transactions(last_inserted + 60)
+            .unwrap();
+        assert_eq!(expired.len(), 2);
+        assert!(expired.contains(&tx1));
+        assert!(expired.contains(&tx2));
+        assert_eq!(unconfirmed_pool.len(), 0);
+
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
+    #[tokio::test]
+    async fn test_priority_lanes_boost_burn_transactions() {
+        let key_manager = create_memory_db_key_manager();
+        let tx_weight = TransactionWeight::latest();
+        let standard = Arc::new(
+            tx!(MicroMinotari(10_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let (_, _, outputs) = tx!(MicroMinotari(20_000), fee: MicroMinotari(5), inputs: 1, outputs: 2, &key_manager)
+            .expect("Failed to get tx");
+        let schema = txn_schema!(from: vec![outputs[0].clone()], to: vec![MicroMinotari(1_000)], fee: MicroMinotari(15), lock: 0, features: OutputFeatures::create_burn_output());
+        let burn1 = Arc::new(spend_utxos(schema, &key_manager).await.0);
+        let schema = txn_schema!(from: vec![outputs[1].clone()], to: vec![MicroMinotari(1_000)], fee: MicroMinotari(15), lock: 0, features: OutputFeatures::create_burn_output());
+        let burn2 = Arc::new(spend_utxos(schema, &key_manager).await.0);
+
+        // Without priority lanes the standard transaction, which pays a higher fee, is preferred
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig::default());
+        unconfirmed_pool
+            .insert_many([standard.clone(), burn1.clone()], &tx_weight)
+            .expect("Failed to insert many");
+        let (_, highest_key) = unconfirmed_pool.tx_by_priority.iter().next_back().unwrap();
+        assert_eq!(unconfirmed_pool.tx_by_key[highest_key].transaction, standard);
+
+        let mut unconfirmed_pool =
+            UnconfirmedPool::new(UnconfirmedPoolConfig::default()).with_priority_lanes(PriorityLanesConfig {
+                enabled: true,
+                burn_boost: 50,
+                max_transactions_per_lane: 1,
+                ..Default::default()
+            });
+        unconfirmed_pool
+            .insert_many([standard.clone(), burn1.clone(), burn2.clone()], &tx_weight)
+            .expect("Failed to insert many");
+        let results = unconfirmed_pool
+            .fetch_highest_priority_txs(burn1.calculate_weight(&tx_weight).unwrap())
+            .unwrap();
+        assert_eq!(results.retrieved_transactions, vec![burn1.clone()]);
+
+        // The lane is full, so the second burn transaction is not boosted until the first one is removed
+        let boost = |pool: &UnconfirmedPool, tx: &Arc<Transaction>| {
+            let key = pool.txs_by_signature[tx.body.kernels()[0].excess_sig.get_signature()][0];
+            pool.tx_by_key[&key].boost
+        };
+        assert_eq!(boost(&unconfirmed_pool, &standard), 0);
+        assert_eq!(boost(&unconfirmed_pool, &burn1), 50);
+        assert_eq!(boost(&unconfirmed_pool, &burn2), 0);
+
+        let burn1_key = unconfirmed_pool.txs_by_signature[burn1.body.kernels()[0].excess_sig.get_signature()][0];
+        unconfirmed_pool.remove_transaction(burn1_key).unwrap();
+        let drained = unconfirmed_pool.drain_all_mempool_transactions();
+        unconfirmed_pool
+            .insert_many(drained, &tx_weight)
+            .expect("Failed to insert many");
+        assert_eq!(boost(&unconfirmed_pool, &burn2), 50);
+
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
     #[tokio::test]
     async fn test_discard_double_spend_txs() {
         let key_manager = create_memory_db_key_manager();
@@ -1176,6 +1716,7 @@ mod test {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
             min_fee: 0,
+            ..Default::default()
         });
         unconfirmed_pool
             .insert_many(
@@ -1231,6 +1772,7 @@ mod test {
             storage_capacity: 10,
             weight_tx_skip_count: 3,
             min_fee: 0,
+            ..Default::default()
         });
         let txns = vec![
             Arc::new(tx1.clone()),
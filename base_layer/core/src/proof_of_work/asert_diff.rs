@@ -0,0 +1,299 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+// Portions of the code:
+
+// aserti3-2d difficulty adjustment algorithm
+// Copyright (c) 2020 The Bitcoin Cash Node developers, Mark Lundeberg, Jonathan Toomim
+// MIT License
+// References:
+// https://github.com/bitcoincashorg/bitcoincash.org/blob/master/spec/2020-11-15-asert.md
+
+use std::{collections::VecDeque, convert::TryFrom};
+
+use log::*;
+use tari_utilities::epoch_time::EpochTime;
+
+use crate::proof_of_work::{
+    difficulty::{Difficulty, DifficultyAdjustment},
+    error::DifficultyAdjustmentError,
+};
+
+pub const LOG_TARGET: &str = "c::pow::asert_diff";
+
+/// The number of fractional bits used in the fixed point exponent
+const RADIX_BITS: u32 = 16;
+
+/// Absolutely Scheduled Exponentially Rising Targets (ASERT). The difficulty is adjusted exponentially by how far the
+/// chain is ahead of or behind the target schedule, doubling (or halving) every `half_life` seconds of deviation. The
+/// schedule is measured from a fixed anchor block to the chain tip that the next block builds on, scaling the target
+/// difficulty of the anchor. The window only holds the most recent samples of the algorithm for reporting.
+#[derive(Debug, Clone)]
+pub struct AbsolutelyScheduledExponentiallyRisingTargets {
+    target_difficulties: VecDeque<(EpochTime, Difficulty)>,
+    block_window: usize,
+    target_time: u64,
+    half_life: u64,
+    anchor_height: u64,
+    anchor_timestamp: EpochTime,
+    anchor_difficulty: Option<Difficulty>,
+    chain_tip: Option<(u64, EpochTime)>,
+}
+
+impl AbsolutelyScheduledExponentiallyRisingTargets {
+    /// Initialize a new `AbsolutelyScheduledExponentiallyRisingTargets`
+    pub fn new(
+        block_window: usize,
+        target_time: u64,
+        half_life: u64,
+        anchor_height: u64,
+        anchor_timestamp: EpochTime,
+    ) -> Result<Self, String> {
+        if target_time == 0 {
+            return Err("ASERT expected `target_time` to be greater than 0, but 0 was given".into());
+        }
+        if block_window == 0 {
+            return Err("ASERT expected `block_window` to be greater than 0, but 0 was given".into());
+        }
+        if half_life == 0 {
+            return Err("ASERT expected `half_life` to be greater than 0, but 0 was given".into());
+        }
+        Ok(Self {
+            target_difficulties: VecDeque::with_capacity(block_window + 1),
+            block_window,
+            target_time,
+            half_life,
+            anchor_height,
+            anchor_timestamp,
+            anchor_difficulty: None,
+            chain_tip: None,
+        })
+    }
+
+    /// Sets the target difficulty of the anchor block that the schedule scales
+    pub fn set_anchor_difficulty(&mut self, difficulty: Difficulty) {
+        self.anchor_difficulty = Some(difficulty);
+    }
+
+    /// Sets the height and timestamp of the block that the next block builds on
+    pub fn set_chain_tip(&mut self, height: u64, timestamp: EpochTime) {
+        self.chain_tip = Some((height, timestamp));
+    }
+
+    fn calculate(&self) -> Option<Difficulty> {
+        let anchor_difficulty = self.anchor_difficulty?;
+        let (tip_height, tip_time) = self.chain_tip?;
+        let num_blocks = i128::from(tip_height.checked_sub(self.anchor_height)?);
+
+        let ideal_time = num_blocks * i128::from(self.target_time);
+        let actual_time = i128::from(tip_time.as_u64()) - i128::from(self.anchor_timestamp.as_u64());
+        // A positive exponent raises the difficulty, i.e. the blocks were found faster than the target schedule
+        let exponent = ((ideal_time - actual_time) << RADIX_BITS) / i128::from(self.half_life);
+        let shifts = exponent >> RADIX_BITS;
+        let frac = u128::try_from(exponent & ((1 << RADIX_BITS) - 1)).ok()?;
+        // Cubic approximation of 2^(frac / 2^16) * 2^16, accurate to within 0.013%
+        let factor = (1u128 << RADIX_BITS) +
+            ((195_766_423_245_049 * frac + 971_821_376 * frac * frac + 5_127 * frac * frac * frac + (1 << 47)) >> 48);
+
+        // At most 64 + 17 bits, so it can be shifted left by up to 46 bits without overflow
+        let scaled = u128::from(anchor_difficulty.as_u64()) * factor;
+        let scaled = if shifts < 0 {
+            u32::try_from(-shifts)
+                .ok()
+                .and_then(|s| scaled.checked_shr(s))
+                .unwrap_or(0)
+        } else if shifts <= 46 {
+            scaled << shifts
+        } else {
+            u128::MAX
+        };
+        let target = u64::try_from(scaled >> RADIX_BITS).unwrap_or(u64::MAX);
+        trace!(
+            target: LOG_TARGET,
+            "DiffCalc; t={}; h={}; n={}; ideal={}; actual={}; exponent={}; anchor={}; target={}",
+            self.target_time,
+            self.half_life,
+            num_blocks,
+            ideal_time,
+            actual_time,
+            exponent,
+            anchor_difficulty,
+            target
+        );
+        if target < Difficulty::min().as_u64() {
+            None
+        } else {
+            Difficulty::from_u64(target).ok()
+        }
+    }
+
+    /// Returns the number of samples in the window
+    #[inline]
+    pub fn num_samples(&self) -> usize {
+        self.target_difficulties.len()
+    }
+
+    /// Indicates if the window is full
+    pub fn is_full(&self) -> bool {
+        self.num_samples() == self.block_window + 1
+    }
+
+    /// Returns the timestamps and target difficulties in the window, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = &(EpochTime, Difficulty)> {
+        self.target_difficulties.iter()
+    }
+
+    /// Adds a new timestamp and target difficulty in front of the queue
+    pub fn add_front(&mut self, timestamp: EpochTime, target_difficulty: Difficulty) {
+        if self.is_full() {
+            self.target_difficulties.pop_back();
+        }
+        self.target_difficulties.push_front((timestamp, target_difficulty));
+    }
+
+    /// Adds a new timestamp and target difficulty at the back of the queue
+    pub fn add_back(&mut self, timestamp: EpochTime, target_difficulty: Difficulty) {
+        if self.is_full() {
+            self.target_difficulties.pop_front();
+        }
+        self.target_difficulties.push_back((timestamp, target_difficulty));
+    }
+}
+
+impl DifficultyAdjustment for AbsolutelyScheduledExponentiallyRisingTargets {
+    fn add(&mut self, timestamp: EpochTime, target_difficulty: Difficulty) -> Result<(), DifficultyAdjustmentError> {
+        self.add_back(timestamp, target_difficulty);
+        Ok(())
+    }
+
+    fn add_front(&mut self, timestamp: EpochTime, target_difficulty: Difficulty) {
+        self.add_front(timestamp, target_difficulty);
+    }
+
+    fn is_full(&self) -> bool {
+        self.is_full()
+    }
+
+    fn num_samples(&self) -> usize {
+        self.num_samples()
+    }
+
+    fn get_difficulty(&self) -> Option<Difficulty> {
+        self.calculate()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn asert_with_solve_time(solve_time: u64, num_blocks: u64) -> AbsolutelyScheduledExponentiallyRisingTargets {
+        let mut asert =
+            AbsolutelyScheduledExponentiallyRisingTargets::new(90, 120, 3600, 0, EpochTime::from(1_000)).unwrap();
+        asert.set_anchor_difficulty(Difficulty::from_u64(1_000_000).unwrap());
+        asert.set_chain_tip(num_blocks, EpochTime::from(1_000 + num_blocks * solve_time));
+        asert
+    }
+
+    #[test]
+    fn asert_rejects_zero_parameters() {
+        assert!(AbsolutelyScheduledExponentiallyRisingTargets::new(0, 120, 3600, 0, EpochTime::from(0)).is_err());
+        assert!(AbsolutelyScheduledExponentiallyRisingTargets::new(90, 0, 3600, 0, EpochTime::from(0)).is_err());
+        assert!(AbsolutelyScheduledExponentiallyRisingTargets::new(90, 120, 0, 0, EpochTime::from(0)).is_err());
+    }
+
+    #[test]
+    fn asert_needs_the_anchor_and_chain_tip() {
+        let mut asert =
+            AbsolutelyScheduledExponentiallyRisingTargets::new(90, 120, 3600, 10, EpochTime::from(1_000)).unwrap();
+        assert_eq!(asert.get_difficulty(), None);
+        asert.set_chain_tip(20, EpochTime::from(2_200));
+        assert_eq!(asert.get_difficulty(), None);
+        asert.set_anchor_difficulty(Difficulty::from_u64(1_000_000).unwrap());
+        assert_eq!(
+            asert.get_difficulty().unwrap(),
+            Difficulty::from_u64(1_000_000).unwrap()
+        );
+        // The chain tip must not be below the anchor
+        asert.set_chain_tip(9, EpochTime::from(880));
+        assert_eq!(asert.get_difficulty(), None);
+    }
+
+    #[test]
+    fn asert_keeps_difficulty_on_schedule() {
+        let asert = asert_with_solve_time(120, 10);
+        assert_eq!(
+            asert.get_difficulty().unwrap(),
+            Difficulty::from_u64(1_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn asert_halves_difficulty_every_half_life_behind_schedule() {
+        // 30 blocks at 240s each are 3600s (one half life) behind schedule
+        let asert = asert_with_solve_time(240, 30);
+        assert_eq!(asert.get_difficulty().unwrap(), Difficulty::from_u64(500_000).unwrap());
+        // Two half lives
+        let asert = asert_with_solve_time(240, 60);
+        assert_eq!(asert.get_difficulty().unwrap(), Difficulty::from_u64(250_000).unwrap());
+    }
+
+    #[test]
+    fn asert_doubles_difficulty_every_half_life_ahead_of_schedule() {
+        // 60 blocks at 60s each are 3600s (one half life) ahead of schedule
+        let asert = asert_with_solve_time(60, 60);
+        assert_eq!(
+            asert.get_difficulty().unwrap(),
+            Difficulty::from_u64(2_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn asert_interpolates_between_half_lives() {
+        // Half a half life ahead of schedule is a factor of sqrt(2)
+        let asert = asert_with_solve_time(60, 30);
+        let difficulty = asert.get_difficulty().unwrap().as_u64();
+        assert!((1_414_000..=1_414_500).contains(&difficulty), "{}", difficulty);
+    }
+
+    #[test]
+    fn asert_is_measured_from_the_anchor() {
+        let mut asert = asert_with_solve_time(120, 10);
+        // The samples in the window do not move the schedule, even when they are far off the target time
+        for i in 0..=90 {
+            asert.add_back(
+                EpochTime::from(1_000 + i * 10),
+                Difficulty::from_u64(5_000_000).unwrap(),
+            );
+        }
+        assert!(asert.is_full());
+        assert_eq!(
+            asert.get_difficulty().unwrap(),
+            Difficulty::from_u64(1_000_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn asert_saturates_extreme_values() {
+        let mut asert =
+            AbsolutelyScheduledExponentiallyRisingTargets::new(5, 120, 1, 0, EpochTime::from(1_000)).unwrap();
+        asert.set_anchor_difficulty(Difficulty::from_u64(1_000_000).unwrap());
+        asert.set_chain_tip(1, EpochTime::from(1_001));
+        assert_eq!(asert.get_difficulty().unwrap(), Difficulty::max());
+        asert.set_chain_tip(2, EpochTime::from(1_000_000));
+        assert_eq!(asert.get_difficulty(), None);
+    }
+
+    #[test]
+    fn asert_window_is_bounded() {
+        let mut asert =
+            AbsolutelyScheduledExponentiallyRisingTargets::new(2, 120, 3600, 0, EpochTime::from(0)).unwrap();
+        for i in 0..5 {
+            asert.add_back(EpochTime::from(i * 120), Difficulty::from_u64(100).unwrap());
+        }
+        assert!(asert.is_full());
+        assert_eq!(asert.num_samples(), 3);
+        asert.add_front(EpochTime::from(0), Difficulty::from_u64(100).unwrap());
+        assert_eq!(asert.num_samples(), 3);
+    }
+}
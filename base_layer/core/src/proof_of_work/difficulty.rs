@@ -182,10 +182,52 @@ pub trait DifficultyAdjustment {
         accumulated_difficulty: Difficulty,
     ) -> Result<(), DifficultyAdjustmentError>;
 
+    /// Adds an older block timestamp and target difficulty, used when the history is loaded from the tip backwards.
+    /// The newest data point is dropped if the window is full.
+    fn add_front(&mut self, timestamp: EpochTime, target_difficulty: Difficulty);
+
+    /// Returns true if the algorithm has all the data points it uses to calculate the difficulty
+    fn is_full(&self) -> bool;
+
+    /// Returns the number of data points held by the algorithm
+    fn num_samples(&self) -> usize;
+
     /// Return the calculated target difficulty for the next block.
     fn get_difficulty(&self) -> Option<Difficulty>;
 }
 
+/// The difficulty adjustment algorithm used for a consensus epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultyAlgorithm {
+    /// Linear weighted moving average (LWMA-1)
+    Lwma,
+    /// Absolutely scheduled exponentially rising targets (aserti3-2d), with the given half life in seconds. The
+    /// schedule is measured from the block at `anchor_height`, which is expected at `anchor_timestamp`. The anchor
+    /// must be below the height that the algorithm becomes effective from.
+    Asert {
+        half_life: u64,
+        anchor_height: u64,
+        anchor_timestamp: u64,
+    },
+}
+
+impl fmt::Display for DifficultyAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifficultyAlgorithm::Lwma => write!(f, "LWMA"),
+            DifficultyAlgorithm::Asert {
+                half_life,
+                anchor_height,
+                anchor_timestamp,
+            } => write!(
+                f,
+                "ASERT (half life {}s, anchored at height {} and timestamp {})",
+                half_life, anchor_height, anchor_timestamp
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use primitive_types::U256;
@@ -0,0 +1,197 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::collections::VecDeque;
+
+use tari_utilities::epoch_time::EpochTime;
+
+use crate::proof_of_work::{Difficulty, PowAlgorithm};
+
+/// Statistics for a single proof of work algorithm over a window of recent blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowAlgorithmStats {
+    pub pow_algo: PowAlgorithm,
+    /// The number of blocks in the window that were mined with this algorithm
+    pub num_blocks: usize,
+    /// The estimated hash rate in hashes per second: the total difficulty of the blocks divided by the time taken to
+    /// mine them
+    pub estimated_hash_rate: u64,
+    /// The mean time in seconds between consecutive blocks of this algorithm
+    pub average_solve_time: f64,
+    /// The population variance of the solve times, in seconds squared
+    pub solve_time_variance: f64,
+    /// The fraction (0 to 1) of the blocks in the window that were mined with this algorithm
+    pub share: f64,
+}
+
+/// Per-algorithm difficulty statistics over a window of recent blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyStats {
+    /// The number of blocks, of any algorithm, the statistics were calculated over
+    pub num_blocks: usize,
+    pub sha3x: PowAlgorithmStats,
+    pub randomx: PowAlgorithmStats,
+}
+
+impl DifficultyStats {
+    pub fn get(&self, pow_algo: PowAlgorithm) -> &PowAlgorithmStats {
+        match pow_algo {
+            PowAlgorithm::Sha3x => &self.sha3x,
+            PowAlgorithm::RandomX => &self.randomx,
+        }
+    }
+}
+
+/// A rolling window of the most recent blocks (of any algorithm), used to calculate [DifficultyStats]. Blocks must be
+/// added in chain order.
+#[derive(Debug, Clone)]
+pub struct DifficultyStatsWindow {
+    window: usize,
+    blocks: VecDeque<(PowAlgorithm, EpochTime, Difficulty)>,
+}
+
+impl DifficultyStatsWindow {
+    /// Create a window that holds the last `window` blocks
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            blocks: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Adds the next block, removing the oldest block if the window is full
+    pub fn add(&mut self, pow_algo: PowAlgorithm, timestamp: EpochTime, target_difficulty: Difficulty) {
+        if self.window == 0 {
+            return;
+        }
+        if self.blocks.len() == self.window {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back((pow_algo, timestamp, target_difficulty));
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn stats(&self) -> DifficultyStats {
+        DifficultyStats {
+            num_blocks: self.blocks.len(),
+            sha3x: self.algorithm_stats(PowAlgorithm::Sha3x),
+            randomx: self.algorithm_stats(PowAlgorithm::RandomX),
+        }
+    }
+
+    fn algorithm_stats(&self, pow_algo: PowAlgorithm) -> PowAlgorithmStats {
+        let blocks = self
+            .blocks
+            .iter()
+            .filter(|(algo, _, _)| *algo == pow_algo)
+            .map(|(_, timestamp, difficulty)| (timestamp.as_u64(), difficulty.as_u64()))
+            .collect::<Vec<_>>();
+        let solve_times = blocks
+            .windows(2)
+            .map(|pair| pair[1].0.saturating_sub(pair[0].0) as f64)
+            .collect::<Vec<_>>();
+
+        let (average_solve_time, solve_time_variance) = if solve_times.is_empty() {
+            (0.0, 0.0)
+        } else {
+            let n = solve_times.len() as f64;
+            let mean = solve_times.iter().sum::<f64>() / n;
+            let variance = solve_times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+            (mean, variance)
+        };
+
+        // The difficulty of the first block is excluded because the time taken to mine it is not in the window
+        let total_time = match (blocks.first(), blocks.last()) {
+            (Some(first), Some(last)) => last.0.saturating_sub(first.0),
+            _ => 0,
+        };
+        let estimated_hash_rate = if total_time == 0 {
+            0
+        } else {
+            let total_difficulty = blocks.iter().skip(1).fold(0u128, |acc, (_, d)| acc + u128::from(*d));
+            u64::try_from(total_difficulty / u128::from(total_time)).unwrap_or(u64::MAX)
+        };
+
+        let share = if self.blocks.is_empty() {
+            0.0
+        } else {
+            blocks.len() as f64 / self.blocks.len() as f64
+        };
+
+        PowAlgorithmStats {
+            pow_algo,
+            num_blocks: blocks.len(),
+            estimated_hash_rate,
+            average_solve_time,
+            solve_time_variance,
+            share,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn difficulty(d: u64) -> Difficulty {
+        Difficulty::from_u64(d).unwrap()
+    }
+
+    #[test]
+    fn it_returns_empty_stats_for_an_empty_window() {
+        let stats = DifficultyStatsWindow::new(10).stats();
+        assert_eq!(stats.num_blocks, 0);
+        assert_eq!(stats.sha3x.num_blocks, 0);
+        assert_eq!(stats.sha3x.estimated_hash_rate, 0);
+        assert_eq!(stats.randomx.share, 0.0);
+    }
+
+    #[test]
+    fn it_calculates_per_algorithm_stats() {
+        let mut window = DifficultyStatsWindow::new(10);
+        // Sha3x blocks 100s apart, RandomX blocks alternating 100s and 300s apart
+        window.add(PowAlgorithm::Sha3x, 0.into(), difficulty(1_000));
+        window.add(PowAlgorithm::RandomX, 50.into(), difficulty(500));
+        window.add(PowAlgorithm::Sha3x, 100.into(), difficulty(1_000));
+        window.add(PowAlgorithm::RandomX, 150.into(), difficulty(500));
+        window.add(PowAlgorithm::Sha3x, 200.into(), difficulty(1_000));
+        window.add(PowAlgorithm::RandomX, 450.into(), difficulty(500));
+
+        let stats = window.stats();
+        assert_eq!(stats.num_blocks, 6);
+        let sha3x = stats.get(PowAlgorithm::Sha3x);
+        assert_eq!(sha3x.num_blocks, 3);
+        assert_eq!(sha3x.estimated_hash_rate, 10);
+        assert_eq!(sha3x.average_solve_time, 100.0);
+        assert_eq!(sha3x.solve_time_variance, 0.0);
+        assert_eq!(sha3x.share, 0.5);
+        let randomx = stats.get(PowAlgorithm::RandomX);
+        assert_eq!(randomx.estimated_hash_rate, 2);
+        assert_eq!(randomx.average_solve_time, 200.0);
+        assert_eq!(randomx.solve_time_variance, 10_000.0);
+        assert_eq!(randomx.share, 0.5);
+    }
+
+    #[test]
+    fn it_only_keeps_the_most_recent_blocks() {
+        let mut window = DifficultyStatsWindow::new(4);
+        for i in 0..4 {
+            window.add(PowAlgorithm::Sha3x, (i * 100).into(), difficulty(1_000));
+        }
+        for i in 4..7 {
+            window.add(PowAlgorithm::RandomX, (i * 100).into(), difficulty(1_000));
+        }
+        let stats = window.stats();
+        assert_eq!(window.len(), 4);
+        assert_eq!(stats.sha3x.num_blocks, 1);
+        assert_eq!(stats.randomx.num_blocks, 3);
+        assert_eq!(stats.randomx.share, 0.75);
+    }
+}
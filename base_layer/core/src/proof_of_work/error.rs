@@ -26,7 +26,7 @@ use thiserror::Error;
 use crate::proof_of_work::monero_rx::MergeMineError;
 use crate::{
     common::{BanPeriod, BanReason},
-    proof_of_work::Difficulty,
+    proof_of_work::{Difficulty, PowAlgorithm},
 };
 
 /// Errors that can occur when validating a proof of work
@@ -42,6 +42,8 @@ pub enum PowError {
     AchievedDifficultyTooLow { target: Difficulty, achieved: Difficulty },
     #[error("Invalid target difficulty (expected: {expected}, got: {got})")]
     InvalidTargetDifficulty { expected: Difficulty, got: Difficulty },
+    #[error("Proof of work algorithm {pow_algo} is not active at height {height}")]
+    AlgorithmNotActive { pow_algo: PowAlgorithm, height: u64 },
     #[cfg(feature = "base_node")]
     #[error("Invalid merge mining data or operation: {0}")]
     MergeMineError(#[from] MergeMineError),
@@ -54,7 +56,8 @@ impl PowError {
             err @ PowError::AchievedDifficultyBelowMin |
             err @ PowError::Sha3HeaderNonEmptyPowBytes |
             err @ PowError::AchievedDifficultyTooLow { .. } |
-            err @ PowError::InvalidTargetDifficulty { .. } => Some(BanReason {
+            err @ PowError::InvalidTargetDifficulty { .. } |
+            err @ PowError::AlgorithmNotActive { .. } => Some(BanReason {
                 reason: err.to_string(),
                 ban_duration: BanPeriod::Long,
             }),
@@ -164,6 +164,11 @@ impl LinearWeightedMovingAverage {
         self.block_window
     }
 
+    /// Returns the timestamps and target difficulties in the window, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = &(EpochTime, Difficulty)> {
+        self.target_difficulties.iter()
+    }
+
     /// Adds a new timestamp and target difficulty in front of the queue
     pub fn add_front(&mut self, timestamp: EpochTime, target_difficulty: Difficulty) {
         if self.is_full() {
@@ -187,6 +192,18 @@ impl DifficultyAdjustment for LinearWeightedMovingAverage {
         Ok(())
     }
 
+    fn add_front(&mut self, timestamp: EpochTime, target_difficulty: Difficulty) {
+        self.add_front(timestamp, target_difficulty);
+    }
+
+    fn is_full(&self) -> bool {
+        self.is_full()
+    }
+
+    fn num_samples(&self) -> usize {
+        self.num_samples()
+    }
+
     fn get_difficulty(&self) -> Option<Difficulty> {
         self.calculate()
     }
@@ -24,7 +24,7 @@
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub(crate) mod difficulty;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
-pub use difficulty::{Difficulty, DifficultyAdjustment};
+pub use difficulty::{Difficulty, DifficultyAdjustment, DifficultyAlgorithm};
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub(crate) mod accumulated_difficulty;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
@@ -55,13 +55,19 @@ mod proof_of_work_algorithm;
 #[cfg(any(feature = "base_node", feature = "transactions"))]
 pub use proof_of_work_algorithm::PowAlgorithm;
 
+/// Crates for proof of work pow_registry
+#[cfg(feature = "base_node")]
+mod pow_registry;
+#[cfg(feature = "base_node")]
+pub use pow_registry::{PowAlgorithmHooks, PowChainData, PowContext, PowRegistry, RandomXPowHooks, Sha3xPowHooks};
+
 /// Crates for proof of work sha3_pow
 #[cfg(feature = "base_node")]
 mod sha3x_pow;
-#[cfg(feature = "base_node")]
-pub use sha3x_pow::sha3x_difficulty;
 #[cfg(all(test, feature = "base_node"))]
 pub use sha3x_pow::test as sha3x_test;
+#[cfg(feature = "base_node")]
+pub use sha3x_pow::{sha3x_blob_difficulty, sha3x_difficulty, sha3x_difficulty_batch, sha3x_header_blob, Sha3xHasher};
 
 /// Crates for proof of work target_difficulty
 mod target_difficulty;
@@ -73,9 +79,18 @@ mod target_difficulty_window;
 #[cfg(feature = "base_node")]
 pub use target_difficulty_window::TargetDifficultyWindow;
 
+/// Crates for proof of work difficulty_stats
+#[cfg(feature = "base_node")]
+mod difficulty_stats;
+#[cfg(feature = "base_node")]
+pub use difficulty_stats::{DifficultyStats, DifficultyStatsWindow, PowAlgorithmStats};
+
 /// Crates for proof of work lwma_diff
 pub mod lwma_diff;
 
+/// Crates for proof of work asert_diff
+pub mod asert_diff;
+
 /// Crates for proof of work randomx_factory
 #[cfg(feature = "base_node")]
 pub mod randomx_factory;
@@ -50,6 +50,14 @@ pub enum MergeMineError {
     DifficultyError(#[from] DifficultyError),
     #[error("Cannot mine with 0 aux chains")]
     ZeroAuxChains,
+    #[error("Monero block blob is {size} bytes, which exceeds the limit of {max} bytes")]
+    BlobTooLarge { size: usize, max: usize },
+    #[error("Monero block contains {count} transactions, which exceeds the limit of {max}")]
+    TooManyTransactions { count: usize, max: usize },
+    #[error("Monero coinbase extra field is {size} bytes, which exceeds the limit of {max} bytes")]
+    ExtraFieldTooLarge { size: usize, max: usize },
+    #[error("Monero coinbase extra field contains {count} tags, which exceeds the limit of {max}")]
+    TooManyExtraTags { count: usize, max: usize },
 }
 
 impl MergeMineError {
@@ -62,7 +70,11 @@ impl MergeMineError {
             err @ MergeMineError::ValidationError(_) |
             err @ MergeMineError::InvalidMerkleRoot |
             err @ MergeMineError::DifficultyError(_) |
-            err @ MergeMineError::HexError(_) => Some(BanReason {
+            err @ MergeMineError::HexError(_) |
+            err @ MergeMineError::BlobTooLarge { .. } |
+            err @ MergeMineError::TooManyTransactions { .. } |
+            err @ MergeMineError::ExtraFieldTooLarge { .. } |
+            err @ MergeMineError::TooManyExtraTags { .. } => Some(BanReason {
                 reason: err.to_string(),
                 ban_duration: BanPeriod::Long,
             }),
@@ -20,21 +20,28 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryFrom, io, io::Write, ops::Deref};
+use std::{convert::TryFrom, fmt, io, io::Write, marker::PhantomData, ops::Deref, str::FromStr};
 
 use borsh::{BorshDeserialize, BorshSerialize};
-use tari_utilities::{ByteArray, ByteArrayError};
+use serde::de::{Error as DeError, SeqAccess, Visitor};
+use tari_utilities::{
+    hex::{Hex, HexError},
+    ByteArray,
+    ByteArrayError,
+};
 
-const MAX_ARR_SIZE: usize = 63;
+/// The default capacity of a [FixedByteArray], large enough for the RandomX key
+pub const MAX_ARR_SIZE: usize = 63;
 
-/// A fixed size byte array for RandomX that can be serialized and deserialized using Borsh.
+/// A fixed capacity byte array for RandomX that can be serialized and deserialized using Borsh and serde. The
+/// capacity `N` may be at most 255 bytes, since the length is encoded as a single byte.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct FixedByteArray {
-    elems: [u8; MAX_ARR_SIZE],
+pub struct FixedByteArray<const N: usize = MAX_ARR_SIZE> {
+    elems: [u8; N],
     len: u8,
 }
 
-impl BorshSerialize for FixedByteArray {
+impl<const N: usize> BorshSerialize for FixedByteArray<N> {
     fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
         self.len.serialize(writer)?;
         let data = self.as_slice();
@@ -45,26 +52,101 @@ impl BorshSerialize for FixedByteArray {
     }
 }
 
-impl BorshDeserialize for FixedByteArray {
+impl<const N: usize> BorshDeserialize for FixedByteArray<N> {
     fn deserialize_reader<R>(reader: &mut R) -> Result<Self, io::Error>
     where R: io::Read {
-        let len = u8::deserialize_reader(reader)? as usize;
-        if len > MAX_ARR_SIZE {
+        let len = u8::deserialize_reader(reader)?;
+        if usize::from(len) > N {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
-                format!("length exceeded maximum of 63-bytes for FixedByteArray: {}", len),
+                format!("length exceeded maximum of {}-bytes for FixedByteArray: {}", N, len),
             ));
         }
-        let mut bytes = Vec::with_capacity(len);
-        for _ in 0..len {
-            bytes.push(u8::deserialize_reader(reader)?);
+        // Read the bytes straight into the array without an intermediate allocation. Any bytes that are available are
+        // consumed from the reader before an unexpected EOF is reported.
+        let mut arr = Self::default();
+        let mut filled = 0;
+        while filled < usize::from(len) {
+            match reader.read(&mut arr.elems[filled..usize::from(len)]) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!(
+                            "expected {} bytes for FixedByteArray but only {} were available",
+                            len, filled
+                        ),
+                    ))
+                },
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
         }
-        // This unwrap should never fail, the len is checked above.
-        Ok(Self::from_canonical_bytes(bytes.as_bytes()).unwrap())
+        arr.len = len;
+        Ok(arr)
     }
 }
 
-impl FixedByteArray {
+/// Serializes as a hex string for human-readable formats (e.g. JSON) and as raw bytes otherwise.
+impl<const N: usize> serde::Serialize for FixedByteArray<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.to_hex())
+        } else {
+            serializer.serialize_bytes(self.as_slice())
+        }
+    }
+}
+
+impl<'de, const N: usize> serde::Deserialize<'de> for FixedByteArray<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(FixedByteArrayVisitor::<N>(PhantomData))
+        } else {
+            deserializer.deserialize_bytes(FixedByteArrayVisitor::<N>(PhantomData))
+        }
+    }
+}
+
+struct FixedByteArrayVisitor<const N: usize>(PhantomData<[u8; N]>);
+
+impl<'de, const N: usize> Visitor<'de> for FixedByteArrayVisitor<N> {
+    type Value = FixedByteArray<N>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a byte array or hex string of at most {} bytes", N)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where E: DeError {
+        FixedByteArray::from_hex(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where E: DeError {
+        FixedByteArray::from_canonical_bytes(v).map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        let mut arr = FixedByteArray::<N>::new();
+        while let Some(byte) = seq.next_element::<u8>()? {
+            if arr.is_full() {
+                return Err(A::Error::invalid_length(N + 1, &self));
+            }
+            arr.elems[arr.len()] = byte;
+            arr.len += 1;
+        }
+        Ok(arr)
+    }
+}
+
+impl<const N: usize> FixedByteArray<N> {
+    /// Fails to compile (when used) if the capacity cannot be represented by the single length byte
+    const CAPACITY_CHECK: () = assert!(N <= u8::MAX as usize, "FixedByteArray capacity must be at most 255");
+
     /// Create a new FixedByteArray with the preset length. The array will be zeroed.
     pub fn new() -> Self {
         Default::default()
@@ -75,10 +157,16 @@ impl FixedByteArray {
         &self[..self.len()]
     }
 
+    /// Returns the maximum number of bytes the array can hold.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
     /// Returns true if the array is full.
     #[inline]
     pub fn is_full(&self) -> bool {
-        self.len() == MAX_ARR_SIZE
+        self.len() == N
     }
 
     /// Returns the length of the array.
@@ -94,7 +182,7 @@ impl FixedByteArray {
     }
 }
 
-impl Deref for FixedByteArray {
+impl<const N: usize> Deref for FixedByteArray<N> {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
@@ -102,27 +190,43 @@ impl Deref for FixedByteArray {
     }
 }
 
-#[allow(clippy::derivable_impls)]
-impl Default for FixedByteArray {
+impl<const N: usize> Default for FixedByteArray<N> {
     fn default() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAPACITY_CHECK;
         Self {
-            elems: [0u8; MAX_ARR_SIZE],
+            elems: [0u8; N],
             len: 0,
         }
     }
 }
 
-impl ByteArray for FixedByteArray {
+impl<const N: usize> fmt::Display for FixedByteArray<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl<const N: usize> FromStr for FixedByteArray<N> {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl<const N: usize> ByteArray for FixedByteArray<N> {
     fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
-        if bytes.len() > MAX_ARR_SIZE {
+        if bytes.len() > N {
             return Err(ByteArrayError::IncorrectLength {});
         }
 
         let len = u8::try_from(bytes.len()).map_err(|_| ByteArrayError::IncorrectLength {})?;
 
-        let mut elems = [0u8; MAX_ARR_SIZE];
-        elems[..len as usize].copy_from_slice(&bytes[..len as usize]);
-        Ok(Self { elems, len })
+        let mut arr = Self::default();
+        arr.elems[..len as usize].copy_from_slice(&bytes[..len as usize]);
+        arr.len = len;
+        Ok(arr)
     }
 
     fn as_bytes(&self) -> &[u8] {
@@ -137,13 +241,14 @@ mod test {
     #[test]
     fn assert_size() {
         assert_eq!(std::mem::size_of::<FixedByteArray>(), MAX_ARR_SIZE + 1);
+        assert_eq!(std::mem::size_of::<FixedByteArray<32>>(), 32 + 1);
     }
 
     #[test]
     fn from_bytes() {
-        let empty = FixedByteArray::from_canonical_bytes(&[]).unwrap();
+        let empty = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[]).unwrap();
         assert_eq!(empty.len(), 0);
-        let arr = FixedByteArray::from_canonical_bytes(&[1u8][..]).unwrap();
+        let arr = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[1u8][..]).unwrap();
         assert_eq!(arr.len(), 1);
         assert!(arr.iter().all(|b| *b == 1));
         // Iterates only up to len
@@ -154,31 +259,31 @@ mod test {
         }
         assert!(used);
 
-        let arr = FixedByteArray::from_canonical_bytes(&[1u8; 63][..]).unwrap();
+        let arr = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[1u8; 63][..]).unwrap();
         assert_eq!(arr.len(), 63);
         assert!(arr.iter().all(|b| *b == 1));
 
-        FixedByteArray::from_canonical_bytes(&[1u8; 64][..]).unwrap_err();
+        FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[1u8; 64][..]).unwrap_err();
     }
 
     #[test]
     fn capacity_overflow_does_not_panic() {
         let data = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
-        let _result = FixedByteArray::deserialize(&mut data.as_slice()).unwrap_err();
+        let _result = FixedByteArray::<MAX_ARR_SIZE>::deserialize(&mut data.as_slice()).unwrap_err();
     }
 
     #[test]
     fn length_check() {
         let mut buf = [u8::try_from(MAX_ARR_SIZE).unwrap(); MAX_ARR_SIZE + 1];
-        let fixed_byte_array = FixedByteArray::deserialize(&mut buf.as_slice()).unwrap();
+        let fixed_byte_array = FixedByteArray::<MAX_ARR_SIZE>::deserialize(&mut buf.as_slice()).unwrap();
         assert_eq!(fixed_byte_array.len(), MAX_ARR_SIZE);
         buf[0] += 1;
-        FixedByteArray::deserialize(&mut buf.as_slice()).unwrap_err();
+        FixedByteArray::<MAX_ARR_SIZE>::deserialize(&mut buf.as_slice()).unwrap_err();
     }
 
     #[test]
     fn test_borsh_de_serialization() {
-        let fixed_byte_array = FixedByteArray::from_canonical_bytes(&[5, 6, 7]).unwrap();
+        let fixed_byte_array = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[5, 6, 7]).unwrap();
         let mut buf = Vec::new();
         fixed_byte_array.serialize(&mut buf).unwrap();
         buf.extend_from_slice(&[1, 2, 3]);
@@ -192,8 +297,100 @@ mod test {
         let mut buf = Vec::new();
         buf.extend_from_slice(&[3, 1, 1]);
         let buf = &mut buf.as_slice();
-        let result = FixedByteArray::deserialize(buf);
+        let result = FixedByteArray::<MAX_ARR_SIZE>::deserialize(buf);
         assert!(result.is_err());
         assert_eq!(buf, &[1u8; 0]);
     }
+
+    #[test]
+    fn it_supports_other_capacities() {
+        let arr = FixedByteArray::<32>::from_canonical_bytes(&[1u8; 32][..]).unwrap();
+        assert_eq!(arr.len(), 32);
+        assert_eq!(arr.capacity(), 32);
+        assert!(arr.is_full());
+        FixedByteArray::<32>::from_canonical_bytes(&[1u8; 33][..]).unwrap_err();
+
+        let mut buf = Vec::new();
+        arr.serialize(&mut buf).unwrap();
+        assert_eq!(FixedByteArray::<32>::deserialize(&mut buf.as_slice()).unwrap(), arr);
+        // A larger array cannot be decoded into a smaller one
+        let arr = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[1u8; 33][..]).unwrap();
+        let mut buf = Vec::new();
+        arr.serialize(&mut buf).unwrap();
+        FixedByteArray::<32>::deserialize(&mut buf.as_slice()).unwrap_err();
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let arr = FixedByteArray::<32>::from_canonical_bytes(&[9u8; 20][..]).unwrap();
+        let encoded = bincode::serialize(&arr).unwrap();
+        let decoded: FixedByteArray<32> = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded, arr);
+
+        let too_long =
+            bincode::serialize(&FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[9u8; 33]).unwrap()).unwrap();
+        bincode::deserialize::<FixedByteArray<32>>(&too_long).unwrap_err();
+    }
+
+    #[test]
+    fn test_hex_display_and_from_str() {
+        let arr = FixedByteArray::<32>::from_canonical_bytes(&[0xab, 0x01, 0xff]).unwrap();
+        assert_eq!(arr.to_string(), "ab01ff");
+        assert_eq!("ab01ff".parse::<FixedByteArray<32>>().unwrap(), arr);
+        assert_eq!(FixedByteArray::<32>::new().to_string(), "");
+        "zz".parse::<FixedByteArray<32>>().unwrap_err();
+        "00".repeat(33).parse::<FixedByteArray<32>>().unwrap_err();
+    }
+
+    #[test]
+    fn test_json_hex_round_trip() {
+        let arr = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&[0xab, 0x01, 0xff]).unwrap();
+        let json = serde_json::to_string(&arr).unwrap();
+        assert_eq!(json, "\"ab01ff\"");
+        let decoded: FixedByteArray = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, arr);
+        serde_json::from_str::<FixedByteArray<2>>(&json).unwrap_err();
+        serde_json::from_str::<FixedByteArray>("\"not hex\"").unwrap_err();
+    }
+
+    mod quicktest {
+        use borsh::{BorshDeserialize, BorshSerialize};
+        use quickcheck::{quickcheck, TestResult};
+        use tari_utilities::ByteArray;
+
+        use super::super::{FixedByteArray, MAX_ARR_SIZE};
+
+        #[test]
+        fn test_borsh_round_trip() {
+            fn round_trip(bytes: Vec<u8>) -> TestResult {
+                if bytes.len() > MAX_ARR_SIZE {
+                    return TestResult::discard();
+                }
+                let arr = FixedByteArray::<MAX_ARR_SIZE>::from_canonical_bytes(&bytes).unwrap();
+                let mut buf = Vec::new();
+                arr.serialize(&mut buf).unwrap();
+                let decoded = FixedByteArray::<MAX_ARR_SIZE>::deserialize(&mut buf.as_slice()).unwrap();
+                TestResult::from_bool(decoded == arr && decoded.as_slice() == bytes.as_slice())
+            }
+            quickcheck(round_trip as fn(Vec<u8>) -> TestResult)
+        }
+
+        #[test]
+        fn test_borsh_deserialize_arbitrary_input() {
+            fn arbitrary_input(bytes: Vec<u8>) -> bool {
+                let buf = &mut bytes.as_slice();
+                match FixedByteArray::<32>::deserialize(buf) {
+                    // A successful decode must consume exactly the length prefix and `len` bytes
+                    Ok(arr) => {
+                        usize::from(bytes[0]) == arr.len() &&
+                            arr.len() <= 32 &&
+                            arr.as_slice() == &bytes[1..=arr.len()] &&
+                            buf.len() == bytes.len() - arr.len() - 1
+                    },
+                    Err(_) => bytes.is_empty() || usize::from(bytes[0]) > 32 || bytes.len() <= usize::from(bytes[0]),
+                }
+            }
+            quickcheck(arbitrary_input as fn(Vec<u8>) -> bool)
+        }
+    }
 }
@@ -63,8 +63,18 @@ pub fn randomx_difficulty(
     let monero_pow_data = verify_header(header, genesis_block_hash, consensus)?;
     debug!(target: LOG_TARGET, "Valid Monero data: {}", monero_pow_data);
     let blockhashing_blob = monero_pow_data.to_blockhashing_blob();
-    let vm = randomx_factory.create(monero_pow_data.randomx_key())?;
-    get_random_x_difficulty(&blockhashing_blob, &vm).map(|(diff, _)| diff)
+    randomx_blob_difficulty(&blockhashing_blob, monero_pow_data.randomx_key(), randomx_factory)
+}
+
+/// Calculates the achieved RandomX difficulty of a Monero block hashing blob with the given RandomX key. The caller is
+/// responsible for checking that the blob commits to the Tari header, see [verify_header].
+pub fn randomx_blob_difficulty(
+    blockhashing_blob: &[u8],
+    randomx_key: &[u8],
+    randomx_factory: &RandomXFactory,
+) -> Result<Difficulty, MergeMineError> {
+    let vm = randomx_factory.create(randomx_key)?;
+    get_random_x_difficulty(blockhashing_blob, &vm).map(|(diff, _)| diff)
 }
 
 /// Calculate the RandomX mining hash using the virtual machine together with the achieved difficulty
@@ -165,21 +175,80 @@ fn check_aux_chains(
     if merkle_tree_params.number_of_chains == 0 {
         return false;
     }
-    let hash_position = U256::from_little_endian(
+    let expected_position = aux_chain_slot(
+        tari_genesis_block_hash,
+        merkle_tree_params.aux_nonce,
+        merkle_tree_params.number_of_chains,
+    );
+    let (merkle_root, pos) = monero_data
+        .aux_chain_merkle_proof
+        .calculate_root_with_pos(&t_hash, usize::from(merkle_tree_params.number_of_chains));
+    if pos != Some(expected_position) {
+        warn!(
+            target: LOG_TARGET,
+            "Tari hash is at position {:?} in the aux chain merkle tree, expected {}", pos, expected_position
+        );
+        return false;
+    }
+
+    merkle_root == *aux_chain_merkle_root
+}
+
+/// Returns the position (slot) of an aux chain in the aux chain merkle tree of a merge mined Monero block. The chain is
+/// identified by its genesis block hash, and the slot of each chain must be unique for the given nonce. See
+/// https://github.com/SChernykh/p2pool/blob/merge-mining/docs/MERGE_MINING.MD#merge-mining-tx_extra-tag-format
+pub fn aux_chain_slot(chain_id: &FixedHash, aux_nonce: u32, number_of_chains: u8) -> usize {
+    if number_of_chains <= 1 {
+        return 0;
+    }
+    let slot = U256::from_little_endian(
         &Sha256::new()
-            .chain_update(tari_genesis_block_hash)
-            .chain_update(merkle_tree_params.aux_nonce.to_le_bytes())
+            .chain_update(chain_id)
+            .chain_update(aux_nonce.to_le_bytes())
             .chain_update((109_u8).to_le_bytes())
             .finalize(),
     )
     .low_u32() %
-        u32::from(merkle_tree_params.number_of_chains);
-    let (merkle_root, pos) = monero_data.aux_chain_merkle_proof.calculate_root_with_pos(&t_hash);
-    if hash_position != pos {
-        return false;
-    }
+        u32::from(number_of_chains);
+    // A u32 always fits in a usize on the platforms we support
+    usize::try_from(slot).unwrap_or_default()
+}
 
-    merkle_root == *aux_chain_merkle_root
+/// Finds the first nonce, up to and including `max_nonce`, for which every chain is assigned a unique slot in the aux
+/// chain merkle tree.
+pub fn find_aux_nonce(chain_ids: &[FixedHash], max_nonce: u32) -> Option<u32> {
+    let number_of_chains = u8::try_from(chain_ids.len()).ok().filter(|n| *n > 0)?;
+    (0..=max_nonce).find(|nonce| {
+        let mut used = vec![false; chain_ids.len()];
+        chain_ids.iter().all(|id| {
+            let slot = aux_chain_slot(id, *nonce, number_of_chains);
+            !std::mem::replace(&mut used[slot], true)
+        })
+    })
+}
+
+/// Orders the aux chain hashes, given as `(chain id, merge mining hash)` pairs, by their slot for the given nonce. The
+/// result can be passed to [tree_hash] to calculate the aux chain merkle root, and to [construct_monero_data].
+pub fn create_ordered_aux_chain_hashes(
+    aux_chains: &[(FixedHash, monero::Hash)],
+    aux_nonce: u32,
+) -> Result<Vec<monero::Hash>, MergeMineError> {
+    let number_of_chains = u8::try_from(aux_chains.len())
+        .map_err(|_| MergeMineError::ValidationError(format!("Too many aux chains ({})", aux_chains.len())))?;
+    if number_of_chains == 0 {
+        return Err(MergeMineError::ZeroAuxChains);
+    }
+    let mut ordered = vec![None; aux_chains.len()];
+    for (chain_id, hash) in aux_chains {
+        let slot = aux_chain_slot(chain_id, aux_nonce, number_of_chains);
+        if ordered[slot].replace(*hash).is_some() {
+            return Err(MergeMineError::ValidationError(format!(
+                "Aux chain slot {} is used by more than one chain for nonce {}",
+                slot, aux_nonce
+            )));
+        }
+    }
+    Ok(ordered.into_iter().flatten().collect())
 }
 
 /// Extracts the Monero block hash from the coinbase transaction's extra field
@@ -1276,4 +1345,71 @@ mod test {
         );
         assert_eq!(field.0[0], mm_tag);
     }
+
+    #[test]
+    fn test_verify_header_with_multiple_aux_chains() {
+        let rules = ConsensusManager::builder(Network::LocalNet).build().unwrap();
+        let blocktemplate_blob = "0c0c8cd6a0fa057fe21d764e7abf004e975396a2160773b93712bf6118c3b4959ddd8ee0f76aad0000000002e1ea2701ffa5ea2701d5a299e2abb002028eb3066ced1b2cc82ea046f3716a48e9ae37144057d5fb48a97f941225a1957b2b0106225b7ec0a6544d8da39abe68d8bd82619b4a7c5bdae89c3783b256a8fa47820208f63aa86d2e857f070000".to_string();
+        let seed_hash = "9f02e032f9b15d2aded991e0f68cc3c3427270b568b782e55fbd269ead0bad97".to_string();
+        let seed = FixedByteArray::from_canonical_bytes(&from_hex(&seed_hash).unwrap()).unwrap();
+        let bytes = hex::decode(blocktemplate_blob).unwrap();
+        let monero_block = deserialize::<monero::Block>(&bytes[..]).unwrap();
+        let mut block_header = BlockHeader::new(0);
+        let hash = block_header.merge_mining_hash();
+        let tari_genesis_hash = FixedHash::from([1u8; 32]);
+        let chain_ids = [
+            tari_genesis_hash,
+            FixedHash::from([2u8; 32]),
+            FixedHash::from([3u8; 32]),
+        ];
+        let aux_nonce = find_aux_nonce(&chain_ids, u32::MAX).unwrap();
+        let aux_chains = [
+            (chain_ids[0], monero::Hash::from_slice(hash.as_slice())),
+            (chain_ids[1], monero::Hash::new(b"aux chain 1")),
+            (chain_ids[2], monero::Hash::new(b"aux chain 2")),
+        ];
+        let ordered_aux_chain_hashes = create_ordered_aux_chain_hashes(&aux_chains, aux_nonce).unwrap();
+        let tari_slot = aux_chain_slot(&tari_genesis_hash, aux_nonce, 3);
+        assert_eq!(ordered_aux_chain_hashes[tari_slot], aux_chains[0].1);
+
+        let mut block = monero_block.clone();
+        let aux_chain_mr = tree_hash(&ordered_aux_chain_hashes).unwrap();
+        insert_aux_chain_mr_and_info_into_block(&mut block, aux_chain_mr, 3, aux_nonce).unwrap();
+        let monero_data = construct_monero_data(block, seed.clone(), ordered_aux_chain_hashes.clone(), hash).unwrap();
+        let mut serialized = Vec::new();
+        monero_data.serialize(&mut serialized).unwrap();
+        block_header.pow = ProofOfWork {
+            pow_algo: PowAlgorithm::RandomX,
+            pow_data: serialized,
+        };
+        verify_header(&block_header, &tari_genesis_hash, &rules).unwrap();
+
+        // The Tari hash is committed to in the tree, but in the slot of another chain
+        let mut misordered_hashes = ordered_aux_chain_hashes;
+        misordered_hashes.swap(tari_slot, (tari_slot + 1) % 3);
+        let mut block = monero_block;
+        let aux_chain_mr = tree_hash(&misordered_hashes).unwrap();
+        insert_aux_chain_mr_and_info_into_block(&mut block, aux_chain_mr, 3, aux_nonce).unwrap();
+        let monero_data = construct_monero_data(block, seed, misordered_hashes, hash).unwrap();
+        let mut serialized = Vec::new();
+        monero_data.serialize(&mut serialized).unwrap();
+        block_header.pow.pow_data = serialized;
+        let err = verify_header(&block_header, &tari_genesis_hash, &rules).unwrap_err();
+        unpack_enum!(MergeMineError::ValidationError(details) = err);
+        assert!(details.contains("Expected merge mining tag was not found"));
+    }
+
+    #[test]
+    fn test_find_aux_nonce() {
+        let chain_ids = (1..=5u8).map(|i| FixedHash::from([i; 32])).collect::<Vec<_>>();
+        let nonce = find_aux_nonce(&chain_ids, u32::MAX).unwrap();
+        let mut slots = chain_ids
+            .iter()
+            .map(|id| aux_chain_slot(id, nonce, 5))
+            .collect::<Vec<_>>();
+        slots.sort_unstable();
+        assert_eq!(slots, vec![0, 1, 2, 3, 4]);
+        assert_eq!(find_aux_nonce(&[], u32::MAX), None);
+        assert_eq!(aux_chain_slot(&chain_ids[0], nonce, 1), 0);
+    }
 }
@@ -197,30 +197,56 @@ impl MerkleProof {
 
     /// Calculates the merkle root hash from the provide Monero hash
     pub fn calculate_root(&self, hash: &Hash) -> Hash {
-        self.calculate_root_with_pos(hash).0
-    }
-
-    pub fn calculate_root_with_pos(&self, hash: &Hash) -> (Hash, u32) {
-        if self.branch.is_empty() {
-            return (*hash, 0);
-        }
-
         let mut root = *hash;
         let depth = self.branch.len();
-        let mut pos = 0;
-        let mut multiplier = 1;
         for d in 0..depth {
             if (self.path_bitmap >> (depth - d - 1)) & 1 > 0 {
                 root = cn_fast_hash2(&self.branch[d], &root);
             } else {
                 root = cn_fast_hash2(&root, &self.branch[d]);
-                pos += multiplier;
             }
-            // this cant overflow as the max depth is 32, and 2^32 == u32::MAX
-            multiplier *= 2;
         }
+        root
+    }
+
+    /// Calculates the merkle root hash from the provided Monero hash, along with the position of the hash in a tree of
+    /// `leaf_count` leaves. The position is `None` if the shape of the proof does not match a tree of that size.
+    pub fn calculate_root_with_pos(&self, hash: &Hash, leaf_count: usize) -> (Hash, Option<usize>) {
+        (self.calculate_root(hash), self.leaf_index(leaf_count))
+    }
 
-        (root, pos)
+    /// Returns the position of the proven leaf in a tree of `leaf_count` leaves built by [tree_hash], or `None` if the
+    /// depth or path of the proof is not possible for a tree of that size. When the leaf count is not a power of two,
+    /// the first leaves are one level closer to the root than the rest, so the position depends on the leaf count.
+    pub fn leaf_index(&self, leaf_count: usize) -> Option<usize> {
+        let depth = self.branch.len();
+        match leaf_count {
+            0 => return None,
+            1 => return if depth == 0 { Some(0) } else { None },
+            _ => {},
+        }
+        if depth > 32 || u64::from(self.path_bitmap) >> depth != 0 {
+            return None;
+        }
+        // The path bitmap holds the leaf level in the most significant bit, so reversing it gives the position
+        let pos = (0..depth).fold(0usize, |pos, d| {
+            (pos << 1) | usize::try_from((self.path_bitmap >> d) & 1).unwrap_or_default()
+        });
+        // The number of nodes in the first complete level of the tree, and the number of leaves in that level
+        let level_count = 1usize << (usize::BITS - 1 - leaf_count.leading_zeros());
+        let complete_leaves = 2 * level_count - leaf_count;
+        let level_depth = usize::try_from(level_count.trailing_zeros()).ok()?;
+        if depth == level_depth {
+            return if pos < complete_leaves { Some(pos) } else { None };
+        }
+        if depth == level_depth + 1 {
+            let parent = pos >> 1;
+            if parent < complete_leaves || parent >= level_count {
+                return None;
+            }
+            return Some(complete_leaves + 2 * (parent - complete_leaves) + (pos & 1));
+        }
+        None
     }
 }
 
@@ -725,6 +751,39 @@ mod test {
             assert!(!proof.branch().contains(&expected_root));
         }
 
+        #[test]
+        fn proof_positions_match_tree_layout() {
+            for count in 1..=33u8 {
+                let hashes = (0..count).map(|i| cn_fast_hash(&[i])).collect::<Vec<_>>();
+                let root = tree_hash(&hashes).unwrap();
+                for (index, hash) in hashes.iter().enumerate() {
+                    let proof = create_merkle_proof(&hashes, hash).unwrap();
+                    assert_eq!(
+                        proof.calculate_root_with_pos(hash, hashes.len()),
+                        (root, Some(index)),
+                        "leaf {} of {}",
+                        index,
+                        count
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn proof_position_rejects_mismatched_tree_size() {
+            let hashes = (0..5u8).map(|i| cn_fast_hash(&[i])).collect::<Vec<_>>();
+            // Leaf 4 is one level deeper than leaf 0 in a tree of 5 leaves
+            let proof = create_merkle_proof(&hashes, &hashes[4]).unwrap();
+            assert_eq!(proof.leaf_index(5), Some(4));
+            // The same path is the last leaf of a complete tree of 8 leaves
+            assert_eq!(proof.leaf_index(8), Some(7));
+            // Trees of 3 and 4 leaves are not deep enough for the proof
+            assert_eq!(proof.leaf_index(4), None);
+            assert_eq!(proof.leaf_index(3), None);
+            assert_eq!(proof.leaf_index(1), None);
+            assert_eq!(proof.leaf_index(0), None);
+        }
+
         #[test]
         fn test_borsh_de_serialization() {
             let tx_hashes =
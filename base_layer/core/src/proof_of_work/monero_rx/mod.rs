@@ -24,12 +24,16 @@ pub use error::MergeMineError;
 
 mod helpers;
 pub use helpers::{
+    aux_chain_slot,
     construct_monero_data,
     create_blockhashing_blob_from_block,
+    create_ordered_aux_chain_hashes,
     create_ordered_transaction_hashes_from_block,
     deserialize_monero_block_from_hex,
     extract_aux_merkle_root_from_block,
+    find_aux_nonce,
     insert_aux_chain_mr_and_info_into_block,
+    randomx_blob_difficulty,
     randomx_difficulty,
     serialize_monero_block_to_hex,
     verify_header,
@@ -38,12 +42,34 @@ pub use helpers::{
 mod fixed_array;
 pub use fixed_array::FixedByteArray;
 
+mod seed_prewarm;
+pub use seed_prewarm::{
+    blocks_until_seed_change,
+    monero_seed_height,
+    RandomXPreWarmer,
+    SEEDHASH_EPOCH_BLOCKS,
+    SEEDHASH_EPOCH_LAG,
+};
+
+mod strict_decode;
+pub use strict_decode::{
+    deserialize_monero_block_from_hex_strict,
+    MoneroDecodeLimits,
+    DEFAULT_MAX_BLOB_SIZE,
+    DEFAULT_MAX_EXTRA_FIELD_SIZE,
+    DEFAULT_MAX_EXTRA_TAGS,
+    DEFAULT_MAX_TRANSACTIONS,
+};
+
+mod vm_pool;
+pub use vm_pool::{RandomXVmPool, RandomXVmPoolStats};
+
 mod pow_data;
 pub use pow_data::MoneroPowData;
 
 mod merkle_tree;
 mod merkle_tree_parameters;
-pub use merkle_tree::{create_merkle_proof, tree_hash};
+pub use merkle_tree::{create_merkle_proof, tree_hash, MerkleProof};
 pub use merkle_tree_parameters::MerkleTreeParameters;
 // Re-exports
 pub use monero::{
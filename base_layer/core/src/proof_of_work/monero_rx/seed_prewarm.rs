@@ -0,0 +1,178 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use borsh::BorshDeserialize;
+use log::*;
+use tokio::{sync::mpsc, task};
+
+use crate::{
+    blocks::BlockHeader,
+    proof_of_work::{monero_rx::MoneroPowData, randomx_factory::RandomXFactory, PowAlgorithm},
+};
+
+const LOG_TARGET: &str = "c::pow::monero_rx::seed_prewarm";
+
+/// The number of Monero blocks in a RandomX seed hash epoch
+pub const SEEDHASH_EPOCH_BLOCKS: u64 = 2048;
+/// The number of Monero blocks after the start of an epoch before its seed hash is used
+pub const SEEDHASH_EPOCH_LAG: u64 = 64;
+
+const PRE_WARM_QUEUE_SIZE: usize = 8;
+
+/// Returns the height of the Monero block whose hash is used as the RandomX seed hash for a block at `monero_height`.
+/// This matches `rx_seedheight` in Monero.
+pub fn monero_seed_height(monero_height: u64) -> u64 {
+    if monero_height <= SEEDHASH_EPOCH_BLOCKS + SEEDHASH_EPOCH_LAG {
+        0
+    } else {
+        (monero_height - SEEDHASH_EPOCH_LAG - 1) & !(SEEDHASH_EPOCH_BLOCKS - 1)
+    }
+}
+
+/// Returns the number of Monero blocks after `monero_height` until the RandomX seed hash changes
+pub fn blocks_until_seed_change(monero_height: u64) -> u64 {
+    let current = monero_seed_height(monero_height);
+    // The seed hash first changes from the genesis seed at the end of the second epoch
+    let mut next_change = current.max(SEEDHASH_EPOCH_BLOCKS) + SEEDHASH_EPOCH_LAG + 1;
+    if next_change <= monero_height {
+        next_change += SEEDHASH_EPOCH_BLOCKS;
+    }
+    next_change - monero_height
+}
+
+/// Initializes RandomX VMs for upcoming seed hashes on a background task, so that validation does not stall for
+/// several seconds when the first block of a new seed hash epoch is validated. The VMs are added to the pool of the
+/// given [RandomXFactory] and the task exits once every clone of the pre-warmer has been dropped.
+#[derive(Clone, Debug)]
+pub struct RandomXPreWarmer {
+    sender: mpsc::Sender<Vec<u8>>,
+    factory: RandomXFactory,
+}
+
+impl RandomXPreWarmer {
+    /// Spawn the pre-warm task. This must be called from within a tokio runtime.
+    pub fn spawn(factory: RandomXFactory) -> Self {
+        let (sender, receiver) = mpsc::channel(PRE_WARM_QUEUE_SIZE);
+        task::spawn(run_pre_warm_task(factory.clone(), receiver));
+        Self { sender, factory }
+    }
+
+    /// Request that a VM for the seed hash is initialized. This does not block, and the request is dropped if the
+    /// pre-warm queue is full or the VM is already in the pool.
+    pub fn pre_warm(&self, seed_hash: &[u8]) {
+        if self.factory.pool().contains(seed_hash).unwrap_or(false) {
+            return;
+        }
+        if let Err(err) = self.sender.try_send(seed_hash.to_vec()) {
+            debug!(target: LOG_TARGET, "RandomX seed hash pre-warm request dropped: {}", err);
+        }
+    }
+
+    /// Pre-warm the seed hashes of the merge mined headers in a batch of headers that is about to be validated in
+    /// order. The seed hash of the first merge mined header is skipped, because its VM is initialized by the validator
+    /// straight away, and at most `capacity - 1` seed hashes are pre-warmed so that none are evicted before they are
+    /// used.
+    pub fn pre_warm_headers(&self, headers: &[BlockHeader]) {
+        let max_pre_warm = self.factory.pool().capacity().unwrap_or(1).saturating_sub(1);
+        let mut seed_hashes: Vec<Vec<u8>> = Vec::new();
+        for header in headers {
+            if header.pow_algo() != PowAlgorithm::RandomX {
+                continue;
+            }
+            let Ok(pow_data) = MoneroPowData::deserialize(&mut header.pow.pow_data.as_slice()) else {
+                continue;
+            };
+            let seed_hash = pow_data.randomx_key();
+            if seed_hashes.last().map(|s| s.as_slice()) != Some(seed_hash) {
+                seed_hashes.push(seed_hash.to_vec());
+            }
+        }
+        for seed_hash in seed_hashes.iter().skip(1).take(max_pre_warm) {
+            self.pre_warm(seed_hash);
+        }
+    }
+}
+
+async fn run_pre_warm_task(factory: RandomXFactory, mut receiver: mpsc::Receiver<Vec<u8>>) {
+    while let Some(seed_hash) = receiver.recv().await {
+        if factory.pool().contains(&seed_hash).unwrap_or(false) {
+            continue;
+        }
+        let factory = factory.clone();
+        let result = task::spawn_blocking(move || factory.create(&seed_hash).map(|_| seed_hash)).await;
+        match result {
+            Ok(Ok(seed_hash)) => debug!(
+                target: LOG_TARGET,
+                "Pre-warmed RandomX VM for seed hash {}",
+                hex::encode(seed_hash)
+            ),
+            Ok(Err(err)) => warn!(target: LOG_TARGET, "Failed to pre-warm RandomX VM: {}", err),
+            Err(err) => warn!(target: LOG_TARGET, "RandomX VM pre-warm task failed: {}", err),
+        }
+    }
+    debug!(target: LOG_TARGET, "RandomX pre-warm task exited");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_calculates_the_monero_seed_height() {
+        assert_eq!(monero_seed_height(0), 0);
+        assert_eq!(monero_seed_height(SEEDHASH_EPOCH_BLOCKS + SEEDHASH_EPOCH_LAG), 0);
+        assert_eq!(
+            monero_seed_height(SEEDHASH_EPOCH_BLOCKS + SEEDHASH_EPOCH_LAG + 1),
+            SEEDHASH_EPOCH_BLOCKS
+        );
+        assert_eq!(monero_seed_height(3_000_000), 2_998_272);
+        assert_eq!(monero_seed_height(2_998_272 + SEEDHASH_EPOCH_LAG), 2_996_224);
+    }
+
+    #[test]
+    fn it_calculates_the_blocks_until_the_seed_hash_changes() {
+        assert_eq!(blocks_until_seed_change(0), 2113);
+        assert_eq!(blocks_until_seed_change(2112), 1);
+        assert_eq!(blocks_until_seed_change(2113), SEEDHASH_EPOCH_BLOCKS);
+        assert_eq!(blocks_until_seed_change(4160), 1);
+        for height in [10_000u64, 2_998_335, 2_998_336, 3_000_000] {
+            let blocks = blocks_until_seed_change(height);
+            assert!(blocks > 0 && blocks <= SEEDHASH_EPOCH_BLOCKS);
+            assert_eq!(monero_seed_height(height + blocks - 1), monero_seed_height(height));
+            assert_ne!(monero_seed_height(height + blocks), monero_seed_height(height));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_pre_warms_a_seed_hash() {
+        let factory = RandomXFactory::new(2);
+        let pre_warmer = RandomXPreWarmer::spawn(factory.clone());
+        pre_warmer.pre_warm(b"seed-1");
+        let mut attempts = 0;
+        while !factory.pool().contains(b"seed-1").unwrap() {
+            attempts += 1;
+            assert!(attempts < 600, "VM was not pre-warmed");
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(factory.get_count().unwrap(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use monero::{
+    blockdata::transaction::{ExtraField, RawExtraField},
+    consensus,
+};
+use tari_utilities::hex::HexError;
+
+use super::error::MergeMineError;
+
+/// The largest Monero block blob that is accepted, in bytes
+pub const DEFAULT_MAX_BLOB_SIZE: usize = 4 * 1024 * 1024;
+/// The largest number of transaction hashes that a Monero block blob may contain
+pub const DEFAULT_MAX_TRANSACTIONS: usize = 100_000;
+/// The largest coinbase extra field that is accepted, matching Monero's `MAX_TX_EXTRA_SIZE` relay rule
+pub const DEFAULT_MAX_EXTRA_FIELD_SIZE: usize = 1060;
+/// The largest number of tags in the coinbase extra field
+pub const DEFAULT_MAX_EXTRA_TAGS: usize = 16;
+
+/// Limits that are enforced when decoding a Monero block blob that was supplied by an untrusted party, such as a
+/// mining pool submitting a solved block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoneroDecodeLimits {
+    pub max_blob_size: usize,
+    pub max_transactions: usize,
+    pub max_extra_field_size: usize,
+    pub max_extra_tags: usize,
+}
+
+impl Default for MoneroDecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_blob_size: DEFAULT_MAX_BLOB_SIZE,
+            max_transactions: DEFAULT_MAX_TRANSACTIONS,
+            max_extra_field_size: DEFAULT_MAX_EXTRA_FIELD_SIZE,
+            max_extra_tags: DEFAULT_MAX_EXTRA_TAGS,
+        }
+    }
+}
+
+impl MoneroDecodeLimits {
+    /// Checks the decoded block against the transaction count and coinbase extra field limits
+    pub fn check_block(&self, block: &monero::Block) -> Result<(), MergeMineError> {
+        // The miner tx is not included in tx_hashes
+        let count = block.tx_hashes.len() + 1;
+        if count > self.max_transactions {
+            return Err(MergeMineError::TooManyTransactions {
+                count,
+                max: self.max_transactions,
+            });
+        }
+        self.check_extra_field(&block.miner_tx.prefix.extra)
+    }
+
+    /// Checks the size of a raw coinbase extra field and the number of tags it contains. Sub-fields that cannot be
+    /// parsed are not counted, in line with how the extra field is read elsewhere.
+    pub fn check_extra_field(&self, extra: &RawExtraField) -> Result<(), MergeMineError> {
+        let size = extra.0.len();
+        if size > self.max_extra_field_size {
+            return Err(MergeMineError::ExtraFieldTooLarge {
+                size,
+                max: self.max_extra_field_size,
+            });
+        }
+        let count = match ExtraField::try_parse(extra) {
+            Ok(field) => field.0.len(),
+            Err(field) => field.0.len(),
+        };
+        if count > self.max_extra_tags {
+            return Err(MergeMineError::TooManyExtraTags {
+                count,
+                max: self.max_extra_tags,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes the given hex-encoded string into a Monero block, enforcing the given limits. The size of the blob is
+/// checked before it is decoded, which bounds the memory that a malicious blob can cause to be allocated.
+pub fn deserialize_monero_block_from_hex_strict<T>(
+    data: T,
+    limits: &MoneroDecodeLimits,
+) -> Result<monero::Block, MergeMineError>
+where
+    T: AsRef<[u8]>,
+{
+    let data = data.as_ref();
+    let size = data.len() / 2;
+    if size > limits.max_blob_size {
+        return Err(MergeMineError::BlobTooLarge {
+            size,
+            max: limits.max_blob_size,
+        });
+    }
+    let bytes = hex::decode(data).map_err(|_| HexError::HexConversionError {})?;
+    let block = consensus::deserialize::<monero::Block>(&bytes)
+        .map_err(|e| MergeMineError::DeserializeError(format!("blocktemplate blob invalid: {}", e)))?;
+    limits.check_block(&block)?;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod test {
+    use tari_test_utils::unpack_enum;
+
+    use super::*;
+
+    const BLOCKTEMPLATE_BLOB: &str = "0c0c8cd6a0fa057fe21d764e7abf004e975396a2160773b93712bf6118c3b4959ddd8ee0f76aad0000000002e1ea2701ffa5ea2701d5a299e2abb002028eb3066ced1b2cc82ea046f3716a48e9ae37144057d5fb48a97f941225a1957b2b0106225b7ec0a6544d8da39abe68d8bd82619b4a7c5bdae89c3783b256a8fa47820208f63aa86d2e857f070000";
+
+    #[test]
+    fn it_decodes_a_block_within_the_limits() {
+        let block =
+            deserialize_monero_block_from_hex_strict(BLOCKTEMPLATE_BLOB, &MoneroDecodeLimits::default()).unwrap();
+        assert!(block.tx_hashes.is_empty());
+    }
+
+    #[test]
+    fn it_rejects_a_blob_that_is_too_large() {
+        let limits = MoneroDecodeLimits {
+            max_blob_size: 16,
+            ..Default::default()
+        };
+        let err = deserialize_monero_block_from_hex_strict(BLOCKTEMPLATE_BLOB, &limits).unwrap_err();
+        unpack_enum!(MergeMineError::BlobTooLarge { max, .. } = err);
+        assert_eq!(max, 16);
+    }
+
+    #[test]
+    fn it_rejects_too_many_transactions() {
+        let limits = MoneroDecodeLimits {
+            max_transactions: 0,
+            ..Default::default()
+        };
+        let err = deserialize_monero_block_from_hex_strict(BLOCKTEMPLATE_BLOB, &limits).unwrap_err();
+        unpack_enum!(MergeMineError::TooManyTransactions { count, .. } = err);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn it_rejects_large_extra_fields() {
+        let limits = MoneroDecodeLimits::default();
+        let extra = RawExtraField(vec![0u8; DEFAULT_MAX_EXTRA_FIELD_SIZE + 1]);
+        let err = limits.check_extra_field(&extra).unwrap_err();
+        unpack_enum!(MergeMineError::ExtraFieldTooLarge { size, .. } = err);
+        assert_eq!(size, DEFAULT_MAX_EXTRA_FIELD_SIZE + 1);
+    }
+
+    #[test]
+    fn it_rejects_too_many_extra_tags() {
+        let limits = MoneroDecodeLimits {
+            max_extra_tags: 2,
+            ..Default::default()
+        };
+        // Three one-byte extra nonce tags
+        let extra = RawExtraField(vec![2, 1, 0xaa, 2, 1, 0xbb, 2, 1, 0xcc]);
+        let err = limits.check_extra_field(&extra).unwrap_err();
+        unpack_enum!(MergeMineError::TooManyExtraTags { count, .. } = err);
+        assert_eq!(count, 3);
+    }
+}
@@ -0,0 +1,227 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use log::*;
+use randomx_rs::RandomXFlag;
+
+use crate::proof_of_work::randomx_factory::{RandomXVMFactoryError, RandomXVMInstance};
+
+const LOG_TARGET: &str = "c::pow::monero_rx::vm_pool";
+
+/// Hit, miss and eviction counters for a [RandomXVmPool]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RandomXVmPoolStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// A pool of initialized RandomX VMs keyed on the RandomX seed hash. Initializing a VM for a new seed hash takes
+/// several seconds, so the pool keeps the most recently used VMs (up to `capacity`) and evicts the least recently used
+/// VM when a VM for a new seed hash is required. The pool is cheap to clone and is shared across validation threads.
+#[derive(Clone)]
+pub struct RandomXVmPool {
+    inner: Arc<Mutex<RandomXVmPoolInner>>,
+}
+
+struct RandomXVmPoolInner {
+    flags: RandomXFlag,
+    capacity: usize,
+    // Ordered from most recently used to least recently used
+    vms: VecDeque<(Vec<u8>, RandomXVMInstance)>,
+    stats: RandomXVmPoolStats,
+}
+
+impl RandomXVmPool {
+    /// Create a new pool that holds at most `capacity` VMs, using the recommended RandomX flags for this machine
+    pub fn new(capacity: usize) -> Self {
+        Self::with_flags(capacity, RandomXFlag::get_recommended_flags())
+    }
+
+    pub fn with_flags(capacity: usize, flags: RandomXFlag) -> Self {
+        let capacity = capacity.max(1);
+        debug!(
+            target: LOG_TARGET,
+            "RandomX VM pool started with capacity {} and flags = {:?}", capacity, flags
+        );
+        Self {
+            inner: Arc::new(Mutex::new(RandomXVmPoolInner {
+                flags,
+                capacity,
+                vms: VecDeque::with_capacity(capacity),
+                stats: RandomXVmPoolStats::default(),
+            })),
+        }
+    }
+
+    /// Returns the VM for the given seed hash, initializing it if it is not in the pool. The pool lock is not held
+    /// while a VM is initialized, so threads that need a VM for a seed hash that is already in the pool are not
+    /// blocked.
+    pub fn get_or_create(&self, seed_hash: &[u8]) -> Result<RandomXVMInstance, RandomXVMFactoryError> {
+        let flags = {
+            let mut inner = self.lock()?;
+            if let Some(vm) = inner.get(seed_hash) {
+                inner.stats.hits += 1;
+                return Ok(vm);
+            }
+            inner.stats.misses += 1;
+            inner.flags
+        };
+
+        let vm = RandomXVMInstance::create(seed_hash, flags)?;
+
+        let mut inner = self.lock()?;
+        // Another thread may have initialized a VM for this seed hash while the lock was released
+        if let Some(vm) = inner.get(seed_hash) {
+            return Ok(vm);
+        }
+        inner.insert(seed_hash, vm.clone());
+        Ok(vm)
+    }
+
+    /// Returns true if a VM for the given seed hash is in the pool. This does not affect the eviction order.
+    pub fn contains(&self, seed_hash: &[u8]) -> Result<bool, RandomXVMFactoryError> {
+        Ok(self.lock()?.vms.iter().any(|(k, _)| k.as_slice() == seed_hash))
+    }
+
+    /// Get the number of VMs currently in the pool
+    pub fn len(&self) -> Result<usize, RandomXVMFactoryError> {
+        Ok(self.lock()?.vms.len())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, RandomXVMFactoryError> {
+        Ok(self.lock()?.vms.is_empty())
+    }
+
+    /// Get the maximum number of VMs the pool holds
+    pub fn capacity(&self) -> Result<usize, RandomXVMFactoryError> {
+        Ok(self.lock()?.capacity)
+    }
+
+    /// Get the flags used to create the VMs
+    pub fn flags(&self) -> Result<RandomXFlag, RandomXVMFactoryError> {
+        Ok(self.lock()?.flags)
+    }
+
+    pub fn stats(&self) -> Result<RandomXVmPoolStats, RandomXVMFactoryError> {
+        Ok(self.lock()?.stats)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, RandomXVmPoolInner>, RandomXVMFactoryError> {
+        self.inner.lock().map_err(|_| RandomXVMFactoryError::PoisonedLockError)
+    }
+}
+
+impl RandomXVmPoolInner {
+    /// Returns the VM for the seed hash and marks it as the most recently used
+    fn get(&mut self, seed_hash: &[u8]) -> Option<RandomXVMInstance> {
+        let pos = self.vms.iter().position(|(k, _)| k.as_slice() == seed_hash)?;
+        let entry = self.vms.remove(pos)?;
+        let vm = entry.1.clone();
+        self.vms.push_front(entry);
+        Some(vm)
+    }
+
+    fn insert(&mut self, seed_hash: &[u8], vm: RandomXVMInstance) {
+        while self.vms.len() >= self.capacity {
+            if self.vms.pop_back().is_some() {
+                self.stats.evictions += 1;
+            }
+        }
+        self.vms.push_front((seed_hash.to_vec(), vm));
+    }
+}
+
+impl fmt::Debug for RandomXVmPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner.lock() {
+            Ok(inner) => f
+                .debug_struct("RandomXVmPool")
+                .field("flags", &inner.flags)
+                .field("capacity", &inner.capacity)
+                .field("len", &inner.vms.len())
+                .field("stats", &inner.stats)
+                .finish(),
+            Err(_) => f.debug_struct("RandomXVmPool").finish_non_exhaustive(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_reuses_vms_for_the_same_seed_hash() {
+        let pool = RandomXVmPool::new(2);
+        let preimage = b"hashme";
+        let vm = pool.get_or_create(b"seed-1").unwrap();
+        let hash1 = vm.calculate_hash(&preimage[..]).unwrap();
+        let vm = pool.get_or_create(b"seed-1").unwrap();
+        assert_eq!(vm.calculate_hash(&preimage[..]).unwrap(), hash1);
+        assert_eq!(pool.len().unwrap(), 1);
+        assert_eq!(pool.stats().unwrap(), RandomXVmPoolStats {
+            hits: 1,
+            misses: 1,
+            evictions: 0
+        });
+    }
+
+    #[test]
+    fn it_evicts_the_least_recently_used_vm() {
+        let pool = RandomXVmPool::new(2);
+        pool.get_or_create(b"seed-1").unwrap();
+        pool.get_or_create(b"seed-2").unwrap();
+        // Use seed-1 so that seed-2 becomes the least recently used
+        pool.get_or_create(b"seed-1").unwrap();
+        pool.get_or_create(b"seed-3").unwrap();
+
+        assert_eq!(pool.len().unwrap(), 2);
+        assert!(pool.contains(b"seed-1").unwrap());
+        assert!(!pool.contains(b"seed-2").unwrap());
+        assert!(pool.contains(b"seed-3").unwrap());
+        assert_eq!(pool.stats().unwrap().evictions, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn it_is_shared_across_threads() {
+        let pool = RandomXVmPool::new(1);
+        let mut threads = vec![];
+        for _ in 0..8 {
+            let pool = pool.clone();
+            threads.push(tokio::spawn(async move {
+                let vm = pool.get_or_create(b"seed-1").unwrap();
+                let _hash = vm.calculate_hash(b"hashme").unwrap();
+            }));
+        }
+        for t in threads {
+            t.await.unwrap();
+        }
+        assert_eq!(pool.len().unwrap(), 1);
+    }
+}
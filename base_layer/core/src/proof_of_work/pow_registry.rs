@@ -0,0 +1,350 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, sync::Arc};
+
+use tari_common_types::types::FixedHash;
+
+use crate::{
+    blocks::{BlockHeader, BlockHeaderValidationError},
+    chain_storage::{BlockchainBackend, ChainStorageError},
+    consensus::ConsensusManager,
+    proof_of_work::{
+        monero_rx::{randomx_blob_difficulty, verify_header, MoneroPowData},
+        randomx_factory::RandomXFactory,
+        sha3x_blob_difficulty,
+        sha3x_header_blob,
+        Difficulty,
+        PowAlgorithm,
+        PowError,
+    },
+    validation::ValidationError,
+};
+
+/// The shared state that PoW algorithm hooks may need to hash or validate a header
+pub struct PowContext<'a> {
+    pub consensus: &'a ConsensusManager,
+    pub randomx_factory: &'a RandomXFactory,
+    pub genesis_hash: FixedHash,
+}
+
+/// Chain data that PoW algorithm hooks may need to validate the PoW data of a header
+pub trait PowChainData {
+    /// Returns the height at which the RandomX seed was first seen, or 0 if it is unknown
+    fn monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError>;
+}
+
+impl<B: BlockchainBackend> PowChainData for B {
+    fn monero_seed_first_seen_height(&self, seed: &[u8]) -> Result<u64, ChainStorageError> {
+        self.fetch_monero_seed_first_seen_height(seed)
+    }
+}
+
+/// The hooks that a proof of work algorithm provides to the node
+pub trait PowAlgorithmHooks: Send + Sync {
+    fn pow_algo(&self) -> PowAlgorithm;
+
+    /// The hash of the header that miners commit to when mining a block template
+    fn mining_hash(&self, header: &BlockHeader) -> FixedHash;
+
+    /// The serialized header that is hashed by the algorithm. Implementations must check that the blob commits to the
+    /// header, since the achieved difficulty is calculated from the blob alone.
+    fn header_blob(&self, header: &BlockHeader, context: &PowContext<'_>) -> Result<Vec<u8>, ValidationError>;
+
+    /// The difficulty achieved by hashing a blob returned by [PowAlgorithmHooks::header_blob] for the header
+    fn blob_difficulty(
+        &self,
+        header: &BlockHeader,
+        blob: &[u8],
+        context: &PowContext<'_>,
+    ) -> Result<Difficulty, ValidationError>;
+
+    /// The difficulty achieved by the header
+    fn achieved_difficulty(
+        &self,
+        header: &BlockHeader,
+        context: &PowContext<'_>,
+    ) -> Result<Difficulty, ValidationError> {
+        let blob = self.header_blob(header, context)?;
+        self.blob_difficulty(header, &blob, context)
+    }
+
+    /// Checks that the PoW fields of the header are well-formed for the algorithm
+    fn validate_pow_data(
+        &self,
+        header: &BlockHeader,
+        context: &PowContext<'_>,
+        chain: &dyn PowChainData,
+    ) -> Result<(), ValidationError>;
+}
+
+/// Monero merge mined RandomX
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomXPowHooks;
+
+impl PowAlgorithmHooks for RandomXPowHooks {
+    fn pow_algo(&self) -> PowAlgorithm {
+        PowAlgorithm::RandomX
+    }
+
+    fn mining_hash(&self, header: &BlockHeader) -> FixedHash {
+        header.merge_mining_hash()
+    }
+
+    fn header_blob(&self, header: &BlockHeader, context: &PowContext<'_>) -> Result<Vec<u8>, ValidationError> {
+        let monero_data = verify_header(header, &context.genesis_hash, context.consensus)?;
+        Ok(monero_data.to_blockhashing_blob())
+    }
+
+    fn blob_difficulty(
+        &self,
+        header: &BlockHeader,
+        blob: &[u8],
+        context: &PowContext<'_>,
+    ) -> Result<Difficulty, ValidationError> {
+        let monero_data = MoneroPowData::from_header(header, context.consensus)?;
+        Ok(randomx_blob_difficulty(
+            blob,
+            monero_data.randomx_key(),
+            context.randomx_factory,
+        )?)
+    }
+
+    fn validate_pow_data(
+        &self,
+        header: &BlockHeader,
+        context: &PowContext<'_>,
+        chain: &dyn PowChainData,
+    ) -> Result<(), ValidationError> {
+        if header.nonce != 0 {
+            return Err(ValidationError::BlockHeaderError(
+                BlockHeaderValidationError::InvalidNonce,
+            ));
+        }
+        let monero_data = MoneroPowData::from_header(header, context.consensus)?;
+        let seed_height = chain.monero_seed_first_seen_height(&monero_data.randomx_key)?;
+        if seed_height != 0 {
+            // Saturating sub: subtraction can underflow in reorgs / rewind-blockchain command
+            let seed_used_height = header.height.saturating_sub(seed_height);
+            if seed_used_height >
+                context
+                    .consensus
+                    .consensus_constants(header.height)
+                    .max_randomx_seed_height()
+            {
+                return Err(ValidationError::BlockHeaderError(
+                    BlockHeaderValidationError::OldSeedHash,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Tari's standalone Sha3X algorithm
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sha3xPowHooks;
+
+impl PowAlgorithmHooks for Sha3xPowHooks {
+    fn pow_algo(&self) -> PowAlgorithm {
+        PowAlgorithm::Sha3x
+    }
+
+    fn mining_hash(&self, header: &BlockHeader) -> FixedHash {
+        header.mining_hash()
+    }
+
+    fn header_blob(&self, header: &BlockHeader, _context: &PowContext<'_>) -> Result<Vec<u8>, ValidationError> {
+        Ok(sha3x_header_blob(header))
+    }
+
+    fn blob_difficulty(
+        &self,
+        _header: &BlockHeader,
+        blob: &[u8],
+        _context: &PowContext<'_>,
+    ) -> Result<Difficulty, ValidationError> {
+        Ok(sha3x_blob_difficulty(blob)?)
+    }
+
+    fn validate_pow_data(
+        &self,
+        header: &BlockHeader,
+        _context: &PowContext<'_>,
+        _chain: &dyn PowChainData,
+    ) -> Result<(), ValidationError> {
+        if !header.pow.pow_data.is_empty() {
+            return Err(PowError::Sha3HeaderNonEmptyPowBytes.into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct RegisteredPowAlgorithm {
+    activation_height: u64,
+    hooks: Arc<dyn PowAlgorithmHooks>,
+}
+
+/// The proof of work algorithms known to the node, each with the height from which blocks may be mined with it.
+/// Validation looks up the hooks of a header's algorithm here rather than matching on [PowAlgorithm], so that adding
+/// an algorithm for a network upgrade only requires registering it with its activation height.
+#[derive(Clone)]
+pub struct PowRegistry {
+    algorithms: Vec<RegisteredPowAlgorithm>,
+}
+
+impl PowRegistry {
+    /// Creates a registry with the algorithms known to the node, each active from the height of the first consensus
+    /// constants that enable it. Algorithms that the consensus rules never enable are not registered.
+    pub fn from_consensus(consensus: &ConsensusManager) -> Self {
+        let registry = Self::empty();
+        let registry = match consensus.pow_algo_activation_height(PowAlgorithm::RandomX) {
+            Some(height) => registry.with_algorithm(height, RandomXPowHooks),
+            None => registry,
+        };
+        match consensus.pow_algo_activation_height(PowAlgorithm::Sha3x) {
+            Some(height) => registry.with_algorithm(height, Sha3xPowHooks),
+            None => registry,
+        }
+    }
+
+    /// Creates a registry with no algorithms
+    pub fn empty() -> Self {
+        Self { algorithms: Vec::new() }
+    }
+
+    /// Registers an algorithm that is valid from `activation_height`. A previously registered algorithm of the same
+    /// kind is replaced.
+    pub fn with_algorithm<T: PowAlgorithmHooks + 'static>(mut self, activation_height: u64, hooks: T) -> Self {
+        self.algorithms.retain(|a| a.hooks.pow_algo() != hooks.pow_algo());
+        self.algorithms.push(RegisteredPowAlgorithm {
+            activation_height,
+            hooks: Arc::new(hooks),
+        });
+        self
+    }
+
+    /// Returns the hooks of the algorithm if it is registered and active at the given height
+    pub fn get(&self, pow_algo: PowAlgorithm, height: u64) -> Result<&dyn PowAlgorithmHooks, PowError> {
+        self.algorithms
+            .iter()
+            .find(|a| a.hooks.pow_algo() == pow_algo && a.activation_height <= height)
+            .map(|a| &*a.hooks)
+            .ok_or(PowError::AlgorithmNotActive { pow_algo, height })
+    }
+
+    pub fn is_active(&self, pow_algo: PowAlgorithm, height: u64) -> bool {
+        self.get(pow_algo, height).is_ok()
+    }
+
+    /// The algorithms that are active at the given height
+    pub fn active_algorithms(&self, height: u64) -> impl Iterator<Item = PowAlgorithm> + '_ {
+        self.algorithms
+            .iter()
+            .filter(move |a| a.activation_height <= height)
+            .map(|a| a.hooks.pow_algo())
+    }
+}
+
+impl fmt::Debug for PowRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.algorithms
+                    .iter()
+                    .map(|a| (a.hooks.pow_algo(), a.activation_height)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+    use tari_test_utils::unpack_enum;
+
+    use super::*;
+    use crate::consensus::{consensus_constants::PowAlgorithmConstants, ConsensusConstantsBuilder};
+
+    #[test]
+    fn it_gates_algorithms_by_activation_height() {
+        let registry = PowRegistry::empty()
+            .with_algorithm(0, Sha3xPowHooks)
+            .with_algorithm(100, RandomXPowHooks);
+        assert!(registry.is_active(PowAlgorithm::Sha3x, 0));
+        assert!(!registry.is_active(PowAlgorithm::RandomX, 99));
+        assert!(registry.is_active(PowAlgorithm::RandomX, 100));
+        assert_eq!(registry.active_algorithms(50).collect::<Vec<_>>(), vec![
+            PowAlgorithm::Sha3x
+        ]);
+
+        let err = registry.get(PowAlgorithm::RandomX, 1).err().unwrap();
+        unpack_enum!(PowError::AlgorithmNotActive { pow_algo, height } = err);
+        assert_eq!(pow_algo, PowAlgorithm::RandomX);
+        assert_eq!(height, 1);
+    }
+
+    #[test]
+    fn it_replaces_a_registered_algorithm() {
+        let registry = PowRegistry::empty()
+            .with_algorithm(0, RandomXPowHooks)
+            .with_algorithm(0, Sha3xPowHooks)
+            .with_algorithm(10, Sha3xPowHooks);
+        assert!(!registry.is_active(PowAlgorithm::Sha3x, 0));
+        assert!(registry.is_active(PowAlgorithm::RandomX, 0));
+        assert_eq!(registry.active_algorithms(10).count(), 2);
+    }
+
+    #[test]
+    fn it_takes_activation_heights_from_the_consensus_constants() {
+        let pow_constants = PowAlgorithmConstants {
+            min_difficulty: Difficulty::min(),
+            max_difficulty: Difficulty::max(),
+            target_time: 240,
+        };
+        let genesis = ConsensusConstantsBuilder::new(Network::LocalNet)
+            .clear_proof_of_work()
+            .add_proof_of_work(PowAlgorithm::Sha3x, pow_constants.clone())
+            .build();
+        let upgrade = ConsensusConstantsBuilder::new(Network::LocalNet)
+            .with_effective_from_height(100)
+            .add_proof_of_work(PowAlgorithm::Sha3x, pow_constants.clone())
+            .add_proof_of_work(PowAlgorithm::RandomX, pow_constants)
+            .build();
+        let consensus = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(genesis)
+            .add_consensus_constants(upgrade)
+            .build()
+            .unwrap();
+
+        let registry = PowRegistry::from_consensus(&consensus);
+        assert!(registry.is_active(PowAlgorithm::Sha3x, 0));
+        assert!(!registry.is_active(PowAlgorithm::RandomX, 99));
+        assert!(registry.is_active(PowAlgorithm::RandomX, 100));
+
+        let consensus = ConsensusManager::builder(Network::LocalNet).build().unwrap();
+        let registry = PowRegistry::from_consensus(&consensus);
+        assert_eq!(registry.active_algorithms(0).count(), 2);
+    }
+}
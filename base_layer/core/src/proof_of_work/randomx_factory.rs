@@ -1,16 +1,13 @@
 // Copyright 2022 The Tari Project
 // SPDX-License-Identifier: BSD-3-Clause
 
-use std::{
-    collections::HashMap,
-    fmt,
-    sync::{Arc, RwLock},
-    time::Instant,
-};
+use std::sync::{Arc, RwLock};
 
 use log::*;
 use randomx_rs::{RandomXCache, RandomXError, RandomXFlag, RandomXVM};
 
+use crate::proof_of_work::monero_rx::RandomXVmPool;
+
 const LOG_TARGET: &str = "c::pow::randomx_factory";
 
 #[derive(thiserror::Error, Debug)]
@@ -35,7 +32,7 @@ pub struct RandomXVMInstance {
 }
 
 impl RandomXVMInstance {
-    fn create(key: &[u8], flags: RandomXFlag) -> Result<Self, RandomXVMFactoryError> {
+    pub(crate) fn create(key: &[u8], flags: RandomXFlag) -> Result<Self, RandomXVMFactoryError> {
         let (flags, cache) = match RandomXCache::new(flags, key) {
             Ok(cache) => (flags, cache),
             Err(err) => {
@@ -85,11 +82,10 @@ impl RandomXVMInstance {
 unsafe impl Send for RandomXVMInstance {}
 unsafe impl Sync for RandomXVMInstance {}
 
-/// The RandomX factory that manages the creation of RandomX VMs.
+/// The RandomX factory that manages the creation of RandomX VMs. VMs are cached per key in a [RandomXVmPool].
 #[derive(Clone, Debug)]
 pub struct RandomXFactory {
-    // Thread safe impl of the inner impl
-    inner: Arc<RwLock<RandomXFactoryInner>>,
+    pool: RandomXVmPool,
 }
 
 impl Default for RandomXFactory {
@@ -102,108 +98,33 @@ impl RandomXFactory {
     /// Create a new RandomX factory with the specified maximum number of VMs
     pub fn new(max_vms: usize) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(RandomXFactoryInner::new(max_vms))),
+            pool: RandomXVmPool::new(max_vms),
         }
     }
 
+    /// Create a RandomX factory that uses the given VM pool
+    pub fn from_pool(pool: RandomXVmPool) -> Self {
+        Self { pool }
+    }
+
     /// Create a new RandomX VM instance with the specified key
     pub fn create(&self, key: &[u8]) -> Result<RandomXVMInstance, RandomXVMFactoryError> {
-        let res;
-        {
-            let mut inner = self
-                .inner
-                .write()
-                .map_err(|_| RandomXVMFactoryError::PoisonedLockError)?;
-            res = inner.create(key)?;
-        }
-        Ok(res)
+        self.pool.get_or_create(key)
     }
 
     /// Get the number of VMs currently allocated
     pub fn get_count(&self) -> Result<usize, RandomXVMFactoryError> {
-        let inner = self
-            .inner
-            .read()
-            .map_err(|_| RandomXVMFactoryError::PoisonedLockError)?;
-        Ok(inner.get_count())
+        self.pool.len()
     }
 
     /// Get the flags used to create the VMs
     pub fn get_flags(&self) -> Result<RandomXFlag, RandomXVMFactoryError> {
-        let inner = self
-            .inner
-            .read()
-            .map_err(|_| RandomXVMFactoryError::PoisonedLockError)?;
-        Ok(inner.get_flags())
-    }
-}
-struct RandomXFactoryInner {
-    flags: RandomXFlag,
-    vms: HashMap<Vec<u8>, (Instant, RandomXVMInstance)>,
-    max_vms: usize,
-}
-
-impl RandomXFactoryInner {
-    /// Create a new RandomXFactoryInner
-    pub(crate) fn new(max_vms: usize) -> Self {
-        let flags = RandomXFlag::get_recommended_flags();
-        debug!(
-            target: LOG_TARGET,
-            "RandomX factory started with {} max VMs and recommended flags = {:?}", max_vms, flags
-        );
-        Self {
-            flags,
-            vms: Default::default(),
-            max_vms,
-        }
+        self.pool.flags()
     }
 
-    /// Create a new RandomXVMInstance
-    pub(crate) fn create(&mut self, key: &[u8]) -> Result<RandomXVMInstance, RandomXVMFactoryError> {
-        if let Some(entry) = self.vms.get_mut(key) {
-            let vm = entry.1.clone();
-            entry.0 = Instant::now();
-            return Ok(vm);
-        }
-
-        if self.vms.len() >= self.max_vms {
-            let mut oldest_value = Instant::now();
-            let mut oldest_key = None;
-            for (k, v) in &self.vms {
-                if v.0 < oldest_value {
-                    oldest_key = Some(k.clone());
-                    oldest_value = v.0;
-                }
-            }
-            if let Some(k) = oldest_key {
-                self.vms.remove(&k);
-            }
-        }
-
-        let vm = RandomXVMInstance::create(key, self.flags)?;
-
-        self.vms.insert(Vec::from(key), (Instant::now(), vm.clone()));
-
-        Ok(vm)
-    }
-
-    /// Get the number of VMs currently allocated
-    pub(crate) fn get_count(&self) -> usize {
-        self.vms.len()
-    }
-
-    /// Get the flags used to create the VMs
-    pub(crate) fn get_flags(&self) -> RandomXFlag {
-        self.flags
-    }
-}
-
-impl fmt::Debug for RandomXFactoryInner {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("RandomXFactory")
-            .field("flags", &self.flags)
-            .field("max_vms", &self.max_vms)
-            .finish()
+    /// The VM pool shared by all clones of this factory
+    pub fn pool(&self) -> &RandomXVmPool {
+        &self.pool
     }
 }
 
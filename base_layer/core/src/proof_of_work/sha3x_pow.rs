@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use sha3::{Digest, Sha3_256};
+use tari_common_types::types::FixedHash;
 
 use crate::{
     blocks::BlockHeader,
@@ -34,26 +35,93 @@ use crate::{
 /// Mining using this CPU version of the algorithm is unlikely to be profitable, but is included for reference and
 /// can be used to mine tXTR on testnets.
 pub fn sha3x_difficulty(header: &BlockHeader) -> Result<Difficulty, DifficultyError> {
-    Ok(sha3x_difficulty_with_hash(header)?.0)
+    sha3x_blob_difficulty(&sha3x_header_blob(header))
 }
 
 /// Calculate the Tari Sha3 mining hash
 pub fn sha3_hash(header: &BlockHeader) -> Vec<u8> {
-    Sha3_256::new()
-        .chain_update(header.nonce.to_le_bytes())
-        .chain_update(header.mining_hash())
-        .chain_update(header.pow.to_bytes())
-        .finalize()
-        .to_vec()
+    Sha3_256::digest(sha3x_header_blob(header)).to_vec()
 }
 
-/// Calculate the Tari Sha3X mining hash and achieved difficulty
-fn sha3x_difficulty_with_hash(header: &BlockHeader) -> Result<(Difficulty, Vec<u8>), DifficultyError> {
-    let hash = sha3_hash(header);
+/// The serialized header that is hashed by Sha3X: the nonce, the mining hash and the PoW bytes
+pub fn sha3x_header_blob(header: &BlockHeader) -> Vec<u8> {
+    let mut blob = header.nonce.to_le_bytes().to_vec();
+    blob.extend_from_slice(header.mining_hash().as_slice());
+    blob.extend_from_slice(&header.pow.to_bytes());
+    blob
+}
+
+/// Calculate the achieved Sha3X difficulty of a serialized header, see [sha3x_header_blob]
+pub fn sha3x_blob_difficulty(blob: &[u8]) -> Result<Difficulty, DifficultyError> {
+    let hash = Sha3_256::digest(blob);
     let hash = Sha3_256::digest(hash);
     let hash = Sha3_256::digest(hash);
-    let difficulty = Difficulty::big_endian_difficulty(&hash)?;
-    Ok((difficulty, hash.to_vec()))
+    Difficulty::big_endian_difficulty(&hash)
+}
+
+/// Calculate the achieved Sha3X difficulty of each of the given headers, in the same order. The hashing state is shared
+/// across the batch, and the mining hash is only recalculated when a header differs from the previous one by more than
+/// its nonce, which is the common case when searching for a nonce or when validating consecutive candidate headers.
+pub fn sha3x_difficulty_batch(headers: &[BlockHeader]) -> Vec<Result<Difficulty, DifficultyError>> {
+    let mut hasher = Sha3xHasher::new();
+    headers.iter().map(|header| hasher.difficulty(header)).collect()
+}
+
+/// Reusable Sha3X hashing state. The mining hash of the last header that was hashed is cached so that hashing the same
+/// header with a different nonce only requires the three Sha3 rounds.
+#[derive(Debug, Clone, Default)]
+pub struct Sha3xHasher {
+    hasher: Sha3_256,
+    cached: Option<(BlockHeader, FixedHash)>,
+}
+
+impl Sha3xHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calculate the achieved Sha3X difficulty of the header
+    pub fn difficulty(&mut self, header: &BlockHeader) -> Result<Difficulty, DifficultyError> {
+        let mining_hash = self.mining_hash(header);
+        self.hasher.update(header.nonce.to_le_bytes());
+        self.hasher.update(mining_hash);
+        self.hasher.update([header.pow.pow_algo as u8]);
+        self.hasher.update(&header.pow.pow_data);
+        let hash = self.hasher.finalize_reset();
+        self.hasher.update(hash);
+        let hash = self.hasher.finalize_reset();
+        self.hasher.update(hash);
+        let hash = self.hasher.finalize_reset();
+        Difficulty::big_endian_difficulty(&hash)
+    }
+
+    fn mining_hash(&mut self, header: &BlockHeader) -> FixedHash {
+        match &self.cached {
+            Some((cached, hash)) if has_same_mining_hash_fields(cached, header) => *hash,
+            _ => {
+                let hash = header.mining_hash();
+                self.cached = Some((header.clone(), hash));
+                hash
+            },
+        }
+    }
+}
+
+/// Returns true if the headers have identical fields for the purposes of the mining hash
+fn has_same_mining_hash_fields(a: &BlockHeader, b: &BlockHeader) -> bool {
+    a.version == b.version &&
+        a.height == b.height &&
+        a.prev_hash == b.prev_hash &&
+        a.timestamp == b.timestamp &&
+        a.input_mr == b.input_mr &&
+        a.output_mr == b.output_mr &&
+        a.output_smt_size == b.output_smt_size &&
+        a.kernel_mr == b.kernel_mr &&
+        a.kernel_mmr_size == b.kernel_mmr_size &&
+        a.total_kernel_offset == b.total_kernel_offset &&
+        a.total_script_offset == b.total_script_offset &&
+        a.validator_node_mr == b.validator_node_mr &&
+        a.validator_node_size == b.validator_node_size
 }
 
 #[cfg(test)]
@@ -63,7 +131,11 @@ pub mod test {
 
     use crate::{
         blocks::BlockHeader,
-        proof_of_work::{sha3x_pow::sha3x_difficulty, Difficulty, PowAlgorithm},
+        proof_of_work::{
+            sha3x_pow::{sha3x_difficulty, sha3x_difficulty_batch},
+            Difficulty,
+            PowAlgorithm,
+        },
     };
 
     /// A simple example miner. It starts at nonce = 0 and iterates until it finds a header hash that meets the desired
@@ -103,4 +175,22 @@ pub mod test {
         println!("{:?}", header);
         assert_eq!(sha3x_difficulty(&header).unwrap(), Difficulty::from_u64(28).unwrap());
     }
+
+    #[test]
+    fn batch_matches_single_header_difficulty() {
+        let mut headers = Vec::new();
+        let mut header = get_header();
+        for nonce in 0..20 {
+            header.nonce = nonce;
+            if nonce == 10 {
+                header.timestamp = EpochTime::from(header.timestamp.as_u64() + 1);
+            }
+            headers.push(header.clone());
+        }
+        let batch = sha3x_difficulty_batch(&headers);
+        assert_eq!(batch.len(), headers.len());
+        for (header, difficulty) in headers.iter().zip(batch) {
+            assert_eq!(difficulty.unwrap(), sha3x_difficulty(header).unwrap());
+        }
+    }
 }
@@ -24,56 +24,130 @@ use std::cmp;
 
 use tari_utilities::epoch_time::EpochTime;
 
-use crate::proof_of_work::{difficulty::DifficultyAdjustment, lwma_diff::LinearWeightedMovingAverage, Difficulty};
+use crate::proof_of_work::{
+    asert_diff::AbsolutelyScheduledExponentiallyRisingTargets,
+    difficulty::DifficultyAdjustment,
+    lwma_diff::LinearWeightedMovingAverage,
+    Difficulty,
+    DifficultyAlgorithm,
+};
+
+#[derive(Debug, Clone)]
+enum WindowAlgorithm {
+    Lwma(LinearWeightedMovingAverage),
+    Asert(AbsolutelyScheduledExponentiallyRisingTargets),
+}
 
 /// A window of target difficulties
 #[derive(Debug, Clone)]
 pub struct TargetDifficultyWindow {
-    lwma: LinearWeightedMovingAverage,
+    algorithm: WindowAlgorithm,
 }
 
 impl TargetDifficultyWindow {
-    /// Initialize a new `TargetDifficultyWindow`
+    /// Initialize a new `TargetDifficultyWindow` that uses LWMA
     pub(crate) fn new(block_window: usize, target_time: u64) -> Result<Self, String> {
-        Ok(Self {
-            lwma: LinearWeightedMovingAverage::new(block_window, target_time)?,
-        })
+        Self::with_algorithm(DifficultyAlgorithm::Lwma, block_window, target_time)
+    }
+
+    /// Initialize a new `TargetDifficultyWindow` that uses the given difficulty adjustment algorithm
+    pub(crate) fn with_algorithm(
+        algorithm: DifficultyAlgorithm,
+        block_window: usize,
+        target_time: u64,
+    ) -> Result<Self, String> {
+        let algorithm = match algorithm {
+            DifficultyAlgorithm::Lwma => {
+                WindowAlgorithm::Lwma(LinearWeightedMovingAverage::new(block_window, target_time)?)
+            },
+            DifficultyAlgorithm::Asert {
+                half_life,
+                anchor_height,
+                anchor_timestamp,
+            } => WindowAlgorithm::Asert(AbsolutelyScheduledExponentiallyRisingTargets::new(
+                block_window,
+                target_time,
+                half_life,
+                anchor_height,
+                EpochTime::from(anchor_timestamp),
+            )?),
+        };
+        Ok(Self { algorithm })
+    }
+
+    /// The difficulty adjustment algorithm used by this window
+    pub fn algorithm(&self) -> &dyn DifficultyAdjustment {
+        match &self.algorithm {
+            WindowAlgorithm::Lwma(lwma) => lwma,
+            WindowAlgorithm::Asert(asert) => asert,
+        }
+    }
+
+    fn algorithm_mut(&mut self) -> &mut dyn DifficultyAdjustment {
+        match &mut self.algorithm {
+            WindowAlgorithm::Lwma(lwma) => lwma,
+            WindowAlgorithm::Asert(asert) => asert,
+        }
     }
 
     /// Appends a target difficulty. If the number of stored difficulties exceeds the block window, the stored
     /// difficulty at the front is removed keeping the size of the stored difficulties equal to the block window.
     #[inline]
     pub fn add_back(&mut self, time: EpochTime, difficulty: Difficulty) {
-        self.lwma.add_back(time, difficulty);
+        // Neither algorithm rejects data points
+        let _result = self.algorithm_mut().add(time, difficulty);
     }
 
     /// Prepends a target difficulty. If the number of stored difficulties exceeds the block window, the stored
     /// difficulty at the back is removed keeping the size of the stored difficulties equal to the block window.
     #[inline]
     pub fn add_front(&mut self, time: EpochTime, difficulty: Difficulty) {
-        self.lwma.add_front(time, difficulty);
+        self.algorithm_mut().add_front(time, difficulty);
+    }
+
+    /// Sets the target difficulty of the anchor block used by ASERT. LWMA does not use an anchor.
+    pub fn set_anchor_difficulty(&mut self, difficulty: Difficulty) {
+        if let WindowAlgorithm::Asert(asert) = &mut self.algorithm {
+            asert.set_anchor_difficulty(difficulty);
+        }
+    }
+
+    /// Sets the height and timestamp of the block that the next block builds on, which ASERT measures the schedule to.
+    /// LWMA only uses the samples in the window.
+    pub fn set_chain_tip(&mut self, height: u64, timestamp: EpochTime) {
+        if let WindowAlgorithm::Asert(asert) = &mut self.algorithm {
+            asert.set_chain_tip(height, timestamp);
+        }
     }
 
     /// Returns true of the TargetDifficulty has `block_window` data points, otherwise false
     #[inline]
     pub fn is_full(&self) -> bool {
-        self.lwma.is_full()
+        self.algorithm().is_full()
     }
 
     /// Returns the number of target difficulties in the window
     pub fn len(&self) -> usize {
-        self.lwma.num_samples()
+        self.algorithm().num_samples()
     }
 
     /// Returns true if the window is empty
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.lwma.num_samples() == 0
+        self.len() == 0
+    }
+
+    /// Returns the timestamps and target difficulties in the window, oldest first
+    pub fn samples(&self) -> Box<dyn Iterator<Item = &(EpochTime, Difficulty)> + '_> {
+        match &self.algorithm {
+            WindowAlgorithm::Lwma(lwma) => Box::new(lwma.samples()),
+            WindowAlgorithm::Asert(asert) => Box::new(asert.samples()),
+        }
     }
 
     /// Calculates the target difficulty for the current set of target difficulties.
     pub fn calculate(&self, min: Difficulty, max: Difficulty) -> Difficulty {
-        let difficulty = self.lwma.get_difficulty().unwrap_or(min);
+        let difficulty = self.algorithm().get_difficulty().unwrap_or(min);
         cmp::max(min, cmp::min(max, difficulty))
     }
 }
@@ -105,4 +179,31 @@ mod test {
             Difficulty::from_u64(100).unwrap()
         );
     }
+
+    #[test]
+    fn it_uses_the_selected_algorithm() {
+        let mut lwma = TargetDifficultyWindow::with_algorithm(DifficultyAlgorithm::Lwma, 5, 60).unwrap();
+        let algorithm = DifficultyAlgorithm::Asert {
+            half_life: 600,
+            anchor_height: 0,
+            anchor_timestamp: 0,
+        };
+        let mut asert = TargetDifficultyWindow::with_algorithm(algorithm, 5, 60).unwrap();
+        for i in 0..6 {
+            // Blocks are found twice as fast as the target time
+            lwma.add_back(EpochTime::from(i * 30), Difficulty::from_u64(1000).unwrap());
+            asert.add_back(EpochTime::from(i * 30), Difficulty::from_u64(1000).unwrap());
+        }
+        lwma.set_chain_tip(5, EpochTime::from(150));
+        asert.set_chain_tip(5, EpochTime::from(150));
+        lwma.set_anchor_difficulty(Difficulty::from_u64(1000).unwrap());
+        asert.set_anchor_difficulty(Difficulty::from_u64(1000).unwrap());
+        assert!(lwma.is_full());
+        assert!(asert.is_full());
+        let min = Difficulty::min();
+        let max = Difficulty::max();
+        assert_eq!(lwma.calculate(min, max), Difficulty::from_u64(2000).unwrap());
+        // 150s ahead of schedule is a quarter of a half life
+        assert_eq!(asert.calculate(min, max), Difficulty::from_u64(1189).unwrap());
+    }
 }
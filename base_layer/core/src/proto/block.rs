@@ -200,6 +200,8 @@ impl TryFrom<proto::NewBlock> for NewBlock {
                 .map(|bytes| PrivateKey::from_canonical_bytes(bytes))
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|_| "Invalid excess signature scalar")?,
+            kernel_short_ids: new_block.kernel_short_ids,
+            short_id_salt: new_block.short_id_salt,
         })
     }
 }
@@ -213,6 +215,8 @@ impl TryFrom<NewBlock> for proto::NewBlock {
             coinbase_kernel: Some(new_block.coinbase_kernel.into()),
             coinbase_output: Some(new_block.coinbase_output.try_into()?),
             kernel_excess_sigs: new_block.kernel_excess_sigs.into_iter().map(|s| s.to_vec()).collect(),
+            kernel_short_ids: new_block.kernel_short_ids,
+            short_id_salt: new_block.short_id_salt,
         })
     }
 }
@@ -31,13 +31,14 @@ use std::{
 use tari_common::configuration::Network;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
+    epoch::VnEpoch,
     tari_address::TariAddress,
     types::{Commitment, FixedHash, HashOutput, PublicKey, Signature},
 };
 use tari_mmr::sparse_merkle_tree::{NodeKey, ValueHash};
 use tari_storage::lmdb_store::LMDBConfig;
 use tari_test_utils::paths::create_temporary_data_path;
-use tari_utilities::ByteArray;
+use tari_utilities::{epoch_time::EpochTime, ByteArray};
 
 use super::{create_block, mine_to_difficulty};
 use crate::{
@@ -45,6 +46,7 @@ use crate::{
     chain_storage::{
         create_lmdb_database,
         BlockAddResult,
+        BlockBalanceSums,
         BlockchainBackend,
         BlockchainDatabase,
         BlockchainDatabaseConfig,
@@ -55,12 +57,16 @@ use crate::{
         DbTransaction,
         DbValue,
         HorizonData,
+        HorizonSyncCheckpoint,
         InputMinedInfo,
         LMDBDatabase,
+        LmdbCompactionResult,
+        MapUtilization,
         MmrTree,
         OutputMinedInfo,
         Reorg,
         TemplateRegistrationEntry,
+        ValidatorNodeEpochSnapshot,
         Validators,
     },
     consensus::{chain_strength_comparer::ChainStrengthComparerBuilder, ConsensusConstantsBuilder, ConsensusManager},
@@ -276,6 +282,16 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_kernel_by_excess_sig(excess_sig)
     }
 
+    fn fetch_kernel_by_burn_commitment(
+        &self,
+        burn_commitment: &Commitment,
+    ) -> Result<Option<(TransactionKernel, HashOutput)>, ChainStorageError> {
+        self.db
+            .as_ref()
+            .unwrap()
+            .fetch_kernel_by_burn_commitment(burn_commitment)
+    }
+
     fn fetch_outputs_in_block_with_spend_state(
         &self,
         header_hash: &HashOutput,
@@ -295,6 +311,18 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_input(output_hash)
     }
 
+    fn output_indexes_enabled(&self) -> bool {
+        self.db.as_ref().unwrap().output_indexes_enabled()
+    }
+
+    fn fetch_outputs_by_payref(&self, payrefs: &[FixedHash]) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_outputs_by_payref(payrefs)
+    }
+
+    fn fetch_outputs_by_script_key(&self, script_key: &PublicKey) -> Result<Vec<OutputMinedInfo>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_outputs_by_script_key(script_key)
+    }
+
     fn fetch_unspent_output_hash_by_commitment(
         &self,
         commitment: &Commitment,
@@ -380,10 +408,29 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_monero_seed_first_seen_height(seed)
     }
 
+    fn fetch_target_difficulty_window(
+        &self,
+        pow_algo: PowAlgorithm,
+        tip_hash: &HashOutput,
+    ) -> Result<Option<Vec<(EpochTime, Difficulty)>>, ChainStorageError> {
+        self.db
+            .as_ref()
+            .unwrap()
+            .fetch_target_difficulty_window(pow_algo, tip_hash)
+    }
+
     fn fetch_horizon_data(&self) -> Result<Option<HorizonData>, ChainStorageError> {
         self.db.as_ref().unwrap().fetch_horizon_data()
     }
 
+    fn fetch_horizon_sync_checkpoint(&self) -> Result<Option<HorizonSyncCheckpoint>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_horizon_sync_checkpoint()
+    }
+
+    fn fetch_block_balance_sums(&self, height: u64) -> Result<Option<BlockBalanceSums>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_block_balance_sums(height)
+    }
+
     fn get_stats(&self) -> Result<DbBasicStats, ChainStorageError> {
         self.db.as_ref().unwrap().get_stats()
     }
@@ -392,6 +439,21 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().fetch_total_size_stats()
     }
 
+    fn fetch_map_utilization(&self) -> Result<MapUtilization, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_map_utilization()
+    }
+
+    fn grow_map_if_required(&mut self, max_utilization_percentage: u8) -> Result<bool, ChainStorageError> {
+        self.db
+            .as_mut()
+            .unwrap()
+            .grow_map_if_required(max_utilization_percentage)
+    }
+
+    fn compact(&self) -> Result<LmdbCompactionResult, ChainStorageError> {
+        self.db.as_ref().unwrap().compact()
+    }
+
     fn bad_block_exists(&self, block_hash: HashOutput) -> Result<(bool, String), ChainStorageError> {
         self.db.as_ref().unwrap().bad_block_exists(block_hash)
     }
@@ -408,6 +470,13 @@ impl BlockchainBackend for TempDatabase {
         self.db.as_ref().unwrap().get_shard_key(height, public_key)
     }
 
+    fn fetch_validator_node_epoch_snapshot(
+        &self,
+        epoch: VnEpoch,
+    ) -> Result<Option<ValidatorNodeEpochSnapshot>, ChainStorageError> {
+        self.db.as_ref().unwrap().fetch_validator_node_epoch_snapshot(epoch)
+    }
+
     fn fetch_template_registrations(
         &self,
         start_height: u64,
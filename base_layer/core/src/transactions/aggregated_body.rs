@@ -243,6 +243,9 @@ impl AggregateBody {
         let mut coinbase_utxo_sum = Commitment::default();
         let mut coinbase_kernel = None;
         let mut coinbase_counter = 0;
+        // The coinbase may be split across several outputs. The sum of the revealed values of the outputs is a cheap
+        // check of the aggregate amount, before the commitment sum is checked against the kernel.
+        let mut revealed_coinbase_value = MicroMinotari::zero();
         for utxo in self.outputs() {
             if utxo.features.output_type == OutputType::Coinbase {
                 coinbase_counter += 1;
@@ -251,6 +254,9 @@ impl AggregateBody {
                     return Err(TransactionError::InvalidCoinbaseMaturity);
                 }
                 coinbase_utxo_sum = &coinbase_utxo_sum + &utxo.commitment;
+                revealed_coinbase_value = revealed_coinbase_value
+                    .checked_add(utxo.minimum_value_promise)
+                    .ok_or(TransactionError::InvalidCoinbase)?;
             }
         }
 
@@ -258,6 +264,16 @@ impl AggregateBody {
             return Err(TransactionError::NoCoinbase);
         }
 
+        if revealed_coinbase_value > reward {
+            warn!(
+                target: LOG_TARGET,
+                "Coinbase outputs reveal a total value of {}, which is more than the reward of {}",
+                revealed_coinbase_value,
+                reward
+            );
+            return Err(TransactionError::InvalidCoinbase);
+        }
+
         debug!(
             target: LOG_TARGET,
             "{} coinbases found in body.", coinbase_counter,
@@ -296,7 +312,14 @@ impl AggregateBody {
             if !output.is_coinbase() && !output.features.coinbase_extra.is_empty() {
                 return Err(TransactionError::NonCoinbaseHasOutputFeaturesCoinbaseExtra);
             }
+        }
 
+        self.check_coinbase_extra_size(max_coinbase_metadata_size)
+    }
+
+    /// Checks that the `coinbase_extra` field of every coinbase output is within the consensus maximum size
+    pub fn check_coinbase_extra_size(&self, max_coinbase_metadata_size: u32) -> Result<(), TransactionError> {
+        for output in self.outputs() {
             if output.is_coinbase() && output.features.coinbase_extra.len() > max_coinbase_metadata_size as usize {
                 return Err(TransactionError::InvalidOutputFeaturesCoinbaseExtraSize {
                     len: output.features.coinbase_extra.len(),
@@ -21,11 +21,13 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //
 
+use std::convert::TryFrom;
+
 use chacha20poly1305::aead::OsRng;
 use log::*;
 use tari_common_types::{
     tari_address::TariAddress,
-    types::{Commitment, PrivateKey, PublicKey},
+    types::{Commitment, PrivateKey, PublicKey, Signature},
 };
 use tari_crypto::keys::PublicKey as PK;
 use tari_key_manager::key_manager_service::{KeyManagerInterface, KeyManagerServiceError};
@@ -113,6 +115,12 @@ pub enum CoinbaseBuildError {
     KeyManagerServiceError(String),
     #[error("Conversion error: {0}")]
     ByteArrayError(String),
+    #[error("The coinbase extra is {len} bytes, but the consensus maximum is {max} bytes")]
+    CoinbaseExtraTooLarge { len: usize, max: u32 },
+    #[error("The coinbase extra {field} is {len} bytes, but a field can be at most 255 bytes")]
+    CoinbaseExtraFieldTooLarge { field: &'static str, len: usize },
+    #[error("A split coinbase must have at least one recipient with a non-zero share")]
+    NoCoinbaseShares,
 }
 
 impl From<ByteArrayError> for CoinbaseBuildError {
@@ -133,6 +141,123 @@ impl From<KeyManagerServiceError> for CoinbaseBuildError {
     }
 }
 
+const COINBASE_EXTRA_MARKER: u8 = 0x01;
+const COINBASE_EXTRA_POOL_TAG: u8 = 0x01;
+const COINBASE_EXTRA_MINER_ID: u8 = 0x02;
+const COINBASE_EXTRA_DATA: u8 = 0x03;
+
+/// A structured `coinbase_extra` payload that identifies the pool and miner that mined a block. It is encoded as a
+/// marker byte followed by a type-length-value entry for each field that is set, so that it can be told apart from the
+/// free text that miners have historically stored in the `coinbase_extra` field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoinbaseExtra {
+    pool_tag: Option<String>,
+    miner_id: Option<String>,
+    data: Vec<u8>,
+}
+
+impl CoinbaseExtra {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tag the coinbase with the name of the mining pool
+    pub fn with_pool_tag<T: Into<String>>(mut self, pool_tag: T) -> Self {
+        self.pool_tag = Some(pool_tag.into());
+        self
+    }
+
+    /// Tag the coinbase with the identity of the miner, e.g. a worker name
+    pub fn with_miner_id<T: Into<String>>(mut self, miner_id: T) -> Self {
+        self.miner_id = Some(miner_id.into());
+        self
+    }
+
+    /// Add arbitrary data to the coinbase
+    pub fn with_data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    pub fn pool_tag(&self) -> Option<&str> {
+        self.pool_tag.as_deref()
+    }
+
+    pub fn miner_id(&self) -> Option<&str> {
+        self.miner_id.as_deref()
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Encode the payload, checking that it fits in `max_length` bytes. The consensus maximum is given by
+    /// [ConsensusConstants::coinbase_output_features_extra_max_length].
+    pub fn to_bytes(&self, max_length: u32) -> Result<Vec<u8>, CoinbaseBuildError> {
+        let mut bytes = vec![COINBASE_EXTRA_MARKER];
+        let fields = [
+            (
+                COINBASE_EXTRA_POOL_TAG,
+                "pool tag",
+                self.pool_tag.as_ref().map(|t| t.as_bytes()),
+            ),
+            (
+                COINBASE_EXTRA_MINER_ID,
+                "miner id",
+                self.miner_id.as_ref().map(|t| t.as_bytes()),
+            ),
+            (
+                COINBASE_EXTRA_DATA,
+                "data",
+                Some(self.data.as_slice()).filter(|d| !d.is_empty()),
+            ),
+        ];
+        for (field_type, field, value) in fields {
+            let Some(value) = value else { continue };
+            let len = u8::try_from(value.len()).map_err(|_| CoinbaseBuildError::CoinbaseExtraFieldTooLarge {
+                field,
+                len: value.len(),
+            })?;
+            bytes.push(field_type);
+            bytes.push(len);
+            bytes.extend_from_slice(value);
+        }
+        if bytes.len() > max_length as usize {
+            return Err(CoinbaseBuildError::CoinbaseExtraTooLarge {
+                len: bytes.len(),
+                max: max_length,
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Decode a structured payload from a coinbase output's `coinbase_extra` field. Returns `None` if the field does
+    /// not hold a structured payload, e.g. if it holds free text.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&marker, mut rest) = bytes.split_first()?;
+        if marker != COINBASE_EXTRA_MARKER {
+            return None;
+        }
+        let mut extra = Self::default();
+        while let [field_type, len, tail @ ..] = rest {
+            let len = usize::from(*len);
+            if tail.len() < len {
+                return None;
+            }
+            let (value, tail) = tail.split_at(len);
+            match *field_type {
+                COINBASE_EXTRA_POOL_TAG => extra.pool_tag = Some(String::from_utf8(value.to_vec()).ok()?),
+                COINBASE_EXTRA_MINER_ID => extra.miner_id = Some(String::from_utf8(value.to_vec()).ok()?),
+                COINBASE_EXTRA_DATA => extra.data = value.to_vec(),
+                // Unknown fields are skipped so that fields can be added in future
+                _ => {},
+            }
+            rest = tail;
+        }
+        rest.is_empty().then_some(extra)
+    }
+}
+
 pub struct CoinbaseBuilder<TKeyManagerInterface> {
     key_manager: TKeyManagerInterface,
     block_height: Option<u64>,
@@ -144,6 +269,7 @@ pub struct CoinbaseBuilder<TKeyManagerInterface> {
     script: Option<TariScript>,
     covenant: Covenant,
     extra: Option<Vec<u8>>,
+    coinbase_extra: Option<CoinbaseExtra>,
     range_proof_type: Option<RangeProofType>,
 }
 
@@ -164,6 +290,7 @@ where TKeyManagerInterface: TransactionKeyManagerInterface
             script: None,
             covenant: Covenant::default(),
             extra: None,
+            coinbase_extra: None,
             range_proof_type: None,
         }
     }
@@ -226,8 +353,14 @@ where TKeyManagerInterface: TransactionKeyManagerInterface
         self
     }
 
-    /// Provide some arbitrary additional information that will be stored in the coinbase output's `coinbase_extra`
-    /// field.
+    /// Provide a structured payload, e.g. a pool tag and miner id, that will be stored in the coinbase output's
+    /// `coinbase_extra` field. This takes precedence over [with_extra](Self::with_extra).
+    pub fn with_coinbase_extra(mut self, coinbase_extra: CoinbaseExtra) -> Self {
+        self.coinbase_extra = Some(coinbase_extra);
+        self
+    }
+
+    /// Set the range proof type of the coinbase output.
     pub fn with_range_proof_type(mut self, range_proof_type: RangeProofType) -> Self {
         self.range_proof_type = Some(range_proof_type);
         self
@@ -270,6 +403,19 @@ where TKeyManagerInterface: TransactionKeyManagerInterface
         let covenant = self.covenant;
         let script = self.script.ok_or(CoinbaseBuildError::MissingScript)?;
         let range_proof_type = self.range_proof_type.ok_or(CoinbaseBuildError::MissingRangeProofType)?;
+        let max_extra_length = constants.coinbase_output_features_extra_max_length();
+        let extra = match self.coinbase_extra {
+            Some(coinbase_extra) => Some(coinbase_extra.to_bytes(max_extra_length)?),
+            None => self.extra,
+        };
+        if let Some(extra) = &extra {
+            if extra.len() > max_extra_length as usize {
+                return Err(CoinbaseBuildError::CoinbaseExtraTooLarge {
+                    len: extra.len(),
+                    max: max_extra_length,
+                });
+            }
+        }
 
         let kernel_features = KernelFeatures::create_coinbase();
         let metadata = TransactionMetadata::new_with_features(0.into(), 0, kernel_features);
@@ -307,10 +453,16 @@ where TKeyManagerInterface: TransactionKeyManagerInterface
         // generate tx details
         let value: u64 = total_reward.into();
         let output_features =
-            OutputFeatures::create_coinbase(height + constants.coinbase_min_maturity(), self.extra, range_proof_type);
+            OutputFeatures::create_coinbase(height + constants.coinbase_min_maturity(), extra, range_proof_type);
         let encrypted_data = self
             .key_manager
-            .encrypt_data_for_recovery(&spending_key_id, Some(&encryption_key_id), total_reward.into())
+            .encrypt_data_for_recovery(
+                &spending_key_id,
+                Some(&encryption_key_id),
+                total_reward.into(),
+                None,
+                None,
+            )
             .await?;
         let minimum_value_promise = match range_proof_type {
             RangeProofType::BulletProofPlus => MicroMinotari::zero(),
@@ -483,6 +635,132 @@ pub async fn generate_coinbase_with_wallet_output(
     Ok((transaction.clone(), output.clone(), kernel.clone(), wallet_output))
 }
 
+/// A recipient of part of a split coinbase
+#[derive(Debug, Clone)]
+pub struct CoinbaseRecipient {
+    pub address: TariAddress,
+    /// The recipient's share of the block reward and fees, relative to the shares of the other recipients
+    pub share: u64,
+    pub stealth_payment: bool,
+    pub range_proof_type: RangeProofType,
+    pub extra: Vec<u8>,
+}
+
+/// Split `total` in proportion to `shares`. The amounts always add up to `total`; the micro Minotari that are left over
+/// after rounding down are given to the recipients with the largest remainders, in order.
+pub fn split_coinbase_amount(total: MicroMinotari, shares: &[u64]) -> Result<Vec<MicroMinotari>, CoinbaseBuildError> {
+    let total_shares = shares.iter().map(|s| u128::from(*s)).sum::<u128>();
+    if total_shares == 0 {
+        return Err(CoinbaseBuildError::NoCoinbaseShares);
+    }
+    let total = u128::from(total.as_u64());
+    let mut amounts = Vec::with_capacity(shares.len());
+    let mut remainders = Vec::with_capacity(shares.len());
+    for (i, share) in shares.iter().enumerate() {
+        let scaled = total * u128::from(*share);
+        amounts.push(scaled / total_shares);
+        remainders.push((scaled % total_shares, i));
+    }
+    // Sort by largest remainder first, keeping the recipient order for equal remainders
+    remainders.sort_by(|(a, i), (b, j)| b.cmp(a).then(i.cmp(j)));
+    let left_over = total - amounts.iter().sum::<u128>();
+    for (_, i) in remainders
+        .into_iter()
+        .take(usize::try_from(left_over).unwrap_or(usize::MAX))
+    {
+        amounts[i] += 1;
+    }
+    // Each amount is at most `total`, which is a u64
+    Ok(amounts
+        .into_iter()
+        .map(|a| MicroMinotari::from(u64::try_from(a).unwrap_or(u64::MAX)))
+        .collect())
+}
+
+/// Generate a coinbase that splits the block reward plus fees across several recipients in proportion to their shares,
+/// e.g. so that a pool can pay its miners directly from the coinbase. One output is created per recipient with a
+/// non-zero amount, and a single coinbase kernel is created with an aggregate signature for all the outputs.
+pub async fn generate_split_coinbase(
+    fee: MicroMinotari,
+    reward: MicroMinotari,
+    height: u64,
+    recipients: &[CoinbaseRecipient],
+    key_manager: &MemoryDbKeyManager,
+    consensus_constants: &ConsensusConstants,
+) -> Result<(Vec<TransactionOutput>, TransactionKernel), CoinbaseBuildError> {
+    let shares = recipients.iter().map(|r| r.share).collect::<Vec<_>>();
+    let amounts = split_coinbase_amount(reward + fee, &shares)?;
+    // The script key is not used in the Diffie-Hellmann protocol, so we assign default.
+    let script_key_id = TariKeyId::default();
+
+    let mut outputs = Vec::with_capacity(recipients.len());
+    let mut total_excess = Commitment::default();
+    let mut total_nonce = PublicKey::default();
+    let mut private_keys = Vec::with_capacity(recipients.len());
+    let mut last_kernel = None;
+    for (recipient, amount) in recipients.iter().zip(amounts) {
+        if amount == MicroMinotari::zero() {
+            continue;
+        }
+        let (_, output, kernel, wallet_output) = generate_coinbase_with_wallet_output(
+            MicroMinotari::zero(),
+            amount,
+            height,
+            &recipient.extra,
+            key_manager,
+            &script_key_id,
+            &recipient.address,
+            recipient.stealth_payment,
+            consensus_constants,
+            recipient.range_proof_type,
+        )
+        .await?;
+        outputs.push(output);
+        let (nonce_id, public_nonce) = key_manager
+            .get_next_key(TransactionKeyManagerBranch::KernelNonce.get_branch_key())
+            .await?;
+        total_nonce = &total_nonce + &public_nonce;
+        total_excess = &total_excess + &kernel.excess;
+        private_keys.push((wallet_output.spending_key_id, nonce_id));
+        last_kernel = Some(kernel);
+    }
+    let last_kernel = last_kernel.ok_or(CoinbaseBuildError::NoCoinbaseShares)?;
+
+    let kernel_version = TransactionKernelVersion::get_current_version();
+    let kernel_message = TransactionKernel::build_kernel_signature_message(
+        &kernel_version,
+        last_kernel.fee,
+        last_kernel.lock_height,
+        &last_kernel.features,
+        &None,
+    );
+    let mut kernel_signature = Signature::default();
+    for (spending_key_id, nonce_id) in private_keys {
+        kernel_signature = &kernel_signature +
+            &key_manager
+                .get_partial_txo_kernel_signature(
+                    &spending_key_id,
+                    &nonce_id,
+                    &total_nonce,
+                    total_excess.as_public_key(),
+                    &kernel_version,
+                    &kernel_message,
+                    &last_kernel.features,
+                    TxoStage::Output,
+                )
+                .await?;
+    }
+    let kernel = KernelBuilder::new()
+        .with_fee(MicroMinotari::zero())
+        .with_features(last_kernel.features)
+        .with_lock_height(last_kernel.lock_height)
+        .with_excess(&total_excess)
+        .with_signature(kernel_signature)
+        .build()
+        .map_err(|e| CoinbaseBuildError::BuildError(e.to_string()))?;
+    Ok((outputs, kernel))
+}
+
 #[cfg(test)]
 mod test {
     use tari_common::configuration::Network;
@@ -491,12 +769,13 @@ mod test {
     use crate::{
         consensus::{emission::Emission, ConsensusManager, ConsensusManagerBuilder},
         transactions::{
-            coinbase_builder::CoinbaseBuildError,
+            coinbase_builder::{generate_split_coinbase, split_coinbase_amount, CoinbaseBuildError, CoinbaseRecipient},
             crypto_factories::CryptoFactories,
-            tari_amount::uT,
+            tari_amount::{uT, MicroMinotari},
             test_helpers::TestParams,
             transaction_components::{KernelFeatures, OutputFeatures, OutputType, TransactionError, TransactionKernel},
             CoinbaseBuilder,
+            CoinbaseExtra,
         },
         validation::aggregate_body::AggregateBodyInternalConsistencyValidator,
     };
@@ -993,4 +1272,116 @@ mod test {
             .unwrap();
         body2.verify_kernel_signatures().unwrap();
     }
+
+    #[test]
+    fn coinbase_extra_round_trip() {
+        let extra = CoinbaseExtra::new()
+            .with_pool_tag("pool")
+            .with_miner_id("worker1")
+            .with_data(vec![1, 2, 3]);
+        let bytes = extra.to_bytes(64).unwrap();
+        assert_eq!(bytes.len(), 1 + 2 + 4 + 2 + 7 + 2 + 3);
+        let decoded = CoinbaseExtra::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, extra);
+        assert_eq!(decoded.pool_tag(), Some("pool"));
+        assert_eq!(decoded.miner_id(), Some("worker1"));
+        assert_eq!(decoded.data(), &[1, 2, 3]);
+
+        // Free text is not a structured payload
+        assert!(CoinbaseExtra::from_bytes(b"minotari_miner").is_none());
+        // Truncated payloads are rejected
+        assert!(CoinbaseExtra::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert_eq!(
+            extra.to_bytes(8).unwrap_err(),
+            CoinbaseBuildError::CoinbaseExtraTooLarge { len: 21, max: 8 }
+        );
+    }
+
+    #[tokio::test]
+    async fn coinbase_extra_too_large() {
+        let (builder, rules, _, key_manager) = get_builder();
+        let p = TestParams::new(&key_manager).await;
+        let wallet_payment_address = TariAddress::default();
+        let max = rules
+            .consensus_constants(42)
+            .coinbase_output_features_extra_max_length();
+        let builder = builder
+            .with_block_height(42)
+            .with_fees(145 * uT)
+            .with_spend_key_id(p.spend_key_id.clone())
+            .with_encryption_key_id(TariKeyId::default())
+            .with_sender_offset_key_id(p.sender_offset_key_id)
+            .with_script_key_id(p.script_key_id)
+            .with_script(one_sided_payment_script(wallet_payment_address.public_key()))
+            .with_range_proof_type(RangeProofType::RevealedValue)
+            .with_extra(vec![0u8; max as usize + 1]);
+        assert_eq!(
+            builder
+                .build(rules.consensus_constants(42), rules.emission_schedule())
+                .await
+                .unwrap_err(),
+            CoinbaseBuildError::CoinbaseExtraTooLarge {
+                len: max as usize + 1,
+                max
+            }
+        );
+    }
+
+    #[test]
+    fn it_splits_coinbase_amounts_exactly() {
+        let amounts = split_coinbase_amount(MicroMinotari(100), &[1, 1, 1]).unwrap();
+        assert_eq!(amounts, vec![MicroMinotari(34), MicroMinotari(33), MicroMinotari(33)]);
+        let amounts = split_coinbase_amount(MicroMinotari(10), &[5, 0, 2, 3]).unwrap();
+        assert_eq!(amounts, vec![
+            MicroMinotari(5),
+            MicroMinotari(0),
+            MicroMinotari(2),
+            MicroMinotari(3)
+        ]);
+        // More shares than micro Minotari
+        let amounts = split_coinbase_amount(MicroMinotari(2), &[1_000, 1_000, 1_001]).unwrap();
+        assert_eq!(amounts.iter().map(|a| a.as_u64()).sum::<u64>(), 2);
+        assert_eq!(amounts[2], MicroMinotari(1));
+        let amounts = split_coinbase_amount(MicroMinotari(u64::MAX), &[u64::MAX, u64::MAX]).unwrap();
+        assert_eq!(amounts, vec![
+            MicroMinotari(u64::MAX / 2 + 1),
+            MicroMinotari(u64::MAX / 2)
+        ]);
+        assert_eq!(
+            split_coinbase_amount(MicroMinotari(10), &[0, 0]).unwrap_err(),
+            CoinbaseBuildError::NoCoinbaseShares
+        );
+    }
+
+    #[tokio::test]
+    async fn split_coinbase() {
+        let (_, rules, factories, key_manager) = get_builder();
+        let constants = rules.consensus_constants(42);
+        let reward = rules.emission_schedule().block_reward(42);
+        let fee = 145 * uT;
+        let recipients = [3u64, 1]
+            .iter()
+            .map(|share| CoinbaseRecipient {
+                address: TariAddress::default(),
+                share: *share,
+                stealth_payment: false,
+                range_proof_type: RangeProofType::RevealedValue,
+                extra: vec![],
+            })
+            .collect::<Vec<_>>();
+        let (outputs, kernel) = generate_split_coinbase(fee, reward, 42, &recipients, &key_manager, constants)
+            .await
+            .unwrap();
+        assert_eq!(outputs.len(), 2);
+        let total = outputs.iter().map(|o| o.minimum_value_promise.as_u64()).sum::<u64>();
+        assert_eq!(total, (reward + fee).as_u64());
+
+        let mut body = AggregateBody::new(Vec::new(), outputs, vec![kernel]);
+        body.sort();
+        body.check_coinbase_output(reward + fee, constants.coinbase_min_maturity(), &factories, 42)
+            .unwrap();
+        body.verify_kernel_signatures().unwrap();
+        body.check_coinbase_output(reward + fee + uT, constants.coinbase_min_maturity(), &factories, 42)
+            .unwrap_err();
+    }
 }
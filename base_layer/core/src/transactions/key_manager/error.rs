@@ -68,6 +68,9 @@ pub enum LedgerDeviceError {
     /// Not yet supported
     #[error("Ledger is not fully supported")]
     NotSupported,
+    /// The user rejected the transaction on the device
+    #[error("The transaction was rejected on the device")]
+    UserRejected,
 }
 
 impl From<ByteArrayError> for LedgerDeviceError {
@@ -0,0 +1,506 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A hardware signer for [WalletType::Ledger](tari_common_types::wallet_types::WalletType) wallets.
+//!
+//! The device derives keys per key manager branch and index from its own copy of the wallet seed, so the host only
+//! ever refers to device keys by [TariKeyId]. Script signatures, the script offset, one-sided metadata signatures and
+//! kernel signatures are produced on the device. Before the kernel of a spend is signed the device is sent the
+//! [TransactionReviewData] so that the user can confirm the transaction on the device.
+//!
+//! Commands are exchanged over a [SignerTransport], which the Ledger HID transport implements when the `ledger`
+//! feature is enabled. Only device applications that set [FLAG_DEVICE_SIGNING] in their `GetVersion` reply implement
+//! the signing instructions, older applications are used through `GetPrivateKey` instead.
+
+use std::sync::Arc;
+#[cfg(feature = "ledger")]
+use std::sync::Mutex;
+
+#[cfg(feature = "ledger")]
+use ledger_transport::APDUCommand;
+#[cfg(feature = "ledger")]
+use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+use strum::IntoEnumIterator;
+use tari_common_types::types::{ComAndPubSignature, Commitment, PrivateKey, PublicKey, Signature};
+use tari_key_manager::key_manager_service::KeyId;
+use tari_utilities::ByteArray;
+
+use crate::transactions::{
+    key_manager::{LedgerDeviceError, TariKeyId, TransactionKeyManagerBranch},
+    tari_amount::MicroMinotari,
+    transaction_components::{RangeProofType, TransactionInputVersion, TransactionOutputVersion},
+};
+
+/// The status word returned by the device when a command succeeded
+pub const STATUS_OK: u16 = 0x9000;
+/// The status word returned by the device when the user rejected the transaction on the device
+pub const STATUS_USER_REJECTED: u16 = 0x6985;
+
+/// Set in the `GetVersion` flags by device applications that implement the signing instructions
+pub const FLAG_DEVICE_SIGNING: u8 = 0x01;
+
+const KEY_LENGTH: usize = 32;
+
+/// The instructions understood by the device. `0x02` and `0x03` are used by `GetPrivateKey` and `Exit` in the
+/// `minotari_ledger_wallet` application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerInstruction {
+    GetVersion = 0x01,
+    GetScriptSignature = 0x04,
+    GetScriptOffset = 0x05,
+    GetMetadataSignature = 0x06,
+    GetKernelSignature = 0x07,
+    ReviewTransaction = 0x08,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerCommand {
+    pub instruction: SignerInstruction,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerResponse {
+    pub status: u16,
+    pub data: Vec<u8>,
+}
+
+/// The transport used to exchange commands with a signing device
+pub trait SignerTransport: Send + Sync {
+    fn exchange(&self, command: &SignerCommand) -> Result<SignerResponse, LedgerDeviceError>;
+}
+
+/// A [SignerTransport] over the native Ledger HID transport
+#[cfg(feature = "ledger")]
+pub struct LedgerHidTransport {
+    transport: Mutex<TransportNativeHID>,
+}
+
+#[cfg(feature = "ledger")]
+impl LedgerHidTransport {
+    pub fn connect() -> Result<Self, LedgerDeviceError> {
+        let hid_api = HidApi::new().map_err(|e| LedgerDeviceError::HidApi(e.to_string()))?;
+        let transport =
+            TransportNativeHID::new(&hid_api).map_err(|e| LedgerDeviceError::NativeTransport(e.to_string()))?;
+        Ok(Self {
+            transport: Mutex::new(transport),
+        })
+    }
+}
+
+#[cfg(feature = "ledger")]
+impl SignerTransport for LedgerHidTransport {
+    fn exchange(&self, command: &SignerCommand) -> Result<SignerResponse, LedgerDeviceError> {
+        let apdu = APDUCommand {
+            cla: 0x80,
+            ins: command.instruction as u8,
+            p1: 0x00,
+            p2: 0x00,
+            data: command.data.clone(),
+        };
+        let transport = self
+            .transport
+            .lock()
+            .map_err(|e| LedgerDeviceError::NativeTransport(e.to_string()))?;
+        let answer = transport
+            .exchange(&apdu)
+            .map_err(|e| LedgerDeviceError::NativeTransport(e.to_string()))?;
+        Ok(SignerResponse {
+            status: answer.retcode(),
+            data: answer.data().to_vec(),
+        })
+    }
+}
+
+/// The `GetVersion` reply of the device application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerAppInfo {
+    pub name: String,
+    pub version: String,
+    pub flags: u8,
+}
+
+impl SignerAppInfo {
+    /// Parses the reply, a format byte followed by the length prefixed name and version and the flags byte
+    fn from_bytes(bytes: &[u8]) -> Result<Self, LedgerDeviceError> {
+        let malformed = || LedgerDeviceError::Processing(format!("Malformed GetVersion reply ({:?})", bytes));
+        let mut rest = match bytes.split_first() {
+            Some((1, rest)) => rest,
+            _ => return Err(malformed()),
+        };
+        let mut read_string = || -> Result<String, LedgerDeviceError> {
+            let (len, tail) = rest.split_first().ok_or_else(malformed)?;
+            let len = usize::from(*len);
+            if tail.len() < len {
+                return Err(malformed());
+            }
+            rest = &tail[len..];
+            String::from_utf8(tail[..len].to_vec()).map_err(|e| LedgerDeviceError::Processing(e.to_string()))
+        };
+        let name = read_string()?;
+        let version = read_string()?;
+        let flags = *rest.first().ok_or_else(malformed)?;
+        Ok(Self { name, version, flags })
+    }
+
+    pub fn supports_signing(&self) -> bool {
+        self.flags & FLAG_DEVICE_SIGNING != 0
+    }
+}
+
+/// The transaction details shown on the device for confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionReviewData {
+    /// The amount sent to the recipient
+    pub amount: MicroMinotari,
+    pub fee: MicroMinotari,
+    pub change: MicroMinotari,
+    pub num_inputs: usize,
+    pub num_outputs: usize,
+}
+
+impl TransactionReviewData {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(40);
+        bytes.extend_from_slice(&self.amount.as_u64().to_le_bytes());
+        bytes.extend_from_slice(&self.fee.as_u64().to_le_bytes());
+        bytes.extend_from_slice(&self.change.as_u64().to_le_bytes());
+        bytes.extend_from_slice(&(self.num_inputs as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.num_outputs as u64).to_le_bytes());
+        bytes
+    }
+}
+
+/// Maps a key manager branch to the branch identifier used for derivation on the device
+fn device_branch(branch: TransactionKeyManagerBranch) -> u8 {
+    match branch {
+        TransactionKeyManagerBranch::DataEncryption => 0x01,
+        TransactionKeyManagerBranch::Coinbase => 0x02,
+        TransactionKeyManagerBranch::CoinbaseScript => 0x03,
+        TransactionKeyManagerBranch::CommitmentMask => 0x04,
+        TransactionKeyManagerBranch::Nonce => 0x05,
+        TransactionKeyManagerBranch::KernelNonce => 0x06,
+        TransactionKeyManagerBranch::ScriptKey => 0x07,
+        TransactionKeyManagerBranch::SenderOffset => 0x08,
+    }
+}
+
+/// Encodes a managed key id as the device branch identifier followed by the little endian key index. Imported keys
+/// are not known to the device.
+fn key_locator(key_id: &TariKeyId) -> Result<[u8; 9], LedgerDeviceError> {
+    match key_id {
+        KeyId::Managed { branch, index } => {
+            let branch = TransactionKeyManagerBranch::iter()
+                .find(|b| b.get_branch_key() == *branch)
+                .ok_or_else(|| LedgerDeviceError::Instruction(format!("Unknown key branch `{}`", branch)))?;
+            let mut locator = [0u8; 9];
+            locator[0] = device_branch(branch);
+            locator[1..].copy_from_slice(&index.to_le_bytes());
+            Ok(locator)
+        },
+        KeyId::Imported { .. } | KeyId::Zero => Err(LedgerDeviceError::NotSupported),
+    }
+}
+
+/// Signs with keys held on a hardware device
+#[derive(Clone)]
+pub struct HardwareSigner {
+    transport: Arc<dyn SignerTransport>,
+    account: u64,
+}
+
+impl HardwareSigner {
+    pub fn new(transport: Arc<dyn SignerTransport>, account: u64) -> Self {
+        Self { transport, account }
+    }
+
+    /// Connects to the first Ledger device found over HID
+    #[cfg(feature = "ledger")]
+    pub fn connect(account: u64) -> Result<Self, LedgerDeviceError> {
+        Ok(Self::new(Arc::new(LedgerHidTransport::connect()?), account))
+    }
+
+    pub fn account(&self) -> u64 {
+        self.account
+    }
+
+    /// Returns the name, version and capabilities of the application running on the device
+    pub fn get_app_info(&self) -> Result<SignerAppInfo, LedgerDeviceError> {
+        let data = self.exchange(SignerInstruction::GetVersion, Vec::new())?;
+        SignerAppInfo::from_bytes(&data)
+    }
+
+    /// Shows the transaction on the device and waits for the user to confirm it
+    pub fn review_transaction(&self, review: &TransactionReviewData) -> Result<(), LedgerDeviceError> {
+        self.exchange(SignerInstruction::ReviewTransaction, review.to_bytes())?;
+        Ok(())
+    }
+
+    /// Creates the script signature for an input, the script key is derived on the device
+    pub fn get_script_signature(
+        &self,
+        script_key_id: &TariKeyId,
+        value: &PrivateKey,
+        spend_private_key: &PrivateKey,
+        commitment: &Commitment,
+        txi_version: &TransactionInputVersion,
+        script_message: &[u8; 32],
+    ) -> Result<ComAndPubSignature, LedgerDeviceError> {
+        let mut data = key_locator(script_key_id)?.to_vec();
+        data.push(txi_version.as_u8());
+        data.extend_from_slice(value.as_bytes());
+        data.extend_from_slice(spend_private_key.as_bytes());
+        data.extend_from_slice(commitment.as_bytes());
+        data.extend_from_slice(script_message);
+        let response = self.exchange(SignerInstruction::GetScriptSignature, data)?;
+        parse_com_and_pub_signature(&response)
+    }
+
+    /// Calculates the script offset on the device from the script keys of the inputs and the sender offset keys of
+    /// the outputs
+    pub fn get_script_offset(
+        &self,
+        script_key_ids: &[TariKeyId],
+        sender_offset_key_ids: &[TariKeyId],
+    ) -> Result<PrivateKey, LedgerDeviceError> {
+        let mut data = Vec::with_capacity(2 + 9 * (script_key_ids.len() + sender_offset_key_ids.len()));
+        for key_ids in [script_key_ids, sender_offset_key_ids] {
+            let len = u8::try_from(key_ids.len())
+                .map_err(|_| LedgerDeviceError::Instruction(format!("Too many keys: {}", key_ids.len())))?;
+            data.push(len);
+            for key_id in key_ids {
+                data.extend_from_slice(&key_locator(key_id)?);
+            }
+        }
+        let response = self.exchange(SignerInstruction::GetScriptOffset, data)?;
+        parse_private_key(&response)
+    }
+
+    /// Creates the complete metadata signature for an output where the wallet is both the sender and the receiver,
+    /// the sender offset key is derived on the device
+    pub fn get_metadata_signature(
+        &self,
+        value: &PrivateKey,
+        spend_private_key: &PrivateKey,
+        sender_offset_key_id: &TariKeyId,
+        txo_version: &TransactionOutputVersion,
+        metadata_signature_message: &[u8; 32],
+        range_proof_type: RangeProofType,
+    ) -> Result<ComAndPubSignature, LedgerDeviceError> {
+        let mut data = key_locator(sender_offset_key_id)?.to_vec();
+        data.push(txo_version.as_u8());
+        data.push(range_proof_type.as_byte());
+        data.extend_from_slice(value.as_bytes());
+        data.extend_from_slice(spend_private_key.as_bytes());
+        data.extend_from_slice(metadata_signature_message);
+        let response = self.exchange(SignerInstruction::GetMetadataSignature, data)?;
+        parse_com_and_pub_signature(&response)
+    }
+
+    /// Creates a partial kernel signature with the kernel nonce derived on the device
+    pub fn get_kernel_signature(
+        &self,
+        signing_key: &PrivateKey,
+        nonce_id: &TariKeyId,
+        challenge: &[u8; 64],
+    ) -> Result<Signature, LedgerDeviceError> {
+        let mut data = key_locator(nonce_id)?.to_vec();
+        data.extend_from_slice(signing_key.as_bytes());
+        data.extend_from_slice(challenge);
+        let response = self.exchange(SignerInstruction::GetKernelSignature, data)?;
+        if response.len() != 2 * KEY_LENGTH {
+            return Err(unexpected_length("kernel signature", 2 * KEY_LENGTH, response.len()));
+        }
+        let public_nonce = PublicKey::from_canonical_bytes(&response[..KEY_LENGTH])?;
+        let signature = PrivateKey::from_canonical_bytes(&response[KEY_LENGTH..])?;
+        Ok(Signature::new(public_nonce, signature))
+    }
+
+    fn exchange(&self, instruction: SignerInstruction, payload: Vec<u8>) -> Result<Vec<u8>, LedgerDeviceError> {
+        let mut data = self.account.to_le_bytes().to_vec();
+        data.extend(payload);
+        let response = self.transport.exchange(&SignerCommand { instruction, data })?;
+        match response.status {
+            STATUS_OK => Ok(response.data),
+            STATUS_USER_REJECTED => Err(LedgerDeviceError::UserRejected),
+            status => Err(LedgerDeviceError::Instruction(format!(
+                "{:?} failed with status {:#06x}",
+                instruction, status
+            ))),
+        }
+    }
+}
+
+fn unexpected_length(item: &str, expected: usize, actual: usize) -> LedgerDeviceError {
+    LedgerDeviceError::Processing(format!(
+        "Unexpected {} length - expected {} got {} bytes",
+        item, expected, actual
+    ))
+}
+
+fn parse_private_key(bytes: &[u8]) -> Result<PrivateKey, LedgerDeviceError> {
+    if bytes.len() != KEY_LENGTH {
+        return Err(unexpected_length("private key", KEY_LENGTH, bytes.len()));
+    }
+    Ok(PrivateKey::from_canonical_bytes(bytes)?)
+}
+
+/// Parses a signature returned as the ephemeral commitment, the ephemeral public key, `u_a`, `u_x` and `u_y`
+fn parse_com_and_pub_signature(bytes: &[u8]) -> Result<ComAndPubSignature, LedgerDeviceError> {
+    if bytes.len() != 5 * KEY_LENGTH {
+        return Err(unexpected_length("signature", 5 * KEY_LENGTH, bytes.len()));
+    }
+    let mut parts = bytes.chunks_exact(KEY_LENGTH);
+    let mut next = || parts.next().expect("length checked above");
+    let ephemeral_commitment = Commitment::from_canonical_bytes(next())?;
+    let ephemeral_pubkey = PublicKey::from_canonical_bytes(next())?;
+    let u_a = PrivateKey::from_canonical_bytes(next())?;
+    let u_x = PrivateKey::from_canonical_bytes(next())?;
+    let u_y = PrivateKey::from_canonical_bytes(next())?;
+    Ok(ComAndPubSignature::new(
+        ephemeral_commitment,
+        ephemeral_pubkey,
+        u_a,
+        u_x,
+        u_y,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    use super::*;
+
+    struct MockTransport {
+        status: u16,
+        response: Vec<u8>,
+        commands: Mutex<Vec<SignerCommand>>,
+    }
+
+    impl MockTransport {
+        fn new(status: u16, response: Vec<u8>) -> Arc<Self> {
+            Arc::new(Self {
+                status,
+                response,
+                commands: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    impl SignerTransport for MockTransport {
+        fn exchange(&self, command: &SignerCommand) -> Result<SignerResponse, LedgerDeviceError> {
+            self.commands.lock().unwrap().push(command.clone());
+            Ok(SignerResponse {
+                status: self.status,
+                data: self.response.clone(),
+            })
+        }
+    }
+
+    #[test]
+    fn it_sends_the_account_and_key_locator_to_the_device() {
+        let (nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+        let mut response = public_nonce.as_bytes().to_vec();
+        response.extend_from_slice(nonce.as_bytes());
+        let transport = MockTransport::new(STATUS_OK, response);
+        let signer = HardwareSigner::new(transport.clone(), 3);
+        let nonce_id = KeyId::Managed {
+            branch: TransactionKeyManagerBranch::KernelNonce.get_branch_key(),
+            index: 42,
+        };
+
+        let signature = signer
+            .get_kernel_signature(&PrivateKey::random(&mut OsRng), &nonce_id, &[7u8; 64])
+            .unwrap();
+        assert_eq!(signature.get_public_nonce(), &public_nonce);
+        assert_eq!(signature.get_signature(), &nonce);
+
+        let commands = transport.commands.lock().unwrap();
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].instruction, SignerInstruction::GetKernelSignature);
+        let data = &commands[0].data;
+        assert_eq!(data[..8], 3u64.to_le_bytes());
+        assert_eq!(data[8], device_branch(TransactionKeyManagerBranch::KernelNonce));
+        assert_eq!(data[9..17], 42u64.to_le_bytes());
+        assert_eq!(data.len(), 8 + 9 + KEY_LENGTH + 64);
+    }
+
+    #[test]
+    fn it_reads_the_signing_capability_from_the_version_reply() {
+        let reply = |flags: u8| {
+            let mut reply = vec![1, 6];
+            reply.extend_from_slice(b"ledger");
+            reply.push(5);
+            reply.extend_from_slice(b"1.0.0");
+            reply.push(flags);
+            reply
+        };
+        let signer = HardwareSigner::new(MockTransport::new(STATUS_OK, reply(0)), 0);
+        let info = signer.get_app_info().unwrap();
+        assert_eq!(info.name, "ledger");
+        assert_eq!(info.version, "1.0.0");
+        assert!(!info.supports_signing());
+
+        let signer = HardwareSigner::new(MockTransport::new(STATUS_OK, reply(FLAG_DEVICE_SIGNING)), 0);
+        assert!(signer.get_app_info().unwrap().supports_signing());
+
+        let signer = HardwareSigner::new(MockTransport::new(STATUS_OK, reply(0)[..9].to_vec()), 0);
+        assert!(matches!(signer.get_app_info(), Err(LedgerDeviceError::Processing(_))));
+    }
+
+    #[test]
+    fn it_handles_device_errors() {
+        let signer = HardwareSigner::new(MockTransport::new(STATUS_USER_REJECTED, Vec::new()), 0);
+        let review = TransactionReviewData {
+            amount: MicroMinotari(1000),
+            fee: MicroMinotari(10),
+            change: MicroMinotari(0),
+            num_inputs: 1,
+            num_outputs: 1,
+        };
+        assert_eq!(
+            signer.review_transaction(&review).unwrap_err(),
+            LedgerDeviceError::UserRejected
+        );
+
+        // Imported keys are not known to the device
+        let signer = HardwareSigner::new(MockTransport::new(STATUS_OK, Vec::new()), 0);
+        let imported = KeyId::Imported {
+            key: PublicKey::default(),
+        };
+        assert_eq!(
+            signer.get_script_offset(&[imported], &[]).unwrap_err(),
+            LedgerDeviceError::NotSupported
+        );
+
+        // Responses of the wrong length are rejected
+        let signer = HardwareSigner::new(MockTransport::new(STATUS_OK, vec![0u8; 31]), 0);
+        assert!(matches!(
+            signer.get_script_offset(&[], &[]),
+            Err(LedgerDeviceError::Processing(_))
+        ));
+    }
+}
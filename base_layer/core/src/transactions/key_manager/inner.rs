@@ -69,13 +69,15 @@ const LOG_TARGET: &str = "key_manager::key_manager_service";
 const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
 
 use crate::{
-    common::ConfidentialOutputHasher,
-    one_sided::diffie_hellman_stealth_domain_hasher,
+    common::{balance_proof::BalanceProof, ConfidentialOutputHasher},
+    one_sided::{diffie_hellman_stealth_domain_hasher, subaddress_key_offset},
     transactions::{
         key_manager::{
             interface::{TransactionKeyManagerBranch, TxoStage},
+            HardwareSigner,
             LedgerDeviceError,
             TariKeyId,
+            TransactionReviewData,
         },
         tari_amount::MicroMinotari,
         transaction_components::{
@@ -106,6 +108,7 @@ pub struct TransactionKeyManagerInner<TBackend> {
     master_seed: CipherSeed,
     crypto_factories: CryptoFactories,
     wallet_type: WalletType,
+    hardware_signer: Option<HardwareSigner>,
 }
 
 #[cfg(feature = "ledger")]
@@ -130,11 +133,57 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             master_seed,
             crypto_factories,
             wallet_type,
+            hardware_signer: Self::connect_hardware_signer(wallet_type),
         };
         km.add_standard_core_branches()?;
         Ok(km)
     }
 
+    #[cfg(feature = "ledger")]
+    fn connect_hardware_signer(wallet_type: WalletType) -> Option<HardwareSigner> {
+        match wallet_type {
+            WalletType::Ledger(account) => match HardwareSigner::connect(account as u64) {
+                Ok(signer) => match signer.get_app_info() {
+                    Ok(info) if info.supports_signing() => Some(signer),
+                    Ok(info) => {
+                        info!(
+                            target: LOG_TARGET,
+                            "Ledger application {} {} does not sign on the device, using GetPrivateKey",
+                            info.name,
+                            info.version
+                        );
+                        None
+                    },
+                    Err(e) => {
+                        warn!(target: LOG_TARGET, "Could not query the hardware wallet application: {}", e);
+                        None
+                    },
+                },
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Could not connect to the hardware wallet: {}", e);
+                    None
+                },
+            },
+            WalletType::Software | WalletType::WatchOnly => None,
+        }
+    }
+
+    #[cfg(not(feature = "ledger"))]
+    fn connect_hardware_signer(_wallet_type: WalletType) -> Option<HardwareSigner> {
+        None
+    }
+
+    fn hardware_signer(&self) -> Result<&HardwareSigner, TransactionError> {
+        self.hardware_signer
+            .as_ref()
+            .ok_or(TransactionError::LedgerDeviceError(LedgerDeviceError::NotSupported))
+    }
+
+    /// Ledger wallets whose device application does not sign on the device fall back to `GetPrivateKey`
+    fn uses_hardware_signer(&self) -> bool {
+        self.hardware_signer.is_some()
+    }
+
     fn add_standard_core_branches(&mut self) -> Result<(), KeyManagerServiceError> {
         for branch in TransactionKeyManagerBranch::iter() {
             self.add_key_manager_branch(&branch.get_branch_key())?;
@@ -410,6 +459,16 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         self.import_key(secret_key + offset).await
     }
 
+    pub async fn get_subaddress_key_id(
+        &self,
+        view_key_id: &TariKeyId,
+        index: u64,
+    ) -> Result<TariKeyId, KeyManagerServiceError> {
+        let view_key = self.get_private_key(view_key_id).await?;
+        let offset = subaddress_key_offset(&view_key, index);
+        self.import_key(view_key + offset).await
+    }
+
     pub async fn generate_burn_proof(
         &self,
         spending_key: &TariKeyId,
@@ -441,6 +500,21 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         .map_err(|e| TransactionError::InvalidSignatureError(e.to_string()))
     }
 
+    pub async fn create_balance_proof(
+        &self,
+        spend_key_ids: &[TariKeyId],
+        commitments: Vec<Commitment>,
+        total: MicroMinotari,
+        message: String,
+    ) -> Result<BalanceProof, TransactionError> {
+        let mut blinding_factor_sum = PrivateKey::default();
+        for spend_key_id in spend_key_ids {
+            blinding_factor_sum = &blinding_factor_sum + &self.get_private_key(spend_key_id).await?;
+        }
+        BalanceProof::create(&blinding_factor_sum, commitments, total, message)
+            .map_err(|e| TransactionError::InvalidSignatureError(e.to_string()))
+    }
+
     // -----------------------------------------------------------------------------------------------------------------
     // Transaction input section (transactions > transaction_components > transaction_input)
     // -----------------------------------------------------------------------------------------------------------------
@@ -448,6 +522,7 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
     pub async fn get_script_private_key(&self, script_key_id: &TariKeyId) -> Result<PrivateKey, TransactionError> {
         match self.wallet_type {
             WalletType::Software => self.get_private_key(script_key_id).await.map_err(|e| e.into()),
+            WalletType::WatchOnly => Err(TransactionError::WatchOnlyWallet),
             WalletType::Ledger(_account) => {
                 #[cfg(not(feature = "ledger"))]
                 return Err(TransactionError::LedgerDeviceError(LedgerDeviceError::NotSupported));
@@ -493,6 +568,22 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         txi_version: &TransactionInputVersion,
         script_message: &[u8; 32],
     ) -> Result<ComAndPubSignature, TransactionError> {
+        if self.uses_hardware_signer() {
+            let commitment = self.get_commitment(spend_key_id, value).await?;
+            let spend_private_key = self.get_private_key(spend_key_id).await?;
+            return self
+                .hardware_signer()?
+                .get_script_signature(
+                    script_key_id,
+                    value,
+                    &spend_private_key,
+                    &commitment,
+                    txi_version,
+                    script_message,
+                )
+                .map_err(Into::into);
+        }
+
         let r_a = PrivateKey::random(&mut OsRng);
         let r_x = PrivateKey::random(&mut OsRng);
         let r_y = PrivateKey::random(&mut OsRng);
@@ -589,11 +680,21 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         script_key_ids: &[TariKeyId],
         sender_offset_key_ids: &[TariKeyId],
     ) -> Result<PrivateKey, TransactionError> {
+        if self.uses_hardware_signer() {
+            return self
+                .hardware_signer()?
+                .get_script_offset(script_key_ids, sender_offset_key_ids)
+                .map_err(Into::into);
+        }
+
         let mut total_sender_offset_private_key = PrivateKey::default();
         for sender_offset_key_id in sender_offset_key_ids {
             total_sender_offset_private_key =
                 total_sender_offset_private_key + self.get_private_key(sender_offset_key_id).await?;
         }
+        if !script_key_ids.is_empty() && matches!(self.wallet_type, WalletType::WatchOnly) {
+            return Err(TransactionError::WatchOnlyWallet);
+        }
         let mut total_script_private_key = PrivateKey::default();
         for script_key_id in script_key_ids {
             total_script_private_key = total_script_private_key + self.get_private_key(script_key_id).await?;
@@ -602,6 +703,13 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         Ok(script_offset)
     }
 
+    pub async fn review_transaction(&self, review: &TransactionReviewData) -> Result<(), TransactionError> {
+        if self.uses_hardware_signer() {
+            self.hardware_signer()?.review_transaction(review)?;
+        }
+        Ok(())
+    }
+
     async fn get_metadata_signature_ephemeral_private_key_pair(
         &self,
         nonce_id: &TariKeyId,
@@ -692,6 +800,21 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         metadata_signature_message: &[u8; 32],
         range_proof_type: RangeProofType,
     ) -> Result<ComAndPubSignature, TransactionError> {
+        if self.uses_hardware_signer() {
+            let spend_private_key = self.get_private_key(spending_key_id).await?;
+            return self
+                .hardware_signer()?
+                .get_metadata_signature(
+                    value_as_private_key,
+                    &spend_private_key,
+                    sender_offset_key_id,
+                    txo_version,
+                    metadata_signature_message,
+                    range_proof_type,
+                )
+                .map_err(Into::into);
+        }
+
         let sender_offset_public_key = self.get_public_key_at_key_id(sender_offset_key_id).await?;
         let (ephemeral_private_nonce_id, ephemeral_pubkey) = self
             .get_next_key(&TransactionKeyManagerBranch::Nonce.get_branch_key())
@@ -851,14 +974,20 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             PrivateKey::default() - &private_signing_key
         };
 
-        let private_nonce = self.get_private_key(nonce_id).await?;
         let challenge = TransactionKernel::finalize_kernel_signature_challenge(
             kernel_version,
             total_nonce,
             total_excess,
             kernel_message,
         );
+        if self.uses_hardware_signer() {
+            return self
+                .hardware_signer()?
+                .get_kernel_signature(&final_signing_key, nonce_id, &challenge)
+                .map_err(Into::into);
+        }
 
+        let private_nonce = self.get_private_key(nonce_id).await?;
         let signature = Signature::sign_raw_uniform(&final_signing_key, private_nonce, &challenge)?;
         Ok(signature)
     }
@@ -891,6 +1020,8 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         spend_key_id: &TariKeyId,
         custom_recovery_key_id: Option<&TariKeyId>,
         value: u64,
+        payment_reference: Option<&[u8]>,
+        memo: Option<&[u8]>,
     ) -> Result<EncryptedData, TransactionError> {
         let recovery_key = if let Some(key_id) = custom_recovery_key_id {
             self.get_private_key(key_id).await?
@@ -900,7 +1031,14 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         let value_key = value.into();
         let commitment = self.get_commitment(spend_key_id, &value_key).await?;
         let spend_key = self.get_private_key(spend_key_id).await?;
-        let data = EncryptedData::encrypt_data(&recovery_key, &commitment, value.into(), &spend_key)?;
+        let data = EncryptedData::encrypt_data_with_memo(
+            &recovery_key,
+            &commitment,
+            value.into(),
+            &spend_key,
+            payment_reference.unwrap_or_default(),
+            memo.unwrap_or_default(),
+        )?;
         Ok(data)
     }
 
@@ -28,17 +28,21 @@ use tari_comms::types::CommsDHKE;
 use tari_crypto::{hashing::DomainSeparatedHash, ristretto::RistrettoComSig};
 use tari_key_manager::key_manager_service::{KeyId, KeyManagerInterface, KeyManagerServiceError};
 
-use crate::transactions::{
-    tari_amount::MicroMinotari,
-    transaction_components::{
-        EncryptedData,
-        KernelFeatures,
-        RangeProofType,
-        TransactionError,
-        TransactionInputVersion,
-        TransactionKernelVersion,
-        TransactionOutput,
-        TransactionOutputVersion,
+use crate::{
+    common::balance_proof::BalanceProof,
+    transactions::{
+        key_manager::TransactionReviewData,
+        tari_amount::MicroMinotari,
+        transaction_components::{
+            EncryptedData,
+            KernelFeatures,
+            RangeProofType,
+            TransactionError,
+            TransactionInputVersion,
+            TransactionKernelVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
     },
 };
 
@@ -125,6 +129,13 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         offset: PrivateKey,
     ) -> Result<TariKeyId, KeyManagerServiceError>;
 
+    /// Imports the private key of the subaddress at `index`, derived from the given view key
+    async fn get_subaddress_key_id(
+        &self,
+        view_key_id: &TariKeyId,
+        index: u64,
+    ) -> Result<TariKeyId, KeyManagerServiceError>;
+
     async fn get_spending_key_id(&self, public_spending_key: &PublicKey) -> Result<TariKeyId, TransactionError>;
 
     async fn construct_range_proof(
@@ -172,6 +183,8 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         spend_key_id: &TariKeyId,
         custom_recovery_key_id: Option<&TariKeyId>,
         value: u64,
+        payment_reference: Option<&[u8]>,
+        memo: Option<&[u8]>,
     ) -> Result<EncryptedData, TransactionError>;
 
     async fn try_output_key_recovery(
@@ -186,6 +199,10 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         sender_offset_key_ids: &[TariKeyId],
     ) -> Result<PrivateKey, TransactionError>;
 
+    /// Shows the transaction on a hardware signer for confirmation before it is signed. Software wallets accept the
+    /// transaction without review.
+    async fn review_transaction(&self, review: &TransactionReviewData) -> Result<(), TransactionError>;
+
     async fn get_metadata_signature_ephemeral_commitment(
         &self,
         nonce_id: &TariKeyId,
@@ -231,6 +248,16 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         amount: &PrivateKey,
         claim_public_key: &PublicKey,
     ) -> Result<RistrettoComSig, TransactionError>;
+
+    /// Proves that the outputs committed to with `spend_key_ids` hold `total` between them, without revealing the
+    /// individual values or spend keys
+    async fn create_balance_proof(
+        &self,
+        spend_key_ids: &[TariKeyId],
+        commitments: Vec<Commitment>,
+        total: MicroMinotari,
+        message: String,
+    ) -> Result<BalanceProof, TransactionError>;
 }
 
 #[async_trait::async_trait]
@@ -47,3 +47,19 @@ pub use memory_db_key_manager::{
 
 mod error;
 pub use error::{CoreKeyManagerError, LedgerDeviceError};
+
+mod hardware_signer;
+#[cfg(feature = "ledger")]
+pub use hardware_signer::LedgerHidTransport;
+pub use hardware_signer::{
+    HardwareSigner,
+    SignerAppInfo,
+    SignerCommand,
+    SignerInstruction,
+    SignerResponse,
+    SignerTransport,
+    TransactionReviewData,
+    FLAG_DEVICE_SIGNING,
+    STATUS_OK,
+    STATUS_USER_REJECTED,
+};
@@ -41,26 +41,30 @@ use tari_key_manager::{
 };
 use tokio::sync::RwLock;
 
-use crate::transactions::{
-    key_manager::{
-        interface::{SecretTransactionKeyManagerInterface, TxoStage},
-        TariKeyId,
-        TransactionKeyManagerBranch,
-        TransactionKeyManagerInner,
-        TransactionKeyManagerInterface,
+use crate::{
+    common::balance_proof::BalanceProof,
+    transactions::{
+        key_manager::{
+            interface::{SecretTransactionKeyManagerInterface, TxoStage},
+            TariKeyId,
+            TransactionKeyManagerBranch,
+            TransactionKeyManagerInner,
+            TransactionKeyManagerInterface,
+            TransactionReviewData,
+        },
+        tari_amount::MicroMinotari,
+        transaction_components::{
+            EncryptedData,
+            KernelFeatures,
+            RangeProofType,
+            TransactionError,
+            TransactionInputVersion,
+            TransactionKernelVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
+        CryptoFactories,
     },
-    tari_amount::MicroMinotari,
-    transaction_components::{
-        EncryptedData,
-        KernelFeatures,
-        RangeProofType,
-        TransactionError,
-        TransactionInputVersion,
-        TransactionKernelVersion,
-        TransactionOutput,
-        TransactionOutputVersion,
-    },
-    CryptoFactories,
 };
 
 /// The key manager provides a hierarchical key derivation function (KDF) that derives uniformly random secret keys from
@@ -259,6 +263,18 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .await
     }
 
+    async fn get_subaddress_key_id(
+        &self,
+        view_key_id: &TariKeyId,
+        index: u64,
+    ) -> Result<TariKeyId, KeyManagerServiceError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_subaddress_key_id(view_key_id, index)
+            .await
+    }
+
     async fn get_spending_key_id(&self, public_spending_key: &PublicKey) -> Result<TariKeyId, TransactionError> {
         self.transaction_key_manager_inner
             .read()
@@ -351,11 +367,13 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         spend_key_id: &TariKeyId,
         custom_recovery_key_id: Option<&TariKeyId>,
         value: u64,
+        payment_reference: Option<&[u8]>,
+        memo: Option<&[u8]>,
     ) -> Result<EncryptedData, TransactionError> {
         self.transaction_key_manager_inner
             .read()
             .await
-            .encrypt_data_for_recovery(spend_key_id, custom_recovery_key_id, value)
+            .encrypt_data_for_recovery(spend_key_id, custom_recovery_key_id, value, payment_reference, memo)
             .await
     }
 
@@ -383,6 +401,14 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .await
     }
 
+    async fn review_transaction(&self, review: &TransactionReviewData) -> Result<(), TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .review_transaction(review)
+            .await
+    }
+
     async fn get_metadata_signature_ephemeral_commitment(
         &self,
         nonce_id: &TariKeyId,
@@ -478,6 +504,20 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .generate_burn_proof(spending_key, amount, claim_public_key)
             .await
     }
+
+    async fn create_balance_proof(
+        &self,
+        spend_key_ids: &[TariKeyId],
+        commitments: Vec<Commitment>,
+        total: MicroMinotari,
+        message: String,
+    ) -> Result<BalanceProof, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .create_balance_proof(spend_key_ids, commitments, total, message)
+            .await
+    }
 }
 
 #[async_trait::async_trait]
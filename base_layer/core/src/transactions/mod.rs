@@ -11,11 +11,16 @@ mod coinbase_builder;
 pub use coinbase_builder::{
     generate_coinbase,
     generate_coinbase_with_wallet_output,
+    generate_split_coinbase,
+    split_coinbase_amount,
     CoinbaseBuildError,
     CoinbaseBuilder,
+    CoinbaseExtra,
+    CoinbaseRecipient,
 };
 
 pub mod fee;
+pub mod offline_signing;
 pub mod tari_amount;
 pub mod transaction_components;
 
@@ -0,0 +1,170 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The file format for air-gapped (cold storage) signing.
+//!
+//! An online wallet selects the inputs and builds a one-sided transaction up to the point where script signatures are
+//! needed, and exports it as a [TransactionSigningRequest]. An offline wallet that holds the same seed reads the
+//! request, finalizes the transaction with its own key manager and writes a [SignedTransaction], which is carried back
+//! to the online wallet to be broadcast. Both formats are versioned JSON documents; any change to their serialized
+//! layout, including the layout of the sender protocol, must bump [OFFLINE_SIGNING_VERSION].
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tari_common_types::{tari_address::TariAddress, transaction::TxId};
+use thiserror::Error;
+
+use crate::transactions::{
+    key_manager::TransactionKeyManagerInterface,
+    tari_amount::MicroMinotari,
+    transaction_components::Transaction,
+    transaction_protocol::TransactionProtocolError,
+    SenderTransactionProtocol,
+};
+
+/// The version of the signing request and signed transaction formats
+pub const OFFLINE_SIGNING_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum OfflineSigningError {
+    #[error("Unsupported offline signing format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+    #[error("The transaction is not ready to be signed")]
+    NotReadyForSigning,
+    #[error("Transaction protocol error: {0}")]
+    TransactionProtocolError(#[from] TransactionProtocolError),
+}
+
+/// The details of the payment, carried through signing so that the online wallet can record the transaction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentDetails {
+    pub tx_id: TxId,
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee: MicroMinotari,
+    pub message: String,
+    pub payment_reference: Vec<u8>,
+}
+
+/// An unsigned transaction bundle exported by an online or watch-only wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSigningRequest {
+    pub version: u32,
+    pub payment: PaymentDetails,
+    /// The sender protocol, ready to be finalized
+    pub sender_protocol: SenderTransactionProtocol,
+}
+
+impl TransactionSigningRequest {
+    pub fn new(payment: PaymentDetails, sender_protocol: SenderTransactionProtocol) -> Self {
+        Self {
+            version: OFFLINE_SIGNING_VERSION,
+            payment,
+            sender_protocol,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, OfflineSigningError> {
+        to_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, OfflineSigningError> {
+        let request: Self = from_json(json)?;
+        check_version(request.version)?;
+        Ok(request)
+    }
+
+    /// Signs the transaction with the key manager of the signer, which must be derived from the same seed as the
+    /// wallet that created the request
+    pub async fn sign<KM: TransactionKeyManagerInterface>(
+        self,
+        key_manager: &KM,
+    ) -> Result<SignedTransaction, OfflineSigningError> {
+        check_version(self.version)?;
+        let mut sender_protocol = self.sender_protocol;
+        if !sender_protocol.is_finalizing() {
+            return Err(OfflineSigningError::NotReadyForSigning);
+        }
+        sender_protocol.finalize(key_manager).await?;
+        let transaction = sender_protocol.into_transaction()?;
+
+        Ok(SignedTransaction {
+            version: OFFLINE_SIGNING_VERSION,
+            payment: self.payment,
+            transaction,
+        })
+    }
+}
+
+/// A transaction signed by an offline signer, ready to be broadcast by the wallet that created the request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub version: u32,
+    pub payment: PaymentDetails,
+    pub transaction: Transaction,
+}
+
+impl SignedTransaction {
+    pub fn to_json(&self) -> Result<String, OfflineSigningError> {
+        to_json(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, OfflineSigningError> {
+        let signed: Self = from_json(json)?;
+        check_version(signed.version)?;
+        Ok(signed)
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, OfflineSigningError> {
+    serde_json::to_string_pretty(value).map_err(|e| OfflineSigningError::SerializationError(e.to_string()))
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, OfflineSigningError> {
+    serde_json::from_str(json).map_err(|e| OfflineSigningError::SerializationError(e.to_string()))
+}
+
+fn check_version(version: u32) -> Result<(), OfflineSigningError> {
+    if version != OFFLINE_SIGNING_VERSION {
+        return Err(OfflineSigningError::UnsupportedVersion(version));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_rejects_unknown_versions() {
+        assert!(check_version(OFFLINE_SIGNING_VERSION).is_ok());
+        assert!(matches!(
+            check_version(OFFLINE_SIGNING_VERSION + 1),
+            Err(OfflineSigningError::UnsupportedVersion(v)) if v == OFFLINE_SIGNING_VERSION + 1
+        ));
+        assert!(matches!(
+            SignedTransaction::from_json("{}"),
+            Err(OfflineSigningError::SerializationError(_))
+        ));
+    }
+}
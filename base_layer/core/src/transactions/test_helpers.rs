@@ -850,7 +850,7 @@ pub async fn create_utxo(
         .await
         .unwrap();
     let encrypted_data = key_manager
-        .encrypt_data_for_recovery(&spending_key_id, None, value.into())
+        .encrypt_data_for_recovery(&spending_key_id, None, value.into(), None, None)
         .await
         .unwrap();
     let (sender_offset_key_id, sender_offset_public_key) = key_manager
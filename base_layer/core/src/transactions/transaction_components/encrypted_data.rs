@@ -24,8 +24,13 @@
 // Version 2.0, available at http://www.apache.org/licenses/LICENSE-2.0.
 
 //! Encrypted data using the the extended-nonce variant XChaCha20-Poly1305 encryption with secure random nonce.
+//!
+//! Besides the value and mask, the encrypted data can optionally carry a payment reference chosen by the sender (e.g.
+//! an invoice number), so that the recipient of a one-sided payment can reconcile it, and a memo for the recipient
+//! (e.g. an exchange deposit tag). The memo size is carried in the clear, authenticated as associated data, so that
+//! its consensus bound can be enforced without decryption.
 
-use std::mem::size_of;
+use std::{convert::TryFrom, io, io::Write, mem::size_of};
 
 use blake2::Blake2b;
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -37,6 +42,7 @@ use chacha20poly1305::{
     XNonce,
 };
 use digest::{consts::U32, generic_array::GenericArray, FixedOutput};
+use integer_encoding::{VarIntReader, VarIntWriter};
 use serde::{Deserialize, Serialize};
 use tari_common_types::types::{Commitment, PrivateKey};
 use tari_crypto::{hashing::DomainSeparatedHasher, keys::SecretKey};
@@ -59,16 +65,38 @@ const SIZE_VALUE: usize = size_of::<u64>();
 const SIZE_MASK: usize = PrivateKey::KEY_LEN;
 const SIZE_TAG: usize = size_of::<Tag>();
 const SIZE_TOTAL: usize = SIZE_NONCE + SIZE_VALUE + SIZE_MASK + SIZE_TAG;
+/// The maximum size in bytes of the payment reference that can be carried in encrypted data
+pub const MAX_PAYMENT_REFERENCE_SIZE: usize = 256;
+/// The maximum size in bytes of the memo that can be carried in encrypted data
+pub const MAX_MEMO_SIZE: usize = 256;
+const SIZE_MEMO_SIZE: usize = size_of::<u16>();
+const SIZE_MAX: usize = SIZE_TOTAL + MAX_PAYMENT_REFERENCE_SIZE + SIZE_MEMO_SIZE + MAX_MEMO_SIZE;
+
+// Encrypted data carrying a payment reference always uses a nonce starting with this marker, so that the encoding of
+// encrypted data without a payment reference is unchanged
+const PAYMENT_REFERENCE_NONCE_MARKER: &[u8; 8] = b"TARI_REF";
+// Encrypted data carrying a memo (and optionally a payment reference) always uses a nonce starting with this marker,
+// and the nonce is followed by the memo size
+const MEMO_NONCE_MARKER: &[u8; 8] = b"TARI_MEM";
 
 // Number of hex characters of encrypted data to display on each side of ellipsis when truncating
 const DISPLAY_CUTOFF: usize = 16;
 
-#[derive(
-    Debug, Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize, Zeroize,
-)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash, Zeroize)]
+#[serde(try_from = "EncryptedDataSerde", into = "EncryptedDataSerde")]
 pub struct EncryptedData {
+    // nonce, memo size, encrypted value, encrypted mask, encrypted payment reference, encrypted memo, tag; of which
+    // the fixed length data holds the first `SIZE_TOTAL` bytes and the extra data the remaining bytes (at most
+    // `SIZE_MAX - SIZE_TOTAL`), which are only present if there is a payment reference or memo
+    data: [u8; SIZE_TOTAL],
+    extra: Vec<u8>,
+}
+
+// Keeps the serialized form of encrypted data identical to the fixed size encoding
+#[derive(Deserialize, Serialize)]
+struct EncryptedDataSerde {
     #[serde(with = "tari_utilities::serde::hex")]
-    data: [u8; SIZE_TOTAL], // nonce, encrypted value, encrypted mask, tag
+    data: Vec<u8>,
 }
 
 /// AEAD associated data
@@ -86,28 +114,83 @@ impl EncryptedData {
         value: MicroMinotari,
         mask: &PrivateKey,
     ) -> Result<EncryptedData, EncryptedDataError> {
-        // Encode the value and mask
-        let mut bytes = Zeroizing::new([0u8; SIZE_VALUE + SIZE_MASK]);
+        Self::encrypt_data_with_payment_reference(encryption_key, commitment, value, mask, &[])
+    }
+
+    /// Encrypt the value, mask and a payment reference of up to `MAX_PAYMENT_REFERENCE_SIZE` bytes using
+    /// XChaCha20-Poly1305 with a secure random nonce. An empty payment reference produces the same fixed length
+    /// encrypted data as `encrypt_data`.
+    pub fn encrypt_data_with_payment_reference(
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        value: MicroMinotari,
+        mask: &PrivateKey,
+        payment_reference: &[u8],
+    ) -> Result<EncryptedData, EncryptedDataError> {
+        Self::encrypt_data_with_memo(encryption_key, commitment, value, mask, payment_reference, &[])
+    }
+
+    /// Encrypt the value, mask, a payment reference of up to `MAX_PAYMENT_REFERENCE_SIZE` bytes and a memo of up to
+    /// `MAX_MEMO_SIZE` bytes using XChaCha20-Poly1305 with a secure random nonce. An empty memo produces the same
+    /// encrypted data as `encrypt_data_with_payment_reference`.
+    pub fn encrypt_data_with_memo(
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        value: MicroMinotari,
+        mask: &PrivateKey,
+        payment_reference: &[u8],
+        memo: &[u8],
+    ) -> Result<EncryptedData, EncryptedDataError> {
+        if payment_reference.len() > MAX_PAYMENT_REFERENCE_SIZE {
+            return Err(EncryptedDataError::IncorrectLength(format!(
+                "Payment reference must be at most {} bytes, got {}",
+                MAX_PAYMENT_REFERENCE_SIZE,
+                payment_reference.len()
+            )));
+        }
+        let memo_size = u16::try_from(memo.len())
+            .ok()
+            .filter(|size| usize::from(*size) <= MAX_MEMO_SIZE)
+            .ok_or_else(|| {
+                EncryptedDataError::IncorrectLength(format!(
+                    "Memo must be at most {} bytes, got {}",
+                    MAX_MEMO_SIZE,
+                    memo.len()
+                ))
+            })?
+            .to_le_bytes();
+        // The memo size is only present if there is a memo
+        let header = if memo.is_empty() { &[][..] } else { &memo_size[..] };
+
+        // Encode the value, mask, payment reference and memo
+        let size_plaintext = SIZE_VALUE + SIZE_MASK + payment_reference.len() + memo.len();
+        let mut bytes = Zeroizing::new([0u8; SIZE_MAX - SIZE_NONCE - SIZE_TAG]);
         bytes[..SIZE_VALUE].clone_from_slice(value.as_u64().to_le_bytes().as_ref());
-        bytes[SIZE_VALUE..].clone_from_slice(mask.as_bytes());
+        bytes[SIZE_VALUE..SIZE_VALUE + SIZE_MASK].clone_from_slice(mask.as_bytes());
+        bytes[SIZE_VALUE + SIZE_MASK..SIZE_VALUE + SIZE_MASK + payment_reference.len()]
+            .clone_from_slice(payment_reference);
+        bytes[size_plaintext - memo.len()..size_plaintext].clone_from_slice(memo);
 
         // Produce a secure random nonce
-        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let nonce = if !memo.is_empty() {
+            generate_nonce(Some(MEMO_NONCE_MARKER))
+        } else if !payment_reference.is_empty() {
+            generate_nonce(Some(PAYMENT_REFERENCE_NONCE_MARKER))
+        } else {
+            generate_nonce(None)
+        };
 
         // Set up the AEAD
         let aead_key = kdf_aead(encryption_key, commitment);
         let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(aead_key.reveal()));
 
         // Encrypt in place
-        let tag = cipher.encrypt_in_place_detached(&nonce, ENCRYPTED_DATA_AAD, bytes.as_mut_slice())?;
+        let tag = cipher.encrypt_in_place_detached(&nonce, &associated_data(header), &mut bytes[..size_plaintext])?;
 
-        // Put everything together: nonce, ciphertext, tag
-        let mut data = [0u8; SIZE_TOTAL];
-        data[..SIZE_NONCE].clone_from_slice(&nonce);
-        data[SIZE_NONCE..SIZE_NONCE + SIZE_VALUE + SIZE_MASK].clone_from_slice(bytes.as_slice());
-        data[SIZE_NONCE + SIZE_VALUE + SIZE_MASK..].clone_from_slice(&tag);
+        // Put everything together: nonce, memo size, ciphertext, tag
+        let data = [&nonce[..], header, &bytes[..size_plaintext], &tag[..]].concat();
 
-        Ok(Self { data })
+        Ok(Self::from_valid_bytes(&data))
     }
 
     /// Authenticate and decrypt the value and mask
@@ -118,55 +201,131 @@ impl EncryptedData {
         commitment: &Commitment,
         encrypted_data: &EncryptedData,
     ) -> Result<(MicroMinotari, PrivateKey), EncryptedDataError> {
-        // Extract the nonce, ciphertext, and tag
-        let nonce = XNonce::from_slice(&encrypted_data.as_bytes()[..SIZE_NONCE]);
-        let mut bytes = Zeroizing::new([0u8; SIZE_VALUE + SIZE_MASK]);
-        bytes.clone_from_slice(&encrypted_data.as_bytes()[SIZE_NONCE..SIZE_NONCE + SIZE_VALUE + SIZE_MASK]);
-        let tag = Tag::from_slice(&encrypted_data.as_bytes()[SIZE_NONCE + SIZE_VALUE + SIZE_MASK..]);
+        let (value, mask, _) = Self::decrypt_data_with_payment_reference(encryption_key, commitment, encrypted_data)?;
+        Ok((value, mask))
+    }
+
+    /// Authenticate and decrypt the value, mask and payment reference. The payment reference is empty if none was
+    /// encrypted.
+    pub fn decrypt_data_with_payment_reference(
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        encrypted_data: &EncryptedData,
+    ) -> Result<(MicroMinotari, PrivateKey, Vec<u8>), EncryptedDataError> {
+        let (value, mask, payment_reference, _) =
+            Self::decrypt_data_with_memo(encryption_key, commitment, encrypted_data)?;
+        Ok((value, mask, payment_reference))
+    }
+
+    /// Authenticate and decrypt the value, mask, payment reference and memo. The payment reference and memo are empty
+    /// if none were encrypted.
+    pub fn decrypt_data_with_memo(
+        encryption_key: &PrivateKey,
+        commitment: &Commitment,
+        encrypted_data: &EncryptedData,
+    ) -> Result<(MicroMinotari, PrivateKey, Vec<u8>, Vec<u8>), EncryptedDataError> {
+        // Extract the nonce, memo size, ciphertext, and tag
+        let data = encrypted_data.to_byte_vec();
+        let start_ciphertext = SIZE_NONCE + encrypted_data.memo_header_size();
+        let size_plaintext = data.len() - start_ciphertext - SIZE_TAG;
+        let nonce = XNonce::from_slice(&data[..SIZE_NONCE]);
+        let header = &data[SIZE_NONCE..start_ciphertext];
+        let mut bytes = Zeroizing::new([0u8; SIZE_MAX - SIZE_NONCE - SIZE_TAG]);
+        bytes[..size_plaintext].clone_from_slice(&data[start_ciphertext..start_ciphertext + size_plaintext]);
+        let tag = Tag::from_slice(&data[start_ciphertext + size_plaintext..]);
 
         // Set up the AEAD
         let aead_key = kdf_aead(encryption_key, commitment);
         let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(aead_key.reveal()));
 
         // Decrypt in place
-        cipher.decrypt_in_place_detached(nonce, ENCRYPTED_DATA_AAD, bytes.as_mut_slice(), tag)?;
+        cipher.decrypt_in_place_detached(nonce, &associated_data(header), &mut bytes[..size_plaintext], tag)?;
 
-        // Decode the value and mask
+        // Decode the value, mask, payment reference and memo
+        let start_memo = size_plaintext - encrypted_data.memo_size();
         let mut value_bytes = [0u8; SIZE_VALUE];
         value_bytes.clone_from_slice(&bytes[0..SIZE_VALUE]);
         Ok((
             u64::from_le_bytes(value_bytes).into(),
-            PrivateKey::from_canonical_bytes(&bytes[SIZE_VALUE..])?,
+            PrivateKey::from_canonical_bytes(&bytes[SIZE_VALUE..SIZE_VALUE + SIZE_MASK])?,
+            bytes[SIZE_VALUE + SIZE_MASK..start_memo].to_vec(),
+            bytes[start_memo..size_plaintext].to_vec(),
         ))
     }
 
     /// Parse encrypted data from a byte slice
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, EncryptedDataError> {
-        if bytes.len() != SIZE_TOTAL {
+        if bytes.len() < SIZE_TOTAL || bytes.len() > SIZE_MAX {
             return Err(EncryptedDataError::IncorrectLength(format!(
-                "Expected {} bytes, got {}",
+                "Expected between {} and {} bytes, got {}",
                 SIZE_TOTAL,
+                SIZE_MAX,
                 bytes.len()
             )));
         }
+        if bytes.starts_with(MEMO_NONCE_MARKER) {
+            // The memo must be present and bounded, leaving a bounded payment reference
+            let memo_size = usize::from(u16::from_le_bytes([bytes[SIZE_NONCE], bytes[SIZE_NONCE + 1]]));
+            let size_extra = bytes.len() - SIZE_TOTAL;
+            if memo_size == 0 ||
+                memo_size > MAX_MEMO_SIZE ||
+                SIZE_MEMO_SIZE + memo_size > size_extra ||
+                size_extra - SIZE_MEMO_SIZE - memo_size > MAX_PAYMENT_REFERENCE_SIZE
+            {
+                return Err(EncryptedDataError::IncorrectLength(format!(
+                    "Memo size {} does not match the {} byte length",
+                    memo_size,
+                    bytes.len()
+                )));
+            }
+        } else if bytes.starts_with(PAYMENT_REFERENCE_NONCE_MARKER) != (bytes.len() > SIZE_TOTAL) ||
+            bytes.len() > SIZE_TOTAL + MAX_PAYMENT_REFERENCE_SIZE
+        {
+            // Only encrypted data carrying a payment reference may (and must) use the marked nonce
+            return Err(EncryptedDataError::IncorrectLength(format!(
+                "Nonce does not match the {} byte length",
+                bytes.len()
+            )));
+        }
+        Ok(Self::from_valid_bytes(bytes))
+    }
+
+    // Split encrypted data bytes, which must have been validated, into the fixed length and extra data
+    fn from_valid_bytes(bytes: &[u8]) -> Self {
         let mut data = [0u8; SIZE_TOTAL];
-        data.copy_from_slice(bytes);
-        Ok(Self { data })
+        data.copy_from_slice(&bytes[..SIZE_TOTAL]);
+        Self {
+            data,
+            extra: bytes[SIZE_TOTAL..].to_vec(),
+        }
     }
 
     /// Get a byte vector with the encrypted data contents
     pub fn to_byte_vec(&self) -> Vec<u8> {
-        self.data.to_vec()
+        [&self.data[..], &self.extra[..]].concat()
     }
 
-    /// Get a byte array with the encrypted data contents
-    pub fn to_bytes(&self) -> [u8; SIZE_TOTAL] {
-        self.data
+    /// The size in bytes of the (encrypted) payment reference, which is zero if there is none
+    pub fn payment_reference_size(&self) -> usize {
+        self.extra.len() - self.memo_header_size() - self.memo_size()
     }
 
-    /// Get a byte slice with the encrypted data contents
-    pub fn as_bytes(&self) -> &[u8] {
-        &self.data
+    /// The size in bytes of the (encrypted) memo, which is zero if there is none
+    pub fn memo_size(&self) -> usize {
+        if self.data.starts_with(MEMO_NONCE_MARKER) {
+            usize::from(u16::from_le_bytes([self.data[SIZE_NONCE], self.data[SIZE_NONCE + 1]]))
+        } else {
+            0
+        }
+    }
+
+    // The size in bytes of the memo size following the nonce, which is only present if there is a memo
+    fn memo_header_size(&self) -> usize {
+        if self.data.starts_with(MEMO_NONCE_MARKER) {
+            SIZE_MEMO_SIZE
+        } else {
+            0
+        }
     }
 
     /// Accessor method for the encrypted data hex display
@@ -203,9 +362,64 @@ impl Default for EncryptedData {
     fn default() -> Self {
         Self {
             data: [0u8; SIZE_TOTAL],
+            extra: Vec::new(),
         }
     }
 }
+
+impl TryFrom<EncryptedDataSerde> for EncryptedData {
+    type Error = EncryptedDataError;
+
+    fn try_from(value: EncryptedDataSerde) -> Result<Self, Self::Error> {
+        Self::from_bytes(&value.data)
+    }
+}
+
+impl From<EncryptedData> for EncryptedDataSerde {
+    fn from(value: EncryptedData) -> Self {
+        Self {
+            data: value.to_byte_vec(),
+        }
+    }
+}
+
+// Encrypted data without a payment reference or memo is encoded as its fixed length bytes. Otherwise the marked nonce
+// is followed by the size of the optional data and the remaining bytes.
+impl BorshSerialize for EncryptedData {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.extra.is_empty() {
+            return writer.write_all(&self.data);
+        }
+        writer.write_all(&self.data[..SIZE_NONCE])?;
+        writer.write_varint(self.extra.len())?;
+        writer.write_all(&self.data[SIZE_NONCE..])?;
+        writer.write_all(&self.extra)
+    }
+}
+
+impl BorshDeserialize for EncryptedData {
+    fn deserialize_reader<R>(reader: &mut R) -> Result<Self, io::Error>
+    where R: io::Read {
+        let mut nonce = [0u8; SIZE_NONCE];
+        reader.read_exact(&mut nonce)?;
+        let size_extra = if nonce.starts_with(PAYMENT_REFERENCE_NONCE_MARKER) || nonce.starts_with(MEMO_NONCE_MARKER) {
+            let size: usize = reader.read_varint()?;
+            if size == 0 || size > SIZE_MAX - SIZE_TOTAL {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid encrypted data size {}", size),
+                ));
+            }
+            size
+        } else {
+            0
+        };
+        let mut data = vec![0u8; SIZE_TOTAL + size_extra];
+        data[..SIZE_NONCE].copy_from_slice(&nonce);
+        reader.read_exact(&mut data[SIZE_NONCE..])?;
+        Self::from_bytes(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+    }
+}
 // EncryptedOpenings errors
 #[derive(Debug, Error)]
 pub enum EncryptedDataError {
@@ -230,6 +444,25 @@ impl From<Error> for EncryptedDataError {
     }
 }
 
+// Produce a secure random nonce, which starts with the marker of the optional data carried by the encrypted data, and
+// never starts with a marker otherwise
+fn generate_nonce(marker: Option<&[u8; 8]>) -> XNonce {
+    let mut nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    if let Some(marker) = marker {
+        nonce[..marker.len()].clone_from_slice(marker);
+    } else {
+        while nonce.starts_with(PAYMENT_REFERENCE_NONCE_MARKER) || nonce.starts_with(MEMO_NONCE_MARKER) {
+            nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        }
+    }
+    nonce
+}
+
+// The AEAD associated data, which authenticates the memo size carried in the clear
+fn associated_data(memo_size: &[u8]) -> Vec<u8> {
+    [ENCRYPTED_DATA_AAD, memo_size].concat()
+}
+
 // Generate a ChaCha20-Poly1305 key from a private key and commitment using Blake2b
 fn kdf_aead(encryption_key: &PrivateKey, commitment: &Commitment) -> EncryptedDataKey {
     let mut aead_key = EncryptedDataKey::from(SafeArray::default());
@@ -293,4 +526,126 @@ mod test {
             assert_eq!(encrypted_data, encrypted_data_from_bytes);
         }
     }
+
+    #[test]
+    fn it_encrypts_and_decrypts_a_payment_reference() {
+        let mask = PrivateKey::random(&mut OsRng);
+        let commitment = CommitmentFactory::default().commit(&mask, &PrivateKey::from(123456));
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let amount = MicroMinotari::from(123456);
+        for payment_reference in [vec![], b"INV-0042".to_vec(), vec![0xab; MAX_PAYMENT_REFERENCE_SIZE]] {
+            let encrypted_data = EncryptedData::encrypt_data_with_payment_reference(
+                &encryption_key,
+                &commitment,
+                amount,
+                &mask,
+                &payment_reference,
+            )
+            .unwrap();
+            assert_eq!(encrypted_data.payment_reference_size(), payment_reference.len());
+            let (decrypted_value, decrypted_mask, decrypted_payment_reference) =
+                EncryptedData::decrypt_data_with_payment_reference(&encryption_key, &commitment, &encrypted_data)
+                    .unwrap();
+            assert_eq!(amount, decrypted_value);
+            assert_eq!(mask, decrypted_mask);
+            assert_eq!(payment_reference, decrypted_payment_reference);
+        }
+
+        let err = EncryptedData::encrypt_data_with_payment_reference(
+            &encryption_key,
+            &commitment,
+            amount,
+            &mask,
+            &[0u8; MAX_PAYMENT_REFERENCE_SIZE + 1],
+        )
+        .unwrap_err();
+        assert!(matches!(err, EncryptedDataError::IncorrectLength(_)));
+    }
+
+    #[test]
+    fn it_encodes_correctly() {
+        let mask = PrivateKey::random(&mut OsRng);
+        let commitment = CommitmentFactory::default().commit(&mask, &PrivateKey::from(654321));
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let amount = MicroMinotari::from(654321);
+
+        // Without a payment reference the encoding is the fixed length data
+        let encrypted_data = EncryptedData::encrypt_data(&encryption_key, &commitment, amount, &mask).unwrap();
+        assert!(encrypted_data.extra.is_empty());
+        let buf = borsh::to_vec(&encrypted_data).unwrap();
+        assert_eq!(buf, encrypted_data.to_byte_vec());
+        assert_eq!(borsh::from_slice::<EncryptedData>(&buf).unwrap(), encrypted_data);
+
+        let encrypted_data =
+            EncryptedData::encrypt_data_with_payment_reference(&encryption_key, &commitment, amount, &mask, b"INV-1")
+                .unwrap();
+        let mut buf = borsh::to_vec(&encrypted_data).unwrap();
+        buf.push(0xff);
+        let mut slice = buf.as_slice();
+        assert_eq!(
+            <EncryptedData as BorshDeserialize>::deserialize(&mut slice).unwrap(),
+            encrypted_data
+        );
+        assert_eq!(slice, &[0xff]);
+        let bytes = encrypted_data.to_byte_vec();
+        assert_eq!(EncryptedData::from_bytes(&bytes).unwrap(), encrypted_data);
+
+        // The length of the bytes must agree with the nonce
+        assert!(EncryptedData::from_bytes(&bytes[..SIZE_TOTAL]).is_err());
+        assert!(EncryptedData::from_bytes(&[0u8; SIZE_TOTAL + 1]).is_err());
+    }
+
+    #[test]
+    fn it_encrypts_and_decrypts_a_memo() {
+        let mask = PrivateKey::random(&mut OsRng);
+        let commitment = CommitmentFactory::default().commit(&mask, &PrivateKey::from(123456));
+        let encryption_key = PrivateKey::random(&mut OsRng);
+        let amount = MicroMinotari::from(123456);
+        for (payment_reference, memo) in [
+            (vec![], b"deposit tag 1234".to_vec()),
+            (b"INV-0042".to_vec(), b"tag".to_vec()),
+            (vec![0xab; MAX_PAYMENT_REFERENCE_SIZE], vec![0xcd; MAX_MEMO_SIZE]),
+        ] {
+            let encrypted_data = EncryptedData::encrypt_data_with_memo(
+                &encryption_key,
+                &commitment,
+                amount,
+                &mask,
+                &payment_reference,
+                &memo,
+            )
+            .unwrap();
+            assert_eq!(encrypted_data.payment_reference_size(), payment_reference.len());
+            assert_eq!(encrypted_data.memo_size(), memo.len());
+            let (decrypted_value, decrypted_mask, decrypted_payment_reference, decrypted_memo) =
+                EncryptedData::decrypt_data_with_memo(&encryption_key, &commitment, &encrypted_data).unwrap();
+            assert_eq!(amount, decrypted_value);
+            assert_eq!(mask, decrypted_mask);
+            assert_eq!(payment_reference, decrypted_payment_reference);
+            assert_eq!(memo, decrypted_memo);
+
+            let buf = borsh::to_vec(&encrypted_data).unwrap();
+            assert_eq!(borsh::from_slice::<EncryptedData>(&buf).unwrap(), encrypted_data);
+            let bytes = encrypted_data.to_byte_vec();
+            assert_eq!(EncryptedData::from_bytes(&bytes).unwrap(), encrypted_data);
+
+            // The memo size in the clear is authenticated
+            let mut bytes = encrypted_data.to_byte_vec();
+            bytes[SIZE_NONCE] ^= 1;
+            if let Ok(tampered) = EncryptedData::from_bytes(&bytes) {
+                assert!(EncryptedData::decrypt_data_with_memo(&encryption_key, &commitment, &tampered).is_err());
+            }
+        }
+
+        let err = EncryptedData::encrypt_data_with_memo(
+            &encryption_key,
+            &commitment,
+            amount,
+            &mask,
+            &[],
+            &[0u8; MAX_MEMO_SIZE + 1],
+        )
+        .unwrap_err();
+        assert!(matches!(err, EncryptedDataError::IncorrectLength(_)));
+    }
 }
@@ -77,6 +77,8 @@ pub enum TransactionError {
     LedgerDeviceError(#[from] LedgerDeviceError),
     #[error("Transaction has a zero weight, not possible")]
     ZeroWeight,
+    #[error("A watch-only wallet can't sign with script keys, the transaction has to be signed externally")]
+    WatchOnlyWallet,
 }
 
 impl From<KeyManagerServiceError> for TransactionError {
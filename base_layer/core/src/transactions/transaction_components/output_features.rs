@@ -200,6 +200,14 @@ impl OutputFeatures {
             .and_then(|s| s.code_template_registration())
     }
 
+    /// The public key that may claim the value of a burned output on the second layer, if any
+    pub fn burn_claim_public_key(&self) -> Option<&PublicKey> {
+        self.sidechain_feature
+            .as_ref()
+            .and_then(|s| s.confidential_output_data())
+            .map(|data| &data.claim_public_key)
+    }
+
     pub fn is_coinbase(&self) -> bool {
         matches!(self.output_type, OutputType::Coinbase)
     }
@@ -468,10 +468,15 @@ impl TransactionOutput {
         }
     }
 
+    /// The size of the variable length parts of the output that count towards its weight, which includes the payment
+    /// reference and memo carried in the encrypted data. These are only present in epochs whose consensus constants
+    /// permit them, so the weight of other outputs is unchanged.
     pub fn get_features_and_scripts_size(&self) -> std::io::Result<usize> {
         Ok(self.features.get_serialized_size()? +
             self.script.get_serialized_size()? +
-            self.covenant.get_serialized_size()?)
+            self.covenant.get_serialized_size()? +
+            self.encrypted_data.payment_reference_size() +
+            self.encrypted_data.memo_size())
     }
 }
 
@@ -567,17 +572,36 @@ pub fn batch_verify_range_proofs(
 
 #[cfg(test)]
 mod test {
-    use tari_crypto::errors::RangeProofError;
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{Commitment, PrivateKey};
+    use tari_crypto::{errors::RangeProofError, keys::SecretKey};
 
     use super::{batch_verify_range_proofs, TransactionOutput};
     use crate::transactions::{
         key_manager::{create_memory_db_key_manager, MemoryDbKeyManager, TransactionKeyManagerInterface},
         tari_amount::MicroMinotari,
         test_helpers::{TestParams, UtxoTestParams},
-        transaction_components::{OutputFeatures, RangeProofType},
+        transaction_components::{EncryptedData, OutputFeatures, RangeProofType},
         CryptoFactories,
     };
 
+    #[test]
+    fn it_includes_the_payment_reference_and_memo_in_the_weighted_size() {
+        let mut output = TransactionOutput::default();
+        let size = output.get_features_and_scripts_size().unwrap();
+
+        output.encrypted_data = EncryptedData::encrypt_data_with_memo(
+            &PrivateKey::random(&mut OsRng),
+            &Commitment::default(),
+            MicroMinotari(100),
+            &PrivateKey::random(&mut OsRng),
+            b"INV-0042",
+            b"deposit-1234",
+        )
+        .unwrap();
+        assert_eq!(output.get_features_and_scripts_size().unwrap(), size + 8 + 12);
+    }
+
     #[tokio::test]
     async fn it_builds_correctly() {
         let factories = CryptoFactories::default();
@@ -221,7 +221,7 @@ impl WalletOutput {
                 sender_offset_public_key: self.sender_offset_public_key.clone(),
                 covenant: self.covenant.clone(),
                 version: self.version,
-                encrypted_data: self.encrypted_data,
+                encrypted_data: self.encrypted_data.clone(),
                 metadata_signature: self.metadata_signature.clone(),
                 rangeproof_hash,
                 minimum_value_promise: self.minimum_value_promise,
@@ -268,7 +268,7 @@ impl WalletOutput {
             self.sender_offset_public_key.clone(),
             self.metadata_signature.clone(),
             self.covenant.clone(),
-            self.encrypted_data,
+            self.encrypted_data.clone(),
             self.minimum_value_promise,
         );
 
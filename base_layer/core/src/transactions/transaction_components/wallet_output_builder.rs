@@ -58,6 +58,8 @@ pub struct WalletOutputBuilder {
     encrypted_data: EncryptedData,
     custom_recovery_key_id: Option<TariKeyId>,
     minimum_value_promise: MicroMinotari,
+    payment_reference: Option<Vec<u8>>,
+    memo: Option<Vec<u8>>,
 }
 
 #[allow(dead_code)]
@@ -79,6 +81,8 @@ impl WalletOutputBuilder {
             encrypted_data: EncryptedData::default(),
             custom_recovery_key_id: None,
             minimum_value_promise: MicroMinotari::zero(),
+            payment_reference: None,
+            memo: None,
         }
     }
 
@@ -107,13 +111,33 @@ impl WalletOutputBuilder {
         self
     }
 
+    /// Sets the payment reference to include in the encrypted data, which must be done before calling
+    /// `encrypt_data_for_recovery`
+    pub fn with_payment_reference(mut self, payment_reference: Vec<u8>) -> Self {
+        self.payment_reference = Some(payment_reference);
+        self
+    }
+
+    /// Sets the memo for the recipient to include in the encrypted data, which must be done before calling
+    /// `encrypt_data_for_recovery`
+    pub fn with_memo(mut self, memo: Vec<u8>) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
     pub async fn encrypt_data_for_recovery<KM: TransactionKeyManagerInterface>(
         mut self,
         key_manager: &KM,
         custom_recovery_key_id: Option<&TariKeyId>,
     ) -> Result<Self, TransactionError> {
         self.encrypted_data = key_manager
-            .encrypt_data_for_recovery(&self.spending_key_id, custom_recovery_key_id, self.value.as_u64())
+            .encrypt_data_for_recovery(
+                &self.spending_key_id,
+                custom_recovery_key_id,
+                self.value.as_u64(),
+                self.payment_reference.as_deref(),
+                self.memo.as_deref(),
+            )
             .await?;
         Ok(self)
     }
@@ -95,6 +95,7 @@ use thiserror::Error;
 
 use crate::transactions::{tari_amount::*, transaction_components::TransactionError};
 
+pub mod multiparty;
 pub mod proto;
 pub mod recipient;
 pub mod sender;
@@ -0,0 +1,694 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Multiparty (n-of-n) transaction construction
+//!
+//! The parties of an aggregate UTXO each hold a share of its spending key and of its sender offset (or script) key.
+//! The commitment and public keys of the UTXO are the sums of the shares, and the metadata and script signatures are
+//! aggregated from partial signatures in two rounds:
+//!
+//! 1. Every party publishes its [PartyNonces]: the public parts of its key shares and its signature nonces.
+//! 2. Once all nonces are known, every party computes the aggregate challenge and publishes its [PartialSignature].
+//!    Partial signatures are verified against the nonces of the party that created them, so a misbehaving party is
+//!    identified.
+//!
+//! The sum of the partial signatures is an ordinary commitment and public key signature for the aggregate keys, so the
+//! resulting outputs and inputs are validated like any other. The state of a protocol is serializable, so it can be
+//! persisted between rounds.
+//!
+//! Aggregate outputs must use `RevealedValue` range proofs, since no party knows the full spending key required to
+//! create a bulletproof.
+
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{ComAndPubSignature, Commitment, CommitmentFactory, FixedHash, PrivateKey, PublicKey};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+};
+use tari_script::{ExecutionStack, TariScript};
+
+use crate::{
+    covenants::Covenant,
+    transactions::{
+        tari_amount::MicroMinotari,
+        transaction_components::{
+            EncryptedData,
+            OutputFeatures,
+            RangeProofType,
+            TransactionInput,
+            TransactionInputVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
+        transaction_protocol::TransactionProtocolError,
+    },
+};
+
+/// The public contribution of a party to an aggregate signature, exchanged in the first round
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartyNonces {
+    pub party_index: usize,
+    /// The party's share of the aggregate commitment
+    pub commitment: Commitment,
+    /// The party's share of the aggregate public key
+    pub public_key: PublicKey,
+    pub ephemeral_commitment: Commitment,
+    pub ephemeral_pubkey: PublicKey,
+}
+
+/// A party's partial signature, exchanged in the second round
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub party_index: usize,
+    pub signature: ComAndPubSignature,
+}
+
+/// The round an aggregate signing session is in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggregationRound {
+    /// Waiting for the nonces of all parties
+    CollectingNonces,
+    /// Waiting for the partial signatures of all parties
+    CollectingPartialSignatures,
+    /// All partial signatures have been received and verified
+    Complete,
+}
+
+/// One party's state of an n-of-n aggregate commitment and public key signature
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AggregateSignatureSession {
+    party_index: usize,
+    value: PrivateKey,
+    spending_key: PrivateKey,
+    public_key_secret: PrivateKey,
+    nonce_a: PrivateKey,
+    nonce_x: PrivateKey,
+    nonce_y: PrivateKey,
+    nonces: Vec<Option<PartyNonces>>,
+    challenge: Option<Vec<u8>>,
+    partial_signatures: Vec<Option<PartialSignature>>,
+}
+
+impl AggregateSignatureSession {
+    fn new(
+        party_index: usize,
+        num_parties: usize,
+        value: MicroMinotari,
+        spending_key: PrivateKey,
+        public_key_secret: PrivateKey,
+        zero_value_nonce: bool,
+    ) -> Result<Self, TransactionProtocolError> {
+        if num_parties < 2 || party_index >= num_parties {
+            return Err(TransactionProtocolError::ValidationError(format!(
+                "Invalid party index {} for {} parties",
+                party_index, num_parties
+            )));
+        }
+        // With RevealedValue range proofs the value nonce must be zero, so that the sum of the nonces is zero too
+        let nonce_a = if zero_value_nonce {
+            PrivateKey::default()
+        } else {
+            PrivateKey::random(&mut OsRng)
+        };
+        let mut session = Self {
+            party_index,
+            value: PrivateKey::from(value.as_u64()),
+            spending_key,
+            public_key_secret,
+            nonce_a,
+            nonce_x: PrivateKey::random(&mut OsRng),
+            nonce_y: PrivateKey::random(&mut OsRng),
+            nonces: vec![None; num_parties],
+            challenge: None,
+            partial_signatures: vec![None; num_parties],
+        };
+        session.nonces[party_index] = Some(session.own_nonces());
+        Ok(session)
+    }
+
+    fn own_nonces(&self) -> PartyNonces {
+        let factory = CommitmentFactory::default();
+        PartyNonces {
+            party_index: self.party_index,
+            commitment: factory.commit(&self.spending_key, &self.value),
+            public_key: PublicKey::from_secret_key(&self.public_key_secret),
+            ephemeral_commitment: factory.commit(&self.nonce_x, &self.nonce_a),
+            ephemeral_pubkey: PublicKey::from_secret_key(&self.nonce_y),
+        }
+    }
+
+    fn round(&self) -> AggregationRound {
+        if self.nonces.iter().any(Option::is_none) {
+            AggregationRound::CollectingNonces
+        } else if self.partial_signatures.iter().any(Option::is_none) {
+            AggregationRound::CollectingPartialSignatures
+        } else {
+            AggregationRound::Complete
+        }
+    }
+
+    fn add_nonces(&mut self, nonces: PartyNonces) -> Result<(), TransactionProtocolError> {
+        if self.round() != AggregationRound::CollectingNonces {
+            return Err(TransactionProtocolError::InvalidStateError);
+        }
+        match self.nonces.get(nonces.party_index) {
+            Some(None) => {
+                let party_index = nonces.party_index;
+                self.nonces[party_index] = Some(nonces);
+                Ok(())
+            },
+            Some(Some(_)) => Err(TransactionProtocolError::ValidationError(format!(
+                "Nonces for party {} were already received",
+                nonces.party_index
+            ))),
+            None => Err(TransactionProtocolError::ValidationError(format!(
+                "Unknown party index {}",
+                nonces.party_index
+            ))),
+        }
+    }
+
+    fn all_nonces(&self) -> Result<Vec<&PartyNonces>, TransactionProtocolError> {
+        self.nonces
+            .iter()
+            .map(|n| {
+                n.as_ref().ok_or_else(|| {
+                    TransactionProtocolError::IncompleteStateError("Not all parties have sent their nonces".to_string())
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the aggregate commitment, public key, ephemeral commitment and ephemeral public key
+    fn aggregate(&self) -> Result<(Commitment, PublicKey, Commitment, PublicKey), TransactionProtocolError> {
+        let nonces = self.all_nonces()?;
+        let first = nonces[0];
+        Ok(nonces.iter().skip(1).fold(
+            (
+                first.commitment.clone(),
+                first.public_key.clone(),
+                first.ephemeral_commitment.clone(),
+                first.ephemeral_pubkey.clone(),
+            ),
+            |(c, p, ec, ep), n| {
+                (
+                    &c + &n.commitment,
+                    p + &n.public_key,
+                    &ec + &n.ephemeral_commitment,
+                    ep + &n.ephemeral_pubkey,
+                )
+            },
+        ))
+    }
+
+    fn sign(&mut self, challenge: [u8; 64]) -> Result<PartialSignature, TransactionProtocolError> {
+        if self.round() != AggregationRound::CollectingPartialSignatures || self.challenge.is_some() {
+            return Err(TransactionProtocolError::InvalidStateError);
+        }
+        let signature = ComAndPubSignature::sign(
+            &self.value,
+            &self.spending_key,
+            &self.public_key_secret,
+            &self.nonce_a,
+            &self.nonce_x,
+            &self.nonce_y,
+            &challenge,
+            &CommitmentFactory::default(),
+        )
+        .map_err(|e| TransactionProtocolError::SigningError(e.to_string()))?;
+        let partial = PartialSignature {
+            party_index: self.party_index,
+            signature,
+        };
+        self.challenge = Some(challenge.to_vec());
+        self.partial_signatures[self.party_index] = Some(partial.clone());
+        Ok(partial)
+    }
+
+    fn add_partial_signature(&mut self, partial: PartialSignature) -> Result<(), TransactionProtocolError> {
+        let challenge = self.challenge.as_ref().ok_or_else(|| {
+            TransactionProtocolError::IncompleteStateError("This party has not signed yet".to_string())
+        })?;
+        let nonces = self
+            .nonces
+            .get(partial.party_index)
+            .and_then(Option::as_ref)
+            .ok_or_else(|| {
+                TransactionProtocolError::ValidationError(format!("Unknown party index {}", partial.party_index))
+            })?;
+        if self.partial_signatures[partial.party_index].is_some() {
+            return Err(TransactionProtocolError::ValidationError(format!(
+                "A partial signature for party {} was already received",
+                partial.party_index
+            )));
+        }
+        let signature = &partial.signature;
+        if signature.ephemeral_commitment() != &nonces.ephemeral_commitment ||
+            signature.ephemeral_pubkey() != &nonces.ephemeral_pubkey ||
+            !signature.verify_challenge(
+                &nonces.commitment,
+                &nonces.public_key,
+                challenge,
+                &CommitmentFactory::default(),
+                &mut OsRng,
+            )
+        {
+            return Err(TransactionProtocolError::InvalidSignatureError(format!(
+                "Invalid partial signature from party {}",
+                partial.party_index
+            )));
+        }
+        self.partial_signatures[partial.party_index] = Some(partial);
+        Ok(())
+    }
+
+    fn aggregate_signature(&self) -> Result<ComAndPubSignature, TransactionProtocolError> {
+        if self.round() != AggregationRound::Complete {
+            return Err(TransactionProtocolError::IncompleteStateError(
+                "Not all parties have sent their partial signatures".to_string(),
+            ));
+        }
+        let mut signatures = self.partial_signatures.iter().flatten().map(|p| &p.signature);
+        let first = signatures
+            .next()
+            .cloned()
+            .ok_or(TransactionProtocolError::InvalidStateError)?;
+        Ok(signatures.fold(first, |aggregate, signature| &aggregate + signature))
+    }
+}
+
+/// One party's state of the construction of an output that is owned by all parties. The aggregate metadata signature
+/// signs for the sum of the parties' spending key and sender offset key shares.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultipartyOutputProtocol {
+    session: AggregateSignatureSession,
+    version: TransactionOutputVersion,
+    features: OutputFeatures,
+    script: TariScript,
+    covenant: Covenant,
+    encrypted_data: EncryptedData,
+    minimum_value_promise: MicroMinotari,
+}
+
+impl MultipartyOutputProtocol {
+    /// Starts the protocol for the party at `party_index`. The value shares of all parties must add up to the
+    /// `minimum_value_promise`, which is revealed by the `RevealedValue` range proof.
+    pub fn new(
+        party_index: usize,
+        num_parties: usize,
+        value_share: MicroMinotari,
+        spending_key_share: PrivateKey,
+        sender_offset_key_share: PrivateKey,
+        features: OutputFeatures,
+        script: TariScript,
+        covenant: Covenant,
+        encrypted_data: EncryptedData,
+        minimum_value_promise: MicroMinotari,
+    ) -> Result<Self, TransactionProtocolError> {
+        if features.range_proof_type != RangeProofType::RevealedValue {
+            return Err(TransactionProtocolError::UnsupportedError(
+                "Aggregate outputs must use RevealedValue range proofs".to_string(),
+            ));
+        }
+        Ok(Self {
+            session: AggregateSignatureSession::new(
+                party_index,
+                num_parties,
+                value_share,
+                spending_key_share,
+                sender_offset_key_share,
+                true,
+            )?,
+            version: TransactionOutputVersion::get_current_version(),
+            features,
+            script,
+            covenant,
+            encrypted_data,
+            minimum_value_promise,
+        })
+    }
+
+    pub fn round(&self) -> AggregationRound {
+        self.session.round()
+    }
+
+    /// This party's nonces, to be sent to all other parties
+    pub fn nonces(&self) -> PartyNonces {
+        self.session.own_nonces()
+    }
+
+    pub fn add_nonces(&mut self, nonces: PartyNonces) -> Result<(), TransactionProtocolError> {
+        self.session.add_nonces(nonces)
+    }
+
+    /// Creates this party's partial metadata signature, to be sent to all other parties. All nonces must have been
+    /// received.
+    pub fn sign(&mut self) -> Result<PartialSignature, TransactionProtocolError> {
+        let challenge = self.challenge()?;
+        self.session.sign(challenge)
+    }
+
+    pub fn add_partial_signature(&mut self, partial: PartialSignature) -> Result<(), TransactionProtocolError> {
+        self.session.add_partial_signature(partial)
+    }
+
+    /// Builds the aggregate output once all partial signatures have been received
+    pub fn finalize(&self) -> Result<TransactionOutput, TransactionProtocolError> {
+        let (commitment, sender_offset_public_key, ..) = self.session.aggregate()?;
+        let output = TransactionOutput::new(
+            self.version,
+            self.features.clone(),
+            commitment,
+            None,
+            self.script.clone(),
+            sender_offset_public_key,
+            self.session.aggregate_signature()?,
+            self.covenant.clone(),
+            self.encrypted_data.clone(),
+            self.minimum_value_promise,
+        );
+        output.verify_metadata_signature()?;
+        Ok(output)
+    }
+
+    fn challenge(&self) -> Result<[u8; 64], TransactionProtocolError> {
+        let (commitment, sender_offset_public_key, ephemeral_commitment, ephemeral_pubkey) =
+            self.session.aggregate()?;
+        Ok(TransactionOutput::build_metadata_signature_challenge(
+            &self.version,
+            &self.script,
+            &self.features,
+            &sender_offset_public_key,
+            &ephemeral_commitment,
+            &ephemeral_pubkey,
+            &commitment,
+            &self.covenant,
+            &self.encrypted_data,
+            self.minimum_value_promise,
+        ))
+    }
+}
+
+/// One party's state of the construction of an input that spends an aggregate output. The aggregate script signature
+/// signs for the sum of the parties' spending key and script key shares, so the script of the spent output must
+/// resolve to the sum of the parties' script public keys.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MultipartyInputProtocol {
+    session: AggregateSignatureSession,
+    version: TransactionInputVersion,
+    spent_output: TransactionOutput,
+    input_data: ExecutionStack,
+}
+
+impl MultipartyInputProtocol {
+    /// Starts the protocol for the party at `party_index`. The value shares must be the same as the ones used to
+    /// create `spent_output`.
+    pub fn new(
+        party_index: usize,
+        num_parties: usize,
+        value_share: MicroMinotari,
+        spending_key_share: PrivateKey,
+        script_key_share: PrivateKey,
+        spent_output: TransactionOutput,
+        input_data: ExecutionStack,
+    ) -> Result<Self, TransactionProtocolError> {
+        Ok(Self {
+            session: AggregateSignatureSession::new(
+                party_index,
+                num_parties,
+                value_share,
+                spending_key_share,
+                script_key_share,
+                false,
+            )?,
+            version: TransactionInputVersion::get_current_version(),
+            spent_output,
+            input_data,
+        })
+    }
+
+    pub fn round(&self) -> AggregationRound {
+        self.session.round()
+    }
+
+    /// This party's nonces, to be sent to all other parties
+    pub fn nonces(&self) -> PartyNonces {
+        self.session.own_nonces()
+    }
+
+    pub fn add_nonces(&mut self, nonces: PartyNonces) -> Result<(), TransactionProtocolError> {
+        self.session.add_nonces(nonces)
+    }
+
+    /// Creates this party's partial script signature, to be sent to all other parties. All nonces must have been
+    /// received.
+    pub fn sign(&mut self) -> Result<PartialSignature, TransactionProtocolError> {
+        let challenge = self.challenge()?;
+        self.session.sign(challenge)
+    }
+
+    pub fn add_partial_signature(&mut self, partial: PartialSignature) -> Result<(), TransactionProtocolError> {
+        self.session.add_partial_signature(partial)
+    }
+
+    /// Builds the aggregate input once all partial signatures have been received
+    pub fn finalize(&self) -> Result<TransactionInput, TransactionProtocolError> {
+        let output = &self.spent_output;
+        let rangeproof_hash = match &output.proof {
+            Some(rp) => rp.hash(),
+            None => FixedHash::zero(),
+        };
+        let input = TransactionInput::new_with_output_data(
+            self.version,
+            output.features.clone(),
+            output.commitment.clone(),
+            output.script.clone(),
+            self.input_data.clone(),
+            self.session.aggregate_signature()?,
+            output.sender_offset_public_key.clone(),
+            output.covenant.clone(),
+            output.encrypted_data.clone(),
+            output.metadata_signature.clone(),
+            rangeproof_hash,
+            output.minimum_value_promise,
+        );
+        let (_, script_public_key, ..) = self.session.aggregate()?;
+        input.validate_script_signature(&script_public_key, &CommitmentFactory::default())?;
+        Ok(input)
+    }
+
+    fn challenge(&self) -> Result<[u8; 64], TransactionProtocolError> {
+        let (commitment, script_public_key, ephemeral_commitment, ephemeral_pubkey) = self.session.aggregate()?;
+        if commitment != self.spent_output.commitment {
+            return Err(TransactionProtocolError::ValidationError(
+                "The aggregate commitment does not match the spent output".to_string(),
+            ));
+        }
+        Ok(TransactionInput::build_script_signature_challenge(
+            &self.version,
+            &ephemeral_commitment,
+            &ephemeral_pubkey,
+            &self.spent_output.script,
+            &self.input_data,
+            &script_public_key,
+            &commitment,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_script::script;
+
+    use super::*;
+    use crate::transactions::CryptoFactories;
+
+    struct Party {
+        value: MicroMinotari,
+        spending_key: PrivateKey,
+        sender_offset_key: PrivateKey,
+        script_key: PrivateKey,
+    }
+
+    fn parties(values: &[u64]) -> Vec<Party> {
+        values
+            .iter()
+            .map(|v| Party {
+                value: MicroMinotari(*v),
+                spending_key: PrivateKey::random(&mut OsRng),
+                sender_offset_key: PrivateKey::random(&mut OsRng),
+                script_key: PrivateKey::random(&mut OsRng),
+            })
+            .collect()
+    }
+
+    fn output_protocols(parties: &[Party]) -> Vec<MultipartyOutputProtocol> {
+        let script_public_key = parties
+            .iter()
+            .map(|p| PublicKey::from_secret_key(&p.script_key))
+            .fold(PublicKey::default(), |acc, k| acc + k);
+        let total = parties.iter().map(|p| p.value).sum();
+        parties
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                MultipartyOutputProtocol::new(
+                    i,
+                    parties.len(),
+                    p.value,
+                    p.spending_key.clone(),
+                    p.sender_offset_key.clone(),
+                    OutputFeatures {
+                        range_proof_type: RangeProofType::RevealedValue,
+                        ..Default::default()
+                    },
+                    script!(PushPubKey(Box::new(script_public_key.clone()))),
+                    Covenant::default(),
+                    EncryptedData::default(),
+                    total,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn exchange_nonces(nonces: &[PartyNonces], add: &mut dyn FnMut(usize, PartyNonces)) {
+        for i in 0..nonces.len() {
+            for n in nonces.iter().filter(|n| n.party_index != i) {
+                add(i, n.clone());
+            }
+        }
+    }
+
+    fn create_aggregate_output(parties: &[Party]) -> TransactionOutput {
+        let mut protocols = output_protocols(parties);
+        let nonces = protocols.iter().map(|p| p.nonces()).collect::<Vec<_>>();
+        exchange_nonces(&nonces, &mut |i, n| protocols[i].add_nonces(n).unwrap());
+        let partials = protocols.iter_mut().map(|p| p.sign().unwrap()).collect::<Vec<_>>();
+        for protocol in &mut protocols {
+            for partial in &partials {
+                if partial.party_index != protocol.session.party_index {
+                    protocol.add_partial_signature(partial.clone()).unwrap();
+                }
+            }
+            assert_eq!(protocol.round(), AggregationRound::Complete);
+        }
+        let output = protocols[0].finalize().unwrap();
+        assert!(protocols.iter().all(|p| p.finalize().unwrap() == output));
+        output
+    }
+
+    #[test]
+    fn it_creates_and_spends_an_aggregate_output() {
+        let parties = parties(&[100, 200, 300]);
+        let output = create_aggregate_output(&parties);
+        let factories = CryptoFactories::default();
+        output.verify_metadata_signature().unwrap();
+        output.verify_range_proof(&factories.range_proof).unwrap();
+        assert_eq!(output.minimum_value_promise, MicroMinotari(600));
+
+        let mut protocols = parties
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                MultipartyInputProtocol::new(
+                    i,
+                    parties.len(),
+                    p.value,
+                    p.spending_key.clone(),
+                    p.script_key.clone(),
+                    output.clone(),
+                    ExecutionStack::default(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let nonces = protocols.iter().map(|p| p.nonces()).collect::<Vec<_>>();
+        exchange_nonces(&nonces, &mut |i, n| protocols[i].add_nonces(n).unwrap());
+        let partials = protocols.iter_mut().map(|p| p.sign().unwrap()).collect::<Vec<_>>();
+        for (i, protocol) in protocols.iter_mut().enumerate() {
+            for partial in partials.iter().filter(|p| p.party_index != i) {
+                protocol.add_partial_signature(partial.clone()).unwrap();
+            }
+        }
+        let input = protocols[2].finalize().unwrap();
+        input.run_and_verify_script(&factories.commitment, None).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_invalid_partial_signatures() {
+        let parties = parties(&[100, 200]);
+        let mut protocols = output_protocols(&parties);
+        let nonces = protocols.iter().map(|p| p.nonces()).collect::<Vec<_>>();
+        exchange_nonces(&nonces, &mut |i, n| protocols[i].add_nonces(n).unwrap());
+        let err = protocols[0].add_nonces(nonces[1].clone()).unwrap_err();
+        assert!(matches!(err, TransactionProtocolError::InvalidStateError));
+
+        let partial_0 = protocols[0].sign().unwrap();
+        let mut partial_1 = protocols[1].sign().unwrap();
+        // Party 1 claims party 0's signature as its own
+        partial_1.signature = partial_0.signature;
+        let err = protocols[0].add_partial_signature(partial_1).unwrap_err();
+        assert!(matches!(err, TransactionProtocolError::InvalidSignatureError(_)));
+        assert!(matches!(
+            protocols[0].finalize().unwrap_err(),
+            TransactionProtocolError::IncompleteStateError(_)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_non_revealed_value_outputs() {
+        let err = MultipartyOutputProtocol::new(
+            0,
+            2,
+            MicroMinotari(100),
+            PrivateKey::random(&mut OsRng),
+            PrivateKey::random(&mut OsRng),
+            OutputFeatures::default(),
+            script!(Nop),
+            Covenant::default(),
+            EncryptedData::default(),
+            MicroMinotari(100),
+        )
+        .unwrap_err();
+        assert!(matches!(err, TransactionProtocolError::UnsupportedError(_)));
+    }
+
+    #[test]
+    fn it_resumes_from_serialized_state() {
+        let parties = parties(&[50, 70]);
+        let mut protocols = output_protocols(&parties);
+        let nonces = protocols.iter().map(|p| p.nonces()).collect::<Vec<_>>();
+        exchange_nonces(&nonces, &mut |i, n| protocols[i].add_nonces(n).unwrap());
+        let partial_0 = protocols[0].sign().unwrap();
+
+        let state = serde_json::to_string(&protocols[0]).unwrap();
+        let mut resumed: MultipartyOutputProtocol = serde_json::from_str(&state).unwrap();
+        assert_eq!(resumed.round(), AggregationRound::CollectingPartialSignatures);
+
+        let partial_1 = protocols[1].sign().unwrap();
+        protocols[1].add_partial_signature(partial_0).unwrap();
+        resumed.add_partial_signature(partial_1).unwrap();
+        assert_eq!(resumed.finalize().unwrap(), protocols[1].finalize().unwrap());
+    }
+}
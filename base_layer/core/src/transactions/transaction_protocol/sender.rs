@@ -37,7 +37,7 @@ use crate::{
     covenants::Covenant,
     transactions::{
         fee::Fee,
-        key_manager::{TariKeyId, TransactionKeyManagerInterface, TxoStage},
+        key_manager::{TariKeyId, TransactionKeyManagerInterface, TransactionReviewData, TxoStage},
         tari_amount::*,
         transaction_components::{
             KernelBuilder,
@@ -567,12 +567,38 @@ impl SenderTransactionProtocol {
         Ok(metadata_signature)
     }
 
+    /// The transaction details shown on a hardware signer before the transaction is signed
+    fn review_data(info: &RawTransactionInfo) -> TransactionReviewData {
+        let amount = match &info.recipient_data {
+            Some(recipient_data) => recipient_data.amount,
+            None => info
+                .outputs
+                .iter()
+                .fold(MicroMinotari::zero(), |total, output| total + output.output.value),
+        };
+        TransactionReviewData {
+            amount,
+            fee: info.metadata.fee,
+            change: info
+                .change_output
+                .as_ref()
+                .map(|change| change.output.value)
+                .unwrap_or_default(),
+            num_inputs: info.inputs.len(),
+            num_outputs: info.outputs.len() +
+                usize::from(info.change_output.is_some()) +
+                usize::from(info.recipient_data.is_some()),
+        }
+    }
+
     /// Attempts to build the final transaction.
     #[allow(clippy::too_many_lines)]
     async fn build_transaction<KM: TransactionKeyManagerInterface>(
         info: &RawTransactionInfo,
         key_manager: &KM,
     ) -> Result<Transaction, TPE> {
+        key_manager.review_transaction(&Self::review_data(info)).await?;
+
         let mut tx_builder = TransactionBuilder::new();
         let (total_public_nonce, total_public_excess) = if info.recipient_data.is_none() {
             // we dont have a recipient and thus we have not yet calculated the sender_nonce and sender_offset_excess
@@ -984,7 +1010,7 @@ mod test {
 
         // Encrypted value
         let encrypted_data = key_manager
-            .encrypt_data_for_recovery(&spending_key_id, None, value)
+            .encrypt_data_for_recovery(&spending_key_id, None, value, None, None)
             .await
             .unwrap();
 
@@ -384,7 +384,7 @@ where KM: TransactionKeyManagerInterface
 
                         let encrypted_data = self
                             .key_manager
-                            .encrypt_data_for_recovery(&change_key_id, None, v.as_u64())
+                            .encrypt_data_for_recovery(&change_key_id, None, v.as_u64(), None, None)
                             .await
                             .map_err(|e| e.to_string())?;
 
@@ -20,9 +20,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryFrom, num::NonZeroU64};
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Formatter},
+    num::NonZeroU64,
+};
 
-use crate::transactions::aggregated_body::AggregateBody;
+#[cfg(feature = "base_node")]
+use crate::consensus::ConsensusManager;
+use crate::transactions::{aggregated_body::AggregateBody, tari_amount::MicroMinotari};
 
 #[derive(Debug, Clone, Copy)]
 pub struct WeightParams {
@@ -141,6 +147,156 @@ impl From<WeightParams> for TransactionWeight {
     }
 }
 
+/// A component of a transaction that contributes to its weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightComponent {
+    Kernels,
+    Inputs,
+    Outputs,
+    FeaturesAndScripts,
+}
+
+impl Display for WeightComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WeightComponent::Kernels => write!(f, "kernels"),
+            WeightComponent::Inputs => write!(f, "inputs"),
+            WeightComponent::Outputs => write!(f, "outputs"),
+            WeightComponent::FeaturesAndScripts => write!(f, "output features and scripts"),
+        }
+    }
+}
+
+/// The weight in grams of a transaction or aggregate body, broken down per component. The components always add up
+/// to the weight given by [TransactionWeight::calculate_body].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeightBreakdown {
+    /// Weight in grams of all kernels
+    pub kernels: u64,
+    /// Weight in grams of all inputs
+    pub inputs: u64,
+    /// Weight in grams of all outputs, excl. TariScript, OutputFeatures and covenants
+    pub outputs: u64,
+    /// Weight in grams of the rounded up features, scripts and covenants of all outputs
+    pub features_and_scripts: u64,
+}
+
+impl WeightBreakdown {
+    /// The total weight in grams
+    pub fn total(&self) -> u64 {
+        self.kernels
+            .saturating_add(self.inputs)
+            .saturating_add(self.outputs)
+            .saturating_add(self.features_and_scripts)
+    }
+
+    /// The component that contributes the most weight
+    pub fn heaviest_component(&self) -> WeightComponent {
+        [
+            (WeightComponent::Kernels, self.kernels),
+            (WeightComponent::Inputs, self.inputs),
+            (WeightComponent::Outputs, self.outputs),
+            (WeightComponent::FeaturesAndScripts, self.features_and_scripts),
+        ]
+        .iter()
+        .fold((WeightComponent::Kernels, 0), |heaviest, component| {
+            if component.1 > heaviest.1 {
+                *component
+            } else {
+                heaviest
+            }
+        })
+        .0
+    }
+}
+
+impl Display for WeightBreakdown {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "kernels: {}g, inputs: {}g, outputs: {}g, output features and scripts: {}g, heaviest: {}",
+            self.kernels,
+            self.inputs,
+            self.outputs,
+            self.features_and_scripts,
+            self.heaviest_component()
+        )
+    }
+}
+
+/// Calculates the weight of transactions per component for the weight params of a consensus constants epoch. Wallets
+/// can use this to predict the fee of a transaction before it is built.
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionWeightCalculator(TransactionWeight);
+
+impl TransactionWeightCalculator {
+    pub fn new(weighting: TransactionWeight) -> Self {
+        Self(weighting)
+    }
+
+    /// Creates a calculator using the weight params of the consensus constants that are effective at `height`
+    #[cfg(feature = "base_node")]
+    pub fn for_height(rules: &ConsensusManager, height: u64) -> Self {
+        Self(*rules.consensus_constants(height).transaction_weight_params())
+    }
+
+    /// Breaks the weight down for a transaction with the given number of kernels and inputs, and outputs with the given
+    /// (not rounded up) features and scripts sizes.
+    pub fn breakdown(
+        &self,
+        num_kernels: usize,
+        num_inputs: usize,
+        output_features_and_scripts_sizes: &[usize],
+    ) -> WeightBreakdown {
+        let params = self.0.params();
+        let rounded_up_size = output_features_and_scripts_sizes
+            .iter()
+            .map(|size| self.0.round_up_features_and_scripts_size(*size))
+            .fold(0usize, |total, size| total.saturating_add(size));
+        WeightBreakdown {
+            kernels: params.kernel_weight * num_kernels as u64,
+            inputs: params.input_weight * num_inputs as u64,
+            outputs: params.output_weight * output_features_and_scripts_sizes.len() as u64,
+            features_and_scripts: rounded_up_size as u64 / params.features_and_scripts_bytes_per_gram.get(),
+        }
+    }
+
+    /// Breaks the weight of a body down per component
+    pub fn breakdown_body(&self, body: &AggregateBody) -> std::io::Result<WeightBreakdown> {
+        let sizes = body
+            .outputs()
+            .iter()
+            .map(|o| o.get_features_and_scripts_size())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.breakdown(body.kernels().len(), body.inputs().len(), &sizes))
+    }
+
+    /// Predicts the fee for a transaction with the given number of kernels and inputs, and outputs with the given
+    /// features and scripts sizes
+    pub fn predict_fee(
+        &self,
+        fee_per_gram: MicroMinotari,
+        num_kernels: usize,
+        num_inputs: usize,
+        output_features_and_scripts_sizes: &[usize],
+    ) -> MicroMinotari {
+        let weight = self
+            .breakdown(num_kernels, num_inputs, output_features_and_scripts_sizes)
+            .total();
+        MicroMinotari::from(weight.saturating_mul(fee_per_gram.0))
+    }
+
+    pub fn weighting(&self) -> &TransactionWeight {
+        &self.0
+    }
+}
+
+impl From<TransactionWeight> for TransactionWeightCalculator {
+    fn from(weighting: TransactionWeight) -> Self {
+        Self(weighting)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -170,4 +326,36 @@ mod test {
         let body = AggregateBody::empty();
         assert_eq!(weighting.calculate_body(&body).unwrap(), 0);
     }
+
+    #[test]
+    fn breakdown_adds_up_to_the_calculated_weight() {
+        let calculator = TransactionWeightCalculator::new(TransactionWeight::latest());
+        let sizes = [1, 16, 17, 100];
+        let breakdown = calculator.breakdown(2, 3, &sizes);
+        let params = calculator.weighting().params();
+        assert_eq!(breakdown.kernels, 2 * params.kernel_weight);
+        assert_eq!(breakdown.inputs, 3 * params.input_weight);
+        assert_eq!(breakdown.outputs, 4 * params.output_weight);
+        assert_eq!(breakdown.features_and_scripts, (16 + 16 + 32 + 112) / 16);
+        assert_eq!(
+            breakdown.total(),
+            calculator.weighting().calculate(2, 3, 4, 16 + 16 + 32 + 112)
+        );
+        assert_eq!(breakdown.heaviest_component(), WeightComponent::Outputs);
+        assert_eq!(
+            calculator.predict_fee(MicroMinotari(5), 2, 3, &sizes),
+            MicroMinotari(breakdown.total() * 5)
+        );
+
+        let breakdown = calculator.breakdown(100, 1, &[]);
+        assert_eq!(breakdown.heaviest_component(), WeightComponent::Kernels);
+    }
+
+    #[test]
+    fn empty_body_breakdown() {
+        let calculator = TransactionWeightCalculator::new(TransactionWeight::latest());
+        let breakdown = calculator.breakdown_body(&AggregateBody::empty()).unwrap();
+        assert_eq!(breakdown, WeightBreakdown::default());
+        assert_eq!(breakdown.total(), 0);
+    }
 }
@@ -20,10 +20,10 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use log::warn;
-use tari_common_types::types::FixedHash;
+use tari_common_types::types::{FixedHash, HashOutput};
 use tari_utilities::hex::Hex;
 
 use crate::{
@@ -66,6 +66,17 @@ impl AggregateBodyChainLinkedValidator {
         Ok(body)
     }
 
+    /// Resolves the compact inputs of `body` into full inputs. The outputs they spend are looked up in the database, in
+    /// `pending_outputs` (outputs of earlier blocks that have not been stored yet) and in the body itself.
+    pub fn resolve_inputs<B: BlockchainBackend>(
+        &self,
+        body: &AggregateBody,
+        db: &B,
+        pending_outputs: &HashMap<HashOutput, TransactionOutput>,
+    ) -> Result<Vec<TransactionInput>, ValidationError> {
+        validate_input_not_pruned(body, db, pending_outputs)
+    }
+
     fn validate_consensus<B: BlockchainBackend>(
         &self,
         body: &AggregateBody,
@@ -90,7 +101,7 @@ impl AggregateBodyChainLinkedValidator {
     ) -> Result<AggregateBody, ValidationError> {
         // inputs may be "slim", only containing references to outputs
         // so we need to resolve those references, creating a new body in the process
-        let inputs = validate_input_not_pruned(body, db)?;
+        let inputs = validate_input_not_pruned(body, db, &HashMap::new())?;
         // UNCHECKED: sorting has been checked by the AggregateBodyInternalConsistencyValidator
         let body = AggregateBody::new_sorted_unchecked(inputs, body.outputs().to_vec(), body.kernels().to_vec());
 
@@ -108,6 +119,7 @@ impl AggregateBodyChainLinkedValidator {
 fn validate_input_not_pruned<B: BlockchainBackend>(
     body: &AggregateBody,
     db: &B,
+    pending_outputs: &HashMap<HashOutput, TransactionOutput>,
 ) -> Result<Vec<TransactionInput>, ValidationError> {
     let mut inputs: Vec<TransactionInput> = body.inputs().clone();
     for input in &mut inputs {
@@ -117,7 +129,9 @@ fn validate_input_not_pruned<B: BlockchainBackend>(
                     Some(output_mined_info) => output_mined_info.output,
                     None => {
                         let input_output_hash = input.output_hash();
-                        if let Some(found) = body.outputs().iter().find(|o| o.hash() == input_output_hash) {
+                        if let Some(found) = pending_outputs.get(&input_output_hash) {
+                            found.clone()
+                        } else if let Some(found) = body.outputs().iter().find(|o| o.hash() == input_output_hash) {
                             found.clone()
                         } else {
                             warn!(
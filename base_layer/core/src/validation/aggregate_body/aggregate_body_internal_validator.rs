@@ -20,7 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{collections::HashSet, convert::TryInto};
+use std::{collections::HashSet, convert::TryInto, slice};
 
 use log::{trace, warn};
 use tari_common_types::types::{Commitment, CommitmentFactory, HashOutput, PrivateKey, PublicKey, RangeProofService};
@@ -37,21 +37,18 @@ use crate::{
     transactions::{
         aggregated_body::AggregateBody,
         tari_amount::MicroMinotari,
-        transaction_components::{
-            transaction_output::batch_verify_range_proofs,
-            KernelSum,
-            TransactionError,
-            TransactionInput,
-            TransactionKernel,
-            TransactionOutput,
-        },
+        transaction_components::{KernelSum, TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
+        weight::TransactionWeightCalculator,
         CryptoFactories,
     },
     validation::{
         helpers::{
             check_covenant_length,
+            check_memo_size,
+            check_payment_reference_size,
             check_permitted_output_types,
             check_permitted_range_proof_types,
+            check_range_proofs_in_parallel,
             check_tari_script_byte_size,
             is_all_unique_and_sorted,
             validate_input_version,
@@ -59,6 +56,9 @@ use crate::{
             validate_output_version,
         },
         ValidationError,
+        ValidationItem,
+        ValidationItemKind,
+        ValidationReport,
     },
 };
 
@@ -100,62 +100,137 @@ impl AggregateBodyInternalConsistencyValidator {
         total_reward: Option<MicroMinotari>,
         prev_header: Option<HashOutput>,
         height: u64,
+    ) -> Result<(), ValidationError> {
+        self.validate_with_report(
+            body,
+            tx_offset,
+            script_offset,
+            total_reward,
+            prev_header,
+            height,
+            &mut ValidationReport::new(),
+        )
+    }
+
+    /// Performs the same validation as [validate](Self::validate), recording each check that is run, its duration
+    /// and the item that caused it to fail in the given report.
+    pub fn validate_with_report(
+        &self,
+        body: &AggregateBody,
+        tx_offset: &PrivateKey,
+        script_offset: &PrivateKey,
+        total_reward: Option<MicroMinotari>,
+        prev_header: Option<HashOutput>,
+        height: u64,
+        report: &mut ValidationReport,
     ) -> Result<(), ValidationError> {
         let total_reward = total_reward.unwrap_or(MicroMinotari::zero());
 
         // old internal validator
-        verify_kernel_signatures(body)?;
+        trace!(target: LOG_TARGET, "Checking kernel signatures",);
+        report.run_item_checks(
+            "kernel_signatures",
+            ValidationItemKind::Kernel,
+            body.kernels(),
+            |k| k.hash(),
+            verify_kernel_signature,
+        )?;
 
         let constants = self.consensus_manager.consensus_constants(height);
 
-        validate_versions(body, constants)?;
+        report.run_check("versions", || validate_versions(body, constants))?;
 
-        for output in body.outputs() {
-            check_permitted_output_types(constants, output)?;
-            check_script_size(output, constants.max_script_byte_size())?;
-            check_covenant_length(&output.covenant, constants.max_covenant_length())?;
-            check_permitted_range_proof_types(constants, output)?;
-            check_validator_node_registration_utxo(constants, output)?;
-        }
+        report.run_item_checks(
+            "output_rules",
+            ValidationItemKind::Output,
+            body.outputs(),
+            |o| o.hash(),
+            |output| check_output_rules(constants, output),
+        )?;
 
-        check_weight(body, height, constants)?;
-        check_sorting_and_duplicates(body)?;
+        report.run_check("weight", || check_weight(body, height, constants))?;
+        report.run_check("sorting_and_duplicates", || check_sorting_and_duplicates(body))?;
 
         // Check that the inputs are are allowed to be spent
-        check_maturity(height, body.inputs())?;
-        check_kernel_lock_height(height, body.kernels())?;
+        report.run_item_checks(
+            "input_maturity",
+            ValidationItemKind::Input,
+            body.inputs(),
+            |i| i.output_hash(),
+            |input| Ok(check_maturity(height, slice::from_ref(input))?),
+        )?;
+        report.run_item_checks(
+            "kernel_lock_height",
+            ValidationItemKind::Kernel,
+            body.kernels(),
+            |k| k.hash(),
+            |kernel| check_kernel_lock_height(height, slice::from_ref(kernel)),
+        )?;
 
         let total_offset = self.factories.commitment.commit_value(tx_offset, total_reward.0);
-        validate_kernel_sum(body, total_offset, &self.factories.commitment)?;
+        report.run_check("kernel_sum", || {
+            validate_kernel_sum(body, total_offset, &self.factories.commitment)
+        })?;
 
         if !self.bypass_range_proof_verification {
-            validate_range_proofs(body, &self.factories.range_proof)?;
+            report.run_check_with_item("range_proofs", || {
+                validate_range_proofs(body, &self.factories.range_proof).map_err(|err| {
+                    let item = first_invalid_range_proof_output(body, &err);
+                    (err, item)
+                })
+            })?;
         }
-        verify_metadata_signatures(body)?;
+        trace!(target: LOG_TARGET, "Checking sender signatures");
+        report.run_item_checks(
+            "metadata_signatures",
+            ValidationItemKind::Output,
+            body.outputs(),
+            |o| o.hash(),
+            |output| Ok(output.verify_metadata_signature()?),
+        )?;
 
         let script_offset_g = PublicKey::from_secret_key(script_offset);
-        validate_script_and_script_offset(body, script_offset_g, &self.factories.commitment, prev_header, height)?;
-        validate_covenants(body, height)?;
-
-        check_total_burned(body)?;
+        report.run_check("script_and_script_offset", || {
+            validate_script_and_script_offset(body, script_offset_g, &self.factories.commitment, prev_header, height)
+        })?;
+        report.run_item_checks(
+            "covenants",
+            ValidationItemKind::Input,
+            body.inputs(),
+            |i| i.output_hash(),
+            |input| {
+                input.covenant()?.execute(height, input, body.outputs())?;
+                Ok(())
+            },
+        )?;
+
+        report.run_check("total_burned", || check_total_burned(body))?;
 
         Ok(())
     }
 }
 
-/// Verify the signatures in all kernels contained in this aggregate body. Clients must provide an offset that
-/// will be added to the public key used in the signature verification.
-fn verify_kernel_signatures(body: &AggregateBody) -> Result<(), ValidationError> {
-    trace!(target: LOG_TARGET, "Checking kernel signatures",);
-    for kernel in body.kernels() {
-        kernel.verify_signature().map_err(|e| {
-            warn!(target: LOG_TARGET, "Kernel ({}) signature failed {:?}.", kernel, e);
-            e
-        })?;
-    }
+/// Verify the signature of a kernel contained in this aggregate body.
+fn verify_kernel_signature(kernel: &TransactionKernel) -> Result<(), ValidationError> {
+    kernel.verify_signature().map_err(|e| {
+        warn!(target: LOG_TARGET, "Kernel ({}) signature failed {:?}.", kernel, e);
+        e
+    })?;
     Ok(())
 }
 
+/// Checks the consensus rules that apply to a single output
+fn check_output_rules(constants: &ConsensusConstants, output: &TransactionOutput) -> Result<(), ValidationError> {
+    check_permitted_output_types(constants, output)?;
+    check_script_size(output, constants.max_script_byte_size())?;
+    check_covenant_length(&output.covenant, constants.max_covenant_length())?;
+    check_payment_reference_size(output, constants.max_payment_reference_size())?;
+    check_memo_size(output, constants.max_memo_size())?;
+    check_permitted_range_proof_types(constants, output)?;
+    check_validator_node_registration_utxo(constants, output)?;
+    check_template_registration_utxo(constants, output)
+}
+
 /// Verify that the TariScript is not larger than the max size
 fn check_script_size(output: &TransactionOutput, max_script_size: usize) -> Result<(), ValidationError> {
     check_tari_script_byte_size(output.script(), max_script_size).map_err(|e| {
@@ -242,22 +317,21 @@ fn sum_commitments(body: &AggregateBody) -> Result<Commitment, ValidationError>
     Ok(&sum_outputs - &sum_inputs)
 }
 
-fn validate_range_proofs(
-    body: &AggregateBody,
-    range_proof_service: &RangeProofService,
-) -> Result<(), TransactionError> {
-    trace!(target: LOG_TARGET, "Checking range proofs");
-    let outputs = body.outputs().iter().collect::<Vec<_>>();
-    batch_verify_range_proofs(range_proof_service, &outputs)?;
-    Ok(())
+/// Returns the first output that failed range proof verification, if the error identifies one
+fn first_invalid_range_proof_output(body: &AggregateBody, err: &ValidationError) -> Option<ValidationItem> {
+    match err {
+        ValidationError::InvalidRangeProofs { output_indices } => output_indices.first().map(|&index| ValidationItem {
+            kind: ValidationItemKind::Output,
+            index,
+            hash: body.outputs()[index].hash(),
+        }),
+        _ => None,
+    }
 }
 
-fn verify_metadata_signatures(body: &AggregateBody) -> Result<(), ValidationError> {
-    trace!(target: LOG_TARGET, "Checking sender signatures");
-    for o in body.outputs() {
-        o.verify_metadata_signature()?;
-    }
-    Ok(())
+fn validate_range_proofs(body: &AggregateBody, range_proof_service: &RangeProofService) -> Result<(), ValidationError> {
+    trace!(target: LOG_TARGET, "Checking range proofs");
+    check_range_proofs_in_parallel(range_proof_service, body.outputs())
 }
 
 /// this will validate the script and script offset of the aggregate body.
@@ -292,13 +366,6 @@ fn validate_script_and_script_offset(
     Ok(())
 }
 
-fn validate_covenants(body: &AggregateBody, height: u64) -> Result<(), ValidationError> {
-    for input in body.inputs() {
-        input.covenant()?.execute(height, input, body.outputs())?;
-    }
-    Ok(())
-}
-
 fn check_weight(
     body: &AggregateBody,
     height: u64,
@@ -319,9 +386,13 @@ fn check_weight(
 
         Ok(())
     } else {
+        let breakdown = TransactionWeightCalculator::new(*consensus_constants.transaction_weight_params())
+            .breakdown_body(body)
+            .map_err(|e| ValidationError::SerializationError(format!("Unable to calculate body weight: {}", e)))?;
         Err(ValidationError::BlockTooLarge {
             actual_weight: block_weight,
             max_weight,
+            breakdown,
         })
     }
 }
@@ -357,6 +428,12 @@ fn check_total_burned(body: &AggregateBody) -> Result<(), ValidationError> {
         if output.is_burned() {
             // we dont care about duplicate commitments are they should have already been checked
             burned_outputs.insert(output.commitment.clone());
+        } else if output.features.burn_claim_public_key().is_some() {
+            // A claim public key lets the second layer mint the value of the output, so only burned outputs may have
+            // one
+            return Err(ValidationError::InvalidBurnError(
+                "Only burned outputs can have a claim public key".to_string(),
+            ));
         }
     }
     for kernel in body.kernels() {
@@ -400,6 +477,50 @@ fn check_validator_node_registration_utxo(
     Ok(())
 }
 
+fn check_template_registration_utxo(
+    consensus_constants: &ConsensusConstants,
+    utxo: &TransactionOutput,
+) -> Result<(), ValidationError> {
+    let max_url_length = match consensus_constants.max_template_registration_url_length() {
+        Some(max_url_length) => max_url_length,
+        None => return Ok(()),
+    };
+    let reg = match utxo.features.code_template_registration() {
+        Some(reg) => reg,
+        None => return Ok(()),
+    };
+    if reg.template_name.is_empty() {
+        return Err(ValidationError::InvalidTemplateRegistration(
+            "template name is empty".to_string(),
+        ));
+    }
+    if reg.binary_sha.len() != 32 {
+        return Err(ValidationError::InvalidTemplateRegistration(format!(
+            "binary hash must be 32 bytes but was {} bytes",
+            reg.binary_sha.len()
+        )));
+    }
+    // Git commit hashes are either SHA-1 or SHA-256
+    let commit_hash_len = reg.build_info.commit_hash.len();
+    if commit_hash_len != 20 && commit_hash_len != 32 {
+        return Err(ValidationError::InvalidTemplateRegistration(format!(
+            "commit hash must be 20 or 32 bytes but was {} bytes",
+            commit_hash_len
+        )));
+    }
+    for (name, url) in [("binary", &reg.binary_url), ("repository", &reg.build_info.repo_url)] {
+        if url.is_empty() || url.len() > max_url_length {
+            return Err(ValidationError::InvalidTemplateRegistration(format!(
+                "{} URL length {} is not between 1 and {}",
+                name,
+                url.len(),
+                max_url_length
+            )));
+        }
+    }
+    Ok(())
+}
+
 fn validate_versions(body: &AggregateBody, consensus_constants: &ConsensusConstants) -> Result<(), ValidationError> {
     // validate input version
     for input in body.inputs() {
@@ -435,7 +556,7 @@ mod test {
         transactions::{
             key_manager::create_memory_db_key_manager,
             test_helpers,
-            transaction_components::{KernelFeatures, OutputFeatures, TransactionInputVersion},
+            transaction_components::{KernelFeatures, OutputFeatures, OutputType, TransactionInputVersion},
         },
     };
 
@@ -494,6 +615,79 @@ mod test {
         }
     }
 
+    mod check_template_registration_utxo {
+        use super::*;
+        use crate::transactions::transaction_components::{BuildInfo, TemplateType};
+
+        fn template_registration_output(commit_hash: &[u8], binary_url: &str) -> TransactionOutput {
+            TransactionOutput {
+                features: OutputFeatures::for_code_template_registration(
+                    Default::default(),
+                    Default::default(),
+                    "test".try_into().unwrap(),
+                    1,
+                    TemplateType::Wasm { abi_version: 1 },
+                    BuildInfo {
+                        repo_url: "https://github.com/tari-project/tari.git".try_into().unwrap(),
+                        commit_hash: commit_hash.to_vec().try_into().unwrap(),
+                    },
+                    vec![1u8; 32].try_into().unwrap(),
+                    binary_url.try_into().unwrap(),
+                ),
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn it_accepts_a_valid_registration() {
+            let constants = ConsensusConstants::localnet().pop().unwrap();
+            let output = template_registration_output(&[2u8; 20], "https://example.com/template.wasm");
+            check_template_registration_utxo(&constants, &output).unwrap();
+            check_permitted_output_types(&constants, &output).unwrap();
+        }
+
+        #[test]
+        fn it_rejects_invalid_metadata() {
+            let constants = ConsensusConstants::localnet().pop().unwrap();
+            let output = template_registration_output(&[2u8; 16], "https://example.com/template.wasm");
+            assert!(matches!(
+                check_template_registration_utxo(&constants, &output),
+                Err(ValidationError::InvalidTemplateRegistration(_))
+            ));
+
+            let output = template_registration_output(&[2u8; 32], "");
+            assert!(matches!(
+                check_template_registration_utxo(&constants, &output),
+                Err(ValidationError::InvalidTemplateRegistration(_))
+            ));
+        }
+
+        #[test]
+        fn it_rejects_a_mismatched_output_type() {
+            let constants = ConsensusConstants::localnet().pop().unwrap();
+            let mut output = template_registration_output(&[2u8; 20], "https://example.com/template.wasm");
+            output.features.output_type = OutputType::Standard;
+            assert!(matches!(
+                check_permitted_output_types(&constants, &output),
+                Err(ValidationError::InvalidTemplateRegistration(_))
+            ));
+        }
+
+        #[test]
+        fn it_only_applies_the_rules_from_the_activation_height() {
+            let rules = ConsensusManager::builder(Network::LocalNet).build().unwrap();
+            let activation_height = rules.consensus_constants(u64::MAX).effective_from_height();
+            let mut output = template_registration_output(&[2u8; 16], "");
+            output.features.output_type = OutputType::Standard;
+
+            check_output_rules(rules.consensus_constants(activation_height - 1), &output).unwrap();
+            assert!(matches!(
+                check_output_rules(rules.consensus_constants(activation_height), &output),
+                Err(ValidationError::InvalidTemplateRegistration(_))
+            ));
+        }
+    }
+
     #[tokio::test]
     async fn check_burned_succeeds_for_valid_outputs() {
         let mut kernel1 = test_helpers::create_test_kernel(0.into(), 0, KernelFeatures::create_burn());
@@ -548,6 +742,35 @@ mod test {
         assert!(check_total_burned(&body2).is_err());
     }
 
+    #[tokio::test]
+    async fn check_burned_only_allows_claim_public_keys_on_burned_outputs() {
+        let mut kernel = test_helpers::create_test_kernel(0.into(), 0, KernelFeatures::create_burn());
+        let claim_public_key = PublicKey::from_secret_key(&PrivateKey::from(42u64));
+
+        let key_manager = create_memory_db_key_manager();
+        let (output, _, _) = test_helpers::create_utxo(
+            100.into(),
+            &key_manager,
+            &OutputFeatures::create_burn_confidential_output(claim_public_key.clone()),
+            &script!(Nop),
+            &Covenant::default(),
+            0.into(),
+        )
+        .await;
+        assert_eq!(output.features.burn_claim_public_key(), Some(&claim_public_key));
+        kernel.burn_commitment = Some(output.commitment.clone());
+        let body = AggregateBody::new(Vec::new(), vec![output.clone()], vec![kernel]);
+        check_total_burned(&body).unwrap();
+
+        let mut unburned_output = output;
+        unburned_output.features.output_type = OutputType::Standard;
+        let body = AggregateBody::new(Vec::new(), vec![unburned_output], vec![]);
+        assert!(matches!(
+            check_total_burned(&body),
+            Err(ValidationError::InvalidBurnError(_))
+        ));
+    }
+
     mod transaction_ordering {
         use super::*;
 
@@ -20,6 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::collections::HashMap;
+
 use tari_common_types::chain_metadata::ChainMetadata;
 use tari_utilities::hex::Hex;
 
@@ -28,7 +30,7 @@ use crate::{
     blocks::{Block, ChainBlock},
     chain_storage::{self, BlockchainBackend},
     consensus::ConsensusManager,
-    transactions::CryptoFactories,
+    transactions::{aggregated_body::AggregateBody, CryptoFactories},
     validation::{
         aggregate_body::AggregateBodyChainLinkedValidator,
         helpers::check_mmr_roots,
@@ -62,6 +64,66 @@ impl BlockBodyFullValidator {
         backend: &B,
         block: &Block,
         metadata_option: Option<&ChainMetadata>,
+    ) -> Result<Block, ValidationError> {
+        self.validate_with(backend, block, metadata_option, true)
+    }
+
+    /// Validates the internal consistency of a run of consecutive blocks that extend the chain tip concurrently. The
+    /// inputs of each block are resolved from the database, or from the outputs of the blocks before it in the run.
+    pub fn validate_internal_consistency_batch<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        blocks: &[Block],
+    ) -> Vec<Result<(), ValidationError>> {
+        let mut pending_outputs = HashMap::new();
+        let mut resolve_errors = Vec::with_capacity(blocks.len());
+        let mut resolved_blocks = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            match self
+                .aggregate_body_chain_validator
+                .resolve_inputs(&block.body, backend, &pending_outputs)
+            {
+                Ok(inputs) => {
+                    // UNCHECKED: sorting is checked by the internal consistency validator
+                    let body = AggregateBody::new_sorted_unchecked(
+                        inputs,
+                        block.body.outputs().to_vec(),
+                        block.body.kernels().to_vec(),
+                    );
+                    resolved_blocks.push(Block::new(block.header.clone(), body));
+                    resolve_errors.push(None);
+                },
+                Err(err) => {
+                    resolved_blocks.push(block.clone());
+                    resolve_errors.push(Some(err));
+                },
+            }
+            pending_outputs.extend(
+                block
+                    .body
+                    .outputs()
+                    .iter()
+                    .map(|output| (output.hash(), output.clone())),
+            );
+        }
+
+        self.block_internal_validator
+            .validate_batch(&resolved_blocks)
+            .into_iter()
+            .zip(resolve_errors)
+            .map(|(result, resolve_error)| match resolve_error {
+                Some(err) => Err(err),
+                None => result,
+            })
+            .collect()
+    }
+
+    fn validate_with<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        metadata_option: Option<&ChainMetadata>,
+        check_internal_consistency: bool,
     ) -> Result<Block, ValidationError> {
         if let Some(metadata) = metadata_option {
             validate_block_metadata(block, metadata)?;
@@ -76,7 +138,9 @@ impl BlockBodyFullValidator {
         let block = Block::new(block.header.clone(), body);
 
         // validate the internal consistency of the block body
-        self.block_internal_validator.validate(&block)?;
+        if check_internal_consistency {
+            self.block_internal_validator.validate(&block)?;
+        }
 
         // validate the merkle mountain range roots
         let mmr_roots = chain_storage::calculate_mmr_roots(backend, &self.consensus_manager, &block)?;
@@ -102,6 +166,14 @@ impl<B: BlockchainBackend> BlockBodyValidator<B> for BlockBodyFullValidator {
     fn validate_body(&self, backend: &B, block: &Block) -> Result<Block, ValidationError> {
         self.validate(backend, block, None)
     }
+
+    fn validate_internal_consistency_batch(&self, backend: &B, blocks: &[Block]) -> Vec<Result<(), ValidationError>> {
+        BlockBodyFullValidator::validate_internal_consistency_batch(self, backend, blocks)
+    }
+
+    fn validate_chain_linked_body(&self, backend: &B, block: &Block) -> Result<Block, ValidationError> {
+        self.validate_with(backend, block, None, false)
+    }
 }
 
 fn validate_block_metadata(block: &Block, metadata: &ChainMetadata) -> Result<(), ValidationError> {
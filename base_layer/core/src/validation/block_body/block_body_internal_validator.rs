@@ -20,7 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use log::warn;
+use log::{debug, warn};
+use rayon::prelude::*;
 use tari_utilities::hex::Hex;
 
 use crate::{
@@ -29,8 +30,11 @@ use crate::{
     transactions::{aggregated_body::AggregateBody, CryptoFactories},
     validation::{
         aggregate_body::AggregateBodyInternalConsistencyValidator,
+        block_body::{StagePosition, ValidatorPipeline},
+        helpers::check_range_proofs_in_parallel,
         InternalConsistencyValidator,
         ValidationError,
+        ValidationReport,
     },
 };
 
@@ -41,6 +45,8 @@ pub struct BlockBodyInternalConsistencyValidator {
     consensus_manager: ConsensusManager,
     factories: CryptoFactories,
     aggregate_body_validator: AggregateBodyInternalConsistencyValidator,
+    bypass_range_proof_verification: bool,
+    pipeline: ValidatorPipeline,
 }
 
 impl BlockBodyInternalConsistencyValidator {
@@ -58,12 +64,107 @@ impl BlockBodyInternalConsistencyValidator {
             consensus_manager,
             factories,
             aggregate_body_validator,
+            bypass_range_proof_verification,
+            pipeline: ValidatorPipeline::default(),
         }
     }
 
+    /// Run the custom stages in the given pipeline alongside the built-in checks.
+    pub fn with_pipeline(mut self, pipeline: ValidatorPipeline) -> Self {
+        self.pipeline = pipeline;
+        self
+    }
+
     pub fn validate(&self, block: &Block) -> Result<(), ValidationError> {
-        validate_block_specific_checks(block, &self.consensus_manager, &self.factories)?;
-        validate_block_aggregate_body(block, &self.aggregate_body_validator, &self.consensus_manager)?;
+        self.validate_and_record(block, &self.aggregate_body_validator, &mut ValidationReport::new())
+    }
+
+    /// Validates many blocks concurrently, returning a result for each block in the same order. This is intended for
+    /// initial sync, where many candidate blocks are available at once. The range proofs of all the blocks are
+    /// verified together in a single parallel batch once every other check has passed, so a block that fails more
+    /// than one check may report a different error to [validate](Self::validate).
+    pub fn validate_batch(&self, blocks: &[Block]) -> Vec<Result<(), ValidationError>> {
+        let body_validator = AggregateBodyInternalConsistencyValidator::new(
+            true,
+            self.consensus_manager.clone(),
+            self.factories.clone(),
+        );
+        let mut results = blocks
+            .par_iter()
+            .map(|block| self.validate_and_record(block, &body_validator, &mut ValidationReport::new()))
+            .collect::<Vec<_>>();
+        if self.bypass_range_proof_verification {
+            return results;
+        }
+
+        // (block index, output index within the block) for every output in the batch
+        let mut owners = Vec::new();
+        let mut outputs = Vec::new();
+        for (block_index, block) in blocks.iter().enumerate() {
+            if results[block_index].is_err() {
+                continue;
+            }
+            for (output_index, output) in block.body.outputs().iter().enumerate() {
+                owners.push((block_index, output_index));
+                outputs.push(output);
+            }
+        }
+        if let Err(ValidationError::InvalidRangeProofs { output_indices }) =
+            check_range_proofs_in_parallel(&self.factories.range_proof, &outputs)
+        {
+            for index in output_indices {
+                let (block_index, output_index) = owners[index];
+                match &mut results[block_index] {
+                    Err(ValidationError::InvalidRangeProofs {
+                        output_indices: indices,
+                    }) => indices.push(output_index),
+                    result => {
+                        *result = Err(ValidationError::InvalidRangeProofs {
+                            output_indices: vec![output_index],
+                        })
+                    },
+                }
+            }
+        }
+        results
+    }
+
+    /// Validates the block, returning a report of each check that was run, how long it took and the input, output or
+    /// kernel that caused it to fail.
+    pub fn validate_with_report(&self, block: &Block) -> ValidationReport {
+        let mut report = ValidationReport::for_block(block.hash());
+        if let Err(err) = self.validate_and_record(block, &self.aggregate_body_validator, &mut report) {
+            debug!(
+                target: LOG_TARGET,
+                "Block {} failed validation: {}",
+                block.hash().to_hex(),
+                err
+            );
+        }
+        report
+    }
+
+    fn validate_and_record(
+        &self,
+        block: &Block,
+        aggregate_body_validator: &AggregateBodyInternalConsistencyValidator,
+        report: &mut ValidationReport,
+    ) -> Result<(), ValidationError> {
+        let constants = self.consensus_manager.consensus_constants(block.header.height);
+        report.run_check("block_specific", || {
+            validate_block_specific_checks(block, &self.consensus_manager, &self.factories)
+        })?;
+        if !self.pipeline.is_empty() {
+            report.run_check("custom_stages_before_body", || {
+                self.pipeline.run(StagePosition::BeforeBody, block, constants)
+            })?;
+        }
+        validate_block_aggregate_body(block, aggregate_body_validator, &self.consensus_manager, report)?;
+        if !self.pipeline.is_empty() {
+            report.run_check("custom_stages_after_body", || {
+                self.pipeline.run(StagePosition::AfterBody, block, constants)
+            })?;
+        }
 
         Ok(())
     }
@@ -73,6 +174,14 @@ impl InternalConsistencyValidator for BlockBodyInternalConsistencyValidator {
     fn validate_internal_consistency(&self, block: &Block) -> Result<(), ValidationError> {
         self.validate(block)
     }
+
+    fn validate_with_report(&self, block: &Block) -> ValidationReport {
+        BlockBodyInternalConsistencyValidator::validate_with_report(self, block)
+    }
+
+    fn validate_batch(&self, blocks: &[Block]) -> Vec<Result<(), ValidationError>> {
+        BlockBodyInternalConsistencyValidator::validate_batch(self, blocks)
+    }
 }
 
 fn validate_block_specific_checks(
@@ -104,6 +213,7 @@ fn validate_block_aggregate_body(
     block: &Block,
     validator: &AggregateBodyInternalConsistencyValidator,
     consensus_manager: &ConsensusManager,
+    report: &mut ValidationReport,
 ) -> Result<(), ValidationError> {
     let offset = &block.header.total_kernel_offset;
     let script_offset = &block.header.total_script_offset;
@@ -119,13 +229,14 @@ fn validate_block_aggregate_body(
             ValidationError::CoinbaseExceedsMaxLimit
         })?;
     validator
-        .validate(
+        .validate_with_report(
             &block.body,
             offset,
             script_offset,
             Some(total_coinbase),
             Some(block.header.prev_hash),
             block.header.height,
+            report,
         )
         .map_err(|err| {
             warn!(
@@ -454,6 +454,68 @@ async fn it_rejects_zero_conf_double_spends() {
     assert!(matches!(err, ValidationError::UnsortedOrDuplicateInput));
 }
 
+#[tokio::test]
+async fn it_validates_a_batch_of_synced_blocks_that_spend_each_other() {
+    let (mut blockchain, validator) = setup(false).await;
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+
+    let schema = txn_schema!(from: vec![coinbase_a], to: vec![50 * T]);
+    let (txs, outputs) = schema_to_transaction(&[schema], &blockchain.km).await;
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (block_b, _) = blockchain
+        .add_next_tip(block_spec!("B", transactions: txs))
+        .await
+        .unwrap();
+    let schema = txn_schema!(from: outputs, to: vec![20 * T]);
+    let (txs, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (block_c, _) = blockchain
+        .add_next_tip(block_spec!("C", transactions: txs))
+        .await
+        .unwrap();
+
+    // Sync peers send blocks with compact inputs, on top of a tip that does not include any of them yet
+    blockchain.db().rewind_to_height(1).unwrap();
+    let blocks = [block_b, block_c]
+        .iter()
+        .map(|block| {
+            let mut block = block.block().clone();
+            let inputs = block.body.inputs().iter().map(|input| input.to_compact()).collect();
+            block.body =
+                AggregateBody::new_sorted_unchecked(inputs, block.body.outputs().clone(), block.body.kernels().clone());
+            block
+        })
+        .collect::<Vec<_>>();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    // The input of C spends an output of B, which is only available from the batch
+    let results = BlockBodyValidator::validate_internal_consistency_batch(&validator, &*txn, &blocks);
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(Result::is_ok));
+    let results = BlockBodyValidator::validate_internal_consistency_batch(&validator, &*txn, &blocks[1..]);
+    assert!(results[0].is_err());
+
+    let mut invalid = blocks.clone();
+    let mut outputs = invalid[1].body.outputs().clone();
+    let index = outputs
+        .iter()
+        .position(|o| o.features.range_proof_type == RangeProofType::BulletProofPlus)
+        .unwrap();
+    outputs[index].minimum_value_promise = 1_000_000 * T;
+    invalid[1].body = AggregateBody::new_sorted_unchecked(
+        invalid[1].body.inputs().clone(),
+        outputs,
+        invalid[1].body.kernels().clone(),
+    );
+    let results = BlockBodyValidator::validate_internal_consistency_batch(&validator, &*txn, &invalid);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+
+    // The blocks that passed are then validated against the chain one at a time
+    let block = BlockBodyValidator::validate_chain_linked_body(&validator, &*txn, &blocks[0]).unwrap();
+    assert!(block.body.inputs().iter().all(|input| !input.is_compact()));
+}
+
 mod body_only {
     use super::*;
     use crate::validation::block_body::BlockBodyFullValidator;
@@ -494,7 +556,10 @@ mod orphan_validator {
     use crate::{
         transactions::transaction_components::{OutputType, RangeProofType},
         txn_schema,
-        validation::block_body::BlockBodyInternalConsistencyValidator,
+        validation::{
+            block_body::{BlockBodyInternalConsistencyValidator, ValidatorPipeline, ValidatorStage},
+            ValidationItemKind,
+        },
     };
 
     #[tokio::test]
@@ -663,4 +728,165 @@ mod orphan_validator {
         unpack_enum!(ValidationError::OutputTypeNotMatchedToRangeProofType { output_type } = err);
         assert!(output_type == OutputType::Standard || output_type == OutputType::Coinbase);
     }
+
+    #[tokio::test]
+    async fn it_runs_custom_pipeline_stages() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut blockchain = TestBlockchain::create(rules.clone()).await;
+        let (_, coinbase) = blockchain.append(block_spec!("1", parent: "GB")).await.unwrap();
+
+        let schema = txn_schema!(from: vec![coinbase.clone()], to: vec![201 * T]);
+        let (tx, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+        let transactions = tx.into_iter().map(|b| Arc::try_unwrap(b).unwrap()).collect::<Vec<_>>();
+        let (unmined, _) = blockchain
+            .create_unmined_block(block_spec!("2", parent: "1", transactions: transactions))
+            .await;
+
+        let stage: ValidatorStage = Box::new(|block, _| {
+            if block.body.outputs().len() > 2 {
+                return Err(ValidationError::ConsensusError("too many outputs".to_string()));
+            }
+            Ok(())
+        });
+        let pipeline = ValidatorPipeline::new().with_stage(stage);
+        let validator = BlockBodyInternalConsistencyValidator::new(rules.clone(), false, CryptoFactories::default())
+            .with_pipeline(pipeline);
+        let err = validator.validate(&unmined).unwrap_err();
+        unpack_enum!(ValidationError::ConsensusError(reason) = err);
+        assert_eq!(reason, "too many outputs");
+
+        let pipeline = ValidatorPipeline::new()
+            .with_stage(|_, _| Ok(()))
+            .with_final_stage(|block, constants| {
+                assert!(block.header.height >= constants.effective_from_height());
+                Ok(())
+            });
+        assert_eq!(pipeline.len(), 2);
+        let validator = BlockBodyInternalConsistencyValidator::new(rules, false, CryptoFactories::default())
+            .with_pipeline(pipeline);
+        assert!(validator.validate(&unmined).is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_runs_builtin_checks_before_custom_stages() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut blockchain = TestBlockchain::create(rules.clone()).await;
+        blockchain.append(block_spec!("1", parent: "GB")).await.unwrap();
+        let (mut block, _) = blockchain.create_unmined_block(block_spec!("2", parent: "1")).await;
+        block.header.height = 0;
+
+        let pipeline = ValidatorPipeline::new().with_stage(|_, _| {
+            panic!("custom stage must not run when a cheaper built-in check fails");
+        });
+        let validator = BlockBodyInternalConsistencyValidator::new(rules, false, CryptoFactories::default())
+            .with_pipeline(pipeline);
+        let err = validator.validate(&block).unwrap_err();
+        assert!(matches!(err, ValidationError::ValidatingGenesis));
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_checks_that_were_run() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut blockchain = TestBlockchain::create(rules.clone()).await;
+        let validator = BlockBodyInternalConsistencyValidator::new(rules, false, CryptoFactories::default());
+        let (_, coinbase) = blockchain.append(block_spec!("1", parent: "GB")).await.unwrap();
+
+        let schema = txn_schema!(from: vec![coinbase.clone()], to: vec![201 * T]);
+        let (tx, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+        let transactions = tx.into_iter().map(|b| Arc::try_unwrap(b).unwrap()).collect::<Vec<_>>();
+        let (mut unmined, _) = blockchain
+            .create_unmined_block(block_spec!("2", parent: "1", transactions: transactions))
+            .await;
+
+        let report = validator.validate_with_report(&unmined);
+        assert!(report.is_valid());
+        assert_eq!(report.block_hash(), Some(&unmined.hash()));
+        assert!(report.checks().iter().any(|c| c.name == "range_proofs"));
+        assert!(report.failed_check().is_none());
+
+        let index = unmined
+            .body
+            .outputs()
+            .iter()
+            .position(|o| o.features.range_proof_type == RangeProofType::BulletProofPlus)
+            .unwrap();
+        let mut outputs = unmined.body.outputs().clone();
+        outputs[index].minimum_value_promise = 1_000_000 * T;
+        unmined.body =
+            AggregateBody::new_sorted_unchecked(unmined.body.inputs().clone(), outputs, unmined.body.kernels().clone());
+        let report = validator.validate_with_report(&unmined);
+        assert!(!report.is_valid());
+        assert_eq!(report.failed_check().unwrap().name, "range_proofs");
+        let item = report.failed_item().unwrap();
+        assert_eq!(item.kind, ValidationItemKind::Output);
+        assert_eq!(item.index, index);
+        assert_eq!(item.hash, unmined.body.outputs()[index].hash());
+        assert!(validator.validate(&unmined).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_validates_a_batch_of_blocks() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut blockchain = TestBlockchain::create(rules.clone()).await;
+        let validator = BlockBodyInternalConsistencyValidator::new(rules, false, CryptoFactories::default());
+        let (_, coinbase) = blockchain.append(block_spec!("1", parent: "GB")).await.unwrap();
+
+        let schema = txn_schema!(from: vec![coinbase.clone()], to: vec![201 * T, 50 * T]);
+        let (tx, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+        let transactions = tx.into_iter().map(|b| Arc::try_unwrap(b).unwrap()).collect::<Vec<_>>();
+        let (valid, _) = blockchain
+            .create_unmined_block(block_spec!("2", parent: "1", transactions: transactions))
+            .await;
+        let (empty, _) = blockchain.create_unmined_block(block_spec!("2a", parent: "1")).await;
+
+        let index = valid
+            .body
+            .outputs()
+            .iter()
+            .position(|o| o.features.range_proof_type == RangeProofType::BulletProofPlus)
+            .unwrap();
+        let mut outputs = valid.body.outputs().clone();
+        outputs[index].minimum_value_promise = 1_000_000 * T;
+        let mut invalid = valid.clone();
+        invalid.body =
+            AggregateBody::new_sorted_unchecked(valid.body.inputs().clone(), outputs, valid.body.kernels().clone());
+        let mut genesis = empty.clone();
+        genesis.header.height = 0;
+
+        let results = validator.validate_batch(&[valid, invalid, empty, genesis]);
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        unpack_enum!(ValidationError::InvalidRangeProofs { output_indices } = results[1].as_ref().unwrap_err());
+        assert_eq!(output_indices, &vec![index]);
+        assert!(results[2].is_ok());
+        assert!(matches!(results[3], Err(ValidationError::ValidatingGenesis)));
+    }
 }
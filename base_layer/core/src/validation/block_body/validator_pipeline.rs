@@ -0,0 +1,112 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, sync::Arc};
+
+use crate::{blocks::Block, consensus::ConsensusConstants, validation::ValidationError};
+
+/// A single custom check that can be inserted into the orphan (internal consistency) validation of a block.
+pub type ValidatorStage = Box<dyn Fn(&Block, &ConsensusConstants) -> Result<(), ValidationError> + Send + Sync>;
+
+type SharedStage = Arc<dyn Fn(&Block, &ConsensusConstants) -> Result<(), ValidationError> + Send + Sync>;
+
+/// The point in the built-in block validation at which a custom stage is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StagePosition {
+    /// After the cheap block-specific checks (coinbase and coinbase features), but before the expensive aggregate
+    /// body checks (signatures, range proofs, scripts).
+    BeforeBody,
+    /// After all built-in checks have passed.
+    AfterBody,
+}
+
+/// A set of custom validation stages that is run by the
+/// [BlockBodyInternalConsistencyValidator](super::BlockBodyInternalConsistencyValidator) in addition to the
+/// built-in checks. Stages are run in the order they were added within their [StagePosition], so callers should add
+/// cheap checks before expensive ones.
+#[derive(Clone, Default)]
+pub struct ValidatorPipeline {
+    before_body: Vec<SharedStage>,
+    after_body: Vec<SharedStage>,
+}
+
+impl ValidatorPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a stage that is run before the expensive aggregate body checks. Accepts a [ValidatorStage] or any closure
+    /// with the same signature.
+    pub fn with_stage<F>(self, stage: F) -> Self
+    where F: Fn(&Block, &ConsensusConstants) -> Result<(), ValidationError> + Send + Sync + 'static {
+        self.with_stage_at(StagePosition::BeforeBody, stage)
+    }
+
+    /// Add a stage that is run once all the built-in checks have passed.
+    pub fn with_final_stage<F>(self, stage: F) -> Self
+    where F: Fn(&Block, &ConsensusConstants) -> Result<(), ValidationError> + Send + Sync + 'static {
+        self.with_stage_at(StagePosition::AfterBody, stage)
+    }
+
+    pub fn with_stage_at<F>(mut self, position: StagePosition, stage: F) -> Self
+    where F: Fn(&Block, &ConsensusConstants) -> Result<(), ValidationError> + Send + Sync + 'static {
+        match position {
+            StagePosition::BeforeBody => self.before_body.push(Arc::new(stage)),
+            StagePosition::AfterBody => self.after_body.push(Arc::new(stage)),
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.before_body.is_empty() && self.after_body.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.before_body.len() + self.after_body.len()
+    }
+
+    /// Run all the stages at the given position, returning the first error encountered.
+    pub fn run(
+        &self,
+        position: StagePosition,
+        block: &Block,
+        constants: &ConsensusConstants,
+    ) -> Result<(), ValidationError> {
+        let stages = match position {
+            StagePosition::BeforeBody => &self.before_body,
+            StagePosition::AfterBody => &self.after_body,
+        };
+        for stage in stages {
+            stage(block, constants)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ValidatorPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidatorPipeline")
+            .field("before_body", &self.before_body.len())
+            .field("after_body", &self.after_body.len())
+            .finish()
+    }
+}
@@ -27,7 +27,7 @@ use tari_common_types::types::{Commitment, PrivateKey};
 use tari_crypto::commitment::HomomorphicCommitmentFactory;
 
 use crate::{
-    chain_storage::BlockchainBackend,
+    chain_storage::{BlockchainBackend, ChainStorageError},
     consensus::ConsensusManager,
     transactions::{tari_amount::MicroMinotari, CryptoFactories},
     validation::{FinalHorizonStateValidation, ValidationError},
@@ -50,6 +50,20 @@ impl<B: BlockchainBackend> ChainBalanceValidator<B> {
             _phantom: Default::default(),
         }
     }
+
+    /// Validate that the chain balances at a given height using the balance sums that are cached for that height, so
+    /// that the kernels and outputs of the chain do not have to be summed. An error is returned if the sums are not
+    /// cached for the height.
+    pub fn validate_cached(&self, backend: &B, height: u64) -> Result<(), ValidationError> {
+        let sums = backend
+            .fetch_block_balance_sums(height)?
+            .ok_or_else(|| ChainStorageError::ValueNotFound {
+                entity: "BlockBalanceSums",
+                field: "height",
+                value: height.to_string(),
+            })?;
+        self.validate(backend, height, sums.utxo_sum(), sums.kernel_sum(), sums.burned_sum())
+    }
 }
 
 impl<B: BlockchainBackend> FinalHorizonStateValidation<B> for ChainBalanceValidator<B> {
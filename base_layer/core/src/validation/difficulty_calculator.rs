@@ -24,7 +24,7 @@ use crate::{
     blocks::BlockHeader,
     chain_storage::{fetch_target_difficulty_for_next_block, BlockchainBackend},
     consensus::ConsensusManager,
-    proof_of_work::{randomx_factory::RandomXFactory, AchievedTargetDifficulty},
+    proof_of_work::{randomx_factory::RandomXFactory, AchievedTargetDifficulty, Difficulty, PowContext, PowRegistry},
     validation::{helpers::check_target_difficulty, ValidationError},
 };
 
@@ -32,11 +32,32 @@ use crate::{
 pub struct DifficultyCalculator {
     pub rules: ConsensusManager,
     pub randomx_factory: RandomXFactory,
+    pub pow_registry: PowRegistry,
 }
 
 impl DifficultyCalculator {
     pub fn new(rules: ConsensusManager, randomx_factory: RandomXFactory) -> Self {
-        Self { rules, randomx_factory }
+        let pow_registry = PowRegistry::from_consensus(&rules);
+        Self {
+            rules,
+            randomx_factory,
+            pow_registry,
+        }
+    }
+
+    /// Use the given registry of PoW algorithms instead of the algorithms enabled by the consensus rules
+    pub fn with_pow_registry(mut self, pow_registry: PowRegistry) -> Self {
+        self.pow_registry = pow_registry;
+        self
+    }
+
+    /// The context that is passed to the PoW algorithm hooks
+    pub fn pow_context(&self) -> PowContext<'_> {
+        PowContext {
+            consensus: &self.rules,
+            randomx_factory: &self.randomx_factory,
+            genesis_hash: *self.rules.get_genesis_block().hash(),
+        }
     }
 
     pub fn check_achieved_and_target_difficulty<B: BlockchainBackend>(
@@ -44,17 +65,25 @@ impl DifficultyCalculator {
         db: &B,
         block_header: &BlockHeader,
     ) -> Result<AchievedTargetDifficulty, ValidationError> {
+        let target = self.calculate_target_difficulty(db, block_header)?;
+        let achieved_target = check_target_difficulty(block_header, target, &self.pow_registry, &self.pow_context())?;
+
+        Ok(achieved_target)
+    }
+
+    /// Calculates the target difficulty for the given header from the chain it builds on, without checking the
+    /// achieved difficulty of the header.
+    pub fn calculate_target_difficulty<B: BlockchainBackend>(
+        &self,
+        db: &B,
+        block_header: &BlockHeader,
+    ) -> Result<Difficulty, ValidationError> {
         let difficulty_window =
             fetch_target_difficulty_for_next_block(db, &self.rules, block_header.pow_algo(), &block_header.prev_hash)?;
         let constants = self.rules.consensus_constants(block_header.height);
-        let target = difficulty_window.calculate(
+        Ok(difficulty_window.calculate(
             constants.min_pow_difficulty(block_header.pow.pow_algo),
             constants.max_pow_difficulty(block_header.pow.pow_algo),
-        );
-        let gen_hash = *self.rules.get_genesis_block().hash();
-        let achieved_target =
-            check_target_difficulty(block_header, target, &self.randomx_factory, &gen_hash, &self.rules)?;
-
-        Ok(achieved_target)
+        ))
     }
 }
@@ -32,6 +32,7 @@ use crate::{
     transactions::{
         tari_amount::MicroMinotari,
         transaction_components::{OutputType, RangeProofType, TransactionError},
+        weight::WeightBreakdown,
     },
 };
 
@@ -45,8 +46,12 @@ pub enum ValidationError {
     BlockError(#[from] BlockValidationError),
     #[error("Contains kernels or inputs that are not yet spendable")]
     MaturityError,
-    #[error("The block weight ({actual_weight}) is above the maximum ({max_weight})")]
-    BlockTooLarge { actual_weight: u64, max_weight: u64 },
+    #[error("The block weight ({actual_weight}) is above the maximum ({max_weight}) ({breakdown})")]
+    BlockTooLarge {
+        actual_weight: u64,
+        max_weight: u64,
+        breakdown: WeightBreakdown,
+    },
     #[error("Contains {} unknown inputs", .0.len())]
     UnknownInputs(Vec<HashOutput>),
     #[error("Contains an unknown input")]
@@ -90,6 +95,12 @@ pub enum ValidationError {
     IncorrectPreviousHash { expected: String, block_hash: String },
     #[error("Bad block with hash {hash} found")]
     BadBlockFound { hash: String, reason: String },
+    #[error("Header at checkpoint height {height} has hash {actual}, but the checkpoint hash is {expected}")]
+    CheckpointMismatch {
+        height: u64,
+        expected: String,
+        actual: String,
+    },
     #[error("Script exceeded maximum script size, expected less than {max_script_size} but was {actual_script_size}")]
     TariScriptExceedsMaxSize {
         max_script_size: usize,
@@ -117,6 +128,8 @@ pub enum ValidationError {
     ValidatorNodeRegistrationMinLockHeight { min: u64, actual: u64 },
     #[error("Validator node registration signature failed verification")]
     InvalidValidatorNodeSignature,
+    #[error("Invalid code template registration: {0}")]
+    InvalidTemplateRegistration(String),
     #[error(
         "An unexpected number of timestamps were provided to the header validator. THIS IS A BUG. Expected \
          {expected}, got {actual}"
@@ -126,6 +139,12 @@ pub enum ValidationError {
     DifficultyError(#[from] DifficultyError),
     #[error("Covenant too large. Max size: {max_size}, Actual size: {actual_size}")]
     CovenantTooLarge { max_size: usize, actual_size: usize },
+    #[error("Payment reference too large. Max size: {max_size}, Actual size: {actual_size}")]
+    PaymentReferenceTooLarge { max_size: usize, actual_size: usize },
+    #[error("Memo too large. Max size: {max_size}, Actual size: {actual_size}")]
+    MemoTooLarge { max_size: usize, actual_size: usize },
+    #[error("Range proof verification failed for the outputs at indexes {output_indices:?}")]
+    InvalidRangeProofs { output_indices: Vec<usize> },
 }
 
 // ChainStorageError has a ValidationError variant, so to prevent a cyclic dependency we use a string representation in
@@ -161,6 +180,7 @@ impl ValidationError {
             err @ ValidationError::IncorrectHeight { .. } |
             err @ ValidationError::IncorrectPreviousHash { .. } |
             err @ ValidationError::BadBlockFound { .. } |
+            err @ ValidationError::CheckpointMismatch { .. } |
             err @ ValidationError::TariScriptExceedsMaxSize { .. } |
             err @ ValidationError::ConsensusError(_) |
             err @ ValidationError::DuplicateKernelError(_) |
@@ -173,9 +193,13 @@ impl ValidationError {
             err @ ValidationError::ValidatorNodeRegistrationMinDepositAmount { .. } |
             err @ ValidationError::ValidatorNodeRegistrationMinLockHeight { .. } |
             err @ ValidationError::InvalidValidatorNodeSignature |
+            err @ ValidationError::InvalidTemplateRegistration(_) |
             err @ ValidationError::DifficultyError(_) |
             err @ ValidationError::CoinbaseExceedsMaxLimit |
-            err @ ValidationError::CovenantTooLarge { .. } => Some(BanReason {
+            err @ ValidationError::CovenantTooLarge { .. } |
+            err @ ValidationError::PaymentReferenceTooLarge { .. } |
+            err @ ValidationError::MemoTooLarge { .. } |
+            err @ ValidationError::InvalidRangeProofs { .. } => Some(BanReason {
                 reason: err.to_string(),
                 ban_duration: BanPeriod::Long,
             }),
@@ -0,0 +1,126 @@
+// Copyright 2024, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::FixedHash;
+use tari_utilities::hex::Hex;
+
+use crate::validation::ValidationError;
+
+/// A trusted header hash at a fixed height
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeaderCheckpoint {
+    pub height: u64,
+    #[serde(with = "tari_utilities::serde::hex")]
+    pub hash: FixedHash,
+}
+
+/// A set of trusted header hashes at fixed heights, supplied by the node config. A header at a checkpoint height must
+/// have the checkpoint hash, and because each header commits to its predecessor, every header below a checkpoint that
+/// leads to it is trusted without checking its proof of work.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderCheckpoints {
+    checkpoints: Arc<BTreeMap<u64, FixedHash>>,
+}
+
+impl HeaderCheckpoints {
+    pub fn new<I: IntoIterator<Item = HeaderCheckpoint>>(checkpoints: I) -> Self {
+        Self {
+            checkpoints: Arc::new(checkpoints.into_iter().map(|c| (c.height, c.hash)).collect()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+
+    pub fn get(&self, height: u64) -> Option<&FixedHash> {
+        self.checkpoints.get(&height)
+    }
+
+    /// The height of the highest checkpoint, if any
+    pub fn last_checkpoint_height(&self) -> Option<u64> {
+        self.checkpoints.keys().next_back().copied()
+    }
+
+    /// Returns true if the height is strictly below the last checkpoint, i.e. a header at this height does not need its
+    /// proof of work to be checked.
+    pub fn is_below_last_checkpoint(&self, height: u64) -> bool {
+        self.last_checkpoint_height().map_or(false, |last| height < last)
+    }
+
+    /// Checks that the header hash matches the checkpoint at the given height, if there is one.
+    pub fn check(&self, height: u64, hash: &FixedHash) -> Result<(), ValidationError> {
+        match self.checkpoints.get(&height) {
+            Some(expected) if expected != hash => Err(ValidationError::CheckpointMismatch {
+                height,
+                expected: expected.to_hex(),
+                actual: hash.to_hex(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_test_utils::unpack_enum;
+
+    use super::*;
+
+    #[test]
+    fn it_checks_headers_at_checkpoint_heights() {
+        let checkpoints = HeaderCheckpoints::new([
+            HeaderCheckpoint {
+                height: 10,
+                hash: FixedHash::from([1u8; 32]),
+            },
+            HeaderCheckpoint {
+                height: 20,
+                hash: FixedHash::from([2u8; 32]),
+            },
+        ]);
+        assert_eq!(checkpoints.last_checkpoint_height(), Some(20));
+        assert!(checkpoints.is_below_last_checkpoint(19));
+        assert!(!checkpoints.is_below_last_checkpoint(20));
+
+        checkpoints.check(10, &FixedHash::from([1u8; 32])).unwrap();
+        checkpoints.check(11, &FixedHash::from([9u8; 32])).unwrap();
+        let err = checkpoints.check(20, &FixedHash::from([9u8; 32])).unwrap_err();
+        unpack_enum!(ValidationError::CheckpointMismatch { height, .. } = err);
+        assert_eq!(height, 20);
+    }
+
+    #[test]
+    fn no_heights_are_below_an_empty_set_of_checkpoints() {
+        let checkpoints = HeaderCheckpoints::new([]);
+        assert!(checkpoints.is_empty());
+        assert_eq!(checkpoints.last_checkpoint_height(), None);
+        assert!(!checkpoints.is_below_last_checkpoint(0));
+    }
+}
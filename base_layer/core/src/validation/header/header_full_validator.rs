@@ -30,7 +30,7 @@ use crate::{
     blocks::{BlockHeader, BlockHeaderValidationError},
     chain_storage::BlockchainBackend,
     consensus::{ConsensusConstants, ConsensusManager},
-    proof_of_work::{monero_rx::MoneroPowData, AchievedTargetDifficulty, Difficulty, PowAlgorithm, PowError},
+    proof_of_work::{AchievedTargetDifficulty, Difficulty, PowError},
     validation::{
         helpers::{check_header_timestamp_greater_than_median, check_target_difficulty},
         DifficultyCalculator,
@@ -54,17 +54,37 @@ impl HeaderFullValidator {
             difficulty_calculator,
         }
     }
-}
 
-impl<B: BlockchainBackend> HeaderChainLinkedValidator<B> for HeaderFullValidator {
-    fn validate(
+    /// Validates everything but the proof of work of a header, which is recorded as having achieved exactly its target
+    /// difficulty. This must only be used for headers that are later tied to a trusted checkpoint hash through their
+    /// previous hashes.
+    pub fn validate_without_pow<B: BlockchainBackend>(
         &self,
         db: &B,
         header: &BlockHeader,
         prev_header: &BlockHeader,
         prev_timestamps: &[EpochTime],
-        target_difficulty: Option<Difficulty>,
+        target_difficulty: Difficulty,
     ) -> Result<AchievedTargetDifficulty, ValidationError> {
+        self.check_chain_link(db, header, prev_header, prev_timestamps)?;
+        AchievedTargetDifficulty::try_construct(header.pow_algo(), target_difficulty, target_difficulty).ok_or(
+            ValidationError::BlockHeaderError(BlockHeaderValidationError::ProofOfWorkError(
+                PowError::AchievedDifficultyTooLow {
+                    target: target_difficulty,
+                    achieved: target_difficulty,
+                },
+            )),
+        )
+    }
+
+    /// The checks that do not involve the proof of work of the header
+    fn check_chain_link<B: BlockchainBackend>(
+        &self,
+        db: &B,
+        header: &BlockHeader,
+        prev_header: &BlockHeader,
+        prev_timestamps: &[EpochTime],
+    ) -> Result<(), ValidationError> {
         let constants = self.rules.consensus_constants(header.height);
 
         check_not_bad_block(db, header.hash())?;
@@ -75,18 +95,30 @@ impl<B: BlockchainBackend> HeaderChainLinkedValidator<B> for HeaderFullValidator
         sanity_check_timestamp_count(header, prev_timestamps, constants)?;
         check_header_timestamp_greater_than_median(header, prev_timestamps)?;
 
-        check_timestamp_ftl(header, &self.rules)?;
-        check_pow_data(header, &self.rules, db)?;
-        let gen_hash = *self.rules.get_genesis_block().hash();
+        check_timestamp_ftl(header, &self.rules)
+    }
+}
+
+impl<B: BlockchainBackend> HeaderChainLinkedValidator<B> for HeaderFullValidator {
+    fn validate(
+        &self,
+        db: &B,
+        header: &BlockHeader,
+        prev_header: &BlockHeader,
+        prev_timestamps: &[EpochTime],
+        target_difficulty: Option<Difficulty>,
+    ) -> Result<AchievedTargetDifficulty, ValidationError> {
+        self.check_chain_link(db, header, prev_header, prev_timestamps)?;
+
+        let pow_context = self.difficulty_calculator.pow_context();
+        let pow_algo = self
+            .difficulty_calculator
+            .pow_registry
+            .get(header.pow_algo(), header.height)?;
+        pow_algo.validate_pow_data(header, &pow_context, db)?;
 
         let achieved_target = if let Some(target) = target_difficulty {
-            check_target_difficulty(
-                header,
-                target,
-                &self.difficulty_calculator.randomx_factory,
-                &gen_hash,
-                &self.rules,
-            )?
+            check_target_difficulty(header, target, &self.difficulty_calculator.pow_registry, &pow_context)?
         } else {
             self.difficulty_calculator
                 .check_achieved_and_target_difficulty(db, header)?
@@ -183,40 +215,3 @@ fn check_not_bad_block<B: BlockchainBackend>(db: &B, hash: FixedHash) -> Result<
     }
     Ok(())
 }
-
-/// Check the PoW data in the BlockHeader. This currently only applies to blocks merged mined with Monero.
-fn check_pow_data<B: BlockchainBackend>(
-    block_header: &BlockHeader,
-    rules: &ConsensusManager,
-    db: &B,
-) -> Result<(), ValidationError> {
-    use PowAlgorithm::{RandomX, Sha3x};
-    match block_header.pow.pow_algo {
-        RandomX => {
-            if block_header.nonce != 0 {
-                return Err(ValidationError::BlockHeaderError(
-                    BlockHeaderValidationError::InvalidNonce,
-                ));
-            }
-            let monero_data = MoneroPowData::from_header(block_header, rules)?;
-            let seed_height = db.fetch_monero_seed_first_seen_height(&monero_data.randomx_key)?;
-            if seed_height != 0 {
-                // Saturating sub: subtraction can underflow in reorgs / rewind-blockchain command
-                let seed_used_height = block_header.height.saturating_sub(seed_height);
-                if seed_used_height > rules.consensus_constants(block_header.height).max_randomx_seed_height() {
-                    return Err(ValidationError::BlockHeaderError(
-                        BlockHeaderValidationError::OldSeedHash,
-                    ));
-                }
-            }
-
-            Ok(())
-        },
-        Sha3x => {
-            if !block_header.pow.pow_data.is_empty() {
-                return Err(PowError::Sha3HeaderNonEmptyPowBytes.into());
-            }
-            Ok(())
-        },
-    }
-}
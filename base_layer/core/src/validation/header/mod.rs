@@ -20,5 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod checkpoints;
+pub use checkpoints::{HeaderCheckpoint, HeaderCheckpoints};
+
 mod header_full_validator;
 pub use header_full_validator::HeaderFullValidator;
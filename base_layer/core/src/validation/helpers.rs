@@ -20,10 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::convert::TryFrom;
+use std::{borrow::Borrow, convert::TryFrom};
 
 use log::*;
-use tari_common_types::types::FixedHash;
+use rayon::prelude::*;
+use tari_common_types::types::RangeProofService;
 use tari_crypto::tari_utilities::{epoch_time::EpochTime, hex::Hex};
 use tari_script::TariScript;
 
@@ -31,23 +32,24 @@ use crate::{
     blocks::{BlockHeader, BlockHeaderValidationError, BlockValidationError},
     borsh::SerializedSize,
     chain_storage::{BlockchainBackend, MmrRoots, MmrTree},
-    consensus::{ConsensusConstants, ConsensusManager},
+    consensus::ConsensusConstants,
     covenants::Covenant,
-    proof_of_work::{
-        randomx_difficulty,
-        randomx_factory::RandomXFactory,
-        sha3x_difficulty,
-        AchievedTargetDifficulty,
-        Difficulty,
-        PowAlgorithm,
-        PowError,
+    proof_of_work::{AchievedTargetDifficulty, Difficulty, PowContext, PowError, PowRegistry},
+    transactions::transaction_components::{
+        transaction_output::batch_verify_range_proofs,
+        OutputType,
+        TransactionInput,
+        TransactionKernel,
+        TransactionOutput,
     },
-    transactions::transaction_components::{TransactionInput, TransactionKernel, TransactionOutput},
     validation::ValidationError,
 };
 
 pub const LOG_TARGET: &str = "c::val::helpers";
 
+/// The number of range proofs that are batch verified together on a single thread
+const RANGE_PROOF_VERIFICATION_CHUNK_SIZE: usize = 32;
+
 /// Returns the median timestamp for the provided timestamps.
 ///
 /// ## Panics
@@ -116,17 +118,16 @@ pub fn check_header_timestamp_greater_than_median(
 
     Ok(())
 }
+/// Checks that the header achieves the target difficulty, using the hooks registered for its PoW algorithm
 pub fn check_target_difficulty(
     block_header: &BlockHeader,
     target: Difficulty,
-    randomx_factory: &RandomXFactory,
-    gen_hash: &FixedHash,
-    consensus: &ConsensusManager,
+    pow_registry: &PowRegistry,
+    context: &PowContext<'_>,
 ) -> Result<AchievedTargetDifficulty, ValidationError> {
-    let achieved = match block_header.pow_algo() {
-        PowAlgorithm::RandomX => randomx_difficulty(block_header, randomx_factory, gen_hash, consensus)?,
-        PowAlgorithm::Sha3x => sha3x_difficulty(block_header)?,
-    };
+    let achieved = pow_registry
+        .get(block_header.pow_algo(), block_header.height)?
+        .achieved_difficulty(block_header, context)?;
 
     match AchievedTargetDifficulty::try_construct(block_header.pow_algo(), target, achieved) {
         Some(achieved_target) => Ok(achieved_target),
@@ -355,6 +356,18 @@ pub fn check_permitted_output_types(
         });
     }
 
+    // Template registrations are indexed by their sidechain feature, so once the template registration rules are
+    // active the output type and the feature must agree
+    let is_template_registration_type = output.features.output_type == OutputType::CodeTemplateRegistration;
+    if constants.max_template_registration_url_length().is_some() &&
+        is_template_registration_type != output.features.code_template_registration().is_some()
+    {
+        return Err(ValidationError::InvalidTemplateRegistration(format!(
+            "output type '{}' does not match the sidechain feature",
+            output.features.output_type
+        )));
+    }
+
     Ok(())
 }
 
@@ -369,6 +382,26 @@ pub fn check_covenant_length(covenant: &Covenant, max_token_len: u32) -> Result<
     Ok(())
 }
 
+/// Checks that the payment reference carried in the encrypted data of an output is not larger than the max size
+pub fn check_payment_reference_size(output: &TransactionOutput, max_size: usize) -> Result<(), ValidationError> {
+    let actual_size = output.encrypted_data.payment_reference_size();
+    if actual_size > max_size {
+        return Err(ValidationError::PaymentReferenceTooLarge { max_size, actual_size });
+    }
+
+    Ok(())
+}
+
+/// Checks that the memo carried in the encrypted data of an output is not larger than the max size
+pub fn check_memo_size(output: &TransactionOutput, max_size: usize) -> Result<(), ValidationError> {
+    let actual_size = output.encrypted_data.memo_size();
+    if actual_size > max_size {
+        return Err(ValidationError::MemoTooLarge { max_size, actual_size });
+    }
+
+    Ok(())
+}
+
 pub fn check_permitted_range_proof_types(
     constants: &ConsensusConstants,
     output: &TransactionOutput,
@@ -392,6 +425,60 @@ pub fn check_permitted_range_proof_types(
     Ok(())
 }
 
+/// Verifies the range proofs of all the given outputs. The outputs are split into chunks that are batch verified in
+/// parallel. If a chunk fails to verify, each output in the chunk is checked individually so that every offending
+/// output is reported in a single [ValidationError::InvalidRangeProofs] error.
+pub fn check_range_proofs_in_parallel<T>(
+    range_proof_service: &RangeProofService,
+    outputs: &[T],
+) -> Result<(), ValidationError>
+where
+    T: Borrow<TransactionOutput> + Sync,
+{
+    let invalid_indices = outputs
+        .par_chunks(RANGE_PROOF_VERIFICATION_CHUNK_SIZE)
+        .enumerate()
+        .flat_map_iter(|(chunk_index, chunk)| {
+            let offset = chunk_index * RANGE_PROOF_VERIFICATION_CHUNK_SIZE;
+            let batch = chunk
+                .iter()
+                .map(Borrow::<TransactionOutput>::borrow)
+                .collect::<Vec<_>>();
+            if batch_verify_range_proofs(range_proof_service, &batch).is_ok() {
+                return Vec::new();
+            }
+            let invalid = chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, output)| {
+                    let output: &TransactionOutput = (*output).borrow();
+                    output.verify_range_proof(range_proof_service).is_err()
+                })
+                .map(|(i, _)| offset + i)
+                .collect::<Vec<_>>();
+            if invalid.is_empty() {
+                // The batch failed but no single proof did, so we cannot single out an output in this chunk
+                (offset..offset + chunk.len()).collect()
+            } else {
+                invalid
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if invalid_indices.is_empty() {
+        return Ok(());
+    }
+    warn!(
+        target: LOG_TARGET,
+        "Range proof verification failed for {} of {} output(s)",
+        invalid_indices.len(),
+        outputs.len()
+    );
+    Err(ValidationError::InvalidRangeProofs {
+        output_indices: invalid_indices,
+    })
+}
+
 pub fn validate_input_version(
     consensus_constants: &ConsensusConstants,
     input: &TransactionInput,
@@ -624,4 +711,142 @@ mod test {
             unpack_enum!(TransactionError::InvalidCoinbase = err);
         }
     }
+
+    mod check_range_proofs_in_parallel {
+        use super::*;
+        use crate::transactions::{
+            key_manager::create_memory_db_key_manager,
+            tari_amount::MicroMinotari,
+            test_helpers::UtxoTestParams,
+        };
+
+        async fn create_outputs(count: usize) -> Vec<TransactionOutput> {
+            let key_manager = create_memory_db_key_manager();
+            let test_params = TestParams::new(&key_manager).await;
+            let mut outputs = Vec::with_capacity(count);
+            for i in 0..count {
+                let output = test_params
+                    .create_output(UtxoTestParams::with_value(MicroMinotari(100 + i as u64)), &key_manager)
+                    .await
+                    .unwrap();
+                outputs.push(output.to_transaction_output(&key_manager).await.unwrap());
+            }
+            outputs
+        }
+
+        #[tokio::test]
+        async fn it_accepts_valid_range_proofs() {
+            let factories = CryptoFactories::default();
+            let outputs = create_outputs(RANGE_PROOF_VERIFICATION_CHUNK_SIZE + 3).await;
+            check_range_proofs_in_parallel(&factories.range_proof, &outputs).unwrap();
+            check_range_proofs_in_parallel::<TransactionOutput>(&factories.range_proof, &[]).unwrap();
+        }
+
+        #[tokio::test]
+        async fn it_reports_all_invalid_output_indices() {
+            let factories = CryptoFactories::default();
+            let mut outputs = create_outputs(RANGE_PROOF_VERIFICATION_CHUNK_SIZE + 3).await;
+            let invalid = [1, RANGE_PROOF_VERIFICATION_CHUNK_SIZE + 1];
+            for i in invalid {
+                outputs[i].minimum_value_promise = MicroMinotari(1_000_000);
+            }
+            let err = check_range_proofs_in_parallel(&factories.range_proof, &outputs).unwrap_err();
+            unpack_enum!(ValidationError::InvalidRangeProofs { output_indices } = err);
+            assert_eq!(output_indices, invalid.to_vec());
+        }
+    }
+
+    mod validate_output_version {
+        use tari_common::configuration::Network;
+        use tari_script::{script, OpcodeVersion};
+
+        use super::*;
+        use crate::{
+            consensus::ConsensusConstantsBuilder,
+            transactions::{key_manager::create_memory_db_key_manager, test_helpers::UtxoTestParams},
+        };
+
+        #[tokio::test]
+        async fn it_rejects_opcodes_that_are_not_active() {
+            let key_manager = create_memory_db_key_manager();
+            let test_params = TestParams::new(&key_manager).await;
+            let output = test_params
+                .create_output(UtxoTestParams::default(), &key_manager)
+                .await
+                .unwrap();
+            let mut output = output.to_transaction_output(&key_manager).await.unwrap();
+            output.script = script!(HashSha256Verify(Box::default()) PushOne);
+
+            let v0 = ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_opcode_version_range(OpcodeVersion::V0..=OpcodeVersion::V0)
+                .build();
+            let err = validate_output_version(&v0, &output).unwrap_err();
+            assert!(matches!(err, ValidationError::ConsensusError(_)));
+
+            let v1 = ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_opcode_version_range(OpcodeVersion::V0..=OpcodeVersion::V1)
+                .build();
+            validate_output_version(&v1, &output).unwrap();
+        }
+    }
+
+    mod check_payment_reference_size {
+        use rand::rngs::OsRng;
+        use tari_common_types::types::{Commitment, PrivateKey};
+        use tari_crypto::keys::SecretKey;
+
+        use super::*;
+        use crate::transactions::{tari_amount::MicroMinotari, transaction_components::EncryptedData};
+
+        #[test]
+        fn it_rejects_payment_references_larger_than_the_max_size() {
+            let mut output = TransactionOutput::default();
+            check_payment_reference_size(&output, 0).unwrap();
+
+            output.encrypted_data = EncryptedData::encrypt_data_with_payment_reference(
+                &PrivateKey::random(&mut OsRng),
+                &Commitment::default(),
+                MicroMinotari(100),
+                &PrivateKey::random(&mut OsRng),
+                b"INV-0042",
+            )
+            .unwrap();
+            check_payment_reference_size(&output, 8).unwrap();
+            let err = check_payment_reference_size(&output, 7).unwrap_err();
+            unpack_enum!(ValidationError::PaymentReferenceTooLarge { max_size, actual_size } = err);
+            assert_eq!(max_size, 7);
+            assert_eq!(actual_size, 8);
+        }
+    }
+
+    mod check_memo_size {
+        use rand::rngs::OsRng;
+        use tari_common_types::types::{Commitment, PrivateKey};
+        use tari_crypto::keys::SecretKey;
+
+        use super::*;
+        use crate::transactions::{tari_amount::MicroMinotari, transaction_components::EncryptedData};
+
+        #[test]
+        fn it_rejects_memos_larger_than_the_max_size() {
+            let mut output = TransactionOutput::default();
+            check_memo_size(&output, 0).unwrap();
+
+            output.encrypted_data = EncryptedData::encrypt_data_with_memo(
+                &PrivateKey::random(&mut OsRng),
+                &Commitment::default(),
+                MicroMinotari(100),
+                &PrivateKey::random(&mut OsRng),
+                b"INV-0042",
+                b"deposit-1234",
+            )
+            .unwrap();
+            check_payment_reference_size(&output, 8).unwrap();
+            check_memo_size(&output, 12).unwrap();
+            let err = check_memo_size(&output, 11).unwrap_err();
+            unpack_enum!(ValidationError::MemoTooLarge { max_size, actual_size } = err);
+            assert_eq!(max_size, 11);
+            assert_eq!(actual_size, 12);
+        }
+    }
 }
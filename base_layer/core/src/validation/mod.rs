@@ -51,6 +51,8 @@ pub mod transaction;
 pub use chain_balance::ChainBalanceValidator;
 pub mod aggregate_body;
 pub mod header;
+mod report;
+pub use report::{CheckOutcome, CheckRecord, ValidationItem, ValidationItemKind, ValidationReport};
 
 #[cfg(test)]
 mod test;
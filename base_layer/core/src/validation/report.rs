@@ -0,0 +1,226 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    time::{Duration, Instant},
+};
+
+use tari_common_types::types::FixedHash;
+use tari_utilities::hex::Hex;
+
+use crate::validation::ValidationError;
+
+/// The kind of block body item that caused a check to fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationItemKind {
+    Input,
+    Output,
+    Kernel,
+}
+
+impl Display for ValidationItemKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationItemKind::Input => write!(f, "input"),
+            ValidationItemKind::Output => write!(f, "output"),
+            ValidationItemKind::Kernel => write!(f, "kernel"),
+        }
+    }
+}
+
+/// Identifies the input, output or kernel in a block body that caused a check to fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationItem {
+    pub kind: ValidationItemKind,
+    pub index: usize,
+    pub hash: FixedHash,
+}
+
+impl Display for ValidationItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} #{} ({})", self.kind, self.index, self.hash.to_hex())
+    }
+}
+
+/// The outcome of a single validation check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Passed,
+    Failed {
+        reason: String,
+        item: Option<ValidationItem>,
+    },
+}
+
+/// A record of a single validation check that was run
+#[derive(Debug, Clone)]
+pub struct CheckRecord {
+    pub name: &'static str,
+    pub duration: Duration,
+    pub outcome: CheckOutcome,
+}
+
+impl CheckRecord {
+    pub fn is_passed(&self) -> bool {
+        matches!(self.outcome, CheckOutcome::Passed)
+    }
+}
+
+/// A structured report of a validation run. It records every check that was run in order, how long it took and, for
+/// the check that failed, the input, output or kernel that caused the failure (if it can be attributed to one).
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    block_hash: Option<FixedHash>,
+    checks: Vec<CheckRecord>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn for_block(block_hash: FixedHash) -> Self {
+        Self {
+            block_hash: Some(block_hash),
+            ..Default::default()
+        }
+    }
+
+    pub fn block_hash(&self) -> Option<&FixedHash> {
+        self.block_hash.as_ref()
+    }
+
+    pub fn checks(&self) -> &[CheckRecord] {
+        &self.checks
+    }
+
+    /// Returns true if every check that was run passed
+    pub fn is_valid(&self) -> bool {
+        self.checks.iter().all(|c| c.is_passed())
+    }
+
+    /// The record of the failed check, if any
+    pub fn failed_check(&self) -> Option<&CheckRecord> {
+        self.checks.iter().find(|c| !c.is_passed())
+    }
+
+    /// The input, output or kernel that caused the validation to fail, if the failure can be attributed to one
+    pub fn failed_item(&self) -> Option<&ValidationItem> {
+        match self.failed_check().map(|c| &c.outcome) {
+            Some(CheckOutcome::Failed { item, .. }) => item.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.checks.iter().map(|c| c.duration).sum()
+    }
+
+    /// Run a check that does not relate to a single item, recording its duration and outcome. The error of a failed
+    /// check is returned as is so that it can be propagated by the caller.
+    pub fn run_check<F>(&mut self, name: &'static str, check: F) -> Result<(), ValidationError>
+    where F: FnOnce() -> Result<(), ValidationError> {
+        self.run_check_with_item(name, || check().map_err(|e| (e, None)))
+    }
+
+    /// Run a check that is able to report the item that caused it to fail, recording its duration and outcome.
+    pub fn run_check_with_item<F>(&mut self, name: &'static str, check: F) -> Result<(), ValidationError>
+    where F: FnOnce() -> Result<(), (ValidationError, Option<ValidationItem>)> {
+        let timer = Instant::now();
+        let result = check();
+        let duration = timer.elapsed();
+        match result {
+            Ok(()) => {
+                self.checks.push(CheckRecord {
+                    name,
+                    duration,
+                    outcome: CheckOutcome::Passed,
+                });
+                Ok(())
+            },
+            Err((err, item)) => {
+                self.checks.push(CheckRecord {
+                    name,
+                    duration,
+                    outcome: CheckOutcome::Failed {
+                        reason: err.to_string(),
+                        item,
+                    },
+                });
+                Err(err)
+            },
+        }
+    }
+
+    /// Run `check` over each of the given items in order, stopping at, and recording, the first item that fails.
+    /// `hash` is only called for the failing item.
+    pub fn run_item_checks<T, F, H>(
+        &mut self,
+        name: &'static str,
+        kind: ValidationItemKind,
+        items: &[T],
+        hash: H,
+        mut check: F,
+    ) -> Result<(), ValidationError>
+    where
+        F: FnMut(&T) -> Result<(), ValidationError>,
+        H: Fn(&T) -> FixedHash,
+    {
+        self.run_check_with_item(name, || {
+            for (index, item) in items.iter().enumerate() {
+                check(item).map_err(|e| {
+                    let item = ValidationItem {
+                        kind,
+                        index,
+                        hash: hash(item),
+                    };
+                    (e, Some(item))
+                })?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.block_hash {
+            Some(hash) => writeln!(f, "Validation report for block {}", hash.to_hex())?,
+            None => writeln!(f, "Validation report")?,
+        }
+        for check in &self.checks {
+            match &check.outcome {
+                CheckOutcome::Passed => writeln!(f, "  [ok]   {} ({:.2?})", check.name, check.duration)?,
+                CheckOutcome::Failed { reason, item } => {
+                    write!(f, "  [fail] {} ({:.2?}): {}", check.name, check.duration, reason)?;
+                    if let Some(item) = item {
+                        write!(f, " at {}", item)?;
+                    }
+                    writeln!(f)?;
+                },
+            }
+        }
+        Ok(())
+    }
+}
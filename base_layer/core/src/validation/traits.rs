@@ -27,13 +27,27 @@ use crate::{
     chain_storage::BlockchainBackend,
     proof_of_work::{AchievedTargetDifficulty, Difficulty},
     transactions::transaction_components::Transaction,
-    validation::error::ValidationError,
+    validation::{error::ValidationError, ValidationReport},
 };
 
 /// A validator that determines if a block body is valid, assuming that the header has already been
 /// validated
 pub trait BlockBodyValidator<B>: Send + Sync {
     fn validate_body(&self, backend: &B, block: &Block) -> Result<Block, ValidationError>;
+
+    /// Validates the internal consistency of a run of consecutive blocks that extend the chain tip, returning a result
+    /// for each block in the same order. The blocks that pass are then validated, and stored, one at a time with
+    /// [validate_chain_linked_body](Self::validate_chain_linked_body). By default all the checks are left to that
+    /// method.
+    fn validate_internal_consistency_batch(&self, _backend: &B, blocks: &[Block]) -> Vec<Result<(), ValidationError>> {
+        blocks.iter().map(|_| Ok(())).collect()
+    }
+
+    /// Validates a block that passed [validate_internal_consistency_batch](Self::validate_internal_consistency_batch)
+    /// against the chain.
+    fn validate_chain_linked_body(&self, backend: &B, block: &Block) -> Result<Block, ValidationError> {
+        self.validate_body(backend, block)
+    }
 }
 
 /// A validator that validates a body after it has been determined to be a valid orphan
@@ -52,6 +66,24 @@ pub trait TransactionValidator: Send + Sync {
 
 pub trait InternalConsistencyValidator: Send + Sync {
     fn validate_internal_consistency(&self, item: &Block) -> Result<(), ValidationError>;
+
+    /// Performs the same validation as `validate_internal_consistency`, returning a report of the checks that were
+    /// run. Implementations that do not record individual checks report the validation as a single check.
+    fn validate_with_report(&self, item: &Block) -> ValidationReport {
+        let mut report = ValidationReport::for_block(item.hash());
+        // The outcome is recorded in the report
+        let _result = report.run_check("internal_consistency", || self.validate_internal_consistency(item));
+        report
+    }
+
+    /// Validates each of the given blocks, returning a result for each block in the same order. Implementations may
+    /// validate the blocks concurrently.
+    fn validate_batch(&self, items: &[Block]) -> Vec<Result<(), ValidationError>> {
+        items
+            .iter()
+            .map(|item| self.validate_internal_consistency(item))
+            .collect()
+    }
 }
 
 pub trait HeaderChainLinkedValidator<B: BlockchainBackend>: Send + Sync {
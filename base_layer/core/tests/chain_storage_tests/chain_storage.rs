@@ -2025,7 +2025,7 @@ mod malleability {
                     output.script.clone(),
                     output.sender_offset_public_key.clone(),
                     output.covenant.clone(),
-                    output.encrypted_data,
+                    output.encrypted_data.clone(),
                     output.minimum_value_promise,
                 );
             });
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use tari_core::base_node::{state_machine_service::states::StatusInfo, StateMachineHandle};
+use tari_core::base_node::{state_machine_service::states::StatusInfo, sync::SyncBandwidthLimiter, StateMachineHandle};
 use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
 use tokio::sync::{broadcast, watch};
 
@@ -62,6 +62,7 @@ impl ServiceInitializer for MockBaseNodeStateMachineInitializer {
         let handle = StateMachineHandle::new(
             state_event_publisher,
             self.status_receiver.clone(),
+            SyncBandwidthLimiter::default(),
             context.get_shutdown_signal(),
         );
         context.register_handle(handle);
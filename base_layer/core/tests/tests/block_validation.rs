@@ -915,8 +915,8 @@ async fn test_block_sync_body_validator() {
     assert!(
         matches!(
             err,
-            ValidationError::BlockTooLarge { actual_weight, max_weight } if
-            actual_weight == 455 && max_weight == 400
+            ValidationError::BlockTooLarge { actual_weight, max_weight, breakdown } if
+            actual_weight == 455 && max_weight == 400 && breakdown.total() == actual_weight
         ),
         "{}",
         err
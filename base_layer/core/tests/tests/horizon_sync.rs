@@ -48,7 +48,10 @@ async fn test_initial_horizon_sync_from_archival_node_happy_path() {
                 pruning_horizon,
                 pruning_interval: 5,
                 track_reorgs: false,
+                max_reorg_history: 0,
                 cleanup_orphans_at_startup: false,
+                enable_output_indexes: false,
+                max_reorg_depth: None,
             },
             BlockchainDatabaseConfig::default(),
         ])
@@ -301,7 +304,10 @@ async fn test_consecutive_horizon_sync_from_prune_node_happy_path() {
                 pruning_horizon: pruning_horizon_alice,
                 pruning_interval: 5,
                 track_reorgs: false,
+                max_reorg_history: 0,
                 cleanup_orphans_at_startup: false,
+                enable_output_indexes: false,
+                max_reorg_depth: None,
             },
             // Carol is a pruned node
             BlockchainDatabaseConfig {
@@ -309,7 +315,10 @@ async fn test_consecutive_horizon_sync_from_prune_node_happy_path() {
                 pruning_horizon: pruning_horizon_carol,
                 pruning_interval: 5,
                 track_reorgs: false,
+                max_reorg_history: 0,
                 cleanup_orphans_at_startup: false,
+                enable_output_indexes: false,
+                max_reorg_depth: None,
             },
             // Bob is an archival node
             BlockchainDatabaseConfig::default(),
@@ -679,7 +688,10 @@ async fn test_initial_horizon_sync_from_prune_node_happy_path() {
                 pruning_horizon: pruning_horizon_alice,
                 pruning_interval: 5,
                 track_reorgs: false,
+                max_reorg_history: 0,
                 cleanup_orphans_at_startup: false,
+                enable_output_indexes: false,
+                max_reorg_depth: None,
             },
             // Carol is a pruned node
             BlockchainDatabaseConfig {
@@ -687,7 +699,10 @@ async fn test_initial_horizon_sync_from_prune_node_happy_path() {
                 pruning_horizon: pruning_horizon_carol,
                 pruning_interval: 5,
                 track_reorgs: false,
+                max_reorg_history: 0,
                 cleanup_orphans_at_startup: false,
+                enable_output_indexes: false,
+                max_reorg_depth: None,
             },
             // Bob is an archival node
             BlockchainDatabaseConfig::default(),
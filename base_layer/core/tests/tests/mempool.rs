@@ -28,9 +28,10 @@ use tari_common_types::types::{Commitment, PrivateKey, PublicKey, Signature};
 use tari_comms_dht::domain_message::OutboundDomainMessage;
 use tari_core::{
     base_node::state_machine_service::states::{ListeningInfo, StateInfo, StatusInfo},
+    blocks::ShortIdKey,
     chain_storage::BlockchainDatabaseConfig,
     consensus::{ConsensusConstantsBuilder, ConsensusManager},
-    mempool::{Mempool, MempoolConfig, MempoolServiceConfig, TxStorageResponse},
+    mempool::{Mempool, MempoolConfig, MempoolEvent, MempoolServiceConfig, TxStorageResponse},
     proof_of_work::Difficulty,
     proto,
     transactions::{
@@ -1036,6 +1037,260 @@ async fn test_reorg() {
     mempool.process_reorg(vec![], vec![reorg_block4.into()]).await.unwrap();
 }
 
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_replace_by_fee() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager, key_manager) = create_new_blockchain(network).await;
+    let mempool_validator = TransactionChainLinkedValidator::new(store.clone(), consensus_manager.clone());
+    let mut mempool_config = MempoolConfig::default();
+    mempool_config.replace_by_fee.enabled = true;
+    mempool_config.replace_by_fee.min_fee_bump_percentage = 10;
+    let mempool = Mempool::new(mempool_config, consensus_manager.clone(), Box::new(mempool_validator));
+
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T],fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).await.unwrap();
+
+    let original = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let original = Arc::new(spend_utxos(original, &key_manager).await.0);
+    let small_bump = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 21*uT, lock: 0, features: OutputFeatures::default());
+    let small_bump = Arc::new(spend_utxos(small_bump, &key_manager).await.0);
+    let replacement = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 40*uT, lock: 0, features: OutputFeatures::default());
+    let replacement = Arc::new(spend_utxos(replacement, &key_manager).await.0);
+    let unrelated = txn_schema!(from: vec![outputs[1][1].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let unrelated = Arc::new(spend_utxos(unrelated, &key_manager).await.0);
+
+    assert_eq!(
+        mempool.insert(original.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool.insert(unrelated.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    // A 5% fee bump is not enough to replace the original
+    assert_eq!(
+        mempool.insert(small_bump.clone()).await.unwrap(),
+        TxStorageResponse::NotStoredFeeTooLow
+    );
+    assert_eq!(
+        mempool.insert(replacement.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+
+    let excess_sig = |tx: &Transaction| tx.body.kernels()[0].excess_sig.clone();
+    assert_eq!(
+        mempool.has_tx_with_excess_sig(excess_sig(&original)).await.unwrap(),
+        TxStorageResponse::NotStored
+    );
+    assert_eq!(
+        mempool.has_tx_with_excess_sig(excess_sig(&replacement)).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool.has_tx_with_excess_sig(excess_sig(&unrelated)).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(mempool.stats().await.unwrap().unconfirmed_txs, 2);
+
+    // The evicted original cannot replace the replacement
+    assert_eq!(
+        mempool.insert(original.clone()).await.unwrap(),
+        TxStorageResponse::NotStoredFeeTooLow
+    );
+    assert_eq!(mempool.stats().await.unwrap().unconfirmed_txs, 2);
+}
+
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_retrieve_by_short_ids() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager, key_manager) = create_new_blockchain(network).await;
+    let mempool_validator = TransactionChainLinkedValidator::new(store.clone(), consensus_manager.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T],fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).await.unwrap();
+
+    let tx1 = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx1 = Arc::new(spend_utxos(tx1, &key_manager).await.0);
+    let tx2 = txn_schema!(from: vec![outputs[1][1].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx2 = Arc::new(spend_utxos(tx2, &key_manager).await.0);
+    mempool.insert(tx1.clone()).await.unwrap();
+    mempool.insert(tx2.clone()).await.unwrap();
+
+    let key = ShortIdKey::new(*blocks[1].hash(), 42);
+    let short_id = |tx: &Transaction| key.short_id(tx.body.kernels()[0].excess_sig.get_signature());
+    let unknown_short_id = key.short_id(&PrivateKey::default());
+    let (transactions, missing) = mempool
+        .retrieve_by_short_ids(key, vec![short_id(&tx1), short_id(&tx2), unknown_short_id])
+        .await
+        .unwrap();
+    assert_eq!(transactions.len(), 2);
+    assert!(transactions.contains(&tx1));
+    assert!(transactions.contains(&tx2));
+    assert_eq!(missing, vec![unknown_short_id]);
+
+    // Short IDs derived with a different salt do not match
+    let other_key = ShortIdKey::new(*blocks[1].hash(), 43);
+    let other_short_id = other_key.short_id(tx1.body.kernels()[0].excess_sig.get_signature());
+    let (transactions, missing) = mempool.retrieve_by_short_ids(key, vec![other_short_id]).await.unwrap();
+    assert!(transactions.is_empty());
+    assert_eq!(missing, vec![other_short_id]);
+}
+
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_orphan_accepted_when_parent_arrives() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager, key_manager) = create_new_blockchain(network).await;
+    let mempool_validator = TransactionChainLinkedValidator::new(store.clone(), consensus_manager.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T],fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).await.unwrap();
+
+    let (parent, parent_out) = spend_utxos(
+        txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default()),
+        &key_manager,
+    )
+    .await;
+    let (child, _) = spend_utxos(
+        txn_schema!(from: parent_out, to: vec![500_000*uT], fee: 30*uT, lock: 0, features: OutputFeatures::default()),
+        &key_manager,
+    )
+    .await;
+    let parent = Arc::new(parent);
+    let child = Arc::new(child);
+    let mut events = mempool.subscribe_events();
+
+    // The child arrives before its parent and is held in the orphan pool
+    assert_eq!(
+        mempool.insert(child.clone()).await.unwrap(),
+        TxStorageResponse::NotStoredOrphan
+    );
+    assert_eq!(
+        mempool.insert(parent.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    assert_eq!(
+        mempool
+            .has_tx_with_excess_sig(child.body.kernels()[0].excess_sig.clone())
+            .await
+            .unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    match events.try_recv().unwrap().as_ref() {
+        MempoolEvent::OrphansAccepted(txs) => assert_eq!(txs, &vec![child]),
+        event => panic!("Unexpected mempool event {:?}", event),
+    }
+}
+
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_events_published_for_added_and_mined_transactions() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager, key_manager) = create_new_blockchain(network).await;
+    let mempool_validator = TransactionChainLinkedValidator::new(store.clone(), consensus_manager.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T],fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    mempool.process_published_block(blocks[1].to_arc_block()).await.unwrap();
+
+    let tx = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx = Arc::new(spend_utxos(tx, &key_manager).await.0);
+    let mut events = mempool.subscribe_events();
+
+    assert_eq!(
+        mempool.insert(tx.clone()).await.unwrap(),
+        TxStorageResponse::UnconfirmedPool
+    );
+    match events.try_recv().unwrap().as_ref() {
+        MempoolEvent::TransactionAdded(added) => assert_eq!(added, &tx),
+        event => panic!("Unexpected mempool event {:?}", event),
+    }
+
+    generate_block(
+        &store,
+        &mut blocks,
+        vec![tx.deref().clone()],
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    mempool.process_published_block(blocks[2].to_arc_block()).await.unwrap();
+    match events.try_recv().unwrap().as_ref() {
+        MempoolEvent::TransactionsRemoved(removed) => assert_eq!(removed, &vec![tx]),
+        event => panic!("Unexpected mempool event {:?}", event),
+    }
+    assert!(events.try_recv().is_err());
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 #[allow(clippy::too_many_lines)]
 #[allow(clippy::identity_op)]
@@ -22,6 +22,7 @@
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::{NaiveDateTime, Utc};
 use diesel::{prelude::*, SqliteConnection};
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
 use tari_common_types::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce};
 use tari_crypto::keys::PublicKey;
 use tari_utilities::{hex::Hex, ByteArray, Hidden};
@@ -35,7 +36,7 @@ use crate::{
             sqlite_db::{imported_keys, Encryptable},
         },
     },
-    schema::imported_keys::{private_key, public_key, table, timestamp},
+    schema::imported_keys::{id, private_key, public_key, table, timestamp},
 };
 
 /// Represents a row in the imported keys table.
@@ -91,6 +92,24 @@ impl ImportedKeySql {
         Ok(imported_keys::table.load::<ImportedKeySql>(conn)?)
     }
 
+    /// Retrieve up to `limit` imported keys, ordered by id and starting at `offset`.
+    pub fn page(offset: i64, limit: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, KeyManagerStorageError> {
+        Ok(imported_keys::table
+            .order(imported_keys::id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<ImportedKeySql>(conn)?)
+    }
+
+    /// Overwrite the stored private key with the value held in this instance.
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), KeyManagerStorageError> {
+        diesel::update(imported_keys::table.filter(imported_keys::id.eq(self.id)))
+            .set(private_key.eq(self.private_key.clone()))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
     #[allow(clippy::wrong_self_convention)]
     pub fn to_imported_key<PK: PublicKey>(
         self,
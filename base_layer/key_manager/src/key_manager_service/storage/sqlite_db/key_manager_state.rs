@@ -96,6 +96,15 @@ impl KeyManagerStateSql {
         Ok(key_manager_states::table.load::<KeyManagerStateSql>(conn)?)
     }
 
+    /// Retrieve up to `limit` key manager branches, ordered by id and starting at `offset`.
+    pub fn page(offset: i64, limit: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, KeyManagerStorageError> {
+        Ok(key_manager_states::table
+            .order(key_manager_states::id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<KeyManagerStateSql>(conn)?)
+    }
+
     /// Retrieve the key manager for the provided branch
     /// Will return Err if the branch does not exist in the database
     pub fn get_state(branch: &str, conn: &mut SqliteConnection) -> Result<KeyManagerStateSql, KeyManagerStorageError> {
@@ -26,11 +26,12 @@ use std::{
 };
 
 use chacha20poly1305::XChaCha20Poly1305;
+use diesel::{Connection, SqliteConnection};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 pub use key_manager_state::{KeyManagerStateSql, NewKeyManagerStateSql};
 use log::*;
 use tari_common_sqlite::{error::SqliteStorageError, sqlite_connection_pool::PooledDbConnection};
-use tari_common_types::encryption::Encryptable;
+use tari_common_types::encryption::{rekey_encryptable, Encryptable, LegacyPlaintext, RekeyOutcome, RekeySummary};
 use tari_crypto::keys::PublicKey;
 use tari_utilities::acquire_read_lock;
 use tokio::time::Instant;
@@ -93,6 +94,68 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + C
     }
 }
 
+/// Re-encrypt the key manager states and imported keys from `old` to `new`. Every `batch_size` rows are committed in
+/// their own transaction, and rows already encrypted under `new` are skipped, so an interrupted run can be repeated.
+pub fn rekey_key_manager_tables(
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+    batch_size: usize,
+) -> Result<RekeySummary, KeyManagerStorageError> {
+    // The tables only exist once the key manager has been started against this database
+    conn.run_pending_migrations(MIGRATIONS)
+        .map_err(|e| KeyManagerStorageError::DatabaseMigrationError(e.to_string()))?;
+    let mut summary = rekey_in_batches(conn, old, new, batch_size, KeyManagerStateSql::page, |km, conn| {
+        KeyManagerStateSql::set_index(km.id, km.primary_key_index.clone(), conn)
+    })?;
+    summary.merge(rekey_in_batches(
+        conn,
+        old,
+        new,
+        batch_size,
+        ImportedKeySql::page,
+        ImportedKeySql::update_encryption,
+    )?);
+    Ok(summary)
+}
+
+fn rekey_in_batches<T, L, U>(
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+    batch_size: usize,
+    load: L,
+    update: U,
+) -> Result<RekeySummary, KeyManagerStorageError>
+where
+    T: Encryptable<XChaCha20Poly1305> + Clone,
+    L: Fn(i64, i64, &mut SqliteConnection) -> Result<Vec<T>, KeyManagerStorageError>,
+    U: Fn(&T, &mut SqliteConnection) -> Result<(), KeyManagerStorageError>,
+{
+    let batch_size = batch_size.max(1);
+    let limit = i64::try_from(batch_size).unwrap_or(i64::MAX);
+    let mut summary = RekeySummary::default();
+    let mut offset = 0i64;
+    loop {
+        let num_rows = conn.transaction::<_, KeyManagerStorageError, _>(|conn| {
+            let rows = load(offset, limit, conn)?;
+            for row in &rows {
+                let (row, outcome) = rekey_encryptable(row.clone(), old, new, LegacyPlaintext::Reject)
+                    .map_err(|e| KeyManagerStorageError::AeadError(format!("Rekey Error: {}", e)))?;
+                if outcome != RekeyOutcome::AlreadyRekeyed {
+                    update(&row, conn)?;
+                }
+                summary.record(outcome);
+            }
+            Ok(rows.len())
+        })?;
+        if num_rows < batch_size {
+            return Ok(summary);
+        }
+        offset += limit;
+    }
+}
+
 impl<TKeyManagerDbConnection, PK> KeyManagerBackend<PK> for KeyManagerSqliteDatabase<TKeyManagerDbConnection>
 where
     TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + Send + Sync + Clone,
@@ -158,6 +158,12 @@ impl<H: Digest<OutputSize = U32>> InclusionProof<H> {
         Ok(Self::new(proof.siblings))
     }
 
+    /// Returns the sibling hashes along the path to the key's leaf node. These can be used to reconstruct the proof
+    /// with [`InclusionProof::new`].
+    pub fn siblings(&self) -> &[NodeHash] {
+        &self.siblings
+    }
+
     /// Validates the inclusion proof against the given key, value hash and root hash.
     /// The function reconstructs the tree using the expected key and value hash, and then calculates the root hash.
     /// Validation succeeds if the calculated root hash matches the given root hash.
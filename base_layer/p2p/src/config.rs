@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -36,7 +37,7 @@ use tari_common::{
     DnsNameServer,
     SubConfigPath,
 };
-use tari_comms::multiaddr::Multiaddr;
+use tari_comms::{bandwidth, multiaddr::Multiaddr, peer_manager};
 use tari_comms_dht::{DbConnectionUrl, DhtConfig};
 
 use crate::{transport::TransportConfig, DEFAULT_DNS_NAME_SERVER};
@@ -59,6 +60,10 @@ pub struct PeerSeedsConfig {
     pub dns_seeds_name_server: DnsNameServer,
     /// All DNS seed records must pass DNSSEC validation
     pub dns_seeds_use_dnssec: bool,
+    /// Peers resolved from the DNS seeds are cached on disk and used if DNS resolution fails. Cached peers older
+    /// than this are ignored. Set to 0 to disable the cache. Default: 7 days
+    #[serde(with = "serializers::seconds")]
+    pub dns_seeds_cache_max_age: Duration,
 }
 
 impl Default for PeerSeedsConfig {
@@ -69,6 +74,7 @@ impl Default for PeerSeedsConfig {
             dns_seeds: StringList::default(),
             dns_seeds_name_server: DEFAULT_DNS_NAME_SERVER.parse().unwrap(),
             dns_seeds_use_dnssec: false,
+            dns_seeds_cache_max_age: Duration::from_secs(7 * 24 * 60 * 60),
         }
     }
 }
@@ -127,6 +133,15 @@ pub struct P2pConfig {
     /// The maximum allowed RPC sessions per peer.
     /// Default: 10
     pub rpc_max_sessions_per_peer: usize,
+    /// The policy used to score peer offenses and ban misbehaving peers
+    pub ban_policy: BanPolicyConfig,
+    /// The lifetime of noise session tickets. A peer that reconnects within this time can resume its previous session
+    /// with a 1-RTT handshake and skip the identity exchange. Set to None or 0 to disable session resumption.
+    /// Default: 1 hour
+    #[serde(with = "serializers::optional_seconds")]
+    pub noise_session_ticket_lifetime: Option<Duration>,
+    /// Bandwidth accounting per service and optional per-service quotas
+    pub bandwidth: BandwidthConfig,
 }
 
 impl Default for P2pConfig {
@@ -152,6 +167,9 @@ impl Default for P2pConfig {
             auxiliary_tcp_listener_address: None,
             rpc_max_simultaneous_sessions: 100,
             rpc_max_sessions_per_peer: 10,
+            ban_policy: BanPolicyConfig::default(),
+            noise_session_ticket_lifetime: Some(Duration::from_secs(60 * 60)),
+            bandwidth: BandwidthConfig::default(),
         }
     }
 }
@@ -168,6 +186,141 @@ impl P2pConfig {
         if !self.datastore_path.is_absolute() {
             self.datastore_path = base_path.as_ref().join(self.datastore_path.as_path());
         }
+        #[cfg(feature = "i2p")]
+        if let Some(ref mut private_key_file) = self.transport.i2p.private_key_file {
+            if !private_key_file.is_absolute() {
+                *private_key_file = base_path.as_ref().join(private_key_file.as_path());
+            }
+        }
         self.dht.set_base_path(base_path)
     }
 }
+
+/// The score and ban duration of a peer offense
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OffensePolicyConfig {
+    /// The amount the offense adds to the offense score of the peer
+    pub score: u32,
+    /// The ban duration if the offense causes the offense score of the peer to reach the ban threshold
+    #[serde(with = "serializers::seconds")]
+    pub ban_duration: Duration,
+}
+
+impl From<peer_manager::OffensePolicy> for OffensePolicyConfig {
+    fn from(policy: peer_manager::OffensePolicy) -> Self {
+        Self {
+            score: policy.score,
+            ban_duration: policy.ban_duration,
+        }
+    }
+}
+
+impl From<OffensePolicyConfig> for peer_manager::OffensePolicy {
+    fn from(config: OffensePolicyConfig) -> Self {
+        Self::new(config.score, config.ban_duration)
+    }
+}
+
+/// Peer ban policy configuration. Offenses committed by peers add to their offense score, which decays over time. A
+/// peer is banned when its offense score reaches the ban threshold.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BanPolicyConfig {
+    /// A peer is banned once its offense score reaches this threshold
+    pub ban_threshold: u32,
+    /// The amount by which a peer's offense score decays every hour
+    pub score_decay_per_hour: u32,
+    /// The ban duration of an offense is doubled for each previous ban of the peer, up to this maximum
+    #[serde(with = "serializers::seconds")]
+    pub max_ban_duration: Duration,
+    /// The maximum number of offenses kept in the offense ledger for each peer
+    pub max_offenses_per_peer: usize,
+    /// The peer sent a block that failed validation
+    pub invalid_block: OffensePolicyConfig,
+    /// The peer sent invalid or inconsistent data during horizon sync
+    pub bad_horizon_data: OffensePolicyConfig,
+    /// The peer sent an invalid request or response
+    pub invalid_message: OffensePolicyConfig,
+    /// The peer violated a wire protocol
+    pub protocol_violation: OffensePolicyConfig,
+    /// The peer sent excessive or unsolicited messages
+    pub spam: OffensePolicyConfig,
+    /// The peer did not respond, or responded too slowly
+    pub unresponsive: OffensePolicyConfig,
+}
+
+impl Default for BanPolicyConfig {
+    fn default() -> Self {
+        peer_manager::BanPolicyConfig::default().into()
+    }
+}
+
+impl From<peer_manager::BanPolicyConfig> for BanPolicyConfig {
+    fn from(config: peer_manager::BanPolicyConfig) -> Self {
+        Self {
+            ban_threshold: config.ban_threshold,
+            score_decay_per_hour: config.score_decay_per_hour,
+            max_ban_duration: config.max_ban_duration,
+            max_offenses_per_peer: config.max_offenses_per_peer,
+            invalid_block: config.invalid_block.into(),
+            bad_horizon_data: config.bad_horizon_data.into(),
+            invalid_message: config.invalid_message.into(),
+            protocol_violation: config.protocol_violation.into(),
+            spam: config.spam.into(),
+            unresponsive: config.unresponsive.into(),
+        }
+    }
+}
+
+impl From<BanPolicyConfig> for peer_manager::BanPolicyConfig {
+    fn from(config: BanPolicyConfig) -> Self {
+        Self {
+            ban_threshold: config.ban_threshold,
+            score_decay_per_hour: config.score_decay_per_hour,
+            max_ban_duration: config.max_ban_duration,
+            max_offenses_per_peer: config.max_offenses_per_peer,
+            invalid_block: config.invalid_block.into(),
+            bad_horizon_data: config.bad_horizon_data.into(),
+            invalid_message: config.invalid_message.into(),
+            protocol_violation: config.protocol_violation.into(),
+            spam: config.spam.into(),
+            unresponsive: config.unresponsive.into(),
+        }
+    }
+}
+
+/// Bandwidth accounting configuration. Bytes sent and received are attributed to the block_sync, mempool_sync, dht,
+/// saf, rpc and other services.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BandwidthConfig {
+    /// The length of an accounting period. Quotas apply to the bytes used within a period.
+    /// Default: 1 day
+    #[serde(with = "serializers::seconds")]
+    pub accounting_period: Duration,
+    /// The maximum number of bytes (sent and received) that a service may use in an accounting period. Once a service
+    /// has used its quota, new substreams for the service are refused until the next period. Services without a
+    /// quota are unlimited.
+    /// Default: no quotas
+    pub quotas: HashMap<String, u64>,
+}
+
+impl Default for BandwidthConfig {
+    fn default() -> Self {
+        Self {
+            accounting_period: bandwidth::BandwidthConfig::default().period,
+            quotas: HashMap::new(),
+        }
+    }
+}
+
+impl From<BandwidthConfig> for bandwidth::BandwidthConfig {
+    fn from(config: BandwidthConfig) -> Self {
+        Self {
+            period: config.accounting_period,
+            quotas: config.quotas,
+            ..Default::default()
+        }
+    }
+}
@@ -25,13 +25,11 @@ use std::{
     fs::File,
     iter,
     path::Path,
-    str::FromStr,
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use fs2::FileExt;
-use futures::future;
 use lmdb_zero::open;
 use log::*;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
@@ -39,8 +37,12 @@ use tari_common::{
     configuration::Network,
     exit_codes::{ExitCode, ExitError},
 };
+#[cfg(feature = "i2p")]
+use tari_comms::transports::I2pTransport;
 use tari_comms::{
     backoff::ConstantBackoff,
+    bandwidth::{services, BandwidthConfig},
+    connectivity::ConnectivityRequester,
     multiaddr::multiaddr,
     peer_manager::{NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManagerError},
     pipeline,
@@ -60,7 +62,7 @@ use tari_comms::{
         SocksTransport,
         TcpWithTorTransport,
     },
-    utils::cidr::parse_cidrs,
+    utils::{cidr::parse_cidrs, multiaddr::multiaddr_to_socketaddr},
     CommsBuilder,
     CommsBuilderError,
     CommsNode,
@@ -81,7 +83,8 @@ use tower::ServiceBuilder;
 use crate::{
     comms_connector::{InboundDomainConnector, PubsubDomainConnector},
     config::{P2pConfig, PeerSeedsConfig},
-    peer_seeds::{DnsSeedResolver, SeedPeer},
+    port_mapping::PortMapper,
+    seed_bootstrap::{SeedBootstrap, DNS_SEEDS_CACHE_FILE},
     transport::{TorTransportConfig, TransportType},
     TransportConfig,
     MAJOR_NETWORK_VERSION,
@@ -240,6 +243,8 @@ pub async fn spawn_comms_using_transport<F: Fn(TorIdentity) + Send + Sync + Unpi
                     .map(|_| " with Tor support")
                     .unwrap_or("")
             );
+            let port_mapping = config.port_mapping;
+            let listener_port = multiaddr_to_socketaddr(&config.listener_address).map(|addr| addr.port());
             let mut transport = TcpWithTorTransport::new();
             if let Some(addr) = config.tor_socks_address {
                 transport.set_tor_socks_proxy(SocksConfig {
@@ -248,26 +253,63 @@ pub async fn spawn_comms_using_transport<F: Fn(TorIdentity) + Send + Sync + Unpi
                     proxy_bypass_predicate: Arc::new(FalsePredicate::new()),
                 });
             }
-            comms
+            let comms = comms
                 .with_listener_address(config.listener_address)
                 .spawn_with_transport(transport)
-                .await?
+                .await?;
+            if port_mapping.enabled {
+                match listener_port {
+                    Ok(port) if port != 0 => {
+                        PortMapper::new(port_mapping, port, comms.node_identity(), comms.shutdown_signal()).spawn();
+                    },
+                    _ => warn!(
+                        target: LOG_TARGET,
+                        "Port mapping is enabled, but the TCP listener address does not have a fixed port"
+                    ),
+                }
+            }
+            comms
         },
         TransportType::Tor => {
             let tor_config = transport_config.tor;
             debug!(target: LOG_TARGET, "Building TOR comms stack ({:?})", tor_config);
             let listener_address_override = tor_config.listener_address_override.clone();
+            let onion_rotation_interval = tor_config.onion_rotation_interval;
             let hidden_service_ctl = initialize_hidden_service(tor_config)?;
             // Set the listener address to be the address (usually local) to which tor will forward all traffic
             let instant = Instant::now();
-            let transport = HiddenServiceTransport::new(hidden_service_ctl, after_comms);
+            let (identity_updated_tx, identity_updated_rx) = mpsc::unbounded_channel();
+            let transport = HiddenServiceTransport::new(hidden_service_ctl, move |identity: TorIdentity| {
+                after_comms(identity);
+                let _result = identity_updated_tx.send(());
+            });
             debug!(target: LOG_TARGET, "TOR transport initialized in {:.0?}", instant.elapsed());
 
-            comms
+            let comms = comms
                 .with_listener_address(
                     listener_address_override.unwrap_or_else(|| multiaddr![Ip4([127, 0, 0, 1]), Tcp(0u16)]),
                 )
                 .spawn_with_transport(transport)
+                .await?;
+            if onion_rotation_interval.is_some() {
+                tokio::spawn(reconnect_peers_after_onion_rotation(
+                    comms.connectivity(),
+                    identity_updated_rx,
+                ));
+            }
+            comms
+        },
+        #[cfg(feature = "i2p")]
+        TransportType::I2p => {
+            debug!(target: LOG_TARGET, "Building I2P comms stack");
+            let config = transport_config.i2p;
+            let sam_address = config.sam_address.clone();
+            let node_identity = comms.node_identity();
+            // Peers learn this node's I2P address from its signed identity, allowing them to dial back
+            let transport = I2pTransport::new(config.into(), move |address| node_identity.add_public_address(address));
+            comms
+                .with_listener_address(sam_address)
+                .spawn_with_transport(transport)
                 .await?
         },
         TransportType::Socks5 => {
@@ -292,7 +334,10 @@ fn initialize_hidden_service(
         .with_control_server_auth(config.to_control_auth()?)
         .with_socks_address_override(config.socks_address_override)
         .with_control_server_address(config.control_address)
-        .with_bypass_proxy_addresses(config.proxy_bypass_addresses.into());
+        .with_bypass_proxy_addresses(config.proxy_bypass_addresses.into())
+        .with_bridges(config.bridges)
+        .with_client_transport_plugins(config.client_transport_plugins)
+        .with_onion_rotation_interval(config.onion_rotation_interval);
 
     if config.proxy_bypass_for_outbound_tcp {
         builder = builder.bypass_tor_for_tcp_addresses();
@@ -306,6 +351,41 @@ fn initialize_hidden_service(
     Ok(hidden_svc_ctl)
 }
 
+/// Reconnects to every connected peer after the onion address has been rotated, so that each peer receives the newly
+/// signed public address in the identity exchange. Peers are reconnected one at a time so that DHT sessions are not
+/// all dropped at once.
+async fn reconnect_peers_after_onion_rotation(
+    mut connectivity: ConnectivityRequester,
+    mut identity_updated_rx: mpsc::UnboundedReceiver<()>,
+) {
+    // The first update is for the initial creation of the hidden service
+    let _result = identity_updated_rx.recv().await;
+    while identity_updated_rx.recv().await.is_some() {
+        let connections = match connectivity.get_active_connections().await {
+            Ok(connections) => connections,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Failed to get active connections after onion rotation: {}", err);
+                continue;
+            },
+        };
+        info!(
+            target: LOG_TARGET,
+            "Onion address rotated. Reconnecting to {} peer(s)",
+            connections.len()
+        );
+        for mut conn in connections {
+            let node_id = conn.peer_node_id().clone();
+            if let Err(err) = conn.disconnect().await {
+                debug!(target: LOG_TARGET, "Failed to disconnect from peer {}: {}", node_id, err);
+                continue;
+            }
+            if let Err(err) = connectivity.dial_peer(node_id.clone()).await {
+                debug!(target: LOG_TARGET, "Failed to reconnect to peer {}: {}", node_id, err);
+            }
+        }
+    }
+}
+
 async fn configure_comms_and_dht(
     builder: CommsBuilder,
     config: &P2pConfig,
@@ -331,6 +411,8 @@ async fn configure_comms_and_dht(
         .with_listener_liveness_max_sessions(config.listener_liveness_max_sessions)
         .with_listener_liveness_allowlist_cidrs(listener_liveness_allowlist_cidrs)
         .with_dial_backoff(ConstantBackoff::new(Duration::from_millis(500)))
+        .with_ban_policy_config(config.ban_policy.clone().into())
+        .with_bandwidth_config(bandwidth_config(config))
         .with_peer_storage(peer_database, Some(file_lock));
 
     let mut comms = match config.auxiliary_tcp_listener_address {
@@ -346,7 +428,9 @@ async fn configure_comms_and_dht(
     let (outbound_tx, outbound_rx) = mpsc::channel(config.dht.outbound_buffer_size);
 
     let mut dht = Dht::builder();
-    dht.with_config(config.dht.clone()).with_outbound_sender(outbound_tx);
+    dht.with_config(config.dht.clone())
+        .with_outbound_sender(outbound_tx)
+        .with_bandwidth_meter(comms.bandwidth_meter());
     let dht = dht
         .build(node_identity.clone(), peer_manager, connectivity, shutdown_signal)
         .await?;
@@ -385,6 +469,18 @@ async fn configure_comms_and_dht(
     Ok((comms, dht))
 }
 
+/// Returns the bandwidth configuration that maps the substream protocols of minotari services to the service that
+/// their bandwidth is accounted under
+fn bandwidth_config(config: &P2pConfig) -> BandwidthConfig {
+    BandwidthConfig::from(config.bandwidth.clone())
+        .with_protocol_service(ProtocolId::from_static(b"t/blksync/1"), services::BLOCK_SYNC)
+        .with_protocol_service(ProtocolId::from_static(b"t/mempool-sync/2"), services::MEMPOOL_SYNC)
+        .with_protocol_service(MESSAGING_PROTOCOL_ID.clone(), services::DHT)
+        .with_protocol_service(ProtocolId::from_static(b"t/dht/1"), services::RPC)
+        .with_protocol_service(ProtocolId::from_static(b"t/bnwallet/1"), services::RPC)
+        .with_protocol_service(ProtocolId::from_static(b"t/mempool/1"), services::RPC)
+}
+
 /// Acquire an exclusive OS level write lock on a file in the provided path. This is used to check if another instance
 /// of this database has already been initialized in order to prevent two process from using it simultaneously
 /// ## Parameters
@@ -467,80 +563,6 @@ impl P2pInitializer {
             connector: Some(connector),
         }
     }
-
-    // Following are inlined due to Rust ICE: https://github.com/rust-lang/rust/issues/73537
-    fn try_parse_seed_peers(peer_seeds_str: &[String]) -> Result<Vec<Peer>, ServiceInitializationError> {
-        peer_seeds_str
-            .iter()
-            .map(|s| SeedPeer::from_str(s))
-            .map(|r| r.map(Peer::from))
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(Into::into)
-    }
-
-    async fn try_resolve_dns_seeds(config: &PeerSeedsConfig) -> Result<Vec<Peer>, ServiceInitializationError> {
-        if config.dns_seeds.is_empty() {
-            debug!(target: LOG_TARGET, "No DNS Seeds configured");
-            return Ok(Vec::new());
-        }
-
-        debug!(
-            target: LOG_TARGET,
-            "Resolving DNS seeds (NS:{}, addresses: {})...",
-            config.dns_seeds_name_server,
-            config
-                .dns_seeds
-                .iter()
-                .map(ToString::to_string)
-                .collect::<Vec<String>>()
-                .join(",")
-        );
-        let start = Instant::now();
-
-        let resolver = if config.dns_seeds_use_dnssec {
-            debug!(
-                target: LOG_TARGET,
-                "Using {} to resolve DNS seeds. DNSSEC is enabled", config.dns_seeds_name_server
-            );
-            DnsSeedResolver::connect_secure(config.dns_seeds_name_server.clone()).await?
-        } else {
-            debug!(
-                target: LOG_TARGET,
-                "Using {} to resolve DNS seeds. DNSSEC is disabled", config.dns_seeds_name_server
-            );
-            DnsSeedResolver::connect(config.dns_seeds_name_server.clone()).await?
-        };
-        let resolving = config.dns_seeds.iter().map(|addr| {
-            let mut resolver = resolver.clone();
-            async move { (resolver.resolve(addr).await, addr) }
-        });
-
-        let peers = future::join_all(resolving)
-            .await
-            .into_iter()
-            // Log and ignore errors
-            .filter_map(|(result, addr)| match result {
-                Ok(peers) => {
-                    debug!(
-                        target: LOG_TARGET,
-                        "Found {} peer(s) from `{}` in {:.0?}",
-                        peers.len(),
-                        addr,
-                        start.elapsed()
-                    );
-                    Some(peers)
-                },
-                Err(err) => {
-                    warn!(target: LOG_TARGET, "DNS seed `{}` failed to resolve: {}", addr, err);
-                    None
-                },
-            })
-            .flatten()
-            .map(Into::into)
-            .collect::<Vec<_>>();
-
-        Ok(peers)
-    }
 }
 
 #[async_trait]
@@ -559,7 +581,8 @@ impl ServiceInitializer for P2pInitializer {
                 network_byte: self.network.as_byte(),
                 user_agent: config.user_agent.clone(),
             })
-            .set_liveness_check(config.listener_liveness_check_interval);
+            .set_liveness_check(config.listener_liveness_check_interval)
+            .set_noise_session_ticket_lifetime(config.noise_session_ticket_lifetime.filter(|l| !l.is_zero()));
 
         if config.allow_test_addresses || config.dht.peer_validator_config.allow_test_addresses {
             // The default is false, so ensure that both settings are true in this case
@@ -573,17 +596,10 @@ impl ServiceInitializer for P2pInitializer {
         let peer_manager = comms.peer_manager();
         let node_identity = comms.node_identity();
 
-        let peers = match Self::try_resolve_dns_seeds(&self.seed_config).await {
-            Ok(peers) => peers,
-            Err(err) => {
-                warn!(target: LOG_TARGET, "Failed to resolve DNS seeds: {}", err);
-                Vec::new()
-            },
-        };
-        add_seed_peers(&peer_manager, &node_identity, peers).await?;
-
-        let peers = Self::try_parse_seed_peers(&self.seed_config.peer_seeds)?;
-
+        let seed_cache_file = config.datastore_path.join(DNS_SEEDS_CACHE_FILE);
+        let peers = SeedBootstrap::new(self.seed_config.clone(), seed_cache_file)
+            .bootstrap()
+            .await?;
         add_seed_peers(&peer_manager, &node_identity, peers).await?;
 
         context.register_handle(comms.connectivity());
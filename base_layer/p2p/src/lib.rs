@@ -32,7 +32,9 @@ pub mod domain_message;
 pub mod initialization;
 pub mod peer;
 pub mod peer_seeds;
+pub mod port_mapping;
 pub mod proto;
+pub mod seed_bootstrap;
 pub mod services;
 mod socks_authentication;
 pub mod tari_message;
@@ -45,9 +47,11 @@ mod dns;
 pub use socks_authentication::SocksAuthentication;
 pub use tari_common::configuration::Network;
 pub use tor_authentication::TorControlAuthentication;
+#[cfg(feature = "i2p")]
+pub use transport::I2pTransportConfig;
 pub use transport::{Socks5TransportConfig, TcpTransportConfig, TorTransportConfig, TransportConfig, TransportType};
 
-pub use self::config::{P2pConfig, PeerSeedsConfig};
+pub use self::config::{BanPolicyConfig, OffensePolicyConfig, P2pConfig, PeerSeedsConfig};
 
 /// Default DNS resolver set to cloudflare's private 1.1.1.1 resolver
 pub const DEFAULT_DNS_NAME_SERVER: &str = "1.1.1.1:853/cloudflare-dns.com";
@@ -0,0 +1,290 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Port mapping
+//!
+//! Opens the TCP listener port on the local router using UPnP or, failing that, NAT-PMP so that nodes behind a NAT
+//! are reachable by other peers. The mapping is renewed before its lease expires and the discovered external address
+//! is added to the node's public addresses.
+
+mod error;
+pub use error::PortMappingError;
+
+mod natpmp;
+mod upnp;
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
+    time::Duration,
+};
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+use tari_comms::{
+    multiaddr::{multiaddr, Multiaddr, Protocol},
+    peer_manager::NodeIdentity,
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::{net::UdpSocket, task::JoinHandle, time};
+use upnp::Gateway;
+
+const LOG_TARGET: &str = "p2p::port_mapping";
+/// How long to wait before retrying after the port could not be mapped
+const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAPPING_DESCRIPTION: &str = "Tari";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortMappingConfig {
+    /// Attempt to open the listener port on the router using UPnP or NAT-PMP. Default: false
+    pub enabled: bool,
+    /// The lease duration requested for the port mapping. The mapping is renewed halfway through the lease.
+    #[serde(with = "serializers::seconds")]
+    pub lease_duration: Duration,
+    /// The address of the NAT-PMP gateway. If not set, the UPnP gateway address is used if one was discovered,
+    /// otherwise the first address of the local subnet (e.g. 192.168.1.1) is assumed.
+    pub natpmp_gateway_address: Option<Ipv4Addr>,
+}
+
+impl Default for PortMappingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lease_duration: Duration::from_secs(60 * 60),
+            natpmp_gateway_address: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MappingMethod {
+    Upnp(Gateway),
+    NatPmp(Ipv4Addr),
+}
+
+#[derive(Debug, Clone)]
+struct PortMapping {
+    method: MappingMethod,
+    external_address: Multiaddr,
+    external_port: u16,
+    lease_duration: Duration,
+}
+
+/// Maintains a port mapping for the TCP listener port until shutdown
+pub struct PortMapper {
+    config: PortMappingConfig,
+    internal_port: u16,
+    node_identity: Arc<NodeIdentity>,
+    shutdown_signal: ShutdownSignal,
+    current_mapping: Option<PortMapping>,
+}
+
+impl PortMapper {
+    pub fn new(
+        config: PortMappingConfig,
+        internal_port: u16,
+        node_identity: Arc<NodeIdentity>,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            internal_port,
+            node_identity,
+            shutdown_signal,
+            current_mapping: None,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(mut self) {
+        loop {
+            let renew_in = match self.map_port().await {
+                Ok(mapping) => {
+                    let renew_in = mapping.lease_duration / 2;
+                    self.set_current_mapping(mapping);
+                    renew_in
+                },
+                Err(err) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to map port {} using UPnP or NAT-PMP: {}. Retrying in {:.0?}",
+                        self.internal_port,
+                        err,
+                        RETRY_INTERVAL
+                    );
+                    RETRY_INTERVAL
+                },
+            };
+
+            tokio::select! {
+                _ = time::sleep(renew_in) => {},
+                _ = self.shutdown_signal.wait() => break,
+            }
+        }
+
+        if let Some(mapping) = self.current_mapping.take() {
+            if let Err(err) = self.unmap_port(&mapping).await {
+                debug!(target: LOG_TARGET, "Failed to remove port mapping on shutdown: {}", err);
+            }
+        }
+    }
+
+    async fn map_port(&self) -> Result<PortMapping, PortMappingError> {
+        let upnp_gateway = match Gateway::search().await {
+            Ok(gateway) => match self.map_port_upnp(&gateway).await {
+                Ok(mapping) => return Ok(mapping),
+                Err(err) => {
+                    debug!(target: LOG_TARGET, "UPnP port mapping failed: {}. Trying NAT-PMP", err);
+                    Some(gateway.ip())
+                },
+            },
+            Err(err) => {
+                debug!(target: LOG_TARGET, "UPnP gateway discovery failed: {}. Trying NAT-PMP", err);
+                None
+            },
+        };
+
+        let gateway = match self.config.natpmp_gateway_address.or(upnp_gateway) {
+            Some(gateway) => gateway,
+            None => guess_gateway_address().await?,
+        };
+        self.map_port_natpmp(gateway).await
+    }
+
+    async fn map_port_upnp(&self, gateway: &Gateway) -> Result<PortMapping, PortMappingError> {
+        let local_ip = local_address_for(gateway.ip()).await?;
+        gateway
+            .add_port(
+                self.internal_port,
+                SocketAddrV4::new(local_ip, self.internal_port),
+                self.config.lease_duration,
+                MAPPING_DESCRIPTION,
+            )
+            .await?;
+        let external_ip = gateway.external_address().await?;
+        Ok(PortMapping {
+            method: MappingMethod::Upnp(gateway.clone()),
+            external_address: multiaddr!(Ip4(external_ip), Tcp(self.internal_port)),
+            external_port: self.internal_port,
+            lease_duration: self.config.lease_duration,
+        })
+    }
+
+    async fn map_port_natpmp(&self, gateway: Ipv4Addr) -> Result<PortMapping, PortMappingError> {
+        let (external_port, lease_duration) = natpmp::map_tcp_port(
+            gateway,
+            self.internal_port,
+            self.internal_port,
+            self.config.lease_duration,
+        )
+        .await?;
+        let external_ip = natpmp::external_address(gateway).await?;
+        Ok(PortMapping {
+            method: MappingMethod::NatPmp(gateway),
+            external_address: multiaddr!(Ip4(external_ip), Tcp(external_port)),
+            external_port,
+            lease_duration,
+        })
+    }
+
+    async fn unmap_port(&self, mapping: &PortMapping) -> Result<(), PortMappingError> {
+        match mapping.method {
+            MappingMethod::Upnp(ref gateway) => gateway.remove_port(mapping.external_port).await,
+            MappingMethod::NatPmp(gateway) => natpmp::unmap_tcp_port(gateway, self.internal_port).await,
+        }
+    }
+
+    /// Adds the external address of the mapping to the node's public addresses, replacing the address of the previous
+    /// mapping if it changed
+    fn set_current_mapping(&mut self, mapping: PortMapping) {
+        let previous_address = self.current_mapping.take().map(|m| m.external_address);
+        if previous_address.as_ref() != Some(&mapping.external_address) {
+            if is_private_address(&mapping.external_address) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Port mapped, but the gateway's external address '{}' is not public. The node may be behind more \
+                     than one NAT.",
+                    mapping.external_address
+                );
+            } else {
+                let mut addresses = self.node_identity.public_addresses();
+                addresses.retain(|addr| Some(addr) != previous_address.as_ref());
+                if !addresses.contains(&mapping.external_address) {
+                    addresses.push(mapping.external_address.clone());
+                }
+                self.node_identity.set_public_addresses(addresses);
+                info!(
+                    target: LOG_TARGET,
+                    "Port {} mapped. Public address is '{}'", self.internal_port, mapping.external_address
+                );
+            }
+        }
+        self.current_mapping = Some(mapping);
+    }
+}
+
+fn is_private_address(addr: &Multiaddr) -> bool {
+    match addr.iter().next() {
+        Some(Protocol::Ip4(ip)) => ip.is_private() || ip.is_loopback() || ip.is_unspecified(),
+        _ => false,
+    }
+}
+
+/// Returns the local IPv4 address of the interface used to reach the given address
+async fn local_address_for(addr: Ipv4Addr) -> Result<Ipv4Addr, PortMappingError> {
+    // Connecting a UDP socket does not send any packets, it only selects the route
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect((addr, 9)).await?;
+    match socket.local_addr()? {
+        SocketAddr::V4(local) => Ok(*local.ip()),
+        SocketAddr::V6(_) => Err(PortMappingError::InvalidResponse(
+            "local address is not an IPv4 address".to_string(),
+        )),
+    }
+}
+
+/// Assumes the gateway is the first address of the local /24 subnet, which is the default for most home routers
+async fn guess_gateway_address() -> Result<Ipv4Addr, PortMappingError> {
+    let local_ip = local_address_for(Ipv4Addr::new(192, 0, 2, 1)).await?;
+    let [a, b, c, _] = local_ip.octets();
+    Ok(Ipv4Addr::new(a, b, c, 1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_detects_private_addresses() {
+        assert!(is_private_address(&multiaddr!(Ip4([192, 168, 1, 2]), Tcp(18189u16))));
+        assert!(is_private_address(&multiaddr!(Ip4([10, 0, 0, 1]), Tcp(18189u16))));
+        assert_eq!(
+            is_private_address(&multiaddr!(Ip4([203, 0, 113, 7]), Tcp(18189u16))),
+            false
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A minimal NAT-PMP client ([RFC 6886](https://datatracker.ietf.org/doc/html/rfc6886)).
+
+use std::{convert::TryFrom, net::Ipv4Addr, time::Duration};
+
+use tokio::{net::UdpSocket, time};
+
+use crate::port_mapping::PortMappingError;
+
+const NATPMP_PORT: u16 = 5351;
+const NATPMP_VERSION: u8 = 0;
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+const OP_MAP_TCP: u8 = 2;
+/// Responses use the request opcode plus 128
+const RESPONSE_OP_OFFSET: u8 = 128;
+const EXTERNAL_ADDRESS_RESPONSE_LEN: usize = 12;
+const MAP_RESPONSE_LEN: usize = 16;
+/// The request is retransmitted with a doubling timeout starting at this value, as per the RFC
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+const MAX_ATTEMPTS: usize = 5;
+
+/// Requests the external IPv4 address of the gateway
+pub async fn external_address(gateway: Ipv4Addr) -> Result<Ipv4Addr, PortMappingError> {
+    let resp = request(
+        gateway,
+        &[NATPMP_VERSION, OP_EXTERNAL_ADDRESS],
+        EXTERNAL_ADDRESS_RESPONSE_LEN,
+    )
+    .await?;
+    Ok(Ipv4Addr::new(resp[8], resp[9], resp[10], resp[11]))
+}
+
+/// Maps the internal TCP port to the external port on the gateway. Returns the external port and lifetime that the
+/// gateway actually assigned, which may differ from those requested.
+pub async fn map_tcp_port(
+    gateway: Ipv4Addr,
+    internal_port: u16,
+    external_port: u16,
+    lifetime: Duration,
+) -> Result<(u16, Duration), PortMappingError> {
+    let lifetime = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+    let mut req = [0u8; 12];
+    req[0] = NATPMP_VERSION;
+    req[1] = OP_MAP_TCP;
+    req[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    req[6..8].copy_from_slice(&external_port.to_be_bytes());
+    req[8..12].copy_from_slice(&lifetime.to_be_bytes());
+
+    let resp = request(gateway, &req, MAP_RESPONSE_LEN).await?;
+    let mapped_port = u16::from_be_bytes([resp[10], resp[11]]);
+    let lifetime = u32::from_be_bytes([resp[12], resp[13], resp[14], resp[15]]);
+    Ok((mapped_port, Duration::from_secs(u64::from(lifetime))))
+}
+
+/// Removes the mapping for the internal TCP port
+pub async fn unmap_tcp_port(gateway: Ipv4Addr, internal_port: u16) -> Result<(), PortMappingError> {
+    map_tcp_port(gateway, internal_port, 0, Duration::ZERO).await?;
+    Ok(())
+}
+
+async fn request(gateway: Ipv4Addr, req: &[u8], response_len: usize) -> Result<Vec<u8>, PortMappingError> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect((gateway, NATPMP_PORT)).await?;
+    let mut timeout = INITIAL_TIMEOUT;
+    let mut buf = [0u8; MAP_RESPONSE_LEN];
+    for _ in 0..MAX_ATTEMPTS {
+        socket.send(req).await?;
+        if let Ok(result) = time::timeout(timeout, socket.recv(&mut buf)).await {
+            let n = result?;
+            return parse_response(&buf[..n], req[1], response_len).map(<[u8]>::to_vec);
+        }
+        timeout *= 2;
+    }
+    Err(PortMappingError::Timeout)
+}
+
+fn parse_response(resp: &[u8], op: u8, response_len: usize) -> Result<&[u8], PortMappingError> {
+    if resp.len() < response_len {
+        return Err(PortMappingError::InvalidResponse(format!(
+            "NAT-PMP response was {} bytes, expected {}",
+            resp.len(),
+            response_len
+        )));
+    }
+    if resp[0] != NATPMP_VERSION || resp[1] != op + RESPONSE_OP_OFFSET {
+        return Err(PortMappingError::InvalidResponse(format!(
+            "unexpected NAT-PMP version {} or opcode {}",
+            resp[0], resp[1]
+        )));
+    }
+    let result_code = u16::from_be_bytes([resp[2], resp[3]]);
+    if result_code != 0 {
+        return Err(PortMappingError::GatewayRefused(format!(
+            "NAT-PMP result code {}",
+            result_code
+        )));
+    }
+    Ok(&resp[..response_len])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_map_response() {
+        let resp = [0, 130, 0, 0, 0, 0, 1, 0, 0x46, 0xdd, 0x46, 0xde, 0, 0, 0x0e, 0x10];
+        let parsed = parse_response(&resp, OP_MAP_TCP, MAP_RESPONSE_LEN).unwrap();
+        assert_eq!(u16::from_be_bytes([parsed[10], parsed[11]]), 18142);
+
+        // Not authorized
+        let mut refused = resp;
+        refused[3] = 2;
+        assert!(matches!(
+            parse_response(&refused, OP_MAP_TCP, MAP_RESPONSE_LEN),
+            Err(PortMappingError::GatewayRefused(_))
+        ));
+        // Response to a different request
+        parse_response(&resp, OP_EXTERNAL_ADDRESS, EXTERNAL_ADDRESS_RESPONSE_LEN).unwrap_err();
+        // Truncated
+        parse_response(&resp[..12], OP_MAP_TCP, MAP_RESPONSE_LEN).unwrap_err();
+    }
+}
@@ -0,0 +1,396 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A minimal UPnP internet gateway device (IGD) client, supporting only what is needed to map a TCP port.
+
+use std::{
+    net::{Ipv4Addr, SocketAddrV4},
+    str::FromStr,
+    time::Duration,
+};
+
+use log::*;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket},
+    time,
+    time::Instant,
+};
+
+use crate::port_mapping::PortMappingError;
+
+const LOG_TARGET: &str = "p2p::port_mapping::upnp";
+
+const SSDP_MULTICAST_ADDR: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(239, 255, 255, 250), 1900);
+const SSDP_SEARCH_REQUEST: &str = "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nST: \
+                                   urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\nMAN: \
+                                   \"ssdp:discover\"\r\nMX: 2\r\n\r\n";
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_HTTP_RESPONSE_SIZE: u64 = 256 * 1024;
+const WAN_SERVICE_TYPES: [&str; 2] = [
+    "urn:schemas-upnp-org:service:WANIPConnection:",
+    "urn:schemas-upnp-org:service:WANPPPConnection:",
+];
+
+/// The WAN connection service of a UPnP internet gateway device
+#[derive(Debug, Clone)]
+pub struct Gateway {
+    addr: SocketAddrV4,
+    control_path: String,
+    service_type: String,
+}
+
+impl Gateway {
+    /// Searches the local network for an internet gateway device using SSDP
+    pub async fn search() -> Result<Self, PortMappingError> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        socket
+            .send_to(SSDP_SEARCH_REQUEST.as_bytes(), SSDP_MULTICAST_ADDR)
+            .await?;
+        let deadline = Instant::now() + SEARCH_TIMEOUT;
+        let mut buf = [0u8; 2048];
+        loop {
+            let (n, _) = time::timeout_at(deadline, socket.recv_from(&mut buf))
+                .await
+                .map_err(|_| PortMappingError::NoGatewayFound)??;
+            let response = String::from_utf8_lossy(&buf[..n]);
+            if let Some(location) = parse_ssdp_location(&response) {
+                match Self::from_location(location).await {
+                    Ok(gateway) => return Ok(gateway),
+                    Err(err) => debug!(target: LOG_TARGET, "Ignoring UPnP device at '{}': {}", location, err),
+                }
+            }
+        }
+    }
+
+    async fn from_location(location: &str) -> Result<Self, PortMappingError> {
+        let (addr, path) = parse_http_url(location)?;
+        let request = format!("GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, addr);
+        let description = http_request(addr, &request).await?;
+        let (service_type, control_url) = find_wan_service(&description).ok_or_else(|| {
+            PortMappingError::InvalidResponse("device does not have a WAN connection service".to_string())
+        })?;
+        let (addr, control_path) = if control_url.starts_with("http://") {
+            parse_http_url(control_url)?
+        } else if control_url.starts_with('/') {
+            (addr, control_url.to_string())
+        } else {
+            (addr, format!("/{}", control_url))
+        };
+
+        Ok(Self {
+            addr,
+            control_path,
+            service_type: service_type.to_string(),
+        })
+    }
+
+    pub fn ip(&self) -> Ipv4Addr {
+        *self.addr.ip()
+    }
+
+    pub async fn external_address(&self) -> Result<Ipv4Addr, PortMappingError> {
+        let response = self.soap_request("GetExternalIPAddress", "").await?;
+        xml_text(&response, "NewExternalIPAddress")
+            .and_then(|ip| Ipv4Addr::from_str(ip.trim()).ok())
+            .ok_or_else(|| PortMappingError::InvalidResponse("invalid external IP address".to_string()))
+    }
+
+    pub async fn add_port(
+        &self,
+        external_port: u16,
+        internal_addr: SocketAddrV4,
+        lease_duration: Duration,
+        description: &str,
+    ) -> Result<(), PortMappingError> {
+        let args = format!(
+            "<NewRemoteHost></NewRemoteHost><NewExternalPort>{}</NewExternalPort><NewProtocol>TCP</\
+             NewProtocol><NewInternalPort>{}</NewInternalPort><NewInternalClient>{}</NewInternalClient><NewEnabled>1</\
+             NewEnabled><NewPortMappingDescription>{}</NewPortMappingDescription><NewLeaseDuration>{}</\
+             NewLeaseDuration>",
+            external_port,
+            internal_addr.port(),
+            internal_addr.ip(),
+            description,
+            lease_duration.as_secs()
+        );
+        self.soap_request("AddPortMapping", &args).await?;
+        Ok(())
+    }
+
+    pub async fn remove_port(&self, external_port: u16) -> Result<(), PortMappingError> {
+        let args = format!(
+            "<NewRemoteHost></NewRemoteHost><NewExternalPort>{}</NewExternalPort><NewProtocol>TCP</NewProtocol>",
+            external_port
+        );
+        self.soap_request("DeletePortMapping", &args).await?;
+        Ok(())
+    }
+
+    async fn soap_request(&self, action: &str, args: &str) -> Result<String, PortMappingError> {
+        let body = format!(
+            "<?xml version=\"1.0\"?><s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+             s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body><u:{action} \
+             xmlns:u=\"{service}\">{args}</u:{action}></s:Body></s:Envelope>",
+            action = action,
+            service = self.service_type,
+            args = args
+        );
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/xml; charset=\"utf-8\"\r\nSOAPAction: \
+             \"{}#{}\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.control_path,
+            self.addr,
+            self.service_type,
+            action,
+            body.len(),
+            body
+        );
+        http_request(self.addr, &request).await
+    }
+}
+
+/// Sends an HTTP request and returns the body of a successful response
+async fn http_request(addr: SocketAddrV4, request: &str) -> Result<String, PortMappingError> {
+    let response = time::timeout(HTTP_TIMEOUT, async {
+        let mut socket = TcpStream::connect(addr).await?;
+        socket.write_all(request.as_bytes()).await?;
+        let mut response = Vec::new();
+        socket.take(MAX_HTTP_RESPONSE_SIZE).read_to_end(&mut response).await?;
+        Ok::<_, PortMappingError>(response)
+    })
+    .await
+    .map_err(|_| PortMappingError::Timeout)??;
+
+    parse_http_response(&String::from_utf8_lossy(&response))
+}
+
+fn parse_http_response(response: &str) -> Result<String, PortMappingError> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| PortMappingError::InvalidResponse("malformed HTTP response".to_string()))?;
+    let mut lines = head.lines();
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .ok_or_else(|| PortMappingError::InvalidResponse("missing HTTP status".to_string()))?;
+    let is_chunked = lines.any(|line| {
+        line.split_once(':').map_or(false, |(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding") && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    let body = if is_chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_string()
+    };
+
+    if status != "200" {
+        let reason = xml_text(&body, "errorDescription").unwrap_or(status);
+        return Err(PortMappingError::GatewayRefused(reason.to_string()));
+    }
+    Ok(body)
+}
+
+fn decode_chunked(mut body: &str) -> Result<String, PortMappingError> {
+    let mut decoded = String::new();
+    loop {
+        let (size, rest) = body
+            .split_once("\r\n")
+            .ok_or_else(|| PortMappingError::InvalidResponse("malformed chunked body".to_string()))?;
+        let size = usize::from_str_radix(size.split(';').next().unwrap_or_default().trim(), 16)
+            .map_err(|_| PortMappingError::InvalidResponse("invalid chunk size".to_string()))?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = rest
+            .get(..size)
+            .ok_or_else(|| PortMappingError::InvalidResponse("truncated chunk".to_string()))?;
+        decoded.push_str(chunk);
+        let rest = rest.get(size..).unwrap_or_default();
+        body = rest.strip_prefix("\r\n").unwrap_or(rest);
+    }
+}
+
+/// Returns the LOCATION header of an SSDP search response
+fn parse_ssdp_location(response: &str) -> Option<&str> {
+    response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        if name.trim().eq_ignore_ascii_case("location") {
+            Some(value.trim())
+        } else {
+            None
+        }
+    })
+}
+
+/// Splits an `http://host[:port]/path` URL into the socket address and path
+fn parse_http_url(url: &str) -> Result<(SocketAddrV4, String), PortMappingError> {
+    let invalid_url = || PortMappingError::InvalidResponse(format!("unsupported URL '{}'", url));
+    let rest = url.strip_prefix("http://").ok_or_else(invalid_url)?;
+    let (host, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, "/"),
+    };
+    let addr = match host.split_once(':') {
+        Some(_) => SocketAddrV4::from_str(host).map_err(|_| invalid_url())?,
+        None => SocketAddrV4::new(Ipv4Addr::from_str(host).map_err(|_| invalid_url())?, 80),
+    };
+    Ok((addr, path.to_string()))
+}
+
+/// Finds the first WAN connection service in a device description, returning its service type and control URL
+fn find_wan_service(description: &str) -> Option<(&str, &str)> {
+    let mut rest = description;
+    while let Some(start) = rest.find("<service>") {
+        let after_start = &rest[start..];
+        let end = after_start.find("</service>")?;
+        let service = &after_start[..end];
+        if let Some(service_type) = xml_text(service, "serviceType") {
+            let service_type = service_type.trim();
+            if WAN_SERVICE_TYPES.iter().any(|t| service_type.starts_with(t)) {
+                return xml_text(service, "controlURL").map(|url| (service_type, url.trim()));
+            }
+        }
+        rest = &after_start[end..];
+    }
+    None
+}
+
+/// Returns the text of the first element with the given name, ignoring any namespace prefix
+fn xml_text<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("{}>", name);
+    let close = format!("</{}>", name);
+    let mut search_from = 0;
+    loop {
+        let index = search_from + xml[search_from..].find(&open)?;
+        let tag_start = xml[..index].rfind('<')?;
+        let prefix = &xml[tag_start + 1..index];
+        // Match `<name>` and `<ns:name>`, but not `</name>` or `<othername>`
+        if prefix.is_empty() || (prefix.ends_with(':') && !prefix.starts_with('/')) {
+            let text_start = index + open.len();
+            let text_end = text_start +
+                xml[text_start..].find(&close).or_else(|| {
+                    let ns_close = format!("</{}{}", prefix, open);
+                    xml[text_start..].find(&ns_close)
+                })?;
+            return Some(&xml[text_start..text_end]);
+        }
+        search_from = index + open.len();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DESCRIPTION: &str = r#"<?xml version="1.0"?>
+<root xmlns="urn:schemas-upnp-org:device-1-0">
+  <device>
+    <serviceList>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>
+        <controlURL>/ctl/L3F</controlURL>
+      </service>
+      <service>
+        <serviceType>urn:schemas-upnp-org:service:WANIPConnection:2</serviceType>
+        <controlURL>/ctl/IPConn</controlURL>
+      </service>
+    </serviceList>
+  </device>
+</root>"#;
+
+    #[test]
+    fn it_parses_the_ssdp_location() {
+        let response =
+            "HTTP/1.1 200 OK\r\nCACHE-CONTROL: max-age=120\r\nLocation: http://192.168.1.1:5000/rootDesc.xml\r\nST: \
+             urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+        assert_eq!(
+            parse_ssdp_location(response),
+            Some("http://192.168.1.1:5000/rootDesc.xml")
+        );
+        assert_eq!(parse_ssdp_location("HTTP/1.1 200 OK\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn it_parses_http_urls() {
+        let (addr, path) = parse_http_url("http://192.168.1.1:5000/rootDesc.xml").unwrap();
+        assert_eq!(addr, "192.168.1.1:5000".parse().unwrap());
+        assert_eq!(path, "/rootDesc.xml");
+
+        let (addr, path) = parse_http_url("http://10.0.0.1").unwrap();
+        assert_eq!(addr, "10.0.0.1:80".parse().unwrap());
+        assert_eq!(path, "/");
+
+        parse_http_url("https://10.0.0.1/desc.xml").unwrap_err();
+        parse_http_url("http://router.local/desc.xml").unwrap_err();
+    }
+
+    #[test]
+    fn it_finds_the_wan_service() {
+        assert_eq!(
+            find_wan_service(DESCRIPTION),
+            Some(("urn:schemas-upnp-org:service:WANIPConnection:2", "/ctl/IPConn"))
+        );
+        assert_eq!(find_wan_service("<root></root>"), None);
+    }
+
+    #[test]
+    fn it_extracts_xml_text() {
+        let response = r#"<s:Envelope><s:Body><u:GetExternalIPAddressResponse xmlns:u="urn:x">
+            <NewExternalIPAddress>203.0.113.7</NewExternalIPAddress></u:GetExternalIPAddressResponse></s:Body>
+            </s:Envelope>"#;
+        assert_eq!(xml_text(response, "NewExternalIPAddress"), Some("203.0.113.7"));
+        assert_eq!(
+            xml_text("<a:errorDescription>Conflict</a:errorDescription>", "errorDescription"),
+            Some("Conflict")
+        );
+        assert_eq!(
+            xml_text(
+                "<xNewExternalIPAddress>1</xNewExternalIPAddress>",
+                "NewExternalIPAddress"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn it_parses_http_responses() {
+        let body = parse_http_response("HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nbody").unwrap();
+        assert_eq!(body, "body");
+
+        let body = parse_http_response(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nbo\r\n\r\n2\r\ndy\r\n0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(body, "bo\r\ndy");
+
+        let err = parse_http_response(
+            "HTTP/1.1 500 Internal Server \
+             Error\r\n\r\n<UPnPError><errorCode>718</errorCode><errorDescription>ConflictInMappingEntry</\
+             errorDescription></UPnPError>",
+        )
+        .unwrap_err();
+        assert!(matches!(err, PortMappingError::GatewayRefused(reason) if reason == "ConflictInMappingEntry"));
+    }
+}
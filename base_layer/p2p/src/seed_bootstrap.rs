@@ -0,0 +1,337 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Seed peer bootstrapping
+//!
+//! Seed peers are resolved from the configured DNS seeds, optionally validated with DNSSEC. Peers from a successful
+//! resolution are cached on disk, so that if DNS resolution later fails the cached peers are used instead. If neither
+//! DNS nor the cache yields any peers, the node falls back to the statically configured peer seeds.
+
+use std::{
+    fs,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant, SystemTime},
+};
+
+use futures::future;
+use log::*;
+use tari_comms::peer_manager::Peer;
+use tari_service_framework::ServiceInitializationError;
+
+use crate::{
+    peer_seeds::{DnsSeedResolver, SeedPeer},
+    PeerSeedsConfig,
+};
+
+const LOG_TARGET: &str = "p2p::seed_bootstrap";
+
+/// The name of the file, in the peer datastore directory, in which resolved DNS seed peers are cached
+pub const DNS_SEEDS_CACHE_FILE: &str = "dns_seeds_cache.txt";
+
+pub struct SeedBootstrap {
+    config: PeerSeedsConfig,
+    cache_file: PathBuf,
+}
+
+impl SeedBootstrap {
+    pub fn new(config: PeerSeedsConfig, cache_file: PathBuf) -> Self {
+        Self { config, cache_file }
+    }
+
+    /// Returns the seed peers to add to the peer list. These are the DNS seed peers (or the cached DNS seed peers if
+    /// resolution fails) together with the statically configured peer seeds.
+    pub async fn bootstrap(&self) -> Result<Vec<Peer>, ServiceInitializationError> {
+        self.bootstrap_with(resolve_dns_seeds(&self.config)).await
+    }
+
+    /// Bootstraps the seed peers using the given DNS seed resolution, which is only awaited if DNS seeds are configured
+    async fn bootstrap_with<F>(&self, resolve_dns_seeds: F) -> Result<Vec<Peer>, ServiceInitializationError>
+    where F: Future<Output = Result<Vec<SeedPeer>, ServiceInitializationError>> {
+        let static_peers = parse_seed_peers(&self.config.peer_seeds)?;
+        if self.config.dns_seeds.is_empty() {
+            debug!(target: LOG_TARGET, "No DNS Seeds configured");
+            return Ok(static_peers);
+        }
+
+        let mut seeds = match resolve_dns_seeds.await {
+            Ok(seeds) => seeds,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Failed to resolve DNS seeds: {}", err);
+                Vec::new()
+            },
+        };
+
+        if seeds.is_empty() {
+            seeds = self.load_cache();
+            if !seeds.is_empty() {
+                info!(
+                    target: LOG_TARGET,
+                    "DNS seeds did not resolve, using {} cached DNS seed peer(s)",
+                    seeds.len()
+                );
+            }
+        } else if let Err(err) = self.save_cache(&seeds) {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to cache DNS seed peers in '{}': {}",
+                self.cache_file.display(),
+                err
+            );
+        }
+
+        if seeds.is_empty() {
+            warn!(
+                target: LOG_TARGET,
+                "No DNS seed peers are available, falling back to {} static peer seed(s)",
+                static_peers.len()
+            );
+        }
+
+        Ok(seeds.into_iter().map(Peer::from).chain(static_peers).collect())
+    }
+
+    fn load_cache(&self) -> Vec<SeedPeer> {
+        match read_cache(&self.cache_file, self.config.dns_seeds_cache_max_age) {
+            Ok(seeds) => seeds,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to read DNS seed cache '{}': {}",
+                    self.cache_file.display(),
+                    err
+                );
+                Vec::new()
+            },
+        }
+    }
+
+    fn save_cache(&self, seeds: &[SeedPeer]) -> io::Result<()> {
+        if self.config.dns_seeds_cache_max_age.is_zero() {
+            return Ok(());
+        }
+        if let Some(parent) = self.cache_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut contents = format!("# Peers resolved from DNS seeds {}\n", self.config.dns_seeds.join(", "));
+        for seed in seeds {
+            contents.push_str(&seed.to_string());
+            contents.push('\n');
+        }
+        // Write to a temporary file and rename it so that a partially written cache is never read
+        let tmp_file = self.cache_file.with_extension("tmp");
+        fs::write(&tmp_file, contents)?;
+        fs::rename(&tmp_file, &self.cache_file)?;
+        debug!(
+            target: LOG_TARGET,
+            "Cached {} DNS seed peer(s) in '{}'",
+            seeds.len(),
+            self.cache_file.display()
+        );
+        Ok(())
+    }
+}
+
+/// Reads the cached DNS seed peers if the cache is not older than `max_age`. Invalid entries are ignored.
+fn read_cache(path: &Path, max_age: Duration) -> io::Result<Vec<SeedPeer>> {
+    let modified = fs::metadata(path)?.modified()?;
+    let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+    if age > max_age {
+        debug!(
+            target: LOG_TARGET,
+            "DNS seed cache '{}' is stale ({:.0?} old), ignoring it",
+            path.display(),
+            age
+        );
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    let seeds = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match SeedPeer::from_str(line) {
+            Ok(seed) => Some(seed),
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Ignoring invalid cached DNS seed peer '{}': {}", line, err);
+                None
+            },
+        })
+        .collect();
+    Ok(seeds)
+}
+
+fn parse_seed_peers(peer_seeds_str: &[String]) -> Result<Vec<Peer>, ServiceInitializationError> {
+    peer_seeds_str
+        .iter()
+        .map(|s| SeedPeer::from_str(s))
+        .map(|r| r.map(Peer::from))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+async fn resolve_dns_seeds(config: &PeerSeedsConfig) -> Result<Vec<SeedPeer>, ServiceInitializationError> {
+    debug!(
+        target: LOG_TARGET,
+        "Resolving DNS seeds (NS:{}, addresses: {})...",
+        config.dns_seeds_name_server,
+        config
+            .dns_seeds
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>()
+            .join(",")
+    );
+    let start = Instant::now();
+
+    let resolver = if config.dns_seeds_use_dnssec {
+        debug!(
+            target: LOG_TARGET,
+            "Using {} to resolve DNS seeds. DNSSEC is enabled", config.dns_seeds_name_server
+        );
+        DnsSeedResolver::connect_secure(config.dns_seeds_name_server.clone()).await?
+    } else {
+        debug!(
+            target: LOG_TARGET,
+            "Using {} to resolve DNS seeds. DNSSEC is disabled", config.dns_seeds_name_server
+        );
+        DnsSeedResolver::connect(config.dns_seeds_name_server.clone()).await?
+    };
+    let resolving = config.dns_seeds.iter().map(|addr| {
+        let mut resolver = resolver.clone();
+        async move { (resolver.resolve(addr).await, addr) }
+    });
+
+    let peers = future::join_all(resolving)
+        .await
+        .into_iter()
+        // Log and ignore errors
+        .filter_map(|(result, addr)| match result {
+            Ok(peers) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Found {} peer(s) from `{}` in {:.0?}",
+                    peers.len(),
+                    addr,
+                    start.elapsed()
+                );
+                Some(peers)
+            },
+            Err(err) => {
+                warn!(target: LOG_TARGET, "DNS seed `{}` failed to resolve: {}", addr, err);
+                None
+            },
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    Ok(peers)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SEED: &str = "06e98e9c5eb52bd504836edec1878eccf12eb9f26a5fe5ec0e279423156e657a::/ip4/127.0.0.1/tcp/8000";
+    const STATIC_SEED: &str =
+        "0eefb45a4de9484eca74846a4f47d2c8d38e76be1fec63b0112bd00d297c0928::/ip4/127.0.0.1/tcp/8001";
+    const DNS_SEED: &str = "f24a6ed54362cee25c8e08e92bcd33e4d8ab2b733862948f863c982040d0d447::/ip4/127.0.0.1/tcp/8002";
+
+    fn bootstrap_with_cache(dir: &Path, max_age: Duration) -> SeedBootstrap {
+        let config = PeerSeedsConfig {
+            dns_seeds: vec!["seeds.test.local".to_string()].into(),
+            dns_seeds_cache_max_age: max_age,
+            ..Default::default()
+        };
+        SeedBootstrap::new(config, dir.join(DNS_SEEDS_CACHE_FILE))
+    }
+
+    #[test]
+    fn it_caches_dns_seed_peers() {
+        let dir = tempfile::tempdir().unwrap();
+        let bootstrap = bootstrap_with_cache(dir.path(), Duration::from_secs(60));
+        let seeds = vec![SeedPeer::from_str(SEED).unwrap()];
+        bootstrap.save_cache(&seeds).unwrap();
+        let cache_file = dir.path().join(DNS_SEEDS_CACHE_FILE);
+        let contents = fs::read_to_string(&cache_file).unwrap();
+        fs::write(&cache_file, format!("{}invalid\n", contents)).unwrap();
+
+        let cached = bootstrap.load_cache();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].to_string(), SEED);
+    }
+
+    fn failing_resolver() -> future::Ready<Result<Vec<SeedPeer>, ServiceInitializationError>> {
+        future::ready(Err(ServiceInitializationError::msg("DNS seed resolution failed")))
+    }
+
+    fn peer_public_keys(peers: &[Peer]) -> Vec<String> {
+        peers.iter().map(|p| p.public_key.to_string()).collect()
+    }
+
+    fn public_key_of(seed: &str) -> String {
+        SeedPeer::from_str(seed).unwrap().public_key.to_string()
+    }
+
+    #[tokio::test]
+    async fn it_falls_back_from_dns_to_the_cache_to_the_static_seeds() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bootstrap = bootstrap_with_cache(dir.path(), Duration::from_secs(60));
+        bootstrap.config.peer_seeds = vec![STATIC_SEED.to_string()].into();
+
+        // Nothing resolves and nothing is cached, so only the static seeds are used
+        let peers = bootstrap.bootstrap_with(failing_resolver()).await.unwrap();
+        assert_eq!(peer_public_keys(&peers), vec![public_key_of(STATIC_SEED)]);
+
+        // The cached peers are used ahead of the static seeds when DNS fails
+        bootstrap.save_cache(&[SeedPeer::from_str(SEED).unwrap()]).unwrap();
+        let peers = bootstrap.bootstrap_with(failing_resolver()).await.unwrap();
+        assert_eq!(peer_public_keys(&peers), vec![
+            public_key_of(SEED),
+            public_key_of(STATIC_SEED)
+        ]);
+
+        // Resolved peers take precedence over the cache, and replace it
+        let resolved = vec![SeedPeer::from_str(DNS_SEED).unwrap()];
+        let peers = bootstrap.bootstrap_with(future::ok(resolved)).await.unwrap();
+        assert_eq!(peer_public_keys(&peers), vec![
+            public_key_of(DNS_SEED),
+            public_key_of(STATIC_SEED)
+        ]);
+        let cached = bootstrap.load_cache();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].to_string(), DNS_SEED);
+    }
+
+    #[test]
+    fn it_ignores_a_missing_or_disabled_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let bootstrap = bootstrap_with_cache(dir.path(), Duration::ZERO);
+        assert!(bootstrap.load_cache().is_empty());
+        bootstrap.save_cache(&[SeedPeer::from_str(SEED).unwrap()]).unwrap();
+        assert!(!dir.path().join(DNS_SEEDS_CACHE_FILE).exists());
+    }
+}
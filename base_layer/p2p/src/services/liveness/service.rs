@@ -203,6 +203,11 @@ where
                 }
 
                 let maybe_latency = self.state.record_pong(ping_pong_msg.nonce, &node_id);
+                if let Some(latency) = maybe_latency {
+                    if let Err(err) = self.connectivity.record_peer_rtt(node_id.clone(), latency) {
+                        debug!(target: LOG_TARGET, "Unable to record RTT for peer '{}': {}", node_id, err);
+                    }
+                }
                 debug!(
                     target: LOG_TARGET,
                     "Received pong from peer '{}' with useragent '{}'. {} (Trace: {})",
@@ -518,6 +523,7 @@ mod test {
                 flags: Default::default(),
                 message_tag: MessageTag::new(),
                 expires: None,
+                saf_class: Default::default(),
             },
             authenticated_origin: None,
             source_peer,
@@ -69,6 +69,7 @@ pub fn make_dht_header(trace: MessageTag) -> DhtMessageHeader {
         flags: DhtMessageFlags::NONE,
         message_tag: trace,
         expires: None,
+        saf_class: Default::default(),
     }
 }
 
@@ -19,9 +19,14 @@
 //  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-use std::{num::NonZeroU16, sync::Arc};
+#[cfg(feature = "i2p")]
+use std::path::PathBuf;
+use std::{num::NonZeroU16, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers::optional_seconds;
+#[cfg(feature = "i2p")]
+use tari_comms::transports::I2pConfig;
 use tari_comms::{
     multiaddr::Multiaddr,
     socks,
@@ -31,7 +36,12 @@ use tari_comms::{
     utils::multiaddr::multiaddr_to_socketaddr,
 };
 
-use crate::{initialization::CommsInitializationError, SocksAuthentication, TorControlAuthentication};
+use crate::{
+    initialization::CommsInitializationError,
+    port_mapping::PortMappingConfig,
+    SocksAuthentication,
+    TorControlAuthentication,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(deny_unknown_fields)]
@@ -42,6 +52,8 @@ pub struct TransportConfig {
     pub tor: TorTransportConfig,
     pub socks: Socks5TransportConfig,
     pub memory: MemoryTransportConfig,
+    #[cfg(feature = "i2p")]
+    pub i2p: I2pTransportConfig,
 }
 
 impl TransportConfig {
@@ -81,6 +93,15 @@ impl TransportConfig {
         }
     }
 
+    #[cfg(feature = "i2p")]
+    pub fn new_i2p(config: I2pTransportConfig) -> Self {
+        Self {
+            transport_type: TransportType::I2p,
+            i2p: config,
+            ..Default::default()
+        }
+    }
+
     pub fn is_tor(&self) -> bool {
         matches!(self.transport_type, TransportType::Tor)
     }
@@ -99,6 +120,10 @@ pub enum TransportType {
     Tor,
     /// Use a SOCKS5 proxy transport. This transport allows any addresses supported by the proxy.
     Socks5,
+    /// Use the SAM bridge of an I2P router to join the Tari network over I2P. This transport can only connect to I2P
+    /// addresses.
+    #[cfg(feature = "i2p")]
+    I2p,
 }
 
 impl Default for TransportType {
@@ -118,6 +143,8 @@ pub struct TcpTransportConfig {
     pub tor_socks_address: Option<Multiaddr>,
     /// Optional tor SOCKS proxy authentication
     pub tor_socks_auth: SocksAuthentication,
+    /// Automatic mapping of the listener port on the local router using UPnP or NAT-PMP
+    pub port_mapping: PortMappingConfig,
 }
 
 impl Default for TcpTransportConfig {
@@ -126,6 +153,7 @@ impl Default for TcpTransportConfig {
             listener_address: "/ip4/0.0.0.0/tcp/18189".parse().unwrap(),
             tor_socks_address: None,
             tor_socks_auth: SocksAuthentication::None,
+            port_mapping: PortMappingConfig::default(),
         }
     }
 }
@@ -152,6 +180,15 @@ pub struct TorTransportConfig {
     pub forward_address: Option<Multiaddr>,
     /// If set, the listener will bind to this address instead of the forward_address.
     pub listener_address_override: Option<Multiaddr>,
+    /// Bridge lines (e.g. "obfs4 192.0.2.1:443 <fingerprint> cert=... iat-mode=0") that the tor proxy uses to reach
+    /// the tor network. Leave empty to connect to the tor network directly.
+    pub bridges: Vec<String>,
+    /// Pluggable transport plugins required by the configured bridges (e.g. "obfs4 exec /usr/bin/obfs4proxy").
+    pub client_transport_plugins: Vec<String>,
+    /// If set, a new onion address is created on this interval (in seconds) and connected peers are reconnected so
+    /// that they learn the new address. The previous address is removed after a short grace period.
+    #[serde(with = "optional_seconds")]
+    pub onion_rotation_interval: Option<Duration>,
     /// The tor identity to use to create the hidden service. If None, a new one will be generated.
     #[serde(skip)]
     pub identity: Option<TorIdentity>,
@@ -199,6 +236,9 @@ impl Default for TorTransportConfig {
             proxy_bypass_for_outbound_tcp: false,
             forward_address: None,
             listener_address_override: None,
+            bridges: vec![],
+            client_transport_plugins: vec![],
+            onion_rotation_interval: None,
             identity: None,
         }
     }
@@ -243,3 +283,42 @@ impl Default for MemoryTransportConfig {
         }
     }
 }
+
+#[cfg(feature = "i2p")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct I2pTransportConfig {
+    /// The address of the SAM bridge of the I2P router
+    pub sam_address: Multiaddr,
+    /// The ID of the SAM session. This must be unique for each application using the same I2P router.
+    pub session_id: String,
+    /// The I2P virtual port advertised in this node's I2P address
+    pub port: NonZeroU16,
+    /// The file in which the private key of this node's I2P destination is kept. If not set, a new I2P address is
+    /// created every time the node starts.
+    pub private_key_file: Option<PathBuf>,
+}
+
+#[cfg(feature = "i2p")]
+impl Default for I2pTransportConfig {
+    fn default() -> Self {
+        Self {
+            sam_address: "/ip4/127.0.0.1/tcp/7656".parse().unwrap(),
+            session_id: "tari".to_string(),
+            port: NonZeroU16::new(18141).unwrap(),
+            private_key_file: Some(PathBuf::from("i2p_private_key")),
+        }
+    }
+}
+
+#[cfg(feature = "i2p")]
+impl From<I2pTransportConfig> for I2pConfig {
+    fn from(config: I2pTransportConfig) -> Self {
+        Self {
+            sam_address: config.sam_address,
+            session_id: config.session_id,
+            port: config.port.get(),
+            private_key_file: config.private_key_file,
+        }
+    }
+}
@@ -0,0 +1 @@
+DROP TABLE atomic_swaps;
@@ -0,0 +1,18 @@
+CREATE TABLE atomic_swaps
+(
+    swap_id              BIGINT PRIMARY KEY NOT NULL,
+    role                 INTEGER            NOT NULL,
+    counterparty_chain   INTEGER            NOT NULL,
+    counterparty_address TEXT               NOT NULL,
+    amount               BIGINT             NOT NULL,
+    hash_lock            BLOB               NOT NULL,
+    pre_image            TEXT               NULL,
+    output_hash          BLOB               NOT NULL,
+    timeout_height       BIGINT             NOT NULL,
+    status               INTEGER            NOT NULL,
+    funding_tx_id        BIGINT             NULL,
+    redeem_tx_id         BIGINT             NULL,
+    refund_tx_id         BIGINT             NULL,
+    created_at           DATETIME           NOT NULL,
+    updated_at           DATETIME           NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE completed_transactions ADD payment_reference BLOB NULL;
@@ -0,0 +1,2 @@
+DROP TABLE accounts;
+ALTER TABLE outputs DROP account_index;
@@ -0,0 +1,8 @@
+CREATE TABLE accounts
+(
+    account_index BIGINT PRIMARY KEY NOT NULL,
+    name          TEXT UNIQUE        NOT NULL,
+    created_at    DATETIME           NOT NULL
+);
+
+ALTER TABLE outputs ADD account_index BIGINT NOT NULL DEFAULT 0;
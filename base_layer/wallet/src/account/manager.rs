@@ -0,0 +1,150 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use log::*;
+use tari_common_types::{tari_address::TariAddress, transaction::TxId};
+use tari_core::transactions::key_manager::TransactionKeyManagerInterface;
+use tari_script::{one_sided_payment_script, ExecutionStack};
+
+use crate::{
+    account::{
+        error::AccountError,
+        models::{account_key_branch, WalletAccount, DEFAULT_ACCOUNT_INDEX, DEFAULT_ACCOUNT_NAME},
+    },
+    output_manager_service::{
+        handle::OutputManagerHandle,
+        service::Balance,
+        storage::models::KnownOneSidedPaymentScript,
+        BalanceBreakdown,
+    },
+    storage::database::{WalletBackend, WalletDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::account";
+
+/// Creates the named accounts of the wallet and reports their addresses, balances and transactions
+#[derive(Clone)]
+pub struct AccountManager<T, TKeyManagerInterface> {
+    db: WalletDatabase<T>,
+    output_manager_service: OutputManagerHandle,
+    key_manager: TKeyManagerInterface,
+    wallet_address: TariAddress,
+}
+
+impl<T, TKeyManagerInterface> AccountManager<T, TKeyManagerInterface>
+where
+    T: WalletBackend + 'static,
+    TKeyManagerInterface: TransactionKeyManagerInterface,
+{
+    /// `wallet_address` is the address of the default account
+    pub fn new(
+        db: WalletDatabase<T>,
+        output_manager_service: OutputManagerHandle,
+        key_manager: TKeyManagerInterface,
+        wallet_address: TariAddress,
+    ) -> Self {
+        Self {
+            db,
+            output_manager_service,
+            key_manager,
+            wallet_address,
+        }
+    }
+
+    /// The named accounts of the wallet, ordered by index. The default account is not included.
+    pub fn get_accounts(&self) -> Result<Vec<WalletAccount>, AccountError> {
+        Ok(self.db.fetch_accounts()?)
+    }
+
+    /// The index of the account with the given name. An empty name or `default` selects the default account.
+    pub fn resolve_account(&self, name: &str) -> Result<u64, AccountError> {
+        if name.is_empty() || name == DEFAULT_ACCOUNT_NAME {
+            return Ok(DEFAULT_ACCOUNT_INDEX);
+        }
+        self.get_accounts()?
+            .into_iter()
+            .find(|a| a.name == name)
+            .map(|a| a.index)
+            .ok_or_else(|| AccountError::AccountNotFound(name.to_string()))
+    }
+
+    /// Creates an account with the next free index and starts scanning for one-sided payments to its address
+    pub async fn create_account(&mut self, name: String) -> Result<WalletAccount, AccountError> {
+        let name = name.trim().to_string();
+        if name.is_empty() || name == DEFAULT_ACCOUNT_NAME {
+            return Err(AccountError::InvalidAccountName(name));
+        }
+        let accounts = self.get_accounts()?;
+        if accounts.iter().any(|a| a.name == name) {
+            return Err(AccountError::AccountExists(name));
+        }
+        let index = accounts.iter().map(|a| a.index).max().unwrap_or(DEFAULT_ACCOUNT_INDEX) + 1;
+
+        let branch = account_key_branch(index);
+        self.key_manager.add_new_branch(branch.clone()).await?;
+        let script_key_id = self.key_manager.get_static_key(branch).await?;
+        let public_key = self.key_manager.get_public_key_at_key_id(&script_key_id).await?;
+        let script = one_sided_payment_script(&public_key);
+        let known_script = KnownOneSidedPaymentScript {
+            script_hash: script.as_hash::<Blake2b<U32>>()?.to_vec(),
+            script_key_id,
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        };
+        self.output_manager_service.add_known_script(known_script).await?;
+
+        let account = WalletAccount::new(index, name);
+        self.db.save_account(account.clone())?;
+        info!(target: LOG_TARGET, "Created account {} ({})", account.index, account.name);
+        Ok(account)
+    }
+
+    /// The address that receives one-sided payments into the account
+    pub async fn get_account_address(&self, index: u64) -> Result<TariAddress, AccountError> {
+        if index == DEFAULT_ACCOUNT_INDEX {
+            return Ok(self.wallet_address.clone());
+        }
+        if !self.get_accounts()?.iter().any(|a| a.index == index) {
+            return Err(AccountError::AccountNotFound(index.to_string()));
+        }
+        let script_key_id = self.key_manager.get_static_key(account_key_branch(index)).await?;
+        let public_key = self.key_manager.get_public_key_at_key_id(&script_key_id).await?;
+        Ok(TariAddress::new(public_key, self.wallet_address.network()))
+    }
+
+    pub async fn get_balance(&mut self, index: u64) -> Result<Balance, AccountError> {
+        Ok(self.output_manager_service.get_account_balance(index).await?)
+    }
+
+    /// The account's balance broken down by confirmation depth and maturity
+    pub async fn get_balance_breakdown(&mut self, index: u64) -> Result<BalanceBreakdown, AccountError> {
+        Ok(self.output_manager_service.get_balance_breakdown(Some(index)).await?)
+    }
+
+    /// The ids of the transactions that paid into or spent from the account
+    pub async fn get_transaction_ids(&mut self, index: u64) -> Result<Vec<TxId>, AccountError> {
+        Ok(self.output_manager_service.get_account_transaction_ids(index).await?)
+    }
+}
@@ -0,0 +1,91 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use chrono::{NaiveDateTime, Utc};
+use tari_core::transactions::key_manager::TariKeyId;
+use tari_key_manager::key_manager_service::KeyId;
+
+/// The index of the implicit account that owns the outputs of the wallet identity
+pub const DEFAULT_ACCOUNT_INDEX: u64 = 0;
+/// The name under which the default account can be addressed
+pub const DEFAULT_ACCOUNT_NAME: &str = "default";
+
+const ACCOUNT_KEY_BRANCH_PREFIX: &str = "account ";
+
+/// A named account of the wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletAccount {
+    /// The account index, which selects the key manager branch of the account's receiving key
+    pub index: u64,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl WalletAccount {
+    pub fn new(index: u64, name: String) -> Self {
+        Self {
+            index,
+            name,
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+/// The key manager branch holding the receiving key of the account
+pub fn account_key_branch(index: u64) -> String {
+    format!("{}{}", ACCOUNT_KEY_BRANCH_PREFIX, index)
+}
+
+/// The account that owns outputs received with the given script key. Keys that are not on an account branch belong to
+/// the default account.
+pub fn account_index_from_key_id(key_id: &TariKeyId) -> u64 {
+    match key_id {
+        KeyId::Managed { branch, .. } => branch
+            .strip_prefix(ACCOUNT_KEY_BRANCH_PREFIX)
+            .and_then(|index| index.parse().ok())
+            .unwrap_or(DEFAULT_ACCOUNT_INDEX),
+        KeyId::Imported { .. } | KeyId::Zero => DEFAULT_ACCOUNT_INDEX,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_core::transactions::key_manager::TransactionKeyManagerBranch;
+
+    use super::*;
+
+    #[test]
+    fn it_maps_key_ids_to_accounts() {
+        let account_key = KeyId::Managed {
+            branch: account_key_branch(7),
+            index: 0,
+        };
+        assert_eq!(account_index_from_key_id(&account_key), 7);
+
+        let spend_key = KeyId::Managed {
+            branch: TransactionKeyManagerBranch::CommitmentMask.get_branch_key(),
+            index: 3,
+        };
+        assert_eq!(account_index_from_key_id(&spend_key), DEFAULT_ACCOUNT_INDEX);
+        assert_eq!(account_index_from_key_id(&KeyId::Zero), DEFAULT_ACCOUNT_INDEX);
+    }
+}
@@ -0,0 +1,86 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::{transaction::TxId, types::FixedHash};
+use tari_core::transactions::tari_amount::MicroMinotari;
+use thiserror::Error;
+
+use crate::{
+    atomic_swap::models::{AtomicSwapStatus, SwapEvent},
+    error::WalletStorageError,
+    output_manager_service::error::OutputManagerError,
+    transaction_service::error::TransactionServiceError,
+};
+
+#[derive(Debug, Error)]
+pub enum AtomicSwapError {
+    #[error("Atomic swap `{0}` not found")]
+    SwapNotFound(TxId),
+    #[error("Atomic swap `{swap_id}` cannot handle `{event}` while it is `{status}`")]
+    InvalidTransition {
+        swap_id: TxId,
+        status: AtomicSwapStatus,
+        event: SwapEvent,
+    },
+    #[error("The pre-image does not match the hash lock of atomic swap `{0}`")]
+    PreImageMismatch(TxId),
+    #[error("Atomic swap `{swap_id}` cannot be refunded before height {timeout_height} (tip is at {tip_height})")]
+    TimeoutNotReached {
+        swap_id: TxId,
+        timeout_height: u64,
+        tip_height: u64,
+    },
+    #[error("Atomic swap `{0}` was not created by this wallet and cannot be refunded")]
+    NotInitiator(TxId),
+    #[error("Atomic swap `{0}` was created by this wallet and cannot be redeemed by it")]
+    NotParticipant(TxId),
+    #[error("The HTLC output script is not a SHA-256 hash time locked contract")]
+    InvalidHtlcScript,
+    #[error("HTLC output `{0}` does not pay to this wallet")]
+    NotRecipient(FixedHash),
+    #[error("HTLC output `{0}` is locked with a different hash lock")]
+    HashLockMismatch(FixedHash),
+    #[error("HTLC output holds {actual}, but {expected} was expected")]
+    AmountMismatch {
+        expected: MicroMinotari,
+        actual: MicroMinotari,
+    },
+    #[error("HTLC output times out at height {actual}, but {expected} was expected")]
+    TimeoutMismatch { expected: u64, actual: u64 },
+    #[error(
+        "HTLC output times out at height {timeout_height}, which leaves less than {min_window} blocks to redeem it \
+         (tip is at {tip_height})"
+    )]
+    TimeoutTooSoon {
+        timeout_height: u64,
+        tip_height: u64,
+        min_window: u64,
+    },
+    #[error("Conversion error: `{0}`")]
+    ConversionError(String),
+    #[error("Storage error: `{0}`")]
+    StorageError(#[from] WalletStorageError),
+    #[error("Transaction service error: `{0}`")]
+    TransactionServiceError(#[from] TransactionServiceError),
+    #[error("Output manager error: `{0}`")]
+    OutputManagerError(#[from] OutputManagerError),
+}
@@ -0,0 +1,109 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The SHA-256 hash time locked contract (HTLC) script used for atomic swaps. The output can be spent by the
+//! recipient with the pre-image of the hash lock, or by the sender once the timeout height has been reached.
+
+use sha2::{Digest, Sha256};
+use tari_common_types::types::{FixedHash, PublicKey};
+use tari_script::{script, Opcode, TariScript};
+use tari_utilities::ByteArray;
+
+/// Returns the hash lock for `pre_image`
+pub fn hash_pre_image(pre_image: &PublicKey) -> FixedHash {
+    let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+    hash.into()
+}
+
+/// Builds an HTLC script that pays to `recipient` given the pre-image of `hash_lock`, or to `refund_key` from
+/// `timeout_height`
+pub fn sha_htlc_script(
+    hash_lock: &FixedHash,
+    recipient: &PublicKey,
+    refund_key: &PublicKey,
+    timeout_height: u64,
+) -> TariScript {
+    script!(
+        HashSha256 PushHash(Box::new(**hash_lock)) Equal IfThen
+            PushPubKey(Box::new(recipient.clone()))
+        Else
+            CheckHeightVerify(timeout_height) PushPubKey(Box::new(refund_key.clone()))
+        EndIf
+    )
+}
+
+/// The parameters of an HTLC script created by [sha_htlc_script]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HtlcParameters {
+    pub hash_lock: FixedHash,
+    pub recipient: PublicKey,
+    pub refund_key: PublicKey,
+    pub timeout_height: u64,
+}
+
+impl HtlcParameters {
+    /// Extracts the HTLC parameters from `script`, or returns `None` if it is not an HTLC script
+    pub fn from_script(script: &TariScript) -> Option<Self> {
+        match script.as_slice() {
+            [Opcode::HashSha256, Opcode::PushHash(hash_lock), Opcode::Equal, Opcode::IfThen, Opcode::PushPubKey(recipient), Opcode::Else, Opcode::CheckHeightVerify(timeout_height), Opcode::PushPubKey(refund_key), Opcode::EndIf] => {
+                Some(Self {
+                    hash_lock: FixedHash::from(**hash_lock),
+                    recipient: (**recipient).clone(),
+                    refund_key: (**refund_key).clone(),
+                    timeout_height: *timeout_height,
+                })
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    use super::*;
+
+    fn random_public_key() -> PublicKey {
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng))
+    }
+
+    #[test]
+    fn it_extracts_the_htlc_parameters() {
+        let hash_lock = hash_pre_image(&random_public_key());
+        let recipient = random_public_key();
+        let refund_key = random_public_key();
+        let script = sha_htlc_script(&hash_lock, &recipient, &refund_key, 1234);
+        assert_eq!(
+            HtlcParameters::from_script(&script),
+            Some(HtlcParameters {
+                hash_lock,
+                recipient,
+                refund_key,
+                timeout_height: 1234,
+            })
+        );
+        assert_eq!(HtlcParameters::from_script(&script!(Nop)), None);
+    }
+}
@@ -0,0 +1,345 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use log::*;
+use tari_common_types::{
+    tari_address::TariAddress,
+    transaction::{TransactionStatus, TxId},
+    types::{FixedHash, PublicKey},
+};
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    atomic_swap::{
+        error::AtomicSwapError,
+        htlc::{hash_pre_image, HtlcParameters},
+        models::{AtomicSwap, AtomicSwapStatus, CounterpartyChain, SwapEvent, SwapRole},
+    },
+    base_node_service::handle::{BaseNodeEvent, BaseNodeEventReceiver},
+    output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
+    storage::database::{WalletBackend, WalletDatabase},
+    transaction_service::{handle::TransactionServiceHandle, storage::models::WalletTransaction},
+};
+
+const LOG_TARGET: &str = "wallet::atomic_swap";
+/// The minimum number of blocks between the chain tip and the timeout of an accepted HTLC output, which leaves the
+/// participant time to see the pre-image on the counterparty chain and redeem the output before it can be refunded
+pub const MIN_REDEEM_WINDOW: u64 = 30;
+
+/// Creates, redeems and refunds the HTLC outputs of atomic swaps, and keeps track of their state in the wallet
+/// database
+#[derive(Clone)]
+pub struct AtomicSwapManager<T> {
+    db: WalletDatabase<T>,
+    transaction_service: TransactionServiceHandle,
+    output_manager_service: OutputManagerHandle,
+    wallet_address: TariAddress,
+}
+
+impl<T> AtomicSwapManager<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(
+        db: WalletDatabase<T>,
+        transaction_service: TransactionServiceHandle,
+        output_manager_service: OutputManagerHandle,
+        wallet_address: TariAddress,
+    ) -> Self {
+        Self {
+            db,
+            transaction_service,
+            output_manager_service,
+            wallet_address,
+        }
+    }
+
+    pub fn get_swap(&self, swap_id: TxId) -> Result<AtomicSwap, AtomicSwapError> {
+        self.db
+            .fetch_atomic_swap(swap_id)?
+            .ok_or(AtomicSwapError::SwapNotFound(swap_id))
+    }
+
+    pub fn get_swaps(&self) -> Result<Vec<AtomicSwap>, AtomicSwapError> {
+        Ok(self.db.fetch_atomic_swaps()?)
+    }
+
+    /// Locks `amount` in an HTLC output payable to `destination` with the pre-image of a newly generated hash lock.
+    /// The counterparty locks their side of the swap on `counterparty_chain` with the same hash lock. The HTLC is
+    /// funded from `account`, which also receives the change.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn initiate(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+        counterparty_chain: CounterpartyChain,
+        counterparty_address: String,
+        message: String,
+        account: u64,
+    ) -> Result<AtomicSwap, AtomicSwapError> {
+        let selection_criteria = UtxoSelectionCriteria {
+            account,
+            ..Default::default()
+        };
+        let (tx_id, pre_image, output) = self
+            .transaction_service
+            .send_sha_atomic_swap_transaction(destination, amount, selection_criteria, fee_per_gram, message)
+            .await?;
+        let htlc = HtlcParameters::from_script(&output.script).ok_or(AtomicSwapError::InvalidHtlcScript)?;
+        let swap = AtomicSwap::new_initiated(
+            tx_id,
+            counterparty_chain,
+            counterparty_address,
+            amount,
+            pre_image,
+            output.hash(),
+            htlc.timeout_height,
+        );
+        self.db.save_atomic_swap(swap.clone())?;
+        info!(
+            target: LOG_TARGET,
+            "Initiated atomic swap {} against {} with HTLC output {}", swap.swap_id, counterparty_chain, swap.output_hash
+        );
+        Ok(swap)
+    }
+
+    /// Records an HTLC output that the counterparty locked for this wallet, so that it can be redeemed once the
+    /// counterparty reveals the pre-image on `counterparty_chain`. The output is fetched from the base node and must
+    /// pay `amount` to this wallet with `hash_lock`, and must leave enough time to redeem it before `timeout_height`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn accept(
+        &mut self,
+        output_hash: FixedHash,
+        hash_lock: FixedHash,
+        amount: MicroMinotari,
+        timeout_height: u64,
+        counterparty_chain: CounterpartyChain,
+        counterparty_address: String,
+        tip_height: u64,
+    ) -> Result<AtomicSwap, AtomicSwapError> {
+        let (output, value) = self.output_manager_service.fetch_htlc_output(output_hash).await?;
+        let htlc = HtlcParameters::from_script(&output.script).ok_or(AtomicSwapError::InvalidHtlcScript)?;
+        if htlc.recipient != *self.wallet_address.public_key() {
+            return Err(AtomicSwapError::NotRecipient(output_hash));
+        }
+        if htlc.hash_lock != hash_lock {
+            return Err(AtomicSwapError::HashLockMismatch(output_hash));
+        }
+        if value != amount {
+            return Err(AtomicSwapError::AmountMismatch {
+                expected: amount,
+                actual: value,
+            });
+        }
+        if htlc.timeout_height != timeout_height {
+            return Err(AtomicSwapError::TimeoutMismatch {
+                expected: timeout_height,
+                actual: htlc.timeout_height,
+            });
+        }
+        if htlc.timeout_height < tip_height.saturating_add(MIN_REDEEM_WINDOW) {
+            return Err(AtomicSwapError::TimeoutTooSoon {
+                timeout_height: htlc.timeout_height,
+                tip_height,
+                min_window: MIN_REDEEM_WINDOW,
+            });
+        }
+
+        let swap = AtomicSwap::new_participating(
+            counterparty_chain,
+            counterparty_address,
+            amount,
+            hash_lock,
+            output_hash,
+            timeout_height,
+        );
+        self.db.save_atomic_swap(swap.clone())?;
+        info!(
+            target: LOG_TARGET,
+            "Accepted atomic swap {} against {} with HTLC output {}", swap.swap_id, counterparty_chain, output_hash
+        );
+        Ok(swap)
+    }
+
+    /// Claims the HTLC output of a swap this wallet participates in with the pre-image, into `account`
+    pub async fn redeem(
+        &mut self,
+        swap_id: TxId,
+        pre_image: PublicKey,
+        fee_per_gram: MicroMinotari,
+        account: u64,
+    ) -> Result<AtomicSwap, AtomicSwapError> {
+        let mut swap = self.get_swap(swap_id)?;
+        if swap.role != SwapRole::Participant {
+            return Err(AtomicSwapError::NotParticipant(swap_id));
+        }
+        if hash_pre_image(&pre_image) != swap.hash_lock {
+            return Err(AtomicSwapError::PreImageMismatch(swap_id));
+        }
+        swap.next_status(SwapEvent::RedeemSubmitted)?;
+
+        let (tx_id, _fee, amount, tx) = self
+            .output_manager_service
+            .create_claim_sha_atomic_swap_transaction(swap.output_hash, pre_image.clone(), fee_per_gram, account)
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, tx, amount, format!("Redeeming atomic swap {}", swap_id))
+            .await?;
+
+        swap.pre_image = Some(pre_image);
+        swap.redeem_tx_id = Some(tx_id);
+        swap.apply(SwapEvent::RedeemSubmitted)?;
+        self.db.save_atomic_swap(swap.clone())?;
+        info!(target: LOG_TARGET, "Redeeming atomic swap {} in transaction {}", swap_id, tx_id);
+        Ok(swap)
+    }
+
+    /// Reclaims the HTLC output of a swap initiated by this wallet into `account` once its timeout height has been
+    /// reached
+    pub async fn refund(
+        &mut self,
+        swap_id: TxId,
+        fee_per_gram: MicroMinotari,
+        tip_height: u64,
+        account: u64,
+    ) -> Result<AtomicSwap, AtomicSwapError> {
+        let mut swap = self.get_swap(swap_id)?;
+        if swap.role != SwapRole::Initiator {
+            return Err(AtomicSwapError::NotInitiator(swap_id));
+        }
+        if tip_height < swap.timeout_height {
+            return Err(AtomicSwapError::TimeoutNotReached {
+                swap_id,
+                timeout_height: swap.timeout_height,
+                tip_height,
+            });
+        }
+        if swap.status == AtomicSwapStatus::Funded {
+            swap.apply(SwapEvent::TimeoutReached)?;
+        }
+        swap.next_status(SwapEvent::RefundSubmitted)?;
+
+        let (tx_id, _fee, amount, tx) = self
+            .output_manager_service
+            .create_htlc_refund_transaction(swap.output_hash, fee_per_gram, account)
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, tx, amount, format!("Refunding atomic swap {}", swap_id))
+            .await?;
+
+        swap.refund_tx_id = Some(tx_id);
+        swap.apply(SwapEvent::RefundSubmitted)?;
+        self.db.save_atomic_swap(swap.clone())?;
+        info!(target: LOG_TARGET, "Refunding atomic swap {} in transaction {}", swap_id, tx_id);
+        Ok(swap)
+    }
+
+    /// Moves all unfinished swaps along according to the state of their transactions and the chain tip, and returns
+    /// the swaps that changed
+    pub async fn update_swaps(&mut self, tip_height: u64) -> Result<Vec<AtomicSwap>, AtomicSwapError> {
+        let mut updated = Vec::new();
+        for mut swap in self.get_swaps()?.into_iter().filter(|s| !s.status.is_final()) {
+            let event = match swap.status {
+                AtomicSwapStatus::Pending => {
+                    self.transaction_event(
+                        swap.funding_tx_id,
+                        SwapEvent::FundingConfirmed,
+                        SwapEvent::FundingCancelled,
+                    )
+                    .await?
+                },
+                AtomicSwapStatus::Funded if tip_height >= swap.timeout_height => Some(SwapEvent::TimeoutReached),
+                AtomicSwapStatus::Redeeming => {
+                    self.transaction_event(
+                        swap.redeem_tx_id,
+                        SwapEvent::RedeemConfirmed,
+                        SwapEvent::RedeemCancelled,
+                    )
+                    .await?
+                },
+                AtomicSwapStatus::Refunding => {
+                    self.transaction_event(
+                        swap.refund_tx_id,
+                        SwapEvent::RefundConfirmed,
+                        SwapEvent::RefundCancelled,
+                    )
+                    .await?
+                },
+                _ => None,
+            };
+            if let Some(event) = event {
+                debug!(target: LOG_TARGET, "Atomic swap {}: {} ({})", swap.swap_id, event, swap.status);
+                swap.apply(event)?;
+                self.db.save_atomic_swap(swap.clone())?;
+                updated.push(swap);
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Updates the swaps every time the base node service detects a new block, until the event stream is closed
+    pub async fn run_updates(mut self, mut base_node_events: BaseNodeEventReceiver) {
+        loop {
+            match base_node_events.recv().await {
+                Ok(event) => {
+                    if let BaseNodeEvent::NewBlockDetected(_hash, height) = &*event {
+                        if let Err(e) = self.update_swaps(*height).await {
+                            warn!(target: LOG_TARGET, "Failed to update atomic swaps at height {}: {}", height, e);
+                        }
+                    }
+                },
+                Err(RecvError::Lagged(n)) => {
+                    debug!(target: LOG_TARGET, "Atomic swap updates lagged {} base node event(s)", n);
+                },
+                Err(RecvError::Closed) => break,
+            }
+        }
+        debug!(target: LOG_TARGET, "Atomic swap updates stopped");
+    }
+
+    /// Returns `on_confirmed` if the transaction is confirmed, `on_cancelled` if it was cancelled or rejected, and
+    /// `None` if it is still in progress
+    async fn transaction_event(
+        &mut self,
+        tx_id: Option<TxId>,
+        on_confirmed: SwapEvent,
+        on_cancelled: SwapEvent,
+    ) -> Result<Option<SwapEvent>, AtomicSwapError> {
+        let tx_id = match tx_id {
+            Some(tx_id) => tx_id,
+            None => return Ok(None),
+        };
+        match self.transaction_service.get_any_transaction(tx_id).await? {
+            Some(WalletTransaction::Completed(tx)) => {
+                if tx.cancelled.is_some() || tx.status == TransactionStatus::Rejected {
+                    Ok(Some(on_cancelled))
+                } else if tx.status == TransactionStatus::MinedConfirmed {
+                    Ok(Some(on_confirmed))
+                } else {
+                    Ok(None)
+                }
+            },
+            _ => Ok(None),
+        }
+    }
+}
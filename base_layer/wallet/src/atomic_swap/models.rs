@@ -0,0 +1,400 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    fmt::{Display, Error, Formatter},
+};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tari_common_types::{
+    transaction::TxId,
+    types::{FixedHash, PublicKey},
+};
+use tari_core::transactions::tari_amount::MicroMinotari;
+
+use crate::atomic_swap::{error::AtomicSwapError, htlc::hash_pre_image};
+
+/// The side of the swap this wallet is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapRole {
+    /// This wallet generated the pre-image and locked Minotari in an HTLC payable to the counterparty
+    Initiator = 0,
+    /// The counterparty locked Minotari in an HTLC payable to this wallet, which is redeemed with the pre-image once
+    /// the counterparty reveals it on the other chain
+    Participant = 1,
+}
+
+impl TryFrom<i32> for SwapRole {
+    type Error = AtomicSwapError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SwapRole::Initiator),
+            1 => Ok(SwapRole::Participant),
+            v => Err(AtomicSwapError::ConversionError(format!("Invalid swap role {}", v))),
+        }
+    }
+}
+
+impl Display for SwapRole {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SwapRole::Initiator => write!(f, "Initiator"),
+            SwapRole::Participant => write!(f, "Participant"),
+        }
+    }
+}
+
+/// The chain the counterparty's side of the swap is locked on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CounterpartyChain {
+    Bitcoin = 0,
+    Monero = 1,
+}
+
+impl TryFrom<i32> for CounterpartyChain {
+    type Error = AtomicSwapError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CounterpartyChain::Bitcoin),
+            1 => Ok(CounterpartyChain::Monero),
+            v => Err(AtomicSwapError::ConversionError(format!(
+                "Invalid counterparty chain {}",
+                v
+            ))),
+        }
+    }
+}
+
+impl Display for CounterpartyChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            CounterpartyChain::Bitcoin => write!(f, "BTC"),
+            CounterpartyChain::Monero => write!(f, "XMR"),
+        }
+    }
+}
+
+/// The state of an atomic swap. `Redeemed`, `Refunded` and `Cancelled` are final.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AtomicSwapStatus {
+    /// The HTLC funding transaction has been broadcast, but is not confirmed yet
+    Pending = 0,
+    /// The HTLC output is confirmed and can be redeemed with the pre-image
+    Funded = 1,
+    /// A transaction redeeming the HTLC with the pre-image has been broadcast
+    Redeeming = 2,
+    /// The redeem transaction is confirmed
+    Redeemed = 3,
+    /// The timeout height has been reached without the HTLC being redeemed, so the initiator can reclaim it
+    Expired = 4,
+    /// A transaction refunding the HTLC to the initiator has been broadcast
+    Refunding = 5,
+    /// The refund transaction is confirmed
+    Refunded = 6,
+    /// The HTLC funding transaction was cancelled or rejected
+    Cancelled = 7,
+}
+
+impl AtomicSwapStatus {
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            AtomicSwapStatus::Redeemed | AtomicSwapStatus::Refunded | AtomicSwapStatus::Cancelled
+        )
+    }
+}
+
+impl TryFrom<i32> for AtomicSwapStatus {
+    type Error = AtomicSwapError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AtomicSwapStatus::Pending),
+            1 => Ok(AtomicSwapStatus::Funded),
+            2 => Ok(AtomicSwapStatus::Redeeming),
+            3 => Ok(AtomicSwapStatus::Redeemed),
+            4 => Ok(AtomicSwapStatus::Expired),
+            5 => Ok(AtomicSwapStatus::Refunding),
+            6 => Ok(AtomicSwapStatus::Refunded),
+            7 => Ok(AtomicSwapStatus::Cancelled),
+            v => Err(AtomicSwapError::ConversionError(format!(
+                "Invalid atomic swap status {}",
+                v
+            ))),
+        }
+    }
+}
+
+impl Display for AtomicSwapStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            AtomicSwapStatus::Pending => write!(f, "Pending"),
+            AtomicSwapStatus::Funded => write!(f, "Funded"),
+            AtomicSwapStatus::Redeeming => write!(f, "Redeeming"),
+            AtomicSwapStatus::Redeemed => write!(f, "Redeemed"),
+            AtomicSwapStatus::Expired => write!(f, "Expired"),
+            AtomicSwapStatus::Refunding => write!(f, "Refunding"),
+            AtomicSwapStatus::Refunded => write!(f, "Refunded"),
+            AtomicSwapStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// The events that move an atomic swap through its states
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapEvent {
+    FundingConfirmed,
+    FundingCancelled,
+    RedeemSubmitted,
+    RedeemConfirmed,
+    RedeemCancelled,
+    TimeoutReached,
+    RefundSubmitted,
+    RefundConfirmed,
+    RefundCancelled,
+}
+
+impl Display for SwapEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            SwapEvent::FundingConfirmed => write!(f, "FundingConfirmed"),
+            SwapEvent::FundingCancelled => write!(f, "FundingCancelled"),
+            SwapEvent::RedeemSubmitted => write!(f, "RedeemSubmitted"),
+            SwapEvent::RedeemConfirmed => write!(f, "RedeemConfirmed"),
+            SwapEvent::RedeemCancelled => write!(f, "RedeemCancelled"),
+            SwapEvent::TimeoutReached => write!(f, "TimeoutReached"),
+            SwapEvent::RefundSubmitted => write!(f, "RefundSubmitted"),
+            SwapEvent::RefundConfirmed => write!(f, "RefundConfirmed"),
+            SwapEvent::RefundCancelled => write!(f, "RefundCancelled"),
+        }
+    }
+}
+
+/// An atomic swap between Minotari, locked in an HTLC output, and a counterparty's coins on another chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomicSwap {
+    pub swap_id: TxId,
+    pub role: SwapRole,
+    pub counterparty_chain: CounterpartyChain,
+    /// The counterparty's address on the other chain, for reference
+    pub counterparty_address: String,
+    pub amount: MicroMinotari,
+    /// The SHA-256 hash of the pre-image
+    pub hash_lock: FixedHash,
+    /// Known from the start by the initiator, and by the participant once it is revealed on the other chain
+    pub pre_image: Option<PublicKey>,
+    pub output_hash: FixedHash,
+    /// The height from which the initiator can refund the HTLC
+    pub timeout_height: u64,
+    pub status: AtomicSwapStatus,
+    /// The transaction that funded the HTLC, if it was created by this wallet
+    pub funding_tx_id: Option<TxId>,
+    pub redeem_tx_id: Option<TxId>,
+    pub refund_tx_id: Option<TxId>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl AtomicSwap {
+    /// A swap initiated by this wallet, whose HTLC funding transaction `funding_tx_id` has just been broadcast
+    pub fn new_initiated(
+        funding_tx_id: TxId,
+        counterparty_chain: CounterpartyChain,
+        counterparty_address: String,
+        amount: MicroMinotari,
+        pre_image: PublicKey,
+        output_hash: FixedHash,
+        timeout_height: u64,
+    ) -> Self {
+        let now = Utc::now().naive_utc();
+        Self {
+            swap_id: funding_tx_id,
+            role: SwapRole::Initiator,
+            counterparty_chain,
+            counterparty_address,
+            amount,
+            hash_lock: hash_pre_image(&pre_image),
+            pre_image: Some(pre_image),
+            output_hash,
+            timeout_height,
+            status: AtomicSwapStatus::Pending,
+            funding_tx_id: Some(funding_tx_id),
+            redeem_tx_id: None,
+            refund_tx_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// A swap initiated by the counterparty, whose confirmed HTLC output `output_hash` pays to this wallet
+    pub fn new_participating(
+        counterparty_chain: CounterpartyChain,
+        counterparty_address: String,
+        amount: MicroMinotari,
+        hash_lock: FixedHash,
+        output_hash: FixedHash,
+        timeout_height: u64,
+    ) -> Self {
+        let now = Utc::now().naive_utc();
+        Self {
+            swap_id: TxId::new_random(),
+            role: SwapRole::Participant,
+            counterparty_chain,
+            counterparty_address,
+            amount,
+            hash_lock,
+            pre_image: None,
+            output_hash,
+            timeout_height,
+            status: AtomicSwapStatus::Funded,
+            funding_tx_id: None,
+            redeem_tx_id: None,
+            refund_tx_id: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Returns the status the swap moves to on `event`, or an error if the event is not valid in the current status
+    pub fn next_status(&self, event: SwapEvent) -> Result<AtomicSwapStatus, AtomicSwapError> {
+        match (self.status, event) {
+            (AtomicSwapStatus::Pending, SwapEvent::FundingConfirmed) => Ok(AtomicSwapStatus::Funded),
+            (AtomicSwapStatus::Pending, SwapEvent::FundingCancelled) => Ok(AtomicSwapStatus::Cancelled),
+            // The hash lock branch of the script has no time lock, so the HTLC can still be redeemed after the
+            // timeout until it is refunded
+            (AtomicSwapStatus::Funded | AtomicSwapStatus::Expired, SwapEvent::RedeemSubmitted) => {
+                Ok(AtomicSwapStatus::Redeeming)
+            },
+            (AtomicSwapStatus::Redeeming, SwapEvent::RedeemConfirmed) => Ok(AtomicSwapStatus::Redeemed),
+            (AtomicSwapStatus::Redeeming, SwapEvent::RedeemCancelled) => Ok(AtomicSwapStatus::Funded),
+            (AtomicSwapStatus::Funded, SwapEvent::TimeoutReached) => Ok(AtomicSwapStatus::Expired),
+            (AtomicSwapStatus::Expired, SwapEvent::RefundSubmitted) => Ok(AtomicSwapStatus::Refunding),
+            (AtomicSwapStatus::Refunding, SwapEvent::RefundConfirmed) => Ok(AtomicSwapStatus::Refunded),
+            (AtomicSwapStatus::Refunding, SwapEvent::RefundCancelled) => Ok(AtomicSwapStatus::Expired),
+            (status, event) => Err(AtomicSwapError::InvalidTransition {
+                swap_id: self.swap_id,
+                status,
+                event,
+            }),
+        }
+    }
+
+    /// Applies `event` to the swap. The redeem or refund transaction id must be set before a `RedeemSubmitted` or
+    /// `RefundSubmitted` event is applied.
+    pub fn apply(&mut self, event: SwapEvent) -> Result<(), AtomicSwapError> {
+        self.status = self.next_status(event)?;
+        match event {
+            SwapEvent::RedeemCancelled => self.redeem_tx_id = None,
+            SwapEvent::RefundCancelled => self.refund_tx_id = None,
+            _ => {},
+        }
+        self.updated_at = Utc::now().naive_utc();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::PrivateKey;
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    use super::*;
+
+    fn initiated_swap() -> AtomicSwap {
+        AtomicSwap::new_initiated(
+            TxId::new_random(),
+            CounterpartyChain::Bitcoin,
+            "bc1qexample".to_string(),
+            MicroMinotari(1_000_000),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            FixedHash::zero(),
+            1000,
+        )
+    }
+
+    #[test]
+    fn it_follows_the_refund_path() {
+        let mut swap = initiated_swap();
+        assert_eq!(swap.hash_lock, hash_pre_image(swap.pre_image.as_ref().unwrap()));
+        swap.apply(SwapEvent::FundingConfirmed).unwrap();
+        swap.apply(SwapEvent::TimeoutReached).unwrap();
+        swap.refund_tx_id = Some(TxId::new_random());
+        swap.apply(SwapEvent::RefundSubmitted).unwrap();
+        swap.apply(SwapEvent::RefundCancelled).unwrap();
+        assert_eq!(swap.status, AtomicSwapStatus::Expired);
+        assert_eq!(swap.refund_tx_id, None);
+        swap.apply(SwapEvent::RefundSubmitted).unwrap();
+        swap.apply(SwapEvent::RefundConfirmed).unwrap();
+        assert_eq!(swap.status, AtomicSwapStatus::Refunded);
+        assert!(swap.status.is_final());
+    }
+
+    #[test]
+    fn it_follows_the_redeem_path() {
+        let mut swap = AtomicSwap::new_participating(
+            CounterpartyChain::Monero,
+            "4Aexample".to_string(),
+            MicroMinotari(1_000_000),
+            FixedHash::zero(),
+            FixedHash::zero(),
+            1000,
+        );
+        assert_eq!(swap.status, AtomicSwapStatus::Funded);
+        swap.apply(SwapEvent::RedeemSubmitted).unwrap();
+        swap.apply(SwapEvent::RedeemConfirmed).unwrap();
+        assert_eq!(swap.status, AtomicSwapStatus::Redeemed);
+    }
+
+    #[test]
+    fn it_rejects_invalid_transitions() {
+        let mut swap = initiated_swap();
+        let err = swap.apply(SwapEvent::RefundSubmitted).unwrap_err();
+        assert!(matches!(err, AtomicSwapError::InvalidTransition {
+            status: AtomicSwapStatus::Pending,
+            ..
+        }));
+        swap.apply(SwapEvent::FundingCancelled).unwrap();
+        assert!(swap.apply(SwapEvent::FundingConfirmed).is_err());
+        assert_eq!(swap.status, AtomicSwapStatus::Cancelled);
+    }
+
+    #[test]
+    fn it_converts_to_and_from_i32() {
+        for status in 0..8 {
+            assert_eq!(AtomicSwapStatus::try_from(status).unwrap() as i32, status);
+        }
+        assert!(AtomicSwapStatus::try_from(8).is_err());
+        assert_eq!(
+            SwapRole::try_from(SwapRole::Participant as i32).unwrap(),
+            SwapRole::Participant
+        );
+        assert_eq!(
+            CounterpartyChain::try_from(CounterpartyChain::Monero as i32).unwrap(),
+            CounterpartyChain::Monero
+        );
+    }
+}
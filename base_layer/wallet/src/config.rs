@@ -32,7 +32,10 @@ use tari_common::{
     configuration::{serializers, Network, StringList},
     SubConfigPath,
 };
-use tari_common_types::{grpc_authentication::GrpcAuthentication, wallet_types::WalletType};
+use tari_common_types::{
+    grpc_authentication::{GrpcAuthentication, GrpcClientCertificate, GrpcScopedToken},
+    wallet_types::WalletType,
+};
 use tari_comms::multiaddr::Multiaddr;
 use tari_p2p::P2pConfig;
 use tari_utilities::SafePassword;
@@ -95,20 +98,36 @@ pub struct WalletConfig {
     /// transaction events are received by the console wallet .
     /// (see example at 'applications/minotari_console_wallet/src/notifier/notify_example.sh')
     pub notify_file: Option<PathBuf>,
+    /// URLs that the console wallet POSTs signed JSON notifications of transaction events to
+    pub webhook_urls: StringList,
+    /// The secret used to sign webhook payloads with HMAC-SHA256
+    #[serde(deserialize_with = "deserialize_safe_password_option")]
+    pub webhook_secret: Option<SafePassword>,
+    /// The number of times delivery of a webhook notification is retried before it is dropped
+    pub webhook_max_retries: usize,
     /// If true, a GRPC server will bind to the configured address and listen for incoming GRPC requests.
     pub grpc_enabled: bool,
     /// GRPC bind address of the wallet
     pub grpc_address: Option<Multiaddr>,
     /// GRPC authentication mode
     pub grpc_authentication: GrpcAuthentication,
+    /// Bearer tokens that grant access to a limited set of GRPC methods
+    pub grpc_scoped_tokens: Vec<GrpcScopedToken>,
     /// GRPC tls enabled
     pub grpc_tls_enabled: bool,
+    /// Require GRPC TLS clients to present a certificate signed by the CA in `client_ca.pem` in the config directory
+    pub grpc_tls_client_auth_enabled: bool,
+    /// The TLS client certificates that may call the GRPC server, with the methods each may call. Only used if client
+    /// certificate authentication is enabled. If empty, any certificate signed by the client CA is accepted.
+    pub grpc_tls_client_certificates: Vec<GrpcClientCertificate>,
     /// A custom base node peer that will be used to obtain metadata from
     pub custom_base_node: Option<String>,
     /// A list of base node peers that the wallet should use for service requests and tracking chain state
     pub base_node_service_peers: StringList,
     /// The amount of times wallet recovery will be retried before being abandoned
     pub recovery_retry_limit: usize,
+    /// The number of subaddresses to scan for when recovering the wallet
+    pub subaddress_recovery_lookahead: u64,
     /// The default uT fee per gram to use for transaction fees
     pub fee_per_gram: u64,
     /// Number of required transaction confirmations used for UI purposes
@@ -152,13 +171,20 @@ impl Default for WalletConfig {
             command_send_wait_stage: TransactionStage::Broadcast,
             command_send_wait_timeout: Duration::from_secs(300),
             notify_file: None,
+            webhook_urls: StringList::default(),
+            webhook_secret: None,
+            webhook_max_retries: 5,
             grpc_enabled: false,
             grpc_address: None,
             grpc_authentication: GrpcAuthentication::default(),
+            grpc_scoped_tokens: vec![],
             grpc_tls_enabled: false,
+            grpc_tls_client_auth_enabled: false,
+            grpc_tls_client_certificates: vec![],
             custom_base_node: None,
             base_node_service_peers: StringList::default(),
             recovery_retry_limit: 3,
+            subaddress_recovery_lookahead: 0,
             fee_per_gram: 5,
             num_required_confirmations: 3,
             use_libtor: true,
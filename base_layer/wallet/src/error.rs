@@ -40,10 +40,11 @@ use tari_utilities::{hex::HexError, ByteArrayError};
 use thiserror::Error;
 
 use crate::{
+    account::error::AccountError,
     base_node_service::error::BaseNodeServiceError,
     output_manager_service::error::OutputManagerError,
     storage::database::DbKey,
-    transaction_service::error::TransactionServiceError,
+    transaction_service::{error::TransactionServiceError, history::TransactionHistoryError},
     utxo_scanner_service::error::UtxoScannerError,
 };
 
@@ -103,6 +104,10 @@ pub enum WalletError {
     UnexpectedApiResponse { method: String, api: String },
     #[error("Public address not set for this wallet")]
     PublicAddressNotSet,
+    #[error("Account error: `{0}`")]
+    AccountError(#[from] AccountError),
+    #[error("Transaction history error: `{0}`")]
+    TransactionHistoryError(#[from] TransactionHistoryError),
 }
 
 pub const LOG_TARGET: &str = "minotari::application";
@@ -183,6 +188,8 @@ pub enum WalletStorageError {
     RecoverySeedError(String),
     #[error("Bad encryption version: `{0}`")]
     BadEncryptionVersion(String),
+    #[error("Database rekey error: `{0}`")]
+    RekeyError(String),
 }
 
 impl From<HexError> for WalletStorageError {
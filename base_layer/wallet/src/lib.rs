@@ -9,6 +9,8 @@
 
 #[macro_use]
 mod macros;
+pub mod account;
+pub mod atomic_swap;
 pub mod base_node_service;
 pub mod connectivity_service;
 pub mod error;
@@ -50,10 +52,12 @@ mod consts {
     include!(concat!(env!("OUT_DIR"), "/consts.rs"));
 }
 
+pub type WalletKeyManagerSqlite = TransactionKeyManagerWrapper<KeyManagerSqliteDatabase<WalletDbConnection>>;
+
 pub type WalletSqlite = Wallet<
     WalletSqliteDatabase,
     TransactionServiceSqliteDatabase,
     OutputManagerSqliteDatabase,
     ContactsServiceSqliteDatabase<WalletDbConnection>,
-    TransactionKeyManagerWrapper<KeyManagerSqliteDatabase<WalletDbConnection>>,
+    WalletKeyManagerSqlite,
 >;
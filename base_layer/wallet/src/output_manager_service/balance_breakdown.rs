@@ -0,0 +1,151 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A breakdown of the unspent balance of the wallet by confirmation depth and maturity, so that the funds that can be
+//! spent at a given height are not confused with unconfirmed change and incoming funds.
+
+use std::collections::BTreeMap;
+
+use tari_core::transactions::tari_amount::MicroMinotari;
+
+/// An unspent output counted in a balance breakdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceEntry {
+    pub value: MicroMinotari,
+    /// The height at which the output was mined, None if it is not mined yet
+    pub mined_height: Option<u64>,
+    /// The lowest chain tip height at which the output can be spent, due to its maturity or script lock height
+    pub spendable_height: u64,
+}
+
+/// The unspent outputs of the wallet at a chain tip height, including outputs that are not mined yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceBreakdown {
+    /// The chain tip height the breakdown was made at
+    pub tip_height: u64,
+    entries: Vec<BalanceEntry>,
+}
+
+impl BalanceBreakdown {
+    pub fn new(tip_height: u64, entries: Vec<BalanceEntry>) -> Self {
+        Self { tip_height, entries }
+    }
+
+    pub fn entries(&self) -> &[BalanceEntry] {
+        &self.entries
+    }
+
+    /// The balance of the outputs that are not mined yet, i.e. the change and incoming funds of unconfirmed
+    /// transactions
+    pub fn unconfirmed_balance(&self) -> MicroMinotari {
+        self.entries
+            .iter()
+            .filter(|entry| entry.mined_height.is_none())
+            .map(|entry| entry.value)
+            .sum()
+    }
+
+    /// The balance of the mined outputs by their number of confirmations at the tip. An output mined at the tip has
+    /// no confirmations.
+    pub fn balance_by_confirmations(&self) -> BTreeMap<u64, MicroMinotari> {
+        let mut balances = BTreeMap::new();
+        for entry in &self.entries {
+            if let Some(confirmations) = entry.mined_height.and_then(|h| self.tip_height.checked_sub(h)) {
+                *balances.entry(confirmations).or_insert_with(MicroMinotari::zero) += entry.value;
+            }
+        }
+        balances
+    }
+
+    /// The balance of the mined outputs that are not mature at the tip, by the height at which they can be spent
+    pub fn immature_balance_by_height(&self) -> BTreeMap<u64, MicroMinotari> {
+        let mut balances = BTreeMap::new();
+        for entry in &self.entries {
+            if entry.mined_height.is_some() && entry.spendable_height > self.tip_height {
+                *balances
+                    .entry(entry.spendable_height)
+                    .or_insert_with(MicroMinotari::zero) += entry.value;
+            }
+        }
+        balances
+    }
+
+    /// Projects the balance that can be spent once the chain tip reaches `height`, counting only the outputs that are
+    /// mature by then and mined with at least `min_confirmations`. Outputs that are not mined yet are never counted.
+    pub fn spendable_at_height(&self, height: u64, min_confirmations: u64) -> MicroMinotari {
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .mined_height
+                    .and_then(|h| height.checked_sub(h))
+                    .map_or(false, |confirmations| confirmations >= min_confirmations) &&
+                    entry.spendable_height <= height
+            })
+            .map(|entry| entry.value)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(value: u64, mined_height: Option<u64>, spendable_height: u64) -> BalanceEntry {
+        BalanceEntry {
+            value: MicroMinotari::from(value),
+            mined_height,
+            spendable_height,
+        }
+    }
+
+    #[test]
+    fn it_breaks_down_the_balance() {
+        let breakdown = BalanceBreakdown::new(100, vec![
+            entry(1_000, Some(90), 0),
+            entry(2_000, Some(98), 0),
+            entry(4_000, Some(100), 0),
+            // Coinbase that matures at height 150
+            entry(8_000, Some(95), 150),
+            // Unconfirmed change
+            entry(16_000, None, 0),
+        ]);
+
+        assert_eq!(breakdown.unconfirmed_balance(), MicroMinotari::from(16_000));
+        let by_confirmations = breakdown.balance_by_confirmations();
+        assert_eq!(by_confirmations.get(&10), Some(&MicroMinotari::from(1_000)));
+        assert_eq!(by_confirmations.get(&5), Some(&MicroMinotari::from(8_000)));
+        assert_eq!(by_confirmations.get(&2), Some(&MicroMinotari::from(2_000)));
+        assert_eq!(by_confirmations.get(&0), Some(&MicroMinotari::from(4_000)));
+        assert_eq!(
+            breakdown.immature_balance_by_height().into_iter().collect::<Vec<_>>(),
+            vec![(150, MicroMinotari::from(8_000))]
+        );
+
+        assert_eq!(breakdown.spendable_at_height(100, 0), MicroMinotari::from(7_000));
+        assert_eq!(breakdown.spendable_at_height(100, 3), MicroMinotari::from(1_000));
+        assert_eq!(breakdown.spendable_at_height(103, 3), MicroMinotari::from(7_000));
+        assert_eq!(breakdown.spendable_at_height(150, 3), MicroMinotari::from(15_000));
+        // Outputs mined after the height are not counted
+        assert_eq!(breakdown.spendable_at_height(92, 0), MicroMinotari::from(1_000));
+    }
+}
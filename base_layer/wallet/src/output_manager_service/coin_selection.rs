@@ -0,0 +1,450 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Coin selection strategies used by the output manager to choose the unspent outputs that fund a transaction.
+
+use std::cmp::Reverse;
+
+use tari_common_types::types::Commitment;
+use tari_core::transactions::{fee::Fee, tari_amount::MicroMinotari};
+
+use crate::output_manager_service::{input_selection::UtxoSelectionOrdering, storage::models::DbWalletOutput};
+
+/// The number of search steps the branch-and-bound strategy takes before it falls back to largest-first selection
+pub const BRANCH_AND_BOUND_MAX_TRIES: usize = 100_000;
+
+/// The amount and fees that a coin selection has to cover
+#[derive(Debug, Clone, Copy)]
+pub struct SelectionTarget<'a> {
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    /// The number of outputs, excluding a possible change output
+    pub num_outputs: usize,
+    /// The rounded up size of the features and scripts of the outputs, excluding a possible change output
+    pub features_and_scripts_byte_size: usize,
+    /// The rounded up size of the features and script of a change output
+    pub change_features_and_scripts_byte_size: usize,
+    pub fee_calc: &'a Fee,
+}
+
+impl SelectionTarget<'_> {
+    pub fn fee_without_change(&self, num_inputs: usize) -> MicroMinotari {
+        self.fee_calc.calculate(
+            self.fee_per_gram,
+            1,
+            num_inputs,
+            self.num_outputs,
+            self.features_and_scripts_byte_size,
+        )
+    }
+
+    pub fn fee_with_change(&self, num_inputs: usize) -> MicroMinotari {
+        self.fee_calc.calculate(
+            self.fee_per_gram,
+            1,
+            num_inputs,
+            self.num_outputs + 1,
+            self.features_and_scripts_byte_size + self.change_features_and_scripts_byte_size,
+        )
+    }
+
+    /// The cost of adding a change output. The transaction builder adds any surplus up to this value to the fee
+    /// instead of creating a change output.
+    pub fn change_fee(&self) -> MicroMinotari {
+        self.fee_calc
+            .calculate(self.fee_per_gram, 0, 0, 1, self.change_features_and_scripts_byte_size)
+    }
+}
+
+/// The inputs chosen to fund a transaction and the fees they incur
+#[derive(Debug, Clone)]
+pub struct UtxoSelection {
+    pub(crate) utxos: Vec<DbWalletOutput>,
+    pub(crate) requires_change_output: bool,
+    pub(crate) total_value: MicroMinotari,
+    pub(crate) fee_without_change: MicroMinotari,
+    pub(crate) fee_with_change: MicroMinotari,
+}
+
+impl UtxoSelection {
+    /// Adds candidates in the given order until they cover the amount and fees. If `use_all` is set, every candidate
+    /// is used even if fewer would do.
+    pub fn accumulate(candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>, use_all: bool) -> Self {
+        let mut selection = Self {
+            utxos: Vec::with_capacity(candidates.len()),
+            requires_change_output: false,
+            total_value: MicroMinotari::zero(),
+            fee_without_change: MicroMinotari::zero(),
+            fee_with_change: MicroMinotari::zero(),
+        };
+        for o in candidates {
+            selection.total_value += o.wallet_output.value;
+            selection.utxos.push(o);
+            // The assumption here is that the only output will be the payment output and change if required
+            selection.fee_without_change = target.fee_without_change(selection.utxos.len());
+            if selection.total_value == target.amount + selection.fee_without_change {
+                selection.requires_change_output = false;
+                if use_all {
+                    continue;
+                }
+                break;
+            }
+            selection.fee_with_change = target.fee_with_change(selection.utxos.len());
+            selection.requires_change_output = selection.total_value > target.amount + selection.fee_with_change;
+            if selection.requires_change_output && !use_all {
+                break;
+            }
+        }
+        selection
+    }
+
+    /// A selection without a change output, where any surplus over the amount is paid as fee
+    pub fn changeless(utxos: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> Self {
+        let total_value = utxos.iter().map(|o| o.wallet_output.value).sum::<MicroMinotari>();
+        let fee_with_change = target.fee_with_change(utxos.len());
+        Self {
+            utxos,
+            requires_change_output: false,
+            total_value,
+            fee_without_change: total_value.saturating_sub(target.amount),
+            fee_with_change,
+        }
+    }
+
+    /// Whether the selected inputs cover the amount and the fee
+    pub fn is_sufficient(&self, amount: MicroMinotari) -> bool {
+        self.total_value == amount + self.fee_without_change || self.total_value > amount + self.fee_with_change
+    }
+
+    pub fn as_final_fee(&self) -> MicroMinotari {
+        if self.requires_change_output {
+            return self.fee_with_change;
+        }
+        self.fee_without_change
+    }
+
+    /// The value returned to the wallet as change
+    pub fn change_value(&self, amount: MicroMinotari) -> MicroMinotari {
+        if self.requires_change_output {
+            return self.total_value.saturating_sub(amount + self.fee_with_change);
+        }
+        MicroMinotari::zero()
+    }
+
+    /// Summarises the selection. `weight` is the weight of the transaction the selection would produce.
+    pub fn report(&self, strategy: UtxoSelectionOrdering, amount: MicroMinotari, weight: u64) -> CoinSelectionReport {
+        CoinSelectionReport {
+            strategy,
+            num_inputs: self.num_selected(),
+            inputs: self
+                .utxos
+                .iter()
+                .map(|o| SelectedInput {
+                    commitment: o.commitment.clone(),
+                    value: o.wallet_output.value,
+                })
+                .collect(),
+            total_value: self.total_value,
+            fee: self.as_final_fee(),
+            change: self.change_value(amount),
+            requires_change_output: self.requires_change_output,
+            weight,
+        }
+    }
+
+    pub fn requires_change_output(&self) -> bool {
+        self.requires_change_output
+    }
+
+    /// Total value of the selected inputs
+    pub fn total_value(&self) -> MicroMinotari {
+        self.total_value
+    }
+
+    pub fn num_selected(&self) -> usize {
+        self.utxos.len()
+    }
+
+    pub fn into_selected(self) -> Vec<DbWalletOutput> {
+        self.utxos
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DbWalletOutput> + '_ {
+        self.utxos.iter()
+    }
+}
+
+/// The fee impact of the inputs a coin selection strategy would choose for a send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinSelectionReport {
+    pub strategy: UtxoSelectionOrdering,
+    pub num_inputs: usize,
+    /// Total value of the selected inputs
+    pub total_value: MicroMinotari,
+    pub fee: MicroMinotari,
+    /// The value returned to the wallet, zero if no change output is needed
+    pub change: MicroMinotari,
+    pub requires_change_output: bool,
+    /// The selected inputs in the order they were chosen
+    pub inputs: Vec<SelectedInput>,
+    /// The weight of the resulting transaction in grams
+    pub weight: u64,
+}
+
+/// An unspent output chosen to fund a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectedInput {
+    pub commitment: Commitment,
+    pub value: MicroMinotari,
+}
+
+/// Chooses which unspent outputs fund a transaction
+pub trait CoinSelectionStrategy: Send + Sync {
+    /// A short name used in logs and fee reports
+    fn name(&self) -> &'static str;
+
+    /// Chooses inputs from `candidates`, the unspent outputs that passed the selection filter. The returned selection
+    /// does not cover the target if the candidates are not sufficient.
+    fn select(&self, candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection;
+}
+
+/// Returns the strategy for the given ordering
+pub fn coin_selection_strategy(ordering: UtxoSelectionOrdering) -> Box<dyn CoinSelectionStrategy> {
+    match ordering {
+        UtxoSelectionOrdering::Default => Box::new(DefaultSelection),
+        UtxoSelectionOrdering::SmallestFirst => Box::new(SmallestFirst),
+        UtxoSelectionOrdering::LargestFirst => Box::new(LargestFirst),
+        UtxoSelectionOrdering::BranchAndBound => Box::new(BranchAndBound::default()),
+    }
+}
+
+fn sort_by_priority_and_value(candidates: &mut [DbWalletOutput], largest_first: bool) {
+    if largest_first {
+        candidates.sort_by_key(|o| {
+            (
+                Reverse(o.spending_priority.selection_rank()),
+                Reverse(o.wallet_output.value),
+            )
+        });
+    } else {
+        candidates.sort_by_key(|o| (Reverse(o.spending_priority.selection_rank()), o.wallet_output.value));
+    }
+}
+
+/// Spends the largest outputs first, which keeps the number of inputs and the fee low
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LargestFirst;
+
+impl CoinSelectionStrategy for LargestFirst {
+    fn name(&self) -> &'static str {
+        "largest-first"
+    }
+
+    fn select(&self, mut candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        sort_by_priority_and_value(&mut candidates, true);
+        UtxoSelection::accumulate(candidates, target, false)
+    }
+}
+
+/// Spends the smallest outputs first, consolidating small outputs at the cost of a higher fee
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SmallestFirst;
+
+impl CoinSelectionStrategy for SmallestFirst {
+    fn name(&self) -> &'static str {
+        "smallest-first"
+    }
+
+    fn select(&self, mut candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        sort_by_priority_and_value(&mut candidates, false);
+        UtxoSelection::accumulate(candidates, target, false)
+    }
+}
+
+/// Spends the largest outputs first if the amount is larger than every candidate, otherwise the smallest outputs first
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSelection;
+
+impl CoinSelectionStrategy for DefaultSelection {
+    fn name(&self) -> &'static str {
+        "default"
+    }
+
+    fn select(&self, candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        let max = candidates.iter().map(|o| o.wallet_output.value).max();
+        match max {
+            // Want to reduce the number of inputs to reduce fees
+            Some(max) if target.amount > max => LargestFirst.select(candidates, target),
+            // Use the smaller utxos to make up this transaction
+            _ => SmallestFirst.select(candidates, target),
+        }
+    }
+}
+
+/// Searches for a set of inputs that pays the amount and fee without needing a change output, so that the surplus is
+/// smaller than the cost of a change output. Falls back to largest-first selection if no such set is found within
+/// `max_tries` search steps. Spending priorities are ignored while searching.
+#[derive(Debug, Clone, Copy)]
+pub struct BranchAndBound {
+    pub max_tries: usize,
+}
+
+impl Default for BranchAndBound {
+    fn default() -> Self {
+        Self {
+            max_tries: BRANCH_AND_BOUND_MAX_TRIES,
+        }
+    }
+}
+
+impl BranchAndBound {
+    /// Returns the indexes of the values that form the changeless selection with the least surplus. `values` must be
+    /// sorted from largest to smallest.
+    fn search(&self, values: &[u64], target: &SelectionTarget<'_>) -> Option<Vec<usize>> {
+        let mut remaining = vec![0u64; values.len() + 1];
+        for (i, v) in values.iter().enumerate().rev() {
+            remaining[i] = remaining[i + 1].saturating_add(*v);
+        }
+        let mut search = BranchAndBoundSearch {
+            values,
+            remaining,
+            target,
+            change_fee: target.change_fee().as_u64(),
+            tries: 0,
+            max_tries: self.max_tries,
+            current: Vec::new(),
+            best: None,
+        };
+        search.explore(0, 0);
+        search.best.map(|(_, selection)| selection)
+    }
+}
+
+impl CoinSelectionStrategy for BranchAndBound {
+    fn name(&self) -> &'static str {
+        "branch-and-bound"
+    }
+
+    fn select(&self, mut candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        candidates.sort_by_key(|o| Reverse(o.wallet_output.value));
+        // Outputs worth less than the fee to spend them only add to the surplus
+        let input_fee = target
+            .fee_without_change(2)
+            .saturating_sub(target.fee_without_change(1));
+        let (useful, dust): (Vec<_>, Vec<_>) = candidates.into_iter().partition(|o| o.wallet_output.value > input_fee);
+        let values = useful
+            .iter()
+            .map(|o| o.wallet_output.value.as_u64())
+            .collect::<Vec<_>>();
+
+        match self.search(&values, target) {
+            Some(indexes) => {
+                let mut useful = useful.into_iter().map(Some).collect::<Vec<_>>();
+                let utxos = indexes.into_iter().filter_map(|i| useful[i].take()).collect();
+                UtxoSelection::changeless(utxos, target)
+            },
+            None => LargestFirst.select(useful.into_iter().chain(dust).collect(), target),
+        }
+    }
+}
+
+struct BranchAndBoundSearch<'a> {
+    values: &'a [u64],
+    /// The sum of the values from each index to the end
+    remaining: Vec<u64>,
+    target: &'a SelectionTarget<'a>,
+    change_fee: u64,
+    tries: usize,
+    max_tries: usize,
+    current: Vec<usize>,
+    best: Option<(u64, Vec<usize>)>,
+}
+
+impl BranchAndBoundSearch<'_> {
+    fn explore(&mut self, index: usize, total: u64) {
+        if self.tries >= self.max_tries || self.best.as_ref().map_or(false, |(surplus, _)| *surplus == 0) {
+            return;
+        }
+        self.tries += 1;
+
+        let required = self.target.amount.as_u64() + self.target.fee_without_change(self.current.len()).as_u64();
+        if !self.current.is_empty() && total >= required {
+            let surplus = total - required;
+            if surplus <= self.change_fee && self.best.as_ref().map_or(true, |(best, _)| surplus < *best) {
+                self.best = Some((surplus, self.current.clone()));
+            }
+            // Every further input is worth more than its fee, so adding inputs only grows the surplus
+            return;
+        }
+        if index >= self.values.len() || total.saturating_add(self.remaining[index]) < required {
+            return;
+        }
+
+        self.current.push(index);
+        self.explore(index + 1, total.saturating_add(self.values[index]));
+        self.current.pop();
+        self.explore(index + 1, total);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_core::transactions::weight::TransactionWeight;
+
+    use super::*;
+
+    fn target(fee_calc: &Fee, amount: u64) -> SelectionTarget<'_> {
+        SelectionTarget {
+            amount: amount.into(),
+            fee_per_gram: 5.into(),
+            num_outputs: 1,
+            features_and_scripts_byte_size: fee_calc.weighting().round_up_features_and_scripts_size(40),
+            change_features_and_scripts_byte_size: fee_calc.weighting().round_up_features_and_scripts_size(40),
+            fee_calc,
+        }
+    }
+
+    #[test]
+    fn it_finds_a_changeless_selection() {
+        let fee_calc = Fee::new(TransactionWeight::latest());
+        let target = target(&fee_calc, 10_000);
+        let fee_two_inputs = target.fee_without_change(2).as_u64();
+        // Only the 7_000 and 3_000 + fee outputs together pay the amount and fee without needing change
+        let values = [20_000, 7_000, 4_000, 3_000 + fee_two_inputs, 500];
+
+        let selection = BranchAndBound::default().search(&values, &target).unwrap();
+        assert_eq!(selection, vec![1, 3]);
+    }
+
+    #[test]
+    fn it_gives_up_if_no_changeless_selection_exists() {
+        let fee_calc = Fee::new(TransactionWeight::latest());
+        let target = target(&fee_calc, 10_000);
+
+        assert!(BranchAndBound::default().search(&[50_000, 30_000], &target).is_none());
+        assert!(BranchAndBound::default().search(&[4_000, 3_000], &target).is_none());
+        let fee_two_inputs = target.fee_without_change(2).as_u64();
+        assert!(BranchAndBound { max_tries: 1 }
+            .search(&[20_000, 7_000, 4_000, 3_000 + fee_two_inputs], &target)
+            .is_none());
+    }
+}
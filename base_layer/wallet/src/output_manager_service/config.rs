@@ -20,7 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+use crate::output_manager_service::UtxoSelectionOrdering;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -45,6 +50,23 @@ pub struct OutputManagerServiceConfig {
     pub autoignore_onesided_utxos: bool,
     /// The number of seconds that have to pass for the wallet to run revalidation of invalid UTXOs on startup.
     pub num_of_seconds_to_revalidate_invalid_utxos: u64,
+    /// The coin selection strategy used for sends that don't request a specific ordering. `BranchAndBound` looks for
+    /// inputs that avoid creating a change output.
+    pub coin_selection_strategy: UtxoSelectionOrdering,
+    /// If set to `true`, the wallet periodically joins small outputs into a single output when network fees are low
+    pub auto_consolidation_enabled: bool,
+    /// How often the wallet checks whether its outputs should be consolidated
+    #[serde(with = "serializers::seconds")]
+    pub auto_consolidation_interval: Duration,
+    /// Outputs worth less than this value (in micro MinoTari) are candidates for consolidation
+    pub auto_consolidation_dust_threshold: u64,
+    /// The number of candidate outputs that has to be reached before a consolidation transaction is created
+    pub auto_consolidation_min_outputs: usize,
+    /// The maximum number of outputs joined by a single consolidation transaction
+    pub auto_consolidation_max_inputs: usize,
+    /// Consolidation is deferred while the minimum fee per gram of the next block in the base node's mempool is above
+    /// this value
+    pub auto_consolidation_max_fee_per_gram: u64,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -57,6 +79,13 @@ impl Default for OutputManagerServiceConfig {
             tx_validator_batch_size: 100,
             autoignore_onesided_utxos: false,
             num_of_seconds_to_revalidate_invalid_utxos: 60 * 60 * 24 * 3,
+            coin_selection_strategy: UtxoSelectionOrdering::Default,
+            auto_consolidation_enabled: false,
+            auto_consolidation_interval: Duration::from_secs(60 * 60),
+            auto_consolidation_dust_threshold: 100_000,
+            auto_consolidation_min_outputs: 50,
+            auto_consolidation_max_inputs: 500,
+            auto_consolidation_max_fee_per_gram: 5,
         }
     }
 }
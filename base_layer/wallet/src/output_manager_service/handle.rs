@@ -23,10 +23,12 @@
 use std::{fmt, fmt::Formatter, sync::Arc};
 
 use tari_common_types::{
+    tari_address::TariAddress,
     transaction::TxId,
     types::{Commitment, FixedHash, HashOutput, PublicKey},
 };
 use tari_core::{
+    balance_proof::BalanceProof,
     covenants::Covenant,
     transactions::{
         tari_amount::MicroMinotari,
@@ -43,6 +45,8 @@ use tokio::sync::broadcast;
 use tower::Service;
 
 use crate::output_manager_service::{
+    balance_breakdown::BalanceBreakdown,
+    coin_selection::CoinSelectionReport,
     error::OutputManagerError,
     service::{Balance, OutputInfoByTxId},
     storage::models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
@@ -53,9 +57,14 @@ use crate::output_manager_service::{
 #[allow(clippy::large_enum_variant)]
 pub enum OutputManagerRequest {
     GetBalance,
+    GetAccountBalance(u64),
+    /// Break the balance of the wallet, or of the given account, down by confirmation depth and maturity
+    GetBalanceBreakdown(Option<u64>),
+    GetAccountTransactionIds(u64),
     AddOutput((Box<WalletOutput>, Option<SpendingPriority>)),
     AddOutputWithTxId((TxId, Box<WalletOutput>, Option<SpendingPriority>)),
     AddUnvalidatedOutput((TxId, Box<WalletOutput>, Option<SpendingPriority>)),
+    AddUnvalidatedOutputs(Vec<(TxId, WalletOutput)>),
     UpdateOutputMetadataSignature(Box<TransactionOutput>),
     GetRecipientTransaction(TransactionSenderMessage),
     ConfirmPendingTransaction(TxId),
@@ -84,19 +93,33 @@ pub enum OutputManagerRequest {
         fee_per_gram: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
     },
+    CreateOneSidedBatchTransaction {
+        tx_id: TxId,
+        recipients: Vec<OneSidedBatchRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    },
+    CreateBalanceProof {
+        commitments: Vec<Commitment>,
+        message: String,
+    },
+    GetSubaddress(u64),
     CancelTransaction(TxId),
     GetSpentOutputs,
     GetUnspentOutputs,
+    GetSpendableOutputs(UtxoSelectionCriteria),
+    SetOutputsSpendingPriority((Vec<Commitment>, SpendingPriority)),
     GetInvalidOutputs,
     ValidateUtxos,
     RevalidateTxos,
-    CreateCoinSplit((Vec<Commitment>, MicroMinotari, usize, MicroMinotari)),
-    CreateCoinSplitEven((Vec<Commitment>, usize, MicroMinotari)),
+    CreateCoinSplit((Vec<Commitment>, MicroMinotari, usize, MicroMinotari, u64)),
+    CreateCoinSplitEven((Vec<Commitment>, usize, MicroMinotari, u64)),
     PreviewCoinJoin((Vec<Commitment>, MicroMinotari)),
     PreviewCoinSplitEven((Vec<Commitment>, usize, MicroMinotari)),
     CreateCoinJoin {
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
+        account: u64,
     },
     FeeEstimate {
         amount: MicroMinotari,
@@ -105,18 +128,29 @@ pub enum OutputManagerRequest {
         num_kernels: usize,
         num_outputs: usize,
     },
+    PreviewCoinSelection {
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    },
 
     ScanForRecoverableOutputs(Vec<TransactionOutput>),
     ScanOutputs(Vec<TransactionOutput>),
     AddKnownOneSidedPaymentScript(KnownOneSidedPaymentScript),
+    GetKnownOneSidedPaymentScripts,
     CreateOutputWithFeatures {
         value: MicroMinotari,
         features: Box<OutputFeatures>,
     },
 
     ReinstateCancelledInboundTx(TxId),
-    CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroMinotari),
-    CreateHtlcRefundTransaction(HashOutput, MicroMinotari),
+    ReinstateCancelledOutboundTx {
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    },
+    CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroMinotari, u64),
+    CreateHtlcRefundTransaction(HashOutput, MicroMinotari, u64),
+    FetchHtlcOutput(HashOutput),
     GetOutputInfoByTxId(TxId),
 }
 
@@ -127,11 +161,15 @@ impl fmt::Display for OutputManagerRequest {
         use OutputManagerRequest::*;
         match self {
             GetBalance => write!(f, "GetBalance"),
+            GetAccountBalance(account) => write!(f, "GetAccountBalance ({})", account),
+            GetBalanceBreakdown(account) => write!(f, "GetBalanceBreakdown ({:?})", account),
+            GetAccountTransactionIds(account) => write!(f, "GetAccountTransactionIds ({})", account),
             AddOutput((v, _)) => write!(f, "AddOutput ({})", v.value),
             AddOutputWithTxId((t, v, _)) => write!(f, "AddOutputWithTxId ({}: {})", t, v.value),
             AddUnvalidatedOutput((t, v, _)) => {
                 write!(f, "AddUnvalidatedOutput ({}: {})", t, v.value)
             },
+            AddUnvalidatedOutputs(v) => write!(f, "AddUnvalidatedOutputs ({} outputs)", v.len()),
             UpdateOutputMetadataSignature(v) => write!(
                 f,
                 "UpdateOutputMetadataSignature ({}, {}, {}, {}, {})",
@@ -148,6 +186,13 @@ impl fmt::Display for OutputManagerRequest {
             CancelTransaction(v) => write!(f, "CancelTransaction ({})", v),
             GetSpentOutputs => write!(f, "GetSpentOutputs"),
             GetUnspentOutputs => write!(f, "GetUnspentOutputs"),
+            GetSpendableOutputs(selection_criteria) => write!(f, "GetSpendableOutputs ({})", selection_criteria),
+            SetOutputsSpendingPriority((commitments, spending_priority)) => write!(
+                f,
+                "SetOutputsSpendingPriority ({} output(s), {:?})",
+                commitments.len(),
+                spending_priority
+            ),
             GetInvalidOutputs => write!(f, "GetInvalidOutputs"),
             ValidateUtxos => write!(f, "ValidateUtxos"),
             RevalidateTxos => write!(f, "RevalidateTxos"),
@@ -166,10 +211,11 @@ impl fmt::Display for OutputManagerRequest {
             CreateCoinJoin {
                 commitments,
                 fee_per_gram,
+                account,
             } => write!(
                 f,
-                "CreateCoinJoin: commitments={:#?}, fee_per_gram={}",
-                commitments, fee_per_gram,
+                "CreateCoinJoin: commitments={:#?}, fee_per_gram={}, account={}",
+                commitments, fee_per_gram, account,
             ),
             FeeEstimate {
                 amount,
@@ -182,24 +228,46 @@ impl fmt::Display for OutputManagerRequest {
                 "FeeEstimate(amount: {}, fee_per_gram: {}, num_kernels: {}, num_outputs: {}, selection_criteria: {:?})",
                 amount, fee_per_gram, num_kernels, num_outputs, selection_criteria
             ),
+            PreviewCoinSelection {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            } => write!(
+                f,
+                "PreviewCoinSelection(amount: {}, fee_per_gram: {}, selection_criteria: {:?})",
+                amount, fee_per_gram, selection_criteria
+            ),
             ScanForRecoverableOutputs(_) => write!(f, "ScanForRecoverableOutputs"),
             ScanOutputs(_) => write!(f, "ScanOutputs"),
             AddKnownOneSidedPaymentScript(_) => write!(f, "AddKnownOneSidedPaymentScript"),
+            GetKnownOneSidedPaymentScripts => write!(f, "GetKnownOneSidedPaymentScripts"),
             CreateOutputWithFeatures { value, features } => {
                 write!(f, "CreateOutputWithFeatures({}, {})", value, features,)
             },
             CreatePayToSelfWithOutputs { .. } => write!(f, "CreatePayToSelfWithOutputs"),
+            CreateOneSidedBatchTransaction { tx_id, recipients, .. } => write!(
+                f,
+                "CreateOneSidedBatchTransaction ({}, {} recipients)",
+                tx_id,
+                recipients.len()
+            ),
+            CreateBalanceProof { commitments, .. } => {
+                write!(f, "CreateBalanceProof ({} commitments)", commitments.len())
+            },
+            GetSubaddress(index) => write!(f, "GetSubaddress ({})", index),
             ReinstateCancelledInboundTx(_) => write!(f, "ReinstateCancelledInboundTx"),
-            CreateClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram) => write!(
+            ReinstateCancelledOutboundTx { tx_id, .. } => write!(f, "ReinstateCancelledOutboundTx ({})", tx_id),
+            CreateClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram, account) => write!(
                 f,
-                "ClaimShaAtomicSwap(output hash: {}, pre_image: {}, fee_per_gram: {} )",
-                output, pre_image, fee_per_gram,
+                "ClaimShaAtomicSwap(output hash: {}, pre_image: {}, fee_per_gram: {}, account: {} )",
+                output, pre_image, fee_per_gram, account,
             ),
-            CreateHtlcRefundTransaction(output, fee_per_gram) => write!(
+            CreateHtlcRefundTransaction(output, fee_per_gram, account) => write!(
                 f,
-                "CreateHtlcRefundTransaction(output hash: {}, , fee_per_gram: {} )",
-                output, fee_per_gram,
+                "CreateHtlcRefundTransaction(output hash: {}, , fee_per_gram: {}, account: {} )",
+                output, fee_per_gram, account,
             ),
+            FetchHtlcOutput(output) => write!(f, "FetchHtlcOutput(output hash: {})", output),
 
             GetOutputInfoByTxId(t) => write!(f, "GetOutputInfoByTxId: {}", t),
         }
@@ -210,7 +278,10 @@ impl fmt::Display for OutputManagerRequest {
 #[derive(Debug, Clone)]
 pub enum OutputManagerResponse {
     Balance(Balance),
+    BalanceBreakdown(BalanceBreakdown),
+    TransactionIds(Vec<TxId>),
     OutputAdded,
+    UnvalidatedOutputsAdded(Vec<bool>),
     ConvertedToTransactionOutput(Box<TransactionOutput>),
     OutputMetadataSignatureUpdated,
     RecipientTransactionGenerated(ReceiverTransactionProtocol),
@@ -221,6 +292,7 @@ pub enum OutputManagerResponse {
     TransactionCancelled,
     SpentOutputs(Vec<DbWalletOutput>),
     UnspentOutputs(Vec<DbWalletOutput>),
+    OutputsSpendingPriorityUpdated,
     Outputs(Vec<WalletOutput>),
     InvalidOutputs(Vec<WalletOutput>),
     BaseNodePublicKeySet,
@@ -229,13 +301,20 @@ pub enum OutputManagerResponse {
     PublicRewindKeys(Box<PublicRewindKeys>),
     RecoveryByte(u8),
     FeeEstimate(MicroMinotari),
+    CoinSelectionPreview(CoinSelectionReport),
     RewoundOutputs(Vec<RecoveredOutput>),
     ScanOutputs(Vec<RecoveredOutput>),
     AddKnownOneSidedPaymentScript,
+    KnownOneSidedPaymentScripts(Vec<KnownOneSidedPaymentScript>),
     CreateOutputWithFeatures { output: Box<WalletOutputBuilder> },
     CreatePayToSelfWithOutputs { transaction: Box<Transaction>, tx_id: TxId },
+    OneSidedBatchTransaction((MicroMinotari, Transaction)),
+    BalanceProof(Box<BalanceProof>),
+    Subaddress(Box<TariAddress>),
     ReinstatedCancelledInboundTx,
+    ReinstatedCancelledOutboundTx,
     ClaimHtlcTransaction((TxId, MicroMinotari, MicroMinotari, Transaction)),
+    HtlcOutput(Box<(TransactionOutput, MicroMinotari)>),
     OutputInfoByTxId(OutputInfoByTxId),
     CoinPreview((Vec<MicroMinotari>, MicroMinotari)),
 }
@@ -250,6 +329,10 @@ pub enum OutputManagerEvent {
     TxoValidationInternalFailure(u64),
     TxoValidationCommunicationFailure(u64),
     TxoValidationAlreadyBusy(u64),
+    /// A transaction joining small outputs was submitted
+    UtxoConsolidationSubmitted(TxId),
+    /// Consolidation is due but was deferred because the fee per gram is above the configured maximum
+    UtxoConsolidationDeferred(MicroMinotari),
 }
 
 impl fmt::Display for OutputManagerEvent {
@@ -267,6 +350,12 @@ impl fmt::Display for OutputManagerEvent {
             OutputManagerEvent::TxoValidationAlreadyBusy(tx) => {
                 write!(f, "Txo is already running, stopping {}", tx)
             },
+            OutputManagerEvent::UtxoConsolidationSubmitted(tx_id) => {
+                write!(f, "UtxoConsolidationSubmitted for {}", tx_id)
+            },
+            OutputManagerEvent::UtxoConsolidationDeferred(fee_per_gram) => {
+                write!(f, "UtxoConsolidationDeferred at fee per gram {}", fee_per_gram)
+            },
         }
     }
 }
@@ -276,11 +365,23 @@ pub struct PublicRewindKeys {
     pub rewind_blinding_public_key: PublicKey,
 }
 
+/// A recipient of one of the outputs of a one-sided batch transaction
+#[derive(Debug, Clone)]
+pub struct OneSidedBatchRecipient {
+    pub public_key: PublicKey,
+    pub amount: MicroMinotari,
+    pub payment_reference: Vec<u8>,
+}
+
 #[derive(Debug, Clone)]
 pub struct RecoveredOutput {
     pub tx_id: TxId,
     pub output: WalletOutput,
     pub hash: FixedHash,
+    /// The payment reference carried in the encrypted data of a one-sided payment, if any
+    pub payment_reference: Option<Vec<u8>>,
+    /// The memo for the recipient carried in the encrypted data of a one-sided payment, if any
+    pub memo: Option<Vec<u8>>,
 }
 
 #[derive(Clone)]
@@ -359,6 +460,22 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Adds a batch of outputs as `EncumberedToBeReceived` in a single database transaction. Returns, for each output,
+    /// whether it was added or skipped because the wallet already holds it.
+    pub async fn add_unvalidated_outputs(
+        &mut self,
+        outputs: Vec<(TxId, WalletOutput)>,
+    ) -> Result<Vec<bool>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::AddUnvalidatedOutputs(outputs))
+            .await??
+        {
+            OutputManagerResponse::UnvalidatedOutputsAdded(added) => Ok(added),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_output_with_features(
         &mut self,
         value: MicroMinotari,
@@ -398,6 +515,44 @@ impl OutputManagerHandle {
         }
     }
 
+    pub async fn get_account_balance(&mut self, account: u64) -> Result<Balance, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountBalance(account))
+            .await??
+        {
+            OutputManagerResponse::Balance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Get the unspent balance of the wallet, or of the given account, by confirmation depth and maturity
+    pub async fn get_balance_breakdown(
+        &mut self,
+        account: Option<u64>,
+    ) -> Result<BalanceBreakdown, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetBalanceBreakdown(account))
+            .await??
+        {
+            OutputManagerResponse::BalanceBreakdown(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Get the ids of the transactions that created or spent outputs of the given account
+    pub async fn get_account_transaction_ids(&mut self, account: u64) -> Result<Vec<TxId>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountTransactionIds(account))
+            .await??
+        {
+            OutputManagerResponse::TransactionIds(tx_ids) => Ok(tx_ids),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn revalidate_all_outputs(&mut self) -> Result<u64, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::RevalidateTxos).await?? {
             OutputManagerResponse::TxoValidationStarted(request_key) => Ok(request_key),
@@ -479,6 +634,28 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Reports the inputs, fee and change of a standard send of `amount` with the coin selection strategy requested in
+    /// `selection_criteria`, or the configured strategy if it requests the default ordering.
+    pub async fn preview_coin_selection(
+        &mut self,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<CoinSelectionReport, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PreviewCoinSelection {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::CoinSelectionPreview(report) => Ok(report),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn confirm_pending_transaction(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -516,6 +693,42 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Lists the unspent outputs that match the selection criteria, in the order the wallet would select them as
+    /// inputs
+    pub async fn get_spendable_outputs(
+        &mut self,
+        selection_criteria: UtxoSelectionCriteria,
+    ) -> Result<Vec<DbWalletOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetSpendableOutputs(selection_criteria))
+            .await??
+        {
+            OutputManagerResponse::UnspentOutputs(s) => Ok(s),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Pins, freezes or releases outputs. Frozen outputs are skipped by automatic input selection and can only be
+    /// spent by passing them explicitly as inputs. HTLC outputs keep their priority so that they are still swept first.
+    pub async fn set_outputs_spending_priority(
+        &mut self,
+        commitments: Vec<Commitment>,
+        spending_priority: SpendingPriority,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetOutputsSpendingPriority((
+                commitments,
+                spending_priority,
+            )))
+            .await??
+        {
+            OutputManagerResponse::OutputsSpendingPriorityUpdated => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_invalid_outputs(&mut self) -> Result<Vec<WalletOutput>, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::GetInvalidOutputs).await?? {
             OutputManagerResponse::InvalidOutputs(s) => Ok(s),
@@ -565,7 +778,7 @@ impl OutputManagerHandle {
         }
     }
 
-    /// Create a coin split transaction.
+    /// Create a coin split transaction whose outputs belong to `account`.
     /// Returns (tx_id, tx, utxos_total_value).
     pub async fn create_coin_split(
         &mut self,
@@ -573,6 +786,7 @@ impl OutputManagerHandle {
         amount_per_split: MicroMinotari,
         split_count: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         match self
             .handle
@@ -581,6 +795,7 @@ impl OutputManagerHandle {
                 amount_per_split,
                 split_count,
                 fee_per_gram,
+                account,
             )))
             .await??
         {
@@ -594,6 +809,7 @@ impl OutputManagerHandle {
         commitments: Vec<Commitment>,
         split_count: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         match self
             .handle
@@ -601,6 +817,7 @@ impl OutputManagerHandle {
                 commitments,
                 split_count,
                 fee_per_gram,
+                account,
             )))
             .await??
         {
@@ -613,12 +830,14 @@ impl OutputManagerHandle {
         &mut self,
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         match self
             .handle
             .call(OutputManagerRequest::CreateCoinJoin {
                 commitments,
                 fee_per_gram,
+                account,
             })
             .await??
         {
@@ -631,10 +850,15 @@ impl OutputManagerHandle {
         &mut self,
         output: HashOutput,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
         match self
             .handle
-            .call(OutputManagerRequest::CreateHtlcRefundTransaction(output, fee_per_gram))
+            .call(OutputManagerRequest::CreateHtlcRefundTransaction(
+                output,
+                fee_per_gram,
+                account,
+            ))
             .await??
         {
             OutputManagerResponse::ClaimHtlcTransaction(ct) => Ok(ct),
@@ -647,6 +871,7 @@ impl OutputManagerHandle {
         output: HashOutput,
         pre_image: PublicKey,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
         match self
             .handle
@@ -654,6 +879,7 @@ impl OutputManagerHandle {
                 output,
                 pre_image,
                 fee_per_gram,
+                account,
             ))
             .await??
         {
@@ -662,6 +888,21 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Fetches an HTLC output that pays to this wallet from the base node, and returns it with its value
+    pub async fn fetch_htlc_output(
+        &mut self,
+        output: HashOutput,
+    ) -> Result<(TransactionOutput, MicroMinotari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::FetchHtlcOutput(output))
+            .await??
+        {
+            OutputManagerResponse::HtlcOutput(output) => Ok(*output),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn scan_for_recoverable_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
@@ -697,6 +938,19 @@ impl OutputManagerHandle {
         }
     }
 
+    pub async fn get_known_one_sided_payment_scripts(
+        &mut self,
+    ) -> Result<Vec<KnownOneSidedPaymentScript>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetKnownOneSidedPaymentScripts)
+            .await??
+        {
+            OutputManagerResponse::KnownOneSidedPaymentScripts(scripts) => Ok(scripts),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_send_to_self_with_output(
         &mut self,
         outputs: Vec<WalletOutputBuilder>,
@@ -717,6 +971,53 @@ impl OutputManagerHandle {
         }
     }
 
+    pub async fn create_one_sided_batch_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipients: Vec<OneSidedBatchRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateOneSidedBatchTransaction {
+                tx_id,
+                recipients,
+                selection_criteria,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::OneSidedBatchTransaction(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Proves the total value held in the given unspent outputs, or in all unspent outputs if `commitments` is empty
+    pub async fn create_balance_proof(
+        &mut self,
+        commitments: Vec<Commitment>,
+        message: String,
+    ) -> Result<BalanceProof, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateBalanceProof { commitments, message })
+            .await??
+        {
+            OutputManagerResponse::BalanceProof(proof) => Ok(*proof),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// The subaddress at `index`, which receives one-sided payments into this wallet. Index 0 is the wallet's own
+    /// address.
+    pub async fn get_subaddress(&mut self, index: u64) -> Result<TariAddress, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetSubaddress(index)).await?? {
+            OutputManagerResponse::Subaddress(address) => Ok(*address),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -757,6 +1058,23 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Restores the encumberance of the given inputs and of the change output of an outbound transaction whose outputs
+    /// were cancelled
+    pub async fn reinstate_cancelled_outbound_transaction_outputs(
+        &mut self,
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ReinstateCancelledOutboundTx { tx_id, commitments })
+            .await??
+        {
+            OutputManagerResponse::ReinstatedCancelledOutboundTx => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_output_info_for_tx_id(&mut self, tx_id: TxId) -> Result<OutputInfoByTxId, OutputManagerError> {
         match self
             .handle
@@ -25,6 +25,7 @@ use std::{
     fmt::{Display, Formatter},
 };
 
+use serde::{Deserialize, Serialize};
 use tari_common_types::types::Commitment;
 
 #[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
@@ -42,6 +43,9 @@ pub struct UtxoSelectionCriteria {
     pub excluding: Vec<Commitment>,
     pub min_dust: u64,
     pub excluding_onesided: bool,
+    /// The wallet account to select outputs from, 0 is the default account. Specific outputs are selected regardless
+    /// of their account.
+    pub account: u64,
 }
 
 impl UtxoSelectionCriteria {
@@ -75,12 +79,16 @@ impl UtxoSelectionCriteria {
 
 impl Display for UtxoSelectionCriteria {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "filter: {}, ordering: {}", self.filter, self.ordering)
+        write!(
+            f,
+            "filter: {}, ordering: {}, account: {}",
+            self.filter, self.ordering, self.account
+        )
     }
 }
 
 /// UTXO selection ordering
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UtxoSelectionOrdering {
     /// The Default ordering is heuristic and depends on the requested value and the value of the available UTXOs.
     /// If the requested value is larger than the largest available UTXO, we select LargerFirst as inputs, otherwise
@@ -92,6 +100,34 @@ pub enum UtxoSelectionOrdering {
     SmallestFirst,
     /// A strategy that selects the largest UTXOs first. Preferred when the amount is large
     LargestFirst,
+    /// Searches for a combination of UTXOs that covers the amount and fee without a change output, falling back to
+    /// LargestFirst if there is none
+    BranchAndBound,
+}
+
+impl TryFrom<u32> for UtxoSelectionOrdering {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(UtxoSelectionOrdering::Default),
+            1 => Ok(UtxoSelectionOrdering::SmallestFirst),
+            2 => Ok(UtxoSelectionOrdering::LargestFirst),
+            3 => Ok(UtxoSelectionOrdering::BranchAndBound),
+            _ => Err(format!("Invalid UTXO selection ordering value: {}", value)),
+        }
+    }
+}
+
+impl From<UtxoSelectionOrdering> for i32 {
+    fn from(value: UtxoSelectionOrdering) -> Self {
+        match value {
+            UtxoSelectionOrdering::Default => 0,
+            UtxoSelectionOrdering::SmallestFirst => 1,
+            UtxoSelectionOrdering::LargestFirst => 2,
+            UtxoSelectionOrdering::BranchAndBound => 3,
+        }
+    }
 }
 
 impl Display for UtxoSelectionOrdering {
@@ -100,6 +136,7 @@ impl Display for UtxoSelectionOrdering {
             UtxoSelectionOrdering::SmallestFirst => write!(f, "Smallest"),
             UtxoSelectionOrdering::LargestFirst => write!(f, "Largest"),
             UtxoSelectionOrdering::Default => write!(f, "Default"),
+            UtxoSelectionOrdering::BranchAndBound => write!(f, "BranchAndBound"),
         }
     }
 }
@@ -20,12 +20,30 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+mod balance_breakdown;
+pub use balance_breakdown::{BalanceBreakdown, BalanceEntry};
+
 pub mod config;
 pub mod error;
 pub mod handle;
 
+mod coin_selection;
+pub use coin_selection::{
+    coin_selection_strategy,
+    BranchAndBound,
+    CoinSelectionReport,
+    CoinSelectionStrategy,
+    DefaultSelection,
+    LargestFirst,
+    SelectedInput,
+    SelectionTarget,
+    SmallestFirst,
+    UtxoSelection,
+    BRANCH_AND_BOUND_MAX_TRIES,
+};
+
 mod input_selection;
-pub use input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelectionOrdering};
+pub use input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelectionMode, UtxoSelectionOrdering};
 
 mod recovery;
 pub mod resources;
@@ -39,7 +57,7 @@ use futures::future;
 use log::*;
 use tari_core::{
     consensus::NetworkConsensus,
-    transactions::{key_manager::TransactionKeyManagerInterface, CryptoFactories},
+    transactions::{fee::Fee, key_manager::TransactionKeyManagerInterface, CryptoFactories},
 };
 use tari_service_framework::{
     async_trait,
@@ -58,7 +76,9 @@ use crate::{
         handle::OutputManagerHandle,
         service::OutputManagerService,
         storage::database::{OutputManagerBackend, OutputManagerDatabase},
+        tasks::UtxoConsolidationTask,
     },
+    transaction_service::handle::TransactionServiceHandle,
     util::wallet_identity::WalletIdentity,
 };
 
@@ -126,6 +146,18 @@ where
             let connectivity = handles.expect_handle::<WalletConnectivityHandle>();
             let key_manager = handles.expect_handle::<TKeyManagerInterface>();
 
+            if config.auto_consolidation_enabled {
+                let consolidation_task = UtxoConsolidationTask::new(
+                    config.clone(),
+                    Fee::new(*constants.transaction_weight_params()),
+                    handles.expect_handle::<OutputManagerHandle>(),
+                    handles.expect_handle::<TransactionServiceHandle>(),
+                    publisher.clone(),
+                    handles.get_shutdown_signal(),
+                );
+                tokio::spawn(consolidation_task.run());
+            }
+
             let service = OutputManagerService::new(
                 config,
                 receiver,
@@ -147,6 +147,8 @@ where
                 output: output.clone(),
                 tx_id,
                 hash: *hash,
+                payment_reference: None,
+                memo: None,
             });
             self.update_outputs_script_private_key_and_update_key_manager_index(output)
                 .await?;
@@ -22,20 +22,28 @@
 
 use std::{convert::TryInto, fmt, sync::Arc};
 
+use blake2::Blake2b;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use digest::consts::U32;
 use futures::{pin_mut, StreamExt};
 use log::*;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
+    tari_address::TariAddress,
     transaction::TxId,
     types::{BlockHash, Commitment, HashOutput, PrivateKey, PublicKey},
 };
 use tari_comms::types::CommsDHKE;
 use tari_core::{
+    balance_proof::BalanceProof,
     borsh::SerializedSize,
     consensus::ConsensusConstants,
     covenants::Covenant,
-    one_sided::{shared_secret_to_output_encryption_key, stealth_address_script_spending_key},
+    one_sided::{
+        shared_secret_to_output_encryption_key,
+        shared_secret_to_output_spending_key,
+        stealth_address_script_spending_key,
+    },
     proto::base_node::FetchMatchingUtxos,
     transactions::{
         fee::Fee,
@@ -59,26 +67,30 @@ use tari_core::{
     },
 };
 use tari_crypto::keys::SecretKey;
-use tari_script::{inputs, script, ExecutionStack, Opcode, TariScript};
+use tari_script::{inputs, one_sided_payment_script, script, ExecutionStack, Opcode, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
 use tokio::{sync::Mutex, time::Instant};
 
 use crate::{
+    account::models::account_index_from_key_id,
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{
+        balance_breakdown::{BalanceBreakdown, BalanceEntry},
+        coin_selection::{coin_selection_strategy, CoinSelectionReport, SelectionTarget, UtxoSelection},
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
         handle::{
+            OneSidedBatchRecipient,
             OutputManagerEvent,
             OutputManagerEventSender,
             OutputManagerRequest,
             OutputManagerResponse,
             RecoveredOutput,
         },
-        input_selection::UtxoSelectionCriteria,
+        input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelectionOrdering},
         recovery::StandardUtxoRecoverer,
         resources::OutputManagerResources,
         storage::{
@@ -224,6 +236,10 @@ where
                 .add_unvalidated_output(tx_id, *uo, spend_priority)
                 .await
                 .map(|_| OutputManagerResponse::OutputAdded),
+            OutputManagerRequest::AddUnvalidatedOutputs(outputs) => self
+                .add_unvalidated_outputs(outputs)
+                .await
+                .map(OutputManagerResponse::UnvalidatedOutputsAdded),
             OutputManagerRequest::UpdateOutputMetadataSignature(uo) => self
                 .update_output_metadata_signature(*uo)
                 .map(|_| OutputManagerResponse::OutputMetadataSignatureUpdated),
@@ -235,6 +251,30 @@ where
                 self.get_balance(current_tip_for_time_lock_calculation)
                     .map(OutputManagerResponse::Balance)
             },
+            OutputManagerRequest::GetAccountBalance(account) => {
+                let current_tip_for_time_lock_calculation = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.best_block_height()),
+                    Err(_) => None,
+                };
+                self.resources
+                    .db
+                    .get_account_balance(account, current_tip_for_time_lock_calculation)
+                    .map(OutputManagerResponse::Balance)
+                    .map_err(OutputManagerError::from)
+            },
+            OutputManagerRequest::GetBalanceBreakdown(account) => {
+                let tip_height = self
+                    .base_node_service
+                    .get_chain_metadata()
+                    .await?
+                    .map(|m| m.best_block_height())
+                    .ok_or(OutputManagerError::BaseNodeNotSynced)?;
+                self.get_balance_breakdown(tip_height, account)
+                    .map(OutputManagerResponse::BalanceBreakdown)
+            },
+            OutputManagerRequest::GetAccountTransactionIds(account) => self
+                .get_account_transaction_ids(account)
+                .map(OutputManagerResponse::TransactionIds),
             OutputManagerRequest::GetRecipientTransaction(tsm) => self
                 .get_default_recipient_transaction(tsm)
                 .await
@@ -293,6 +333,14 @@ where
                 .fee_estimate(amount, selection_criteria, fee_per_gram, num_kernels, num_outputs)
                 .await
                 .map(OutputManagerResponse::FeeEstimate),
+            OutputManagerRequest::PreviewCoinSelection {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            } => self
+                .preview_coin_selection(amount, selection_criteria, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::CoinSelectionPreview),
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
                 .confirm_encumberance(tx_id)
                 .map(|_| OutputManagerResponse::PendingTransactionConfirmed),
@@ -307,6 +355,21 @@ where
                 let outputs = self.fetch_unspent_outputs()?;
                 Ok(OutputManagerResponse::UnspentOutputs(outputs))
             },
+            OutputManagerRequest::GetSpendableOutputs(selection_criteria) => {
+                let outputs = self.fetch_spendable_outputs(selection_criteria).await?;
+                Ok(OutputManagerResponse::UnspentOutputs(outputs))
+            },
+            OutputManagerRequest::SetOutputsSpendingPriority((commitments, spending_priority)) => {
+                if commitments.is_empty() {
+                    return Err(OutputManagerError::InvalidArgument(
+                        "At least one output commitment is required".to_string(),
+                    ));
+                }
+                self.resources
+                    .db
+                    .set_outputs_spending_priority(commitments, spending_priority)?;
+                Ok(OutputManagerResponse::OutputsSpendingPriorityUpdated)
+            },
             OutputManagerRequest::ValidateUtxos => {
                 self.validate_outputs().map(OutputManagerResponse::TxoValidationStarted)
             },
@@ -329,9 +392,15 @@ where
                         .await?,
                 ))
             },
-            OutputManagerRequest::CreateCoinSplit((commitments, amount_per_split, split_count, fee_per_gram)) => {
+            OutputManagerRequest::CreateCoinSplit((
+                commitments,
+                amount_per_split,
+                split_count,
+                fee_per_gram,
+                account,
+            )) => {
                 if commitments.is_empty() {
-                    self.create_coin_split_auto(Some(amount_per_split), split_count, fee_per_gram)
+                    self.create_coin_split_auto(Some(amount_per_split), split_count, fee_per_gram, account)
                         .await
                         .map(OutputManagerResponse::Transaction)
                 } else {
@@ -340,18 +409,19 @@ where
                         Some(amount_per_split),
                         split_count,
                         fee_per_gram,
+                        account,
                     )
                     .await
                     .map(OutputManagerResponse::Transaction)
                 }
             },
-            OutputManagerRequest::CreateCoinSplitEven((commitments, split_count, fee_per_gram)) => {
+            OutputManagerRequest::CreateCoinSplitEven((commitments, split_count, fee_per_gram, account)) => {
                 if commitments.is_empty() {
-                    self.create_coin_split_auto(None, split_count, fee_per_gram)
+                    self.create_coin_split_auto(None, split_count, fee_per_gram, account)
                         .await
                         .map(OutputManagerResponse::Transaction)
                 } else {
-                    self.create_coin_split_with_commitments(commitments, None, split_count, fee_per_gram)
+                    self.create_coin_split_with_commitments(commitments, None, split_count, fee_per_gram, account)
                         .await
                         .map(OutputManagerResponse::Transaction)
                 }
@@ -359,8 +429,9 @@ where
             OutputManagerRequest::CreateCoinJoin {
                 commitments,
                 fee_per_gram,
+                account,
             } => self
-                .create_coin_join(commitments, fee_per_gram)
+                .create_coin_join(commitments, fee_per_gram, account)
                 .await
                 .map(OutputManagerResponse::Transaction),
 
@@ -377,9 +448,16 @@ where
             OutputManagerRequest::AddKnownOneSidedPaymentScript(known_script) => self
                 .add_known_script(known_script)
                 .map(|_| OutputManagerResponse::AddKnownOneSidedPaymentScript),
+            OutputManagerRequest::GetKnownOneSidedPaymentScripts => {
+                let scripts = self.resources.db.get_all_known_one_sided_payment_scripts()?;
+                Ok(OutputManagerResponse::KnownOneSidedPaymentScripts(scripts))
+            },
             OutputManagerRequest::ReinstateCancelledInboundTx(tx_id) => self
                 .reinstate_cancelled_inbound_transaction_outputs(tx_id)
                 .map(|_| OutputManagerResponse::ReinstatedCancelledInboundTx),
+            OutputManagerRequest::ReinstateCancelledOutboundTx { tx_id, commitments } => self
+                .reinstate_cancelled_outbound_transaction_outputs(tx_id, commitments)
+                .map(|_| OutputManagerResponse::ReinstatedCancelledOutboundTx),
             OutputManagerRequest::CreateOutputWithFeatures { value, features } => {
                 let wallet_output = self.create_output_with_features(value, *features).await?;
                 Ok(OutputManagerResponse::CreateOutputWithFeatures {
@@ -399,12 +477,34 @@ where
                     tx_id,
                 })
             },
-            OutputManagerRequest::CreateClaimShaAtomicSwapTransaction(output_hash, pre_image, fee_per_gram) => {
-                self.claim_sha_atomic_swap_with_hash(output_hash, pre_image, fee_per_gram)
+            OutputManagerRequest::CreateOneSidedBatchTransaction {
+                tx_id,
+                recipients,
+                selection_criteria,
+                fee_per_gram,
+            } => self
+                .create_one_sided_batch_transaction(tx_id, recipients, selection_criteria, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::OneSidedBatchTransaction),
+            OutputManagerRequest::GetSubaddress(index) => self
+                .get_subaddress(index)
+                .await
+                .map(|address| OutputManagerResponse::Subaddress(Box::new(address))),
+            OutputManagerRequest::CreateBalanceProof { commitments, message } => self
+                .create_balance_proof(commitments, message)
+                .await
+                .map(|proof| OutputManagerResponse::BalanceProof(Box::new(proof))),
+            OutputManagerRequest::CreateClaimShaAtomicSwapTransaction(
+                output_hash,
+                pre_image,
+                fee_per_gram,
+                account,
+            ) => {
+                self.claim_sha_atomic_swap_with_hash(output_hash, pre_image, fee_per_gram, account)
                     .await
             },
-            OutputManagerRequest::CreateHtlcRefundTransaction(output, fee_per_gram) => self
-                .create_htlc_refund_transaction(output, fee_per_gram)
+            OutputManagerRequest::CreateHtlcRefundTransaction(output, fee_per_gram, account) => self
+                .create_htlc_refund_transaction(output, fee_per_gram, account)
                 .await
                 .map(OutputManagerResponse::ClaimHtlcTransaction),
             OutputManagerRequest::GetOutputInfoByTxId(tx_id) => {
@@ -441,6 +541,7 @@ where
         output_hash: HashOutput,
         pre_image: PublicKey,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<OutputManagerResponse, OutputManagerError> {
         let output = self
             .fetch_outputs_from_node(vec![output_hash])
@@ -448,7 +549,7 @@ where
             .pop()
             .ok_or_else(|| OutputManagerError::ServiceError("Output not found".to_string()))?;
 
-        self.create_claim_sha_atomic_swap_transaction(output, pre_image, fee_per_gram)
+        self.create_claim_sha_atomic_swap_transaction(output, pre_image, fee_per_gram, account)
             .await
             .map(OutputManagerResponse::ClaimHtlcTransaction)
     }
@@ -636,6 +737,38 @@ where
         Ok(())
     }
 
+    /// Adds a batch of key manager outputs as `EncumberedToBeReceived`. The batch is written in a single database
+    /// transaction, outputs the wallet already holds are skipped.
+    pub async fn add_unvalidated_outputs(
+        &mut self,
+        outputs: Vec<(TxId, WalletOutput)>,
+    ) -> Result<Vec<bool>, OutputManagerError> {
+        debug!(
+            target: LOG_TARGET,
+            "Add {} unvalidated outputs to Output Manager",
+            outputs.len()
+        );
+        let mut db_outputs = Vec::with_capacity(outputs.len());
+        for (tx_id, output) in outputs {
+            let output = DbWalletOutput::from_wallet_output(
+                output,
+                &self.resources.key_manager,
+                None,
+                OutputSource::default(),
+                Some(tx_id),
+                None,
+            )
+            .await?;
+            db_outputs.push((tx_id, output));
+        }
+        let added = self.resources.db.add_unvalidated_outputs(db_outputs)?;
+
+        if added.iter().any(|a| *a) {
+            self.validate_outputs()?;
+        }
+        Ok(added)
+    }
+
     /// Update an output's metadata signature, akin to 'finalize output'
     pub fn update_output_metadata_signature(&mut self, output: TransactionOutput) -> Result<(), OutputManagerError> {
         self.resources.db.update_output_metadata_signature(output)?;
@@ -665,6 +798,59 @@ where
         Ok(balance)
     }
 
+    fn get_balance_breakdown(
+        &self,
+        tip_height: u64,
+        account: Option<u64>,
+    ) -> Result<BalanceBreakdown, OutputManagerError> {
+        // Outputs encumbered to be spent belong to pending outbound transactions, and are left out
+        let outputs = self.resources.db.fetch_outputs_by_query(OutputBackendQuery {
+            status: vec![
+                OutputStatus::Unspent,
+                OutputStatus::UnspentMinedUnconfirmed,
+                OutputStatus::EncumberedToBeReceived,
+                OutputStatus::ShortTermEncumberedToBeReceived,
+            ],
+            account,
+            ..Default::default()
+        })?;
+        let coinbase_min_maturity = self.resources.consensus_constants.coinbase_min_maturity();
+        let entries = outputs
+            .into_iter()
+            .map(|output| {
+                let mut spendable_height = output
+                    .wallet_output
+                    .features
+                    .maturity
+                    .max(output.wallet_output.script_lock_height);
+                if let (OutputSource::Coinbase, Some(mined_height)) = (&output.source, output.mined_height) {
+                    spendable_height = spendable_height.max(mined_height.saturating_add(coinbase_min_maturity));
+                }
+                BalanceEntry {
+                    value: output.wallet_output.value,
+                    mined_height: output.mined_height,
+                    spendable_height,
+                }
+            })
+            .collect();
+        Ok(BalanceBreakdown::new(tip_height, entries))
+    }
+
+    fn get_account_transaction_ids(&self, account: u64) -> Result<Vec<TxId>, OutputManagerError> {
+        let outputs = self.resources.db.fetch_outputs_by_query(OutputBackendQuery {
+            account: Some(account),
+            ..Default::default()
+        })?;
+        let mut tx_ids = outputs
+            .into_iter()
+            .flat_map(|o| vec![o.received_in_tx_id, o.spent_in_tx_id])
+            .flatten()
+            .collect::<Vec<_>>();
+        tx_ids.sort_unstable_by_key(TxId::as_u64);
+        tx_ids.dedup();
+        Ok(tx_ids)
+    }
+
     /// Request a receiver transaction be generated from the supplied Sender Message
     async fn get_default_recipient_transaction(
         &mut self,
@@ -711,7 +897,13 @@ where
         let encrypted_data = self
             .resources
             .key_manager
-            .encrypt_data_for_recovery(&spending_key_id, None, single_round_sender_data.amount.as_u64())
+            .encrypt_data_for_recovery(
+                &spending_key_id,
+                None,
+                single_round_sender_data.amount.as_u64(),
+                None,
+                None,
+            )
             .await
             .unwrap();
         let minimum_value_promise = single_round_sender_data.minimum_value_promise;
@@ -859,6 +1051,65 @@ where
         Ok(fee)
     }
 
+    /// Sends that don't ask for a specific ordering use the configured coin selection strategy
+    fn coin_selection_ordering(&self, requested: UtxoSelectionOrdering) -> UtxoSelectionOrdering {
+        if requested == UtxoSelectionOrdering::Default {
+            return self.resources.config.coin_selection_strategy;
+        }
+        requested
+    }
+
+    async fn preview_coin_selection(
+        &mut self,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<CoinSelectionReport, OutputManagerError> {
+        // We assume that a single recipient output with default OutputFeatures and PushPubKey TariScript is used
+        let features_and_scripts_byte_size = self
+            .resources
+            .consensus_constants
+            .transaction_weight_params()
+            .round_up_features_and_scripts_size(
+                OutputFeatures::default()
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    TariScript::default()
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    Covenant::new()
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+            );
+        let strategy = self.coin_selection_ordering(selection_criteria.ordering);
+        let utxo_selection = self
+            .select_utxos(
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                1,
+                features_and_scripts_byte_size,
+            )
+            .await?;
+
+        let (num_outputs, total_features_and_scripts_byte_size) = if utxo_selection.requires_change_output() {
+            (
+                2,
+                features_and_scripts_byte_size + self.default_features_and_scripts_size()?,
+            )
+        } else {
+            (1, features_and_scripts_byte_size)
+        };
+        let weight = self.get_fee_calc().weighting().calculate(
+            1,
+            utxo_selection.num_selected(),
+            num_outputs,
+            total_features_and_scripts_byte_size,
+        );
+
+        Ok(utxo_selection.report(strategy, amount, weight))
+    }
+
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
     /// will be produced.
     #[allow(clippy::too_many_lines)]
@@ -882,6 +1133,7 @@ where
             selection_criteria,
             fee_per_gram,
         );
+        let account = selection_criteria.account;
         let features_and_scripts_byte_size = self
             .resources
             .consensus_constants
@@ -970,7 +1222,8 @@ where
                     Some(tx_id),
                     None,
                 )
-                .await?,
+                .await?
+                .with_account(account),
             );
         }
 
@@ -993,6 +1246,7 @@ where
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, Transaction), OutputManagerError> {
         let total_value = outputs.iter().map(|o| o.value()).sum();
+        let account = selection_criteria.account;
         let nop_script = script![Nop];
         let weighting = self.resources.consensus_constants.transaction_weight_params();
         let mut features_and_scripts_byte_size = 0;
@@ -1078,7 +1332,8 @@ where
                     None,
                     None,
                 )
-                .await?,
+                .await?
+                .with_account(account),
             )
         }
 
@@ -1097,7 +1352,8 @@ where
                     Some(tx_id),
                     None,
                 )
-                .await?,
+                .await?
+                .with_account(account),
             );
         }
 
@@ -1109,6 +1365,152 @@ where
         Ok((tx_id, stp.into_transaction()?))
     }
 
+    /// Creates a single transaction with a one-sided output for each of the recipients, returning any change to this
+    /// wallet. Only the change output is recorded as an output of this wallet.
+    #[allow(clippy::too_many_lines)]
+    async fn create_one_sided_batch_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipients: Vec<OneSidedBatchRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        if recipients.is_empty() {
+            return Err(OutputManagerError::BuildError(
+                "A batch transaction needs at least one recipient".to_string(),
+            ));
+        }
+        let account = selection_criteria.account;
+        let total_value: MicroMinotari = recipients.iter().map(|recipient| recipient.amount).sum();
+        let weighting = self.resources.consensus_constants.transaction_weight_params();
+        let output_features = OutputFeatures::default();
+        let covenant = Covenant::default();
+        let mut features_and_scripts_byte_size = 0;
+        for recipient in &recipients {
+            let script = one_sided_payment_script(&recipient.public_key);
+            features_and_scripts_byte_size += weighting.round_up_features_and_scripts_size(
+                output_features
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    script
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    covenant
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+            );
+        }
+
+        let input_selection = self
+            .select_utxos(
+                total_value,
+                selection_criteria,
+                fee_per_gram,
+                recipients.len(),
+                features_and_scripts_byte_size,
+            )
+            .await?;
+
+        let mut builder = SenderTransactionProtocol::builder(
+            self.resources.consensus_constants.clone(),
+            self.resources.key_manager.clone(),
+        );
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_tx_id(tx_id);
+
+        for kmo in input_selection.iter() {
+            builder.with_input(kmo.wallet_output.clone()).await?;
+        }
+
+        for recipient in recipients {
+            // The recipient derives the spending and encryption keys of its output from the Diffie-Hellman shared
+            // secret of the sender offset key, exactly as for a single one-sided payment
+            let (sender_offset_key_id, _) = self
+                .resources
+                .key_manager
+                .get_next_key(&TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+                .await?;
+            let shared_secret = self
+                .resources
+                .key_manager
+                .get_diffie_hellman_shared_secret(&sender_offset_key_id, &recipient.public_key)
+                .await?;
+            let spending_key_id = self
+                .resources
+                .key_manager
+                .import_key(shared_secret_to_output_spending_key(&shared_secret)?)
+                .await?;
+            let encryption_key_id = self
+                .resources
+                .key_manager
+                .import_key(shared_secret_to_output_encryption_key(&shared_secret)?)
+                .await?;
+
+            let output = WalletOutputBuilder::new(recipient.amount, spending_key_id)
+                .with_features(output_features.clone())
+                .with_script(one_sided_payment_script(&recipient.public_key))
+                .with_payment_reference(recipient.payment_reference)
+                .encrypt_data_for_recovery(&self.resources.key_manager, Some(&encryption_key_id))
+                .await?
+                .with_input_data(ExecutionStack::default())
+                .with_script_key(self.resources.wallet_identity.wallet_node_key_id.clone())
+                .with_minimum_value_promise(MicroMinotari::zero())
+                .sign_as_sender_and_receiver(&self.resources.key_manager, &sender_offset_key_id)
+                .await?
+                .try_build(&self.resources.key_manager)
+                .await?;
+            builder
+                .with_output(output, sender_offset_key_id)
+                .await
+                .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
+        }
+
+        let (change_spending_key_id, _spend_public_key, change_script_key_id, change_script_public_key) =
+            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+        builder.with_change_data(
+            script!(PushPubKey(Box::new(change_script_public_key))),
+            ExecutionStack::default(),
+            change_script_key_id,
+            change_spending_key_id,
+            Covenant::default(),
+        );
+
+        let mut stp = builder
+            .build()
+            .await
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut outputs = Vec::new();
+        if let Some(wallet_output) = stp.get_change_output()? {
+            outputs.push(
+                DbWalletOutput::from_wallet_output(
+                    wallet_output,
+                    &self.resources.key_manager,
+                    None,
+                    OutputSource::default(),
+                    Some(tx_id),
+                    None,
+                )
+                .await?
+                .with_account(account),
+            );
+        }
+
+        self.resources
+            .db
+            .encumber_outputs(tx_id, input_selection.into_selected(), outputs)?;
+        self.confirm_encumberance(tx_id)?;
+        let fee = stp.get_fee_amount()?;
+        stp.finalize(&self.resources.key_manager).await?;
+        let transaction = stp.into_transaction()?;
+
+        Ok((fee, transaction))
+    }
+
     async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -1118,6 +1520,7 @@ where
         fee_per_gram: MicroMinotari,
         lock_height: Option<u64>,
     ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        let account = selection_criteria.account;
         let covenant = Covenant::default();
 
         let features_and_scripts_byte_size = self
@@ -1169,7 +1572,7 @@ where
             .await
             .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
 
-        let mut outputs = vec![output];
+        let mut outputs = vec![output.with_account(account)];
 
         let (change_spending_key_id, _spend_public_key, change_script_key_id, change_script_public_key) =
             self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
@@ -1200,7 +1603,8 @@ where
                 Some(tx_id),
                 None,
             )
-            .await?;
+            .await?
+            .with_account(account);
             outputs.push(change_output);
         }
 
@@ -1246,6 +1650,25 @@ where
         Ok(())
     }
 
+    /// Restore the encumberance of the inputs and the pending change output of an outbound transaction whose outputs
+    /// were cancelled, used when a replacement for the transaction could not be built.
+    fn reinstate_cancelled_outbound_transaction_outputs(
+        &mut self,
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    ) -> Result<(), OutputManagerError> {
+        let inputs = self.resources.db.fetch_unspent_outputs_for_spending(
+            &UtxoSelectionCriteria::specific(commitments),
+            MicroMinotari::zero(),
+            None,
+        )?;
+        self.resources.db.encumber_outputs(tx_id, inputs, Vec::new())?;
+        self.confirm_encumberance(tx_id)?;
+        self.resources.db.reinstate_cancelled_inbound_output(tx_id)?;
+
+        Ok(())
+    }
+
     /// Select which unspent transaction outputs to use to send a transaction of the specified amount. Use the specified
     /// selection strategy to choose the outputs. It also determines if a change output is required.
     #[allow(clippy::too_many_lines)]
@@ -1268,8 +1691,6 @@ where
             total_output_features_and_scripts_byte_size,
             selection_criteria
         );
-        let mut utxos = Vec::new();
-
         let fee_calc = self.get_fee_calc();
 
         // Attempt to get the chain tip height
@@ -1279,6 +1700,7 @@ where
         if self.resources.config.autoignore_onesided_utxos {
             selection_criteria.excluding_onesided = self.resources.config.autoignore_onesided_utxos;
         }
+        selection_criteria.ordering = self.coin_selection_ordering(selection_criteria.ordering);
 
         debug!(
             target: LOG_TARGET,
@@ -1301,7 +1723,11 @@ where
         let start_new = Instant::now();
 
         // For non-standard queries, we want to ensure that the intended UTXOs are selected
-        if !selection_criteria.filter.is_standard() && uo.is_empty() {
+        let missing_specific_outputs = match &selection_criteria.filter {
+            UtxoSelectionFilter::Standard => false,
+            UtxoSelectionFilter::SpecificOutputs { commitments } => uo.is_empty() || uo.len() < commitments.len(),
+        };
+        if missing_specific_outputs {
             return Err(OutputManagerError::NoUtxosSelected {
                 criteria: selection_criteria,
             });
@@ -1323,53 +1749,41 @@ where
 
         trace!(target: LOG_TARGET, "We found {} UTXOs to select from", uo_len);
 
-        let mut requires_change_output = false;
-        let mut utxos_total_value = MicroMinotari::from(0);
-        let mut fee_without_change = MicroMinotari::from(0);
-        let mut fee_with_change = MicroMinotari::from(0);
-        for o in uo {
-            utxos_total_value += o.wallet_output.value;
-
-            trace!(target: LOG_TARGET, "-- utxos_total_value = {:?}", utxos_total_value);
-            utxos.push(o);
-            // The assumption here is that the only output will be the payment output and change if required
-            fee_without_change = fee_calc.calculate(
-                fee_per_gram,
-                1,
-                utxos.len(),
-                num_outputs,
-                total_output_features_and_scripts_byte_size,
-            );
-            if utxos_total_value == amount + fee_without_change {
-                break;
-            }
-            fee_with_change = fee_calc.calculate(
-                fee_per_gram,
-                1,
-                utxos.len(),
-                num_outputs + 1,
-                total_output_features_and_scripts_byte_size + default_features_and_scripts_size,
-            );
-
-            trace!(target: LOG_TARGET, "-- amt+fee = {:?} {}", amount, fee_with_change);
-            if utxos_total_value > amount + fee_with_change {
-                requires_change_output = true;
-                break;
-            }
-        }
+        let target = SelectionTarget {
+            amount,
+            fee_per_gram,
+            num_outputs,
+            features_and_scripts_byte_size: total_output_features_and_scripts_byte_size,
+            change_features_and_scripts_byte_size: default_features_and_scripts_size,
+            fee_calc: &fee_calc,
+        };
+        let strategy = coin_selection_strategy(selection_criteria.ordering);
+        let selection = if selection_criteria.filter.is_standard() {
+            strategy.select(uo, &target)
+        } else {
+            // Explicitly chosen inputs are all spent, not only as many as are needed to cover the amount
+            UtxoSelection::accumulate(uo, &target, true)
+        };
+        debug!(
+            target: LOG_TARGET,
+            "select_utxos strategy: {}, inputs: {}, total value: {}, fee: {}, change: {}",
+            strategy.name(),
+            selection.num_selected(),
+            selection.total_value(),
+            selection.as_final_fee(),
+            selection.change_value(amount)
+        );
 
-        let perfect_utxo_selection = utxos_total_value == amount + fee_without_change;
-        let enough_spendable = utxos_total_value > amount + fee_with_change;
         trace!(
             target: LOG_TARGET,
             "select_utxos profile - final_selection: {} outputs from {}, {} ms (at {})",
-            utxos.len(),
+            selection.num_selected(),
             uo_len,
             start_new.elapsed().as_millis(),
             start.elapsed().as_millis(),
         );
 
-        if !perfect_utxo_selection && !enough_spendable {
+        if !selection.is_sufficient(amount) {
             if uo_len == TRANSACTION_INPUTS_LIMIT as usize {
                 return Err(OutputManagerError::TooManyInputsToFulfillTransaction(format!(
                     "Input limit '{}' reached",
@@ -1379,20 +1793,14 @@ where
             let current_tip_for_time_lock_calculation = chain_metadata.map(|cm| cm.best_block_height());
             let balance = self.get_balance(current_tip_for_time_lock_calculation)?;
             let pending_incoming = balance.pending_incoming_balance;
-            if utxos_total_value + pending_incoming >= amount + fee_with_change {
+            if selection.total_value() + pending_incoming >= amount + selection.fee_with_change {
                 return Err(OutputManagerError::FundsPending);
             } else {
                 return Err(OutputManagerError::NotEnoughFunds);
             }
         }
 
-        Ok(UtxoSelection {
-            utxos,
-            requires_change_output,
-            total_value: utxos_total_value,
-            fee_without_change,
-            fee_with_change,
-        })
+        Ok(selection)
     }
 
     pub fn fetch_spent_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerError> {
@@ -1403,6 +1811,78 @@ where
         Ok(self.resources.db.fetch_all_unspent_outputs()?)
     }
 
+    /// Creates a proof of the total value held in the selected unspent outputs, without revealing their individual
+    /// values or spend keys. All unspent outputs are used if no commitments are given.
+    async fn create_balance_proof(
+        &self,
+        commitments: Vec<Commitment>,
+        message: String,
+    ) -> Result<BalanceProof, OutputManagerError> {
+        let unspent_outputs = self.resources.db.fetch_all_unspent_outputs()?;
+        let selected = if commitments.is_empty() {
+            unspent_outputs
+        } else {
+            let mut selected: Vec<DbWalletOutput> = Vec::with_capacity(commitments.len());
+            for commitment in &commitments {
+                if selected.iter().any(|o| &o.commitment == commitment) {
+                    continue;
+                }
+                let output = unspent_outputs
+                    .iter()
+                    .find(|o| &o.commitment == commitment)
+                    .ok_or_else(|| {
+                        OutputManagerError::InvalidArgument(format!(
+                            "Commitment {} is not an unspent output of this wallet",
+                            commitment.to_hex()
+                        ))
+                    })?;
+                selected.push(output.clone());
+            }
+            selected
+        };
+        if selected.is_empty() {
+            return Err(OutputManagerError::InvalidArgument(
+                "There are no unspent outputs to prove".to_string(),
+            ));
+        }
+
+        let total: MicroMinotari = selected.iter().map(|o| o.wallet_output.value).sum();
+        let spend_key_ids = selected
+            .iter()
+            .map(|o| o.wallet_output.spending_key_id.clone())
+            .collect::<Vec<_>>();
+        let commitments = selected.into_iter().map(|o| o.commitment).collect();
+        let proof = self
+            .resources
+            .key_manager
+            .create_balance_proof(&spend_key_ids, commitments, total, message)
+            .await?;
+        debug!(
+            target: LOG_TARGET,
+            "Created balance proof of {} over {} outputs",
+            total,
+            spend_key_ids.len()
+        );
+        Ok(proof)
+    }
+
+    /// Lists the outputs that input selection would consider for the given criteria, without encumbering them
+    pub async fn fetch_spendable_outputs(
+        &mut self,
+        selection_criteria: UtxoSelectionCriteria,
+    ) -> Result<Vec<DbWalletOutput>, OutputManagerError> {
+        let tip_height = self
+            .base_node_service
+            .get_chain_metadata()
+            .await?
+            .map(|m| m.best_block_height());
+        Ok(self.resources.db.fetch_unspent_outputs_for_spending(
+            &selection_criteria,
+            MicroMinotari::zero(),
+            tip_height,
+        )?)
+    }
+
     pub fn fetch_outputs_by_query(&self, q: OutputBackendQuery) -> Result<Vec<DbWalletOutput>, OutputManagerError> {
         Ok(self.resources.db.fetch_outputs_by_query(q)?)
     }
@@ -1511,6 +1991,7 @@ where
         amount_per_split: Option<MicroMinotari>,
         number_of_splits: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         if commitments.is_empty() {
             return Err(OutputManagerError::NoCommitmentsProvided);
@@ -1524,11 +2005,11 @@ where
 
         match amount_per_split {
             None => {
-                self.create_coin_split_even(src_outputs, number_of_splits, fee_per_gram)
+                self.create_coin_split_even(src_outputs, number_of_splits, fee_per_gram, account)
                     .await
             },
             Some(amount_per_split) => {
-                self.create_coin_split(src_outputs, amount_per_split, number_of_splits, fee_per_gram)
+                self.create_coin_split(src_outputs, amount_per_split, number_of_splits, fee_per_gram, account)
                     .await
             },
         }
@@ -1539,16 +2020,20 @@ where
         amount_per_split: Option<MicroMinotari>,
         number_of_splits: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         match amount_per_split {
             None => Err(OutputManagerError::InvalidArgument(
                 "coin split without `amount_per_split` is not supported yet".to_string(),
             )),
             Some(amount_per_split) => {
+                let mut selection_criteria =
+                    UtxoSelectionCriteria::largest_first(self.resources.config.dust_ignore_value);
+                selection_criteria.account = account;
                 let selection = self
                     .select_utxos(
                         amount_per_split * MicroMinotari(number_of_splits as u64),
-                        UtxoSelectionCriteria::largest_first(self.resources.config.dust_ignore_value),
+                        selection_criteria,
                         fee_per_gram,
                         number_of_splits,
                         self.default_features_and_scripts_size()
@@ -1557,8 +2042,14 @@ where
                     )
                     .await?;
 
-                self.create_coin_split(selection.utxos, amount_per_split, number_of_splits, fee_per_gram)
-                    .await
+                self.create_coin_split(
+                    selection.utxos,
+                    amount_per_split,
+                    number_of_splits,
+                    fee_per_gram,
+                    account,
+                )
+                .await
             },
         }
     }
@@ -1569,6 +2060,7 @@ where
         src_outputs: Vec<DbWalletOutput>,
         number_of_splits: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         if number_of_splits == 0 {
             return Err(OutputManagerError::InvalidArgument(
@@ -1640,7 +2132,7 @@ where
                 .await
                 .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
 
-            dest_outputs.push(output);
+            dest_outputs.push(output.with_account(account));
         }
 
         let mut stp = tx_builder
@@ -1683,6 +2175,7 @@ where
         amount_per_split: MicroMinotari,
         number_of_splits: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         if number_of_splits == 0 {
             return Err(OutputManagerError::InvalidArgument(
@@ -1795,7 +2288,7 @@ where
                 .await
                 .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
 
-            dest_outputs.push(output);
+            dest_outputs.push(output.with_account(account));
         }
 
         let has_leftover_change = change > MicroMinotari::zero();
@@ -1847,7 +2340,8 @@ where
                     Some(tx_id),
                     None,
                 )
-                .await?,
+                .await?
+                .with_account(account),
             );
         }
 
@@ -1888,7 +2382,7 @@ where
         let encrypted_data = self
             .resources
             .key_manager
-            .encrypt_data_for_recovery(&spending_key_id, None, amount.as_u64())
+            .encrypt_data_for_recovery(&spending_key_id, None, amount.as_u64(), None, None)
             .await?;
         let minimum_value_promise = MicroMinotari::zero();
         let metadata_message = TransactionOutput::metadata_signature_message_from_parts(
@@ -1950,6 +2444,7 @@ where
         &mut self,
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
         let default_features_and_scripts_size = self
             .default_features_and_scripts_size()
@@ -2032,7 +2527,7 @@ where
         // encumbering transaction
         self.resources
             .db
-            .encumber_outputs(tx_id, src_outputs.clone(), vec![output])?;
+            .encumber_outputs(tx_id, src_outputs.clone(), vec![output.with_account(account)])?;
         self.confirm_encumberance(tx_id)?;
 
         trace!(
@@ -2075,13 +2570,11 @@ where
         Ok(results)
     }
 
-    #[allow(clippy::too_many_lines)]
-    pub async fn create_claim_sha_atomic_swap_transaction(
-        &mut self,
-        output: TransactionOutput,
-        pre_image: PublicKey,
-        fee_per_gram: MicroMinotari,
-    ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
+    /// Opens the commitment of an HTLC output that pays to this wallet, and returns its value and spending key
+    async fn open_htlc_output(
+        &self,
+        output: &TransactionOutput,
+    ) -> Result<(MicroMinotari, PrivateKey), OutputManagerError> {
         let shared_secret = self
             .resources
             .key_manager
@@ -2091,107 +2584,116 @@ where
             )
             .await?;
         let encryption_key = shared_secret_to_output_encryption_key(&shared_secret)?;
-        if let Ok((amount, spending_key)) =
-            EncryptedData::decrypt_data(&encryption_key, &output.commitment, &output.encrypted_data)
-        {
-            if output.verify_mask(&self.resources.factories.range_proof, &spending_key, amount.as_u64())? {
-                let spending_key_id = self.resources.key_manager.import_key(spending_key).await?;
-                let rewound_output = WalletOutput::new(
-                    output.version,
-                    amount,
-                    spending_key_id,
-                    output.features,
-                    output.script,
-                    inputs!(pre_image),
-                    self.resources.wallet_identity.wallet_node_key_id.clone(),
-                    output.sender_offset_public_key,
-                    output.metadata_signature,
-                    // Although the technically the script does have a script lock higher than 0, this does not apply
-                    // to to us as we are claiming the Hashed part which has a 0 time lock
-                    0,
-                    output.covenant,
-                    output.encrypted_data,
-                    output.minimum_value_promise,
-                    &self.resources.key_manager,
-                )
-                .await?;
+        let (amount, spending_key) =
+            EncryptedData::decrypt_data(&encryption_key, &output.commitment, &output.encrypted_data).map_err(|_| {
+                OutputManagerError::TransactionError(TransactionError::RangeProofError(
+                    "Atomic swap: Encrypted value could not be decrypted!".to_string(),
+                ))
+            })?;
+        if !output.verify_mask(&self.resources.factories.range_proof, &spending_key, amount.as_u64())? {
+            return Err(OutputManagerError::TransactionError(TransactionError::RangeProofError(
+                "Atomic swap: Blinding factor could not open the commitment!".to_string(),
+            )));
+        }
+        Ok((amount, spending_key))
+    }
 
-                let message = "SHA-XTR atomic swap".to_string();
+    #[allow(clippy::too_many_lines)]
+    pub async fn create_claim_sha_atomic_swap_transaction(
+        &mut self,
+        output: TransactionOutput,
+        pre_image: PublicKey,
+        fee_per_gram: MicroMinotari,
+        account: u64,
+    ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
+        let (amount, spending_key) = self.open_htlc_output(&output).await?;
+        let spending_key_id = self.resources.key_manager.import_key(spending_key).await?;
+        let rewound_output = WalletOutput::new(
+            output.version,
+            amount,
+            spending_key_id,
+            output.features,
+            output.script,
+            inputs!(pre_image),
+            self.resources.wallet_identity.wallet_node_key_id.clone(),
+            output.sender_offset_public_key,
+            output.metadata_signature,
+            // Although the technically the script does have a script lock higher than 0, this does not apply
+            // to to us as we are claiming the Hashed part which has a 0 time lock
+            0,
+            output.covenant,
+            output.encrypted_data,
+            output.minimum_value_promise,
+            &self.resources.key_manager,
+        )
+        .await?;
 
-                // Create builder with no recipients (other than ourselves)
-                let mut builder = SenderTransactionProtocol::builder(
-                    self.resources.consensus_constants.clone(),
-                    self.resources.key_manager.clone(),
-                );
-                builder
-                    .with_lock_height(0)
-                    .with_fee_per_gram(fee_per_gram)
-                    .with_message(message)
-                    .with_kernel_features(KernelFeatures::empty())
-                    .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
-                    .with_input(rewound_output)
-                    .await?;
+        let message = "SHA-XTR atomic swap".to_string();
 
-                let mut outputs = Vec::new();
+        // Create builder with no recipients (other than ourselves)
+        let mut builder = SenderTransactionProtocol::builder(
+            self.resources.consensus_constants.clone(),
+            self.resources.key_manager.clone(),
+        );
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_message(message)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_input(rewound_output)
+            .await?;
 
-                let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
-                    self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
-                builder.with_change_data(
-                    script!(PushPubKey(Box::new(change_script_public_key.clone()))),
-                    ExecutionStack::default(),
-                    change_script_key_id,
-                    change_spending_key_id,
-                    Covenant::default(),
-                );
+        let mut outputs = Vec::new();
 
-                let mut stp = builder
-                    .build()
-                    .await
-                    .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
+            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+        builder.with_change_data(
+            script!(PushPubKey(Box::new(change_script_public_key.clone()))),
+            ExecutionStack::default(),
+            change_script_key_id,
+            change_spending_key_id,
+            Covenant::default(),
+        );
 
-                let tx_id = stp.get_tx_id()?;
+        let mut stp = builder
+            .build()
+            .await
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
 
-                let wallet_output = stp.get_change_output()?.ok_or_else(|| {
-                    OutputManagerError::BuildError(
-                        "There should be a change output metadata signature available".to_string(),
-                    )
-                })?;
-                let change_output = DbWalletOutput::from_wallet_output(
-                    wallet_output,
-                    &self.resources.key_manager,
-                    None,
-                    OutputSource::AtomicSwap,
-                    Some(tx_id),
-                    None,
-                )
-                .await?;
-                outputs.push(change_output);
+        let tx_id = stp.get_tx_id()?;
 
-                trace!(target: LOG_TARGET, "Claiming HTLC with transaction ({}).", tx_id);
-                self.resources.db.encumber_outputs(tx_id, Vec::new(), outputs)?;
-                self.confirm_encumberance(tx_id)?;
-                let fee = stp.get_fee_amount()?;
-                trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
-                stp.finalize(&self.resources.key_manager).await?;
-                let tx = stp.into_transaction()?;
+        let wallet_output = stp.get_change_output()?.ok_or_else(|| {
+            OutputManagerError::BuildError("There should be a change output metadata signature available".to_string())
+        })?;
+        let change_output = DbWalletOutput::from_wallet_output(
+            wallet_output,
+            &self.resources.key_manager,
+            None,
+            OutputSource::AtomicSwap,
+            Some(tx_id),
+            None,
+        )
+        .await?
+        .with_account(account);
+        outputs.push(change_output);
 
-                Ok((tx_id, fee, amount - fee, tx))
-            } else {
-                Err(OutputManagerError::TransactionError(TransactionError::RangeProofError(
-                    "Atomic swap: Blinding factor could not open the commitment!".to_string(),
-                )))
-            }
-        } else {
-            Err(OutputManagerError::TransactionError(TransactionError::RangeProofError(
-                "Atomic swap: Encrypted value could not be decrypted!".to_string(),
-            )))
-        }
+        trace!(target: LOG_TARGET, "Claiming HTLC with transaction ({}).", tx_id);
+        self.resources.db.encumber_outputs(tx_id, Vec::new(), outputs)?;
+        self.confirm_encumberance(tx_id)?;
+        let fee = stp.get_fee_amount()?;
+        trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
+        stp.finalize(&self.resources.key_manager).await?;
+        let tx = stp.into_transaction()?;
+
+        Ok((tx_id, fee, amount - fee, tx))
     }
 
     pub async fn create_htlc_refund_transaction(
         &mut self,
         output_hash: HashOutput,
         fee_per_gram: MicroMinotari,
+        account: u64,
     ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
         let output = self.resources.db.get_unspent_output(output_hash)?.wallet_output;
 
@@ -2244,7 +2746,8 @@ where
             Some(tx_id),
             None,
         )
-        .await?;
+        .await?
+        .with_account(account);
         outputs.push(change_output);
 
         trace!(target: LOG_TARGET, "Claiming HTLC refund with transaction ({}).", tx_id);
@@ -2281,6 +2784,36 @@ where
         Ok(())
     }
 
+    /// The subaddress at `index`, which receives one-sided payments into the default account. Index 0 is the wallet's
+    /// own address. The subaddress script is persisted so that payments to the subaddress are found when scanning.
+    async fn get_subaddress(&mut self, index: u64) -> Result<TariAddress, OutputManagerError> {
+        if index == 0 {
+            return Ok(self.resources.wallet_identity.address.clone());
+        }
+        let script_key_id = self
+            .resources
+            .key_manager
+            .get_subaddress_key_id(&self.resources.wallet_identity.wallet_node_key_id, index)
+            .await?;
+        let public_key = self
+            .resources
+            .key_manager
+            .get_public_key_at_key_id(&script_key_id)
+            .await?;
+        let script = one_sided_payment_script(&public_key);
+        self.add_known_script(KnownOneSidedPaymentScript {
+            script_hash: script.as_hash::<Blake2b<U32>>()?.to_vec(),
+            script_key_id,
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        })?;
+        Ok(TariAddress::new_subaddress(
+            public_key,
+            self.resources.wallet_identity.network,
+        ))
+    }
+
     // Scanning outputs addressed to this wallet
     async fn scan_outputs_for_one_sided_payments(
         &mut self,
@@ -2300,6 +2833,9 @@ where
 
         let wallet_sk = self.resources.wallet_identity.wallet_node_key_id.clone();
         let wallet_pk = self.resources.key_manager.get_public_key_at_key_id(&wallet_sk).await?;
+        // Stealth payments need a Diffie-Hellman exchange per candidate key, so the wallet key is tried first
+        let mut stealth_keys = vec![(wallet_pk.clone(), wallet_sk)];
+        stealth_keys.extend(known_keys.iter().filter(|(pk, _)| pk != &wallet_pk).cloned());
 
         let mut scanned_outputs = vec![];
 
@@ -2334,37 +2870,41 @@ where
                 // NOTE: Extracting the nonce R and a spending (public aka scan_key) key from the script
                 // NOTE: [RFC 203 on Stealth Addresses](https://rfc.tari.com/RFC-0203_StealthAddresses.html)
                 [Opcode::PushPubKey(nonce), Opcode::Drop, Opcode::PushPubKey(scanned_pk)] => {
-                    // matching spending (public) keys
-                    let stealth_address_hasher = self
-                        .resources
-                        .key_manager
-                        .get_diffie_hellman_stealth_domain_hasher(&wallet_sk, nonce.as_ref())
-                        .await?;
-                    let script_spending_key = stealth_address_script_spending_key(&stealth_address_hasher, &wallet_pk);
-                    if &script_spending_key != scanned_pk.as_ref() {
-                        continue;
-                    }
+                    // matching spending (public) keys of the wallet, its subaddresses and accounts
+                    for (public_key, key_id) in &stealth_keys {
+                        let stealth_address_hasher = self
+                            .resources
+                            .key_manager
+                            .get_diffie_hellman_stealth_domain_hasher(key_id, nonce.as_ref())
+                            .await?;
+                        let script_spending_key =
+                            stealth_address_script_spending_key(&stealth_address_hasher, public_key);
+                        if &script_spending_key != scanned_pk.as_ref() {
+                            continue;
+                        }
 
-                    // Compute the stealth address offset
-                    let stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
-                        .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size");
-                    let stealth_key = self
-                        .resources
-                        .key_manager
-                        .import_add_offset_to_private_key(&wallet_sk, stealth_address_offset)
-                        .await?;
-
-                    let shared_secret = self
-                        .resources
-                        .key_manager
-                        .get_diffie_hellman_shared_secret(&wallet_sk, &output.sender_offset_public_key)
-                        .await?;
-                    scanned_outputs.push((
-                        output.clone(),
-                        OutputSource::StealthOneSided,
-                        stealth_key,
-                        shared_secret,
-                    ));
+                        // Compute the stealth address offset
+                        let stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
+                            .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size");
+                        let stealth_key = self
+                            .resources
+                            .key_manager
+                            .import_add_offset_to_private_key(key_id, stealth_address_offset)
+                            .await?;
+
+                        let shared_secret = self
+                            .resources
+                            .key_manager
+                            .get_diffie_hellman_shared_secret(key_id, &output.sender_offset_public_key)
+                            .await?;
+                        scanned_outputs.push((
+                            output.clone(),
+                            OutputSource::StealthOneSided,
+                            stealth_key,
+                            shared_secret,
+                        ));
+                        break;
+                    }
                 },
 
                 _ => {},
@@ -2382,9 +2922,10 @@ where
         let mut rewound_outputs = Vec::with_capacity(scanned_outputs.len());
 
         for (output, output_source, script_private_key, shared_secret) in scanned_outputs {
+            let account = account_index_from_key_id(&script_private_key);
             let encryption_key = shared_secret_to_output_encryption_key(&shared_secret)?;
-            if let Ok((committed_value, spending_key)) =
-                EncryptedData::decrypt_data(&encryption_key, &output.commitment, &output.encrypted_data)
+            if let Ok((committed_value, spending_key, payment_reference, memo)) =
+                EncryptedData::decrypt_data_with_memo(&encryption_key, &output.commitment, &output.encrypted_data)
             {
                 if output.verify_mask(
                     &self.resources.factories.range_proof,
@@ -2419,7 +2960,8 @@ where
                         Some(tx_id),
                         None,
                     )
-                    .await?;
+                    .await?
+                    .with_account(account);
 
                     match self.resources.db.add_unspent_output_with_tx_id(tx_id, db_output) {
                         Ok(_) => {
@@ -2434,6 +2976,8 @@ where
                                 output: rewound_output,
                                 tx_id,
                                 hash,
+                                payment_reference: Some(payment_reference).filter(|r| !r.is_empty()),
+                                memo: Some(memo).filter(|m| !m.is_empty()),
                             })
                         },
                         Err(OutputManagerStorageError::DuplicateOutput) => {
@@ -2495,46 +3039,6 @@ impl fmt::Display for Balance {
     }
 }
 
-#[derive(Debug, Clone)]
-struct UtxoSelection {
-    utxos: Vec<DbWalletOutput>,
-    requires_change_output: bool,
-    total_value: MicroMinotari,
-    fee_without_change: MicroMinotari,
-    fee_with_change: MicroMinotari,
-}
-
-#[allow(dead_code)]
-impl UtxoSelection {
-    pub fn as_final_fee(&self) -> MicroMinotari {
-        if self.requires_change_output {
-            return self.fee_with_change;
-        }
-        self.fee_without_change
-    }
-
-    pub fn requires_change_output(&self) -> bool {
-        self.requires_change_output
-    }
-
-    /// Total value of the selected inputs
-    pub fn total_value(&self) -> MicroMinotari {
-        self.total_value
-    }
-
-    pub fn num_selected(&self) -> usize {
-        self.utxos.len()
-    }
-
-    pub fn into_selected(self) -> Vec<DbWalletOutput> {
-        self.utxos
-    }
-
-    pub fn iter(&self) -> impl Iterator<Item = &DbWalletOutput> + '_ {
-        self.utxos.iter()
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct OutputInfoByTxId {
     pub statuses: Vec<OutputStatus>,
@@ -13,7 +13,7 @@ use crate::output_manager_service::{
     service::Balance,
     storage::{
         database::{DbKey, DbValue, OutputBackendQuery, WriteOperation},
-        models::DbWalletOutput,
+        models::{DbWalletOutput, SpendingPriority},
         sqlite_db::{ReceivedOutputInfoForBatch, SpentOutputInfoForBatch},
     },
 };
@@ -84,14 +84,28 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     /// Return the available, time locked, pending incoming and pending outgoing balance
     fn get_balance(&self, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
+    /// Get the balance of the outputs belonging to a single wallet account
+    fn get_account_balance(&self, account: u64, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
     /// Import unvalidated output
     fn add_unvalidated_output(&self, output: DbWalletOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    /// Import a batch of unvalidated outputs in a single database transaction. Returns, for each output, whether it was
+    /// added or skipped because the wallet already holds it.
+    fn add_unvalidated_outputs(
+        &self,
+        outputs: Vec<(TxId, DbWalletOutput)>,
+    ) -> Result<Vec<bool>, OutputManagerStorageError>;
     fn fetch_unspent_outputs_for_spending(
         &self,
         selection_criteria: &UtxoSelectionCriteria,
         amount: u64,
         current_tip_height: Option<u64>,
     ) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError>;
+    /// Set the spending priority of the outputs with the given commitments, used to pin or freeze outputs
+    fn set_outputs_spending_priority(
+        &self,
+        commitments: Vec<Commitment>,
+        spending_priority: SpendingPriority,
+    ) -> Result<(), OutputManagerStorageError>;
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError>;
     fn fetch_outputs_by_query(&self, q: OutputBackendQuery) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError>;
 }
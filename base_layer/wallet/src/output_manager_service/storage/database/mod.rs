@@ -43,7 +43,7 @@ use crate::output_manager_service::{
     input_selection::UtxoSelectionCriteria,
     service::Balance,
     storage::{
-        models::{DbWalletOutput, KnownOneSidedPaymentScript},
+        models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
         sqlite_db::{ReceivedOutputInfoForBatch, SpentOutputInfoForBatch},
         OutputStatus,
     },
@@ -66,6 +66,7 @@ pub struct OutputBackendQuery {
     pub value_min: Option<(i64, bool)>,
     pub value_max: Option<(i64, bool)>,
     pub sorting: Vec<(&'static str, SortDirection)>,
+    pub account: Option<u64>,
 }
 
 impl Default for OutputBackendQuery {
@@ -78,6 +79,7 @@ impl Default for OutputBackendQuery {
             value_min: None,
             value_max: None,
             sorting: vec![],
+            account: None,
         }
     }
 }
@@ -163,6 +165,13 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    pub fn add_unvalidated_outputs(
+        &self,
+        outputs: Vec<(TxId, DbWalletOutput)>,
+    ) -> Result<Vec<bool>, OutputManagerStorageError> {
+        self.db.add_unvalidated_outputs(outputs)
+    }
+
     pub fn add_output_to_be_received(
         &self,
         tx_id: TxId,
@@ -184,6 +193,15 @@ where T: OutputManagerBackend + 'static
         self.db.get_balance(current_tip_for_time_lock_calculation)
     }
 
+    pub fn get_account_balance(
+        &self,
+        account: u64,
+        current_tip_for_time_lock_calculation: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        self.db
+            .get_account_balance(account, current_tip_for_time_lock_calculation)
+    }
+
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
     /// transaction in the short term.
     pub fn encumber_outputs(
@@ -426,6 +444,14 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    pub fn set_outputs_spending_priority(
+        &self,
+        commitments: Vec<Commitment>,
+        spending_priority: SpendingPriority,
+    ) -> Result<(), OutputManagerStorageError> {
+        self.db.set_outputs_spending_priority(commitments, spending_priority)
+    }
+
     pub fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let outputs = self.db.fetch_outputs_by_tx_id(tx_id)?;
         Ok(outputs)
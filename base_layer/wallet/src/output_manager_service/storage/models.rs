@@ -56,6 +56,8 @@ pub struct DbWalletOutput {
     pub source: OutputSource,
     pub received_in_tx_id: Option<TxId>,
     pub spent_in_tx_id: Option<TxId>,
+    /// The index of the wallet account the output belongs to, 0 is the default account
+    pub account: u64,
 }
 
 impl DbWalletOutput {
@@ -82,8 +84,14 @@ impl DbWalletOutput {
             source,
             received_in_tx_id,
             spent_in_tx_id,
+            account: 0,
         })
     }
+
+    pub fn with_account(mut self, account: u64) -> Self {
+        self.account = account;
+        self
+    }
 }
 
 impl From<DbWalletOutput> for WalletOutput {
@@ -118,6 +126,10 @@ impl Eq for DbWalletOutput {}
 pub enum SpendingPriority {
     Normal,
     HtlcSpendAsap,
+    /// Preferred ahead of normal outputs when inputs are selected automatically
+    Pinned,
+    /// Never selected automatically, only spent when explicitly chosen as an input
+    Frozen,
 }
 
 impl TryFrom<u32> for SpendingPriority {
@@ -127,16 +139,44 @@ impl TryFrom<u32> for SpendingPriority {
         match value {
             0 => Ok(SpendingPriority::Normal),
             1 => Ok(SpendingPriority::HtlcSpendAsap),
+            2 => Ok(SpendingPriority::Pinned),
+            3 => Ok(SpendingPriority::Frozen),
             _ => Err(format!("Invalid spending priority value: {}", value)),
         }
     }
 }
 
+impl SpendingPriority {
+    /// The order in which outputs are preferred when inputs are selected automatically, highest first. HTLC outputs
+    /// must be swept as soon as possible, so they rank ahead of pinned outputs. The stored values predate pinning and
+    /// freezing, so they are not in this order.
+    pub fn selection_rank(&self) -> i32 {
+        match self {
+            SpendingPriority::HtlcSpendAsap => 2,
+            SpendingPriority::Pinned => 1,
+            SpendingPriority::Normal => 0,
+            SpendingPriority::Frozen => -1,
+        }
+    }
+
+    /// The priorities that a user may set and replace on an output. HTLC priority is set by the wallet and is never
+    /// overwritten.
+    pub fn user_selectable() -> [SpendingPriority; 3] {
+        [
+            SpendingPriority::Normal,
+            SpendingPriority::Pinned,
+            SpendingPriority::Frozen,
+        ]
+    }
+}
+
 impl From<SpendingPriority> for i32 {
     fn from(value: SpendingPriority) -> Self {
         match value {
             SpendingPriority::Normal => 0,
             SpendingPriority::HtlcSpendAsap => 1,
+            SpendingPriority::Pinned => 2,
+            SpendingPriority::Frozen => 3,
         }
     }
 }
@@ -53,7 +53,7 @@ use crate::{
         service::Balance,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputBackendQuery, OutputManagerBackend, WriteOperation},
-            models::{DbWalletOutput, KnownOneSidedPaymentScript},
+            models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
             OutputStatus,
         },
         UtxoSelectionCriteria,
@@ -961,6 +961,15 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         result
     }
 
+    fn get_account_balance(
+        &self,
+        account: u64,
+        current_tip_for_time_lock_calculation: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OutputSql::get_account_balance(account, current_tip_for_time_lock_calculation, &mut conn)
+    }
+
     fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -1146,6 +1155,42 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn add_unvalidated_outputs(
+        &self,
+        outputs: Vec<(TxId, DbWalletOutput)>,
+    ) -> Result<Vec<bool>, OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let num_outputs = outputs.len();
+
+        let added = conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let mut added = Vec::with_capacity(outputs.len());
+            for (tx_id, output) in outputs {
+                // Outputs inserted earlier in the batch are visible here, so duplicates within the batch are skipped
+                if OutputSql::find_by_commitment_and_cancelled(&output.commitment.to_vec(), false, conn).is_ok() {
+                    added.push(false);
+                    continue;
+                }
+                NewOutputSql::new(output, Some(OutputStatus::EncumberedToBeReceived), Some(tx_id))?.commit(conn)?;
+                added.push(true);
+            }
+            Ok(added)
+        })?;
+
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - add_unvalidated_outputs ({} outputs): lock {} + db_op {} = {} ms",
+                num_outputs,
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(added)
+    }
+
     /// Retrieves UTXOs than can be spent, sorted by priority, then value from smallest to largest.
     fn fetch_unspent_outputs_for_spending(
         &self,
@@ -1172,6 +1217,39 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             .collect::<Result<Vec<_>, _>>()
     }
 
+    fn set_outputs_spending_priority(
+        &self,
+        commitments: Vec<Commitment>,
+        spending_priority: SpendingPriority,
+    ) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        conn.transaction::<_, _, _>(|conn| {
+            if !OutputSql::verify_outputs_exist(&commitments, conn)? {
+                return Err(OutputManagerStorageError::ValuesNotFound);
+            }
+            OutputSql::update_spending_priority_by_commitments(
+                commitments.iter().map(|c| c.as_bytes()).collect(),
+                spending_priority,
+                conn,
+            )?;
+
+            Ok(())
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - set_outputs_spending_priority: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let outputs = OutputSql::find_by_tx_id(tx_id, &mut conn)?;
@@ -1264,6 +1342,7 @@ pub struct UpdateOutput {
     mined_height: Option<Option<u64>>,
     mined_in_block: Option<Option<Vec<u8>>>,
     last_validation_timestamp: Option<Option<NaiveDateTime>>,
+    spending_priority: Option<SpendingPriority>,
 }
 
 #[derive(AsChangeset)]
@@ -1281,6 +1360,7 @@ pub struct UpdateOutputSql {
     mined_height: Option<Option<i64>>,
     mined_in_block: Option<Option<Vec<u8>>>,
     last_validation_timestamp: Option<Option<NaiveDateTime>>,
+    spending_priority: Option<i32>,
 }
 
 /// Map a Rust friendly UpdateOutput to the Sql data type form
@@ -1299,6 +1379,7 @@ impl From<UpdateOutput> for UpdateOutputSql {
             mined_height: u.mined_height.map(|t| t.map(|h| h as i64)),
             mined_in_block: u.mined_in_block,
             last_validation_timestamp: u.last_validation_timestamp,
+            spending_priority: u.spending_priority.map(i32::from),
         }
     }
 }
@@ -28,7 +28,7 @@ use std::{
 use borsh::BorshDeserialize;
 use chrono::NaiveDateTime;
 use derivative::Derivative;
-use diesel::{prelude::*, sql_query, SqliteConnection};
+use diesel::{dsl::sql, expression::SqlLiteral, prelude::*, sql_query, sql_types::Integer, SqliteConnection};
 use log::*;
 use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
 use tari_common_types::{
@@ -65,6 +65,21 @@ use crate::{
 
 const LOG_TARGET: &str = "wallet::output_manager_service::database::wallet";
 
+/// Maps the stored spending priority to its selection rank, see `SpendingPriority::selection_rank`
+fn spending_priority_rank() -> SqlLiteral<Integer> {
+    let cases = [
+        SpendingPriority::HtlcSpendAsap,
+        SpendingPriority::Pinned,
+        SpendingPriority::Normal,
+        SpendingPriority::Frozen,
+    ]
+    .iter()
+    .map(|p| format!("WHEN {} THEN {}", i32::from(p.clone()), p.selection_rank()))
+    .collect::<Vec<_>>()
+    .join(" ");
+    sql(&format!("CASE spending_priority {} ELSE 0 END", cases))
+}
+
 #[derive(Clone, Derivative, Queryable, Identifiable, PartialEq, QueryableByName)]
 #[diesel(table_name = outputs)]
 pub struct OutputSql {
@@ -102,6 +117,7 @@ pub struct OutputSql {
     pub minimum_value_promise: i64,
     pub source: i32,
     pub last_validation_timestamp: Option<NaiveDateTime>,
+    pub account_index: i64,
 }
 
 impl OutputSql {
@@ -153,6 +169,11 @@ impl OutputSql {
             };
         }
 
+        // if set, filtering by account
+        if let Some(account) = q.account {
+            query = query.filter(outputs::account_index.eq(i64::try_from(account).unwrap_or(i64::MAX)));
+        }
+
         // if set, filtering by minimum value
         if let Some((min, is_inclusive)) = q.value_min {
             query = if is_inclusive {
@@ -198,12 +219,13 @@ impl OutputSql {
     ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
         let i64_tip_height = tip_height.and_then(|h| i64::try_from(h).ok()).unwrap_or(i64::MAX);
         let i64_value = i64::try_from(selection_criteria.min_dust).unwrap_or(i64::MAX);
+        let i64_account = i64::try_from(selection_criteria.account).unwrap_or(i64::MAX);
 
         let mut query = outputs::table
             .into_boxed()
             .filter(outputs::status.eq(OutputStatus::Unspent as i32))
             .filter(outputs::value.gt(i64_value))
-            .order_by(outputs::spending_priority.desc());
+            .order_by(spending_priority_rank().desc());
 
         // NOTE: Safe mode presets `script_lock_height` and `maturity` filters for all queries
         if selection_criteria.mode == UtxoSelectionMode::Safe {
@@ -219,10 +241,16 @@ impl OutputSql {
                         .eq(i32::from(OutputType::Standard.as_byte()))
                         .or(outputs::output_type.eq(i32::from(OutputType::Coinbase.as_byte()))),
                 );
+                query = query.filter(outputs::account_index.eq(i64_account));
 
                 if selection_criteria.excluding_onesided {
                     query = query.filter(outputs::source.ne(OutputSource::OneSided as i32));
                 }
+
+                // Frozen outputs can only be spent by selecting them explicitly
+                if selection_criteria.mode == UtxoSelectionMode::Safe {
+                    query = query.filter(outputs::spending_priority.ne(i32::from(SpendingPriority::Frozen)));
+                }
             },
 
             UtxoSelectionFilter::SpecificOutputs { commitments } => {
@@ -242,7 +270,9 @@ impl OutputSql {
 
         query = match selection_criteria.ordering {
             UtxoSelectionOrdering::SmallestFirst => query.then_order_by(outputs::value.asc()),
-            UtxoSelectionOrdering::LargestFirst => query.then_order_by(outputs::value.desc()),
+            UtxoSelectionOrdering::LargestFirst | UtxoSelectionOrdering::BranchAndBound => {
+                query.then_order_by(outputs::value.desc())
+            },
             UtxoSelectionOrdering::Default => {
                 // NOTE: keeping filtering by `script_lock_height` and `maturity` for all modes
                 // lets get the max value for all utxos
@@ -250,6 +280,8 @@ impl OutputSql {
                     .filter(outputs::status.eq(OutputStatus::Unspent as i32))
                     .filter(outputs::script_lock_height.le(i64_tip_height))
                     .filter(outputs::maturity.le(i64_tip_height))
+                    .filter(outputs::spending_priority.ne(i32::from(SpendingPriority::Frozen)))
+                    .filter(outputs::account_index.eq(i64_account))
                     .order(outputs::value.desc())
                     .select(outputs::value)
                     .first(conn)
@@ -516,6 +548,52 @@ impl OutputSql {
         })
     }
 
+    /// Return the available, time locked, pending incoming and pending outgoing balance of a wallet account
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn get_account_balance(
+        account: u64,
+        current_tip_for_time_lock_calculation: Option<u64>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        let account_outputs: Vec<(i64, i32, i64, i64, i32)> = outputs::table
+            .filter(outputs::account_index.eq(account as i64))
+            .select((
+                outputs::value,
+                outputs::status,
+                outputs::maturity,
+                outputs::script_lock_height,
+                outputs::source,
+            ))
+            .load(conn)?;
+
+        let mut balance = Balance::zero();
+        if current_tip_for_time_lock_calculation.is_some() {
+            balance.time_locked_balance = Some(MicroMinotari::zero());
+        }
+        for (value, status, maturity, script_lock_height, source) in account_outputs {
+            let value = MicroMinotari::from(value as u64);
+            match OutputStatus::try_from(status)? {
+                OutputStatus::Unspent => {
+                    match (current_tip_for_time_lock_calculation, &mut balance.time_locked_balance) {
+                        (Some(tip), Some(time_locked)) if maturity > tip as i64 || script_lock_height > tip as i64 => {
+                            *time_locked += value;
+                        },
+                        _ => balance.available_balance += value,
+                    }
+                },
+                OutputStatus::EncumberedToBeReceived if source == OutputSource::Coinbase as i32 => {},
+                OutputStatus::EncumberedToBeReceived |
+                OutputStatus::ShortTermEncumberedToBeReceived |
+                OutputStatus::UnspentMinedUnconfirmed => balance.pending_incoming_balance += value,
+                OutputStatus::EncumberedToBeSpent |
+                OutputStatus::ShortTermEncumberedToBeSpent |
+                OutputStatus::SpentMinedUnconfirmed => balance.pending_outgoing_balance += value,
+                _ => {},
+            }
+        }
+        Ok(balance)
+    }
+
     pub fn find_by_commitment(
         commitment: &[u8],
         conn: &mut SqliteConnection,
@@ -548,6 +626,22 @@ impl OutputSql {
         )
     }
 
+    /// Sets the spending priority of the given outputs, leaving outputs with a priority set by the wallet unchanged
+    pub fn update_spending_priority_by_commitments(
+        commitments: Vec<&[u8]>,
+        spending_priority: SpendingPriority,
+        conn: &mut SqliteConnection,
+    ) -> Result<usize, OutputManagerStorageError> {
+        let user_selectable = SpendingPriority::user_selectable().map(i32::from).to_vec();
+        Ok(diesel::update(
+            outputs::table
+                .filter(outputs::commitment.eq_any(commitments))
+                .filter(outputs::spending_priority.eq_any(user_selectable)),
+        )
+        .set(outputs::spending_priority.eq(i32::from(spending_priority)))
+        .execute(conn)?)
+    }
+
     pub fn find_by_commitment_and_cancelled(
         commitment: &[u8],
         cancelled: bool,
@@ -800,6 +894,7 @@ impl OutputSql {
             source: self.source.try_into()?,
             received_in_tx_id: self.received_in_tx_id.map(|d| (d as u64).into()),
             spent_in_tx_id: self.spent_in_tx_id.map(|d| (d as u64).into()),
+            account: self.account_index as u64,
         })
     }
 }
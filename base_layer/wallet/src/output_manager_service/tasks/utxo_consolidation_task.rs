@@ -0,0 +1,223 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::sync::Arc;
+
+use log::*;
+use tari_common_types::types::Commitment;
+use tari_core::transactions::{fee::Fee, tari_amount::MicroMinotari};
+use tari_shutdown::ShutdownSignal;
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    account::models::DEFAULT_ACCOUNT_INDEX,
+    output_manager_service::{
+        config::OutputManagerServiceConfig,
+        error::OutputManagerError,
+        handle::{OutputManagerEvent, OutputManagerEventSender, OutputManagerHandle},
+        UtxoSelectionCriteria,
+    },
+    transaction_service::handle::TransactionServiceHandle,
+};
+
+const LOG_TARGET: &str = "wallet::output_service::utxo_consolidation_task";
+
+/// Periodically joins the wallet's small outputs into a single output while the network fees are low
+pub struct UtxoConsolidationTask {
+    config: OutputManagerServiceConfig,
+    fee_calc: Fee,
+    output_manager: OutputManagerHandle,
+    transaction_service: TransactionServiceHandle,
+    event_publisher: OutputManagerEventSender,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl UtxoConsolidationTask {
+    pub fn new(
+        config: OutputManagerServiceConfig,
+        fee_calc: Fee,
+        output_manager: OutputManagerHandle,
+        transaction_service: TransactionServiceHandle,
+        event_publisher: OutputManagerEventSender,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            fee_calc,
+            output_manager,
+            transaction_service,
+            event_publisher,
+            shutdown_signal,
+        }
+    }
+
+    pub async fn run(mut self) {
+        let mut interval = time::interval(self.config.auto_consolidation_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately, give the wallet time to sync before the first check
+        interval.tick().await;
+        let mut shutdown = self.shutdown_signal.clone();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.consolidate().await {
+                        warn!(target: LOG_TARGET, "UTXO consolidation failed: {}", e);
+                    }
+                },
+                _ = shutdown.wait() => {
+                    info!(target: LOG_TARGET, "UTXO consolidation task shutting down because it received the shutdown signal");
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn consolidate(&mut self) -> Result<(), OutputManagerError> {
+        let outputs = self
+            .output_manager
+            .get_spendable_outputs(UtxoSelectionCriteria::default())
+            .await?;
+        let num_candidates = outputs
+            .iter()
+            .filter(|o| o.wallet_output.value < self.config.auto_consolidation_dust_threshold.into())
+            .count();
+        if num_candidates < self.config.auto_consolidation_min_outputs {
+            trace!(
+                target: LOG_TARGET,
+                "{} outputs below the dust threshold, not consolidating",
+                num_candidates
+            );
+            return Ok(());
+        }
+
+        let stats = self
+            .transaction_service
+            .get_fee_per_gram_stats_per_block(1)
+            .await
+            .map_err(|e| OutputManagerError::ServiceError(e.to_string()))?;
+        let fee_per_gram = stats
+            .stats
+            .first()
+            .map(|s| s.min_fee_per_gram)
+            .unwrap_or_else(|| MicroMinotari::from(1))
+            .max(MicroMinotari::from(1));
+        if fee_per_gram > self.config.auto_consolidation_max_fee_per_gram.into() {
+            debug!(
+                target: LOG_TARGET,
+                "Deferring consolidation of {} outputs, fee per gram {} is above the maximum of {}",
+                num_candidates,
+                fee_per_gram,
+                self.config.auto_consolidation_max_fee_per_gram
+            );
+            self.publish_event(OutputManagerEvent::UtxoConsolidationDeferred(fee_per_gram));
+            return Ok(());
+        }
+
+        let input_fee = self.fee_calc.calculate(fee_per_gram, 0, 1, 0, 0);
+        let candidates = outputs.into_iter().map(|o| (o.wallet_output.value, o.commitment));
+        let commitments = select_consolidation_inputs(candidates, &self.config, input_fee);
+        if commitments.len() < 2 {
+            debug!(
+                target: LOG_TARGET,
+                "Not enough outputs are worth more than the fee {} to spend them, not consolidating", input_fee
+            );
+            return Ok(());
+        }
+
+        let num_inputs = commitments.len();
+        let (tx_id, transaction, amount) = self
+            .output_manager
+            .create_coin_join(commitments, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
+            .await?;
+        if let Err(e) = self
+            .transaction_service
+            .submit_transaction(tx_id, transaction, amount, "UTXO consolidation".to_string())
+            .await
+        {
+            // Release the encumbered outputs so that they can be spent again
+            self.output_manager.cancel_transaction(tx_id).await?;
+            return Err(OutputManagerError::ServiceError(e.to_string()));
+        }
+        info!(
+            target: LOG_TARGET,
+            "Submitted UTXO consolidation transaction {} joining {} outputs at fee per gram {}",
+            tx_id,
+            num_inputs,
+            fee_per_gram
+        );
+        self.publish_event(OutputManagerEvent::UtxoConsolidationSubmitted(tx_id));
+        Ok(())
+    }
+
+    fn publish_event(&self, event: OutputManagerEvent) {
+        if let Err(e) = self.event_publisher.send(Arc::new(event)) {
+            debug!(
+                target: LOG_TARGET,
+                "Error sending event because there are no subscribers: {:?}", e
+            );
+        }
+    }
+}
+
+/// Chooses the outputs below the dust threshold that are worth more than the fee to spend them, smallest first and
+/// at most `auto_consolidation_max_inputs` of them
+fn select_consolidation_inputs<I: IntoIterator<Item = (MicroMinotari, Commitment)>>(
+    outputs: I,
+    config: &OutputManagerServiceConfig,
+    input_fee: MicroMinotari,
+) -> Vec<Commitment> {
+    let dust_threshold = MicroMinotari::from(config.auto_consolidation_dust_threshold);
+    let mut candidates = outputs
+        .into_iter()
+        .filter(|(value, _)| *value < dust_threshold && *value > input_fee)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(value, _)| *value);
+    candidates
+        .into_iter()
+        .take(config.auto_consolidation_max_inputs)
+        .map(|(_, commitment)| commitment)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::{CommitmentFactory, PrivateKey};
+    use tari_crypto::commitment::HomomorphicCommitmentFactory;
+
+    use super::*;
+
+    #[test]
+    fn it_selects_the_smallest_economical_dust_outputs() {
+        let factory = CommitmentFactory::default();
+        let commit = |v: u64| factory.commit_value(&PrivateKey::default(), v);
+        let config = OutputManagerServiceConfig {
+            auto_consolidation_dust_threshold: 1_000,
+            auto_consolidation_max_inputs: 3,
+            ..Default::default()
+        };
+        let values = [5_000, 40, 900, 100, 600, 1_000, 300];
+        let outputs = values.iter().map(|v| (MicroMinotari::from(*v), commit(*v)));
+
+        let selected = select_consolidation_inputs(outputs, &config, MicroMinotari::from(50));
+        assert_eq!(selected, vec![commit(100), commit(300), commit(600)]);
+    }
+}
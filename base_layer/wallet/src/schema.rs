@@ -1,5 +1,33 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    accounts (account_index) {
+        account_index -> BigInt,
+        name -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    atomic_swaps (swap_id) {
+        swap_id -> BigInt,
+        role -> Integer,
+        counterparty_chain -> Integer,
+        counterparty_address -> Text,
+        amount -> BigInt,
+        hash_lock -> Binary,
+        pre_image -> Nullable<Text>,
+        output_hash -> Binary,
+        timeout_height -> BigInt,
+        status -> Integer,
+        funding_tx_id -> Nullable<BigInt>,
+        redeem_tx_id -> Nullable<BigInt>,
+        refund_tx_id -> Nullable<BigInt>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     burnt_proofs (id) {
         id -> Integer,
@@ -37,6 +65,8 @@ diesel::table! {
         mined_timestamp -> Nullable<Timestamp>,
         transaction_signature_nonce -> Binary,
         transaction_signature_key -> Binary,
+        payment_reference -> Nullable<Binary>,
+        memo -> Nullable<Binary>,
     }
 }
 
@@ -117,6 +147,7 @@ diesel::table! {
         minimum_value_promise -> BigInt,
         source -> Integer,
         last_validation_timestamp -> Nullable<Timestamp>,
+        account_index -> BigInt,
     }
 }
 
@@ -138,6 +169,8 @@ diesel::table! {
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    accounts,
+    atomic_swaps,
     burnt_proofs,
     client_key_values,
     completed_transactions,
@@ -27,16 +27,26 @@ use std::{
 
 use chrono::NaiveDateTime;
 use log::*;
-use tari_common_types::{chain_metadata::ChainMetadata, wallet_types::WalletType};
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    encryption::RekeySummary,
+    transaction::TxId,
+    wallet_types::WalletType,
+};
 use tari_comms::{
     multiaddr::Multiaddr,
     peer_manager::{IdentitySignature, PeerFeatures},
     tor::TorIdentity,
 };
-use tari_key_manager::cipher_seed::CipherSeed;
+use tari_key_manager::{cipher_seed::CipherSeed, mnemonic::MnemonicLanguage};
 use tari_utilities::SafePassword;
 
-use crate::{error::WalletStorageError, utxo_scanner_service::service::ScannedBlock};
+use crate::{
+    account::models::WalletAccount,
+    atomic_swap::models::AtomicSwap,
+    error::WalletStorageError,
+    utxo_scanner_service::service::ScannedBlock,
+};
 
 const LOG_TARGET: &str = "wallet::database";
 
@@ -63,6 +73,16 @@ pub trait WalletBackend: Send + Sync + Clone {
     /// Change the passphrase used to encrypt the database
     fn change_passphrase(&self, existing: &SafePassword, new: &SafePassword) -> Result<(), WalletStorageError>;
 
+    /// Rotate the main database key, re-encrypting every encrypted field in batches of `batch_size` rows and protecting
+    /// the new key with the `new` passphrase. This must not be used while the wallet services are running, since they
+    /// each hold a copy of the current key.
+    fn rekey(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        batch_size: usize,
+    ) -> Result<RekeySummary, WalletStorageError>;
+
     fn create_burnt_proof(
         &self,
         id: u32,
@@ -72,6 +92,15 @@ pub trait WalletBackend: Send + Sync + Clone {
     fn fetch_burnt_proof(&self, id: u32) -> Result<(u32, String, String, NaiveDateTime), WalletStorageError>;
     fn fetch_burnt_proofs(&self) -> Result<Vec<(u32, String, String, NaiveDateTime)>, WalletStorageError>;
     fn delete_burnt_proof(&self, id: u32) -> Result<(), WalletStorageError>;
+
+    /// Insert the atomic swap, or replace the existing swap with the same id
+    fn save_atomic_swap(&self, swap: AtomicSwap) -> Result<(), WalletStorageError>;
+    fn fetch_atomic_swap(&self, swap_id: TxId) -> Result<Option<AtomicSwap>, WalletStorageError>;
+    fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, WalletStorageError>;
+
+    /// Insert a new wallet account, failing if the index or name is already in use
+    fn save_account(&self, account: WalletAccount) -> Result<(), WalletStorageError>;
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, WalletStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -87,10 +116,12 @@ pub enum DbKey {
     SecondaryKeySalt,    // the salt used (with the user's passphrase) to derive the secondary derivation key
     SecondaryKeyVersion, // the parameter version for the secondary derivation key
     SecondaryKeyHash,    // a hash commitment to the secondary derivation key
+    PendingMainKey,      // the replacement main key of an unfinished rekey, encrypted with the secondary key
     WalletBirthday,
     LastAccessedNetwork,
     LastAccessedVersion,
     WalletType,
+    MnemonicLanguage,
 }
 
 impl DbKey {
@@ -106,11 +137,13 @@ impl DbKey {
             DbKey::SecondaryKeySalt => "SecondaryKeySalt".to_string(),
             DbKey::SecondaryKeyVersion => "SecondaryKeyVersion".to_string(),
             DbKey::SecondaryKeyHash => "SecondaryKeyHash".to_string(),
+            DbKey::PendingMainKey => "PendingMainKey".to_string(),
             DbKey::WalletBirthday => "WalletBirthday".to_string(),
             DbKey::CommsIdentitySignature => "CommsIdentitySignature".to_string(),
             DbKey::LastAccessedNetwork => "LastAccessedNetwork".to_string(),
             DbKey::LastAccessedVersion => "LastAccessedVersion".to_string(),
             DbKey::WalletType => "WalletType".to_string(),
+            DbKey::MnemonicLanguage => "MnemonicLanguage".to_string(),
         }
     }
 }
@@ -132,6 +165,7 @@ pub enum DbValue {
     LastAccessedNetwork(String),
     LastAccessedVersion(String),
     WalletType(WalletType),
+    MnemonicLanguage(MnemonicLanguage),
 }
 
 #[derive(Clone)]
@@ -145,6 +179,7 @@ pub enum DbKeyValuePair {
     CommsIdentitySignature(Box<IdentitySignature>),
     NetworkAndVersion((String, String)),
     WalletType(WalletType),
+    MnemonicLanguage(MnemonicLanguage),
 }
 
 pub enum WriteOperation {
@@ -169,6 +204,15 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    pub fn rekey(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        batch_size: usize,
+    ) -> Result<RekeySummary, WalletStorageError> {
+        self.db.rekey(existing, new, batch_size)
+    }
+
     pub fn get_master_seed(&self) -> Result<Option<CipherSeed>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::MasterSeed) {
             Ok(None) => Ok(None),
@@ -389,6 +433,26 @@ where T: WalletBackend + 'static
         self.db.delete_burnt_proof(id)
     }
 
+    pub fn save_atomic_swap(&self, swap: AtomicSwap) -> Result<(), WalletStorageError> {
+        self.db.save_atomic_swap(swap)
+    }
+
+    pub fn fetch_atomic_swap(&self, swap_id: TxId) -> Result<Option<AtomicSwap>, WalletStorageError> {
+        self.db.fetch_atomic_swap(swap_id)
+    }
+
+    pub fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, WalletStorageError> {
+        self.db.fetch_atomic_swaps()
+    }
+
+    pub fn save_account(&self, account: WalletAccount) -> Result<(), WalletStorageError> {
+        self.db.save_account(account)
+    }
+
+    pub fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, WalletStorageError> {
+        self.db.fetch_accounts()
+    }
+
     pub fn get_wallet_type(&self) -> Result<Option<WalletType>, WalletStorageError> {
         match self.db.fetch(&DbKey::WalletType) {
             Ok(None) => Ok(None),
@@ -403,6 +467,22 @@ where T: WalletBackend + 'static
             .write(WriteOperation::Insert(DbKeyValuePair::WalletType(wallet_type)))?;
         Ok(())
     }
+
+    /// The language the seed words of the wallet are presented in
+    pub fn get_mnemonic_language(&self) -> Result<Option<MnemonicLanguage>, WalletStorageError> {
+        match self.db.fetch(&DbKey::MnemonicLanguage) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::MnemonicLanguage(language))) => Ok(Some(language)),
+            Ok(Some(other)) => unexpected_result(DbKey::MnemonicLanguage, other),
+            Err(e) => log_error(DbKey::MnemonicLanguage, e),
+        }
+    }
+
+    pub fn set_mnemonic_language(&self, language: MnemonicLanguage) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::MnemonicLanguage(language)))?;
+        Ok(())
+    }
 }
 
 impl Display for DbValue {
@@ -424,6 +504,7 @@ impl Display for DbValue {
             DbValue::LastAccessedNetwork(network) => f.write_str(&format!("LastAccessedNetwork: {}", network)),
             DbValue::LastAccessedVersion(version) => f.write_str(&format!("LastAccessedVersion: {}", version)),
             DbValue::WalletType(wallet_type) => f.write_str(&format!("WalletType: {:?}", wallet_type)),
+            DbValue::MnemonicLanguage(language) => f.write_str(&format!("MnemonicLanguage: {}", language)),
         }
     }
 }
@@ -0,0 +1,72 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, SqliteConnection};
+
+use crate::{account::models::WalletAccount, error::WalletStorageError, schema::accounts};
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = accounts)]
+pub struct AccountSql {
+    account_index: i64,
+    name: String,
+    created_at: NaiveDateTime,
+}
+
+impl AccountSql {
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(accounts::table
+            .order(accounts::account_index.asc())
+            .load::<AccountSql>(conn)?)
+    }
+
+    /// Inserts the account, failing if an account with the same index or name exists
+    pub fn insert(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::insert_into(accounts::table).values(self).execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<WalletAccount> for AccountSql {
+    fn from(account: WalletAccount) -> Self {
+        Self {
+            account_index: account.index as i64,
+            name: account.name,
+            created_at: account.created_at,
+        }
+    }
+}
+
+impl TryFrom<AccountSql> for WalletAccount {
+    type Error = WalletStorageError;
+
+    fn try_from(a: AccountSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            index: u64::try_from(a.account_index).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            name: a.name,
+            created_at: a.created_at,
+        })
+    }
+}
@@ -0,0 +1,192 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, str::from_utf8};
+
+use chacha20poly1305::XChaCha20Poly1305;
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, result::Error, SqliteConnection};
+use tari_common_types::{
+    encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
+    transaction::TxId,
+    types::{FixedHash, PublicKey},
+};
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    ByteArray,
+    Hidden,
+};
+use zeroize::Zeroize;
+
+use crate::{
+    atomic_swap::models::{AtomicSwap, AtomicSwapStatus, CounterpartyChain, SwapRole},
+    error::WalletStorageError,
+    schema::atomic_swaps,
+};
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = atomic_swaps)]
+pub struct AtomicSwapSql {
+    swap_id: i64,
+    role: i32,
+    counterparty_chain: i32,
+    counterparty_address: String,
+    amount: i64,
+    hash_lock: Vec<u8>,
+    pre_image: Option<String>,
+    output_hash: Vec<u8>,
+    timeout_height: i64,
+    status: i32,
+    funding_tx_id: Option<i64>,
+    redeem_tx_id: Option<i64>,
+    refund_tx_id: Option<i64>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+impl AtomicSwapSql {
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(atomic_swaps::table
+            .order(atomic_swaps::created_at.desc())
+            .load::<AtomicSwapSql>(conn)?)
+    }
+
+    pub fn page(offset: i64, limit: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(atomic_swaps::table
+            .order(atomic_swaps::swap_id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<AtomicSwapSql>(conn)?)
+    }
+
+    pub fn get(swap_id: TxId, conn: &mut SqliteConnection) -> Result<Option<Self>, WalletStorageError> {
+        atomic_swaps::table
+            .filter(atomic_swaps::swap_id.eq(swap_id.as_i64_wrapped()))
+            .first::<AtomicSwapSql>(conn)
+            .map(Some)
+            .or_else(|err| match err {
+                Error::NotFound => Ok(None),
+                err => Err(err.into()),
+            })
+    }
+
+    /// Inserts the swap, or replaces it if a swap with the same id exists
+    pub fn upsert(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::replace_into(atomic_swaps::table).values(self).execute(conn)?;
+        Ok(())
+    }
+}
+
+impl From<AtomicSwap> for AtomicSwapSql {
+    fn from(swap: AtomicSwap) -> Self {
+        Self {
+            swap_id: swap.swap_id.as_i64_wrapped(),
+            role: swap.role as i32,
+            counterparty_chain: swap.counterparty_chain as i32,
+            counterparty_address: swap.counterparty_address,
+            amount: swap.amount.as_u64() as i64,
+            hash_lock: swap.hash_lock.to_vec(),
+            pre_image: swap.pre_image.map(|p| p.to_hex()),
+            output_hash: swap.output_hash.to_vec(),
+            timeout_height: swap.timeout_height as i64,
+            status: swap.status as i32,
+            funding_tx_id: swap.funding_tx_id.map(TxId::as_i64_wrapped),
+            redeem_tx_id: swap.redeem_tx_id.map(TxId::as_i64_wrapped),
+            refund_tx_id: swap.refund_tx_id.map(TxId::as_i64_wrapped),
+            created_at: swap.created_at,
+            updated_at: swap.updated_at,
+        }
+    }
+}
+
+impl TryFrom<AtomicSwapSql> for AtomicSwap {
+    type Error = WalletStorageError;
+
+    fn try_from(s: AtomicSwapSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            swap_id: TxId::from(s.swap_id as u64),
+            role: SwapRole::try_from(s.role).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            counterparty_chain: CounterpartyChain::try_from(s.counterparty_chain)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            counterparty_address: s.counterparty_address,
+            amount: MicroMinotari::from(s.amount as u64),
+            hash_lock: FixedHash::try_from(s.hash_lock)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            pre_image: s
+                .pre_image
+                .map(|p| PublicKey::from_hex(&p))
+                .transpose()
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            output_hash: FixedHash::try_from(s.output_hash)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            timeout_height: s.timeout_height as u64,
+            status: AtomicSwapStatus::try_from(s.status)
+                .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            funding_tx_id: s.funding_tx_id.map(|id| TxId::from(id as u64)),
+            redeem_tx_id: s.redeem_tx_id.map(|id| TxId::from(id as u64)),
+            refund_tx_id: s.refund_tx_id.map(|id| TxId::from(id as u64)),
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        })
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for AtomicSwapSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::ATOMIC_SWAP,
+            self.swap_id.to_be_bytes().as_bytes(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        if let Some(pre_image) = self.pre_image.take() {
+            self.pre_image = Some(
+                encrypt_bytes_integral_nonce(cipher, self.domain("pre_image"), Hidden::hide(pre_image.into_bytes()))?
+                    .to_hex(),
+            );
+        }
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        if let Some(pre_image) = self.pre_image.take() {
+            let mut decrypted_value = decrypt_bytes_integral_nonce(
+                cipher,
+                self.domain("pre_image"),
+                &from_hex(pre_image.as_str()).map_err(|e| e.to_string())?,
+            )?;
+            self.pre_image = Some(
+                from_utf8(decrypted_value.as_slice())
+                    .map_err(|e| e.to_string())?
+                    .to_string(),
+            );
+            // we zeroize the decrypted value
+            decrypted_value.zeroize();
+        }
+        Ok(self)
+    }
+}
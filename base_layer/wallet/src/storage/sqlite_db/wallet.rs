@@ -41,7 +41,16 @@ use log::*;
 use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
-    encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
+    encryption::{
+        decrypt_bytes_integral_nonce,
+        encrypt_bytes_integral_nonce,
+        rekey_bytes_integral_nonce,
+        Encryptable,
+        LegacyPlaintext,
+        RekeyOutcome,
+        RekeySummary,
+    },
+    transaction::TxId,
 };
 use tari_comms::{
     multiaddr::Multiaddr,
@@ -49,7 +58,11 @@ use tari_comms::{
     tor::TorIdentity,
 };
 use tari_crypto::{hash_domain, hashing::DomainSeparatedHasher};
-use tari_key_manager::cipher_seed::CipherSeed;
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    key_manager_service::storage::sqlite_db::rekey_key_manager_tables,
+    mnemonic::MnemonicLanguage,
+};
 use tari_utilities::{
     hex::{from_hex, Hex},
     hidden_type,
@@ -62,13 +75,16 @@ use tokio::time::Instant;
 use zeroize::Zeroize;
 
 use crate::{
+    account::models::WalletAccount,
+    atomic_swap::models::AtomicSwap,
     error::WalletStorageError,
     schema::{burnt_proofs, client_key_values, wallet_settings},
     storage::{
         database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
-        sqlite_db::scanned_blocks::ScannedBlockSql,
-        sqlite_utilities::wallet_db_connection::WalletDbConnection,
+        sqlite_db::{accounts::AccountSql, atomic_swaps::AtomicSwapSql, scanned_blocks::ScannedBlockSql},
+        sqlite_utilities::{rekey_in_batches, wallet_db_connection::WalletDbConnection},
     },
+    transaction_service::storage::sqlite_db::rekey_transaction_tables,
     utxo_scanner_service::service::ScannedBlock,
 };
 
@@ -283,7 +299,6 @@ impl WalletSqliteDatabase {
         Ok(o)
     }
 
-    #[allow(dead_code)]
     fn encrypt_value<T: Encryptable<XChaCha20Poly1305>>(&self, o: T) -> Result<T, WalletStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
         o.encrypt(&cipher)
@@ -427,6 +442,10 @@ impl WalletSqliteDatabase {
                 WalletSettingSql::new(DbKey::WalletType, serde_json::to_string(&wallet_type).unwrap())
                     .set(&mut conn)?;
             },
+            DbKeyValuePair::MnemonicLanguage(language) => {
+                kvp_text = "MnemonicLanguage";
+                WalletSettingSql::new(DbKey::MnemonicLanguage, language.to_string()).set(&mut conn)?;
+            },
         }
 
         if start.elapsed().as_millis() > 0 {
@@ -465,8 +484,10 @@ impl WalletSqliteDatabase {
             DbKey::SecondaryKeyVersion |
             DbKey::SecondaryKeySalt |
             DbKey::SecondaryKeyHash |
+            DbKey::PendingMainKey |
             DbKey::WalletBirthday |
             DbKey::WalletType |
+            DbKey::MnemonicLanguage |
             DbKey::CommsIdentitySignature |
             DbKey::LastAccessedNetwork |
             DbKey::LastAccessedVersion => {
@@ -515,10 +536,14 @@ impl WalletBackend for WalletSqliteDatabase {
             DbKey::SecondaryKeyVersion => WalletSettingSql::get(key, &mut conn)?.map(DbValue::SecondaryKeyVersion),
             DbKey::SecondaryKeySalt => WalletSettingSql::get(key, &mut conn)?.map(DbValue::SecondaryKeySalt),
             DbKey::SecondaryKeyHash => WalletSettingSql::get(key, &mut conn)?.map(DbValue::SecondaryKeyHash),
+            DbKey::PendingMainKey => return Err(WalletStorageError::OperationNotSupported),
             DbKey::WalletBirthday => WalletSettingSql::get(key, &mut conn)?.map(DbValue::WalletBirthday),
             DbKey::WalletType => {
                 WalletSettingSql::get(key, &mut conn)?.map(|d| DbValue::WalletType(serde_json::from_str(&d).unwrap()))
             },
+            DbKey::MnemonicLanguage => WalletSettingSql::get(key, &mut conn)?
+                .and_then(|s| MnemonicLanguage::from_str(&s).ok())
+                .map(DbValue::MnemonicLanguage),
             DbKey::LastAccessedNetwork => WalletSettingSql::get(key, &mut conn)?.map(DbValue::LastAccessedNetwork),
             DbKey::LastAccessedVersion => WalletSettingSql::get(key, &mut conn)?.map(DbValue::LastAccessedVersion),
             DbKey::CommsIdentitySignature => WalletSettingSql::get(key, &mut conn)?
@@ -634,6 +659,137 @@ impl WalletBackend for WalletSqliteDatabase {
         Ok(())
     }
 
+    fn rekey(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        batch_size: usize,
+    ) -> Result<RekeySummary, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        // Authenticate the existing passphrase and recover the current main key
+        let data = DatabaseEncryptionFields::read(&mut conn)?.ok_or_else(|| {
+            WalletStorageError::UnexpectedResult("Unable to get valid key-related data from database".into())
+        })?;
+        let argon2_params = Argon2Parameters::from_version(Some(data.secondary_key_version))?;
+        let (secondary_key, secondary_key_hash) =
+            derive_secondary_key(existing, argon2_params.clone(), &data.secondary_key_salt)?;
+        if data.secondary_key_hash != secondary_key_hash {
+            return Err(WalletStorageError::InvalidPassphrase);
+        }
+        let main_key = decrypt_main_key(&secondary_key, &data.encrypted_main_key, argon2_params.id)?;
+
+        // Resume an unfinished rekey with its replacement key, otherwise stage a fresh one so that an interrupted run
+        // can be completed by running it again with the same existing passphrase
+        let new_main_key = match WalletSettingSql::get(&DbKey::PendingMainKey, &mut conn)? {
+            Some(pending) => decrypt_main_key(&secondary_key, &from_hex(&pending)?, argon2_params.id)?,
+            None => {
+                let mut new_main_key = WalletMainEncryptionKey::from(vec![0u8; size_of::<Key>()]);
+                OsRng.fill_bytes(new_main_key.reveal_mut());
+                let encrypted_new_main_key = encrypt_main_key(&secondary_key, &new_main_key, argon2_params.id)?;
+                WalletSettingSql::new(DbKey::PendingMainKey, encrypted_new_main_key.to_hex()).set(&mut conn)?;
+                new_main_key
+            },
+        };
+        let old_cipher = XChaCha20Poly1305::new(Key::from_slice(main_key.reveal()));
+        let new_cipher = XChaCha20Poly1305::new(Key::from_slice(new_main_key.reveal()));
+
+        // The master seed and Tor identity are stored as raw ciphertext in the settings table. Wallets created before
+        // database encryption was mandatory stored them, and the client key-values, as plaintext.
+        let mut summary = RekeySummary::default();
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            for (key, domain) in [
+                (DbKey::MasterSeed, &b"wallet_setting_master_seed"[..]),
+                (DbKey::TorId, &b"wallet_setting_tor_id"[..]),
+            ] {
+                if let Some(value) = WalletSettingSql::get(&key, conn)? {
+                    let (ciphertext, outcome) = rekey_bytes_integral_nonce(
+                        &from_hex(&value)?,
+                        domain,
+                        &old_cipher,
+                        &new_cipher,
+                        LegacyPlaintext::Migrate,
+                    )
+                    .map_err(WalletStorageError::AeadError)?;
+                    if outcome != RekeyOutcome::AlreadyRekeyed {
+                        WalletSettingSql::new(key, ciphertext.to_hex()).set(conn)?;
+                    }
+                    summary.record(outcome);
+                }
+            }
+            Ok(())
+        })?;
+
+        summary.merge(rekey_in_batches(
+            &mut conn,
+            &old_cipher,
+            &new_cipher,
+            batch_size,
+            LegacyPlaintext::Migrate,
+            ClientKeyValueSql::page,
+            ClientKeyValueSql::set,
+            WalletStorageError::AeadError,
+        )?);
+        summary.merge(rekey_in_batches(
+            &mut conn,
+            &old_cipher,
+            &new_cipher,
+            batch_size,
+            LegacyPlaintext::Reject,
+            BurntProofSql::page,
+            BurntProofSql::update_encryption,
+            WalletStorageError::AeadError,
+        )?);
+        summary.merge(rekey_in_batches(
+            &mut conn,
+            &old_cipher,
+            &new_cipher,
+            batch_size,
+            LegacyPlaintext::Reject,
+            AtomicSwapSql::page,
+            AtomicSwapSql::upsert,
+            WalletStorageError::AeadError,
+        )?);
+        summary.merge(
+            rekey_transaction_tables(&mut conn, &old_cipher, &new_cipher, batch_size)
+                .map_err(|e| WalletStorageError::RekeyError(e.to_string()))?,
+        );
+        summary.merge(
+            rekey_key_manager_tables(&mut conn, &old_cipher, &new_cipher, batch_size)
+                .map_err(|e| WalletStorageError::RekeyError(e.to_string()))?,
+        );
+
+        // Protect the replacement key with the new passphrase and retire the staged copy in one step
+        let new_argon2_params = Argon2Parameters::from_version(None)?;
+        let new_secondary_key_salt = SaltString::generate(&mut OsRng).to_string();
+        let (new_secondary_key, new_secondary_key_hash) =
+            derive_secondary_key(new, new_argon2_params.clone(), &new_secondary_key_salt)?;
+        let new_encrypted_main_key = encrypt_main_key(&new_secondary_key, &new_main_key, new_argon2_params.id)?;
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            DatabaseEncryptionFields {
+                secondary_key_version: new_argon2_params.id,
+                secondary_key_salt: new_secondary_key_salt,
+                secondary_key_hash: new_secondary_key_hash,
+                encrypted_main_key: new_encrypted_main_key,
+            }
+            .write(conn)?;
+            WalletSettingSql::clear(&DbKey::PendingMainKey, conn)?;
+            Ok(())
+        })?;
+
+        let mut cipher = acquire_write_lock!(self.cipher);
+        *cipher = new_cipher;
+        info!(
+            target: LOG_TARGET,
+            "Database rekeyed: {} values re-encrypted, {} already rekeyed, {} legacy plaintext values migrated",
+            summary.rekeyed,
+            summary.already_rekeyed,
+            summary.migrated_plaintext
+        );
+
+        Ok(summary)
+    }
+
     fn create_burnt_proof(
         &self,
         id: u32,
@@ -726,6 +882,39 @@ impl WalletBackend for WalletSqliteDatabase {
         BurntProofSql::delete(id, &mut conn)?;
         Ok(())
     }
+
+    fn save_atomic_swap(&self, swap: AtomicSwap) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        self.encrypt_value(AtomicSwapSql::from(swap))?.upsert(&mut conn)
+    }
+
+    fn fetch_atomic_swap(&self, swap_id: TxId) -> Result<Option<AtomicSwap>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AtomicSwapSql::get(swap_id, &mut conn)?
+            .map(|swap| AtomicSwap::try_from(self.decrypt_value(swap)?))
+            .transpose()
+    }
+
+    fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AtomicSwapSql::index(&mut conn)?
+            .into_iter()
+            .map(|swap| AtomicSwap::try_from(self.decrypt_value(swap)?))
+            .collect()
+    }
+
+    fn save_account(&self, account: WalletAccount) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AccountSql::from(account).insert(&mut conn)
+    }
+
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        AccountSql::index(&mut conn)?
+            .into_iter()
+            .map(WalletAccount::try_from)
+            .collect()
+    }
 }
 
 /// Derive a secondary database key and associated commitment
@@ -925,6 +1114,14 @@ impl ClientKeyValueSql {
         Ok(client_key_values::table.load::<ClientKeyValueSql>(conn)?)
     }
 
+    pub fn page(offset: i64, limit: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(client_key_values::table
+            .order(client_key_values::key.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<ClientKeyValueSql>(conn)?)
+    }
+
     pub fn set(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
         diesel::replace_into(client_key_values::table)
             .values(self)
@@ -1020,11 +1217,26 @@ impl BurntProofSql {
         Ok(burnt_proofs::table.load::<BurntProofSql>(conn)?)
     }
 
+    pub fn page(offset: i64, limit: i64, conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
+        Ok(burnt_proofs::table
+            .order(burnt_proofs::id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<BurntProofSql>(conn)?)
+    }
+
     pub fn insert(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
         diesel::insert_into(burnt_proofs::table).values(self).execute(conn)?;
         Ok(())
     }
 
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::update(burnt_proofs::table.filter(burnt_proofs::id.eq(self.id)))
+            .set(burnt_proofs::payload.eq(&self.payload))
+            .execute(conn)?;
+        Ok(())
+    }
+
     pub fn get(id: u32, conn: &mut SqliteConnection) -> Result<Option<Self>, WalletStorageError> {
         burnt_proofs::table
             .filter(burnt_proofs::id.eq(id as i32))
@@ -1086,8 +1298,14 @@ impl Encryptable<XChaCha20Poly1305> for BurntProofSql {
 
 #[cfg(test)]
 mod test {
+    use rand::rngs::OsRng;
     use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
-    use tari_common_types::encryption::{decrypt_bytes_integral_nonce, Encryptable};
+    use tari_common_types::{
+        encryption::{decrypt_bytes_integral_nonce, Encryptable},
+        transaction::TxId,
+        types::{FixedHash, PrivateKey, PublicKey},
+    };
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
     use tari_key_manager::cipher_seed::CipherSeed;
     use tari_test_utils::random::string;
     use tari_utilities::{
@@ -1097,10 +1315,14 @@ mod test {
     };
     use tempfile::tempdir;
 
-    use crate::storage::{
-        database::{DbKey, DbValue, WalletBackend},
-        sqlite_db::wallet::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
-        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    use crate::{
+        account::models::WalletAccount,
+        atomic_swap::models::{AtomicSwap, AtomicSwapStatus, CounterpartyChain, SwapEvent},
+        storage::{
+            database::{DbKey, DbValue, WalletBackend},
+            sqlite_db::wallet::{BurntProofSql, ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
+            sqlite_utilities::run_migration_and_create_sqlite_connection,
+        },
     };
     #[test]
     fn test_passphrase() {
@@ -1146,6 +1368,109 @@ mod test {
         assert!(WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).is_ok());
     }
 
+    #[test]
+    fn test_rekey() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+        let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+        let mut conn = connection.get_pooled_connection().unwrap();
+
+        let db = WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).unwrap();
+        let old_cipher = db.cipher();
+        let seed = CipherSeed::new();
+        db.set_master_seed(&seed, &mut conn).unwrap();
+        for i in 0..5 {
+            ClientKeyValueSql::new(format!("key{}", i), format!("value{}", i), &old_cipher)
+                .unwrap()
+                .set(&mut conn)
+                .unwrap();
+        }
+        // A value written before encryption was enforced
+        ClientKeyValueSql {
+            key: "legacy".to_string(),
+            value: "plaintext".to_string(),
+        }
+        .set(&mut conn)
+        .unwrap();
+
+        // A wrong passphrase changes nothing
+        assert!(db
+            .rekey(
+                &"evil passphrase".to_string().into(),
+                &"new passphrase".to_string().into(),
+                2
+            )
+            .is_err());
+        assert!(WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).is_ok());
+
+        let summary = db
+            .rekey(
+                &"passphrase".to_string().into(),
+                &"new passphrase".to_string().into(),
+                2,
+            )
+            .unwrap();
+        assert_eq!(summary.rekeyed, 6);
+        assert_eq!(summary.migrated_plaintext, 1);
+        assert!(WalletSettingSql::get(&DbKey::PendingMainKey, &mut conn)
+            .unwrap()
+            .is_none());
+
+        // Only the new passphrase opens the database, and the values survive under the new key
+        assert!(WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).is_err());
+        let reopened = WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).unwrap();
+        let new_cipher = reopened.cipher();
+        assert_eq!(
+            reopened.get_master_seed(&mut conn).unwrap().unwrap().entropy(),
+            seed.entropy()
+        );
+        // The handle that performed the rekey switches to the new key as well
+        assert!(db.get_master_seed(&mut conn).unwrap().is_some());
+        for i in 0..5 {
+            let kv = ClientKeyValueSql::get(&format!("key{}", i), &mut conn)
+                .unwrap()
+                .unwrap();
+            assert!(kv.clone().decrypt(&old_cipher).is_err());
+            assert_eq!(kv.decrypt(&new_cipher).unwrap().value, format!("value{}", i));
+        }
+        let legacy = ClientKeyValueSql::get("legacy", &mut conn).unwrap().unwrap();
+        assert_eq!(legacy.decrypt(&new_cipher).unwrap().value, "plaintext");
+    }
+
+    #[test]
+    fn test_rekey_rejects_undecryptable_values() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+        let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+        let mut conn = connection.get_pooled_connection().unwrap();
+
+        let db = WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).unwrap();
+        // Burnt proofs have always been encrypted, so a value that does not decrypt is corrupt rather than legacy
+        BurntProofSql {
+            id: 1,
+            reciprocal_claim_public_key: PublicKey::default().to_hex(),
+            payload: "not encrypted".to_string(),
+            burned_at: chrono::Utc::now().naive_utc(),
+        }
+        .insert(&mut conn)
+        .unwrap();
+
+        assert!(db
+            .rekey(
+                &"passphrase".to_string().into(),
+                &"new passphrase".to_string().into(),
+                2,
+            )
+            .is_err());
+        // The passphrase is only replaced once every value has been rekeyed
+        assert!(WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).is_ok());
+        assert!(WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).is_err());
+    }
+
     #[test]
     #[allow(unused_must_use)]
     fn test_malleated_secondary_key_hash() {
@@ -1336,4 +1661,55 @@ mod test {
 
         assert_eq!(decrypted_db_seed, seed_bytes);
     }
+
+    #[test]
+    fn test_atomic_swaps() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+        let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+        let db = WalletSqliteDatabase::new(connection, "passphrase".to_string().into()).unwrap();
+
+        let mut swap = AtomicSwap::new_initiated(
+            TxId::new_random(),
+            CounterpartyChain::Bitcoin,
+            "bc1qexample".to_string(),
+            1_000_000.into(),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            FixedHash::zero(),
+            1000,
+        );
+        db.save_atomic_swap(swap.clone()).unwrap();
+        assert_eq!(db.fetch_atomic_swap(swap.swap_id).unwrap(), Some(swap.clone()));
+        assert_eq!(db.fetch_atomic_swap(TxId::new_random()).unwrap(), None);
+
+        swap.apply(SwapEvent::FundingConfirmed).unwrap();
+        db.save_atomic_swap(swap.clone()).unwrap();
+        let swaps = db.fetch_atomic_swaps().unwrap();
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(swaps[0].status, AtomicSwapStatus::Funded);
+        assert_eq!(swaps[0], swap);
+    }
+
+    #[test]
+    fn test_accounts() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+        let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+        let db = WalletSqliteDatabase::new(connection, "passphrase".to_string().into()).unwrap();
+
+        assert!(db.fetch_accounts().unwrap().is_empty());
+        let savings = WalletAccount::new(2, "savings".to_string());
+        let spending = WalletAccount::new(1, "spending".to_string());
+        db.save_account(savings.clone()).unwrap();
+        db.save_account(spending.clone()).unwrap();
+        assert_eq!(db.fetch_accounts().unwrap(), vec![spending, savings]);
+
+        // Neither the index nor the name can be reused
+        assert!(db.save_account(WalletAccount::new(1, "other".to_string())).is_err());
+        assert!(db.save_account(WalletAccount::new(3, "savings".to_string())).is_err());
+    }
 }
@@ -20,12 +20,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fs::File, ops::DerefMut, path::Path, time::Duration};
+use std::{convert::TryFrom, fs::File, ops::DerefMut, path::Path, time::Duration};
 
+use chacha20poly1305::XChaCha20Poly1305;
+use diesel::{result::Error as DieselError, Connection, SqliteConnection};
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
 use fs2::FileExt;
 use log::*;
 use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
+use tari_common_types::encryption::{rekey_encryptable, Encryptable, LegacyPlaintext, RekeyOutcome, RekeySummary};
 use tari_contacts::contacts_service::storage::sqlite_db::ContactsServiceSqliteDatabase;
 use tari_key_manager::key_manager_service::storage::sqlite_db::KeyManagerSqliteDatabase;
 use tari_utilities::SafePassword;
@@ -127,6 +130,50 @@ pub fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, WalletStorage
     Ok(file)
 }
 
+/// Move the rows returned by `load` from the `old` cipher to the `new` one, writing changed rows back with `update`.
+/// Each page of `batch_size` rows is committed in its own transaction so that large tables do not hold a single
+/// long-running write lock, and rows already encrypted under `new` are skipped so an interrupted run can be repeated.
+/// Rows that decrypt under neither cipher fail the rekey unless `legacy_plaintext` allows them to be migrated.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn rekey_in_batches<T, E, L, U>(
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+    batch_size: usize,
+    legacy_plaintext: LegacyPlaintext,
+    load: L,
+    update: U,
+    aead_error: fn(String) -> E,
+) -> Result<RekeySummary, E>
+where
+    T: Encryptable<XChaCha20Poly1305> + Clone,
+    E: From<DieselError>,
+    L: Fn(i64, i64, &mut SqliteConnection) -> Result<Vec<T>, E>,
+    U: Fn(&T, &mut SqliteConnection) -> Result<(), E>,
+{
+    let batch_size = batch_size.max(1);
+    let limit = i64::try_from(batch_size).unwrap_or(i64::MAX);
+    let mut summary = RekeySummary::default();
+    let mut offset = 0i64;
+    loop {
+        let num_rows = conn.transaction::<_, E, _>(|conn| {
+            let rows = load(offset, limit, conn)?;
+            for row in &rows {
+                let (row, outcome) = rekey_encryptable(row.clone(), old, new, legacy_plaintext).map_err(aead_error)?;
+                if outcome != RekeyOutcome::AlreadyRekeyed {
+                    update(&row, conn)?;
+                }
+                summary.record(outcome);
+            }
+            Ok(rows.len())
+        })?;
+        if num_rows < batch_size {
+            return Ok(summary);
+        }
+        offset += limit;
+    }
+}
+
 #[allow(clippy::type_complexity)]
 pub fn initialize_sqlite_database_backends<P: AsRef<Path>>(
     db_path: P,
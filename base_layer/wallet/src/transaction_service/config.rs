@@ -69,6 +69,8 @@ pub struct TransactionServiceConfig {
     /// This is the timeout period that will be used to re-submit transactions not found in the mempool
     #[serde(with = "serializers::seconds")]
     pub transaction_mempool_resubmission_window: Duration,
+    /// What to do with a broadcast transaction that the base node reports as expired from its mempool
+    pub expired_transaction_action: ExpiredTransactionAction,
 }
 
 impl Default for TransactionServiceConfig {
@@ -87,6 +89,7 @@ impl Default for TransactionServiceConfig {
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
             transaction_event_channel_size: 1000,
             transaction_mempool_resubmission_window: Duration::from_secs(600),
+            expired_transaction_action: ExpiredTransactionAction::default(),
         }
     }
 }
@@ -131,3 +134,23 @@ impl Default for TransactionRoutingMechanism {
         Self::DirectAndStoreAndForward
     }
 }
+
+/// The action taken by the transaction broadcast protocol when the base node reports that a transaction expired from
+/// its mempool without being mined
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum ExpiredTransactionAction {
+    /// Submit the transaction to the base node again
+    #[default]
+    Rebroadcast,
+    /// Cancel the transaction, releasing its inputs
+    Cancel,
+}
+
+impl fmt::Display for ExpiredTransactionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rebroadcast => f.write_str("'Rebroadcast'"),
+            Self::Cancel => f.write_str("'Cancel'"),
+        }
+    }
+}
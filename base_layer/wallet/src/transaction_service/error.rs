@@ -32,6 +32,7 @@ use tari_common_types::{
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    offline_signing::OfflineSigningError,
     transaction_components::{EncryptedDataError, TransactionError},
     transaction_protocol::TransactionProtocolError,
 };
@@ -47,6 +48,7 @@ use crate::{
     error::WalletStorageError,
     output_manager_service::error::OutputManagerError,
     transaction_service::{
+        payment_request::PaymentRequestError,
         storage::{database::DbKey, sqlite_db::CompletedTransactionConversionError},
         utc::NegativeDurationError,
     },
@@ -58,6 +60,8 @@ pub enum TransactionServiceError {
     InvalidStateError,
     #[error("Transaction is sending to a network different than ours")]
     InvalidNetwork,
+    #[error("Subaddresses can only receive one-sided payments")]
+    SubaddressNotInteractive,
     #[error("One-sided transaction error: `{0}`")]
     OneSidedTransactionError(String),
     #[error("Transaction Protocol Error: `{0}`")]
@@ -150,6 +154,8 @@ pub enum TransactionServiceError {
     MempoolRejectionDoubleSpend,
     #[error("Transaction detected as rejected by mempool due to invalid transaction")]
     MempoolRejectionInvalidTransaction,
+    #[error("Transaction expired from the mempool before it was mined")]
+    MempoolRejectionExpired,
     #[error("Transaction is malformed")]
     InvalidTransaction,
     #[error("RpcError: `{0}`")]
@@ -191,6 +197,12 @@ pub enum TransactionServiceError {
     TransactionTooLarge { got: usize, expected: usize },
     #[error("Pending Transaction was oversized")]
     Oversized,
+    #[error("Offline signing error: {0}")]
+    OfflineSigningError(#[from] OfflineSigningError),
+    #[error("Transaction cannot be replaced: {0}")]
+    TransactionNotReplaceable(String),
+    #[error("Payment request error: {0}")]
+    PaymentRequestError(#[from] PaymentRequestError),
 }
 
 impl From<RangeProofError> for TransactionServiceError {
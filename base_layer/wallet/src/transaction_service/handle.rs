@@ -40,6 +40,7 @@ use tari_core::{
     mempool::FeePerGramStat,
     proto,
     transactions::{
+        offline_signing::{SignedTransaction, TransactionSigningRequest},
         tari_amount::MicroMinotari,
         transaction_components::{
             BuildInfo,
@@ -56,9 +57,11 @@ use tokio::sync::broadcast;
 use tower::Service;
 
 use crate::{
-    output_manager_service::UtxoSelectionCriteria,
+    output_manager_service::{CoinSelectionReport, UtxoSelectionCriteria},
     transaction_service::{
         error::TransactionServiceError,
+        history::TransactionHistoryRecord,
+        payment_request::PaymentRequest,
         storage::models::{
             CompletedTransaction,
             InboundTransaction,
@@ -82,6 +85,7 @@ pub enum TransactionServiceRequest {
     GetCancelledCompletedTransactions,
     GetCompletedTransaction(TxId),
     GetAnyTransaction(TxId),
+    GetTransactionsByPaymentReference(Vec<u8>),
     ImportTransaction(WalletTransaction),
     SendTransaction {
         destination: TariAddress,
@@ -124,6 +128,35 @@ pub enum TransactionServiceRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
+    },
+    PrepareOneSidedTransactionForSigning {
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: Box<OutputFeatures>,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        payment_reference: Vec<u8>,
+    },
+    SubmitSignedTransaction(Box<SignedTransaction>),
+    RestoreTransactionMessages(Vec<TransactionHistoryRecord>),
+    CreatePaymentRequest {
+        amount: MicroMinotari,
+        memo: String,
+        expiry: u64,
+    },
+    PayPaymentRequest {
+        payment_request: Box<PaymentRequest>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    },
+    SendOneSidedBatch {
+        recipients: Vec<BatchRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
     },
     SendOneSidedToStealthAddressTransaction {
         destination: TariAddress,
@@ -132,9 +165,21 @@ pub enum TransactionServiceRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
     },
     SendShaAtomicSwapTransaction(TariAddress, MicroMinotari, UtxoSelectionCriteria, MicroMinotari, String),
     CancelTransaction(TxId),
+    /// Replaces an unconfirmed outbound one-sided transaction with one that spends the same inputs at a higher fee
+    BumpFee {
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    },
+    /// Replaces an unconfirmed outbound transaction with one that spends its inputs back to this wallet
+    CancelPending {
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    },
     ImportUtxoWithStatus {
         amount: MicroMinotari,
         source_address: TariAddress,
@@ -144,6 +189,8 @@ pub enum TransactionServiceRequest {
         current_height: Option<u64>,
         mined_timestamp: Option<NaiveDateTime>,
         scanned_output: TransactionOutput,
+        payment_reference: Option<Vec<u8>>,
+        memo: Option<Vec<u8>>,
     },
     SubmitTransactionToSelf(TxId, Transaction, MicroMinotari, MicroMinotari, String),
     SetLowPowerMode,
@@ -158,6 +205,16 @@ pub enum TransactionServiceRequest {
     GetFeePerGramStatsPerBlock {
         count: usize,
     },
+    /// Returns the fee per gram needed for a transaction to be mined within {target_blocks} blocks.
+    EstimateFeePerGram {
+        target_blocks: usize,
+    },
+    /// Returns the inputs, fee, change and weight of a transaction sending {amount} without committing to it.
+    PreviewTransaction {
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    },
 }
 
 impl fmt::Display for TransactionServiceRequest {
@@ -193,6 +250,46 @@ impl fmt::Display for TransactionServiceRequest {
                 "SendOneSidedTransaction (to {}, {}, {})",
                 destination, amount, message
             ),
+            Self::PrepareOneSidedTransactionForSigning {
+                destination,
+                amount,
+                message,
+                ..
+            } => write!(
+                f,
+                "PrepareOneSidedTransactionForSigning (to {}, {}, {})",
+                destination, amount, message
+            ),
+            Self::SubmitSignedTransaction(signed_transaction) => {
+                write!(f, "SubmitSignedTransaction ({})", signed_transaction.payment.tx_id)
+            },
+            Self::RestoreTransactionMessages(records) => {
+                write!(f, "RestoreTransactionMessages ({} records)", records.len())
+            },
+            Self::CreatePaymentRequest { amount, memo, expiry } => {
+                write!(f, "CreatePaymentRequest ({}, {}, expiry: {})", amount, memo, expiry)
+            },
+            Self::PayPaymentRequest {
+                payment_request,
+                fee_per_gram,
+                ..
+            } => write!(
+                f,
+                "PayPaymentRequest (to {}, {}, {}, fee_per_gram: {})",
+                payment_request.recipient, payment_request.amount, payment_request.memo, fee_per_gram
+            ),
+            Self::SendOneSidedBatch {
+                recipients,
+                fee_per_gram,
+                message,
+                ..
+            } => write!(
+                f,
+                "SendOneSidedBatch ({} recipients, {}, fee_per_gram: {})",
+                recipients.len(),
+                message,
+                fee_per_gram
+            ),
             Self::SendOneSidedToStealthAddressTransaction {
                 destination,
                 amount,
@@ -207,6 +304,12 @@ impl fmt::Display for TransactionServiceRequest {
                 write!(f, "SendShaAtomicSwapTransaction (to {}, {}, {})", k, v, msg)
             },
             Self::CancelTransaction(t) => write!(f, "CancelTransaction ({})", t),
+            Self::BumpFee { tx_id, fee_per_gram } => {
+                write!(f, "BumpFee (tx_id: {}, fee_per_gram: {})", tx_id, fee_per_gram)
+            },
+            Self::CancelPending { tx_id, fee_per_gram } => {
+                write!(f, "CancelPending (tx_id: {}, fee_per_gram: {})", tx_id, fee_per_gram)
+            },
             Self::ImportUtxoWithStatus {
                 amount,
                 source_address,
@@ -229,11 +332,26 @@ impl fmt::Display for TransactionServiceRequest {
             Self::GetNumConfirmationsRequired => write!(f, "GetNumConfirmationsRequired"),
             Self::SetNumConfirmationsRequired(_) => write!(f, "SetNumConfirmationsRequired"),
             Self::GetAnyTransaction(t) => write!(f, "GetAnyTransaction({})", t),
+            Self::GetTransactionsByPaymentReference(r) => {
+                write!(f, "GetTransactionsByPaymentReference({} bytes)", r.len())
+            },
             Self::ValidateTransactions => write!(f, "ValidateTransactions"),
             Self::ReValidateTransactions => write!(f, "ReValidateTransactions"),
             Self::GetFeePerGramStatsPerBlock { count } => {
                 write!(f, "GetFeePerGramEstimatesPerBlock(count: {})", count,)
             },
+            Self::EstimateFeePerGram { target_blocks } => {
+                write!(f, "EstimateFeePerGram(target_blocks: {})", target_blocks)
+            },
+            Self::PreviewTransaction {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            } => write!(
+                f,
+                "PreviewTransaction(amount: {}, fee_per_gram: {}, selection_criteria: {})",
+                amount, fee_per_gram, selection_criteria
+            ),
             TransactionServiceRequest::RegisterCodeTemplate { template_name, .. } => {
                 write!(f, "RegisterCodeTemplate: {}", template_name)
             },
@@ -255,6 +373,7 @@ pub enum TransactionServiceResponse {
         template_registration: Box<CodeTemplateRegistration>,
     },
     TransactionCancelled,
+    TransactionReplaced(TxId),
     PendingInboundTransactions(HashMap<TxId, InboundTransaction>),
     PendingOutboundTransactions(HashMap<TxId, OutboundTransaction>),
     CompletedTransactions(HashMap<TxId, CompletedTransaction>),
@@ -272,6 +391,30 @@ pub enum TransactionServiceResponse {
     CompletedTransactionValidityChanged,
     ShaAtomicSwapTransactionSent(Box<(TxId, PublicKey, TransactionOutput)>),
     FeePerGramStatsPerBlock(FeePerGramStatsResponse),
+    FeePerGramEstimate(MicroMinotari),
+    SigningRequestPrepared(Box<TransactionSigningRequest>),
+    PaymentRequestCreated(Box<PaymentRequest>),
+    TransactionMessagesRestored(usize),
+    BatchSent(Vec<BatchRecipientStatus>),
+    TransactionPreview(Box<CoinSelectionReport>),
+}
+
+/// A recipient of a batch payment made with [TransactionServiceHandle::send_to_many]
+#[derive(Debug, Clone)]
+pub struct BatchRecipient {
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    /// One-sided recipients share a single transaction, interactive recipients each get a transaction of their own
+    pub one_sided: bool,
+    /// Only included in one-sided outputs
+    pub payment_reference: Vec<u8>,
+}
+
+/// The outcome of a batch payment for a single recipient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchRecipientStatus {
+    Sent(TxId),
+    Failed(String),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
@@ -318,6 +461,8 @@ pub enum TransactionEvent {
         is_valid: bool,
     },
     TransactionMinedRequestTimedOut(TxId),
+    /// A previously mined transaction is no longer in the main chain after a reorg
+    TransactionReorgedOut(TxId),
     TransactionMinedUnconfirmed {
         tx_id: TxId,
         num_confirmations: u64,
@@ -382,6 +527,9 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::TransactionMinedRequestTimedOut(tx) => {
                 write!(f, "TransactionMinedRequestTimedOut for {tx}")
             },
+            TransactionEvent::TransactionReorgedOut(tx) => {
+                write!(f, "TransactionReorgedOut for {tx}")
+            },
             TransactionEvent::TransactionMinedUnconfirmed {
                 tx_id,
                 num_confirmations,
@@ -540,6 +688,8 @@ impl TransactionServiceHandle {
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
     ) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -550,6 +700,109 @@ impl TransactionServiceHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 message,
+                payment_reference,
+                memo,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Builds a one-sided payment without signing it, for an external signer. Used by watch-only wallets.
+    pub async fn prepare_one_sided_transaction_for_signing(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        payment_reference: Vec<u8>,
+    ) -> Result<TransactionSigningRequest, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::PrepareOneSidedTransactionForSigning {
+                destination,
+                amount,
+                selection_criteria,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+                payment_reference,
+            })
+            .await??
+        {
+            TransactionServiceResponse::SigningRequestPrepared(request) => Ok(*request),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Records and broadcasts a transaction returned by an external signer
+    pub async fn submit_signed_transaction(
+        &mut self,
+        signed_transaction: SignedTransaction,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SubmitSignedTransaction(Box::new(
+                signed_transaction,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Restores the messages of exported transactions to the matching transactions of this wallet, returning the
+    /// number of transactions updated
+    pub async fn restore_transaction_messages(
+        &mut self,
+        records: Vec<TransactionHistoryRecord>,
+    ) -> Result<usize, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::RestoreTransactionMessages(records))
+            .await??
+        {
+            TransactionServiceResponse::TransactionMessagesRestored(count) => Ok(count),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a payment request for `amount` to this wallet, signed with the wallet's identity key. `expiry` is the
+    /// unix timestamp in seconds after which the request must not be paid.
+    pub async fn create_payment_request(
+        &mut self,
+        amount: MicroMinotari,
+        memo: String,
+        expiry: u64,
+    ) -> Result<PaymentRequest, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CreatePaymentRequest { amount, memo, expiry })
+            .await??
+        {
+            TransactionServiceResponse::PaymentRequestCreated(request) => Ok(*request),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Pays a payment request with a one-sided transaction, after checking its signature and expiry
+    pub async fn pay_payment_request(
+        &mut self,
+        payment_request: PaymentRequest,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::PayPaymentRequest {
+                payment_request: Box::new(payment_request),
+                selection_criteria,
+                fee_per_gram,
             })
             .await??
         {
@@ -558,6 +811,65 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Pays many recipients at once, e.g. to batch exchange withdrawals. All one-sided recipients are paid in a single
+    /// transaction, while each interactive recipient is paid in a negotiated transaction of its own. A failure only
+    /// affects the recipients of the failed transaction; the returned statuses are in the order of `recipients`.
+    pub async fn send_to_many(
+        &mut self,
+        recipients: Vec<BatchRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<Vec<BatchRecipientStatus>, TransactionServiceError> {
+        let mut statuses = vec![None; recipients.len()];
+        let (one_sided, interactive): (Vec<_>, Vec<_>) = recipients
+            .into_iter()
+            .enumerate()
+            .partition(|(_, recipient)| recipient.one_sided);
+
+        if !one_sided.is_empty() {
+            let (indexes, batch): (Vec<_>, Vec<_>) = one_sided.into_iter().unzip();
+            let num_recipients = batch.len();
+            let batch_statuses = match self
+                .handle
+                .call(TransactionServiceRequest::SendOneSidedBatch {
+                    recipients: batch,
+                    selection_criteria: selection_criteria.clone(),
+                    fee_per_gram,
+                    message: message.clone(),
+                })
+                .await?
+            {
+                Ok(TransactionServiceResponse::BatchSent(batch_statuses)) => batch_statuses,
+                Ok(_) => return Err(TransactionServiceError::UnexpectedApiResponse),
+                Err(e) => vec![BatchRecipientStatus::Failed(e.to_string()); num_recipients],
+            };
+            for (index, status) in indexes.into_iter().zip(batch_statuses) {
+                statuses[index] = Some(status);
+            }
+        }
+
+        for (index, recipient) in interactive {
+            let status = match self
+                .send_transaction(
+                    recipient.destination,
+                    recipient.amount,
+                    selection_criteria.clone(),
+                    OutputFeatures::default(),
+                    fee_per_gram,
+                    message.clone(),
+                )
+                .await
+            {
+                Ok(tx_id) => BatchRecipientStatus::Sent(tx_id),
+                Err(e) => BatchRecipientStatus::Failed(e.to_string()),
+            };
+            statuses[index] = Some(status);
+        }
+
+        Ok(statuses.into_iter().flatten().collect())
+    }
+
     /// Burns the given amount of Tari from the wallet
     pub async fn burn_tari(
         &mut self,
@@ -591,6 +903,8 @@ impl TransactionServiceHandle {
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
     ) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -601,6 +915,8 @@ impl TransactionServiceHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 message,
+                payment_reference,
+                memo,
             })
             .await??
         {
@@ -620,6 +936,40 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Replaces an unconfirmed outbound one-sided transaction with one paying `fee_per_gram`, returning the id of the
+    /// replacement transaction
+    pub async fn bump_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::BumpFee { tx_id, fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::TransactionReplaced(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancels an unconfirmed outbound transaction by spending its inputs back to this wallet at `fee_per_gram`,
+    /// returning the id of the cancelling transaction
+    pub async fn cancel_pending(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CancelPending { tx_id, fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::TransactionReplaced(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn get_pending_inbound_transactions(
         &mut self,
     ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
@@ -726,6 +1076,23 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Returns the completed transactions carrying the given payment reference
+    pub async fn get_transactions_by_payment_reference(
+        &mut self,
+        payment_reference: Vec<u8>,
+    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionsByPaymentReference(
+                payment_reference,
+            ))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn import_transaction(&mut self, tx: WalletTransaction) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -747,6 +1114,8 @@ impl TransactionServiceHandle {
         current_height: Option<u64>,
         mined_timestamp: Option<NaiveDateTime>,
         scanned_output: TransactionOutput,
+        payment_reference: Option<Vec<u8>>,
+        memo: Option<Vec<u8>>,
     ) -> Result<TxId, TransactionServiceError> {
         match self
             .handle
@@ -759,6 +1128,8 @@ impl TransactionServiceHandle {
                 current_height,
                 mined_timestamp,
                 scanned_output,
+                payment_reference,
+                memo,
             })
             .await??
         {
@@ -912,4 +1283,41 @@ impl TransactionServiceHandle {
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
+
+    /// Query the base node for the fee per gram needed for a transaction to be mined within {target_blocks} blocks.
+    pub async fn estimate_fee_per_gram(
+        &mut self,
+        target_blocks: usize,
+    ) -> Result<MicroMinotari, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::EstimateFeePerGram { target_blocks })
+            .await??
+        {
+            TransactionServiceResponse::FeePerGramEstimate(fee_per_gram) => Ok(fee_per_gram),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Selects the inputs a transaction sending `amount` would spend and reports the resulting fee, change and
+    /// weight. Nothing is encumbered, so the preview may differ from the transaction that is eventually sent.
+    pub async fn preview_transaction(
+        &mut self,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<CoinSelectionReport, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::PreviewTransaction {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionPreview(report) => Ok(*report),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
 }
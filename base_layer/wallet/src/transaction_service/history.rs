@@ -0,0 +1,366 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Export and import of the transaction history in CSV and JSON.
+//!
+//! An export carries the full fee, kernel and commitment detail of every completed transaction, along with the name of
+//! the counterparty contact. Importing an export into a recovered wallet restores the memos of the transactions that
+//! can be matched by kernel excess or received output commitment, and the contact names, neither of which can be
+//! recovered from the chain.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+
+use crate::transaction_service::storage::models::CompletedTransaction;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Separates the commitments within a single CSV field
+const CSV_LIST_SEPARATOR: char = ';';
+const CSV_COLUMNS: [&str; 19] = [
+    "tx_id",
+    "direction",
+    "status",
+    "cancellation_reason",
+    "amount",
+    "fee",
+    "source_address",
+    "destination_address",
+    "contact_name",
+    "message",
+    "payment_reference",
+    "timestamp",
+    "mined_height",
+    "mined_in_block",
+    "kernel_excess",
+    "kernel_public_nonce",
+    "kernel_signature",
+    "input_commitments",
+    "output_commitments",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum TransactionHistoryFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TransactionHistoryError {
+    #[error("Invalid JSON transaction history: {0}")]
+    InvalidJson(String),
+    #[error("Invalid CSV transaction history: {0}")]
+    InvalidCsv(String),
+}
+
+/// The outcome of importing a transaction history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionHistoryImport {
+    pub num_messages_restored: usize,
+    pub num_contacts_restored: usize,
+}
+
+/// A single transaction in an exported transaction history. Keys, hashes and addresses are hex encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionHistoryRecord {
+    pub tx_id: u64,
+    pub direction: String,
+    pub status: String,
+    pub cancellation_reason: Option<String>,
+    pub amount: u64,
+    pub fee: u64,
+    pub source_address: String,
+    pub destination_address: String,
+    /// The name of the counterparty in the wallet's contacts
+    pub contact_name: Option<String>,
+    pub message: String,
+    pub payment_reference: Option<String>,
+    pub timestamp: String,
+    pub mined_height: Option<u64>,
+    pub mined_in_block: Option<String>,
+    pub kernel_excess: Option<String>,
+    pub kernel_public_nonce: Option<String>,
+    pub kernel_signature: Option<String>,
+    pub input_commitments: Vec<String>,
+    pub output_commitments: Vec<String>,
+}
+
+impl TransactionHistoryRecord {
+    pub fn new(tx: &CompletedTransaction, contact_name: Option<String>) -> Self {
+        let kernel = tx.transaction.body.kernels().first();
+        Self {
+            tx_id: tx.tx_id.as_u64(),
+            direction: tx.direction.to_string(),
+            status: tx.status.to_string(),
+            cancellation_reason: tx.cancelled.as_ref().map(|reason| reason.to_string()),
+            amount: tx.amount.as_u64(),
+            fee: tx.fee.as_u64(),
+            source_address: tx.source_address.to_hex(),
+            destination_address: tx.destination_address.to_hex(),
+            contact_name,
+            message: tx.message.clone(),
+            payment_reference: tx.payment_reference.as_ref().map(|reference| reference.to_hex()),
+            timestamp: tx.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+            mined_height: tx.mined_height,
+            mined_in_block: tx.mined_in_block.as_ref().map(|hash| hash.to_hex()),
+            kernel_excess: kernel.map(|kernel| kernel.excess.to_hex()),
+            kernel_public_nonce: kernel.map(|kernel| kernel.excess_sig.get_public_nonce().to_hex()),
+            kernel_signature: kernel.map(|kernel| kernel.excess_sig.get_signature().to_hex()),
+            input_commitments: tx
+                .transaction
+                .body
+                .inputs()
+                .iter()
+                .filter_map(|input| input.commitment().ok().map(|commitment| commitment.to_hex()))
+                .collect(),
+            output_commitments: tx
+                .transaction
+                .body
+                .outputs()
+                .iter()
+                .map(|output| output.commitment.to_hex())
+                .collect(),
+        }
+    }
+
+    fn to_csv_fields(&self) -> Vec<String> {
+        vec![
+            self.tx_id.to_string(),
+            self.direction.clone(),
+            self.status.clone(),
+            self.cancellation_reason.clone().unwrap_or_default(),
+            self.amount.to_string(),
+            self.fee.to_string(),
+            self.source_address.clone(),
+            self.destination_address.clone(),
+            self.contact_name.clone().unwrap_or_default(),
+            self.message.clone(),
+            self.payment_reference.clone().unwrap_or_default(),
+            self.timestamp.clone(),
+            self.mined_height.map(|height| height.to_string()).unwrap_or_default(),
+            self.mined_in_block.clone().unwrap_or_default(),
+            self.kernel_excess.clone().unwrap_or_default(),
+            self.kernel_public_nonce.clone().unwrap_or_default(),
+            self.kernel_signature.clone().unwrap_or_default(),
+            join_csv_list(&self.input_commitments),
+            join_csv_list(&self.output_commitments),
+        ]
+    }
+
+    fn from_csv_fields(columns: &HashMap<&str, usize>, fields: &[String]) -> Result<Self, TransactionHistoryError> {
+        let field = |name: &str| -> String {
+            columns
+                .get(name)
+                .and_then(|i| fields.get(*i))
+                .cloned()
+                .unwrap_or_default()
+        };
+        let optional = |name: &str| Some(field(name)).filter(|value| !value.is_empty());
+        let number = |name: &str| -> Result<Option<u64>, TransactionHistoryError> {
+            optional(name)
+                .map(|value| {
+                    value
+                        .parse::<u64>()
+                        .map_err(|e| TransactionHistoryError::InvalidCsv(format!("{}: {}", name, e)))
+                })
+                .transpose()
+        };
+
+        Ok(Self {
+            tx_id: number("tx_id")?.unwrap_or_default(),
+            direction: field("direction"),
+            status: field("status"),
+            cancellation_reason: optional("cancellation_reason"),
+            amount: number("amount")?.unwrap_or_default(),
+            fee: number("fee")?.unwrap_or_default(),
+            source_address: field("source_address"),
+            destination_address: field("destination_address"),
+            contact_name: optional("contact_name"),
+            message: field("message"),
+            payment_reference: optional("payment_reference"),
+            timestamp: field("timestamp"),
+            mined_height: number("mined_height")?,
+            mined_in_block: optional("mined_in_block"),
+            kernel_excess: optional("kernel_excess"),
+            kernel_public_nonce: optional("kernel_public_nonce"),
+            kernel_signature: optional("kernel_signature"),
+            input_commitments: split_csv_list(&field("input_commitments")),
+            output_commitments: split_csv_list(&field("output_commitments")),
+        })
+    }
+}
+
+/// Serialize the records in the given format
+pub fn encode_transaction_history(
+    records: &[TransactionHistoryRecord],
+    format: TransactionHistoryFormat,
+) -> Result<String, TransactionHistoryError> {
+    match format {
+        TransactionHistoryFormat::Json => {
+            serde_json::to_string_pretty(records).map_err(|e| TransactionHistoryError::InvalidJson(e.to_string()))
+        },
+        TransactionHistoryFormat::Csv => {
+            let mut csv = write_csv_row(CSV_COLUMNS.iter().copied());
+            for record in records {
+                csv.push_str(&write_csv_row(record.to_csv_fields().iter().map(String::as_str)));
+            }
+            Ok(csv)
+        },
+    }
+}
+
+/// Parse records serialized in the given format. CSV columns are matched by the names in the header row.
+pub fn decode_transaction_history(
+    data: &str,
+    format: TransactionHistoryFormat,
+) -> Result<Vec<TransactionHistoryRecord>, TransactionHistoryError> {
+    match format {
+        TransactionHistoryFormat::Json => {
+            serde_json::from_str(data).map_err(|e| TransactionHistoryError::InvalidJson(e.to_string()))
+        },
+        TransactionHistoryFormat::Csv => {
+            let mut rows = parse_csv(data)?.into_iter();
+            let header = rows
+                .next()
+                .ok_or_else(|| TransactionHistoryError::InvalidCsv("missing header row".to_string()))?;
+            let columns = header
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.as_str(), i))
+                .collect::<HashMap<_, _>>();
+            if !columns.contains_key("tx_id") {
+                return Err(TransactionHistoryError::InvalidCsv("missing tx_id column".to_string()));
+            }
+            rows.map(|fields| TransactionHistoryRecord::from_csv_fields(&columns, &fields))
+                .collect()
+        },
+    }
+}
+
+fn join_csv_list(values: &[String]) -> String {
+    values.join(&CSV_LIST_SEPARATOR.to_string())
+}
+
+fn split_csv_list(value: &str) -> Vec<String> {
+    value
+        .split(CSV_LIST_SEPARATOR)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Write a CSV row with every field quoted, as RFC 4180 describes
+fn write_csv_row<'a, I: Iterator<Item = &'a str>>(fields: I) -> String {
+    let mut row = fields
+        .map(|field| format!("\"{}\"", field.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push_str("\r\n");
+    row
+}
+
+/// Parse RFC 4180 CSV, in which quoted fields may contain separators, line breaks and doubled quotes
+fn parse_csv(data: &str) -> Result<Vec<Vec<String>>, TransactionHistoryError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            },
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {},
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            },
+            (c, _) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(TransactionHistoryError::InvalidCsv(
+            "unterminated quoted field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record() -> TransactionHistoryRecord {
+        TransactionHistoryRecord {
+            tx_id: 42,
+            direction: "Outbound".to_string(),
+            status: "Mined Confirmed".to_string(),
+            amount: 1_000_000,
+            fee: 250,
+            contact_name: Some("Alice, \"the\" merchant".to_string()),
+            message: "Invoice 7\nthanks".to_string(),
+            timestamp: "2024-01-02 03:04:05".to_string(),
+            mined_height: Some(1234),
+            kernel_excess: Some("aa".repeat(32)),
+            input_commitments: vec!["01".repeat(32), "02".repeat(32)],
+            output_commitments: vec!["03".repeat(32)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_round_trips_through_both_formats() {
+        let records = vec![record(), TransactionHistoryRecord {
+            tx_id: 43,
+            ..Default::default()
+        }];
+        for format in [TransactionHistoryFormat::Csv, TransactionHistoryFormat::Json] {
+            let encoded = encode_transaction_history(&records, format).unwrap();
+            assert_eq!(decode_transaction_history(&encoded, format).unwrap(), records);
+        }
+    }
+
+    #[test]
+    fn it_reads_csv_columns_by_name() {
+        let csv = "message,tx_id\r\n\"hello, world\",7\r\n";
+        let records = decode_transaction_history(csv, TransactionHistoryFormat::Csv).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tx_id, 7);
+        assert_eq!(records[0].message, "hello, world");
+
+        assert!(decode_transaction_history("message\r\nhi\r\n", TransactionHistoryFormat::Csv).is_err());
+        assert!(decode_transaction_history("tx_id\r\n\"7\r\n", TransactionHistoryFormat::Csv).is_err());
+    }
+}
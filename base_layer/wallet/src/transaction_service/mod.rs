@@ -66,6 +66,8 @@ use crate::{
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod history;
+pub mod payment_request;
 pub mod protocols;
 pub mod service;
 pub mod storage;
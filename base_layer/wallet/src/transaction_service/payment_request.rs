@@ -0,0 +1,339 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Signed payment requests (invoices) that a merchant wallet hands to a payer.
+//!
+//! A [PaymentRequest] states the amount, the recipient address, a memo and an expiry time, and is signed with the
+//! secret key behind the recipient address so that none of these can be altered in transit. It encodes to a compact
+//! base58 string that can be shared as text or in a QR code. The payer's wallet pays it with a one-sided transaction
+//! that carries the memo as the payment reference and stores it as the transaction message.
+
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rand::rngs::OsRng;
+use tari_common_types::{
+    tari_address::TariAddress,
+    types::{PrivateKey, PublicKey, SignatureWithDomain},
+};
+use tari_core::transactions::{
+    tari_amount::MicroMinotari,
+    transaction_components::encrypted_data::MAX_PAYMENT_REFERENCE_SIZE,
+};
+use tari_crypto::hash_domain;
+use tari_utilities::ByteArray;
+use thiserror::Error;
+
+hash_domain!(
+    PaymentRequestSigningDomain,
+    "com.tari.base_layer.wallet.payment_request",
+    1
+);
+
+pub type PaymentRequestSignature = SignatureWithDomain<PaymentRequestSigningDomain>;
+
+/// The version of the payment request encoding
+pub const PAYMENT_REQUEST_VERSION: u8 = 1;
+/// The size of an encoded address
+const ADDRESS_SIZE: usize = 33;
+/// The size of an encoded signature, the public nonce followed by the signature scalar
+const SIGNATURE_SIZE: usize = 64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PaymentRequestError {
+    #[error("Invalid payment request encoding: {0}")]
+    InvalidEncoding(String),
+    #[error("Unsupported payment request version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Memo of {0} bytes exceeds the maximum of {1} bytes")]
+    MemoTooLong(usize, usize),
+    #[error("Payment request signature is invalid")]
+    InvalidSignature,
+    #[error("Payment request expired at {0}")]
+    Expired(u64),
+    #[error("Could not sign payment request: {0}")]
+    SigningError(String),
+}
+
+/// A request for payment of `amount` to `recipient`, signed by the recipient
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub amount: MicroMinotari,
+    pub recipient: TariAddress,
+    /// Identifies the payment to the merchant, e.g. an order number
+    pub memo: String,
+    /// The unix timestamp in seconds after which the request must not be paid
+    pub expiry: u64,
+    pub signature: PaymentRequestSignature,
+}
+
+impl PaymentRequest {
+    /// Create a payment request signed with `secret_key`, which must be the secret key of the recipient address. The
+    /// memo is paid as the payment reference, so it may be at most `max_memo_size` bytes, the consensus limit on
+    /// payment references.
+    pub fn new(
+        amount: MicroMinotari,
+        recipient: TariAddress,
+        memo: String,
+        expiry: u64,
+        max_memo_size: usize,
+        secret_key: &PrivateKey,
+    ) -> Result<Self, PaymentRequestError> {
+        if memo.len() > max_memo_size {
+            return Err(PaymentRequestError::MemoTooLong(memo.len(), max_memo_size));
+        }
+        let message = unsigned_bytes(amount, &recipient, &memo, expiry);
+        let signature = PaymentRequestSignature::sign(secret_key, message, &mut OsRng)
+            .map_err(|e| PaymentRequestError::SigningError(e.to_string()))?;
+
+        Ok(Self {
+            amount,
+            recipient,
+            memo,
+            expiry,
+            signature,
+        })
+    }
+
+    /// Checks that the request was signed by its recipient
+    pub fn verify_signature(&self) -> Result<(), PaymentRequestError> {
+        let message = unsigned_bytes(self.amount, &self.recipient, &self.memo, self.expiry);
+        if self.signature.verify(self.recipient.public_key(), message) {
+            Ok(())
+        } else {
+            Err(PaymentRequestError::InvalidSignature)
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        now > self.expiry
+    }
+
+    /// Checks the signature and expiry of the request before it is paid
+    pub fn validate(&self) -> Result<(), PaymentRequestError> {
+        self.verify_signature()?;
+        if self.is_expired() {
+            return Err(PaymentRequestError::Expired(self.expiry));
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = unsigned_bytes(self.amount, &self.recipient, &self.memo, self.expiry);
+        bytes.extend_from_slice(self.signature.get_public_nonce().as_bytes());
+        bytes.extend_from_slice(self.signature.get_signature().as_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PaymentRequestError> {
+        let mut reader = ByteReader(bytes);
+        let version = reader.read(1)?[0];
+        if version != PAYMENT_REQUEST_VERSION {
+            return Err(PaymentRequestError::UnsupportedVersion(version));
+        }
+        let amount = MicroMinotari::from(reader.read_u64()?);
+        let recipient = TariAddress::from_bytes(reader.read(ADDRESS_SIZE)?)
+            .map_err(|e| PaymentRequestError::InvalidEncoding(e.to_string()))?;
+        let expiry = reader.read_u64()?;
+        let memo_len = usize::from(u16::from_le_bytes(read_array(&mut reader)?));
+        if memo_len > MAX_PAYMENT_REFERENCE_SIZE {
+            return Err(PaymentRequestError::MemoTooLong(memo_len, MAX_PAYMENT_REFERENCE_SIZE));
+        }
+        let memo = String::from_utf8(reader.read(memo_len)?.to_vec())
+            .map_err(|e| PaymentRequestError::InvalidEncoding(e.to_string()))?;
+        let signature_bytes = reader.read(SIGNATURE_SIZE)?;
+        if !reader.0.is_empty() {
+            return Err(PaymentRequestError::InvalidEncoding("trailing bytes".to_string()));
+        }
+        let public_nonce = PublicKey::from_canonical_bytes(&signature_bytes[..32])
+            .map_err(|e| PaymentRequestError::InvalidEncoding(e.to_string()))?;
+        let signature = PrivateKey::from_canonical_bytes(&signature_bytes[32..])
+            .map_err(|e| PaymentRequestError::InvalidEncoding(e.to_string()))?;
+
+        Ok(Self {
+            amount,
+            recipient,
+            memo,
+            expiry,
+            signature: PaymentRequestSignature::new(public_nonce, signature),
+        })
+    }
+
+    /// Encodes the request as a base58 string
+    pub fn to_base58(&self) -> String {
+        bs58::encode(self.to_bytes()).into_string()
+    }
+
+    pub fn from_base58(encoded: &str) -> Result<Self, PaymentRequestError> {
+        let bytes = bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|e| PaymentRequestError::InvalidEncoding(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_base58())
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_base58(s)
+    }
+}
+
+/// The encoding of every field except the signature, which is also the message that is signed
+fn unsigned_bytes(amount: MicroMinotari, recipient: &TariAddress, memo: &str, expiry: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + 8 + ADDRESS_SIZE + 8 + 2 + memo.len() + SIGNATURE_SIZE);
+    bytes.push(PAYMENT_REQUEST_VERSION);
+    bytes.extend_from_slice(&amount.as_u64().to_le_bytes());
+    bytes.extend_from_slice(&recipient.to_bytes());
+    bytes.extend_from_slice(&expiry.to_le_bytes());
+    // The memo length is checked against the consensus limit on construction
+    let memo_len = u16::try_from(memo.len()).unwrap_or(u16::MAX);
+    bytes.extend_from_slice(&memo_len.to_le_bytes());
+    bytes.extend_from_slice(memo.as_bytes());
+    bytes
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn read(&mut self, len: usize) -> Result<&'a [u8], PaymentRequestError> {
+        if self.0.len() < len {
+            return Err(PaymentRequestError::InvalidEncoding(
+                "unexpected end of data".to_string(),
+            ));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn read_u64(&mut self) -> Result<u64, PaymentRequestError> {
+        Ok(u64::from_le_bytes(read_array(self)?))
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut ByteReader<'_>) -> Result<[u8; N], PaymentRequestError> {
+    reader
+        .read(N)?
+        .try_into()
+        .map_err(|_| PaymentRequestError::InvalidEncoding("unexpected end of data".to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    fn create_request(expiry: u64) -> (PaymentRequest, PrivateKey) {
+        let (secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let recipient = TariAddress::new(public_key, Network::LocalNet);
+        let request = PaymentRequest::new(
+            MicroMinotari::from(1_234_567),
+            recipient,
+            "Order #42".to_string(),
+            expiry,
+            64,
+            &secret_key,
+        )
+        .unwrap();
+        (request, secret_key)
+    }
+
+    #[test]
+    fn it_round_trips_through_base58() {
+        let (request, _) = create_request(u64::MAX);
+        let encoded = request.to_base58();
+        let decoded = PaymentRequest::from_base58(&encoded).unwrap();
+        assert_eq!(decoded, request);
+        assert!(decoded.validate().is_ok());
+        assert_eq!(encoded.parse::<PaymentRequest>().unwrap(), request);
+    }
+
+    #[test]
+    fn it_rejects_tampered_and_expired_requests() {
+        let (request, _) = create_request(u64::MAX);
+        let mut tampered = request.clone();
+        tampered.amount = MicroMinotari::from(1);
+        assert_eq!(tampered.validate(), Err(PaymentRequestError::InvalidSignature));
+
+        let (other_secret, other_public) = PublicKey::random_keypair(&mut OsRng);
+        let redirected = PaymentRequest::new(
+            request.amount,
+            TariAddress::new(other_public, Network::LocalNet),
+            request.memo.clone(),
+            request.expiry,
+            64,
+            &other_secret,
+        )
+        .unwrap();
+        let mut forged = redirected;
+        forged.signature = request.signature.clone();
+        assert_eq!(forged.validate(), Err(PaymentRequestError::InvalidSignature));
+
+        let (expired, _) = create_request(1);
+        assert_eq!(expired.validate(), Err(PaymentRequestError::Expired(1)));
+    }
+
+    #[test]
+    fn it_rejects_malformed_encodings() {
+        let (request, _) = create_request(u64::MAX);
+        let bytes = request.to_bytes();
+        assert!(matches!(
+            PaymentRequest::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(PaymentRequestError::InvalidEncoding(_))
+        ));
+        let mut versioned = bytes;
+        versioned[0] = 2;
+        assert_eq!(
+            PaymentRequest::from_bytes(&versioned),
+            Err(PaymentRequestError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            PaymentRequest::new(
+                MicroMinotari::from(1),
+                request.recipient,
+                "x".repeat(65),
+                u64::MAX,
+                64,
+                &PrivateKey::default(),
+            ),
+            Err(PaymentRequestError::MemoTooLong(65, 64))
+        );
+    }
+}
@@ -45,6 +45,7 @@ use tokio::{sync::watch, time::sleep};
 use crate::{
     connectivity_service::WalletConnectivityInterface,
     transaction_service::{
+        config::ExpiredTransactionAction,
         error::{TransactionServiceError, TransactionServiceProtocolError},
         handle::TransactionEvent,
         protocols::check_transaction_size,
@@ -347,6 +348,8 @@ where
                 "Broadcast transaction detected as mined, will be managed by transaction validation protocol"
             );
             Ok(true)
+        } else if response.location == TxLocation::Expired {
+            self.handle_expired_transaction().await
         } else if response.location != TxLocation::InMempool {
             if self.last_rejection.is_none() ||
                 self.last_rejection.unwrap().elapsed() >
@@ -425,6 +428,50 @@ where
         }
     }
 
+    /// Rebroadcast or cancel a transaction that the base node reports as expired from its mempool, depending on the
+    /// configured [ExpiredTransactionAction].
+    async fn handle_expired_transaction(&mut self) -> Result<bool, TransactionServiceProtocolError<TxId>> {
+        match self.resources.config.expired_transaction_action {
+            ExpiredTransactionAction::Rebroadcast => {
+                info!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) expired from the mempool, attempting to rebroadcast transaction", self.tx_id
+                );
+                // An expired transaction was valid when it was accepted, so it is resubmitted without waiting for the
+                // rejection window
+                self.mode = TxBroadcastMode::TransactionSubmission;
+                Ok(false)
+            },
+            ExpiredTransactionAction::Cancel => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) expired from the mempool, cancelling transaction", self.tx_id
+                );
+                self.cancel_transaction(TxCancellationReason::Expired).await;
+
+                let _size = self
+                    .resources
+                    .event_publisher
+                    .send(Arc::new(TransactionEvent::TransactionCancelled(
+                        self.tx_id,
+                        TxCancellationReason::Expired,
+                    )))
+                    .map_err(|e| {
+                        trace!(
+                            target: LOG_TARGET,
+                            "Error sending event because there are no subscribers: {:?}",
+                            e
+                        );
+                        e
+                    });
+                Err(TransactionServiceProtocolError::new(
+                    self.tx_id,
+                    TransactionServiceError::MempoolRejectionExpired,
+                ))
+            },
+        }
+    }
+
     async fn cancel_transaction(&mut self, reason: TxCancellationReason) {
         if let Err(e) = self
             .resources
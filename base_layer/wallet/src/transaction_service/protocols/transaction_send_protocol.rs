@@ -32,6 +32,7 @@ use tari_common_types::{
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
+    envelope::SafMessageClass,
     outbound::{OutboundEncryption, SendMessageResponse},
 };
 use tari_core::{
@@ -852,7 +853,8 @@ where
                 self.dest_address.public_key().clone(),
                 OutboundEncryption::encrypt_for(self.dest_address.public_key().clone()),
                 vec![],
-                OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message),
+                OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message)
+                    .with_saf_class(SafMessageClass::Transaction),
             )
             .await
         {
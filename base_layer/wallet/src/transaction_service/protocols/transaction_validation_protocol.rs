@@ -407,6 +407,16 @@ where
             .set_transaction_as_unmined(tx_id)
             .for_protocol(self.operation_id)?;
 
+        if status.is_confirmed() ||
+            matches!(
+                status,
+                TransactionStatus::MinedUnconfirmed |
+                    TransactionStatus::OneSidedUnconfirmed |
+                    TransactionStatus::CoinbaseUnconfirmed
+            )
+        {
+            self.publish_event(TransactionEvent::TransactionReorgedOut(tx_id));
+        }
         self.publish_event(TransactionEvent::TransactionBroadcast(tx_id));
         Ok(())
     }
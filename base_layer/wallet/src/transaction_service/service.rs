@@ -28,16 +28,14 @@ use std::{
 };
 
 use chrono::{NaiveDateTime, Utc};
-use digest::Digest;
 use futures::{pin_mut, stream::FuturesUnordered, Stream, StreamExt};
 use log::*;
 use rand::rngs::OsRng;
-use sha2::Sha256;
 use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{PrivateKey, PublicKey, Signature},
+    types::{Commitment, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::outbound::OutboundMessageRequester;
@@ -54,6 +52,7 @@ use tari_core::{
     proto::base_node as base_node_proto,
     transactions::{
         key_manager::TransactionKeyManagerInterface,
+        offline_signing::{PaymentDetails, SignedTransaction, TransactionSigningRequest},
         tari_amount::MicroMinotari,
         transaction_components::{
             CodeTemplateRegistration,
@@ -71,27 +70,28 @@ use tari_core::{
         },
         CryptoFactories,
         ReceiverTransactionProtocol,
+        SenderTransactionProtocol,
     },
 };
-use tari_crypto::{
-    keys::{PublicKey as PKtrait, SecretKey},
-    tari_utilities::ByteArray,
-};
+use tari_crypto::keys::{PublicKey as PKtrait, SecretKey};
 use tari_key_manager::key_manager_service::KeyId;
 use tari_p2p::domain_message::DomainMessage;
-use tari_script::{inputs, one_sided_payment_script, script, stealth_payment_script, TariScript};
+use tari_script::{inputs, one_sided_payment_script, script, stealth_payment_script, Opcode, TariScript};
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::Hex;
 use tokio::{
     sync::{mpsc, mpsc::Sender, oneshot, Mutex},
     task::JoinHandle,
 };
 
 use crate::{
+    account::models::DEFAULT_ACCOUNT_INDEX,
+    atomic_swap::htlc::{hash_pre_image, sha_htlc_script},
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{
-        handle::{OutputManagerEvent, OutputManagerHandle},
+        handle::{OneSidedBatchRecipient, OutputManagerEvent, OutputManagerHandle},
         storage::models::SpendingPriority,
         UtxoSelectionCriteria,
     },
@@ -100,12 +100,16 @@ use crate::{
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError},
         handle::{
+            BatchRecipient,
+            BatchRecipientStatus,
             FeePerGramStatsResponse,
             TransactionEvent,
             TransactionEventSender,
             TransactionServiceRequest,
             TransactionServiceResponse,
         },
+        history::TransactionHistoryRecord,
+        payment_request::PaymentRequest,
         protocols::{
             check_transaction_size,
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
@@ -135,6 +139,9 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::transaction_service::service";
+/// The percentage by which a replacement transaction must raise the fee of the transaction it replaces, matching the
+/// default replace-by-fee policy of the base node mempool
+const MIN_REPLACEMENT_FEE_BUMP_PERCENTAGE: u64 = 10;
 
 /// TransactionService allows for the management of multiple inbound and outbound transaction protocols
 /// which are uniquely identified by a tx_id. The TransactionService generates and accepts the various protocol
@@ -622,6 +629,8 @@ where
                 output_features,
                 fee_per_gram,
                 message,
+                payment_reference,
+                memo,
             } => self
                 .send_one_sided_transaction(
                     destination,
@@ -630,9 +639,69 @@ where
                     *output_features,
                     fee_per_gram,
                     message,
+                    payment_reference,
+                    memo,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::PrepareOneSidedTransactionForSigning {
+                destination,
+                amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                message,
+                payment_reference,
+            } => self
+                .prepare_one_sided_transaction_for_signing(
+                    destination,
+                    amount,
+                    selection_criteria,
+                    *output_features,
+                    fee_per_gram,
+                    message,
+                    payment_reference,
+                )
+                .await
+                .map(|request| TransactionServiceResponse::SigningRequestPrepared(Box::new(request))),
+            TransactionServiceRequest::RestoreTransactionMessages(records) => self
+                .restore_transaction_messages(&records)
+                .map(TransactionServiceResponse::TransactionMessagesRestored),
+            TransactionServiceRequest::CreatePaymentRequest { amount, memo, expiry } => self
+                .create_payment_request(amount, memo, expiry)
+                .map(|request| TransactionServiceResponse::PaymentRequestCreated(Box::new(request))),
+            TransactionServiceRequest::PayPaymentRequest {
+                payment_request,
+                selection_criteria,
+                fee_per_gram,
+            } => self
+                .pay_payment_request(
+                    *payment_request,
+                    selection_criteria,
+                    fee_per_gram,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendOneSidedBatch {
+                recipients,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            } => self
+                .send_one_sided_batch(
+                    recipients,
+                    selection_criteria,
+                    fee_per_gram,
+                    message,
                     transaction_broadcast_join_handles,
                 )
                 .await
+                .map(TransactionServiceResponse::BatchSent),
+            TransactionServiceRequest::SubmitSignedTransaction(signed_transaction) => self
+                .submit_signed_transaction(*signed_transaction, transaction_broadcast_join_handles)
+                .await
                 .map(TransactionServiceResponse::TransactionSent),
             TransactionServiceRequest::SendOneSidedToStealthAddressTransaction {
                 destination,
@@ -641,6 +710,8 @@ where
                 output_features,
                 fee_per_gram,
                 message,
+                payment_reference,
+                memo,
             } => self
                 .send_one_sided_to_stealth_address_transaction(
                     destination,
@@ -649,6 +720,8 @@ where
                     *output_features,
                     fee_per_gram,
                     message,
+                    payment_reference,
+                    memo,
                     transaction_broadcast_join_handles,
                 )
                 .await
@@ -750,6 +823,14 @@ where
                 .cancel_pending_transaction(tx_id)
                 .await
                 .map(|_| TransactionServiceResponse::TransactionCancelled),
+            TransactionServiceRequest::BumpFee { tx_id, fee_per_gram } => self
+                .bump_fee(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionReplaced),
+            TransactionServiceRequest::CancelPending { tx_id, fee_per_gram } => self
+                .cancel_by_replacement(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionReplaced),
             TransactionServiceRequest::GetPendingInboundTransactions => Ok(
                 TransactionServiceResponse::PendingInboundTransactions(self.db.get_pending_inbound_transactions()?),
             ),
@@ -779,6 +860,15 @@ where
             TransactionServiceRequest::GetAnyTransaction(tx_id) => Ok(TransactionServiceResponse::AnyTransaction(
                 Box::new(self.db.get_any_transaction(tx_id)?),
             )),
+            TransactionServiceRequest::GetTransactionsByPaymentReference(payment_reference) => {
+                Ok(TransactionServiceResponse::CompletedTransactions(
+                    self.db
+                        .get_transactions_by_payment_reference(&payment_reference)?
+                        .into_iter()
+                        .map(|tx| (tx.tx_id, tx))
+                        .collect(),
+                ))
+            },
             TransactionServiceRequest::ImportTransaction(tx) => {
                 let tx_id = match tx {
                     PendingInbound(inbound_tx) => {
@@ -811,6 +901,8 @@ where
                 current_height,
                 mined_timestamp,
                 scanned_output,
+                payment_reference,
+                memo,
             } => self
                 .add_utxo_import_transaction_with_status(
                     amount,
@@ -821,6 +913,8 @@ where
                     current_height,
                     mined_timestamp,
                     scanned_output,
+                    payment_reference,
+                    memo,
                 )
                 .await
                 .map(TransactionServiceResponse::UtxoImported),
@@ -865,6 +959,22 @@ where
                 self.handle_get_fee_per_gram_stats_per_block_request(count, reply_channel);
                 return Ok(());
             },
+            TransactionServiceRequest::EstimateFeePerGram { target_blocks } => {
+                let reply_channel = reply_channel.take().expect("reply_channel is Some");
+                self.handle_estimate_fee_per_gram_request(target_blocks, reply_channel);
+                return Ok(());
+            },
+            TransactionServiceRequest::PreviewTransaction {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            } => self
+                .resources
+                .output_manager_service
+                .preview_coin_selection(amount, selection_criteria, fee_per_gram)
+                .await
+                .map(|report| TransactionServiceResponse::TransactionPreview(Box::new(report)))
+                .map_err(Into::into),
         };
 
         // If the individual handlers did not already send the API response then do it here.
@@ -919,6 +1029,38 @@ where
         });
     }
 
+    fn handle_estimate_fee_per_gram_request(
+        &self,
+        target_blocks: usize,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) {
+        let mut connectivity = self.resources.connectivity.clone();
+
+        let query_base_node_fut = async move {
+            let mut client = connectivity
+                .obtain_base_node_wallet_rpc_client()
+                .await
+                .ok_or(TransactionServiceError::Shutdown)?;
+
+            let resp = client
+                .estimate_fee_per_gram(base_node_proto::EstimateFeePerGramRequest {
+                    target_blocks: target_blocks as u64,
+                })
+                .await?;
+            Ok(TransactionServiceResponse::FeePerGramEstimate(resp.fee_per_gram.into()))
+        };
+
+        tokio::spawn(async move {
+            let resp = query_base_node_fut.await;
+            if reply_channel.send(resp).is_err() {
+                warn!(
+                    target: LOG_TARGET,
+                    "handle_estimate_fee_per_gram_request: service reply cancelled"
+                );
+            }
+        });
+    }
+
     async fn handle_base_node_service_event(
         &mut self,
         event: Arc<BaseNodeEvent>,
@@ -998,6 +1140,15 @@ where
                 });
             return Err(TransactionServiceError::InvalidNetwork);
         }
+        if destination.is_subaddress() {
+            let _result = reply_channel
+                .send(Err(TransactionServiceError::SubaddressNotInteractive))
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+            return Err(TransactionServiceError::SubaddressNotInteractive);
+        }
         let dest_pubkey = destination.public_key();
         // If we're paying ourselves, let's complete and submit the transaction immediately
         if self.resources.wallet_identity.address.public_key() == dest_pubkey {
@@ -1093,19 +1244,18 @@ where
         let tx_id = TxId::new_random();
         // this can be anything, so lets generate a random private key
         let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
-        let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+        let hash = hash_pre_image(&pre_image);
 
         // lets make the unlock height a day from now, 2 min blocks which gives us 30 blocks per hour * 24 hours
         let tip_height = self.last_seen_tip_height.unwrap_or(0);
         let height = tip_height + (24 * 30);
 
         // lets create the HTLC script
-        let script = script!(
-            HashSha256 PushHash(Box::new(hash)) Equal IfThen
-                PushPubKey(Box::new(dest_pubkey.clone()))
-            Else
-                CheckHeightVerify(height) PushPubKey(Box::new(self.resources.wallet_identity.node_identity.public_key().clone()))
-            EndIf
+        let script = sha_htlc_script(
+            &hash,
+            dest_pubkey,
+            self.resources.wallet_identity.node_identity.public_key(),
+            height,
         );
 
         // Empty covenant
@@ -1302,11 +1452,109 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
         script: TariScript,
     ) -> Result<TxId, TransactionServiceError> {
+        let (tx_id, mut stp) = self
+            .prepare_one_sided_or_stealth(
+                &dest_address,
+                amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                message.clone(),
+                payment_reference.clone(),
+                memo.clone(),
+                script,
+            )
+            .await?;
+
+        // Finalize
+
+        stp.finalize(&self.resources.transaction_key_manager_service)
+            .await
+            .map_err(|e| {
+                error!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) could not be finalized. Failure error: {:?}", tx_id, e,
+                );
+                TransactionServiceProtocolError::new(tx_id, e.into())
+            })?;
+        info!(target: LOG_TARGET, "Finalized one-side transaction TxId: {}", tx_id);
+
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _result = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        // Broadcast one-sided transaction
+
+        let tx = stp
+            .get_transaction()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let fee = stp
+            .get_fee_amount()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let mut completed_transaction = CompletedTransaction::new(
+            tx_id,
+            self.resources.wallet_identity.address.clone(),
+            dest_address,
+            amount,
+            fee,
+            tx.clone(),
+            TransactionStatus::Completed,
+            message.clone(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+        )?;
+        if !payment_reference.is_empty() {
+            completed_transaction.payment_reference = Some(payment_reference);
+        }
+        if !memo.is_empty() {
+            completed_transaction.memo = Some(memo);
+        }
+        self.submit_transaction(transaction_broadcast_join_handles, completed_transaction)
+            .await?;
+
+        Ok(tx_id)
+    }
+
+    /// Selects the inputs and builds the sender and receiver parts of a one-sided transaction, leaving the sender
+    /// protocol ready to be finalized
+    async fn prepare_one_sided_or_stealth(
+        &mut self,
+        dest_address: &TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
+        script: TariScript,
+    ) -> Result<(TxId, SenderTransactionProtocol), TransactionServiceError> {
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        let consensus_constants = self.consensus_manager.consensus_constants(tip_height);
+        if payment_reference.len() > consensus_constants.max_payment_reference_size() {
+            return Err(TransactionServiceError::OneSidedTransactionError(format!(
+                "Payment reference must be at most {} bytes",
+                consensus_constants.max_payment_reference_size()
+            )));
+        }
+        if memo.len() > consensus_constants.max_memo_size() {
+            return Err(TransactionServiceError::OneSidedTransactionError(format!(
+                "Memo must be at most {} bytes",
+                consensus_constants.max_memo_size()
+            )));
+        }
+
         let tx_id = TxId::new_random();
 
         // Prepare sender part of the transaction
@@ -1320,7 +1568,7 @@ where
                 output_features,
                 fee_per_gram,
                 TransactionMetadata::default(),
-                message.clone(),
+                message,
                 script.clone(),
                 Covenant::default(),
                 MicroMinotari::zero(),
@@ -1397,6 +1645,8 @@ where
                     .clone(),
             )
             .with_script(script)
+            .with_payment_reference(payment_reference.clone())
+            .with_memo(memo)
             .encrypt_data_for_recovery(&self.resources.transaction_key_manager_service, Some(&encryption_key))
             .await?
             .with_input_data(inputs!(PublicKey::from_secret_key(
@@ -1413,8 +1663,6 @@ where
             .try_build(&self.resources.transaction_key_manager_service)
             .await?;
 
-        let tip_height = self.last_seen_tip_height.unwrap_or(0);
-        let consensus_constants = self.consensus_manager.consensus_constants(tip_height);
         let rtp = ReceiverTransactionProtocol::new(
             sender_message,
             output,
@@ -1429,53 +1677,213 @@ where
         stp.add_presigned_recipient_info(recipient_reply)
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
 
-        // Finalize
+        Ok((tx_id, stp))
+    }
 
-        stp.finalize(&self.resources.transaction_key_manager_service)
+    /// Restore the messages of exported transactions that can be matched to completed transactions of this wallet by
+    /// kernel excess or, for received transactions, by output commitment. Outputs recovered from the chain are
+    /// recorded without a kernel, so the commitment is the only way to match them.
+    fn restore_transaction_messages(
+        &self,
+        records: &[TransactionHistoryRecord],
+    ) -> Result<usize, TransactionServiceError> {
+        let mut messages_by_excess = HashMap::new();
+        let mut messages_by_commitment = HashMap::new();
+        for record in records.iter().filter(|record| !record.message.is_empty()) {
+            if let Some(excess) = &record.kernel_excess {
+                messages_by_excess.insert(excess.as_str(), record.message.as_str());
+            }
+            if record.direction == TransactionDirection::Inbound.to_string() {
+                for commitment in &record.output_commitments {
+                    messages_by_commitment.insert(commitment.as_str(), record.message.as_str());
+                }
+            }
+        }
+
+        let mut num_restored = 0;
+        for tx in self.db.get_completed_transactions()?.values() {
+            let message = tx
+                .transaction
+                .body
+                .kernels()
+                .iter()
+                .find_map(|kernel| messages_by_excess.get(kernel.excess.to_hex().as_str()))
+                .or_else(|| {
+                    tx.transaction
+                        .body
+                        .outputs()
+                        .iter()
+                        .find_map(|output| messages_by_commitment.get(output.commitment.to_hex().as_str()))
+                });
+            if let Some(message) = message.filter(|message| **message != tx.message) {
+                self.db
+                    .update_completed_transaction_message(tx.tx_id, (*message).to_string())?;
+                num_restored += 1;
+            }
+        }
+        info!(
+            target: LOG_TARGET,
+            "Restored the messages of {} transactions from {} exported records",
+            num_restored,
+            records.len()
+        );
+
+        Ok(num_restored)
+    }
+
+    /// Creates a payment request to this wallet, signed with the wallet's identity key
+    fn create_payment_request(
+        &self,
+        amount: MicroMinotari,
+        memo: String,
+        expiry: u64,
+    ) -> Result<PaymentRequest, TransactionServiceError> {
+        let wallet_identity = &self.resources.wallet_identity;
+        // The memo is paid as the payment reference, so it must fit the consensus limit for payment references
+        let max_memo_size = self
+            .consensus_manager
+            .consensus_constants(self.last_seen_tip_height.unwrap_or(0))
+            .max_payment_reference_size();
+        Ok(PaymentRequest::new(
+            amount,
+            wallet_identity.address.clone(),
+            memo,
+            expiry,
+            max_memo_size,
+            wallet_identity.node_identity.secret_key(),
+        )?)
+    }
+
+    /// Pays the recipients with one-sided outputs in a single transaction. Recipients that cannot be paid, e.g. because
+    /// their address is for another network, are reported as failed and left out of the transaction; all other
+    /// recipients share the outcome of the transaction.
+    async fn send_one_sided_batch(
+        &mut self,
+        recipients: Vec<BatchRecipient>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<Vec<BatchRecipientStatus>, TransactionServiceError> {
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        let max_payment_reference_size = self
+            .consensus_manager
+            .consensus_constants(tip_height)
+            .max_payment_reference_size();
+
+        let mut statuses = Vec::with_capacity(recipients.len());
+        let mut batch = Vec::new();
+        let mut destinations = Vec::new();
+        for recipient in recipients {
+            let error = if recipient.destination.network() != self.resources.wallet_identity.network {
+                Some(TransactionServiceError::InvalidNetwork.to_string())
+            } else if recipient.amount == MicroMinotari::zero() {
+                Some("Amount must be greater than zero".to_string())
+            } else if recipient.payment_reference.len() > max_payment_reference_size {
+                Some(format!(
+                    "Payment reference must be at most {} bytes",
+                    max_payment_reference_size
+                ))
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                statuses.push(Some(BatchRecipientStatus::Failed(error)));
+                continue;
+            }
+            statuses.push(None);
+            batch.push(OneSidedBatchRecipient {
+                public_key: recipient.destination.public_key().clone(),
+                amount: recipient.amount,
+                payment_reference: recipient.payment_reference,
+            });
+            destinations.push(recipient.destination);
+        }
+        if batch.is_empty() {
+            return Ok(statuses.into_iter().flatten().collect());
+        }
+
+        let tx_id = TxId::new_random();
+        let amount: MicroMinotari = batch.iter().map(|recipient| recipient.amount).sum();
+        let batch_status = match self
+            .resources
+            .output_manager_service
+            .create_one_sided_batch_transaction(tx_id, batch, selection_criteria, fee_per_gram)
             .await
-            .map_err(|e| {
-                error!(
+        {
+            Ok((fee, transaction)) => {
+                info!(
                     target: LOG_TARGET,
-                    "Transaction (TxId: {}) could not be finalized. Failure error: {:?}", tx_id, e,
+                    "Created one-sided batch transaction (TxId: {}) paying {} to {} recipients",
+                    tx_id,
+                    amount,
+                    destinations.len()
                 );
-                TransactionServiceProtocolError::new(tx_id, e.into())
-            })?;
-        info!(target: LOG_TARGET, "Finalized one-side transaction TxId: {}", tx_id);
-
-        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
-        // there are no subscribers.
-        let _result = self
-            .event_publisher
-            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+                let _result = self
+                    .event_publisher
+                    .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+                // A completed transaction has a single destination, so the batch is recorded against the first
+                // recipient with the total amount paid
+                let completed_transaction = CompletedTransaction::new(
+                    tx_id,
+                    self.resources.wallet_identity.address.clone(),
+                    destinations.swap_remove(0),
+                    amount,
+                    fee,
+                    transaction,
+                    TransactionStatus::Completed,
+                    message,
+                    Utc::now().naive_utc(),
+                    TransactionDirection::Outbound,
+                    None,
+                    None,
+                )?;
+                self.submit_transaction(transaction_broadcast_join_handles, completed_transaction)
+                    .await?;
+                BatchRecipientStatus::Sent(tx_id)
+            },
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not create one-sided batch transaction (TxId: {}): {}", tx_id, e
+                );
+                BatchRecipientStatus::Failed(e.to_string())
+            },
+        };
 
-        // Broadcast one-sided transaction
+        Ok(statuses
+            .into_iter()
+            .map(|status| status.unwrap_or_else(|| batch_status.clone()))
+            .collect())
+    }
 
-        let tx = stp
-            .get_transaction()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        let fee = stp
-            .get_fee_amount()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        self.submit_transaction(
+    /// Pays a payment request with a one-sided transaction. The memo is sent as the payment reference, so that the
+    /// recipient can match the payment to the request, and is stored as the transaction message.
+    pub async fn pay_payment_request(
+        &mut self,
+        payment_request: PaymentRequest,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        payment_request.validate()?;
+        let payment_reference = payment_request.memo.as_bytes().to_vec();
+        self.send_one_sided_transaction(
+            payment_request.recipient,
+            payment_request.amount,
+            selection_criteria,
+            OutputFeatures::default(),
+            fee_per_gram,
+            payment_request.memo,
+            payment_reference,
+            Vec::new(),
             transaction_broadcast_join_handles,
-            CompletedTransaction::new(
-                tx_id,
-                self.resources.wallet_identity.address.clone(),
-                dest_address,
-                amount,
-                fee,
-                tx.clone(),
-                TransactionStatus::Completed,
-                message.clone(),
-                Utc::now().naive_utc(),
-                TransactionDirection::Outbound,
-                None,
-                None,
-            )?,
         )
-        .await?;
-
-        Ok(tx_id)
+        .await
     }
 
     /// Sends a one side payment transaction to a recipient
@@ -1491,6 +1899,8 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
@@ -1512,12 +1922,112 @@ where
             output_features,
             fee_per_gram,
             message,
+            payment_reference,
+            memo,
             transaction_broadcast_join_handles,
             one_sided_payment_script(&dest_pubkey),
         )
         .await
     }
 
+    /// Builds a one-sided payment to be signed by an external signer. The selected inputs stay encumbered until the
+    /// signed transaction is submitted or the transaction is cancelled.
+    pub async fn prepare_one_sided_transaction_for_signing(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        payment_reference: Vec<u8>,
+    ) -> Result<TransactionSigningRequest, TransactionServiceError> {
+        if destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if self.resources.wallet_identity.node_identity.public_key() == destination.public_key() {
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+        let script = one_sided_payment_script(destination.public_key());
+        let (tx_id, stp) = self
+            .prepare_one_sided_or_stealth(
+                &destination,
+                amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                message.clone(),
+                payment_reference.clone(),
+                Vec::new(),
+                script,
+            )
+            .await?;
+        let fee = stp
+            .get_fee_amount()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        info!(
+            target: LOG_TARGET,
+            "Prepared one-sided transaction TxId: {} for external signing", tx_id
+        );
+
+        Ok(TransactionSigningRequest::new(
+            PaymentDetails {
+                tx_id,
+                destination,
+                amount,
+                fee,
+                message,
+                payment_reference,
+            },
+            stp,
+        ))
+    }
+
+    /// Records and broadcasts a one-sided payment that was signed by an external signer
+    pub async fn submit_signed_transaction(
+        &mut self,
+        signed_transaction: SignedTransaction,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let SignedTransaction {
+            payment, transaction, ..
+        } = signed_transaction;
+        let tx_id = payment.tx_id;
+        if transaction.body.get_total_fee()? != payment.fee {
+            return Err(TransactionServiceError::InvalidCompletedTransaction);
+        }
+
+        let mut completed_transaction = CompletedTransaction::new(
+            tx_id,
+            self.resources.wallet_identity.address.clone(),
+            payment.destination,
+            payment.amount,
+            payment.fee,
+            transaction,
+            TransactionStatus::Completed,
+            payment.message,
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+        )?;
+        if !payment.payment_reference.is_empty() {
+            completed_transaction.payment_reference = Some(payment.payment_reference);
+        }
+        let _result = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+        self.submit_transaction(transaction_broadcast_join_handles, completed_transaction)
+            .await?;
+        info!(target: LOG_TARGET, "Submitted externally signed transaction TxId: {}", tx_id);
+
+        Ok(tx_id)
+    }
+
     /// Creates a transaction to burn some Minotari. The optional _claim public key_ parameter is used in the challenge
     /// of the
     // corresponding optional _ownership proof_ return value. Burn commitments and ownership proofs will exclusively be
@@ -1822,6 +2332,8 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
+        payment_reference: Vec<u8>,
+        memo: Vec<u8>,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
@@ -1850,6 +2362,8 @@ where
             output_features,
             fee_per_gram,
             message,
+            payment_reference,
+            memo,
             transaction_broadcast_join_handles,
             stealth_payment_script(&nonce_public_key, &script_spending_key),
         )
@@ -2099,6 +2613,271 @@ where
         Ok(())
     }
 
+    /// Fetch an unconfirmed outbound transaction that may be replaced by one paying `fee_per_gram`, along with the
+    /// commitments of the inputs it spends
+    /// The account that owned the inputs of a cancelled transaction, so that its replacement returns the funds there
+    async fn replaced_inputs_account(&mut self, commitments: &[Commitment]) -> Result<u64, TransactionServiceError> {
+        let inputs = self
+            .resources
+            .output_manager_service
+            .get_spendable_outputs(UtxoSelectionCriteria::specific(commitments.to_vec()))
+            .await?;
+        Ok(inputs.first().map_or(DEFAULT_ACCOUNT_INDEX, |input| input.account))
+    }
+
+    fn fetch_replaceable_transaction(
+        &self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(CompletedTransaction, Vec<Commitment>), TransactionServiceError> {
+        let completed_tx = self.db.get_completed_transaction(tx_id)?;
+        if completed_tx.direction != TransactionDirection::Outbound {
+            return Err(TransactionServiceError::TransactionNotReplaceable(
+                "only outbound transactions can be replaced".to_string(),
+            ));
+        }
+        if !matches!(
+            completed_tx.status,
+            TransactionStatus::Completed | TransactionStatus::Broadcast
+        ) {
+            return Err(TransactionServiceError::TransactionNotReplaceable(format!(
+                "transaction status is {}",
+                completed_tx.status
+            )));
+        }
+
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        let weight = completed_tx.transaction.calculate_weight(
+            self.consensus_manager
+                .consensus_constants(tip_height)
+                .transaction_weight_params(),
+        )?;
+        let original_fee_per_gram = completed_tx.fee.as_u64().div_ceil(weight.max(1));
+        let min_fee_per_gram = original_fee_per_gram
+            .saturating_mul(100 + MIN_REPLACEMENT_FEE_BUMP_PERCENTAGE)
+            .div_ceil(100);
+        if fee_per_gram.as_u64() < min_fee_per_gram {
+            return Err(TransactionServiceError::TransactionNotReplaceable(format!(
+                "the replacement fee per gram must be at least {} (original fee per gram is {})",
+                min_fee_per_gram, original_fee_per_gram
+            )));
+        }
+
+        let commitments = completed_tx
+            .transaction
+            .body
+            .inputs()
+            .iter()
+            .map(|input| input.commitment().cloned())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((completed_tx, commitments))
+    }
+
+    /// Replace an unconfirmed outbound one-sided or stealth transaction with one that pays the same recipient from
+    /// the same inputs at a higher fee per gram. Base nodes with replace-by-fee enabled evict the original transaction
+    /// from their mempool once they receive the replacement.
+    pub async fn bump_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let (completed_tx, commitments) = self.fetch_replaceable_transaction(tx_id, fee_per_gram)?;
+
+        // Interactive transactions need the recipient to sign again, so only one-sided payments can be re-sent
+        let destination = completed_tx.destination_address.clone();
+        let one_sided_script = one_sided_payment_script(destination.public_key());
+        let recipient_output = completed_tx.transaction.body.outputs().iter().find(|output| {
+            output.script == one_sided_script ||
+                matches!(output.script.as_slice(), [
+                    Opcode::PushPubKey(_),
+                    Opcode::Drop,
+                    Opcode::PushPubKey(_)
+                ])
+        });
+        let (output_features, is_stealth) = match recipient_output {
+            Some(output) => (output.features.clone(), output.script != one_sided_script),
+            None => {
+                return Err(TransactionServiceError::TransactionNotReplaceable(
+                    "only one-sided transactions can have their fee bumped, use cancel instead".to_string(),
+                ))
+            },
+        };
+
+        self.resources.output_manager_service.cancel_transaction(tx_id).await?;
+        let mut selection_criteria = UtxoSelectionCriteria::specific(commitments.clone());
+        selection_criteria.account = self.replaced_inputs_account(&commitments).await?;
+        let payment_reference = completed_tx.payment_reference.clone().unwrap_or_default();
+        let memo = completed_tx.memo.clone().unwrap_or_default();
+        let replacement = if is_stealth {
+            self.send_one_sided_to_stealth_address_transaction(
+                destination,
+                completed_tx.amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                completed_tx.message.clone(),
+                payment_reference,
+                memo,
+                transaction_broadcast_join_handles,
+            )
+            .await
+        } else {
+            self.send_one_sided_transaction(
+                destination,
+                completed_tx.amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                completed_tx.message.clone(),
+                payment_reference,
+                memo,
+                transaction_broadcast_join_handles,
+            )
+            .await
+        };
+
+        self.complete_replacement(tx_id, commitments, replacement).await
+    }
+
+    /// Cancel an unconfirmed outbound transaction by replacing it with one that spends its inputs back to this wallet
+    /// at a higher fee per gram. This works for interactive and one-sided transactions alike, provided that base nodes
+    /// have replace-by-fee enabled.
+    pub async fn cancel_by_replacement(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let (completed_tx, commitments) = self.fetch_replaceable_transaction(tx_id, fee_per_gram)?;
+
+        self.resources.output_manager_service.cancel_transaction(tx_id).await?;
+        let account = self.replaced_inputs_account(&commitments).await?;
+        let replacement = match self
+            .resources
+            .output_manager_service
+            .create_coin_join(commitments.clone(), fee_per_gram, account)
+            .await
+        {
+            Ok((replacement_tx_id, transaction, amount)) => {
+                let result = self
+                    .submit_cancelling_transaction(
+                        tx_id,
+                        completed_tx.fee,
+                        replacement_tx_id,
+                        transaction,
+                        amount,
+                        transaction_broadcast_join_handles,
+                    )
+                    .await;
+                if result.is_err() {
+                    // Release the inputs so that the original transaction's outputs can be restored
+                    self.resources
+                        .output_manager_service
+                        .cancel_transaction(replacement_tx_id)
+                        .await?;
+                }
+                result.map(|_| replacement_tx_id)
+            },
+            Err(e) => Err(e.into()),
+        };
+
+        self.complete_replacement(tx_id, commitments, replacement).await
+    }
+
+    /// Submit a transaction spending the inputs of `tx_id` back to this wallet, provided that its fee is high enough
+    /// for base nodes to accept it as a replacement
+    async fn submit_cancelling_transaction(
+        &mut self,
+        tx_id: TxId,
+        original_fee: MicroMinotari,
+        replacement_tx_id: TxId,
+        transaction: Transaction,
+        amount: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<(), TransactionServiceError> {
+        // The replacement spends the same inputs into fewer outputs, so a higher fee per gram alone does not guarantee
+        // a higher absolute fee
+        let fee = transaction.body.get_total_fee()?;
+        let min_fee = MicroMinotari::from(
+            original_fee
+                .as_u64()
+                .saturating_mul(100 + MIN_REPLACEMENT_FEE_BUMP_PERCENTAGE)
+                .div_ceil(100),
+        );
+        if fee < min_fee {
+            return Err(TransactionServiceError::TransactionNotReplaceable(format!(
+                "the replacement fee of {} is lower than the required {}",
+                fee, min_fee
+            )));
+        }
+
+        self.submit_transaction_to_self(
+            transaction_broadcast_join_handles,
+            replacement_tx_id,
+            transaction,
+            fee,
+            amount,
+            format!("Cancels transaction {}", tx_id),
+        )
+        .await
+    }
+
+    /// Mark the original transaction as replaced once its replacement has been submitted, or restore its outputs if
+    /// the replacement could not be built
+    async fn complete_replacement(
+        &mut self,
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+        replacement: Result<TxId, TransactionServiceError>,
+    ) -> Result<TxId, TransactionServiceError> {
+        let replacement_tx_id = match replacement {
+            Ok(replacement_tx_id) => replacement_tx_id,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not replace transaction (TxId: {}), restoring its outputs: {:?}", tx_id, e
+                );
+                self.resources
+                    .output_manager_service
+                    .reinstate_cancelled_outbound_transaction_outputs(tx_id, commitments)
+                    .await?;
+                return Err(e);
+            },
+        };
+
+        self.db
+            .reject_completed_transaction(tx_id, TxCancellationReason::Replaced)?;
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCancelled(
+                tx_id,
+                TxCancellationReason::Replaced,
+            )))
+            .map_err(|e| {
+                trace!(
+                    target: LOG_TARGET,
+                    "Error sending event because there are no subscribers: {:?}",
+                    e
+                );
+                e
+            });
+
+        info!(
+            target: LOG_TARGET,
+            "Transaction (TxId: {}) replaced by transaction (TxId: {})", tx_id, replacement_tx_id
+        );
+
+        Ok(replacement_tx_id)
+    }
+
     /// Handle a Transaction Cancelled message received from the Comms layer
     pub async fn handle_transaction_cancelled_message(
         &mut self,
@@ -2831,6 +3610,8 @@ where
         current_height: Option<u64>,
         mined_timestamp: Option<NaiveDateTime>,
         scanned_output: TransactionOutput,
+        payment_reference: Option<Vec<u8>>,
+        memo: Option<Vec<u8>>,
     ) -> Result<TxId, TransactionServiceError> {
         let tx_id = if let Some(id) = tx_id { id } else { TxId::new_random() };
         self.db.add_utxo_import_transaction_with_status(
@@ -2843,6 +3624,8 @@ where
             current_height,
             mined_timestamp,
             scanned_output,
+            payment_reference,
+            memo,
         )?;
         let transaction_event = match import_status {
             ImportStatus::Imported => TransactionEvent::TransactionImported(tx_id),
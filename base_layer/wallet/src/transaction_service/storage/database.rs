@@ -135,6 +135,9 @@ pub trait TransactionBackend: Send + Sync + Clone {
     ) -> Result<(), TransactionStorageError>;
     /// Clears the mined block and height of a transaction
     fn set_transaction_as_unmined(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Replace the message of a completed transaction
+    fn update_completed_transaction_message(&self, tx_id: TxId, message: String)
+        -> Result<(), TransactionStorageError>;
     /// Reset optional 'mined height' and 'mined in block' fields to nothing
     fn mark_all_non_coinbases_transactions_as_unvalidated(&self) -> Result<(), TransactionStorageError>;
     /// Light weight method to retrieve pertinent transaction sender info for all pending inbound transactions
@@ -142,6 +145,11 @@ pub trait TransactionBackend: Send + Sync + Clone {
         &self,
     ) -> Result<Vec<InboundTransactionSenderInfo>, TransactionStorageError>;
     fn fetch_imported_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
+    /// Retrieve the completed transactions carrying the given payment reference
+    fn fetch_transactions_by_payment_reference(
+        &self,
+        payment_reference: &[u8],
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
     fn fetch_unconfirmed_detected_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
     fn fetch_confirmed_detected_transactions_from_height(
         &self,
@@ -444,6 +452,13 @@ where T: TransactionBackend + 'static
         Ok(t)
     }
 
+    pub fn get_transactions_by_payment_reference(
+        &self,
+        payment_reference: &[u8],
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        self.db.fetch_transactions_by_payment_reference(payment_reference)
+    }
+
     pub fn get_unconfirmed_detected_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let t = self.db.fetch_unconfirmed_detected_transactions()?;
         Ok(t)
@@ -681,8 +696,10 @@ where T: TransactionBackend + 'static
         current_height: Option<u64>,
         mined_timestamp: Option<NaiveDateTime>,
         scanned_output: TransactionOutput,
+        payment_reference: Option<Vec<u8>>,
+        memo: Option<Vec<u8>>,
     ) -> Result<(), TransactionStorageError> {
-        let transaction = CompletedTransaction::new(
+        let mut transaction = CompletedTransaction::new(
             tx_id,
             source_address,
             comms_address,
@@ -702,6 +719,8 @@ where T: TransactionBackend + 'static
             current_height,
             mined_timestamp,
         )?;
+        transaction.payment_reference = payment_reference;
+        transaction.memo = memo;
 
         self.db
             .write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
@@ -719,6 +738,14 @@ where T: TransactionBackend + 'static
         self.db.set_transaction_as_unmined(tx_id)
     }
 
+    pub fn update_completed_transaction_message(
+        &self,
+        tx_id: TxId,
+        message: String,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.update_completed_transaction_message(tx_id, message)
+    }
+
     pub fn mark_all_non_coinbases_transactions_as_unvalidated(&self) -> Result<(), TransactionStorageError> {
         self.db.mark_all_non_coinbases_transactions_as_unvalidated()
     }
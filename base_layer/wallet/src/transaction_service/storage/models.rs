@@ -147,6 +147,10 @@ pub struct CompletedTransaction {
     pub mined_height: Option<u64>,
     pub mined_in_block: Option<BlockHash>,
     pub mined_timestamp: Option<NaiveDateTime>,
+    /// The payment reference carried in the encrypted data of a one-sided payment, if any
+    pub payment_reference: Option<Vec<u8>>,
+    /// The memo for the recipient carried in the encrypted data of a one-sided payment, if any
+    pub memo: Option<Vec<u8>>,
 }
 
 impl CompletedTransaction {
@@ -191,6 +195,8 @@ impl CompletedTransaction {
             mined_height,
             mined_in_block: None,
             mined_timestamp,
+            payment_reference: None,
+            memo: None,
         })
     }
 }
@@ -270,6 +276,8 @@ impl From<OutboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            payment_reference: None,
+            memo: None,
         }
     }
 }
@@ -299,6 +307,8 @@ impl From<InboundTransaction> for CompletedTransaction {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            payment_reference: None,
+            memo: None,
         }
     }
 }
@@ -331,6 +341,8 @@ pub enum TxCancellationReason {
     TimeLocked,         // 5
     InvalidTransaction, // 6
     Oversized,          // 7
+    Expired,            // 8
+    Replaced,           // 9
 }
 
 impl TryFrom<u32> for TxCancellationReason {
@@ -346,6 +358,8 @@ impl TryFrom<u32> for TxCancellationReason {
             5 => Ok(TxCancellationReason::TimeLocked),
             6 => Ok(TxCancellationReason::InvalidTransaction),
             7 => Ok(TxCancellationReason::Oversized),
+            8 => Ok(TxCancellationReason::Expired),
+            9 => Ok(TxCancellationReason::Replaced),
             code => Err(TransactionConversionError { code: code as i32 }),
         }
     }
@@ -364,6 +378,8 @@ impl Display for TxCancellationReason {
             TimeLocked => "TimeLocked",
             InvalidTransaction => "Invalid Transaction",
             Oversized => "Oversized",
+            Expired => "Expired",
+            Replaced => "Replaced",
         };
         fmt.write_str(response)
     }
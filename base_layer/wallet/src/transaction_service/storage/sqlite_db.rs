@@ -32,7 +32,13 @@ use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
 use log::*;
 use tari_common_sqlite::{sqlite_connection_pool::PooledDbConnection, util::diesel_ext::ExpectedRowsExtension};
 use tari_common_types::{
-    encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
+    encryption::{
+        decrypt_bytes_integral_nonce,
+        encrypt_bytes_integral_nonce,
+        Encryptable,
+        LegacyPlaintext,
+        RekeySummary,
+    },
     tari_address::TariAddress,
     transaction::{
         TransactionConversionError,
@@ -51,7 +57,7 @@ use zeroize::Zeroize;
 
 use crate::{
     schema::{completed_transactions, inbound_transactions, outbound_transactions},
-    storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
+    storage::sqlite_utilities::{rekey_in_batches, wallet_db_connection::WalletDbConnection},
     transaction_service::{
         error::{TransactionKeyError, TransactionStorageError},
         storage::{
@@ -200,6 +206,48 @@ impl TransactionServiceSqliteDatabase {
     }
 }
 
+/// Re-encrypt the protocol fields of the inbound, outbound and completed transactions from `old` to `new`. Every
+/// `batch_size` rows are committed in their own transaction, and rows already encrypted under `new` are skipped, so an
+/// interrupted run can be repeated.
+pub fn rekey_transaction_tables(
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+    batch_size: usize,
+) -> Result<RekeySummary, TransactionStorageError> {
+    let mut summary = rekey_in_batches(
+        conn,
+        old,
+        new,
+        batch_size,
+        LegacyPlaintext::Reject,
+        InboundTransactionSql::page,
+        InboundTransactionSql::update_encryption,
+        TransactionStorageError::AeadError,
+    )?;
+    summary.merge(rekey_in_batches(
+        conn,
+        old,
+        new,
+        batch_size,
+        LegacyPlaintext::Reject,
+        OutboundTransactionSql::page,
+        OutboundTransactionSql::update_encryption,
+        TransactionStorageError::AeadError,
+    )?);
+    summary.merge(rekey_in_batches(
+        conn,
+        old,
+        new,
+        batch_size,
+        LegacyPlaintext::Reject,
+        CompletedTransactionSql::page,
+        CompletedTransactionSql::update_encryption,
+        TransactionStorageError::AeadError,
+    )?);
+    Ok(summary)
+}
+
 impl TransactionBackend for TransactionServiceSqliteDatabase {
     #[allow(clippy::too_many_lines)]
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, TransactionStorageError> {
@@ -973,6 +1021,21 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         Ok(())
     }
 
+    fn update_completed_transaction_message(
+        &self,
+        tx_id: TxId,
+        message: String,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        match CompletedTransactionSql::update_message(tx_id, message, &mut conn) {
+            Ok(_) => Ok(()),
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => Err(
+                TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(tx_id)),
+            ),
+            Err(e) => Err(e),
+        }
+    }
+
     fn get_pending_inbound_transaction_sender_info(
         &self,
     ) -> Result<Vec<InboundTransactionSenderInfo>, TransactionStorageError> {
@@ -1012,6 +1075,21 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
             .collect::<Result<Vec<CompletedTransaction>, TransactionStorageError>>()
     }
 
+    fn fetch_transactions_by_payment_reference(
+        &self,
+        payment_reference: &[u8],
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        CompletedTransactionSql::index_by_payment_reference(payment_reference, &mut conn)?
+            .into_iter()
+            .map(|ct: CompletedTransactionSql| {
+                CompletedTransaction::try_from(ct, &cipher).map_err(TransactionStorageError::from)
+            })
+            .collect::<Result<Vec<CompletedTransaction>, TransactionStorageError>>()
+    }
+
     fn fetch_unconfirmed_detected_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         let cipher = acquire_read_lock!(self.cipher);
@@ -1159,6 +1237,18 @@ impl InboundTransactionSql {
         Ok(inbound_transactions::table.load::<InboundTransactionSql>(conn)?)
     }
 
+    pub fn page(
+        offset: i64,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<InboundTransactionSql>, TransactionStorageError> {
+        Ok(inbound_transactions::table
+            .order(inbound_transactions::tx_id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<InboundTransactionSql>(conn)?)
+    }
+
     pub fn index_by_cancelled(
         conn: &mut SqliteConnection,
         cancelled: bool,
@@ -1301,7 +1391,6 @@ impl InboundTransactionSql {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
         self.update(
             UpdateInboundTransactionSql {
@@ -1423,6 +1512,18 @@ impl OutboundTransactionSql {
         Ok(outbound_transactions::table.load::<OutboundTransactionSql>(conn)?)
     }
 
+    pub fn page(
+        offset: i64,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<OutboundTransactionSql>, TransactionStorageError> {
+        Ok(outbound_transactions::table
+            .order(outbound_transactions::tx_id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<OutboundTransactionSql>(conn)?)
+    }
+
     pub fn index_by_cancelled(
         conn: &mut SqliteConnection,
         cancelled: bool,
@@ -1549,7 +1650,6 @@ impl OutboundTransactionSql {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
         self.update(
             UpdateOutboundTransactionSql {
@@ -1672,6 +1772,8 @@ pub struct CompletedTransactionSql {
     mined_timestamp: Option<NaiveDateTime>,
     transaction_signature_nonce: Vec<u8>,
     transaction_signature_key: Vec<u8>,
+    payment_reference: Option<Vec<u8>>,
+    memo: Option<Vec<u8>>,
 }
 
 impl CompletedTransactionSql {
@@ -1687,6 +1789,18 @@ impl CompletedTransactionSql {
         Ok(completed_transactions::table.load::<CompletedTransactionSql>(conn)?)
     }
 
+    pub fn page(
+        offset: i64,
+        limit: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        Ok(completed_transactions::table
+            .order(completed_transactions::tx_id.asc())
+            .offset(offset)
+            .limit(limit)
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
     pub fn index_by_cancelled(
         conn: &mut SqliteConnection,
         cancelled: bool,
@@ -1718,6 +1832,15 @@ impl CompletedTransactionSql {
             .load::<CompletedTransactionSql>(conn)?)
     }
 
+    pub fn index_by_payment_reference(
+        payment_reference: &[u8],
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        Ok(completed_transactions::table
+            .filter(completed_transactions::payment_reference.eq(payment_reference))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
     pub fn index_by_status_and_cancelled_from_block_height(
         status: TransactionStatus,
         cancelled: bool,
@@ -1874,6 +1997,18 @@ impl CompletedTransactionSql {
         Ok(())
     }
 
+    pub fn update_message(
+        tx_id: TxId,
+        message: String,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(completed_transactions::message.eq(message))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
     pub fn set_as_unmined(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
         let (current_status, current_mined_height) = *completed_transactions::table
             .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
@@ -1920,7 +2055,6 @@ impl CompletedTransactionSql {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
         self.update(
             UpdateCompletedTransactionSql {
@@ -1957,6 +2091,8 @@ impl CompletedTransactionSql {
             mined_timestamp: c.mined_timestamp,
             transaction_signature_nonce: c.transaction_signature.get_public_nonce().to_vec(),
             transaction_signature_key: c.transaction_signature.get_signature().to_vec(),
+            payment_reference: c.payment_reference,
+            memo: c.memo,
         };
 
         output.encrypt(cipher).map_err(TransactionStorageError::AeadError)
@@ -2054,6 +2190,8 @@ impl CompletedTransaction {
             mined_height: c.mined_height.map(|ic| ic as u64),
             mined_in_block,
             mined_timestamp: c.mined_timestamp,
+            payment_reference: c.payment_reference,
+            memo: c.memo,
         };
 
         // zeroize sensitive data
@@ -2458,6 +2596,8 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            payment_reference: None,
+            memo: None,
         };
         let source_address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
@@ -2486,6 +2626,8 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            payment_reference: None,
+            memo: None,
         };
 
         CompletedTransactionSql::try_from(completed_tx1.clone(), &cipher)
@@ -2721,6 +2863,8 @@ mod test {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            payment_reference: None,
+            memo: None,
         };
 
         let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone(), &cipher).unwrap();
@@ -2849,6 +2993,8 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                payment_reference: None,
+                memo: None,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx, &cipher).unwrap();
 
@@ -2989,6 +3135,8 @@ mod test {
                 mined_height: None,
                 mined_in_block: None,
                 mined_timestamp: None,
+                payment_reference: None,
+                memo: None,
             };
             let completed_tx_sql = CompletedTransactionSql::try_from(completed_tx.clone(), &cipher).unwrap();
 
@@ -28,6 +28,7 @@ use tari_common_types::transaction::TxId;
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
+    envelope::SafMessageClass,
     outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageResponse},
 };
 use tari_core::transactions::{transaction_components::Transaction, transaction_protocol::proto};
@@ -226,7 +227,8 @@ async fn send_transaction_finalized_message_store_and_forward(
             destination_pubkey.clone(),
             OutboundEncryption::encrypt_for(destination_pubkey.clone()),
             vec![],
-            OutboundDomainMessage::new(&TariMessageType::TransactionFinalized, msg.clone()),
+            OutboundDomainMessage::new(&TariMessageType::TransactionFinalized, msg.clone())
+                .with_saf_class(SafMessageClass::Transaction),
         )
         .await
     {
@@ -23,6 +23,7 @@ use tari_common_types::transaction::TxId;
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
+    envelope::SafMessageClass,
     outbound::{OutboundEncryption, OutboundMessageRequester},
 };
 use tari_core::transactions::transaction_protocol::proto::protocol as proto;
@@ -52,7 +53,8 @@ pub async fn send_transaction_cancelled_message(
             destination_public_key.clone(),
             OutboundEncryption::encrypt_for(destination_public_key),
             vec![],
-            OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message),
+            OutboundDomainMessage::new(&TariMessageType::SenderPartialTransaction, proto_message)
+                .with_saf_class(SafMessageClass::Transaction),
         )
         .await?;
     Ok(())
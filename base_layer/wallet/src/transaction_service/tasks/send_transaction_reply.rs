@@ -27,6 +27,7 @@ use tari_common_types::transaction::TxId;
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::{
     domain_message::OutboundDomainMessage,
+    envelope::SafMessageClass,
     outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageResponse},
 };
 use tari_core::transactions::transaction_protocol::proto;
@@ -208,7 +209,8 @@ async fn send_transaction_reply_store_and_forward(
             destination_pubkey.clone(),
             OutboundEncryption::encrypt_for(destination_pubkey.clone()),
             vec![],
-            OutboundDomainMessage::new(&TariMessageType::ReceiverPartialTransactionReply, msg),
+            OutboundDomainMessage::new(&TariMessageType::ReceiverPartialTransactionReply, msg)
+                .with_saf_class(SafMessageClass::Transaction),
         )
         .await
     {
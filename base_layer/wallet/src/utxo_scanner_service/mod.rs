@@ -34,5 +34,6 @@ pub mod initializer;
 pub mod service;
 mod utxo_scanner_task;
 pub mod uxto_scanner_service_builder;
+pub mod view_key_scanner;
 
 pub const RECOVERY_KEY: &str = "recovery_data";
@@ -198,6 +198,8 @@ pub struct UtxoScannerResources<TBackend, TWalletConnectivity> {
     pub factories: CryptoFactories,
     pub recovery_message: String,
     pub one_sided_payment_message: String,
+    /// The number of subaddresses to register before recovering the wallet
+    pub subaddress_lookahead: u64,
 }
 
 #[derive(Debug, Clone)]
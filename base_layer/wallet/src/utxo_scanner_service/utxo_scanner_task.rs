@@ -26,7 +26,10 @@ use std::{
 };
 
 use chrono::{NaiveDateTime, Utc};
-use futures::StreamExt;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
 use log::*;
 use tari_common_types::{
     tari_address::TariAddress,
@@ -35,15 +38,16 @@ use tari_common_types::{
 };
 use tari_comms::{
     peer_manager::NodeId,
-    protocol::rpc::RpcClientLease,
+    protocol::rpc::{RpcClientLease, RpcStatus, RpcStatusCode},
     traits::OrOptional,
     types::CommsPublicKey,
     PeerConnection,
 };
 use tari_core::{
-    base_node::rpc::BaseNodeWalletRpcClient,
+    base_node::rpc::{scan_tag_filter::ScanTagFilter, BaseNodeWalletRpcClient},
     blocks::BlockHeader,
-    proto::base_node::SyncUtxosByBlockRequest,
+    one_sided::one_sided_scan_tag,
+    proto::base_node::{SyncCandidateUtxosByBlockRequest, SyncUtxosByBlockRequest, SyncUtxosByBlockResponse},
     transactions::{
         tari_amount::MicroMinotari,
         transaction_components::{TransactionOutput, WalletOutput},
@@ -70,6 +74,18 @@ use crate::{
 
 pub const LOG_TARGET: &str = "wallet::utxo_scanning";
 
+// A scanned output belonging to this wallet: the output, import message and status, faux transaction id, the output as
+// found on chain and its payment reference, if any
+type FoundOutput = (
+    WalletOutput,
+    String,
+    ImportStatus,
+    TxId,
+    TransactionOutput,
+    Option<Vec<u8>>,
+    Option<Vec<u8>>,
+);
+
 pub struct UtxoScannerTask<TBackend, TWalletConnectivity> {
     pub(crate) resources: UtxoScannerResources<TBackend, TWalletConnectivity>,
     pub(crate) event_sender: broadcast::Sender<UtxoScannerEvent>,
@@ -88,6 +104,7 @@ where
     pub async fn run(mut self) -> Result<(), UtxoScannerError> {
         if self.mode == UtxoScannerMode::Recovery {
             self.set_recovery_mode()?;
+            self.register_subaddresses().await?;
         } else {
             let in_progress = self.check_recovery_mode()?;
             if in_progress {
@@ -300,11 +317,6 @@ where
                     tip_header.height,
                 )
                 .await?;
-            if num_scanned == 0 {
-                return Err(UtxoScannerError::UtxoScanningError(
-                    "Peer returned 0 UTXOs to scan".to_string(),
-                ));
-            }
             debug!(
                 target: LOG_TARGET,
                 "Scanning round completed up to height {} in {:.2?} ({} outputs scanned, {} recovered with value {})",
@@ -451,13 +463,10 @@ where
         let mut total_amount = MicroMinotari::from(0);
         let mut total_scanned = 0;
 
-        let request = SyncUtxosByBlockRequest {
-            start_header_hash: start_header_hash.to_vec(),
-            end_header_hash: end_header_hash.to_vec(),
-        };
-
         let start = Instant::now();
-        let mut utxo_stream = client.sync_utxos_by_block(request).await?;
+        let mut utxo_stream = self
+            .open_utxo_stream(client, start_header_hash, end_header_hash)
+            .await?;
         trace!(
             target: LOG_TARGET,
             "bulletproof rewind profile - UTXO stream request time {} ms",
@@ -533,12 +542,19 @@ where
             });
         }
         // We need to update the last one
-        if let Some(scanned_block) = prev_scanned_block {
-            self.resources.db.clear_scanned_blocks_before_height(
-                scanned_block.height.saturating_sub(SCANNED_BLOCK_CACHE_SIZE),
-                true,
-            )?;
-            self.resources.db.save_scanned_block(scanned_block)?;
+        match prev_scanned_block {
+            Some(scanned_block) => {
+                self.resources.db.clear_scanned_blocks_before_height(
+                    scanned_block.height.saturating_sub(SCANNED_BLOCK_CACHE_SIZE),
+                    true,
+                )?;
+                self.resources.db.save_scanned_block(scanned_block)?;
+            },
+            None => {
+                return Err(UtxoScannerError::UtxoScanningError(
+                    "Peer returned 0 blocks to scan".to_string(),
+                ))
+            },
         }
         trace!(
             target: LOG_TARGET,
@@ -556,12 +572,58 @@ where
         Ok((num_recovered, total_scanned as u64, total_amount))
     }
 
+    /// Opens a stream of the outputs in each block of the range. Base nodes that support it only send the outputs
+    /// matching a filter of our one-sided payment scan tags, along with the untagged outputs that still need trial
+    /// decryption; older base nodes send every output.
+    async fn open_utxo_stream(
+        &mut self,
+        client: &mut BaseNodeWalletRpcClient,
+        start_header_hash: HashOutput,
+        end_header_hash: HashOutput,
+    ) -> Result<BoxStream<'static, Result<SyncUtxosByBlockResponse, RpcStatus>>, UtxoScannerError> {
+        let known_scripts = self
+            .resources
+            .output_manager_service
+            .get_known_one_sided_payment_scripts()
+            .await?;
+        let mut filter = ScanTagFilter::with_capacity(known_scripts.len());
+        for known_script in &known_scripts {
+            if let Some(scan_tag) = one_sided_scan_tag(&known_script.script) {
+                filter.insert(scan_tag);
+            }
+        }
+
+        let mut candidate_stream = client
+            .sync_candidate_utxos_by_block(SyncCandidateUtxosByBlockRequest {
+                start_header_hash: start_header_hash.to_vec(),
+                end_header_hash: end_header_hash.to_vec(),
+                filter: Some(filter.into()),
+                include_untagged: true,
+            })
+            .await?;
+        match candidate_stream.next().await {
+            Some(Err(status)) if status.as_status_code() == RpcStatusCode::UnsupportedMethod => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Base node does not support filtered UTXO sync, scanning all outputs"
+                );
+            },
+            first => return Ok(stream::iter(first).chain(candidate_stream).boxed()),
+        }
+
+        let request = SyncUtxosByBlockRequest {
+            start_header_hash: start_header_hash.to_vec(),
+            end_header_hash: end_header_hash.to_vec(),
+        };
+        Ok(client.sync_utxos_by_block(request).await?.boxed())
+    }
+
     async fn scan_for_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
         height: u64,
-    ) -> Result<Vec<(WalletOutput, String, ImportStatus, TxId, TransactionOutput)>, UtxoScannerError> {
-        let mut found_outputs: Vec<(WalletOutput, String, ImportStatus, TxId, TransactionOutput)> = Vec::new();
+    ) -> Result<Vec<FoundOutput>, UtxoScannerError> {
+        let mut found_outputs: Vec<FoundOutput> = Vec::new();
         found_outputs.append(
             &mut self
                 .resources
@@ -581,7 +643,15 @@ where
                     let output = outputs.iter().find(|o| o.hash() == ro.hash).ok_or_else(|| {
                         UtxoScannerError::UtxoScanningError(format!("Output '{}' not found", ro.hash.to_hex()))
                     })?;
-                    Ok((ro.output, message, status, ro.tx_id, output.clone()))
+                    Ok((
+                        ro.output,
+                        message,
+                        status,
+                        ro.tx_id,
+                        output.clone(),
+                        ro.payment_reference,
+                        ro.memo,
+                    ))
                 })
                 .collect::<Result<Vec<_>, _>>()?,
         );
@@ -608,7 +678,15 @@ where
                     let output = outputs.iter().find(|o| o.hash() == ro.hash).ok_or_else(|| {
                         UtxoScannerError::UtxoScanningError(format!("Output '{}' not found", ro.hash.to_hex()))
                     })?;
-                    Ok((ro.output, message, status, ro.tx_id, output.clone()))
+                    Ok((
+                        ro.output,
+                        message,
+                        status,
+                        ro.tx_id,
+                        output.clone(),
+                        ro.payment_reference,
+                        ro.memo,
+                    ))
                 })
                 .collect::<Result<Vec<_>, _>>()?,
         );
@@ -617,13 +695,13 @@ where
 
     async fn import_utxos_to_transaction_service(
         &mut self,
-        utxos: Vec<(WalletOutput, String, ImportStatus, TxId, TransactionOutput)>,
+        utxos: Vec<FoundOutput>,
         current_height: u64,
         mined_timestamp: NaiveDateTime,
     ) -> Result<(u64, MicroMinotari), UtxoScannerError> {
         let mut num_recovered = 0u64;
         let mut total_amount = MicroMinotari::from(0);
-        for (wo, message, import_status, tx_id, to) in utxos {
+        for (wo, message, import_status, tx_id, to, payment_reference, memo) in utxos {
             let source_address = if wo.features.is_coinbase() {
                 // It's a coinbase, so we know we mined it (we do mining with cold wallets).
                 self.resources.wallet_identity.address.clone()
@@ -642,6 +720,8 @@ where
                     current_height,
                     mined_timestamp,
                     to.clone(),
+                    payment_reference,
+                    memo,
                 )
                 .await
             {
@@ -665,6 +745,20 @@ where
         Ok((num_recovered, total_amount))
     }
 
+    /// Adds the recovery lookahead of subaddresses to the candidate keys that outputs are scanned for
+    async fn register_subaddresses(&mut self) -> Result<(), UtxoScannerError> {
+        for index in 1..=self.resources.subaddress_lookahead {
+            self.resources.output_manager_service.get_subaddress(index).await?;
+        }
+        if self.resources.subaddress_lookahead > 0 {
+            debug!(
+                target: LOG_TARGET,
+                "Registered {} subaddresses for recovery", self.resources.subaddress_lookahead
+            );
+        }
+        Ok(())
+    }
+
     fn set_recovery_mode(&self) -> Result<(), UtxoScannerError> {
         self.resources
             .db
@@ -701,6 +795,8 @@ where
         current_height: u64,
         mined_timestamp: NaiveDateTime,
         scanned_output: TransactionOutput,
+        payment_reference: Option<Vec<u8>>,
+        memo: Option<Vec<u8>>,
     ) -> Result<TxId, WalletError> {
         let tx_id = self
             .resources
@@ -714,6 +810,8 @@ where
                 Some(current_height),
                 Some(mined_timestamp),
                 scanned_output,
+                payment_reference,
+                memo,
             )
             .await?;
 
@@ -56,6 +56,7 @@ pub struct UtxoScannerServiceBuilder {
     mode: Option<UtxoScannerMode>,
     one_sided_message: String,
     recovery_message: String,
+    subaddress_lookahead: u64,
 }
 
 impl Default for UtxoScannerServiceBuilder {
@@ -66,6 +67,7 @@ impl Default for UtxoScannerServiceBuilder {
             mode: None,
             one_sided_message: "Detected one-sided payment on blockchain".to_string(),
             recovery_message: "Output found on blockchain during Wallet Recovery".to_string(),
+            subaddress_lookahead: 0,
         }
     }
 }
@@ -98,6 +100,13 @@ impl UtxoScannerServiceBuilder {
         self
     }
 
+    /// Subaddresses are derived from the wallet key rather than stored, so recovery registers this many of them in
+    /// order to find payments to subaddresses that were handed out before the wallet was recovered
+    pub fn with_subaddress_lookahead(&mut self, lookahead: u64) -> &mut Self {
+        self.subaddress_lookahead = lookahead;
+        self
+    }
+
     pub fn build_with_wallet(
         &mut self,
         wallet: &WalletSqlite,
@@ -115,6 +124,7 @@ impl UtxoScannerServiceBuilder {
             factories: wallet.factories.clone(),
             recovery_message: self.recovery_message.clone(),
             one_sided_payment_message: self.one_sided_message.clone(),
+            subaddress_lookahead: self.subaddress_lookahead,
         };
 
         let (event_sender, _) = broadcast::channel(200);
@@ -158,6 +168,7 @@ impl UtxoScannerServiceBuilder {
             factories,
             recovery_message: self.recovery_message.clone(),
             one_sided_payment_message: self.one_sided_message.clone(),
+            subaddress_lookahead: self.subaddress_lookahead,
         };
 
         UtxoScannerService::new(
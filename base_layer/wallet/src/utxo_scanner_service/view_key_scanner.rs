@@ -0,0 +1,364 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A standalone scanner that finds the outputs belonging to a wallet using only its private view and scan keys. No
+//! spend key is needed, so the scanner can be handed to an auditor to produce a balance and output list without
+//! giving them the ability to move funds.
+
+use std::convert::TryFrom;
+
+use futures::StreamExt;
+use log::*;
+use tari_common_types::types::{Commitment, HashOutput, PrivateKey, PublicKey};
+use tari_comms::types::CommsDHKE;
+use tari_core::{
+    base_node::rpc::BaseNodeWalletRpcClient,
+    blocks::BlockHeader,
+    one_sided::{
+        diffie_hellman_stealth_domain_hasher,
+        shared_secret_to_output_encryption_key,
+        stealth_address_script_spending_key,
+    },
+    proto::base_node::{QueryDeletedRequest, SyncUtxosByBlockRequest},
+    transactions::{
+        tari_amount::MicroMinotari,
+        transaction_components::{EncryptedData, TransactionOutput},
+        CryptoFactories,
+    },
+};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_script::Opcode;
+
+use crate::{output_manager_service::storage::OutputSource, utxo_scanner_service::error::UtxoScannerError};
+
+const LOG_TARGET: &str = "wallet::utxo_scanning::view_key";
+
+/// The base node rejects `query_deleted` requests for more than this many hashes
+const QUERY_DELETED_BATCH_SIZE: usize = 1000;
+
+/// An output found on chain that can be decrypted with the scanner's keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewKeyScannedOutput {
+    pub output_hash: HashOutput,
+    pub commitment: Commitment,
+    pub value: MicroMinotari,
+    pub source: OutputSource,
+    pub payment_reference: Vec<u8>,
+    pub mined_height: u64,
+    /// The height at which the output was spent, if it has been spent
+    pub spent_height: Option<u64>,
+}
+
+/// The result of a view key scan
+#[derive(Debug, Clone, Default)]
+pub struct ViewKeyScanReport {
+    pub outputs: Vec<ViewKeyScannedOutput>,
+    /// The chain tip height the scan ran up to
+    pub tip_height: u64,
+    /// The number of outputs that were checked
+    pub num_scanned: u64,
+}
+
+impl ViewKeyScanReport {
+    /// The total value of the unspent outputs found
+    pub fn balance(&self) -> MicroMinotari {
+        self.outputs
+            .iter()
+            .filter(|o| o.spent_height.is_none())
+            .map(|o| o.value)
+            .sum()
+    }
+
+    /// The total value of all the outputs found, spent or not
+    pub fn total_received(&self) -> MicroMinotari {
+        self.outputs.iter().map(|o| o.value).sum()
+    }
+}
+
+/// Scans the chain for outputs belonging to a wallet given its private view key, used to decrypt the data of standard
+/// outputs, and its private scan key, used to detect and decrypt one-sided and stealth one-sided payments.
+pub struct ViewKeyScanner {
+    view_key: PrivateKey,
+    scan_key: PrivateKey,
+    scan_public_key: PublicKey,
+    factories: CryptoFactories,
+}
+
+impl ViewKeyScanner {
+    pub fn new(view_key: PrivateKey, scan_key: PrivateKey, factories: CryptoFactories) -> Self {
+        let scan_public_key = PublicKey::from_secret_key(&scan_key);
+        Self {
+            view_key,
+            scan_key,
+            scan_public_key,
+            factories,
+        }
+    }
+
+    /// Returns the decrypted output if it belongs to the scanned wallet. The recovered mask is checked against the
+    /// commitment, so a successful decryption with the wrong key is never reported.
+    pub fn scan_output(&self, output: &TransactionOutput, mined_height: u64) -> Option<ViewKeyScannedOutput> {
+        let (encryption_key, source) = match output.script.as_slice() {
+            [Opcode::PushPubKey(scanned_pk)] if scanned_pk.as_ref() == &self.scan_public_key => {
+                (self.one_sided_encryption_key(output)?, OutputSource::OneSided)
+            },
+            [Opcode::PushPubKey(nonce), Opcode::Drop, Opcode::PushPubKey(scanned_pk)]
+                if self.is_stealth_address_match(nonce, scanned_pk) =>
+            {
+                (self.one_sided_encryption_key(output)?, OutputSource::StealthOneSided)
+            },
+            _ => (self.view_key.clone(), OutputSource::Standard),
+        };
+        let (value, mask, payment_reference) = EncryptedData::decrypt_data_with_payment_reference(
+            &encryption_key,
+            &output.commitment,
+            &output.encrypted_data,
+        )
+        .ok()?;
+        if !output
+            .verify_mask(&self.factories.range_proof, &mask, value.as_u64())
+            .unwrap_or(false)
+        {
+            return None;
+        }
+        let source = if output.is_coinbase() {
+            OutputSource::Coinbase
+        } else {
+            source
+        };
+
+        Some(ViewKeyScannedOutput {
+            output_hash: output.hash(),
+            commitment: output.commitment.clone(),
+            value,
+            source,
+            payment_reference,
+            mined_height,
+            spent_height: None,
+        })
+    }
+
+    /// Scans every block from `start_height` to the current tip of the base node the client is connected to, then
+    /// asks the base node which of the found outputs have since been spent.
+    pub async fn scan(
+        &self,
+        client: &mut BaseNodeWalletRpcClient,
+        start_height: u64,
+    ) -> Result<ViewKeyScanReport, UtxoScannerError> {
+        let tip_info = client.get_tip_info().await?;
+        let tip_height = tip_info.metadata.map(|m| m.best_block_height()).unwrap_or(0);
+        let mut report = ViewKeyScanReport {
+            tip_height,
+            ..Default::default()
+        };
+        if start_height > tip_height {
+            return Ok(report);
+        }
+
+        let start_header = BlockHeader::try_from(client.get_header_by_height(start_height).await?)
+            .map_err(UtxoScannerError::ConversionError)?;
+        let end_header = BlockHeader::try_from(client.get_header_by_height(tip_height).await?)
+            .map_err(UtxoScannerError::ConversionError)?;
+        let end_header_hash = end_header.hash();
+        debug!(
+            target: LOG_TARGET,
+            "View key scan from height {} to height {}", start_height, tip_height
+        );
+
+        let mut utxo_stream = client
+            .sync_utxos_by_block(SyncUtxosByBlockRequest {
+                start_header_hash: start_header.hash().to_vec(),
+                end_header_hash: end_header_hash.to_vec(),
+            })
+            .await?;
+        while let Some(response) = utxo_stream.next().await {
+            let response = response.map_err(|e| UtxoScannerError::RpcStatus(e.to_string()))?;
+            for output in response.outputs {
+                let output = TransactionOutput::try_from(output).map_err(UtxoScannerError::ConversionError)?;
+                report.num_scanned += 1;
+                if let Some(found) = self.scan_output(&output, response.height) {
+                    report.outputs.push(found);
+                }
+            }
+        }
+
+        for batch in report.outputs.chunks_mut(QUERY_DELETED_BATCH_SIZE) {
+            let response = client
+                .query_deleted(QueryDeletedRequest {
+                    hashes: batch.iter().map(|o| o.output_hash.to_vec()).collect(),
+                    chain_must_include_header: end_header_hash.to_vec(),
+                })
+                .await?;
+            if response.data.len() != batch.len() {
+                return Err(UtxoScannerError::BaseNodeResponseError(
+                    "Base node did not send back information for all outputs".to_string(),
+                ));
+            }
+            for (output, data) in batch.iter_mut().zip(response.data) {
+                if data.height_deleted_at > 0 {
+                    output.spent_height = Some(data.height_deleted_at);
+                }
+            }
+        }
+        debug!(
+            target: LOG_TARGET,
+            "View key scan found {} of {} outputs with a balance of {}",
+            report.outputs.len(),
+            report.num_scanned,
+            report.balance()
+        );
+
+        Ok(report)
+    }
+
+    fn one_sided_encryption_key(&self, output: &TransactionOutput) -> Option<PrivateKey> {
+        let shared_secret = CommsDHKE::new(&self.scan_key, &output.sender_offset_public_key);
+        shared_secret_to_output_encryption_key(&shared_secret).ok()
+    }
+
+    fn is_stealth_address_match(&self, nonce: &PublicKey, scanned_pk: &PublicKey) -> bool {
+        let stealth_address_hasher = diffie_hellman_stealth_domain_hasher(&self.scan_key, nonce);
+        &stealth_address_script_spending_key(&stealth_address_hasher, &self.scan_public_key) == scanned_pk
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::SecretKey};
+    use tari_script::{script, TariScript};
+
+    use super::*;
+
+    fn create_output(
+        factories: &CryptoFactories,
+        encryption_key: &PrivateKey,
+        value: MicroMinotari,
+        script: TariScript,
+        sender_offset_public_key: PublicKey,
+    ) -> TransactionOutput {
+        let mask = PrivateKey::random(&mut OsRng);
+        let commitment = factories.commitment.commit_value(&mask, value.as_u64());
+        let encrypted_data =
+            EncryptedData::encrypt_data_with_payment_reference(encryption_key, &commitment, value, &mask, b"invoice 7")
+                .unwrap();
+        TransactionOutput {
+            commitment,
+            script,
+            sender_offset_public_key,
+            encrypted_data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_finds_outputs_with_only_the_view_and_scan_keys() {
+        let factories = CryptoFactories::default();
+        let view_key = PrivateKey::random(&mut OsRng);
+        let scan_key = PrivateKey::random(&mut OsRng);
+        let scan_public_key = PublicKey::from_secret_key(&scan_key);
+        let scanner = ViewKeyScanner::new(view_key.clone(), scan_key, factories.clone());
+
+        let standard = create_output(
+            &factories,
+            &view_key,
+            MicroMinotari::from(1_000),
+            script!(Nop),
+            PublicKey::default(),
+        );
+        let found = scanner.scan_output(&standard, 10).unwrap();
+        assert_eq!(found.value, MicroMinotari::from(1_000));
+        assert_eq!(found.source, OutputSource::Standard);
+        assert_eq!(found.payment_reference, b"invoice 7".to_vec());
+        assert_eq!(found.mined_height, 10);
+
+        // One-sided payment, encrypted with the Diffie-Hellman secret of the sender offset key and the scan key
+        let sender_offset_key = PrivateKey::random(&mut OsRng);
+        let shared_secret = CommsDHKE::new(&sender_offset_key, &scan_public_key);
+        let one_sided = create_output(
+            &factories,
+            &shared_secret_to_output_encryption_key(&shared_secret).unwrap(),
+            MicroMinotari::from(2_000),
+            script!(PushPubKey(Box::new(scan_public_key.clone()))),
+            PublicKey::from_secret_key(&sender_offset_key),
+        );
+        let found = scanner.scan_output(&one_sided, 11).unwrap();
+        assert_eq!(found.value, MicroMinotari::from(2_000));
+        assert_eq!(found.source, OutputSource::OneSided);
+
+        // Stealth one-sided payment, the script key is derived from a fresh nonce
+        let nonce = PrivateKey::random(&mut OsRng);
+        let stealth_address_hasher = diffie_hellman_stealth_domain_hasher(&nonce, &scan_public_key);
+        let script_spending_key = stealth_address_script_spending_key(&stealth_address_hasher, &scan_public_key);
+        let stealth = create_output(
+            &factories,
+            &shared_secret_to_output_encryption_key(&shared_secret).unwrap(),
+            MicroMinotari::from(3_000),
+            script!(PushPubKey(Box::new(PublicKey::from_secret_key(&nonce))) Drop PushPubKey(Box::new(script_spending_key))),
+            PublicKey::from_secret_key(&sender_offset_key),
+        );
+        let found = scanner.scan_output(&stealth, 12).unwrap();
+        assert_eq!(found.value, MicroMinotari::from(3_000));
+        assert_eq!(found.source, OutputSource::StealthOneSided);
+
+        let report = ViewKeyScanReport {
+            outputs: vec![scanner.scan_output(&standard, 10).unwrap(), ViewKeyScannedOutput {
+                spent_height: Some(20),
+                ..scanner.scan_output(&one_sided, 11).unwrap()
+            }],
+            tip_height: 20,
+            num_scanned: 2,
+        };
+        assert_eq!(report.balance(), MicroMinotari::from(1_000));
+        assert_eq!(report.total_received(), MicroMinotari::from(3_000));
+    }
+
+    #[test]
+    fn it_ignores_outputs_for_other_wallets() {
+        let factories = CryptoFactories::default();
+        let scanner = ViewKeyScanner::new(
+            PrivateKey::random(&mut OsRng),
+            PrivateKey::random(&mut OsRng),
+            factories.clone(),
+        );
+
+        let other_view_key = PrivateKey::random(&mut OsRng);
+        let output = create_output(
+            &factories,
+            &other_view_key,
+            MicroMinotari::from(1_000),
+            script!(Nop),
+            PublicKey::default(),
+        );
+        assert!(scanner.scan_output(&output, 10).is_none());
+
+        let other_scan_public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let output = create_output(
+            &factories,
+            &other_view_key,
+            MicroMinotari::from(1_000),
+            script!(PushPubKey(Box::new(other_scan_public_key))),
+            PublicKey::default(),
+        );
+        assert!(scanner.scan_output(&output, 10).is_none());
+    }
+}
@@ -30,7 +30,7 @@ use rand::rngs::OsRng;
 use tari_common::configuration::bootstrap::ApplicationType;
 use tari_common_types::{
     tari_address::TariAddress,
-    transaction::{ImportStatus, TxId},
+    transaction::{ImportStatus, TransactionDirection, TxId},
     types::{ComAndPubSignature, Commitment, PrivateKey, PublicKey, SignatureWithDomain},
     wallet_types::WalletType,
 };
@@ -48,6 +48,7 @@ use tari_comms_dht::{store_forward::StoreAndForwardRequester, Dht};
 use tari_contacts::contacts_service::{
     handle::ContactsServiceHandle,
     storage::database::ContactsBackend,
+    types::Contact,
     ContactsServiceInitializer,
 };
 use tari_core::{
@@ -83,13 +84,14 @@ use tari_shutdown::ShutdownSignal;
 use tari_utilities::{hex::Hex, ByteArray};
 
 use crate::{
+    account::models::account_key_branch,
     base_node_service::{handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
     config::{WalletConfig, KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY},
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInitializer, WalletConnectivityInterface},
     consts,
     error::{WalletError, WalletStorageError},
     output_manager_service::{
-        error::OutputManagerError,
+        error::{OutputManagerError, OutputManagerStorageError},
         handle::OutputManagerHandle,
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase},
@@ -100,6 +102,13 @@ use crate::{
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
         handle::TransactionServiceHandle,
+        history::{
+            decode_transaction_history,
+            encode_transaction_history,
+            TransactionHistoryFormat,
+            TransactionHistoryImport,
+            TransactionHistoryRecord,
+        },
         storage::database::TransactionBackend,
         TransactionServiceInitializer,
     },
@@ -279,9 +288,7 @@ where
                     return;
                 }
                 let address = result.unwrap();
-                if !node_id.public_addresses().contains(&address) {
-                    node_id.add_public_address(address.clone());
-                }
+                node_id.replace_onion_address(address.clone());
                 // Persist the comms node address and features after it has been spawned to capture any modifications
                 // made during comms startup. In the case of a Tor Transport the public address could
                 // have been generated
@@ -317,6 +324,13 @@ where
             None
         };
 
+        // The account key branches must be known to the key manager to scan for payments to the account addresses
+        for account in wallet_database.fetch_accounts()? {
+            key_manager_handle
+                .add_new_branch(account_key_branch(account.index))
+                .await?;
+        }
+
         persist_one_sided_payment_script_for_node_identity(&mut output_manager_handle, wallet_identity.clone())
             .await
             .map_err(|e| {
@@ -542,6 +556,8 @@ where
                     .await?
                     .to_transaction_output(&self.key_manager_service)
                     .await?,
+                None,
+                None,
             )
             .await?;
         let wallet_output = unblinded_output.to_wallet_output(&self.key_manager_service).await?;
@@ -559,6 +575,79 @@ where
         Ok(tx_id)
     }
 
+    /// Import a batch of external spendable UTXOs into the wallet as non-rewindable/non-recoverable UTXOs. All outputs
+    /// are added to the Output Manager in a single database transaction, and a faux incoming transaction is created for
+    /// each output that was added. Returns the result of each import in the order of `unblinded_outputs`; outputs that
+    /// the wallet already holds fail with `OutputManagerStorageError::DuplicateOutput`.
+    pub async fn import_unblinded_outputs_as_non_rewindable(
+        &mut self,
+        unblinded_outputs: Vec<UnblindedOutput>,
+        source_address: TariAddress,
+        message: String,
+    ) -> Result<Vec<Result<TxId, WalletError>>, WalletError> {
+        let mut results = Vec::with_capacity(unblinded_outputs.len());
+        let mut to_import = Vec::with_capacity(unblinded_outputs.len());
+        for (i, unblinded_output) in unblinded_outputs.into_iter().enumerate() {
+            let wallet_output = match unblinded_output.to_wallet_output(&self.key_manager_service).await {
+                Ok(output) => output,
+                Err(e) => {
+                    results.push(Err(e.into()));
+                    continue;
+                },
+            };
+            match wallet_output.to_transaction_output(&self.key_manager_service).await {
+                Ok(transaction_output) => {
+                    let tx_id = TxId::new_random();
+                    to_import.push((i, tx_id, wallet_output, transaction_output));
+                    // Replaced below once the output has been imported
+                    results.push(Ok(tx_id));
+                },
+                Err(e) => results.push(Err(e.into())),
+            }
+        }
+
+        let added = self
+            .output_manager_service
+            .add_unvalidated_outputs(
+                to_import
+                    .iter()
+                    .map(|(_, tx_id, wallet_output, _)| (*tx_id, wallet_output.clone()))
+                    .collect(),
+            )
+            .await?;
+
+        for ((i, tx_id, wallet_output, transaction_output), added) in to_import.into_iter().zip(added) {
+            if !added {
+                results[i] = Err(OutputManagerError::from(OutputManagerStorageError::DuplicateOutput).into());
+                continue;
+            }
+            results[i] = self
+                .transaction_service
+                .import_utxo_with_status(
+                    wallet_output.value,
+                    source_address.clone(),
+                    message.clone(),
+                    ImportStatus::Imported,
+                    Some(tx_id),
+                    None,
+                    None,
+                    transaction_output,
+                    None,
+                    None,
+                )
+                .await
+                .map_err(WalletError::from);
+        }
+        info!(
+            target: LOG_TARGET,
+            "Imported {} of {} UTXOs into wallet as 'ImportStatus::Imported' and non-rewindable",
+            results.iter().filter(|r| r.is_ok()).count(),
+            results.len(),
+        );
+
+        Ok(results)
+    }
+
     pub fn sign_message(
         &mut self,
         secret: &PrivateKey,
@@ -601,18 +690,19 @@ where
             .map_err(WalletError::OutputManagerError)
     }
 
-    /// Do a coin split
+    /// Do a coin split, the split outputs and change belong to `account`
     pub async fn coin_split(
         &mut self,
         commitments: Vec<Commitment>,
         amount_per_split: MicroMinotari,
         split_count: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
         message: String,
     ) -> Result<TxId, WalletError> {
         let coin_split_tx = self
             .output_manager_service
-            .create_coin_split(commitments, amount_per_split, split_count, fee_per_gram)
+            .create_coin_split(commitments, amount_per_split, split_count, fee_per_gram, account)
             .await;
 
         match coin_split_tx {
@@ -630,17 +720,18 @@ where
         }
     }
 
-    /// Do a coin split
+    /// Do a coin split, the split outputs belong to `account`
     pub async fn coin_split_even(
         &mut self,
         commitments: Vec<Commitment>,
         split_count: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
         message: String,
     ) -> Result<TxId, WalletError> {
         let coin_split_tx = self
             .output_manager_service
-            .create_coin_split_even(commitments, split_count, fee_per_gram)
+            .create_coin_split_even(commitments, split_count, fee_per_gram, account)
             .await;
 
         match coin_split_tx {
@@ -658,17 +749,18 @@ where
         }
     }
 
-    /// Do a coin split
+    /// Do a coin split, the split outputs belong to `account`
     pub async fn coin_split_even_with_commitments(
         &mut self,
         commitments: Vec<Commitment>,
         split_count: usize,
         fee_per_gram: MicroMinotari,
+        account: u64,
         message: String,
     ) -> Result<TxId, WalletError> {
         let coin_split_tx = self
             .output_manager_service
-            .create_coin_split_even(commitments, split_count, fee_per_gram)
+            .create_coin_split_even(commitments, split_count, fee_per_gram, account)
             .await;
 
         match coin_split_tx {
@@ -690,11 +782,12 @@ where
         &mut self,
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
+        account: u64,
         msg: Option<String>,
     ) -> Result<TxId, WalletError> {
         let coin_join_tx = self
             .output_manager_service
-            .create_coin_join(commitments, fee_per_gram)
+            .create_coin_join(commitments, fee_per_gram, account)
             .await;
 
         match coin_join_tx {
@@ -713,6 +806,90 @@ where
         }
     }
 
+    /// Export every completed transaction, including cancelled ones, in the given format
+    pub async fn export_transaction_history(
+        &mut self,
+        format: TransactionHistoryFormat,
+    ) -> Result<String, WalletError> {
+        let contacts = self.contacts_service.get_contacts().await?;
+        let mut transactions = self.transaction_service.get_completed_transactions().await?;
+        transactions.extend(self.transaction_service.get_cancelled_completed_transactions().await?);
+        let mut transactions = transactions.into_values().collect::<Vec<_>>();
+        transactions.sort_by_key(|tx| tx.timestamp);
+
+        let records = transactions
+            .iter()
+            .map(|tx| {
+                let counterparty = match tx.direction {
+                    TransactionDirection::Inbound => &tx.source_address,
+                    _ => &tx.destination_address,
+                };
+                let contact_name = contacts
+                    .iter()
+                    .find(|contact| &contact.address == counterparty)
+                    .map(|contact| contact.alias.clone());
+                TransactionHistoryRecord::new(tx, contact_name)
+            })
+            .collect::<Vec<_>>();
+        Ok(encode_transaction_history(&records, format)?)
+    }
+
+    /// Import a transaction history exported by [Wallet::export_transaction_history], typically after recovering the
+    /// wallet from its seed words. Transaction messages and contact names, which cannot be recovered from the chain,
+    /// are restored; existing contacts are left unchanged.
+    pub async fn import_transaction_history(
+        &mut self,
+        data: &str,
+        format: TransactionHistoryFormat,
+    ) -> Result<TransactionHistoryImport, WalletError> {
+        let records = decode_transaction_history(data, format)?;
+
+        let mut known_addresses = self
+            .contacts_service
+            .get_contacts()
+            .await?
+            .into_iter()
+            .map(|contact| contact.address)
+            .collect::<Vec<_>>();
+        let own_public_key = self.comms.node_identity().public_key().clone();
+        let mut num_contacts_restored = 0;
+        for record in &records {
+            let name = match &record.contact_name {
+                Some(name) => name,
+                None => continue,
+            };
+            let counterparty = if record.direction == TransactionDirection::Inbound.to_string() {
+                &record.source_address
+            } else {
+                &record.destination_address
+            };
+            let address = match TariAddress::from_hex(counterparty) {
+                Ok(address) => address,
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Not restoring contact '{}' with invalid address {}: {}", name, counterparty, e
+                    );
+                    continue;
+                },
+            };
+            if address.public_key() == &own_public_key || known_addresses.contains(&address) {
+                continue;
+            }
+            self.contacts_service
+                .upsert_contact(Contact::new(name.clone(), address.clone(), None, None, false))
+                .await?;
+            known_addresses.push(address);
+            num_contacts_restored += 1;
+        }
+
+        let num_messages_restored = self.transaction_service.restore_transaction_messages(records).await?;
+        Ok(TransactionHistoryImport {
+            num_messages_restored,
+            num_contacts_restored,
+        })
+    }
+
     /// Utility function to find out if there is data in the database indicating that there is an incomplete recovery
     /// process in progress
     pub fn is_recovery_in_progress(&self) -> Result<bool, WalletError> {
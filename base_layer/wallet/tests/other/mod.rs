@@ -155,6 +155,7 @@ async fn create_wallet(
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
         listener_liveness_check_interval: None,
+        ban_policy: Default::default(),
     };
 
     let sql_database_path = comms_config
@@ -679,6 +680,7 @@ async fn test_import_utxo() {
             listener_address: "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
             tor_socks_address: None,
             tor_socks_auth: Default::default(),
+            port_mapping: Default::default(),
         }),
         datastore_path: temp_dir.path().to_path_buf(),
         peer_database_name: random::string(8),
@@ -693,6 +695,7 @@ async fn test_import_utxo() {
         rpc_max_simultaneous_sessions: 0,
         rpc_max_sessions_per_peer: 0,
         listener_liveness_check_interval: None,
+        ban_policy: Default::default(),
     };
     let config = WalletConfig {
         p2p: comms_config,
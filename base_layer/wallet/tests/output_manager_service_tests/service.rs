@@ -23,6 +23,7 @@
 use std::{collections::HashMap, convert::TryInto, sync::Arc, time::Duration};
 
 use minotari_wallet::{
+    account::models::DEFAULT_ACCOUNT_INDEX,
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
     connectivity_service::{create_wallet_connectivity_mock, WalletConnectivityMock},
     output_manager_service::{
@@ -37,6 +38,8 @@ use minotari_wallet::{
             OutputStatus,
         },
         UtxoSelectionCriteria,
+        UtxoSelectionMode,
+        UtxoSelectionOrdering,
     },
     test_utils::create_consensus_constants,
     transaction_service::handle::TransactionServiceHandle,
@@ -46,7 +49,7 @@ use rand::{rngs::OsRng, RngCore};
 use tari_common::configuration::Network;
 use tari_common_types::{
     transaction::TxId,
-    types::{ComAndPubSignature, FixedHash, PublicKey},
+    types::{ComAndPubSignature, Commitment, FixedHash, PublicKey},
 };
 use tari_comms::{
     peer_manager::{NodeIdentity, PeerFeatures},
@@ -492,7 +495,10 @@ async fn test_utxo_selection_no_chain_metadata() {
     assert_eq!(fee, MicroMinotari::from(256));
 
     // coin split uses the "Largest" selection strategy
-    let (_, tx, utxos_total_value) = oms.create_coin_split(vec![], amount, 5, fee_per_gram).await.unwrap();
+    let (_, tx, utxos_total_value) = oms
+        .create_coin_split(vec![], amount, 5, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
+        .await
+        .unwrap();
     let expected_fee = fee_calc.calculate(
         fee_per_gram,
         1,
@@ -593,7 +599,10 @@ async fn test_utxo_selection_with_chain_metadata() {
     assert_eq!(fee, MicroMinotari::from(256));
 
     // test coin split is maturity aware
-    let (_, tx, utxos_total_value) = oms.create_coin_split(vec![], amount, 5, fee_per_gram).await.unwrap();
+    let (_, tx, utxos_total_value) = oms
+        .create_coin_split(vec![], amount, 5, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
+        .await
+        .unwrap();
     assert_eq!(utxos_total_value, MicroMinotari::from(5_000));
     let expected_fee = fee_calc.calculate(
         fee_per_gram,
@@ -763,6 +772,252 @@ async fn test_utxo_selection_with_tx_priority() {
     assert_ne!(utxos[1].wallet_output.spending_key_id, uo_high.spending_key_id);
 }
 
+#[tokio::test]
+async fn test_htlc_outputs_are_selected_before_pinned_outputs() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    // setup with chain metadata at a height of 6
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let (mut oms, _shutdown, _, _, _, key_manager) =
+        setup_oms_with_bn_state(backend.clone(), Some(6), server_node_identity).await;
+
+    let mut outputs = Vec::new();
+    for spending_priority in [None, Some(SpendingPriority::HtlcSpendAsap), None] {
+        let uo = make_input_with_features(
+            &mut OsRng.clone(),
+            MicroMinotari::from(2000),
+            OutputFeatures {
+                maturity: 1,
+                ..Default::default()
+            },
+            &key_manager,
+        )
+        .await;
+        oms.add_output(uo.clone(), spending_priority).await.unwrap();
+        backend
+            .mark_outputs_as_unspent(vec![(uo.hash(&key_manager).await.unwrap(), true)])
+            .unwrap();
+        outputs.push(uo.commitment(&key_manager).await.unwrap());
+    }
+
+    // Pinning the HTLC output leaves it to be swept first
+    oms.set_outputs_spending_priority(vec![outputs[0].clone(), outputs[1].clone()], SpendingPriority::Pinned)
+        .await
+        .unwrap();
+    let spendable = oms
+        .get_spendable_outputs(UtxoSelectionCriteria::default())
+        .await
+        .unwrap();
+    assert_eq!(spendable.len(), 3);
+    assert_eq!(spendable[0].commitment, outputs[1]);
+    assert_eq!(spendable[0].spending_priority, SpendingPriority::HtlcSpendAsap);
+    assert_eq!(spendable[1].commitment, outputs[0]);
+    assert_eq!(spendable[1].spending_priority, SpendingPriority::Pinned);
+    assert_eq!(spendable[2].spending_priority, SpendingPriority::Normal);
+
+    // Unpinning does not release the HTLC priority
+    oms.set_outputs_spending_priority(vec![outputs[0].clone(), outputs[1].clone()], SpendingPriority::Normal)
+        .await
+        .unwrap();
+    let spendable = oms
+        .get_spendable_outputs(UtxoSelectionCriteria::default())
+        .await
+        .unwrap();
+    assert_eq!(spendable[0].commitment, outputs[1]);
+    assert_eq!(spendable[0].spending_priority, SpendingPriority::HtlcSpendAsap);
+    assert!(spendable[1..]
+        .iter()
+        .all(|o| o.spending_priority == SpendingPriority::Normal));
+}
+
+#[tokio::test]
+async fn test_utxo_selection_with_frozen_and_explicit_inputs() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    // setup with chain metadata at a height of 6
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let (mut oms, _shutdown, _, _, _, key_manager) =
+        setup_oms_with_bn_state(backend.clone(), Some(6), server_node_identity).await;
+
+    let amount = MicroMinotari::from(2000);
+    let fee_per_gram = MicroMinotari::from(2);
+
+    let mut outputs = Vec::new();
+    for _ in 0..3 {
+        let uo = make_input_with_features(
+            &mut OsRng.clone(),
+            amount,
+            OutputFeatures {
+                maturity: 1,
+                ..Default::default()
+            },
+            &key_manager,
+        )
+        .await;
+        oms.add_output(uo.clone(), None).await.unwrap();
+        backend
+            .mark_outputs_as_unspent(vec![(uo.hash(&key_manager).await.unwrap(), true)])
+            .unwrap();
+        outputs.push(uo.commitment(&key_manager).await.unwrap());
+    }
+
+    oms.set_outputs_spending_priority(vec![outputs[0].clone()], SpendingPriority::Frozen)
+        .await
+        .unwrap();
+    let err = oms
+        .set_outputs_spending_priority(vec![Commitment::default()], SpendingPriority::Frozen)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        OutputManagerError::OutputManagerStorageError(OutputManagerStorageError::ValuesNotFound)
+    ));
+
+    // Frozen outputs are not offered for automatic selection, but are still listed
+    let spendable = oms
+        .get_spendable_outputs(UtxoSelectionCriteria::default())
+        .await
+        .unwrap();
+    assert_eq!(spendable.len(), 2);
+    assert!(spendable.iter().all(|o| o.commitment != outputs[0]));
+    let listed = oms
+        .get_spendable_outputs(UtxoSelectionCriteria {
+            mode: UtxoSelectionMode::ListingOnly,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 3);
+    let frozen = listed.iter().find(|o| o.commitment == outputs[0]).unwrap();
+    assert_eq!(frozen.spending_priority, SpendingPriority::Frozen);
+
+    let stp = oms
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroMinotari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            fee_per_gram,
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroMinotari::zero(),
+        )
+        .await
+        .unwrap();
+    assert!(stp.get_tx_id().is_ok());
+    let utxos = oms.get_unspent_outputs().await.unwrap();
+    assert_eq!(utxos.len(), 2);
+    assert!(utxos.iter().any(|o| o.commitment == outputs[0]));
+
+    // Explicit inputs are all spent, including frozen ones, even if fewer would cover the amount
+    let remaining = utxos.into_iter().map(|o| o.commitment).collect::<Vec<_>>();
+    oms.prepare_transaction_to_send(
+        TxId::new_random(),
+        MicroMinotari::from(1000),
+        UtxoSelectionCriteria::specific(remaining),
+        OutputFeatures::default(),
+        fee_per_gram,
+        TransactionMetadata::default(),
+        "".to_string(),
+        script!(Nop),
+        Covenant::default(),
+        MicroMinotari::zero(),
+    )
+    .await
+    .unwrap();
+    assert!(oms.get_unspent_outputs().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_preview_coin_selection_strategies() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+
+    // setup with chain metadata at a height of 6
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let (mut oms, _shutdown, _, _, _, key_manager) =
+        setup_oms_with_bn_state(backend.clone(), Some(6), server_node_identity).await;
+
+    for value in [2000, 5000, 7000] {
+        let uo = make_input(
+            &mut OsRng.clone(),
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        oms.add_output(uo.clone(), None).await.unwrap();
+        backend
+            .mark_outputs_as_unspent(vec![(uo.hash(&key_manager).await.unwrap(), true)])
+            .unwrap();
+    }
+
+    let fee_per_gram = MicroMinotari::from(2);
+    let fee_calc = Fee::new(*create_consensus_constants(0).transaction_weight_params());
+    let features_and_scripts_size = fee_calc.weighting().round_up_features_and_scripts_size(
+        OutputFeatures::default().get_serialized_size().unwrap() +
+            TariScript::default().get_serialized_size().unwrap() +
+            Covenant::new().get_serialized_size().unwrap(),
+    );
+    let fee = fee_calc.calculate(fee_per_gram, 1, 1, 1, features_and_scripts_size);
+    // The 5000 output pays the amount and fee exactly
+    let amount = MicroMinotari::from(5000) - fee;
+
+    let report = oms
+        .preview_coin_selection(
+            amount,
+            UtxoSelectionCriteria {
+                ordering: UtxoSelectionOrdering::BranchAndBound,
+                ..Default::default()
+            },
+            fee_per_gram,
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.strategy, UtxoSelectionOrdering::BranchAndBound);
+    assert_eq!(report.num_inputs, 1);
+    assert_eq!(report.total_value, MicroMinotari::from(5000));
+    assert_eq!(report.fee, fee);
+    assert!(!report.requires_change_output);
+    assert_eq!(report.change, MicroMinotari::zero());
+    assert_eq!(report.inputs.len(), 1);
+    assert_eq!(report.inputs[0].value, MicroMinotari::from(5000));
+    assert_eq!(
+        report.weight,
+        fee_calc.weighting().calculate(1, 1, 1, features_and_scripts_size)
+    );
+
+    let report = oms
+        .preview_coin_selection(
+            amount,
+            UtxoSelectionCriteria {
+                ordering: UtxoSelectionOrdering::LargestFirst,
+                ..Default::default()
+            },
+            fee_per_gram,
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.num_inputs, 1);
+    assert_eq!(report.total_value, MicroMinotari::from(7000));
+    assert!(report.requires_change_output);
+    assert_eq!(report.total_value, amount + report.fee + report.change);
+
+    // The configured strategy is used if the send doesn't request one
+    let report = oms
+        .preview_coin_selection(amount, UtxoSelectionCriteria::default(), fee_per_gram)
+        .await
+        .unwrap();
+    assert_eq!(report.strategy, UtxoSelectionOrdering::Default);
+}
+
 #[tokio::test]
 async fn send_not_enough_funds() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -1228,7 +1483,7 @@ async fn coin_split_with_change() {
     let split_count = 8;
     let (_tx_id, coin_split_tx, amount) = oms
         .output_manager_handle
-        .create_coin_split(vec![], 1000.into(), split_count, fee_per_gram)
+        .create_coin_split(vec![], 1000.into(), split_count, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
         .await
         .unwrap();
     assert_eq!(coin_split_tx.body.inputs().len(), 2);
@@ -1289,7 +1544,7 @@ async fn coin_split_no_change() {
         .unwrap();
     let (_tx_id, coin_split_tx, amount) = oms
         .output_manager_handle
-        .create_coin_split(vec![], 1000.into(), split_count, fee_per_gram)
+        .create_coin_split(vec![], 1000.into(), split_count, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
         .await
         .unwrap();
     assert_eq!(coin_split_tx.body.inputs().len(), 3);
@@ -1317,13 +1572,43 @@ async fn it_handles_large_coin_splits() {
 
     let (_tx_id, coin_split_tx, _amount) = oms
         .output_manager_handle
-        .create_coin_split(vec![], 10000.into(), split_count, fee_per_gram)
+        .create_coin_split(vec![], 10000.into(), split_count, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
         .await
         .unwrap();
     assert_eq!(coin_split_tx.body.inputs().len(), 1);
     assert_eq!(coin_split_tx.body.outputs().len(), split_count + 1);
 }
 
+#[tokio::test]
+async fn coin_split_outputs_belong_to_the_selected_account() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend.clone(), true).await;
+
+    let uo = make_input(&mut OsRng, 20 * T, &OutputFeatures::default(), &oms.key_manager_handle).await;
+    assert!(oms.output_manager_handle.add_output(uo.clone(), None).await.is_ok());
+    backend
+        .mark_outputs_as_unspent(vec![(uo.hash(&oms.key_manager_handle).await.unwrap(), true)])
+        .unwrap();
+
+    let account = 1;
+    let commitment = uo.commitment(&oms.key_manager_handle).await.unwrap();
+    let (tx_id, _coin_split_tx, _amount) = oms
+        .output_manager_handle
+        .create_coin_split(vec![commitment], 1000.into(), 3, MicroMinotari::from(5), account)
+        .await
+        .unwrap();
+
+    let outputs = backend.fetch_outputs_by_tx_id(tx_id).unwrap();
+    let received = outputs
+        .iter()
+        .filter(|o| o.status == OutputStatus::EncumberedToBeReceived)
+        .collect::<Vec<_>>();
+    // three split outputs and the change
+    assert_eq!(received.len(), 4);
+    assert!(received.iter().all(|o| o.account == account));
+}
+
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn test_txo_validation() {
@@ -2185,7 +2470,7 @@ async fn scan_for_recovery_test() {
         let features = OutputFeatures::default();
         let encrypted_data = oms
             .key_manager_handle
-            .encrypt_data_for_recovery(&spending_key_result, None, amount)
+            .encrypt_data_for_recovery(&spending_key_result, None, amount, None, None)
             .await
             .unwrap();
 
@@ -26,7 +26,7 @@ use minotari_wallet::output_manager_service::{
     error::OutputManagerStorageError,
     service::Balance,
     storage::{
-        database::{OutputManagerBackend, OutputManagerDatabase},
+        database::{OutputBackendQuery, OutputManagerBackend, OutputManagerDatabase},
         models::DbWalletOutput,
         sqlite_db::{OutputManagerSqliteDatabase, ReceivedOutputInfoForBatch, SpentOutputInfoForBatch},
         OutputSource,
@@ -762,3 +762,49 @@ pub async fn test_mark_as_unmined() {
     }
     assert_eq!(batch_invalid_count, batch_count);
 }
+
+#[tokio::test]
+pub async fn test_account_balances() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_memory_db_key_manager();
+    for (value, account) in [(1000, 0), (2000, 1), (3000, 1)] {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Standard, None, None)
+            .await
+            .unwrap()
+            .with_account(account);
+        db.add_unspent_output(kmo).unwrap();
+    }
+
+    assert_eq!(
+        db.get_balance(None).unwrap().available_balance,
+        MicroMinotari::from(6000)
+    );
+    assert_eq!(
+        db.get_account_balance(0, None).unwrap().available_balance,
+        MicroMinotari::from(1000)
+    );
+    assert_eq!(
+        db.get_account_balance(1, None).unwrap().available_balance,
+        MicroMinotari::from(5000)
+    );
+    assert_eq!(db.get_account_balance(2, None).unwrap(), Balance::zero());
+
+    let outputs = db
+        .fetch_outputs_by_query(OutputBackendQuery {
+            account: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert!(outputs.iter().all(|o| o.account == 1));
+}
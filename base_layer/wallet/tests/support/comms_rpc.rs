@@ -46,6 +46,8 @@ use tari_core::{
     proto::{
         base_node::{
             ChainMetadata as ChainMetadataProto,
+            EstimateFeePerGramRequest,
+            EstimateFeePerGramResponse,
             FetchMatchingUtxos,
             FetchUtxosResponse,
             GetMempoolFeePerGramStatsRequest,
@@ -53,6 +55,7 @@ use tari_core::{
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures as SignaturesProto,
+            SyncCandidateUtxosByBlockRequest,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
@@ -111,6 +114,7 @@ pub struct BaseNodeWalletRpcMockState {
     utxos: Arc<Mutex<Vec<TransactionOutput>>>,
     blocks: Arc<Mutex<HashMap<u64, BlockHeader>>>,
     get_mempool_fee_per_gram_stats: Arc<Mutex<GetMempoolFeePerGramStatsResponse>>,
+    estimate_fee_per_gram: Arc<Mutex<EstimateFeePerGramResponse>>,
     utxos_by_block: Arc<Mutex<Vec<UtxosByBlock>>>,
     sync_utxos_by_block_trigger_channel: Arc<Mutex<Option<mpsc::Receiver<usize>>>>,
 }
@@ -175,6 +179,7 @@ impl BaseNodeWalletRpcMockState {
             utxos: Arc::new(Mutex::new(Vec::new())),
             blocks: Arc::new(Mutex::new(Default::default())),
             get_mempool_fee_per_gram_stats: Default::default(),
+            estimate_fee_per_gram: Default::default(),
 
             utxos_by_block: Arc::new(Mutex::new(vec![])),
             sync_utxos_by_block_trigger_channel: Arc::new(Mutex::new(None)),
@@ -243,6 +248,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = resp;
     }
 
+    pub fn set_estimate_fee_per_gram_response(&self, resp: EstimateFeePerGramResponse) {
+        let mut lock = acquire_lock!(self.estimate_fee_per_gram);
+        *lock = resp;
+    }
+
     pub fn set_utxos_by_block(&self, utxos_by_block: Vec<UtxosByBlock>) {
         let mut lock = acquire_lock!(self.utxos_by_block);
         *lock = utxos_by_block;
@@ -850,6 +860,21 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             acquire_lock!(self.state.get_mempool_fee_per_gram_stats).clone(),
         ))
     }
+
+    async fn estimate_fee_per_gram(
+        &self,
+        _request: Request<EstimateFeePerGramRequest>,
+    ) -> Result<Response<EstimateFeePerGramResponse>, RpcStatus> {
+        Ok(Response::new(acquire_lock!(self.state.estimate_fee_per_gram).clone()))
+    }
+
+    async fn sync_candidate_utxos_by_block(
+        &self,
+        _request: Request<SyncCandidateUtxosByBlockRequest>,
+    ) -> Result<Streaming<SyncUtxosByBlockResponse>, RpcStatus> {
+        // Behave like a base node that predates filtered syncing so that scanners fall back to `sync_utxos_by_block`
+        Err(RpcStatus::unsupported_method("sync_candidate_utxos_by_block"))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -108,6 +108,8 @@ impl OutputManagerServiceMock {
                                 output: dbuo.wallet_output,
                                 tx_id: TxId::new_random(),
                                 hash: dbuo.hash,
+                                payment_reference: None,
+                                memo: None,
                             })
                         } else {
                             None
@@ -133,6 +135,8 @@ impl OutputManagerServiceMock {
                                 output: dbuo.wallet_output,
                                 tx_id: TxId::new_random(),
                                 hash: dbuo.hash,
+                                payment_reference: None,
+                                memo: None,
                             })
                         } else {
                             None
@@ -146,6 +150,14 @@ impl OutputManagerServiceMock {
                         e
                     });
             },
+            OutputManagerRequest::GetKnownOneSidedPaymentScripts => {
+                let _result = reply_tx
+                    .send(Ok(OutputManagerResponse::KnownOneSidedPaymentScripts(Vec::new())))
+                    .map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send reply");
+                        e
+                    });
+            },
             OutputManagerRequest::ValidateUtxos => {},
             _ => panic!("Output Manager Service Mock does not support this call"),
         }
@@ -74,7 +74,7 @@ pub async fn create_wallet_output_from_sender_data(
         .await
         .unwrap();
     let encrypted_data = key_manager
-        .encrypt_data_for_recovery(&test_params.spend_key_id, None, sender_data.amount.as_u64())
+        .encrypt_data_for_recovery(&test_params.spend_key_id, None, sender_data.amount.as_u64(), None, None)
         .await
         .unwrap();
     let mut utxo = WalletOutput::new(
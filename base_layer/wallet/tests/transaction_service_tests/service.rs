@@ -38,6 +38,7 @@ use futures::{
     SinkExt,
 };
 use minotari_wallet::{
+    account::models::DEFAULT_ACCOUNT_INDEX,
     base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
     connectivity_service::{
         create_wallet_connectivity_mock,
@@ -1368,7 +1369,7 @@ async fn large_coin_split_transaction() {
     let fee_per_gram = MicroMinotari::from(1);
     let split_count = 499;
     let (tx_id, coin_split_tx, amount) = alice_oms
-        .create_coin_split(vec![], 10000.into(), split_count, fee_per_gram)
+        .create_coin_split(vec![], 10000.into(), split_count, fee_per_gram, DEFAULT_ACCOUNT_INDEX)
         .await
         .unwrap();
     assert_eq!(coin_split_tx.body.inputs().len(), 1);
@@ -1612,6 +1613,8 @@ async fn send_one_sided_transaction_to_other() {
             OutputFeatures::default(),
             20.into(),
             message.clone(),
+            Vec::new(),
+            Vec::new(),
         )
         .await
         .expect("Alice sending one-sided tx to Bob");
@@ -1755,6 +1758,8 @@ async fn recover_one_sided_transaction() {
             OutputFeatures::default(),
             20.into(),
             message.clone(),
+            b"INV-0042".to_vec(),
+            b"deposit-1234".to_vec(),
         )
         .await
         .expect("Alice sending one-sided tx to Bob");
@@ -1763,6 +1768,8 @@ async fn recover_one_sided_transaction() {
         .get_completed_transaction(tx_id)
         .await
         .expect("Could not find completed one-sided tx");
+    assert_eq!(completed_tx.payment_reference, Some(b"INV-0042".to_vec()));
+    assert_eq!(completed_tx.memo, Some(b"deposit-1234".to_vec()));
     let outputs = completed_tx.transaction.body.outputs().clone();
 
     let recovered_outputs_1 = bob_oms
@@ -1772,6 +1779,8 @@ async fn recover_one_sided_transaction() {
     // Bob should be able to claim 1 output.
     assert_eq!(1, recovered_outputs_1.len());
     assert_eq!(value, recovered_outputs_1[0].output.value);
+    assert_eq!(recovered_outputs_1[0].payment_reference, Some(b"INV-0042".to_vec()));
+    assert_eq!(recovered_outputs_1[0].memo, Some(b"deposit-1234".to_vec()));
 
     // Should ignore already existing outputs
     let recovered_outputs_2 = bob_oms.scan_outputs_for_one_sided_payments(outputs).await.unwrap();
@@ -1895,7 +1904,7 @@ async fn test_htlc_send_and_claim() {
     bob_ts_interface.base_node_rpc_mock_state.set_utxos(vec![output]);
     let (tx_id_htlc, _htlc_fee, htlc_amount, tx) = bob_ts_interface
         .output_manager_service_handle
-        .create_claim_sha_atomic_swap_transaction(hash, pre_image, 20.into())
+        .create_claim_sha_atomic_swap_transaction(hash, pre_image, 20.into(), DEFAULT_ACCOUNT_INDEX)
         .await
         .unwrap();
 
@@ -1984,6 +1993,8 @@ async fn send_one_sided_transaction_to_self() {
             OutputFeatures::default(),
             20.into(),
             message.clone(),
+            Vec::new(),
+            Vec::new(),
         )
         .await
     {
@@ -2927,6 +2938,8 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        payment_reference: None,
+        memo: None,
     };
 
     let source_address = TariAddress::new(
@@ -2956,6 +2969,8 @@ async fn test_power_mode_updates() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        payment_reference: None,
+        memo: None,
     };
 
     tx_backend
@@ -5603,6 +5618,8 @@ async fn broadcast_all_completed_transactions_on_startup() {
         mined_height: None,
         mined_in_block: None,
         mined_timestamp: None,
+        payment_reference: None,
+        memo: None,
     };
 
     let completed_tx2 = CompletedTransaction {
@@ -5741,6 +5758,8 @@ async fn test_update_faux_tx_on_oms_validation() {
             uo_1.to_transaction_output(&alice_ts_interface.key_manager_handle)
                 .await
                 .unwrap(),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -5757,6 +5776,8 @@ async fn test_update_faux_tx_on_oms_validation() {
             uo_2.to_transaction_output(&alice_ts_interface.key_manager_handle)
                 .await
                 .unwrap(),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -5773,6 +5794,8 @@ async fn test_update_faux_tx_on_oms_validation() {
             uo_3.to_transaction_output(&alice_ts_interface.key_manager_handle)
                 .await
                 .unwrap(),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -5916,6 +5939,8 @@ async fn test_update_coinbase_tx_on_oms_validation() {
             uo_1.to_transaction_output(&alice_ts_interface.key_manager_handle)
                 .await
                 .unwrap(),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -5932,6 +5957,8 @@ async fn test_update_coinbase_tx_on_oms_validation() {
             uo_2.to_transaction_output(&alice_ts_interface.key_manager_handle)
                 .await
                 .unwrap(),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -5948,6 +5975,8 @@ async fn test_update_coinbase_tx_on_oms_validation() {
             uo_3.to_transaction_output(&alice_ts_interface.key_manager_handle)
                 .await
                 .unwrap(),
+            None,
+            None,
         )
         .await
         .unwrap();
@@ -6065,3 +6094,20 @@ async fn test_get_fee_per_gram_per_block_basic() {
     assert_eq!(estimates.stats, stats.into_iter().map(Into::into).collect::<Vec<_>>());
     assert_eq!(estimates.stats.len(), 1)
 }
+
+#[tokio::test]
+async fn test_estimate_fee_per_gram() {
+    let factories = CryptoFactories::default();
+    let connection = make_wallet_database_memory_connection();
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_estimate_fee_per_gram_response(base_node_proto::EstimateFeePerGramResponse { fee_per_gram: 25 });
+
+    let fee_per_gram = alice_ts_interface
+        .transaction_service_handle
+        .estimate_fee_per_gram(3)
+        .await
+        .unwrap();
+    assert_eq!(fee_per_gram, MicroMinotari::from(25));
+}
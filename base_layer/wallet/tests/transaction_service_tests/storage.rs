@@ -184,7 +184,7 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
         .await
         .unwrap();
     let encrypted_data = key_manager
-        .encrypt_data_for_recovery(&spending_key_id, None, sender.amount.as_u64())
+        .encrypt_data_for_recovery(&spending_key_id, None, sender.amount.as_u64(), None, None)
         .await
         .unwrap();
     let mut output = WalletOutput::new(
@@ -331,6 +331,8 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
             mined_height: None,
             mined_in_block: None,
             mined_timestamp: None,
+            payment_reference: Some(format!("INV-{}", i).into_bytes()),
+            memo: None,
         });
         db.complete_outbound_transaction(outbound_txs[i].tx_id, completed_txs[i].clone())
             .unwrap();
@@ -358,6 +360,13 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
         );
     }
 
+    let by_payment_reference = db.get_transactions_by_payment_reference(b"INV-0").unwrap();
+    assert_eq!(by_payment_reference.len(), 2);
+    assert!(by_payment_reference
+        .iter()
+        .all(|tx| tx.payment_reference.as_deref() == Some(b"INV-0".as_slice())));
+    assert!(db.get_transactions_by_payment_reference(b"INV-X").unwrap().is_empty());
+
     db.increment_send_count(completed_txs[0].tx_id).unwrap();
     db.increment_send_count(completed_txs[0].tx_id).unwrap();
     let retrieved_completed_tx = db.get_completed_transaction(completed_txs[0].tx_id).unwrap();
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use log::*;
 use minotari_wallet::{
+    account::error::AccountError,
     error::{WalletError, WalletStorageError},
     output_manager_service::error::{OutputManagerError, OutputManagerStorageError},
     transaction_service::error::{TransactionServiceError, TransactionStorageError},
@@ -318,6 +319,19 @@ impl From<WalletError> for LibWalletError {
                 code: 433,
                 message: format!("{:?}", w),
             },
+            // Account Errors
+            WalletError::AccountError(AccountError::AccountNotFound(_)) => Self {
+                code: 435,
+                message: format!("{:?}", w),
+            },
+            WalletError::AccountError(AccountError::AccountExists(_)) => Self {
+                code: 436,
+                message: format!("{:?}", w),
+            },
+            WalletError::AccountError(AccountError::InvalidAccountName(_)) => Self {
+                code: 437,
+                message: format!("{:?}", w),
+            },
             WalletError::WalletStorageError(WalletStorageError::FileError(_)) => Self {
                 code: 434,
                 message: format!("{:?}", w),
@@ -81,6 +81,7 @@ use log4rs::{
     encode::pattern::PatternEncoder,
 };
 use minotari_wallet::{
+    account::{models::DEFAULT_ACCOUNT_INDEX, AccountManager},
     base_node_service::config::BaseNodeServiceConfig,
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInterface},
     error::{WalletError, WalletStorageError},
@@ -88,19 +89,27 @@ use minotari_wallet::{
         error::OutputManagerError,
         storage::{
             database::{OutputBackendQuery, OutputManagerDatabase, SortDirection},
-            models::DbWalletOutput,
+            models::{DbWalletOutput, SpendingPriority},
             OutputStatus,
         },
         UtxoSelectionCriteria,
+        UtxoSelectionMode,
+        UtxoSelectionOrdering,
     },
     storage::{
         database::WalletDatabase,
         sqlite_db::wallet::WalletSqliteDatabase,
-        sqlite_utilities::{get_last_network, get_last_version, initialize_sqlite_database_backends},
+        sqlite_utilities::{
+            get_last_network,
+            get_last_version,
+            initialize_sqlite_database_backends,
+            run_migration_and_create_sqlite_connection,
+        },
     },
     transaction_service::{
         config::TransactionServiceConfig,
         error::TransactionServiceError,
+        handle::{BatchRecipient, BatchRecipientStatus},
         storage::{
             database::TransactionDatabase,
             models::{CompletedTransaction, InboundTransaction, OutboundTransaction},
@@ -110,6 +119,7 @@ use minotari_wallet::{
     wallet::{derive_comms_secret_key, read_or_create_master_seed, WalletMessageSigningDomain},
     Wallet,
     WalletConfig,
+    WalletKeyManagerSqlite,
     WalletSqlite,
 };
 use num_traits::FromPrimitive;
@@ -132,10 +142,14 @@ use tari_comms::{
     types::CommsPublicKey,
 };
 use tari_comms_dht::{store_forward::SafConfig, DbConnectionUrl, DhtConfig};
-use tari_contacts::contacts_service::{handle::ContactsServiceHandle, types::Contact};
+use tari_contacts::contacts_service::{
+    handle::ContactsServiceHandle,
+    types::{Contact, Direction, MessageBuilder},
+};
 use tari_core::{
     borsh::FromBytes,
     consensus::ConsensusManager,
+    covenants::CovenantBuilder,
     transactions::{
         tari_amount::MicroMinotari,
         transaction_components::{OutputFeatures, OutputFeaturesVersion, OutputType, RangeProofType, UnblindedOutput},
@@ -174,7 +188,7 @@ use crate::{
     callback_handler::CallbackHandler,
     enums::SeedWordPushResult,
     error::{InterfaceError, TransactionError},
-    tasks::recovery_event_monitoring,
+    tasks::{recovery_event_monitoring, recovery_sync_progress_monitoring, sync_progress_monitoring},
 };
 
 mod callback_handler;
@@ -212,6 +226,10 @@ pub struct TariUnblindedOutputs(Vec<UnblindedOutput>);
 pub struct TariContacts(Vec<TariContact>);
 
 pub type TariContact = tari_contacts::contacts_service::types::Contact;
+pub type TariChatMessage = tari_contacts::contacts_service::types::Message;
+
+pub struct TariChatMessages(Vec<TariChatMessage>);
+
 pub type TariCompletedTransaction = minotari_wallet::transaction_service::storage::models::CompletedTransaction;
 pub type TariTransactionSendStatus = minotari_wallet::transaction_service::handle::TransactionSendStatus;
 pub type TariFeePerGramStats = minotari_wallet::transaction_service::handle::FeePerGramStatsResponse;
@@ -245,6 +263,7 @@ pub struct TariWallet {
     wallet: WalletSqlite,
     runtime: Runtime,
     shutdown: Shutdown,
+    sync_progress_callback: Option<unsafe extern "C" fn(u8, u64, u64)>,
 }
 
 #[derive(Debug)]
@@ -254,6 +273,23 @@ pub struct TariCoinPreview {
     pub fee: u64,
 }
 
+#[derive(Debug)]
+#[repr(C)]
+pub struct TariUtxoImportResults {
+    pub tx_ids: *mut TariVector,
+    pub error_codes: *mut TariVector,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct TariTransactionPreview {
+    pub inputs: *mut TariVector,
+    pub input_values: *mut TariVector,
+    pub fee: u64,
+    pub change: u64,
+    pub weight: u64,
+}
+
 #[derive(Debug)]
 #[repr(C)]
 pub enum TariUtxoSort {
@@ -295,6 +331,7 @@ pub struct TariUtxo {
     pub lock_height: u64,
     pub status: u8,
     pub coinbase_extra: Vec<u8>,
+    pub spending_priority: u8,
 }
 
 impl From<DbWalletOutput> for TariUtxo {
@@ -324,6 +361,12 @@ impl From<DbWalletOutput> for TariUtxo {
                 OutputStatus::NotStored => 10,
             },
             coinbase_extra: x.wallet_output.features.coinbase_extra,
+            spending_priority: match x.spending_priority {
+                SpendingPriority::Normal => 0,
+                SpendingPriority::HtlcSpendAsap => 1,
+                SpendingPriority::Pinned => 2,
+                SpendingPriority::Frozen => 3,
+            },
         }
     }
 }
@@ -464,6 +507,27 @@ impl TariVector {
             .try_collect::<Commitment, Vec<Commitment>, InterfaceError>()
     }
 
+    /// Copies the values out of a vector of `TariTypeTag::U64`, leaving the vector itself untouched
+    fn to_u64_vec(&self) -> Result<Vec<u64>, InterfaceError> {
+        if self.tag != TariTypeTag::U64 {
+            return Err(InterfaceError::InvalidArgument(format!(
+                "expecting U64, got {}",
+                self.tag
+            )));
+        }
+
+        if self.len == 0 {
+            return Ok(Vec::new());
+        }
+        if self.ptr.is_null() {
+            return Err(InterfaceError::NullError(String::from(
+                "tari vector of u64 has null pointer",
+            )));
+        }
+
+        Ok(unsafe { std::slice::from_raw_parts(self.ptr as *const u64, self.len).to_vec() })
+    }
+
     #[allow(dead_code)]
     fn to_utxo_vec(&self) -> Result<Vec<TariUtxo>, InterfaceError> {
         if self.tag != TariTypeTag::Utxo {
@@ -563,6 +627,45 @@ pub unsafe extern "C" fn tari_vector_push_string(tv: *mut TariVector, s: *const
     ptr::replace(error_ptr, 0);
 }
 
+/// Appending a given value to the back of a vector of `TariTypeTag::U64`.
+///
+/// ## Arguments
+/// `value` - An item to push.
+///
+/// ## Returns
+///
+///
+/// # Safety
+/// `destroy_tari_vector()` must be called to free the allocated memory.
+#[no_mangle]
+pub unsafe extern "C" fn tari_vector_push_u64(tv: *mut TariVector, value: c_ulonglong, error_ptr: *mut i32) {
+    if tv.is_null() {
+        error!(target: LOG_TARGET, "tari vector pointer is null");
+        ptr::replace(
+            error_ptr,
+            LibWalletError::from(InterfaceError::NullError("vector".to_string())).code,
+        );
+        return;
+    }
+
+    let mut v = match (*tv).to_u64_vec() {
+        Ok(v) => v,
+        Err(e) => {
+            error!(target: LOG_TARGET, "{:#?}", e);
+            ptr::replace(error_ptr, LibWalletError::from(e).code);
+            return;
+        },
+    };
+
+    v.push(value);
+
+    let mut v = ManuallyDrop::new(v);
+    (*tv).len = v.len();
+    (*tv).cap = v.capacity();
+    (*tv).ptr = v.as_mut_ptr() as *mut c_void;
+    ptr::replace(error_ptr, 0);
+}
+
 /// Frees memory allocated for `TariVector`.
 ///
 /// ## Arguments
@@ -599,6 +702,44 @@ pub unsafe extern "C" fn destroy_tari_coin_preview(p: *mut TariCoinPreview) {
     }
 }
 
+/// Frees memory allocated for `TariTransactionPreview`.
+///
+/// ## Arguments
+/// `p` - The pointer to `TariTransactionPreview`
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn destroy_tari_transaction_preview(p: *mut TariTransactionPreview) {
+    if !p.is_null() {
+        let x = Box::from_raw(p);
+        destroy_tari_vector(x.inputs);
+        destroy_tari_vector(x.input_values);
+    }
+}
+
+/// Frees memory allocated for `TariUtxoImportResults`.
+///
+/// ## Arguments
+/// `p` - The pointer to `TariUtxoImportResults`
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn destroy_tari_utxo_import_results(p: *mut TariUtxoImportResults) {
+    if !p.is_null() {
+        let x = Box::from_raw(p);
+        destroy_tari_vector(x.tx_ids);
+        destroy_tari_vector(x.error_codes);
+    }
+}
+
 /// -------------------------------- Strings ------------------------------------------------ ///
 
 /// Frees memory for a char array
@@ -1596,7 +1737,7 @@ pub unsafe extern "C" fn create_tari_unblinded_output(
     let encrypted_data = if encrypted_data.is_null() {
         TariEncryptedOpenings::default()
     } else {
-        *encrypted_data
+        (*encrypted_data).clone()
     };
 
     let unblinded_output = UnblindedOutput::new_current_version(
@@ -1800,6 +1941,57 @@ pub unsafe extern "C" fn unblinded_outputs_get_at(
     Box::into_raw(Box::new((*outputs).0[position as usize].clone()))
 }
 
+/// Create an empty instance of TariUnblindedOutputs
+///
+/// ## Arguments
+/// None
+///
+/// ## Returns
+/// `*mut TariUnblindedOutputs` - Returns an empty TariUnblindedOutputs instance
+///
+/// # Safety
+/// The ```unblinded_outputs_destroy``` method must be called when finished with a TariUnblindedOutputs to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_outputs_create() -> *mut TariUnblindedOutputs {
+    Box::into_raw(Box::new(TariUnblindedOutputs(Vec::new())))
+}
+
+/// Appends a copy of a TariUnblindedOutput to TariUnblindedOutputs
+///
+/// ## Arguments
+/// `outputs` - The pointer to a TariUnblindedOutputs
+/// `output` - The pointer to the TariUnblindedOutput to append
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the output was appended
+///
+/// # Safety
+/// `output` is copied, it must still be freed with ```tari_unblinded_output_destroy```
+#[no_mangle]
+pub unsafe extern "C" fn unblinded_outputs_push(
+    outputs: *mut TariUnblindedOutputs,
+    output: *mut TariUnblindedOutput,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if outputs.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("outputs".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if output.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("output".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    (*outputs).0.push((*output).clone());
+    true
+}
+
 /// Frees memory for a TariUnblindedOutputs
 ///
 /// ## Arguments
@@ -1903,6 +2095,94 @@ pub unsafe extern "C" fn wallet_import_external_utxo_as_non_rewindable(
     }
 }
 
+/// Import a batch of external UTXOs, e.g. faucet or claimed outputs, into the wallet as non-rewindable (i.e.
+/// non-recoverable) outputs. All outputs are added in a single database transaction as EncumberedToBeReceived, and a
+/// faux completed transaction is created for each imported output to record the event. This is much faster than
+/// importing the outputs one at a time with `wallet_import_external_utxo_as_non_rewindable`.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `outputs` - The TariUnblindedOutputs to import
+/// `source_address` - The tari address of the source of the transactions, may be null
+/// `message` - The message that the transactions will have
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariUtxoImportResults` - Returns the result of each import in the order of `outputs`: a `TariVector` tagged
+/// as `TariTypeTag::U64` with the TransactionID of each generated transaction, or zero if the import failed, and a
+/// `TariVector` tagged as `TariTypeTag::I64` with the error code of each import, or zero if it succeeded. Outputs that
+/// the wallet already holds fail. Returns null if the batch could not be imported at all.
+///
+/// # Safety
+/// `destroy_tari_utxo_import_results()` must be called after use to free the allocated memory.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_import_external_utxos_as_non_rewindable(
+    wallet: *mut TariWallet,
+    outputs: *mut TariUnblindedOutputs,
+    source_address: *mut TariWalletAddress,
+    message: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariUtxoImportResults {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if outputs.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("outputs".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let source_address = if source_address.is_null() {
+        TariWalletAddress::default()
+    } else {
+        (*source_address).clone()
+    };
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let message_string = match CStr::from_ptr(message).to_str() {
+        Ok(v) => v.to_owned(),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("message".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.import_unblinded_outputs_as_non_rewindable(
+            (*outputs).0.clone(),
+            source_address,
+            message_string,
+        )) {
+        Ok(results) => {
+            let (tx_ids, error_codes): (Vec<u64>, Vec<i64>) = results
+                .into_iter()
+                .map(|result| match result {
+                    Ok(tx_id) => (tx_id.as_u64(), 0),
+                    Err(e) => (0, i64::from(LibWalletError::from(e).code)),
+                })
+                .unzip();
+            Box::into_raw(Box::new(TariUtxoImportResults {
+                tx_ids: Box::into_raw(Box::new(TariVector::from(tx_ids))),
+                error_codes: Box::into_raw(Box::new(TariVector::from(error_codes))),
+            }))
+        },
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Get the TariUnblindedOutputs from a TariWallet
 ///
 /// ## Arguments
@@ -2150,6 +2430,90 @@ pub unsafe extern "C" fn covenant_create_from_bytes(
     }
 }
 
+/// Creates a TariCovenant for a time-locked vault: until `unlock_height` the output may only be spent into an output
+/// with the same script and covenant, after which it can be spent freely
+///
+/// ## Arguments
+/// `unlock_height` - The block height from which the output can be spent freely
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `TariCovenant` - Returns the covenant
+///
+/// # Safety
+/// The ```covenant_destroy``` function must be called when finished with a TariCovenant to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn covenant_create_time_locked_vault(
+    unlock_height: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut TariCovenant {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    Box::into_raw(Box::new(CovenantBuilder::time_locked_vault(unlock_height).build()))
+}
+
+/// Creates a TariCovenant that only allows the output to be spent into an output of the given output type
+///
+/// ## Arguments
+/// `output_type` - The encoded value of the output type as a byte
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `TariCovenant` - Returns the covenant. Note that it will be ptr::null_mut() if the output type is invalid
+///
+/// # Safety
+/// The ```covenant_destroy``` function must be called when finished with a TariCovenant to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn covenant_create_output_type_restriction(
+    output_type: c_ushort,
+    error_out: *mut c_int,
+) -> *mut TariCovenant {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    match output_type.try_into().ok().and_then(OutputType::from_byte) {
+        Some(output_type) => Box::into_raw(Box::new(CovenantBuilder::output_type_restriction(output_type).build())),
+        None => {
+            error!(target: LOG_TARGET, "output_type overflowed",);
+            error = LibWalletError::from(InterfaceError::InvalidArgument("output_type".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Creates a TariCovenant that only allows the output to be spent into a one-sided payment to the given address
+///
+/// ## Arguments
+/// `address` - The pointer to a TariWalletAddress
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `TariCovenant` - Returns the covenant. Note that it will be ptr::null_mut() if the address is null
+///
+/// # Safety
+/// The ```covenant_destroy``` function must be called when finished with a TariCovenant to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn covenant_create_pinned_to_address(
+    address: *const TariWalletAddress,
+    error_out: *mut c_int,
+) -> *mut TariCovenant {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    Box::into_raw(Box::new(CovenantBuilder::pinned_to_address(&*address).build()))
+}
+
 /// Frees memory for a TariCovenant
 ///
 /// ## Arguments
@@ -2450,6 +2814,60 @@ pub unsafe extern "C" fn seed_words_get_mnemonic_word_list_for_language(
     Box::into_raw(Box::new(TariSeedWords(mnemonic_word_list_vec)))
 }
 
+/// Create a TariSeedWords instance holding the seed words of a new random wallet seed in the requested language. The
+/// seed words can be passed to `wallet_create` to create a wallet whose seed words are presented in that language.
+///
+/// ## Arguments
+/// `language` - The required language as a string, e.g. "Spanish", "ChineseSimplified" or "Japanese"
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `TariSeedWords` - Returns the seed words of the new seed, note that it returns ptr::null_mut() if the language is
+/// null or not supported
+///
+/// # Safety
+/// The `seed_words_destroy` method must be called when finished with a TariSeedWords instance from rust to prevent a
+/// memory leak
+#[no_mangle]
+pub unsafe extern "C" fn seed_words_create_random(
+    language: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariSeedWords {
+    use tari_key_manager::mnemonic::Mnemonic;
+
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if language.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("language".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let language = match CStr::from_ptr(language)
+        .to_str()
+        .map_err(|e| e.to_string())
+        .and_then(|l| TariMnemonicLanguage::from_str(l).map_err(|_| format!("'{}' language not supported", l)))
+    {
+        Ok(language) => language,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Mnemonic language error: {}", e);
+            error = LibWalletError::from(InterfaceError::InvalidArgument(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    match CipherSeed::new().to_mnemonic(language, None) {
+        Ok(seed_words) => Box::into_raw(Box::new(TariSeedWords(seed_words))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::KeyManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Gets the length of TariSeedWords
 ///
 /// ## Arguments
@@ -2925,6 +3343,289 @@ pub unsafe extern "C" fn contacts_destroy(contacts: *mut TariContacts) {
 
 /// -------------------------------------------------------------------------------------------- ///
 
+/// ----------------------------------- Chat Messages ----------------------------------------------///
+
+/// Gets the id of a TariChatMessage
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut ByteVector` - Returns a pointer to a ByteVector. Note that it returns ptr::null_mut() if message is null
+///
+/// # Safety
+/// The ```byte_vector_destroy``` method must be called when finished with a ByteVector to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_get_id(message: *mut TariChatMessage, error_out: *mut c_int) -> *mut ByteVector {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new(ByteVector((*message).message_id.clone())))
+}
+
+/// Gets the body of a TariChatMessage
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut ByteVector` - Returns a pointer to a ByteVector. Note that it returns ptr::null_mut() if message is null
+///
+/// # Safety
+/// The ```byte_vector_destroy``` method must be called when finished with a ByteVector to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_get_body(
+    message: *mut TariChatMessage,
+    error_out: *mut c_int,
+) -> *mut ByteVector {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new(ByteVector((*message).body.clone())))
+}
+
+/// Gets the TariWalletAddress of the other wallet in the conversation of a TariChatMessage
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariWalletAddress` - Returns a pointer to a TariWalletAddress. Note that it returns ptr::null_mut() if
+/// message is null
+///
+/// # Safety
+/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_get_address(
+    message: *mut TariChatMessage,
+    error_out: *mut c_int,
+) -> *mut TariWalletAddress {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*message).address.clone()))
+}
+
+/// Gets whether a TariChatMessage was sent by this wallet
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the message is outbound. Note that it returns false if message is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_is_outbound(message: *mut TariChatMessage, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    (*message).direction == Direction::Outbound
+}
+
+/// Gets the unix timestamp at which a TariChatMessage was sent
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the timestamp in seconds. Note that it returns 0 if message is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_get_sent_at(message: *mut TariChatMessage, error_out: *mut c_int) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*message).sent_at as c_ulonglong
+}
+
+/// Gets the unix timestamp of the delivery receipt of a TariChatMessage
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the timestamp in seconds, or 0 if the message has not been delivered. Note that it returns
+/// 0 if message is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_get_delivered_at(
+    message: *mut TariChatMessage,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*message).delivery_confirmation_at.unwrap_or_default() as c_ulonglong
+}
+
+/// Gets the unix timestamp of the read receipt of a TariChatMessage
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_ulonglong` - Returns the timestamp in seconds, or 0 if the message has not been read. Note that it returns 0 if
+/// message is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_get_read_at(message: *mut TariChatMessage, error_out: *mut c_int) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    (*message).read_confirmation_at.unwrap_or_default() as c_ulonglong
+}
+
+/// Frees memory for a TariChatMessage
+///
+/// ## Arguments
+/// `message` - The pointer to a TariChatMessage
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn chat_message_destroy(message: *mut TariChatMessage) {
+    if !message.is_null() {
+        drop(Box::from_raw(message))
+    }
+}
+
+/// Gets the length of TariChatMessages
+///
+/// ## Arguments
+/// `messages` - The pointer to a TariChatMessages
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_uint` - Returns number of elements in messages, zero if messages is null
+///
+/// # Safety
+/// None
+// casting here is okay as a page holds at most 2500 messages
+#[allow(clippy::cast_possible_truncation)]
+#[no_mangle]
+pub unsafe extern "C" fn chat_messages_get_length(messages: *mut TariChatMessages, error_out: *mut c_int) -> c_uint {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut len = 0;
+    if messages.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("messages".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        len = (*messages).0.len();
+    }
+    len as c_uint
+}
+
+/// Gets a TariChatMessage from TariChatMessages at position
+///
+/// ## Arguments
+/// `messages` - The pointer to a TariChatMessages
+/// `position` - The integer position
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariChatMessage` - Returns a TariChatMessage, note that it returns ptr::null_mut() if messages is null or
+/// position is invalid
+///
+/// # Safety
+/// The ```chat_message_destroy``` method must be called when finished with a TariChatMessage to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn chat_messages_get_at(
+    messages: *mut TariChatMessages,
+    position: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariChatMessage {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if messages.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("messages".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    match (*messages).0.get(position as usize) {
+        Some(message) => Box::into_raw(Box::new(message.clone())),
+        None => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Frees memory for a TariChatMessages
+///
+/// ## Arguments
+/// `messages` - The pointer to a TariChatMessages
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn chat_messages_destroy(messages: *mut TariChatMessages) {
+    if !messages.is_null() {
+        drop(Box::from_raw(messages))
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
 /// ----------------------------------- Contacts Liveness Data ----------------------------------///
 
 /// Gets the public_key from a TariContactsLivenessData
@@ -4867,6 +5568,7 @@ pub unsafe extern "C" fn comms_config_create(
                 rpc_max_simultaneous_sessions: 0,
                 rpc_max_sessions_per_peer: 0,
                 listener_liveness_check_interval: None,
+                ban_policy: Default::default(),
             };
 
             Box::into_raw(Box::new(config))
@@ -5214,6 +5916,9 @@ unsafe fn init_logging(
 ///     Orphan,                 // 4
 ///     TimeLocked,             // 5
 ///     InvalidTransaction,     // 6
+///     Oversized,              // 7
+///     Expired,                // 8
+///     Replaced,               // 9
 /// }
 /// `callback_txo_validation_complete` - The callback function pointer matching the function signature. This is called
 /// when a TXO validation process is completed. The request_key is used to identify which request this
@@ -5342,6 +6047,12 @@ pub unsafe extern "C" fn wallet_create(
         peer_seed
     };
 
+    // The seed words are presented in the language they were provided in
+    let seed_words_language = if seed_words.is_null() {
+        None
+    } else {
+        MnemonicLanguage::detect_language(&(*seed_words).0).ok()
+    };
     let recovery_seed = if seed_words.is_null() {
         None
     } else {
@@ -5424,6 +6135,9 @@ pub unsafe extern "C" fn wallet_create(
     let result = runtime.block_on(async {
         let master_seed = read_or_create_master_seed(recovery_seed, &wallet_database)
             .map_err(|err| WalletStorageError::RecoverySeedError(err.to_string()))?;
+        if let Some(language) = seed_words_language {
+            wallet_database.set_mnemonic_language(language)?;
+        }
         let comms_secret_key = derive_comms_secret_key(&master_seed)
             .map_err(|err| WalletStorageError::RecoverySeedError(err.to_string()))?;
 
@@ -5574,6 +6288,7 @@ pub unsafe extern "C" fn wallet_create(
                 wallet: w,
                 runtime,
                 shutdown,
+                sync_progress_callback: None,
             };
 
             Box::into_raw(Box::new(tari_wallet))
@@ -5664,10 +6379,93 @@ pub unsafe extern "C" fn wallet_get_last_network(config: *mut TariCommsConfig, e
     }
 }
 
-/// Retrieves the balance from a wallet
+/// Rotates the database encryption key and changes the passphrase of a wallet database that is not currently open.
+/// Every encrypted field is re-encrypted in batches of `batch_size` rows, and the legacy plaintext values of the wallet
+/// settings and client key-values are encrypted. A value in any other table that cannot be decrypted fails the rekey
+/// without changing the passphrase. If this is interrupted, call it again with the same `existing_passphrase` to
+/// finish the rekey.
+///
+/// Rekeying is offline only: the services of a running wallet each hold a copy of the current key, so this takes
+/// the database lock and fails while a `TariWallet` using the same database exists.
 ///
 /// ## Arguments
-/// `wallet` - The TariWallet pointer.
+/// `config` - The TariCommsConfig pointer used to locate the wallet database
+/// `existing_passphrase` - The current passphrase of the database
+/// `new_passphrase` - The passphrase to protect the new key with, which may be the same as the existing passphrase
+/// `batch_size` - The number of rows re-encrypted per database transaction
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// Call `wallet_destroy` on any `TariWallet` using the same database first.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_rekey_database(
+    config: *mut TariCommsConfig,
+    existing_passphrase: *const c_char,
+    new_passphrase: *const c_char,
+    batch_size: c_uint,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if config.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("config".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    let mut passphrases = Vec::with_capacity(2);
+    for (name, passphrase) in [
+        ("existing_passphrase", existing_passphrase),
+        ("new_passphrase", new_passphrase),
+    ] {
+        if passphrase.is_null() {
+            error = LibWalletError::from(InterfaceError::NullError(name.to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        }
+        match CStr::from_ptr(passphrase).to_str() {
+            Ok(v) => passphrases.push(SafePassword::from(v.to_owned())),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError(name.to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return false;
+            },
+        }
+    }
+
+    let sql_database_path = (*config)
+        .datastore_path
+        .join((*config).peer_database_name.clone())
+        .with_extension("sqlite3");
+    let result = run_migration_and_create_sqlite_connection(sql_database_path, 1)
+        .and_then(|connection| WalletSqliteDatabase::new(connection, passphrases[0].clone()))
+        .and_then(|db| WalletDatabase::new(db).rekey(&passphrases[0], &passphrases[1], batch_size as usize));
+    match result {
+        Ok(summary) => {
+            debug!(
+                target: LOG_TARGET,
+                "Database rekeyed: {} values re-encrypted, {} already rekeyed, {} legacy plaintext values encrypted",
+                summary.rekeyed,
+                summary.already_rekeyed,
+                summary.migrated_plaintext
+            );
+            true
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::WalletStorageError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Retrieves the balance from a wallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 /// ## Returns
@@ -5697,6 +6495,192 @@ pub unsafe extern "C" fn wallet_get_balance(wallet: *mut TariWallet, error_out:
     }
 }
 
+fn account_manager(wallet: &TariWallet) -> AccountManager<WalletSqliteDatabase, WalletKeyManagerSqlite> {
+    let network = wallet.wallet.network.as_network();
+    let pk = wallet.wallet.comms.node_identity().public_key().clone();
+    AccountManager::new(
+        wallet.wallet.db.clone(),
+        wallet.wallet.output_manager_service.clone(),
+        wallet.wallet.key_manager_service.clone(),
+        TariWalletAddress::new(pk, network),
+    )
+}
+
+/// Resolves the name of an account, or null for the default account, to its index
+unsafe fn resolve_account(wallet: &TariWallet, account: *const c_char) -> Result<u64, LibWalletError> {
+    if account.is_null() {
+        return Ok(DEFAULT_ACCOUNT_INDEX);
+    }
+    let account = CStr::from_ptr(account)
+        .to_str()
+        .map_err(|_| LibWalletError::from(InterfaceError::PointerError("account".to_string())))?;
+    account_manager(wallet)
+        .resolve_account(account)
+        .map_err(|e| LibWalletError::from(WalletError::AccountError(e)))
+}
+
+/// Creates a named account with its own one-sided payment address
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `name` - The name of the account. It must not be empty, `default` or the name of an existing account.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+/// ## Returns
+/// `*mut TariWalletAddress` - Returns the one-sided payment address of the new account or null if an error occurs
+///
+/// # Safety
+/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_account(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariWalletAddress {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if name.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(v) => v.to_owned(),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("name".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    let mut manager = account_manager(&*wallet);
+    let result = (*wallet).runtime.block_on(async {
+        let account = manager.create_account(name).await?;
+        manager.get_account_address(account.index).await
+    });
+    match result {
+        Ok(address) => Box::into_raw(Box::new(address)),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::AccountError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Retrieves the one-sided payment address of one account of a wallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `name` - The name of the account. An empty name or `default` selects the default account.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+/// ## Returns
+/// `*mut TariWalletAddress` - Returns the address of the account or null if an error occurs
+///
+/// # Safety
+/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_account_address(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariWalletAddress {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if name.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(v) => v.to_owned(),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("name".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    let manager = account_manager(&*wallet);
+    let result = (*wallet).runtime.block_on(async {
+        let index = manager.resolve_account(&name)?;
+        manager.get_account_address(index).await
+    });
+    match result {
+        Ok(address) => Box::into_raw(Box::new(address)),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::AccountError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Retrieves the balance of one account of a wallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `name` - The name of the account. An empty name or `default` selects the default account.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+/// ## Returns
+/// `*mut Balance` - Returns the pointer to the TariBalance or null if error occurs
+///
+/// # Safety
+/// The ```balance_destroy``` method must be called when finished with a TariBalance to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_account_balance(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariBalance {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if name.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let name = match CStr::from_ptr(name).to_str() {
+        Ok(v) => v.to_owned(),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("name".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    let mut manager = account_manager(&*wallet);
+    let result = (*wallet).runtime.block_on(async {
+        let index = manager.resolve_account(&name)?;
+        manager.get_balance(index).await
+    });
+    match result {
+        Ok(balance) => Box::into_raw(Box::new(balance)),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::AccountError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// This function returns a list of unspent UTXO values and commitments.
 ///
 /// ## Arguments
@@ -5767,6 +6751,7 @@ pub unsafe extern "C" fn wallet_get_utxos(
             TariUtxoSort::ValueAsc => ("value", Asc),
             TariUtxoSort::ValueDesc => ("value", Desc),
         }],
+        account: None,
     };
 
     match (*wallet).wallet.output_db.fetch_outputs_by_query(q) {
@@ -5837,6 +6822,7 @@ pub unsafe extern "C" fn wallet_get_all_utxos(wallet: *mut TariWallet, error_ptr
         value_min: None,
         value_max: None,
         sorting: vec![],
+        account: None,
     };
 
     match (*wallet).wallet.output_db.fetch_outputs_by_query(q) {
@@ -5859,6 +6845,152 @@ pub unsafe extern "C" fn wallet_get_all_utxos(wallet: *mut TariWallet, error_ptr
     }
 }
 
+/// This function returns the unspent outputs the wallet may use as transaction inputs, in the order in which they
+/// would be selected.
+///
+/// ## Arguments
+/// * `wallet` - The TariWallet pointer,
+/// * `dust_threshold` - A value filtering threshold. Outputs whose values are <= `dust_threshold` are not listed in the
+/// result.
+/// * `include_locked` - Also list immature, script locked and frozen outputs
+/// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+/// Functions as an out parameter.
+///
+/// ## Returns
+/// `*mut TariVector` - Returns a struct with an array pointer, length and capacity (needed for proper destruction
+/// after use).
+///
+/// # Safety
+/// `destroy_tari_vector()` must be called after use.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_select_utxos(
+    wallet: *mut TariWallet,
+    dust_threshold: u64,
+    include_locked: bool,
+    error_ptr: *mut i32,
+) -> *mut TariVector {
+    if wallet.is_null() {
+        error!(target: LOG_TARGET, "wallet pointer is null");
+        ptr::replace(
+            error_ptr,
+            LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code,
+        );
+        return ptr::null_mut();
+    }
+
+    let selection_criteria = UtxoSelectionCriteria {
+        mode: if include_locked {
+            UtxoSelectionMode::ListingOnly
+        } else {
+            UtxoSelectionMode::Safe
+        },
+        min_dust: dust_threshold,
+        ..Default::default()
+    };
+
+    match (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .output_manager_service
+            .get_spendable_outputs(selection_criteria),
+    ) {
+        Ok(outputs) => {
+            ptr::replace(error_ptr, 0);
+            Box::into_raw(Box::new(TariVector::from(outputs)))
+        },
+
+        Err(e) => {
+            error!(target: LOG_TARGET, "failed to select outputs: {:#?}", e);
+            ptr::replace(error_ptr, LibWalletError::from(WalletError::OutputManagerError(e)).code);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// This function pins, freezes or releases outputs. Frozen outputs are never selected automatically and can only be
+/// spent by passing them to `wallet_send_transaction` as explicit inputs.
+///
+/// ## Arguments
+/// * `wallet` - The TariWallet pointer
+/// * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
+///   (see `Commitment::to_hex()`)
+/// * `spending_priority` - 0 to release the outputs, 2 to pin them (selected first) or 3 to freeze them
+/// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+/// Functions as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the spending priority of all the outputs was updated
+///
+/// # Safety
+/// `TariVector` must be freed after use with `destroy_tari_vector()`
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_utxos_spending_priority(
+    wallet: *mut TariWallet,
+    commitments: *mut TariVector,
+    spending_priority: u8,
+    error_ptr: *mut i32,
+) -> bool {
+    if wallet.is_null() {
+        error!(target: LOG_TARGET, "wallet pointer is null");
+        ptr::replace(
+            error_ptr,
+            LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code,
+        );
+        return false;
+    }
+
+    let spending_priority = match spending_priority {
+        0 => SpendingPriority::Normal,
+        2 => SpendingPriority::Pinned,
+        3 => SpendingPriority::Frozen,
+        v => {
+            error!(target: LOG_TARGET, "invalid spending priority: {}", v);
+            ptr::replace(
+                error_ptr,
+                LibWalletError::from(InterfaceError::InvalidArgument("spending_priority".to_string())).code,
+            );
+            return false;
+        },
+    };
+
+    let commitments = match commitments.as_ref() {
+        None => {
+            error!(target: LOG_TARGET, "failed to obtain commitments as reference");
+            ptr::replace(
+                error_ptr,
+                LibWalletError::from(InterfaceError::NullError("commitments vector".to_string())).code,
+            );
+            return false;
+        },
+        Some(cs) => match cs.to_commitment_vec() {
+            Ok(cs) => cs,
+            Err(e) => {
+                error!(target: LOG_TARGET, "failed to convert from tari vector: {:?}", e);
+                ptr::replace(error_ptr, LibWalletError::from(e).code);
+                return false;
+            },
+        },
+    };
+
+    match (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .output_manager_service
+            .set_outputs_spending_priority(commitments, spending_priority),
+    ) {
+        Ok(()) => {
+            ptr::replace(error_ptr, 0);
+            true
+        },
+
+        Err(e) => {
+            error!(target: LOG_TARGET, "failed to set spending priority: {:#?}", e);
+            ptr::replace(error_ptr, LibWalletError::from(WalletError::OutputManagerError(e)).code);
+            false
+        },
+    }
+}
+
 /// This function will tell the wallet to do a coin split.
 ///
 /// ## Arguments
@@ -5867,6 +6999,7 @@ pub unsafe extern "C" fn wallet_get_all_utxos(wallet: *mut TariWallet, error_ptr
 ///   (see `Commitment::to_hex()`)
 /// * `number_of_splits` - The number of times to split the amount
 /// * `fee_per_gram` - The transaction fee
+/// * `account` - The name of the account that receives the split outputs, or null for the default account
 /// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
 /// Functions as an out parameter.
 ///
@@ -5881,6 +7014,7 @@ pub unsafe extern "C" fn wallet_coin_split(
     commitments: *mut TariVector,
     number_of_splits: usize,
     fee_per_gram: u64,
+    account: *const c_char,
     error_ptr: *mut i32,
 ) -> u64 {
     if wallet.is_null() {
@@ -5911,10 +7045,19 @@ pub unsafe extern "C" fn wallet_coin_split(
         },
     };
 
+    let account = match resolve_account(&*wallet, account) {
+        Ok(index) => index,
+        Err(e) => {
+            ptr::replace(error_ptr, e.code);
+            return 0;
+        },
+    };
+
     match (*wallet).runtime.block_on((*wallet).wallet.coin_split_even(
         commitments,
         number_of_splits,
         MicroMinotari(fee_per_gram),
+        account,
         String::new(),
     )) {
         Ok(tx_id) => {
@@ -5937,6 +7080,7 @@ pub unsafe extern "C" fn wallet_coin_split(
 /// * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
 ///   (see `Commitment::to_hex()`)
 /// * `fee_per_gram` - The transaction fee
+/// * `account` - The name of the account that receives the joined output, or null for the default account
 /// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
 /// Functions as an out parameter.
 ///
@@ -5950,6 +7094,7 @@ pub unsafe extern "C" fn wallet_coin_join(
     wallet: *mut TariWallet,
     commitments: *mut TariVector,
     fee_per_gram: u64,
+    account: *const c_char,
     error_ptr: *mut i32,
 ) -> u64 {
     if wallet.is_null() {
@@ -5980,10 +7125,19 @@ pub unsafe extern "C" fn wallet_coin_join(
         },
     };
 
-    match (*wallet)
-        .runtime
-        .block_on((*wallet).wallet.coin_join(commitments, fee_per_gram.into(), None))
-    {
+    let account = match resolve_account(&*wallet, account) {
+        Ok(index) => index,
+        Err(e) => {
+            ptr::replace(error_ptr, e.code);
+            return 0;
+        },
+    };
+
+    match (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .coin_join(commitments, fee_per_gram.into(), account, None),
+    ) {
         Ok(tx_id) => {
             ptr::replace(error_ptr, 0);
             tx_id.as_u64()
@@ -6161,6 +7315,85 @@ pub unsafe extern "C" fn wallet_preview_coin_split(
     }
 }
 
+/// This function will tell what the outcome of sending a transaction would be, without sending it or encumbering any
+/// outputs.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `amount` - The value to send
+/// `fee_per_gram` - The transaction fee
+/// `strategy` - The coin selection strategy: 0 for the wallet default, 1 for smallest first, 2 for largest first and 3
+/// for branch and bound
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariTransactionPreview` - A struct with the selected inputs as a `TariVector` of commitments tagged as
+/// `TariTypeTag::Commitment`, their values as a `TariVector` tagged as `TariTypeTag::U64`, the fee, the change returned
+/// to the wallet and the weight of the transaction. Returns null if an error occurred, e.g. when the available funds
+/// are insufficient.
+///
+/// # Safety
+/// `destroy_tari_transaction_preview()` must be called after use to free the allocated memory.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_preview_transaction(
+    wallet: *mut TariWallet,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    strategy: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariTransactionPreview {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let ordering = match UtxoSelectionOrdering::try_from(strategy) {
+        Ok(ordering) => ordering,
+        Err(e) => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    let selection_criteria = UtxoSelectionCriteria {
+        ordering,
+        ..Default::default()
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.preview_transaction(
+            MicroMinotari::from(amount),
+            selection_criteria,
+            MicroMinotari::from(fee_per_gram),
+        )) {
+        Ok(report) => {
+            let (commitments, values): (Vec<_>, Vec<_>) = report
+                .inputs
+                .into_iter()
+                .map(|input| (input.commitment, input.value.as_u64()))
+                .unzip();
+            Box::into_raw(Box::new(TariTransactionPreview {
+                inputs: Box::into_raw(Box::new(TariVector::from(commitments))),
+                input_values: Box::into_raw(Box::new(TariVector::from(values))),
+                fee: report.fee.as_u64(),
+                change: report.change.as_u64(),
+                weight: report.weight,
+            }))
+        },
+        Err(e) => {
+            error!(target: LOG_TARGET, "failed to preview transaction: {:?}", e);
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Signs a message using the public key of the TariWallet
 ///
 /// ## Arguments
@@ -6656,6 +7889,9 @@ pub unsafe extern "C" fn balance_destroy(balance: *mut TariBalance) {
 ///   (see `Commitment::to_hex()`)
 /// `fee_per_gram` - The transaction fee
 /// `message` - The pointer to a char array
+/// `one_sided` - Send the transaction as a one-sided payment to the stealth address of the destination
+/// `account` - The name of the account that funds the transaction and receives the change, or null for the default
+/// account
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
@@ -6673,6 +7909,7 @@ pub unsafe extern "C" fn wallet_send_transaction(
     fee_per_gram: c_ulonglong,
     message: *const c_char,
     one_sided: bool,
+    account: *const c_char,
     error_out: *mut c_int,
 ) -> c_ulonglong {
     let mut error = 0;
@@ -6689,7 +7926,7 @@ pub unsafe extern "C" fn wallet_send_transaction(
         return 0;
     }
 
-    let selection_criteria = match commitments.as_ref() {
+    let mut selection_criteria = match commitments.as_ref() {
         None => UtxoSelectionCriteria::default(),
         Some(cs) => match cs.to_commitment_vec() {
             Ok(cs) => UtxoSelectionCriteria::specific(cs),
@@ -6701,6 +7938,15 @@ pub unsafe extern "C" fn wallet_send_transaction(
         },
     };
 
+    match resolve_account(&*wallet, account) {
+        Ok(index) => selection_criteria.account = index,
+        Err(e) => {
+            error = e.code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return 0;
+        },
+    }
+
     let message_string;
     if message.is_null() {
         error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
@@ -6739,6 +7985,8 @@ pub unsafe extern "C" fn wallet_send_transaction(
                     OutputFeatures::default(),
                     MicroMinotari::from(fee_per_gram),
                     message_string,
+                    Vec::new(),
+                    Vec::new(),
                 ),
         ) {
             Ok(tx_id) => tx_id.as_u64(),
@@ -6769,6 +8017,151 @@ pub unsafe extern "C" fn wallet_send_transaction(
     }
 }
 
+/// Pays many recipients at once, e.g. to batch exchange withdrawals. All one-sided recipients are paid in a single
+/// transaction, while each interactive recipient is paid in a negotiated transaction of its own. A failed transaction
+/// only affects its own recipients.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `destinations` - A `TariVector` of "strings", tagged as `TariTypeTag::Text`, containing the hex encoded
+///   `TariWalletAddress` of each recipient
+/// `amounts` - A `TariVector` tagged as `TariTypeTag::U64` containing the amount for each recipient
+/// `one_sided` - A `TariVector` tagged as `TariTypeTag::U64` containing, for each recipient, 1 to pay it one-sided or 0
+///   to pay it with an interactive transaction
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `account` - The name of the account that funds the transactions and receives the change, or null for the default
+/// account
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariVector` - Returns a `TariVector` tagged as `TariTypeTag::U64` with the TxId paying each recipient, in the
+/// order of `destinations`, or 0 for recipients that could not be paid. Returns null if the arguments are invalid.
+///
+/// # Safety
+/// `destroy_tari_vector()` must be called after use to free the allocated memory.
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_to_many(
+    wallet: *mut TariWallet,
+    destinations: *mut TariVector,
+    amounts: *mut TariVector,
+    one_sided: *mut TariVector,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    account: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariVector {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let (destinations, amounts, one_sided) = match (destinations.as_ref(), amounts.as_ref(), one_sided.as_ref()) {
+        (Some(destinations), Some(amounts), Some(one_sided)) => {
+            match (
+                destinations.to_string_vec(),
+                amounts.to_u64_vec(),
+                one_sided.to_u64_vec(),
+            ) {
+                (Ok(destinations), Ok(amounts), Ok(one_sided)) => (destinations, amounts, one_sided),
+                (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                    error!(target: LOG_TARGET, "failed to convert from tari vector: {:?}", e);
+                    error = LibWalletError::from(e).code;
+                    ptr::swap(error_out, &mut error as *mut c_int);
+                    return ptr::null_mut();
+                },
+            }
+        },
+        _ => {
+            error = LibWalletError::from(InterfaceError::NullError("recipients".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    if destinations.len() != amounts.len() || destinations.len() != one_sided.len() {
+        error = LibWalletError::from(InterfaceError::InvalidArgument(
+            "destinations, amounts and one_sided must have the same length".to_string(),
+        ))
+        .code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let mut recipients = Vec::with_capacity(destinations.len());
+    for ((destination, amount), one_sided) in destinations.iter().zip(amounts).zip(one_sided) {
+        match TariAddress::from_hex(destination) {
+            Ok(destination) => recipients.push(BatchRecipient {
+                destination,
+                amount: MicroMinotari::from(amount),
+                one_sided: one_sided != 0,
+                payment_reference: Vec::new(),
+            }),
+            Err(e) => {
+                error = LibWalletError::from(e).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    }
+
+    let message_string = if message.is_null() {
+        String::new()
+    } else {
+        match CStr::from_ptr(message).to_str() {
+            Ok(v) => v.to_owned(),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("message".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    };
+
+    let selection_criteria = match resolve_account(&*wallet, account) {
+        Ok(account) => UtxoSelectionCriteria {
+            account,
+            ..Default::default()
+        },
+        Err(e) => {
+            error = e.code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.send_to_many(
+            recipients,
+            selection_criteria,
+            MicroMinotari::from(fee_per_gram),
+            message_string,
+        )) {
+        Ok(statuses) => {
+            let tx_ids = statuses
+                .into_iter()
+                .map(|status| match status {
+                    BatchRecipientStatus::Sent(tx_id) => tx_id.as_u64(),
+                    BatchRecipientStatus::Failed(e) => {
+                        warn!(target: LOG_TARGET, "Batch payment to a recipient failed: {}", e);
+                        0
+                    },
+                })
+                .collect::<Vec<u64>>();
+            Box::into_raw(Box::new(TariVector::from(tx_ids)))
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Gets a fee estimate for an amount
 ///
 /// ## Arguments
@@ -6946,7 +8339,161 @@ pub unsafe extern "C" fn wallet_get_contacts(wallet: *mut TariWallet, error_out:
         Err(e) => {
             error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
             ptr::swap(error_out, &mut error as *mut c_int);
-            ptr::null_mut()
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Sends an end-to-end encrypted chat message to a wallet. If the wallet is offline the message is held by the network
+/// and delivered once it comes back online.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `destination` - The TariWalletAddress pointer of the wallet to send the message to
+/// `body` - The ByteVector pointer of the message body
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut ByteVector` - Returns the id of the sent message, note that it returns ptr::null_mut() if any argument is
+/// null or an error is encountered
+///
+/// # Safety
+/// The ```byte_vector_destroy``` method must be called when finished with a ByteVector to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_chat_message(
+    wallet: *mut TariWallet,
+    destination: *mut TariWalletAddress,
+    body: *mut ByteVector,
+    error_out: *mut c_int,
+) -> *mut ByteVector {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if destination.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("destination".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if body.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("body".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let mut message = MessageBuilder::new().address((*destination).clone()).build();
+    message.body = (*body).0.clone();
+    let message_id = message.message_id.clone();
+    let mut contacts_service = (*wallet).wallet.contacts_service.clone();
+    match (*wallet).runtime.block_on(contacts_service.send_message(message)) {
+        Ok(_) => Box::into_raw(Box::new(ByteVector(message_id))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Gets a page of the chat messages exchanged with a wallet, ordered by the time they were sent with the newest first
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `address` - The TariWalletAddress pointer of the other wallet in the conversation
+/// `limit` - The number of messages per page, defaults to 35 if 0
+/// `page` - The page to return, starting at 0
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariChatMessages` - Returns the messages, note that it returns ptr::null_mut() if wallet or address is null
+/// or an error is encountered
+///
+/// # Safety
+/// The ```chat_messages_destroy``` method must be called when finished with a TariChatMessages to prevent a memory
+/// leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_chat_messages(
+    wallet: *mut TariWallet,
+    address: *mut TariWalletAddress,
+    limit: c_uint,
+    page: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariChatMessages {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let mut contacts_service = (*wallet).wallet.contacts_service.clone();
+    match (*wallet).runtime.block_on(contacts_service.get_messages(
+        (*address).clone(),
+        u64::from(limit),
+        u64::from(page),
+    )) {
+        Ok(messages) => Box::into_raw(Box::new(TariChatMessages(messages))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Sends a read receipt for a received chat message
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `message` - The TariChatMessage pointer of the message that was read
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_chat_read_receipt(
+    wallet: *mut TariWallet,
+    message: *mut TariChatMessage,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    let mut contacts_service = (*wallet).wallet.contacts_service.clone();
+    match (*wallet)
+        .runtime
+        .block_on(contacts_service.send_read_confirmation((*message).address.clone(), (*message).message_id.clone()))
+    {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
         },
     }
 }
@@ -7758,7 +9305,8 @@ pub unsafe extern "C" fn wallet_restart_transaction_broadcast(wallet: *mut TariW
     }
 }
 
-/// Gets the seed words representing the seed private key of the provided `TariWallet`.
+/// Gets the seed words representing the seed private key of the provided `TariWallet`, in the language of the seed
+/// words the wallet was created with or English if it was created without seed words.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
@@ -7782,7 +9330,15 @@ pub unsafe extern "C" fn wallet_get_seed_words(wallet: *mut TariWallet, error_ou
         return ptr::null_mut();
     }
 
-    match (*wallet).wallet.get_seed_words(&MnemonicLanguage::English) {
+    let language = match (*wallet).wallet.db.get_mnemonic_language() {
+        Ok(language) => language.unwrap_or(MnemonicLanguage::English),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::WalletStorageError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+    match (*wallet).wallet.get_seed_words(&language) {
         Ok(seed_words) => Box::into_raw(Box::new(TariSeedWords(seed_words))),
         Err(e) => {
             error = LibWalletError::from(e).code;
@@ -8176,6 +9732,12 @@ pub unsafe extern "C" fn wallet_start_recovery(
         .build_with_wallet(&(*wallet).wallet, shutdown_signal);
 
     let event_stream = recovery_task.get_event_receiver();
+    if let Some(sync_progress_callback) = (*wallet).sync_progress_callback {
+        (*wallet).runtime.spawn(recovery_sync_progress_monitoring(
+            recovery_task.get_event_receiver(),
+            sync_progress_callback,
+        ));
+    }
     let recovery_join_handle = (*wallet).runtime.spawn(recovery_task.run());
 
     // Spawn a task to monitor the recovery process events and call the callback appropriately
@@ -8188,6 +9750,77 @@ pub unsafe extern "C" fn wallet_start_recovery(
     true
 }
 
+/// Registers a callback that reports the progress of the wallet's sync with the chain, so that clients can render
+/// progress without polling. Only one callback can be registered for the lifetime of the wallet.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `sync_progress_callback` - The callback function pointer. The first argument of the callback is an event enum
+/// encoded as a u8 as follows:
+/// ```
+/// enum SyncProgressEvent {
+///     ScanningProgress,            // 0
+///     ScanningCompleted,           // 1
+///     ScanningFailed,              // 2
+///     RecoveryProgress,            // 3
+///     RecoveryCompleted,           // 4
+///     BaseNodeConnectivityChanged, // 5
+/// }
+/// ```
+/// The meaning of the second and third arguments for each event are as follows:
+///     - ScanningProgress, height scanned, chain tip height
+///     - ScanningCompleted, height scanned, number of one-sided outputs found
+///     - ScanningFailed, number of retries, retry limit (0, 0 if the scanner exited)
+///     - RecoveryProgress, height scanned, chain tip height
+///     - RecoveryCompleted, number of outputs recovered, MicroMinotari recovered
+///     - BaseNodeConnectivityChanged, connectivity status as for `callback_connectivity_status`, 0
+///
+/// Scanning events are reported for the background scanner that detects one-sided payments, and recovery events for
+/// recoveries started with `wallet_start_recovery` after the callback was registered. The current connectivity status
+/// is reported as soon as the callback is registered.
+///
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_register_sync_progress_callback(
+    wallet: *mut TariWallet,
+    sync_progress_callback: unsafe extern "C" fn(u8, u64, u64),
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if (*wallet).sync_progress_callback.is_some() {
+        error = LibWalletError::from(InterfaceError::InvalidArgument(
+            "A sync progress callback is already registered".to_string(),
+        ))
+        .code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    (*wallet).sync_progress_callback = Some(sync_progress_callback);
+    let mut utxo_scanner_service = (*wallet).wallet.utxo_scanner_service.clone();
+    (*wallet).runtime.spawn(sync_progress_monitoring(
+        utxo_scanner_service.get_event_receiver(),
+        (*wallet).wallet.wallet_connectivity.get_connectivity_status_watch(),
+        sync_progress_callback,
+        (*wallet).shutdown.to_signal(),
+    ));
+
+    true
+}
+
 /// Set the text message that is applied to a detected One-Side payment transaction when it is scanned from the
 /// blockchain
 ///
@@ -9288,6 +10921,42 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_covenant_create_from_templates() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+
+            let covenant = covenant_create_time_locked_vault(100, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(*covenant, CovenantBuilder::time_locked_vault(100).build());
+            covenant_destroy(covenant);
+
+            let covenant =
+                covenant_create_output_type_restriction(c_ushort::from(OutputType::Burn.as_byte()), error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(
+                *covenant,
+                covenant!(field_eq(@field::features_output_type, @output_type(Burn)))
+            );
+            covenant_destroy(covenant);
+
+            let covenant = covenant_create_output_type_restriction(c_ushort::MAX, error_ptr);
+            assert_ne!(error, 0);
+            assert!(covenant.is_null());
+
+            let address = TariWalletAddress::new(PublicKey::default(), Network::LocalNet);
+            let covenant = covenant_create_pinned_to_address(&address, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(*covenant, CovenantBuilder::pinned_to_address(&address).build());
+            covenant_destroy(covenant);
+
+            let covenant = covenant_create_pinned_to_address(ptr::null(), error_ptr);
+            assert_ne!(error, 0);
+            assert!(covenant.is_null());
+        }
+    }
+
     #[test]
     fn test_encrypted_data_empty() {
         unsafe {
@@ -9482,6 +11151,71 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_chat_messages() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            let address = TariWalletAddress::new(
+                PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+                Network::LocalNet,
+            );
+            let mut message = MessageBuilder::new().address(address.clone()).build();
+            message.body = b"hello".to_vec();
+            message.direction = Direction::Inbound;
+            message.sent_at = 1_700_000_000;
+            message.delivery_confirmation_at = Some(1_700_000_010);
+            let messages = Box::into_raw(Box::new(TariChatMessages(vec![message.clone()])));
+
+            assert_eq!(chat_messages_get_length(messages, error_ptr), 1);
+            let chat_message = chat_messages_get_at(messages, 0, error_ptr);
+            assert_eq!(error, 0);
+            assert!(chat_messages_get_at(messages, 1, error_ptr).is_null());
+            assert_eq!(error, LibWalletError::from(InterfaceError::PositionInvalidError).code);
+
+            let body = chat_message_get_body(chat_message, error_ptr);
+            assert_eq!((*body).0, b"hello".to_vec());
+            let message_id = chat_message_get_id(chat_message, error_ptr);
+            assert_eq!((*message_id).0, message.message_id);
+            let message_address = chat_message_get_address(chat_message, error_ptr);
+            assert_eq!(*message_address, address);
+            assert!(!chat_message_is_outbound(chat_message, error_ptr));
+            assert_eq!(chat_message_get_sent_at(chat_message, error_ptr), 1_700_000_000);
+            assert_eq!(chat_message_get_delivered_at(chat_message, error_ptr), 1_700_000_010);
+            assert_eq!(chat_message_get_read_at(chat_message, error_ptr), 0);
+
+            assert!(chat_message_get_body(ptr::null_mut(), error_ptr).is_null());
+            assert_eq!(
+                error,
+                LibWalletError::from(InterfaceError::NullError("message".to_string())).code
+            );
+
+            byte_vector_destroy(body);
+            byte_vector_destroy(message_id);
+            tari_address_destroy(message_address);
+            chat_message_destroy(chat_message);
+            chat_messages_destroy(messages);
+        }
+    }
+
+    #[test]
+    fn test_register_sync_progress_callback_dont_panic() {
+        unsafe extern "C" fn sync_progress_callback(_event: u8, _first: u64, _second: u64) {}
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            assert!(!wallet_register_sync_progress_callback(
+                ptr::null_mut(),
+                sync_progress_callback,
+                error_ptr
+            ));
+            assert_eq!(
+                error,
+                LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code
+            );
+        }
+    }
+
     #[test]
     fn test_contact_dont_panic() {
         unsafe {
@@ -9890,6 +11624,41 @@ mod test {
         }
     }
 
+    #[test]
+    pub fn test_seed_words_create_random() {
+        use tari_key_manager::mnemonic::Mnemonic;
+
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+
+            for language in [
+                MnemonicLanguage::Spanish,
+                MnemonicLanguage::ChineseSimplified,
+                MnemonicLanguage::Japanese,
+            ] {
+                let language_str: *const c_char =
+                    CString::into_raw(CString::new(language.to_string()).unwrap()) as *const c_char;
+                let seed_words = seed_words_create_random(language_str, error_ptr);
+                assert_eq!(error, 0);
+                assert_eq!(seed_words_get_length(seed_words, error_ptr), 24);
+                assert_eq!(MnemonicLanguage::detect_language(&(*seed_words).0).unwrap(), language);
+                assert!(CipherSeed::from_mnemonic(&(*seed_words).0, None).is_ok());
+                seed_words_destroy(seed_words);
+                string_destroy(language_str as *mut c_char);
+            }
+
+            let language_str: *const c_char = CString::into_raw(CString::new("Klingon").unwrap()) as *const c_char;
+            let seed_words = seed_words_create_random(language_str, error_ptr);
+            assert!(seed_words.is_null());
+            assert_eq!(
+                error,
+                LibWalletError::from(InterfaceError::InvalidArgument(String::new())).code
+            );
+            string_destroy(language_str as *mut c_char);
+        }
+    }
+
     #[test]
     #[allow(clippy::too_many_lines)]
     pub fn test_seed_words() {
@@ -10363,7 +12132,7 @@ mod test {
                 .collect::<Vec<String>>();
 
             let commitments = Box::into_raw(Box::new(TariVector::from(payload)));
-            let result = wallet_coin_join(alice_wallet, commitments, 5, error_ptr);
+            let result = wallet_coin_join(alice_wallet, commitments, 5, ptr::null(), error_ptr);
             assert_eq!(error, 0);
             assert!(result > 0);
 
@@ -10385,6 +12154,151 @@ mod test {
         }
     }
 
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn test_wallet_set_utxos_spending_priority() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            let mut recovery_in_progress = true;
+            let recovery_in_progress_ptr = &mut recovery_in_progress as *mut bool;
+
+            let secret_key_alice = private_key_generate();
+            let db_name_alice = CString::new(random::string(8).as_str()).unwrap();
+            let db_name_alice_str: *const c_char = CString::into_raw(db_name_alice) as *const c_char;
+            let alice_temp_dir = tempdir().unwrap();
+            let db_path_alice = CString::new(alice_temp_dir.path().to_str().unwrap()).unwrap();
+            let db_path_alice_str: *const c_char = CString::into_raw(db_path_alice) as *const c_char;
+            let transport_config_alice = transport_memory_create();
+            let address_alice = transport_memory_get_address(transport_config_alice, error_ptr);
+            let address_alice_str = CStr::from_ptr(address_alice).to_str().unwrap().to_owned();
+            let address_alice_str: *const c_char = CString::new(address_alice_str).unwrap().into_raw() as *const c_char;
+            let network = CString::new(NETWORK_STRING).unwrap();
+            let network_str: *const c_char = CString::into_raw(network) as *const c_char;
+
+            let alice_config = comms_config_create(
+                address_alice_str,
+                transport_config_alice,
+                db_name_alice_str,
+                db_path_alice_str,
+                20,
+                10800,
+                error_ptr,
+            );
+
+            let passphrase: *const c_char =
+                CString::into_raw(CString::new("J-bay open corona").unwrap()) as *const c_char;
+            let dns_string: *const c_char = CString::into_raw(CString::new("").unwrap()) as *const c_char;
+            let alice_wallet = wallet_create(
+                alice_config,
+                ptr::null(),
+                0,
+                0,
+                0,
+                passphrase,
+                ptr::null(),
+                network_str,
+                dns_string,
+                false,
+                received_tx_callback,
+                received_tx_reply_callback,
+                received_tx_finalized_callback,
+                broadcast_callback,
+                mined_callback,
+                mined_unconfirmed_callback,
+                scanned_callback,
+                scanned_unconfirmed_callback,
+                transaction_send_result_callback,
+                tx_cancellation_callback,
+                txo_validation_complete_callback,
+                contacts_liveness_data_updated_callback,
+                balance_updated_callback,
+                transaction_validation_complete_callback,
+                saf_messages_received_callback,
+                connectivity_status_callback,
+                base_node_state_callback,
+                recovery_in_progress_ptr,
+                error_ptr,
+            );
+            assert_eq!(error, 0);
+
+            for i in 0..10 {
+                let uo = (*alice_wallet).runtime.block_on(create_test_input(
+                    (1000 * i).into(),
+                    0,
+                    &(*alice_wallet).wallet.key_manager_service,
+                    vec![],
+                ));
+                (*alice_wallet)
+                    .runtime
+                    .block_on(
+                        (*alice_wallet)
+                            .wallet
+                            .output_manager_service
+                            .add_output(uo.clone(), None),
+                    )
+                    .unwrap();
+                (*alice_wallet)
+                    .wallet
+                    .output_db
+                    .mark_outputs_as_unspent(vec![(
+                        (*alice_wallet)
+                            .runtime
+                            .block_on(uo.hash(&(*alice_wallet).wallet.key_manager_service))
+                            .unwrap(),
+                        true,
+                    )])
+                    .unwrap();
+            }
+
+            let outputs = wallet_select_utxos(alice_wallet, 0, false, error_ptr);
+            let utxos: &[TariUtxo] = slice::from_raw_parts_mut((*outputs).ptr as *mut TariUtxo, (*outputs).len);
+            assert_eq!(error, 0);
+            assert_eq!(utxos.len(), 9);
+            let payload = vec![CStr::from_ptr(utxos[0].commitment).to_str().unwrap().to_owned()];
+            destroy_tari_vector(outputs);
+
+            let commitments = Box::into_raw(Box::new(TariVector::from(payload)));
+            assert!(!wallet_set_utxos_spending_priority(
+                alice_wallet,
+                commitments,
+                1,
+                error_ptr
+            ));
+            assert_ne!(error, 0);
+            assert!(wallet_set_utxos_spending_priority(
+                alice_wallet,
+                commitments,
+                3,
+                error_ptr
+            ));
+            assert_eq!(error, 0);
+            destroy_tari_vector(commitments);
+
+            // Frozen outputs are only listed when locked outputs are included
+            let outputs = wallet_select_utxos(alice_wallet, 0, false, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!((*outputs).len, 8);
+            destroy_tari_vector(outputs);
+
+            let outputs = wallet_select_utxos(alice_wallet, 0, true, error_ptr);
+            let utxos: &[TariUtxo] = slice::from_raw_parts_mut((*outputs).ptr as *mut TariUtxo, (*outputs).len);
+            assert_eq!(error, 0);
+            assert_eq!(utxos.len(), 9);
+            assert_eq!(utxos[0].spending_priority, 3);
+            destroy_tari_vector(outputs);
+
+            string_destroy(network_str as *mut c_char);
+            string_destroy(db_name_alice_str as *mut c_char);
+            string_destroy(db_path_alice_str as *mut c_char);
+            string_destroy(address_alice_str as *mut c_char);
+            private_key_destroy(secret_key_alice);
+            transport_config_destroy(transport_config_alice);
+            comms_config_destroy(alice_config);
+            wallet_destroy(alice_wallet);
+        }
+    }
+
     #[test]
     #[allow(clippy::too_many_lines, clippy::needless_collect)]
     fn test_wallet_coin_join() {
@@ -10529,7 +12443,7 @@ mod test {
                 .collect::<Vec<String>>();
 
             let commitments = Box::into_raw(Box::new(TariVector::from(payload)));
-            let result = wallet_coin_join(alice_wallet, commitments, 5, error_ptr);
+            let result = wallet_coin_join(alice_wallet, commitments, 5, ptr::null(), error_ptr);
             assert_eq!(error, 0);
             assert!(result > 0);
 
@@ -10748,7 +12662,7 @@ mod test {
 
             let commitments = Box::into_raw(Box::new(TariVector::from(payload)));
 
-            let result = wallet_coin_split(alice_wallet, commitments, 3, 5, error_ptr);
+            let result = wallet_coin_split(alice_wallet, commitments, 3, 5, ptr::null(), error_ptr);
             assert_eq!(error, 0);
             assert!(result > 0);
 
@@ -10971,6 +12885,28 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_tari_vector_push_u64() {
+        let mut error = 0;
+
+        unsafe {
+            let tv = create_tari_vector(TariTypeTag::U64);
+            for value in [5, 0, u64::MAX] {
+                tari_vector_push_u64(tv, value, &mut error as *mut c_int);
+                assert_eq!(error, 0);
+            }
+            assert_eq!((*tv).tag, TariTypeTag::U64);
+            assert_eq!((*tv).to_u64_vec().unwrap(), vec![5, 0, u64::MAX]);
+
+            let text = create_tari_vector(TariTypeTag::Text);
+            tari_vector_push_u64(text, 1, &mut error as *mut c_int);
+            assert_ne!(error, 0);
+
+            destroy_tari_vector(tv);
+            destroy_tari_vector(text);
+        }
+    }
+
     #[test]
     fn test_com_pub_sig_create() {
         unsafe {
@@ -11227,7 +13163,26 @@ mod test {
             assert_eq!((*outputs).0.len(), 0);
             assert_eq!(unblinded_outputs_get_length(outputs, error_ptr), 0);
 
+            // Importing the same output again in a batch reports it as a duplicate
+            let outputs_to_import = unblinded_outputs_create();
+            assert!(unblinded_outputs_push(outputs_to_import, tari_utxo, error_ptr));
+            assert_eq!(unblinded_outputs_get_length(outputs_to_import, error_ptr), 1);
+            let import_results = wallet_import_external_utxos_as_non_rewindable(
+                wallet_ptr,
+                outputs_to_import,
+                source_address_ptr,
+                message_ptr,
+                error_ptr,
+            );
+            assert_eq!(error, 0);
+            assert_eq!((*(*import_results).tx_ids).to_u64_vec().unwrap(), vec![0]);
+            assert_eq!((*(*import_results).error_codes).len, 1);
+            let error_codes = std::slice::from_raw_parts((*(*import_results).error_codes).ptr as *const i64, 1);
+            assert_ne!(error_codes[0], 0);
+
             // Cleanup
+            destroy_tari_utxo_import_results(import_results);
+            unblinded_outputs_destroy(outputs_to_import);
             tari_unblinded_output_destroy(tari_utxo);
             unblinded_outputs_destroy(outputs);
             string_destroy(message_ptr as *mut c_char);
@@ -11567,6 +13522,7 @@ mod test {
                             address: bob_wallet_address.clone(),
                             direction: Direction::Outbound,
                             stored_at: u64::from(i),
+                            sent_at: u64::from(i),
                             delivery_confirmation_at: None,
                             read_confirmation_at: None,
                             message_id: vec![i],
@@ -11583,6 +13539,7 @@ mod test {
                             address: alice_wallet_address.clone(),
                             direction: Direction::Outbound,
                             stored_at: u64::from(i),
+                            sent_at: u64::from(i),
                             delivery_confirmation_at: None,
                             read_confirmation_at: None,
                             message_id: vec![i],
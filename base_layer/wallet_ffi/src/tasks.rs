@@ -21,9 +21,17 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use log::*;
-use minotari_wallet::{error::WalletError, utxo_scanner_service::handle::UtxoScannerEvent};
+use minotari_wallet::{
+    connectivity_service::OnlineStatus,
+    error::WalletError,
+    utxo_scanner_service::handle::UtxoScannerEvent,
+};
+use tari_shutdown::ShutdownSignal;
 use tari_utilities::hex::Hex;
-use tokio::{sync::broadcast, task::JoinHandle};
+use tokio::{
+    sync::{broadcast, watch},
+    task::JoinHandle,
+};
 
 const LOG_TARGET: &str = "wallet_ffi";
 
@@ -38,6 +46,16 @@ enum RecoveryEvent {
     RecoveryFailed,             // 6
 }
 
+/// Events that the sync progress callback will report
+enum SyncProgressEvent {
+    ScanningProgress,            // 0
+    ScanningCompleted,           // 1
+    ScanningFailed,              // 2
+    RecoveryProgress,            // 3
+    RecoveryCompleted,           // 4
+    BaseNodeConnectivityChanged, // 5
+}
+
 #[allow(clippy::too_many_lines)]
 pub async fn recovery_event_monitoring(
     mut event_stream: broadcast::Receiver<UtxoScannerEvent>,
@@ -171,3 +189,84 @@ pub async fn recovery_event_monitoring(
         },
     }
 }
+
+pub async fn sync_progress_monitoring(
+    mut scanner_event_stream: broadcast::Receiver<UtxoScannerEvent>,
+    mut connectivity_status_watch: watch::Receiver<OnlineStatus>,
+    sync_progress_callback: unsafe extern "C" fn(u8, u64, u64),
+    mut shutdown_signal: ShutdownSignal,
+) {
+    let report = |event: SyncProgressEvent, first: u64, second: u64| unsafe {
+        (sync_progress_callback)(event as u8, first, second);
+    };
+    let status = *connectivity_status_watch.borrow();
+    report(SyncProgressEvent::BaseNodeConnectivityChanged, status as u64, 0);
+    loop {
+        tokio::select! {
+            event = scanner_event_stream.recv() => {
+                match event {
+                    Ok(UtxoScannerEvent::Progress { current_height, tip_height }) => {
+                        report(SyncProgressEvent::ScanningProgress, current_height, tip_height);
+                    },
+                    Ok(UtxoScannerEvent::Completed { final_height, num_recovered, .. }) => {
+                        report(SyncProgressEvent::ScanningCompleted, final_height, num_recovered);
+                    },
+                    Ok(UtxoScannerEvent::ScanningRoundFailed { num_retries, retry_limit, .. }) => {
+                        report(SyncProgressEvent::ScanningFailed, num_retries as u64, retry_limit as u64);
+                    },
+                    Ok(UtxoScannerEvent::ScanningFailed) => report(SyncProgressEvent::ScanningFailed, 0, 0),
+                    Ok(_) => {},
+                    Err(broadcast::error::RecvError::Closed) => break,
+                    Err(e) => {
+                        // Event lagging
+                        warn!(target: LOG_TARGET, "{}", e);
+                    },
+                }
+            },
+            Ok(_) = connectivity_status_watch.changed() => {
+                let status = *connectivity_status_watch.borrow();
+                report(SyncProgressEvent::BaseNodeConnectivityChanged, status as u64, 0);
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Sync progress monitoring shutting down because of the shutdown signal");
+                break;
+            },
+        }
+    }
+}
+
+pub async fn recovery_sync_progress_monitoring(
+    mut event_stream: broadcast::Receiver<UtxoScannerEvent>,
+    sync_progress_callback: unsafe extern "C" fn(u8, u64, u64),
+) {
+    loop {
+        match event_stream.recv().await {
+            Ok(UtxoScannerEvent::Progress {
+                current_height,
+                tip_height,
+            }) => unsafe {
+                (sync_progress_callback)(SyncProgressEvent::RecoveryProgress as u8, current_height, tip_height);
+            },
+            Ok(UtxoScannerEvent::Completed {
+                num_recovered,
+                value_recovered,
+                ..
+            }) => {
+                unsafe {
+                    (sync_progress_callback)(
+                        SyncProgressEvent::RecoveryCompleted as u8,
+                        num_recovered,
+                        u64::from(value_recovered),
+                    );
+                }
+                break;
+            },
+            Ok(_) => {},
+            Err(broadcast::error::RecvError::Closed) => break,
+            Err(e) => {
+                // Event lagging
+                warn!(target: LOG_TARGET, "{}", e);
+            },
+        }
+    }
+}
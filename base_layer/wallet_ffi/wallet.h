@@ -90,6 +90,8 @@ struct FeePerGramStatsResponse;
 
 struct InboundTransaction;
 
+struct Message;
+
 struct OutboundTransaction;
 
 /**
@@ -160,6 +162,8 @@ struct TariAddress;
 
 struct TariBaseNodeState;
 
+struct TariChatMessages;
+
 struct TariCompletedTransactions;
 
 struct TariContacts;
@@ -213,6 +217,19 @@ struct TariCoinPreview {
   uint64_t fee;
 };
 
+struct TariUtxoImportResults {
+  struct TariVector *tx_ids;
+  struct TariVector *error_codes;
+};
+
+struct TariTransactionPreview {
+  struct TariVector *inputs;
+  struct TariVector *input_values;
+  uint64_t fee;
+  uint64_t change;
+  uint64_t weight;
+};
+
 typedef struct TransactionKernel TariTransactionKernel;
 
 /**
@@ -322,6 +339,8 @@ typedef struct EncryptedData TariEncryptedOpenings;
 
 typedef struct Contact TariContact;
 
+typedef struct Message TariChatMessage;
+
 typedef struct ContactsLivenessData TariContactsLivenessData;
 
 typedef struct CompletedTransaction TariCompletedTransaction;
@@ -350,6 +369,7 @@ struct TariUtxo {
   uint64_t lock_height;
   uint8_t status;
   struct Vec_u8 coinbase_extra;
+  uint8_t spending_priority;
 };
 
 #ifdef __cplusplus
@@ -384,6 +404,20 @@ struct TariVector *create_tari_vector(enum TariTypeTag tag);
  */
 void tari_vector_push_string(struct TariVector *tv, const char *s, int32_t *error_ptr);
 
+/**
+ * Appending a given value to the back of a vector of `TariTypeTag::U64`.
+ *
+ * ## Arguments
+ * `value` - An item to push.
+ *
+ * ## Returns
+ *
+ *
+ * # Safety
+ * `destroy_tari_vector()` must be called to free the allocated memory.
+ */
+void tari_vector_push_u64(struct TariVector *tv, unsigned long long value, int32_t *error_ptr);
+
 /**
  * Frees memory allocated for `TariVector`.
  *
@@ -412,6 +446,34 @@ void destroy_tari_vector(struct TariVector *v);
  */
 void destroy_tari_coin_preview(struct TariCoinPreview *p);
 
+/**
+ * Frees memory allocated for `TariTransactionPreview`.
+ *
+ * ## Arguments
+ * `p` - The pointer to `TariTransactionPreview`
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void destroy_tari_transaction_preview(struct TariTransactionPreview *p);
+
+/**
+ * Frees memory allocated for `TariUtxoImportResults`.
+ *
+ * ## Arguments
+ * `p` - The pointer to `TariUtxoImportResults`
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void destroy_tari_utxo_import_results(struct TariUtxoImportResults *p);
+
 /**
  * -------------------------------- Strings ------------------------------------------------ ///
  * Frees memory for a char array
@@ -982,6 +1044,40 @@ TariUnblindedOutput *unblinded_outputs_get_at(struct TariUnblindedOutputs *outpu
                                               unsigned int position,
                                               int *error_out);
 
+/**
+ * Create an empty instance of TariUnblindedOutputs
+ *
+ * ## Arguments
+ * None
+ *
+ * ## Returns
+ * `*mut TariUnblindedOutputs` - Returns an empty TariUnblindedOutputs instance
+ *
+ * # Safety
+ * The ```unblinded_outputs_destroy``` method must be called when finished with a TariUnblindedOutputs to prevent a
+ * memory leak
+ */
+struct TariUnblindedOutputs *unblinded_outputs_create(void);
+
+/**
+ * Appends a copy of a TariUnblindedOutput to TariUnblindedOutputs
+ *
+ * ## Arguments
+ * `outputs` - The pointer to a TariUnblindedOutputs
+ * `output` - The pointer to the TariUnblindedOutput to append
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the output was appended
+ *
+ * # Safety
+ * `output` is copied, it must still be freed with ```tari_unblinded_output_destroy```
+ */
+bool unblinded_outputs_push(struct TariUnblindedOutputs *outputs,
+                            TariUnblindedOutput *output,
+                            int *error_out);
+
 /**
  * Frees memory for a TariUnblindedOutputs
  *
@@ -1029,6 +1125,35 @@ unsigned long long wallet_import_external_utxo_as_non_rewindable(struct TariWall
                                                                  const char *message,
                                                                  int *error_out);
 
+/**
+ * Import a batch of external UTXOs, e.g. faucet or claimed outputs, into the wallet as non-rewindable (i.e.
+ * non-recoverable) outputs. All outputs are added in a single database transaction as EncumberedToBeReceived, and a
+ * faux completed transaction is created for each imported output to record the event. This is much faster than
+ * importing the outputs one at a time with `wallet_import_external_utxo_as_non_rewindable`.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `outputs` - The TariUnblindedOutputs to import
+ * `source_address` - The tari address of the source of the transactions, may be null
+ * `message` - The message that the transactions will have
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariUtxoImportResults` - Returns the result of each import in the order of `outputs`: a `TariVector` tagged
+ * as `TariTypeTag::U64` with the TransactionID of each generated transaction, or zero if the import failed, and a
+ * `TariVector` tagged as `TariTypeTag::I64` with the error code of each import, or zero if it succeeded. Outputs that
+ * the wallet already holds fail. Returns null if the batch could not be imported at all.
+ *
+ * # Safety
+ * `destroy_tari_utxo_import_results()` must be called after use to free the allocated memory.
+ */
+struct TariUtxoImportResults *wallet_import_external_utxos_as_non_rewindable(struct TariWallet *wallet,
+                                                                             struct TariUnblindedOutputs *outputs,
+                                                                             TariWalletAddress *source_address,
+                                                                             const char *message,
+                                                                             int *error_out);
+
 /**
  * Get the TariUnblindedOutputs from a TariWallet
  *
@@ -1150,6 +1275,58 @@ TariPrivateKey *private_key_from_hex(const char *key,
 TariCovenant *covenant_create_from_bytes(const struct ByteVector *covenant_bytes,
                                          int *error_out);
 
+/**
+ * Creates a TariCovenant for a time-locked vault: until `unlock_height` the output may only be spent into an output
+ * with the same script and covenant, after which it can be spent freely
+ *
+ * ## Arguments
+ * `unlock_height` - The block height from which the output can be spent freely
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `TariCovenant` - Returns the covenant
+ *
+ * # Safety
+ * The ```covenant_destroy``` function must be called when finished with a TariCovenant to prevent a memory leak
+ */
+TariCovenant *covenant_create_time_locked_vault(unsigned long long unlock_height,
+                                                int *error_out);
+
+/**
+ * Creates a TariCovenant that only allows the output to be spent into an output of the given output type
+ *
+ * ## Arguments
+ * `output_type` - The encoded value of the output type as a byte
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `TariCovenant` - Returns the covenant. Note that it will be ptr::null_mut() if the output type is invalid
+ *
+ * # Safety
+ * The ```covenant_destroy``` function must be called when finished with a TariCovenant to prevent a memory leak
+ */
+TariCovenant *covenant_create_output_type_restriction(unsigned short output_type,
+                                                      int *error_out);
+
+/**
+ * Creates a TariCovenant that only allows the output to be spent into a one-sided payment to the given address
+ *
+ * ## Arguments
+ * `address` - The pointer to a TariWalletAddress
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `TariCovenant` - Returns the covenant. Note that it will be ptr::null_mut() if the address is null
+ *
+ * # Safety
+ * The ```covenant_destroy``` function must be called when finished with a TariCovenant to prevent a memory leak
+ */
+TariCovenant *covenant_create_pinned_to_address(const TariWalletAddress *address,
+                                                int *error_out);
+
 /**
  * Frees memory for a TariCovenant
  *
@@ -1292,6 +1469,26 @@ struct TariSeedWords *seed_words_create(void);
 struct TariSeedWords *seed_words_get_mnemonic_word_list_for_language(const char *language,
                                                                      int *error_out);
 
+/**
+ * Create a TariSeedWords instance holding the seed words of a new random wallet seed in the requested language. The
+ * seed words can be passed to `wallet_create` to create a wallet whose seed words are presented in that language.
+ *
+ * ## Arguments
+ * `language` - The required language as a string, e.g. "Spanish", "ChineseSimplified" or "Japanese"
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `TariSeedWords` - Returns the seed words of the new seed, note that it returns ptr::null_mut() if the language is
+ * null or not supported
+ *
+ * # Safety
+ * The `seed_words_destroy` method must be called when finished with a TariSeedWords instance from rust to prevent a
+ * memory leak
+ */
+struct TariSeedWords *seed_words_create_random(const char *language,
+                                               int *error_out);
+
 /**
  * Gets the length of TariSeedWords
  *
@@ -1512,6 +1709,195 @@ TariContact *contacts_get_at(struct TariContacts *contacts,
  */
 void contacts_destroy(struct TariContacts *contacts);
 
+/**
+ * -------------------------------------------------------------------------------------------- ///
+ * ----------------------------------- Chat Messages ----------------------------------------------///
+ * Gets the id of a TariChatMessage
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut ByteVector` - Returns a pointer to a ByteVector. Note that it returns ptr::null_mut() if message is null
+ *
+ * # Safety
+ * The ```byte_vector_destroy``` method must be called when finished with a ByteVector to prevent a memory leak
+ */
+struct ByteVector *chat_message_get_id(TariChatMessage *message,
+                                       int *error_out);
+
+/**
+ * Gets the body of a TariChatMessage
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut ByteVector` - Returns a pointer to a ByteVector. Note that it returns ptr::null_mut() if message is null
+ *
+ * # Safety
+ * The ```byte_vector_destroy``` method must be called when finished with a ByteVector to prevent a memory leak
+ */
+struct ByteVector *chat_message_get_body(TariChatMessage *message,
+                                         int *error_out);
+
+/**
+ * Gets the TariWalletAddress of the other wallet in the conversation of a TariChatMessage
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariWalletAddress` - Returns a pointer to a TariWalletAddress. Note that it returns ptr::null_mut() if
+ * message is null
+ *
+ * # Safety
+ * The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+ */
+TariWalletAddress *chat_message_get_address(TariChatMessage *message,
+                                            int *error_out);
+
+/**
+ * Gets whether a TariChatMessage was sent by this wallet
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the message is outbound. Note that it returns false if message is null
+ *
+ * # Safety
+ * None
+ */
+bool chat_message_is_outbound(TariChatMessage *message,
+                              int *error_out);
+
+/**
+ * Gets the unix timestamp at which a TariChatMessage was sent
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_ulonglong` - Returns the timestamp in seconds. Note that it returns 0 if message is null
+ *
+ * # Safety
+ * None
+ */
+unsigned long long chat_message_get_sent_at(TariChatMessage *message,
+                                            int *error_out);
+
+/**
+ * Gets the unix timestamp of the delivery receipt of a TariChatMessage
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_ulonglong` - Returns the timestamp in seconds, or 0 if the message has not been delivered. Note that it returns
+ * 0 if message is null
+ *
+ * # Safety
+ * None
+ */
+unsigned long long chat_message_get_delivered_at(TariChatMessage *message,
+                                                 int *error_out);
+
+/**
+ * Gets the unix timestamp of the read receipt of a TariChatMessage
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_ulonglong` - Returns the timestamp in seconds, or 0 if the message has not been read. Note that it returns 0 if
+ * message is null
+ *
+ * # Safety
+ * None
+ */
+unsigned long long chat_message_get_read_at(TariChatMessage *message,
+                                            int *error_out);
+
+/**
+ * Frees memory for a TariChatMessage
+ *
+ * ## Arguments
+ * `message` - The pointer to a TariChatMessage
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void chat_message_destroy(TariChatMessage *message);
+
+/**
+ * Gets the length of TariChatMessages
+ *
+ * ## Arguments
+ * `messages` - The pointer to a TariChatMessages
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_uint` - Returns number of elements in messages, zero if messages is null
+ *
+ * # Safety
+ * None
+ */
+unsigned int chat_messages_get_length(struct TariChatMessages *messages,
+                                      int *error_out);
+
+/**
+ * Gets a TariChatMessage from TariChatMessages at position
+ *
+ * ## Arguments
+ * `messages` - The pointer to a TariChatMessages
+ * `position` - The integer position
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariChatMessage` - Returns a TariChatMessage, note that it returns ptr::null_mut() if messages is null or
+ * position is invalid
+ *
+ * # Safety
+ * The ```chat_message_destroy``` method must be called when finished with a TariChatMessage to prevent a memory leak
+ */
+TariChatMessage *chat_messages_get_at(struct TariChatMessages *messages,
+                                      unsigned int position,
+                                      int *error_out);
+
+/**
+ * Frees memory for a TariChatMessages
+ *
+ * ## Arguments
+ * `messages` - The pointer to a TariChatMessages
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void chat_messages_destroy(struct TariChatMessages *messages);
+
 /**
  * -------------------------------------------------------------------------------------------- ///
  * ----------------------------------- Contacts Liveness Data ----------------------------------///
@@ -2661,6 +3047,9 @@ TariPublicKey *public_keys_get_at(const struct TariPublicKeys *public_keys,
  *     Orphan,                 // 4
  *     TimeLocked,             // 5
  *     InvalidTransaction,     // 6
+ *     Oversized,              // 7
+ *     Expired,                // 8
+ *     Replaced,               // 9
  * }
  * `callback_txo_validation_complete` - The callback function pointer matching the function signature. This is called
  * when a TXO validation process is completed. The request_key is used to identify which request this
@@ -2768,6 +3157,35 @@ char *wallet_get_last_version(TariCommsConfig *config,
 char *wallet_get_last_network(TariCommsConfig *config,
                               int *error_out);
 
+/**
+ * Rotates the database encryption key and changes the passphrase of a wallet database that is not currently open.
+ * Every encrypted field is re-encrypted in batches of `batch_size` rows, and the legacy plaintext values of the wallet
+ * settings and client key-values are encrypted. A value in any other table that cannot be decrypted fails the rekey
+ * without changing the passphrase. If this is interrupted, call it again with the same `existing_passphrase` to
+ * finish the rekey.
+ *
+ * Rekeying is offline only: the services of a running wallet each hold a copy of the current key, so this takes
+ * the database lock and fails while a `TariWallet` using the same database exists.
+ *
+ * ## Arguments
+ * `config` - The TariCommsConfig pointer used to locate the wallet database
+ * `existing_passphrase` - The current passphrase of the database
+ * `new_passphrase` - The passphrase to protect the new key with, which may be the same as the existing passphrase
+ * `batch_size` - The number of rows re-encrypted per database transaction
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ * ## Returns
+ * `bool` - Returns if successful or not
+ *
+ * # Safety
+ * Call `wallet_destroy` on any `TariWallet` using the same database first.
+ */
+bool wallet_rekey_database(TariCommsConfig *config,
+                           const char *existing_passphrase,
+                           const char *new_passphrase,
+                           unsigned int batch_size,
+                           int *error_out);
+
 /**
  * Retrieves the balance from a wallet
  *
@@ -2784,6 +3202,60 @@ char *wallet_get_last_network(TariCommsConfig *config,
 TariBalance *wallet_get_balance(struct TariWallet *wallet,
                                 int *error_out);
 
+/**
+ * Creates a named account with its own one-sided payment address
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `name` - The name of the account. It must not be empty, `default` or the name of an existing account.
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ * ## Returns
+ * `*mut TariWalletAddress` - Returns the one-sided payment address of the new account or null if an error occurs
+ *
+ * # Safety
+ * The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+ */
+TariWalletAddress *wallet_create_account(struct TariWallet *wallet,
+                                         const char *name,
+                                         int *error_out);
+
+/**
+ * Retrieves the one-sided payment address of one account of a wallet
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `name` - The name of the account. An empty name or `default` selects the default account.
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ * ## Returns
+ * `*mut TariWalletAddress` - Returns the address of the account or null if an error occurs
+ *
+ * # Safety
+ * The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+ */
+TariWalletAddress *wallet_get_account_address(struct TariWallet *wallet,
+                                              const char *name,
+                                              int *error_out);
+
+/**
+ * Retrieves the balance of one account of a wallet
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `name` - The name of the account. An empty name or `default` selects the default account.
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ * ## Returns
+ * `*mut Balance` - Returns the pointer to the TariBalance or null if error occurs
+ *
+ * # Safety
+ * The ```balance_destroy``` method must be called when finished with a TariBalance to prevent a memory leak
+ */
+TariBalance *wallet_get_account_balance(struct TariWallet *wallet,
+                                        const char *name,
+                                        int *error_out);
+
 /**
  * This function returns a list of unspent UTXO values and commitments.
  *
@@ -2848,6 +3320,53 @@ struct TariVector *wallet_get_utxos(struct TariWallet *wallet,
 struct TariVector *wallet_get_all_utxos(struct TariWallet *wallet,
                                         int32_t *error_ptr);
 
+/**
+ * This function returns the unspent outputs the wallet may use as transaction inputs, in the order in which they
+ * would be selected.
+ *
+ * ## Arguments
+ * * `wallet` - The TariWallet pointer,
+ * * `dust_threshold` - A value filtering threshold. Outputs whose values are <= `dust_threshold` are not listed in the
+ * result.
+ * * `include_locked` - Also list immature, script locked and frozen outputs
+ * * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+ * Functions as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariVector` - Returns a struct with an array pointer, length and capacity (needed for proper destruction
+ * after use).
+ *
+ * # Safety
+ * `destroy_tari_vector()` must be called after use.
+ */
+struct TariVector *wallet_select_utxos(struct TariWallet *wallet,
+                                       uint64_t dust_threshold,
+                                       bool include_locked,
+                                       int32_t *error_ptr);
+
+/**
+ * This function pins, freezes or releases outputs. Frozen outputs are never selected automatically and can only be
+ * spent by passing them to `wallet_send_transaction` as explicit inputs.
+ *
+ * ## Arguments
+ * * `wallet` - The TariWallet pointer
+ * * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
+ *   (see `Commitment::to_hex()`)
+ * * `spending_priority` - 0 to release the outputs, 2 to pin them (selected first) or 3 to freeze them
+ * * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+ * Functions as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the spending priority of all the outputs was updated
+ *
+ * # Safety
+ * `TariVector` must be freed after use with `destroy_tari_vector()`
+ */
+bool wallet_set_utxos_spending_priority(struct TariWallet *wallet,
+                                        struct TariVector *commitments,
+                                        uint8_t spending_priority,
+                                        int32_t *error_ptr);
+
 /**
  * This function will tell the wallet to do a coin split.
  *
@@ -2857,6 +3376,7 @@ struct TariVector *wallet_get_all_utxos(struct TariWallet *wallet,
  *   (see `Commitment::to_hex()`)
  * * `number_of_splits` - The number of times to split the amount
  * * `fee_per_gram` - The transaction fee
+ * * `account` - The name of the account that receives the split outputs, or null for the default account
  * * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
  * Functions as an out parameter.
  *
@@ -2870,6 +3390,7 @@ uint64_t wallet_coin_split(struct TariWallet *wallet,
                            struct TariVector *commitments,
                            uintptr_t number_of_splits,
                            uint64_t fee_per_gram,
+                           const char *account,
                            int32_t *error_ptr);
 
 /**
@@ -2881,6 +3402,7 @@ uint64_t wallet_coin_split(struct TariWallet *wallet,
  * * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
  *   (see `Commitment::to_hex()`)
  * * `fee_per_gram` - The transaction fee
+ * * `account` - The name of the account that receives the joined output, or null for the default account
  * * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
  * Functions as an out parameter.
  *
@@ -2893,6 +3415,7 @@ uint64_t wallet_coin_split(struct TariWallet *wallet,
 uint64_t wallet_coin_join(struct TariWallet *wallet,
                           struct TariVector *commitments,
                           uint64_t fee_per_gram,
+                          const char *account,
                           int32_t *error_ptr);
 
 /**
@@ -2941,6 +3464,34 @@ struct TariCoinPreview *wallet_preview_coin_split(struct TariWallet *wallet,
                                                   uint64_t fee_per_gram,
                                                   int32_t *error_ptr);
 
+/**
+ * This function will tell what the outcome of sending a transaction would be, without sending it or encumbering any
+ * outputs.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `amount` - The value to send
+ * `fee_per_gram` - The transaction fee
+ * `strategy` - The coin selection strategy: 0 for the wallet default, 1 for smallest first, 2 for largest first and 3
+ * for branch and bound
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariTransactionPreview` - A struct with the selected inputs as a `TariVector` of commitments tagged as
+ * `TariTypeTag::Commitment`, their values as a `TariVector` tagged as `TariTypeTag::U64`, the fee, the change returned
+ * to the wallet and the weight of the transaction. Returns null if an error occurred, e.g. when the available funds
+ * are insufficient.
+ *
+ * # Safety
+ * `destroy_tari_transaction_preview()` must be called after use to free the allocated memory.
+ */
+struct TariTransactionPreview *wallet_preview_transaction(struct TariWallet *wallet,
+                                                          unsigned long long amount,
+                                                          unsigned long long fee_per_gram,
+                                                          unsigned int strategy,
+                                                          int *error_out);
+
 /**
  * Signs a message using the public key of the TariWallet
  *
@@ -3153,6 +3704,9 @@ void balance_destroy(TariBalance *balance);
  *   (see `Commitment::to_hex()`)
  * `fee_per_gram` - The transaction fee
  * `message` - The pointer to a char array
+ * `one_sided` - Send the transaction as a one-sided payment to the stealth address of the destination
+ * `account` - The name of the account that funds the transaction and receives the change, or null for the default
+ * account
  * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
  * as an out parameter.
  *
@@ -3169,8 +3723,44 @@ unsigned long long wallet_send_transaction(struct TariWallet *wallet,
                                            unsigned long long fee_per_gram,
                                            const char *message,
                                            bool one_sided,
+                                           const char *account,
                                            int *error_out);
 
+/**
+ * Pays many recipients at once, e.g. to batch exchange withdrawals. All one-sided recipients are paid in a single
+ * transaction, while each interactive recipient is paid in a negotiated transaction of its own. A failed transaction
+ * only affects its own recipients.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `destinations` - A `TariVector` of "strings", tagged as `TariTypeTag::Text`, containing the hex encoded
+ *   `TariWalletAddress` of each recipient
+ * `amounts` - A `TariVector` tagged as `TariTypeTag::U64` containing the amount for each recipient
+ * `one_sided` - A `TariVector` tagged as `TariTypeTag::U64` containing, for each recipient, 1 to pay it one-sided or 0
+ *   to pay it with an interactive transaction
+ * `fee_per_gram` - The transaction fee
+ * `message` - The pointer to a char array
+ * `account` - The name of the account that funds the transactions and receives the change, or null for the default
+ * account
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariVector` - Returns a `TariVector` tagged as `TariTypeTag::U64` with the TxId paying each recipient, in the
+ * order of `destinations`, or 0 for recipients that could not be paid. Returns null if the arguments are invalid.
+ *
+ * # Safety
+ * `destroy_tari_vector()` must be called after use to free the allocated memory.
+ */
+struct TariVector *wallet_send_to_many(struct TariWallet *wallet,
+                                       struct TariVector *destinations,
+                                       struct TariVector *amounts,
+                                       struct TariVector *one_sided,
+                                       unsigned long long fee_per_gram,
+                                       const char *message,
+                                       const char *account,
+                                       int *error_out);
+
 /**
  * Gets a fee estimate for an amount
  *
@@ -3253,6 +3843,73 @@ void wallet_set_num_confirmations_required(struct TariWallet *wallet,
 struct TariContacts *wallet_get_contacts(struct TariWallet *wallet,
                                          int *error_out);
 
+/**
+ * Sends an end-to-end encrypted chat message to a wallet. If the wallet is offline the message is held by the network
+ * and delivered once it comes back online.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `destination` - The TariWalletAddress pointer of the wallet to send the message to
+ * `body` - The ByteVector pointer of the message body
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut ByteVector` - Returns the id of the sent message, note that it returns ptr::null_mut() if any argument is
+ * null or an error is encountered
+ *
+ * # Safety
+ * The ```byte_vector_destroy``` method must be called when finished with a ByteVector to prevent a memory leak
+ */
+struct ByteVector *wallet_send_chat_message(struct TariWallet *wallet,
+                                            TariWalletAddress *destination,
+                                            struct ByteVector *body,
+                                            int *error_out);
+
+/**
+ * Gets a page of the chat messages exchanged with a wallet, ordered by the time they were sent with the newest first
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `address` - The TariWalletAddress pointer of the other wallet in the conversation
+ * `limit` - The number of messages per page, defaults to 35 if 0
+ * `page` - The page to return, starting at 0
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariChatMessages` - Returns the messages, note that it returns ptr::null_mut() if wallet or address is null
+ * or an error is encountered
+ *
+ * # Safety
+ * The ```chat_messages_destroy``` method must be called when finished with a TariChatMessages to prevent a memory
+ * leak
+ */
+struct TariChatMessages *wallet_get_chat_messages(struct TariWallet *wallet,
+                                                  TariWalletAddress *address,
+                                                  unsigned int limit,
+                                                  unsigned int page,
+                                                  int *error_out);
+
+/**
+ * Sends a read receipt for a received chat message
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `message` - The TariChatMessage pointer of the message that was read
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns if successful or not
+ *
+ * # Safety
+ * None
+ */
+bool wallet_send_chat_read_receipt(struct TariWallet *wallet,
+                                   TariChatMessage *message,
+                                   int *error_out);
+
 /**
  * Get the TariCompletedTransactions from a TariWallet
  *
@@ -3512,7 +4169,8 @@ bool wallet_restart_transaction_broadcast(struct TariWallet *wallet,
                                           int *error_out);
 
 /**
- * Gets the seed words representing the seed private key of the provided `TariWallet`.
+ * Gets the seed words representing the seed private key of the provided `TariWallet`, in the language of the seed
+ * words the wallet was created with or English if it was created without seed words.
  *
  * ## Arguments
  * `wallet` - The TariWallet pointer
@@ -3699,6 +4357,49 @@ bool wallet_start_recovery(struct TariWallet *wallet,
                            const char *recovered_output_message,
                            int *error_out);
 
+/**
+ * Registers a callback that reports the progress of the wallet's sync with the chain, so that clients can render
+ * progress without polling. Only one callback can be registered for the lifetime of the wallet.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `sync_progress_callback` - The callback function pointer. The first argument of the callback is an event enum
+ * encoded as a u8 as follows:
+ * ```
+ * enum SyncProgressEvent {
+ *     ScanningProgress,            // 0
+ *     ScanningCompleted,           // 1
+ *     ScanningFailed,              // 2
+ *     RecoveryProgress,            // 3
+ *     RecoveryCompleted,           // 4
+ *     BaseNodeConnectivityChanged, // 5
+ * }
+ * ```
+ * The meaning of the second and third arguments for each event are as follows:
+ *     - ScanningProgress, height scanned, chain tip height
+ *     - ScanningCompleted, height scanned, number of one-sided outputs found
+ *     - ScanningFailed, number of retries, retry limit (0, 0 if the scanner exited)
+ *     - RecoveryProgress, height scanned, chain tip height
+ *     - RecoveryCompleted, number of outputs recovered, MicroMinotari recovered
+ *     - BaseNodeConnectivityChanged, connectivity status as for `callback_connectivity_status`, 0
+ *
+ * Scanning events are reported for the background scanner that detects one-sided payments, and recovery events for
+ * recoveries started with `wallet_start_recovery` after the callback was registered. The current connectivity status
+ * is reported as soon as the callback is registered.
+ *
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns if successful or not
+ *
+ * # Safety
+ * None
+ */
+bool wallet_register_sync_progress_callback(struct TariWallet *wallet,
+                                            void (*sync_progress_callback)(uint8_t, uint64_t, uint64_t),
+                                            int *error_out);
+
 /**
  * Set the text message that is applied to a detected One-Side payment transaction when it is scanned from the
  * blockchain
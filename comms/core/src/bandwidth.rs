@@ -0,0 +1,371 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # Bandwidth accounting
+//!
+//! Bytes sent and received on negotiated substreams are attributed to the service that the substream's protocol is
+//! mapped to. A service may be given a quota of bytes (sent and received) per accounting period. Once a service has
+//! used its quota, new substreams for its protocols are refused until the next accounting period starts.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::protocol::ProtocolId;
+
+/// Well-known service names that bandwidth is accounted under
+pub mod services {
+    pub const BLOCK_SYNC: &str = "block_sync";
+    pub const MEMPOOL_SYNC: &str = "mempool_sync";
+    pub const DHT: &str = "dht";
+    pub const SAF: &str = "saf";
+    pub const RPC: &str = "rpc";
+    /// Substreams for protocols that are not mapped to a service
+    pub const OTHER: &str = "other";
+}
+
+/// Bandwidth accounting configuration
+#[derive(Debug, Clone)]
+pub struct BandwidthConfig {
+    /// The length of an accounting period. Quotas apply to the bytes used within a period. Default: 1 day
+    pub period: Duration,
+    /// Maps protocols to the service that their substreams are accounted under. Substreams for protocols that are not
+    /// mapped are accounted under [services::OTHER].
+    pub protocol_services: HashMap<ProtocolId, String>,
+    /// The maximum number of bytes (sent and received) that a service may use in an accounting period. Services
+    /// without a quota are unlimited.
+    pub quotas: HashMap<String, u64>,
+}
+
+impl BandwidthConfig {
+    /// Account substreams for the given protocol under the given service
+    pub fn with_protocol_service(mut self, protocol: ProtocolId, service: &str) -> Self {
+        self.protocol_services.insert(protocol, service.to_string());
+        self
+    }
+
+    /// Set the quota in bytes per accounting period for the given service
+    pub fn with_quota(mut self, service: &str, quota: u64) -> Self {
+        self.quotas.insert(service.to_string(), quota);
+        self
+    }
+}
+
+impl Default for BandwidthConfig {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(24 * 60 * 60),
+            protocol_services: HashMap::new(),
+            quotas: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Bandwidth quota of {quota} bytes per accounting period for service '{service}' has been used")]
+pub struct BandwidthQuotaExceeded {
+    pub service: String,
+    pub quota: u64,
+}
+
+/// Bandwidth usage of all services
+#[derive(Debug, Clone)]
+pub struct NetworkUsage {
+    /// The length of an accounting period
+    pub period: Duration,
+    /// The time elapsed in the current accounting period
+    pub period_elapsed: Duration,
+    /// Usage per service, ordered by service name
+    pub services: Vec<ServiceUsage>,
+}
+
+/// Bandwidth usage of a service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceUsage {
+    pub service: String,
+    pub total_bytes_sent: u64,
+    pub total_bytes_received: u64,
+    pub period_bytes_sent: u64,
+    pub period_bytes_received: u64,
+    pub quota: Option<u64>,
+}
+
+/// Byte counters for a single service
+#[derive(Debug)]
+pub(crate) struct ServiceCounter {
+    started_at: Instant,
+    period: Duration,
+    period_index: AtomicU64,
+    total_sent: AtomicU64,
+    total_received: AtomicU64,
+    period_sent: AtomicU64,
+    period_received: AtomicU64,
+}
+
+impl ServiceCounter {
+    fn new(started_at: Instant, period: Duration) -> Self {
+        Self {
+            started_at,
+            period,
+            period_index: AtomicU64::new(started_at.elapsed().as_secs() / period.as_secs().max(1)),
+            total_sent: AtomicU64::new(0),
+            total_received: AtomicU64::new(0),
+            period_sent: AtomicU64::new(0),
+            period_received: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record_sent(&self, bytes: u64) {
+        self.roll_period();
+        self.total_sent.fetch_add(bytes, Ordering::Relaxed);
+        self.period_sent.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn record_received(&self, bytes: u64) {
+        self.roll_period();
+        self.total_received.fetch_add(bytes, Ordering::Relaxed);
+        self.period_received.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn subtract(&self, bytes_sent: u64, bytes_received: u64) {
+        self.roll_period();
+        for (counter, bytes) in [
+            (&self.total_sent, bytes_sent),
+            (&self.period_sent, bytes_sent),
+            (&self.total_received, bytes_received),
+            (&self.period_received, bytes_received),
+        ] {
+            let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |v| Some(v.saturating_sub(bytes)));
+        }
+    }
+
+    fn period_bytes(&self) -> u64 {
+        self.roll_period();
+        self.period_sent
+            .load(Ordering::Relaxed)
+            .saturating_add(self.period_received.load(Ordering::Relaxed))
+    }
+
+    fn current_period_index(&self) -> u64 {
+        self.started_at.elapsed().as_secs() / self.period.as_secs().max(1)
+    }
+
+    /// Resets the period counters if a new accounting period has started
+    fn roll_period(&self) {
+        let current = self.current_period_index();
+        let previous = self.period_index.load(Ordering::Relaxed);
+        if previous != current &&
+            self.period_index
+                .compare_exchange(previous, current, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.period_sent.store(0, Ordering::Relaxed);
+            self.period_received.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Attributes bytes sent and received to services and enforces the configured service quotas. The meter is cheap to
+/// clone and all clones share the same counters.
+#[derive(Debug, Clone)]
+pub struct BandwidthMeter {
+    inner: Arc<BandwidthMeterInner>,
+}
+
+#[derive(Debug)]
+struct BandwidthMeterInner {
+    config: BandwidthConfig,
+    started_at: Instant,
+    counters: RwLock<HashMap<String, Arc<ServiceCounter>>>,
+}
+
+impl BandwidthMeter {
+    pub fn new(config: BandwidthConfig) -> Self {
+        Self {
+            inner: Arc::new(BandwidthMeterInner {
+                config,
+                started_at: Instant::now(),
+                counters: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Returns the service that substreams for the given protocol are accounted under
+    pub fn service_for_protocol(&self, protocol: &ProtocolId) -> &str {
+        self.inner
+            .config
+            .protocol_services
+            .get(protocol)
+            .map(String::as_str)
+            .unwrap_or(services::OTHER)
+    }
+
+    pub fn record_sent(&self, service: &str, bytes: u64) {
+        self.counter(service).record_sent(bytes);
+    }
+
+    pub fn record_received(&self, service: &str, bytes: u64) {
+        self.counter(service).record_received(bytes);
+    }
+
+    /// Moves bytes that were accounted under one service to another. This is used to attribute messages to a service
+    /// that shares a substream protocol with other services, for example, store and forward messages sent over the
+    /// DHT messaging protocol.
+    pub fn reattribute(&self, from_service: &str, to_service: &str, bytes_sent: u64, bytes_received: u64) {
+        self.counter(from_service).subtract(bytes_sent, bytes_received);
+        let to = self.counter(to_service);
+        to.record_sent(bytes_sent);
+        to.record_received(bytes_received);
+    }
+
+    /// Returns an error if the service has used its quota for the current accounting period
+    pub fn check_quota(&self, service: &str) -> Result<(), BandwidthQuotaExceeded> {
+        match self.inner.config.quotas.get(service) {
+            Some(quota) if self.counter(service).period_bytes() >= *quota => Err(BandwidthQuotaExceeded {
+                service: service.to_string(),
+                quota: *quota,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_protocol_quota(&self, protocol: &ProtocolId) -> Result<(), BandwidthQuotaExceeded> {
+        self.check_quota(self.service_for_protocol(protocol))
+    }
+
+    pub(crate) fn protocol_counter(&self, protocol: &ProtocolId) -> Arc<ServiceCounter> {
+        self.counter(self.service_for_protocol(protocol))
+    }
+
+    /// Returns the bandwidth usage of all services that have used bandwidth or have a quota
+    pub fn usage(&self) -> NetworkUsage {
+        for service in self.inner.config.quotas.keys() {
+            self.counter(service);
+        }
+        let mut services = acquire_read_lock!(self.inner.counters)
+            .iter()
+            .map(|(service, counter)| {
+                counter.roll_period();
+                ServiceUsage {
+                    service: service.clone(),
+                    total_bytes_sent: counter.total_sent.load(Ordering::Relaxed),
+                    total_bytes_received: counter.total_received.load(Ordering::Relaxed),
+                    period_bytes_sent: counter.period_sent.load(Ordering::Relaxed),
+                    period_bytes_received: counter.period_received.load(Ordering::Relaxed),
+                    quota: self.inner.config.quotas.get(service).copied(),
+                }
+            })
+            .collect::<Vec<_>>();
+        services.sort_by(|a, b| a.service.cmp(&b.service));
+
+        let period = self.inner.config.period;
+        let elapsed = self.inner.started_at.elapsed().as_secs() % period.as_secs().max(1);
+        NetworkUsage {
+            period,
+            period_elapsed: Duration::from_secs(elapsed),
+            services,
+        }
+    }
+
+    fn counter(&self, service: &str) -> Arc<ServiceCounter> {
+        if let Some(counter) = acquire_read_lock!(self.inner.counters).get(service) {
+            return counter.clone();
+        }
+        acquire_write_lock!(self.inner.counters)
+            .entry(service.to_string())
+            .or_insert_with(|| Arc::new(ServiceCounter::new(self.inner.started_at, self.inner.config.period)))
+            .clone()
+    }
+}
+
+impl Default for BandwidthMeter {
+    fn default() -> Self {
+        Self::new(BandwidthConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn meter() -> BandwidthMeter {
+        BandwidthMeter::new(
+            BandwidthConfig::default()
+                .with_protocol_service(ProtocolId::from_static(b"t/blksync/1"), services::BLOCK_SYNC)
+                .with_protocol_service(ProtocolId::from_static(b"t/msg/0.1"), services::DHT)
+                .with_quota(services::BLOCK_SYNC, 1000),
+        )
+    }
+
+    #[test]
+    fn it_attributes_protocol_bytes_to_services() {
+        let meter = meter();
+        meter
+            .protocol_counter(&ProtocolId::from_static(b"t/blksync/1"))
+            .record_received(600);
+        meter
+            .protocol_counter(&ProtocolId::from_static(b"t/unknown/1"))
+            .record_sent(10);
+        meter.record_sent(services::DHT, 100);
+        meter.record_received(services::DHT, 300);
+        meter.reattribute(services::DHT, services::SAF, 40, 200);
+
+        let usage = meter.usage();
+        let names = usage.services.iter().map(|s| s.service.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, [
+            services::BLOCK_SYNC,
+            services::DHT,
+            services::OTHER,
+            services::SAF
+        ]);
+        assert_eq!(usage.services[0].total_bytes_received, 600);
+        assert_eq!(usage.services[0].quota, Some(1000));
+        assert_eq!(usage.services[1].total_bytes_sent, 60);
+        assert_eq!(usage.services[1].period_bytes_received, 100);
+        assert_eq!(usage.services[2].total_bytes_sent, 10);
+        assert_eq!(usage.services[3].total_bytes_sent, 40);
+        assert_eq!(usage.services[3].total_bytes_received, 200);
+    }
+
+    #[test]
+    fn it_enforces_quotas() {
+        let meter = meter();
+        let protocol = ProtocolId::from_static(b"t/blksync/1");
+        meter.check_protocol_quota(&protocol).unwrap();
+        meter.protocol_counter(&protocol).record_sent(999);
+        meter.check_protocol_quota(&protocol).unwrap();
+        meter.protocol_counter(&protocol).record_received(1);
+        let err = meter.check_protocol_quota(&protocol).unwrap_err();
+        assert_eq!(err.service, services::BLOCK_SYNC);
+        // Services without a quota are unlimited
+        meter.record_received(services::DHT, u64::MAX);
+        meter.check_quota(services::DHT).unwrap();
+    }
+}
@@ -31,6 +31,7 @@ use tokio::{
 
 use super::{CommsBuilderError, CommsShutdown};
 use crate::{
+    bandwidth::BandwidthMeter,
     connection_manager::{
         ConnectionManager,
         ConnectionManagerEvent,
@@ -67,6 +68,7 @@ pub struct UnspawnedCommsNode {
     pub(super) connectivity_requester: ConnectivityRequester,
     pub(super) connectivity_rx: mpsc::Receiver<ConnectivityRequest>,
     pub(super) peer_manager: Arc<PeerManager>,
+    pub(super) bandwidth_meter: BandwidthMeter,
     pub(super) protocol_extensions: ProtocolExtensions,
     pub(super) protocols: Protocols<Substream>,
     pub(super) shutdown_signal: ShutdownSignal,
@@ -147,6 +149,7 @@ impl UnspawnedCommsNode {
             node_identity,
             shutdown_signal,
             peer_manager,
+            bandwidth_meter,
             protocol_extensions,
             protocols,
         } = self;
@@ -192,6 +195,7 @@ impl UnspawnedCommsNode {
             node_identity.clone(),
             peer_manager.clone(),
             connection_manager_requester.get_event_publisher(),
+            bandwidth_meter.clone(),
             shutdown_signal.clone(),
         );
 
@@ -242,6 +246,7 @@ impl UnspawnedCommsNode {
             connectivity_requester,
             node_identity,
             peer_manager,
+            bandwidth_meter,
             liveness_watch,
             complete_signals: ext_context.drain_complete_signals(),
         })
@@ -262,6 +267,11 @@ impl UnspawnedCommsNode {
         self.connectivity_requester.clone()
     }
 
+    /// Return a handle to the bandwidth meter that accounts substream bandwidth per service
+    pub fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.bandwidth_meter.clone()
+    }
+
     /// Returns an owned copy`ShutdownSignal`
     pub fn shutdown_signal(&self) -> ShutdownSignal {
         self.shutdown_signal.clone()
@@ -284,6 +294,8 @@ pub struct CommsNode {
     node_identity: Arc<NodeIdentity>,
     /// Shared PeerManager instance
     peer_manager: Arc<PeerManager>,
+    /// Accounts substream bandwidth per service
+    bandwidth_meter: BandwidthMeter,
     /// Current liveness status
     liveness_watch: watch::Receiver<LivenessStatus>,
     /// The 'reciprocal' shutdown signals for each comms service
@@ -320,6 +332,11 @@ impl CommsNode {
         &self.node_identity
     }
 
+    /// Return a handle to the bandwidth meter that accounts substream bandwidth per service
+    pub fn bandwidth_meter(&self) -> BandwidthMeter {
+        self.bandwidth_meter.clone()
+    }
+
     /// Returns the current liveness status
     pub fn liveness_status(&self) -> LivenessStatus {
         *self.liveness_watch.borrow()
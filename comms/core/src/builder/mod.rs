@@ -42,10 +42,11 @@ use tokio::sync::{broadcast, mpsc};
 
 use crate::{
     backoff::{Backoff, BoxedBackoff, ConstantBackoff},
+    bandwidth::{BandwidthConfig, BandwidthMeter},
     connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester},
     connectivity::{ConnectivityConfig, ConnectivityRequester},
     multiaddr::Multiaddr,
-    peer_manager::{NodeIdentity, PeerManager},
+    peer_manager::{BanPolicyConfig, NodeIdentity, PeerManager},
     peer_validator::PeerValidatorConfig,
     protocol::{NodeNetworkInfo, ProtocolExtensions},
     tor,
@@ -125,6 +126,8 @@ pub struct CommsBuilder {
     hidden_service_ctl: Option<tor::HiddenServiceController>,
     connection_manager_config: ConnectionManagerConfig,
     connectivity_config: ConnectivityConfig,
+    ban_policy_config: BanPolicyConfig,
+    bandwidth_config: BandwidthConfig,
 
     shutdown_signal: Option<ShutdownSignal>,
 }
@@ -139,6 +142,8 @@ impl Default for CommsBuilder {
             hidden_service_ctl: None,
             connection_manager_config: ConnectionManagerConfig::default(),
             connectivity_config: ConnectivityConfig::default(),
+            ban_policy_config: BanPolicyConfig::default(),
+            bandwidth_config: BandwidthConfig::default(),
             shutdown_signal: None,
         }
     }
@@ -279,6 +284,19 @@ impl CommsBuilder {
         self
     }
 
+    /// Set the [BanPolicyConfig] used by the peer manager to score peer offenses and decide on bans.
+    pub fn with_ban_policy_config(mut self, config: BanPolicyConfig) -> Self {
+        self.ban_policy_config = config;
+        self
+    }
+
+    /// Set the [BandwidthConfig] that determines which service substream bandwidth is accounted under and the
+    /// per-service quotas.
+    pub fn with_bandwidth_config(mut self, config: BandwidthConfig) -> Self {
+        self.bandwidth_config = config;
+        self
+    }
+
     /// Set the backoff to use when a dial to a remote peer fails. This is optional. If omitted the default
     /// [ConstantBackoff](crate::backoff::ConstantBackoff) of 500ms is used.
     pub fn with_dial_backoff<T>(mut self, backoff: T) -> Self
@@ -293,6 +311,13 @@ impl CommsBuilder {
         self
     }
 
+    /// Enable noise session resumption with session tickets valid for the given lifetime, or None to disable it
+    /// (default)
+    pub fn set_noise_session_ticket_lifetime(mut self, lifetime: Option<Duration>) -> Self {
+        self.connection_manager_config.noise_session_ticket_lifetime = lifetime;
+        self
+    }
+
     fn make_peer_manager(&mut self) -> Result<Arc<PeerManager>, CommsBuilderError> {
         let file_lock = self.peer_storage_file_lock.take();
 
@@ -301,7 +326,9 @@ impl CommsBuilder {
                 #[cfg(not(test))]
                 PeerManager::migrate_lmdb(&storage.inner())?;
 
-                let peer_manager = PeerManager::new(storage, file_lock).map_err(CommsBuilderError::PeerManagerError)?;
+                let peer_manager = PeerManager::new(storage, file_lock)
+                    .map_err(CommsBuilderError::PeerManagerError)?
+                    .with_ban_policy_config(self.ban_policy_config.clone());
                 Ok(Arc::new(peer_manager))
             },
             None => Err(CommsBuilderError::PeerStorageNotProvided),
@@ -317,6 +344,7 @@ impl CommsBuilder {
             .ok_or(CommsBuilderError::ShutdownSignalNotSet)?;
 
         let peer_manager = self.make_peer_manager()?;
+        let bandwidth_meter = BandwidthMeter::new(self.bandwidth_config.clone());
 
         //---------------------------------- Connection Manager --------------------------------------------//
         let (conn_man_tx, connection_manager_request_rx) =
@@ -339,6 +367,7 @@ impl CommsBuilder {
             connectivity_requester,
             connectivity_rx,
             peer_manager,
+            bandwidth_meter,
             protocol_extensions: ProtocolExtensions::new(),
         })
     }
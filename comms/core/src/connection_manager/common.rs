@@ -29,9 +29,10 @@ use log::*;
 use tokio::io::{AsyncRead, AsyncWrite};
 
 use crate::{
-    connection_manager::error::ConnectionManagerError,
+    connection_manager::{error::ConnectionManagerError, ConnectionDirection, ConnectionManagerConfig},
     multiaddr::Multiaddr,
     net_address::{MultiaddressesWithStats, PeerAddressSource},
+    noise::{NoiseConfig, NoiseSocket},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerIdentityClaim, PeerManagerError},
     peer_validator::{validate_peer_identity_claim, PeerValidatorConfig, PeerValidatorError},
     proto::identity::PeerIdentityMsg,
@@ -80,13 +81,82 @@ pub(super) async fn perform_identity_exchange<
     node_identity: &NodeIdentity,
     our_supported_protocols: P,
     network_info: NodeNetworkInfo,
+    session_ticket_seed: Vec<u8>,
 ) -> Result<PeerIdentityMsg, ConnectionManagerError> {
-    let peer_identity =
-        protocol::identity_exchange(node_identity, our_supported_protocols, network_info, socket).await?;
+    let peer_identity = protocol::identity_exchange(
+        node_identity,
+        our_supported_protocols,
+        network_info,
+        session_ticket_seed,
+        socket,
+    )
+    .await?;
 
     Ok(peer_identity)
 }
 
+/// Returns the validated identity of the peer. If the noise session was resumed, the identity held in the session
+/// ticket is returned and the identity exchange is skipped. Otherwise, the identity exchange is performed, the peer is
+/// banned for any offence and, if both peers support session resumption, a session ticket is issued for the peer.
+pub(super) async fn exchange_or_resume_peer_identity<'p, P, TSocket>(
+    socket: &mut NoiseSocket<TSocket>,
+    noise_config: &NoiseConfig,
+    direction: ConnectionDirection,
+    node_identity: &NodeIdentity,
+    peer_manager: &PeerManager,
+    authenticated_public_key: &CommsPublicKey,
+    our_supported_protocols: P,
+    config: &ConnectionManagerConfig,
+) -> Result<ValidatedPeerIdentityExchange, ConnectionManagerError>
+where
+    P: IntoIterator<Item = &'p ProtocolId>,
+    TSocket: AsyncRead + AsyncWrite + Unpin,
+{
+    if let Some(ticket) = socket.session_ticket() {
+        debug!(
+            target: LOG_TARGET,
+            "Resumed {} noise session with peer '{}', skipping identity exchange", direction, authenticated_public_key
+        );
+        return Ok(ticket.peer_identity().clone());
+    }
+
+    debug!(
+        target: LOG_TARGET,
+        "Starting peer identity exchange for peer with public key '{}'", authenticated_public_key
+    );
+    let our_seed = noise_config.session_tickets().generate_seed();
+    let peer_identity_result = perform_identity_exchange(
+        socket,
+        node_identity,
+        our_supported_protocols,
+        config.network_info.clone(),
+        our_seed.clone(),
+    )
+    .await;
+    let peer_identity = ban_on_offence(peer_manager, authenticated_public_key, peer_identity_result).await?;
+    let peer_seed = peer_identity.session_ticket_seed.clone();
+
+    let valid_peer_identity_result =
+        validate_peer_identity_message(&config.peer_validation_config, authenticated_public_key, peer_identity);
+    let valid_peer_identity =
+        ban_on_offence(peer_manager, authenticated_public_key, valid_peer_identity_result).await?;
+
+    let (initiator_seed, responder_seed) = if direction.is_outbound() {
+        (&our_seed, &peer_seed)
+    } else {
+        (&peer_seed, &our_seed)
+    };
+    noise_config.session_tickets().issue(
+        authenticated_public_key.clone(),
+        socket.handshake_hash(),
+        initiator_seed,
+        responder_seed,
+        valid_peer_identity.clone(),
+    );
+
+    Ok(valid_peer_identity)
+}
+
 /// Validate the peer identity info.
 ///
 /// The following process is used to validate the peer:
@@ -107,6 +177,7 @@ pub(super) fn validate_peer_identity_message(
         supported_protocols,
         user_agent,
         identity_signature,
+        session_ticket_seed: _,
     } = peer_identity_msg;
 
     // Perform basic length checks before parsing
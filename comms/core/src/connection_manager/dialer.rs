@@ -46,6 +46,7 @@ use super::{direction::ConnectionDirection, error::ConnectionManagerError, peer_
 use crate::connection_manager::metrics;
 use crate::{
     backoff::Backoff,
+    bandwidth::BandwidthMeter,
     connection_manager::{
         common,
         common::ValidatedPeerIdentityExchange,
@@ -100,6 +101,7 @@ pub struct Dialer<TTransport, TBackoff> {
     shutdown: Option<ShutdownSignal>,
     pending_dial_requests: HashMap<NodeId, Vec<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>>,
     our_supported_protocols: Arc<Vec<ProtocolId>>,
+    bandwidth_meter: BandwidthMeter,
 }
 
 impl<TTransport, TBackoff> Dialer<TTransport, TBackoff>
@@ -117,6 +119,7 @@ where
         backoff: TBackoff,
         request_rx: mpsc::Receiver<DialerRequest>,
         conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
+        bandwidth_meter: BandwidthMeter,
         shutdown: ShutdownSignal,
     ) -> Self {
         Self {
@@ -132,6 +135,7 @@ where
             shutdown: Some(shutdown),
             pending_dial_requests: Default::default(),
             our_supported_protocols: Arc::new(Vec::new()),
+            bandwidth_meter,
         }
     }
 
@@ -341,6 +345,7 @@ where
         let node_identity = Arc::clone(&self.node_identity);
         let conn_man_notifier = self.conn_man_notifier.clone();
         let supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth_meter = self.bandwidth_meter.clone();
         let noise_config = self.noise_config.clone();
         let config = self.config.clone();
         let peer_manager = self.peer_manager.clone();
@@ -348,7 +353,7 @@ where
         let span = span!(Level::TRACE, "handle_dial_peer_request_inner1");
         let dial_fut = async move {
             let (dial_state, dial_result) =
-                Self::dial_peer_with_retry(dial_state, noise_config, transport, backoff, &config).await;
+                Self::dial_peer_with_retry(dial_state, &noise_config, transport, backoff, &config).await;
 
             let cancel_signal = dial_state.get_cancel_signal();
 
@@ -370,11 +375,13 @@ where
                     let result = Self::perform_socket_upgrade_procedure(
                         &peer_manager,
                         &node_identity,
+                        &noise_config,
                         socket,
                         addr.clone(),
                         authenticated_public_key,
                         conn_man_notifier,
                         supported_protocols,
+                        bandwidth_meter,
                         &config,
                         cancel_signal,
                     )
@@ -420,42 +427,28 @@ where
     async fn perform_socket_upgrade_procedure(
         peer_manager: &PeerManager,
         node_identity: &NodeIdentity,
+        noise_config: &NoiseConfig,
         mut socket: NoiseSocket<TTransport::Output>,
         dialed_addr: Multiaddr,
         authenticated_public_key: CommsPublicKey,
         conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Arc<Vec<ProtocolId>>,
+        bandwidth_meter: BandwidthMeter,
         config: &ConnectionManagerConfig,
         cancel_signal: ShutdownSignal,
     ) -> Result<(PeerConnection, ValidatedPeerIdentityExchange), ConnectionManagerError> {
         static CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Outbound;
-        debug!(
-            target: LOG_TARGET,
-            "Starting peer identity exchange for peer with public key '{}'", authenticated_public_key
-        );
-
-        let peer_identity_result = common::perform_identity_exchange(
+        let peer_identity = common::exchange_or_resume_peer_identity(
             &mut socket,
+            noise_config,
+            CONNECTION_DIRECTION,
             node_identity,
+            peer_manager,
+            &authenticated_public_key,
             &*our_supported_protocols,
-            config.network_info.clone(),
+            config,
         )
-        .await;
-
-        let peer_identity =
-            common::ban_on_offence(peer_manager, &authenticated_public_key, peer_identity_result).await?;
-
-        if cancel_signal.is_terminated() {
-            return Err(ConnectionManagerError::DialCancelled);
-        }
-
-        let peer_identity_result = common::validate_peer_identity_message(
-            &config.peer_validation_config,
-            &authenticated_public_key,
-            peer_identity,
-        );
-        let peer_identity =
-            common::ban_on_offence(peer_manager, &authenticated_public_key, peer_identity_result).await?;
+        .await?;
 
         if cancel_signal.is_terminated() {
             return Err(ConnectionManagerError::DialCancelled);
@@ -478,6 +471,7 @@ where
             conn_man_notifier,
             our_supported_protocols,
             peer_identity.metadata.supported_protocols.clone(),
+            bandwidth_meter,
         );
 
         Ok((peer_connection, peer_identity))
@@ -485,7 +479,7 @@ where
 
     async fn dial_peer_with_retry(
         dial_state: DialState,
-        noise_config: NoiseConfig,
+        noise_config: &NoiseConfig,
         transport: TTransport,
         backoff: Arc<TBackoff>,
         config: &ConnectionManagerConfig,
@@ -511,7 +505,7 @@ where
             tokio::select! {
                 _ = delay => {
                     debug!(target: LOG_TARGET, "[Attempt {}] Connecting to peer '{}'", current_state.num_attempts(), current_state.peer().node_id.short_str());
-                    match Self::dial_peer(current_state, &noise_config, &current_transport, config.network_info.network_byte).await {
+                    match Self::dial_peer(current_state, noise_config, &current_transport, config.network_info.network_byte).await {
                         (state, Ok((socket, addr))) => {
                             debug!(target: LOG_TARGET, "Dial succeeded for peer '{}' after {} attempt(s)", state.peer().node_id.short_str(), state.num_attempts());
                             break (state, Ok((socket, addr)));
@@ -563,6 +557,7 @@ where
 
             let moved_address = address.clone();
             let node_id = dial_state.peer().node_id.clone();
+            let public_key = dial_state.peer().public_key.clone();
             let dial_fut = async move {
                 let mut timer = Instant::now();
                 let mut socket =
@@ -592,9 +587,7 @@ where
                     .await
                     .map_err(|_| ConnectionManagerError::WireFormatSendFailed)?;
 
-                let noise_socket = noise_config
-                    .upgrade_socket(socket, ConnectionDirection::Outbound)
-                    .await?;
+                let noise_socket = noise_config.resume_or_upgrade_socket(socket, &public_key).await?;
 
                 let noise_upgrade_time = timer.elapsed();
                 debug!(
@@ -24,6 +24,7 @@ use thiserror::Error;
 use tokio::{sync::mpsc, time::error::Elapsed};
 
 use crate::{
+    bandwidth::BandwidthQuotaExceeded,
     connection_manager::PeerConnectionRequest,
     noise,
     peer_manager::PeerManagerError,
@@ -115,6 +116,8 @@ pub enum PeerConnectionError {
     ProtocolError(#[from] ProtocolError),
     #[error("Protocol negotiation timeout")]
     ProtocolNegotiationTimeout,
+    #[error("{0}")]
+    BandwidthQuotaExceeded(#[from] BandwidthQuotaExceeded),
 }
 
 impl From<Elapsed> for PeerConnectionError {
@@ -53,6 +53,7 @@ use super::{
 #[cfg(feature = "metrics")]
 use crate::connection_manager::metrics;
 use crate::{
+    bandwidth::BandwidthMeter,
     bounded_executor::BoundedExecutor,
     connection_manager::{
         liveness::LivenessSession,
@@ -84,6 +85,7 @@ pub struct PeerListener<TTransport> {
     our_supported_protocols: Arc<Vec<ProtocolId>>,
     liveness_session_count: Arc<AtomicUsize>,
     on_listening: OneshotTrigger<Result<Multiaddr, ConnectionManagerError>>,
+    bandwidth_meter: BandwidthMeter,
 }
 
 impl<TTransport> PeerListener<TTransport>
@@ -99,6 +101,7 @@ where
         conn_man_notifier: mpsc::Sender<ConnectionManagerEvent>,
         peer_manager: Arc<PeerManager>,
         node_identity: Arc<NodeIdentity>,
+        bandwidth_meter: BandwidthMeter,
         shutdown_signal: ShutdownSignal,
     ) -> Self {
         Self {
@@ -114,6 +117,7 @@ where
             liveness_session_count: Arc::new(AtomicUsize::new(config.liveness_max_sessions)),
             config,
             on_listening: oneshot_trigger::channel(),
+            bandwidth_meter,
         }
     }
 
@@ -238,6 +242,7 @@ where
         let our_supported_protocols = self.our_supported_protocols.clone();
         let liveness_session_count = self.liveness_session_count.clone();
         let shutdown_signal = self.shutdown_signal.clone();
+        let bandwidth_meter = self.bandwidth_meter.clone();
 
         let span = span!(Level::TRACE, "connection_mann::listener::inbound_task",);
         let inbound_fut = async move {
@@ -254,6 +259,7 @@ where
                         socket,
                         peer_addr,
                         our_supported_protocols,
+                        bandwidth_meter,
                         &config,
                     )
                     .await;
@@ -345,6 +351,7 @@ where
         socket: TTransport::Output,
         peer_addr: Multiaddr,
         our_supported_protocols: Arc<Vec<ProtocolId>>,
+        bandwidth_meter: BandwidthMeter,
         config: &ConnectionManagerConfig,
     ) -> Result<PeerConnection, ConnectionManagerError> {
         const CONNECTION_DIRECTION: ConnectionDirection = ConnectionDirection::Inbound;
@@ -370,30 +377,17 @@ where
         // Check if we know the peer and if it is banned
         let known_peer = common::find_unbanned_peer(peer_manager, &authenticated_public_key).await?;
 
-        debug!(
-            target: LOG_TARGET,
-            "Starting peer identity exchange for peer with public key '{}'", authenticated_public_key
-        );
-
-        let peer_identity_result = common::perform_identity_exchange(
+        let valid_peer_identity = common::exchange_or_resume_peer_identity(
             &mut noise_socket,
+            &noise_config,
+            CONNECTION_DIRECTION,
             node_identity,
+            peer_manager,
+            &authenticated_public_key,
             &*our_supported_protocols,
-            config.network_info.clone(),
+            config,
         )
-        .await;
-
-        let peer_identity =
-            common::ban_on_offence(peer_manager, &authenticated_public_key, peer_identity_result).await?;
-
-        let valid_peer_identity_result = common::validate_peer_identity_message(
-            &config.peer_validation_config,
-            &authenticated_public_key,
-            peer_identity,
-        );
-
-        let valid_peer_identity =
-            common::ban_on_offence(peer_manager, &authenticated_public_key, valid_peer_identity_result).await?;
+        .await?;
 
         let peer = common::create_or_update_peer_from_validated_peer_identity(
             known_peer,
@@ -413,6 +407,7 @@ where
             conn_man_notifier,
             our_supported_protocols,
             valid_peer_identity.metadata.supported_protocols,
+            bandwidth_meter,
         );
 
         peer_manager.add_peer(peer).await?;
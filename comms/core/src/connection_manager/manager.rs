@@ -47,6 +47,7 @@ use crate::connection_manager::metrics;
 use crate::connection_manager::ConnectionDirection;
 use crate::{
     backoff::Backoff,
+    bandwidth::BandwidthMeter,
     connection_manager::ConnectionId,
     multiplexing::Substream,
     noise::NoiseConfig,
@@ -119,6 +120,10 @@ pub struct ConnectionManagerConfig {
     /// the responder will wait 2 x this value (1 per receive) before timing out.
     /// Default: 3s
     pub noise_handshake_recv_timeout: Duration,
+    /// If set, session tickets with this lifetime are issued to peers that support noise session resumption. A
+    /// reconnecting peer that holds a valid ticket performs a 1-RTT handshake and skips the identity exchange.
+    /// Default: None (disabled)
+    pub noise_session_ticket_lifetime: Option<Duration>,
     /// The number of liveness check sessions to allow. Default: 0
     pub liveness_max_sessions: usize,
     /// CIDR blocks that allowlist liveness checks. Default: Localhost only (127.0.0.1/32)
@@ -151,6 +156,7 @@ impl Default for ConnectionManagerConfig {
             auxiliary_tcp_listener_address: None,
             peer_validation_config: PeerValidatorConfig::default(),
             noise_handshake_recv_timeout: Duration::from_secs(6),
+            noise_session_ticket_lifetime: None,
         }
     }
 }
@@ -206,13 +212,15 @@ where
         node_identity: Arc<NodeIdentity>,
         peer_manager: Arc<PeerManager>,
         connection_manager_events_tx: broadcast::Sender<Arc<ConnectionManagerEvent>>,
+        bandwidth_meter: BandwidthMeter,
         shutdown_signal: ShutdownSignal,
     ) -> Self {
         let (internal_event_tx, internal_event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
         let (dialer_tx, dialer_rx) = mpsc::channel(DIALER_REQUEST_CHANNEL_SIZE);
 
-        let noise_config =
-            NoiseConfig::new(node_identity.clone()).with_recv_timeout(config.noise_handshake_recv_timeout);
+        let noise_config = NoiseConfig::new(node_identity.clone())
+            .with_recv_timeout(config.noise_handshake_recv_timeout)
+            .with_session_ticket_lifetime(config.noise_session_ticket_lifetime);
 
         let listener = PeerListener::new(
             config.clone(),
@@ -222,6 +230,7 @@ where
             internal_event_tx.clone(),
             peer_manager.clone(),
             node_identity.clone(),
+            bandwidth_meter.clone(),
             shutdown_signal.clone(),
         );
 
@@ -240,6 +249,7 @@ where
                 internal_event_tx.clone(),
                 peer_manager.clone(),
                 node_identity.clone(),
+                bandwidth_meter.clone(),
                 shutdown_signal.clone(),
             )
         });
@@ -253,6 +263,7 @@ where
             backoff,
             dialer_rx,
             internal_event_tx,
+            bandwidth_meter,
             shutdown_signal.clone(),
         );
 
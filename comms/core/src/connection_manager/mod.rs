@@ -34,6 +34,9 @@ mod listener;
 mod metrics;
 
 mod common;
+#[cfg(test)]
+pub(crate) use common::PeerIdentityMetadata;
+pub(crate) use common::ValidatedPeerIdentityExchange;
 
 mod direction;
 pub use direction::ConnectionDirection;
@@ -52,6 +52,7 @@ use crate::protocol::rpc::{
     RPC_MAX_FRAME_SIZE,
 };
 use crate::{
+    bandwidth::BandwidthMeter,
     framing,
     framing::CanonicalFraming,
     multiplexing::{Control, IncomingSubstreams, Substream, Yamux},
@@ -75,6 +76,7 @@ pub fn create(
     event_notifier: mpsc::Sender<ConnectionManagerEvent>,
     our_supported_protocols: Arc<Vec<ProtocolId>>,
     their_supported_protocols: Vec<ProtocolId>,
+    bandwidth_meter: BandwidthMeter,
 ) -> PeerConnection {
     trace!(
         target: LOG_TARGET,
@@ -103,6 +105,7 @@ pub fn create(
         event_notifier,
         our_supported_protocols,
         their_supported_protocols,
+        bandwidth_meter,
     );
     tokio::spawn(peer_actor.run());
 
@@ -332,6 +335,7 @@ struct PeerConnectionActor {
     inbound_protocol_negotiations:
         FuturesUnordered<BoxFuture<'static, Result<(ProtocolId, Substream), PeerConnectionError>>>,
     their_supported_protocols: Vec<ProtocolId>,
+    bandwidth_meter: BandwidthMeter,
 }
 
 impl PeerConnectionActor {
@@ -344,6 +348,7 @@ impl PeerConnectionActor {
         event_notifier: mpsc::Sender<ConnectionManagerEvent>,
         our_supported_protocols: Arc<Vec<ProtocolId>>,
         their_supported_protocols: Vec<ProtocolId>,
+        bandwidth_meter: BandwidthMeter,
     ) -> Self {
         Self {
             id,
@@ -356,6 +361,7 @@ impl PeerConnectionActor {
             our_supported_protocols,
             inbound_protocol_negotiations: FuturesUnordered::new(),
             their_supported_protocols,
+            bandwidth_meter,
         }
     }
 
@@ -429,6 +435,7 @@ impl PeerConnectionActor {
 
     async fn handle_incoming_substream(&mut self, mut stream: Substream) {
         let our_supported_protocols = self.our_supported_protocols.clone();
+        let bandwidth_meter = self.bandwidth_meter.clone();
         self.inbound_protocol_negotiations.push(Box::pin(async move {
             let mut protocol_negotiation = ProtocolNegotiation::new(&mut stream);
 
@@ -438,6 +445,8 @@ impl PeerConnectionActor {
             )
             .await
             .map_err(|_| PeerConnectionError::ProtocolNegotiationTimeout)??;
+            bandwidth_meter.check_protocol_quota(&selected_protocol)?;
+            stream.set_bandwidth_counter(bandwidth_meter.protocol_counter(&selected_protocol));
             Ok((selected_protocol, stream))
         }));
     }
@@ -493,6 +502,7 @@ impl PeerConnectionActor {
             String::from_utf8_lossy(&protocol),
             self.peer_node_id.short_str()
         );
+        self.bandwidth_meter.check_protocol_quota(&protocol)?;
         let mut stream = self.control.open_stream().await?;
 
         let mut negotiation = ProtocolNegotiation::new(&mut stream);
@@ -505,6 +515,7 @@ impl PeerConnectionActor {
             let fut = negotiation.negotiate_protocol_outbound(&selected_protocols);
             time::timeout(PROTOCOL_NEGOTIATION_TIMEOUT, fut).await??
         };
+        stream.set_bandwidth_counter(self.bandwidth_meter.protocol_counter(&selected_protocol));
 
         Ok(NegotiatedSubstream::new(selected_protocol, stream))
     }
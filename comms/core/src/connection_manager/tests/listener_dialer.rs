@@ -33,6 +33,7 @@ use tokio::{
 
 use crate::{
     backoff::ConstantBackoff,
+    bandwidth::BandwidthMeter,
     connection_manager::{
         dialer::{Dialer, DialerRequest},
         listener::PeerListener,
@@ -63,6 +64,7 @@ async fn listen() -> Result<(), Box<dyn Error>> {
         event_tx,
         peer_manager,
         node_identity,
+        BandwidthMeter::default(),
         shutdown.to_signal(),
     );
 
@@ -97,6 +99,7 @@ async fn smoke() {
         event_tx.clone(),
         peer_manager1.clone(),
         node_identity1.clone(),
+        BandwidthMeter::default(),
         shutdown.to_signal(),
     );
     listener.set_supported_protocols(supported_protocols.clone());
@@ -117,6 +120,7 @@ async fn smoke() {
         ConstantBackoff::new(Duration::from_millis(100)),
         request_rx,
         event_tx,
+        BandwidthMeter::default(),
         shutdown.to_signal(),
     );
     dialer.set_supported_protocols(supported_protocols.clone());
@@ -200,6 +204,7 @@ async fn banned() {
         event_tx.clone(),
         peer_manager1.clone(),
         node_identity1.clone(),
+        BandwidthMeter::default(),
         shutdown.to_signal(),
     );
     listener.set_supported_protocols(supported_protocols.clone());
@@ -225,6 +230,7 @@ async fn banned() {
         ConstantBackoff::new(Duration::from_millis(100)),
         request_rx,
         event_tx,
+        BandwidthMeter::default(),
         shutdown.to_signal(),
     );
     dialer.set_supported_protocols(supported_protocols);
@@ -33,6 +33,7 @@ use tokio::{
 
 use crate::{
     backoff::ConstantBackoff,
+    bandwidth::BandwidthMeter,
     connection_manager::{
         ConnectionManager,
         ConnectionManagerError,
@@ -71,6 +72,7 @@ async fn connect_to_nonexistent_peer() {
         node_identity,
         peer_manager,
         event_tx,
+        BandwidthMeter::default(),
         shutdown.to_signal(),
     );
 
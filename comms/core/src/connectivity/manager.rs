@@ -42,6 +42,7 @@ use super::{
     connection_pool::{ConnectionPool, ConnectionStatus},
     connection_stats::PeerConnectionStats,
     error::ConnectivityError,
+    peer_quality::PeerQualityMetrics,
     requester::{ConnectivityEvent, ConnectivityRequest},
     selection::ConnectivitySelection,
     ConnectivityEventTx,
@@ -53,7 +54,7 @@ use crate::{
         ConnectionManagerEvent,
         ConnectionManagerRequester,
     },
-    peer_manager::NodeId,
+    peer_manager::{NodeId, PeerOffense},
     utils::datetime::format_duration,
     NodeIdentity,
     PeerConnection,
@@ -89,6 +90,7 @@ impl ConnectivityManager {
             peer_manager: self.peer_manager.clone(),
             event_tx: self.event_tx,
             connection_stats: HashMap::new(),
+            peer_quality: HashMap::new(),
             node_identity: self.node_identity,
             pool: ConnectionPool::new(),
             shutdown_signal: self.shutdown_signal,
@@ -147,6 +149,7 @@ struct ConnectivityManagerActor {
     peer_manager: Arc<PeerManager>,
     event_tx: ConnectivityEventTx,
     connection_stats: HashMap<NodeId, PeerConnectionStats>,
+    peer_quality: HashMap<NodeId, PeerQualityMetrics>,
     pool: ConnectionPool,
     shutdown_signal: ShutdownSignal,
     #[cfg(feature = "metrics")]
@@ -251,6 +254,18 @@ impl ConnectivityManagerActor {
                 };
                 let _result = reply.send(peer);
             },
+            RecordPeerQuality(node_id, sample) => {
+                self.peer_quality.entry(node_id).or_default().record(sample);
+            },
+            GetPeerQualityMetrics(reply) => {
+                let metrics = self
+                    .peer_quality
+                    .iter()
+                    .filter(|(node_id, _)| self.pool.get_connection_status(node_id) == ConnectionStatus::Connected)
+                    .map(|(node_id, metrics)| (node_id.clone(), metrics.clone()))
+                    .collect();
+                let _result = reply.send(metrics);
+            },
             GetAllConnectionStates(reply) => {
                 let states = self.pool.all().into_iter().cloned().collect();
                 let _result = reply.send(states);
@@ -267,6 +282,11 @@ impl ConnectivityManagerActor {
                     // we banned the peer
                 }
             },
+            ReportOffense(node_id, offense, reason) => {
+                if let Err(err) = self.report_offense(&node_id, offense, reason).await {
+                    error!(target: LOG_TARGET, "Error when reporting peer offense: {:?}", err);
+                }
+            },
             AddPeerToAllowList(node_id) => {
                 if !self.allow_list.contains(&node_id) {
                     self.allow_list.push(node_id)
@@ -459,7 +479,7 @@ impl ConnectivityManagerActor {
             self.pool.count_connected_nodes()
         );
 
-        let conns = selection.select(&self.pool);
+        let conns = selection.select(&self.pool, &self.peer_quality);
         debug!(target: LOG_TARGET, "Selected {} connections(s)", conns.len());
 
         Ok(conns.into_iter().cloned().collect())
@@ -897,6 +917,31 @@ impl ConnectivityManagerActor {
         Ok(())
     }
 
+    async fn report_offense(
+        &mut self,
+        node_id: &NodeId,
+        offense: PeerOffense,
+        reason: String,
+    ) -> Result<(), ConnectivityError> {
+        debug!(
+            target: LOG_TARGET,
+            "Peer {} committed offense '{}': {}", node_id, offense, reason
+        );
+        let maybe_ban_duration = self.peer_manager.record_offense(node_id, offense, reason.clone()).await;
+        if let Some(duration) = maybe_ban_duration {
+            if self.allow_list.contains(node_id) {
+                info!(
+                    target: LOG_TARGET,
+                    "Peer is excluded from being banned as it was found in the AllowList, NodeId: {:?}", node_id
+                );
+                return Ok(());
+            }
+            self.ban_peer(node_id, duration, format!("{}: {}", offense, reason))
+                .await?;
+        }
+        Ok(())
+    }
+
     fn cleanup_connection_stats(&mut self) {
         let mut to_remove = Vec::new();
         for node_id in self.connection_stats.keys() {
@@ -911,6 +956,13 @@ impl ConnectivityManagerActor {
         for node_id in to_remove {
             self.connection_stats.remove(&node_id);
         }
+        let pool = &self.pool;
+        self.peer_quality.retain(|node_id, _| {
+            !matches!(
+                pool.get_connection_status(node_id),
+                ConnectionStatus::NotConnected | ConnectionStatus::Failed | ConnectionStatus::Disconnected
+            )
+        });
     }
 }
 
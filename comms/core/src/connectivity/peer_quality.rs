@@ -0,0 +1,147 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, time::Duration};
+
+/// The inverse weight given to a new RTT sample in the smoothed RTT i.e. 1/8 as in RFC 6298
+const RTT_SMOOTHING_DIVISOR: u32 = 8;
+/// How much a peer's smoothed RTT is inflated per unit of message failure rate when ranking peers.
+const FAILURE_RATE_PENALTY: f64 = 4.0;
+
+/// A quality sample reported to the connectivity manager for a peer.
+#[derive(Debug, Clone, Copy)]
+pub enum PeerQualitySample {
+    /// A round-trip time measurement
+    Rtt(Duration),
+    /// A number of messages and their total size in bytes were sent to the peer
+    MessagesSent { count: u64, bytes: u64 },
+    /// A number of messages could not be sent to the peer
+    MessagesFailed(u64),
+    /// A number of bytes were received from the peer
+    BytesReceived(u64),
+}
+
+/// Connection quality metrics for a peer. These are used to prefer well-performing peers when selecting connections.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeerQualityMetrics {
+    /// The most recent RTT measurement, or None if the RTT has never been measured
+    pub last_rtt: Option<Duration>,
+    /// Exponentially smoothed RTT, or None if the RTT has never been measured
+    pub smoothed_rtt: Option<Duration>,
+    /// The number of messages successfully sent to the peer
+    pub messages_sent: u64,
+    /// The number of messages that could not be sent to the peer
+    pub messages_failed: u64,
+    /// The number of message bytes sent to the peer
+    pub bytes_sent: u64,
+    /// The number of message bytes received from the peer
+    pub bytes_received: u64,
+}
+
+impl PeerQualityMetrics {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Update the metrics with the given sample
+    pub fn record(&mut self, sample: PeerQualitySample) {
+        match sample {
+            PeerQualitySample::Rtt(rtt) => self.record_rtt(rtt),
+            PeerQualitySample::MessagesSent { count, bytes } => {
+                self.messages_sent = self.messages_sent.saturating_add(count);
+                self.bytes_sent = self.bytes_sent.saturating_add(bytes);
+            },
+            PeerQualitySample::MessagesFailed(count) => {
+                self.messages_failed = self.messages_failed.saturating_add(count);
+            },
+            PeerQualitySample::BytesReceived(bytes) => {
+                self.bytes_received = self.bytes_received.saturating_add(bytes);
+            },
+        }
+    }
+
+    fn record_rtt(&mut self, rtt: Duration) {
+        self.last_rtt = Some(rtt);
+        self.smoothed_rtt = Some(match self.smoothed_rtt {
+            Some(srtt) => (srtt * (RTT_SMOOTHING_DIVISOR - 1) + rtt) / RTT_SMOOTHING_DIVISOR,
+            None => rtt,
+        });
+    }
+
+    /// Returns the fraction of messages that could not be sent to the peer, or 0.0 if no messages have been sent.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn failure_rate(&self) -> f64 {
+        let total = self.messages_sent.saturating_add(self.messages_failed);
+        if total == 0 {
+            return 0.0;
+        }
+        self.messages_failed as f64 / total as f64
+    }
+
+    /// Returns a latency score used to rank peers, lower is better. This is the smoothed RTT inflated by the message
+    /// failure rate. None is returned if the RTT of the peer has never been measured.
+    pub fn latency_score(&self) -> Option<Duration> {
+        self.smoothed_rtt
+            .map(|srtt| srtt.mul_f64(1.0 + self.failure_rate() * FAILURE_RATE_PENALTY))
+    }
+}
+
+impl fmt::Display for PeerQualityMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.smoothed_rtt {
+            Some(srtt) => write!(f, "RTT: {:.2?}, ", srtt)?,
+            None => write!(f, "RTT: --, ")?,
+        }
+        write!(
+            f,
+            "sent: {} msg(s) ({} bytes), failed: {} msg(s), received: {} bytes",
+            self.messages_sent, self.bytes_sent, self.messages_failed, self.bytes_received
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn peer_quality_metrics() {
+        let mut metrics = PeerQualityMetrics::new();
+        assert!(metrics.latency_score().is_none());
+        assert!(metrics.failure_rate().abs() < f64::EPSILON);
+
+        metrics.record(PeerQualitySample::Rtt(Duration::from_millis(100)));
+        assert_eq!(metrics.smoothed_rtt, Some(Duration::from_millis(100)));
+        metrics.record(PeerQualitySample::Rtt(Duration::from_millis(900)));
+        assert_eq!(metrics.last_rtt, Some(Duration::from_millis(900)));
+        assert_eq!(metrics.smoothed_rtt, Some(Duration::from_millis(200)));
+        assert_eq!(metrics.latency_score(), Some(Duration::from_millis(200)));
+
+        metrics.record(PeerQualitySample::MessagesSent { count: 3, bytes: 300 });
+        metrics.record(PeerQualitySample::MessagesFailed(1));
+        metrics.record(PeerQualitySample::BytesReceived(50));
+        assert_eq!(metrics.bytes_sent, 300);
+        assert_eq!(metrics.bytes_received, 50);
+        assert!((metrics.failure_rate() - 0.25).abs() < f64::EPSILON);
+        assert_eq!(metrics.latency_score(), Some(Duration::from_millis(400)));
+    }
+}
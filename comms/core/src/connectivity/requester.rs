@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::HashMap,
     fmt,
     time::{Duration, Instant},
 };
@@ -28,7 +29,12 @@ use std::{
 use futures::{future, stream::FuturesUnordered, Stream};
 use log::*;
 use tokio::{
-    sync::{broadcast, broadcast::error::RecvError, mpsc, oneshot},
+    sync::{
+        broadcast,
+        broadcast::error::RecvError,
+        mpsc::{self, error::TrySendError},
+        oneshot,
+    },
     time,
 };
 
@@ -37,10 +43,12 @@ use super::{
     error::ConnectivityError,
     manager::ConnectivityStatus,
     ConnectivitySelection,
+    PeerQualityMetrics,
+    PeerQualitySample,
 };
 use crate::{
     connection_manager::ConnectionManagerError,
-    peer_manager::{NodeId, Peer},
+    peer_manager::{NodeId, Peer, PeerOffense},
     PeerConnection,
 };
 
@@ -98,9 +106,12 @@ pub enum ConnectivityRequest {
     GetAllConnectionStates(oneshot::Sender<Vec<PeerConnectionState>>),
     GetActiveConnections(oneshot::Sender<Vec<PeerConnection>>),
     BanPeer(NodeId, Duration, String),
+    ReportOffense(NodeId, PeerOffense, String),
     AddPeerToAllowList(NodeId),
     RemovePeerFromAllowList(NodeId),
     GetPeerStats(NodeId, oneshot::Sender<Option<Peer>>),
+    RecordPeerQuality(NodeId, PeerQualitySample),
+    GetPeerQualityMetrics(oneshot::Sender<HashMap<NodeId, PeerQualityMetrics>>),
 }
 
 /// Handle to make requests and read events from the ConnectivityManager actor.
@@ -213,6 +224,33 @@ impl ConnectivityRequester {
         reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
     }
 
+    /// Report a connection quality sample for a peer. This is best-effort: the sample is discarded if the
+    /// connectivity actor is too busy to accept it.
+    pub fn record_peer_quality(&self, node_id: NodeId, sample: PeerQualitySample) -> Result<(), ConnectivityError> {
+        match self
+            .sender
+            .try_send(ConnectivityRequest::RecordPeerQuality(node_id, sample))
+        {
+            Ok(_) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(TrySendError::Closed(_)) => Err(ConnectivityError::ActorDisconnected),
+        }
+    }
+
+    /// Report a round-trip time measurement for a peer.
+    pub fn record_peer_rtt(&self, node_id: NodeId, rtt: Duration) -> Result<(), ConnectivityError> {
+        self.record_peer_quality(node_id, PeerQualitySample::Rtt(rtt))
+    }
+
+    /// Get the [PeerQualityMetrics](self::PeerQualityMetrics) for all connected peers.
+    pub async fn get_peer_quality_metrics(&self) -> Result<HashMap<NodeId, PeerQualityMetrics>, ConnectivityError> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(ConnectivityRequest::GetPeerQualityMetrics(reply_tx))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        reply_rx.await.map_err(|_| ConnectivityError::ActorResponseCancelled)
+    }
+
     /// Get the current [ConnectivityStatus](self::ConnectivityStatus).
     pub async fn get_connectivity_status(&mut self) -> Result<ConnectivityStatus, ConnectivityError> {
         let (reply_tx, reply_rx) = oneshot::channel();
@@ -263,6 +301,21 @@ impl ConnectivityRequester {
             .await
     }
 
+    /// Reports an offense committed by a peer. The offense is scored by the peer manager's ban policy and the peer is
+    /// banned if its offense score reaches the ban threshold.
+    pub async fn report_offense<T: Into<String>>(
+        &mut self,
+        node_id: NodeId,
+        offense: PeerOffense,
+        reason: T,
+    ) -> Result<(), ConnectivityError> {
+        self.sender
+            .send(ConnectivityRequest::ReportOffense(node_id, offense, reason.into()))
+            .await
+            .map_err(|_| ConnectivityError::ActorDisconnected)?;
+        Ok(())
+    }
+
     /// Adds a peer to an allow list, preventing it from being banned.
     pub async fn add_peer_to_allow_list(&mut self, node_id: NodeId) -> Result<(), ConnectivityError> {
         self.sender
@@ -20,11 +20,11 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fmt, fmt::Display};
+use std::{collections::HashMap, fmt, fmt::Display};
 
 use rand::{rngs::OsRng, seq::SliceRandom};
 
-use super::connection_pool::ConnectionPool;
+use super::{connection_pool::ConnectionPool, PeerQualityMetrics};
 use crate::{connectivity::connection_pool::ConnectionStatus, peer_manager::NodeId, PeerConnection};
 
 /// Selection query for PeerConnections.
@@ -45,6 +45,7 @@ enum SelectionMode {
     AllNodes,
     RandomNodes(usize),
     ClosestTo(Box<NodeId>, usize),
+    LowestLatency(usize),
 }
 
 impl ConnectivitySelection {
@@ -80,9 +81,32 @@ impl ConnectivitySelection {
         }
     }
 
+    /// Select `n` peer connections ordered by their latency score, lowest first, excluding the given `exclude`
+    /// [NodeId]s. Peers whose latency has not been measured are selected last, in random order.
+    ///
+    /// [NodeId](crate::peer_manager::NodeId)
+    pub fn lowest_latency(n: usize, exclude: Vec<NodeId>) -> Self {
+        Self {
+            selection_mode: SelectionMode::LowestLatency(n),
+            excluded_peers: exclude,
+        }
+    }
+
+    /// Returns a query that will return all connections for peers with `PeerFeatures::COMMUNICATION_NODES`, ordered
+    /// by their latency score, excluding the given [NodeId]s.
+    ///
+    /// [NodeId](crate::peer_manager::NodeId)
+    pub fn all_nodes_by_latency(exclude: Vec<NodeId>) -> Self {
+        Self::lowest_latency(usize::MAX, exclude)
+    }
+
     /// Select peers from the pool according to the ConnectivitySelection
-    pub fn select<'a>(&self, pool: &'a ConnectionPool) -> Vec<&'a PeerConnection> {
-        use SelectionMode::{AllNodes, ClosestTo, RandomNodes};
+    pub fn select<'a>(
+        &self,
+        pool: &'a ConnectionPool,
+        peer_quality: &HashMap<NodeId, PeerQualityMetrics>,
+    ) -> Vec<&'a PeerConnection> {
+        use SelectionMode::{AllNodes, ClosestTo, LowestLatency, RandomNodes};
         match &self.selection_mode {
             AllNodes => select_connected_nodes(pool, &self.excluded_peers),
             RandomNodes(n) => select_random_nodes(pool, *n, &self.excluded_peers),
@@ -91,6 +115,11 @@ impl ConnectivitySelection {
                 connections.truncate(*n);
                 connections.to_vec()
             },
+            LowestLatency(n) => {
+                let mut connections = select_lowest_latency(pool, peer_quality, &self.excluded_peers);
+                connections.truncate(*n);
+                connections
+            },
         }
     }
 }
@@ -119,6 +148,24 @@ fn select_closest<'a>(pool: &'a ConnectionPool, node_id: &NodeId, exclude: &[Nod
     nodes
 }
 
+fn select_lowest_latency<'a>(
+    pool: &'a ConnectionPool,
+    peer_quality: &HashMap<NodeId, PeerQualityMetrics>,
+    exclude: &[NodeId],
+) -> Vec<&'a PeerConnection> {
+    let mut nodes = select_connected_nodes(pool, exclude);
+    // Shuffle first so that peers without a latency score (and peers with equal scores) are not always in pool order
+    nodes.shuffle(&mut OsRng);
+    // None sorts before Some, so peers without a score are moved to the end
+    nodes.sort_by_key(|conn| {
+        let score = peer_quality
+            .get(conn.peer_node_id())
+            .and_then(PeerQualityMetrics::latency_score);
+        (score.is_none(), score)
+    });
+    nodes
+}
+
 fn select_random_nodes<'a>(pool: &'a ConnectionPool, n: usize, exclude: &[NodeId]) -> Vec<&'a PeerConnection> {
     let nodes = select_connected_nodes(pool, exclude);
     nodes.choose_multiple(&mut OsRng, n).copied().collect()
@@ -137,24 +184,26 @@ impl Display for ConnectivitySelection {
 
 impl Display for SelectionMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use SelectionMode::{AllNodes, ClosestTo, RandomNodes};
+        use SelectionMode::{AllNodes, ClosestTo, LowestLatency, RandomNodes};
         match self {
             AllNodes => write!(f, "AllNodes"),
             RandomNodes(n) => write!(f, "RandomNodes({})", n),
             ClosestTo(node_id, n) => write!(f, "ClosestTo({}, {})", node_id, n),
+            LowestLatency(n) => write!(f, "LowestLatency({})", n),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use std::iter::repeat_with;
+    use std::{iter::repeat_with, time::Duration};
 
     use tokio::sync::mpsc;
 
     use super::*;
     use crate::{
         connection_manager::PeerConnectionRequest,
+        connectivity::PeerQualitySample,
         peer_manager::NodeDistance,
         test_utils::{mocks::create_dummy_peer_connection, node_id, node_identity::build_node_identity},
     };
@@ -199,6 +248,32 @@ mod test {
         }
     }
 
+    #[test]
+    fn select_lowest_latency_ordering() {
+        let (pool, _receivers) = create_pool_with_connections(5);
+        let node_ids = pool
+            .all()
+            .into_iter()
+            .map(|state| state.node_id().clone())
+            .collect::<Vec<_>>();
+        let mut peer_quality = HashMap::new();
+        for (i, rtt_ms) in [300, 100, 200].iter().enumerate() {
+            let mut metrics = PeerQualityMetrics::new();
+            metrics.record(PeerQualitySample::Rtt(Duration::from_millis(*rtt_ms)));
+            peer_quality.insert(node_ids[i].clone(), metrics);
+        }
+
+        let conns = select_lowest_latency(&pool, &peer_quality, &[]);
+        assert_eq!(conns.len(), 5);
+        assert_eq!(conns[0].peer_node_id(), &node_ids[1]);
+        assert_eq!(conns[1].peer_node_id(), &node_ids[2]);
+        assert_eq!(conns[2].peer_node_id(), &node_ids[0]);
+
+        let conns = ConnectivitySelection::lowest_latency(1, vec![node_ids[1].clone()]).select(&pool, &peer_quality);
+        assert_eq!(conns.len(), 1);
+        assert_eq!(conns[0].peer_node_id(), &node_ids[2]);
+    }
+
     #[test]
     fn select_closest_empty() {
         let pool = ConnectionPool::new();
@@ -32,6 +32,7 @@ mod proto;
 mod stream_id;
 
 pub mod backoff;
+pub mod bandwidth;
 pub mod bounded_executor;
 pub mod memsocket;
 pub mod protocol;
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{future::Future, io, pin::Pin, task::Poll};
+use std::{future::Future, io, pin::Pin, sync::Arc, task::Poll};
 
 use futures::{task::Context, Stream};
 use tokio::{
@@ -34,6 +34,7 @@ pub use yamux::ConnectionError;
 use yamux::Mode;
 
 use crate::{
+    bandwidth::ServiceCounter,
     connection_manager::ConnectionDirection,
     stream_id,
     stream_id::StreamId,
@@ -143,6 +144,7 @@ impl Control {
         Ok(Substream {
             stream: stream.compat(),
             _counter_guard: counter_guard,
+            bandwidth_counter: None,
         })
     }
 
@@ -186,6 +188,7 @@ impl Stream for IncomingSubstreams {
             Some(stream) => Poll::Ready(Some(Substream {
                 stream: stream.compat(),
                 _counter_guard: self.substream_counter.new_guard(),
+                bandwidth_counter: None,
             })),
             None => Poll::Ready(None),
         }
@@ -197,6 +200,14 @@ impl Stream for IncomingSubstreams {
 pub struct Substream {
     stream: Compat<yamux::Stream>,
     _counter_guard: AtomicRefCounterGuard,
+    bandwidth_counter: Option<Arc<ServiceCounter>>,
+}
+
+impl Substream {
+    /// Attribute the bytes read from and written to this substream to the given bandwidth counter
+    pub(crate) fn set_bandwidth_counter(&mut self, counter: Arc<ServiceCounter>) {
+        self.bandwidth_counter = Some(counter);
+    }
 }
 
 impl StreamId for Substream {
@@ -207,10 +218,14 @@ impl StreamId for Substream {
 
 impl tokio::io::AsyncRead for Substream {
     fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let filled_before = buf.filled().len();
         match Pin::new(&mut self.stream).poll_read(cx, buf) {
             Poll::Ready(Ok(())) => {
                 #[cfg(feature = "metrics")]
                 super::metrics::TOTAL_BYTES_READ.inc_by(buf.filled().len() as u64);
+                if let Some(counter) = self.bandwidth_counter.as_ref() {
+                    counter.record_received(buf.filled().len().saturating_sub(filled_before) as u64);
+                }
                 Poll::Ready(Ok(()))
             },
             res => res,
@@ -222,7 +237,11 @@ impl tokio::io::AsyncWrite for Substream {
     fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
         #[cfg(feature = "metrics")]
         super::metrics::TOTAL_BYTES_WRITTEN.inc_by(buf.len() as u64);
-        Pin::new(&mut self.stream).poll_write(cx, buf)
+        let result = Pin::new(&mut self.stream).poll_write(cx, buf);
+        if let (Poll::Ready(Ok(n)), Some(counter)) = (&result, self.bandwidth_counter.as_ref()) {
+            counter.record_sent(*n as u64);
+        }
+        result
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
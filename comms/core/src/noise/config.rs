@@ -25,7 +25,7 @@
 use std::{sync::Arc, time::Duration};
 
 use log::*;
-use snow::{self, params::NoiseParams};
+use snow::{self, params::NoiseParams, HandshakeState};
 use tari_utilities::ByteArray;
 use tokio::io::{AsyncRead, AsyncWrite};
 
@@ -34,13 +34,22 @@ use crate::{
     noise::{
         crypto_resolver::TariCryptoResolver,
         error::NoiseError,
-        socket::{Handshake, NoiseSocket},
+        socket::{read_handshake_message, Handshake, NoiseSocket},
+        SessionTicketStore,
     },
     peer_manager::NodeIdentity,
+    types::CommsPublicKey,
 };
 
 const LOG_TARGET: &str = "comms::noise";
 pub(super) const NOISE_PARAMETERS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2b";
+/// Parameters for the 1-RTT handshake used to resume a session with a peer for which a session ticket is held
+pub(super) const NOISE_RESUMPTION_PARAMETERS: &str = "Noise_IKpsk2_25519_ChaChaPoly_BLAKE2b";
+const TARI_PROLOGUE: &[u8] = b"com.tari.comms.noise.prologue";
+/// The length of the first XX handshake message (`-> e`). A longer first message is a resumption (IKpsk2) handshake.
+const XX_FIRST_MESSAGE_LEN: usize = 32;
+/// The pre-shared key is mixed in at the end of the second IKpsk2 handshake message
+const RESUMPTION_PSK_LOCATION: u8 = 2;
 
 /// The Noise protocol configuration to be used to perform a protocol upgrade on an underlying
 /// socket.
@@ -48,17 +57,24 @@ pub(super) const NOISE_PARAMETERS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2b";
 pub struct NoiseConfig {
     node_identity: Arc<NodeIdentity>,
     parameters: NoiseParams,
+    resumption_parameters: NoiseParams,
     recv_timeout: Duration,
+    session_tickets: SessionTicketStore,
 }
 
 impl NoiseConfig {
     /// Create a new NoiseConfig with the provided keypair
     pub fn new(node_identity: Arc<NodeIdentity>) -> Self {
         let parameters: NoiseParams = NOISE_PARAMETERS.parse().expect("Invalid noise parameters");
+        let resumption_parameters: NoiseParams = NOISE_RESUMPTION_PARAMETERS
+            .parse()
+            .expect("Invalid noise resumption parameters");
         Self {
             node_identity,
             parameters,
+            resumption_parameters,
             recv_timeout: Duration::from_secs(3),
+            session_tickets: SessionTicketStore::new(None),
         }
     }
 
@@ -68,33 +84,48 @@ impl NoiseConfig {
         self
     }
 
+    /// Enables session resumption with session tickets that are valid for the given lifetime, or disables it if None
+    /// (default).
+    pub fn with_session_ticket_lifetime(mut self, lifetime: Option<Duration>) -> Self {
+        self.session_tickets = SessionTicketStore::new(lifetime);
+        self
+    }
+
+    pub(crate) fn session_tickets(&self) -> &SessionTicketStore {
+        &self.session_tickets
+    }
+
     /// Upgrades the given socket to using the noise protocol. The upgraded socket and the peer's static key
     /// is returned.
+    ///
+    /// An outbound upgrade always performs a full handshake. An inbound upgrade accepts either a full handshake or a
+    /// resumption handshake from a peer for which a session ticket is held.
     pub async fn upgrade_socket<TSocket>(
         &self,
-        socket: TSocket,
+        mut socket: TSocket,
         direction: ConnectionDirection,
     ) -> Result<NoiseSocket<TSocket>, NoiseError>
     where
         TSocket: AsyncWrite + AsyncRead + Unpin,
     {
-        const TARI_PROLOGUE: &[u8] = b"com.tari.comms.noise.prologue";
-
-        let handshake_state = {
-            let builder = snow::Builder::with_resolver(self.parameters.clone(), Box::<TariCryptoResolver>::default())
-                .prologue(TARI_PROLOGUE)
-                .local_private_key(self.node_identity.secret_key().as_bytes());
-
-            match direction {
-                ConnectionDirection::Outbound => {
-                    debug!(target: LOG_TARGET, "Starting noise initiator handshake ");
-                    builder.build_initiator()?
-                },
-                ConnectionDirection::Inbound => {
-                    debug!(target: LOG_TARGET, "Starting noise responder handshake");
-                    builder.build_responder()?
-                },
-            }
+        let handshake_state = match direction {
+            ConnectionDirection::Outbound => {
+                debug!(target: LOG_TARGET, "Starting noise initiator handshake ");
+                self.builder(&self.parameters).build_initiator()?
+            },
+            ConnectionDirection::Inbound => {
+                let first_message = read_handshake_message(&mut socket, self.recv_timeout)
+                    .await
+                    .map_err(NoiseError::HandshakeFailed)?;
+                if first_message.len() > XX_FIRST_MESSAGE_LEN && self.session_tickets.is_enabled() {
+                    return self.accept_resumption(socket, &first_message).await;
+                }
+
+                debug!(target: LOG_TARGET, "Starting noise responder handshake");
+                let mut state = self.builder(&self.parameters).build_responder()?;
+                read_first_message(&mut state, &first_message)?;
+                state
+            },
         };
 
         let handshake = Handshake::new(socket, handshake_state, self.recv_timeout);
@@ -105,6 +136,90 @@ impl NoiseConfig {
 
         Ok(socket)
     }
+
+    /// Upgrades an outbound socket to using the noise protocol. If a session ticket is held for the peer, the session
+    /// is resumed with a 1-RTT handshake, otherwise a full handshake is performed. If resumption fails, the ticket is
+    /// discarded so that the next connection attempt performs a full handshake.
+    pub async fn resume_or_upgrade_socket<TSocket>(
+        &self,
+        socket: TSocket,
+        peer_public_key: &CommsPublicKey,
+    ) -> Result<NoiseSocket<TSocket>, NoiseError>
+    where
+        TSocket: AsyncWrite + AsyncRead + Unpin,
+    {
+        let ticket = match self.session_tickets.get(peer_public_key) {
+            Some(ticket) => ticket,
+            None => return self.upgrade_socket(socket, ConnectionDirection::Outbound).await,
+        };
+
+        debug!(
+            target: LOG_TARGET,
+            "Resuming noise session with peer '{}' using a session ticket", peer_public_key
+        );
+        let handshake_state = self
+            .builder(&self.resumption_parameters)
+            .remote_public_key(peer_public_key.as_bytes())
+            .psk(RESUMPTION_PSK_LOCATION, ticket.secret())
+            .build_initiator()?;
+
+        let handshake = Handshake::new(socket, handshake_state, self.recv_timeout);
+        match handshake.perform_handshake().await {
+            Ok(mut socket) => {
+                socket.set_session_ticket(ticket);
+                Ok(socket)
+            },
+            Err(err) => {
+                self.session_tickets.remove(peer_public_key);
+                Err(NoiseError::HandshakeFailed(err))
+            },
+        }
+    }
+
+    async fn accept_resumption<TSocket>(
+        &self,
+        socket: TSocket,
+        first_message: &[u8],
+    ) -> Result<NoiseSocket<TSocket>, NoiseError>
+    where
+        TSocket: AsyncWrite + AsyncRead + Unpin,
+    {
+        debug!(target: LOG_TARGET, "Starting noise responder resumption handshake");
+        let mut state = self.builder(&self.resumption_parameters).build_responder()?;
+        read_first_message(&mut state, first_message)?;
+
+        let peer_public_key = state
+            .get_remote_static()
+            .and_then(|s| CommsPublicKey::from_canonical_bytes(s).ok())
+            .ok_or(NoiseError::InvalidRemoteStaticKey)?;
+        let ticket = self
+            .session_tickets
+            .get(&peer_public_key)
+            .ok_or(NoiseError::SessionTicketNotFound(peer_public_key))?;
+        state.set_psk(usize::from(RESUMPTION_PSK_LOCATION), ticket.secret())?;
+
+        let handshake = Handshake::new(socket, state, self.recv_timeout);
+        let mut socket = handshake
+            .perform_handshake()
+            .await
+            .map_err(NoiseError::HandshakeFailed)?;
+        socket.set_session_ticket(ticket);
+
+        Ok(socket)
+    }
+
+    fn builder<'a>(&'a self, parameters: &NoiseParams) -> snow::Builder<'a> {
+        snow::Builder::with_resolver(parameters.clone(), Box::<TariCryptoResolver>::default())
+            .prologue(TARI_PROLOGUE)
+            .local_private_key(self.node_identity.secret_key().as_bytes())
+    }
+}
+
+fn read_first_message(state: &mut HandshakeState, message: &[u8]) -> Result<(), NoiseError> {
+    // The payload of the first message is always empty, so it is at most as long as the message
+    let mut payload = vec![0u8; message.len()];
+    state.read_message(message, &mut payload)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -114,7 +229,12 @@ mod test {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     use super::*;
-    use crate::{memsocket::MemorySocket, peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+    use crate::{
+        connection_manager::{PeerIdentityMetadata, ValidatedPeerIdentityExchange},
+        memsocket::MemorySocket,
+        peer_manager::{PeerFeatures, PeerIdentityClaim},
+        test_utils::node_identity::build_node_identity,
+    };
 
     fn check_noise_params(config: &NoiseConfig) {
         assert_eq!(config.parameters.hash, HashChoice::Blake2b);
@@ -164,4 +284,112 @@ mod test {
         socket_out.read_to_end(&mut read_buf).await.unwrap();
         assert_eq!(read_buf, sample);
     }
+
+    fn validated_identity(node_identity: &NodeIdentity) -> ValidatedPeerIdentityExchange {
+        ValidatedPeerIdentityExchange {
+            claim: PeerIdentityClaim::new(
+                node_identity.public_addresses(),
+                node_identity.features(),
+                node_identity.identity_signature_read().clone().unwrap(),
+            ),
+            metadata: PeerIdentityMetadata {
+                user_agent: "test".to_string(),
+                supported_protocols: vec![],
+            },
+        }
+    }
+
+    /// Performs a full handshake and issues session tickets to both peers
+    async fn establish_session_tickets(initiator: &NoiseConfig, responder: &NoiseConfig) {
+        let (in_socket, out_socket) = MemorySocket::new_pair();
+        let (socket_in, socket_out) = future::join(
+            responder.upgrade_socket(in_socket, ConnectionDirection::Inbound),
+            initiator.resume_or_upgrade_socket(out_socket, responder.node_identity.public_key()),
+        )
+        .map(|(s1, s2)| (s1.unwrap(), s2.unwrap()))
+        .await;
+        assert!(socket_in.session_ticket().is_none());
+        assert!(socket_out.session_ticket().is_none());
+        assert_eq!(socket_in.handshake_hash(), socket_out.handshake_hash());
+
+        let initiator_seed = initiator.session_tickets().generate_seed();
+        let responder_seed = responder.session_tickets().generate_seed();
+        assert!(initiator.session_tickets().issue(
+            responder.node_identity.public_key().clone(),
+            socket_out.handshake_hash(),
+            &initiator_seed,
+            &responder_seed,
+            validated_identity(&responder.node_identity),
+        ));
+        assert!(responder.session_tickets().issue(
+            initiator.node_identity.public_key().clone(),
+            socket_in.handshake_hash(),
+            &initiator_seed,
+            &responder_seed,
+            validated_identity(&initiator.node_identity),
+        ));
+    }
+
+    #[tokio::test]
+    async fn resume_session() {
+        let node_identity1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let config1 =
+            NoiseConfig::new(node_identity1.clone()).with_session_ticket_lifetime(Some(Duration::from_secs(60)));
+        let node_identity2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let config2 =
+            NoiseConfig::new(node_identity2.clone()).with_session_ticket_lifetime(Some(Duration::from_secs(60)));
+        establish_session_tickets(&config2, &config1).await;
+
+        let (in_socket, out_socket) = MemorySocket::new_pair();
+        let (mut socket_in, mut socket_out) = future::join(
+            config1.upgrade_socket(in_socket, ConnectionDirection::Inbound),
+            config2.resume_or_upgrade_socket(out_socket, node_identity1.public_key()),
+        )
+        .map(|(s1, s2)| (s1.unwrap(), s2.unwrap()))
+        .await;
+
+        assert_eq!(&socket_in.get_remote_public_key().unwrap(), node_identity2.public_key());
+        assert_eq!(
+            &socket_out.get_remote_public_key().unwrap(),
+            node_identity1.public_key()
+        );
+        assert_eq!(
+            socket_in.session_ticket().unwrap().peer_identity().claim.features,
+            node_identity2.features()
+        );
+        assert!(socket_out.session_ticket().is_some());
+
+        let sample = b"Children of time";
+        socket_out.write_all(sample).await.unwrap();
+        socket_out.flush().await.unwrap();
+        socket_out.shutdown().await.unwrap();
+
+        let mut read_buf = Vec::with_capacity(16);
+        socket_in.read_to_end(&mut read_buf).await.unwrap();
+        assert_eq!(read_buf, sample);
+    }
+
+    #[tokio::test]
+    async fn resume_session_without_responder_ticket() {
+        let node_identity1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let config1 =
+            NoiseConfig::new(node_identity1.clone()).with_session_ticket_lifetime(Some(Duration::from_secs(60)));
+        let node_identity2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let config2 =
+            NoiseConfig::new(node_identity2.clone()).with_session_ticket_lifetime(Some(Duration::from_secs(60)));
+        establish_session_tickets(&config2, &config1).await;
+        // The responder has lost its ticket, e.g. because it restarted
+        config1.session_tickets().remove(node_identity2.public_key());
+
+        let (in_socket, out_socket) = MemorySocket::new_pair();
+        let (result_in, result_out) = future::join(
+            config1.upgrade_socket(in_socket, ConnectionDirection::Inbound),
+            config2.resume_or_upgrade_socket(out_socket, node_identity1.public_key()),
+        )
+        .await;
+        assert!(matches!(result_in.unwrap_err(), NoiseError::SessionTicketNotFound(_)));
+        result_out.unwrap_err();
+        // The initiator discards its ticket so that the next attempt performs a full handshake
+        assert!(config2.session_tickets().get(node_identity1.public_key()).is_none());
+    }
 }
@@ -24,10 +24,16 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::types::CommsPublicKey;
+
 #[derive(Debug, Error)]
 pub enum NoiseError {
     #[error("Snow Error: {0}")]
     SnowError(#[from] snow::Error),
     #[error("Handshake Failed: {0}")]
     HandshakeFailed(io::Error),
+    #[error("Remote static key is missing or invalid")]
+    InvalidRemoteStaticKey,
+    #[error("No session ticket for peer '{0}'")]
+    SessionTicketNotFound(CommsPublicKey),
 }
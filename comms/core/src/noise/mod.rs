@@ -34,6 +34,9 @@ pub use error::NoiseError;
 
 mod socket;
 pub use socket::NoiseSocket;
+
+mod session_ticket;
+pub(crate) use session_ticket::{SessionTicket, SessionTicketStore};
 use tari_utilities::{hidden_type, safe_array::SafeArray, Hidden};
 use zeroize::Zeroize;
 
@@ -0,0 +1,256 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Session tickets for noise session resumption.
+//!
+//! After a full XX handshake and identity exchange, each peer contributes a random seed in its identity message. Both
+//! peers derive the same ticket secret from the seeds and the handshake hash, and store it together with the
+//! validated peer identity. A subsequent connection to the same peer may then perform a 1-RTT `IKpsk2` handshake using
+//! the ticket secret as the pre-shared key and skip the identity exchange. Tickets are bound to the peer's public key
+//! and are only held in memory.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use blake2::Blake2b;
+use digest::{consts::U32, generic_array::GenericArray};
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use tari_crypto::hashing::DomainSeparatedHasher;
+use tari_utilities::safe_array::SafeArray;
+
+use super::CommsNoiseKey;
+use crate::{
+    connection_manager::ValidatedPeerIdentityExchange,
+    types::{CommsCoreHashDomain, CommsPublicKey},
+};
+
+const LOG_TARGET: &str = "comms::noise::session_ticket";
+
+/// The length of the random seed that each peer contributes to a session ticket
+const SESSION_TICKET_SEED_LEN: usize = 32;
+/// The maximum number of session tickets to hold. When full, the ticket closest to expiry is evicted.
+const MAX_SESSION_TICKETS: usize = 1000;
+
+type SessionTicketHasher = DomainSeparatedHasher<Blake2b<U32>, CommsCoreHashDomain>;
+
+/// A ticket that allows a connection to a peer to be resumed without a full handshake and identity exchange.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionTicket {
+    secret: CommsNoiseKey,
+    peer_identity: ValidatedPeerIdentityExchange,
+    expires_at: Instant,
+}
+
+impl SessionTicket {
+    /// The pre-shared key used in the resumption handshake
+    pub fn secret(&self) -> &[u8] {
+        &self.secret.reveal()[..]
+    }
+
+    /// The peer identity that was validated in the identity exchange when this ticket was issued
+    pub fn peer_identity(&self) -> &ValidatedPeerIdentityExchange {
+        &self.peer_identity
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at <= Instant::now()
+    }
+}
+
+/// In-memory store of session tickets, keyed by the peer's public key. Resumption is disabled if no ticket lifetime is
+/// set.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionTicketStore {
+    lifetime: Option<Duration>,
+    tickets: Arc<Mutex<HashMap<CommsPublicKey, SessionTicket>>>,
+}
+
+impl SessionTicketStore {
+    pub fn new(lifetime: Option<Duration>) -> Self {
+        Self {
+            lifetime,
+            tickets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lifetime.is_some()
+    }
+
+    /// Returns a random seed to send to the peer in the identity exchange, or an empty seed if resumption is disabled.
+    pub fn generate_seed(&self) -> Vec<u8> {
+        if !self.is_enabled() {
+            return Vec::new();
+        }
+        let mut seed = vec![0u8; SESSION_TICKET_SEED_LEN];
+        OsRng.fill_bytes(&mut seed);
+        seed
+    }
+
+    /// Returns the unexpired session ticket for the peer, if any
+    pub fn get(&self, public_key: &CommsPublicKey) -> Option<SessionTicket> {
+        let mut tickets = acquire_lock!(self.tickets);
+        match tickets.get(public_key) {
+            Some(ticket) if ticket.is_expired() => {
+                tickets.remove(public_key);
+                None
+            },
+            Some(ticket) => Some(ticket.clone()),
+            None => None,
+        }
+    }
+
+    /// Removes the session ticket for the peer, for example, after a failed resumption
+    pub fn remove(&self, public_key: &CommsPublicKey) {
+        if acquire_lock!(self.tickets).remove(public_key).is_some() {
+            debug!(target: LOG_TARGET, "Removed session ticket for peer '{}'", public_key);
+        }
+    }
+
+    /// Issues a session ticket for the peer after a full handshake and identity exchange. A ticket is only issued if
+    /// resumption is enabled and both peers provided a valid seed. Returns true if a ticket was issued.
+    pub fn issue(
+        &self,
+        public_key: CommsPublicKey,
+        handshake_hash: &[u8],
+        initiator_seed: &[u8],
+        responder_seed: &[u8],
+        peer_identity: ValidatedPeerIdentityExchange,
+    ) -> bool {
+        let lifetime = match self.lifetime {
+            Some(lifetime) => lifetime,
+            None => return false,
+        };
+        if handshake_hash.is_empty() ||
+            initiator_seed.len() != SESSION_TICKET_SEED_LEN ||
+            responder_seed.len() != SESSION_TICKET_SEED_LEN
+        {
+            return false;
+        }
+
+        let mut secret = CommsNoiseKey::from(SafeArray::default());
+        SessionTicketHasher::new_with_label("noise.session_ticket")
+            .chain(handshake_hash)
+            .chain(initiator_seed)
+            .chain(responder_seed)
+            .finalize_into(GenericArray::from_mut_slice(secret.reveal_mut()));
+
+        let ticket = SessionTicket {
+            secret,
+            peer_identity,
+            expires_at: Instant::now() + lifetime,
+        };
+
+        let mut tickets = acquire_lock!(self.tickets);
+        if tickets.len() >= MAX_SESSION_TICKETS && !tickets.contains_key(&public_key) {
+            tickets.retain(|_, t| !t.is_expired());
+            if tickets.len() >= MAX_SESSION_TICKETS {
+                let oldest = tickets
+                    .iter()
+                    .min_by_key(|(_, t)| t.expires_at)
+                    .map(|(pk, _)| pk.clone());
+                if let Some(pk) = oldest {
+                    tickets.remove(&pk);
+                }
+            }
+        }
+        debug!(target: LOG_TARGET, "Issued session ticket for peer '{}'", public_key);
+        tickets.insert(public_key, ticket);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        connection_manager::PeerIdentityMetadata,
+        peer_manager::{PeerFeatures, PeerIdentityClaim},
+        test_utils::node_identity::build_node_identity,
+    };
+
+    fn peer_identity() -> (CommsPublicKey, ValidatedPeerIdentityExchange) {
+        let node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+        let identity = ValidatedPeerIdentityExchange {
+            claim: PeerIdentityClaim::new(
+                node_identity.public_addresses(),
+                node_identity.features(),
+                node_identity.identity_signature_read().clone().unwrap(),
+            ),
+            metadata: PeerIdentityMetadata {
+                user_agent: "test".to_string(),
+                supported_protocols: vec![],
+            },
+        };
+        (node_identity.public_key().clone(), identity)
+    }
+
+    #[test]
+    fn it_issues_matching_tickets_to_both_peers() {
+        let initiator = SessionTicketStore::new(Some(Duration::from_secs(60)));
+        let responder = SessionTicketStore::new(Some(Duration::from_secs(60)));
+        let initiator_seed = initiator.generate_seed();
+        let responder_seed = responder.generate_seed();
+        let (pk1, identity1) = peer_identity();
+        let (pk2, identity2) = peer_identity();
+
+        assert!(initiator.issue(
+            pk2.clone(),
+            b"hash",
+            &initiator_seed,
+            &responder_seed,
+            identity2.clone()
+        ));
+        assert!(responder.issue(pk1.clone(), b"hash", &initiator_seed, &responder_seed, identity1));
+
+        let ticket1 = initiator.get(&pk2).unwrap();
+        let ticket2 = responder.get(&pk1).unwrap();
+        assert_eq!(ticket1.secret(), ticket2.secret());
+        assert_eq!(*ticket1.peer_identity(), identity2);
+
+        initiator.remove(&pk2);
+        assert!(initiator.get(&pk2).is_none());
+    }
+
+    #[test]
+    fn it_does_not_issue_tickets_when_disabled_or_unsupported() {
+        let disabled = SessionTicketStore::new(None);
+        assert!(disabled.generate_seed().is_empty());
+        let (pk, identity) = peer_identity();
+        let seed = [1u8; SESSION_TICKET_SEED_LEN];
+        assert!(!disabled.issue(pk.clone(), b"hash", &seed, &seed, identity.clone()));
+
+        let store = SessionTicketStore::new(Some(Duration::from_secs(60)));
+        // The peer did not provide a seed
+        assert!(!store.issue(pk.clone(), b"hash", &seed, &[], identity.clone()));
+        assert!(store.get(&pk).is_none());
+
+        let store = SessionTicketStore::new(Some(Duration::ZERO));
+        assert!(store.issue(pk.clone(), b"hash", &seed, &seed, identity));
+        // Expired
+        assert!(store.get(&pk).is_none());
+    }
+}
@@ -44,7 +44,7 @@ use tokio::{
     time,
 };
 
-use crate::types::CommsPublicKey;
+use crate::{noise::SessionTicket, types::CommsPublicKey};
 
 const LOG_TARGET: &str = "comms::noise::socket";
 
@@ -139,6 +139,8 @@ pub struct NoiseSocket<TSocket> {
     buffers: Box<NoiseBuffers>,
     read_state: ReadState,
     write_state: WriteState,
+    handshake_hash: Vec<u8>,
+    session_ticket: Option<SessionTicket>,
 }
 
 impl<TSocket> NoiseSocket<TSocket> {
@@ -149,6 +151,8 @@ impl<TSocket> NoiseSocket<TSocket> {
             buffers: Box::new(NoiseBuffers::new()),
             read_state: ReadState::Init,
             write_state: WriteState::Init,
+            handshake_hash: Vec::new(),
+            session_ticket: None,
         }
     }
 
@@ -162,6 +166,20 @@ impl<TSocket> NoiseSocket<TSocket> {
         self.get_remote_static()
             .and_then(|s| CommsPublicKey::from_canonical_bytes(s).ok())
     }
+
+    /// The hash of the completed handshake, or empty if the handshake has not completed
+    pub(crate) fn handshake_hash(&self) -> &[u8] {
+        &self.handshake_hash
+    }
+
+    /// The session ticket used to resume this session, or None if a full handshake was performed
+    pub(crate) fn session_ticket(&self) -> Option<&SessionTicket> {
+        self.session_ticket.as_ref()
+    }
+
+    pub(super) fn set_session_ticket(&mut self, ticket: SessionTicket) {
+        self.session_ticket = Some(ticket);
+    }
 }
 
 fn poll_write_all<TSocket>(
@@ -534,10 +552,9 @@ impl<TSocket> Handshake<TSocket> {
 impl<TSocket> Handshake<TSocket>
 where TSocket: AsyncRead + AsyncWrite + Unpin
 {
-    /// Perform a Single Round-Trip noise IX handshake returning the underlying [NoiseSocket]
-    /// (switched to transport mode) upon success.
+    /// Perform the noise handshake returning the underlying [NoiseSocket] (switched to transport mode) upon success.
     pub async fn perform_handshake(mut self) -> io::Result<NoiseSocket<TSocket>> {
-        match self.handshake_1_5rtt().await {
+        match self.run_handshake().await {
             Ok(_) => self.build(),
             Err(err) => {
                 warn!(
@@ -550,29 +567,25 @@ where TSocket: AsyncRead + AsyncWrite + Unpin
         }
     }
 
-    /// Performs a 1.5 RTT handshake. For example, the noise XX handshake.
-    async fn handshake_1_5rtt(&mut self) -> io::Result<()> {
-        if self.socket.state.is_initiator() {
-            //   -> e
-            self.send().await?;
-            self.flush().await?;
-
-            // <- e, ee, s, es
-            self.receive().await?;
-
-            //   -> s, se
-            self.send().await?;
-            self.flush().await?;
-        } else {
-            //   -> e
-            self.receive().await?;
-
-            // <- e, ee, s, es
-            self.send().await?;
-            self.flush().await?;
-
-            //   -> s, se
-            self.receive().await?;
+    /// Sends and receives handshake messages until the handshake is complete. The order of messages is determined by
+    /// the handshake pattern, for example, 1.5 RTT for XX and 1 RTT for IK. A message that was read before the
+    /// handshake state was handed over is accounted for.
+    async fn run_handshake(&mut self) -> io::Result<()> {
+        loop {
+            let is_my_turn = match &self.socket.state {
+                NoiseState::HandshakeState(state) if !state.is_handshake_finished() => state.is_my_turn(),
+                _ => break,
+            };
+
+            if is_my_turn {
+                self.send().await?;
+                self.flush().await?;
+            } else {
+                self.receive().await?;
+                if let ReadState::Eof(_) = self.socket.read_state {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+            }
         }
 
         Ok(())
@@ -593,6 +606,10 @@ where TSocket: AsyncRead + AsyncWrite + Unpin
     }
 
     fn build(self) -> io::Result<NoiseSocket<TSocket>> {
+        let handshake_hash = match &self.socket.state {
+            NoiseState::HandshakeState(state) => state.get_handshake_hash().to_vec(),
+            NoiseState::TransportState(_) => Vec::new(),
+        };
         let transport_state = self
             .socket
             .state
@@ -601,11 +618,35 @@ where TSocket: AsyncRead + AsyncWrite + Unpin
 
         Ok(NoiseSocket {
             state: transport_state,
+            handshake_hash,
             ..self.socket
         })
     }
 }
 
+/// Reads a single length-prefixed handshake message from the socket. This is used by the responder to inspect the
+/// first handshake message before deciding which handshake pattern to use.
+pub(super) async fn read_handshake_message<TSocket>(
+    socket: &mut TSocket,
+    recv_timeout: Duration,
+) -> io::Result<Vec<u8>>
+where
+    TSocket: AsyncRead + Unpin,
+{
+    let read = async {
+        let len = socket.read_u16().await?;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Empty handshake message"));
+        }
+        let mut msg = vec![0u8; usize::from(len)];
+        socket.read_exact(&mut msg).await?;
+        Ok(msg)
+    };
+    time::timeout(recv_timeout, read)
+        .await
+        .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?
+}
+
 #[derive(Debug)]
 enum NoiseState {
     HandshakeState(Box<HandshakeState>),
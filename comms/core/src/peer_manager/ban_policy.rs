@@ -0,0 +1,361 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    fmt,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::peer_manager::NodeId;
+
+/// Categories of peer misbehaviour that are scored by the [BanPolicy].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerOffense {
+    /// The peer sent a block that failed validation
+    InvalidBlock,
+    /// The peer sent invalid or inconsistent data during horizon sync
+    BadHorizonData,
+    /// The peer sent an invalid request or response
+    InvalidMessage,
+    /// The peer violated a wire protocol
+    ProtocolViolation,
+    /// The peer sent excessive or unsolicited messages
+    Spam,
+    /// The peer did not respond, or responded too slowly
+    Unresponsive,
+}
+
+impl PeerOffense {
+    pub fn as_str(&self) -> &'static str {
+        use PeerOffense::{BadHorizonData, InvalidBlock, InvalidMessage, ProtocolViolation, Spam, Unresponsive};
+        match self {
+            InvalidBlock => "invalid_block",
+            BadHorizonData => "bad_horizon_data",
+            InvalidMessage => "invalid_message",
+            ProtocolViolation => "protocol_violation",
+            Spam => "spam",
+            Unresponsive => "unresponsive",
+        }
+    }
+}
+
+impl fmt::Display for PeerOffense {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The score an offense adds to a peer's offense score and the duration of the ban if the offense causes the score to
+/// reach the ban threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffensePolicy {
+    pub score: u32,
+    pub ban_duration: Duration,
+}
+
+impl OffensePolicy {
+    pub const fn new(score: u32, ban_duration: Duration) -> Self {
+        Self { score, ban_duration }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanPolicyConfig {
+    /// A peer is banned once its offense score reaches this threshold
+    pub ban_threshold: u32,
+    /// The amount by which a peer's offense score decays every hour
+    pub score_decay_per_hour: u32,
+    /// The ban duration of an offense is doubled for each previous ban of the peer, up to this maximum
+    pub max_ban_duration: Duration,
+    /// The maximum number of offenses kept in the ledger for each peer
+    pub max_offenses_per_peer: usize,
+    pub invalid_block: OffensePolicy,
+    pub bad_horizon_data: OffensePolicy,
+    pub invalid_message: OffensePolicy,
+    pub protocol_violation: OffensePolicy,
+    pub spam: OffensePolicy,
+    pub unresponsive: OffensePolicy,
+}
+
+impl BanPolicyConfig {
+    pub fn policy_for(&self, offense: PeerOffense) -> &OffensePolicy {
+        use PeerOffense::{BadHorizonData, InvalidBlock, InvalidMessage, ProtocolViolation, Spam, Unresponsive};
+        match offense {
+            InvalidBlock => &self.invalid_block,
+            BadHorizonData => &self.bad_horizon_data,
+            InvalidMessage => &self.invalid_message,
+            ProtocolViolation => &self.protocol_violation,
+            Spam => &self.spam,
+            Unresponsive => &self.unresponsive,
+        }
+    }
+}
+
+impl Default for BanPolicyConfig {
+    fn default() -> Self {
+        const LONG_BAN: Duration = Duration::from_secs(2 * 60 * 60);
+        const SHORT_BAN: Duration = Duration::from_secs(4 * 60);
+        Self {
+            ban_threshold: 100,
+            score_decay_per_hour: 50,
+            max_ban_duration: Duration::from_secs(7 * 24 * 60 * 60),
+            max_offenses_per_peer: 32,
+            invalid_block: OffensePolicy::new(100, LONG_BAN),
+            bad_horizon_data: OffensePolicy::new(100, LONG_BAN),
+            invalid_message: OffensePolicy::new(100, LONG_BAN),
+            protocol_violation: OffensePolicy::new(100, LONG_BAN),
+            spam: OffensePolicy::new(25, LONG_BAN),
+            unresponsive: OffensePolicy::new(50, SHORT_BAN),
+        }
+    }
+}
+
+/// A single offense committed by a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffenseEntry {
+    pub offense: PeerOffense,
+    pub reason: String,
+    pub score: u32,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// The offense history and current (decayed) offense score of a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerOffenseRecord {
+    pub node_id: NodeId,
+    pub score: u32,
+    pub num_bans: u32,
+    pub last_banned_at: Option<DateTime<Utc>>,
+    pub offenses: Vec<OffenseEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PeerLedger {
+    score: u32,
+    score_updated_at: Option<DateTime<Utc>>,
+    num_bans: u32,
+    last_banned_at: Option<DateTime<Utc>>,
+    offenses: VecDeque<OffenseEntry>,
+}
+
+impl PeerLedger {
+    fn decayed_score(&self, decay_per_hour: u32, now: DateTime<Utc>) -> u32 {
+        let elapsed_secs = self
+            .score_updated_at
+            .map(|t| now.signed_duration_since(t).num_seconds().max(0).unsigned_abs())
+            .unwrap_or_default();
+        let decay = elapsed_secs.saturating_mul(u64::from(decay_per_hour)) / 3600;
+        u32::try_from(u64::from(self.score).saturating_sub(decay)).unwrap_or(u32::MAX)
+    }
+
+    fn to_record(&self, node_id: &NodeId, decay_per_hour: u32, now: DateTime<Utc>) -> PeerOffenseRecord {
+        PeerOffenseRecord {
+            node_id: node_id.clone(),
+            score: self.decayed_score(decay_per_hour, now),
+            num_bans: self.num_bans,
+            last_banned_at: self.last_banned_at,
+            offenses: self.offenses.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Scores peer offenses and decides when, and for how long, a peer should be banned. Offense scores decay over time so
+/// that occasional minor offenses do not lead to a ban, and repeat offenders are banned for progressively longer.
+#[derive(Debug, Clone, Default)]
+pub struct BanPolicy {
+    config: BanPolicyConfig,
+    ledger: HashMap<NodeId, PeerLedger>,
+}
+
+impl BanPolicy {
+    pub fn new(config: BanPolicyConfig) -> Self {
+        Self {
+            config,
+            ledger: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &BanPolicyConfig {
+        &self.config
+    }
+
+    /// Records an offense committed by a peer. Returns the duration the peer should be banned for if its offense score
+    /// reached the ban threshold, otherwise None.
+    pub fn record_offense(&mut self, node_id: &NodeId, offense: PeerOffense, reason: String) -> Option<Duration> {
+        self.record_offense_at(node_id, offense, reason, Utc::now())
+    }
+
+    fn record_offense_at(
+        &mut self,
+        node_id: &NodeId,
+        offense: PeerOffense,
+        reason: String,
+        now: DateTime<Utc>,
+    ) -> Option<Duration> {
+        self.prune(now);
+        let policy = *self.config.policy_for(offense);
+        let decay_per_hour = self.config.score_decay_per_hour;
+        let entry = self.ledger.entry(node_id.clone()).or_default();
+
+        entry.score = entry.decayed_score(decay_per_hour, now).saturating_add(policy.score);
+        entry.score_updated_at = Some(now);
+        entry.offenses.push_back(OffenseEntry {
+            offense,
+            reason,
+            score: policy.score,
+            committed_at: now,
+        });
+        while entry.offenses.len() > self.config.max_offenses_per_peer {
+            entry.offenses.pop_front();
+        }
+
+        if entry.score < self.config.ban_threshold {
+            return None;
+        }
+
+        let multiplier = 1u32.checked_shl(entry.num_bans).unwrap_or(u32::MAX);
+        let duration = policy
+            .ban_duration
+            .checked_mul(multiplier)
+            .map_or(self.config.max_ban_duration, |d| d.min(self.config.max_ban_duration));
+        entry.score = 0;
+        entry.num_bans = entry.num_bans.saturating_add(1);
+        entry.last_banned_at = Some(now);
+        Some(duration)
+    }
+
+    /// Returns the offense record of a peer, or None if the peer has not committed any offenses
+    pub fn get_record(&self, node_id: &NodeId) -> Option<PeerOffenseRecord> {
+        let now = Utc::now();
+        self.ledger
+            .get(node_id)
+            .map(|entry| entry.to_record(node_id, self.config.score_decay_per_hour, now))
+    }
+
+    /// Returns the offense records of all peers, ordered by current offense score, highest first
+    pub fn records(&self) -> Vec<PeerOffenseRecord> {
+        let now = Utc::now();
+        let mut records = self
+            .ledger
+            .iter()
+            .map(|(node_id, entry)| entry.to_record(node_id, self.config.score_decay_per_hour, now))
+            .collect::<Vec<_>>();
+        records.sort_by(|a, b| b.score.cmp(&a.score));
+        records
+    }
+
+    /// Forgets peers whose offense score has fully decayed and that have not been banned within the maximum ban
+    /// duration
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let decay_per_hour = self.config.score_decay_per_hour;
+        let max_ban_duration =
+            chrono::Duration::from_std(self.config.max_ban_duration).unwrap_or_else(|_| chrono::Duration::max_value());
+        self.ledger.retain(|_, entry| {
+            let recently_banned = entry
+                .last_banned_at
+                .map_or(false, |t| now.signed_duration_since(t) < max_ban_duration);
+            recently_banned || entry.decayed_score(decay_per_hour, now) > 0
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::node_id;
+
+    #[test]
+    fn it_bans_when_the_threshold_is_reached() {
+        let mut policy = BanPolicy::new(Default::default());
+        let peer = node_id::random();
+        let now = Utc::now();
+
+        assert!(policy
+            .record_offense_at(&peer, PeerOffense::Spam, "spam".to_string(), now)
+            .is_none());
+        assert!(policy
+            .record_offense_at(&peer, PeerOffense::Unresponsive, "timeout".to_string(), now)
+            .is_none());
+        let duration = policy
+            .record_offense_at(&peer, PeerOffense::Unresponsive, "timeout".to_string(), now)
+            .unwrap();
+        assert_eq!(duration, Duration::from_secs(4 * 60));
+
+        let record = policy.get_record(&peer).unwrap();
+        assert_eq!(record.score, 0);
+        assert_eq!(record.num_bans, 1);
+        assert_eq!(record.offenses.len(), 3);
+
+        // Repeat bans are longer
+        let duration = policy
+            .record_offense_at(&peer, PeerOffense::InvalidBlock, "bad block".to_string(), now)
+            .unwrap();
+        assert_eq!(duration, Duration::from_secs(4 * 60 * 60));
+    }
+
+    #[test]
+    fn it_decays_scores_over_time() {
+        let mut policy = BanPolicy::new(Default::default());
+        let peer = node_id::random();
+        let now = Utc::now();
+
+        assert!(policy
+            .record_offense_at(&peer, PeerOffense::Unresponsive, "timeout".to_string(), now)
+            .is_none());
+        // One hour later the score of the first offense has fully decayed
+        let later = now + chrono::Duration::hours(1);
+        assert!(policy
+            .record_offense_at(&peer, PeerOffense::Unresponsive, "timeout".to_string(), later)
+            .is_none());
+        assert_eq!(policy.ledger.get(&peer).unwrap().decayed_score(50, later), 50);
+
+        // Fully decayed peers that were never banned are pruned
+        policy.prune(later + chrono::Duration::hours(1));
+        assert!(policy.get_record(&peer).is_none());
+    }
+
+    #[test]
+    fn it_caps_the_ban_duration() {
+        let mut policy = BanPolicy::new(BanPolicyConfig {
+            max_ban_duration: Duration::from_secs(3 * 60 * 60),
+            ..Default::default()
+        });
+        let peer = node_id::random();
+        let now = Utc::now();
+        let durations = (0..3)
+            .map(|_| {
+                policy
+                    .record_offense_at(&peer, PeerOffense::InvalidBlock, "bad block".to_string(), now)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(durations, vec![
+            Duration::from_secs(2 * 60 * 60),
+            Duration::from_secs(3 * 60 * 60),
+            Duration::from_secs(3 * 60 * 60)
+        ]);
+    }
+}
@@ -31,6 +31,7 @@ use crate::peer_manager::metrics;
 use crate::{
     net_address::{MultiaddressesWithStats, PeerAddressSource},
     peer_manager::{
+        ban_policy::{BanPolicy, BanPolicyConfig, PeerOffense, PeerOffenseRecord},
         migrations,
         peer::{Peer, PeerFlags},
         peer_id::PeerId,
@@ -50,6 +51,7 @@ use crate::{
 pub struct PeerManager {
     // yo dawg, I heard you like wrappers, so I wrapped your wrapper in a wrapper so you can wrap while you wrap
     peer_storage: RwLock<PeerStorage<CachedStore<PeerId, Peer, KeyValueWrapper<CommsDatabase>>>>,
+    ban_policy: RwLock<BanPolicy>,
     _file_lock: Option<File>,
 }
 
@@ -59,10 +61,17 @@ impl PeerManager {
         let storage = PeerStorage::new_indexed(CachedStore::new(KeyValueWrapper::new(database)))?;
         Ok(Self {
             peer_storage: RwLock::new(storage),
+            ban_policy: RwLock::new(BanPolicy::default()),
             _file_lock: file_lock,
         })
     }
 
+    /// Sets the [BanPolicyConfig] used to score peer offenses
+    pub fn with_ban_policy_config(mut self, config: BanPolicyConfig) -> Self {
+        self.ban_policy = RwLock::new(BanPolicy::new(config));
+        self
+    }
+
     /// Migrate the peer database, this only applies to the LMDB database
     pub fn migrate_lmdb(database: &LMDBDatabase) -> Result<(), PeerManagerError> {
         migrations::migrate(database).map_err(|err| PeerManagerError::MigrationError(err.to_string()))
@@ -317,6 +326,23 @@ impl PeerManager {
             .ban_peer_by_node_id(node_id, duration, reason)
     }
 
+    /// Records an offense committed by a peer in the offense ledger. Returns the duration the peer should be banned for
+    /// if the offense caused the peer's offense score to reach the ban threshold. This does not ban the peer, use the
+    /// connectivity manager to report offenses.
+    pub async fn record_offense(&self, node_id: &NodeId, offense: PeerOffense, reason: String) -> Option<Duration> {
+        self.ban_policy.write().await.record_offense(node_id, offense, reason)
+    }
+
+    /// Returns the offense records of all peers in the offense ledger, ordered by offense score
+    pub async fn offense_records(&self) -> Vec<PeerOffenseRecord> {
+        self.ban_policy.read().await.records()
+    }
+
+    /// Returns the offense record of a peer, or None if the peer is not in the offense ledger
+    pub async fn get_offense_record(&self, node_id: &NodeId) -> Option<PeerOffenseRecord> {
+        self.ban_policy.read().await.get_record(node_id)
+    }
+
     pub async fn is_peer_banned(&self, node_id: &NodeId) -> Result<bool, PeerManagerError> {
         self.peer_storage.read().await.is_peer_banned(node_id)
     }
@@ -70,6 +70,9 @@
 //! let returned_peer = peer_manager.find_by_node_id(&node_id).unwrap();
 //! ```
 
+mod ban_policy;
+pub use ban_policy::{BanPolicy, BanPolicyConfig, OffenseEntry, OffensePolicy, PeerOffense, PeerOffenseRecord};
+
 mod error;
 pub use error::PeerManagerError;
 
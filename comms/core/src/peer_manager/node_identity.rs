@@ -26,7 +26,7 @@ use std::{
 };
 
 use chrono::Utc;
-use multiaddr::Multiaddr;
+use multiaddr::{Multiaddr, Protocol};
 use rand::{CryptoRng, Rng};
 use serde::{Deserialize, Serialize};
 use tari_crypto::{
@@ -145,6 +145,23 @@ impl NodeIdentity {
         }
     }
 
+    /// Replace any onion addresses with the given onion address, e.g. after the onion address has been rotated. Other
+    /// public addresses are left unchanged.
+    pub fn replace_onion_address(&self, address: Multiaddr) {
+        let mut addresses = self
+            .public_addresses()
+            .into_iter()
+            .filter(|addr| {
+                !matches!(
+                    addr.iter().next(),
+                    Some(Protocol::Onion(..)) | Some(Protocol::Onion3(_))
+                )
+            })
+            .collect::<Vec<_>>();
+        addresses.push(address);
+        self.set_public_addresses(addresses);
+    }
+
     /// Set the available addresses. If none of the addresses have changed, the identity signature remains unchanged.
     pub fn set_public_addresses(&self, addresses: Vec<Multiaddr>) {
         let mut must_sign = false;
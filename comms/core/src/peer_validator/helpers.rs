@@ -29,6 +29,7 @@ use crate::{
     peer_manager::{NodeId, PeerIdentityClaim},
     peer_validator::{error::PeerValidatorError, PeerValidatorConfig},
     types::CommsPublicKey,
+    utils::multiaddr::is_i2p_b32_host,
 };
 
 /// Checks that the given peer addresses are well-formed and valid. If allow_test_addrs is false, all localhost and
@@ -86,6 +87,15 @@ fn validate_address(addr: &Multiaddr, allow_test_addrs: bool) -> Result<(), Peer
     }
 
     match proto {
+        // I2P addresses are encoded as /dns/<base32>.b32.i2p/tcp/<port>
+        Protocol::Dns(host) if is_i2p_b32_host(&host) => {
+            let tcp = addr_iter.next().ok_or_else(|| {
+                PeerValidatorError::InvalidMultiaddr("I2P address does not include a port".to_string())
+            })?;
+
+            validate_tcp_port(tcp)?;
+            expect_end_of_address(addr_iter)
+        },
         Protocol::Dns4(_) | Protocol::Dns6(_) | Protocol::Dnsaddr(_) => {
             let tcp = addr_iter.next().ok_or_else(|| {
                 PeerValidatorError::InvalidMultiaddr("Address does not include a TCP port".to_string())
@@ -211,6 +221,9 @@ mod test {
                 .parse()
                 .unwrap(),
             multiaddr!(Dnsaddr("mike-magic-nodes.com"), Tcp(1u16)),
+            "/dns/ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p/tcp/18141"
+                .parse()
+                .unwrap(),
         ];
 
         let invalid = &[
@@ -222,6 +235,10 @@ mod test {
             multiaddr!(Dnsaddr("mike-magic-nodes.com")),
             multiaddr!(Memory(1234u64)),
             multiaddr!(Memory(0u64)),
+            "/dns/ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p"
+                .parse()
+                .unwrap(),
+            "/dns/example.i2p/tcp/18141".parse().unwrap(),
         ];
 
         for addr in valid {
@@ -52,10 +52,13 @@ const MAX_IDENTITY_PROTOCOL_MSG_SIZE: u16 = 1024;
 ///   |  ---------[identity]--------> |
 ///   |  <---------[identity]-------- |
 /// ```
+///
+/// A non-empty `session_ticket_seed` indicates that this node supports noise session resumption.
 pub async fn identity_exchange<'p, TSocket, P>(
     node_identity: &NodeIdentity,
     our_supported_protocols: P,
     network_info: NodeNetworkInfo,
+    session_ticket_seed: Vec<u8>,
     socket: &mut TSocket,
 ) -> Result<PeerIdentityMsg, IdentityProtocolError>
 where
@@ -71,6 +74,7 @@ where
         supported_protocols,
         user_agent: network_info.user_agent,
         identity_signature: node_identity.identity_signature_read().as_ref().map(Into::into),
+        session_ticket_seed,
     }
     .to_encoded_bytes();
 
@@ -247,6 +251,7 @@ mod test {
                     minor_version: 1,
                     ..Default::default()
                 },
+                vec![1u8; 32],
                 &mut in_sock,
             ),
             super::identity_exchange(
@@ -256,6 +261,7 @@ mod test {
                     minor_version: 2,
                     ..Default::default()
                 },
+                Vec::new(),
                 &mut out_sock,
             ),
         )
@@ -266,6 +272,8 @@ mod test {
         let identity1 = result2.unwrap();
 
         assert_eq!(identity1.features, node_identity1.features().bits());
+        assert_eq!(identity1.session_ticket_seed, vec![1u8; 32]);
+        assert!(identity2.session_ticket_seed.is_empty());
         assert_eq!(
             identity1.addresses,
             node_identity1
@@ -308,6 +316,7 @@ mod test {
                     major_version: 0,
                     ..Default::default()
                 },
+                Vec::new(),
                 &mut in_sock,
             ),
             super::identity_exchange(
@@ -317,6 +326,7 @@ mod test {
                     major_version: 1,
                     ..Default::default()
                 },
+                Vec::new(),
                 &mut out_sock,
             ),
         )
@@ -31,8 +31,12 @@ use tokio::{
 
 #[cfg(feature = "metrics")]
 use super::metrics;
-use super::{MessagingEvent, MessagingProtocol};
-use crate::{message::InboundMessage, peer_manager::NodeId};
+use super::{protocol::QUALITY_REPORT_INTERVAL, MessagingEvent, MessagingProtocol};
+use crate::{
+    connectivity::{ConnectivityRequester, PeerQualitySample},
+    message::InboundMessage,
+    peer_manager::NodeId,
+};
 
 const LOG_TARGET: &str = "comms::protocol::messaging::inbound";
 
@@ -41,6 +45,7 @@ pub struct InboundMessaging {
     peer: NodeId,
     inbound_message_tx: mpsc::Sender<InboundMessage>,
     messaging_events_tx: broadcast::Sender<MessagingEvent>,
+    connectivity: ConnectivityRequester,
     enable_message_received_event: bool,
 }
 
@@ -49,12 +54,14 @@ impl InboundMessaging {
         peer: NodeId,
         inbound_message_tx: mpsc::Sender<InboundMessage>,
         messaging_events_tx: broadcast::Sender<MessagingEvent>,
+        connectivity: ConnectivityRequester,
         enable_message_received_event: bool,
     ) -> Self {
         Self {
             peer,
             inbound_message_tx,
             messaging_events_tx,
+            connectivity,
             enable_message_received_event,
         }
     }
@@ -74,12 +81,23 @@ impl InboundMessaging {
 
         tokio::pin!(stream);
 
+        let mut unreported_count = 0u64;
+        let mut unreported_bytes = 0u64;
         while let Some(result) = stream.next().await {
             match result {
                 Ok(raw_msg) => {
                     #[cfg(feature = "metrics")]
                     metrics::inbound_message_count(&self.peer).inc();
                     let msg_len = raw_msg.len();
+                    unreported_count += 1;
+                    unreported_bytes += msg_len as u64;
+                    if unreported_count >= QUALITY_REPORT_INTERVAL {
+                        let _result = self
+                            .connectivity
+                            .record_peer_quality(peer.clone(), PeerQualitySample::BytesReceived(unreported_bytes));
+                        unreported_count = 0;
+                        unreported_bytes = 0;
+                    }
                     let inbound_msg = InboundMessage::new(peer.clone(), raw_msg.freeze());
                     debug!(
                         target: LOG_TARGET,
@@ -138,6 +156,12 @@ impl InboundMessaging {
             }
         }
 
+        if unreported_bytes > 0 {
+            let _result = self
+                .connectivity
+                .record_peer_quality(peer.clone(), PeerQualitySample::BytesReceived(unreported_bytes));
+        }
+
         let _ignore = self
             .messaging_events_tx
             .send(MessagingEvent::InboundProtocolExited(peer.clone()));
@@ -28,10 +28,16 @@ use tracing::{debug, error, span, Instrument, Level};
 
 #[cfg(feature = "metrics")]
 use super::metrics;
-use super::{error::MessagingProtocolError, MessagingEvent, MessagingProtocol, SendFailReason};
+use super::{
+    error::MessagingProtocolError,
+    protocol::QUALITY_REPORT_INTERVAL,
+    MessagingEvent,
+    MessagingProtocol,
+    SendFailReason,
+};
 use crate::{
     connection_manager::{NegotiatedSubstream, PeerConnection},
-    connectivity::{ConnectivityError, ConnectivityRequester},
+    connectivity::{ConnectivityError, ConnectivityRequester, PeerQualitySample},
     message::OutboundMessage,
     multiplexing::Substream,
     peer_manager::NodeId,
@@ -245,6 +251,7 @@ impl OutboundMessaging {
         let Self {
             mut messages_rx,
             peer_node_id,
+            connectivity,
             ..
         } = self;
         let span = span!(
@@ -269,6 +276,8 @@ impl OutboundMessaging {
 
         #[cfg(feature = "metrics")]
         let outbound_count = metrics::outbound_message_count(&peer_node_id);
+        let mut unreported_count = 0u64;
+        let mut unreported_bytes = 0u64;
         let stream = outbound_stream.map(|mut out_msg| {
             #[cfg(feature = "metrics")]
             outbound_count.inc();
@@ -277,6 +286,17 @@ impl OutboundMessaging {
                 "Message for peer '{}' sending {} on stream {}", peer_node_id, out_msg, stream_id
             );
 
+            unreported_count += 1;
+            unreported_bytes += out_msg.body.len() as u64;
+            if unreported_count >= QUALITY_REPORT_INTERVAL {
+                let _result = connectivity.record_peer_quality(peer_node_id.clone(), PeerQualitySample::MessagesSent {
+                    count: unreported_count,
+                    bytes: unreported_bytes,
+                });
+                unreported_count = 0;
+                unreported_bytes = 0;
+            }
+
             out_msg.reply_success();
             Result::<_, MessagingProtocolError>::Ok(out_msg.body)
         });
@@ -300,7 +320,14 @@ impl OutboundMessaging {
             )
         });
 
-        super::forward::Forward::new(stream, sink.sink_map_err(Into::into)).await?;
+        let forward_result = super::forward::Forward::new(stream, sink.sink_map_err(Into::into)).await;
+        if unreported_count > 0 {
+            let _result = connectivity.record_peer_quality(peer_node_id.clone(), PeerQualitySample::MessagesSent {
+                count: unreported_count,
+                bytes: unreported_bytes,
+            });
+        }
+        forward_result?;
 
         // Close so that the protocol handler does not resend to this session
         messages_rx.close();
@@ -334,8 +361,15 @@ impl OutboundMessaging {
         // Close the request channel so that we can read all the remaining messages and flush them
         // to a failed event
         self.messages_rx.close();
+        let mut num_failed = 0u64;
         while let Some(mut out_msg) = self.messages_rx.recv().await {
             out_msg.reply_fail(reason);
+            num_failed += 1;
+        }
+        if num_failed > 0 {
+            let _result = self
+                .connectivity
+                .record_peer_quality(self.peer_node_id.clone(), PeerQualitySample::MessagesFailed(num_failed));
         }
     }
 }
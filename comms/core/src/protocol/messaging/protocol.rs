@@ -57,6 +57,10 @@ const INTERNAL_MESSAGING_EVENT_CHANNEL_SIZE: usize = 10;
 
 const MAX_FRAME_LENGTH: usize = 8 * 1_024 * 1_024;
 
+/// The number of messages sent to or received from a peer after which quality metrics are reported to the
+/// connectivity manager.
+pub(super) const QUALITY_REPORT_INTERVAL: u64 = 10;
+
 pub type MessagingEventSender = broadcast::Sender<MessagingEvent>;
 pub type MessagingEventReceiver = broadcast::Receiver<MessagingEvent>;
 
@@ -350,6 +354,7 @@ impl MessagingProtocol {
             peer.clone(),
             inbound_message_tx,
             messaging_events_tx,
+            self.connectivity.clone(),
             self.enable_message_received_event,
         );
         let handle = tokio::spawn(inbound_messaging.run(substream));
@@ -37,7 +37,7 @@ use crate::{
         ConnectivityRequester,
         ConnectivityStatus,
     },
-    peer_manager::NodeId,
+    peer_manager::{NodeId, PeerOffense},
 };
 
 pub fn create_connectivity_mock() -> (ConnectivityRequester, ConnectivityManagerMock) {
@@ -63,6 +63,7 @@ struct State {
     pending_conns: HashMap<NodeId, Vec<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>>,
     selected_connections: Vec<PeerConnection>,
     banned_peers: Vec<(NodeId, Duration, String)>,
+    reported_offenses: Vec<(NodeId, PeerOffense, String)>,
     connectivity_status: ConnectivityStatus,
 }
 
@@ -190,6 +191,11 @@ impl ConnectivityManagerMockState {
         self.with_state(|state| state.banned_peers.drain(..).collect()).await
     }
 
+    pub async fn take_reported_offenses(&self) -> Vec<(NodeId, PeerOffense, String)> {
+        self.with_state(|state| state.reported_offenses.drain(..).collect())
+            .await
+    }
+
     pub(self) async fn with_state<F, R>(&self, f: F) -> R
     where F: FnOnce(&mut State) -> R {
         let mut lock = self.inner.lock().await;
@@ -229,6 +235,10 @@ impl ConnectivityManagerMock {
     async fn handle_request(&self, req: ConnectivityRequest) {
         #[allow(clippy::enum_glob_use)]
         use ConnectivityRequest::*;
+        // Quality samples are reported as a side effect of messaging and are not counted as calls
+        if matches!(req, RecordPeerQuality(_, _)) {
+            return;
+        }
         self.state.add_call(format!("{:?}", req)).await;
         match req {
             DialPeer { node_id, reply_tx } => {
@@ -285,8 +295,17 @@ impl ConnectivityManagerMock {
                     })
                     .await
             },
+            ReportOffense(node_id, offense, reason) => {
+                self.state
+                    .with_state(|state| {
+                        state.reported_offenses.push((node_id, offense, reason));
+                    })
+                    .await
+            },
             AddPeerToAllowList(_) => {},
             RemovePeerFromAllowList(_) => {},
+            RecordPeerQuality(_, _) => unreachable!(),
+            GetPeerQualityMetrics(reply) => reply.send(Default::default()).unwrap(),
             GetActiveConnections(reply) => {
                 self.state
                     .with_state(|state| reply.send(state.active_conns.values().cloned().collect()).unwrap())
@@ -31,6 +31,7 @@ use tokio::{
 
 use crate::{
     backoff::ConstantBackoff,
+    bandwidth::BandwidthMeter,
     connection_manager::{ConnectionManager, ConnectionManagerConfig, ConnectionManagerRequester},
     multiplexing::Substream,
     peer_manager::{NodeIdentity, PeerFeatures, PeerManager},
@@ -93,6 +94,7 @@ where
         config.node_identity,
         peer_manager,
         event_tx,
+        BandwidthMeter::default(),
         shutdown,
     );
     connection_manager.add_protocols(protocols);
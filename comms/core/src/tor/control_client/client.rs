@@ -135,6 +135,13 @@ impl TorControlPortClient {
         Ok(())
     }
 
+    /// The SETCONF command. Each setting is a `Key=Value` pair, values containing spaces must be quoted.
+    pub async fn set_conf(&mut self, settings: &[&str]) -> Result<(), TorClientError> {
+        let command = commands::set_conf(settings);
+        let _result = self.request_response(command).await?;
+        Ok(())
+    }
+
     /// The ADD_ONION command, used to create onion hidden services.
     pub async fn add_onion_custom<P: Into<PortMapping>>(
         &mut self,
@@ -45,6 +45,14 @@ pub fn set_events<'a>(event_types: &[&'a str]) -> KeyValueCommand<'a> {
     KeyValueCommand::new("SETEVENTS", event_types)
 }
 
+/// The SETCONF command.
+///
+/// This command is used to change the Tor proxy configuration. Each setting is a `Key=Value` pair, values containing
+/// spaces must be quoted.
+pub fn set_conf<'a>(settings: &[&'a str]) -> KeyValueCommand<'a> {
+    KeyValueCommand::new("SETCONF", settings)
+}
+
 pub struct KeyValueCommand<'a> {
     command: &'static str,
     args: Vec<&'a str>,
@@ -119,5 +127,11 @@ mod test {
 
         let command = KeyValueCommand::new("GETINFO", &["net/listeners/socks"]);
         assert_eq!(command.to_command_string().unwrap(), "GETINFO net/listeners/socks");
+
+        let command = set_conf(&["UseBridges=1", r#"Bridge="obfs4 192.0.2.1:443""#]);
+        assert_eq!(
+            command.to_command_string().unwrap(),
+            r#"SETCONF UseBridges=1 Bridge="obfs4 192.0.2.1:443""#
+        );
     }
 }
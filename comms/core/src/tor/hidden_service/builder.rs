@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use bitflags::bitflags;
 use log::*;
@@ -72,6 +72,9 @@ pub struct HiddenServiceBuilder {
     control_server_auth: Authentication,
     socks_auth: socks::Authentication,
     hs_flags: HsFlags,
+    bridges: Vec<String>,
+    client_transport_plugins: Vec<String>,
+    rotation_interval: Option<Duration>,
     shutdown_signal: OptionalShutdownSignal,
 }
 
@@ -125,6 +128,29 @@ impl HiddenServiceBuilder {
         HsFlags
     );
 
+    setter!(
+        /// Bridge lines (e.g. `obfs4 192.0.2.1:443 <fingerprint> cert=... iat-mode=0`) that the tor proxy should use
+        /// to reach the tor network. If any are given, `UseBridges` is enabled on the tor proxy.
+        with_bridges,
+        bridges,
+        Vec<String>
+    );
+
+    setter!(
+        /// Pluggable transport plugins (e.g. `obfs4 exec /usr/bin/obfs4proxy`) used by the configured bridges.
+        with_client_transport_plugins,
+        client_transport_plugins,
+        Vec<String>
+    );
+
+    setter!(
+        /// If set, a new onion address is created on this interval and the previous one is removed once connected
+        /// peers have had a chance to reconnect to the new address.
+        with_onion_rotation_interval,
+        rotation_interval,
+        Option<Duration>
+    );
+
     /// Use a direct TCP/IP connection if a TCP address is given instead of the tor proxy. This is worse for privacy
     /// but can use the full available connection bandwidth
     pub fn bypass_tor_for_tcp_addresses(mut self) -> Self {
@@ -180,6 +206,9 @@ impl HiddenServiceBuilder {
             self.identity,
             self.hs_flags,
             self.proxy_opts,
+            self.bridges,
+            self.client_transport_plugins,
+            self.rotation_interval,
             self.shutdown_signal,
         );
 
@@ -27,7 +27,11 @@ use log::*;
 use tari_shutdown::OptionalShutdownSignal;
 use tari_utilities::hex::Hex;
 use thiserror::Error;
-use tokio::{sync::broadcast, time};
+use tokio::{
+    sync::{broadcast, watch},
+    time,
+    time::Instant,
+};
 
 use crate::{
     multiaddr::Multiaddr,
@@ -51,6 +55,8 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "comms::tor::hidden_service_controller";
+/// How long a rotated-out onion address remains reachable so that connected peers can move to the new address
+const ONION_ROTATION_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
 
 #[derive(Debug, Error)]
 pub enum HiddenServiceControllerError {
@@ -87,6 +93,9 @@ pub struct HiddenServiceController {
     hs_flags: HsFlags,
     is_authenticated: bool,
     proxy_opts: TorProxyOpts,
+    bridges: Vec<String>,
+    client_transport_plugins: Vec<String>,
+    rotation_interval: Option<Duration>,
     shutdown_signal: OptionalShutdownSignal,
 }
 
@@ -100,6 +109,9 @@ impl HiddenServiceController {
         identity: Option<TorIdentity>,
         hs_flags: HsFlags,
         proxy_opts: TorProxyOpts,
+        bridges: Vec<String>,
+        client_transport_plugins: Vec<String>,
+        rotation_interval: Option<Duration>,
         shutdown_signal: OptionalShutdownSignal,
     ) -> Self {
         Self {
@@ -113,6 +125,9 @@ impl HiddenServiceController {
             identity,
             is_authenticated: false,
             proxy_opts,
+            bridges,
+            client_transport_plugins,
+            rotation_interval,
             shutdown_signal,
         }
     }
@@ -137,48 +152,85 @@ impl HiddenServiceController {
     /// Connects, authenticates to the Tor control port and creates a hidden service using the tor identity if provided,
     /// otherwise a new tor identity will be created. The creation of a hidden service is idempotent i.e. if the
     /// hidden service exists, the
+    ///
+    /// If an onion rotation interval is configured, a new onion address is created on that interval and published
+    /// to subscribers of [HiddenService::subscribe_identity_rotations]. The previous address is removed after a grace
+    /// period.
     pub async fn create_hidden_service(mut self) -> Result<HiddenService, HiddenServiceControllerError> {
         self.connect_and_auth().await?;
         self.set_events().await?;
 
-        let hidden_service = self.create_hidden_service_from_identity().await?;
+        let mut hidden_service = self.create_hidden_service_from_identity().await?;
+        let rotated_identity_tx = self.rotation_interval.map(|_| {
+            let (tx, rx) = watch::channel(hidden_service.identity.clone());
+            hidden_service.rotated_identity_rx = Some(rx);
+            tx
+        });
         let mut shutdown_signal = hidden_service.shutdown_signal.clone();
         let mut event_stream = self.client.as_ref().unwrap().get_event_stream();
 
         tokio::spawn({
             async move {
+                let mut next_rotation = self.rotation_interval.map(|period| Instant::now() + period);
+                let mut retiring_onion = None::<(String, Instant)>;
                 loop {
-                    let either = future::select(&mut shutdown_signal, event_stream.next()).await;
-                    match either {
-                        Either::Left(_) => {
+                    tokio::select! {
+                        _ = &mut shutdown_signal => {
                             debug!(
                                 target: LOG_TARGET,
                                 "Tor controller shut down because the shutdown signal was received"
                             );
                             break;
                         },
-                        Either::Right((Some(Ok(TorControlEvent::TorControlDisconnected)), shutdown_signal)) => {
-                            let event_tx = self
-                                .client
-                                .as_ref()
-                                .map(|c| c.event_sender().clone())
-                                .expect("HiddenServiceController::client was None");
-                            warn!(
-                                target: LOG_TARGET,
-                                "Tor control server disconnected. Attempting to reestablish connection..."
-                            );
-                            if let Err(err) = self.reestablish_hidden_service(event_tx, shutdown_signal).await {
-                                error!(
-                                    target: LOG_TARGET,
-                                    "Failed to reestablish connection to tor control server because '{:?}'", err
-                                );
-                                break;
+                        _ = sleep_until_or_pending(next_rotation) => {
+                            if let Some((service_id, _)) = retiring_onion.take() {
+                                self.retire_onion(&service_id).await;
+                            }
+                            let previous_service_id = self.identity.as_ref().map(|id| id.service_id.clone());
+                            match self.rotate_onion().await {
+                                Ok(identity) => {
+                                    if let Some(tx) = rotated_identity_tx.as_ref() {
+                                        let _result = tx.send(identity);
+                                    }
+                                    retiring_onion = previous_service_id
+                                        .map(|service_id| (service_id, Instant::now() + ONION_ROTATION_GRACE_PERIOD));
+                                },
+                                Err(err) => {
+                                    error!(target: LOG_TARGET, "Failed to rotate onion address because '{:?}'", err);
+                                },
                             }
+                            next_rotation = self.rotation_interval.map(|period| Instant::now() + period);
                         },
-                        Either::Right((Some(Ok(evt)), _)) => {
-                            trace!(target: LOG_TARGET, "Tor control event: {:?}", evt);
+                        _ = sleep_until_or_pending(retiring_onion.as_ref().map(|(_, deadline)| *deadline)) => {
+                            if let Some((service_id, _)) = retiring_onion.take() {
+                                self.retire_onion(&service_id).await;
+                            }
+                        },
+                        event = event_stream.next() => match event {
+                            Some(Ok(TorControlEvent::TorControlDisconnected)) => {
+                                let event_tx = self
+                                    .client
+                                    .as_ref()
+                                    .map(|c| c.event_sender().clone())
+                                    .expect("HiddenServiceController::client was None");
+                                warn!(
+                                    target: LOG_TARGET,
+                                    "Tor control server disconnected. Attempting to reestablish connection..."
+                                );
+                                let result = self.reestablish_hidden_service(event_tx, &mut shutdown_signal).await;
+                                if let Err(err) = result {
+                                    error!(
+                                        target: LOG_TARGET,
+                                        "Failed to reestablish connection to tor control server because '{:?}'", err
+                                    );
+                                    break;
+                                }
+                            },
+                            Some(Ok(evt)) => {
+                                trace!(target: LOG_TARGET, "Tor control event: {:?}", evt);
+                            },
+                            _ => {},
                         },
-                        _ => {},
                     }
                 }
             }
@@ -191,6 +243,7 @@ impl HiddenServiceController {
         if !self.is_authenticated {
             self.connect().await?;
             self.authenticate().await?;
+            self.configure_bridges().await?;
         }
         Ok(())
     }
@@ -214,6 +267,7 @@ impl HiddenServiceController {
                     info!(target: LOG_TARGET, "Connection to tor control port re-established");
                     self.client = Some(client);
                     self.authenticate().await?;
+                    self.configure_bridges().await?;
                     self.set_events().await?;
                     let _result = self.create_hidden_service_from_identity().await;
                     break Ok(());
@@ -305,6 +359,32 @@ impl HiddenServiceController {
         Ok(())
     }
 
+    async fn configure_bridges(&mut self) -> Result<(), HiddenServiceControllerError> {
+        if self.bridges.is_empty() {
+            return Ok(());
+        }
+
+        let mut settings = vec!["UseBridges=1".to_string()];
+        settings.extend(
+            self.client_transport_plugins
+                .iter()
+                .map(|plugin| format!("ClientTransportPlugin={}", quote_conf_value(plugin))),
+        );
+        settings.extend(
+            self.bridges
+                .iter()
+                .map(|bridge| format!("Bridge={}", quote_conf_value(bridge))),
+        );
+        info!(
+            target: LOG_TARGET,
+            "Configuring tor to connect using {} bridge(s)",
+            self.bridges.len()
+        );
+        let settings = settings.iter().map(String::as_str).collect::<Vec<_>>();
+        self.client_mut()?.set_conf(&settings).await?;
+        Ok(())
+    }
+
     async fn set_events(&mut self) -> Result<(), HiddenServiceControllerError> {
         self.client_mut()?.set_events(&["NETWORK_LIVENESS"]).await?;
         Ok(())
@@ -377,9 +457,50 @@ impl HiddenServiceController {
             identity,
             proxied_addr,
             shutdown_signal: self.shutdown_signal.clone(),
+            rotated_identity_rx: None,
         })
     }
 
+    /// Creates a new onion service with a freshly generated key and makes it the current identity. The previous onion
+    /// service is left active.
+    async fn rotate_onion(&mut self) -> Result<TorIdentity, HiddenServiceControllerError> {
+        let mut flags = Vec::new();
+        if self.hs_flags.contains(HsFlags::DETACH) {
+            flags.push(AddOnionFlag::Detach);
+        }
+
+        let port_mapping = self.proxied_port_mapping;
+        let resp = self.client_mut()?.add_onion(flags, port_mapping, None).await?;
+        let private_key = resp
+            .private_key
+            .expect("Tor server MUST return private key according to spec");
+        let identity = TorIdentity {
+            private_key,
+            service_id: resp.service_id,
+            onion_port: resp.onion_port,
+        };
+        info!(
+            target: LOG_TARGET,
+            "Rotated hidden service to service id '{}' on port '{}'", identity.service_id, identity.onion_port
+        );
+        self.identity = Some(identity.clone());
+        Ok(identity)
+    }
+
+    async fn retire_onion(&mut self, service_id: &str) {
+        let result = match self.client_mut() {
+            Ok(client) => client.del_onion(service_id).await.map_err(Into::into),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(_) => debug!(target: LOG_TARGET, "Removed rotated-out onion service '{}'", service_id),
+            Err(err) => warn!(
+                target: LOG_TARGET,
+                "Failed to remove rotated-out onion service '{}' because '{:?}'", service_id, err
+            ),
+        }
+    }
+
     pub fn set_proxied_addr(&mut self, addr: &Multiaddr) {
         self.proxied_port_mapping.set_proxied_addr(
             multiaddr_to_socketaddr(addr).expect("set_proxied_addr: multiaddr must be a valid TCP socket address"),
@@ -432,3 +553,29 @@ impl HiddenServiceController {
         }
     }
 }
+
+async fn sleep_until_or_pending(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => future::pending().await,
+    }
+}
+
+/// Quotes a value for use in a SETCONF command
+fn quote_conf_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quote_conf_value_escapes() {
+        assert_eq!(
+            quote_conf_value("obfs4 192.0.2.1:443 cert=abc"),
+            r#""obfs4 192.0.2.1:443 cert=abc""#
+        );
+        assert_eq!(quote_conf_value(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+}
@@ -33,6 +33,7 @@ use derivative::Derivative;
 pub use proxy_opts::TorProxyOpts;
 use serde_derive::{Deserialize, Serialize};
 use tari_shutdown::OptionalShutdownSignal;
+use tokio::sync::watch;
 
 use crate::{
     multiaddr::Multiaddr,
@@ -49,6 +50,8 @@ pub struct HiddenService {
     pub(super) proxied_addr: Multiaddr,
     /// Shutdown signal for hidden service
     pub(super) shutdown_signal: OptionalShutdownSignal,
+    /// Receives the new identity each time the onion address is rotated. None if rotation is disabled.
+    pub(super) rotated_identity_rx: Option<watch::Receiver<TorIdentity>>,
 }
 
 impl HiddenService {
@@ -69,6 +72,12 @@ impl HiddenService {
     pub fn tor_identity(&self) -> &TorIdentity {
         &self.identity
     }
+
+    /// Returns a receiver that is notified with the new identity each time the onion address of this hidden service
+    /// is rotated, or None if onion address rotation is not enabled.
+    pub fn subscribe_identity_rotations(&self) -> Option<watch::Receiver<TorIdentity>> {
+        self.rotated_identity_rx.clone()
+    }
 }
 
 fn multiaddr_from_service_id_and_port(service_id: &str, onion_port: u16) -> Result<Multiaddr, TorClientError> {
@@ -46,20 +46,22 @@ struct HiddenServiceTransportInner {
     hidden_service_ctl: Option<HiddenServiceController>,
 }
 
+/// Transport that listens on a tor hidden service. `after_init` is called with the tor identity once the hidden service
+/// has been created, and again each time the onion address is rotated.
 #[derive(Clone)]
 pub struct HiddenServiceTransport<F: Fn(TorIdentity)> {
     inner: Arc<RwLock<HiddenServiceTransportInner>>,
-    after_init: F,
+    after_init: Arc<F>,
 }
 
-impl<F: Fn(TorIdentity)> HiddenServiceTransport<F> {
+impl<F: Fn(TorIdentity) + Send + Sync + 'static> HiddenServiceTransport<F> {
     pub fn new(hidden_service_ctl: HiddenServiceController, after_init: F) -> Self {
         Self {
             inner: Arc::new(RwLock::new(HiddenServiceTransportInner {
                 socks_transport: None,
                 hidden_service_ctl: Some(hidden_service_ctl),
             })),
-            after_init,
+            after_init: Arc::new(after_init),
         }
     }
 
@@ -105,11 +107,20 @@ impl<F: Fn(TorIdentity)> HiddenServiceTransport<F> {
         })?;
 
         (self.after_init)(hidden_service.tor_identity().clone());
+        if let Some(mut rotated_identity_rx) = hidden_service.subscribe_identity_rotations() {
+            let after_init = self.after_init.clone();
+            tokio::spawn(async move {
+                while rotated_identity_rx.changed().await.is_ok() {
+                    let identity = rotated_identity_rx.borrow_and_update().clone();
+                    (after_init)(identity);
+                }
+            });
+        }
         Ok((inbound, listen_addr))
     }
 }
 #[crate::async_trait]
-impl<F: Fn(TorIdentity) + Send + Sync> Transport for HiddenServiceTransport<F> {
+impl<F: Fn(TorIdentity) + Send + Sync + 'static> Transport for HiddenServiceTransport<F> {
     type Error = <SocksTransport as Transport>::Error;
     type Listener = <SocksTransport as Transport>::Listener;
     type Output = <SocksTransport as Transport>::Output;
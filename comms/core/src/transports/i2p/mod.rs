@@ -0,0 +1,181 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # I2P transport
+//!
+//! Dials and accepts streams over the I2P network using the SAM v3 bridge of a local I2P router (e.g. i2pd or the
+//! Java router with the SAM application enabled). I2P addresses are represented as `/dns/<base32>.b32.i2p/tcp/<port>`
+//! where the port is the I2P virtual port.
+
+mod sam;
+
+use std::{fs, io, path::PathBuf, sync::Arc, time::Duration};
+
+use log::*;
+pub use sam::SamError;
+use sam::{destination_to_b32_host, SamConnection};
+use tokio::{net::TcpStream, sync::mpsc, time};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    multiaddr::Multiaddr,
+    transports::Transport,
+    utils::multiaddr::{i2p_host_to_multiaddr, multiaddr_to_i2p_host, multiaddr_to_socketaddr},
+};
+
+const LOG_TARGET: &str = "comms::transports::i2p";
+/// Delay before accepting again after the SAM bridge failed to accept a stream
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// I2P transport config
+#[derive(Debug, Clone)]
+pub struct I2pConfig {
+    /// The address of the SAM bridge of the I2P router
+    pub sam_address: Multiaddr,
+    /// The ID of the SAM session. This must be unique for each application using the same SAM bridge.
+    pub session_id: String,
+    /// The I2P virtual port advertised in this node's I2P address
+    pub port: u16,
+    /// If set, the private key of this node's I2P destination is loaded from this file, or saved to it when a new
+    /// destination is created, so that the node keeps the same I2P address across restarts.
+    pub private_key_file: Option<PathBuf>,
+}
+
+impl Default for I2pConfig {
+    fn default() -> Self {
+        Self {
+            sam_address: "/ip4/127.0.0.1/tcp/7656".parse().unwrap(),
+            session_id: "tari".to_string(),
+            port: 18141,
+            private_key_file: None,
+        }
+    }
+}
+
+/// Transport over the I2P network. `after_init` is called with this node's I2P address once the SAM session has been
+/// created, so that it can be added to the node's public addresses.
+#[derive(Clone)]
+pub struct I2pTransport<F> {
+    config: Arc<I2pConfig>,
+    after_init: Arc<F>,
+}
+
+impl<F: Fn(Multiaddr) + Send + Sync + 'static> I2pTransport<F> {
+    pub fn new(config: I2pConfig, after_init: F) -> Self {
+        Self {
+            config: Arc::new(config),
+            after_init: Arc::new(after_init),
+        }
+    }
+
+    fn load_private_key(&self) -> io::Result<Option<String>> {
+        match self.config.private_key_file {
+            Some(ref path) if path.exists() => Ok(Some(fs::read_to_string(path)?.trim().to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    fn save_private_key(&self, private_key: &str) -> io::Result<()> {
+        if let Some(ref path) = self.config.private_key_file {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, private_key)?;
+        }
+        Ok(())
+    }
+}
+
+#[crate::async_trait]
+impl<F: Fn(Multiaddr) + Send + Sync + 'static> Transport for I2pTransport<F> {
+    type Error = io::Error;
+    type Listener = ReceiverStream<io::Result<(TcpStream, Multiaddr)>>;
+    type Output = TcpStream;
+
+    /// Creates the SAM session and starts accepting inbound streams. The given address is not used, the returned
+    /// address is this node's I2P address.
+    async fn listen(&self, _addr: &Multiaddr) -> Result<(Self::Listener, Multiaddr), Self::Error> {
+        let private_key = self.load_private_key()?;
+        let mut session = connect_sam(&self.config).await?;
+        let session_private_key = session
+            .create_session(&self.config.session_id, private_key.as_deref())
+            .await?;
+        if private_key.is_none() {
+            self.save_private_key(&session_private_key)?;
+        }
+        let destination = session.naming_lookup("ME").await?;
+        let address = i2p_host_to_multiaddr(&destination_to_b32_host(&destination)?, self.config.port);
+        info!(
+            target: LOG_TARGET,
+            "I2P session '{}' created with address '{}'", self.config.session_id, address
+        );
+        (self.after_init)(address.clone());
+
+        let (inbound_tx, inbound_rx) = mpsc::channel(1);
+        tokio::spawn(accept_streams(self.config.clone(), session, inbound_tx));
+        Ok((ReceiverStream::new(inbound_rx), address))
+    }
+
+    async fn dial(&self, addr: &Multiaddr) -> Result<Self::Output, Self::Error> {
+        let (host, port) = multiaddr_to_i2p_host(addr).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Address '{}' is not an I2P address", addr),
+            )
+        })?;
+        let mut conn = connect_sam(&self.config).await?;
+        let destination = conn.naming_lookup(&host).await?;
+        let stream = conn.stream_connect(&self.config.session_id, &destination, port).await?;
+        Ok(stream)
+    }
+}
+
+/// Accepts inbound streams one at a time until the listener is dropped. The SAM session is closed when this returns.
+async fn accept_streams(
+    config: Arc<I2pConfig>,
+    _session: SamConnection,
+    inbound_tx: mpsc::Sender<io::Result<(TcpStream, Multiaddr)>>,
+) {
+    loop {
+        let result = accept_stream(&config).await;
+        let is_err = result.is_err();
+        if inbound_tx.send(result.map_err(Into::into)).await.is_err() {
+            debug!(target: LOG_TARGET, "I2P listener dropped. Closing session '{}'", config.session_id);
+            break;
+        }
+        if is_err {
+            time::sleep(ACCEPT_RETRY_DELAY).await;
+        }
+    }
+}
+
+async fn accept_stream(config: &I2pConfig) -> Result<(TcpStream, Multiaddr), SamError> {
+    let conn = connect_sam(config).await?;
+    let (stream, destination, from_port) = conn.stream_accept(&config.session_id).await?;
+    let peer_addr = i2p_host_to_multiaddr(&destination_to_b32_host(&destination)?, from_port);
+    Ok((stream, peer_addr))
+}
+
+async fn connect_sam(config: &I2pConfig) -> Result<SamConnection, SamError> {
+    let addr = multiaddr_to_socketaddr(&config.sam_address)?;
+    SamConnection::connect(addr).await
+}
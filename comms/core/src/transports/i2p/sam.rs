@@ -0,0 +1,312 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A minimal client for the [SAM v3](https://geti2p.net/en/docs/api/samv3) bridge of an I2P router.
+
+use std::{collections::HashMap, io, net::SocketAddr};
+
+use data_encoding::{Encoding, Specification, BASE32_NOPAD};
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::utils::multiaddr::I2P_B32_SUFFIX;
+
+const SAM_MIN_VERSION: &str = "3.1";
+const SAM_MAX_VERSION: &str = "3.3";
+const MAX_REPLY_LINE_LENGTH: usize = 8 * 1024;
+
+/// The base64 alphabet used by I2P, which replaces `+` and `/` with `-` and `~`
+static I2P_BASE64: Lazy<Encoding> = Lazy::new(|| {
+    let mut spec = Specification::new();
+    spec.symbols
+        .push_str("ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-~");
+    spec.padding = Some('=');
+    spec.encoding().expect("I2P base64 specification is valid")
+});
+
+#[derive(Debug, Error)]
+pub enum SamError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Invalid reply from the SAM bridge: {0}")]
+    InvalidReply(String),
+    #[error("SAM command failed with result {result}: {message}")]
+    CommandFailed { result: String, message: String },
+    #[error("Invalid I2P destination")]
+    InvalidDestination,
+}
+
+impl From<SamError> for io::Error {
+    fn from(err: SamError) -> Self {
+        match err {
+            SamError::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::Other, err),
+        }
+    }
+}
+
+/// A single line reply from the SAM bridge e.g. `STREAM STATUS RESULT=OK`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamReply {
+    pub topic: String,
+    pub kind: String,
+    pub args: HashMap<String, String>,
+}
+
+impl SamReply {
+    pub fn parse(line: &str) -> Result<Self, SamError> {
+        let mut tokens = tokenize(line).into_iter();
+        let topic = tokens
+            .next()
+            .ok_or_else(|| SamError::InvalidReply("empty reply".to_string()))?;
+        let kind = tokens
+            .next()
+            .ok_or_else(|| SamError::InvalidReply(format!("reply '{}' has no type", line)))?;
+        let args = tokens
+            .map(|token| match token.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => (token, String::new()),
+            })
+            .collect();
+
+        Ok(Self { topic, kind, args })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.args.get(key).map(String::as_str)
+    }
+
+    /// Checks that this is a `<topic> <kind>` reply with `RESULT=OK`
+    fn expect_ok(self, topic: &str, kind: &str) -> Result<Self, SamError> {
+        if self.topic != topic || self.kind != kind {
+            return Err(SamError::InvalidReply(format!(
+                "expected '{} {}' but got '{} {}'",
+                topic, kind, self.topic, self.kind
+            )));
+        }
+        if self.get("RESULT") == Some("OK") {
+            return Ok(self);
+        }
+        Err(SamError::CommandFailed {
+            result: self.get("RESULT").unwrap_or("<none>").to_string(),
+            message: self.get("MESSAGE").unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// Splits a reply line on spaces, keeping double-quoted values (e.g. `MESSAGE="some message"`) together
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            },
+            ' ' if !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            },
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Returns the `<base32>.b32.i2p` hostname of the given base64 encoded destination
+pub fn destination_to_b32_host(destination: &str) -> Result<String, SamError> {
+    let bytes = I2P_BASE64
+        .decode(destination.as_bytes())
+        .map_err(|_| SamError::InvalidDestination)?;
+    let hash = Sha256::digest(bytes);
+    Ok(format!(
+        "{}{}",
+        BASE32_NOPAD.encode(&hash).to_ascii_lowercase(),
+        I2P_B32_SUFFIX
+    ))
+}
+
+/// A connection to the SAM bridge on which the HELLO handshake has been completed
+pub struct SamConnection {
+    socket: TcpStream,
+}
+
+impl SamConnection {
+    pub async fn connect(addr: SocketAddr) -> Result<Self, SamError> {
+        let socket = TcpStream::connect(addr).await?;
+        let mut conn = Self { socket };
+        conn.command(&format!(
+            "HELLO VERSION MIN={} MAX={}",
+            SAM_MIN_VERSION, SAM_MAX_VERSION
+        ))
+        .await?
+        .expect_ok("HELLO", "REPLY")?;
+        Ok(conn)
+    }
+
+    /// Creates a stream session with the given private key, or a new transient destination if None. Returns the
+    /// private key of the session. The session is closed when this connection is dropped.
+    pub async fn create_session(&mut self, session_id: &str, private_key: Option<&str>) -> Result<String, SamError> {
+        let destination = match private_key {
+            Some(key) => key.to_string(),
+            None => "TRANSIENT SIGNATURE_TYPE=EdDSA_SHA512_Ed25519".to_string(),
+        };
+        let reply = self
+            .command(&format!(
+                "SESSION CREATE STYLE=STREAM ID={} DESTINATION={}",
+                session_id, destination
+            ))
+            .await?
+            .expect_ok("SESSION", "STATUS")?;
+        reply
+            .get("DESTINATION")
+            .map(ToString::to_string)
+            .ok_or_else(|| SamError::InvalidReply("SESSION STATUS did not include a DESTINATION".to_string()))
+    }
+
+    /// Resolves a name (e.g. a `.b32.i2p` hostname or `ME` for the destination of this session) to a base64
+    /// destination
+    pub async fn naming_lookup(&mut self, name: &str) -> Result<String, SamError> {
+        let reply = self
+            .command(&format!("NAMING LOOKUP NAME={}", name))
+            .await?
+            .expect_ok("NAMING", "REPLY")?;
+        reply
+            .get("VALUE")
+            .map(ToString::to_string)
+            .ok_or_else(|| SamError::InvalidReply("NAMING REPLY did not include a VALUE".to_string()))
+    }
+
+    /// Connects to the given destination using the session. On success, the connection becomes the stream.
+    pub async fn stream_connect(
+        mut self,
+        session_id: &str,
+        destination: &str,
+        to_port: u16,
+    ) -> Result<TcpStream, SamError> {
+        self.command(&format!(
+            "STREAM CONNECT ID={} DESTINATION={} SILENT=false TO_PORT={}",
+            session_id, destination, to_port
+        ))
+        .await?
+        .expect_ok("STREAM", "STATUS")?;
+        Ok(self.socket)
+    }
+
+    /// Waits for an inbound stream on the session. Returns the stream, the destination of the peer and the peer's
+    /// port.
+    pub async fn stream_accept(mut self, session_id: &str) -> Result<(TcpStream, String, u16), SamError> {
+        self.command(&format!("STREAM ACCEPT ID={} SILENT=false", session_id))
+            .await?
+            .expect_ok("STREAM", "STATUS")?;
+        // The first line received is "$destination FROM_PORT=nnn TO_PORT=nnn", after which the stream begins
+        let line = self.read_line().await?;
+        let mut tokens = line.split(' ');
+        let destination = tokens
+            .next()
+            .filter(|d| !d.is_empty())
+            .ok_or(SamError::InvalidDestination)?
+            .to_string();
+        let from_port = tokens
+            .filter_map(|token| token.strip_prefix("FROM_PORT="))
+            .find_map(|port| port.parse().ok())
+            .unwrap_or_default();
+        Ok((self.socket, destination, from_port))
+    }
+
+    async fn command(&mut self, command: &str) -> Result<SamReply, SamError> {
+        self.socket.write_all(command.as_bytes()).await?;
+        self.socket.write_all(b"\n").await?;
+        let line = self.read_line().await?;
+        SamReply::parse(&line)
+    }
+
+    /// Reads a single line one byte at a time so that no stream data following the line is consumed
+    async fn read_line(&mut self) -> Result<String, SamError> {
+        let mut line = Vec::new();
+        loop {
+            let byte = self.socket.read_u8().await?;
+            if byte == b'\n' {
+                break;
+            }
+            if line.len() >= MAX_REPLY_LINE_LENGTH {
+                return Err(SamError::InvalidReply("reply line too long".to_string()));
+            }
+            line.push(byte);
+        }
+        String::from_utf8(line).map_err(|_| SamError::InvalidReply("reply was not valid UTF-8".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_reply() {
+        let reply = SamReply::parse("HELLO REPLY RESULT=OK VERSION=3.3\n").unwrap();
+        assert_eq!(reply.topic, "HELLO");
+        assert_eq!(reply.kind, "REPLY");
+        assert_eq!(reply.get("VERSION"), Some("3.3"));
+        reply.expect_ok("HELLO", "REPLY").unwrap();
+
+        let reply =
+            SamReply::parse(r#"STREAM STATUS RESULT=CANT_REACH_PEER MESSAGE="Peer \"x\" unreachable""#).unwrap();
+        assert_eq!(reply.get("MESSAGE"), Some(r#"Peer "x" unreachable"#));
+        match reply.expect_ok("STREAM", "STATUS").unwrap_err() {
+            SamError::CommandFailed { result, message } => {
+                assert_eq!(result, "CANT_REACH_PEER");
+                assert_eq!(message, r#"Peer "x" unreachable"#);
+            },
+            err => panic!("unexpected error {:?}", err),
+        }
+
+        let reply = SamReply::parse("SESSION STATUS RESULT=OK").unwrap();
+        reply.expect_ok("STREAM", "STATUS").unwrap_err();
+        SamReply::parse("").unwrap_err();
+    }
+
+    #[test]
+    fn destination_to_b32() {
+        let destination = I2P_BASE64.encode(&[0xab; 391]);
+        let host = destination_to_b32_host(&destination).unwrap();
+        assert_eq!(host.len(), 52 + I2P_B32_SUFFIX.len());
+        assert!(host.ends_with(I2P_B32_SUFFIX));
+        assert_eq!(host, host.to_ascii_lowercase());
+
+        destination_to_b32_host("not+base64").unwrap_err();
+    }
+}
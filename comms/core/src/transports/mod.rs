@@ -30,6 +30,7 @@
 //! - [TCP](self::TcpTransport) - communication over TCP and IP4/IP6 and DNS
 //! - [SOCKS](self::SocksTransport) - communication over a SOCKS5 proxy.
 //! - [Memory](self::MemoryTransport) - in-process communication (mpsc channel), typically for testing.
+//! - [I2P](self::I2pTransport) - communication over the I2P network using a SAM bridge (requires the `i2p` feature).
 
 use multiaddr::Multiaddr;
 use tokio_stream::Stream;
@@ -52,6 +53,11 @@ mod tcp_with_tor;
 pub use hidden_service_transport::HiddenServiceTransport;
 pub use tcp_with_tor::TcpWithTorTransport;
 
+#[cfg(feature = "i2p")]
+mod i2p;
+#[cfg(feature = "i2p")]
+pub use i2p::{I2pConfig, I2pTransport, SamError};
+
 /// Defines an abstraction for implementations that can dial and listen for connections over a provided address.
 #[crate::async_trait]
 pub trait Transport {
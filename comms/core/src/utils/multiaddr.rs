@@ -69,6 +69,36 @@ pub fn multiaddr_to_socketaddr(addr: &Multiaddr) -> io::Result<SocketAddr> {
     }
 }
 
+/// The suffix of the hostname of an I2P destination e.g. `<base32>.b32.i2p`
+pub const I2P_B32_SUFFIX: &str = ".b32.i2p";
+
+/// Returns true if the host is a `<base32>.b32.i2p` I2P hostname
+pub fn is_i2p_b32_host(host: &str) -> bool {
+    const I2P_B32_LEN: usize = 52;
+    host.strip_suffix(I2P_B32_SUFFIX).map_or(false, |b32| {
+        b32.len() == I2P_B32_LEN && b32.bytes().all(|b| matches!(b, b'a'..=b'z' | b'2'..=b'7'))
+    })
+}
+
+/// Returns the I2P hostname and port of an I2P address of the form `/dns/<base32>.b32.i2p/tcp/<port>`, or None if the
+/// address is not an I2P address.
+pub fn multiaddr_to_i2p_host(addr: &Multiaddr) -> Option<(String, u16)> {
+    let mut addr_iter = addr.iter();
+    match (addr_iter.next(), addr_iter.next(), addr_iter.next()) {
+        (Some(Protocol::Dns(host)), Some(Protocol::Tcp(port)), None) if is_i2p_b32_host(&host) => {
+            Some((host.into_owned(), port))
+        },
+        _ => None,
+    }
+}
+
+/// Convert an I2P hostname and virtual port to a multiaddress of the form `/dns/<base32>.b32.i2p/tcp/<port>`
+pub fn i2p_host_to_multiaddr(host: &str, port: u16) -> Multiaddr {
+    let mut addr: Multiaddr = Protocol::Dns(host.into()).into();
+    addr.push(Protocol::Tcp(port));
+    addr
+}
+
 /// Convert a socket address to a multiaddress. Assumes the protocol is Tcp
 pub fn socketaddr_to_multiaddr(socket_addr: &SocketAddr) -> Multiaddr {
     let mut addr: Multiaddr = match socket_addr.ip() {
@@ -122,6 +152,30 @@ mod test {
         expect_fail("/dns4/doesntexist.theresnotldlikethis/tcp/1234")
     }
 
+    #[test]
+    fn i2p_multiaddr() {
+        let host = "ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkdq.b32.i2p";
+        let addr = i2p_host_to_multiaddr(host, 18141);
+        assert_eq!(addr.to_string(), format!("/dns/{}/tcp/18141", host));
+        assert_eq!(multiaddr_to_i2p_host(&addr), Some((host.to_string(), 18141)));
+
+        assert!(!is_i2p_b32_host(
+            "ukeu3k5oycgaauneqgtnvselmt4yemvoilkln7jpvamvfx7dnkd.b32.i2p"
+        ));
+        assert!(!is_i2p_b32_host(
+            "UKEU3K5OYCGAAUNEQGTNVSELMT4YEMVOILKLN7JPVAMVFX7DNKDQ.b32.i2p"
+        ));
+        assert!(!is_i2p_b32_host("example.i2p"));
+        assert_eq!(
+            multiaddr_to_i2p_host(&"/dns/example.com/tcp/18141".parse().unwrap()),
+            None
+        );
+        assert_eq!(
+            multiaddr_to_i2p_host(&format!("/dns4/{}/tcp/18141", host).parse().unwrap()),
+            None
+        );
+    }
+
     #[test]
     fn multiaddr_from_components() {
         let ip: Ipv4Addr = "127.0.0.1".parse().unwrap();
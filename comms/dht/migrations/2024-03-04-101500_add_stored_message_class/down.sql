@@ -0,0 +1,4 @@
+DROP INDEX idx_stored_messages_message_class;
+
+ALTER TABLE stored_messages
+    DROP COLUMN message_class;
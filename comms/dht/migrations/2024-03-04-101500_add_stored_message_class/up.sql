@@ -0,0 +1,4 @@
+ALTER TABLE stored_messages
+    ADD message_class INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_stored_messages_message_class ON stored_messages (message_class, stored_at);
@@ -542,8 +542,9 @@ impl DhtActor {
                     .map_err(Into::into)
             },
             Flood(exclude) => {
+                // Order by latency so that the lowest latency peers are sent the message first
                 let peers = connectivity
-                    .select_connections(ConnectivitySelection::all_nodes(exclude))
+                    .select_connections(ConnectivitySelection::all_nodes_by_latency(exclude))
                     .await?;
                 Ok(peers.into_iter().map(|p| p.peer_node_id().clone()).collect())
             },
@@ -24,7 +24,7 @@
 
 use std::{sync::Arc, time::Duration};
 
-use tari_comms::{connectivity::ConnectivityRequester, NodeIdentity, PeerManager};
+use tari_comms::{bandwidth::BandwidthMeter, connectivity::ConnectivityRequester, NodeIdentity, PeerManager};
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::mpsc;
 
@@ -50,6 +50,7 @@ use crate::{
 pub struct DhtBuilder {
     config: DhtConfig,
     outbound_tx: Option<mpsc::Sender<DhtOutboundRequest>>,
+    bandwidth_meter: Option<BandwidthMeter>,
 }
 
 impl DhtBuilder {
@@ -60,6 +61,7 @@ impl DhtBuilder {
             #[cfg(not(test))]
             config: Default::default(),
             outbound_tx: None,
+            bandwidth_meter: None,
         }
     }
 
@@ -87,6 +89,13 @@ impl DhtBuilder {
         self
     }
 
+    /// Sets the comms bandwidth meter. Store and forward traffic is attributed to the SAF service of the meter and
+    /// stored message requests are not answered once the SAF quota is used.
+    pub fn with_bandwidth_meter(&mut self, bandwidth_meter: BandwidthMeter) -> &mut Self {
+        self.bandwidth_meter = Some(bandwidth_meter);
+        self
+    }
+
     /// Sets the mpsc sender that is hooked up to the outbound messaging pipeline.
     pub fn with_outbound_sender(&mut self, outbound_tx: mpsc::Sender<DhtOutboundRequest>) -> &mut Self {
         self.outbound_tx = Some(outbound_tx);
@@ -172,6 +181,7 @@ impl DhtBuilder {
             peer_manager,
             outbound_tx,
             connectivity,
+            self.bandwidth_meter.clone(),
             shutdown_signal,
         )
         .await
@@ -112,6 +112,9 @@ pub struct DhtConfig {
     /// Configuration for peer validation
     /// See [PeerValidatorConfig]
     pub peer_validator_config: PeerValidatorConfig,
+    /// Inbound message rate limiting
+    /// See [DhtRateLimitConfig]
+    pub rate_limit: DhtRateLimitConfig,
 }
 
 impl DhtConfig {
@@ -188,6 +191,7 @@ impl Default for DhtConfig {
             max_permitted_peer_claims: 5,
             offline_peer_cooldown: Duration::from_secs(24 * 60 * 60),
             peer_validator_config: Default::default(),
+            rate_limit: Default::default(),
         }
     }
 }
@@ -223,3 +227,57 @@ impl Default for DhtConnectivityConfig {
         }
     }
 }
+
+/// A token-bucket quota. A peer may send up to `burst` messages at once, after which messages are allowed at a rate of
+/// `messages_per_minute`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateQuota {
+    pub messages_per_minute: u32,
+    pub burst: u32,
+}
+
+impl RateQuota {
+    pub const fn new(messages_per_minute: u32, burst: u32) -> Self {
+        Self {
+            messages_per_minute,
+            burst,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DhtRateLimitConfig {
+    /// Set to false to disable inbound message rate limiting. Default: true
+    pub enabled: bool,
+    /// The quota for all inbound messages from a single peer.
+    /// Default: 6,000 messages per minute with a burst of 500
+    pub peer_quota: RateQuota,
+    /// The per-peer quota for join messages.
+    /// Default: 6 messages per minute with a burst of 10
+    pub join_quota: RateQuota,
+    /// The per-peer quota for discovery messages.
+    /// Default: 30 messages per minute with a burst of 30
+    pub discovery_quota: RateQuota,
+    /// The per-peer quota for store and forward retrieval requests.
+    /// Default: 6 messages per minute with a burst of 5
+    pub saf_request_quota: RateQuota,
+    /// The maximum number of peers for which quotas are tracked. Peers that have been idle the longest are evicted
+    /// first.
+    /// Default: 10,000
+    pub max_tracked_peers: usize,
+}
+
+impl Default for DhtRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            peer_quota: RateQuota::new(6_000, 500),
+            join_quota: RateQuota::new(6, 10),
+            discovery_quota: RateQuota::new(30, 30),
+            saf_request_quota: RateQuota::new(6, 5),
+            max_tracked_peers: 10_000,
+        }
+    }
+}
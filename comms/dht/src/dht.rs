@@ -25,6 +25,7 @@ use std::sync::Arc;
 use futures::Future;
 use log::*;
 use tari_comms::{
+    bandwidth::BandwidthMeter,
     connectivity::ConnectivityRequester,
     message::{InboundMessage, OutboundMessage},
     peer_manager::{NodeIdentity, PeerFeatures, PeerManager},
@@ -105,6 +106,8 @@ pub struct Dht {
     event_publisher: DhtEventSender,
     /// Used by MetricsLayer to collect metrics and to inform heuristics for peer banning
     metrics_collector: MetricsCollectorHandle,
+    /// Comms bandwidth meter used to attribute store and forward traffic
+    bandwidth_meter: Option<BandwidthMeter>,
 }
 
 impl Dht {
@@ -114,6 +117,7 @@ impl Dht {
         peer_manager: Arc<PeerManager>,
         outbound_tx: mpsc::Sender<DhtOutboundRequest>,
         connectivity: ConnectivityRequester,
+        bandwidth_meter: Option<BandwidthMeter>,
         shutdown_signal: ShutdownSignal,
     ) -> Result<Self, DhtInitializationError> {
         let (dht_sender, dht_receiver) = mpsc::channel(DHT_ACTOR_CHANNEL_SIZE);
@@ -136,6 +140,7 @@ impl Dht {
             connectivity,
             discovery_sender,
             event_publisher,
+            bandwidth_meter,
         };
 
         let conn = DbConnection::connect_and_migrate(&dht.config.database_url.clone())
@@ -299,6 +304,7 @@ impl Dht {
         ServiceBuilder::new()
             .layer(MetricsLayer::new(self.metrics_collector.clone()))
             .layer(inbound::DeserializeLayer::new(self.peer_manager.clone()))
+            .layer(inbound::RateLimitLayer::new(self.config.rate_limit))
             .layer(filter::FilterLayer::new(self.unsupported_saf_messages_filter()))
             .layer(filter::FilterLayer::new(discard_expired_messages))
             .layer(inbound::DecryptionLayer::new(
@@ -333,6 +339,7 @@ impl Dht {
                 Arc::clone(&self.node_identity),
                 self.outbound_requester(),
                 self.saf_response_signal_sender.clone(),
+                self.bandwidth_meter.clone(),
             ))
             .layer(inbound::DhtHandlerLayer::new(
                 self.config.clone(),
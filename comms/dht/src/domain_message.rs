@@ -24,6 +24,8 @@ use std::cmp;
 
 use rand::{rngs::OsRng, RngCore};
 
+use crate::envelope::SafMessageClass;
+
 /// Trait that exposes conversion to a protobuf i32 enum type.
 pub trait ToProtoEnum {
     fn as_i32(&self) -> i32;
@@ -40,6 +42,7 @@ impl ToProtoEnum for i32 {
 pub struct OutboundDomainMessage<T> {
     inner: T,
     message_type: i32,
+    saf_class: Option<SafMessageClass>,
 }
 
 impl<T> OutboundDomainMessage<T> {
@@ -48,9 +51,21 @@ impl<T> OutboundDomainMessage<T> {
         Self {
             inner: message,
             message_type: message_type.as_i32(),
+            saf_class: None,
         }
     }
 
+    /// Sets the store and forward class of the message, overriding the class set in the send parameters.
+    pub fn with_saf_class(mut self, saf_class: SafMessageClass) -> Self {
+        self.saf_class = Some(saf_class);
+        self
+    }
+
+    /// Returns the store and forward class of this message, if set.
+    pub fn saf_class(&self) -> Option<SafMessageClass> {
+        self.saf_class
+    }
+
     /// Consumes this instance returning the inner message.
     pub fn into_inner(self) -> T {
         self.inner
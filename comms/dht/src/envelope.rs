@@ -34,7 +34,7 @@ use tari_utilities::{epoch_time::EpochTime, ByteArray, ByteArrayError};
 use thiserror::Error;
 
 // Re-export applicable protos
-pub use crate::proto::envelope::{dht_header::Destination, DhtEnvelope, DhtHeader, DhtMessageType};
+pub use crate::proto::envelope::{dht_header::Destination, DhtEnvelope, DhtHeader, DhtMessageType, SafMessageClass};
 use crate::version::DhtProtocolVersion;
 
 /// Utility function that converts a `chrono::DateTime` to a `EpochTime`
@@ -80,6 +80,12 @@ impl fmt::Display for DhtMessageType {
     }
 }
 
+impl fmt::Display for SafMessageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 bitflags! {
     /// Used to indicate characteristics of the incoming or outgoing message, such
     /// as whether the message is encrypted.
@@ -140,6 +146,7 @@ pub struct DhtMessageHeader {
     pub flags: DhtMessageFlags,
     pub message_tag: MessageTag,
     pub expires: Option<EpochTime>,
+    pub saf_class: SafMessageClass,
 }
 
 impl DhtMessageHeader {
@@ -169,6 +176,16 @@ impl DhtMessageHeader {
 
         true
     }
+
+    /// Returns the store and forward class of the message. Join and discovery messages are always in the discovery
+    /// class, otherwise the class hint set by the sender is used.
+    pub fn saf_message_class(&self) -> SafMessageClass {
+        if self.message_type.is_dht_message() {
+            SafMessageClass::Discovery
+        } else {
+            self.saf_class
+        }
+    }
 }
 
 impl PartialEq for DhtMessageHeader {
@@ -180,7 +197,8 @@ impl PartialEq for DhtMessageHeader {
             self.ephemeral_public_key == other.ephemeral_public_key &&
             self.message_type == other.message_type &&
             self.flags == other.flags &&
-            self.expires == other.expires
+            self.expires == other.expires &&
+            self.saf_class == other.saf_class
     }
 }
 
@@ -228,6 +246,8 @@ impl TryFrom<DhtHeader> for DhtMessageHeader {
             flags: DhtMessageFlags::from_bits(header.flags).ok_or(DhtMessageError::InvalidMessageFlags)?,
             message_tag: MessageTag::from(header.message_tag),
             expires,
+            // Unrecognised classes are stored as general messages
+            saf_class: SafMessageClass::from_i32(header.saf_class).unwrap_or_default(),
         })
     }
 }
@@ -258,6 +278,7 @@ impl From<DhtMessageHeader> for DhtHeader {
             flags: header.flags.bits(),
             message_tag: header.message_tag.as_value(),
             expires: header.expires.map(EpochTime::as_u64).unwrap_or_default(),
+            saf_class: header.saf_class as i32,
         }
     }
 }
@@ -37,6 +37,9 @@ pub use forward::ForwardLayer;
 mod metrics;
 pub use metrics::MetricsLayer;
 
+mod rate_limit;
+pub use rate_limit::{RateLimitDecision, RateLimitLayer, RateLimiter};
+
 mod error;
 pub use error::DhtInboundError;
 
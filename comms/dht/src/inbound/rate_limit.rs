@@ -0,0 +1,474 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! # DHT inbound rate limiting
+//!
+//! Token-bucket rate limiting of inbound messages. Each peer is given a bucket for all of its messages as well as
+//! buckets for the more expensive DHT message types (joins, discovery and SAF retrievals). A message that exceeds a
+//! quota is briefly delayed if a token will soon be available, applying backpressure to the peer, otherwise it is
+//! discarded rather than being queued.
+
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+use futures::{future::BoxFuture, task::Context};
+use log::*;
+use tari_comms::{peer_manager::NodeId, pipeline::PipelineError};
+use tower::{layer::Layer, Service, ServiceExt};
+
+use crate::{
+    config::{DhtRateLimitConfig, RateQuota},
+    envelope::DhtMessageType,
+    inbound::DhtInboundMessage,
+};
+
+const LOG_TARGET: &str = "comms::dht::rate_limit";
+
+/// A message that exceeds a quota is delayed until a token is available if that is within this duration, otherwise
+/// the message is discarded.
+const MAX_THROTTLE_DELAY: Duration = Duration::from_millis(500);
+/// Peers that have not sent a message within this period are removed from the limiter
+const PEER_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// The number of token units that make up a single message token. Buckets refill by `messages_per_minute` units every
+/// millisecond so that integer arithmetic can be used.
+const UNITS_PER_TOKEN: u64 = 60 * 1000;
+
+/// The outcome of checking a message against the rate limiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// The message is within quota
+    Allow,
+    /// The message exceeded a quota but will be within quota after the given delay
+    Throttle(Duration),
+    /// The message exceeded a quota and should be discarded
+    Discard,
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    capacity: u64,
+    refill_per_ms: u64,
+    units: u64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(quota: RateQuota, now: Instant) -> Self {
+        let capacity = u64::from(quota.burst.max(1)) * UNITS_PER_TOKEN;
+        Self {
+            capacity,
+            refill_per_ms: u64::from(quota.messages_per_minute),
+            units: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed_ms = u64::try_from(now.saturating_duration_since(self.last_refill).as_millis()).unwrap_or(u64::MAX);
+        if elapsed_ms == 0 {
+            return;
+        }
+        self.units = self
+            .units
+            .saturating_add(elapsed_ms.saturating_mul(self.refill_per_ms))
+            .min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Returns the time until a token is available, or None if the bucket never refills
+    fn time_until_available(&self) -> Option<Duration> {
+        let deficit = UNITS_PER_TOKEN.saturating_sub(self.units);
+        if deficit == 0 {
+            return Some(Duration::ZERO);
+        }
+        if self.refill_per_ms == 0 {
+            return None;
+        }
+        Some(Duration::from_millis(
+            (deficit + self.refill_per_ms - 1) / self.refill_per_ms,
+        ))
+    }
+
+    /// Takes a token from the bucket. If a token is not yet available, the token that becomes available next is
+    /// reserved by moving the refill time forward, so that throttled messages are spaced out.
+    fn take(&mut self) {
+        if self.units >= UNITS_PER_TOKEN {
+            self.units -= UNITS_PER_TOKEN;
+            return;
+        }
+        if let Some(wait) = self.time_until_available() {
+            self.last_refill += wait;
+            self.units = 0;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PeerBuckets {
+    all: TokenBucket,
+    join: TokenBucket,
+    discovery: TokenBucket,
+    saf_request: TokenBucket,
+    last_seen: Instant,
+}
+
+impl PeerBuckets {
+    fn new(config: &DhtRateLimitConfig, now: Instant) -> Self {
+        Self {
+            all: TokenBucket::new(config.peer_quota, now),
+            join: TokenBucket::new(config.join_quota, now),
+            discovery: TokenBucket::new(config.discovery_quota, now),
+            saf_request: TokenBucket::new(config.saf_request_quota, now),
+            last_seen: now,
+        }
+    }
+
+    fn message_type_bucket_mut(&mut self, message_type: DhtMessageType) -> Option<&mut TokenBucket> {
+        match message_type {
+            DhtMessageType::Join => Some(&mut self.join),
+            DhtMessageType::Discovery => Some(&mut self.discovery),
+            DhtMessageType::SafRequestMessages => Some(&mut self.saf_request),
+            _ => None,
+        }
+    }
+
+    fn check(&mut self, message_type: DhtMessageType, now: Instant) -> RateLimitDecision {
+        self.last_seen = now;
+        self.all.refill(now);
+        let mut wait = match self.all.time_until_available() {
+            Some(wait) => wait,
+            None => return RateLimitDecision::Discard,
+        };
+
+        if let Some(bucket) = self.message_type_bucket_mut(message_type) {
+            bucket.refill(now);
+            match bucket.time_until_available() {
+                Some(type_wait) => wait = wait.max(type_wait),
+                None => return RateLimitDecision::Discard,
+            }
+        }
+
+        if wait > MAX_THROTTLE_DELAY {
+            return RateLimitDecision::Discard;
+        }
+
+        self.all.take();
+        if let Some(bucket) = self.message_type_bucket_mut(message_type) {
+            bucket.take();
+        }
+
+        if wait.is_zero() {
+            RateLimitDecision::Allow
+        } else {
+            RateLimitDecision::Throttle(wait)
+        }
+    }
+}
+
+/// Tracks the token buckets of each peer
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: DhtRateLimitConfig,
+    peers: HashMap<NodeId, PeerBuckets>,
+    num_throttled: u64,
+    num_discarded: u64,
+}
+
+impl RateLimiter {
+    pub fn new(config: DhtRateLimitConfig) -> Self {
+        Self {
+            config,
+            peers: HashMap::new(),
+            num_throttled: 0,
+            num_discarded: 0,
+        }
+    }
+
+    /// Checks an inbound message of the given type from the given peer against its quotas, consuming a token if the
+    /// message is allowed or throttled.
+    pub fn check(&mut self, node_id: &NodeId, message_type: DhtMessageType, now: Instant) -> RateLimitDecision {
+        if !self.peers.contains_key(node_id) {
+            self.make_room(now);
+        }
+        let config = &self.config;
+        let decision = self
+            .peers
+            .entry(node_id.clone())
+            .or_insert_with(|| PeerBuckets::new(config, now))
+            .check(message_type, now);
+        match decision {
+            RateLimitDecision::Allow => {},
+            RateLimitDecision::Throttle(_) => self.num_throttled += 1,
+            RateLimitDecision::Discard => self.num_discarded += 1,
+        }
+        decision
+    }
+
+    /// The number of messages that have been delayed
+    pub fn num_throttled(&self) -> u64 {
+        self.num_throttled
+    }
+
+    /// The number of messages that have been discarded
+    pub fn num_discarded(&self) -> u64 {
+        self.num_discarded
+    }
+
+    /// The number of peers currently tracked by the limiter
+    pub fn num_tracked_peers(&self) -> usize {
+        self.peers.len()
+    }
+
+    fn make_room(&mut self, now: Instant) {
+        if self.peers.len() < self.config.max_tracked_peers {
+            return;
+        }
+        self.peers
+            .retain(|_, buckets| now.saturating_duration_since(buckets.last_seen) < PEER_IDLE_TIMEOUT);
+        if self.peers.len() < self.config.max_tracked_peers {
+            return;
+        }
+        // All tracked peers are active, evict the peer that was seen least recently
+        let oldest = self
+            .peers
+            .iter()
+            .min_by_key(|(_, buckets)| buckets.last_seen)
+            .map(|(node_id, _)| node_id.clone());
+        if let Some(node_id) = oldest {
+            self.peers.remove(&node_id);
+        }
+    }
+}
+
+/// # DHT rate limiting middleware
+///
+/// Takes in a `DhtInboundMessage` and checks it against the per-peer and per-message-type quotas of the source peer.
+/// Messages that are over quota are delayed or discarded.
+#[derive(Clone)]
+pub struct RateLimitMiddleware<S> {
+    next_service: S,
+    limiter: Option<Arc<Mutex<RateLimiter>>>,
+}
+
+impl<S> RateLimitMiddleware<S> {
+    pub fn new(service: S, limiter: Option<Arc<Mutex<RateLimiter>>>) -> Self {
+        Self {
+            next_service: service,
+            limiter,
+        }
+    }
+}
+
+impl<S> Service<DhtInboundMessage> for RateLimitMiddleware<S>
+where
+    S: Service<DhtInboundMessage, Response = (), Error = PipelineError> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Error = PipelineError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = ();
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, message: DhtInboundMessage) -> Self::Future {
+        let next_service = self.next_service.clone();
+        let decision = match self.limiter {
+            Some(ref limiter) => limiter.lock().expect("rate limiter lock poisoned").check(
+                &message.source_peer.node_id,
+                message.dht_header.message_type,
+                Instant::now(),
+            ),
+            None => RateLimitDecision::Allow,
+        };
+
+        Box::pin(async move {
+            match decision {
+                RateLimitDecision::Allow => {},
+                RateLimitDecision::Throttle(delay) => {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Throttling {} message {} from peer '{}' for {:.0?} (Trace: {})",
+                        message.dht_header.message_type,
+                        message.tag,
+                        message.source_peer.node_id.short_str(),
+                        delay,
+                        message.dht_header.message_tag
+                    );
+                    tokio::time::sleep(delay).await;
+                },
+                RateLimitDecision::Discard => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Peer '{}' exceeded its {} message quota. Message {} discarded (Trace: {})",
+                        message.source_peer.node_id.short_str(),
+                        message.dht_header.message_type,
+                        message.tag,
+                        message.dht_header.message_tag
+                    );
+                    return Ok(());
+                },
+            }
+            next_service.oneshot(message).await
+        })
+    }
+}
+
+pub struct RateLimitLayer {
+    limiter: Option<Arc<Mutex<RateLimiter>>>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: DhtRateLimitConfig) -> Self {
+        let limiter = if config.enabled {
+            Some(Arc::new(Mutex::new(RateLimiter::new(config))))
+        } else {
+            None
+        };
+        Self { limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitMiddleware<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        RateLimitMiddleware::new(service, self.limiter.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        envelope::DhtMessageFlags,
+        test_utils::{make_dht_inbound_message, make_node_identity, make_peer, service_spy},
+    };
+
+    fn test_config() -> DhtRateLimitConfig {
+        DhtRateLimitConfig {
+            enabled: true,
+            peer_quota: RateQuota::new(60, 5),
+            join_quota: RateQuota::new(0, 1),
+            discovery_quota: RateQuota::new(60, 2),
+            saf_request_quota: RateQuota::new(60, 1),
+            max_tracked_peers: 2,
+        }
+    }
+
+    #[test]
+    fn it_enforces_per_peer_and_per_message_type_quotas() {
+        let mut limiter = RateLimiter::new(test_config());
+        let node_id = make_peer().node_id;
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::Join, now),
+            RateLimitDecision::Allow
+        );
+        // The join bucket never refills
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::Join, now),
+            RateLimitDecision::Discard
+        );
+        for _ in 0..2 {
+            assert_eq!(
+                limiter.check(&node_id, DhtMessageType::Discovery, now),
+                RateLimitDecision::Allow
+            );
+        }
+        // Discovery burst exhausted, the next token is a second away which is too long to wait
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::Discovery, now),
+            RateLimitDecision::Discard
+        );
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::None, now),
+            RateLimitDecision::Allow
+        );
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::None, now),
+            RateLimitDecision::Allow
+        );
+        // Peer burst of 5 exhausted
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::None, now),
+            RateLimitDecision::Discard
+        );
+        // Within the max throttle delay of the next token
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::None, now + Duration::from_millis(600)),
+            RateLimitDecision::Throttle(Duration::from_millis(400))
+        );
+        assert_eq!(
+            limiter.check(&node_id, DhtMessageType::None, now + Duration::from_secs(3)),
+            RateLimitDecision::Allow
+        );
+        assert_eq!(limiter.num_throttled(), 1);
+        assert_eq!(limiter.num_discarded(), 3);
+
+        // Other peers have their own quotas
+        let other = make_peer().node_id;
+        assert_eq!(
+            limiter.check(&other, DhtMessageType::Join, now),
+            RateLimitDecision::Allow
+        );
+    }
+
+    #[test]
+    fn it_limits_the_number_of_tracked_peers() {
+        let mut limiter = RateLimiter::new(test_config());
+        let now = Instant::now();
+        let peers = (0..3).map(|_| make_peer().node_id).collect::<Vec<_>>();
+        for (i, node_id) in peers.iter().enumerate() {
+            limiter.check(node_id, DhtMessageType::None, now + Duration::from_secs(i as u64));
+        }
+        assert_eq!(limiter.num_tracked_peers(), 2);
+        assert!(!limiter.peers.contains_key(&peers[0]));
+    }
+
+    #[tokio::test]
+    async fn it_discards_messages_over_quota() {
+        let spy = service_spy();
+        let mut service = RateLimitLayer::new(test_config()).layer(spy.to_service::<PipelineError>());
+        let node_identity = make_node_identity();
+        let mut msg = make_dht_inbound_message(
+            &node_identity,
+            &b"test".to_vec(),
+            DhtMessageFlags::empty(),
+            false,
+            false,
+        )
+        .unwrap();
+        msg.dht_header.message_type = DhtMessageType::Join;
+
+        service.call(msg.clone()).await.unwrap();
+        service.call(msg).await.unwrap();
+        assert_eq!(spy.call_count(), 1);
+    }
+}
@@ -81,7 +81,7 @@ mod connectivity;
 pub use connectivity::MetricsCollectorHandle;
 
 mod config;
-pub use config::{DhtConfig, DhtConnectivityConfig};
+pub use config::{DhtConfig, DhtConnectivityConfig, DhtRateLimitConfig, RateQuota};
 
 mod crypt;
 
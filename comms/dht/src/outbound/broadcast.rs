@@ -51,7 +51,7 @@ use crate::{
     crypt,
     dedup,
     discovery::DhtDiscoveryRequester,
-    envelope::{datetime_to_epochtime, DhtMessageFlags, DhtMessageHeader, NodeDestination},
+    envelope::{datetime_to_epochtime, DhtMessageFlags, DhtMessageHeader, NodeDestination, SafMessageClass},
     message_signature::MessageSignature,
     outbound::{
         message::{DhtOutboundMessage, OutboundEncryption, SendFailure},
@@ -265,6 +265,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
             dht_header,
             debug_info: _,
             tag,
+            saf_class,
         } = params;
 
         match self.select_peers(broadcast_strategy.clone()).await {
@@ -327,6 +328,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
                         body,
                         Some(expires),
                         tag,
+                        saf_class,
                     )
                     .await
                 {
@@ -409,6 +411,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
         body: BytesMut,
         expires: Option<DateTime<Utc>>,
         tag: Option<MessageTag>,
+        saf_class: SafMessageClass,
     ) -> Result<(Vec<DhtOutboundMessage>, Vec<MessageSendState>), DhtOutboundError> {
         let dht_flags = encryption.flags() | extra_flags;
         let expires_epochtime = expires.map(datetime_to_epochtime);
@@ -448,6 +451,7 @@ where S: Service<DhtOutboundMessage, Response = (), Error = PipelineError>
                     message_signature: message_signature.clone(),
                     is_broadcast,
                     expires: expires_epochtime.map(EpochTime::as_u64),
+                    saf_class,
                 },
                 send_state,
             )
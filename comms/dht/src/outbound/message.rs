@@ -34,7 +34,7 @@ use thiserror::Error;
 use tokio::sync::oneshot;
 
 use crate::{
-    envelope::{DhtMessageFlags, DhtMessageHeader, DhtMessageType, NodeDestination},
+    envelope::{DhtMessageFlags, DhtMessageHeader, DhtMessageType, NodeDestination, SafMessageClass},
     outbound::{message_params::FinalSendMessageParams, message_send_state::MessageSendStates},
     version::DhtProtocolVersion,
 };
@@ -175,6 +175,7 @@ pub struct DhtOutboundMessage {
     pub dht_flags: DhtMessageFlags,
     pub is_broadcast: bool,
     pub expires: Option<u64>,
+    pub saf_class: SafMessageClass,
 }
 
 impl fmt::Display for DhtOutboundMessage {
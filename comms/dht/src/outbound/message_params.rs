@@ -26,7 +26,7 @@ use tari_comms::{message::MessageTag, peer_manager::NodeId, types::CommsPublicKe
 
 use crate::{
     broadcast_strategy::{BroadcastClosestRequest, BroadcastStrategy},
-    envelope::{DhtMessageFlags, DhtMessageHeader, NodeDestination},
+    envelope::{DhtMessageFlags, DhtMessageHeader, NodeDestination, SafMessageClass},
     outbound::OutboundEncryption,
     proto::envelope::DhtMessageType,
 };
@@ -70,6 +70,7 @@ pub struct FinalSendMessageParams {
     pub dht_header: Option<DhtMessageHeader>,
     pub debug_info: Option<String>,
     pub tag: Option<MessageTag>,
+    pub saf_class: SafMessageClass,
 }
 
 impl Default for FinalSendMessageParams {
@@ -85,6 +86,7 @@ impl Default for FinalSendMessageParams {
             dht_header: None,
             debug_info: None,
             tag: None,
+            saf_class: Default::default(),
         }
     }
 }
@@ -225,6 +227,13 @@ impl SendMessageParams {
         self
     }
 
+    /// Set the store and forward class of the message. This determines how long and with which storage quota the
+    /// message is kept by store and forward nodes.
+    pub fn with_saf_class(&mut self, saf_class: SafMessageClass) -> &mut Self {
+        self.params_mut().saf_class = saf_class;
+        self
+    }
+
     /// Override the DHtHeader of a message(s) with the given header
     pub fn with_dht_header(&mut self, dht_header: DhtMessageHeader) -> &mut Self {
         self.params_mut().dht_header = Some(dht_header);
@@ -268,7 +268,7 @@ impl OutboundMessageRequester {
     /// Send a message with custom parameters
     pub async fn send_message<T>(
         &mut self,
-        params: FinalSendMessageParams,
+        mut params: FinalSendMessageParams,
         message: OutboundDomainMessage<T>,
     ) -> Result<SendMessageResponse, DhtOutboundError>
     where
@@ -282,6 +282,9 @@ impl OutboundMessageRequester {
                 message
             );
         }
+        if let Some(saf_class) = message.saf_class() {
+            params.saf_class = saf_class;
+        }
         let header = if params.broadcast_strategy.is_direct() {
             message.to_header()
         } else {
@@ -79,6 +79,7 @@ where
             message_signature,
             reply,
             expires,
+            saf_class,
             ..
         } = message;
         trace!(
@@ -96,6 +97,7 @@ where
             destination: Some(destination.into()),
             message_tag: tag.as_value(),
             expires: expires.unwrap_or_default(),
+            saf_class: saf_class.into(),
         });
         let envelope = DhtEnvelope::new(dht_header, body.into());
 
@@ -25,6 +25,8 @@ use std::time::Duration;
 use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
 
+use crate::envelope::SafMessageClass;
+
 /// Store and forward configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -33,7 +35,8 @@ pub struct SafConfig {
     /// Default: 3 hours
     #[serde(with = "serializers::seconds")]
     pub msg_validity: Duration,
-    /// The maximum number of messages that can be stored using the Store-and-forward middleware.
+    /// The maximum number of general messages that can be stored using the Store-and-forward middleware. Messages in
+    /// other classes have their own storage capacity.
     /// Default: 100,000
     pub msg_storage_capacity: usize,
     /// A request to retrieve stored messages will be ignored if the requesting node is
@@ -43,11 +46,13 @@ pub struct SafConfig {
     /// The maximum number of messages to return from a store and forward retrieval request.
     /// Default: 100
     pub max_returned_messages: usize,
-    /// The time-to-live duration used for storage of low priority messages by the Store-and-forward middleware.
+    /// The time-to-live duration used for storage of low priority general messages by the Store-and-forward
+    /// middleware.
     /// Default: 6 hours
     #[serde(with = "serializers::seconds")]
     pub low_priority_msg_storage_ttl: Duration,
-    /// The time-to-live duration used for storage of high priority messages by the Store-and-forward middleware.
+    /// The time-to-live duration used for storage of high priority general messages by the Store-and-forward
+    /// middleware.
     /// Default: 3 days
     #[serde(with = "serializers::seconds")]
     pub high_priority_msg_storage_ttl: Duration,
@@ -61,6 +66,28 @@ pub struct SafConfig {
     /// The maximum number of peer nodes that a message must be closer than to get stored by SAF
     /// Default: 8
     pub num_neighbouring_nodes: usize,
+    /// Retention of transaction negotiation messages.
+    /// Default: 3 days, 50,000 messages
+    pub transaction_tier: SafStorageTier,
+    /// Retention of chat messages.
+    /// Default: 1 day, 20,000 messages
+    pub chat_tier: SafStorageTier,
+    /// Retention of join and discovery messages.
+    /// Default: 6 hours, 10,000 messages
+    pub discovery_tier: SafStorageTier,
+}
+
+impl SafConfig {
+    /// Returns the storage tier of the given message class, or None for general messages which are retained according
+    /// to their priority.
+    pub fn storage_tier(&self, class: SafMessageClass) -> Option<&SafStorageTier> {
+        match class {
+            SafMessageClass::General => None,
+            SafMessageClass::Transaction => Some(&self.transaction_tier),
+            SafMessageClass::Chat => Some(&self.chat_tier),
+            SafMessageClass::Discovery => Some(&self.discovery_tier),
+        }
+    }
 }
 
 impl Default for SafConfig {
@@ -76,6 +103,30 @@ impl Default for SafConfig {
             max_message_size: 512 * 1024,
             max_inflight_request_age: Duration::from_secs(120),
             num_neighbouring_nodes: 8,
+            transaction_tier: SafStorageTier {
+                msg_storage_ttl: Duration::from_secs(3 * 24 * 60 * 60), // 3 days
+                msg_storage_capacity: 50_000,
+            },
+            chat_tier: SafStorageTier {
+                msg_storage_ttl: Duration::from_secs(24 * 60 * 60), // 1 day
+                msg_storage_capacity: 20_000,
+            },
+            discovery_tier: SafStorageTier {
+                msg_storage_ttl: Duration::from_secs(6 * 60 * 60), // 6 hours
+                msg_storage_capacity: 10_000,
+            },
         }
     }
 }
+
+/// The retention period and storage quota of a class of store and forward messages.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SafStorageTier {
+    /// The time-to-live duration of messages in this class
+    #[serde(with = "serializers::seconds")]
+    pub msg_storage_ttl: Duration,
+    /// The maximum number of messages in this class that can be stored. Once exceeded, the oldest messages in this
+    /// class are removed.
+    pub msg_storage_capacity: usize,
+}
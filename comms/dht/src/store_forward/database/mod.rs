@@ -21,6 +21,8 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod stored_message;
+use std::convert::TryFrom;
+
 use chrono::{DateTime, NaiveDateTime, Utc};
 use diesel::{dsl, result::DatabaseErrorKind, BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl};
 pub use stored_message::{NewStoredMessage, StoredMessage};
@@ -28,7 +30,7 @@ use tari_comms::{peer_manager::NodeId, types::CommsPublicKey};
 use tari_utilities::hex::Hex;
 
 use crate::{
-    envelope::DhtMessageType,
+    envelope::{DhtMessageType, SafMessageClass},
     schema::stored_messages,
     storage::{DbConnection, StorageError},
     store_forward::message::StoredMessagePriority,
@@ -182,17 +184,50 @@ impl StoreAndForwardDatabase {
 
     pub(crate) fn delete_messages_with_priority_older_than(
         &self,
+        class: SafMessageClass,
         priority: StoredMessagePriority,
         since: NaiveDateTime,
     ) -> Result<usize, StorageError> {
         let mut conn = self.connection.get_pooled_connection()?;
         diesel::delete(stored_messages::table)
             .filter(stored_messages::stored_at.lt(since))
+            .filter(stored_messages::message_class.eq(class as i32))
             .filter(stored_messages::priority.eq(priority as i32))
             .execute(&mut conn)
             .map_err(Into::into)
     }
 
+    pub(crate) fn delete_messages_of_class_older_than(
+        &self,
+        class: SafMessageClass,
+        since: NaiveDateTime,
+    ) -> Result<usize, StorageError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        diesel::delete(stored_messages::table)
+            .filter(stored_messages::stored_at.lt(since))
+            .filter(stored_messages::message_class.eq(class as i32))
+            .execute(&mut conn)
+            .map_err(Into::into)
+    }
+
+    /// Returns the number of stored messages in each message class
+    pub(crate) fn count_messages_by_class(&self) -> Result<Vec<(SafMessageClass, usize)>, StorageError> {
+        let mut conn = self.connection.get_pooled_connection()?;
+        let counts = stored_messages::table
+            .group_by(stored_messages::message_class)
+            .select((stored_messages::message_class, dsl::count(stored_messages::id)))
+            .load::<(i32, i64)>(&mut conn)?;
+        Ok(counts
+            .into_iter()
+            .map(|(class, count)| {
+                (
+                    SafMessageClass::from_i32(class).unwrap_or_default(),
+                    usize::try_from(count).unwrap_or(usize::MAX),
+                )
+            })
+            .collect())
+    }
+
     pub(crate) fn delete_messages_older_than(&self, since: NaiveDateTime) -> Result<usize, StorageError> {
         let mut conn = self.connection.get_pooled_connection()?;
         diesel::delete(stored_messages::table)
@@ -201,19 +236,22 @@ impl StoreAndForwardDatabase {
             .map_err(Into::into)
     }
 
-    pub(crate) fn truncate_messages(&self, max_size: usize) -> Result<usize, StorageError> {
+    /// Removes the oldest messages of the given class so that at most `max_size` messages of that class remain
+    pub(crate) fn truncate_messages(&self, class: SafMessageClass, max_size: usize) -> Result<usize, StorageError> {
         let mut num_removed = 0;
         let mut conn = self.connection.get_pooled_connection()?;
         let max_size = max_size as u64;
         #[allow(clippy::cast_sign_loss)]
         let msg_count = stored_messages::table
             .select(dsl::count(stored_messages::id))
+            .filter(stored_messages::message_class.eq(class as i32))
             .first::<i64>(&mut conn)? as u64;
         if msg_count > max_size {
             let remove_count = msg_count - max_size;
             #[allow(clippy::cast_possible_wrap)]
             let message_ids: Vec<i32> = stored_messages::table
                 .select(stored_messages::id)
+                .filter(stored_messages::message_class.eq(class as i32))
                 .order_by(stored_messages::stored_at.asc())
                 .limit(remove_count as i64)
                 .get_results(&mut conn)?;
@@ -295,11 +333,42 @@ mod test {
         db.insert_message_if_unique(msg2.clone()).unwrap();
         db.insert_message_if_unique(msg3.clone()).unwrap();
         db.insert_message_if_unique(msg4.clone()).unwrap();
-        let num_removed = db.truncate_messages(2).unwrap();
+        let num_removed = db.truncate_messages(SafMessageClass::General, 2).unwrap();
         assert_eq!(num_removed, 2);
         let messages = db.get_all_messages().unwrap();
         assert_eq!(messages.len(), 2);
         assert_eq!(messages[0].body_hash, msg3.body_hash);
         assert_eq!(messages[1].body_hash, msg4.body_hash);
     }
+
+    #[tokio::test]
+    async fn truncate_messages_by_class() {
+        let conn = DbConnection::connect_memory(random::string(8)).unwrap();
+        conn.migrate().unwrap();
+        let db = StoreAndForwardDatabase::new(conn);
+        let mut tx_msg = NewStoredMessage::default();
+        tx_msg.body_hash.push('0');
+        tx_msg.message_class = SafMessageClass::Transaction as i32;
+        db.insert_message_if_unique(tx_msg.clone()).unwrap();
+        for i in 1..=3 {
+            let mut msg = NewStoredMessage::default();
+            msg.body_hash = i.to_string();
+            msg.message_class = SafMessageClass::Chat as i32;
+            db.insert_message_if_unique(msg).unwrap();
+        }
+
+        let num_removed = db.truncate_messages(SafMessageClass::Chat, 1).unwrap();
+        assert_eq!(num_removed, 2);
+        let num_removed = db.truncate_messages(SafMessageClass::Transaction, 1).unwrap();
+        assert_eq!(num_removed, 0);
+
+        let mut counts = db.count_messages_by_class().unwrap();
+        counts.sort_by_key(|(class, _)| *class);
+        assert_eq!(counts, vec![
+            (SafMessageClass::Transaction, 1),
+            (SafMessageClass::Chat, 1)
+        ]);
+        let messages = db.get_all_messages().unwrap();
+        assert!(messages.iter().any(|m| m.body_hash == tx_msg.body_hash));
+    }
 }
@@ -45,6 +45,7 @@ pub struct NewStoredMessage {
     pub is_encrypted: bool,
     pub priority: i32,
     pub body_hash: String,
+    pub message_class: i32,
 }
 
 impl NewStoredMessage {
@@ -64,6 +65,7 @@ impl NewStoredMessage {
             Err(encrypted_body) => encrypted_body,
         };
         let body_hash = hex::to_hex(&dedup::create_message_hash(&dht_header.message_signature, &body));
+        let message_class = dht_header.saf_message_class();
 
         Self {
             version: dht_header.version.as_major() as i32,
@@ -82,6 +84,7 @@ impl NewStoredMessage {
             },
             body_hash,
             body,
+            message_class: message_class as i32,
         }
     }
 }
@@ -100,4 +103,5 @@ pub struct StoredMessage {
     pub priority: i32,
     pub stored_at: NaiveDateTime,
     pub body_hash: String,
+    pub message_class: i32,
 }
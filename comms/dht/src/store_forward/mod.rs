@@ -34,7 +34,7 @@ mod error;
 pub use error::StoreAndForwardError;
 
 mod config;
-pub use config::SafConfig;
+pub use config::{SafConfig, SafStorageTier};
 
 mod message;
 
@@ -43,5 +43,8 @@ pub use saf_handler::MessageHandlerLayer;
 
 mod local_state;
 
+mod stats;
+pub use stats::{SafClassStats, SafStorageStats};
+
 mod store;
 pub use store::StoreLayer;
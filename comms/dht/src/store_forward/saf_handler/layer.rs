@@ -22,7 +22,7 @@
 
 use std::sync::Arc;
 
-use tari_comms::peer_manager::NodeIdentity;
+use tari_comms::{bandwidth::BandwidthMeter, peer_manager::NodeIdentity};
 use tokio::sync::mpsc;
 use tower::layer::Layer;
 
@@ -41,6 +41,7 @@ pub struct MessageHandlerLayer {
     node_identity: Arc<NodeIdentity>,
     outbound_service: OutboundMessageRequester,
     saf_response_signal_sender: mpsc::Sender<()>,
+    bandwidth_meter: Option<BandwidthMeter>,
 }
 
 impl MessageHandlerLayer {
@@ -51,6 +52,7 @@ impl MessageHandlerLayer {
         node_identity: Arc<NodeIdentity>,
         outbound_service: OutboundMessageRequester,
         saf_response_signal_sender: mpsc::Sender<()>,
+        bandwidth_meter: Option<BandwidthMeter>,
     ) -> Self {
         Self {
             config,
@@ -60,6 +62,7 @@ impl MessageHandlerLayer {
 
             outbound_service,
             saf_response_signal_sender,
+            bandwidth_meter,
         }
     }
 }
@@ -76,6 +79,7 @@ impl<S> Layer<S> for MessageHandlerLayer {
             Arc::clone(&self.node_identity),
             self.outbound_service.clone(),
             self.saf_response_signal_sender.clone(),
+            self.bandwidth_meter.clone(),
         )
     }
 }
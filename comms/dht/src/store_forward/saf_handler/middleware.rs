@@ -23,7 +23,7 @@
 use std::{sync::Arc, task::Poll};
 
 use futures::{future::BoxFuture, task::Context};
-use tari_comms::{peer_manager::NodeIdentity, pipeline::PipelineError};
+use tari_comms::{bandwidth::BandwidthMeter, peer_manager::NodeIdentity, pipeline::PipelineError};
 use tokio::sync::mpsc;
 use tower::Service;
 
@@ -44,6 +44,7 @@ pub struct MessageHandlerMiddleware<S> {
     node_identity: Arc<NodeIdentity>,
     outbound_service: OutboundMessageRequester,
     saf_response_signal_sender: mpsc::Sender<()>,
+    bandwidth_meter: Option<BandwidthMeter>,
 }
 
 impl<S> MessageHandlerMiddleware<S> {
@@ -55,6 +56,7 @@ impl<S> MessageHandlerMiddleware<S> {
         node_identity: Arc<NodeIdentity>,
         outbound_service: OutboundMessageRequester,
         saf_response_signal_sender: mpsc::Sender<()>,
+        bandwidth_meter: Option<BandwidthMeter>,
     ) -> Self {
         Self {
             config,
@@ -65,6 +67,7 @@ impl<S> MessageHandlerMiddleware<S> {
 
             outbound_service,
             saf_response_signal_sender,
+            bandwidth_meter,
         }
     }
 }
@@ -94,6 +97,7 @@ where
                 message,
                 self.saf_response_signal_sender.clone(),
             )
+            .with_bandwidth_meter(self.bandwidth_meter.clone())
             .run(),
         )
     }
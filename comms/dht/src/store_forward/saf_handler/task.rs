@@ -31,6 +31,7 @@ use futures::{future, stream, StreamExt};
 use log::*;
 use prost::Message;
 use tari_comms::{
+    bandwidth::{services, BandwidthMeter},
     message::{EnvelopeBody, MessageTag},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManagerError},
     pipeline::PipelineError,
@@ -78,6 +79,7 @@ pub struct MessageHandlerTask<S> {
     message: Option<DecryptedDhtMessage>,
     saf_requester: StoreAndForwardRequester,
     saf_response_signal_sender: mpsc::Sender<()>,
+    bandwidth_meter: Option<BandwidthMeter>,
 }
 
 impl<S> MessageHandlerTask<S>
@@ -102,9 +104,16 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             node_identity,
             message: Some(message),
             saf_response_signal_sender,
+            bandwidth_meter: None,
         }
     }
 
+    /// Attribute store and forward traffic to the SAF service of the given bandwidth meter and enforce its quota
+    pub fn with_bandwidth_meter(mut self, bandwidth_meter: Option<BandwidthMeter>) -> Self {
+        self.bandwidth_meter = bandwidth_meter;
+        self
+    }
+
     pub async fn run(mut self) -> Result<(), PipelineError> {
         let message = self
             .message
@@ -180,6 +189,17 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             message.source_peer.node_id,
             message.dht_header.message_tag
         );
+        if let Some(Err(err)) = self.bandwidth_meter.as_ref().map(|m| m.check_quota(services::SAF)) {
+            warn!(
+                target: LOG_TARGET,
+                "Not responding to stored message request {} from {}: {}",
+                message.tag,
+                message.source_peer.node_id.short_str(),
+                err
+            );
+            return Ok(());
+        }
+
         let msg = message
             .success()
             .expect("already checked that this message decrypted successfully");
@@ -240,6 +260,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 stored_messages.messages().len(),
                 resp_type
             );
+            let response_size = stored_messages.encoded_len() as u64;
 
             match self
                 .outbound_service
@@ -253,6 +274,9 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 .await
             {
                 Ok(_) => {
+                    if let Some(meter) = self.bandwidth_meter.as_ref() {
+                        meter.reattribute(services::DHT, services::SAF, response_size, 0);
+                    }
                     if let Some(threshold) = since {
                         debug!(
                             target: LOG_TARGET,
@@ -300,6 +324,9 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         let response = msg
             .decode_part::<StoredMessagesResponse>(0)?
             .ok_or(StoreAndForwardError::EnvelopeBodyMissingMessagePart)?;
+        if let Some(meter) = self.bandwidth_meter.as_ref() {
+            meter.reattribute(services::DHT, services::SAF, 0, response.encoded_len() as u64);
+        }
 
         if response.messages.len() > self.config.max_returned_messages {
             warn!(
@@ -760,7 +787,7 @@ mod test {
 
     use super::*;
     use crate::{
-        envelope::DhtMessageFlags,
+        envelope::{DhtMessageFlags, SafMessageClass},
         outbound::mock::create_outbound_service_mock,
         proto::envelope::DhtHeader,
         store_forward::{message::StoredMessagePriority, StoredMessage},
@@ -800,6 +827,7 @@ mod test {
             priority: StoredMessagePriority::High as i32,
             stored_at,
             body_hash: msg_hash,
+            message_class: SafMessageClass::General as i32,
         }
     }
 
@@ -41,12 +41,13 @@ use tokio::{
 use super::{
     database::{NewStoredMessage, StoreAndForwardDatabase, StoredMessage},
     message::StoredMessagePriority,
+    stats::SafStorageStats,
     SafResult,
     StoreAndForwardError,
 };
 use crate::{
     broadcast_strategy::BroadcastStrategy,
-    envelope::DhtMessageType,
+    envelope::{DhtMessageType, SafMessageClass},
     event::{DhtEvent, DhtEventSender},
     outbound::{OutboundMessageRequester, SendMessageParams},
     proto::store_forward::{stored_messages_response::SafResponseType, StoredMessagesRequest},
@@ -57,7 +58,8 @@ use crate::{
 
 const LOG_TARGET: &str = "comms::dht::storeforward::actor";
 /// The interval to initiate a database cleanup.
-/// This involves cleaning up messages which have been stored too long according to their priority
+/// This involves cleaning up messages which have been stored too long according to their class and priority, and
+/// removing the oldest messages of classes that exceed their storage capacity
 const CLEANUP_INTERVAL: Duration = Duration::from_secs(10 * 60); // 10 mins
 
 /// Query object for fetching stored messages
@@ -116,6 +118,7 @@ pub enum StoreAndForwardRequest {
     SendStoreForwardRequestToPeer(NodeId),
     SendStoreForwardRequestNeighbours,
     MarkSafResponseReceived(NodeId, oneshot::Sender<Option<Duration>>),
+    GetStorageStats(oneshot::Sender<SafResult<SafStorageStats>>),
 }
 
 /// Store and forward actor handle.
@@ -185,6 +188,16 @@ impl StoreAndForwardRequester {
         Ok(())
     }
 
+    /// Returns the storage statistics of the local SAF database by message class.
+    pub async fn get_storage_stats(&mut self) -> SafResult<SafStorageStats> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.sender
+            .send(StoreAndForwardRequest::GetStorageStats(reply_tx))
+            .await
+            .map_err(|_| StoreAndForwardError::RequesterChannelClosed)?;
+        reply_rx.await.map_err(|_| StoreAndForwardError::RequestCancelled)?
+    }
+
     /// Updates internal SAF state that a SAF response has been received, removing it from the pending list.
     pub(crate) async fn mark_saf_response_received(&mut self, peer: NodeId) -> SafResult<Option<Duration>> {
         let (reply_tx, reply_rx) = oneshot::channel();
@@ -211,6 +224,7 @@ pub struct StoreAndForwardService {
     saf_response_signal_rx: mpsc::Receiver<()>,
     event_publisher: DhtEventSender,
     local_state: SafLocalState,
+    stats: SafStorageStats,
 }
 
 impl StoreAndForwardService {
@@ -241,6 +255,7 @@ impl StoreAndForwardService {
             saf_response_signal_rx,
             event_publisher,
             local_state: Default::default(),
+            stats: Default::default(),
         }
     }
 
@@ -353,6 +368,9 @@ impl StoreAndForwardService {
             MarkSafResponseReceived(peer, reply) => {
                 let _ = reply.send(self.local_state.mark_infight_response_received(peer));
             },
+            GetStorageStats(reply) => {
+                let _result = reply.send(self.get_storage_stats());
+            },
         }
     }
 
@@ -499,31 +517,73 @@ impl StoreAndForwardService {
         Ok(messages)
     }
 
+    fn get_storage_stats(&self) -> SafResult<SafStorageStats> {
+        let mut stats = self.stats.clone();
+        for (class, num_stored) in self.database.count_messages_by_class()? {
+            stats.set_num_stored(class, num_stored);
+        }
+        Ok(stats)
+    }
+
     fn cleanup(&mut self) -> SafResult<()> {
         self.local_state
             .garbage_collect(self.config.max_inflight_request_age * 2);
 
         let num_removed = self.database.delete_messages_with_priority_older_than(
+            SafMessageClass::General,
             StoredMessagePriority::Low,
             since(self.config.low_priority_msg_storage_ttl),
         )?;
         debug!(target: LOG_TARGET, "Cleaned {} old low priority messages", num_removed);
+        self.stats.record_expired(SafMessageClass::General, num_removed);
 
         let num_removed = self.database.delete_messages_with_priority_older_than(
+            SafMessageClass::General,
             StoredMessagePriority::High,
             since(self.config.high_priority_msg_storage_ttl),
         )?;
         debug!(target: LOG_TARGET, "Cleaned {} old high priority messages", num_removed);
+        self.stats.record_expired(SafMessageClass::General, num_removed);
+
+        let num_removed = self
+            .database
+            .truncate_messages(SafMessageClass::General, self.config.msg_storage_capacity)?;
+        self.record_evicted(SafMessageClass::General, num_removed);
+
+        for class in [
+            SafMessageClass::Transaction,
+            SafMessageClass::Chat,
+            SafMessageClass::Discovery,
+        ]
+        .iter()
+        {
+            let tier = match self.config.storage_tier(*class) {
+                Some(tier) => *tier,
+                None => continue,
+            };
+            let num_removed = self
+                .database
+                .delete_messages_of_class_older_than(*class, since(tier.msg_storage_ttl))?;
+            debug!(target: LOG_TARGET, "Cleaned {} old {} messages", num_removed, class);
+            self.stats.record_expired(*class, num_removed);
+
+            let num_removed = self.database.truncate_messages(*class, tier.msg_storage_capacity)?;
+            self.record_evicted(*class, num_removed);
+        }
 
-        let num_removed = self.database.truncate_messages(self.config.msg_storage_capacity)?;
+        debug!(target: LOG_TARGET, "SAF storage: {}", self.get_storage_stats()?);
+
+        Ok(())
+    }
+
+    fn record_evicted(&mut self, class: SafMessageClass, num_removed: usize) {
         if num_removed > 0 {
             debug!(
                 target: LOG_TARGET,
-                "Storage limits exceeded, removing {} oldest messages", num_removed
+                "Storage limits exceeded for {} messages, removing {} oldest messages", class, num_removed
             );
         }
-
-        Ok(())
+        self.stats.record_evicted(class, num_removed);
     }
 
     fn publish_event(&mut self, event: DhtEvent) {
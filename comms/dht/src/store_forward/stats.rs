@@ -0,0 +1,95 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, fmt};
+
+use crate::envelope::SafMessageClass;
+
+const ALL_CLASSES: [SafMessageClass; 4] = [
+    SafMessageClass::General,
+    SafMessageClass::Transaction,
+    SafMessageClass::Chat,
+    SafMessageClass::Discovery,
+];
+
+/// Storage statistics for a class of store and forward messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SafClassStats {
+    /// The number of messages currently stored
+    pub num_stored: usize,
+    /// The number of messages removed because their retention period elapsed
+    pub num_expired: u64,
+    /// The number of messages removed because the storage capacity of the class was exceeded
+    pub num_evicted: u64,
+}
+
+/// Storage statistics of the store and forward database, by message class. Expiry and eviction counts are totals since
+/// the node started.
+#[derive(Debug, Clone, Default)]
+pub struct SafStorageStats {
+    classes: HashMap<SafMessageClass, SafClassStats>,
+}
+
+impl SafStorageStats {
+    /// Returns the statistics of the given message class
+    pub fn get(&self, class: SafMessageClass) -> SafClassStats {
+        self.classes.get(&class).copied().unwrap_or_default()
+    }
+
+    /// Returns the statistics of all message classes
+    pub fn iter(&self) -> impl Iterator<Item = (SafMessageClass, SafClassStats)> + '_ {
+        ALL_CLASSES.iter().map(move |class| (*class, self.get(*class)))
+    }
+
+    /// The total number of stored messages
+    pub fn total_stored(&self) -> usize {
+        self.classes.values().map(|stats| stats.num_stored).sum()
+    }
+
+    pub(super) fn set_num_stored(&mut self, class: SafMessageClass, num_stored: usize) {
+        self.classes.entry(class).or_default().num_stored = num_stored;
+    }
+
+    pub(super) fn record_expired(&mut self, class: SafMessageClass, num_expired: usize) {
+        self.classes.entry(class).or_default().num_expired += num_expired as u64;
+    }
+
+    pub(super) fn record_evicted(&mut self, class: SafMessageClass, num_evicted: usize) {
+        self.classes.entry(class).or_default().num_evicted += num_evicted as u64;
+    }
+}
+
+impl fmt::Display for SafStorageStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (class, stats)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(
+                f,
+                "{}: {} stored, {} expired, {} evicted",
+                class, stats.num_stored, stats.num_expired, stats.num_evicted
+            )?;
+        }
+        Ok(())
+    }
+}
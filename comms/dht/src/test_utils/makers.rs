@@ -121,6 +121,7 @@ pub fn make_dht_header(
         flags,
         message_tag: trace,
         expires: None,
+        saf_class: Default::default(),
     })
 }
 
@@ -264,5 +265,6 @@ pub fn create_outbound_message(body: &[u8]) -> DhtOutboundMessage {
         message_signature: None,
         is_broadcast: false,
         expires: None,
+        saf_class: Default::default(),
     }
 }
@@ -151,6 +151,7 @@ impl StoreAndForwardMock {
                     priority: msg.priority,
                     stored_at: Utc::now().naive_utc(),
                     body_hash: msg.body_hash,
+                    message_class: msg.message_class,
                 });
                 reply_tx.send(Ok(false)).unwrap();
             },
@@ -171,6 +172,9 @@ impl StoreAndForwardMock {
             MarkSafResponseReceived(_, reply) => {
                 let _ = reply.send(*self.state.inflight_request.read().await);
             },
+            GetStorageStats(reply) => {
+                let _result = reply.send(Ok(Default::default()));
+            },
         }
     }
 }
@@ -51,6 +51,8 @@ pub enum ScriptError {
     InvalidDigest,
     #[error("A compare opcode failed, aborting the script immediately with reason: `{0}`")]
     CompareFailed(String),
+    #[error("The script exceeded the maximum execution cost")]
+    ExecutionCostExceeded,
 }
 
 impl From<TryFromIntError> for ScriptError {
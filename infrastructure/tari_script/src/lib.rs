@@ -23,8 +23,18 @@ mod serde;
 mod stack;
 
 pub use error::ScriptError;
-pub use op_codes::{slice_to_boxed_hash, slice_to_hash, HashValue, Message, Opcode, OpcodeVersion, ScalarValue};
-pub use script::TariScript;
+pub use op_codes::{
+    slice_to_boxed_hash,
+    slice_to_hash,
+    HashValue,
+    Message,
+    Opcode,
+    OpcodeVersion,
+    ScalarValue,
+    HASH_EXECUTION_COST,
+    SIGNATURE_VERIFICATION_COST,
+};
+pub use script::{TariScript, MAX_SCRIPT_EXECUTION_COST};
 pub use script_context::ScriptContext;
 pub use stack::{ExecutionStack, StackItem};
 use tari_crypto::{
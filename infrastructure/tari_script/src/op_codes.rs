@@ -27,6 +27,11 @@ pub type HashValue = [u8; 32];
 pub type ScalarValue = [u8; 32];
 pub type Message = [u8; MESSAGE_LENGTH];
 
+/// The execution cost of hashing a stack item
+pub const HASH_EXECUTION_COST: u64 = 1;
+/// The execution cost of verifying a signature or performing a scalar multiplication
+pub const SIGNATURE_VERIFICATION_COST: u64 = 32;
+
 const PUBLIC_KEY_LENGTH: usize = 32;
 const MESSAGE_LENGTH: usize = 32;
 type MultiSigArgs = (u8, u8, Vec<RistrettoPublicKey>, Box<Message>, usize);
@@ -119,6 +124,8 @@ const OP_HASH_SHA256: u8 = 0xb1;
 const OP_HASH_SHA3: u8 = 0xb2;
 const OP_TO_RISTRETTO_POINT: u8 = 0xb3;
 const OP_CHECK_MULTI_SIG_VERIFY_AGGREGATE_PUB_KEY: u8 = 0xb4;
+const OP_CHECK_MULTI_SIG_THRESHOLD: u8 = 0xb5;
+const OP_HASH_SHA256_VERIFY: u8 = 0xb6;
 
 // Opcode constants: Miscellaneous
 const OP_RETURN: u8 = 0x60;
@@ -262,6 +269,21 @@ pub enum Opcode {
     /// Identical to CheckMultiSig, except that the aggregate of the public keys is pushed to the stack if multiple
     /// signature validation succeeds. Fails with `VerifyFailed` if any signature is invalid.
     CheckMultiSigVerifyAggregatePubKey(u8, u8, Vec<RistrettoPublicKey>, Box<Message>),
+    /// Pops the number of signatures `k` provided as an integer from the stack, followed by `k` signatures. Unlike
+    /// CheckMultiSig, the signatures do not have to be in the order of the public keys and signatures that do not
+    /// match a public key are ignored. If at least `m` of the signatures are valid for distinct public keys, push 1 to
+    /// the stack, otherwise push 0. This opcode is only permitted from `OpcodeVersion::V1`.
+    /// Fails with `ValueExceedsBounds` if `m` == 0 or if `n` == 0 or if `m` > `n` or if `n` > `MAX_MULTISIG_LIMIT`
+    /// (32) or if the number of public keys provided != `n`, or if `k` < `m` or `k` > `n`.
+    /// Fails with `StackUnderflow` if the stack has fewer than `k` + 1 items.
+    /// Fails with `InvalidInput` if `k` is not an integer.
+    /// Fails with `IncompatibleTypes` if any of the `k` items is not a signature.
+    CheckMultiSigThreshold(u8, u8, Vec<RistrettoPublicKey>, Box<Message>),
+    /// Pops the top element, hashes it with the SHA256 hash function and compares the result to the associated
+    /// 32-byte hash. Nothing is pushed to the stack. This opcode is only permitted from `OpcodeVersion::V1`.
+    /// Fails with `StackUnderflow` if the stack is empty. Fails with `IncompatibleTypes` if the stack item cannot be
+    /// hashed. Fails with `VerifyFailed` if the hash of the stack item does not match.
+    HashSha256Verify(Box<HashValue>),
     /// Pops the top element from the stack (either a scalar or a hash), parses it canonically as a Ristretto secret
     /// key if possible, computes the corresponding Ristretto public key, and pushes this value to the stack.
     /// Fails with `StackUnderflow` if the stack is empty.
@@ -324,6 +346,26 @@ impl Opcode {
             Opcode::IfThen |
             Opcode::Else |
             Opcode::EndIf => OpcodeVersion::V0,
+            Opcode::CheckMultiSigThreshold(..) | Opcode::HashSha256Verify(..) => OpcodeVersion::V1,
+        }
+    }
+
+    /// The cost of executing the opcode, in units of [HASH_EXECUTION_COST] and [SIGNATURE_VERIFICATION_COST]. This is
+    /// the worst case cost, e.g. the cost of a multisig opcode assumes that every signature is checked against every
+    /// public key it could be checked against.
+    pub fn execution_cost(&self) -> u64 {
+        match self {
+            Opcode::HashBlake256 | Opcode::HashSha256 | Opcode::HashSha3 | Opcode::HashSha256Verify(..) => {
+                HASH_EXECUTION_COST
+            },
+            Opcode::CheckSig(..) | Opcode::CheckSigVerify(..) | Opcode::ToRistrettoPoint => SIGNATURE_VERIFICATION_COST,
+            // The public keys are only iterated over once, because the signatures must be in the order of the keys
+            Opcode::CheckMultiSig(_, n, ..) |
+            Opcode::CheckMultiSigVerify(_, n, ..) |
+            Opcode::CheckMultiSigVerifyAggregatePubKey(_, n, ..) => u64::from(*n) * SIGNATURE_VERIFICATION_COST,
+            // Up to n signatures can each be checked against all n public keys
+            Opcode::CheckMultiSigThreshold(_, n, ..) => u64::from(*n) * u64::from(*n) * SIGNATURE_VERIFICATION_COST,
+            _ => 0,
         }
     }
 
@@ -432,6 +474,17 @@ impl Opcode {
                 let (m, n, keys, msg, end) = Opcode::read_multisig_args(bytes)?;
                 Ok((CheckMultiSigVerifyAggregatePubKey(m, n, keys, msg), &bytes[end..]))
             },
+            OP_CHECK_MULTI_SIG_THRESHOLD => {
+                let (m, n, keys, msg, end) = Opcode::read_multisig_args(bytes)?;
+                Ok((CheckMultiSigThreshold(m, n, keys, msg), &bytes[end..]))
+            },
+            OP_HASH_SHA256_VERIFY => {
+                if bytes.len() < 33 {
+                    return Err(ScriptError::InvalidData);
+                }
+                let hash = slice_to_boxed_hash(&bytes[1..33]);
+                Ok((HashSha256Verify(hash), &bytes[33..]))
+            },
             OP_TO_RISTRETTO_POINT => Ok((ToRistrettoPoint, &bytes[1..])),
             OP_RETURN => Ok((Return, &bytes[1..])),
             OP_IF_THEN => Ok((IfThen, &bytes[1..])),
@@ -548,6 +601,17 @@ impl Opcode {
                 }
                 array.extend_from_slice(msg.deref());
             },
+            CheckMultiSigThreshold(m, n, public_keys, msg) => {
+                array.extend_from_slice(&[OP_CHECK_MULTI_SIG_THRESHOLD, *m, *n]);
+                for public_key in public_keys {
+                    array.extend(public_key.as_bytes());
+                }
+                array.extend_from_slice(msg.deref());
+            },
+            HashSha256Verify(h) => {
+                array.push(OP_HASH_SHA256_VERIFY);
+                array.extend_from_slice(h.deref());
+            },
             ToRistrettoPoint => array.push(OP_TO_RISTRETTO_POINT),
             Return => array.push(OP_RETURN),
             IfThen => array.push(OP_IF_THEN),
@@ -625,6 +689,18 @@ impl fmt::Display for Opcode {
                     (*msg).to_hex()
                 )
             },
+            CheckMultiSigThreshold(m, n, public_keys, msg) => {
+                let keys: Vec<String> = public_keys.iter().map(|p| p.to_hex()).collect();
+                write!(
+                    fmt,
+                    "CheckMultiSigThreshold({}, {}, [{}], {})",
+                    *m,
+                    *n,
+                    keys.join(", "),
+                    (*msg).to_hex()
+                )
+            },
+            HashSha256Verify(h) => write!(fmt, "HashSha256Verify({})", (*h).to_hex()),
             ToRistrettoPoint => write!(fmt, "ToRistrettoPoint"),
             Return => write!(fmt, "Return"),
             IfThen => write!(fmt, "IfThen"),
@@ -638,6 +714,7 @@ impl fmt::Display for Opcode {
 #[repr(u8)]
 pub enum OpcodeVersion {
     V0 = 0,
+    V1 = 1,
 }
 
 #[cfg(test)]
@@ -673,6 +750,39 @@ mod test {
         assert!(b.is_empty());
     }
 
+    #[test]
+    fn hash_sha256_verify() {
+        assert!(matches!(Opcode::read_next(b"\xb6short"), Err(ScriptError::InvalidData)));
+        let (code, b) = Opcode::read_next(b"\xb6/thirty-two~character~hash~val./\x01").unwrap();
+        assert!(matches!(&code, Opcode::HashSha256Verify(v) if &**v == b"/thirty-two~character~hash~val./"));
+        assert_eq!(b, &[1]);
+        let mut arr = vec![];
+        code.to_bytes(&mut arr);
+        assert_eq!(arr.as_slice(), b"\xb6/thirty-two~character~hash~val./");
+        assert_eq!(
+            code.to_string(),
+            "HashSha256Verify(2f7468697274792d74776f7e6368617261637465727e686173687e76616c2e2f)"
+        );
+    }
+
+    #[test]
+    fn opcode_versions() {
+        assert_eq!(
+            Opcode::CheckMultiSig(1, 1, vec![], Box::default()).get_version(),
+            OpcodeVersion::V0
+        );
+        assert_eq!(Opcode::HashSha256.get_version(), OpcodeVersion::V0);
+        assert_eq!(
+            Opcode::CheckMultiSigThreshold(1, 1, vec![], Box::default()).get_version(),
+            OpcodeVersion::V1
+        );
+        assert_eq!(
+            Opcode::HashSha256Verify(Box::default()).get_version(),
+            OpcodeVersion::V1
+        );
+        assert!(OpcodeVersion::V0 < OpcodeVersion::V1);
+    }
+
     #[test]
     fn check_height() {
         fn test_check_height(op: &Opcode, val: u8, display: &str) {
@@ -873,6 +983,13 @@ mod test {
              56e9f018b138ba843521b3243a29d81730c3a4c25108b108b1ca47c2132db569], \
              6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401)",
         );
+        test_checkmultisig(
+            &Opcode::CheckMultiSigThreshold(1, 2, keys.clone(), Box::new(*msg)),
+            OP_CHECK_MULTI_SIG_THRESHOLD,
+            "CheckMultiSigThreshold(1, 2, [9c8bc5f90d221191748e8dd7686f09e1114b4bada4c367ed58ae199c51eb100b, \
+             56e9f018b138ba843521b3243a29d81730c3a4c25108b108b1ca47c2132db569], \
+             6c9cb4d3e57351462122310fa22c90b1e6dfb528d64615363d1261a75da3e401)",
+        );
         test_checkmultisig(
             &Opcode::CheckMultiSigVerifyAggregatePubKey(1, 2, keys, Box::new(*msg)),
             OP_CHECK_MULTI_SIG_VERIFY_AGGREGATE_PUB_KEY,
@@ -34,7 +34,7 @@ use tari_utilities::{
 };
 
 use crate::{
-    op_codes::Message,
+    op_codes::{Message, SIGNATURE_VERIFICATION_COST},
     slice_to_hash,
     CheckSigSchnorrSignature,
     ExecutionStack,
@@ -57,6 +57,9 @@ macro_rules! script {
 
 const MAX_MULTISIG_LIMIT: u8 = 32;
 const MAX_SCRIPT_BYTES: usize = 4096;
+/// The maximum accumulated [Opcode::execution_cost] of the opcodes executed by a script. This is chosen so that no
+/// script of at most `MAX_SCRIPT_BYTES` consisting of `OpcodeVersion::V0` opcodes can exceed it.
+pub const MAX_SCRIPT_EXECUTION_COST: u64 = MAX_SCRIPT_BYTES as u64 * SIGNATURE_VERIFICATION_COST;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TariScript {
@@ -146,6 +149,7 @@ impl TariScript {
 
         for opcode in &self.script {
             if self.should_execute(opcode, &state)? {
+                state.charge(opcode.execution_cost())?;
                 self.execute_opcode(opcode, &mut stack, context, &mut state)?
             } else {
                 continue;
@@ -167,6 +171,14 @@ impl TariScript {
         }
     }
 
+    /// Returns the worst case execution cost of the script, assuming every opcode is executed. See
+    /// [Opcode::execution_cost].
+    pub fn execution_cost(&self) -> u64 {
+        self.script
+            .iter()
+            .fold(0u64, |cost, op| cost.saturating_add(op.execution_cost()))
+    }
+
     /// Returns the number of script op codes
     pub fn size(&self) -> usize {
         self.script.len()
@@ -322,6 +334,14 @@ impl TariScript {
                     Err(ScriptError::VerifyFailed)
                 }
             },
+            CheckMultiSigThreshold(m, n, public_keys, msg) => {
+                if self.check_multisig_threshold(stack, *m, *n, public_keys, *msg.deref())? {
+                    stack.push(Number(1))
+                } else {
+                    stack.push(Number(0))
+                }
+            },
+            HashSha256Verify(h) => TariScript::handle_hash_verify::<Sha256>(stack, h),
             ToRistrettoPoint => self.handle_to_ristretto_point(stack),
             Return => Err(ScriptError::Return),
             IfThen => TariScript::handle_if_then(stack, state),
@@ -515,6 +535,15 @@ impl TariScript {
         stack.push(Hash(hash_value))
     }
 
+    /// Hashes the top stack item and compares it to the expected hash, without pushing anything to the stack.
+    fn handle_hash_verify<D: Digest>(stack: &mut ExecutionStack, expected: &HashValue) -> Result<(), ScriptError> {
+        TariScript::handle_hash::<D>(stack)?;
+        match stack.pop() {
+            Some(StackItem::Hash(h)) if h == *expected => Ok(()),
+            _ => Err(ScriptError::VerifyFailed),
+        }
+    }
+
     fn handle_dup(stack: &mut ExecutionStack) -> Result<(), ScriptError> {
         let last = if let Some(last) = stack.peek() {
             last.clone()
@@ -656,6 +685,65 @@ impl TariScript {
         }
     }
 
+    /// Validates an m-of-n threshold multisig script
+    ///
+    /// The number of provided signatures, _k_, is popped from the stack first, followed by _k_ signatures. The
+    /// validation succeeds (`Ok(true)`) if at least _m_ of the signatures are valid signatures for distinct entries in
+    /// the public key list, and `Ok(false)` otherwise.
+    ///
+    /// Notes:
+    /// * Unlike `check_multisig`, the signatures may be in any order and signatures that do not match any of the public
+    ///   keys are ignored.
+    /// * The list may contain duplicate keys, but each occurrence of a public key may be used AT MOST once.
+    /// * The same signature is only counted once.
+    /// * _m_ and _n_ must be positive AND m <= n AND n <= MAX_MULTISIG_LIMIT (32) AND m <= k <= n.
+    fn check_multisig_threshold(
+        &self,
+        stack: &mut ExecutionStack,
+        m: u8,
+        n: u8,
+        public_keys: &[RistrettoPublicKey],
+        message: Message,
+    ) -> Result<bool, ScriptError> {
+        if m == 0 || n == 0 || m > n || n > MAX_MULTISIG_LIMIT || public_keys.len() != n as usize {
+            return Err(ScriptError::ValueExceedsBounds);
+        }
+        let k = stack.pop_into_number::<u8>()?;
+        if k < m || k > n {
+            return Err(ScriptError::ValueExceedsBounds);
+        }
+        let signatures = stack
+            .pop_num_items(k as usize)?
+            .into_iter()
+            .map(|item| match item {
+                StackItem::Signature(s) => Ok(s),
+                _ => Err(ScriptError::IncompatibleTypes),
+            })
+            .collect::<Result<Vec<CheckSigSchnorrSignature>, ScriptError>>()?;
+
+        #[allow(clippy::mutable_key_type)]
+        let mut sig_set = HashSet::new();
+        let mut used_keys = vec![false; public_keys.len()];
+        let mut valid = 0usize;
+
+        for s in &signatures {
+            if !sig_set.insert(s) {
+                continue;
+            }
+            let matched = public_keys
+                .iter()
+                .enumerate()
+                .find(|(i, pk)| !used_keys[*i] && s.verify(pk, message))
+                .map(|(i, _)| i);
+            if let Some(i) = matched {
+                used_keys[i] = true;
+                valid += 1;
+            }
+        }
+
+        Ok(valid >= m as usize)
+    }
+
     fn handle_to_ristretto_point(&self, stack: &mut ExecutionStack) -> Result<(), ScriptError> {
         let item = stack.pop().ok_or(ScriptError::StackUnderflow)?;
         let scalar = match &item {
@@ -713,6 +801,18 @@ struct IfState {
 struct ExecutionState {
     executing: bool,
     if_stack: Vec<IfState>,
+    cost: u64,
+}
+
+impl ExecutionState {
+    /// Adds the cost of an opcode to the accumulated execution cost, failing if the maximum is exceeded.
+    fn charge(&mut self, cost: u64) -> Result<(), ScriptError> {
+        self.cost = self.cost.saturating_add(cost);
+        if self.cost > MAX_SCRIPT_EXECUTION_COST {
+            return Err(ScriptError::ExecutionCostExceeded);
+        }
+        Ok(())
+    }
 }
 
 impl Default for ExecutionState {
@@ -720,6 +820,7 @@ impl Default for ExecutionState {
         Self {
             executing: true,
             if_stack: Vec::new(),
+            cost: 0,
         }
     }
 }
@@ -1748,6 +1849,172 @@ mod test {
         assert!(matches!(script.execute(&inputs), Err(ScriptError::InvalidInput)));
     }
 
+    #[test]
+    fn check_multisig_threshold() {
+        use crate::{
+            op_codes::Opcode::CheckMultiSigThreshold,
+            StackItem::{PublicKey, Signature},
+        };
+        let mut rng = rand::thread_rng();
+        let (k_alice, p_alice) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (k_bob, p_bob) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (k_eve, _) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (k_carol, p_carol) = RistrettoPublicKey::random_keypair(&mut rng);
+        let m = RistrettoSecretKey::random(&mut rng);
+        let s_alice = CheckSigSchnorrSignature::sign(&k_alice, m.as_bytes(), &mut rng).unwrap();
+        let s_bob = CheckSigSchnorrSignature::sign(&k_bob, m.as_bytes(), &mut rng).unwrap();
+        let s_eve = CheckSigSchnorrSignature::sign(&k_eve, m.as_bytes(), &mut rng).unwrap();
+        let s_carol = CheckSigSchnorrSignature::sign(&k_carol, m.as_bytes(), &mut rng).unwrap();
+        let s_alice2 = CheckSigSchnorrSignature::sign(&k_alice, m.as_bytes(), &mut rng).unwrap();
+        let msg = slice_to_boxed_message(m.as_bytes());
+
+        // 2 of 3
+        let keys = vec![p_alice.clone(), p_bob.clone(), p_carol.clone()];
+        let script = TariScript::new(vec![CheckMultiSigThreshold(2, 3, keys.clone(), msg.clone())]);
+
+        // Signatures do not have to be in the order of the public keys
+        let inputs = inputs!(s_bob.clone(), s_alice.clone(), 2);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(1));
+        let inputs = inputs!(s_carol.clone(), s_alice.clone(), 2);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(1));
+        // Invalid signatures are ignored as long as the threshold is met
+        let inputs = inputs!(s_alice.clone(), s_eve.clone(), s_carol.clone(), 3);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(1));
+        let inputs = inputs!(s_alice.clone(), s_eve.clone(), 2);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(0));
+        // The same signature is only counted once
+        let inputs = inputs!(s_alice.clone(), s_alice.clone(), 2);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(0));
+        // Two signatures for the same key only count once
+        let inputs = inputs!(s_alice.clone(), s_alice2.clone(), 2);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(0));
+
+        // k must be in [m, n]
+        let inputs = inputs!(s_alice.clone(), 1);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ValueExceedsBounds));
+        let inputs = inputs!(s_alice.clone(), s_bob.clone(), s_carol.clone(), s_eve.clone(), 4);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ValueExceedsBounds));
+        let inputs = inputs!(s_alice.clone(), -2);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ValueExceedsBounds));
+        // k must be a number
+        let inputs = inputs!(s_alice.clone(), s_bob.clone());
+        assert_eq!(script.execute(&inputs), Err(ScriptError::InvalidInput));
+        // There must be k items, all of which are signatures
+        let inputs = inputs!(s_alice.clone(), 2);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::StackUnderflow));
+        let inputs = ExecutionStack::new(vec![PublicKey(p_bob.clone()), Signature(s_alice.clone()), Number(2)]);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::IncompatibleTypes));
+
+        // Invalid m and n parameters
+        let inputs = inputs!(s_alice.clone(), s_bob.clone(), 2);
+        let script = TariScript::new(vec![CheckMultiSigThreshold(0, 3, keys.clone(), msg.clone())]);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ValueExceedsBounds));
+        let script = TariScript::new(vec![CheckMultiSigThreshold(3, 2, keys.clone(), msg.clone())]);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ValueExceedsBounds));
+        let script = TariScript::new(vec![CheckMultiSigThreshold(2, 2, keys, msg.clone())]);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ValueExceedsBounds));
+
+        // Duplicate keys may each be used once
+        let keys = vec![p_alice.clone(), p_alice, p_bob];
+        let script = TariScript::new(vec![CheckMultiSigThreshold(2, 3, keys, msg)]);
+        let inputs = inputs!(s_alice2, s_alice, 2);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(1));
+    }
+
+    #[test]
+    fn hash_sha256_verify() {
+        let preimage = [7u8; 32];
+        let hash = slice_to_boxed_hash(Sha256::digest(preimage).as_slice());
+        let script = script!(HashSha256Verify(hash) PushOne);
+
+        let inputs = ExecutionStack::new(vec![Hash(preimage)]);
+        assert_eq!(script.execute(&inputs).unwrap(), Number(1));
+
+        let inputs = ExecutionStack::new(vec![Hash([8u8; 32])]);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::VerifyFailed));
+
+        let inputs = ExecutionStack::new(vec![]);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::StackUnderflow));
+
+        let inputs = inputs!(1);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::IncompatibleTypes));
+    }
+
+    #[test]
+    fn hash_time_locked_contract_example() {
+        use crate::StackItem::PublicKey;
+        let mut rng = rand::thread_rng();
+        let (_, p_alice) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_, p_bob) = RistrettoPublicKey::random_keypair(&mut rng);
+        let preimage = [42u8; 32];
+        let hash = slice_to_boxed_hash(Sha256::digest(preimage).as_slice());
+
+        // Alice can claim the output by revealing the preimage; Bob can reclaim it after the timeout
+        let script = script!(
+            IfThen HashSha256Verify(hash) PushPubKey(Box::new(p_alice.clone()))
+            Else CheckHeightVerify(100) PushPubKey(Box::new(p_bob.clone())) EndIf
+        );
+
+        let ctx = context_with_height(50);
+        let inputs = ExecutionStack::new(vec![Hash(preimage), Number(1)]);
+        assert_eq!(
+            script.execute_with_context(&inputs, &ctx).unwrap(),
+            PublicKey(p_alice.clone())
+        );
+        let inputs = ExecutionStack::new(vec![Hash([0u8; 32]), Number(1)]);
+        assert_eq!(
+            script.execute_with_context(&inputs, &ctx),
+            Err(ScriptError::VerifyFailed)
+        );
+        let inputs = inputs!(0);
+        assert_eq!(
+            script.execute_with_context(&inputs, &ctx),
+            Err(ScriptError::VerifyFailed)
+        );
+
+        let ctx = context_with_height(100);
+        let inputs = inputs!(0);
+        assert_eq!(script.execute_with_context(&inputs, &ctx).unwrap(), PublicKey(p_bob));
+        let inputs = ExecutionStack::new(vec![Hash(preimage), Number(1)]);
+        assert_eq!(script.execute_with_context(&inputs, &ctx).unwrap(), PublicKey(p_alice));
+    }
+
+    #[test]
+    fn execution_cost() {
+        use std::convert::TryFrom;
+
+        use crate::{
+            op_codes::{HASH_EXECUTION_COST, SIGNATURE_VERIFICATION_COST},
+            script::MAX_SCRIPT_EXECUTION_COST,
+            Opcode::{CheckMultiSig, CheckMultiSigThreshold, HashBlake256, PushOne},
+        };
+        let mut rng = rand::thread_rng();
+        let keys = (0..3)
+            .map(|_| RistrettoPublicKey::random_keypair(&mut rng).1)
+            .collect::<Vec<_>>();
+        let msg = slice_to_boxed_message(&[0u8; 32]);
+        let script = TariScript::new(vec![
+            PushOne,
+            HashBlake256,
+            CheckMultiSig(2, 3, keys.clone(), msg.clone()),
+            CheckMultiSigThreshold(2, 3, keys, msg),
+        ]);
+        assert_eq!(
+            script.execution_cost(),
+            HASH_EXECUTION_COST + 3 * SIGNATURE_VERIFICATION_COST + 9 * SIGNATURE_VERIFICATION_COST
+        );
+
+        // The script fails as soon as the maximum execution cost is exceeded
+        let n = usize::try_from(MAX_SCRIPT_EXECUTION_COST / HASH_EXECUTION_COST).unwrap();
+        let inputs = ExecutionStack::new(vec![Hash([0u8; 32])]);
+        let mut ops = vec![HashBlake256; n];
+        let script = TariScript::new(ops.clone());
+        assert!(script.execute(&inputs).is_ok());
+        ops.push(HashBlake256);
+        let script = TariScript::new(ops);
+        assert_eq!(script.execute(&inputs), Err(ScriptError::ExecutionCostExceeded));
+    }
+
     #[test]
     fn test_borsh_de_serialization() {
         let hex_script = "71b07aae2337ce44f9ebb6169c863ec168046cb35ab4ef7aa9ed4f5f1f669bb74b09e58170ac276657a418820f34036b20ea615302b373c70ac8feab8d30681a3e0f0960e708";
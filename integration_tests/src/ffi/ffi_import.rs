@@ -424,12 +424,14 @@ extern "C" {
         commitments: *mut TariVector,
         number_of_splits: usize,
         fee_per_gram: u64,
+        account: *const c_char,
         error_ptr: *mut i32,
     ) -> u64;
     pub fn wallet_coin_join(
         wallet: *mut TariWallet,
         commitments: *mut TariVector,
         fee_per_gram: u64,
+        account: *const c_char,
         error_ptr: *mut i32,
     ) -> u64;
     pub fn wallet_preview_coin_join(
@@ -474,6 +476,7 @@ extern "C" {
         fee_per_gram: c_ulonglong,
         message: *const c_char,
         one_sided: bool,
+        account: *const c_char,
         error_out: *mut c_int,
     ) -> c_ulonglong;
     pub fn wallet_get_fee_estimate(
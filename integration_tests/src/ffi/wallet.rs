@@ -22,7 +22,7 @@
 
 use std::{
     ffi::CString,
-    ptr::null_mut,
+    ptr::{null, null_mut},
     sync::{Arc, Mutex},
 };
 
@@ -339,6 +339,7 @@ impl Wallet {
                 fee_per_gram,
                 CString::new(message).unwrap().into_raw(),
                 one_sided,
+                null(),
                 &mut error,
             );
             if error > 0 {
@@ -273,6 +273,7 @@ async fn create_block_template_with_coinbase(
             pow_algo: PowAlgos::Sha3x.into(),
         }),
         max_weight: weight,
+        template_version: 0,
     };
 
     let template_response = base_client
@@ -591,7 +591,10 @@ async fn stop_node(world: &mut TariWorld, node: String) {
 async fn node_lists_heights(world: &mut TariWorld, node: String, start: u64, end: u64) {
     let mut node_client = world.get_node_client(&node).await.unwrap();
     let heights = (start..=end).collect::<Vec<_>>();
-    let blocks_req = GetBlocksRequest { heights };
+    let blocks_req = GetBlocksRequest {
+        heights,
+        ..Default::default()
+    };
     let mut blocks_stream = node_client.get_blocks(blocks_req).await.unwrap().into_inner();
 
     let mut height = start;
@@ -616,6 +619,7 @@ async fn node_lists_headers_with_correct_heights(world: &mut TariWorld, node: St
         from_height: start,
         num_headers: end - start + 1,
         sorting: 1,
+        ..Default::default()
     };
     let mut headers_stream = node_client.list_headers(list_headers_req).await.unwrap().into_inner();
 
@@ -742,6 +746,7 @@ async fn generate_block_with_2_coinbases(world: &mut TariWorld, node: String) {
             pow_algo: PowAlgos::Sha3x.into(),
         }),
         max_weight: 0,
+        template_version: 0,
     };
 
     let template_response = client.get_new_block_template(template_req).await.unwrap().into_inner();
@@ -807,6 +812,7 @@ async fn generate_block_with_2_as_single_request_coinbases(world: &mut TariWorld
             pow_algo: PowAlgos::Sha3x.into(),
         }),
         max_weight: 0,
+        template_version: 0,
         coinbases: vec![
             NewBlockCoinbase {
                 address: TariAddress::from_hex("30a815df7b8d7f653ce3252f08a21d570b1ac44958cb4d7af0e0ef124f89b11943")
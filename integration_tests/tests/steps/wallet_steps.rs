@@ -110,7 +110,10 @@ async fn wait_for_wallet_to_have_micro_tari(world: &mut TariWorld, wallet: Strin
     for _ in 0..=num_retries {
         let _result = client.validate_all_transactions(ValidateRequest {}).await;
         curr_amount = client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest {
+                account: String::new(),
+                ..Default::default()
+            })
             .await
             .unwrap()
             .into_inner()
@@ -157,7 +160,7 @@ async fn wallet_detects_all_txs_as_mined_status(world: &mut TariWorld, wallet_na
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
 
     let mut completed_tx_stream = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -491,7 +494,7 @@ async fn list_all_txs_for_wallet(world: &mut TariWorld, transaction_type: String
     }
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
 
-    let request = GetCompletedTransactionsRequest {};
+    let request = GetCompletedTransactionsRequest { account: String::new() };
     let mut completed_txs = client.get_completed_transactions(request).await.unwrap().into_inner();
 
     while let Some(tx) = completed_txs.next().await {
@@ -538,7 +541,7 @@ async fn wallet_has_at_least_num_txs(world: &mut TariWorld, wallet: String, num_
 
     for _ in 0..num_retries {
         let mut txs = client
-            .get_completed_transactions(grpc::GetCompletedTransactionsRequest {})
+            .get_completed_transactions(grpc::GetCompletedTransactionsRequest { account: String::new() })
             .await
             .unwrap()
             .into_inner();
@@ -619,7 +622,10 @@ async fn wait_for_wallet_to_have_less_than_micro_tari(world: &mut TariWorld, wal
     println!("Waiting for wallet {} to have less than {} uT", wallet, amount);
 
     let num_retries = 100;
-    let request = GetBalanceRequest {};
+    let request = GetBalanceRequest {
+        account: String::new(),
+        ..Default::default()
+    };
 
     for _ in 0..num_retries {
         let balance_res = client.get_balance(request.clone()).await.unwrap().into_inner();
@@ -706,6 +712,11 @@ async fn send_amount_from_source_wallet_to_dest_wallet_without_broadcast(
             dest_wallet.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -766,6 +777,11 @@ async fn send_one_sided_transaction_from_source_wallet_to_dest_wallt(
             dest_wallet.as_str()
         ),
         payment_type: 1, // one sided transaction
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -865,6 +881,11 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
             fee_per_gram
         ),
         payment_type: 0, // mimblewimble transaction
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -942,7 +963,7 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
 async fn wallet_detects_at_least_coinbase_transactions(world: &mut TariWorld, wallet_name: String, coinbases: u64) {
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
     let mut completed_tx_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -998,7 +1019,7 @@ async fn wallet_detects_at_least_coinbase_unconfirmed_transactions(
 ) {
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
     let mut completed_tx_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -1213,7 +1234,7 @@ async fn wallets_should_have_at_least_num_spendable_coinbase_outs(
 
         'inner: for _ in 0..num_retries {
             let mut stream = client
-                .get_completed_transactions(GetCompletedTransactionsRequest {})
+                .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
                 .await
                 .unwrap()
                 .into_inner();
@@ -1294,6 +1315,11 @@ async fn send_num_transactions_to_wallets_at_fee(
                 receiver_wallet.as_str()
             ),
             payment_type: 0, // standard mimblewimble transaction
+            payment_reference: vec![],
+            input_commitments: vec![],
+            coin_selection_strategy: 0,
+            account: String::new(),
+            memo: vec![],
         };
         let transfer_req = TransferRequest {
             recipients: vec![payment_recipient],
@@ -1435,6 +1461,11 @@ async fn transfer_tari_from_wallet_to_receiver(world: &mut TariWorld, amount: u6
             receiver.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -1520,7 +1551,10 @@ async fn wallet_has_tari(world: &mut TariWorld, wallet: String, amount: u64) {
     for _ in 0..num_retries {
         let _result = wallet_client.validate_all_transactions(ValidateRequest {}).await;
         let balance_res = wallet_client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest {
+                account: String::new(),
+                ..Default::default()
+            })
             .await
             .unwrap()
             .into_inner();
@@ -1587,7 +1621,10 @@ async fn wallet_with_tari_connected_to_base_node(
     for _ in 0..num_retries {
         let _result = wallet_client.validate_all_transactions(ValidateRequest {}).await;
         let balance_res = wallet_client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest {
+                account: String::new(),
+                ..Default::default()
+            })
             .await
             .unwrap()
             .into_inner();
@@ -1629,6 +1666,11 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
             receiver1.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
 
     let payment_recipient2 = PaymentRecipient {
@@ -1642,6 +1684,11 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
             receiver2.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient1, payment_recipient2],
@@ -1753,6 +1800,11 @@ async fn transfer_tari_to_self(world: &mut TariWorld, amount: u64, sender: Strin
         fee_per_gram,
         message: format!("transfer amount {} from {} to self", amount, sender.as_str(),),
         payment_type: 0, // normal mimblewimble payment type
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -1838,6 +1890,11 @@ async fn htlc_transaction(world: &mut TariWorld, amount: u64, sender: String, re
             fee_per_gram
         ),
         payment_type: 0, // normal mimblewimble transaction
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
 
     let atomic_swap_request = SendShaAtomicSwapRequest {
@@ -1924,6 +1981,7 @@ async fn claim_htlc_refund_transaction_with_wallet_at_fee(world: &mut TariWorld,
     let claim_htlc_req = ClaimHtlcRefundRequest {
         output_hash,
         fee_per_gram,
+        account: String::new(),
     };
 
     let claim_htlc_refund_res = wallet_client
@@ -1996,6 +2054,7 @@ async fn wallet_claims_htlc_transaction_at_fee(world: &mut TariWorld, wallet: St
         output: output_hash,
         pre_image,
         fee_per_gram,
+        account: String::new(),
     };
 
     let claim_htlc_res = wallet_client
@@ -2068,7 +2127,10 @@ async fn wait_for_wallet_to_have_less_than_amount(world: &mut TariWorld, wallet:
     for _ in 0..=num_retries {
         let _result = client.validate_all_transactions(ValidateRequest {}).await;
         curr_amount = client
-            .get_balance(GetBalanceRequest {})
+            .get_balance(GetBalanceRequest {
+                account: String::new(),
+                ..Default::default()
+            })
             .await
             .unwrap()
             .into_inner()
@@ -2127,6 +2189,11 @@ async fn send_one_sided_stealth_transaction(
             receiver.as_str()
         ),
         payment_type: 2, // one sided stealth transaction
+        payment_reference: vec![],
+        input_commitments: vec![],
+        coin_selection_strategy: 0,
+        account: String::new(),
+        memo: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -2545,7 +2612,7 @@ async fn restart_wallet(world: &mut TariWorld, wallet: String) {
 async fn check_if_wallet_has_num_transactions(world: &mut TariWorld, wallet: String, num_txs: u64) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -2608,6 +2675,11 @@ async fn multi_send_txs_from_wallet(
                 fee_per_gram
             ),
             payment_type: 0, // mimblewimble transaction
+            payment_reference: vec![],
+            input_commitments: vec![],
+            coin_selection_strategy: 0,
+            account: String::new(),
+            memo: vec![],
         };
 
         let transfer_req = TransferRequest {
@@ -2692,7 +2764,7 @@ async fn multi_send_txs_from_wallet(
 async fn check_if_last_imported_txs_are_invalid_in_wallet(world: &mut TariWorld, wallet: String) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();
@@ -2715,7 +2787,7 @@ async fn check_if_last_imported_txs_are_invalid_in_wallet(world: &mut TariWorld,
 async fn check_if_last_imported_txs_are_valid_in_wallet(world: &mut TariWorld, wallet: String) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest { account: String::new() })
         .await
         .unwrap()
         .into_inner();